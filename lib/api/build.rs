@@ -1,6 +1,12 @@
+use std::env;
+use std::path::PathBuf;
+
 fn main() -> std::io::Result<()> {
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
     tonic_build::configure()
         .out_dir("src/grpc/") // saves generated structures at this location
+        // descriptors of the services for the gRPC server reflection
+        .file_descriptor_set_path(out_dir.join("qdrant_descriptor.bin"))
         .compile(
             &["src/grpc/proto/qdrant.proto"], // proto entry point
             &["src/grpc/proto"], // specify the root location to search proto dependencies
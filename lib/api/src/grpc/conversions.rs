@@ -1,15 +1,16 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::time::Instant;
 
 use chrono::{NaiveDateTime, Timelike};
+use segment::data_types::keyword_index::KeywordIndexType;
 use segment::data_types::text_index::TextIndexType;
 use segment::data_types::vectors::VectorElementType;
 use segment::types::{PayloadSelector, WithPayloadInterface};
 use tonic::Status;
-use uuid::Uuid;
 
-use crate::grpc::models::{CollectionsResponse, VersionInfo};
+use crate::grpc::models::{CollectionsAliasesResponse, CollectionsResponse, VersionInfo};
 use crate::grpc::qdrant::condition::ConditionOneOf;
+use crate::grpc::qdrant::facet_value::Variant;
 use crate::grpc::qdrant::payload_index_params::IndexParams;
 use crate::grpc::qdrant::point_id::PointIdOptions;
 use crate::grpc::qdrant::r#match::MatchValue;
@@ -17,13 +18,17 @@ use crate::grpc::qdrant::value::Kind;
 use crate::grpc::qdrant::vectors::VectorsOptions;
 use crate::grpc::qdrant::with_payload_selector::SelectorOptions;
 use crate::grpc::qdrant::{
-    with_vectors_selector, CollectionDescription, CollectionOperationResponse, Condition, Distance,
-    FieldCondition, Filter, GeoBoundingBox, GeoPoint, GeoRadius, HasIdCondition, HealthCheckReply,
-    HnswConfigDiff, IsEmptyCondition, ListCollectionsResponse, ListValue, Match, NamedVectors,
-    PayloadExcludeSelector, PayloadIncludeSelector, PayloadIndexParams, PayloadSchemaInfo,
-    PayloadSchemaType, PointId, Range, ScoredPoint, SearchParams, Struct, TextIndexParams,
-    TokenizerType, Value, ValuesCount, Vector, Vectors, VectorsSelector, WithPayloadSelector,
-    WithVectorsSelector,
+    payload_update, with_vectors_selector, AliasDescription, AppendPayload, CollectionDescription,
+    CollectionOperationResponse, Condition, Datatype, DatetimeRange, Direction, Distance,
+    FacetValue, FacetValueHit, FieldCondition, Filter, FilterCardinality, GeoBoundingBox, GeoPoint,
+    GeoRadius, HasIdCondition, HealthCheckReply, HnswConfigDiff, IncrementPayload,
+    IsEmptyCondition, KeywordIndexParams, ListAliasesResponse, ListCollectionsResponse, ListValue,
+    Match, MinShould, MultiVectorComparator, MultiVectorConfig, NamedVectors, NestedCondition,
+    OrderBy, PayloadExcludeSelector, PayloadFieldTypes, PayloadIncludeSelector, PayloadIndexParams,
+    PayloadSchemaInfo, PayloadSchemaType, PayloadUpdate, PointId, Range, ScoredPoint,
+    SearchExplanation, SearchParams, SetPayloadIfAbsent, ShardSearchExplanation, Struct,
+    TextIndexParams, TokenizerType, Value, ValuesCount, Vector, Vectors, VectorsSelector,
+    WithPayloadSelector, WithVectorsSelector,
 };
 
 pub fn payload_to_proto(payload: segment::types::Payload) -> HashMap<String, Value> {
@@ -107,6 +112,77 @@ fn proto_to_json(proto: Value) -> Result<serde_json::Value, Status> {
     }
 }
 
+impl TryFrom<PayloadUpdate> for segment::data_types::payload_update::PayloadUpdate {
+    type Error = Status;
+
+    fn try_from(value: PayloadUpdate) -> Result<Self, Self::Error> {
+        use segment::data_types::payload_update::PayloadUpdate as SegmentPayloadUpdate;
+
+        let update = match value.update {
+            Some(update) => update,
+            None => return Err(Status::invalid_argument("Malformed payload update")),
+        };
+        Ok(match update {
+            payload_update::Update::Increment(IncrementPayload { key, by }) => {
+                let by = match by.map(proto_to_json).transpose()? {
+                    Some(serde_json::Value::Number(by)) => by,
+                    _ => {
+                        return Err(Status::invalid_argument(format!(
+                            "Increment of payload key `{key}` must be a number"
+                        )))
+                    }
+                };
+                SegmentPayloadUpdate::Increment { key, by }
+            }
+            payload_update::Update::Append(AppendPayload { key, values }) => {
+                SegmentPayloadUpdate::Append {
+                    key,
+                    values: values
+                        .into_iter()
+                        .map(proto_to_json)
+                        .collect::<Result<_, _>>()?,
+                }
+            }
+            payload_update::Update::SetIfAbsent(SetPayloadIfAbsent { key, value }) => {
+                SegmentPayloadUpdate::SetIfAbsent {
+                    key,
+                    value: value.map(proto_to_json).transpose()?.unwrap_or_default(),
+                }
+            }
+        })
+    }
+}
+
+impl From<segment::data_types::payload_update::PayloadUpdate> for PayloadUpdate {
+    fn from(value: segment::data_types::payload_update::PayloadUpdate) -> Self {
+        use segment::data_types::payload_update::PayloadUpdate as SegmentPayloadUpdate;
+
+        let update = match value {
+            SegmentPayloadUpdate::Increment { key, by } => {
+                payload_update::Update::Increment(IncrementPayload {
+                    key,
+                    by: Some(json_to_proto(serde_json::Value::Number(by))),
+                })
+            }
+            SegmentPayloadUpdate::Append { key, values } => {
+                payload_update::Update::Append(AppendPayload {
+                    key,
+                    values: values.into_iter().map(json_to_proto).collect(),
+                })
+            }
+            SegmentPayloadUpdate::SetIfAbsent { key, value } => {
+                payload_update::Update::SetIfAbsent(SetPayloadIfAbsent {
+                    key,
+                    value: Some(json_to_proto(value)),
+                })
+            }
+        };
+        Self {
+            update: Some(update),
+        }
+    }
+}
+
 impl From<VersionInfo> for HealthCheckReply {
     fn from(info: VersionInfo) -> Self {
         HealthCheckReply {
@@ -131,6 +207,24 @@ impl From<(Instant, CollectionsResponse)> for ListCollectionsResponse {
     }
 }
 
+impl From<(Instant, CollectionsAliasesResponse)> for ListAliasesResponse {
+    fn from(value: (Instant, CollectionsAliasesResponse)) -> Self {
+        let (timing, response) = value;
+        let aliases = response
+            .aliases
+            .into_iter()
+            .map(|desc| AliasDescription {
+                alias_name: desc.alias_name,
+                collection_name: desc.collection_name,
+            })
+            .collect::<Vec<_>>();
+        Self {
+            aliases,
+            time: timing.elapsed().as_secs_f64(),
+        }
+    }
+}
+
 impl From<segment::data_types::text_index::TokenizerType> for TokenizerType {
     fn from(tokenizer_type: segment::data_types::text_index::TokenizerType) -> Self {
         match tokenizer_type {
@@ -155,21 +249,89 @@ impl From<segment::data_types::text_index::TextIndexParams> for PayloadIndexPara
     }
 }
 
+impl From<segment::data_types::keyword_index::KeywordIndexParams> for PayloadIndexParams {
+    fn from(params: segment::data_types::keyword_index::KeywordIndexParams) -> Self {
+        PayloadIndexParams {
+            index_params: Some(IndexParams::KeywordIndexParams(KeywordIndexParams {
+                on_disk: params.on_disk,
+            })),
+        }
+    }
+}
+
+impl From<segment::types::PayloadSchemaType> for PayloadSchemaType {
+    fn from(data_type: segment::types::PayloadSchemaType) -> Self {
+        match data_type {
+            segment::types::PayloadSchemaType::Keyword => PayloadSchemaType::Keyword,
+            segment::types::PayloadSchemaType::Integer => PayloadSchemaType::Integer,
+            segment::types::PayloadSchemaType::Float => PayloadSchemaType::Float,
+            segment::types::PayloadSchemaType::Geo => PayloadSchemaType::Geo,
+            segment::types::PayloadSchemaType::Text => PayloadSchemaType::Text,
+            segment::types::PayloadSchemaType::Datetime => PayloadSchemaType::Datetime,
+        }
+    }
+}
+
+impl TryFrom<PayloadSchemaType> for segment::types::PayloadSchemaType {
+    type Error = Status;
+
+    fn try_from(data_type: PayloadSchemaType) -> Result<Self, Self::Error> {
+        match data_type {
+            PayloadSchemaType::Keyword => Ok(segment::types::PayloadSchemaType::Keyword),
+            PayloadSchemaType::Integer => Ok(segment::types::PayloadSchemaType::Integer),
+            PayloadSchemaType::Float => Ok(segment::types::PayloadSchemaType::Float),
+            PayloadSchemaType::Geo => Ok(segment::types::PayloadSchemaType::Geo),
+            PayloadSchemaType::Text => Ok(segment::types::PayloadSchemaType::Text),
+            PayloadSchemaType::Datetime => Ok(segment::types::PayloadSchemaType::Datetime),
+            PayloadSchemaType::UnknownType => Err(Status::invalid_argument(
+                "Malformed payload schema".to_string(),
+            )),
+        }
+    }
+}
+
+fn payload_schema_type_from_proto(
+    data_type: i32,
+) -> Result<segment::types::PayloadSchemaType, Status> {
+    PayloadSchemaType::from_i32(data_type)
+        .ok_or_else(|| Status::invalid_argument("Malformed payload schema".to_string()))?
+        .try_into()
+}
+
+impl From<BTreeMap<String, segment::types::PayloadSchemaType>> for PayloadFieldTypes {
+    fn from(field_types: BTreeMap<String, segment::types::PayloadSchemaType>) -> Self {
+        PayloadFieldTypes {
+            types: field_types
+                .into_iter()
+                .map(|(key, data_type)| (key, PayloadSchemaType::from(data_type) as i32))
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<PayloadFieldTypes> for BTreeMap<String, segment::types::PayloadSchemaType> {
+    type Error = Status;
+
+    fn try_from(field_types: PayloadFieldTypes) -> Result<Self, Self::Error> {
+        field_types
+            .types
+            .into_iter()
+            .map(|(key, data_type)| Ok((key, payload_schema_type_from_proto(data_type)?)))
+            .collect()
+    }
+}
+
 impl From<segment::types::PayloadIndexInfo> for PayloadSchemaInfo {
     fn from(schema: segment::types::PayloadIndexInfo) -> Self {
         PayloadSchemaInfo {
-            data_type: match schema.data_type {
-                segment::types::PayloadSchemaType::Keyword => PayloadSchemaType::Keyword,
-                segment::types::PayloadSchemaType::Integer => PayloadSchemaType::Integer,
-                segment::types::PayloadSchemaType::Float => PayloadSchemaType::Float,
-                segment::types::PayloadSchemaType::Geo => PayloadSchemaType::Geo,
-                segment::types::PayloadSchemaType::Text => PayloadSchemaType::Text,
-            }
-            .into(),
+            data_type: PayloadSchemaType::from(schema.data_type).into(),
             params: schema.params.map(|params| match params {
                 segment::types::PayloadSchemaParams::Text(text_index_params) => {
                     text_index_params.into()
                 }
+                segment::types::PayloadSchemaParams::Keyword(keyword_index_params) => {
+                    keyword_index_params.into()
+                }
             }),
             points: Some(schema.points as u64),
         }
@@ -205,6 +367,15 @@ impl TryFrom<TextIndexParams> for segment::data_types::text_index::TextIndexPara
     }
 }
 
+impl From<KeywordIndexParams> for segment::data_types::keyword_index::KeywordIndexParams {
+    fn from(params: KeywordIndexParams) -> Self {
+        segment::data_types::keyword_index::KeywordIndexParams {
+            r#type: KeywordIndexType::Keyword,
+            on_disk: params.on_disk,
+        }
+    }
+}
+
 impl TryFrom<PayloadIndexParams> for segment::data_types::text_index::TextIndexParams {
     type Error = Status;
     fn try_from(params: PayloadIndexParams) -> Result<Self, Self::Error> {
@@ -213,6 +384,9 @@ impl TryFrom<PayloadIndexParams> for segment::data_types::text_index::TextIndexP
             Some(IndexParams::TextIndexParams(text_index_params)) => {
                 Ok(text_index_params.try_into()?)
             }
+            Some(IndexParams::KeywordIndexParams(_)) => Err(Status::invalid_argument(
+                "expected text index params, got keyword index params",
+            )),
         }
     }
 }
@@ -225,6 +399,9 @@ impl TryFrom<IndexParams> for segment::types::PayloadSchemaParams {
             IndexParams::TextIndexParams(text_index_params) => Ok(
                 segment::types::PayloadSchemaParams::Text(text_index_params.try_into()?),
             ),
+            IndexParams::KeywordIndexParams(keyword_index_params) => Ok(
+                segment::types::PayloadSchemaParams::Keyword(keyword_index_params.into()),
+            ),
         }
     }
 }
@@ -233,25 +410,7 @@ impl TryFrom<PayloadSchemaInfo> for segment::types::PayloadIndexInfo {
     type Error = Status;
 
     fn try_from(schema: PayloadSchemaInfo) -> Result<Self, Self::Error> {
-        let data_type = match PayloadSchemaType::from_i32(schema.data_type) {
-            None => {
-                return Err(Status::invalid_argument(
-                    "Malformed payload schema".to_string(),
-                ))
-            }
-            Some(data_type) => match data_type {
-                PayloadSchemaType::Keyword => segment::types::PayloadSchemaType::Keyword,
-                PayloadSchemaType::Integer => segment::types::PayloadSchemaType::Integer,
-                PayloadSchemaType::Float => segment::types::PayloadSchemaType::Float,
-                PayloadSchemaType::Geo => segment::types::PayloadSchemaType::Geo,
-                PayloadSchemaType::Text => segment::types::PayloadSchemaType::Text,
-                PayloadSchemaType::UnknownType => {
-                    return Err(Status::invalid_argument(
-                        "Malformed payload schema".to_string(),
-                    ))
-                }
-            },
-        };
+        let data_type = payload_schema_type_from_proto(schema.data_type)?;
         let params = match schema.params {
             None => None,
             Some(PayloadIndexParams { index_params: None }) => None,
@@ -274,6 +433,7 @@ impl From<(Instant, bool)> for CollectionOperationResponse {
         CollectionOperationResponse {
             result,
             time: timing.elapsed().as_secs_f64(),
+            shard_placement: vec![],
         }
     }
 }
@@ -333,6 +493,8 @@ impl From<SearchParams> for segment::types::SearchParams {
         Self {
             hnsw_ef: params.hnsw_ef.map(|x| x as usize),
             exact: params.exact.unwrap_or(false),
+            max_time_ms: params.max_time_ms,
+            explain: params.explain.unwrap_or(false),
         }
     }
 }
@@ -342,6 +504,70 @@ impl From<segment::types::SearchParams> for SearchParams {
         Self {
             hnsw_ef: params.hnsw_ef.map(|x| x as u64),
             exact: Some(params.exact),
+            max_time_ms: params.max_time_ms,
+            explain: Some(params.explain),
+        }
+    }
+}
+
+impl From<SearchExplanation> for segment::types::SearchExplanation {
+    fn from(explanation: SearchExplanation) -> Self {
+        Self {
+            exact: explanation.exact as usize,
+            hnsw: explanation.hnsw as usize,
+            filtered_hnsw: explanation.filtered_hnsw as usize,
+            hnsw_post_filter: explanation.hnsw_post_filter as usize,
+            segments: explanation.segments as usize,
+            scored_vectors: explanation.scored_vectors as usize,
+            hnsw_ef: explanation.hnsw_ef.map(|ef| ef as usize),
+            filter_cardinality: explanation.filter_cardinality.map(|cardinality| {
+                segment::types::FilterCardinality {
+                    min: cardinality.min as usize,
+                    exp: cardinality.exp as usize,
+                    max: cardinality.max as usize,
+                }
+            }),
+            shards: explanation
+                .shards
+                .into_iter()
+                .map(|shard| segment::types::ShardSearchExplanation {
+                    shard_id: shard.shard_id,
+                    time: shard.time,
+                    segments: shard.segments as usize,
+                    scored_vectors: shard.scored_vectors as usize,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<segment::types::SearchExplanation> for SearchExplanation {
+    fn from(explanation: segment::types::SearchExplanation) -> Self {
+        Self {
+            exact: explanation.exact as u64,
+            hnsw: explanation.hnsw as u64,
+            filtered_hnsw: explanation.filtered_hnsw as u64,
+            hnsw_post_filter: explanation.hnsw_post_filter as u64,
+            segments: explanation.segments as u64,
+            scored_vectors: explanation.scored_vectors as u64,
+            hnsw_ef: explanation.hnsw_ef.map(|ef| ef as u64),
+            filter_cardinality: explanation.filter_cardinality.map(|cardinality| {
+                FilterCardinality {
+                    min: cardinality.min as u64,
+                    exp: cardinality.exp as u64,
+                    max: cardinality.max as u64,
+                }
+            }),
+            shards: explanation
+                .shards
+                .into_iter()
+                .map(|shard| ShardSearchExplanation {
+                    shard_id: shard.shard_id,
+                    time: shard.time,
+                    segments: shard.segments as u64,
+                    scored_vectors: shard.scored_vectors as u64,
+                })
+                .collect(),
         }
     }
 }
@@ -483,11 +709,11 @@ impl TryFrom<PointId> for segment::types::PointIdType {
     fn try_from(value: PointId) -> Result<Self, Self::Error> {
         match value.point_id_options {
             Some(PointIdOptions::Num(num_id)) => Ok(segment::types::PointIdType::NumId(num_id)),
-            Some(PointIdOptions::Uuid(uui_str)) => Uuid::parse_str(&uui_str)
-                .map(segment::types::PointIdType::Uuid)
-                .map_err(|_err| {
-                    Status::invalid_argument(format!("Unable to parse UUID: {}", uui_str))
-                }),
+            Some(PointIdOptions::Uuid(uuid_str)) => {
+                segment::types::PointIdType::parse_str(&uuid_str).ok_or_else(|| {
+                    Status::invalid_argument("String point ID can not be empty".to_string())
+                })
+            }
             _ => Err(Status::invalid_argument(
                 "No ID options provided".to_string(),
             )),
@@ -528,6 +754,19 @@ impl TryFrom<Filter> for segment::types::Filter {
     fn try_from(value: Filter) -> Result<Self, Self::Error> {
         Ok(Self {
             should: conditions_helper_from_grpc(value.should)?,
+            min_should: value
+                .min_should
+                .map(|min_should| -> Result<_, Status> {
+                    Ok(segment::types::MinShould {
+                        conditions: min_should
+                            .conditions
+                            .into_iter()
+                            .map(|c| c.try_into())
+                            .collect::<Result<_, _>>()?,
+                        min_count: min_should.min_count as usize,
+                    })
+                })
+                .transpose()?,
             must: conditions_helper_from_grpc(value.must)?,
             must_not: conditions_helper_from_grpc(value.must_not)?,
         })
@@ -540,6 +779,14 @@ impl From<segment::types::Filter> for Filter {
             should: conditions_helper_to_grpc(value.should),
             must: conditions_helper_to_grpc(value.must),
             must_not: conditions_helper_to_grpc(value.must_not),
+            min_should: value.min_should.map(|min_should| MinShould {
+                conditions: min_should
+                    .conditions
+                    .into_iter()
+                    .map(|c| c.into())
+                    .collect(),
+                min_count: min_should.min_count as u64,
+            }),
         }
     }
 }
@@ -562,6 +809,9 @@ impl TryFrom<Condition> for segment::types::Condition {
                 ConditionOneOf::IsEmpty(is_empty) => {
                     Ok(segment::types::Condition::IsEmpty(is_empty.into()))
                 }
+                ConditionOneOf::Nested(nested) => {
+                    Ok(segment::types::Condition::Nested(nested.try_into()?))
+                }
             };
         }
         Err(Status::invalid_argument("Malformed Condition type"))
@@ -577,6 +827,7 @@ impl From<segment::types::Condition> for Condition {
             }
             segment::types::Condition::HasId(has_id) => ConditionOneOf::HasId(has_id.into()),
             segment::types::Condition::Filter(filter) => ConditionOneOf::Filter(filter.into()),
+            segment::types::Condition::Nested(nested) => ConditionOneOf::Nested(nested.into()),
         };
 
         Self {
@@ -585,6 +836,29 @@ impl From<segment::types::Condition> for Condition {
     }
 }
 
+impl TryFrom<NestedCondition> for segment::types::NestedCondition {
+    type Error = Status;
+
+    fn try_from(value: NestedCondition) -> Result<Self, Self::Error> {
+        let filter = value
+            .filter
+            .ok_or_else(|| Status::invalid_argument("Nested condition requires filter"))?;
+        Ok(segment::types::NestedCondition {
+            nested: segment::types::Nested::new(value.key, filter.try_into()?)
+                .map_err(Status::invalid_argument)?,
+        })
+    }
+}
+
+impl From<segment::types::NestedCondition> for NestedCondition {
+    fn from(value: segment::types::NestedCondition) -> Self {
+        Self {
+            key: value.nested.key,
+            filter: Some(value.nested.filter.into()),
+        }
+    }
+}
+
 impl From<IsEmptyCondition> for segment::types::IsEmptyCondition {
     fn from(value: IsEmptyCondition) -> Self {
         segment::types::IsEmptyCondition {
@@ -632,6 +906,7 @@ impl TryFrom<FieldCondition> for segment::types::FieldCondition {
             geo_bounding_box,
             geo_radius,
             values_count,
+            datetime_range,
         } = value;
 
         let geo_bounding_box =
@@ -644,6 +919,7 @@ impl TryFrom<FieldCondition> for segment::types::FieldCondition {
             geo_bounding_box,
             geo_radius,
             values_count: values_count.map(|r| r.into()),
+            datetime_range: datetime_range.map(|r| r.into()),
         })
     }
 }
@@ -657,6 +933,7 @@ impl From<segment::types::FieldCondition> for FieldCondition {
             geo_bounding_box,
             geo_radius,
             values_count,
+            datetime_range,
         } = value;
 
         let geo_bounding_box = geo_bounding_box.map(|g| g.into());
@@ -668,6 +945,7 @@ impl From<segment::types::FieldCondition> for FieldCondition {
             geo_bounding_box,
             geo_radius,
             values_count: values_count.map(|r| r.into()),
+            datetime_range: datetime_range.map(|r| r.into()),
         }
     }
 }
@@ -755,6 +1033,75 @@ impl From<segment::types::Range> for Range {
     }
 }
 
+fn datetime_from_proto(timestamp: prost_types::Timestamp) -> segment::types::DateTimePayloadType {
+    segment::types::DateTimePayloadType(
+        timestamp.seconds * 1_000_000 + i64::from(timestamp.nanos) / 1_000,
+    )
+}
+
+fn datetime_to_proto(datetime: segment::types::DateTimePayloadType) -> prost_types::Timestamp {
+    let micros = datetime.timestamp_micros();
+    prost_types::Timestamp {
+        seconds: micros.div_euclid(1_000_000),
+        nanos: (micros.rem_euclid(1_000_000) * 1_000) as i32,
+    }
+}
+
+impl From<DatetimeRange> for segment::types::DatetimeRange {
+    fn from(value: DatetimeRange) -> Self {
+        Self {
+            lt: value.lt.map(datetime_from_proto),
+            gt: value.gt.map(datetime_from_proto),
+            gte: value.gte.map(datetime_from_proto),
+            lte: value.lte.map(datetime_from_proto),
+        }
+    }
+}
+
+impl From<segment::types::DatetimeRange> for DatetimeRange {
+    fn from(value: segment::types::DatetimeRange) -> Self {
+        Self {
+            lt: value.lt.map(datetime_to_proto),
+            gt: value.gt.map(datetime_to_proto),
+            gte: value.gte.map(datetime_to_proto),
+            lte: value.lte.map(datetime_to_proto),
+        }
+    }
+}
+
+impl TryFrom<OrderBy> for segment::types::OrderBy {
+    type Error = Status;
+
+    fn try_from(value: OrderBy) -> Result<Self, Self::Error> {
+        let direction = value
+            .direction
+            .map(|direction| match Direction::from_i32(direction) {
+                Some(Direction::Asc) => Ok(segment::types::Direction::Asc),
+                Some(Direction::Desc) => Ok(segment::types::Direction::Desc),
+                None => Err(Status::invalid_argument("unknown ordering direction")),
+            })
+            .transpose()?;
+        Ok(Self {
+            key: value.key,
+            direction,
+            start_from: value.start_from,
+        })
+    }
+}
+
+impl From<segment::types::OrderBy> for OrderBy {
+    fn from(value: segment::types::OrderBy) -> Self {
+        Self {
+            key: value.key,
+            direction: value.direction.map(|direction| match direction {
+                segment::types::Direction::Asc => Direction::Asc as i32,
+                segment::types::Direction::Desc => Direction::Desc as i32,
+            }),
+            start_from: value.start_from,
+        }
+    }
+}
+
 impl From<ValuesCount> for segment::types::ValuesCount {
     fn from(value: ValuesCount) -> Self {
         Self {
@@ -811,6 +1158,57 @@ impl From<segment::types::Match> for Match {
     }
 }
 
+impl TryFrom<FacetValue> for segment::data_types::facets::FacetValue {
+    type Error = Status;
+
+    fn try_from(value: FacetValue) -> Result<Self, Self::Error> {
+        match value.variant {
+            Some(Variant::KeywordValue(keyword)) => Ok(Self::Keyword(keyword)),
+            Some(Variant::IntegerValue(integer)) => Ok(Self::Integer(integer)),
+            None => Err(Status::invalid_argument("Malformed FacetValue")),
+        }
+    }
+}
+
+impl From<segment::data_types::facets::FacetValue> for FacetValue {
+    fn from(value: segment::data_types::facets::FacetValue) -> Self {
+        let variant = match value {
+            segment::data_types::facets::FacetValue::Keyword(keyword) => {
+                Variant::KeywordValue(keyword)
+            }
+            segment::data_types::facets::FacetValue::Integer(integer) => {
+                Variant::IntegerValue(integer)
+            }
+        };
+        Self {
+            variant: Some(variant),
+        }
+    }
+}
+
+impl TryFrom<FacetValueHit> for segment::data_types::facets::FacetValueHit {
+    type Error = Status;
+
+    fn try_from(value: FacetValueHit) -> Result<Self, Self::Error> {
+        let facet_value = value
+            .value
+            .ok_or_else(|| Status::invalid_argument("FacetValueHit value is missing"))?;
+        Ok(Self {
+            value: facet_value.try_into()?,
+            count: value.count as usize,
+        })
+    }
+}
+
+impl From<segment::data_types::facets::FacetValueHit> for FacetValueHit {
+    fn from(value: segment::data_types::facets::FacetValueHit) -> Self {
+        Self {
+            value: Some(value.value.into()),
+            count: value.count as u64,
+        }
+    }
+}
+
 impl From<HnswConfigDiff> for segment::types::HnswConfig {
     fn from(hnsw_config: HnswConfigDiff) -> Self {
         Self {
@@ -854,3 +1252,54 @@ pub fn from_grpc_dist(dist: i32) -> Result<segment::types::Distance, Status> {
         Some(grpc_distance) => Ok(grpc_distance.try_into()?),
     }
 }
+
+/// Type of the stored vector elements, `None` if the default one is requested
+pub fn from_grpc_datatype(
+    datatype: i32,
+) -> Result<Option<segment::types::VectorStorageDatatype>, Status> {
+    match Datatype::from_i32(datatype) {
+        None => Err(Status::invalid_argument(format!(
+            "Malformed datatype parameter, unexpected value: {datatype}"
+        ))),
+        Some(Datatype::Default) => Ok(None),
+        Some(Datatype::Float32) => Ok(Some(segment::types::VectorStorageDatatype::Float32)),
+        Some(Datatype::Uint8) => Ok(Some(segment::types::VectorStorageDatatype::Uint8)),
+    }
+}
+
+impl From<segment::types::VectorStorageDatatype> for Datatype {
+    fn from(value: segment::types::VectorStorageDatatype) -> Self {
+        match value {
+            segment::types::VectorStorageDatatype::Float32 => Datatype::Float32,
+            segment::types::VectorStorageDatatype::Uint8 => Datatype::Uint8,
+        }
+    }
+}
+
+impl TryFrom<MultiVectorConfig> for segment::types::MultiVectorConfig {
+    type Error = Status;
+
+    fn try_from(config: MultiVectorConfig) -> Result<Self, Self::Error> {
+        let comparator = match MultiVectorComparator::from_i32(config.comparator) {
+            None => {
+                return Err(Status::invalid_argument(format!(
+                    "Malformed multivector comparator, unexpected value: {}",
+                    config.comparator
+                )))
+            }
+            Some(MultiVectorComparator::MaxSim) => segment::types::MultiVectorComparator::MaxSim,
+        };
+        Ok(Self { comparator })
+    }
+}
+
+impl From<segment::types::MultiVectorConfig> for MultiVectorConfig {
+    fn from(config: segment::types::MultiVectorConfig) -> Self {
+        let comparator = match config.comparator {
+            segment::types::MultiVectorComparator::MaxSim => MultiVectorComparator::MaxSim,
+        };
+        Self {
+            comparator: comparator.into(),
+        }
+    }
+}
@@ -2,11 +2,15 @@
 
 pub mod conversions;
 pub mod models;
+pub mod peer_channel;
 #[allow(clippy::all)]
 #[rustfmt::skip] // tonic uses `prettyplease` to format its output
 pub mod qdrant;
 pub mod transport_channel_pool;
 
+/// Encoded descriptors of the gRPC API, served by the reflection service
+pub const QDRANT_DESCRIPTOR_SET: &[u8] = tonic::include_file_descriptor_set!("qdrant_descriptor");
+
 pub const fn api_crate_version() -> &'static str {
     env!("CARGO_PKG_VERSION")
 }
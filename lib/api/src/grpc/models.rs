@@ -32,6 +32,26 @@ pub struct ApiResponse<D: Serialize + Debug> {
     pub result: Option<D>,
     pub status: ApiStatus,
     pub time: f64,
+    /// Only present for searches with time budget.
+    /// If true - the budget was exhausted and result might be incomplete.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub truncated: Option<bool>,
+    /// Only present for searches with `explain` param.
+    /// Number of segments, searched with each strategy.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<segment::types::SearchExplanation>,
+    /// Only present for collection creation in distributed mode.
+    /// Peers, the shards of the new collection were placed on.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shard_placement: Option<Vec<ShardPlacement>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ShardPlacement {
+    pub shard_id: u32,
+    /// Peers, which hold replicas of the shard
+    pub peers: Vec<u64>,
 }
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema)]
@@ -45,3 +65,17 @@ pub struct CollectionDescription {
 pub struct CollectionsResponse {
     pub collections: Vec<CollectionDescription>,
 }
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct AliasDescription {
+    pub alias_name: String,
+    /// Name of the collection, the alias points to
+    pub collection_name: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct CollectionsAliasesResponse {
+    pub aliases: Vec<AliasDescription>,
+}
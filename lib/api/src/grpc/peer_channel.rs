@@ -0,0 +1,654 @@
+//! Transport features of the internal gRPC traffic between peers.
+//!
+//! * Compression - peers advertise support of `gzip` compressed requests in every response of
+//!   the internal server. Requests to a peer are only compressed after it has advertised support,
+//!   so peers running an older version keep working during a rolling upgrade.
+//! * Encryption - for clusters without TLS between peers, bodies of internal requests and
+//!   responses are encrypted with AES-256-GCM using a key, shared by all peers. gRPC framing,
+//!   compression and statuses are kept as is, only the bytes of the body are replaced.
+//! * Authentication - requests carry the id of the sending peer and, if the secret shared by all
+//!   peers is configured, a signature of the id. The secret itself is never sent. The signature
+//!   covers the time of the request and a random nonce, so the internal server rejects outdated
+//!   and replayed requests, as well as the method and the digest of the body, so a signed request
+//!   can't be altered or redirected to another method.
+
+use std::collections::HashSet;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, RwLock};
+use std::task::{Context, Poll};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use bytes::Bytes;
+use hmac::{Hmac, Mac};
+use http_body::Body as HttpBody;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use tonic::body::BoxBody;
+use tonic::codegen::http::{HeaderMap, HeaderValue, Request, Response};
+use tonic::codegen::StdError;
+use tonic::transport::{Body, Channel, Uri};
+use tonic::Status;
+use tower::{Layer, Service};
+
+pub const P2P_KEY_SIZE: usize = 32;
+const NONCE_SIZE: usize = 12;
+
+/// Marks requests and responses with encrypted body
+const ENCRYPTION_HEADER: &str = "x-qdrant-p2p-encryption";
+const ENCRYPTION_ALGORITHM: &str = "aes-256-gcm";
+/// Added by the internal server to every response, lists accepted compression encodings
+const ACCEPT_ENCODING_HEADER: &str = "x-qdrant-p2p-accept-encoding";
+const GZIP_ENCODING: &str = "gzip";
+/// Id of the peer, which sent the request
+pub const PEER_ID_HEADER: &str = "x-qdrant-peer-id";
+/// Unix time in seconds, when the request was signed
+pub const PEER_TIMESTAMP_HEADER: &str = "x-qdrant-peer-timestamp";
+/// Random value, unique for every request
+pub const PEER_NONCE_HEADER: &str = "x-qdrant-peer-nonce";
+/// Hex-encoded HMAC-SHA256 of the peer id, timestamp, nonce, method path and body digest,
+/// keyed by the shared secret
+pub const PEER_SIGNATURE_HEADER: &str = "x-qdrant-peer-signature";
+/// Signatures, which differ from the current time by more than this, are rejected.
+/// Clocks of the peers should be synchronized within this interval.
+pub const PEER_SIGNATURE_TTL_SEC: u64 = 60;
+const PEER_NONCE_SIZE: usize = 16;
+/// Service, which is available without encryption, e.g. for health checks
+const PUBLIC_SERVICE_PREFIX: &str = "/qdrant.Qdrant/";
+
+/// Symmetric cipher for messages between peers
+#[derive(Clone)]
+pub struct MessageCipher {
+    cipher: Arc<Aes256Gcm>,
+}
+
+impl MessageCipher {
+    pub fn new(key: &[u8]) -> Result<Self, String> {
+        let cipher = Aes256Gcm::new_from_slice(key)
+            .map_err(|_| format!("key must be {P2P_KEY_SIZE} bytes long"))?;
+        Ok(Self {
+            cipher: Arc::new(cipher),
+        })
+    }
+
+    /// Encrypted message is prefixed with a random nonce
+    fn encrypt(&self, data: &[u8]) -> Result<Bytes, Status> {
+        let mut nonce = [0u8; NONCE_SIZE];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let encrypted = self
+            .cipher
+            .encrypt(Nonce::from_slice(&nonce), data)
+            .map_err(|_| Status::internal("Failed to encrypt peer-to-peer message"))?;
+        let mut message = Vec::with_capacity(NONCE_SIZE + encrypted.len());
+        message.extend_from_slice(&nonce);
+        message.extend_from_slice(&encrypted);
+        Ok(message.into())
+    }
+
+    fn decrypt(&self, message: &[u8]) -> Result<Bytes, Status> {
+        if message.len() < NONCE_SIZE {
+            return Err(Status::unauthenticated(
+                "Malformed encrypted peer-to-peer message",
+            ));
+        }
+        let (nonce, encrypted) = message.split_at(NONCE_SIZE);
+        self.cipher
+            .decrypt(Nonce::from_slice(nonce), encrypted)
+            .map(Bytes::from)
+            .map_err(|_| {
+                Status::unauthenticated(
+                    "Can't decrypt peer-to-peer message, all peers must use the same encryption key",
+                )
+            })
+    }
+}
+
+/// Secret, shared by the peers of the cluster, used to sign the identity of the peer in requests
+#[derive(Clone)]
+pub struct PeerSecret {
+    key: Arc<[u8]>,
+}
+
+impl PeerSecret {
+    pub fn new(secret: &str) -> Self {
+        Self {
+            key: Arc::from(secret.as_bytes()),
+        }
+    }
+
+    fn mac(
+        &self,
+        peer_id: &str,
+        timestamp: &str,
+        nonce: &str,
+        request: &PeerRequestDigest,
+    ) -> Hmac<Sha256> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.key).expect("HMAC accepts keys of any size");
+        for part in [
+            peer_id,
+            timestamp,
+            nonce,
+            &request.path,
+            &request.body_digest,
+        ] {
+            mac.update(part.as_bytes());
+            mac.update(b"\n");
+        }
+        mac
+    }
+
+    pub fn sign(
+        &self,
+        peer_id: &str,
+        timestamp: &str,
+        nonce: &str,
+        request: &PeerRequestDigest,
+    ) -> String {
+        hex::encode(
+            self.mac(peer_id, timestamp, nonce, request)
+                .finalize()
+                .into_bytes(),
+        )
+    }
+
+    /// Check the signature in constant time
+    pub fn verify(
+        &self,
+        peer_id: &str,
+        timestamp: &str,
+        nonce: &str,
+        request: &PeerRequestDigest,
+        signature: &str,
+    ) -> bool {
+        hex::decode(signature).map_or(false, |signature| {
+            self.mac(peer_id, timestamp, nonce, request)
+                .verify_slice(&signature)
+                .is_ok()
+        })
+    }
+}
+
+/// Method path and digest of the body of an internal request, covered by the signature.
+/// The internal server adds it to the extensions of the request, if requests are signed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerRequestDigest {
+    pub path: String,
+    /// Hex-encoded SHA256 of the body, as it is sent before the encryption
+    pub body_digest: String,
+}
+
+impl PeerRequestDigest {
+    pub fn new(path: &str, body: &[u8]) -> Self {
+        Self {
+            path: path.to_string(),
+            body_digest: hex::encode(Sha256::digest(body)),
+        }
+    }
+}
+
+/// Current Unix time in seconds, used in signatures of the requests
+pub fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+/// Identity of this peer, attached to the requests to other peers
+#[derive(Clone)]
+pub struct PeerCredentials {
+    peer_id: String,
+    secret: Option<PeerSecret>,
+}
+
+impl PeerCredentials {
+    pub fn new(peer_id: u64, secret: Option<&str>) -> Self {
+        Self {
+            peer_id: peer_id.to_string(),
+            secret: secret.map(PeerSecret::new),
+        }
+    }
+
+    pub fn secret(&self) -> Option<&PeerSecret> {
+        self.secret.as_ref()
+    }
+
+    /// Request is only signed if the secret is configured and the `request` digest is given
+    fn apply(&self, headers: &mut HeaderMap, request: Option<&PeerRequestDigest>) {
+        headers.insert(PEER_ID_HEADER, header_value(&self.peer_id));
+        if let (Some(secret), Some(request)) = (&self.secret, request) {
+            let timestamp = unix_timestamp().to_string();
+            let mut nonce = [0u8; PEER_NONCE_SIZE];
+            rand::thread_rng().fill_bytes(&mut nonce);
+            let nonce = hex::encode(nonce);
+            let signature = secret.sign(&self.peer_id, &timestamp, &nonce, request);
+            headers.insert(PEER_TIMESTAMP_HEADER, header_value(&timestamp));
+            headers.insert(PEER_NONCE_HEADER, header_value(&nonce));
+            headers.insert(PEER_SIGNATURE_HEADER, header_value(&signature));
+        }
+    }
+}
+
+/// Numbers and hex strings are always valid header values
+fn header_value(value: &str) -> HeaderValue {
+    HeaderValue::from_str(value).expect("valid header value")
+}
+
+/// Set of peers, which advertised support of compressed requests
+pub type GzipPeers = Arc<RwLock<HashSet<Uri>>>;
+
+/// Channel to a remote peer, which encrypts messages if the cipher is configured
+/// and keeps track of the compression support of the peer.
+#[derive(Clone)]
+pub struct PeerChannel {
+    channel: Channel,
+    uri: Uri,
+    cipher: Option<MessageCipher>,
+    credentials: Option<PeerCredentials>,
+    compression: bool,
+    gzip_peers: GzipPeers,
+}
+
+impl PeerChannel {
+    pub fn new(
+        channel: Channel,
+        uri: Uri,
+        cipher: Option<MessageCipher>,
+        credentials: Option<PeerCredentials>,
+        compression: bool,
+        gzip_peers: GzipPeers,
+    ) -> Self {
+        Self {
+            channel,
+            uri,
+            cipher,
+            credentials,
+            compression,
+            gzip_peers,
+        }
+    }
+
+    /// Whether requests to the peer should be compressed.
+    /// True only if compression is enabled and the peer has advertised support of it.
+    pub fn send_gzip(&self) -> bool {
+        self.compression
+            && self
+                .gzip_peers
+                .read()
+                .map_or(false, |peers| peers.contains(&self.uri))
+    }
+
+    fn observe_response_headers(&self, headers: &HeaderMap) {
+        let accepts_gzip = headers
+            .get(ACCEPT_ENCODING_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map_or(false, |value| {
+                value
+                    .split(',')
+                    .any(|encoding| encoding.trim() == GZIP_ENCODING)
+            });
+        if let Ok(mut peers) = self.gzip_peers.write() {
+            if accepts_gzip {
+                peers.insert(self.uri.clone());
+            } else {
+                peers.remove(&self.uri);
+            }
+        }
+    }
+}
+
+impl Service<Request<BoxBody>> for PeerChannel {
+    type Response = Response<BoxBody>;
+    type Error = StdError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.channel.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, request: Request<BoxBody>) -> Self::Future {
+        // Only the channel, which was polled, is guaranteed to be ready
+        let ready_channel = self.channel.clone();
+        let mut channel = std::mem::replace(&mut self.channel, ready_channel);
+        let this = self.clone();
+        Box::pin(async move {
+            let (mut parts, body) = request.into_parts();
+            let signed = this
+                .credentials
+                .as_ref()
+                .map_or(false, |credentials| credentials.secret.is_some());
+            if this.cipher.is_none() && !signed {
+                if let Some(credentials) = &this.credentials {
+                    credentials.apply(&mut parts.headers, None);
+                }
+                let response = channel.call(Request::from_parts(parts, body)).await?;
+                this.observe_response_headers(response.headers());
+                return Ok(response.map(box_body));
+            }
+
+            // Signature covers the body, so the whole body is read before sending
+            let (data, _) = read_body(body).await?;
+            if let Some(credentials) = &this.credentials {
+                let digest = PeerRequestDigest::new(parts.uri.path(), &data);
+                credentials.apply(&mut parts.headers, Some(&digest));
+            }
+            let cipher = match &this.cipher {
+                None => {
+                    let body = BufferedBody::new(data, None);
+                    let response = channel
+                        .call(Request::from_parts(parts, body.boxed_unsync()))
+                        .await?;
+                    this.observe_response_headers(response.headers());
+                    return Ok(response.map(box_body));
+                }
+                Some(cipher) => cipher,
+            };
+
+            parts.headers.insert(
+                ENCRYPTION_HEADER,
+                HeaderValue::from_static(ENCRYPTION_ALGORITHM),
+            );
+            let body = BufferedBody::new(cipher.encrypt(&data)?, None);
+            let response = channel
+                .call(Request::from_parts(parts, body.boxed_unsync()))
+                .await?;
+            this.observe_response_headers(response.headers());
+
+            let (parts, body) = response.into_parts();
+            if !parts.headers.contains_key(ENCRYPTION_HEADER) {
+                // Error, returned before the message was decrypted
+                return Ok(Response::from_parts(parts, box_body(body)));
+            }
+            let (data, trailers) = read_body(body).await?;
+            let data = if data.is_empty() {
+                data
+            } else {
+                cipher.decrypt(&data)?
+            };
+            Ok(Response::from_parts(
+                parts,
+                BufferedBody::new(data, trailers).boxed_unsync(),
+            ))
+        })
+    }
+}
+
+/// Server side of the peer-to-peer transport features.
+/// Should only be applied to the internal gRPC server.
+#[derive(Clone)]
+pub struct PeerTransportLayer {
+    cipher: Option<MessageCipher>,
+    /// Attach [`PeerRequestDigest`] to requests, so their signatures could be verified
+    digest_requests: bool,
+}
+
+impl PeerTransportLayer {
+    pub fn new(cipher: Option<MessageCipher>, digest_requests: bool) -> Self {
+        Self {
+            cipher,
+            digest_requests,
+        }
+    }
+}
+
+impl<S> Layer<S> for PeerTransportLayer {
+    type Service = PeerTransportService<S>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        PeerTransportService {
+            service,
+            cipher: self.cipher.clone(),
+            digest_requests: self.digest_requests,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct PeerTransportService<S> {
+    service: S,
+    cipher: Option<MessageCipher>,
+    digest_requests: bool,
+}
+
+impl<S, ResBody> Service<Request<Body>> for PeerTransportService<S>
+where
+    S: Service<Request<Body>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    ResBody: HttpBody<Data = Bytes> + Send + Unpin + 'static,
+    ResBody::Error: Into<StdError>,
+{
+    type Response = Response<BoxBody>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        let ready_service = self.service.clone();
+        let mut service = std::mem::replace(&mut self.service, ready_service);
+        let cipher = self.cipher.clone();
+        let digest_requests = self.digest_requests;
+        Box::pin(async move {
+            let encrypted = request.headers().contains_key(ENCRYPTION_HEADER);
+            let is_public = request.uri().path().starts_with(PUBLIC_SERVICE_PREFIX);
+            let mut response = match (cipher, encrypted) {
+                (Some(cipher), true) => {
+                    match decrypt_request(&cipher, request, digest_requests).await {
+                        Ok(request) => {
+                            let response = service.call(request).await?;
+                            encrypt_response(&cipher, response)
+                                .await
+                                .unwrap_or_else(|status| status.to_http())
+                        }
+                        Err(status) => status.to_http(),
+                    }
+                }
+                (Some(_), false) if !is_public => {
+                    Status::unauthenticated("Peer-to-peer messages must be encrypted").to_http()
+                }
+                (None, true) => {
+                    Status::unimplemented("Peer-to-peer encryption is not configured").to_http()
+                }
+                _ if digest_requests => match digest_request(request).await {
+                    Ok(request) => service.call(request).await?.map(box_body),
+                    Err(status) => status.to_http(),
+                },
+                _ => service.call(request).await?.map(box_body),
+            };
+            response.headers_mut().insert(
+                ACCEPT_ENCODING_HEADER,
+                HeaderValue::from_static(GZIP_ENCODING),
+            );
+            Ok(response)
+        })
+    }
+}
+
+async fn decrypt_request(
+    cipher: &MessageCipher,
+    request: Request<Body>,
+    digest: bool,
+) -> Result<Request<Body>, Status> {
+    let (mut parts, body) = request.into_parts();
+    let (data, _) = read_body(body).await?;
+    let data = cipher.decrypt(&data)?;
+    if digest {
+        let digest = PeerRequestDigest::new(parts.uri.path(), &data);
+        parts.extensions.insert(digest);
+    }
+    Ok(Request::from_parts(parts, Body::from(data)))
+}
+
+/// Read the body of the plain request to attach its digest
+async fn digest_request(request: Request<Body>) -> Result<Request<Body>, Status> {
+    let (mut parts, body) = request.into_parts();
+    let (data, _) = read_body(body).await?;
+    let digest = PeerRequestDigest::new(parts.uri.path(), &data);
+    parts.extensions.insert(digest);
+    Ok(Request::from_parts(parts, Body::from(data)))
+}
+
+async fn encrypt_response<B>(
+    cipher: &MessageCipher,
+    response: Response<B>,
+) -> Result<Response<BoxBody>, Status>
+where
+    B: HttpBody<Data = Bytes> + Unpin,
+    B::Error: Into<StdError>,
+{
+    let (mut parts, body) = response.into_parts();
+    let (data, trailers) = read_body(body).await?;
+    let data = if data.is_empty() {
+        data
+    } else {
+        cipher.encrypt(&data)?
+    };
+    parts.headers.insert(
+        ENCRYPTION_HEADER,
+        HeaderValue::from_static(ENCRYPTION_ALGORITHM),
+    );
+    Ok(Response::from_parts(
+        parts,
+        BufferedBody::new(data, trailers).boxed_unsync(),
+    ))
+}
+
+/// Read the whole body, internal messages are unary and fit into memory anyway
+async fn read_body<B>(mut body: B) -> Result<(Bytes, Option<HeaderMap>), Status>
+where
+    B: HttpBody<Data = Bytes> + Unpin,
+    B::Error: Into<StdError>,
+{
+    let mut data = Vec::new();
+    while let Some(chunk) = body.data().await {
+        data.extend_from_slice(&chunk.map_err(body_error)?);
+    }
+    let trailers = body.trailers().await.map_err(body_error)?;
+    Ok((data.into(), trailers))
+}
+
+fn body_error(err: impl Into<StdError>) -> Status {
+    Status::internal(format!(
+        "Failed to read peer-to-peer message: {}",
+        err.into()
+    ))
+}
+
+fn box_body<B>(body: B) -> BoxBody
+where
+    B: HttpBody<Data = Bytes> + Send + 'static,
+    B::Error: Into<StdError>,
+{
+    body.map_err(|err| Status::internal(err.into().to_string()))
+        .boxed_unsync()
+}
+
+/// Body, which is fully loaded into memory
+struct BufferedBody {
+    data: Option<Bytes>,
+    trailers: Option<HeaderMap>,
+}
+
+impl BufferedBody {
+    fn new(data: Bytes, trailers: Option<HeaderMap>) -> Self {
+        Self {
+            data: Some(data).filter(|data| !data.is_empty()),
+            trailers,
+        }
+    }
+}
+
+impl HttpBody for BufferedBody {
+    type Data = Bytes;
+    type Error = Status;
+
+    fn poll_data(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        Poll::Ready(self.data.take().map(Ok))
+    }
+
+    fn poll_trailers(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        Poll::Ready(Ok(self.trailers.take()))
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.data.is_none() && self.trailers.is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_cipher() {
+        let cipher = MessageCipher::new(&[7u8; P2P_KEY_SIZE]).unwrap();
+        let other_cipher = MessageCipher::new(&[8u8; P2P_KEY_SIZE]).unwrap();
+        assert!(MessageCipher::new(&[7u8; 16]).is_err());
+
+        let message = b"raft message";
+        let encrypted = cipher.encrypt(message).unwrap();
+        assert_ne!(&encrypted[NONCE_SIZE..], &message[..]);
+        assert_eq!(&cipher.decrypt(&encrypted).unwrap()[..], &message[..]);
+
+        // Same message is encrypted differently every time
+        assert_ne!(cipher.encrypt(message).unwrap(), encrypted);
+
+        assert!(other_cipher.decrypt(&encrypted).is_err());
+        assert!(cipher.decrypt(&encrypted[..NONCE_SIZE - 1]).is_err());
+    }
+
+    fn header<'a>(headers: &'a HeaderMap, name: &str) -> &'a str {
+        headers.get(name).unwrap().to_str().unwrap()
+    }
+
+    #[test]
+    fn test_peer_credentials() {
+        let request = PeerRequestDigest::new("/qdrant.PointsInternal/Upsert", b"points");
+        let mut headers = HeaderMap::new();
+        PeerCredentials::new(42, None).apply(&mut headers, Some(&request));
+        assert_eq!(headers.get(PEER_ID_HEADER).unwrap(), "42");
+        assert!(!headers.contains_key(PEER_SIGNATURE_HEADER));
+
+        let credentials = PeerCredentials::new(42, Some("cluster-secret"));
+        credentials.apply(&mut headers, Some(&request));
+        let timestamp = header(&headers, PEER_TIMESTAMP_HEADER);
+        let nonce = header(&headers, PEER_NONCE_HEADER);
+        let signature = header(&headers, PEER_SIGNATURE_HEADER);
+        // Secret is not sent
+        assert!(headers
+            .values()
+            .all(|value| !value.to_str().unwrap().contains("cluster-secret")));
+        assert!(timestamp.parse::<u64>().unwrap() <= unix_timestamp());
+
+        let secret = credentials.secret().unwrap();
+        assert!(secret.verify("42", timestamp, nonce, &request, signature));
+        assert!(!secret.verify("43", timestamp, nonce, &request, signature));
+        assert!(!secret.verify("42", "0", nonce, &request, signature));
+        assert!(!secret.verify("42", timestamp, "other", &request, signature));
+        assert!(!secret.verify("42", timestamp, nonce, &request, "not hex"));
+        assert!(
+            !PeerSecret::new("other-secret").verify("42", timestamp, nonce, &request, signature)
+        );
+
+        // Signature is bound to the method and the body
+        let other_method = PeerRequestDigest::new("/qdrant.PointsInternal/Delete", b"points");
+        assert!(!secret.verify("42", timestamp, nonce, &other_method, signature));
+        let other_body = PeerRequestDigest::new("/qdrant.PointsInternal/Upsert", b"other points");
+        assert!(!secret.verify("42", timestamp, nonce, &other_body, signature));
+
+        // Every request has its own nonce
+        let mut other_headers = HeaderMap::new();
+        credentials.apply(&mut other_headers, Some(&request));
+        assert_ne!(header(&other_headers, PEER_NONCE_HEADER), nonce);
+    }
+}
@@ -6,6 +6,33 @@ pub struct VectorParams {
     /// Distance function used for comparing vectors
     #[prost(enumeration="Distance", tag="2")]
     pub distance: i32,
+    /// Custom params for HNSW index of this vector. If none - values from collection configuration are used.
+    #[prost(message, optional, tag="3")]
+    pub hnsw_config: ::core::option::Option<HnswConfigDiff>,
+    /// If set - vectors of indexed segments are also stored product-quantized in RAM
+    #[prost(message, optional, tag="4")]
+    pub quantization_config: ::core::option::Option<ProductQuantizationConfig>,
+    /// If true - vectors of optimized segments are stored in mmap files, regardless of the memmap_threshold
+    #[prost(bool, optional, tag="5")]
+    pub on_disk: ::core::option::Option<bool>,
+    /// Type of the stored vector elements. Default is Float32
+    #[prost(enumeration="Datatype", optional, tag="6")]
+    pub datatype: ::core::option::Option<i32>,
+    /// If set - each point stores a list of vectors of the size, flattened into a single vector
+    #[prost(message, optional, tag="7")]
+    pub multivector_config: ::core::option::Option<MultiVectorConfig>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MultiVectorConfig {
+    /// Comparator of the multivectors
+    #[prost(enumeration="MultiVectorComparator", tag="1")]
+    pub comparator: i32,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ProductQuantizationConfig {
+    /// Number of parts, vectors are split into. Each part of each vector takes 1 byte
+    #[prost(uint64, tag="1")]
+    pub num_subvectors: u64,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct VectorParamsMap {
@@ -28,6 +55,42 @@ pub mod vectors_config {
     }
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Disabled {
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QuantizationConfigDiff {
+    #[prost(oneof="quantization_config_diff::Quantization", tags="1, 2")]
+    pub quantization: ::core::option::Option<quantization_config_diff::Quantization>,
+}
+/// Nested message and enum types in `QuantizationConfigDiff`.
+pub mod quantization_config_diff {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Quantization {
+        #[prost(message, tag="1")]
+        Product(super::ProductQuantizationConfig),
+        #[prost(message, tag="2")]
+        Disabled(super::Disabled),
+    }
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VectorParamsDiff {
+    /// Update of the custom params for HNSW index of this vector
+    #[prost(message, optional, tag="1")]
+    pub hnsw_config: ::core::option::Option<HnswConfigDiff>,
+    /// New config of the product quantization of this vector, disabled removes it
+    #[prost(message, optional, tag="2")]
+    pub quantization_config: ::core::option::Option<QuantizationConfigDiff>,
+    /// If true - vectors of optimized segments are stored in mmap files, regardless of their size
+    #[prost(bool, optional, tag="3")]
+    pub on_disk: ::core::option::Option<bool>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VectorsConfigDiff {
+    /// Updates of the vector params by their names, the single unnamed vector has an empty name
+    #[prost(map="string, message", tag="1")]
+    pub map: ::std::collections::HashMap<::prost::alloc::string::String, VectorParamsDiff>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetCollectionInfoRequest {
     /// Name of the collection
     #[prost(string, tag="1")]
@@ -59,6 +122,32 @@ pub struct ListCollectionsResponse {
     pub time: f64,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListAliasesRequest {
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListCollectionAliasesRequest {
+    /// Name of the collection or its alias
+    #[prost(string, tag="1")]
+    pub collection_name: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AliasDescription {
+    /// Name of the alias
+    #[prost(string, tag="1")]
+    pub alias_name: ::prost::alloc::string::String,
+    /// Name of the collection, the alias points to
+    #[prost(string, tag="2")]
+    pub collection_name: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListAliasesResponse {
+    #[prost(message, repeated, tag="1")]
+    pub aliases: ::prost::alloc::vec::Vec<AliasDescription>,
+    /// Time spent to process
+    #[prost(double, tag="2")]
+    pub time: f64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct OptimizerStatus {
     #[prost(bool, tag="1")]
     pub ok: bool,
@@ -95,6 +184,12 @@ pub struct WalConfigDiff {
     /// Number of segments to create in advance
     #[prost(uint64, optional, tag="2")]
     pub wal_segments_ahead: ::core::option::Option<u64>,
+    /// If true - encrypt WAL records, payloads and vectors with the key from the storage configuration
+    #[prost(bool, optional, tag="3")]
+    pub encrypted: ::core::option::Option<bool>,
+    /// Number of seconds, during which retries of the operation with the same id are applied only once
+    #[prost(uint64, optional, tag="4")]
+    pub operation_id_window_sec: ::core::option::Option<u64>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct OptimizersConfigDiff {
@@ -150,6 +245,30 @@ pub struct OptimizersConfigDiff {
     pub max_optimization_threads: ::core::option::Option<u64>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PayloadFieldTypes {
+    /// Expected types of payload fields by their key
+    #[prost(map="string, enumeration(PayloadSchemaType)", tag="1")]
+    pub types: ::std::collections::HashMap<::prost::alloc::string::String, i32>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AutoPayloadIndexConfig {
+    /// Indexes are not created automatically, if the collection already has this number of payload indexes, default = 16
+    #[prost(uint64, optional, tag="1")]
+    pub max_indexed_fields: ::core::option::Option<u64>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TtlConfig {
+    /// Payload field with the RFC 3339 datetime, the lifetime of the point is counted from
+    #[prost(string, tag="1")]
+    pub field: ::prost::alloc::string::String,
+    /// Points are deleted once this number of seconds is passed since the datetime in the field, 0 disables the expiration
+    #[prost(uint64, tag="2")]
+    pub ttl_sec: u64,
+    /// How often expired points are deleted, in seconds, default = 60
+    #[prost(uint64, optional, tag="3")]
+    pub check_interval_sec: ::core::option::Option<u64>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CreateCollection {
     /// Name of the collection
     #[prost(string, tag="1")]
@@ -181,6 +300,21 @@ pub struct CreateCollection {
     /// How many replicas should apply the operation for us to consider it successful, default = 1
     #[prost(uint32, optional, tag="12")]
     pub write_consistency_factor: ::core::option::Option<u32>,
+    /// Specify name of the other collection to copy data from
+    #[prost(string, optional, tag="13")]
+    pub init_from_collection: ::core::option::Option<::prost::alloc::string::String>,
+    /// Reject points with payload values of other types
+    #[prost(message, optional, tag="14")]
+    pub payload_field_types: ::core::option::Option<PayloadFieldTypes>,
+    /// Create payload indexes for the fields, first seen in the written points
+    #[prost(message, optional, tag="15")]
+    pub auto_payload_index: ::core::option::Option<AutoPayloadIndexConfig>,
+    /// Name of the vector, used by searches which do not specify one
+    #[prost(string, optional, tag="16")]
+    pub default_vector: ::core::option::Option<::prost::alloc::string::String>,
+    /// Delete points, once their lifetime is expired
+    #[prost(message, optional, tag="17")]
+    pub ttl: ::core::option::Option<TtlConfig>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct UpdateCollection {
@@ -196,6 +330,21 @@ pub struct UpdateCollection {
     /// New configuration parameters for the collection
     #[prost(message, optional, tag="4")]
     pub params: ::core::option::Option<CollectionParamsDiff>,
+    /// New expected types of payload fields, empty map disables the check
+    #[prost(message, optional, tag="5")]
+    pub payload_field_types: ::core::option::Option<PayloadFieldTypes>,
+    /// New config of the automatic payload indexing, max_indexed_fields = 0 disables it
+    #[prost(message, optional, tag="6")]
+    pub auto_payload_index: ::core::option::Option<AutoPayloadIndexConfig>,
+    /// New HNSW params of the collection, indexed segments are rebuilt in background
+    #[prost(message, optional, tag="7")]
+    pub hnsw_config: ::core::option::Option<HnswConfigDiff>,
+    /// New params of the vectors, indexed segments are rebuilt in background
+    #[prost(message, optional, tag="8")]
+    pub vectors_config: ::core::option::Option<VectorsConfigDiff>,
+    /// New config of the points expiration, ttl_sec = 0 disables it
+    #[prost(message, optional, tag="9")]
+    pub ttl: ::core::option::Option<TtlConfig>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DeleteCollection {
@@ -207,6 +356,15 @@ pub struct DeleteCollection {
     pub timeout: ::core::option::Option<u64>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ShardPlacement {
+    /// Id of the shard
+    #[prost(uint32, tag="1")]
+    pub shard_id: u32,
+    /// Peers, which hold replicas of the shard
+    #[prost(uint64, repeated, tag="2")]
+    pub peers: ::prost::alloc::vec::Vec<u64>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CollectionOperationResponse {
     /// if operation made changes
     #[prost(bool, tag="1")]
@@ -214,6 +372,9 @@ pub struct CollectionOperationResponse {
     /// Time spent to process
     #[prost(double, tag="2")]
     pub time: f64,
+    /// Placement of shards of the new collection, only for creation in distributed mode
+    #[prost(message, repeated, tag="3")]
+    pub shard_placement: ::prost::alloc::vec::Vec<ShardPlacement>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CollectionParams {
@@ -232,6 +393,9 @@ pub struct CollectionParams {
     /// How many replicas should apply the operation for us to consider it successful
     #[prost(uint32, optional, tag="7")]
     pub write_consistency_factor: ::core::option::Option<u32>,
+    /// Name of the vector, used by searches which do not specify one
+    #[prost(string, optional, tag="8")]
+    pub default_vector: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CollectionParamsDiff {
@@ -256,6 +420,15 @@ pub struct CollectionConfig {
     /// Configuration of the Write-Ahead-Log
     #[prost(message, optional, tag="4")]
     pub wal_config: ::core::option::Option<WalConfigDiff>,
+    /// Expected types of payload fields
+    #[prost(message, optional, tag="5")]
+    pub payload_field_types: ::core::option::Option<PayloadFieldTypes>,
+    /// Automatic creation of payload indexes
+    #[prost(message, optional, tag="6")]
+    pub auto_payload_index: ::core::option::Option<AutoPayloadIndexConfig>,
+    /// Expiration of the points
+    #[prost(message, optional, tag="7")]
+    pub ttl: ::core::option::Option<TtlConfig>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct TextIndexParams {
@@ -273,8 +446,14 @@ pub struct TextIndexParams {
     pub max_token_len: ::core::option::Option<u64>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct KeywordIndexParams {
+    /// If true - points of each value are read from disk instead of being kept in RAM
+    #[prost(bool, optional, tag="1")]
+    pub on_disk: ::core::option::Option<bool>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct PayloadIndexParams {
-    #[prost(oneof="payload_index_params::IndexParams", tags="1")]
+    #[prost(oneof="payload_index_params::IndexParams", tags="1, 2")]
     pub index_params: ::core::option::Option<payload_index_params::IndexParams>,
 }
 /// Nested message and enum types in `PayloadIndexParams`.
@@ -284,6 +463,9 @@ pub mod payload_index_params {
         /// Parameters for text index
         #[prost(message, tag="1")]
         TextIndexParams(super::TextIndexParams),
+        /// Parameters for keyword index
+        #[prost(message, tag="2")]
+        KeywordIndexParams(super::KeywordIndexParams),
     }
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -299,6 +481,27 @@ pub struct PayloadSchemaInfo {
     pub points: ::core::option::Option<u64>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct IndexRebuildProgress {
+    /// Number of segments, waiting for the rebuild
+    #[prost(uint64, tag="1")]
+    pub pending_segments: u64,
+    /// Number of segments, being rebuilt right now
+    #[prost(uint64, tag="2")]
+    pub rebuilding_segments: u64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CollectionInitStatus {
+    /// Name of the collection, points are copied from
+    #[prost(string, tag="1")]
+    pub source: ::prost::alloc::string::String,
+    /// Number of points, copied into the local shards so far
+    #[prost(uint64, tag="2")]
+    pub copied_points: u64,
+    /// Set if the copying failed
+    #[prost(string, optional, tag="3")]
+    pub error: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CollectionInfo {
     /// operating condition of the collection
     #[prost(enumeration="CollectionStatus", tag="1")]
@@ -324,6 +527,15 @@ pub struct CollectionInfo {
     /// number of indexed vectors in the collection.
     #[prost(uint64, optional, tag="10")]
     pub indexed_vectors_count: ::core::option::Option<u64>,
+    /// Progress of rebuilding indexes, which were not found on load
+    #[prost(message, optional, tag="11")]
+    pub index_rebuild: ::core::option::Option<IndexRebuildProgress>,
+    /// Progress of copying points from another collection, requested with `init_from`
+    #[prost(message, optional, tag="12")]
+    pub initialization: ::core::option::Option<CollectionInitStatus>,
+    /// Progress of rebuilding segments, built with the vector params before the last collection update
+    #[prost(message, optional, tag="13")]
+    pub config_migration: ::core::option::Option<IndexRebuildProgress>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ChangeAliases {
@@ -336,7 +548,7 @@ pub struct ChangeAliases {
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct AliasOperations {
-    #[prost(oneof="alias_operations::Action", tags="1, 2, 3")]
+    #[prost(oneof="alias_operations::Action", tags="1, 2, 3, 4")]
     pub action: ::core::option::Option<alias_operations::Action>,
 }
 /// Nested message and enum types in `AliasOperations`.
@@ -349,6 +561,8 @@ pub mod alias_operations {
         RenameAlias(super::RenameAlias),
         #[prost(message, tag="3")]
         DeleteAlias(super::DeleteAlias),
+        #[prost(message, tag="4")]
+        SwapAliases(super::SwapAliases),
     }
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -375,6 +589,15 @@ pub struct DeleteAlias {
     #[prost(string, tag="1")]
     pub alias_name: ::prost::alloc::string::String,
 }
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SwapAliases {
+    /// Name of the first alias
+    #[prost(string, tag="1")]
+    pub first_alias_name: ::prost::alloc::string::String,
+    /// Name of the second alias
+    #[prost(string, tag="2")]
+    pub second_alias_name: ::prost::alloc::string::String,
+}
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum Distance {
@@ -385,6 +608,20 @@ pub enum Distance {
 }
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
+pub enum Datatype {
+    Default = 0,
+    Float32 = 1,
+    /// Elements should be integers in range [0, 255]
+    Uint8 = 2,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum MultiVectorComparator {
+    /// Sum of the best similarities of each query vector to any of the point vectors
+    MaxSim = 0,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
 pub enum CollectionStatus {
     UnknownCollectionStatus = 0,
     /// All segments are ready
@@ -403,6 +640,7 @@ pub enum PayloadSchemaType {
     Float = 3,
     Geo = 4,
     Text = 5,
+    Datetime = 6,
 }
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
@@ -598,6 +836,48 @@ pub mod collections_client {
             );
             self.inner.unary(request.into_request(), path, codec).await
         }
+        ///
+        ///Get list of all aliases for a collection
+        pub async fn list_collection_aliases(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListCollectionAliasesRequest>,
+        ) -> Result<tonic::Response<super::ListAliasesResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/qdrant.Collections/ListCollectionAliases",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        ///
+        ///Get list of all aliases for all existing collections
+        pub async fn list_aliases(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListAliasesRequest>,
+        ) -> Result<tonic::Response<super::ListAliasesResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/qdrant.Collections/ListAliases",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -643,12 +923,24 @@ pub mod collections_server {
             &self,
             request: tonic::Request<super::ChangeAliases>,
         ) -> Result<tonic::Response<super::CollectionOperationResponse>, tonic::Status>;
+        ///
+        ///Get list of all aliases for a collection
+        async fn list_collection_aliases(
+            &self,
+            request: tonic::Request<super::ListCollectionAliasesRequest>,
+        ) -> Result<tonic::Response<super::ListAliasesResponse>, tonic::Status>;
+        ///
+        ///Get list of all aliases for all existing collections
+        async fn list_aliases(
+            &self,
+            request: tonic::Request<super::ListAliasesRequest>,
+        ) -> Result<tonic::Response<super::ListAliasesResponse>, tonic::Status>;
     }
     #[derive(Debug)]
     pub struct CollectionsServer<T: Collections> {
         inner: _Inner<T>,
-        accept_compression_encodings: (),
-        send_compression_encodings: (),
+        accept_compression_encodings: EnabledCompressionEncodings,
+        send_compression_encodings: EnabledCompressionEncodings,
     }
     struct _Inner<T>(Arc<T>);
     impl<T: Collections> CollectionsServer<T> {
@@ -672,6 +964,18 @@ pub mod collections_server {
         {
             InterceptedService::new(Self::new(inner), interceptor)
         }
+        /// Enable decompressing requests with `gzip`.
+        #[must_use]
+        pub fn accept_gzip(mut self) -> Self {
+            self.accept_compression_encodings.enable_gzip();
+            self
+        }
+        /// Compress responses with `gzip`, if the client supports it.
+        #[must_use]
+        pub fn send_gzip(mut self) -> Self {
+            self.send_compression_encodings.enable_gzip();
+            self
+        }
     }
     impl<T, B> tonic::codegen::Service<http::Request<B>> for CollectionsServer<T>
     where
@@ -921,6 +1225,86 @@ pub mod collections_server {
                     };
                     Box::pin(fut)
                 }
+                "/qdrant.Collections/ListCollectionAliases" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListCollectionAliasesSvc<T: Collections>(pub Arc<T>);
+                    impl<
+                        T: Collections,
+                    > tonic::server::UnaryService<super::ListCollectionAliasesRequest>
+                    for ListCollectionAliasesSvc<T> {
+                        type Response = super::ListAliasesResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListCollectionAliasesRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).list_collection_aliases(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ListCollectionAliasesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/qdrant.Collections/ListAliases" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListAliasesSvc<T: Collections>(pub Arc<T>);
+                    impl<
+                        T: Collections,
+                    > tonic::server::UnaryService<super::ListAliasesRequest>
+                    for ListAliasesSvc<T> {
+                        type Response = super::ListAliasesResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListAliasesRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).list_aliases(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ListAliasesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         Ok(
@@ -1107,8 +1491,8 @@ pub mod collections_internal_server {
     #[derive(Debug)]
     pub struct CollectionsInternalServer<T: CollectionsInternal> {
         inner: _Inner<T>,
-        accept_compression_encodings: (),
-        send_compression_encodings: (),
+        accept_compression_encodings: EnabledCompressionEncodings,
+        send_compression_encodings: EnabledCompressionEncodings,
     }
     struct _Inner<T>(Arc<T>);
     impl<T: CollectionsInternal> CollectionsInternalServer<T> {
@@ -1132,6 +1516,18 @@ pub mod collections_internal_server {
         {
             InterceptedService::new(Self::new(inner), interceptor)
         }
+        /// Enable decompressing requests with `gzip`.
+        #[must_use]
+        pub fn accept_gzip(mut self) -> Self {
+            self.accept_compression_encodings.enable_gzip();
+            self
+        }
+        /// Compress responses with `gzip`, if the client supports it.
+        #[must_use]
+        pub fn send_gzip(mut self) -> Self {
+            self.send_compression_encodings.enable_gzip();
+            self
+        }
     }
     impl<T, B> tonic::codegen::Service<http::Request<B>> for CollectionsInternalServer<T>
     where
@@ -1359,7 +1755,7 @@ pub mod point_id {
         /// Numerical ID of the point
         #[prost(uint64, tag="1")]
         Num(u64),
-        /// UUID
+        /// UUID or any other non-empty string ID, e.g. document URI. Strings of digits are numeric IDs, other strings are stored as name-based UUIDs
         #[prost(string, tag="2")]
         Uuid(::prost::alloc::string::String),
     }
@@ -1383,6 +1779,12 @@ pub struct UpsertPoints {
     pub wait: ::core::option::Option<bool>,
     #[prost(message, repeated, tag="3")]
     pub points: ::prost::alloc::vec::Vec<PointStruct>,
+    /// Write ordering guarantees
+    #[prost(enumeration="WriteOrdering", optional, tag="4")]
+    pub ordering: ::core::option::Option<i32>,
+    /// Retries with the same id are applied only once
+    #[prost(string, optional, tag="5")]
+    pub operation_id: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DeletePoints {
@@ -1395,6 +1797,76 @@ pub struct DeletePoints {
     /// Affected points
     #[prost(message, optional, tag="3")]
     pub points: ::core::option::Option<PointsSelector>,
+    /// Write ordering guarantees
+    #[prost(enumeration="WriteOrdering", optional, tag="4")]
+    pub ordering: ::core::option::Option<i32>,
+    /// Retries with the same id are applied only once
+    #[prost(string, optional, tag="5")]
+    pub operation_id: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PointsUpdateOperation {
+    #[prost(oneof="points_update_operation::Operation", tags="1, 2, 3, 4, 5")]
+    pub operation: ::core::option::Option<points_update_operation::Operation>,
+}
+/// Nested message and enum types in `PointsUpdateOperation`.
+pub mod points_update_operation {
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct PointStructList {
+        #[prost(message, repeated, tag="1")]
+        pub points: ::prost::alloc::vec::Vec<super::PointStruct>,
+    }
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct SetPayload {
+        /// New payload values
+        #[prost(map="string, message", tag="1")]
+        pub payload: ::std::collections::HashMap<::prost::alloc::string::String, super::Value>,
+        /// List of point to modify
+        #[prost(message, repeated, tag="2")]
+        pub points: ::prost::alloc::vec::Vec<super::PointId>,
+    }
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct DeletePayload {
+        /// List of keys to delete
+        #[prost(string, repeated, tag="1")]
+        pub keys: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+        /// Affected points
+        #[prost(message, repeated, tag="2")]
+        pub points: ::prost::alloc::vec::Vec<super::PointId>,
+    }
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Operation {
+        /// Insert or overwrite points
+        #[prost(message, tag="1")]
+        Upsert(PointStructList),
+        /// Delete points
+        #[prost(message, tag="2")]
+        Delete(super::PointsSelector),
+        /// Set payload for points
+        #[prost(message, tag="3")]
+        SetPayload(SetPayload),
+        /// Delete specified key payload for points
+        #[prost(message, tag="4")]
+        DeletePayload(DeletePayload),
+        /// Remove all payload for specified points
+        #[prost(message, tag="5")]
+        ClearPayload(super::PointsSelector),
+    }
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateBatchPoints {
+    /// name of the collection
+    #[prost(string, tag="1")]
+    pub collection_name: ::prost::alloc::string::String,
+    /// Wait until the changes have been applied?
+    #[prost(bool, optional, tag="2")]
+    pub wait: ::core::option::Option<bool>,
+    /// Operations to apply, in the given order
+    #[prost(message, repeated, tag="3")]
+    pub operations: ::prost::alloc::vec::Vec<PointsUpdateOperation>,
+    /// Write ordering guarantees
+    #[prost(enumeration="WriteOrdering", optional, tag="4")]
+    pub ordering: ::core::option::Option<i32>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetPoints {
@@ -1425,6 +1897,9 @@ pub struct SetPayloadPoints {
     /// List of point to modify
     #[prost(message, repeated, tag="4")]
     pub points: ::prost::alloc::vec::Vec<PointId>,
+    /// Write ordering guarantees
+    #[prost(enumeration="WriteOrdering", optional, tag="5")]
+    pub ordering: ::core::option::Option<i32>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DeletePayloadPoints {
@@ -1440,9 +1915,77 @@ pub struct DeletePayloadPoints {
     /// Affected points
     #[prost(message, repeated, tag="4")]
     pub points: ::prost::alloc::vec::Vec<PointId>,
+    /// Write ordering guarantees
+    #[prost(enumeration="WriteOrdering", optional, tag="5")]
+    pub ordering: ::core::option::Option<i32>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct ClearPayloadPoints {
+pub struct IncrementPayload {
+    /// Top-level payload key
+    #[prost(string, tag="1")]
+    pub key: ::prost::alloc::string::String,
+    /// Number to add. Missing value is set to it
+    #[prost(message, optional, tag="2")]
+    pub by: ::core::option::Option<Value>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AppendPayload {
+    /// Top-level payload key
+    #[prost(string, tag="1")]
+    pub key: ::prost::alloc::string::String,
+    /// Values to append to the array. Missing value is set to them, a single value is turned into an array
+    #[prost(message, repeated, tag="2")]
+    pub values: ::prost::alloc::vec::Vec<Value>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SetPayloadIfAbsent {
+    /// Top-level payload key
+    #[prost(string, tag="1")]
+    pub key: ::prost::alloc::string::String,
+    /// Value to set, if the key has no value yet
+    #[prost(message, optional, tag="2")]
+    pub value: ::core::option::Option<Value>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PayloadUpdate {
+    #[prost(oneof="payload_update::Update", tags="1, 2, 3")]
+    pub update: ::core::option::Option<payload_update::Update>,
+}
+/// Nested message and enum types in `PayloadUpdate`.
+pub mod payload_update {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Update {
+        /// Add a number to the numeric value
+        #[prost(message, tag="1")]
+        Increment(super::IncrementPayload),
+        /// Append values to the array
+        #[prost(message, tag="2")]
+        Append(super::AppendPayload),
+        /// Set the value, if it is missing
+        #[prost(message, tag="3")]
+        SetIfAbsent(super::SetPayloadIfAbsent),
+    }
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdatePayloadPoints {
+    /// name of the collection
+    #[prost(string, tag="1")]
+    pub collection_name: ::prost::alloc::string::String,
+    /// Wait until the changes have been applied?
+    #[prost(bool, optional, tag="2")]
+    pub wait: ::core::option::Option<bool>,
+    /// Changes of the payload values, applied in order
+    #[prost(message, repeated, tag="3")]
+    pub updates: ::prost::alloc::vec::Vec<PayloadUpdate>,
+    /// Affected points
+    #[prost(message, repeated, tag="4")]
+    pub points: ::prost::alloc::vec::Vec<PointId>,
+    /// Write ordering guarantees
+    #[prost(enumeration="WriteOrdering", optional, tag="5")]
+    pub ordering: ::core::option::Option<i32>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ClearPayloadPoints {
     /// name of the collection
     #[prost(string, tag="1")]
     pub collection_name: ::prost::alloc::string::String,
@@ -1452,6 +1995,9 @@ pub struct ClearPayloadPoints {
     /// Affected points
     #[prost(message, optional, tag="3")]
     pub points: ::core::option::Option<PointsSelector>,
+    /// Write ordering guarantees
+    #[prost(enumeration="WriteOrdering", optional, tag="4")]
+    pub ordering: ::core::option::Option<i32>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CreateFieldIndexCollection {
@@ -1470,6 +2016,9 @@ pub struct CreateFieldIndexCollection {
     /// Payload index params.
     #[prost(message, optional, tag="5")]
     pub field_index_params: ::core::option::Option<PayloadIndexParams>,
+    /// Write ordering guarantees
+    #[prost(enumeration="WriteOrdering", optional, tag="6")]
+    pub ordering: ::core::option::Option<i32>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DeleteFieldIndexCollection {
@@ -1482,16 +2031,19 @@ pub struct DeleteFieldIndexCollection {
     /// Field name to delete
     #[prost(string, tag="3")]
     pub field_name: ::prost::alloc::string::String,
+    /// Write ordering guarantees
+    #[prost(enumeration="WriteOrdering", optional, tag="4")]
+    pub ordering: ::core::option::Option<i32>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct PayloadIncludeSelector {
-    /// List of payload keys to include into result
+    /// List of payload keys to include into result, nested keys are separated by dots
     #[prost(string, repeated, tag="1")]
     pub fields: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct PayloadExcludeSelector {
-    /// List of payload keys to exclude from the result
+    /// List of payload keys to exclude from the result, nested keys are separated by dots
     #[prost(string, repeated, tag="1")]
     pub fields: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
 }
@@ -1567,6 +2119,16 @@ pub struct SearchParams {
     ///Search without approximation. If set to true, search may run long but with exact results.
     #[prost(bool, optional, tag="2")]
     pub exact: ::core::option::Option<bool>,
+    ///
+    ///Time budget of the HNSW search in milliseconds.
+    ///If the budget is exhausted, search returns the best points found so far instead of failing.
+    #[prost(uint64, optional, tag="3")]
+    pub max_time_ms: ::core::option::Option<u64>,
+    ///
+    ///Report, how the search was executed: which strategy the query planner chose for each segment,
+    ///depending on the number of points, matching the filter.
+    #[prost(bool, optional, tag="4")]
+    pub explain: ::core::option::Option<bool>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SearchPoints {
@@ -1628,6 +2190,27 @@ pub struct ScrollPoints {
     /// Options for specifying which vectors to include into response
     #[prost(message, optional, tag="7")]
     pub with_vectors: ::core::option::Option<WithVectorsSelector>,
+    /// Order points by the value of a payload field
+    #[prost(message, optional, tag="8")]
+    pub order_by: ::core::option::Option<OrderBy>,
+    /// Return a sample of points instead of a page, not compatible with `offset` and `order_by`
+    #[prost(enumeration="Sample", optional, tag="9")]
+    pub sample: ::core::option::Option<i32>,
+}
+/// Requires an integer or float payload index on the field.
+/// Point with multiple values is ordered by the smallest value for `Asc` and by the largest one for `Desc`.
+/// Points without the field are skipped.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct OrderBy {
+    /// Payload key to order by
+    #[prost(string, tag="1")]
+    pub key: ::prost::alloc::string::String,
+    /// Default: Asc
+    #[prost(enumeration="Direction", optional, tag="2")]
+    pub direction: ::core::option::Option<i32>,
+    /// Start reading from this value, use together with `offset` for pagination
+    #[prost(double, optional, tag="3")]
+    pub start_from: ::core::option::Option<f64>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct RecommendPoints {
@@ -1664,6 +2247,18 @@ pub struct RecommendPoints {
     /// Options for specifying which vectors to include into response
     #[prost(message, optional, tag="12")]
     pub with_vectors: ::core::option::Option<WithVectorsSelector>,
+    /// Name of the collection to use for points lookup, if not specified - use current collection
+    #[prost(message, optional, tag="13")]
+    pub lookup_from: ::core::option::Option<LookupLocation>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LookupLocation {
+    /// Name of the collection used for lookup
+    #[prost(string, tag="1")]
+    pub collection_name: ::prost::alloc::string::String,
+    /// Name of the vector in the lookup collection, if not specified - default vector
+    #[prost(string, optional, tag="2")]
+    pub vector_name: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct RecommendBatchPoints {
@@ -1685,6 +2280,36 @@ pub struct CountPoints {
     #[prost(bool, optional, tag="3")]
     pub exact: ::core::option::Option<bool>,
 }
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FacetPoints {
+    /// name of the collection
+    #[prost(string, tag="1")]
+    pub collection_name: ::prost::alloc::string::String,
+    /// Payload field to count values of
+    #[prost(string, tag="2")]
+    pub key: ::prost::alloc::string::String,
+    /// Filter conditions - count only those points that satisfy the specified conditions
+    #[prost(message, optional, tag="3")]
+    pub filter: ::core::option::Option<Filter>,
+    /// Max number of values to return, default is 10, at most 1000
+    #[prost(uint64, optional, tag="4")]
+    pub limit: ::core::option::Option<u64>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StreamPointsChanges {
+    /// name of the collection
+    #[prost(string, tag="1")]
+    pub collection_name: ::prost::alloc::string::String,
+    /// Shard to read changes of, may be omitted if there is only one local shard
+    #[prost(uint32, optional, tag="2")]
+    pub shard_id: ::core::option::Option<u32>,
+    /// Sequential number of the first operation to stream
+    #[prost(uint64, tag="3")]
+    pub from: u64,
+    /// Max number of operations to read from the WAL at once, default is 100
+    #[prost(uint64, optional, tag="4")]
+    pub limit: ::core::option::Option<u64>,
+}
 // ---------------------------------------------
 // ---------------- RPC Response ---------------
 // ---------------------------------------------
@@ -1698,6 +2323,15 @@ pub struct PointsOperationResponse {
     pub time: f64,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateBatchResponse {
+    /// Results of the operations, in the same order
+    #[prost(message, repeated, tag="1")]
+    pub result: ::prost::alloc::vec::Vec<UpdateResult>,
+    /// Time spent to process
+    #[prost(double, tag="2")]
+    pub time: f64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct UpdateResult {
     /// Number of operation
     #[prost(uint64, tag="1")]
@@ -1705,6 +2339,21 @@ pub struct UpdateResult {
     /// Operation status
     #[prost(enumeration="UpdateStatus", tag="2")]
     pub status: i32,
+    /// Number of operation in the WAL of each updated replica
+    #[prost(message, repeated, tag="3")]
+    pub replica_operation_ids: ::prost::alloc::vec::Vec<ReplicaOperationId>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReplicaOperationId {
+    /// Id of the shard
+    #[prost(uint32, tag="1")]
+    pub shard_id: u32,
+    /// Id of the peer, which holds the replica
+    #[prost(uint64, tag="2")]
+    pub peer_id: u64,
+    /// Number of operation in the WAL of the replica
+    #[prost(uint64, tag="3")]
+    pub operation_id: u64,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ScoredPoint {
@@ -1731,6 +2380,12 @@ pub struct SearchResponse {
     /// Time spent to process
     #[prost(double, tag="2")]
     pub time: f64,
+    /// Time budget of the search was exhausted, result might be incomplete
+    #[prost(bool, tag="3")]
+    pub truncated: bool,
+    /// How the search was executed, if `explain` is requested
+    #[prost(message, optional, tag="4")]
+    pub explanation: ::core::option::Option<SearchExplanation>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct BatchResult {
@@ -1744,6 +2399,68 @@ pub struct SearchBatchResponse {
     /// Time spent to process
     #[prost(double, tag="2")]
     pub time: f64,
+    /// Time budget of some search was exhausted, result might be incomplete
+    #[prost(bool, tag="3")]
+    pub truncated: bool,
+    /// How the searches were executed, if `explain` is requested
+    #[prost(message, optional, tag="4")]
+    pub explanation: ::core::option::Option<SearchExplanation>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SearchExplanation {
+    /// Segment searches, which scored all the points, matching the filter
+    #[prost(uint64, tag="1")]
+    pub exact: u64,
+    /// Segment searches in the HNSW graph without a filter
+    #[prost(uint64, tag="2")]
+    pub hnsw: u64,
+    /// Segment searches in the HNSW graph, which checked the filter on each visited point
+    #[prost(uint64, tag="3")]
+    pub filtered_hnsw: u64,
+    /// Segment searches in the HNSW graph without the filter, which filtered the found points afterwards
+    #[prost(uint64, tag="4")]
+    pub hnsw_post_filter: u64,
+    /// Number of searched segments
+    #[prost(uint64, tag="5")]
+    pub segments: u64,
+    /// Number of stored vectors, the query vectors were compared with
+    #[prost(uint64, tag="6")]
+    pub scored_vectors: u64,
+    /// The largest size of the beam, used by the HNSW searches
+    #[prost(uint64, optional, tag="7")]
+    pub hnsw_ef: ::core::option::Option<u64>,
+    /// Estimated number of points, matching the filter, summed over the planned searches
+    #[prost(message, optional, tag="8")]
+    pub filter_cardinality: ::core::option::Option<FilterCardinality>,
+    /// Time spent in each shard
+    #[prost(message, repeated, tag="9")]
+    pub shards: ::prost::alloc::vec::Vec<ShardSearchExplanation>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FilterCardinality {
+    /// Number of matching points in the best case
+    #[prost(uint64, tag="1")]
+    pub min: u64,
+    /// Expected number of matching points
+    #[prost(uint64, tag="2")]
+    pub exp: u64,
+    /// Number of matching points in the worst case
+    #[prost(uint64, tag="3")]
+    pub max: u64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ShardSearchExplanation {
+    #[prost(uint32, tag="1")]
+    pub shard_id: u32,
+    /// Time spent to search the shard, including the network round trip for remote shards
+    #[prost(double, tag="2")]
+    pub time: f64,
+    /// Number of searched segments of the shard
+    #[prost(uint64, tag="3")]
+    pub segments: u64,
+    /// Number of stored vectors of the shard, the query vectors were compared with
+    #[prost(uint64, tag="4")]
+    pub scored_vectors: u64,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CountResponse {
@@ -1763,6 +2480,9 @@ pub struct ScrollResponse {
     /// Time spent to process
     #[prost(double, tag="3")]
     pub time: f64,
+    /// Use this value as `order_by.start_from` for the next query
+    #[prost(double, optional, tag="4")]
+    pub next_page_order_value: ::core::option::Option<f64>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CountResult {
@@ -1770,6 +2490,54 @@ pub struct CountResult {
     pub count: u64,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FacetValue {
+    #[prost(oneof="facet_value::Variant", tags="1, 2")]
+    pub variant: ::core::option::Option<facet_value::Variant>,
+}
+/// Nested message and enum types in `FacetValue`.
+pub mod facet_value {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Variant {
+        #[prost(string, tag="1")]
+        KeywordValue(::prost::alloc::string::String),
+        #[prost(int64, tag="2")]
+        IntegerValue(i64),
+    }
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FacetValueHit {
+    /// Value of the payload field
+    #[prost(message, optional, tag="1")]
+    pub value: ::core::option::Option<FacetValue>,
+    /// Approximate number of points with this value
+    #[prost(uint64, tag="2")]
+    pub count: u64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FacetResponse {
+    /// Most frequent values, sorted by count in descending order
+    #[prost(message, repeated, tag="1")]
+    pub hits: ::prost::alloc::vec::Vec<FacetValueHit>,
+    /// Time spent to process
+    #[prost(double, tag="2")]
+    pub time: f64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PointsChange {
+    /// Shard of the changed points
+    #[prost(uint32, tag="1")]
+    pub shard_id: u32,
+    /// Sequential number of the operation in the WAL of the shard
+    #[prost(uint64, tag="2")]
+    pub op_num: u64,
+    /// Type of the change, e.g. `upsert`, `delete` or `set_payload`
+    #[prost(string, tag="3")]
+    pub event: ::prost::alloc::string::String,
+    /// JSON of the change, same as in the REST API
+    #[prost(string, tag="4")]
+    pub data: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct RetrievedPoint {
     #[prost(message, optional, tag="1")]
     pub id: ::core::option::Option<PointId>,
@@ -1777,6 +2545,9 @@ pub struct RetrievedPoint {
     pub payload: ::std::collections::HashMap<::prost::alloc::string::String, Value>,
     #[prost(message, optional, tag="4")]
     pub vectors: ::core::option::Option<Vectors>,
+    /// Value of the `order_by` field
+    #[prost(double, optional, tag="5")]
+    pub order_value: ::core::option::Option<f64>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetResponse {
@@ -1817,10 +2588,20 @@ pub struct Filter {
     /// All conditions must NOT match
     #[prost(message, repeated, tag="3")]
     pub must_not: ::prost::alloc::vec::Vec<Condition>,
+    /// At least minimum amount of given conditions should match
+    #[prost(message, optional, tag="4")]
+    pub min_should: ::core::option::Option<MinShould>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MinShould {
+    #[prost(message, repeated, tag="1")]
+    pub conditions: ::prost::alloc::vec::Vec<Condition>,
+    #[prost(uint64, tag="2")]
+    pub min_count: u64,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Condition {
-    #[prost(oneof="condition::ConditionOneOf", tags="1, 2, 3, 4")]
+    #[prost(oneof="condition::ConditionOneOf", tags="1, 2, 3, 4, 5")]
     pub condition_one_of: ::core::option::Option<condition::ConditionOneOf>,
 }
 /// Nested message and enum types in `Condition`.
@@ -1835,6 +2616,8 @@ pub mod condition {
         HasId(super::HasIdCondition),
         #[prost(message, tag="4")]
         Filter(super::Filter),
+        #[prost(message, tag="5")]
+        Nested(super::NestedCondition),
     }
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -1843,6 +2626,15 @@ pub struct IsEmptyCondition {
     pub key: ::prost::alloc::string::String,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct NestedCondition {
+    /// Path to the array of objects, e.g. `metadata.tags`
+    #[prost(string, tag="1")]
+    pub key: ::prost::alloc::string::String,
+    /// Filter, applied to each element of the array separately
+    #[prost(message, optional, tag="2")]
+    pub filter: ::core::option::Option<Filter>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct HasIdCondition {
     #[prost(message, repeated, tag="1")]
     pub has_id: ::prost::alloc::vec::Vec<PointId>,
@@ -1866,6 +2658,9 @@ pub struct FieldCondition {
     /// Check number of values for a specific field
     #[prost(message, optional, tag="6")]
     pub values_count: ::core::option::Option<ValuesCount>,
+    /// Check if points datetime value lies in a given range
+    #[prost(message, optional, tag="7")]
+    pub datetime_range: ::core::option::Option<DatetimeRange>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Match {
@@ -1902,6 +2697,17 @@ pub struct Range {
     pub lte: ::core::option::Option<f64>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DatetimeRange {
+    #[prost(message, optional, tag="1")]
+    pub lt: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(message, optional, tag="2")]
+    pub gt: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(message, optional, tag="3")]
+    pub gte: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(message, optional, tag="4")]
+    pub lte: ::core::option::Option<::prost_types::Timestamp>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GeoBoundingBox {
     /// north-west corner
     #[prost(message, optional, tag="1")]
@@ -1976,12 +2782,35 @@ pub struct GeoPoint {
 }
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
+pub enum WriteOrdering {
+    /// Write operations are sent to all replicas directly, concurrent writes may be applied in a different order on different replicas. Default
+    Weak = 0,
+    /// Write operations go through the active replica with the highest peer id, the order may differ for a short time when this replica changes
+    Medium = 1,
+    /// Write operations go through the replica with the highest peer id, which only changes through consensus. Writes fail while this replica is not active
+    Strong = 2,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
 pub enum FieldType {
     Keyword = 0,
     Integer = 1,
     Float = 2,
     Geo = 3,
     Text = 4,
+    Datetime = 5,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum Direction {
+    Asc = 0,
+    Desc = 1,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum Sample {
+    /// Uniformly random subset of the points
+    Random = 0,
 }
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
@@ -2076,6 +2905,31 @@ pub mod points_client {
             self.inner.unary(request.into_request(), path, codec).await
         }
         ///
+        ///Perform insert + updates on points, sent as a stream of chunks. Each chunk is acknowledged with a response in the same order.
+        ///Next chunk is processed only after the response to the previous one is sent. Stream is aborted on the first failed chunk.
+        pub async fn upsert_stream(
+            &mut self,
+            request: impl tonic::IntoStreamingRequest<Message = super::UpsertPoints>,
+        ) -> Result<
+            tonic::Response<tonic::codec::Streaming<super::PointsOperationResponse>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/qdrant.Points/UpsertStream",
+            );
+            self.inner.streaming(request.into_streaming_request(), path, codec).await
+        }
+        ///
         ///Delete points
         pub async fn delete(
             &mut self,
@@ -2095,6 +2949,28 @@ pub mod points_client {
             self.inner.unary(request.into_request(), path, codec).await
         }
         ///
+        ///Apply a list of point operations in the given order, with a single response.
+        ///Operations are not applied after the first failed one, previous operations stay applied.
+        pub async fn update_batch(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UpdateBatchPoints>,
+        ) -> Result<tonic::Response<super::UpdateBatchResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/qdrant.Points/UpdateBatch",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        ///
         ///Retrieve points
         pub async fn get(
             &mut self,
@@ -2154,6 +3030,27 @@ pub mod points_client {
             self.inner.unary(request.into_request(), path, codec).await
         }
         ///
+        ///Change payload values of points, depending on their current values, e.g. increment counters
+        pub async fn update_payload(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UpdatePayloadPoints>,
+        ) -> Result<tonic::Response<super::PointsOperationResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/qdrant.Points/UpdatePayload",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        ///
         ///Remove all payload for specified points
         pub async fn clear_payload(
             &mut self,
@@ -2334,6 +3231,50 @@ pub mod points_client {
             let path = http::uri::PathAndQuery::from_static("/qdrant.Points/Count");
             self.inner.unary(request.into_request(), path, codec).await
         }
+        ///
+        ///Count the most frequent values of the payload field among points with given filtering conditions
+        pub async fn facet(
+            &mut self,
+            request: impl tonic::IntoRequest<super::FacetPoints>,
+        ) -> Result<tonic::Response<super::FacetResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/qdrant.Points/Facet");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        ///
+        ///Stream changes of the points from the WAL of the shard, starting from the given operation.
+        ///Stream doesn't end, new changes are sent as they are applied.
+        pub async fn stream_changes(
+            &mut self,
+            request: impl tonic::IntoRequest<super::StreamPointsChanges>,
+        ) -> Result<
+            tonic::Response<tonic::codec::Streaming<super::PointsChange>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/qdrant.Points/StreamChanges",
+            );
+            self.inner.server_streaming(request.into_request(), path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -2349,6 +3290,19 @@ pub mod points_server {
             &self,
             request: tonic::Request<super::UpsertPoints>,
         ) -> Result<tonic::Response<super::PointsOperationResponse>, tonic::Status>;
+        ///Server streaming response type for the UpsertStream method.
+        type UpsertStreamStream: futures_core::Stream<
+                Item = Result<super::PointsOperationResponse, tonic::Status>,
+            >
+            + Send
+            + 'static;
+        ///
+        ///Perform insert + updates on points, sent as a stream of chunks. Each chunk is acknowledged with a response in the same order.
+        ///Next chunk is processed only after the response to the previous one is sent. Stream is aborted on the first failed chunk.
+        async fn upsert_stream(
+            &self,
+            request: tonic::Request<tonic::Streaming<super::UpsertPoints>>,
+        ) -> Result<tonic::Response<Self::UpsertStreamStream>, tonic::Status>;
         ///
         ///Delete points
         async fn delete(
@@ -2356,6 +3310,13 @@ pub mod points_server {
             request: tonic::Request<super::DeletePoints>,
         ) -> Result<tonic::Response<super::PointsOperationResponse>, tonic::Status>;
         ///
+        ///Apply a list of point operations in the given order, with a single response.
+        ///Operations are not applied after the first failed one, previous operations stay applied.
+        async fn update_batch(
+            &self,
+            request: tonic::Request<super::UpdateBatchPoints>,
+        ) -> Result<tonic::Response<super::UpdateBatchResponse>, tonic::Status>;
+        ///
         ///Retrieve points
         async fn get(
             &self,
@@ -2374,6 +3335,12 @@ pub mod points_server {
             request: tonic::Request<super::DeletePayloadPoints>,
         ) -> Result<tonic::Response<super::PointsOperationResponse>, tonic::Status>;
         ///
+        ///Change payload values of points, depending on their current values, e.g. increment counters
+        async fn update_payload(
+            &self,
+            request: tonic::Request<super::UpdatePayloadPoints>,
+        ) -> Result<tonic::Response<super::PointsOperationResponse>, tonic::Status>;
+        ///
         ///Remove all payload for specified points
         async fn clear_payload(
             &self,
@@ -2427,12 +3394,31 @@ pub mod points_server {
             &self,
             request: tonic::Request<super::CountPoints>,
         ) -> Result<tonic::Response<super::CountResponse>, tonic::Status>;
+        ///
+        ///Count the most frequent values of the payload field among points with given filtering conditions
+        async fn facet(
+            &self,
+            request: tonic::Request<super::FacetPoints>,
+        ) -> Result<tonic::Response<super::FacetResponse>, tonic::Status>;
+        ///Server streaming response type for the StreamChanges method.
+        type StreamChangesStream: futures_core::Stream<
+                Item = Result<super::PointsChange, tonic::Status>,
+            >
+            + Send
+            + 'static;
+        ///
+        ///Stream changes of the points from the WAL of the shard, starting from the given operation.
+        ///Stream doesn't end, new changes are sent as they are applied.
+        async fn stream_changes(
+            &self,
+            request: tonic::Request<super::StreamPointsChanges>,
+        ) -> Result<tonic::Response<Self::StreamChangesStream>, tonic::Status>;
     }
     #[derive(Debug)]
     pub struct PointsServer<T: Points> {
         inner: _Inner<T>,
-        accept_compression_encodings: (),
-        send_compression_encodings: (),
+        accept_compression_encodings: EnabledCompressionEncodings,
+        send_compression_encodings: EnabledCompressionEncodings,
     }
     struct _Inner<T>(Arc<T>);
     impl<T: Points> PointsServer<T> {
@@ -2456,6 +3442,18 @@ pub mod points_server {
         {
             InterceptedService::new(Self::new(inner), interceptor)
         }
+        /// Enable decompressing requests with `gzip`.
+        #[must_use]
+        pub fn accept_gzip(mut self) -> Self {
+            self.accept_compression_encodings.enable_gzip();
+            self
+        }
+        /// Compress responses with `gzip`, if the client supports it.
+        #[must_use]
+        pub fn send_gzip(mut self) -> Self {
+            self.send_compression_encodings.enable_gzip();
+            self
+        }
     }
     impl<T, B> tonic::codegen::Service<http::Request<B>> for PointsServer<T>
     where
@@ -2511,6 +3509,45 @@ pub mod points_server {
                     };
                     Box::pin(fut)
                 }
+                "/qdrant.Points/UpsertStream" => {
+                    #[allow(non_camel_case_types)]
+                    struct UpsertStreamSvc<T: Points>(pub Arc<T>);
+                    impl<T: Points> tonic::server::StreamingService<super::UpsertPoints>
+                    for UpsertStreamSvc<T> {
+                        type Response = super::PointsOperationResponse;
+                        type ResponseStream = T::UpsertStreamStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<tonic::Streaming<super::UpsertPoints>>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).upsert_stream(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = UpsertStreamSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/qdrant.Points/Delete" => {
                     #[allow(non_camel_case_types)]
                     struct DeleteSvc<T: Points>(pub Arc<T>);
@@ -2547,6 +3584,44 @@ pub mod points_server {
                     };
                     Box::pin(fut)
                 }
+                "/qdrant.Points/UpdateBatch" => {
+                    #[allow(non_camel_case_types)]
+                    struct UpdateBatchSvc<T: Points>(pub Arc<T>);
+                    impl<T: Points> tonic::server::UnaryService<super::UpdateBatchPoints>
+                    for UpdateBatchSvc<T> {
+                        type Response = super::UpdateBatchResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::UpdateBatchPoints>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).update_batch(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = UpdateBatchSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/qdrant.Points/Get" => {
                     #[allow(non_camel_case_types)]
                     struct GetSvc<T: Points>(pub Arc<T>);
@@ -2659,6 +3734,46 @@ pub mod points_server {
                     };
                     Box::pin(fut)
                 }
+                "/qdrant.Points/UpdatePayload" => {
+                    #[allow(non_camel_case_types)]
+                    struct UpdatePayloadSvc<T: Points>(pub Arc<T>);
+                    impl<
+                        T: Points,
+                    > tonic::server::UnaryService<super::UpdatePayloadPoints>
+                    for UpdatePayloadSvc<T> {
+                        type Response = super::PointsOperationResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::UpdatePayloadPoints>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).update_payload(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = UpdatePayloadSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/qdrant.Points/ClearPayload" => {
                     #[allow(non_camel_case_types)]
                     struct ClearPayloadSvc<T: Points>(pub Arc<T>);
@@ -3001,6 +4116,83 @@ pub mod points_server {
                     };
                     Box::pin(fut)
                 }
+                "/qdrant.Points/Facet" => {
+                    #[allow(non_camel_case_types)]
+                    struct FacetSvc<T: Points>(pub Arc<T>);
+                    impl<T: Points> tonic::server::UnaryService<super::FacetPoints>
+                    for FacetSvc<T> {
+                        type Response = super::FacetResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::FacetPoints>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).facet(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = FacetSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/qdrant.Points/StreamChanges" => {
+                    #[allow(non_camel_case_types)]
+                    struct StreamChangesSvc<T: Points>(pub Arc<T>);
+                    impl<
+                        T: Points,
+                    > tonic::server::ServerStreamingService<super::StreamPointsChanges>
+                    for StreamChangesSvc<T> {
+                        type Response = super::PointsChange;
+                        type ResponseStream = T::StreamChangesStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::StreamPointsChanges>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).stream_changes(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = StreamChangesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         Ok(
@@ -3093,6 +4285,13 @@ pub struct DeletePayloadPointsInternal {
     pub shard_id: u32,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdatePayloadPointsInternal {
+    #[prost(message, optional, tag="1")]
+    pub update_payload_points: ::core::option::Option<UpdatePayloadPoints>,
+    #[prost(uint32, tag="2")]
+    pub shard_id: u32,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ClearPayloadPointsInternal {
     #[prost(message, optional, tag="1")]
     pub clear_payload_points: ::core::option::Option<ClearPayloadPoints>,
@@ -3157,6 +4356,110 @@ pub struct CountPointsInternal {
     #[prost(uint32, tag="2")]
     pub shard_id: u32,
 }
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FacetPointsInternal {
+    #[prost(message, optional, tag="1")]
+    pub facet_points: ::core::option::Option<FacetPoints>,
+    #[prost(uint32, tag="2")]
+    pub shard_id: u32,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReadChangesInternal {
+    #[prost(string, tag="1")]
+    pub collection_name: ::prost::alloc::string::String,
+    #[prost(uint32, tag="2")]
+    pub shard_id: u32,
+    /// Sequential number of the first operation to read
+    #[prost(uint64, tag="3")]
+    pub from: u64,
+    /// Max number of operations to read
+    #[prost(uint64, tag="4")]
+    pub limit: u64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReadChangesResponse {
+    #[prost(message, repeated, tag="1")]
+    pub changes: ::prost::alloc::vec::Vec<PointsChange>,
+    /// Sequential number of the operation to continue reading from
+    #[prost(uint64, tag="2")]
+    pub next_op_num: u64,
+    /// Time spent to process
+    #[prost(double, tag="3")]
+    pub time: f64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ContextPairInternal {
+    /// Preprocessed vector of the positive example
+    #[prost(float, repeated, tag="1")]
+    pub positive: ::prost::alloc::vec::Vec<f32>,
+    /// Preprocessed vector of the negative example
+    #[prost(float, repeated, tag="2")]
+    pub negative: ::prost::alloc::vec::Vec<f32>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DiscoverPointsInternal {
+    #[prost(string, tag="1")]
+    pub collection_name: ::prost::alloc::string::String,
+    #[prost(uint32, tag="2")]
+    pub shard_id: u32,
+    /// Which vector to use for discovery, if not specified - use default vector
+    #[prost(string, optional, tag="3")]
+    pub vector_name: ::core::option::Option<::prost::alloc::string::String>,
+    /// Preprocessed vector of the target
+    #[prost(float, repeated, tag="4")]
+    pub target: ::prost::alloc::vec::Vec<f32>,
+    /// Preprocessed vectors of the context pairs
+    #[prost(message, repeated, tag="5")]
+    pub context: ::prost::alloc::vec::Vec<ContextPairInternal>,
+    /// Filter conditions, examples given by ids are already excluded
+    #[prost(message, optional, tag="6")]
+    pub filter: ::core::option::Option<Filter>,
+    /// Search config
+    #[prost(message, optional, tag="7")]
+    pub params: ::core::option::Option<SearchParams>,
+    /// Max number of points to return, including the offset of the request
+    #[prost(uint64, tag="8")]
+    pub limit: u64,
+    /// Options for specifying which payload to include or not
+    #[prost(message, optional, tag="9")]
+    pub with_payload: ::core::option::Option<WithPayloadSelector>,
+    /// Options for specifying which vectors to include into response
+    #[prost(message, optional, tag="10")]
+    pub with_vectors: ::core::option::Option<WithVectorsSelector>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PointDigestsInternal {
+    #[prost(string, tag="1")]
+    pub collection_name: ::prost::alloc::string::String,
+    #[prost(uint32, tag="2")]
+    pub shard_id: u32,
+    /// Lowest id of the points to read, inclusive
+    #[prost(message, optional, tag="3")]
+    pub from: ::core::option::Option<PointId>,
+    /// Highest id of the points to read, exclusive
+    #[prost(message, optional, tag="4")]
+    pub to: ::core::option::Option<PointId>,
+    /// Max number of points to read
+    #[prost(uint64, optional, tag="5")]
+    pub limit: ::core::option::Option<u64>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PointDigest {
+    #[prost(message, optional, tag="1")]
+    pub id: ::core::option::Option<PointId>,
+    /// Digest of the vectors and the payload of the point
+    #[prost(uint64, tag="2")]
+    pub digest: u64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PointDigestsResponse {
+    /// Digests of the points, ordered by id
+    #[prost(message, repeated, tag="1")]
+    pub digests: ::prost::alloc::vec::Vec<PointDigest>,
+    /// Time spent to process
+    #[prost(double, tag="2")]
+    pub time: f64,
+}
 /// Generated client implementations.
 pub mod points_internal_client {
     #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
@@ -3312,7 +4615,26 @@ pub mod points_internal_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/qdrant.PointsInternal/DeletePayload",
+                "/qdrant.PointsInternal/DeletePayload",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn update_payload(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UpdatePayloadPointsInternal>,
+        ) -> Result<tonic::Response<super::PointsOperationResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/qdrant.PointsInternal/UpdatePayload",
             );
             self.inner.unary(request.into_request(), path, codec).await
         }
@@ -3449,6 +4771,25 @@ pub mod points_internal_client {
             );
             self.inner.unary(request.into_request(), path, codec).await
         }
+        pub async fn facet(
+            &mut self,
+            request: impl tonic::IntoRequest<super::FacetPointsInternal>,
+        ) -> Result<tonic::Response<super::FacetResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/qdrant.PointsInternal/Facet",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
         pub async fn recommend(
             &mut self,
             request: impl tonic::IntoRequest<super::RecommendPointsInternal>,
@@ -3487,6 +4828,63 @@ pub mod points_internal_client {
             );
             self.inner.unary(request.into_request(), path, codec).await
         }
+        pub async fn read_changes(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ReadChangesInternal>,
+        ) -> Result<tonic::Response<super::ReadChangesResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/qdrant.PointsInternal/ReadChanges",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn point_digests(
+            &mut self,
+            request: impl tonic::IntoRequest<super::PointDigestsInternal>,
+        ) -> Result<tonic::Response<super::PointDigestsResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/qdrant.PointsInternal/PointDigests",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn discover(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DiscoverPointsInternal>,
+        ) -> Result<tonic::Response<super::SearchResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/qdrant.PointsInternal/Discover",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -3516,6 +4914,10 @@ pub mod points_internal_server {
             &self,
             request: tonic::Request<super::DeletePayloadPointsInternal>,
         ) -> Result<tonic::Response<super::PointsOperationResponse>, tonic::Status>;
+        async fn update_payload(
+            &self,
+            request: tonic::Request<super::UpdatePayloadPointsInternal>,
+        ) -> Result<tonic::Response<super::PointsOperationResponse>, tonic::Status>;
         async fn clear_payload(
             &self,
             request: tonic::Request<super::ClearPayloadPointsInternal>,
@@ -3544,6 +4946,10 @@ pub mod points_internal_server {
             &self,
             request: tonic::Request<super::CountPointsInternal>,
         ) -> Result<tonic::Response<super::CountResponse>, tonic::Status>;
+        async fn facet(
+            &self,
+            request: tonic::Request<super::FacetPointsInternal>,
+        ) -> Result<tonic::Response<super::FacetResponse>, tonic::Status>;
         async fn recommend(
             &self,
             request: tonic::Request<super::RecommendPointsInternal>,
@@ -3552,12 +4958,24 @@ pub mod points_internal_server {
             &self,
             request: tonic::Request<super::GetPointsInternal>,
         ) -> Result<tonic::Response<super::GetResponse>, tonic::Status>;
+        async fn read_changes(
+            &self,
+            request: tonic::Request<super::ReadChangesInternal>,
+        ) -> Result<tonic::Response<super::ReadChangesResponse>, tonic::Status>;
+        async fn point_digests(
+            &self,
+            request: tonic::Request<super::PointDigestsInternal>,
+        ) -> Result<tonic::Response<super::PointDigestsResponse>, tonic::Status>;
+        async fn discover(
+            &self,
+            request: tonic::Request<super::DiscoverPointsInternal>,
+        ) -> Result<tonic::Response<super::SearchResponse>, tonic::Status>;
     }
     #[derive(Debug)]
     pub struct PointsInternalServer<T: PointsInternal> {
         inner: _Inner<T>,
-        accept_compression_encodings: (),
-        send_compression_encodings: (),
+        accept_compression_encodings: EnabledCompressionEncodings,
+        send_compression_encodings: EnabledCompressionEncodings,
     }
     struct _Inner<T>(Arc<T>);
     impl<T: PointsInternal> PointsInternalServer<T> {
@@ -3581,6 +4999,18 @@ pub mod points_internal_server {
         {
             InterceptedService::new(Self::new(inner), interceptor)
         }
+        /// Enable decompressing requests with `gzip`.
+        #[must_use]
+        pub fn accept_gzip(mut self) -> Self {
+            self.accept_compression_encodings.enable_gzip();
+            self
+        }
+        /// Compress responses with `gzip`, if the client supports it.
+        #[must_use]
+        pub fn send_gzip(mut self) -> Self {
+            self.send_compression_encodings.enable_gzip();
+            self
+        }
     }
     impl<T, B> tonic::codegen::Service<http::Request<B>> for PointsInternalServer<T>
     where
@@ -3792,6 +5222,46 @@ pub mod points_internal_server {
                     };
                     Box::pin(fut)
                 }
+                "/qdrant.PointsInternal/UpdatePayload" => {
+                    #[allow(non_camel_case_types)]
+                    struct UpdatePayloadSvc<T: PointsInternal>(pub Arc<T>);
+                    impl<
+                        T: PointsInternal,
+                    > tonic::server::UnaryService<super::UpdatePayloadPointsInternal>
+                    for UpdatePayloadSvc<T> {
+                        type Response = super::PointsOperationResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::UpdatePayloadPointsInternal>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).update_payload(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = UpdatePayloadSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/qdrant.PointsInternal/ClearPayload" => {
                     #[allow(non_camel_case_types)]
                     struct ClearPayloadSvc<T: PointsInternal>(pub Arc<T>);
@@ -4072,6 +5542,44 @@ pub mod points_internal_server {
                     };
                     Box::pin(fut)
                 }
+                "/qdrant.PointsInternal/Facet" => {
+                    #[allow(non_camel_case_types)]
+                    struct FacetSvc<T: PointsInternal>(pub Arc<T>);
+                    impl<
+                        T: PointsInternal,
+                    > tonic::server::UnaryService<super::FacetPointsInternal>
+                    for FacetSvc<T> {
+                        type Response = super::FacetResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::FacetPointsInternal>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).facet(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = FacetSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/qdrant.PointsInternal/Recommend" => {
                     #[allow(non_camel_case_types)]
                     struct RecommendSvc<T: PointsInternal>(pub Arc<T>);
@@ -4148,6 +5656,120 @@ pub mod points_internal_server {
                     };
                     Box::pin(fut)
                 }
+                "/qdrant.PointsInternal/ReadChanges" => {
+                    #[allow(non_camel_case_types)]
+                    struct ReadChangesSvc<T: PointsInternal>(pub Arc<T>);
+                    impl<
+                        T: PointsInternal,
+                    > tonic::server::UnaryService<super::ReadChangesInternal>
+                    for ReadChangesSvc<T> {
+                        type Response = super::ReadChangesResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ReadChangesInternal>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).read_changes(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ReadChangesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/qdrant.PointsInternal/PointDigests" => {
+                    #[allow(non_camel_case_types)]
+                    struct PointDigestsSvc<T: PointsInternal>(pub Arc<T>);
+                    impl<
+                        T: PointsInternal,
+                    > tonic::server::UnaryService<super::PointDigestsInternal>
+                    for PointDigestsSvc<T> {
+                        type Response = super::PointDigestsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::PointDigestsInternal>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).point_digests(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = PointDigestsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/qdrant.PointsInternal/Discover" => {
+                    #[allow(non_camel_case_types)]
+                    struct DiscoverSvc<T: PointsInternal>(pub Arc<T>);
+                    impl<
+                        T: PointsInternal,
+                    > tonic::server::UnaryService<super::DiscoverPointsInternal>
+                    for DiscoverSvc<T> {
+                        type Response = super::SearchResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DiscoverPointsInternal>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).discover(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = DiscoverSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         Ok(
@@ -4214,6 +5836,9 @@ pub struct AddPeerToKnownMessage {
     pub port: ::core::option::Option<u32>,
     #[prost(uint64, tag="3")]
     pub id: u64,
+    /// Peer joins as a learner, which is never promoted to a voter
+    #[prost(bool, tag="4")]
+    pub learner: bool,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct PeerId {
@@ -4412,8 +6037,8 @@ pub mod raft_server {
     #[derive(Debug)]
     pub struct RaftServer<T: Raft> {
         inner: _Inner<T>,
-        accept_compression_encodings: (),
-        send_compression_encodings: (),
+        accept_compression_encodings: EnabledCompressionEncodings,
+        send_compression_encodings: EnabledCompressionEncodings,
     }
     struct _Inner<T>(Arc<T>);
     impl<T: Raft> RaftServer<T> {
@@ -4437,6 +6062,18 @@ pub mod raft_server {
         {
             InterceptedService::new(Self::new(inner), interceptor)
         }
+        /// Enable decompressing requests with `gzip`.
+        #[must_use]
+        pub fn accept_gzip(mut self) -> Self {
+            self.accept_compression_encodings.enable_gzip();
+            self
+        }
+        /// Compress responses with `gzip`, if the client supports it.
+        #[must_use]
+        pub fn send_gzip(mut self) -> Self {
+            self.send_compression_encodings.enable_gzip();
+            self
+        }
     }
     impl<T, B> tonic::codegen::Service<http::Request<B>> for RaftServer<T>
     where
@@ -4656,6 +6293,9 @@ pub struct CreateSnapshotRequest {
     /// Name of the collection
     #[prost(string, tag="1")]
     pub collection_name: ::prost::alloc::string::String,
+    /// Create an incremental snapshot, which only includes the segments, changed since this snapshot
+    #[prost(string, optional, tag="2")]
+    pub base: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListSnapshotsRequest {
@@ -4872,8 +6512,8 @@ pub mod snapshots_server {
     #[derive(Debug)]
     pub struct SnapshotsServer<T: Snapshots> {
         inner: _Inner<T>,
-        accept_compression_encodings: (),
-        send_compression_encodings: (),
+        accept_compression_encodings: EnabledCompressionEncodings,
+        send_compression_encodings: EnabledCompressionEncodings,
     }
     struct _Inner<T>(Arc<T>);
     impl<T: Snapshots> SnapshotsServer<T> {
@@ -4897,6 +6537,18 @@ pub mod snapshots_server {
         {
             InterceptedService::new(Self::new(inner), interceptor)
         }
+        /// Enable decompressing requests with `gzip`.
+        #[must_use]
+        pub fn accept_gzip(mut self) -> Self {
+            self.accept_compression_encodings.enable_gzip();
+            self
+        }
+        /// Compress responses with `gzip`, if the client supports it.
+        #[must_use]
+        pub fn send_gzip(mut self) -> Self {
+            self.send_compression_encodings.enable_gzip();
+            self
+        }
     }
     impl<T, B> tonic::codegen::Service<http::Request<B>> for SnapshotsServer<T>
     where
@@ -5217,8 +6869,8 @@ pub mod qdrant_server {
     #[derive(Debug)]
     pub struct QdrantServer<T: Qdrant> {
         inner: _Inner<T>,
-        accept_compression_encodings: (),
-        send_compression_encodings: (),
+        accept_compression_encodings: EnabledCompressionEncodings,
+        send_compression_encodings: EnabledCompressionEncodings,
     }
     struct _Inner<T>(Arc<T>);
     impl<T: Qdrant> QdrantServer<T> {
@@ -5242,6 +6894,18 @@ pub mod qdrant_server {
         {
             InterceptedService::new(Self::new(inner), interceptor)
         }
+        /// Enable decompressing requests with `gzip`.
+        #[must_use]
+        pub fn accept_gzip(mut self) -> Self {
+            self.accept_compression_encodings.enable_gzip();
+            self
+        }
+        /// Compress responses with `gzip`, if the client supports it.
+        #[must_use]
+        pub fn send_gzip(mut self) -> Self {
+            self.send_compression_encodings.enable_gzip();
+            self
+        }
     }
     impl<T, B> tonic::codegen::Service<http::Request<B>> for QdrantServer<T>
     where
@@ -9,6 +9,7 @@ use tokio::select;
 use tonic::transport::{Channel, Error as TonicError, Uri};
 use tonic::{Code, Status};
 
+use crate::grpc::peer_channel::{GzipPeers, MessageCipher, PeerChannel, PeerCredentials};
 use crate::grpc::qdrant::qdrant_client::QdrantClient;
 use crate::grpc::qdrant::HealthCheckRequest;
 
@@ -75,6 +76,15 @@ pub struct TransportChannelPool {
     pool_size: NonZeroUsize,
     grpc_timeout: Duration,
     connection_timeout: Duration,
+    /// Compress requests sent through the pool with `gzip`.
+    /// Requests are only compressed for peers, which advertised support of it.
+    compression: bool,
+    /// Peers, which accept compressed requests
+    gzip_peers: GzipPeers,
+    /// Encrypt messages sent through the pool
+    cipher: Option<MessageCipher>,
+    /// Identity of this peer, attached to the requests sent through the pool
+    credentials: Option<PeerCredentials>,
 }
 
 impl Default for TransportChannelPool {
@@ -84,6 +94,10 @@ impl Default for TransportChannelPool {
             pool_size: NonZeroUsize::new(DEFAULT_POOL_SIZE).unwrap(),
             grpc_timeout: DEFAULT_GRPC_TIMEOUT,
             connection_timeout: DEFAULT_CONNECT_TIMEOUT,
+            compression: false,
+            gzip_peers: Default::default(),
+            cipher: None,
+            credentials: None,
         }
     }
 }
@@ -95,9 +109,62 @@ impl TransportChannelPool {
             grpc_timeout: p2p_grpc_timeout,
             connection_timeout,
             pool_size: NonZeroUsize::new(pool_size).unwrap(),
+            compression: false,
+            gzip_peers: Default::default(),
+            cipher: None,
+            credentials: None,
         }
     }
 
+    pub fn with_compression(self, compression: bool) -> Self {
+        Self {
+            compression,
+            ..self
+        }
+    }
+
+    pub fn with_encryption(self, cipher: Option<MessageCipher>) -> Self {
+        Self { cipher, ..self }
+    }
+
+    pub fn cipher(&self) -> Option<MessageCipher> {
+        self.cipher.clone()
+    }
+
+    pub fn with_credentials(self, credentials: PeerCredentials) -> Self {
+        Self {
+            credentials: Some(credentials),
+            ..self
+        }
+    }
+
+    pub fn credentials(&self) -> Option<&PeerCredentials> {
+        self.credentials.as_ref()
+    }
+
+    fn peer_channel(&self, channel: Channel, uri: &Uri) -> PeerChannel {
+        PeerChannel::new(
+            channel,
+            uri.clone(),
+            self.cipher.clone(),
+            self.credentials.clone(),
+            self.compression,
+            self.gzip_peers.clone(),
+        )
+    }
+
+    /// Make a dedicated channel to the peer, which is not stored in the pool,
+    /// but uses the same transport settings.
+    pub async fn make_peer_channel(
+        &self,
+        grpc_timeout: Duration,
+        connection_timeout: Duration,
+        uri: Uri,
+    ) -> Result<PeerChannel, TonicError> {
+        let channel = Self::make_channel(grpc_timeout, connection_timeout, uri.clone()).await?;
+        Ok(self.peer_channel(channel, &uri))
+    }
+
     pub async fn make_channel(
         grpc_timeout: Duration,
         connection_timeout: Duration,
@@ -189,12 +256,12 @@ impl TransportChannelPool {
     pub async fn with_channel<T, O: Future<Output = Result<T, Status>>>(
         &self,
         uri: &Uri,
-        f: impl Fn(Channel) -> O,
+        f: impl Fn(PeerChannel) -> O,
     ) -> Result<T, RequestError<Status>> {
         let channel = self.get_or_create_pooled_channel(uri).await?;
 
         let result: Result<T, Status> = select! {
-            res = f(channel) => {
+            res = f(self.peer_channel(channel, uri)) => {
                 res
             }
             res = self.check_connectability(uri) => {
@@ -213,7 +280,9 @@ impl TransportChannelPool {
                     if channel_uptime > CHANNEL_TTL {
                         self.drop_pool(uri).await;
                         let channel = self.get_or_create_pooled_channel(uri).await?;
-                        f(channel).await.map_err(RequestError::FromClosure)
+                        f(self.peer_channel(channel, uri))
+                            .await
+                            .map_err(RequestError::FromClosure)
                     } else {
                         Err(err).map_err(RequestError::FromClosure)
                     }
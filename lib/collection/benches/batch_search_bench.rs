@@ -5,6 +5,7 @@ mod prof;
 use std::num::{NonZeroU32, NonZeroU64};
 use std::sync::Arc;
 
+use collection::common::optimization_scheduler::OptimizationScheduler;
 use collection::config::{CollectionConfig, CollectionParams, VectorParams, WalConfig};
 use collection::operations::point_ops::{PointInsertOperations, PointOperations, PointStruct};
 use collection::operations::types::{SearchRequest, SearchRequestBatch};
@@ -55,18 +56,26 @@ fn batch_search_bench(c: &mut Criterion) {
     let wal_config = WalConfig {
         wal_capacity_mb: 1,
         wal_segments_ahead: 0,
+        encrypted: false,
+        operation_id_window_sec: 600,
     };
 
     let collection_params = CollectionParams {
         vectors: VectorParams {
             size: NonZeroU64::new(100).unwrap(),
             distance: Distance::Dot,
+            hnsw_config: None,
+            quantization_config: None,
+            on_disk: None,
+            datatype: None,
+            multivector_config: None,
         }
         .into(),
         shard_number: NonZeroU32::new(1).expect("Shard number can not be zero"),
         replication_factor: NonZeroU32::new(1).unwrap(),
         write_consistency_factor: NonZeroU32::new(1).unwrap(),
         on_disk_payload: false,
+        default_vector: None,
     };
 
     let collection_config = CollectionConfig {
@@ -83,6 +92,9 @@ fn batch_search_bench(c: &mut Criterion) {
         },
         wal_config,
         hnsw_config: Default::default(),
+        payload_field_types: None,
+        auto_payload_index: None,
+        ttl: None,
     };
 
     let shared_config = Arc::new(RwLock::new(collection_config));
@@ -93,12 +105,17 @@ fn batch_search_bench(c: &mut Criterion) {
             "test_collection".to_string(),
             storage_dir.path(),
             shared_config,
+            OptimizationScheduler::new(handle.clone(), handle.clone(), 0),
+            None,
+            Arc::new(|_shard_id, _event| {}),
         ))
         .unwrap();
 
     let rnd_batch = create_rnd_batch();
 
-    handle.block_on(shard.update(rnd_batch, true)).unwrap();
+    handle
+        .block_on(shard.update(rnd_batch.into(), true))
+        .unwrap();
 
     let mut group = c.benchmark_group("batch-search-bench");
 
@@ -145,10 +162,11 @@ fn batch_search_bench(c: &mut Criterion) {
                                     searches: vec![search_query],
                                 }),
                                 search_runtime_handle,
+                                None,
                             )
                             .await
                             .unwrap();
-                        assert!(!result.is_empty());
+                        assert!(!result.results.is_empty());
                     }
                 });
             })
@@ -176,10 +194,10 @@ fn batch_search_bench(c: &mut Criterion) {
 
                     let search_query = SearchRequestBatch { searches };
                     let result = shard
-                        .search(Arc::new(search_query), search_runtime_handle)
+                        .search(Arc::new(search_query), search_runtime_handle, None)
                         .await
                         .unwrap();
-                    assert!(!result.is_empty());
+                    assert!(!result.results.is_empty());
                 });
             })
         });
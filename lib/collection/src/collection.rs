@@ -5,46 +5,76 @@ use std::future::Future;
 use std::num::NonZeroU32;
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use futures::future::{join_all, try_join_all};
+use futures::{StreamExt, TryStreamExt};
 use itertools::Itertools;
+use segment::common::encryption::StorageCipher;
 use segment::common::version::StorageVersion;
+use segment::data_types::discovery::{ContextPair, DiscoveryQuery};
+use segment::data_types::facets::merge_hits;
 use segment::data_types::vectors::{NamedVector, VectorElementType, DEFAULT_VECTOR_NAME};
 use segment::spaces::tools::{peek_top_largest_iterable, peek_top_smallest_iterable};
 use segment::types::{
-    Condition, ExtendedPointId, Filter, HasIdCondition, Order, ScoredPoint, WithPayload,
-    WithPayloadInterface, WithVector,
+    BatchSearchResult, Condition, ExtendedPointId, Filter, HasIdCondition, Order, PayloadKeyType,
+    PointIdType, ScoredPoint, SearchExplanation, SeqNumberType, ShardSearchExplanation,
+    VectorStorageDatatype, WithPayload, WithPayloadInterface, WithVector,
 };
 use semver::Version;
 use tar::Builder as TarBuilder;
-use tokio::fs::{copy, create_dir_all, remove_dir_all, remove_file, rename};
+use tokio::fs::{create_dir_all, remove_dir_all, remove_file, rename};
 use tokio::runtime::Handle;
 use tokio::sync::{Mutex, RwLock};
 
 use crate::collection_state::{ShardInfo, State};
+use crate::common::optimization_scheduler::OptimizationScheduler;
+use crate::common::stoppable_task_async::{spawn_async_stoppable, StoppableAsyncTaskHandle};
 use crate::config::CollectionConfig;
 use crate::hash_ring::HashRing;
-use crate::operations::config_diff::{CollectionParamsDiff, DiffConfig, OptimizersConfigDiff};
+use crate::jobs::export::{CreateExport, ExportDescription, ExportJobs};
+use crate::jobs::JobInfo;
+use crate::operations::auto_payload_index::{
+    infer_operation_payload_fields, select_auto_indexes, AutoPayloadIndexConfig,
+};
+use crate::operations::changes::PointsChanges;
+use crate::operations::config_diff::{
+    CollectionParamsDiff, DiffConfig, HnswConfigDiff, OptimizersConfigDiff, VectorsConfigDiff,
+};
+use crate::operations::payload_types::{
+    check_operation_payload_types, check_payload_types, PayloadFieldTypes,
+};
+use crate::operations::point_ops::{PointInsertOperations, PointStruct, WriteOrdering};
 use crate::operations::snapshot_ops::{
-    get_snapshot_description, list_snapshots_in_directory, SnapshotDescription,
+    archive_entry_path, directory_checksums, file_checksum, get_snapshot_description,
+    list_snapshots_in_directory, SnapshotDescription, SnapshotManifest, SNAPSHOT_MANIFEST_FILE,
 };
+use crate::operations::ttl::TtlConfig;
 use crate::operations::types::{
-    CollectionClusterInfo, CollectionError, CollectionInfo, CollectionResult, CountRequest,
-    CountResult, LocalShardInfo, PointRequest, RecommendRequest, RecommendRequestBatch, Record,
-    RemoteShardInfo, ScrollRequest, ScrollResult, SearchRequest, SearchRequestBatch,
-    ShardTransferInfo, UpdateResult, UsingVector,
+    CollectionClusterInfo, CollectionError, CollectionInfo, CollectionInitStatus, CollectionResult,
+    CollectionStatus, CollectionUsage, CollectionVerificationReport, CountRequest, CountResult,
+    DiscoverExample, DiscoverRequest, FacetRequest, FacetResponse, IndexRebuildProgress,
+    LocalShardInfo, PointRequest, RecommendRequest, RecommendRequestBatch, Record, RemoteShardInfo,
+    ReplicaOperationId, ReplicaSyncReport, ReshardingPreview, ReshardingPreviewRequest, Sample,
+    SampleRequest, ScrollRequest, ScrollResult, SearchRequest, SearchRequestBatch,
+    ShardDiscoverRequest, ShardTransferInfo, UpdateResult, UpdateStatus, UsingVector,
+    MAX_FACET_LIMIT,
+};
+use crate::operations::{
+    CollectionUpdateOperations, CreateIndex, FieldIndexOperations, OperationWithId, Validate,
 };
-use crate::operations::{CollectionUpdateOperations, Validate};
 use crate::optimizers_builder::OptimizersConfig;
 use crate::shards::channel_service::ChannelService;
 use crate::shards::collection_shard_distribution::CollectionShardDistribution;
-use crate::shards::local_shard::LocalShard;
+use crate::shards::local_shard::{LocalShard, OnShardEvent};
 use crate::shards::remote_shard::RemoteShard;
 use crate::shards::replica_set::{
     Change, OnPeerFailure, ReplicaState, ShardReplicaSet as ReplicaSetShard,
 }; // TODO rename ReplicaShard to ReplicaSetShard
+use crate::shards::replica_sync::PointDigest;
+use crate::shards::resharding_preview::{point_vectors_size_bytes, ReshardingPreviewBuilder};
 use crate::shards::shard::{PeerId, ShardId};
 use crate::shards::shard_config::{self, ShardConfig};
 use crate::shards::shard_holder::{LockedShardHolder, ShardHolder};
@@ -59,7 +89,16 @@ use crate::telemetry::CollectionTelemetry;
 
 pub type RequestShardTransfer = Arc<dyn Fn(ShardTransfer) + Send + Sync>;
 
-struct CollectionVersion;
+/// Number of points per shard, used to estimate resharding
+const RESHARDING_PREVIEW_SAMPLE_SIZE: usize = 1000;
+
+/// Assumed size of a single point payload, used to estimate memory of responses
+const ESTIMATED_PAYLOAD_SIZE_BYTES: usize = 1024;
+
+/// How often the TTL expirer checks, whether it is asked to stop
+const TTL_EXPIRER_STOP_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+pub struct CollectionVersion;
 
 impl StorageVersion for CollectionVersion {
     fn current() -> String {
@@ -83,6 +122,14 @@ pub struct Collection {
     #[allow(dead_code)] //Might be useful in case of repartition implementation
     notify_peer_failure_cb: OnPeerFailure,
     init_time: Duration,
+    /// Progress of copying points from another collection into the local shards, if any
+    init_status: parking_lot::RwLock<Option<CollectionInitStatus>>,
+    /// Background exports of the collection points into chunk files
+    exports: Mutex<ExportJobs>,
+    /// Payload fields, already considered for the automatic indexing
+    seen_payload_fields: parking_lot::Mutex<HashSet<PayloadKeyType>>,
+    /// Background deletion of the expired points, running if TTL is configured
+    ttl_expirer: parking_lot::Mutex<Option<StoppableAsyncTaskHandle<()>>>,
 }
 
 impl Collection {
@@ -90,6 +137,15 @@ impl Collection {
         self.id.clone()
     }
 
+    /// Shards of the collection, which can be used without borrowing the collection itself
+    pub fn shards_holder(&self) -> Arc<LockedShardHolder> {
+        self.shards_holder.clone()
+    }
+
+    pub fn set_init_status(&self, init_status: Option<CollectionInitStatus>) {
+        *self.init_status.write() = init_status;
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub async fn new(
         name: CollectionId,
@@ -99,8 +155,11 @@ impl Collection {
         config: &CollectionConfig,
         shard_distribution: CollectionShardDistribution,
         channel_service: ChannelService,
+        optimization_scheduler: OptimizationScheduler,
+        storage_cipher: Option<StorageCipher>,
         on_replica_failure: replica_set::OnPeerFailure,
         request_shard_transfer: RequestShardTransfer,
+        on_shard_event: OnShardEvent,
     ) -> Result<Self, CollectionError> {
         let start_time = std::time::Instant::now();
 
@@ -121,6 +180,9 @@ impl Collection {
                 path,
                 shared_config.clone(),
                 channel_service.clone(),
+                optimization_scheduler.clone(),
+                storage_cipher.clone(),
+                on_shard_event.clone(),
             )
             .await;
 
@@ -140,7 +202,7 @@ impl Collection {
         CollectionVersion::save(path)?;
         config.save(path)?;
 
-        Ok(Self {
+        let collection = Self {
             id: name.clone(),
             shards_holder: locked_shard_holder,
             config: shared_config,
@@ -153,7 +215,13 @@ impl Collection {
             request_shard_transfer_cb: request_shard_transfer.clone(),
             notify_peer_failure_cb: on_replica_failure.clone(),
             init_time: start_time.elapsed(),
-        })
+            init_status: Default::default(),
+            exports: Mutex::new(ExportJobs::new(path)),
+            seen_payload_fields: Default::default(),
+            ttl_expirer: Default::default(),
+        };
+        collection.start_ttl_expirer(config.ttl.clone());
+        Ok(collection)
     }
 
     /// Check if stored version have consequent version.
@@ -179,14 +247,18 @@ impl Collection {
         true
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn load(
         collection_id: CollectionId,
         this_peer_id: PeerId,
         path: &Path,
         snapshots_path: &Path,
         channel_service: ChannelService,
+        optimization_scheduler: OptimizationScheduler,
+        storage_cipher: Option<StorageCipher>,
         on_replica_failure: replica_set::OnPeerFailure,
         request_shard_transfer: RequestShardTransfer,
+        on_shard_event: OnShardEvent,
     ) -> Self {
         let start_time = std::time::Instant::now();
         let stored_version = CollectionVersion::load(path)
@@ -234,12 +306,20 @@ impl Collection {
                 channel_service.clone(),
                 on_replica_failure.clone(),
                 this_peer_id,
+                optimization_scheduler,
+                storage_cipher,
+                on_shard_event,
             )
             .await;
 
         let locked_shard_holder = Arc::new(LockedShardHolder::new(shard_holder));
 
-        Self {
+        let exports = ExportJobs::load(path, locked_shard_holder.clone()).unwrap_or_else(|err| {
+            log::error!("Can't load exports of collection {collection_id}: {err}");
+            ExportJobs::new(path)
+        });
+
+        let collection = Self {
             id: collection_id.clone(),
             shards_holder: locked_shard_holder,
             config: shared_config,
@@ -252,7 +332,13 @@ impl Collection {
             request_shard_transfer_cb: request_shard_transfer.clone(),
             notify_peer_failure_cb: on_replica_failure,
             init_time: start_time.elapsed(),
-        }
+            init_status: Default::default(),
+            exports: Mutex::new(exports),
+            seen_payload_fields: Default::default(),
+            ttl_expirer: Default::default(),
+        };
+        collection.start_ttl_expirer(config.ttl);
+        collection
     }
 
     /// Return a list of local shards, present on this peer
@@ -332,6 +418,8 @@ impl Collection {
                     from: transfer_from,
                     to: self.this_peer_id,
                     sync: true,
+                    method: None,
+                    max_points_per_second: None,
                 })
             } else {
                 log::warn!("No alive replicas to recover shard {shard_id}");
@@ -565,6 +653,9 @@ impl Collection {
                 self.name(),
                 &replica_set.shard_path,
                 self.config.clone(),
+                replica_set.optimization_scheduler.clone(),
+                replica_set.storage_cipher.clone(),
+                replica_set.on_shard_event.clone(),
             )
             .await?;
 
@@ -585,17 +676,27 @@ impl Collection {
     /// Handle collection updates from peers.
     ///
     /// Shard transfer aware.
+    ///
+    /// Operations with `weak` ordering are replicated by the sending peer, so they are applied to
+    /// the local replica only. Operations with stronger ordering are forwarded to this peer as the
+    /// leader of the replica set, so they are applied to all replicas of the shard.
     pub async fn update_from_peer(
         &self,
-        operation: CollectionUpdateOperations,
+        operation: OperationWithId,
         shard_selection: ShardId,
         wait: bool,
+        ordering: WriteOrdering,
     ) -> CollectionResult<UpdateResult> {
         let shard_holder_guard = self.shards_holder.read().await;
 
         let res = match shard_holder_guard.get_shard(&shard_selection) {
             None => None,
-            Some(target_shard) => target_shard.update_local(operation.clone(), wait).await?,
+            Some(target_shard) => match ordering {
+                WriteOrdering::Weak => target_shard.update_local(operation.clone(), wait).await?,
+                WriteOrdering::Medium | WriteOrdering::Strong => {
+                    Some(target_shard.update(operation, wait).await?)
+                }
+            },
         };
 
         if let Some(res) = res {
@@ -608,13 +709,180 @@ impl Collection {
         }
     }
 
+    /// Apply update only to the local replicas of the affected shards.
+    ///
+    /// Used to populate the collection with data, which is delivered to each replica independently.
+    pub async fn update_local_shards(
+        &self,
+        operation: CollectionUpdateOperations,
+        wait: bool,
+    ) -> CollectionResult<()> {
+        operation.validate()?;
+
+        let shards_holder = self.shards_holder.read().await;
+        let shard_requests = shards_holder
+            .split_by_shard(operation)
+            .into_iter()
+            .map(|(replica_set, operation)| replica_set.update_local(operation.into(), wait));
+        try_join_all(shard_requests).await?;
+        Ok(())
+    }
+
+    /// Ids of the shards, which would be affected by the update operation from client
+    pub async fn affected_shards(&self, operation: &CollectionUpdateOperations) -> Vec<ShardId> {
+        self.shards_holder
+            .read()
+            .await
+            .affected_shards(operation.clone())
+    }
+
     pub async fn update_from_client(
         &self,
         operation: CollectionUpdateOperations,
         wait: bool,
+        ordering: WriteOrdering,
+    ) -> CollectionResult<UpdateResult> {
+        self.update_from_client_with_id(operation, None, wait, ordering)
+            .await
+    }
+
+    /// Update from client with the client-supplied `operation_id`.
+    /// Retries of the operation with the same id are applied only once by each shard replica,
+    /// see [`crate::config::WalConfig::operation_id_window_sec`].
+    pub async fn update_from_client_with_id(
+        &self,
+        operation: CollectionUpdateOperations,
+        operation_id: Option<String>,
+        wait: bool,
+        ordering: WriteOrdering,
     ) -> CollectionResult<UpdateResult> {
         operation.validate()?;
+        if let Some(field_types) = &self.config.read().await.payload_field_types {
+            check_operation_payload_types(field_types, &operation)?;
+        }
+
+        if let CollectionUpdateOperations::FieldIndexOperation(FieldIndexOperations::DeleteIndex(
+            field_name,
+        )) = &operation
+        {
+            // Field could be indexed automatically again
+            self.seen_payload_fields.lock().remove(field_name);
+        }
+        let auto_payload_index = self.config.read().await.auto_payload_index;
+        if let Some(auto_payload_index) = auto_payload_index {
+            self.create_auto_payload_indexes(&operation, &auto_payload_index, wait, ordering)
+                .await;
+        }
+
+        let operation = OperationWithId::new(operation, operation_id);
+        self.update_shards(operation, wait, ordering).await
+    }
+
+    /// Load points into empty shards by building indexed segments from them directly, instead of
+    /// the appendable segments and the following optimizations, see [`LocalShard::bulk_build`].
+    /// Intended for the initial load of a large collection, only shards with a single local
+    /// replica could be loaded this way.
+    pub async fn bulk_upsert(
+        &self,
+        points: PointInsertOperations,
+    ) -> CollectionResult<UpdateResult> {
+        points.validate()?;
+        let points: Vec<PointStruct> = match points {
+            PointInsertOperations::PointsBatch(batch) => batch.into(),
+            PointInsertOperations::PointsList(points) => points,
+        };
+        if let Some(field_types) = &self.config.read().await.payload_field_types {
+            points
+                .iter()
+                .filter_map(|point| point.payload.as_ref())
+                .try_for_each(|payload| check_payload_types(field_types, payload))?;
+        }
+
+        let shards_holder = self.shards_holder.read().await;
+        let shard_builds = shards_holder.split_by_shard(points).into_iter().map(
+            |(replica_set, points)| async move {
+                let operation_id = replica_set.bulk_build_local(points).await?;
+                CollectionResult::Ok(ReplicaOperationId {
+                    shard_id: replica_set.shard_id,
+                    peer_id: replica_set.this_peer_id(),
+                    operation_id,
+                })
+            },
+        );
+        let replica_operation_ids = try_join_all(shard_builds).await?;
+
+        Ok(UpdateResult {
+            operation_id: replica_operation_ids
+                .iter()
+                .map(|replica_operation_id| replica_operation_id.operation_id)
+                .max()
+                .unwrap_or_default(),
+            status: UpdateStatus::Completed,
+            replica_operation_ids,
+        })
+    }
+
+    /// Create payload indexes for the fields of the operation, which are seen for the first time.
+    /// Failures are only logged, as the operation itself is valid without the indexes.
+    async fn create_auto_payload_indexes(
+        &self,
+        operation: &CollectionUpdateOperations,
+        config: &AutoPayloadIndexConfig,
+        wait: bool,
+        ordering: WriteOrdering,
+    ) {
+        let mut fields = infer_operation_payload_fields(operation);
+        {
+            let mut seen_payload_fields = self.seen_payload_fields.lock();
+            fields.retain(|key, _| !seen_payload_fields.contains(key));
+            seen_payload_fields.extend(fields.keys().cloned());
+        }
+        if fields.is_empty() {
+            return;
+        }
+
+        let payload_schema = match self.info(None).await {
+            Ok(info) => info.payload_schema,
+            Err(err) => {
+                log::warn!(
+                    "Can't check payload indexes of collection {}: {err}",
+                    self.id
+                );
+                // Try again with the next operation
+                let mut seen_payload_fields = self.seen_payload_fields.lock();
+                fields.keys().for_each(|key| {
+                    seen_payload_fields.remove(key);
+                });
+                return;
+            }
+        };
+
+        for (field_name, field_type) in select_auto_indexes(fields, &payload_schema, config) {
+            log::info!(
+                "Creating {field_type:?} index for payload field {field_name} of collection {}",
+                self.id
+            );
+            let create_index = CollectionUpdateOperations::FieldIndexOperation(
+                FieldIndexOperations::CreateIndex(CreateIndex {
+                    field_name: field_name.clone(),
+                    field_schema: Some(field_type.into()),
+                }),
+            );
+            if let Err(err) = self
+                .update_shards(create_index.into(), wait, ordering)
+                .await
+            {
+                log::warn!("Failed to create index for payload field {field_name}: {err}");
+            }
+        }
+    }
 
+    async fn update_shards(
+        &self,
+        operation: OperationWithId,
+        wait: bool,
+        ordering: WriteOrdering,
+    ) -> CollectionResult<UpdateResult> {
         let mut results = {
             let shards_holder = self.shards_holder.read().await;
             let shard_to_op = shards_holder.split_by_shard(operation);
@@ -627,7 +895,9 @@ impl Collection {
 
             let shard_requests = shard_to_op
                 .into_iter()
-                .map(move |(replica_set, operation)| replica_set.update(operation, wait));
+                .map(move |(replica_set, operation)| {
+                    replica_set.update_with_consistency(operation, wait, ordering)
+                });
             join_all(shard_requests).await
         };
 
@@ -661,15 +931,26 @@ impl Collection {
             }
         } else {
             // At least one result is always present.
-            results.pop().unwrap()
+            let mut res = results.pop().unwrap()?;
+            // Report the numbers of the operation in the WAL of the replicas of all shards
+            for shard_res in results {
+                res.replica_operation_ids
+                    .extend(shard_res?.replica_operation_ids);
+            }
+            Ok(res)
         }
     }
 
+    /// Recommend points by the positive and negative examples.
+    ///
+    /// `lookup_records` - records of the examples, retrieved from other collections by the requests
+    /// with `lookup_from`, by the name of the lookup collection
     pub async fn recommend_by(
         &self,
         request: RecommendRequest,
         search_runtime_handle: &Handle,
         shard_selection: Option<ShardId>,
+        lookup_records: &HashMap<String, Vec<Record>>,
     ) -> CollectionResult<Vec<ScoredPoint>> {
         if request.limit == 0 {
             return Ok(vec![]);
@@ -679,7 +960,12 @@ impl Collection {
             searches: vec![request],
         };
         let results = self
-            .recommend_batch_by(request_batch, search_runtime_handle, shard_selection)
+            .recommend_batch_by(
+                request_batch,
+                search_runtime_handle,
+                shard_selection,
+                lookup_records,
+            )
             .await?;
         Ok(results.into_iter().next().unwrap())
     }
@@ -689,12 +975,13 @@ impl Collection {
         request_batch: RecommendRequestBatch,
         search_runtime_handle: &Handle,
         shard_selection: Option<ShardId>,
+        lookup_records: &HashMap<String, Vec<Record>>,
     ) -> CollectionResult<Vec<Vec<ScoredPoint>>> {
         // shortcuts batch if all requests with limit=0
         if request_batch.searches.iter().all(|s| s.limit == 0) {
             return Ok(vec![]);
         }
-        // pack all reference vector ids
+        // pack all reference vector ids, which are stored in this collection
         let mut all_reference_vectors_ids = HashSet::new();
         for request in &request_batch.searches {
             if request.positive.is_empty() {
@@ -702,14 +989,19 @@ impl Collection {
                     description: "At least one positive vector ID required".to_owned(),
                 });
             }
+            if request.lookup_from.is_some() {
+                continue;
+            }
             for point_id in request.positive.iter().chain(&request.negative) {
                 all_reference_vectors_ids.insert(*point_id);
             }
         }
 
         // batch vector retrieval
-        let all_vectors = self
-            .retrieve(
+        let all_vectors = if all_reference_vectors_ids.is_empty() {
+            vec![]
+        } else {
+            self.retrieve(
                 PointRequest {
                     ids: all_reference_vectors_ids.into_iter().collect(),
                     with_payload: Some(WithPayloadInterface::Bool(true)),
@@ -717,28 +1009,52 @@ impl Collection {
                 },
                 shard_selection,
             )
-            .await?;
+            .await?
+        };
 
         let mut searches = Vec::with_capacity(request_batch.searches.len());
+        let collection_params = self.config.read().await.params.clone();
 
         for request in request_batch.searches {
-            let vector_name = match request.using {
-                None => DEFAULT_VECTOR_NAME.to_owned(),
-                Some(UsingVector::Name(name)) => name,
+            let vector_name = match &request.using {
+                None => DEFAULT_VECTOR_NAME,
+                Some(UsingVector::Name(name)) => name.as_str(),
+            };
+            let vector_name = collection_params
+                .resolve_vector_name(vector_name)?
+                .to_owned();
+
+            // Examples may be stored in another collection, under another vector name
+            let (reference_records, reference_vector_name) = match &request.lookup_from {
+                None => (&all_vectors, vector_name.as_str()),
+                Some(lookup_location) => {
+                    let lookup_collection = &lookup_location.collection;
+                    let records = lookup_records.get(lookup_collection).ok_or_else(|| {
+                        CollectionError::BadRequest {
+                            description: format!(
+                                "Vectors of {lookup_collection} are not looked up"
+                            ),
+                        }
+                    })?;
+                    let name = lookup_location
+                        .vector
+                        .as_deref()
+                        .unwrap_or(DEFAULT_VECTOR_NAME);
+                    (records, name)
+                }
             };
 
-            //let rec_vectors = rec.get
             let mut all_vectors_map = HashMap::new();
 
-            for rec in all_vectors.iter() {
-                let vector = rec.get_vector_by_name(&vector_name);
+            for rec in reference_records.iter() {
+                let vector = rec.get_vector_by_name(reference_vector_name);
                 if let Some(vector) = vector {
                     all_vectors_map.insert(rec.id, vector);
                 } else {
                     return Err(CollectionError::BadRequest {
                         description: format!(
                             "Vector '{}' not found, expected one of {:?}",
-                            vector_name,
+                            reference_vector_name,
                             rec.vector_names()
                         ),
                     });
@@ -793,13 +1109,18 @@ impl Collection {
                 .into(),
                 filter: Some(Filter {
                     should: None,
+                    min_should: None,
                     must: request
                         .filter
                         .clone()
                         .map(|filter| vec![Condition::Filter(filter)]),
-                    must_not: Some(vec![Condition::HasId(HasIdCondition {
-                        has_id: reference_vectors_ids.iter().cloned().collect(),
-                    })]),
+                    // Examples from another collection are not excluded:
+                    // same IDs refer to unrelated points there
+                    must_not: request.lookup_from.is_none().then(|| {
+                        vec![Condition::HasId(HasIdCondition {
+                            has_id: reference_vectors_ids.iter().cloned().collect(),
+                        })]
+                    }),
                 }),
                 with_payload: request.with_payload.clone(),
                 with_vector: request.with_vector,
@@ -813,20 +1134,182 @@ impl Collection {
 
         let search_batch_request = SearchRequestBatch { searches };
 
-        self.search_batch(search_batch_request, search_runtime_handle, shard_selection)
-            .await
+        let result = self
+            .search_batch(search_batch_request, search_runtime_handle, shard_selection)
+            .await?;
+        Ok(result.results)
+    }
+
+    /// Discover points by the target and the context pairs.
+    ///
+    /// Examples are resolved into vectors here, the segments rank points by the discovery score
+    /// in both plain and HNSW search.
+    pub async fn discover_by(
+        &self,
+        request: DiscoverRequest,
+        search_runtime_handle: &Handle,
+        shard_selection: Option<ShardId>,
+    ) -> CollectionResult<Vec<ScoredPoint>> {
+        if request.limit == 0 {
+            return Ok(vec![]);
+        }
+        let collection_params = self.config.read().await.params.clone();
+        let vector_name = match &request.using {
+            None => DEFAULT_VECTOR_NAME,
+            Some(UsingVector::Name(name)) => name.as_str(),
+        };
+        let vector_name = collection_params
+            .resolve_vector_name(vector_name)?
+            .to_owned();
+        let vector_params = collection_params.get_vector_params(&vector_name)?;
+        if vector_params.multivector_config.is_some() {
+            return Err(CollectionError::bad_request(format!(
+                "Discovery is not supported for multivector `{vector_name}`"
+            )));
+        }
+        let distance = vector_params.distance;
+
+        let example_ids: HashSet<PointIdType> = request
+            .examples()
+            .filter_map(|example| match example {
+                DiscoverExample::PointId(point_id) => Some(*point_id),
+                DiscoverExample::Vector(_) => None,
+            })
+            .collect();
+        let example_records = if example_ids.is_empty() {
+            vec![]
+        } else {
+            self.retrieve(
+                PointRequest {
+                    ids: example_ids.iter().cloned().collect(),
+                    with_payload: Some(WithPayloadInterface::Bool(false)),
+                    with_vector: WithVector::Selector(vec![vector_name.clone()]),
+                },
+                shard_selection,
+            )
+            .await?
+        };
+        let example_vectors: HashMap<_, _> = example_records
+            .iter()
+            .filter_map(|record| Some((record.id, record.get_vector_by_name(&vector_name)?)))
+            .collect();
+
+        let resolve_example = |example: &DiscoverExample| -> CollectionResult<Vec<_>> {
+            let vector = match example {
+                DiscoverExample::PointId(point_id) => example_vectors
+                    .get(point_id)
+                    .map(|vector| vector.to_vec())
+                    .ok_or(CollectionError::PointNotFound {
+                        missed_point_id: *point_id,
+                    })?,
+                DiscoverExample::Vector(vector) => vector.clone(),
+            };
+            if vector.len() as u64 != vector_params.size.get() {
+                return Err(CollectionError::bad_request(format!(
+                    "Wrong dimension of the example vector: expected {}, got {}",
+                    vector_params.size,
+                    vector.len()
+                )));
+            }
+            Ok(vector)
+        };
+        // Query vectors are preprocessed the same way as stored ones, stored byte vectors are not
+        let preprocess_examples =
+            vector_params.datatype.unwrap_or_default() != VectorStorageDatatype::Uint8;
+        let preprocess = |vector: Vec<VectorElementType>| -> Vec<VectorElementType> {
+            if preprocess_examples {
+                distance.preprocess_vector(&vector).unwrap_or(vector)
+            } else {
+                vector
+            }
+        };
+        let query = DiscoveryQuery {
+            target: preprocess(resolve_example(&request.target)?),
+            pairs: request
+                .context
+                .iter()
+                .map(|pair| {
+                    Ok(ContextPair {
+                        positive: preprocess(resolve_example(&pair.positive)?),
+                        negative: preprocess(resolve_example(&pair.negative)?),
+                    })
+                })
+                .collect::<CollectionResult<_>>()?,
+        };
+
+        let shard_request = ShardDiscoverRequest {
+            vector_name,
+            query,
+            filter: Some(Filter {
+                should: None,
+                min_should: None,
+                must: request
+                    .filter
+                    .clone()
+                    .map(|filter| vec![Condition::Filter(filter)]),
+                must_not: (!example_ids.is_empty()).then(|| {
+                    vec![Condition::HasId(HasIdCondition {
+                        has_id: example_ids,
+                    })]
+                }),
+            }),
+            params: request.params,
+            limit: request.limit + request.offset,
+            with_payload: request.with_payload.clone(),
+            with_vector: request.with_vector.clone().unwrap_or_default(),
+        };
+        let points = self
+            .discover_shards(shard_request, search_runtime_handle, shard_selection)
+            .await?;
+
+        Ok(points
+            .into_iter()
+            .skip(request.offset)
+            .take(request.limit)
+            .collect())
+    }
+
+    /// Discovery search with the resolved examples in the selected shards.
+    /// Returns `request.limit` best points of all shards, the points are scored
+    /// by [`DiscoveryQuery::score`] in the segments.
+    pub async fn discover_shards(
+        &self,
+        request: ShardDiscoverRequest,
+        search_runtime_handle: &Handle,
+        shard_selection: Option<ShardId>,
+    ) -> CollectionResult<Vec<ScoredPoint>> {
+        let request = Arc::new(request);
+
+        let results: Vec<_> = {
+            let shards_holder = self.shards_holder.read().await;
+            let target_shards = shards_holder.target_shard(shard_selection)?;
+            let discover_futures = target_shards
+                .into_iter()
+                .map(|shard| shard.discover(request.clone(), search_runtime_handle));
+            try_join_all(discover_futures).await?
+        };
+
+        Ok(peek_top_largest_iterable(
+            results.into_iter().flatten(),
+            request.limit,
+        ))
     }
 
+    /// Search for a batch of requests.
+    /// If some of the requests has a time budget, the smallest budget is applied to the whole
+    /// batch: approximate searches are interrupted when it is exhausted and the result is
+    /// marked as truncated.
     pub async fn search_batch(
         &self,
         request: SearchRequestBatch,
         search_runtime_handle: &Handle,
         shard_selection: Option<ShardId>,
-    ) -> CollectionResult<Vec<Vec<ScoredPoint>>> {
+    ) -> CollectionResult<BatchSearchResult> {
         // shortcuts batch if all requests with limit=0
         if request.searches.iter().all(|s| s.limit == 0) {
-            return Ok(vec![]);
+            return Ok(BatchSearchResult::default());
         }
+        let deadline = request.max_time().map(|max_time| Instant::now() + max_time);
         // A factor which determines if we need to use the 2-step search or not
         // Should be adjusted based on usage statistics.
         const PAYLOAD_TRANSFERS_FACTOR_THRESHOLD: usize = 10;
@@ -877,9 +1360,11 @@ impl Collection {
                     without_payload_batch,
                     search_runtime_handle,
                     shard_selection,
+                    deadline,
                 )
                 .await?;
             let filled_results = without_payload_results
+                .results
                 .into_iter()
                 .zip(request.clone().searches.into_iter())
                 .map(|(without_payload_result, req)| {
@@ -890,21 +1375,46 @@ impl Collection {
                         shard_selection,
                     )
                 });
-            try_join_all(filled_results).await
+            Ok(BatchSearchResult {
+                results: try_join_all(filled_results).await?,
+                truncated: without_payload_results.truncated,
+                explanation: without_payload_results.explanation,
+            })
         } else {
             let result = self
-                ._search_batch(request, search_runtime_handle, shard_selection)
+                ._search_batch(request, search_runtime_handle, shard_selection, deadline)
                 .await?;
             Ok(result)
         }
     }
 
+    /// Replace vector names, omitted in the requests, with the `default_vector` of the collection.
+    /// Fails with the list of available vectors, if a request refers to an unknown one.
+    async fn resolve_vector_names(&self, request: &mut SearchRequestBatch) -> CollectionResult<()> {
+        let collection_params = &self.config.read().await.params;
+        for search in &mut request.searches {
+            let vector_name = collection_params
+                .resolve_vector_name(search.vector.get_name())?
+                .to_owned();
+            if vector_name != search.vector.get_name() {
+                search.vector = NamedVector {
+                    name: vector_name,
+                    vector: search.vector.get_vector().clone(),
+                }
+                .into();
+            }
+        }
+        Ok(())
+    }
+
     pub async fn _search_batch(
         &self,
-        request: SearchRequestBatch,
+        mut request: SearchRequestBatch,
         search_runtime_handle: &Handle,
         shard_selection: Option<ShardId>,
-    ) -> CollectionResult<Vec<Vec<ScoredPoint>>> {
+        deadline: Option<Instant>,
+    ) -> CollectionResult<BatchSearchResult> {
+        self.resolve_vector_names(&mut request).await?;
         let batch_size = request.searches.len();
         let request = Arc::new(request);
 
@@ -912,16 +1422,37 @@ impl Collection {
         let mut all_searches_res = {
             let shard_holder = self.shards_holder.read().await;
             let target_shards = shard_holder.target_shard(shard_selection)?;
-            let all_searches = target_shards
-                .iter()
-                .map(|shard| shard.search(request.clone(), search_runtime_handle));
+            let all_searches = target_shards.iter().map(|shard| {
+                let request = request.clone();
+                async move {
+                    let started = Instant::now();
+                    let mut result = shard
+                        .search(request, search_runtime_handle, deadline)
+                        .await?;
+                    // Shards of remote peers report their own timing, replace it with the one,
+                    // which includes the network round trip
+                    result.explanation.shards = vec![ShardSearchExplanation {
+                        shard_id: shard.shard_id,
+                        time: started.elapsed().as_secs_f64(),
+                        segments: result.explanation.segments,
+                        scored_vectors: result.explanation.scored_vectors,
+                    }];
+                    CollectionResult::Ok(result)
+                }
+            });
             try_join_all(all_searches).await?
         };
 
         // merge results from shards in order
         let mut merged_results: Vec<Vec<ScoredPoint>> = vec![vec![]; batch_size];
+        let mut truncated = false;
+        let mut explanation = SearchExplanation::default();
         for shard_searches_results in all_searches_res.iter_mut() {
-            for (index, shard_searches_result) in shard_searches_results.iter_mut().enumerate() {
+            truncated |= shard_searches_results.truncated;
+            explanation.merge(&shard_searches_results.explanation);
+            for (index, shard_searches_result) in
+                shard_searches_results.results.iter_mut().enumerate()
+            {
                 merged_results[index].append(shard_searches_result)
             }
         }
@@ -955,7 +1486,11 @@ impl Collection {
             })
             .collect::<CollectionResult<Vec<_>>>()?;
 
-        Ok(top_results)
+        Ok(BatchSearchResult {
+            results: top_results,
+            truncated,
+            explanation,
+        })
     }
 
     async fn fill_search_result_with_payload(
@@ -991,23 +1526,56 @@ impl Collection {
         Ok(enriched_result)
     }
 
+    /// Search for a single request, see `search_with_truncation` to know if the result is complete
     pub async fn search(
         &self,
         request: SearchRequest,
         search_runtime_handle: &Handle,
         shard_selection: Option<ShardId>,
     ) -> CollectionResult<Vec<ScoredPoint>> {
+        let (result, _truncated, _explanation) = self
+            .search_with_truncation(request, search_runtime_handle, shard_selection)
+            .await?;
+        Ok(result)
+    }
+
+    /// Search for a single request.
+    /// Second value of the result is `true` if the time budget of the request was exhausted.
+    /// Third value counts the strategies, the segments were searched with.
+    pub async fn search_with_truncation(
+        &self,
+        request: SearchRequest,
+        search_runtime_handle: &Handle,
+        shard_selection: Option<ShardId>,
+    ) -> CollectionResult<(Vec<ScoredPoint>, bool, SearchExplanation)> {
         if request.limit == 0 {
-            return Ok(vec![]);
+            return Ok((vec![], false, SearchExplanation::default()));
         }
+        let deadline = request.max_time().map(|max_time| Instant::now() + max_time);
         // search is a special case of search_batch with a single batch
         let request_batch = SearchRequestBatch {
             searches: vec![request],
         };
-        let results = self
-            ._search_batch(request_batch, search_runtime_handle, shard_selection)
+        let result = self
+            ._search_batch(
+                request_batch,
+                search_runtime_handle,
+                shard_selection,
+                deadline,
+            )
             .await?;
-        Ok(results.into_iter().next().unwrap())
+        Ok((
+            result.results.into_iter().next().unwrap(),
+            result.truncated,
+            result.explanation,
+        ))
+    }
+
+    /// Order of the search scores for the vector, e.g. distances are better when smaller
+    pub async fn score_order(&self, vector_name: &str) -> CollectionResult<Order> {
+        let config = self.config.read().await;
+        let vector_params = config.params.get_vector_params(vector_name)?;
+        Ok(vector_params.distance.distance_order())
     }
 
     pub async fn scroll_by(
@@ -1033,6 +1601,35 @@ impl Collection {
             });
         }
 
+        let order_by = request.order_by.as_ref();
+        if let Some(sample) = request.sample {
+            if offset.is_some() || order_by.is_some() {
+                return Err(CollectionError::BadRequest {
+                    description: "`sample` is not compatible with `offset` and `order_by`"
+                        .to_string(),
+                });
+            }
+            return self
+                .scroll_sample(
+                    sample,
+                    limit,
+                    &with_payload_interface,
+                    &with_vector,
+                    request.filter.as_ref(),
+                    shard_selection,
+                )
+                .await;
+        }
+
+        if let Some(order_by) = order_by {
+            if offset.is_some() && order_by.start_from.is_none() {
+                return Err(CollectionError::BadRequest {
+                    description: "`offset` requires `order_by.start_from` to be specified"
+                        .to_string(),
+                });
+            }
+        }
+
         // Needed to return next page offset.
         let limit = limit + 1;
         let retrieved_points: Vec<_> = {
@@ -1045,45 +1642,140 @@ impl Collection {
                     &with_payload_interface,
                     &with_vector,
                     request.filter.as_ref(),
+                    order_by,
+                    None,
                 )
             });
 
             try_join_all(scroll_futures).await?
         };
-        let mut points: Vec<_> = retrieved_points
-            .into_iter()
-            .flatten()
-            .sorted_by_key(|point| point.id)
-            .take(limit)
-            .collect();
+        let retrieved_points = retrieved_points.into_iter().flatten();
+        let mut points: Vec<_> = match order_by {
+            None => retrieved_points
+                .sorted_by_key(|point| point.id)
+                .take(limit)
+                .collect(),
+            Some(order_by) => retrieved_points
+                .sorted_by(|a, b| {
+                    order_by.compare(
+                        (a.order_value.unwrap_or_default(), a.id),
+                        (b.order_value.unwrap_or_default(), b.id),
+                    )
+                })
+                .take(limit)
+                .collect(),
+        };
 
-        let next_page_offset = if points.len() < limit {
+        let (next_page_offset, next_page_order_value) = if points.len() < limit {
             // This was the last page
-            None
+            (None, None)
         } else {
             // remove extra point, it would be a first point of the next page
-            Some(points.pop().unwrap().id)
+            let next_point = points.pop().unwrap();
+            (Some(next_point.id), next_point.order_value)
         };
         Ok(ScrollResult {
             points,
             next_page_offset,
+            next_page_order_value,
         })
     }
 
-    pub async fn count(
+    /// Uniformly random points of the collection
+    pub async fn sample(
         &self,
-        request: CountRequest,
+        request: SampleRequest,
         shard_selection: Option<ShardId>,
-    ) -> CollectionResult<CountResult> {
-        let request = Arc::new(request);
+    ) -> CollectionResult<Vec<Record>> {
+        if request.limit == 0 {
+            return Ok(vec![]);
+        }
+        let with_payload_interface = request
+            .with_payload
+            .unwrap_or(WithPayloadInterface::Bool(true));
+        let result = self
+            .scroll_sample(
+                Sample::Random,
+                request.limit,
+                &with_payload_interface,
+                &request.with_vector,
+                request.filter.as_ref(),
+                shard_selection,
+            )
+            .await?;
+        Ok(result.points)
+    }
 
-        let counts: Vec<_> = {
+    /// Sample of the points from all target shards.
+    ///
+    /// Shards return points with their random keys as order values, points with the largest keys
+    /// make a uniform sample of the collection.
+    /// Keys are kept in the response to the specific shard, as it is merged by the remote peer.
+    async fn scroll_sample(
+        &self,
+        sample: Sample,
+        limit: usize,
+        with_payload_interface: &WithPayloadInterface,
+        with_vector: &WithVector,
+        filter: Option<&Filter>,
+        shard_selection: Option<ShardId>,
+    ) -> CollectionResult<ScrollResult> {
+        let retrieved_points: Vec<_> = {
             let shards_holder = self.shards_holder.read().await;
             let target_shards = shards_holder.target_shard(shard_selection)?;
-            let count_futures = target_shards
-                .into_iter()
-                .map(|shard| shard.count(request.clone()));
-            try_join_all(count_futures).await?.into_iter().collect()
+            let scroll_futures = target_shards.into_iter().map(|shard| {
+                shard.scroll_by(
+                    None,
+                    limit,
+                    with_payload_interface,
+                    with_vector,
+                    filter,
+                    None,
+                    Some(sample),
+                )
+            });
+
+            try_join_all(scroll_futures).await?
+        };
+        let points = retrieved_points
+            .into_iter()
+            .flatten()
+            .sorted_by(|a, b| {
+                let key_a = a.order_value.unwrap_or_default();
+                let key_b = b.order_value.unwrap_or_default();
+                key_b.total_cmp(&key_a)
+            })
+            .take(limit)
+            .map(|mut point| {
+                // Random keys are internal, they are not order values of the payload field
+                if shard_selection.is_none() {
+                    point.order_value = None;
+                }
+                point
+            })
+            .collect();
+
+        Ok(ScrollResult {
+            points,
+            next_page_offset: None,
+            next_page_order_value: None,
+        })
+    }
+
+    pub async fn count(
+        &self,
+        request: CountRequest,
+        shard_selection: Option<ShardId>,
+    ) -> CollectionResult<CountResult> {
+        let request = Arc::new(request);
+
+        let counts: Vec<_> = {
+            let shards_holder = self.shards_holder.read().await;
+            let target_shards = shards_holder.target_shard(shard_selection)?;
+            let count_futures = target_shards
+                .into_iter()
+                .map(|shard| shard.count(request.clone()));
+            try_join_all(count_futures).await?.into_iter().collect()
         };
 
         let total_count = counts.iter().map(|x| x.count).sum::<usize>();
@@ -1091,6 +1783,254 @@ impl Collection {
         Ok(aggregated_count)
     }
 
+    pub async fn facet(
+        &self,
+        request: FacetRequest,
+        shard_selection: Option<ShardId>,
+    ) -> CollectionResult<FacetResponse> {
+        if request.limit > MAX_FACET_LIMIT {
+            return Err(CollectionError::BadRequest {
+                description: format!(
+                    "Facet limit {} is too large, max allowed is {MAX_FACET_LIMIT}",
+                    request.limit
+                ),
+            });
+        }
+        let request = Arc::new(request);
+
+        let responses: Vec<_> = {
+            let shards_holder = self.shards_holder.read().await;
+            let target_shards = shards_holder.target_shard(shard_selection)?;
+            let facet_futures = target_shards
+                .into_iter()
+                .map(|shard| shard.facet(request.clone()));
+            try_join_all(facet_futures).await?
+        };
+
+        let hits = merge_hits(
+            responses.into_iter().flat_map(|response| response.hits),
+            request.limit,
+        );
+        Ok(FacetResponse { hits })
+    }
+
+    /// Read vectors of all local shards, stored on disk, into the page cache,
+    /// so the first searches are not slowed down by disk reads
+    pub async fn warm_up(&self) -> CollectionResult<()> {
+        let shards_holder = self.shards_holder.read().await;
+        for (_shard_id, replica_set) in shards_holder.get_shards() {
+            replica_set.populate_local().await?;
+        }
+        Ok(())
+    }
+
+    /// Length of the longest update queue among the local shards
+    pub async fn max_update_queue_length(&self) -> usize {
+        let shards_holder = self.shards_holder.read().await;
+        let mut max_length = 0;
+        for (_shard_id, replica_set) in shards_holder.get_shards() {
+            max_length = max_length.max(replica_set.update_queue_length_local().await);
+        }
+        max_length
+    }
+
+    /// Vacuum segments with deleted points and merge small segments of the local shards right away,
+    /// regardless of the thresholds in the optimizers config.
+    /// If `shard_id` is specified, only this shard is optimized.
+    /// Returns the number of scheduled optimizations
+    pub async fn force_optimization(&self, shard_id: Option<ShardId>) -> CollectionResult<usize> {
+        let shards_holder = self.shards_holder.read().await;
+        let mut scheduled = 0;
+        match shard_id {
+            Some(shard_id) => {
+                let replica_set = shards_holder.get_shard(&shard_id).ok_or_else(|| {
+                    CollectionError::NotFound {
+                        what: format!("Shard {shard_id}"),
+                    }
+                })?;
+                scheduled += replica_set.force_optimization_local().await?;
+            }
+            None => {
+                for (_shard_id, replica_set) in shards_holder.get_shards() {
+                    scheduled += replica_set.force_optimization_local().await?;
+                }
+            }
+        }
+        Ok(scheduled)
+    }
+
+    /// Verify data of all local shards against the stored checksums
+    pub async fn verify(&self) -> CollectionResult<CollectionVerificationReport> {
+        let shards_holder = self.shards_holder.read().await;
+        let mut report = CollectionVerificationReport::default();
+        for (_shard_id, replica_set) in shards_holder.get_shards() {
+            report += replica_set.verify_local().await?;
+        }
+        Ok(report)
+    }
+
+    /// Resources, used by the collection on this peer.
+    /// Disk usage is the size of the collection directory, RAM usage is estimated
+    /// by the size of the vectors of the local points.
+    pub async fn local_usage(&self) -> CollectionResult<CollectionUsage> {
+        let count_request = Arc::new(CountRequest {
+            filter: None,
+            exact: false,
+        });
+        let mut points_count = 0;
+        {
+            let shards_holder = self.shards_holder.read().await;
+            for (_shard_id, replica_set) in shards_holder.get_shards() {
+                if let Some(count) = replica_set.count_local(count_request.clone()).await? {
+                    points_count += count.count;
+                }
+            }
+        }
+
+        let point_vectors_size: u64 = self
+            .config
+            .read()
+            .await
+            .params
+            .vectors
+            .params_iter()
+            .map(|(_, params)| {
+                params.size.get() * params.datatype.unwrap_or_default().element_size() as u64
+            })
+            .sum();
+        let ram_bytes = points_count as u64 * point_vectors_size;
+
+        let path = self.path.clone();
+        let disk_bytes = tokio::task::spawn_blocking(move || fs_extra::dir::get_size(&path))
+            .await?
+            .map_err(|err| {
+                CollectionError::service_error(format!(
+                    "Can't measure size of the collection {}: {err}",
+                    self.id
+                ))
+            })?;
+
+        Ok(CollectionUsage {
+            points_count,
+            disk_bytes,
+            ram_bytes,
+        })
+    }
+
+    /// Read changes of the points from the WAL of the shard, starting from the operation `from`.
+    /// Shard without a local replica is read from a remote one.
+    /// Shard can be omitted, if there is only one local shard or only one shard in the collection.
+    pub async fn read_points_changes(
+        &self,
+        shard_id: Option<ShardId>,
+        from: SeqNumberType,
+        limit: usize,
+    ) -> CollectionResult<PointsChanges> {
+        let shard_id = match shard_id {
+            Some(shard_id) => shard_id,
+            None => {
+                let local_shards = self.get_local_shards().await;
+                let shards: Vec<_> = self
+                    .shards_holder
+                    .read()
+                    .await
+                    .get_shards()
+                    .map(|(shard_id, _)| *shard_id)
+                    .collect();
+                default_changes_shard(&local_shards, &shards)?
+            }
+        };
+
+        let shards_holder = self.shards_holder.read().await;
+        let replica_set =
+            shards_holder
+                .get_shard(&shard_id)
+                .ok_or_else(|| CollectionError::NotFound {
+                    what: format!("Shard {shard_id}"),
+                })?;
+        replica_set.read_points_changes(from, limit).await
+    }
+
+    /// Read changes of the points from the WAL of the local replica of the shard
+    pub async fn read_local_points_changes(
+        &self,
+        shard_id: ShardId,
+        from: SeqNumberType,
+        limit: usize,
+    ) -> CollectionResult<PointsChanges> {
+        let shards_holder = self.shards_holder.read().await;
+        let replica_set =
+            shards_holder
+                .get_shard(&shard_id)
+                .ok_or_else(|| CollectionError::NotFound {
+                    what: format!("Shard {shard_id}"),
+                })?;
+        let operations = replica_set
+            .read_wal_local(from, limit)
+            .await?
+            .ok_or_else(|| {
+                CollectionError::bad_request(format!("Shard {shard_id} is not local"))
+            })?;
+        Ok(PointsChanges::from_operations(shard_id, from, operations))
+    }
+
+    /// Digests of the points of the local replica of the shard, used to compare it with other replicas
+    pub async fn local_point_digests(
+        &self,
+        shard_id: ShardId,
+        from: Option<PointIdType>,
+        to: Option<PointIdType>,
+        limit: Option<usize>,
+    ) -> CollectionResult<Vec<PointDigest>> {
+        let shards_holder = self.shards_holder.read().await;
+        let replica_set =
+            shards_holder
+                .get_shard(&shard_id)
+                .ok_or_else(|| CollectionError::NotFound {
+                    what: format!("Shard {shard_id}"),
+                })?;
+        replica_set
+            .point_digests_local(from, to, limit)
+            .await?
+            .ok_or_else(|| CollectionError::bad_request(format!("Shard {shard_id} is not local")))
+    }
+
+    /// Wait for the operation with the sequential number `operation_id` of the WAL of the local
+    /// replica of the shard to be applied. Returns `false`, if it is not applied within the `timeout`
+    pub async fn wait_for_local_operation(
+        &self,
+        shard_id: ShardId,
+        operation_id: SeqNumberType,
+        timeout: Duration,
+    ) -> CollectionResult<bool> {
+        let shards_holder = self.shards_holder.read().await;
+        let replica_set =
+            shards_holder
+                .get_shard(&shard_id)
+                .ok_or_else(|| CollectionError::NotFound {
+                    what: format!("Shard {shard_id}"),
+                })?;
+        replica_set
+            .wait_for_operation_local(operation_id, timeout)
+            .await?
+            .ok_or_else(|| CollectionError::bad_request(format!("Shard {shard_id} is not local")))
+    }
+
+    /// Compare the replicas of the shards with the leading ones and re-sync diverged points.
+    /// If `leading_only` - only shards with the leading replica on this peer are compared,
+    /// so each shard is compared by a single peer of the cluster.
+    pub async fn sync_replicas(&self, leading_only: bool) -> CollectionResult<ReplicaSyncReport> {
+        let shards_holder = self.shards_holder.read().await;
+        let mut report = ReplicaSyncReport::default();
+        for (_shard_id, replica_set) in shards_holder.get_shards() {
+            if leading_only && !replica_set.is_leading_replica() {
+                continue;
+            }
+            report += replica_set.sync_replicas().await?;
+        }
+        Ok(report)
+    }
+
     pub async fn retrieve(
         &self,
         request: PointRequest,
@@ -1123,6 +2063,77 @@ impl Collection {
         Ok(())
     }
 
+    /// Replace expected types of payload fields. Empty map disables the check
+    pub async fn update_payload_field_types(
+        &self,
+        field_types: PayloadFieldTypes,
+    ) -> CollectionResult<()> {
+        {
+            let mut config = self.config.write().await;
+            config.payload_field_types = if field_types.is_empty() {
+                None
+            } else {
+                Some(field_types)
+            };
+        }
+        self.config.read().await.save(&self.path)?;
+        Ok(())
+    }
+
+    /// Replace config of the automatic payload indexing. Zero `max_indexed_fields` disables it
+    pub async fn update_auto_payload_index(
+        &self,
+        auto_payload_index: AutoPayloadIndexConfig,
+    ) -> CollectionResult<()> {
+        {
+            let mut config = self.config.write().await;
+            config.auto_payload_index = if auto_payload_index.max_indexed_fields == 0 {
+                None
+            } else {
+                Some(auto_payload_index)
+            };
+        }
+        self.seen_payload_fields.lock().clear();
+        self.config.read().await.save(&self.path)?;
+        Ok(())
+    }
+
+    /// Replace config of the points expiration. Zero `ttl_sec` disables it
+    pub async fn update_ttl(&self, ttl: TtlConfig) -> CollectionResult<()> {
+        let ttl = if ttl.ttl_sec == 0 { None } else { Some(ttl) };
+        {
+            let mut config = self.config.write().await;
+            config.ttl = ttl.clone();
+        }
+        self.config.read().await.save(&self.path)?;
+        self.start_ttl_expirer(ttl);
+        Ok(())
+    }
+
+    /// Delete points, which are expired by now, without waiting for the next expirer run
+    pub async fn expire_points(&self) -> CollectionResult<()> {
+        let ttl = self.config.read().await.ttl.clone();
+        match ttl {
+            Some(ttl) => expire_points(&self.shards_holder, &ttl).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Replace the running expirer, if any, with the one for the new config
+    fn start_ttl_expirer(&self, ttl: Option<TtlConfig>) {
+        let mut ttl_expirer = self.ttl_expirer.lock();
+        if let Some(expirer) = ttl_expirer.take() {
+            expirer.ask_to_stop();
+        }
+        if let Some(ttl) = ttl {
+            let collection_id = self.id.clone();
+            let shards_holder = self.shards_holder.clone();
+            *ttl_expirer = Some(spawn_async_stoppable(move |stopped| async move {
+                run_ttl_expirer(&collection_id, &shards_holder, &ttl, &stopped).await
+            }));
+        }
+    }
+
     pub fn request_shard_transfer(&self, shard_transfer: ShardTransfer) {
         self.request_shard_transfer_cb.deref()(shard_transfer)
     }
@@ -1201,6 +2212,50 @@ impl Collection {
         Ok(())
     }
 
+    /// Updates HNSW params of the collection:
+    /// - Saves new params on disk
+    /// - Restarts optimizers, which rebuild segments indexed with the previous params in background
+    pub async fn update_hnsw_config_from_diff(
+        &self,
+        hnsw_config_diff: HnswConfigDiff,
+    ) -> CollectionResult<()> {
+        {
+            let mut config = self.config.write().await;
+            config.hnsw_config = hnsw_config_diff.update(&config.hnsw_config)?;
+        }
+        {
+            let shard_holder = self.shards_holder.read().await;
+            for replica_set in shard_holder.all_shards() {
+                replica_set.on_optimizer_config_update().await?;
+            }
+        }
+        self.config.read().await.save(&self.path)?;
+        Ok(())
+    }
+
+    /// Updates HNSW, quantization and storage params of the vectors:
+    /// - Saves new params on disk
+    /// - Restarts optimizers, which rebuild segments built with the previous params in background
+    pub async fn update_vectors_from_diff(
+        &self,
+        vectors_config_diff: &VectorsConfigDiff,
+    ) -> CollectionResult<()> {
+        {
+            let mut config = self.config.write().await;
+            config
+                .params
+                .update_vectors_from_diff(vectors_config_diff)?;
+        }
+        {
+            let shard_holder = self.shards_holder.read().await;
+            for replica_set in shard_holder.all_shards() {
+                replica_set.on_optimizer_config_update().await?;
+            }
+        }
+        self.config.read().await.save(&self.path)?;
+        Ok(())
+    }
+
     /// Updates shard optimization params:
     /// - Saves new params on disk
     /// - Stops existing optimization loop
@@ -1223,6 +2278,36 @@ impl Collection {
         Ok(())
     }
 
+    /// Rough estimation of the memory, taken by `points` points in the response.
+    /// Payload size is unknown before the points are read, so a fixed size is assumed
+    pub async fn estimate_response_size(
+        &self,
+        points: usize,
+        with_vector: &WithVector,
+        with_payload: bool,
+    ) -> usize {
+        let vectors_dim: u64 = {
+            let config = self.config.read().await;
+            config
+                .params
+                .vectors
+                .params_iter()
+                .filter(|(name, _)| match with_vector {
+                    WithVector::Bool(with_vector) => *with_vector,
+                    WithVector::Selector(names) => names.iter().any(|selected| selected == name),
+                })
+                .map(|(_, params)| params.size.get())
+                .sum()
+        };
+        let vectors_size = vectors_dim as usize * std::mem::size_of::<VectorElementType>();
+        let payload_size = if with_payload {
+            ESTIMATED_PAYLOAD_SIZE_BYTES
+        } else {
+            0
+        };
+        points * (std::mem::size_of::<ScoredPoint>() + vectors_size + payload_size)
+    }
+
     pub async fn info(&self, shard_selection: Option<ShardId>) -> CollectionResult<CollectionInfo> {
         let (all_shard_collection_results, mut info) = {
             let shards_holder = self.shards_holder.read().await;
@@ -1252,6 +2337,10 @@ impl Collection {
                 info.indexed_vectors_count += shard_info.indexed_vectors_count;
                 info.points_count += shard_info.points_count;
                 info.segments_count += shard_info.segments_count;
+                info.index_rebuild =
+                    IndexRebuildProgress::merge(info.index_rebuild, shard_info.index_rebuild);
+                info.config_migration =
+                    IndexRebuildProgress::merge(info.config_migration, shard_info.config_migration);
                 for (key, schema) in shard_info.payload_schema {
                     match info.payload_schema.entry(key) {
                         Entry::Occupied(o) => {
@@ -1263,6 +2352,17 @@ impl Collection {
                     };
                 }
             });
+        if shard_selection.is_none() {
+            if let Some(init_status) = self.init_status.read().clone() {
+                let init_collection_status = if init_status.error.is_some() {
+                    CollectionStatus::Red
+                } else {
+                    CollectionStatus::Yellow
+                };
+                info.status = max(info.status, init_collection_status);
+                info.initialization = Some(init_status);
+            }
+        }
         Ok(info)
     }
 
@@ -1272,6 +2372,7 @@ impl Collection {
         let mut local_shards = Vec::new();
         let mut remote_shards = Vec::new();
         let mut shard_transfers = Vec::new();
+        let peer_address_by_id = self.channel_service.id_to_address.read().clone();
         let count_request = Arc::new(CountRequest {
             filter: None,
             exact: true,
@@ -1301,6 +2402,7 @@ impl Collection {
                 remote_shards.push(RemoteShardInfo {
                     shard_id,
                     peer_id,
+                    peer_uri: peer_address_by_id.get(&peer_id).map(|uri| uri.to_string()),
                     state,
                 });
             }
@@ -1311,11 +2413,15 @@ impl Collection {
             let to = shard_transfer.to;
             let from = shard_transfer.from;
             let sync = shard_transfer.sync;
+            let method = shard_transfer.method.unwrap_or_default();
+            let max_points_per_second = shard_transfer.max_points_per_second;
             shard_transfers.push(ShardTransferInfo {
                 shard_id,
                 from,
                 to,
                 sync,
+                method,
+                max_points_per_second,
             })
         }
 
@@ -1334,7 +2440,79 @@ impl Collection {
         Ok(info)
     }
 
+    /// Estimate distribution of points over the shards, if the number of shards is changed.
+    /// Nothing is changed in the collection.
+    ///
+    /// All points of the collection are read to determine their new shards,
+    /// so it might take a while for large collections.
+    pub async fn resharding_preview(
+        &self,
+        request: ReshardingPreviewRequest,
+    ) -> CollectionResult<ReshardingPreview> {
+        let (current_shard_number, vectors_size_bytes) = {
+            let config = self.config.read().await;
+            (
+                config.params.shard_number.get(),
+                point_vectors_size_bytes(&config.params.vectors),
+            )
+        };
+        let shard_ids: Vec<ShardId> = self
+            .shards_holder
+            .read()
+            .await
+            .get_shards()
+            .map(|(shard_id, _)| *shard_id)
+            .sorted()
+            .collect();
+
+        let mut preview =
+            ReshardingPreviewBuilder::new(current_shard_number, request.shard_number.get());
+        for shard_id in shard_ids {
+            // Only a sample of points is read, the rest is extrapolated from the points count
+            let scroll_request = ScrollRequest {
+                offset: None,
+                limit: Some(RESHARDING_PREVIEW_SAMPLE_SIZE),
+                filter: None,
+                with_payload: Some(WithPayloadInterface::Bool(true)),
+                with_vector: WithVector::Bool(false),
+                order_by: None,
+                sample: None,
+            };
+            let result = self.scroll_by(scroll_request, Some(shard_id)).await?;
+            let points_count = if result.next_page_offset.is_none() {
+                // Whole shard fits into the sample
+                result.points.len()
+            } else {
+                let count_request = CountRequest {
+                    filter: None,
+                    exact: false,
+                };
+                self.count(count_request, Some(shard_id)).await?.count
+            };
+            let sample = result
+                .points
+                .iter()
+                .map(|point| {
+                    let payload_size_bytes = match &point.payload {
+                        None => 0,
+                        Some(payload) => serde_json::to_vec(payload)?.len(),
+                    };
+                    Ok((point.id, vectors_size_bytes + payload_size_bytes))
+                })
+                .collect::<CollectionResult<Vec<_>>>()?;
+            preview.add_sample(shard_id, points_count, &sample);
+        }
+        Ok(preview.build())
+    }
+
     pub async fn before_drop(&mut self) {
+        self.exports.lock().await.stop_all();
+        let ttl_expirer = self.ttl_expirer.lock().take();
+        if let Some(ttl_expirer) = ttl_expirer {
+            if let Err(err) = ttl_expirer.stop().await {
+                log::error!("TTL expirer of collection {} failed: {err}", self.id);
+            }
+        }
         self.shards_holder.write().await.before_drop().await;
         self.before_drop_called = true
     }
@@ -1398,29 +2576,154 @@ impl Collection {
         Ok(snapshot_path)
     }
 
-    pub async fn create_snapshot(&self, temp_dir: &Path) -> CollectionResult<SnapshotDescription> {
+    /// Start exporting points of the collection into chunk files in background
+    pub async fn create_export(
+        &self,
+        request: CreateExport,
+    ) -> CollectionResult<ExportDescription> {
+        self.exports
+            .lock()
+            .await
+            .create(&self.id, request, self.shards_holder.clone())
+    }
+
+    pub async fn list_exports(&self) -> Vec<ExportDescription> {
+        self.exports.lock().await.list()
+    }
+
+    pub async fn get_export(&self, export_id: &str) -> CollectionResult<ExportDescription> {
+        self.exports.lock().await.get(export_id)
+    }
+
+    pub async fn get_export_chunk_path(
+        &self,
+        export_id: &str,
+        chunk: usize,
+    ) -> CollectionResult<PathBuf> {
+        self.exports.lock().await.get_chunk_path(export_id, chunk)
+    }
+
+    /// Background jobs of all kinds, running or finished, in this collection
+    pub async fn list_jobs(&self) -> Vec<JobInfo> {
+        self.exports
+            .lock()
+            .await
+            .list()
+            .into_iter()
+            .map(|export| export.job)
+            .collect()
+    }
+
+    pub async fn get_job(&self, job_id: &str) -> CollectionResult<JobInfo> {
+        Ok(self.exports.lock().await.get(job_id)?.job)
+    }
+
+    pub async fn has_job(&self, job_id: &str) -> bool {
+        self.exports.lock().await.contains(job_id)
+    }
+
+    /// Stop the running background job. Finished jobs are left unchanged
+    pub async fn cancel_job(&self, job_id: &str) -> CollectionResult<JobInfo> {
+        Ok(self.exports.lock().await.cancel(job_id)?.job)
+    }
+
+    /// Create snapshot of the collection. Up to `max_parallel_shards` shards are snapshotted
+    /// simultaneously.
+    pub async fn create_snapshot(
+        &self,
+        temp_dir: &Path,
+        max_parallel_shards: usize,
+    ) -> CollectionResult<SnapshotDescription> {
+        self.create_snapshot_with_base(temp_dir, None, max_parallel_shards)
+            .await
+    }
+
+    /// Create snapshot, which only includes the segments, changed since the `base` snapshot.
+    /// Versions of the segments are compared, the base could be incremental as well.
+    pub async fn create_incremental_snapshot(
+        &self,
+        temp_dir: &Path,
+        base: &str,
+        max_parallel_shards: usize,
+    ) -> CollectionResult<SnapshotDescription> {
+        self.create_snapshot_with_base(temp_dir, Some(base), max_parallel_shards)
+            .await
+    }
+
+    async fn create_snapshot_with_base(
+        &self,
+        temp_dir: &Path,
+        base: Option<&str>,
+        max_parallel_shards: usize,
+    ) -> CollectionResult<SnapshotDescription> {
+        let base_manifest = match base {
+            None => SnapshotManifest::default(),
+            Some(base) => {
+                let base_path = self.get_snapshot_path(base).await?;
+                SnapshotManifest::read_from_archive(&base_path)?.ok_or_else(|| {
+                    CollectionError::bad_request(format!(
+                        "Snapshot {base} has no segment versions and can't be a base of an incremental snapshot"
+                    ))
+                })?
+            }
+        };
+        let mut manifest = SnapshotManifest {
+            base: base.map(|base| base.to_string()),
+            ..Default::default()
+        };
+
         let snapshot_name = format!(
-            "{}-{}.snapshot",
+            "{}-{}{}.snapshot",
             self.name(),
-            chrono::Utc::now().format("%Y-%m-%d-%H-%M-%S")
+            chrono::Utc::now().format("%Y-%m-%d-%H-%M-%S"),
+            if base.is_some() { "-incremental" } else { "" },
         );
         let snapshot_path = self.snapshots_path.join(&snapshot_name);
 
         let snapshot_path_tmp = snapshot_path.with_extension("tmp");
 
         let snapshot_path_with_tmp_extension = temp_dir.join(&snapshot_name).with_extension("tmp");
-        let snapshot_path_with_arc_extension = temp_dir.join(snapshot_name).with_extension("arc");
+        let manifest_path = temp_dir.join(snapshot_name).with_extension("manifest");
 
         create_dir_all(&snapshot_path_with_tmp_extension).await?;
 
         {
             let shards_holder = self.shards_holder.read().await;
-            // Create snapshot of each shard
-            for (shard_id, replica_set) in shards_holder.get_shards() {
-                let shard_snapshot_path =
-                    versioned_shard_path(&snapshot_path_with_tmp_extension, *shard_id, 0);
-                create_dir_all(&shard_snapshot_path).await?;
-                replica_set.create_snapshot(&shard_snapshot_path).await?;
+            // Create snapshots of the shards in parallel. Copying of the shard files and their
+            // checksums runs on the blocking pool, the number of shards in progress is limited
+            // to not saturate the disk.
+            let shard_snapshots: Vec<_> = futures::stream::iter(shards_holder.get_shards())
+                .map(|(shard_id, replica_set)| {
+                    let shard_snapshot_path =
+                        versioned_shard_path(&snapshot_path_with_tmp_extension, *shard_id, 0);
+                    let base_versions = base_manifest
+                        .shards
+                        .get(shard_id)
+                        .cloned()
+                        .unwrap_or_default();
+                    async move {
+                        create_dir_all(&shard_snapshot_path).await?;
+                        let versions = replica_set
+                            .create_snapshot(&shard_snapshot_path, &base_versions)
+                            .await?;
+                        let checksums = tokio::task::spawn_blocking(move || {
+                            directory_checksums(&shard_snapshot_path)
+                        })
+                        .await??;
+                        CollectionResult::Ok((*shard_id, versions, checksums))
+                    }
+                })
+                .buffer_unordered(max(max_parallel_shards, 1))
+                .try_collect()
+                .await?;
+
+            for (shard_id, versions, checksums) in shard_snapshots {
+                let shard_dir = versioned_shard_path(Path::new(""), shard_id, 0);
+                manifest.shards.insert(shard_id, versions);
+                for (path, checksum) in checksums {
+                    let path = shard_dir.join(path).to_string_lossy().into_owned();
+                    manifest.checksums.insert(path, checksum);
+                }
             }
         }
 
@@ -1430,22 +2733,40 @@ impl Collection {
             .await
             .save(&snapshot_path_with_tmp_extension)?;
 
-        // have to use std here, cause TarBuilder is not async
-        let file = std::fs::File::create(&snapshot_path_with_arc_extension)?;
-        let mut builder = TarBuilder::new(file);
-        // archive recursively collection directory `snapshot_path_with_arc_extension` into `snapshot_path`
-        builder.append_dir_all(".", &snapshot_path_with_tmp_extension)?;
-        builder.finish()?;
+        // Shard directories are already checksummed, only the collection files are left
+        for entry in std::fs::read_dir(&snapshot_path_with_tmp_extension)? {
+            let path = entry?.path();
+            if path.is_file() {
+                let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+                manifest
+                    .checksums
+                    .insert(file_name.into_owned(), file_checksum(&path)?);
+            }
+        }
+        manifest.save(&manifest_path)?;
+
+        // Archive is written straight into the snapshots directory under a temporary name,
+        // and renamed once complete, so incomplete snapshots are never listed
+        let archive_source = snapshot_path_with_tmp_extension.clone();
+        let archive_manifest = manifest_path.clone();
+        let archive_path = snapshot_path_tmp.clone();
+        tokio::task::spawn_blocking(move || -> CollectionResult<()> {
+            // have to use std here, cause TarBuilder is not async
+            let file = std::fs::File::create(&archive_path)?;
+            let mut builder = TarBuilder::new(file);
+            builder.append_path_with_name(&archive_manifest, SNAPSHOT_MANIFEST_FILE)?;
+            // archive recursively collection directory `archive_source` into `archive_path`
+            builder.append_dir_all(".", &archive_source)?;
+            builder.finish()?;
+            Ok(())
+        })
+        .await??;
 
         // remove temporary snapshot directory
         remove_dir_all(&snapshot_path_with_tmp_extension).await?;
+        remove_file(&manifest_path).await?;
 
-        // move snapshot to permanent location
-        // We can't move right away, because snapshot folder can be on another mounting point.
-        // We can't copy to the target location directly, cause copy is not atomic.
-        copy(&snapshot_path_with_arc_extension, &snapshot_path_tmp).await?;
         rename(&snapshot_path_tmp, &snapshot_path).await?;
-        remove_file(snapshot_path_with_arc_extension).await?;
 
         get_snapshot_description(&snapshot_path).await
     }
@@ -1456,6 +2777,13 @@ impl Collection {
         let mut ar = tar::Archive::new(archive_file);
         ar.unpack(target_dir)?;
 
+        // Snapshots of older versions have no manifest
+        if let Some(manifest) = SnapshotManifest::load(target_dir)? {
+            manifest.verify_checksums(target_dir)?;
+            Self::restore_base_segments(snapshot_path, target_dir, &manifest)?;
+            std::fs::remove_file(target_dir.join(SNAPSHOT_MANIFEST_FILE))?;
+        }
+
         let config = CollectionConfig::load(target_dir)?;
         let configured_shards = config.params.shard_number.get();
 
@@ -1484,6 +2812,68 @@ impl Collection {
         Ok(())
     }
 
+    /// Unpack segments, which are not included into the incremental snapshot, from the chain
+    /// of its base snapshots. Base snapshots are expected in the directory of the snapshot.
+    fn restore_base_segments(
+        snapshot_path: &Path,
+        target_dir: &Path,
+        manifest: &SnapshotManifest,
+    ) -> CollectionResult<()> {
+        // Archives of the missing segments, relative to the collection directory
+        let mut missing: HashSet<PathBuf> = manifest
+            .shards
+            .iter()
+            .flat_map(|(shard_id, versions)| {
+                let segments_path =
+                    versioned_shard_path(Path::new(""), *shard_id, 0).join("segments");
+                versions
+                    .keys()
+                    .map(move |segment_id| segments_path.join(format!("{segment_id}.tar")))
+            })
+            .filter(|archive| !target_dir.join(archive).exists())
+            .collect();
+
+        let mut base = manifest.base.clone();
+        while !missing.is_empty() {
+            let base_name = match base {
+                Some(base_name) => base_name,
+                None => break,
+            };
+            let base_path = snapshot_path.with_file_name(&base_name);
+            if !base_path.exists() {
+                return Err(CollectionError::service_error(format!(
+                    "Base snapshot {} is not found",
+                    base_path.display()
+                )));
+            }
+            let mut base_manifest = SnapshotManifest::default();
+            let mut unpacked = vec![];
+            let mut archive = tar::Archive::new(std::fs::File::open(&base_path)?);
+            for entry in archive.entries()? {
+                let mut entry = entry?;
+                let entry_path = archive_entry_path(&entry.path()?);
+                if entry_path == Path::new(SNAPSHOT_MANIFEST_FILE) {
+                    base_manifest = serde_json::from_reader(entry)?;
+                } else if missing.remove(&entry_path) {
+                    entry.unpack(target_dir.join(&entry_path))?;
+                    unpacked.push(entry_path);
+                }
+            }
+            for entry_path in &unpacked {
+                base_manifest.verify_checksum(target_dir, entry_path)?;
+            }
+            base = base_manifest.base;
+        }
+
+        if !missing.is_empty() {
+            return Err(CollectionError::service_error(format!(
+                "Segments {missing:?} are not found in the base snapshots of {}",
+                snapshot_path.display()
+            )));
+        }
+        Ok(())
+    }
+
     pub async fn suggest_shard_replica_changes(
         &self,
         new_repl_factor: NonZeroU32,
@@ -1547,6 +2937,48 @@ impl Drop for Collection {
     }
 }
 
+/// Delete points, which are expired by now.
+/// Only the shards, where this peer is the leader of the alive replicas, are processed,
+/// so the same deletions are not issued by every peer of the cluster.
+async fn expire_points(shards_holder: &LockedShardHolder, ttl: &TtlConfig) -> CollectionResult<()> {
+    let operation = ttl.expire_operation();
+    let shards_holder = shards_holder.read().await;
+    let deletions = shards_holder
+        .all_shards()
+        .filter(|replica_set| replica_set.is_alive_leader())
+        .map(|replica_set| {
+            replica_set.update_with_consistency(
+                operation.clone().into(),
+                true,
+                WriteOrdering::Medium,
+            )
+        });
+    try_join_all(deletions).await?;
+    Ok(())
+}
+
+/// Delete expired points every `check_interval_sec` until the expirer is asked to stop.
+/// Failed deletions are retried with the next check.
+async fn run_ttl_expirer(
+    collection_id: &str,
+    shards_holder: &LockedShardHolder,
+    ttl: &TtlConfig,
+    stopped: &AtomicBool,
+) {
+    loop {
+        let next_check = Instant::now() + ttl.check_interval();
+        while Instant::now() < next_check {
+            if stopped.load(Ordering::Relaxed) {
+                return;
+            }
+            tokio::time::sleep(TTL_EXPIRER_STOP_CHECK_INTERVAL).await;
+        }
+        if let Err(err) = expire_points(shards_holder, ttl).await {
+            log::warn!("Failed to delete expired points of collection {collection_id}: {err}");
+        }
+    }
+}
+
 fn avg_vectors<'a>(
     vectors: impl Iterator<Item = &'a Vec<VectorElementType>>,
 ) -> Vec<VectorElementType> {
@@ -1569,3 +3001,19 @@ fn avg_vectors<'a>(
 
     avg_vector
 }
+
+/// Shard to read the changes of the points from, if it is not specified in the request:
+/// the only local shard, or the only shard of the collection, if it has no local shards
+pub(crate) fn default_changes_shard(
+    local_shards: &[ShardId],
+    shards: &[ShardId],
+) -> CollectionResult<ShardId> {
+    match (local_shards, shards) {
+        ([shard_id], _) | ([], [shard_id]) => Ok(*shard_id),
+        _ => Err(CollectionError::bad_request(format!(
+            "Collection has {} shards, {} of them on this peer, shard_id is required",
+            shards.len(),
+            local_shards.len(),
+        ))),
+    }
+}
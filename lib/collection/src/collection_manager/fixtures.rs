@@ -175,13 +175,20 @@ pub(crate) fn get_merge_optimizer(
             vectors: VectorsConfig::Single(VectorParams {
                 size: NonZeroU64::new(dim as u64).unwrap(),
                 distance: Distance::Dot,
+                hnsw_config: None,
+                quantization_config: None,
+                on_disk: None,
+                datatype: None,
+                multivector_config: None,
             }),
             shard_number: NonZeroU32::new(1).unwrap(),
             on_disk_payload: false,
+            default_vector: None,
             replication_factor: NonZeroU32::new(1).unwrap(),
             write_consistency_factor: NonZeroU32::new(1).unwrap(),
         },
         Default::default(),
+        None,
     )
 }
 
@@ -202,12 +209,19 @@ pub(crate) fn get_indexing_optimizer(
             vectors: VectorsConfig::Single(VectorParams {
                 size: NonZeroU64::new(dim as u64).unwrap(),
                 distance: Distance::Dot,
+                hnsw_config: None,
+                quantization_config: None,
+                on_disk: None,
+                datatype: None,
+                multivector_config: None,
             }),
             shard_number: NonZeroU32::new(1).unwrap(),
             on_disk_payload: false,
+            default_vector: None,
             replication_factor: NonZeroU32::new(1).unwrap(),
             write_consistency_factor: NonZeroU32::new(1).unwrap(),
         },
         Default::default(),
+        None,
     )
 }
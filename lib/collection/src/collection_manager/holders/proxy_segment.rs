@@ -3,20 +3,25 @@ use std::collections::{HashMap, HashSet};
 use std::fs::{create_dir_all, remove_dir_all};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Instant;
 
 use parking_lot::{RwLock, RwLockUpgradableReadGuard};
+use segment::common::encryption::StorageCipher;
+use segment::data_types::discovery::DiscoveryQuery;
+use segment::data_types::facets::FacetSketch;
 use segment::data_types::named_vectors::NamedVectors;
+use segment::data_types::payload_update::PayloadUpdate;
 use segment::data_types::vectors::VectorElementType;
 use segment::entry::entry_point::{
     OperationError, OperationResult, SegmentEntry, SegmentFailedState,
 };
 use segment::index::field_index::CardinalityEstimation;
-use segment::segment_constructor::load_segment;
+use segment::segment_constructor::load_segment_with_cipher;
 use segment::telemetry::SegmentTelemetry;
 use segment::types::{
-    Condition, Filter, Payload, PayloadFieldSchema, PayloadKeyType, PayloadKeyTypeRef, PointIdType,
-    ScoredPoint, SearchParams, SegmentConfig, SegmentInfo, SegmentType, SeqNumberType, WithPayload,
-    WithVector,
+    BatchSearchResult, Condition, Filter, FloatPayloadType, OrderBy, Payload, PayloadFieldSchema,
+    PayloadKeyType, PayloadKeyTypeRef, PointIdType, ScoreType, ScoredPoint, SearchParams,
+    SegmentConfig, SegmentInfo, SegmentType, SeqNumberType, WithPayload, WithVector,
 };
 use uuid::Uuid;
 
@@ -181,6 +186,7 @@ impl SegmentEntry for ProxySegment {
         filter: Option<&Filter>,
         top: usize,
         params: Option<&SearchParams>,
+        score_threshold: Option<ScoreType>,
     ) -> OperationResult<Vec<ScoredPoint>> {
         let deleted_points = self.deleted_points.read();
 
@@ -203,6 +209,7 @@ impl SegmentEntry for ProxySegment {
                 Some(&wrapped_filter),
                 top,
                 params,
+                score_threshold,
             )?
         } else {
             self.wrapped_segment.get().read().search(
@@ -213,6 +220,7 @@ impl SegmentEntry for ProxySegment {
                 filter,
                 top,
                 params,
+                score_threshold,
             )?
         };
 
@@ -224,6 +232,7 @@ impl SegmentEntry for ProxySegment {
             filter,
             top,
             params,
+            score_threshold,
         )?;
 
         wrapped_result.append(&mut write_result);
@@ -239,14 +248,16 @@ impl SegmentEntry for ProxySegment {
         filter: Option<&Filter>,
         top: usize,
         params: Option<&SearchParams>,
-    ) -> OperationResult<Vec<Vec<ScoredPoint>>> {
+        score_threshold: Option<ScoreType>,
+        deadline: Option<Instant>,
+    ) -> OperationResult<BatchSearchResult> {
         let deleted_points = self.deleted_points.read();
 
         // Some point might be deleted after temporary segment creation
         // We need to prevent them from being found by search request
         // That is why we need to pass additional filter for deleted points
         let do_update_filter = !deleted_points.is_empty();
-        let mut wrapped_results = if do_update_filter {
+        let mut wrapped_result = if do_update_filter {
             // ToDo: Come up with better way to pass deleted points into Filter
             // e.g. implement AtomicRefCell for Serializer.
             // This copy might slow process down if there will be a lot of deleted points
@@ -261,6 +272,8 @@ impl SegmentEntry for ProxySegment {
                 Some(&wrapped_filter),
                 top,
                 params,
+                score_threshold,
+                deadline,
             )?
         } else {
             self.wrapped_segment.get().read().search_batch(
@@ -271,9 +284,11 @@ impl SegmentEntry for ProxySegment {
                 filter,
                 top,
                 params,
+                score_threshold,
+                deadline,
             )?
         };
-        let mut write_results = self.write_segment.get().read().search_batch(
+        let mut write_result = self.write_segment.get().read().search_batch(
             vector_name,
             vectors,
             with_payload,
@@ -281,11 +296,67 @@ impl SegmentEntry for ProxySegment {
             filter,
             top,
             params,
+            score_threshold,
+            deadline,
         )?;
-        for (index, write_result) in write_results.iter_mut().enumerate() {
-            wrapped_results[index].append(write_result)
+        for (index, write_result) in write_result.results.iter_mut().enumerate() {
+            wrapped_result.results[index].append(write_result)
         }
-        Ok(wrapped_results)
+        wrapped_result.truncated |= write_result.truncated;
+        wrapped_result.explanation.merge(&write_result.explanation);
+        Ok(wrapped_result)
+    }
+
+    fn discover(
+        &self,
+        vector_name: &str,
+        query: &DiscoveryQuery,
+        with_payload: &WithPayload,
+        with_vector: &WithVector,
+        filter: Option<&Filter>,
+        top: usize,
+        params: Option<&SearchParams>,
+    ) -> OperationResult<Vec<ScoredPoint>> {
+        let deleted_points = self.deleted_points.read();
+
+        // Points, deleted after temporary segment creation, are excluded the same way as in search
+        let mut wrapped_result = if deleted_points.is_empty() {
+            self.wrapped_segment.get().read().discover(
+                vector_name,
+                query,
+                with_payload,
+                with_vector,
+                filter,
+                top,
+                params,
+            )?
+        } else {
+            let wrapped_filter =
+                self.add_deleted_points_condition_to_filter(filter, &deleted_points);
+
+            self.wrapped_segment.get().read().discover(
+                vector_name,
+                query,
+                with_payload,
+                with_vector,
+                Some(&wrapped_filter),
+                top,
+                params,
+            )?
+        };
+
+        let mut write_result = self.write_segment.get().read().discover(
+            vector_name,
+            query,
+            with_payload,
+            with_vector,
+            filter,
+            top,
+            params,
+        )?;
+
+        wrapped_result.append(&mut write_result);
+        Ok(wrapped_result)
     }
 
     fn upsert_vector(
@@ -346,6 +417,19 @@ impl SegmentEntry for ProxySegment {
             .set_payload(op_num, point_id, payload)
     }
 
+    fn update_payload(
+        &mut self,
+        op_num: SeqNumberType,
+        point_id: PointIdType,
+        updates: &[PayloadUpdate],
+    ) -> OperationResult<bool> {
+        self.move_if_exists(op_num, point_id)?;
+        self.write_segment
+            .get()
+            .write()
+            .update_payload(op_num, point_id, updates)
+    }
+
     fn delete_payload(
         &mut self,
         op_num: SeqNumberType,
@@ -463,6 +547,99 @@ impl SegmentEntry for ProxySegment {
         read_points
     }
 
+    fn read_ordered_filtered<'a>(
+        &'a self,
+        offset: Option<PointIdType>,
+        limit: Option<usize>,
+        filter: Option<&'a Filter>,
+        order_by: &'a OrderBy,
+    ) -> OperationResult<Vec<(FloatPayloadType, PointIdType)>> {
+        let deleted_points = self.deleted_points.read();
+        let mut read_points = if deleted_points.is_empty() {
+            self.wrapped_segment
+                .get()
+                .read()
+                .read_ordered_filtered(offset, limit, filter, order_by)?
+        } else {
+            let wrapped_filter =
+                self.add_deleted_points_condition_to_filter(filter, &deleted_points);
+            self.wrapped_segment.get().read().read_ordered_filtered(
+                offset,
+                limit,
+                Some(&wrapped_filter),
+                order_by,
+            )?
+        };
+        let mut write_segment_points = self
+            .write_segment
+            .get()
+            .read()
+            .read_ordered_filtered(offset, limit, filter, order_by)?;
+        read_points.append(&mut write_segment_points);
+        read_points.sort_unstable_by(|a, b| order_by.compare(*a, *b));
+        Ok(read_points)
+    }
+
+    fn read_random_filtered<'a>(
+        &'a self,
+        limit: usize,
+        filter: Option<&'a Filter>,
+    ) -> Vec<(FloatPayloadType, PointIdType)> {
+        let deleted_points = self.deleted_points.read();
+        let mut read_points = if deleted_points.is_empty() {
+            self.wrapped_segment
+                .get()
+                .read()
+                .read_random_filtered(limit, filter)
+        } else {
+            let wrapped_filter =
+                self.add_deleted_points_condition_to_filter(filter, &deleted_points);
+            self.wrapped_segment
+                .get()
+                .read()
+                .read_random_filtered(limit, Some(&wrapped_filter))
+        };
+        let mut write_segment_points = self
+            .write_segment
+            .get()
+            .read()
+            .read_random_filtered(limit, filter);
+        read_points.append(&mut write_segment_points);
+        // Keep points with the largest keys, as a single segment would
+        read_points.sort_unstable_by(|(key_a, _), (key_b, _)| key_b.total_cmp(key_a));
+        read_points.truncate(limit);
+        read_points
+    }
+
+    fn facet<'a>(
+        &'a self,
+        key: PayloadKeyTypeRef,
+        filter: Option<&'a Filter>,
+        sketch_capacity: usize,
+    ) -> OperationResult<FacetSketch> {
+        let deleted_points = self.deleted_points.read();
+        let mut sketch = if deleted_points.is_empty() {
+            self.wrapped_segment
+                .get()
+                .read()
+                .facet(key, filter, sketch_capacity)?
+        } else {
+            let wrapped_filter =
+                self.add_deleted_points_condition_to_filter(filter, &deleted_points);
+            self.wrapped_segment
+                .get()
+                .read()
+                .facet(key, Some(&wrapped_filter), sketch_capacity)?
+        };
+        let write_segment_sketch =
+            self.write_segment
+                .get()
+                .read()
+                .facet(key, filter, sketch_capacity)?;
+        sketch.merge(write_segment_sketch);
+        Ok(sketch)
+    }
+
     /// Read points in [from; to) range
     fn read_range(&self, from: Option<PointIdType>, to: Option<PointIdType>) -> Vec<PointIdType> {
         let deleted_points = self.deleted_points.read();
@@ -542,6 +719,11 @@ impl SegmentEntry for ProxySegment {
         self.write_segment.get().read().deleted_count()
     }
 
+    fn point_offsets_count(&self) -> usize {
+        self.wrapped_segment.get().read().point_offsets_count()
+            + self.write_segment.get().read().point_offsets_count()
+    }
+
     fn segment_type(&self) -> SegmentType {
         SegmentType::Special
     }
@@ -571,6 +753,14 @@ impl SegmentEntry for ProxySegment {
         true
     }
 
+    fn has_missing_indexes(&self) -> bool {
+        false
+    }
+
+    fn storage_cipher(&self) -> Option<StorageCipher> {
+        self.wrapped_segment.get().read().storage_cipher()
+    }
+
     fn flush(&self, sync: bool) -> OperationResult<SeqNumberType> {
         let deleted_points_guard = self.deleted_points.read();
         let deleted_indexes_guard = self.deleted_indexes.read();
@@ -719,6 +909,7 @@ impl SegmentEntry for ProxySegment {
 
         // copy proxy segment current wrapped data
         let full_copy_path = wrapped_segment_guard.copy_segment_directory(&copy_target_dir)?;
+        let storage_cipher = wrapped_segment_guard.storage_cipher();
         // snapshot write_segment
         let write_segment_rw = self.write_segment.get();
         let write_segment_guard = write_segment_rw.read();
@@ -733,12 +924,13 @@ impl SegmentEntry for ProxySegment {
         drop(deleted_points_guard);
 
         // load copy of wrapped segment in memory
-        let mut in_memory_wrapped_segment = load_segment(&full_copy_path)?.ok_or_else(|| {
-            OperationError::service_error(&format!(
-                "Failed to load segment from {:?}",
-                full_copy_path
-            ))
-        })?;
+        let mut in_memory_wrapped_segment =
+            load_segment_with_cipher(&full_copy_path, storage_cipher)?.ok_or_else(|| {
+                OperationError::service_error(&format!(
+                    "Failed to load segment from {:?}",
+                    full_copy_path
+                ))
+            })?;
 
         // remove potentially deleted points from wrapped_segment
         for deleted_point in deleted_points_copy {
@@ -764,6 +956,15 @@ impl SegmentEntry for ProxySegment {
     fn get_telemetry_data(&self) -> SegmentTelemetry {
         self.wrapped_segment.get().read().get_telemetry_data()
     }
+    fn populate(&self) {
+        self.wrapped_segment.get().read().populate();
+        self.write_segment.get().read().populate();
+    }
+
+    fn verify_data(&self) -> OperationResult<()> {
+        self.wrapped_segment.get().read().verify_data()?;
+        self.write_segment.get().read().verify_data()
+    }
 }
 
 #[cfg(test)]
@@ -820,6 +1021,7 @@ mod tests {
                 None,
                 10,
                 None,
+                None,
             )
             .unwrap();
 
@@ -887,6 +1089,7 @@ mod tests {
                 None,
                 10,
                 None,
+                None,
             )
             .unwrap();
 
@@ -901,8 +1104,11 @@ mod tests {
                 None,
                 10,
                 None,
+                None,
+                None,
             )
-            .unwrap();
+            .unwrap()
+            .results;
 
         eprintln!("search_batch_result = {:#?}", search_batch_result);
 
@@ -940,6 +1146,7 @@ mod tests {
                 None,
                 10,
                 None,
+                None,
             )
             .unwrap();
 
@@ -954,8 +1161,11 @@ mod tests {
                 None,
                 10,
                 None,
+                None,
+                None,
             )
-            .unwrap();
+            .unwrap()
+            .results;
 
         eprintln!("search_batch_result = {:#?}", search_batch_result);
 
@@ -1002,6 +1212,7 @@ mod tests {
                     None,
                     10,
                     None,
+                    None,
                 )
                 .unwrap();
             all_single_results.push(res);
@@ -1018,8 +1229,11 @@ mod tests {
                 None,
                 10,
                 None,
+                None,
+                None,
             )
-            .unwrap();
+            .unwrap()
+            .results;
 
         eprintln!("search_batch_result = {:#?}", search_batch_result);
 
@@ -14,6 +14,7 @@ use segment::segment::Segment;
 use segment::types::{PointIdType, SeqNumberType};
 
 use crate::collection_manager::holders::proxy_segment::ProxySegment;
+use crate::operations::snapshot_ops::SegmentVersions;
 use crate::operations::types::CollectionError;
 
 pub type SegmentId = usize;
@@ -413,12 +414,38 @@ impl<'s> SegmentHolder {
     ///
     /// Shortcuts at the first failing segment snapshot
     pub fn snapshot_all_segments(&self, snapshot_dir_path: &Path) -> OperationResult<()> {
+        self.snapshot_changed_segments(snapshot_dir_path, &SegmentVersions::default())?;
+        Ok(())
+    }
+
+    /// Take a snapshot of the segments, which are not in `base` with the same version.
+    /// Returns versions of all the segments, except the proxies, which are always snapshotted.
+    pub fn snapshot_changed_segments(
+        &self,
+        snapshot_dir_path: &Path,
+        base: &SegmentVersions,
+    ) -> OperationResult<SegmentVersions> {
+        let mut versions = SegmentVersions::new();
         for segment in self.segments.values() {
-            let segment_lock = segment.get();
-            let read_segment = segment_lock.read();
-            read_segment.take_snapshot(snapshot_dir_path)?
+            match segment {
+                LockedSegment::Original(original) => {
+                    let read_segment = original.read();
+                    let segment_id = read_segment
+                        .data_path()
+                        .file_stem()
+                        .and_then(|stem| stem.to_str())
+                        .map(|stem| stem.to_string())
+                        .unwrap_or_default();
+                    let version = read_segment.version();
+                    if base.get(&segment_id) != Some(&version) {
+                        read_segment.take_snapshot(snapshot_dir_path)?;
+                    }
+                    versions.insert(segment_id, version);
+                }
+                LockedSegment::Proxy(proxy) => proxy.read().take_snapshot(snapshot_dir_path)?,
+            }
         }
-        Ok(())
+        Ok(versions)
     }
 
     pub fn report_optimizer_error<E: Into<CollectionError>>(&mut self, error: E) {
@@ -555,4 +582,36 @@ mod tests {
         // one archive produced per concrete segment in the SegmentHolder
         assert_eq!(archive_count, 2);
     }
+
+    #[test]
+    fn test_snapshot_changed_segments() {
+        let dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+        let mut holder = SegmentHolder::default();
+        holder.add(build_segment_1(dir.path()));
+        holder.add(build_segment_2(dir.path()));
+
+        let full_dir = Builder::new().prefix("snapshot_dir").tempdir().unwrap();
+        let versions = holder
+            .snapshot_changed_segments(full_dir.path(), &SegmentVersions::default())
+            .unwrap();
+        assert_eq!(versions.len(), 2);
+        assert_eq!(read_dir(&full_dir).unwrap().count(), 2);
+
+        // Only the changed segment is included into the incremental snapshot
+        let mut base = versions.clone();
+        let (changed_id, changed_version) = base.iter_mut().next().unwrap();
+        *changed_version += 1;
+        let changed_archive = format!("{changed_id}.tar");
+
+        let incremental_dir = Builder::new().prefix("snapshot_dir").tempdir().unwrap();
+        let incremental_versions = holder
+            .snapshot_changed_segments(incremental_dir.path(), &base)
+            .unwrap();
+        assert_eq!(incremental_versions, versions);
+        let archives: Vec<_> = read_dir(&incremental_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        assert_eq!(archives, vec![changed_archive]);
+    }
 }
@@ -0,0 +1,266 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use segment::common::encryption::StorageCipher;
+use segment::common::operation_time_statistics::{
+    OperationDurationStatistics, OperationDurationsAggregator,
+};
+use segment::types::{HnswConfig, Indexes, SegmentConfig, StorageType};
+
+use crate::collection_manager::holders::segment_holder::{LockedSegmentHolder, SegmentId};
+use crate::collection_manager::optimizers::segment_optimizer::{
+    OptimizerThresholds, SegmentOptimizer,
+};
+use crate::common::optimization_scheduler::OptimizationPriority;
+use crate::config::CollectionParams;
+
+/// Whether the segment was built with vector params, which differ from the current params of the
+/// collection, e.g. after they were changed with a collection update.
+/// Appendable segments get the current params with their regular optimization.
+pub fn has_outdated_config(
+    segment_config: &SegmentConfig,
+    is_appendable: bool,
+    collection_params: &CollectionParams,
+    hnsw_config: &HnswConfig,
+    storage_cipher: &Option<StorageCipher>,
+) -> bool {
+    if is_appendable {
+        return false;
+    }
+
+    // Mmap vector storage can't be encrypted
+    if collection_params.vectors_on_disk()
+        && storage_cipher.is_none()
+        && segment_config.storage_type == StorageType::InMemory
+    {
+        return true;
+    }
+
+    // HNSW and quantization params only apply to indexed segments
+    let segment_hnsw_config = match &segment_config.index {
+        Indexes::Hnsw(segment_hnsw_config) => segment_hnsw_config,
+        Indexes::Plain {} => return false,
+    };
+    let target_vector_data = match collection_params.get_all_vector_params(hnsw_config) {
+        Ok(target_vector_data) => target_vector_data,
+        Err(_) => return false,
+    };
+    target_vector_data.iter().any(|(vector_name, target)| {
+        let current = match segment_config.vector_data.get(vector_name) {
+            Some(current) => current,
+            None => return false,
+        };
+        let current_hnsw_config = current.hnsw_config.as_ref().unwrap_or(segment_hnsw_config);
+        let target_hnsw_config = target.hnsw_config.as_ref().unwrap_or(hnsw_config);
+        current_hnsw_config.mismatch_requires_rebuild(target_hnsw_config)
+            || current.quantization_config != target.quantization_config
+    })
+}
+
+/// Optimizer which rebuilds segments with outdated vector params, so HNSW, quantization and
+/// storage params of the collection could be changed without re-creating it.
+pub struct ConfigMismatchOptimizer {
+    thresholds_config: OptimizerThresholds,
+    segments_path: PathBuf,
+    collection_temp_dir: PathBuf,
+    collection_params: CollectionParams,
+    hnsw_config: HnswConfig,
+    storage_cipher: Option<StorageCipher>,
+    telemetry_durations_aggregator: Arc<Mutex<OperationDurationsAggregator>>,
+}
+
+impl ConfigMismatchOptimizer {
+    pub fn new(
+        thresholds_config: OptimizerThresholds,
+        segments_path: PathBuf,
+        collection_temp_dir: PathBuf,
+        collection_params: CollectionParams,
+        hnsw_config: HnswConfig,
+        storage_cipher: Option<StorageCipher>,
+    ) -> Self {
+        ConfigMismatchOptimizer {
+            thresholds_config,
+            segments_path,
+            collection_temp_dir,
+            collection_params,
+            hnsw_config,
+            storage_cipher,
+            telemetry_durations_aggregator: OperationDurationsAggregator::new(),
+        }
+    }
+
+    /// Smallest outdated segment, so the new params are applied to as many segments as possible
+    /// in the shortest time
+    fn smallest_outdated_segment(
+        &self,
+        segments: LockedSegmentHolder,
+        excluded_ids: &HashSet<SegmentId>,
+    ) -> Option<SegmentId> {
+        let segments_read_guard = segments.read();
+        segments_read_guard
+            .iter()
+            .filter(|(idx, _segment)| !excluded_ids.contains(idx))
+            .filter_map(|(idx, segment)| {
+                let segment_entry = segment.get();
+                let read_segment = segment_entry.read();
+                has_outdated_config(
+                    &read_segment.config(),
+                    read_segment.is_appendable(),
+                    &self.collection_params,
+                    &self.hnsw_config,
+                    &self.storage_cipher,
+                )
+                .then(|| (*idx, read_segment.points_count()))
+            })
+            .min_by_key(|(_idx, points_count)| *points_count)
+            .map(|(idx, _points_count)| idx)
+    }
+}
+
+impl SegmentOptimizer for ConfigMismatchOptimizer {
+    fn collection_path(&self) -> &Path {
+        self.segments_path.as_path()
+    }
+
+    fn temp_path(&self) -> &Path {
+        self.collection_temp_dir.as_path()
+    }
+
+    fn collection_params(&self) -> CollectionParams {
+        self.collection_params.clone()
+    }
+
+    fn hnsw_config(&self) -> HnswConfig {
+        self.hnsw_config
+    }
+
+    fn storage_cipher(&self) -> Option<StorageCipher> {
+        self.storage_cipher.clone()
+    }
+
+    fn threshold_config(&self) -> &OptimizerThresholds {
+        &self.thresholds_config
+    }
+
+    fn check_condition(
+        &self,
+        segments: LockedSegmentHolder,
+        excluded_ids: &HashSet<SegmentId>,
+    ) -> Vec<SegmentId> {
+        self.smallest_outdated_segment(segments, excluded_ids)
+            .into_iter()
+            .collect()
+    }
+
+    fn get_telemetry_data(&self) -> OperationDurationStatistics {
+        self.get_telemetry_counter().lock().get_statistics()
+    }
+
+    /// Rebuilding of the segments includes building of vector index
+    fn priority(&self) -> OptimizationPriority {
+        OptimizationPriority::Low
+    }
+
+    fn get_telemetry_counter(&self) -> Arc<Mutex<OperationDurationsAggregator>> {
+        self.telemetry_durations_aggregator.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+
+    use itertools::Itertools;
+    use parking_lot::RwLock;
+    use segment::data_types::vectors::DEFAULT_VECTOR_NAME;
+    use tempfile::Builder;
+
+    use super::*;
+    use crate::collection_manager::fixtures::{get_indexing_optimizer, random_segment};
+    use crate::collection_manager::holders::segment_holder::SegmentHolder;
+    use crate::operations::config_diff::{HnswConfigDiff, VectorParamsDiff, VectorsConfigDiff};
+
+    #[test]
+    fn test_rebuild_outdated_segments() {
+        let stopped = AtomicBool::new(false);
+        let dim = 256;
+
+        let segments_dir = Builder::new().prefix("segments_dir").tempdir().unwrap();
+        let segments_temp_dir = Builder::new()
+            .prefix("segments_temp_dir")
+            .tempdir()
+            .unwrap();
+
+        let mut holder = SegmentHolder::default();
+        let segment_id = holder.add(random_segment(segments_dir.path(), 100, 200, dim));
+        let locked_holder: LockedSegmentHolder = Arc::new(RwLock::new(holder));
+
+        // Build an indexed segment with the initial params
+        let indexing_optimizer =
+            get_indexing_optimizer(segments_dir.path(), segments_temp_dir.path(), dim);
+        indexing_optimizer
+            .optimize(locked_holder.clone(), vec![segment_id], &stopped)
+            .unwrap();
+
+        let mut collection_params = indexing_optimizer.collection_params();
+        let thresholds_config = indexing_optimizer.threshold_config().clone();
+        let hnsw_config = indexing_optimizer.hnsw_config();
+
+        let optimizer = |collection_params: &CollectionParams| {
+            ConfigMismatchOptimizer::new(
+                thresholds_config.clone(),
+                segments_dir.path().to_owned(),
+                segments_temp_dir.path().to_owned(),
+                collection_params.clone(),
+                hnsw_config,
+                None,
+            )
+        };
+
+        let excluded_ids = Default::default();
+        assert!(optimizer(&collection_params)
+            .check_condition(locked_holder.clone(), &excluded_ids)
+            .is_empty());
+
+        let mut vectors_diff = VectorsConfigDiff::default();
+        vectors_diff.0.insert(
+            DEFAULT_VECTOR_NAME.to_string(),
+            VectorParamsDiff {
+                hnsw_config: Some(HnswConfigDiff {
+                    m: Some(hnsw_config.m * 2),
+                    ef_construct: None,
+                    full_scan_threshold: None,
+                }),
+                quantization_config: None,
+                on_disk: None,
+            },
+        );
+        collection_params
+            .update_vectors_from_diff(&vectors_diff)
+            .unwrap();
+
+        let config_mismatch_optimizer = optimizer(&collection_params);
+        let suggested_to_optimize =
+            config_mismatch_optimizer.check_condition(locked_holder.clone(), &excluded_ids);
+        assert_eq!(suggested_to_optimize.len(), 1);
+
+        config_mismatch_optimizer
+            .optimize(locked_holder.clone(), suggested_to_optimize, &stopped)
+            .unwrap();
+        assert!(config_mismatch_optimizer
+            .check_condition(locked_holder.clone(), &excluded_ids)
+            .is_empty());
+
+        let rebuilt_hnsw_configs = locked_holder
+            .read()
+            .iter()
+            .map(|(_idx, segment)| segment.get().read().config())
+            .filter(|config| matches!(config.index, Indexes::Hnsw(_)))
+            .map(|config| config.vector_data[DEFAULT_VECTOR_NAME].hnsw_config)
+            .collect_vec();
+        assert_eq!(rebuilt_hnsw_configs.len(), 1);
+        assert_eq!(rebuilt_hnsw_configs[0].unwrap().m, hnsw_config.m * 2);
+    }
+}
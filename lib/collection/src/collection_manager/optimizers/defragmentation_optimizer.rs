@@ -0,0 +1,249 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use ordered_float::OrderedFloat;
+use parking_lot::Mutex;
+use segment::common::encryption::StorageCipher;
+use segment::common::operation_time_statistics::{
+    OperationDurationStatistics, OperationDurationsAggregator,
+};
+use segment::types::{HnswConfig, SegmentType};
+
+use crate::collection_manager::holders::segment_holder::{LockedSegmentHolder, SegmentId};
+use crate::collection_manager::optimizers::segment_optimizer::{
+    OptimizerThresholds, SegmentOptimizer,
+};
+use crate::config::CollectionParams;
+
+/// Optimizer which looks for segments, where most of the internal offsets belong to deleted points.
+///
+/// Deleted points keep their offsets, slots of the vector storages and bits of the deleted flags,
+/// until the segment is rebuilt. Segments with too few remaining points are not vacuumed,
+/// so after heavy deletes they would carry the tombstones until they are merged with others.
+/// Rebuilding compacts the offsets of the remaining points together with their versions.
+pub struct DefragmentationOptimizer {
+    deleted_threshold: f64,
+    min_vectors_number: usize,
+    thresholds_config: OptimizerThresholds,
+    segments_path: PathBuf,
+    collection_temp_dir: PathBuf,
+    collection_params: CollectionParams,
+    hnsw_config: HnswConfig,
+    storage_cipher: Option<StorageCipher>,
+    telemetry_durations_aggregator: Arc<Mutex<OperationDurationsAggregator>>,
+}
+
+impl DefragmentationOptimizer {
+    /// # Arguments
+    ///
+    /// * `deleted_threshold` - min fraction of the offsets of deleted points in a segment
+    /// * `min_vectors_number` - min number of offsets in a segment. Segments with at least
+    ///   this number of remaining points are left to the vacuum optimizer
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        deleted_threshold: f64,
+        min_vectors_number: usize,
+        thresholds_config: OptimizerThresholds,
+        segments_path: PathBuf,
+        collection_temp_dir: PathBuf,
+        collection_params: CollectionParams,
+        hnsw_config: HnswConfig,
+        storage_cipher: Option<StorageCipher>,
+    ) -> Self {
+        DefragmentationOptimizer {
+            deleted_threshold,
+            min_vectors_number,
+            thresholds_config,
+            segments_path,
+            collection_temp_dir,
+            collection_params,
+            hnsw_config,
+            storage_cipher,
+            telemetry_durations_aggregator: OperationDurationsAggregator::new(),
+        }
+    }
+
+    fn most_fragmented_segment(
+        &self,
+        segments: LockedSegmentHolder,
+        excluded_ids: &HashSet<SegmentId>,
+    ) -> Option<SegmentId> {
+        let segments_read_guard = segments.read();
+        segments_read_guard
+            .iter()
+            .filter(|(idx, _segment)| !excluded_ids.contains(idx))
+            .filter_map(|(idx, segment)| {
+                let segment_entry = segment.get();
+                let read_segment = segment_entry.read();
+                if read_segment.segment_type() == SegmentType::Special {
+                    return None;
+                }
+
+                let offsets_count = read_segment.point_offsets_count();
+                let points_count = read_segment.points_count();
+                let is_big = offsets_count >= self.min_vectors_number;
+                let is_vacuumed = points_count >= self.min_vectors_number;
+                if !is_big || is_vacuumed {
+                    return None;
+                }
+
+                let fragmented_ratio =
+                    offsets_count.saturating_sub(points_count) as f64 / offsets_count as f64;
+                (fragmented_ratio > self.deleted_threshold).then_some((*idx, fragmented_ratio))
+            })
+            .max_by_key(|(_idx, ratio)| OrderedFloat(*ratio))
+            .map(|(idx, _ratio)| idx)
+    }
+}
+
+impl SegmentOptimizer for DefragmentationOptimizer {
+    fn collection_path(&self) -> &Path {
+        self.segments_path.as_path()
+    }
+
+    fn temp_path(&self) -> &Path {
+        self.collection_temp_dir.as_path()
+    }
+
+    fn collection_params(&self) -> CollectionParams {
+        self.collection_params.clone()
+    }
+
+    fn hnsw_config(&self) -> HnswConfig {
+        self.hnsw_config
+    }
+
+    fn storage_cipher(&self) -> Option<StorageCipher> {
+        self.storage_cipher.clone()
+    }
+
+    fn threshold_config(&self) -> &OptimizerThresholds {
+        &self.thresholds_config
+    }
+
+    fn check_condition(
+        &self,
+        segments: LockedSegmentHolder,
+        excluded_ids: &HashSet<SegmentId>,
+    ) -> Vec<SegmentId> {
+        self.most_fragmented_segment(segments, excluded_ids)
+            .into_iter()
+            .collect()
+    }
+
+    fn get_telemetry_data(&self) -> OperationDurationStatistics {
+        self.get_telemetry_counter().lock().get_statistics()
+    }
+
+    fn get_telemetry_counter(&self) -> Arc<Mutex<OperationDurationsAggregator>> {
+        self.telemetry_durations_aggregator.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+
+    use itertools::Itertools;
+    use parking_lot::RwLock;
+    use tempfile::Builder;
+
+    use super::*;
+    use crate::collection_manager::fixtures::{get_indexing_optimizer, random_segment};
+    use crate::collection_manager::holders::segment_holder::SegmentHolder;
+    use crate::collection_manager::optimizers::vacuum_optimizer::VacuumOptimizer;
+
+    #[test]
+    fn test_defragment_after_heavy_deletes() {
+        let stopped = AtomicBool::new(false);
+        let dim = 4;
+
+        let segments_dir = Builder::new().prefix("segments_dir").tempdir().unwrap();
+        let segments_temp_dir = Builder::new()
+            .prefix("segments_temp_dir")
+            .tempdir()
+            .unwrap();
+
+        let mut holder = SegmentHolder::default();
+        let segment_id = holder.add(random_segment(segments_dir.path(), 100, 200, dim));
+        let segment = holder.get(segment_id).unwrap().clone();
+
+        let points = segment.get().read().iter_points().collect_vec();
+        let offsets_count = segment.get().read().point_offsets_count();
+        let (remaining, deleted) = points.split_at(10);
+        for &point_id in deleted {
+            segment.get().write().delete_point(101, point_id).unwrap();
+        }
+        assert_eq!(segment.get().read().point_offsets_count(), offsets_count);
+
+        let locked_holder: LockedSegmentHolder = Arc::new(RwLock::new(holder));
+
+        let indexing_optimizer =
+            get_indexing_optimizer(segments_dir.path(), segments_temp_dir.path(), dim);
+        let optimizer = |min_vectors_number| {
+            (
+                VacuumOptimizer::new(
+                    0.2,
+                    min_vectors_number,
+                    indexing_optimizer.threshold_config().clone(),
+                    segments_dir.path().to_owned(),
+                    segments_temp_dir.path().to_owned(),
+                    indexing_optimizer.collection_params(),
+                    indexing_optimizer.hnsw_config(),
+                    None,
+                ),
+                DefragmentationOptimizer::new(
+                    0.2,
+                    min_vectors_number,
+                    indexing_optimizer.threshold_config().clone(),
+                    segments_dir.path().to_owned(),
+                    segments_temp_dir.path().to_owned(),
+                    indexing_optimizer.collection_params(),
+                    indexing_optimizer.hnsw_config(),
+                    None,
+                ),
+            )
+        };
+        let excluded_ids = Default::default();
+
+        // Segment with enough remaining points is left to the vacuum optimizer
+        let (vacuum_optimizer, defragmentation_optimizer) = optimizer(5);
+        assert_eq!(
+            vacuum_optimizer
+                .check_condition(locked_holder.clone(), &excluded_ids)
+                .len(),
+            1
+        );
+        assert!(defragmentation_optimizer
+            .check_condition(locked_holder.clone(), &excluded_ids)
+            .is_empty());
+
+        // Vacuum ignores the segment with few remaining points, but it is defragmented
+        let (vacuum_optimizer, defragmentation_optimizer) = optimizer(100);
+        assert!(vacuum_optimizer
+            .check_condition(locked_holder.clone(), &excluded_ids)
+            .is_empty());
+        let suggested_to_optimize =
+            defragmentation_optimizer.check_condition(locked_holder.clone(), &excluded_ids);
+        assert_eq!(suggested_to_optimize, vec![segment_id]);
+
+        defragmentation_optimizer
+            .optimize(locked_holder.clone(), suggested_to_optimize, &stopped)
+            .unwrap();
+        assert!(defragmentation_optimizer
+            .check_condition(locked_holder.clone(), &excluded_ids)
+            .is_empty());
+
+        let holder_guard = locked_holder.read();
+        let (_idx, optimized_segment) = holder_guard.iter().exactly_one().ok().unwrap();
+        let optimized_segment = optimized_segment.get();
+        let optimized_segment = optimized_segment.read();
+        assert_eq!(optimized_segment.points_count(), remaining.len());
+        assert_eq!(optimized_segment.point_offsets_count(), remaining.len());
+        for &point_id in remaining {
+            assert!(optimized_segment.has_point(point_id));
+            assert_eq!(optimized_segment.point_version(point_id), Some(100));
+        }
+    }
+}
@@ -3,6 +3,7 @@ use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use parking_lot::Mutex;
+use segment::common::encryption::StorageCipher;
 use segment::common::operation_time_statistics::{
     OperationDurationStatistics, OperationDurationsAggregator,
 };
@@ -14,6 +15,7 @@ use crate::collection_manager::holders::segment_holder::{
 use crate::collection_manager::optimizers::segment_optimizer::{
     OptimizerThresholds, SegmentOptimizer,
 };
+use crate::common::optimization_scheduler::OptimizationPriority;
 use crate::config::CollectionParams;
 
 const BYTES_IN_KB: usize = 1024;
@@ -28,6 +30,7 @@ pub struct IndexingOptimizer {
     collection_temp_dir: PathBuf,
     collection_params: CollectionParams,
     hnsw_config: HnswConfig,
+    storage_cipher: Option<StorageCipher>,
     telemetry_durations_aggregator: Arc<Mutex<OperationDurationsAggregator>>,
 }
 
@@ -38,6 +41,7 @@ impl IndexingOptimizer {
         collection_temp_dir: PathBuf,
         collection_params: CollectionParams,
         hnsw_config: HnswConfig,
+        storage_cipher: Option<StorageCipher>,
     ) -> Self {
         IndexingOptimizer {
             thresholds_config,
@@ -45,6 +49,7 @@ impl IndexingOptimizer {
             collection_temp_dir,
             collection_params,
             hnsw_config,
+            storage_cipher,
             telemetry_durations_aggregator: OperationDurationsAggregator::new(),
         }
     }
@@ -154,8 +159,10 @@ impl IndexingOptimizer {
                         .indexing_threshold
                         .saturating_mul(BYTES_IN_KB);
 
-                let require_indexing =
-                    (big_for_mmap && !is_memmaped) || (big_for_index && !is_vector_indexed);
+                // Segments with indexes missing on load are rebuilt regardless of the size
+                let require_indexing = (big_for_mmap && !is_memmaped)
+                    || (big_for_index && !is_vector_indexed)
+                    || read_segment.has_missing_indexes();
 
                 match require_indexing {
                     true => Some((*idx, vector_size)),
@@ -224,6 +231,10 @@ impl SegmentOptimizer for IndexingOptimizer {
         self.hnsw_config
     }
 
+    fn storage_cipher(&self) -> Option<StorageCipher> {
+        self.storage_cipher.clone()
+    }
+
     fn threshold_config(&self) -> &OptimizerThresholds {
         &self.thresholds_config
     }
@@ -240,6 +251,11 @@ impl SegmentOptimizer for IndexingOptimizer {
         self.get_telemetry_counter().lock().get_statistics()
     }
 
+    /// Building of vector index takes much longer than other optimizations
+    fn priority(&self) -> OptimizationPriority {
+        OptimizationPriority::Low
+    }
+
     fn get_telemetry_counter(&self) -> Arc<Mutex<OperationDurationsAggregator>> {
         self.telemetry_durations_aggregator.clone()
     }
@@ -308,6 +324,11 @@ mod tests {
                     VectorParams {
                         size: NonZeroU64::new(params.size as u64).unwrap(),
                         distance: params.distance,
+                        hnsw_config: None,
+                        quantization_config: None,
+                        on_disk: None,
+                        datatype: None,
+                        multivector_config: None,
                     },
                 )
             })
@@ -327,8 +348,10 @@ mod tests {
                 replication_factor: NonZeroU32::new(1).unwrap(),
                 write_consistency_factor: NonZeroU32::new(1).unwrap(),
                 on_disk_payload: false,
+                default_vector: None,
             },
             Default::default(),
+            None,
         );
         let locked_holder: Arc<RwLock<_, _>> = Arc::new(RwLock::new(holder));
 
@@ -422,13 +445,20 @@ mod tests {
                     )
                     .unwrap(),
                     distance: segment_config.vector_data[DEFAULT_VECTOR_NAME].distance,
+                    hnsw_config: None,
+                    quantization_config: None,
+                    on_disk: None,
+                    datatype: None,
+                    multivector_config: None,
                 }),
                 shard_number: NonZeroU32::new(1).unwrap(),
                 replication_factor: NonZeroU32::new(1).unwrap(),
                 write_consistency_factor: NonZeroU32::new(1).unwrap(),
                 on_disk_payload: false,
+                default_vector: None,
             },
             Default::default(),
+            None,
         );
 
         let locked_holder: Arc<RwLock<_, _>> = Arc::new(RwLock::new(holder));
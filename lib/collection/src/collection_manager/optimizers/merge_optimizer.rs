@@ -4,6 +4,7 @@ use std::sync::Arc;
 
 use itertools::Itertools;
 use parking_lot::Mutex;
+use segment::common::encryption::StorageCipher;
 use segment::common::operation_time_statistics::{
     OperationDurationStatistics, OperationDurationsAggregator,
 };
@@ -30,6 +31,7 @@ pub struct MergeOptimizer {
     collection_temp_dir: PathBuf,
     collection_params: CollectionParams,
     hnsw_config: HnswConfig,
+    storage_cipher: Option<StorageCipher>,
     telemetry_durations_aggregator: Arc<Mutex<OperationDurationsAggregator>>,
 }
 
@@ -42,6 +44,7 @@ impl MergeOptimizer {
         collection_temp_dir: PathBuf,
         collection_params: CollectionParams,
         hnsw_config: HnswConfig,
+        storage_cipher: Option<StorageCipher>,
     ) -> Self {
         MergeOptimizer {
             max_segments,
@@ -50,6 +53,7 @@ impl MergeOptimizer {
             collection_temp_dir,
             collection_params,
             hnsw_config,
+            storage_cipher,
             telemetry_durations_aggregator: OperationDurationsAggregator::new(),
         }
     }
@@ -72,6 +76,10 @@ impl SegmentOptimizer for MergeOptimizer {
         self.hnsw_config
     }
 
+    fn storage_cipher(&self) -> Option<StorageCipher> {
+        self.storage_cipher.clone()
+    }
+
     fn threshold_config(&self) -> &OptimizerThresholds {
         &self.thresholds_config
     }
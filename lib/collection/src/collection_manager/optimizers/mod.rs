@@ -1,3 +1,5 @@
+pub mod config_mismatch_optimizer;
+pub mod defragmentation_optimizer;
 pub mod indexing_optimizer;
 pub mod merge_optimizer;
 pub mod segment_optimizer;
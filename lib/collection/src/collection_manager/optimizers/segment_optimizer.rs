@@ -5,27 +5,37 @@ use std::sync::Arc;
 
 use itertools::Itertools;
 use parking_lot::{Mutex, RwLock, RwLockUpgradableReadGuard};
+use segment::common::encryption::StorageCipher;
 use segment::common::operation_time_statistics::{
     OperationDurationStatistics, OperationDurationsAggregator, ScopeDurationMeasurer,
 };
 use segment::entry::entry_point::SegmentEntry;
 use segment::segment::Segment;
-use segment::segment_constructor::build_segment;
+use segment::segment_constructor::build_segment_with_cipher;
 use segment::segment_constructor::segment_builder::SegmentBuilder;
 use segment::types::{
     HnswConfig, Indexes, PayloadFieldSchema, PayloadKeyType, PayloadStorageType, PointIdType,
-    SegmentConfig, StorageType, VECTOR_ELEMENT_SIZE,
+    SegmentConfig, SeqNumberType, StorageType, VECTOR_ELEMENT_SIZE,
 };
 
 use crate::collection_manager::holders::proxy_segment::ProxySegment;
 use crate::collection_manager::holders::segment_holder::{
     LockedSegment, LockedSegmentHolder, SegmentId,
 };
+use crate::common::optimization_scheduler::OptimizationPriority;
 use crate::config::CollectionParams;
+use crate::operations::point_ops::PointStruct;
 use crate::operations::types::{CollectionError, CollectionResult};
 
 const BYTES_IN_KB: usize = 1024;
 
+/// Estimated size of the vectors of the segment, compared with the size thresholds of optimizers
+fn segment_vectors_size(segment: &dyn SegmentEntry) -> usize {
+    segment.points_count()
+        * segment.vector_dims().values().max().copied().unwrap_or(0)
+        * VECTOR_ELEMENT_SIZE
+}
+
 #[derive(Debug, Clone)]
 pub struct OptimizerThresholds {
     pub max_segment_size: usize,
@@ -54,6 +64,9 @@ pub trait SegmentOptimizer {
     /// Get HNSW config
     fn hnsw_config(&self) -> HnswConfig;
 
+    /// Get cipher for payloads and vectors of optimized segments, if the collection is encrypted
+    fn storage_cipher(&self) -> Option<StorageCipher>;
+
     /// Get thresholds configuration for the current optimizer
     fn threshold_config(&self) -> &OptimizerThresholds;
 
@@ -66,26 +79,77 @@ pub trait SegmentOptimizer {
 
     fn get_telemetry_data(&self) -> OperationDurationStatistics;
 
+    /// Priority of the optimization among all optimizations of the node
+    fn priority(&self) -> OptimizationPriority {
+        OptimizationPriority::High
+    }
+
     fn get_telemetry_counter(&self) -> Arc<Mutex<OperationDurationsAggregator>>;
 
-    /// Build temp segment
-    fn temp_segment(&self) -> CollectionResult<LockedSegment> {
+    /// Config of the plain segment, which accepts updates during the optimization
+    fn temp_segment_config(&self) -> CollectionResult<SegmentConfig> {
         let collection_params = self.collection_params();
-        let config = SegmentConfig {
-            vector_data: collection_params.get_all_vector_params()?,
+        Ok(SegmentConfig {
+            vector_data: collection_params.get_all_vector_params(&self.hnsw_config())?,
             index: Indexes::Plain {},
             storage_type: StorageType::InMemory,
             payload_storage_type: match collection_params.on_disk_payload {
                 true => PayloadStorageType::OnDisk,
                 false => PayloadStorageType::InMemory,
             },
-        };
-        Ok(LockedSegment::new(build_segment(
+        })
+    }
+
+    /// Build temp segment
+    fn temp_segment(&self) -> CollectionResult<LockedSegment> {
+        Ok(LockedSegment::new(build_segment_with_cipher(
             self.collection_path(),
-            &config,
+            &self.temp_segment_config()?,
+            self.storage_cipher(),
         )?))
     }
 
+    /// Build segment directly from the points of a bulk load, bypassing the appendable segments
+    /// and the following optimizations.
+    /// Points are written into a plain segment in the temp directory, which is then rebuilt
+    /// with the same config as the optimized segments of the same size.
+    fn build_bulk_segment(
+        &self,
+        points: &[PointStruct],
+        op_num: SeqNumberType,
+        indexed_fields: &HashMap<PayloadKeyType, PayloadFieldSchema>,
+        stopped: &AtomicBool,
+    ) -> CollectionResult<Segment> {
+        let mut input_segment = build_segment_with_cipher(
+            self.temp_path(),
+            &self.temp_segment_config()?,
+            self.storage_cipher(),
+        )?;
+
+        let build = |input_segment: &mut Segment| -> CollectionResult<Segment> {
+            for point in points {
+                input_segment.upsert_vector(op_num, point.id, &point.get_vectors())?;
+                if let Some(payload) = &point.payload {
+                    input_segment.set_full_payload(op_num, point.id, payload)?;
+                }
+            }
+            self.check_cancellation(stopped)?;
+
+            let mut segment_builder =
+                self.sized_segment_builder(segment_vectors_size(&*input_segment))?;
+            segment_builder.update_from(input_segment, stopped)?;
+            segment_builder.indexed_fields.extend(
+                indexed_fields
+                    .iter()
+                    .map(|(field, schema)| (field.clone(), schema.clone())),
+            );
+            Ok(segment_builder.build(stopped)?)
+        };
+        let result = build(&mut input_segment);
+        input_segment.drop_data()?;
+        result
+    }
+
     /// Build optimized segment
     fn optimized_segment_builder(
         &self,
@@ -93,31 +157,27 @@ pub trait SegmentOptimizer {
     ) -> CollectionResult<SegmentBuilder> {
         let total_vectors_size: usize = optimizing_segments
             .iter()
-            .map(|s| {
-                let segment = s.get();
-                let locked_segment = segment.read();
-                locked_segment.points_count()
-                    * locked_segment
-                        .vector_dims()
-                        .values()
-                        .max()
-                        .copied()
-                        .unwrap_or(0)
-                    * VECTOR_ELEMENT_SIZE
-            })
+            .map(|s| segment_vectors_size(&*s.get().read()))
             .sum();
+        self.sized_segment_builder(total_vectors_size)
+    }
 
+    /// Builder of the segment with the given size of vectors, the size defines
+    /// if the segment is indexed and stored on disk
+    fn sized_segment_builder(&self, total_vectors_size: usize) -> CollectionResult<SegmentBuilder> {
         let thresholds = self.threshold_config();
         let collection_params = self.collection_params();
 
         let is_indexed =
             total_vectors_size >= thresholds.indexing_threshold.saturating_mul(BYTES_IN_KB);
 
-        let is_on_disk =
-            total_vectors_size >= thresholds.memmap_threshold.saturating_mul(BYTES_IN_KB);
+        // Mmap vector storage can't be encrypted
+        let is_on_disk = total_vectors_size
+            >= thresholds.memmap_threshold.saturating_mul(BYTES_IN_KB)
+            || (collection_params.vectors_on_disk() && self.storage_cipher().is_none());
 
         let optimized_config = SegmentConfig {
-            vector_data: collection_params.get_all_vector_params()?,
+            vector_data: collection_params.get_all_vector_params(&self.hnsw_config())?,
             index: if is_indexed {
                 Indexes::Hnsw(self.hnsw_config())
             } else {
@@ -134,10 +194,11 @@ pub trait SegmentOptimizer {
             },
         };
 
-        Ok(SegmentBuilder::new(
+        Ok(SegmentBuilder::new_with_cipher(
             self.collection_path(),
             self.temp_path(),
             &optimized_config,
+            self.storage_cipher(),
         )?)
     }
 
@@ -4,6 +4,7 @@ use std::sync::Arc;
 
 use ordered_float::OrderedFloat;
 use parking_lot::Mutex;
+use segment::common::encryption::StorageCipher;
 use segment::common::operation_time_statistics::{
     OperationDurationStatistics, OperationDurationsAggregator,
 };
@@ -27,6 +28,7 @@ pub struct VacuumOptimizer {
     collection_temp_dir: PathBuf,
     collection_params: CollectionParams,
     hnsw_config: HnswConfig,
+    storage_cipher: Option<StorageCipher>,
     telemetry_durations_aggregator: Arc<Mutex<OperationDurationsAggregator>>,
 }
 
@@ -40,6 +42,7 @@ impl VacuumOptimizer {
         collection_temp_dir: PathBuf,
         collection_params: CollectionParams,
         hnsw_config: HnswConfig,
+        storage_cipher: Option<StorageCipher>,
     ) -> Self {
         VacuumOptimizer {
             deleted_threshold,
@@ -49,6 +52,7 @@ impl VacuumOptimizer {
             collection_temp_dir,
             collection_params,
             hnsw_config,
+            storage_cipher,
             telemetry_durations_aggregator: OperationDurationsAggregator::new(),
         }
     }
@@ -104,6 +108,10 @@ impl SegmentOptimizer for VacuumOptimizer {
         self.hnsw_config
     }
 
+    fn storage_cipher(&self) -> Option<StorageCipher> {
+        self.storage_cipher.clone()
+    }
+
     fn threshold_config(&self) -> &OptimizerThresholds {
         &self.thresholds_config
     }
@@ -219,13 +227,20 @@ mod tests {
                 vectors: VectorsConfig::Single(VectorParams {
                     size: NonZeroU64::new(4).unwrap(),
                     distance: Distance::Dot,
+                    hnsw_config: None,
+                    quantization_config: None,
+                    on_disk: None,
+                    datatype: None,
+                    multivector_config: None,
                 }),
                 shard_number: NonZeroU32::new(1).unwrap(),
                 on_disk_payload: false,
+                default_vector: None,
                 replication_factor: NonZeroU32::new(1).unwrap(),
                 write_consistency_factor: NonZeroU32::new(1).unwrap(),
             },
             Default::default(),
+            None,
         );
 
         let suggested_to_optimize =
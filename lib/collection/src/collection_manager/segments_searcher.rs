@@ -1,5 +1,6 @@
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::time::Instant;
 
 use futures::future::try_join_all;
 use itertools::Itertools;
@@ -9,13 +10,15 @@ use segment::data_types::vectors::VectorElementType;
 use segment::entry::entry_point::OperationError;
 use segment::spaces::tools::peek_top_largest_iterable;
 use segment::types::{
-    Filter, PointIdType, ScoredPoint, SearchParams, SeqNumberType, WithPayload,
-    WithPayloadInterface, WithVector,
+    BatchSearchResult, Filter, PointIdType, ScoreType, ScoredPoint, SearchExplanation,
+    SearchParams, SeqNumberType, WithPayload, WithPayloadInterface, WithVector,
 };
 use tokio::runtime::Handle;
 
 use crate::collection_manager::holders::segment_holder::{LockedSegment, SegmentHolder};
-use crate::operations::types::{CollectionResult, Record, SearchRequestBatch};
+use crate::operations::types::{
+    CollectionResult, Record, SearchRequestBatch, ShardDiscoverRequest,
+};
 
 /// Simple implementation of segment manager
 ///  - rebuild segment for memory optimization purposes
@@ -23,11 +26,13 @@ use crate::operations::types::{CollectionResult, Record, SearchRequestBatch};
 pub struct SegmentsSearcher {}
 
 impl SegmentsSearcher {
+    /// Search in all segments concurrently, approximate searches are interrupted at `deadline`
     pub async fn search(
         segments: &RwLock<SegmentHolder>,
         request: Arc<SearchRequestBatch>,
         runtime_handle: &Handle,
-    ) -> CollectionResult<Vec<Vec<ScoredPoint>>> {
+        deadline: Option<Instant>,
+    ) -> CollectionResult<BatchSearchResult> {
         // Using { } block to ensure segments variable is dropped in the end of it
         // and is not transferred across the all_searches.await? boundary as it
         // does not impl Send trait
@@ -37,19 +42,19 @@ impl SegmentsSearcher {
             let some_segment = segments.iter().next();
 
             if some_segment.is_none() {
-                return Ok(vec![]);
+                return Ok(BatchSearchResult::default());
             }
 
             segments
                 .iter()
-                .map(|(_id, segment)| search_in_segment(segment.clone(), request.clone()))
+                .map(|(_id, segment)| search_in_segment(segment.clone(), request.clone(), deadline))
                 .map(|f| runtime_handle.spawn(f))
                 .collect()
         };
 
         // perform search on all segments concurrently
         let all_searches = try_join_all(searches);
-        let all_search_results: Vec<CollectionResult<Vec<Vec<ScoredPoint>>>> = all_searches.await?;
+        let all_search_results: Vec<CollectionResult<BatchSearchResult>> = all_searches.await?;
 
         match all_search_results
             .iter()
@@ -61,9 +66,14 @@ impl SegmentsSearcher {
         }
 
         let mut merged_results: Vec<Vec<ScoredPoint>> = vec![vec![]; request.searches.len()];
+        let mut truncated = false;
+        let mut explanation = SearchExplanation::default();
         for segment_result in all_search_results {
             let segment_result = segment_result.unwrap();
-            for (idx, query_res) in segment_result.into_iter().enumerate() {
+            truncated |= segment_result.truncated;
+            explanation.merge(&segment_result.explanation);
+            explanation.segments += 1;
+            for (idx, query_res) in segment_result.results.into_iter().enumerate() {
                 merged_results[idx].extend(query_res);
             }
         }
@@ -88,7 +98,42 @@ impl SegmentsSearcher {
             })
             .collect();
 
-        Ok(top_scores)
+        Ok(BatchSearchResult {
+            results: top_scores,
+            truncated,
+            explanation,
+        })
+    }
+
+    /// Discovery search in all segments concurrently,
+    /// see [`segment::entry::entry_point::SegmentEntry::discover`]
+    pub async fn discover(
+        segments: &RwLock<SegmentHolder>,
+        request: Arc<ShardDiscoverRequest>,
+        runtime_handle: &Handle,
+    ) -> CollectionResult<Vec<ScoredPoint>> {
+        let searches: Vec<_> = {
+            let segments = segments.read();
+            segments
+                .iter()
+                .map(|(_id, segment)| discover_in_segment(segment.clone(), request.clone()))
+                .map(|f| runtime_handle.spawn(f))
+                .collect()
+        };
+
+        let mut all_points = vec![];
+        for segment_result in try_join_all(searches).await? {
+            all_points.extend(segment_result?);
+        }
+
+        let mut seen_idx: HashSet<PointIdType> = HashSet::new();
+        Ok(peek_top_largest_iterable(
+            all_points
+                .into_iter()
+                .sorted_by_key(|a| (a.id, 1 - a.version as i64)) // Prefer higher version first
+                .filter(|scored| seen_idx.insert(scored.id)),
+            request.limit,
+        ))
     }
 
     pub async fn retrieve(
@@ -133,6 +178,7 @@ impl SegmentsSearcher {
                                 Some(selected_vectors.into())
                             }
                         },
+                        order_value: None,
                     },
                 );
                 point_version.insert(id, version);
@@ -151,14 +197,16 @@ struct BatchSearchParams<'a> {
     pub with_vector: WithVector,
     pub top: usize,
     pub params: Option<&'a SearchParams>,
+    pub score_threshold: Option<ScoreType>,
 }
 
 /// Process sequentially contiguous batches
 async fn search_in_segment(
     segment: LockedSegment,
     request: Arc<SearchRequestBatch>,
-) -> CollectionResult<Vec<Vec<ScoredPoint>>> {
-    let mut result: Vec<Vec<ScoredPoint>> = vec![];
+    deadline: Option<Instant>,
+) -> CollectionResult<BatchSearchResult> {
+    let mut result = BatchSearchResult::default();
     let mut vectors_batch: Vec<&[VectorElementType]> = vec![];
     let mut prev_params = BatchSearchParams::default();
 
@@ -175,6 +223,7 @@ async fn search_in_segment(
             with_vector: search_query.with_vector.clone().unwrap_or_default(),
             top: search_query.limit + search_query.offset,
             params: search_query.params.as_ref(),
+            score_threshold: search_query.score_threshold,
         };
 
         // same params enables batching
@@ -192,8 +241,12 @@ async fn search_in_segment(
                     prev_params.filter,
                     prev_params.top,
                     prev_params.params,
+                    prev_params.score_threshold,
+                    deadline,
                 )?;
-                result.append(&mut res);
+                result.results.append(&mut res.results);
+                result.truncated |= res.truncated;
+                result.explanation.merge(&res.explanation);
                 // clear current batch
                 vectors_batch.clear();
             }
@@ -213,13 +266,37 @@ async fn search_in_segment(
             prev_params.filter,
             prev_params.top,
             prev_params.params,
+            prev_params.score_threshold,
+            deadline,
         )?;
-        result.append(&mut res);
+        result.results.append(&mut res.results);
+        result.truncated |= res.truncated;
+        result.explanation.merge(&res.explanation);
     }
 
     Ok(result)
 }
 
+async fn discover_in_segment(
+    segment: LockedSegment,
+    request: Arc<ShardDiscoverRequest>,
+) -> CollectionResult<Vec<ScoredPoint>> {
+    let with_payload_interface = request
+        .with_payload
+        .as_ref()
+        .unwrap_or(&WithPayloadInterface::Bool(false));
+    let points = segment.get().read().discover(
+        &request.vector_name,
+        &request.query,
+        &WithPayload::from(with_payload_interface),
+        &request.with_vector,
+        request.filter.as_ref(),
+        request.limit,
+        request.params.as_ref(),
+    )?;
+    Ok(points)
+}
+
 #[cfg(test)]
 mod tests {
     use tempfile::Builder;
@@ -251,13 +328,18 @@ mod tests {
             searches: vec![req],
         };
 
-        let result =
-            SegmentsSearcher::search(&segment_holder, Arc::new(batch_request), &Handle::current())
-                .await
-                .unwrap()
-                .into_iter()
-                .next()
-                .unwrap();
+        let result = SegmentsSearcher::search(
+            &segment_holder,
+            Arc::new(batch_request),
+            &Handle::current(),
+            None,
+        )
+        .await
+        .unwrap()
+        .results
+        .into_iter()
+        .next()
+        .unwrap();
 
         // eprintln!("result = {:?}", &result);
 
@@ -3,6 +3,7 @@ use std::collections::{HashMap, HashSet};
 use itertools::Itertools;
 use parking_lot::{RwLock, RwLockWriteGuard};
 use segment::data_types::named_vectors::NamedVectors;
+use segment::data_types::payload_update::PayloadUpdate;
 use segment::entry::entry_point::{OperationResult, SegmentEntry};
 use segment::types::{
     Filter, Payload, PayloadFieldSchema, PayloadKeyType, PayloadKeyTypeRef, PointIdType,
@@ -85,6 +86,21 @@ pub(crate) fn delete_payload(
     Ok(updated_points.len())
 }
 
+pub(crate) fn update_payload(
+    segments: &SegmentHolder,
+    op_num: SeqNumberType,
+    points: &[PointIdType],
+    updates: &[PayloadUpdate],
+) -> CollectionResult<usize> {
+    let updated_points =
+        segments.apply_points_to_appendable(op_num, points, |id, write_segment| {
+            write_segment.update_payload(op_num, id, updates)
+        })?;
+
+    check_unprocessed_points(points, &updated_points)?;
+    Ok(updated_points.len())
+}
+
 pub(crate) fn clear_payload(
     segments: &SegmentHolder,
     op_num: SeqNumberType,
@@ -367,6 +383,9 @@ pub(crate) fn process_payload_operation(
         PayloadOps::DeletePayload(dp) => {
             delete_payload(&segments.read(), op_num, &dp.points, &dp.keys)
         }
+        PayloadOps::UpdatePayload(up) => {
+            update_payload(&segments.read(), op_num, &up.points, &up.updates)
+        }
         PayloadOps::ClearPayload { ref points, .. } => {
             clear_payload(&segments.read(), op_num, points)
         }
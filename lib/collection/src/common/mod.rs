@@ -1,2 +1,3 @@
+pub mod optimization_scheduler;
 pub mod stoppable_task;
 pub mod stoppable_task_async;
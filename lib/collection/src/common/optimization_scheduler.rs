@@ -0,0 +1,349 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use tokio::runtime::{self, Handle, Runtime};
+use tokio::sync::oneshot;
+
+use crate::common::stoppable_task::{spawn_stoppable_after, StoppableTaskHandle};
+
+/// Number of threads, which wait for optimization permits and start optimizations.
+/// Optimizations themselves are executed in blocking threads of the same runtime.
+const OPTIMIZATION_RUNTIME_WORKER_THREADS: usize = 2;
+
+/// Priority of the optimization. Among waiting optimizations, the one with the highest
+/// priority is started first, optimizations of the same priority are started in order of arrival.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum OptimizationPriority {
+    /// Long-running optimizations, e.g. building of vector index
+    Low,
+    /// Fast optimizations, which reduce the number of segments or remove deleted points
+    High,
+}
+
+/// Waiting optimizations are ordered by priority (highest first), then by arrival
+type Ticket = (Reverse<OptimizationPriority>, u64);
+
+#[derive(Default)]
+struct OptimizationQueue {
+    running: usize,
+    /// Waiting optimizations are notified, once they are allowed to run
+    waiting: BTreeMap<Ticket, oneshot::Sender<()>>,
+    next_ticket: u64,
+}
+
+/// Limits the number of simultaneously running optimizations.
+/// Works like an async semaphore, which grants permits in order of priority.
+struct OptimizationPermits {
+    /// Could be changed at runtime, running optimizations are not interrupted by decreasing it
+    max_running: AtomicUsize,
+    queue: Mutex<OptimizationQueue>,
+}
+
+impl OptimizationPermits {
+    /// Wait until it is allowed to start the optimization.
+    /// If the future is dropped while waiting, its place in the queue is released.
+    async fn acquire(self: Arc<Self>, priority: OptimizationPriority) -> OptimizationPermit {
+        let (ticket, receiver) = {
+            let mut queue = self.queue.lock();
+            if queue.waiting.is_empty() && queue.running < self.max_running() {
+                queue.running += 1;
+                return OptimizationPermit { permits: self };
+            }
+            let ticket = (Reverse(priority), queue.next_ticket);
+            queue.next_ticket += 1;
+            let (sender, receiver) = oneshot::channel();
+            queue.waiting.insert(ticket, sender);
+            (ticket, receiver)
+        };
+
+        let mut waiting = WaitingTicket {
+            permits: self.clone(),
+            ticket,
+            granted: false,
+        };
+        // Sender is only removed from the queue by `release`, after sending,
+        // or by `WaitingTicket`, after the receiver is no longer awaited
+        let _ = receiver.await;
+        waiting.granted = true;
+        OptimizationPermit { permits: self }
+    }
+
+    fn max_running(&self) -> usize {
+        self.max_running.load(Ordering::Relaxed)
+    }
+
+    fn set_max_running(&self, max_running: usize) {
+        let mut queue = self.queue.lock();
+        self.max_running.store(max_running, Ordering::Relaxed);
+        self.grant_waiting(&mut queue);
+    }
+
+    /// Free the slot of a finished optimization and pass it to the next waiting ones
+    fn release(&self, queue: &mut OptimizationQueue) {
+        queue.running -= 1;
+        self.grant_waiting(queue);
+    }
+
+    /// Pass free slots to the waiting optimizations in order of priority
+    fn grant_waiting(&self, queue: &mut OptimizationQueue) {
+        while queue.running < self.max_running() {
+            let ticket = match queue.waiting.keys().next() {
+                Some(ticket) => *ticket,
+                None => break,
+            };
+            let sender = queue.waiting.remove(&ticket).unwrap();
+            queue.running += 1;
+            let _ = sender.send(());
+        }
+    }
+}
+
+/// Place of the optimization in the queue, while it waits for a permit
+struct WaitingTicket {
+    permits: Arc<OptimizationPermits>,
+    ticket: Ticket,
+    granted: bool,
+}
+
+impl Drop for WaitingTicket {
+    fn drop(&mut self) {
+        if self.granted {
+            return;
+        }
+        let mut queue = self.permits.queue.lock();
+        if queue.waiting.remove(&self.ticket).is_none() {
+            // Slot was already passed to this optimization, but it is cancelled
+            self.permits.release(&mut queue);
+        }
+    }
+}
+
+struct OptimizationPermit {
+    permits: Arc<OptimizationPermits>,
+}
+
+impl Drop for OptimizationPermit {
+    fn drop(&mut self) {
+        let mut queue = self.permits.queue.lock();
+        self.permits.release(&mut queue);
+    }
+}
+
+/// Schedules optimizations of all collections of the node on a dedicated runtime.
+///
+/// The number of simultaneously running optimizations is limited globally, so a burst of
+/// optimizations (e.g. indexing of a newly created collection) can't take all CPUs
+/// and starve search threads.
+///
+/// Update and flush workers of shards run on a separate runtime, so they are not delayed
+/// by scheduling of optimizations.
+#[derive(Clone)]
+pub struct OptimizationScheduler {
+    optimization_runtime_handle: Handle,
+    update_runtime_handle: Handle,
+    permits: Arc<OptimizationPermits>,
+}
+
+impl OptimizationScheduler {
+    /// Create scheduler, which runs optimizations on the runtime of `optimization_runtime_handle`
+    ///
+    /// # Arguments
+    ///
+    /// * `update_runtime_handle` - runtime for update and flush workers of shards
+    /// * `max_optimization_threads` - max number of simultaneously running optimizations. If 0 - auto selection.
+    pub fn new(
+        optimization_runtime_handle: Handle,
+        update_runtime_handle: Handle,
+        max_optimization_threads: usize,
+    ) -> Self {
+        Self {
+            optimization_runtime_handle,
+            update_runtime_handle,
+            permits: Arc::new(OptimizationPermits {
+                max_running: AtomicUsize::new(optimization_threads(max_optimization_threads)),
+                queue: Default::default(),
+            }),
+        }
+    }
+
+    /// Runtime for the update and flush loops of shards
+    pub fn update_runtime_handle(&self) -> &Handle {
+        &self.update_runtime_handle
+    }
+
+    pub fn max_optimization_threads(&self) -> usize {
+        self.permits.max_running()
+    }
+
+    /// Change the max number of simultaneously running optimizations. If 0 - auto selection.
+    /// Waiting optimizations are started immediately, if the limit is increased.
+    pub fn set_max_optimization_threads(&self, max_optimization_threads: usize) {
+        self.permits
+            .set_max_running(optimization_threads(max_optimization_threads));
+    }
+
+    /// Number of optimizations, which are waiting for a free thread
+    pub fn waiting_count(&self) -> usize {
+        self.permits.queue.lock().waiting.len()
+    }
+
+    /// Spawn blocking optimization task.
+    /// The task waits asynchronously until it is allowed to run. If it is stopped while waiting,
+    /// `T::default()` is returned without running it.
+    pub fn spawn<F, T>(&self, priority: OptimizationPriority, f: F) -> StoppableTaskHandle<T>
+    where
+        F: FnOnce(&AtomicBool) -> T,
+        F: Send + 'static,
+        T: Send + Default + 'static,
+    {
+        let permit = self.permits.clone().acquire(priority);
+        spawn_stoppable_after(&self.optimization_runtime_handle, permit, f)
+    }
+}
+
+/// Resolve number of optimization threads. If 0 - use all CPUs except one, reserved for search.
+fn optimization_threads(max_optimization_threads: usize) -> usize {
+    if max_optimization_threads == 0 {
+        std::cmp::max(1, num_cpus::get().saturating_sub(1))
+    } else {
+        max_optimization_threads
+    }
+}
+
+/// Create dedicated runtime for optimizations, shared by all collections
+pub fn create_optimization_runtime() -> std::io::Result<Runtime> {
+    runtime::Builder::new_multi_thread()
+        .worker_threads(OPTIMIZATION_RUNTIME_WORKER_THREADS)
+        .enable_time()
+        .thread_name_fn(|| {
+            static ATOMIC_ID: AtomicUsize = AtomicUsize::new(0);
+            let id = ATOMIC_ID.fetch_add(1, Ordering::SeqCst);
+            format!("optimizer-{id}")
+        })
+        .build()
+}
+
+/// Create dedicated runtime for update and flush workers, shared by all collections.
+/// Updates are applied to segments synchronously, so it uses a thread per CPU.
+pub fn create_update_runtime() -> std::io::Result<Runtime> {
+    runtime::Builder::new_multi_thread()
+        .worker_threads(num_cpus::get().max(2))
+        .enable_time()
+        .thread_name_fn(|| {
+            static ATOMIC_ID: AtomicUsize = AtomicUsize::new(0);
+            let id = ATOMIC_ID.fetch_add(1, Ordering::SeqCst);
+            format!("update-{id}")
+        })
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_max_running_optimizations() {
+        let scheduler = OptimizationScheduler::new(Handle::current(), Handle::current(), 2);
+        let running = Arc::new(AtomicUsize::new(0));
+        let max_running = Arc::new(AtomicUsize::new(0));
+
+        let handles: Vec<_> = (0..6)
+            .map(|_| {
+                let running = running.clone();
+                let max_running = max_running.clone();
+                scheduler.spawn(OptimizationPriority::Low, move |_stopped| {
+                    let now_running = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_running.fetch_max(now_running, Ordering::SeqCst);
+                    thread::sleep(Duration::from_millis(20));
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    true
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            assert!(handle.join_handle.await.unwrap());
+        }
+        assert_eq!(max_running.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_set_max_optimization_threads() {
+        let scheduler = OptimizationScheduler::new(Handle::current(), Handle::current(), 1);
+        let started = Arc::new(AtomicUsize::new(0));
+        let finish = Arc::new(AtomicBool::new(false));
+
+        let handles: Vec<_> = (0..3)
+            .map(|_| {
+                let started = started.clone();
+                let finish = finish.clone();
+                scheduler.spawn(OptimizationPriority::Low, move |_stopped| {
+                    started.fetch_add(1, Ordering::SeqCst);
+                    while !finish.load(Ordering::SeqCst) {
+                        thread::sleep(Duration::from_millis(1));
+                    }
+                    true
+                })
+            })
+            .collect();
+        while scheduler.waiting_count() < 2 {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+
+        // Waiting optimizations are started, once the limit is increased
+        scheduler.set_max_optimization_threads(3);
+        assert_eq!(scheduler.max_optimization_threads(), 3);
+        while started.load(Ordering::SeqCst) < 3 {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        assert_eq!(scheduler.waiting_count(), 0);
+
+        finish.store(true, Ordering::SeqCst);
+        for handle in handles {
+            assert!(handle.join_handle.await.unwrap());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_priority_and_stop() {
+        let scheduler = OptimizationScheduler::new(Handle::current(), Handle::current(), 1);
+        let order = Arc::new(Mutex::new(vec![]));
+
+        let spawn = |priority, name: &'static str| {
+            let order = order.clone();
+            scheduler.spawn(priority, move |_stopped| {
+                order.lock().push(name);
+                thread::sleep(Duration::from_millis(100));
+                true
+            })
+        };
+
+        let first = spawn(OptimizationPriority::Low, "first");
+        while order.lock().is_empty() {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        let low = spawn(OptimizationPriority::Low, "low");
+        let stopped = spawn(OptimizationPriority::High, "stopped");
+        while scheduler.waiting_count() < 2 {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        let high = spawn(OptimizationPriority::High, "high");
+        while scheduler.waiting_count() < 3 {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+
+        // Stopped optimization is not started
+        assert!(!stopped.stop().await.unwrap());
+
+        for handle in [first, low, high] {
+            assert!(handle.join_handle.await.unwrap());
+        }
+        assert_eq!(*order.lock(), vec!["first", "high", "low"]);
+    }
+}
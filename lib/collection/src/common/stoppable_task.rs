@@ -1,12 +1,17 @@
+use std::future::Future;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Weak};
 
+use tokio::runtime::Handle;
+use tokio::sync::Notify;
 use tokio::task::JoinHandle;
 
 pub struct StoppableTaskHandle<T> {
     pub join_handle: JoinHandle<T>,
     finished: Arc<AtomicBool>,
     stopped: Weak<AtomicBool>,
+    /// Wakes up the task, if it is not started yet
+    stop_notify: Weak<Notify>,
 }
 
 impl<T> StoppableTaskHandle<T> {
@@ -18,6 +23,9 @@ impl<T> StoppableTaskHandle<T> {
         if let Some(v) = self.stopped.upgrade() {
             v.store(true, Ordering::Relaxed);
         }
+        if let Some(notify) = self.stop_notify.upgrade() {
+            notify.notify_one();
+        }
     }
 
     pub fn stop(self) -> JoinHandle<T> {
@@ -27,6 +35,16 @@ impl<T> StoppableTaskHandle<T> {
 }
 
 pub fn spawn_stoppable<F, T>(f: F) -> StoppableTaskHandle<T>
+where
+    F: FnOnce(&AtomicBool) -> T,
+    F: Send + 'static,
+    T: Send + 'static,
+{
+    spawn_stoppable_on(&Handle::current(), f)
+}
+
+/// Same as [`spawn_stoppable`], but the blocking task is executed on the given runtime
+pub fn spawn_stoppable_on<F, T>(runtime_handle: &Handle, f: F) -> StoppableTaskHandle<T>
 where
     F: FnOnce(&AtomicBool) -> T,
     F: Send + 'static,
@@ -41,7 +59,7 @@ where
     let stopped_w = Arc::downgrade(&stopped);
 
     StoppableTaskHandle {
-        join_handle: tokio::task::spawn_blocking(move || {
+        join_handle: runtime_handle.spawn_blocking(move || {
             let res = f(&stopped);
             // We use `Release` ordering to ensure that `f` won't be moved after the `store`
             // by the compiler
@@ -50,6 +68,64 @@ where
         }),
         stopped: stopped_w,
         finished: finished_c,
+        stop_notify: Weak::new(),
+    }
+}
+
+/// Same as [`spawn_stoppable_on`], but the blocking task is started only once `ready` resolves.
+/// The output of `ready` is kept until the task is finished, e.g. to hold a permit.
+/// If the task is asked to stop before that, it finishes with `T::default()` without running `f`.
+pub fn spawn_stoppable_after<R, F, T>(
+    runtime_handle: &Handle,
+    ready: R,
+    f: F,
+) -> StoppableTaskHandle<T>
+where
+    R: Future + Send + 'static,
+    R::Output: Send + 'static,
+    F: FnOnce(&AtomicBool) -> T,
+    F: Send + 'static,
+    T: Send + Default + 'static,
+{
+    let finished = Arc::new(AtomicBool::new(false));
+    let finished_c = finished.clone();
+
+    let stopped = Arc::new(AtomicBool::new(false));
+    let stopped_w = Arc::downgrade(&stopped);
+
+    let stop_notify = Arc::new(Notify::new());
+    let stop_notify_w = Arc::downgrade(&stop_notify);
+
+    let join_handle = runtime_handle.spawn(async move {
+        let ready_output = tokio::select! {
+            output = ready => Some(output),
+            _ = stop_notify.notified() => None,
+        };
+        let res = match ready_output {
+            None => T::default(),
+            Some(ready_output) => {
+                let blocking = tokio::task::spawn_blocking(move || {
+                    let res = f(&stopped);
+                    drop(ready_output);
+                    res
+                });
+                match blocking.await {
+                    Ok(res) => res,
+                    // Propagate panic of the task into the join handle
+                    Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+                    Err(_) => T::default(),
+                }
+            }
+        };
+        finished.store(true, Ordering::Release);
+        res
+    });
+
+    StoppableTaskHandle {
+        join_handle,
+        stopped: stopped_w,
+        finished: finished_c,
+        stop_notify: stop_notify_w,
     }
 }
 
@@ -87,4 +163,21 @@ mod tests {
         let res = handle.stop().await.unwrap();
         assert!(res < 10);
     }
+
+    #[tokio::test]
+    async fn test_stop_before_ready() {
+        let (sender, receiver) = tokio::sync::oneshot::channel::<()>();
+        let handle = spawn_stoppable_after(&Handle::current(), receiver, |_stopped| 1);
+        tokio::time::sleep(Duration::from_millis(STEP_MILLIS)).await;
+        assert!(!handle.is_finished());
+
+        // Task is not started, if it is stopped while waiting
+        assert_eq!(handle.stop().await.unwrap(), 0);
+        assert!(sender.is_closed());
+
+        let (sender, receiver) = tokio::sync::oneshot::channel::<()>();
+        let handle = spawn_stoppable_after(&Handle::current(), receiver, |_stopped| 1);
+        sender.send(()).unwrap();
+        assert_eq!(handle.join_handle.await.unwrap(), 1);
+    }
 }
@@ -7,13 +7,23 @@ use std::path::Path;
 
 use atomicwrites::AtomicFile;
 use atomicwrites::OverwriteBehavior::AllowOverwrite;
+use merge::Merge;
 use schemars::JsonSchema;
 use segment::common::anonymize::Anonymize;
 use segment::data_types::vectors::DEFAULT_VECTOR_NAME;
-use segment::types::{Distance, HnswConfig, VectorDataConfig};
+use segment::types::{
+    Distance, HnswConfig, MultiVectorConfig, ProductQuantizationConfig, VectorDataConfig,
+    VectorStorageDatatype,
+};
 use serde::{Deserialize, Serialize};
 use wal::WalOptions;
 
+use crate::operations::auto_payload_index::AutoPayloadIndexConfig;
+use crate::operations::config_diff::{
+    DiffConfig, HnswConfigDiff, QuantizationConfigDiff, VectorsConfigDiff,
+};
+use crate::operations::payload_types::PayloadFieldTypes;
+use crate::operations::ttl::TtlConfig;
 use crate::operations::types::{CollectionError, CollectionResult};
 use crate::optimizers_builder::OptimizersConfig;
 
@@ -25,6 +35,14 @@ pub struct WalConfig {
     pub wal_capacity_mb: usize,
     /// Number of WAL segments to create ahead of actually used ones
     pub wal_segments_ahead: usize,
+    /// If true - WAL records, payloads and vectors are encrypted with the key from the storage
+    /// configuration. Vectors are never stored in mmap files in this case.
+    #[serde(default)]
+    pub encrypted: bool,
+    /// Number of seconds, during which retries of the operation with the same client-supplied id
+    /// are applied only once
+    #[serde(default = "default_operation_id_window_sec")]
+    pub operation_id_window_sec: u64,
 }
 
 impl From<&WalConfig> for WalOptions {
@@ -41,6 +59,8 @@ impl Default for WalConfig {
         WalConfig {
             wal_capacity_mb: 32,
             wal_segments_ahead: 0,
+            encrypted: false,
+            operation_id_window_sec: default_operation_id_window_sec(),
         }
     }
 }
@@ -68,6 +88,10 @@ pub struct CollectionParams {
     /// Note: those payload values that are involved in filtering and are indexed - remain in RAM.
     #[serde(default = "default_on_disk_payload")]
     pub on_disk_payload: bool,
+    /// Name of the vector, used by searches and recommendations which do not specify one.
+    /// Allows to omit the vector name in requests to collections with multiple vectors.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_vector: Option<String>,
 }
 
 /// Params of single vector data storage
@@ -78,6 +102,30 @@ pub struct VectorParams {
     pub size: NonZeroU64,
     /// Type of distance function used for measuring distance between vectors
     pub distance: Distance,
+    /// Custom params for HNSW index of this vector.
+    /// If none - values from collection configuration are used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hnsw_config: Option<HnswConfigDiff>,
+    /// If set - vectors of indexed segments are also stored product-quantized in RAM,
+    /// so they can be kept on disk with less impact on the search speed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quantization_config: Option<ProductQuantizationConfig>,
+    /// If true - vectors of optimized segments are stored in mmap files, regardless of the
+    /// `memmap_threshold`. Vectors of a segment share the storage, so the segment is stored
+    /// on disk if any of its vectors is. Ignored for encrypted collections.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_disk: Option<bool>,
+    /// Type of the stored vector elements. Default is `float32`.
+    /// Vectors of `uint8` type are always kept in RAM, so they can't be `on_disk`,
+    /// and are not product-quantized
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub datatype: Option<VectorStorageDatatype>,
+    /// If set - each point stores a list of vectors of the `size`, e.g. token embeddings of
+    /// late interaction models. Lists are flattened into a single vector, which length should
+    /// be a multiple of the `size`. Multivectors are always kept in RAM and are not
+    /// product-quantized, query vectors are lists of vectors too
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multivector_config: Option<MultiVectorConfig>,
 }
 
 /// Vector params separator for single and multiple vector modes
@@ -124,6 +172,7 @@ impl Anonymize for CollectionParams {
             replication_factor: self.replication_factor,
             write_consistency_factor: self.write_consistency_factor,
             on_disk_payload: self.on_disk_payload,
+            default_vector: self.default_vector.clone(),
         }
     }
 }
@@ -147,6 +196,31 @@ impl VectorsConfig {
             VectorsConfig::Multi(params) => params.get(name),
         }
     }
+
+    fn get_params_mut(&mut self, name: &str) -> Option<&mut VectorParams> {
+        match self {
+            VectorsConfig::Single(params) => {
+                if name == DEFAULT_VECTOR_NAME {
+                    Some(params)
+                } else {
+                    None
+                }
+            }
+            VectorsConfig::Multi(params) => params.get_mut(name),
+        }
+    }
+
+    /// Params of all vectors with their names
+    pub fn params_iter(&self) -> impl Iterator<Item = (&str, &VectorParams)> {
+        let params: Vec<_> = match self {
+            VectorsConfig::Single(params) => vec![(DEFAULT_VECTOR_NAME, params)],
+            VectorsConfig::Multi(params) => params
+                .iter()
+                .map(|(name, params)| (name.as_str(), params))
+                .collect(),
+        };
+        params.into_iter()
+    }
 }
 
 fn default_shard_number() -> NonZeroU32 {
@@ -165,12 +239,25 @@ fn default_on_disk_payload() -> bool {
     false
 }
 
+pub fn default_operation_id_window_sec() -> u64 {
+    600
+}
+
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
 pub struct CollectionConfig {
     pub params: CollectionParams,
     pub hnsw_config: HnswConfig,
     pub optimizer_config: OptimizersConfig,
     pub wal_config: WalConfig,
+    /// Expected types of payload fields. Points with payload values of other types are rejected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload_field_types: Option<PayloadFieldTypes>,
+    /// If set - payload indexes are created automatically for the fields, first written to the collection
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_payload_index: Option<AutoPayloadIndexConfig>,
+    /// If set - points are deleted in background, once their lifetime is expired
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl: Option<TtlConfig>,
 }
 
 impl CollectionConfig {
@@ -202,50 +289,116 @@ impl CollectionConfig {
 
 impl CollectionParams {
     pub fn get_vector_params(&self, vector_name: &str) -> CollectionResult<VectorParams> {
-        if vector_name == DEFAULT_VECTOR_NAME {
-            self.vectors
-                .get_params(vector_name)
-                .cloned()
-                .ok_or_else(|| CollectionError::BadInput {
-                    description: "Default vector params are not specified in config".to_string(),
-                })
-        } else {
-            self.vectors
-                .get_params(vector_name)
-                .cloned()
-                .ok_or_else(|| CollectionError::BadInput {
-                    description: format!(
-                        "vector params for {vector_name} are not specified in config"
-                    ),
-                })
+        self.vectors
+            .get_params(vector_name)
+            .cloned()
+            .ok_or_else(|| self.missing_vector_error(vector_name))
+    }
+
+    /// Name of the vector, which a request should use.
+    /// Requests without a vector name use `default_vector` of the collection, if it is configured.
+    pub fn resolve_vector_name<'a>(&'a self, vector_name: &'a str) -> CollectionResult<&'a str> {
+        let vector_name = match &self.default_vector {
+            Some(default_vector) if vector_name == DEFAULT_VECTOR_NAME => default_vector.as_str(),
+            _ => vector_name,
+        };
+        match self.vectors.get_params(vector_name) {
+            Some(_) => Ok(vector_name),
+            None => Err(self.missing_vector_error(vector_name)),
+        }
+    }
+
+    /// Whether vectors of optimized segments are stored on disk regardless of their size
+    pub fn vectors_on_disk(&self) -> bool {
+        self.vectors
+            .params_iter()
+            .any(|(_, params)| params.on_disk == Some(true))
+    }
+
+    /// Apply the update of the vector params.
+    /// Segments, built with the previous params, keep them until they are rebuilt
+    pub fn update_vectors_from_diff(&mut self, diff: &VectorsConfigDiff) -> CollectionResult<()> {
+        // Check all the names first, so the params are not updated partially
+        if let Some(vector_name) = diff
+            .0
+            .keys()
+            .find(|vector_name| self.vectors.get_params(vector_name).is_none())
+        {
+            return Err(self.missing_vector_error(vector_name));
+        }
+        for (vector_name, params_diff) in &diff.0 {
+            let params = match self.vectors.get_params_mut(vector_name) {
+                Some(params) => params,
+                None => continue,
+            };
+            if let Some(mut hnsw_diff) = params_diff.hnsw_config {
+                if let Some(current) = params.hnsw_config {
+                    hnsw_diff.merge(current);
+                }
+                params.hnsw_config = Some(hnsw_diff);
+            }
+            match params_diff.quantization_config {
+                None => {}
+                Some(QuantizationConfigDiff::Product(config)) => {
+                    params.quantization_config = Some(config)
+                }
+                Some(QuantizationConfigDiff::Disabled(_)) => params.quantization_config = None,
+            }
+            if let Some(on_disk) = params_diff.on_disk {
+                params.on_disk = Some(on_disk);
+            }
         }
+        Ok(())
+    }
+
+    fn missing_vector_error(&self, vector_name: &str) -> CollectionError {
+        let available_names = self
+            .vectors
+            .params_iter()
+            .map(|(name, _)| format!("`{name}`"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let description = if vector_name == DEFAULT_VECTOR_NAME {
+            format!(
+                "Collection has multiple vectors and no `default_vector`, specify one of: {available_names}"
+            )
+        } else if matches!(self.vectors, VectorsConfig::Single(_)) {
+            format!("Vector `{vector_name}` does not exist, collection has a single unnamed vector")
+        } else {
+            format!("Vector `{vector_name}` does not exist, available vectors: {available_names}")
+        };
+        CollectionError::BadInput { description }
     }
 
-    pub fn get_all_vector_params(&self) -> CollectionResult<HashMap<String, VectorDataConfig>> {
+    /// Get configs of all vectors for segments.
+    /// Custom HNSW params of vectors are applied on top of the collection's `hnsw_config`.
+    pub fn get_all_vector_params(
+        &self,
+        hnsw_config: &HnswConfig,
+    ) -> CollectionResult<HashMap<String, VectorDataConfig>> {
+        let vector_data_config = |params: &VectorParams| -> CollectionResult<VectorDataConfig> {
+            Ok(VectorDataConfig {
+                size: params.size.get() as usize,
+                distance: params.distance,
+                hnsw_config: params
+                    .hnsw_config
+                    .map(|diff| diff.update(hnsw_config))
+                    .transpose()?,
+                quantization_config: params.quantization_config,
+                datatype: params.datatype.unwrap_or_default(),
+                multivector_config: params.multivector_config,
+            })
+        };
         let vector_config = match &self.vectors {
             VectorsConfig::Single(params) => {
                 let mut map = HashMap::new();
-                map.insert(
-                    DEFAULT_VECTOR_NAME.to_string(),
-                    VectorDataConfig {
-                        size: params.size.get() as usize,
-                        distance: params.distance,
-                    },
-                );
+                map.insert(DEFAULT_VECTOR_NAME.to_string(), vector_data_config(params)?);
                 map
             }
             VectorsConfig::Multi(ref map) => map
                 .iter()
-                .map(|(name, params)| {
-                    (
-                        name.clone(),
-                        VectorDataConfig {
-                            size: params.size.get() as usize,
-                            distance: params.distance,
-                        },
-                    )
-                })
-                .collect(),
+                .map(|(name, params)| Ok((name.clone(), vector_data_config(params)?)))
+                .collect::<CollectionResult<_>>()?,
         };
         Ok(vector_config)
     }
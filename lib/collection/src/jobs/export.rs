@@ -0,0 +1,344 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use futures::future::try_join_all;
+use itertools::Itertools;
+use schemars::JsonSchema;
+use segment::types::{Filter, PointIdType, WithPayloadInterface, WithVector};
+use serde::{Deserialize, Serialize};
+use tokio::fs::{rename, write};
+
+use crate::jobs::{JobInfo, JobKind, JobState, JobStatus, JobsPool};
+use crate::operations::types::{CollectionError, CollectionResult, CountRequest, Record};
+use crate::save_on_disk::SaveOnDisk;
+use crate::shards::shard_holder::LockedShardHolder;
+
+pub const EXPORTS_DIR: &str = "exports";
+/// Number of points in a single chunk file, if not specified in the request
+const DEFAULT_EXPORT_CHUNK_SIZE: usize = 10_000;
+/// Number of points, read at once by the streaming export, if not specified in the request
+pub const DEFAULT_STREAM_EXPORT_BATCH_SIZE: usize = 1_000;
+
+/// Format of the exported chunk files
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    /// One JSON-encoded point per line
+    #[default]
+    Jsonl,
+    /// JSON array of points
+    Json,
+}
+
+impl ExportFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Jsonl => "jsonl",
+            ExportFormat::Json => "json",
+        }
+    }
+
+    pub fn encode(&self, points: &[Record]) -> CollectionResult<Vec<u8>> {
+        let mut bytes = vec![];
+        match self {
+            ExportFormat::Jsonl => {
+                for point in points {
+                    serde_json::to_writer(&mut bytes, point)?;
+                    bytes.push(b'\n');
+                }
+            }
+            ExportFormat::Json => serde_json::to_writer(&mut bytes, points)?,
+        }
+        Ok(bytes)
+    }
+}
+
+/// Request to export points of the collection into chunk files
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct CreateExport {
+    /// Export only points which satisfy this conditions. If not provided - all points.
+    pub filter: Option<Filter>,
+    /// Format of the chunk files. Default: jsonl
+    #[serde(default)]
+    pub format: ExportFormat,
+    /// Number of points in a single chunk file. Default: 10000
+    pub chunk_size: Option<usize>,
+    /// Whether to export vectors of the points. Default: true
+    pub with_vector: Option<bool>,
+}
+
+/// Request to stream points of the collection in the response, one JSON-encoded point per line
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct StreamExport {
+    /// Export only points which satisfy this conditions. If not provided - all points.
+    pub filter: Option<Filter>,
+    /// Whether to export vectors of the points. Default: true
+    pub with_vector: Option<bool>,
+    /// Number of points, read from the collection at once. Default: 1000
+    pub batch_size: Option<usize>,
+}
+
+/// State of the export job. Persisted after each chunk, so the export could be resumed
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default)]
+pub struct ExportDescription {
+    #[serde(flatten)]
+    pub job: JobInfo,
+    pub filter: Option<Filter>,
+    pub format: ExportFormat,
+    pub chunk_size: usize,
+    pub with_vector: bool,
+    /// Number of chunk files produced so far
+    pub chunks: usize,
+    /// Id of the first point, which is not exported yet.
+    /// Export continues from this point after restart
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_offset: Option<PointIdType>,
+}
+
+impl JobState for ExportDescription {
+    fn job(&self) -> &JobInfo {
+        &self.job
+    }
+
+    fn job_mut(&mut self) -> &mut JobInfo {
+        &mut self.job
+    }
+}
+
+/// Background export jobs of a single collection
+pub struct ExportJobs {
+    pool: JobsPool<ExportDescription>,
+}
+
+impl ExportJobs {
+    pub fn new(collection_path: &Path) -> Self {
+        Self {
+            pool: JobsPool::new(&Self::exports_path(collection_path)),
+        }
+    }
+
+    pub fn exports_path(collection_path: &Path) -> PathBuf {
+        collection_path.join(EXPORTS_DIR)
+    }
+
+    fn chunk_path(export_path: &Path, chunk: usize, format: ExportFormat) -> PathBuf {
+        export_path.join(format!("chunk-{chunk}.{}", format.extension()))
+    }
+
+    /// Load exports of the collection and resume the unfinished ones
+    pub fn load(
+        collection_path: &Path,
+        shards_holder: Arc<LockedShardHolder>,
+    ) -> CollectionResult<Self> {
+        let mut jobs = Self {
+            pool: JobsPool::load(&Self::exports_path(collection_path))?,
+        };
+        for export in jobs.pool.running() {
+            log::info!("Resuming export {}", export.read().job.id);
+            jobs.spawn(export, shards_holder.clone());
+        }
+        Ok(jobs)
+    }
+
+    pub fn create(
+        &mut self,
+        collection_name: &str,
+        request: CreateExport,
+        shards_holder: Arc<LockedShardHolder>,
+    ) -> CollectionResult<ExportDescription> {
+        let chunk_size = request.chunk_size.unwrap_or(DEFAULT_EXPORT_CHUNK_SIZE);
+        if chunk_size == 0 {
+            return Err(CollectionError::bad_request(
+                "Chunk size cannot be 0".to_string(),
+            ));
+        }
+
+        let export = self.pool.create(ExportDescription {
+            job: JobInfo {
+                id: uuid::Uuid::new_v4().to_string(),
+                kind: JobKind::Export,
+                collection_name: collection_name.to_string(),
+                ..Default::default()
+            },
+            filter: request.filter,
+            format: request.format,
+            chunk_size,
+            with_vector: request.with_vector.unwrap_or(true),
+            ..Default::default()
+        })?;
+        let description = export.read().clone();
+
+        self.spawn(export, shards_holder);
+        Ok(description)
+    }
+
+    pub fn list(&self) -> Vec<ExportDescription> {
+        self.pool.list()
+    }
+
+    pub fn get(&self, export_id: &str) -> CollectionResult<ExportDescription> {
+        self.pool.get(export_id)
+    }
+
+    pub fn contains(&self, export_id: &str) -> bool {
+        self.pool.contains(export_id)
+    }
+
+    /// Stop the export. Already written chunks are kept
+    pub fn cancel(&mut self, export_id: &str) -> CollectionResult<ExportDescription> {
+        self.pool.cancel(export_id)
+    }
+
+    /// Path to the chunk file, only available once the chunk is completely written
+    pub fn get_chunk_path(&self, export_id: &str, chunk: usize) -> CollectionResult<PathBuf> {
+        let description = self.get(export_id)?;
+        if chunk >= description.chunks {
+            return Err(CollectionError::NotFound {
+                what: format!("Chunk {chunk} of export {export_id}"),
+            });
+        }
+        let export_path = self.pool.job_path(export_id);
+        Ok(Self::chunk_path(&export_path, chunk, description.format))
+    }
+
+    /// Stop all running exports. They will be resumed on the next load
+    pub fn stop_all(&mut self) {
+        self.pool.stop_all();
+    }
+
+    fn spawn(
+        &mut self,
+        export: Arc<SaveOnDisk<ExportDescription>>,
+        shards_holder: Arc<LockedShardHolder>,
+    ) {
+        let export_path = self.pool.job_path(&export.read().job.id);
+        self.pool.spawn(export, move |export, stopped| async move {
+            run_export(&export_path, &export, &shards_holder, &stopped).await
+        });
+    }
+}
+
+/// Write chunks until all points are exported or the job is asked to stop.
+/// Each chunk is written before the checkpoint is saved, so an interrupted chunk is rewritten on resume
+async fn run_export(
+    export_path: &Path,
+    export: &SaveOnDisk<ExportDescription>,
+    shards_holder: &LockedShardHolder,
+    stopped: &AtomicBool,
+) -> CollectionResult<()> {
+    loop {
+        if stopped.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        let description = export.read().clone();
+        if description.job.status != JobStatus::Running {
+            return Ok(());
+        }
+
+        if description.job.progress.total.is_none() {
+            let total = count_points(shards_holder, description.filter.as_ref()).await?;
+            export.write(|description| description.job.progress.total = Some(total))?;
+        }
+
+        let with_vector = WithVector::Bool(description.with_vector);
+        let (points, next_offset) = scroll_page(
+            shards_holder,
+            description.next_offset,
+            description.chunk_size,
+            description.filter.as_ref(),
+            &with_vector,
+        )
+        .await?;
+
+        if !points.is_empty() {
+            let chunk_path =
+                ExportJobs::chunk_path(export_path, description.chunks, description.format);
+            let tmp_path = chunk_path.with_extension("tmp");
+            write(&tmp_path, description.format.encode(&points)?).await?;
+            rename(&tmp_path, &chunk_path).await?;
+        }
+
+        export.write(|description| {
+            if !points.is_empty() {
+                description.chunks += 1;
+                description.job.progress.processed += points.len();
+            }
+            description.next_offset = next_offset;
+            // Job might be cancelled while the chunk was written
+            if next_offset.is_none() && description.job.status == JobStatus::Running {
+                description.job.status = JobStatus::Completed;
+            }
+        })?;
+
+        if next_offset.is_none() {
+            return Ok(());
+        }
+    }
+}
+
+/// Number of points to export, used to report the export progress
+async fn count_points(
+    shards_holder: &LockedShardHolder,
+    filter: Option<&Filter>,
+) -> CollectionResult<usize> {
+    let request = Arc::new(CountRequest {
+        filter: filter.cloned(),
+        exact: true,
+    });
+    let shards_holder = shards_holder.read().await;
+    let counts = try_join_all(
+        shards_holder
+            .target_shard(None)?
+            .into_iter()
+            .map(|shard| shard.count(request.clone())),
+    )
+    .await?;
+    Ok(counts.into_iter().map(|count| count.count).sum())
+}
+
+/// Read a page of points from all shards, ordered by id.
+/// Returns the points and the offset of the next page, if any
+async fn scroll_page(
+    shards_holder: &LockedShardHolder,
+    offset: Option<PointIdType>,
+    limit: usize,
+    filter: Option<&Filter>,
+    with_vector: &WithVector,
+) -> CollectionResult<(Vec<Record>, Option<PointIdType>)> {
+    let with_payload = WithPayloadInterface::Bool(true);
+    // Needed to return next page offset.
+    let limit = limit + 1;
+    let retrieved_points = {
+        let shards_holder = shards_holder.read().await;
+        let scroll_futures = shards_holder.target_shard(None)?.into_iter().map(|shard| {
+            shard.scroll_by(
+                offset,
+                limit,
+                &with_payload,
+                with_vector,
+                filter,
+                None,
+                None,
+            )
+        });
+        try_join_all(scroll_futures).await?
+    };
+
+    let mut points: Vec<_> = retrieved_points
+        .into_iter()
+        .flatten()
+        .sorted_by_key(|point| point.id)
+        .take(limit)
+        .collect();
+
+    let next_offset = if points.len() < limit {
+        None
+    } else {
+        points.pop().map(|point| point.id)
+    };
+    Ok((points, next_offset))
+}
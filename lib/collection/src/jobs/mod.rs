@@ -0,0 +1,210 @@
+pub mod export;
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use itertools::Itertools;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::common::stoppable_task_async::{spawn_async_stoppable, StoppableAsyncTaskHandle};
+use crate::operations::types::{CollectionError, CollectionResult};
+use crate::save_on_disk::SaveOnDisk;
+
+/// Name of the file with the persisted job state inside of the job directory
+const JOB_STATE_FILE: &str = "job.json";
+
+/// Status of the background job
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    /// Job is in progress. Interrupted jobs are resumed after restart
+    #[default]
+    Running,
+    /// Job is finished successfully
+    Completed,
+    /// Job is stopped due to an error
+    Failed,
+    /// Job is stopped by the user request
+    Cancelled,
+}
+
+/// Type of the background job
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    /// Export of the collection points into chunk files
+    #[default]
+    Export,
+}
+
+/// Progress of the background job
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, Default)]
+pub struct JobProgress {
+    /// Number of items processed so far
+    pub processed: usize,
+    /// Total number of items to process, if known
+    pub total: Option<usize>,
+}
+
+/// Common state of the background job of any kind
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default)]
+pub struct JobInfo {
+    pub id: String,
+    pub kind: JobKind,
+    /// Name of the collection, the job operates on
+    pub collection_name: String,
+    pub status: JobStatus,
+    /// Error message, if the job has failed
+    pub error: Option<String>,
+    pub progress: JobProgress,
+}
+
+/// Persisted state of the background job, specific to the job kind
+pub trait JobState: Serialize + DeserializeOwned + Default + Clone + Send + Sync + 'static {
+    fn job(&self) -> &JobInfo;
+
+    fn job_mut(&mut self) -> &mut JobInfo;
+}
+
+/// Background jobs of a single kind.
+/// Each job has a separate directory with the persisted state and artifacts of the job.
+pub struct JobsPool<S: JobState> {
+    path: PathBuf,
+    jobs: HashMap<String, Arc<SaveOnDisk<S>>>,
+    tasks: HashMap<String, StoppableAsyncTaskHandle<()>>,
+}
+
+impl<S: JobState> JobsPool<S> {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            jobs: Default::default(),
+            tasks: Default::default(),
+        }
+    }
+
+    /// Load persisted jobs from the `path`.
+    /// Jobs, which were running before the shutdown, are not started automatically - see [`Self::running`]
+    pub fn load(path: &Path) -> CollectionResult<Self> {
+        let mut pool = Self::new(path);
+        if !path.exists() {
+            return Ok(pool);
+        }
+
+        for entry in std::fs::read_dir(path)? {
+            let state_path = entry?.path().join(JOB_STATE_FILE);
+            if !state_path.exists() {
+                continue;
+            }
+            let state: Arc<SaveOnDisk<S>> = Arc::new(SaveOnDisk::load_or_init(state_path)?);
+            let job_id = state.read().job().id.clone();
+            pool.jobs.insert(job_id, state);
+        }
+        Ok(pool)
+    }
+
+    /// Directory with the artifacts of the job
+    pub fn job_path(&self, job_id: &str) -> PathBuf {
+        self.path.join(job_id)
+    }
+
+    /// Persist the state of the new job
+    pub fn create(&mut self, state: S) -> CollectionResult<Arc<SaveOnDisk<S>>> {
+        let job_id = state.job().id.clone();
+        let job_path = self.job_path(&job_id);
+        std::fs::create_dir_all(&job_path)?;
+
+        let saved: Arc<SaveOnDisk<S>> =
+            Arc::new(SaveOnDisk::load_or_init(job_path.join(JOB_STATE_FILE))?);
+        saved.write(|saved| *saved = state)?;
+        self.jobs.insert(job_id, saved.clone());
+        Ok(saved)
+    }
+
+    /// Jobs, which are not finished yet
+    pub fn running(&self) -> Vec<Arc<SaveOnDisk<S>>> {
+        self.jobs
+            .values()
+            .filter(|state| state.read().job().status == JobStatus::Running)
+            .cloned()
+            .collect()
+    }
+
+    /// Run the job in the background. If the job returns an error, it is marked as failed
+    pub fn spawn<F, Fut>(&mut self, state: Arc<SaveOnDisk<S>>, run: F)
+    where
+        F: FnOnce(Arc<SaveOnDisk<S>>, Arc<AtomicBool>) -> Fut + Send + 'static,
+        Fut: Future<Output = CollectionResult<()>> + Send + 'static,
+    {
+        self.tasks.retain(|_, task| !task.is_finished());
+        let job_id = state.read().job().id.clone();
+        let task = spawn_async_stoppable(move |stopped| async move {
+            if let Err(err) = run(state.clone(), stopped).await {
+                log::error!("Job {} failed: {err}", state.read().job().id);
+                let saved = state.write(|state| {
+                    let job = state.job_mut();
+                    job.status = JobStatus::Failed;
+                    job.error = Some(err.to_string());
+                });
+                if let Err(err) = saved {
+                    log::error!("Can't save job state: {err}");
+                }
+            }
+        });
+        self.tasks.insert(job_id, task);
+    }
+
+    pub fn list(&self) -> Vec<S> {
+        self.jobs
+            .values()
+            .map(|state| state.read().clone())
+            .sorted_by(|a, b| a.job().id.cmp(&b.job().id))
+            .collect()
+    }
+
+    pub fn contains(&self, job_id: &str) -> bool {
+        self.jobs.contains_key(job_id)
+    }
+
+    pub fn get(&self, job_id: &str) -> CollectionResult<S> {
+        self.jobs
+            .get(job_id)
+            .map(|state| state.read().clone())
+            .ok_or_else(|| CollectionError::NotFound {
+                what: format!("Job {job_id}"),
+            })
+    }
+
+    /// Stop the running job. Cancelled jobs are not resumed after restart
+    pub fn cancel(&mut self, job_id: &str) -> CollectionResult<S> {
+        let state = self
+            .jobs
+            .get(job_id)
+            .ok_or_else(|| CollectionError::NotFound {
+                what: format!("Job {job_id}"),
+            })?;
+        let cancelled = state.write(|state| {
+            let job = state.job_mut();
+            if job.status == JobStatus::Running {
+                job.status = JobStatus::Cancelled;
+            }
+            state.clone()
+        })?;
+        if let Some(task) = self.tasks.remove(job_id) {
+            task.ask_to_stop();
+        }
+        Ok(cancelled)
+    }
+
+    /// Stop all running jobs. They will be resumed on the next load
+    pub fn stop_all(&mut self) {
+        for (_, task) in self.tasks.drain() {
+            task.ask_to_stop();
+        }
+    }
+}
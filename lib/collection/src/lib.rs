@@ -3,9 +3,10 @@
 pub mod collection;
 pub mod collection_manager;
 pub mod collection_state;
-mod common;
+pub mod common;
 pub mod config;
 pub mod hash_ring;
+pub mod jobs;
 pub mod operations;
 pub mod optimizers_builder;
 pub mod save_on_disk;
@@ -0,0 +1,187 @@
+use std::collections::{BTreeMap, HashMap};
+
+use schemars::JsonSchema;
+use segment::types::{infer_value_type, Payload, PayloadKeyType, PayloadSchemaType};
+use serde::{Deserialize, Serialize};
+
+use crate::operations::payload_ops::PayloadOps;
+use crate::operations::point_ops::{PointInsertOperations, PointOperations};
+use crate::operations::CollectionUpdateOperations;
+
+fn default_max_indexed_fields() -> usize {
+    16
+}
+
+/// Automatic creation of payload indexes for the fields, written to the collection
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Copy, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub struct AutoPayloadIndexConfig {
+    /// Indexes are not created automatically, if the collection already has this number
+    /// of payload indexes. 0 disables automatic indexing.
+    #[serde(default = "default_max_indexed_fields")]
+    pub max_indexed_fields: usize,
+}
+
+impl Default for AutoPayloadIndexConfig {
+    fn default() -> Self {
+        AutoPayloadIndexConfig {
+            max_indexed_fields: default_max_indexed_fields(),
+        }
+    }
+}
+
+fn collect_payload_fields(
+    payload: &Payload,
+    fields: &mut BTreeMap<PayloadKeyType, Option<PayloadSchemaType>>,
+) {
+    for (key, value) in payload.0.iter() {
+        let value_type = infer_value_type(value);
+        fields
+            .entry(key.clone())
+            .and_modify(|field_type| {
+                // Fields with values of different types in the same operation are ambiguous
+                if *field_type != value_type {
+                    *field_type = None;
+                }
+            })
+            .or_insert(value_type);
+    }
+}
+
+/// Top-level payload fields, written by the operation, with the types inferred from the values.
+/// Fields with null, boolean, mixed or nested values are skipped, as their type is ambiguous.
+pub fn infer_operation_payload_fields(
+    operation: &CollectionUpdateOperations,
+) -> BTreeMap<PayloadKeyType, PayloadSchemaType> {
+    let mut fields = BTreeMap::new();
+    match operation {
+        CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(
+            PointInsertOperations::PointsBatch(batch),
+        )) => batch
+            .payloads
+            .iter()
+            .flatten()
+            .flatten()
+            .for_each(|payload| collect_payload_fields(payload, &mut fields)),
+        CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(
+            PointInsertOperations::PointsList(points),
+        )) => points
+            .iter()
+            .filter_map(|point| point.payload.as_ref())
+            .for_each(|payload| collect_payload_fields(payload, &mut fields)),
+        CollectionUpdateOperations::PayloadOperation(PayloadOps::SetPayload(set_payload)) => {
+            collect_payload_fields(&set_payload.payload, &mut fields)
+        }
+        CollectionUpdateOperations::PayloadOperation(PayloadOps::UpdatePayload(update_payload)) => {
+            collect_payload_fields(&update_payload.written_values(), &mut fields)
+        }
+        _ => {}
+    }
+    fields
+        .into_iter()
+        .filter_map(|(key, field_type)| Some((key, field_type?)))
+        .collect()
+}
+
+/// Fields to create indexes for: not indexed yet, while the number of indexes is below the cap
+pub fn select_auto_indexes<T>(
+    fields: BTreeMap<PayloadKeyType, PayloadSchemaType>,
+    payload_schema: &HashMap<PayloadKeyType, T>,
+    config: &AutoPayloadIndexConfig,
+) -> Vec<(PayloadKeyType, PayloadSchemaType)> {
+    let available = config
+        .max_indexed_fields
+        .saturating_sub(payload_schema.len());
+    fields
+        .into_iter()
+        .filter(|(key, _)| !payload_schema.contains_key(key))
+        .take(available)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::operations::payload_ops::SetPayload;
+    use crate::operations::point_ops::Batch;
+
+    #[test]
+    fn test_infer_operation_payload_fields() {
+        let operation = CollectionUpdateOperations::PointOperation(
+            Batch {
+                ids: vec![0.into(), 1.into()],
+                vectors: vec![vec![1.0, 0.0], vec![0.0, 1.0]].into(),
+                payloads: serde_json::from_value(json!([
+                    {
+                        "city": "Berlin",
+                        "count": 1,
+                        "price": 1.5,
+                        "location": {"lon": 13.4, "lat": 52.5},
+                        "tags": ["a", "b"],
+                        "flag": true,
+                        "nested": {"key": "value"},
+                    },
+                    {"count": 2, "price": 2, "tags": [1]},
+                ]))
+                .unwrap(),
+            }
+            .into(),
+        );
+
+        let fields = infer_operation_payload_fields(&operation);
+        assert_eq!(
+            fields,
+            BTreeMap::from([
+                ("city".to_string(), PayloadSchemaType::Keyword),
+                ("count".to_string(), PayloadSchemaType::Integer),
+                ("location".to_string(), PayloadSchemaType::Geo),
+            ])
+        );
+
+        let operation =
+            CollectionUpdateOperations::PayloadOperation(PayloadOps::SetPayload(SetPayload {
+                payload: serde_json::from_value(json!({"rating": 4.5})).unwrap(),
+                points: vec![0.into()],
+            }));
+        let fields = infer_operation_payload_fields(&operation);
+        assert_eq!(
+            fields,
+            BTreeMap::from([("rating".to_string(), PayloadSchemaType::Float)])
+        );
+    }
+
+    #[test]
+    fn test_select_auto_indexes() {
+        let fields = BTreeMap::from([
+            ("a".to_string(), PayloadSchemaType::Keyword),
+            ("b".to_string(), PayloadSchemaType::Integer),
+            ("c".to_string(), PayloadSchemaType::Float),
+        ]);
+        let payload_schema = HashMap::from([("a".to_string(), ())]);
+
+        let config = AutoPayloadIndexConfig {
+            max_indexed_fields: 10,
+        };
+        let selected = select_auto_indexes(fields.clone(), &payload_schema, &config);
+        assert_eq!(
+            selected,
+            vec![
+                ("b".to_string(), PayloadSchemaType::Integer),
+                ("c".to_string(), PayloadSchemaType::Float),
+            ]
+        );
+
+        let config = AutoPayloadIndexConfig {
+            max_indexed_fields: 2,
+        };
+        let selected = select_auto_indexes(fields.clone(), &payload_schema, &config);
+        assert_eq!(selected.len(), 1);
+
+        let config = AutoPayloadIndexConfig {
+            max_indexed_fields: 1,
+        };
+        assert!(select_auto_indexes(fields, &payload_schema, &config).is_empty());
+    }
+}
@@ -0,0 +1,239 @@
+use schemars::JsonSchema;
+use segment::data_types::payload_update::PayloadUpdate;
+use segment::types::{Filter, Payload, PayloadKeyType, PointIdType, SeqNumberType};
+use serde::{Deserialize, Serialize};
+
+use crate::operations::payload_ops::PayloadOps;
+use crate::operations::point_ops::{PointInsertOperations, PointOperations, PointStruct};
+use crate::operations::types::{CollectionError, CollectionResult};
+use crate::operations::CollectionUpdateOperations;
+use crate::shards::shard::ShardId;
+
+/// Max number of changes, returned by a single read of the WAL
+pub const DEFAULT_CHANGES_LIMIT: usize = 100;
+
+/// Change of the points, as it is recorded in the WAL of the shard
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum PointsChangeEvent {
+    /// Points are inserted or replaced
+    Upsert { points: Vec<PointStruct> },
+    /// Points are deleted
+    Delete { points: Vec<PointIdType> },
+    /// Points, matching the filter, are deleted
+    DeleteByFilter { filter: Filter },
+    /// Points in the range `[from_id, to_id)`, which are not in `points`, are deleted,
+    /// the rest are replaced with `points`
+    Sync {
+        from_id: Option<PointIdType>,
+        to_id: Option<PointIdType>,
+        points: Vec<PointStruct>,
+    },
+    /// Payload values are set for the points
+    SetPayload {
+        points: Vec<PointIdType>,
+        payload: Payload,
+    },
+    /// Payload values of the points are changed, depending on their current values
+    UpdatePayload {
+        points: Vec<PointIdType>,
+        updates: Vec<PayloadUpdate>,
+    },
+    /// Payload keys are removed from the points
+    DeletePayload {
+        points: Vec<PointIdType>,
+        keys: Vec<PayloadKeyType>,
+    },
+    /// Payload of the points is removed
+    ClearPayload { points: Vec<PointIdType> },
+    /// Payload of the points, matching the filter, is removed
+    ClearPayloadByFilter { filter: Filter },
+}
+
+impl PointsChangeEvent {
+    /// Event, produced by the update operation. Operations, which don't change points
+    /// (e.g. payload index changes), don't produce events
+    pub fn from_operation(operation: CollectionUpdateOperations) -> Option<Self> {
+        let event = match operation {
+            CollectionUpdateOperations::PointOperation(operation) => match operation {
+                PointOperations::UpsertPoints(PointInsertOperations::PointsList(points)) => {
+                    PointsChangeEvent::Upsert { points }
+                }
+                PointOperations::UpsertPoints(PointInsertOperations::PointsBatch(batch)) => {
+                    PointsChangeEvent::Upsert {
+                        points: batch.into(),
+                    }
+                }
+                PointOperations::DeletePoints { ids } => PointsChangeEvent::Delete { points: ids },
+                PointOperations::DeletePointsByFilter(filter) => {
+                    PointsChangeEvent::DeleteByFilter { filter }
+                }
+                PointOperations::SyncPoints(sync) => PointsChangeEvent::Sync {
+                    from_id: sync.from_id,
+                    to_id: sync.to_id,
+                    points: sync.points,
+                },
+            },
+            CollectionUpdateOperations::PayloadOperation(operation) => match operation {
+                PayloadOps::SetPayload(set_payload) => PointsChangeEvent::SetPayload {
+                    points: set_payload.points,
+                    payload: set_payload.payload,
+                },
+                PayloadOps::UpdatePayload(update_payload) => PointsChangeEvent::UpdatePayload {
+                    points: update_payload.points,
+                    updates: update_payload.updates,
+                },
+                PayloadOps::DeletePayload(delete_payload) => PointsChangeEvent::DeletePayload {
+                    points: delete_payload.points,
+                    keys: delete_payload.keys,
+                },
+                PayloadOps::ClearPayload { points } => PointsChangeEvent::ClearPayload { points },
+                PayloadOps::ClearPayloadByFilter(filter) => {
+                    PointsChangeEvent::ClearPayloadByFilter { filter }
+                }
+            },
+            CollectionUpdateOperations::FieldIndexOperation(_) => return None,
+        };
+        Some(event)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PointsChangeEvent::Upsert { .. } => "upsert",
+            PointsChangeEvent::Delete { .. } => "delete",
+            PointsChangeEvent::DeleteByFilter { .. } => "delete_by_filter",
+            PointsChangeEvent::Sync { .. } => "sync",
+            PointsChangeEvent::SetPayload { .. } => "set_payload",
+            PointsChangeEvent::UpdatePayload { .. } => "update_payload",
+            PointsChangeEvent::DeletePayload { .. } => "delete_payload",
+            PointsChangeEvent::ClearPayload { .. } => "clear_payload",
+            PointsChangeEvent::ClearPayloadByFilter { .. } => "clear_payload_by_filter",
+        }
+    }
+}
+
+/// Change of the points in a single shard of the collection
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+pub struct PointsChange {
+    pub shard_id: ShardId,
+    /// Sequential number of the operation in the WAL of the shard
+    pub op_num: SeqNumberType,
+    #[serde(flatten)]
+    pub event: PointsChangeEvent,
+}
+
+/// Changes of the shard points, read from the WAL
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default)]
+pub struct PointsChanges {
+    pub changes: Vec<PointsChange>,
+    /// Sequential number of the operation to continue reading from
+    pub next_op_num: SeqNumberType,
+}
+
+impl PointsChanges {
+    /// Build changes from the operations, read from the WAL of the shard starting from `from`
+    pub fn from_operations(
+        shard_id: ShardId,
+        from: SeqNumberType,
+        operations: Vec<(SeqNumberType, CollectionUpdateOperations)>,
+    ) -> Self {
+        let next_op_num = operations
+            .last()
+            .map(|(op_num, _)| op_num + 1)
+            .unwrap_or(from);
+        let changes = operations
+            .into_iter()
+            .filter_map(|(op_num, operation)| {
+                PointsChangeEvent::from_operation(operation).map(|event| PointsChange {
+                    shard_id,
+                    op_num,
+                    event,
+                })
+            })
+            .collect();
+        Self {
+            changes,
+            next_op_num,
+        }
+    }
+}
+
+/// Operations, which are already removed from the WAL, can't be streamed
+pub fn check_changes_available(
+    from: SeqNumberType,
+    first_op_num: SeqNumberType,
+) -> CollectionResult<()> {
+    if from < first_op_num {
+        return Err(CollectionError::BadInput {
+            description: format!(
+                "Changes before operation {first_op_num} are already removed from the WAL, \
+                 use a snapshot to get the full state"
+            ),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operations::payload_ops::SetPayload;
+    use crate::operations::{CreateIndex, FieldIndexOperations};
+
+    #[test]
+    fn test_changes_from_operations() {
+        let operations = vec![
+            (
+                5,
+                CollectionUpdateOperations::PointOperation(PointOperations::DeletePoints {
+                    ids: vec![1.into(), 2.into()],
+                }),
+            ),
+            (
+                6,
+                CollectionUpdateOperations::FieldIndexOperation(FieldIndexOperations::CreateIndex(
+                    CreateIndex {
+                        field_name: "city".to_string(),
+                        field_schema: None,
+                    },
+                )),
+            ),
+            (
+                7,
+                CollectionUpdateOperations::PayloadOperation(PayloadOps::SetPayload(SetPayload {
+                    payload: Default::default(),
+                    points: vec![3.into()],
+                })),
+            ),
+        ];
+
+        let changes = PointsChanges::from_operations(0, 5, operations);
+        assert_eq!(changes.next_op_num, 8);
+        assert_eq!(changes.changes.len(), 2);
+        assert_eq!(changes.changes[0].op_num, 5);
+        assert_eq!(changes.changes[0].event.name(), "delete");
+        assert_eq!(changes.changes[1].op_num, 7);
+        assert_eq!(changes.changes[1].event.name(), "set_payload");
+
+        let json = serde_json::to_value(&changes.changes[0]).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"shard_id": 0, "op_num": 5, "type": "delete", "points": [1, 2]})
+        );
+
+        let empty = PointsChanges::from_operations(0, 8, vec![]);
+        assert_eq!(empty.next_op_num, 8);
+        assert!(empty.changes.is_empty());
+
+        assert!(check_changes_available(3, 5).is_err());
+        assert!(check_changes_available(5, 5).is_ok());
+
+        // Changes, read from a remote replica, are transferred as gRPC messages
+        let grpc_change =
+            api::grpc::qdrant::PointsChange::try_from(changes.changes[1].clone()).unwrap();
+        assert_eq!(grpc_change.event, "set_payload");
+        let change = PointsChange::try_from(grpc_change).unwrap();
+        assert_eq!(change.op_num, 7);
+        assert_eq!(change.event.name(), "set_payload");
+    }
+}
@@ -1,7 +1,9 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::shards::replica_set::ReplicaState;
 use crate::shards::shard::{PeerId, ShardId};
+use crate::shards::transfer::shard_transfer::ShardTransferMethod;
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 #[serde(rename_all = "snake_case")]
@@ -15,6 +17,9 @@ pub enum ClusterOperations {
     AbortTransfer(AbortTransferOperation),
     /// Drop replica of a shard from a peer
     DropReplica(DropReplicaOperation),
+    /// Turn an active replica into a listener.
+    /// Listener is promoted back into an active replica with a shard transfer.
+    SetReplicaState(SetReplicaStateOperation),
 }
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
@@ -35,6 +40,12 @@ pub struct DropReplicaOperation {
     pub drop_replica: Replica,
 }
 
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct SetReplicaStateOperation {
+    pub set_replica_state: ReplicaStateChange,
+}
+
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 #[serde(rename_all = "snake_case")]
 pub struct AbortTransferOperation {
@@ -47,6 +58,12 @@ pub struct MoveShard {
     pub shard_id: ShardId,
     pub to_peer_id: PeerId,
     pub from_peer_id: PeerId,
+    /// Method of the transfer, `stream_records` if not set
+    #[serde(default)]
+    pub method: Option<ShardTransferMethod>,
+    /// Max number of points, sent to the target peer per second. Not limited if not set
+    #[serde(default)]
+    pub max_points_per_second: Option<usize>,
 }
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
@@ -55,3 +72,12 @@ pub struct Replica {
     pub shard_id: ShardId,
     pub peer_id: PeerId,
 }
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct ReplicaStateChange {
+    pub shard_id: ShardId,
+    pub peer_id: PeerId,
+    /// New state of the replica, only `Listener` can be set directly
+    pub state: ReplicaState,
+}
@@ -1,8 +1,9 @@
+use std::collections::BTreeMap;
 use std::num::NonZeroU32;
 
 use merge::Merge;
 use schemars::JsonSchema;
-use segment::types::HnswConfig;
+use segment::types::{HnswConfig, ProductQuantizationConfig};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
@@ -55,6 +56,13 @@ pub struct WalConfigDiff {
     pub wal_capacity_mb: Option<usize>,
     /// Number of WAL segments to create ahead of actually used ones
     pub wal_segments_ahead: Option<usize>,
+    /// If true - WAL records, payloads and vectors are encrypted with the key from the storage
+    /// configuration. Payload indexes are not encrypted.
+    /// Can only be set on collection creation.
+    pub encrypted: Option<bool>,
+    /// Number of seconds, during which retries of the operation with the same client-supplied id
+    /// are applied only once
+    pub operation_id_window_sec: Option<u64>,
 }
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Merge, PartialEq, Eq, Hash)]
@@ -65,6 +73,39 @@ pub struct CollectionParamsDiff {
     pub write_consistency_factor: Option<NonZeroU32>,
 }
 
+/// Update of the params of the existing vectors by their names.
+/// The single unnamed vector of the collection has an empty name.
+/// Indexed segments are rebuilt with the new params in background.
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Eq, Hash, Default)]
+pub struct VectorsConfigDiff(pub BTreeMap<String, VectorParamsDiff>);
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub struct VectorParamsDiff {
+    /// Update of the custom params for HNSW index of this vector
+    #[serde(default)]
+    pub hnsw_config: Option<HnswConfigDiff>,
+    /// New config of the product quantization of this vector, `"disabled"` removes it
+    #[serde(default)]
+    pub quantization_config: Option<QuantizationConfigDiff>,
+    /// If true - vectors of optimized segments are stored in mmap files, regardless of their size
+    #[serde(default)]
+    pub on_disk: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(untagged)]
+pub enum QuantizationConfigDiff {
+    Product(ProductQuantizationConfig),
+    Disabled(Disabled),
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Disabled {
+    Disabled,
+}
+
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Merge)]
 pub struct OptimizersConfigDiff {
     /// The minimal fraction of deleted vectors in a segment, required to perform segment optimization
@@ -230,4 +271,41 @@ mod tests {
         let new_config = update.update(&base_config).unwrap();
         assert_eq!(new_config.wal_segments_ahead, 2)
     }
+
+    #[test]
+    fn test_vectors_update() {
+        let mut params: CollectionParams = serde_json::from_str(
+            r#"{
+                "vectors": {
+                    "size": 16,
+                    "distance": "Cosine",
+                    "hnsw_config": { "m": 8 },
+                    "quantization_config": { "num_subvectors": 4 }
+                }
+            }"#,
+        )
+        .unwrap();
+        let update: VectorsConfigDiff = serde_json::from_str(
+            r#"{
+                "": {
+                    "hnsw_config": { "ef_construct": 200 },
+                    "quantization_config": "disabled",
+                    "on_disk": true
+                }
+            }"#,
+        )
+        .unwrap();
+        params.update_vectors_from_diff(&update).unwrap();
+
+        let vector_params = params.get_vector_params("").unwrap();
+        let hnsw_config = vector_params.hnsw_config.unwrap();
+        assert_eq!(hnsw_config.m, Some(8));
+        assert_eq!(hnsw_config.ef_construct, Some(200));
+        assert_eq!(vector_params.quantization_config, None);
+        assert!(params.vectors_on_disk());
+
+        let update: VectorsConfigDiff =
+            serde_json::from_str(r#"{ "missing": { "on_disk": true } }"#).unwrap();
+        assert!(params.update_vectors_from_diff(&update).is_err());
+    }
 }
@@ -1,28 +1,39 @@
 use std::collections::{BTreeMap, HashMap};
 use std::num::{NonZeroU32, NonZeroU64};
 
-use api::grpc::conversions::{from_grpc_dist, payload_to_proto, proto_to_payloads};
+use api::grpc::conversions::{
+    from_grpc_datatype, from_grpc_dist, payload_to_proto, proto_to_payloads,
+};
 use itertools::Itertools;
 use segment::data_types::vectors::{NamedVector, VectorStruct, DEFAULT_VECTOR_NAME};
-use segment::types::Distance;
+use segment::types::{Distance, ProductQuantizationConfig};
 use tonic::Status;
 
 use super::config_diff::CollectionParamsDiff;
 use crate::config::{
-    default_replication_factor, default_write_consistency_factor, CollectionConfig,
-    CollectionParams, VectorParams, VectorsConfig, WalConfig,
+    default_operation_id_window_sec, default_replication_factor, default_write_consistency_factor,
+    CollectionConfig, CollectionParams, VectorParams, VectorsConfig, WalConfig,
+};
+use crate::operations::auto_payload_index::AutoPayloadIndexConfig;
+use crate::operations::changes::{PointsChange, PointsChanges};
+use crate::operations::config_diff::{
+    Disabled, HnswConfigDiff, OptimizersConfigDiff, QuantizationConfigDiff, VectorParamsDiff,
+    VectorsConfigDiff, WalConfigDiff,
 };
-use crate::operations::config_diff::{HnswConfigDiff, OptimizersConfigDiff, WalConfigDiff};
 use crate::operations::point_ops::PointsSelector::PointIdsSelector;
 use crate::operations::point_ops::{
-    Batch, FilterSelector, PointIdsList, PointStruct, PointsSelector,
+    Batch, FilterSelector, PointIdsList, PointStruct, PointsSelector, WriteOrdering,
 };
+use crate::operations::ttl::{default_check_interval_sec, TtlConfig};
 use crate::operations::types::{
-    CollectionInfo, CollectionStatus, CountResult, OptimizersStatus, RecommendRequest, Record,
-    SearchRequest, UpdateResult, UpdateStatus,
+    default_facet_limit, CollectionInfo, CollectionInitStatus, CollectionStatus, CountResult,
+    FacetRequest, FacetResponse, IndexRebuildProgress, LookupLocation, OptimizersStatus,
+    RecommendRequest, Record, ReplicaOperationId, Sample, SearchRequest, UpdateResult,
+    UpdateStatus,
 };
 use crate::optimizers_builder::OptimizersConfig;
 use crate::shards::remote_shard::CollectionSearchRequest;
+use crate::shards::replica_sync::PointDigest;
 
 impl From<api::grpc::qdrant::HnswConfigDiff> for HnswConfigDiff {
     fn from(value: api::grpc::qdrant::HnswConfigDiff) -> Self {
@@ -39,6 +50,8 @@ impl From<api::grpc::qdrant::WalConfigDiff> for WalConfigDiff {
         Self {
             wal_capacity_mb: value.wal_capacity_mb.map(|v| v as usize),
             wal_segments_ahead: value.wal_segments_ahead.map(|v| v as usize),
+            encrypted: value.encrypted,
+            operation_id_window_sec: value.operation_id_window_sec,
         }
     }
 }
@@ -93,6 +106,9 @@ impl From<CollectionInfo> for api::grpc::qdrant::CollectionInfo {
             segments_count,
             config,
             payload_schema,
+            index_rebuild,
+            config_migration,
+            initialization,
         } = value;
 
         api::grpc::qdrant::CollectionInfo {
@@ -143,6 +159,7 @@ impl From<CollectionInfo> for api::grpc::qdrant::CollectionInfo {
                     replication_factor: Some(config.params.replication_factor.get()),
                     on_disk_payload: config.params.on_disk_payload,
                     write_consistency_factor: Some(config.params.write_consistency_factor.get()),
+                    default_vector: config.params.default_vector,
                 }),
                 hnsw_config: Some(api::grpc::qdrant::HnswConfigDiff {
                     m: Some(config.hnsw_config.m as u64),
@@ -169,12 +186,32 @@ impl From<CollectionInfo> for api::grpc::qdrant::CollectionInfo {
                 wal_config: Some(api::grpc::qdrant::WalConfigDiff {
                     wal_capacity_mb: Some(config.wal_config.wal_capacity_mb as u64),
                     wal_segments_ahead: Some(config.wal_config.wal_segments_ahead as u64),
+                    encrypted: Some(config.wal_config.encrypted),
+                    operation_id_window_sec: Some(config.wal_config.operation_id_window_sec),
                 }),
+                payload_field_types: config.payload_field_types.map(Into::into),
+                auto_payload_index: config.auto_payload_index.map(Into::into),
+                ttl: config.ttl.map(Into::into),
             }),
             payload_schema: payload_schema
                 .into_iter()
                 .map(|(k, v)| (k, v.into()))
                 .collect(),
+            index_rebuild: index_rebuild.map(|progress| api::grpc::qdrant::IndexRebuildProgress {
+                pending_segments: progress.pending_segments as u64,
+                rebuilding_segments: progress.rebuilding_segments as u64,
+            }),
+            config_migration: config_migration.map(|progress| {
+                api::grpc::qdrant::IndexRebuildProgress {
+                    pending_segments: progress.pending_segments as u64,
+                    rebuilding_segments: progress.rebuilding_segments as u64,
+                }
+            }),
+            initialization: initialization.map(|status| api::grpc::qdrant::CollectionInitStatus {
+                source: status.source,
+                copied_points: status.copied_points as u64,
+                error: status.error,
+            }),
         }
     }
 }
@@ -187,6 +224,7 @@ impl From<Record> for api::grpc::qdrant::RetrievedPoint {
             id: Some(record.id.into()),
             payload: record.payload.map(payload_to_proto).unwrap_or_default(),
             vectors,
+            order_value: record.order_value,
         }
     }
 }
@@ -204,6 +242,7 @@ impl TryFrom<api::grpc::qdrant::RetrievedPoint> for Record {
             id: retrieved_point.id.unwrap().try_into()?,
             payload: Some(proto_to_payloads(retrieved_point.payload)?),
             vector: vectors,
+            order_value: retrieved_point.order_value,
         })
     }
 }
@@ -246,6 +285,10 @@ impl From<api::grpc::qdrant::WalConfigDiff> for WalConfig {
         Self {
             wal_capacity_mb: wal_config.wal_capacity_mb.unwrap_or_default() as usize,
             wal_segments_ahead: wal_config.wal_segments_ahead.unwrap_or_default() as usize,
+            encrypted: wal_config.encrypted.unwrap_or_default(),
+            operation_id_window_sec: wal_config
+                .operation_id_window_sec
+                .unwrap_or_else(default_operation_id_window_sec),
         }
     }
 }
@@ -259,10 +302,111 @@ impl TryFrom<api::grpc::qdrant::VectorParams> for VectorParams {
                 Status::invalid_argument("VectorParams size must be greater than zero")
             })?,
             distance: from_grpc_dist(vector_params.distance)?,
+            hnsw_config: vector_params.hnsw_config.map(HnswConfigDiff::from),
+            quantization_config: vector_params.quantization_config.map(|config| {
+                ProductQuantizationConfig {
+                    num_subvectors: config.num_subvectors as usize,
+                }
+            }),
+            on_disk: vector_params.on_disk,
+            datatype: match vector_params.datatype {
+                Some(datatype) => from_grpc_datatype(datatype)?,
+                None => None,
+            },
+            multivector_config: vector_params
+                .multivector_config
+                .map(TryInto::try_into)
+                .transpose()?,
         })
     }
 }
 
+impl TryFrom<api::grpc::qdrant::QuantizationConfigDiff> for QuantizationConfigDiff {
+    type Error = Status;
+
+    fn try_from(config: api::grpc::qdrant::QuantizationConfigDiff) -> Result<Self, Self::Error> {
+        use api::grpc::qdrant::quantization_config_diff::Quantization;
+        match config.quantization {
+            None => Err(Status::invalid_argument(
+                "Expected `quantization` - product quantization config or disabled",
+            )),
+            Some(Quantization::Product(config)) => {
+                Ok(QuantizationConfigDiff::Product(ProductQuantizationConfig {
+                    num_subvectors: config.num_subvectors as usize,
+                }))
+            }
+            Some(Quantization::Disabled(_)) => {
+                Ok(QuantizationConfigDiff::Disabled(Disabled::Disabled))
+            }
+        }
+    }
+}
+
+impl TryFrom<api::grpc::qdrant::VectorsConfigDiff> for VectorsConfigDiff {
+    type Error = Status;
+
+    fn try_from(config: api::grpc::qdrant::VectorsConfigDiff) -> Result<Self, Self::Error> {
+        let params = config
+            .map
+            .into_iter()
+            .map(|(name, params)| {
+                let params = VectorParamsDiff {
+                    hnsw_config: params.hnsw_config.map(HnswConfigDiff::from),
+                    quantization_config: params
+                        .quantization_config
+                        .map(TryInto::try_into)
+                        .transpose()?,
+                    on_disk: params.on_disk,
+                };
+                Ok((name, params))
+            })
+            .collect::<Result<_, Status>>()?;
+        Ok(VectorsConfigDiff(params))
+    }
+}
+
+impl From<api::grpc::qdrant::AutoPayloadIndexConfig> for AutoPayloadIndexConfig {
+    fn from(config: api::grpc::qdrant::AutoPayloadIndexConfig) -> Self {
+        let default = AutoPayloadIndexConfig::default();
+        Self {
+            max_indexed_fields: config
+                .max_indexed_fields
+                .map(|max| max as usize)
+                .unwrap_or(default.max_indexed_fields),
+        }
+    }
+}
+
+impl From<AutoPayloadIndexConfig> for api::grpc::qdrant::AutoPayloadIndexConfig {
+    fn from(config: AutoPayloadIndexConfig) -> Self {
+        Self {
+            max_indexed_fields: Some(config.max_indexed_fields as u64),
+        }
+    }
+}
+
+impl From<api::grpc::qdrant::TtlConfig> for TtlConfig {
+    fn from(config: api::grpc::qdrant::TtlConfig) -> Self {
+        Self {
+            field: config.field,
+            ttl_sec: config.ttl_sec,
+            check_interval_sec: config
+                .check_interval_sec
+                .unwrap_or_else(default_check_interval_sec),
+        }
+    }
+}
+
+impl From<TtlConfig> for api::grpc::qdrant::TtlConfig {
+    fn from(config: TtlConfig) -> Self {
+        Self {
+            field: config.field,
+            ttl_sec: config.ttl_sec,
+            check_interval_sec: Some(config.check_interval_sec),
+        }
+    }
+}
+
 impl TryFrom<api::grpc::qdrant::CollectionConfig> for CollectionConfig {
     type Error = Status;
 
@@ -316,6 +460,7 @@ impl TryFrom<api::grpc::qdrant::CollectionConfig> for CollectionConfig {
                     .ok_or_else(|| {
                         Status::invalid_argument("`write_consistency_factor` cannot be zero")
                     })?,
+                    default_vector: params.default_vector,
                 },
             },
             hnsw_config: match config.hnsw_config {
@@ -330,6 +475,12 @@ impl TryFrom<api::grpc::qdrant::CollectionConfig> for CollectionConfig {
                 None => return Err(Status::invalid_argument("Malformed WalConfig type")),
                 Some(wal_config) => wal_config.into(),
             },
+            payload_field_types: config
+                .payload_field_types
+                .map(TryInto::try_into)
+                .transpose()?,
+            auto_payload_index: config.auto_payload_index.map(Into::into),
+            ttl: config.ttl.map(Into::into),
         })
     }
 }
@@ -371,6 +522,25 @@ impl TryFrom<api::grpc::qdrant::GetCollectionInfoResponse> for CollectionInfo {
                     .into_iter()
                     .map(|(k, v)| v.try_into().map(|v| (k, v)))
                     .try_collect()?,
+                index_rebuild: collection_info_response.index_rebuild.map(|progress| {
+                    IndexRebuildProgress {
+                        pending_segments: progress.pending_segments as usize,
+                        rebuilding_segments: progress.rebuilding_segments as usize,
+                    }
+                }),
+                config_migration: collection_info_response.config_migration.map(|progress| {
+                    IndexRebuildProgress {
+                        pending_segments: progress.pending_segments as usize,
+                        rebuilding_segments: progress.rebuilding_segments as usize,
+                    }
+                }),
+                initialization: collection_info_response.initialization.map(|status| {
+                    CollectionInitStatus {
+                        source: status.source,
+                        copied_points: status.copied_points as usize,
+                        error: status.error,
+                    }
+                }),
             }),
         }
     }
@@ -486,6 +656,17 @@ impl From<UpdateResult> for api::grpc::qdrant::UpdateResult {
                 UpdateStatus::Acknowledged => api::grpc::qdrant::UpdateStatus::Acknowledged as i32,
                 UpdateStatus::Completed => api::grpc::qdrant::UpdateStatus::Completed as i32,
             },
+            replica_operation_ids: value
+                .replica_operation_ids
+                .into_iter()
+                .map(
+                    |replica_operation_id| api::grpc::qdrant::ReplicaOperationId {
+                        shard_id: replica_operation_id.shard_id,
+                        peer_id: replica_operation_id.peer_id,
+                        operation_id: replica_operation_id.operation_id,
+                    },
+                )
+                .collect(),
         }
     }
 }
@@ -505,10 +686,61 @@ impl TryFrom<api::grpc::qdrant::UpdateResult> for UpdateResult {
                 }
                 _ => return Err(Status::invalid_argument("Malformed UpdateStatus type")),
             },
+            replica_operation_ids: value
+                .replica_operation_ids
+                .into_iter()
+                .map(|replica_operation_id| ReplicaOperationId {
+                    shard_id: replica_operation_id.shard_id,
+                    peer_id: replica_operation_id.peer_id,
+                    operation_id: replica_operation_id.operation_id,
+                })
+                .collect(),
         })
     }
 }
 
+impl From<WriteOrdering> for api::grpc::qdrant::WriteOrdering {
+    fn from(value: WriteOrdering) -> Self {
+        match value {
+            WriteOrdering::Weak => api::grpc::qdrant::WriteOrdering::Weak,
+            WriteOrdering::Medium => api::grpc::qdrant::WriteOrdering::Medium,
+            WriteOrdering::Strong => api::grpc::qdrant::WriteOrdering::Strong,
+        }
+    }
+}
+
+impl TryFrom<i32> for WriteOrdering {
+    type Error = Status;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match api::grpc::qdrant::WriteOrdering::from_i32(value) {
+            Some(api::grpc::qdrant::WriteOrdering::Weak) => Ok(WriteOrdering::Weak),
+            Some(api::grpc::qdrant::WriteOrdering::Medium) => Ok(WriteOrdering::Medium),
+            Some(api::grpc::qdrant::WriteOrdering::Strong) => Ok(WriteOrdering::Strong),
+            None => Err(Status::invalid_argument("Malformed WriteOrdering type")),
+        }
+    }
+}
+
+impl From<Sample> for api::grpc::qdrant::Sample {
+    fn from(value: Sample) -> Self {
+        match value {
+            Sample::Random => api::grpc::qdrant::Sample::Random,
+        }
+    }
+}
+
+impl TryFrom<i32> for Sample {
+    type Error = Status;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match api::grpc::qdrant::Sample::from_i32(value) {
+            Some(api::grpc::qdrant::Sample::Random) => Ok(Sample::Random),
+            None => Err(Status::invalid_argument("Malformed Sample type")),
+        }
+    }
+}
+
 impl From<api::grpc::qdrant::CountResult> for CountResult {
     fn from(value: api::grpc::qdrant::CountResult) -> Self {
         Self {
@@ -525,6 +757,96 @@ impl From<CountResult> for api::grpc::qdrant::CountResult {
     }
 }
 
+impl TryFrom<api::grpc::qdrant::FacetPoints> for FacetRequest {
+    type Error = Status;
+
+    fn try_from(value: api::grpc::qdrant::FacetPoints) -> Result<Self, Self::Error> {
+        Ok(Self {
+            key: value.key,
+            filter: value.filter.map(|f| f.try_into()).transpose()?,
+            limit: value
+                .limit
+                .map(|limit| limit as usize)
+                .unwrap_or_else(default_facet_limit),
+        })
+    }
+}
+
+impl TryFrom<api::grpc::qdrant::FacetResponse> for FacetResponse {
+    type Error = Status;
+
+    fn try_from(value: api::grpc::qdrant::FacetResponse) -> Result<Self, Self::Error> {
+        Ok(Self {
+            hits: value
+                .hits
+                .into_iter()
+                .map(|hit| hit.try_into())
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl TryFrom<PointsChange> for api::grpc::qdrant::PointsChange {
+    type Error = Status;
+
+    fn try_from(value: PointsChange) -> Result<Self, Self::Error> {
+        Ok(Self {
+            shard_id: value.shard_id,
+            op_num: value.op_num,
+            event: value.event.name().to_string(),
+            data: serde_json::to_string(&value).map_err(|err| Status::internal(err.to_string()))?,
+        })
+    }
+}
+
+impl TryFrom<api::grpc::qdrant::PointsChange> for PointsChange {
+    type Error = Status;
+
+    fn try_from(value: api::grpc::qdrant::PointsChange) -> Result<Self, Self::Error> {
+        serde_json::from_str(&value.data).map_err(|err| {
+            Status::invalid_argument(format!("Malformed change {}: {err}", value.op_num))
+        })
+    }
+}
+
+impl TryFrom<api::grpc::qdrant::ReadChangesResponse> for PointsChanges {
+    type Error = Status;
+
+    fn try_from(value: api::grpc::qdrant::ReadChangesResponse) -> Result<Self, Self::Error> {
+        Ok(Self {
+            changes: value
+                .changes
+                .into_iter()
+                .map(|change| change.try_into())
+                .collect::<Result<_, _>>()?,
+            next_op_num: value.next_op_num,
+        })
+    }
+}
+
+impl From<PointDigest> for api::grpc::qdrant::PointDigest {
+    fn from(value: PointDigest) -> Self {
+        Self {
+            id: Some(value.id.into()),
+            digest: value.digest,
+        }
+    }
+}
+
+impl TryFrom<api::grpc::qdrant::PointDigest> for PointDigest {
+    type Error = Status;
+
+    fn try_from(value: api::grpc::qdrant::PointDigest) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: value
+                .id
+                .ok_or_else(|| Status::invalid_argument("Empty ID is not allowed"))?
+                .try_into()?,
+            digest: value.digest,
+        })
+    }
+}
+
 // Use wrapper type to bundle CollectionId & SearchRequest
 impl<'a> From<CollectionSearchRequest<'a>> for api::grpc::qdrant::SearchPoints {
     fn from(value: CollectionSearchRequest<'a>) -> Self {
@@ -605,10 +927,20 @@ impl TryFrom<api::grpc::qdrant::RecommendPoints> for RecommendRequest {
             ),
             score_threshold: value.score_threshold,
             using: value.using.map(|name| name.into()),
+            lookup_from: value.lookup_from.map(|location| location.into()),
         })
     }
 }
 
+impl From<api::grpc::qdrant::LookupLocation> for LookupLocation {
+    fn from(value: api::grpc::qdrant::LookupLocation) -> Self {
+        Self {
+            collection: value.collection_name,
+            vector: value.vector_name,
+        }
+    }
+}
+
 impl From<VectorParams> for api::grpc::qdrant::VectorParams {
     fn from(value: VectorParams) -> Self {
         api::grpc::qdrant::VectorParams {
@@ -619,6 +951,24 @@ impl From<VectorParams> for api::grpc::qdrant::VectorParams {
                 Distance::Dot => api::grpc::qdrant::Distance::Dot,
             }
             .into(),
+            hnsw_config: value
+                .hnsw_config
+                .map(|hnsw_config| api::grpc::qdrant::HnswConfigDiff {
+                    m: hnsw_config.m.map(|v| v as u64),
+                    ef_construct: hnsw_config.ef_construct.map(|v| v as u64),
+                    full_scan_threshold: hnsw_config.full_scan_threshold.map(|v| v as u64),
+                    max_indexing_threads: None,
+                }),
+            quantization_config: value.quantization_config.map(|config| {
+                api::grpc::qdrant::ProductQuantizationConfig {
+                    num_subvectors: config.num_subvectors as u64,
+                }
+            }),
+            on_disk: value.on_disk,
+            datatype: value
+                .datatype
+                .map(|datatype| api::grpc::qdrant::Datatype::from(datatype).into()),
+            multivector_config: value.multivector_config.map(Into::into),
         }
     }
 }
@@ -1,10 +1,14 @@
+pub mod auto_payload_index;
+pub mod changes;
 pub mod cluster_ops;
 pub mod config_diff;
 mod conversions;
 pub mod operation_effect;
 pub mod payload_ops;
+pub mod payload_types;
 pub mod point_ops;
 pub mod snapshot_ops;
+pub mod ttl;
 pub mod types;
 
 use std::collections::HashMap;
@@ -41,6 +45,33 @@ pub enum CollectionUpdateOperations {
     FieldIndexOperation(FieldIndexOperations),
 }
 
+/// Update operation with the optional client-supplied id, as it is stored in the shard WAL.
+/// Operations with the same id are applied to the shard only once within the deduplication
+/// window, so retries of the timed out requests do not duplicate the changes.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct OperationWithId {
+    #[serde(flatten)]
+    pub operation: CollectionUpdateOperations,
+    /// Records, written before the ids were supported, have no id
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operation_id: Option<String>,
+}
+
+impl OperationWithId {
+    pub fn new(operation: CollectionUpdateOperations, operation_id: Option<String>) -> Self {
+        Self {
+            operation,
+            operation_id,
+        }
+    }
+}
+
+impl From<CollectionUpdateOperations> for OperationWithId {
+    fn from(operation: CollectionUpdateOperations) -> Self {
+        Self::new(operation, None)
+    }
+}
+
 /// A mapping of operation to shard.
 /// Is a result of splitting one operation into several shards by corresponding PointIds
 pub enum OperationToShard<O> {
@@ -145,6 +176,16 @@ impl SplitByShard for CollectionUpdateOperations {
     }
 }
 
+/// Each shard receives the same id, so retries are deduplicated by every shard independently
+impl SplitByShard for OperationWithId {
+    fn split_by_shard(self, ring: &HashRing<ShardId>) -> OperationToShard<Self> {
+        let operation_id = self.operation_id;
+        self.operation
+            .split_by_shard(ring)
+            .map(|operation| OperationWithId::new(operation, operation_id.clone()))
+    }
+}
+
 impl CollectionUpdateOperations {
     pub fn is_write_operation(&self) -> bool {
         match self {
@@ -175,4 +216,25 @@ mod tests {
         let json = serde_json::to_string_pretty(&op).unwrap();
         println!("{}", json)
     }
+
+    #[test]
+    fn test_operation_with_id_serialization() {
+        let op = CollectionUpdateOperations::PointOperation(point_ops::PointOperations::DeletePoints {
+            ids: vec![1.into(), 2.into()],
+        });
+
+        // Records, written before the ids were supported
+        let plain = serde_cbor::to_vec(&op).unwrap();
+        let record: OperationWithId = serde_cbor::from_slice(&plain).unwrap();
+        assert!(record.operation_id.is_none());
+        assert!(matches!(
+            record.operation,
+            CollectionUpdateOperations::PointOperation(point_ops::PointOperations::DeletePoints { .. })
+        ));
+
+        let with_id = OperationWithId::new(op, Some("batch-1".to_string()));
+        let record: OperationWithId =
+            serde_cbor::from_slice(&serde_cbor::to_vec(&with_id).unwrap()).unwrap();
+        assert_eq!(record.operation_id.as_deref(), Some("batch-1"));
+    }
 }
@@ -83,6 +83,9 @@ impl EstimateOperationEffectArea for PayloadOps {
             PayloadOps::DeletePayload(delete_payload) => {
                 OperationEffectArea::Points(delete_payload.points.clone())
             }
+            PayloadOps::UpdatePayload(update_payload) => {
+                OperationEffectArea::Points(update_payload.points.clone())
+            }
             PayloadOps::ClearPayload { points } => OperationEffectArea::Points(points.clone()),
             PayloadOps::ClearPayloadByFilter(filter) => OperationEffectArea::Filter(filter.clone()),
         }
@@ -1,7 +1,9 @@
 use schemars::JsonSchema;
+use segment::data_types::payload_update::PayloadUpdate;
 use segment::types::{Filter, Payload, PayloadKeyType, PointIdType};
 use serde;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 use super::{split_iter_by_shard, OperationToShard, SplitByShard};
 use crate::hash_ring::HashRing;
@@ -21,6 +23,30 @@ pub struct DeletePayload {
     pub points: Vec<PointIdType>, // ToDo: replace with point selector
 }
 
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+pub struct UpdatePayload {
+    /// Changes of the payload values, applied in order
+    pub updates: Vec<PayloadUpdate>,
+    /// Updates payload of each point in this list
+    pub points: Vec<PointIdType>,
+}
+
+impl UpdatePayload {
+    /// Payload of the values, written by the updates, to check their types and infer indexes
+    pub fn written_values(&self) -> Payload {
+        let mut payload = Payload::default();
+        for update in &self.updates {
+            let value = match update {
+                PayloadUpdate::Increment { by, .. } => Value::Number(by.clone()),
+                PayloadUpdate::Append { values, .. } => Value::Array(values.clone()),
+                PayloadUpdate::SetIfAbsent { value, .. } => value.clone(),
+            };
+            payload.0.insert(update.key().clone(), value);
+        }
+        payload
+    }
+}
+
 /// Define operations description for point payloads manipulation
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 #[serde(rename_all = "snake_case")]
@@ -29,6 +55,8 @@ pub enum PayloadOps {
     SetPayload(SetPayload),
     /// Deletes specified payload values if they are assigned
     DeletePayload(DeletePayload),
+    /// Change payload values, depending on their current values, e.g. increment counters
+    UpdatePayload(UpdatePayload),
     /// Drops all Payload values associated with given points.
     ClearPayload { points: Vec<PointIdType> },
     /// Clear all Payload values by given filter criteria.
@@ -40,6 +68,7 @@ impl PayloadOps {
         match self {
             PayloadOps::SetPayload(_) => true,
             PayloadOps::DeletePayload(_) => false,
+            PayloadOps::UpdatePayload(_) => true,
             PayloadOps::ClearPayload { .. } => false,
             PayloadOps::ClearPayloadByFilter(_) => false,
         }
@@ -55,6 +84,9 @@ impl SplitByShard for PayloadOps {
             PayloadOps::DeletePayload(operation) => operation
                 .split_by_shard(ring)
                 .map(PayloadOps::DeletePayload),
+            PayloadOps::UpdatePayload(operation) => operation
+                .split_by_shard(ring)
+                .map(PayloadOps::UpdatePayload),
             PayloadOps::ClearPayload { points } => split_iter_by_shard(points, |id| *id, ring)
                 .map(|points| PayloadOps::ClearPayload { points }),
             operation @ PayloadOps::ClearPayloadByFilter(_) => OperationToShard::to_all(operation),
@@ -71,6 +103,15 @@ impl SplitByShard for DeletePayload {
     }
 }
 
+impl SplitByShard for UpdatePayload {
+    fn split_by_shard(self, ring: &HashRing<ShardId>) -> OperationToShard<Self> {
+        split_iter_by_shard(self.points, |id| *id, ring).map(|points| UpdatePayload {
+            points,
+            updates: self.updates.clone(),
+        })
+    }
+}
+
 impl SplitByShard for SetPayload {
     fn split_by_shard(self, ring: &HashRing<ShardId>) -> OperationToShard<Self> {
         split_iter_by_shard(self.points, |id| *id, ring).map(|points| SetPayload {
@@ -0,0 +1,128 @@
+use std::collections::BTreeMap;
+
+use segment::common::utils::get_value_from_json_map;
+use segment::types::{value_type, DateTimePayloadType, Payload, PayloadKeyType, PayloadSchemaType};
+use serde_json::Value;
+
+use crate::operations::payload_ops::PayloadOps;
+use crate::operations::point_ops::{PointInsertOperations, PointOperations};
+use crate::operations::types::{CollectionError, CollectionResult};
+use crate::operations::CollectionUpdateOperations;
+
+/// Expected types of payload fields by their key. Nested keys are separated with `.`
+pub type PayloadFieldTypes = BTreeMap<PayloadKeyType, PayloadSchemaType>;
+
+fn value_has_type(value: &Value, expected: PayloadSchemaType) -> bool {
+    match value {
+        // Missing values do not break payload indexes
+        Value::Null => true,
+        Value::Array(values) => values.iter().all(|value| value_has_type(value, expected)),
+        _ => match expected {
+            PayloadSchemaType::Keyword | PayloadSchemaType::Text => value.is_string(),
+            PayloadSchemaType::Integer => value.is_i64(),
+            PayloadSchemaType::Float => value.is_number(),
+            PayloadSchemaType::Geo => value_type(value) == Some(PayloadSchemaType::Geo),
+            PayloadSchemaType::Datetime => value
+                .as_str()
+                .map_or(false, |value| value.parse::<DateTimePayloadType>().is_ok()),
+        },
+    }
+}
+
+/// Check that values of the typed fields in the payload have the expected types.
+/// Fields, which are not mentioned in `field_types`, could have any type.
+pub fn check_payload_types(
+    field_types: &PayloadFieldTypes,
+    payload: &Payload,
+) -> CollectionResult<()> {
+    for (key, expected) in field_types {
+        if let Some(value) = get_value_from_json_map(key, &payload.0) {
+            if !value_has_type(value, *expected) {
+                return Err(CollectionError::BadInput {
+                    description: format!(
+                        "Payload field `{key}` is expected to be of type {expected:?}, got {value}"
+                    ),
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check payloads, written by the operation, against the expected field types
+pub fn check_operation_payload_types(
+    field_types: &PayloadFieldTypes,
+    operation: &CollectionUpdateOperations,
+) -> CollectionResult<()> {
+    if field_types.is_empty() {
+        return Ok(());
+    }
+    match operation {
+        CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(
+            PointInsertOperations::PointsBatch(batch),
+        )) => batch
+            .payloads
+            .iter()
+            .flatten()
+            .flatten()
+            .try_for_each(|payload| check_payload_types(field_types, payload)),
+        CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(
+            PointInsertOperations::PointsList(points),
+        )) => points
+            .iter()
+            .filter_map(|point| point.payload.as_ref())
+            .try_for_each(|payload| check_payload_types(field_types, payload)),
+        CollectionUpdateOperations::PayloadOperation(PayloadOps::SetPayload(set_payload)) => {
+            check_payload_types(field_types, &set_payload.payload)
+        }
+        CollectionUpdateOperations::PayloadOperation(PayloadOps::UpdatePayload(update_payload)) => {
+            check_payload_types(field_types, &update_payload.written_values())
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn payload(value: Value) -> Payload {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_check_payload_types() {
+        let field_types = PayloadFieldTypes::from([
+            ("city".to_string(), PayloadSchemaType::Keyword),
+            ("count".to_string(), PayloadSchemaType::Integer),
+            ("price".to_string(), PayloadSchemaType::Float),
+            ("address.location".to_string(), PayloadSchemaType::Geo),
+        ]);
+
+        let valid = payload(json!({
+            "city": ["Berlin", "Moscow"],
+            "count": 10,
+            "price": 1,
+            "address": {"location": {"lon": 13.4, "lat": 52.5}},
+            "other": true,
+        }));
+        assert!(check_payload_types(&field_types, &valid).is_ok());
+
+        let partial = payload(json!({"price": 1.5}));
+        assert!(check_payload_types(&field_types, &partial).is_ok());
+
+        let wrong_type = payload(json!({"count": "10"}));
+        assert!(check_payload_types(&field_types, &wrong_type).is_err());
+
+        let mixed_array = payload(json!({"city": ["Berlin", 1]}));
+        assert!(check_payload_types(&field_types, &mixed_array).is_err());
+
+        let float_as_integer = payload(json!({"count": 1.5}));
+        assert!(check_payload_types(&field_types, &float_as_integer).is_err());
+
+        let wrong_nested = payload(json!({"address": {"location": "Berlin"}}));
+        assert!(check_payload_types(&field_types, &wrong_nested).is_err());
+    }
+}
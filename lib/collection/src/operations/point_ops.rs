@@ -37,6 +37,7 @@ impl TryFrom<Record> for PointStruct {
             id,
             payload,
             vector,
+            order_value: _,
         } = record;
 
         if vector.is_none() {
@@ -181,6 +182,23 @@ impl JsonSchema for PointInsertOperations {
     }
 }
 
+/// Defines write ordering guarantees for collection operations
+///
+/// * `weak` - write operations may be reordered, works faster, default
+///
+/// * `medium` - write operations go through dynamically selected leader, may be inconsistent for a short period of time in case of leader change
+///
+/// * `strong` - Write operations go through the permanent leader, consistent, but may be unavailable if leader is down
+///
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WriteOrdering {
+    #[default]
+    Weak,
+    Medium,
+    Strong,
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(rename_all = "snake_case")]
 pub enum PointOperations {
@@ -412,6 +430,23 @@ impl From<Vec<PointStruct>> for PointInsertOperations {
     }
 }
 
+impl From<Batch> for Vec<PointStruct> {
+    fn from(batch: Batch) -> Self {
+        let num_points = batch.ids.len();
+        let mut payloads = batch.payloads.unwrap_or_default().into_iter();
+        batch
+            .ids
+            .into_iter()
+            .zip(batch.vectors.into_all_vectors(num_points))
+            .map(|(id, vectors)| PointStruct {
+                id,
+                vector: vectors.into(),
+                payload: payloads.next().flatten(),
+            })
+            .collect()
+    }
+}
+
 impl From<Batch> for PointOperations {
     fn from(batch: Batch) -> Self {
         PointOperations::UpsertPoints(PointInsertOperations::PointsBatch(batch))
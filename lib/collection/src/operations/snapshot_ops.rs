@@ -1,12 +1,139 @@
-use std::path::Path;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io;
+use std::path::{Component, Path, PathBuf};
 use std::time::SystemTime;
 
 use api::grpc::conversions::date_time_to_proto;
 use chrono::NaiveDateTime;
 use schemars::JsonSchema;
+use segment::types::SeqNumberType;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
-use crate::operations::types::CollectionResult;
+use crate::operations::types::{CollectionError, CollectionResult};
+use crate::shards::shard::ShardId;
+
+/// File in the root of the snapshot archive with the checksums of its files and the versions
+/// of its segments
+pub const SNAPSHOT_MANIFEST_FILE: &str = "snapshot_manifest.json";
+
+/// Versions of the segments of a shard by segment id
+pub type SegmentVersions = HashMap<String, SeqNumberType>;
+
+/// Content of the snapshot. Used to verify the snapshot on recovery and to create and recover
+/// incremental snapshots.
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
+pub struct SnapshotManifest {
+    /// Snapshot, the segments, which are not included into this snapshot, are taken from.
+    /// `None` for full snapshots.
+    #[serde(default)]
+    pub base: Option<String>,
+    /// Versions of the segments of the local shards at the moment of the snapshot,
+    /// including the ones, taken from the base snapshot
+    #[serde(default)]
+    pub shards: HashMap<ShardId, SegmentVersions>,
+    /// SHA256 of the files of the snapshot by their path relative to the archive root
+    #[serde(default)]
+    pub checksums: BTreeMap<String, String>,
+}
+
+impl SnapshotManifest {
+    /// Write the manifest into the file. It is added into the archive first, so it is not
+    /// lost, if the archive is truncated.
+    pub fn save(&self, path: &Path) -> CollectionResult<()> {
+        let file = File::create(path)?;
+        serde_json::to_writer(file, self)?;
+        Ok(())
+    }
+
+    pub fn load(dir: &Path) -> CollectionResult<Option<Self>> {
+        let path = dir.join(SNAPSHOT_MANIFEST_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_reader(File::open(path)?)?))
+    }
+
+    /// Read the manifest from the snapshot archive without unpacking it.
+    /// Snapshots of older versions have no manifest.
+    pub fn read_from_archive(snapshot_path: &Path) -> CollectionResult<Option<Self>> {
+        let mut archive = tar::Archive::new(File::open(snapshot_path)?);
+        for entry in archive.entries()? {
+            let entry = entry?;
+            if archive_entry_path(&entry.path()?) == Path::new(SNAPSHOT_MANIFEST_FILE) {
+                return Ok(Some(serde_json::from_reader(entry)?));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Check that all files of the manifest are unpacked into `dir` without changes
+    pub fn verify_checksums(&self, dir: &Path) -> CollectionResult<()> {
+        for path in self.checksums.keys() {
+            self.verify_checksum(dir, Path::new(path))?;
+        }
+        Ok(())
+    }
+
+    /// Check the file, unpacked into `dir` from the archive entry `path`.
+    /// Files, which are not in the manifest, are not checked.
+    pub fn verify_checksum(&self, dir: &Path, path: &Path) -> CollectionResult<()> {
+        let expected = match self.checksums.get(path.to_string_lossy().as_ref()) {
+            Some(expected) => expected,
+            None => return Ok(()),
+        };
+        let file_path = dir.join(path);
+        if !file_path.exists() {
+            return Err(CollectionError::service_error(format!(
+                "File {} is missing in the snapshot, the archive might be truncated",
+                path.display()
+            )));
+        }
+        if &file_checksum(&file_path)? != expected {
+            return Err(CollectionError::service_error(format!(
+                "Checksum of the file {} doesn't match the snapshot manifest",
+                path.display()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// SHA256 of the file content as a hex string
+pub fn file_checksum(path: &Path) -> CollectionResult<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Checksums of all files in the directory by their path relative to it
+pub fn directory_checksums(dir: &Path) -> CollectionResult<BTreeMap<String, String>> {
+    let mut checksums = BTreeMap::new();
+    let mut directories = vec![dir.to_path_buf()];
+    while let Some(directory) = directories.pop() {
+        for entry in std::fs::read_dir(&directory)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                directories.push(path);
+            } else {
+                let relative_path = path.strip_prefix(dir).unwrap_or(&path);
+                checksums.insert(
+                    relative_path.to_string_lossy().into_owned(),
+                    file_checksum(&path)?,
+                );
+            }
+        }
+    }
+    Ok(checksums)
+}
+
+/// Path of the archive entry relative to the archive root
+pub fn archive_entry_path(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|component| !matches!(component, Component::CurDir))
+        .collect()
+}
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 pub struct SnapshotDescription {
@@ -56,3 +183,34 @@ pub async fn list_snapshots_in_directory(
     }
     Ok(snapshots)
 }
+
+#[cfg(test)]
+mod tests {
+    use tempfile::Builder;
+
+    use super::*;
+
+    #[test]
+    fn test_verify_checksums() {
+        let dir = Builder::new().prefix("snapshot").tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("0/segments")).unwrap();
+        std::fs::write(dir.path().join("config.json"), "{}").unwrap();
+        std::fs::write(dir.path().join("0/segments/segment.tar"), "segment").unwrap();
+
+        let manifest = SnapshotManifest {
+            checksums: directory_checksums(dir.path()).unwrap(),
+            ..Default::default()
+        };
+        assert_eq!(
+            manifest.checksums.keys().collect::<Vec<_>>(),
+            vec!["0/segments/segment.tar", "config.json"]
+        );
+        manifest.verify_checksums(dir.path()).unwrap();
+
+        std::fs::write(dir.path().join("0/segments/segment.tar"), "segm").unwrap();
+        assert!(manifest.verify_checksums(dir.path()).is_err());
+
+        std::fs::remove_file(dir.path().join("0/segments/segment.tar")).unwrap();
+        assert!(manifest.verify_checksums(dir.path()).is_err());
+    }
+}
@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use schemars::JsonSchema;
+use segment::types::{
+    Condition, DateTimePayloadType, DatetimeRange, FieldCondition, Filter, PayloadKeyType,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::operations::point_ops::PointOperations;
+use crate::operations::CollectionUpdateOperations;
+
+pub fn default_check_interval_sec() -> u64 {
+    60
+}
+
+/// Expiration of the points by the datetime, stored in the payload
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub struct TtlConfig {
+    /// Payload field with the RFC 3339 datetime, the lifetime of the point is counted from.
+    /// Points without the field never expire.
+    pub field: PayloadKeyType,
+    /// Points are deleted once this number of seconds is passed since the datetime in the field.
+    /// 0 disables the expiration.
+    pub ttl_sec: u64,
+    /// How often expired points are deleted, in seconds
+    #[serde(default = "default_check_interval_sec")]
+    pub check_interval_sec: u64,
+}
+
+impl TtlConfig {
+    pub fn check_interval(&self) -> Duration {
+        // Zero interval would make the expirer spin
+        Duration::from_secs(self.check_interval_sec.max(1))
+    }
+
+    /// Filter of the points, which are expired at the `now` moment
+    pub fn expired_filter(&self, now: DateTimePayloadType) -> Filter {
+        let ttl_micros = i64::try_from(self.ttl_sec.saturating_mul(1_000_000)).unwrap_or(i64::MAX);
+        let expiration_start =
+            DateTimePayloadType(now.timestamp_micros().saturating_sub(ttl_micros));
+        Filter::new_must(Condition::Field(FieldCondition::new_datetime_range(
+            self.field.clone(),
+            DatetimeRange {
+                lt: Some(expiration_start),
+                gt: None,
+                gte: None,
+                lte: None,
+            },
+        )))
+    }
+
+    /// Operation, which deletes the points, expired by now
+    pub fn expire_operation(&self) -> CollectionUpdateOperations {
+        let now = DateTimePayloadType(Utc::now().timestamp_micros());
+        CollectionUpdateOperations::PointOperation(PointOperations::DeletePointsByFilter(
+            self.expired_filter(now),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_expired_filter() {
+        let config = TtlConfig {
+            field: "created_at".to_string(),
+            ttl_sec: 3600,
+            check_interval_sec: default_check_interval_sec(),
+        };
+        let now: DateTimePayloadType = "2024-01-02T12:00:00Z".parse().unwrap();
+        let filter = config.expired_filter(now);
+
+        let range = match &filter.must.as_ref().unwrap()[0] {
+            Condition::Field(condition) => {
+                assert_eq!(condition.key, "created_at");
+                condition.datetime_range.unwrap()
+            }
+            condition => panic!("Unexpected condition {condition:?}"),
+        };
+        let datetime = |value: &str| value.parse::<DateTimePayloadType>().unwrap();
+        assert!(range.check_range(datetime("2024-01-02T10:59:59Z")));
+        assert!(!range.check_range(datetime("2024-01-02T11:00:00Z")));
+        assert!(!range.check_range(datetime("2024-01-02T11:30:00+00:00")));
+
+        let parsed: TtlConfig =
+            serde_json::from_value(json!({"field": "created_at", "ttl_sec": 3600})).unwrap();
+        assert_eq!(parsed, config);
+    }
+}
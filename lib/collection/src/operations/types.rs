@@ -1,18 +1,21 @@
 use std::collections::HashMap;
+use std::num::NonZeroU32;
 use std::result;
-use std::time::SystemTimeError;
+use std::time::{Duration, SystemTimeError};
 
 use api::grpc::transport_channel_pool::RequestError;
 use futures::io;
 use schemars::JsonSchema;
 use segment::common::file_operations::FileStorageError;
+use segment::data_types::discovery::DiscoveryQuery;
+use segment::data_types::facets::FacetValueHit;
 use segment::data_types::vectors::{
     NamedVectorStruct, VectorStruct, VectorType, DEFAULT_VECTOR_NAME,
 };
 use segment::entry::entry_point::OperationError;
 use segment::types::{
-    Filter, Payload, PayloadIndexInfo, PayloadKeyType, PointIdType, ScoreType, SearchParams,
-    SeqNumberType, WithPayloadInterface, WithVector,
+    Filter, FloatPayloadType, OrderBy, Payload, PayloadIndexInfo, PayloadKeyType, PointIdType,
+    ScoreType, SearchParams, SeqNumberType, WithPayloadInterface, WithVector,
 };
 use serde;
 use serde::{Deserialize, Serialize};
@@ -27,6 +30,7 @@ use crate::config::CollectionConfig;
 use crate::save_on_disk;
 use crate::shards::replica_set::ReplicaState;
 use crate::shards::shard::{PeerId, ShardId};
+use crate::shards::transfer::shard_transfer::ShardTransferMethod;
 use crate::wal::WalError;
 
 /// Current state of the collection.
@@ -68,6 +72,9 @@ pub struct Record {
     pub payload: Option<Payload>,
     /// Vector of the point
     pub vector: Option<VectorStruct>,
+    /// Value of the payload field, used for ordering. Only present if `order_by` was requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_value: Option<FloatPayloadType>,
 }
 
 /// Current statistics and configuration of the collection
@@ -96,6 +103,52 @@ pub struct CollectionInfo {
     pub config: CollectionConfig,
     /// Types of stored payload
     pub payload_schema: HashMap<PayloadKeyType, PayloadIndexInfo>,
+    /// Progress of rebuilding indexes, which were not found on load (e.g. after snapshot recovery).
+    /// Absent if all indexes are in place.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index_rebuild: Option<IndexRebuildProgress>,
+    /// Progress of rebuilding segments, built with the vector params before the last collection
+    /// update. Absent if all segments use the current params.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_migration: Option<IndexRebuildProgress>,
+    /// Progress of copying points from another collection, requested with `init_from`.
+    /// Absent if the collection was not initialized from another one, or the copying is finished.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub initialization: Option<CollectionInitStatus>,
+}
+
+/// Each peer copies points into its own replicas, so the status is local to the peer
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Eq)]
+pub struct CollectionInitStatus {
+    /// Name of the collection, points are copied from
+    pub source: String,
+    /// Number of points, copied into the local shards so far
+    pub copied_points: usize,
+    /// Set if the copying failed. Points, copied before the failure, are kept
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Progress of rebuilding segments in background.
+/// Segments are searchable during the rebuild, but may be slower
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IndexRebuildProgress {
+    /// Number of segments, waiting for the rebuild
+    pub pending_segments: usize,
+    /// Number of segments, being rebuilt right now
+    pub rebuilding_segments: usize,
+}
+
+impl IndexRebuildProgress {
+    pub fn merge(this: Option<Self>, other: Option<Self>) -> Option<Self> {
+        match (this, other) {
+            (Some(this), Some(other)) => Some(Self {
+                pending_segments: this.pending_segments + other.pending_segments,
+                rebuilding_segments: this.rebuilding_segments + other.rebuilding_segments,
+            }),
+            (this, other) => this.or(other),
+        }
+    }
 }
 
 /// Current clustering distribution for the collection
@@ -113,6 +166,70 @@ pub struct CollectionClusterInfo {
     pub shard_transfers: Vec<ShardTransferInfo>,
 }
 
+/// Resources, used by a collection on a single peer
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CollectionUsage {
+    /// Number of points in the local shards
+    pub points_count: usize,
+    /// Size of the collection files
+    pub disk_bytes: u64,
+    /// Estimated size of the vectors, loaded into RAM
+    pub ram_bytes: u64,
+}
+
+impl std::ops::AddAssign for CollectionUsage {
+    fn add_assign(&mut self, other: Self) {
+        self.points_count += other.points_count;
+        self.disk_bytes += other.disk_bytes;
+        self.ram_bytes += other.ram_bytes;
+    }
+}
+
+/// Segment of a local shard, which data does not match the stored checksums
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Eq)]
+pub struct CorruptedSegment {
+    pub shard_id: ShardId,
+    /// Name of the segment directory
+    pub segment: String,
+    /// Description of the detected corruption
+    pub error: String,
+}
+
+/// Result of the verification of the collection data on a single peer
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default, PartialEq, Eq)]
+pub struct CollectionVerificationReport {
+    /// Number of the verified segments in the local shards
+    pub segments_checked: usize,
+    pub corrupted_segments: Vec<CorruptedSegment>,
+}
+
+impl std::ops::AddAssign for CollectionVerificationReport {
+    fn add_assign(&mut self, other: Self) {
+        self.segments_checked += other.segments_checked;
+        self.corrupted_segments.extend(other.corrupted_segments);
+    }
+}
+
+/// Result of the comparison of the shard replicas with the leading replica of each shard
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default, PartialEq, Eq)]
+pub struct ReplicaSyncReport {
+    /// Number of the shards, which had more than one active replica to compare
+    pub shards_checked: usize,
+    /// Number of the points of the leading replicas, compared with the other replicas
+    pub points_checked: usize,
+    /// Number of the points, which were missing or different on some replica and were re-synced
+    /// from the leading replica
+    pub points_repaired: usize,
+}
+
+impl std::ops::AddAssign for ReplicaSyncReport {
+    fn add_assign(&mut self, other: Self) {
+        self.shards_checked += other.shards_checked;
+        self.points_checked += other.points_checked;
+        self.points_repaired += other.points_repaired;
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct ShardTransferInfo {
     pub shard_id: ShardId,
@@ -121,6 +238,9 @@ pub struct ShardTransferInfo {
     /// If `true` transfer is a synchronization of a replicas
     /// If `false` transfer is a moving of a shard from one peer to another
     pub sync: bool,
+    pub method: ShardTransferMethod,
+    /// Max number of points, sent per second. Not limited if absent
+    pub max_points_per_second: Option<usize>,
 }
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema)]
@@ -141,10 +261,52 @@ pub struct RemoteShardInfo {
     pub shard_id: ShardId,
     /// Remote peer id
     pub peer_id: PeerId,
+    /// Address of the remote peer, as known to the consensus
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peer_uri: Option<String>,
     /// Is replica active
     pub state: ReplicaState,
 }
 
+/// Parameters of the resharding, which should be estimated without applying it
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct ReshardingPreviewRequest {
+    /// Number of shards after resharding
+    pub shard_number: NonZeroU32,
+}
+
+/// Estimated state of the shard after resharding
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub struct ShardPreview {
+    pub shard_id: ShardId,
+    /// Number of points in the shard after resharding
+    pub points_count: usize,
+    /// Estimated size of vectors and payload in the shard after resharding, excluding indexes
+    pub estimated_size_bytes: usize,
+    /// Number of points, which should be transferred into the shard from other shards
+    pub incoming_points_count: usize,
+    /// Number of points, which should be transferred from the shard into other shards
+    pub outgoing_points_count: usize,
+}
+
+/// Estimated result of the resharding, extrapolated from a sample of points of each shard
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub struct ReshardingPreview {
+    /// Number of shards before resharding
+    pub current_shard_number: u32,
+    /// Number of shards after resharding
+    pub shard_number: u32,
+    /// Estimated state of each shard after resharding
+    pub shards: Vec<ShardPreview>,
+    /// Number of points, which change their shard
+    pub transfer_points_count: usize,
+    /// Estimated size of vectors and payload, which should be transferred between shards
+    pub transfer_size_bytes: usize,
+}
+
 /// `Acknowledged` - Request is saved to WAL and will be process in a queue.
 /// `Completed` - Request is completed, changes are actual.
 #[derive(Debug, Deserialize, Serialize, JsonSchema, PartialEq, Eq)]
@@ -161,6 +323,19 @@ pub struct UpdateResult {
     pub operation_id: SeqNumberType,
     /// Update status
     pub status: UpdateStatus,
+    /// Sequential numbers of the operation in the WAL of each updated replica.
+    /// Replicas number operations independently, so the number is only valid for its replica.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub replica_operation_ids: Vec<ReplicaOperationId>,
+}
+
+/// Sequential number of the operation in the WAL of a replica of the shard
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub struct ReplicaOperationId {
+    pub shard_id: ShardId,
+    pub peer_id: PeerId,
+    pub operation_id: SeqNumberType,
 }
 
 /// Scroll request - paginate over all points which matches given condition
@@ -178,6 +353,15 @@ pub struct ScrollRequest {
     /// Whether to return the point vector with the result?
     #[serde(default)]
     pub with_vector: WithVector,
+    /// Order points by the value of a payload field instead of ids.
+    /// If specified - `offset` is used as a tie-breaker for points with the same value,
+    /// equal to `order_by.start_from`.
+    #[serde(default)]
+    pub order_by: Option<OrderBy>,
+    /// Return a sample of points, which satisfy the filter, instead of a page.
+    /// Not compatible with `offset` and `order_by`, sample is returned in a single response.
+    #[serde(default)]
+    pub sample: Option<Sample>,
 }
 
 impl Default for ScrollRequest {
@@ -188,10 +372,41 @@ impl Default for ScrollRequest {
             filter: None,
             with_payload: Some(WithPayloadInterface::Bool(true)),
             with_vector: WithVector::Bool(false),
+            order_by: None,
+            sample: None,
         }
     }
 }
 
+/// Method of the points sampling
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Sample {
+    /// Uniformly random subset of the points
+    Random,
+}
+
+/// Sample request
+/// Returns uniformly random points of the collection, which satisfy the filter.
+/// Without the filter, points are picked by random offsets, so the collection is not read whole.
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct SampleRequest {
+    /// Number of the points to return
+    pub limit: usize,
+    /// Look only for points which satisfies this conditions
+    pub filter: Option<Filter>,
+    /// Select which payload to return with the response. Default: All
+    pub with_payload: Option<WithPayloadInterface>,
+    /// Whether to return the point vector with the result? Default: true
+    #[serde(default = "default_sample_with_vector")]
+    pub with_vector: WithVector,
+}
+
+fn default_sample_with_vector() -> WithVector {
+    WithVector::Bool(true)
+}
+
 /// Result of the points read request
 #[derive(Debug, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -200,6 +415,10 @@ pub struct ScrollResult {
     pub points: Vec<Record>,
     /// Offset which should be used to retrieve a next page result
     pub next_page_offset: Option<PointIdType>,
+    /// Value of the `order_by` field, which should be used as `order_by.start_from`
+    /// to retrieve a next page result. Only present if `order_by` was requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_page_order_value: Option<FloatPayloadType>,
 }
 
 /// Search request.
@@ -240,6 +459,33 @@ pub struct SearchRequestBatch {
     pub searches: Vec<SearchRequest>,
 }
 
+impl SearchRequest {
+    /// Time budget of the search, if any
+    pub fn max_time(&self) -> Option<Duration> {
+        self.params.and_then(|params| params.max_time())
+    }
+
+    /// Whether the chosen search strategies should be reported
+    pub fn explain(&self) -> bool {
+        self.params.map_or(false, |params| params.explain)
+    }
+}
+
+impl SearchRequestBatch {
+    /// The smallest time budget among the searches of the batch
+    pub fn max_time(&self) -> Option<Duration> {
+        self.searches
+            .iter()
+            .filter_map(|search| search.max_time())
+            .min()
+    }
+
+    /// Whether any search of the batch asks to report the chosen search strategies
+    pub fn explain(&self) -> bool {
+        self.searches.iter().any(|search| search.explain())
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct PointRequest {
@@ -272,7 +518,7 @@ impl From<String> for UsingVector {
 /// Service should look for the points which are closer to positive examples and at the same time
 /// further to negative examples. The concrete way of how to compare negative and positive distances
 /// is up to implementation in `segment` crate.
-#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 #[serde(rename_all = "snake_case")]
 pub struct RecommendRequest {
     /// Look for vectors closest to those
@@ -304,18 +550,110 @@ pub struct RecommendRequest {
     /// Define which vector to use for recommendation, if not specified - try to use default vector
     #[serde(default)]
     pub using: Option<UsingVector>,
+    /// The location used to lookup vectors of the positive and negative examples.
+    /// If not specified - vectors are looked up in the current collection
+    #[serde(default)]
+    pub lookup_from: Option<LookupLocation>,
 }
 
-#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+/// Collection and vector, which vectors of the recommendation examples are taken from
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub struct LookupLocation {
+    /// Name of the collection used for lookup
+    pub collection: String,
+    /// Name of the vector in the lookup collection. If not specified - the default vector is used
+    #[serde(default)]
+    pub vector: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 #[serde(rename_all = "snake_case")]
 pub struct RecommendRequestBatch {
     pub searches: Vec<RecommendRequest>,
 }
 
+/// Example of the discovery search: ID of the point, stored in the collection, or a raw vector
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum DiscoverExample {
+    PointId(PointIdType),
+    Vector(VectorType),
+}
+
+/// Pair of the examples, which splits the space into preferred and avoided zones:
+/// points, closer to the positive example than to the negative one, are preferred
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct ContextExamplePair {
+    pub positive: DiscoverExample,
+    pub negative: DiscoverExample,
+}
+
+/// Discovery request
+/// Look for the points, which are in the preferred zones of the most context pairs and, among
+/// the points in the same number of zones, are the closest to the target.
+///
+/// Points are ranked by the discovery score of the `segment` crate, which also guides the search
+/// in the HNSW graph. Integer part of the score is the number of the preferred zones
+/// minus the number of the avoided ones, fractional part reflects the similarity to the target.
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct DiscoverRequest {
+    /// Look for vectors closest to this
+    pub target: DiscoverExample,
+    /// Pairs of the examples, which constrain the search to the preferred zones
+    #[serde(default)]
+    pub context: Vec<ContextExamplePair>,
+    /// Look only for points which satisfies this conditions
+    pub filter: Option<Filter>,
+    /// Additional search params
+    pub params: Option<SearchParams>,
+    /// Max number of result to return
+    pub limit: usize,
+    /// Offset of the first result to return.
+    /// May be used to paginate results.
+    #[serde(default)]
+    pub offset: usize,
+    /// Select which payload to return with the response. Default: None
+    pub with_payload: Option<WithPayloadInterface>,
+    /// Whether to return the point vector with the result?
+    #[serde(default)]
+    pub with_vector: Option<WithVector>,
+    /// Define which vector to use for discovery, if not specified - try to use default vector
+    #[serde(default)]
+    pub using: Option<UsingVector>,
+}
+
+impl DiscoverRequest {
+    /// Target and all context examples
+    pub fn examples(&self) -> impl Iterator<Item = &DiscoverExample> {
+        std::iter::once(&self.target).chain(
+            self.context
+                .iter()
+                .flat_map(|pair| [&pair.positive, &pair.negative]),
+        )
+    }
+}
+
+/// Discovery search in the shards, examples of the [`DiscoverRequest`] are resolved into
+/// preprocessed vectors and the examples, given by ids, are excluded by the filter
+#[derive(Debug, Clone)]
+pub struct ShardDiscoverRequest {
+    pub vector_name: String,
+    pub query: DiscoveryQuery,
+    pub filter: Option<Filter>,
+    pub params: Option<SearchParams>,
+    /// Max number of points to return from each shard, including the offset of the request
+    pub limit: usize,
+    pub with_payload: Option<WithPayloadInterface>,
+    pub with_vector: WithVector,
+}
+
 /// Count Request
 /// Counts the number of points which satisfy the given filter.
 /// If filter is not provided, the count of all points in the collection will be returned.
-#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 #[serde(rename_all = "snake_case")]
 pub struct CountRequest {
     /// Look only for points which satisfies this conditions
@@ -337,6 +675,37 @@ pub struct CountResult {
     pub count: usize,
 }
 
+/// Facet Request
+/// Counts the most frequent values of the payload field among points, which satisfy the given filter.
+/// Counts are approximate for fields with many distinct values.
+/// Values of a single field are counted: to find values, co-occurring with a given one,
+/// add a condition on that value into the filter.
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct FacetRequest {
+    /// Payload field to count values of. Only keyword and integer values are counted
+    pub key: PayloadKeyType,
+    /// Look only for points which satisfies this conditions
+    pub filter: Option<Filter>,
+    /// Max number of values to return. Default: 10, at most 1000
+    #[serde(default = "default_facet_limit")]
+    pub limit: usize,
+}
+
+/// Size of the facet sketch grows with the limit, so it is capped
+pub const MAX_FACET_LIMIT: usize = 1000;
+
+pub fn default_facet_limit() -> usize {
+    10
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct FacetResponse {
+    /// Most frequent values with the number of points, sorted by count in descending order
+    pub hits: Vec<FacetValueHit>,
+}
+
 #[derive(Error, Debug, Clone)]
 #[error("{0}")]
 pub enum CollectionError {
@@ -402,6 +771,12 @@ impl From<OperationError> for CollectionError {
             OperationError::WrongVector { .. } => Self::BadInput {
                 description: format!("{}", err),
             },
+            OperationError::WrongMultiVector { .. } => Self::BadInput {
+                description: format!("{}", err),
+            },
+            OperationError::WrongByteVectorElement { .. } => Self::BadInput {
+                description: format!("{}", err),
+            },
             OperationError::VectorNameNotExists { .. } => Self::BadInput {
                 description: format!("{}", err),
             },
@@ -421,6 +796,9 @@ impl From<OperationError> for CollectionError {
             OperationError::TypeInferenceError { .. } => Self::BadInput {
                 description: format!("{}", err),
             },
+            OperationError::MissingOrderIndex { .. } => Self::BadInput {
+                description: format!("{}", err),
+            },
         }
     }
 }
@@ -3,9 +3,12 @@ use std::path::Path;
 use std::sync::Arc;
 
 use schemars::JsonSchema;
+use segment::common::encryption::StorageCipher;
 use segment::types::HnswConfig;
 use serde::{Deserialize, Serialize};
 
+use crate::collection_manager::optimizers::config_mismatch_optimizer::ConfigMismatchOptimizer;
+use crate::collection_manager::optimizers::defragmentation_optimizer::DefragmentationOptimizer;
 use crate::collection_manager::optimizers::indexing_optimizer::IndexingOptimizer;
 use crate::collection_manager::optimizers::merge_optimizer::MergeOptimizer;
 use crate::collection_manager::optimizers::segment_optimizer::OptimizerThresholds;
@@ -96,20 +99,33 @@ impl OptimizersConfig {
     }
 }
 
+fn optimizer_thresholds(
+    optimizers_config: &OptimizersConfig,
+    storage_cipher: &Option<StorageCipher>,
+) -> OptimizerThresholds {
+    // Mmap vector storage can't be encrypted, so vectors of encrypted collections stay in RocksDB
+    let memmap_threshold = match storage_cipher {
+        Some(_) => None,
+        None => optimizers_config.memmap_threshold,
+    };
+
+    OptimizerThresholds {
+        memmap_threshold: memmap_threshold.unwrap_or(usize::MAX),
+        indexing_threshold: optimizers_config.indexing_threshold,
+        max_segment_size: optimizers_config.get_max_segment_size(),
+    }
+}
+
 pub fn build_optimizers(
     shard_path: &Path,
     collection_params: &CollectionParams,
     optimizers_config: &OptimizersConfig,
     hnsw_config: &HnswConfig,
+    storage_cipher: Option<StorageCipher>,
 ) -> Arc<Vec<Arc<Optimizer>>> {
     let segments_path = shard_path.join("segments");
     let temp_segments_path = shard_path.join("temp_segments");
-
-    let threshold_config = OptimizerThresholds {
-        memmap_threshold: optimizers_config.memmap_threshold.unwrap_or(usize::MAX),
-        indexing_threshold: optimizers_config.indexing_threshold,
-        max_segment_size: optimizers_config.get_max_segment_size(),
-    };
+    let threshold_config = optimizer_thresholds(optimizers_config, &storage_cipher);
 
     Arc::new(vec![
         Arc::new(MergeOptimizer::new(
@@ -119,6 +135,7 @@ pub fn build_optimizers(
             temp_segments_path.clone(),
             collection_params.clone(),
             *hnsw_config,
+            storage_cipher.clone(),
         )),
         Arc::new(IndexingOptimizer::new(
             threshold_config.clone(),
@@ -126,15 +143,71 @@ pub fn build_optimizers(
             temp_segments_path.clone(),
             collection_params.clone(),
             *hnsw_config,
+            storage_cipher.clone(),
         )),
         Arc::new(VacuumOptimizer::new(
             optimizers_config.deleted_threshold,
             optimizers_config.vacuum_min_vector_number,
+            threshold_config.clone(),
+            segments_path.clone(),
+            temp_segments_path.clone(),
+            collection_params.clone(),
+            *hnsw_config,
+            storage_cipher.clone(),
+        )),
+        Arc::new(DefragmentationOptimizer::new(
+            optimizers_config.deleted_threshold,
+            optimizers_config.vacuum_min_vector_number,
+            threshold_config.clone(),
+            segments_path.clone(),
+            temp_segments_path.clone(),
+            collection_params.clone(),
+            *hnsw_config,
+            storage_cipher.clone(),
+        )),
+        Arc::new(ConfigMismatchOptimizer::new(
+            threshold_config,
+            segments_path,
+            temp_segments_path,
+            collection_params.clone(),
+            *hnsw_config,
+            storage_cipher,
+        )),
+    ])
+}
+
+/// Optimizers for a manually requested cycle: every segment with deleted points is vacuumed,
+/// regardless of `deleted_threshold` and `vacuum_min_vector_number`, then small segments are merged
+pub fn build_forced_optimizers(
+    shard_path: &Path,
+    collection_params: &CollectionParams,
+    optimizers_config: &OptimizersConfig,
+    hnsw_config: &HnswConfig,
+    storage_cipher: Option<StorageCipher>,
+) -> Arc<Vec<Arc<Optimizer>>> {
+    let segments_path = shard_path.join("segments");
+    let temp_segments_path = shard_path.join("temp_segments");
+    let threshold_config = optimizer_thresholds(optimizers_config, &storage_cipher);
+
+    Arc::new(vec![
+        Arc::new(VacuumOptimizer::new(
+            0.0,
+            0,
+            threshold_config.clone(),
+            segments_path.clone(),
+            temp_segments_path.clone(),
+            collection_params.clone(),
+            *hnsw_config,
+            storage_cipher.clone(),
+        )),
+        Arc::new(MergeOptimizer::new(
+            optimizers_config.get_number_segments(),
             threshold_config,
             segments_path,
             temp_segments_path,
             collection_params.clone(),
             *hnsw_config,
+            storage_cipher,
         )),
     ])
 }
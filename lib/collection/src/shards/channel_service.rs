@@ -4,6 +4,7 @@ use std::sync::Arc;
 use api::grpc::transport_channel_pool::TransportChannelPool;
 use tonic::transport::Uri;
 
+use crate::shards::peer_stats::PeerStats;
 use crate::shards::shard::PeerId;
 
 #[derive(Clone, Default)]
@@ -11,6 +12,8 @@ pub struct ChannelService {
     // Shared with consensus_state
     pub id_to_address: Arc<parking_lot::RwLock<HashMap<PeerId, Uri>>>,
     pub channel_pool: Arc<TransportChannelPool>,
+    /// Latency and errors of read requests to peers, used to select replicas for reads
+    pub peer_stats: Arc<PeerStats>,
 }
 
 impl ChannelService {
@@ -21,10 +24,12 @@ impl ChannelService {
         Self {
             id_to_address,
             channel_pool,
+            peer_stats: Default::default(),
         }
     }
 
     pub async fn remove_peer(&self, peer_id: PeerId) {
+        self.peer_stats.remove_peer(peer_id);
         let removed = self.id_to_address.write().remove(&peer_id);
         if let Some(uri) = removed {
             self.channel_pool.drop_pool(&uri).await;
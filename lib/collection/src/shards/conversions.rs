@@ -5,17 +5,22 @@ use api::grpc::qdrant::{
     CreateFieldIndexCollectionInternal, DeleteFieldIndexCollection,
     DeleteFieldIndexCollectionInternal, DeletePayloadPoints, DeletePayloadPointsInternal,
     DeletePoints, DeletePointsInternal, PointsIdsList, PointsSelector, SetPayloadPoints,
-    SetPayloadPointsInternal, SyncPoints, SyncPointsInternal, UpsertPoints, UpsertPointsInternal,
+    SetPayloadPointsInternal, SyncPoints, SyncPointsInternal, UpdatePayloadPoints,
+    UpdatePayloadPointsInternal, UpsertPoints, UpsertPointsInternal,
 };
 use segment::types::{Filter, PayloadFieldSchema, PayloadSchemaParams, PointIdType};
 use tonic::Status;
 
-use crate::operations::payload_ops::{DeletePayload, SetPayload};
-use crate::operations::point_ops::{PointInsertOperations, PointSyncOperation};
+use crate::operations::payload_ops::{DeletePayload, SetPayload, UpdatePayload};
+use crate::operations::point_ops::{PointInsertOperations, PointSyncOperation, WriteOrdering};
 use crate::operations::types::CollectionResult;
 use crate::operations::CreateIndex;
 use crate::shards::remote_shard::RemoteShard;
 
+fn write_ordering_to_proto(ordering: WriteOrdering) -> i32 {
+    api::grpc::qdrant::WriteOrdering::from(ordering) as i32
+}
+
 pub fn internal_sync_points(
     points_sync_operation: PointSyncOperation,
     shard: &RemoteShard,
@@ -41,12 +46,15 @@ pub fn internal_upsert_points(
     point_insert_operations: PointInsertOperations,
     shard: &RemoteShard,
     wait: bool,
+    ordering: Option<WriteOrdering>,
+    operation_id: Option<String>,
 ) -> CollectionResult<UpsertPointsInternal> {
     Ok(UpsertPointsInternal {
         shard_id: shard.id,
         upsert_points: Some(UpsertPoints {
             collection_name: shard.collection_id.clone(),
             wait: Some(wait),
+            ordering: ordering.map(write_ordering_to_proto),
             points: match point_insert_operations {
                 PointInsertOperations::PointsBatch(batch) => batch.try_into()?,
                 PointInsertOperations::PointsList(list) => list
@@ -54,6 +62,7 @@ pub fn internal_upsert_points(
                     .map(|id| id.try_into())
                     .collect::<Result<Vec<_>, Status>>()?,
             },
+            operation_id,
         }),
     })
 }
@@ -62,17 +71,21 @@ pub fn internal_delete_points(
     ids: Vec<PointIdType>,
     shard: &RemoteShard,
     wait: bool,
+    ordering: Option<WriteOrdering>,
+    operation_id: Option<String>,
 ) -> DeletePointsInternal {
     DeletePointsInternal {
         shard_id: shard.id,
         delete_points: Some(DeletePoints {
             collection_name: shard.collection_id.clone(),
             wait: Some(wait),
+            ordering: ordering.map(write_ordering_to_proto),
             points: Some(PointsSelector {
                 points_selector_one_of: Some(PointsSelectorOneOf::Points(PointsIdsList {
                     ids: ids.into_iter().map(|id| id.into()).collect(),
                 })),
             }),
+            operation_id,
         }),
     }
 }
@@ -81,15 +94,19 @@ pub fn internal_delete_points_by_filter(
     filter: Filter,
     shard: &RemoteShard,
     wait: bool,
+    ordering: Option<WriteOrdering>,
+    operation_id: Option<String>,
 ) -> DeletePointsInternal {
     DeletePointsInternal {
         shard_id: shard.id,
         delete_points: Some(DeletePoints {
             collection_name: shard.collection_id.clone(),
             wait: Some(wait),
+            ordering: ordering.map(write_ordering_to_proto),
             points: Some(PointsSelector {
                 points_selector_one_of: Some(PointsSelectorOneOf::Filter(filter.into())),
             }),
+            operation_id,
         }),
     }
 }
@@ -98,12 +115,14 @@ pub fn internal_set_payload(
     set_payload: SetPayload,
     shard: &RemoteShard,
     wait: bool,
+    ordering: Option<WriteOrdering>,
 ) -> SetPayloadPointsInternal {
     SetPayloadPointsInternal {
         shard_id: shard.id,
         set_payload_points: Some(SetPayloadPoints {
             collection_name: shard.collection_id.clone(),
             wait: Some(wait),
+            ordering: ordering.map(write_ordering_to_proto),
             payload: payload_to_proto(set_payload.payload),
             points: set_payload.points.into_iter().map(|id| id.into()).collect(),
         }),
@@ -114,12 +133,14 @@ pub fn internal_delete_payload(
     delete_payload: DeletePayload,
     shard: &RemoteShard,
     wait: bool,
+    ordering: Option<WriteOrdering>,
 ) -> DeletePayloadPointsInternal {
     DeletePayloadPointsInternal {
         shard_id: shard.id,
         delete_payload_points: Some(DeletePayloadPoints {
             collection_name: shard.collection_id.clone(),
             wait: Some(wait),
+            ordering: ordering.map(write_ordering_to_proto),
             keys: delete_payload.keys,
             points: delete_payload
                 .points
@@ -130,16 +151,44 @@ pub fn internal_delete_payload(
     }
 }
 
+pub fn internal_update_payload(
+    update_payload: UpdatePayload,
+    shard: &RemoteShard,
+    wait: bool,
+    ordering: Option<WriteOrdering>,
+) -> UpdatePayloadPointsInternal {
+    UpdatePayloadPointsInternal {
+        shard_id: shard.id,
+        update_payload_points: Some(UpdatePayloadPoints {
+            collection_name: shard.collection_id.clone(),
+            wait: Some(wait),
+            updates: update_payload
+                .updates
+                .into_iter()
+                .map(|update| update.into())
+                .collect(),
+            points: update_payload
+                .points
+                .into_iter()
+                .map(|id| id.into())
+                .collect(),
+            ordering: ordering.map(write_ordering_to_proto),
+        }),
+    }
+}
+
 pub fn internal_clear_payload(
     points: Vec<PointIdType>,
     shard: &RemoteShard,
     wait: bool,
+    ordering: Option<WriteOrdering>,
 ) -> ClearPayloadPointsInternal {
     ClearPayloadPointsInternal {
         shard_id: shard.id,
         clear_payload_points: Some(ClearPayloadPoints {
             collection_name: shard.collection_id.clone(),
             wait: Some(wait),
+            ordering: ordering.map(write_ordering_to_proto),
             points: Some(PointsSelector {
                 points_selector_one_of: Some(PointsSelectorOneOf::Points(PointsIdsList {
                     ids: points.into_iter().map(|id| id.into()).collect(),
@@ -153,12 +202,14 @@ pub fn internal_clear_payload_by_filter(
     filter: Filter,
     shard: &RemoteShard,
     wait: bool,
+    ordering: Option<WriteOrdering>,
 ) -> ClearPayloadPointsInternal {
     ClearPayloadPointsInternal {
         shard_id: shard.id,
         clear_payload_points: Some(ClearPayloadPoints {
             collection_name: shard.collection_id.clone(),
             wait: Some(wait),
+            ordering: ordering.map(write_ordering_to_proto),
             points: Some(PointsSelector {
                 points_selector_one_of: Some(PointsSelectorOneOf::Filter(filter.into())),
             }),
@@ -170,6 +221,7 @@ pub fn internal_create_index(
     create_index: CreateIndex,
     shard: &RemoteShard,
     wait: bool,
+    ordering: Option<WriteOrdering>,
 ) -> CreateFieldIndexCollectionInternal {
     let (field_type, field_index_params) = create_index
         .field_schema
@@ -191,6 +243,9 @@ pub fn internal_create_index(
                     segment::types::PayloadSchemaType::Text => {
                         api::grpc::qdrant::FieldType::Text as i32
                     }
+                    segment::types::PayloadSchemaType::Datetime => {
+                        api::grpc::qdrant::FieldType::Datetime as i32
+                    }
                 },
                 None,
             ),
@@ -199,6 +254,10 @@ pub fn internal_create_index(
                     api::grpc::qdrant::FieldType::Text as i32,
                     Some(text_index_params.into()),
                 ),
+                PayloadSchemaParams::Keyword(keyword_index_params) => (
+                    api::grpc::qdrant::FieldType::Keyword as i32,
+                    Some(keyword_index_params.into()),
+                ),
             },
         })
         .map(|(field_type, field_params)| (Some(field_type), field_params))
@@ -209,6 +268,7 @@ pub fn internal_create_index(
         create_field_index_collection: Some(CreateFieldIndexCollection {
             collection_name: shard.collection_id.clone(),
             wait: Some(wait),
+            ordering: ordering.map(write_ordering_to_proto),
             field_name: create_index.field_name,
             field_type,
             field_index_params,
@@ -220,12 +280,14 @@ pub fn internal_delete_index(
     delete_index: String,
     shard: &RemoteShard,
     wait: bool,
+    ordering: Option<WriteOrdering>,
 ) -> DeleteFieldIndexCollectionInternal {
     DeleteFieldIndexCollectionInternal {
         shard_id: shard.id,
         delete_field_index_collection: Some(DeleteFieldIndexCollection {
             collection_name: shard.collection_id.clone(),
             wait: Some(wait),
+            ordering: ordering.map(write_ordering_to_proto),
             field_name: delete_index,
         }),
     }
@@ -0,0 +1,144 @@
+use std::collections::BTreeSet;
+use std::fs::{create_dir_all, read_dir};
+use std::path::{Path, PathBuf};
+
+use segment::common::encryption::StorageCipher;
+use segment::types::SeqNumberType;
+use serde::{Deserialize, Serialize};
+
+use crate::operations::types::CollectionResult;
+use crate::operations::CollectionUpdateOperations;
+
+pub const DEAD_LETTER_DIR: &str = "dead_letter";
+
+const QUARANTINED_OPERATION_EXTENSION: &str = "op";
+
+/// Operation, which can't be applied to the segments of the shard
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct QuarantinedOperation {
+    pub op_num: SeqNumberType,
+    pub operation: CollectionUpdateOperations,
+    /// Last error of the operation
+    pub error: String,
+}
+
+/// Storage of the operations, which repeatedly failed to apply and were skipped.
+///
+/// Each operation is saved into a separate file named by its sequential number,
+/// so skipped operations are known without reading the content.
+/// Content is encrypted if encryption is enabled for the collection.
+pub struct DeadLetter {
+    path: PathBuf,
+    storage_cipher: Option<StorageCipher>,
+}
+
+impl DeadLetter {
+    pub fn new(shard_path: &Path, storage_cipher: Option<StorageCipher>) -> Self {
+        Self {
+            path: Self::dead_letter_path(shard_path),
+            storage_cipher,
+        }
+    }
+
+    pub fn dead_letter_path(shard_path: &Path) -> PathBuf {
+        shard_path.join(DEAD_LETTER_DIR)
+    }
+
+    fn operation_path(&self, op_num: SeqNumberType) -> PathBuf {
+        self.path
+            .join(format!("{op_num}.{QUARANTINED_OPERATION_EXTENSION}"))
+    }
+
+    /// Persist the operation, so it could be inspected and re-applied manually
+    pub fn quarantine(&self, operation: &QuarantinedOperation) -> CollectionResult<()> {
+        create_dir_all(&self.path)?;
+        let data = serde_json::to_vec(operation)?;
+        let data = match &self.storage_cipher {
+            None => data,
+            Some(cipher) => cipher.encrypt(&data)?,
+        };
+        std::fs::write(self.operation_path(operation.op_num), data)?;
+        Ok(())
+    }
+
+    pub fn read(&self, op_num: SeqNumberType) -> CollectionResult<QuarantinedOperation> {
+        let data = std::fs::read(self.operation_path(op_num))?;
+        let data = match &self.storage_cipher {
+            None => data,
+            Some(cipher) => cipher.decrypt(&data)?,
+        };
+        Ok(serde_json::from_slice(&data)?)
+    }
+
+    /// Sequential numbers of all quarantined operations
+    pub fn quarantined_operations(&self) -> CollectionResult<BTreeSet<SeqNumberType>> {
+        let mut operations = BTreeSet::new();
+        if !self.path.exists() {
+            return Ok(operations);
+        }
+        for entry in read_dir(&self.path)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str())
+                != Some(QUARANTINED_OPERATION_EXTENSION)
+            {
+                continue;
+            }
+            let op_num = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.parse().ok());
+            match op_num {
+                Some(op_num) => {
+                    operations.insert(op_num);
+                }
+                None => log::warn!("Unexpected file in dead letter storage: {:?}", path),
+            }
+        }
+        Ok(operations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use segment::common::encryption::STORAGE_KEY_SIZE;
+    use tempfile::Builder;
+
+    use super::*;
+    use crate::operations::point_ops::PointOperations;
+
+    fn delete_operation(op_num: SeqNumberType) -> QuarantinedOperation {
+        QuarantinedOperation {
+            op_num,
+            operation: CollectionUpdateOperations::PointOperation(PointOperations::DeletePoints {
+                ids: vec![op_num.into()],
+            }),
+            error: "Service internal error: broken segment".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_quarantine_operations() {
+        let dir = Builder::new().prefix("shard").tempdir().unwrap();
+        let cipher = StorageCipher::new(&[7; STORAGE_KEY_SIZE]).unwrap();
+        let dead_letter = DeadLetter::new(dir.path(), Some(cipher));
+
+        assert!(dead_letter.quarantined_operations().unwrap().is_empty());
+
+        dead_letter.quarantine(&delete_operation(10)).unwrap();
+        dead_letter.quarantine(&delete_operation(3)).unwrap();
+
+        assert_eq!(
+            dead_letter.quarantined_operations().unwrap(),
+            BTreeSet::from([3, 10])
+        );
+        let quarantined = dead_letter.read(10).unwrap();
+        assert_eq!(quarantined.op_num, 10);
+        assert_eq!(quarantined.error, "Service internal error: broken segment");
+
+        // Quarantined operations are encrypted, same as the WAL
+        let wrong_cipher = StorageCipher::new(&[8; STORAGE_KEY_SIZE]).unwrap();
+        assert!(DeadLetter::new(dir.path(), Some(wrong_cipher))
+            .read(10)
+            .is_err());
+    }
+}
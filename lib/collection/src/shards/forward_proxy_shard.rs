@@ -1,22 +1,27 @@
 use std::path::Path;
 use std::sync::Arc;
+use std::time::Instant;
 
 use async_trait::async_trait;
 use segment::types::{
-    ExtendedPointId, Filter, PointIdType, ScoredPoint, WithPayload, WithPayloadInterface,
-    WithVector,
+    BatchSearchResult, ExtendedPointId, Filter, OrderBy, PointIdType, ScoredPoint, WithPayload,
+    WithPayloadInterface, WithVector,
 };
 use tokio::runtime::Handle;
 use tokio::sync::Mutex;
 
 use crate::operations::point_ops::{PointOperations, PointStruct, PointSyncOperation};
+use crate::operations::snapshot_ops::SegmentVersions;
 use crate::operations::types::{
-    CollectionInfo, CollectionResult, CountRequest, CountResult, PointRequest, Record,
-    SearchRequestBatch, UpdateResult,
+    CollectionInfo, CollectionResult, CountRequest, CountResult, FacetRequest, FacetResponse,
+    PointRequest, Record, Sample, SearchRequestBatch, ShardDiscoverRequest, UpdateResult,
+};
+use crate::operations::{
+    CollectionUpdateOperations, CreateIndex, FieldIndexOperations, OperationWithId,
 };
-use crate::operations::{CollectionUpdateOperations, CreateIndex, FieldIndexOperations};
 use crate::shards::local_shard::LocalShard;
 use crate::shards::remote_shard::RemoteShard;
+use crate::shards::replica_sync::PointDigest;
 use crate::shards::shard_trait::ShardOperation;
 use crate::shards::telemetry::LocalShardTelemetry;
 
@@ -54,7 +59,8 @@ impl ForwardProxyShard {
                             field_name: index_key,
                             field_schema: Some(index_type.try_into()?),
                         }),
-                    ),
+                    )
+                    .into(),
                     false,
                 )
                 .await?;
@@ -80,6 +86,8 @@ impl ForwardProxyShard {
                 &WithPayloadInterface::Bool(true),
                 &true.into(),
                 None,
+                None,
+                None,
             )
             .await?;
         let next_page_offset = if batch.len() < limit {
@@ -109,7 +117,7 @@ impl ForwardProxyShard {
         // We only need to wait for the last batch.
         let wait = next_page_offset.is_none();
         self.remote_shard
-            .update(insert_points_operation, wait)
+            .update(insert_points_operation.into(), wait)
             .await?;
 
         Ok(next_page_offset)
@@ -120,8 +128,12 @@ impl ForwardProxyShard {
     }
 
     /// Forward `create_snapshot` to `wrapped_shard`
-    pub async fn create_snapshot(&self, target_path: &Path) -> CollectionResult<()> {
-        self.wrapped_shard.create_snapshot(target_path).await
+    pub async fn create_snapshot(
+        &self,
+        target_path: &Path,
+        base: &SegmentVersions,
+    ) -> CollectionResult<SegmentVersions> {
+        self.wrapped_shard.create_snapshot(target_path, base).await
     }
 
     pub async fn on_optimizer_config_update(&self) -> CollectionResult<()> {
@@ -143,7 +155,7 @@ impl ShardOperation for ForwardProxyShard {
     /// Update `wrapped_shard` while keeping track of the changed points
     async fn update(
         &self,
-        operation: CollectionUpdateOperations,
+        operation: OperationWithId,
         wait: bool,
     ) -> CollectionResult<UpdateResult> {
         let _update_lock = self.update_lock.lock().await;
@@ -163,10 +175,20 @@ impl ShardOperation for ForwardProxyShard {
         with_payload_interface: &WithPayloadInterface,
         with_vector: &WithVector,
         filter: Option<&Filter>,
+        order_by: Option<&OrderBy>,
+        sample: Option<Sample>,
     ) -> CollectionResult<Vec<Record>> {
         let local_shard = &self.wrapped_shard;
         local_shard
-            .scroll_by(offset, limit, with_payload_interface, with_vector, filter)
+            .scroll_by(
+                offset,
+                limit,
+                with_payload_interface,
+                with_vector,
+                filter,
+                order_by,
+                sample,
+            )
             .await
     }
 
@@ -179,9 +201,21 @@ impl ShardOperation for ForwardProxyShard {
         &self,
         request: Arc<SearchRequestBatch>,
         search_runtime_handle: &Handle,
-    ) -> CollectionResult<Vec<Vec<ScoredPoint>>> {
+        deadline: Option<Instant>,
+    ) -> CollectionResult<BatchSearchResult> {
+        let local_shard = &self.wrapped_shard;
+        local_shard
+            .search(request, search_runtime_handle, deadline)
+            .await
+    }
+
+    async fn discover(
+        &self,
+        request: Arc<ShardDiscoverRequest>,
+        search_runtime_handle: &Handle,
+    ) -> CollectionResult<Vec<ScoredPoint>> {
         let local_shard = &self.wrapped_shard;
-        local_shard.search(request, search_runtime_handle).await
+        local_shard.discover(request, search_runtime_handle).await
     }
 
     async fn count(&self, request: Arc<CountRequest>) -> CollectionResult<CountResult> {
@@ -189,6 +223,11 @@ impl ShardOperation for ForwardProxyShard {
         local_shard.count(request).await
     }
 
+    async fn facet(&self, request: Arc<FacetRequest>) -> CollectionResult<FacetResponse> {
+        let local_shard = &self.wrapped_shard;
+        local_shard.facet(request).await
+    }
+
     async fn retrieve(
         &self,
         request: Arc<PointRequest>,
@@ -200,4 +239,15 @@ impl ShardOperation for ForwardProxyShard {
             .retrieve(request, with_payload, with_vector)
             .await
     }
+
+    /// Forward read-only `point_digests` to `wrapped_shard`
+    async fn point_digests(
+        &self,
+        from: Option<ExtendedPointId>,
+        to: Option<ExtendedPointId>,
+        limit: Option<usize>,
+    ) -> CollectionResult<Vec<PointDigest>> {
+        let local_shard = &self.wrapped_shard;
+        local_shard.point_digests(from, to, limit).await
+    }
 }
@@ -1,44 +1,82 @@
+use std::cmp;
 use std::collections::hash_map::Entry;
 use std::collections::{BTreeSet, HashMap};
 use std::fs::remove_file;
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::thread;
+use std::time::Duration;
 
 use arc_swap::ArcSwap;
 use indicatif::{ProgressBar, ProgressStyle};
 use itertools::Itertools;
 use parking_lot::{Mutex as ParkingMutex, RwLock};
+use segment::common::encryption::StorageCipher;
 use segment::entry::entry_point::SegmentEntry;
 use segment::index::field_index::CardinalityEstimation;
 use segment::segment::Segment;
-use segment::segment_constructor::{build_segment, load_segment};
+use segment::segment_constructor::{build_segment_with_cipher, load_segment_with_cipher};
 use segment::types::{
     Filter, PayloadIndexInfo, PayloadKeyType, PayloadStorageType, PointIdType, SegmentConfig,
-    SegmentType,
+    SegmentType, SeqNumberType, VECTOR_ELEMENT_SIZE,
 };
 use tokio::fs::{copy, create_dir_all, remove_dir_all};
-use tokio::runtime::{self, Runtime};
 use tokio::sync::mpsc::Sender;
-use tokio::sync::{mpsc, Mutex, RwLock as TokioRwLock};
+use tokio::sync::{mpsc, oneshot, Mutex, RwLock as TokioRwLock};
 
 use crate::collection_manager::collection_updater::CollectionUpdater;
 use crate::collection_manager::holders::segment_holder::{LockedSegment, SegmentHolder};
-use crate::config::CollectionConfig;
+use crate::collection_manager::optimizers::config_mismatch_optimizer::has_outdated_config;
+use crate::common::optimization_scheduler::{OptimizationPriority, OptimizationScheduler};
+use crate::config::{CollectionConfig, WalConfig};
+use crate::operations::changes::check_changes_available;
+use crate::operations::point_ops::PointStruct;
+use crate::operations::snapshot_ops::SegmentVersions;
 use crate::operations::types::{
-    CollectionError, CollectionInfo, CollectionResult, CollectionStatus, OptimizersStatus,
+    CollectionError, CollectionInfo, CollectionResult, CollectionStatus,
+    CollectionVerificationReport, CorruptedSegment, IndexRebuildProgress, OptimizersStatus,
 };
-use crate::operations::CollectionUpdateOperations;
-use crate::optimizers_builder::build_optimizers;
+use crate::operations::{CollectionUpdateOperations, OperationWithId};
+use crate::optimizers_builder::{build_forced_optimizers, build_optimizers};
+use crate::shards::dead_letter::DeadLetter;
+use crate::shards::operation_ids::RecentOperationIds;
 use crate::shards::shard::ShardId;
 use crate::shards::shard_config::{ShardConfig, SHARD_CONFIG_FILE};
-use crate::shards::telemetry::{LocalShardTelemetry, OptimizerTelemetry};
+use crate::shards::telemetry::{LocalShardTelemetry, OptimizerTelemetry, UpdateQueueTelemetry};
 use crate::shards::CollectionId;
 use crate::update_handler::{Optimizer, UpdateHandler, UpdateSignal, UPDATE_QUEUE_SIZE};
 use crate::wal::SerdeWal;
 
+/// How often the WAL is checked for the operation, which is not received by the replica yet
+const WAIT_FOR_OPERATION_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+const BYTES_IN_KB: usize = 1024;
+
+fn has_points(segments: &SegmentHolder) -> bool {
+    segments
+        .iter()
+        .any(|(_id, segment)| segment.get().read().points_count() > 0)
+}
+
+/// State transition of a local shard, which may change the status of its collection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShardEvent {
+    /// Optimization of some segments of the shard is started
+    OptimizationStarted,
+    /// Optimization is finished. `completed` is false, if it was cancelled or failed
+    OptimizationFinished { completed: bool },
+    /// Update operation failed, it will be re-applied on the next optimization check
+    UpdateFailed,
+    /// Previously failed update operations are re-applied
+    UpdateRecovered,
+    /// Update operation repeatedly failed to re-apply, so it is saved into the dead letter
+    /// storage and skipped
+    OperationQuarantined { op_num: SeqNumberType },
+}
+
+pub type OnShardEvent = Arc<dyn Fn(ShardId, ShardEvent) + Send + Sync>;
+
 /// LocalShard
 ///
 /// LocalShard is an entity that can be moved between peers and contains some part of one collections data.
@@ -47,13 +85,16 @@ use crate::wal::SerdeWal;
 pub struct LocalShard {
     pub(super) segments: Arc<RwLock<SegmentHolder>>,
     pub(super) config: Arc<TokioRwLock<CollectionConfig>>,
-    pub(super) wal: Arc<ParkingMutex<SerdeWal<CollectionUpdateOperations>>>,
+    pub(super) wal: Arc<ParkingMutex<SerdeWal<OperationWithId>>>,
+    /// Ids of the operations in the WAL, which retries should not be applied again
+    pub(super) recent_operation_ids: ParkingMutex<RecentOperationIds>,
     pub(super) update_handler: Arc<Mutex<UpdateHandler>>,
-    pub(super) runtime_handle: Option<Runtime>,
     pub(super) update_sender: ArcSwap<Sender<UpdateSignal>>,
     pub(super) path: PathBuf,
     before_drop_called: bool,
     pub(super) optimizers: Arc<Vec<Arc<Optimizer>>>,
+    /// Cipher of WAL and segments, if encryption is enabled for the collection
+    storage_cipher: Option<StorageCipher>,
 }
 
 /// Shard holds information about segments and WAL.
@@ -72,6 +113,11 @@ impl LocalShard {
         if segments_path.exists() {
             remove_dir_all(segments_path).await?;
         }
+        // Delete quarantined operations, they refer to sequential numbers of the removed WAL
+        let dead_letter_path = DeadLetter::dead_letter_path(shard_path);
+        if dead_letter_path.exists() {
+            remove_dir_all(dead_letter_path).await?;
+        }
 
         Ok(())
     }
@@ -82,40 +128,29 @@ impl LocalShard {
         collection_id: CollectionId,
         segment_holder: SegmentHolder,
         shared_config: Arc<TokioRwLock<CollectionConfig>>,
-        wal: SerdeWal<CollectionUpdateOperations>,
+        wal: SerdeWal<OperationWithId>,
         optimizers: Arc<Vec<Arc<Optimizer>>>,
         collection_path: &Path,
+        optimization_scheduler: OptimizationScheduler,
+        storage_cipher: Option<StorageCipher>,
+        on_shard_event: OnShardEvent,
     ) -> Self {
         let segment_holder = Arc::new(RwLock::new(segment_holder));
         let config = shared_config.read().await;
-        let mut optimize_runtime_builder = runtime::Builder::new_multi_thread();
-
-        optimize_runtime_builder
-            .worker_threads(3)
-            .enable_time()
-            .thread_name_fn(move || {
-                static ATOMIC_ID: AtomicUsize = AtomicUsize::new(0);
-                let optimizer_id = ATOMIC_ID.fetch_add(1, Ordering::SeqCst);
-                format!("collection-{collection_id}-shard-{id}-optimizer-{optimizer_id}")
-            });
-
-        if config.optimizer_config.max_optimization_threads > 0 {
-            // panics if val is not larger than 0.
-            optimize_runtime_builder
-                .max_blocking_threads(config.optimizer_config.max_optimization_threads);
-        }
-
-        let optimize_runtime = optimize_runtime_builder.build().unwrap();
 
         let locked_wal = Arc::new(ParkingMutex::new(wal));
 
+        log::debug!("Starting update workers of collection {collection_id} shard {id}");
         let mut update_handler = UpdateHandler::new(
             optimizers.clone(),
-            optimize_runtime.handle().clone(),
+            optimization_scheduler,
             segment_holder.clone(),
             locked_wal.clone(),
             config.optimizer_config.flush_interval_sec,
             config.optimizer_config.max_optimization_threads,
+            DeadLetter::new(collection_path, storage_cipher.clone()),
+            id,
+            on_shard_event,
         );
 
         let (update_sender, update_receiver) = mpsc::channel(UPDATE_QUEUE_SIZE);
@@ -127,12 +162,13 @@ impl LocalShard {
             segments: segment_holder,
             config: shared_config,
             wal: locked_wal,
+            recent_operation_ids: Default::default(),
             update_handler: Arc::new(Mutex::new(update_handler)),
-            runtime_handle: Some(optimize_runtime),
             update_sender: ArcSwap::from_pointee(update_sender),
             path: collection_path.to_owned(),
             before_drop_called: false,
             optimizers,
+            storage_cipher,
         }
     }
 
@@ -149,6 +185,9 @@ impl LocalShard {
         collection_id: CollectionId,
         shard_path: &Path,
         shared_config: Arc<TokioRwLock<CollectionConfig>>,
+        optimization_scheduler: OptimizationScheduler,
+        storage_cipher: Option<StorageCipher>,
+        on_shard_event: OnShardEvent,
     ) -> LocalShard {
         let collection_config = shared_config.read().await;
 
@@ -156,9 +195,12 @@ impl LocalShard {
         let segments_path = Self::segments_path(shard_path);
         let mut segment_holder = SegmentHolder::default();
 
-        let wal: SerdeWal<CollectionUpdateOperations> = SerdeWal::new(
-            wal_path.to_str().unwrap(),
-            &(&collection_config.wal_config).into(),
+        let storage_cipher = Self::collection_cipher(&collection_config.wal_config, storage_cipher)
+            .expect("Can't open encrypted shard");
+        let wal = Self::open_wal(
+            &wal_path,
+            &collection_config.wal_config,
+            storage_cipher.clone(),
         )
         .expect("Can't read WAL");
 
@@ -183,10 +225,11 @@ impl LocalShard {
                 });
                 continue;
             }
+            let segment_cipher = storage_cipher.clone();
             load_handlers.push(
                 thread::Builder::new()
                     .name("shard-load".to_string())
-                    .spawn(move || load_segment(&segments_path))
+                    .spawn(move || load_segment_with_cipher(&segments_path, segment_cipher))
                     .unwrap(),
             );
         }
@@ -211,6 +254,7 @@ impl LocalShard {
             &collection_config.params,
             &collection_config.optimizer_config,
             &collection_config.hnsw_config,
+            storage_cipher.clone(),
         );
 
         drop(collection_config); // release `shared_config` from borrow checker
@@ -223,11 +267,28 @@ impl LocalShard {
             wal,
             optimizers,
             shard_path,
+            optimization_scheduler,
+            storage_cipher,
+            on_shard_event,
         )
         .await;
 
         collection.load_from_wal(collection_id).await;
 
+        // Loading is not an update, so optimizers have to be triggered explicitly
+        // to rebuild segments with missing indexes
+        let has_missing_indexes =
+            Self::index_rebuild_progress(&collection.segments().read()).is_some();
+        if has_missing_indexes {
+            log::info!("Shard {id} has segments with missing indexes, scheduling rebuild");
+            collection
+                .update_sender
+                .load()
+                .send(UpdateSignal::Nop)
+                .await
+                .expect("Can't schedule rebuild of missing indexes");
+        }
+
         collection
     }
 
@@ -243,15 +304,58 @@ impl LocalShard {
         shard_path.join("segments")
     }
 
+    /// Select cipher for WAL and segments of the shard.
+    /// Returns `None` if encryption is not enabled in `wal_config`.
+    fn collection_cipher(
+        wal_config: &WalConfig,
+        storage_cipher: Option<StorageCipher>,
+    ) -> CollectionResult<Option<StorageCipher>> {
+        if !wal_config.encrypted {
+            return Ok(None);
+        }
+        let cipher = storage_cipher.ok_or_else(|| {
+            CollectionError::service_error(
+                "Encryption is enabled for the collection, but no encryption key is configured"
+                    .to_string(),
+            )
+        })?;
+        Ok(Some(cipher))
+    }
+
+    /// Open WAL of the shard. Records are encrypted, if `cipher` is provided.
+    fn open_wal(
+        wal_path: &Path,
+        wal_config: &WalConfig,
+        cipher: Option<StorageCipher>,
+    ) -> CollectionResult<SerdeWal<OperationWithId>> {
+        Ok(SerdeWal::new_with_cipher(
+            wal_path.to_str().unwrap(),
+            &wal_config.into(),
+            cipher,
+        )?)
+    }
+
     pub async fn build_temp(
         id: ShardId,
         collection_id: CollectionId,
         shard_path: &Path,
         shared_config: Arc<TokioRwLock<CollectionConfig>>,
+        optimization_scheduler: OptimizationScheduler,
+        storage_cipher: Option<StorageCipher>,
+        on_shard_event: OnShardEvent,
     ) -> CollectionResult<LocalShard> {
         // initialize temporary shard config file
         let temp_shard_config = ShardConfig::new_temp();
-        let shard = Self::build(id, collection_id, shard_path, shared_config).await?;
+        let shard = Self::build(
+            id,
+            collection_id,
+            shard_path,
+            shared_config,
+            optimization_scheduler,
+            storage_cipher,
+            on_shard_event,
+        )
+        .await?;
         temp_shard_config.save(shard_path)?;
         Ok(shard)
     }
@@ -261,10 +365,22 @@ impl LocalShard {
         collection_id: CollectionId,
         shard_path: &Path,
         shared_config: Arc<TokioRwLock<CollectionConfig>>,
+        optimization_scheduler: OptimizationScheduler,
+        storage_cipher: Option<StorageCipher>,
+        on_shard_event: OnShardEvent,
     ) -> CollectionResult<LocalShard> {
         // initialize local shard config file
         let local_shard_config = ShardConfig::new_local();
-        let shard = Self::build(id, collection_id, shard_path, shared_config).await?;
+        let shard = Self::build(
+            id,
+            collection_id,
+            shard_path,
+            shared_config,
+            optimization_scheduler,
+            storage_cipher,
+            on_shard_event,
+        )
+        .await?;
         local_shard_config.save(shard_path)?;
         Ok(shard)
     }
@@ -275,8 +391,12 @@ impl LocalShard {
         collection_id: CollectionId,
         shard_path: &Path,
         shared_config: Arc<TokioRwLock<CollectionConfig>>,
+        optimization_scheduler: OptimizationScheduler,
+        storage_cipher: Option<StorageCipher>,
+        on_shard_event: OnShardEvent,
     ) -> CollectionResult<LocalShard> {
         let config = shared_config.read().await;
+        let storage_cipher = Self::collection_cipher(&config.wal_config, storage_cipher)?;
 
         let wal_path = shard_path.join("wal");
 
@@ -297,11 +417,12 @@ impl LocalShard {
         let mut segment_holder = SegmentHolder::default();
         let mut build_handlers = vec![];
 
-        let vector_params = config.params.get_all_vector_params()?;
+        let vector_params = config.params.get_all_vector_params(&config.hnsw_config)?;
         let segment_number = config.optimizer_config.get_number_segments();
 
         for _sid in 0..segment_number {
             let path_clone = segments_path.clone();
+            let segment_cipher = storage_cipher.clone();
             let segment_config = SegmentConfig {
                 vector_data: vector_params.clone(),
                 index: Default::default(),
@@ -313,7 +434,9 @@ impl LocalShard {
             };
             let segment = thread::Builder::new()
                 .name("shard-build".to_string())
-                .spawn(move || build_segment(&path_clone, &segment_config))
+                .spawn(move || {
+                    build_segment_with_cipher(&path_clone, &segment_config, segment_cipher)
+                })
                 .unwrap();
             build_handlers.push(segment);
         }
@@ -337,14 +460,14 @@ impl LocalShard {
             segment_holder.add(segment);
         }
 
-        let wal: SerdeWal<CollectionUpdateOperations> =
-            SerdeWal::new(wal_path.to_str().unwrap(), &(&config.wal_config).into())?;
+        let wal = Self::open_wal(&wal_path, &config.wal_config, storage_cipher.clone())?;
 
         let optimizers = build_optimizers(
             shard_path,
             &config.params,
             &config.optimizer_config,
             &config.hnsw_config,
+            storage_cipher.clone(),
         );
 
         drop(config); // release `shared_config` from borrow checker
@@ -357,6 +480,9 @@ impl LocalShard {
             wal,
             optimizers,
             shard_path,
+            optimization_scheduler,
+            storage_cipher,
+            on_shard_event,
         )
         .await;
 
@@ -385,11 +511,24 @@ impl LocalShard {
 
         bar.set_message(format!("Recovering collection {}", collection_id));
         let segments = self.segments();
+        let quarantined = DeadLetter::new(&self.path, self.storage_cipher.clone())
+            .quarantined_operations()
+            .expect("Can't read quarantined operations");
+        let mut recent_operation_ids = self.recent_operation_ids.lock();
         // ToDo: Start from minimal applied version
         for (op_num, update) in wal.read_all() {
+            // Deduplication window of the recovered ids starts from the shard load
+            if let Some(operation_id) = update.operation_id {
+                recent_operation_ids.record(operation_id, op_num);
+            }
+            if quarantined.contains(&op_num) {
+                log::warn!("Skipping quarantined operation {op_num} of {collection_id}");
+                bar.inc(1);
+                continue;
+            }
             // Panic only in case of internal error. If wrong formatting - skip
             if let Err(CollectionError::ServiceError { error }) =
-                CollectionUpdater::update(segments, op_num, update)
+                CollectionUpdater::update(segments, op_num, update.operation)
             {
                 panic!("Can't apply WAL operation: {}", error)
             }
@@ -416,6 +555,7 @@ impl LocalShard {
             &config.params,
             &config.optimizer_config,
             &config.hnsw_config,
+            self.storage_cipher.clone(),
         );
         update_handler.optimizers = new_optimizers;
         update_handler.flush_interval_sec = config.optimizer_config.flush_interval_sec;
@@ -425,6 +565,31 @@ impl LocalShard {
         Ok(())
     }
 
+    /// Vacuum segments with deleted points and merge small segments right away,
+    /// regardless of the thresholds in the optimizers config.
+    /// Returns the number of scheduled optimizations
+    pub async fn force_optimization(&self) -> CollectionResult<usize> {
+        let optimizers = {
+            let config = self.config.read().await;
+            build_forced_optimizers(
+                &self.path,
+                &config.params,
+                &config.optimizer_config,
+                &config.hnsw_config,
+                self.storage_cipher.clone(),
+            )
+        };
+        let update_sender = self.update_sender.load().clone();
+        let update_handler = self.update_handler.lock().await;
+        let scheduled = update_handler
+            .launch_forced_optimization(optimizers, move |_optimization_result| {
+                // Regular optimizers check the segments, produced by the forced ones
+                let _ = update_sender.try_send(UpdateSignal::Nop);
+            })
+            .await;
+        Ok(scheduled)
+    }
+
     pub async fn before_drop(&mut self) {
         // Finishes update tasks right before destructor stuck to do so with runtime
         self.update_sender
@@ -437,26 +602,24 @@ impl LocalShard {
 
         self.wait_update_workers_stop().await.unwrap();
 
-        match self.runtime_handle.take() {
-            None => {}
-            Some(handle) => {
-                // The drop could be called from the tokio context, e.g. from perform_collection_operation method.
-                // Calling remove from there would lead to the following error in a new version of tokio:
-                // "Cannot drop a runtime in a context where blocking is not allowed. This happens when a runtime is dropped from within an asynchronous context."
-                // So the workaround for move out the runtime handler and drop it in the separate thread.
-                // The proper solution is to reconsider the collection to be an owner of the runtime
-
-                let thread_handler = thread::Builder::new()
-                    .name("collection_drop".to_string())
-                    .spawn(move || drop(handle))
-                    .unwrap();
-                thread_handler.join().unwrap();
-            }
+        // Persist the applied updates, so they are not replayed from the WAL on the next start
+        if let Err(err) = self.flush_and_ack_wal() {
+            log::error!(
+                "Failed to flush shard {} on drop: {err}",
+                self.path.display()
+            );
         }
 
         self.before_drop_called = true;
     }
 
+    /// Flush all segments and remove the flushed operations from the WAL
+    fn flush_and_ack_wal(&self) -> CollectionResult<()> {
+        let confirmed_version = UpdateHandler::flush_segments(self.segments.clone())?;
+        self.wal.lock().ack(confirmed_version)?;
+        Ok(())
+    }
+
     pub fn restore_snapshot(snapshot_path: &Path) -> CollectionResult<()> {
         // recover segments
         let segments_path = LocalShard::segments_path(snapshot_path);
@@ -481,15 +644,28 @@ impl LocalShard {
     }
 
     /// create snapshot for local shard into `target_path`
-    pub async fn create_snapshot(&self, target_path: &Path) -> CollectionResult<()> {
+    ///
+    /// Segments, which have the same version in `base`, are not included.
+    /// Returns versions of the segments of the shard.
+    pub async fn create_snapshot(
+        &self,
+        target_path: &Path,
+        base: &SegmentVersions,
+    ) -> CollectionResult<SegmentVersions> {
         let snapshot_shard_path = target_path;
 
         // snapshot all shard's segment
+        // Copying is blocking, so it runs on the blocking pool to let shards be snapshotted in parallel
         let snapshot_segments_shard_path = snapshot_shard_path.join("segments");
         create_dir_all(&snapshot_segments_shard_path).await?;
-        self.segments
-            .read()
-            .snapshot_all_segments(&snapshot_segments_shard_path)?;
+        let segments = self.segments.clone();
+        let base = base.clone();
+        let versions = tokio::task::spawn_blocking(move || {
+            segments
+                .read()
+                .snapshot_changed_segments(&snapshot_segments_shard_path, &base)
+        })
+        .await??;
 
         // snapshot all shard's WAL
         self.snapshot_wal(snapshot_shard_path).await?;
@@ -498,26 +674,279 @@ impl LocalShard {
         let shard_config_path = ShardConfig::get_config_path(&self.path);
         let target_shard_config_path = snapshot_shard_path.join(SHARD_CONFIG_FILE);
         copy(&shard_config_path, &target_shard_config_path).await?;
-        Ok(())
+        Ok(versions)
     }
 
     /// snapshot WAL
     ///
     /// copies all WAL files into `snapshot_shard_path/wal`
+    /// and quarantined operations into `snapshot_shard_path/dead_letter`,
+    /// so they are not re-applied from the WAL on recovery
     pub async fn snapshot_wal(&self, snapshot_shard_path: &Path) -> CollectionResult<()> {
-        // lock wal during snapshot
-        let _wal_guard = self.wal.lock();
+        let wal = self.wal.clone();
         let source_wal_path = self.path.join("wal");
-        let options = fs_extra::dir::CopyOptions::new();
-        fs_extra::dir::copy(&source_wal_path, snapshot_shard_path, &options).map_err(|err| {
-            CollectionError::service_error(format!(
-                "Error while copy WAL {:?} {}",
-                snapshot_shard_path, err
-            ))
+        let source_dead_letter_path = DeadLetter::dead_letter_path(&self.path);
+        let snapshot_shard_path = snapshot_shard_path.to_path_buf();
+        tokio::task::spawn_blocking(move || {
+            // lock wal during snapshot
+            let _wal_guard = wal.lock();
+            let options = fs_extra::dir::CopyOptions::new();
+            fs_extra::dir::copy(&source_wal_path, &snapshot_shard_path, &options).map_err(
+                |err| {
+                    CollectionError::service_error(format!(
+                        "Error while copy WAL {:?} {}",
+                        snapshot_shard_path, err
+                    ))
+                },
+            )?;
+            if source_dead_letter_path.exists() {
+                fs_extra::dir::copy(&source_dead_letter_path, &snapshot_shard_path, &options)
+                    .map_err(|err| {
+                        CollectionError::service_error(format!(
+                            "Error while copy quarantined operations {:?} {}",
+                            snapshot_shard_path, err
+                        ))
+                    })?;
+            }
+            Ok(())
+        })
+        .await?
+    }
+
+    /// Read up to `limit` operations from the WAL, starting from the operation `from`
+    pub fn read_wal(
+        &self,
+        from: SeqNumberType,
+        limit: usize,
+    ) -> CollectionResult<Vec<(SeqNumberType, CollectionUpdateOperations)>> {
+        let wal = self.wal.lock();
+        check_changes_available(from, wal.first_index())?;
+        wal.try_read(from)
+            .take(limit)
+            .map(|(op_num, operation)| {
+                operation
+                    .map(|record| (op_num, record.operation))
+                    .map_err(|err| CollectionError::service_error(err.to_string()))
+            })
+            .collect()
+    }
+
+    /// Wait until the operation with the sequential number `operation_id` of the WAL is applied.
+    /// Returns `false`, if the operation is not applied within the `timeout`.
+    pub async fn wait_for_operation(
+        &self,
+        operation_id: SeqNumberType,
+        timeout: Duration,
+    ) -> CollectionResult<bool> {
+        let applied = async {
+            // The operation might not be received by this replica yet
+            loop {
+                let next_operation_id = {
+                    let wal = self.wal.lock();
+                    wal.first_index() + wal.len()
+                };
+                if operation_id < next_operation_id {
+                    break;
+                }
+                tokio::time::sleep(WAIT_FOR_OPERATION_POLL_INTERVAL).await;
+            }
+            // Operations are applied in the order of the WAL, so the plunger is processed after it
+            let (tx, rx) = oneshot::channel();
+            self.update_sender
+                .load()
+                .send(UpdateSignal::Plunger(tx))
+                .await?;
+            rx.await?;
+            CollectionResult::Ok(())
+        };
+        match tokio::time::timeout(timeout, applied).await {
+            Ok(res) => res.map(|()| true),
+            Err(_elapsed) => Ok(false),
+        }
+    }
+
+    /// Load points into the empty shard by building immutable segments from them directly,
+    /// bypassing the appendable segments and the following optimizations of the regular upserts.
+    ///
+    /// Points are sorted by id and split into segments, limited by `max_segment_size`, which are
+    /// built in parallel on the optimization threads.
+    /// Points are not written into the WAL, built segments are persisted instead. They get the
+    /// number of the next operation of the WAL, so the following updates are applied on top.
+    ///
+    /// Fails, if the shard contains points or receives them while the segments are built.
+    /// Returns the sequential number, the points are written with.
+    pub async fn bulk_build(
+        &self,
+        mut points: Vec<PointStruct>,
+    ) -> CollectionResult<SeqNumberType> {
+        if has_points(&self.segments.read()) {
+            return Err(CollectionError::bad_request(
+                "Bulk build is only allowed for an empty shard".to_string(),
+            ));
+        }
+
+        let op_num = {
+            let wal = self.wal.lock();
+            wal.first_index() + wal.len()
+        };
+
+        // The last one of the points with the same id is kept, same as with the regular upsert
+        points.reverse();
+        points.sort_by_key(|point| point.id);
+        points.dedup_by_key(|point| point.id);
+        if points.is_empty() {
+            return Ok(op_num);
+        }
+
+        let optimizer = self.optimizers.first().cloned().ok_or_else(|| {
+            CollectionError::service_error("No optimizers to build segments with".to_string())
         })?;
+        let segment_points = {
+            let config = self.config.read().await;
+            let max_dim = config
+                .params
+                .get_all_vector_params(&config.hnsw_config)?
+                .values()
+                .map(|params| params.size)
+                .max()
+                .unwrap_or(0);
+            let point_size = cmp::max(1, max_dim * VECTOR_ELEMENT_SIZE);
+            let max_segment_points = cmp::max(
+                1,
+                optimizer.threshold_config().max_segment_size * BYTES_IN_KB / point_size,
+            );
+            // Spread small loads over the default number of segments, so they are built in parallel
+            let number_of_segments = config.optimizer_config.get_number_segments();
+            cmp::min(
+                max_segment_points,
+                cmp::max(1, points.len().div_ceil(number_of_segments)),
+            )
+        };
+        let indexed_fields = self
+            .segments
+            .read()
+            .iter()
+            .next()
+            .map(|(_id, segment)| segment.get().read().get_indexed_fields())
+            .unwrap_or_default();
+
+        let optimization_scheduler = self
+            .update_handler
+            .lock()
+            .await
+            .optimization_scheduler()
+            .clone();
+        let mut build_handles = Vec::new();
+        while !points.is_empty() {
+            let rest = points.split_off(cmp::min(segment_points, points.len()));
+            let segment_points = std::mem::replace(&mut points, rest);
+            let optimizer = optimizer.clone();
+            let indexed_fields = indexed_fields.clone();
+            build_handles.push(optimization_scheduler.spawn(
+                OptimizationPriority::Low,
+                move |stopped| {
+                    Some(optimizer.build_bulk_segment(
+                        &segment_points,
+                        op_num,
+                        &indexed_fields,
+                        stopped,
+                    ))
+                },
+            ));
+        }
+
+        let mut built_segments = Vec::with_capacity(build_handles.len());
+        let mut build_error = None;
+        for build_handle in build_handles {
+            match build_handle.join_handle.await {
+                Ok(Some(Ok(segment))) => built_segments.push(segment),
+                Ok(Some(Err(err))) => build_error = Some(err),
+                Ok(None) => {
+                    build_error = Some(CollectionError::Cancelled {
+                        description: "Bulk build is cancelled".to_string(),
+                    })
+                }
+                Err(err) => build_error = Some(CollectionError::service_error(err.to_string())),
+            }
+        }
+
+        if build_error.is_none() {
+            let mut segments = self.segments.write();
+            // Updates are blocked by the lock, so no points are added after the check
+            if has_points(&segments) {
+                build_error = Some(CollectionError::bad_request(
+                    "Shard is updated during the bulk build".to_string(),
+                ));
+            } else {
+                for segment in built_segments.drain(..) {
+                    segments.add(segment);
+                }
+            }
+        }
+
+        // Segments are only left if the build failed
+        for segment in built_segments {
+            segment.drop_data()?;
+        }
+        match build_error {
+            Some(err) => Err(err),
+            None => Ok(op_num),
+        }
+    }
+
+    /// Read vectors of all segments, stored on disk, into the page cache
+    pub async fn populate(&self) -> CollectionResult<()> {
+        let segments: Vec<_> = self
+            .segments()
+            .read()
+            .iter()
+            .map(|(_id, segment)| segment.clone())
+            .collect();
+        tokio::task::spawn_blocking(move || {
+            for segment in segments {
+                segment.get().read().populate();
+            }
+        })
+        .await?;
         Ok(())
     }
 
+    /// Verify data of all segments against the stored checksums.
+    /// Corrupted segments are reported, not failed on
+    pub async fn verify(
+        &self,
+        shard_id: ShardId,
+    ) -> CollectionResult<CollectionVerificationReport> {
+        let segments: Vec<_> = self
+            .segments()
+            .read()
+            .iter()
+            .map(|(_id, segment)| segment.clone())
+            .collect();
+        let report = tokio::task::spawn_blocking(move || {
+            let mut report = CollectionVerificationReport::default();
+            for segment in segments {
+                let segment = segment.get();
+                let segment = segment.read();
+                report.segments_checked += 1;
+                if let Err(err) = segment.verify_data() {
+                    report.corrupted_segments.push(CorruptedSegment {
+                        shard_id,
+                        segment: segment
+                            .data_path()
+                            .file_name()
+                            .map(|name| name.to_string_lossy().to_string())
+                            .unwrap_or_default(),
+                        error: err.to_string(),
+                    });
+                }
+            }
+            report
+        })
+        .await?;
+        Ok(report)
+    }
+
     pub async fn estimate_cardinality<'a>(
         &'a self,
         filter: Option<&'a Filter>,
@@ -585,6 +1014,40 @@ impl LocalShard {
                 status: optimizer_status,
                 optimizations,
             },
+            update_queue: self.get_update_queue_telemetry().await,
+        }
+    }
+
+    /// Number of updates, waiting in the queue to be applied to the segments
+    pub fn update_queue_length(&self) -> usize {
+        UPDATE_QUEUE_SIZE.saturating_sub(self.update_sender.load().capacity())
+    }
+
+    async fn get_update_queue_telemetry(&self) -> UpdateQueueTelemetry {
+        let applied_version = self
+            .segments()
+            .read()
+            .iter()
+            .map(|(_id, segment)| segment.get().read().version())
+            .max();
+        let wal_lag = {
+            let wal = self.wal.lock();
+            let next_op_num = wal.first_index() + wal.len();
+            match applied_version {
+                Some(version) => next_op_num.saturating_sub(version + 1),
+                None => wal.len(),
+            }
+        };
+        let optimizer_backlog = self
+            .update_handler
+            .lock()
+            .await
+            .optimizations_backlog()
+            .await;
+        UpdateQueueTelemetry {
+            queue_length: self.update_queue_length(),
+            wal_lag,
+            optimizer_backlog,
         }
     }
 
@@ -614,8 +1077,11 @@ impl LocalShard {
 
             let segment_info = match segment {
                 LockedSegment::Original(original_segment) => {
-                    let info = original_segment.read().info();
-                    if info.segment_type == SegmentType::Indexed {
+                    let segment_lock = original_segment.read();
+                    let info = segment_lock.info();
+                    if info.segment_type == SegmentType::Indexed
+                        && !segment_lock.has_missing_indexes()
+                    {
                         indexed_vectors_count += info.num_vectors;
                     }
                     info
@@ -624,8 +1090,12 @@ impl LocalShard {
                     let proxy_segment_lock = proxy_segment.read();
                     let proxy_segment_info = proxy_segment_lock.info();
 
-                    let wrapped_info = proxy_segment_lock.wrapped_segment.get().read().info();
-                    if wrapped_info.segment_type == SegmentType::Indexed {
+                    let wrapped_segment = proxy_segment_lock.wrapped_segment.get();
+                    let wrapped_segment_lock = wrapped_segment.read();
+                    let wrapped_info = wrapped_segment_lock.info();
+                    if wrapped_info.segment_type == SegmentType::Indexed
+                        && !wrapped_segment_lock.has_missing_indexes()
+                    {
                         indexed_vectors_count += wrapped_info.num_vectors;
                     }
                     proxy_segment_info
@@ -648,6 +1118,11 @@ impl LocalShard {
                 }
             }
         }
+        let index_rebuild = Self::index_rebuild_progress(&segments);
+        let config_migration = self.config_migration_progress(&segments, &collection_config);
+        if index_rebuild.is_some() || config_migration.is_some() {
+            status = CollectionStatus::Yellow;
+        }
         if !segments.failed_operation.is_empty() || segments.optimizer_errors.is_some() {
             status = CollectionStatus::Red;
         }
@@ -666,7 +1141,71 @@ impl LocalShard {
             segments_count,
             config: collection_config,
             payload_schema: schema,
+            index_rebuild,
+            config_migration,
+            initialization: None,
+        }
+    }
+
+    /// Count segments, which indexes were not found on load and are not rebuilt yet
+    fn index_rebuild_progress(segments: &SegmentHolder) -> Option<IndexRebuildProgress> {
+        let mut progress = IndexRebuildProgress::default();
+        for (_idx, segment) in segments.iter() {
+            match segment {
+                LockedSegment::Original(original_segment) => {
+                    if original_segment.read().has_missing_indexes() {
+                        progress.pending_segments += 1;
+                    }
+                }
+                LockedSegment::Proxy(proxy_segment) => {
+                    let proxy_segment_lock = proxy_segment.read();
+                    if proxy_segment_lock
+                        .wrapped_segment
+                        .get()
+                        .read()
+                        .has_missing_indexes()
+                    {
+                        progress.rebuilding_segments += 1;
+                    }
+                }
+            }
+        }
+        (progress.pending_segments + progress.rebuilding_segments > 0).then_some(progress)
+    }
+
+    /// Count segments, built with outdated vector params and not rebuilt with the current ones yet
+    fn config_migration_progress(
+        &self,
+        segments: &SegmentHolder,
+        config: &CollectionConfig,
+    ) -> Option<IndexRebuildProgress> {
+        let is_outdated = |segment: &dyn SegmentEntry| {
+            has_outdated_config(
+                &segment.config(),
+                segment.is_appendable(),
+                &config.params,
+                &config.hnsw_config,
+                &self.storage_cipher,
+            )
+        };
+        let mut progress = IndexRebuildProgress::default();
+        for (_idx, segment) in segments.iter() {
+            match segment {
+                LockedSegment::Original(original_segment) => {
+                    if is_outdated(&*original_segment.read()) {
+                        progress.pending_segments += 1;
+                    }
+                }
+                LockedSegment::Proxy(proxy_segment) => {
+                    let wrapped_segment = proxy_segment.read().wrapped_segment.get();
+                    let is_rebuilding = is_outdated(&*wrapped_segment.read());
+                    if is_rebuilding {
+                        progress.rebuilding_segments += 1;
+                    }
+                }
+            }
         }
+        (progress.pending_segments + progress.rebuilding_segments > 0).then_some(progress)
     }
 }
 
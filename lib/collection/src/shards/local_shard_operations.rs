@@ -1,23 +1,33 @@
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
 use itertools::Itertools;
+use segment::data_types::facets::FacetSketch;
 use segment::types::{
-    ExtendedPointId, Filter, ScoredPoint, WithPayload, WithPayloadInterface, WithVector,
+    BatchSearchResult, ExtendedPointId, Filter, OrderBy, ScoredPoint, WithPayload,
+    WithPayloadInterface, WithVector,
 };
 use tokio::runtime::Handle;
 use tokio::sync::oneshot;
 
 use crate::collection_manager::segments_searcher::SegmentsSearcher;
 use crate::operations::types::{
-    CollectionInfo, CollectionResult, CountRequest, CountResult, PointRequest, Record,
-    SearchRequestBatch, UpdateResult, UpdateStatus,
+    CollectionInfo, CollectionResult, CountRequest, CountResult, FacetRequest, FacetResponse,
+    PointRequest, Record, Sample, SearchRequestBatch, ShardDiscoverRequest, UpdateResult,
+    UpdateStatus,
 };
-use crate::operations::CollectionUpdateOperations;
+use crate::operations::OperationWithId;
 use crate::shards::local_shard::LocalShard;
+use crate::shards::replica_sync::PointDigest;
 use crate::shards::shard_trait::ShardOperation;
 use crate::update_handler::{OperationData, UpdateSignal};
 
+/// Number of counters in the facet sketch per each requested value.
+/// Larger sketch makes counts of the top values more accurate.
+const FACET_SKETCH_FACTOR: usize = 10;
+
 #[async_trait]
 impl ShardOperation for LocalShard {
     /// Imply interior mutability.
@@ -25,7 +35,7 @@ impl ShardOperation for LocalShard {
     /// Explicitly waits for result to be updated.
     async fn update(
         &self,
-        operation: CollectionUpdateOperations,
+        operation: OperationWithId,
         wait: bool,
     ) -> CollectionResult<UpdateResult> {
         let (callback_sender, callback_receiver) = if wait {
@@ -35,14 +45,37 @@ impl ShardOperation for LocalShard {
             (None, None)
         };
 
+        let operation_id_window =
+            Duration::from_secs(self.config.read().await.wal_config.operation_id_window_sec);
+
         let operation_id = {
             let update_sender = self.update_sender.load();
             let channel_permit = update_sender.reserve().await?;
             let mut wal_lock = self.wal.lock();
+
+            // Retry of the operation, which is already in the WAL, is only acknowledged
+            if let Some(client_operation_id) = &operation.operation_id {
+                let mut recent_operation_ids = self.recent_operation_ids.lock();
+                if let Some(op_num) =
+                    recent_operation_ids.get(client_operation_id, operation_id_window)
+                {
+                    return Ok(UpdateResult {
+                        operation_id: op_num,
+                        status: UpdateStatus::Acknowledged,
+                        replica_operation_ids: vec![],
+                    });
+                }
+            }
+
             let operation_id = wal_lock.write(&operation)?;
+            if let Some(client_operation_id) = operation.operation_id {
+                self.recent_operation_ids
+                    .lock()
+                    .record(client_operation_id, operation_id);
+            }
             channel_permit.send(UpdateSignal::Operation(OperationData {
                 op_num: operation_id,
-                operation,
+                operation: operation.operation,
                 sender: callback_sender,
             }));
             operation_id
@@ -53,11 +86,13 @@ impl ShardOperation for LocalShard {
             Ok(UpdateResult {
                 operation_id,
                 status: UpdateStatus::Completed,
+                replica_operation_ids: vec![],
             })
         } else {
             Ok(UpdateResult {
                 operation_id,
                 status: UpdateStatus::Acknowledged,
+                replica_operation_ids: vec![],
             })
         }
     }
@@ -69,7 +104,28 @@ impl ShardOperation for LocalShard {
         with_payload_interface: &WithPayloadInterface,
         with_vector: &WithVector,
         filter: Option<&Filter>,
+        order_by: Option<&OrderBy>,
+        sample: Option<Sample>,
     ) -> CollectionResult<Vec<Record>> {
+        if let Some(Sample::Random) = sample {
+            return self
+                .scroll_randomly(limit, with_payload_interface, with_vector, filter)
+                .await;
+        }
+
+        if let Some(order_by) = order_by {
+            return self
+                .scroll_by_order(
+                    offset,
+                    limit,
+                    with_payload_interface,
+                    with_vector,
+                    filter,
+                    order_by,
+                )
+                .await;
+        }
+
         // ToDo: Make faster points selection with a set
         let segments = self.segments();
         let point_ids = segments
@@ -103,15 +159,22 @@ impl ShardOperation for LocalShard {
         &self,
         request: Arc<SearchRequestBatch>,
         search_runtime_handle: &Handle,
-    ) -> CollectionResult<Vec<Vec<ScoredPoint>>> {
+        deadline: Option<Instant>,
+    ) -> CollectionResult<BatchSearchResult> {
         let collection_params = self.config.read().await.params.clone();
         // check vector names existing
         for req in &request.searches {
             collection_params.get_vector_params(req.vector.get_name())?;
         }
-        let res = SegmentsSearcher::search(self.segments(), request.clone(), search_runtime_handle)
-            .await?;
+        let res = SegmentsSearcher::search(
+            self.segments(),
+            request.clone(),
+            search_runtime_handle,
+            deadline,
+        )
+        .await?;
         let top_results = res
+            .results
             .into_iter()
             .zip(request.searches.iter())
             .map(|(vector_res, req)| {
@@ -136,7 +199,22 @@ impl ShardOperation for LocalShard {
                 }
             })
             .collect();
-        Ok(top_results)
+        Ok(BatchSearchResult {
+            results: top_results,
+            truncated: res.truncated,
+            explanation: res.explanation,
+        })
+    }
+
+    async fn discover(
+        &self,
+        request: Arc<ShardDiscoverRequest>,
+        search_runtime_handle: &Handle,
+    ) -> CollectionResult<Vec<ScoredPoint>> {
+        let collection_params = self.config.read().await.params.clone();
+        collection_params.get_vector_params(&request.vector_name)?;
+        // Discovery score is not a distance, so it is returned as it is
+        SegmentsSearcher::discover(self.segments(), request, search_runtime_handle).await
     }
 
     async fn count(&self, request: Arc<CountRequest>) -> CollectionResult<CountResult> {
@@ -151,6 +229,21 @@ impl ShardOperation for LocalShard {
         Ok(CountResult { count: total_count })
     }
 
+    async fn facet(&self, request: Arc<FacetRequest>) -> CollectionResult<FacetResponse> {
+        let sketch_capacity = request.limit.saturating_mul(FACET_SKETCH_FACTOR);
+        let mut sketch = FacetSketch::new(sketch_capacity);
+        for (_id, segment) in self.segments().read().iter() {
+            sketch.merge(segment.get().read().facet(
+                &request.key,
+                request.filter.as_ref(),
+                sketch_capacity,
+            )?);
+        }
+        Ok(FacetResponse {
+            hits: sketch.top(request.limit),
+        })
+    }
+
     async fn retrieve(
         &self,
         request: Arc<PointRequest>,
@@ -159,4 +252,148 @@ impl ShardOperation for LocalShard {
     ) -> CollectionResult<Vec<Record>> {
         SegmentsSearcher::retrieve(self.segments(), &request.ids, with_payload, with_vector).await
     }
+
+    async fn point_digests(
+        &self,
+        from: Option<ExtendedPointId>,
+        to: Option<ExtendedPointId>,
+        limit: Option<usize>,
+    ) -> CollectionResult<Vec<PointDigest>> {
+        let segments = self.segments();
+        let point_ids = segments
+            .read()
+            .iter()
+            .flat_map(|(_, segment)| {
+                let segment = segment.get();
+                let read_segment = segment.read();
+                match to {
+                    // Limit applies to the whole shard, but is enough for each segment
+                    None => read_segment.read_filtered(from, limit, None),
+                    Some(_) => read_segment.read_range(from, to),
+                }
+            })
+            .sorted()
+            .dedup()
+            .take(limit.unwrap_or(usize::MAX))
+            .collect_vec();
+
+        let records =
+            SegmentsSearcher::retrieve(segments, &point_ids, &true.into(), &true.into()).await?;
+        Ok(records
+            .iter()
+            .map(PointDigest::from_record)
+            .sorted_by_key(|digest| digest.id)
+            .collect())
+    }
+}
+
+impl LocalShard {
+    /// Read points ordered by the value of `order_by` payload field.
+    ///
+    /// Points with the same value are ordered by id, so `(value, id)` pair of the last point
+    /// could be used as a cursor for the next page.
+    async fn scroll_by_order(
+        &self,
+        offset: Option<ExtendedPointId>,
+        limit: usize,
+        with_payload_interface: &WithPayloadInterface,
+        with_vector: &WithVector,
+        filter: Option<&Filter>,
+        order_by: &OrderBy,
+    ) -> CollectionResult<Vec<Record>> {
+        let segments = self.segments();
+        let ordered_points = {
+            let segments = segments.read();
+
+            // Fails if any of the segments has no suitable index on the field
+            let mut ordered_points = vec![];
+            for (_, segment) in segments.iter() {
+                ordered_points.extend(segment.get().read().read_ordered_filtered(
+                    offset,
+                    Some(limit),
+                    filter,
+                    order_by,
+                )?);
+            }
+            ordered_points
+        };
+
+        let ordered_points = ordered_points
+            .into_iter()
+            .sorted_by(|a, b| order_by.compare(*a, *b))
+            .unique_by(|(_, point_id)| *point_id)
+            .take(limit)
+            .collect_vec();
+
+        let point_ids = ordered_points
+            .iter()
+            .map(|(_, point_id)| *point_id)
+            .collect_vec();
+        let with_payload = WithPayload::from(with_payload_interface);
+        let mut records: HashMap<_, _> =
+            SegmentsSearcher::retrieve(segments, &point_ids, &with_payload, with_vector)
+                .await?
+                .into_iter()
+                .map(|record| (record.id, record))
+                .collect();
+
+        let points = ordered_points
+            .into_iter()
+            .filter_map(|(order_value, point_id)| {
+                // Point might have been deleted in between
+                records.remove(&point_id).map(|mut record| {
+                    record.order_value = Some(order_value);
+                    record
+                })
+            })
+            .collect();
+
+        Ok(points)
+    }
+
+    /// Read uniformly random sample of the points.
+    ///
+    /// Random key of each point is returned as its order value, so samples of several shards
+    /// could be merged into a uniform sample of the collection.
+    async fn scroll_randomly(
+        &self,
+        limit: usize,
+        with_payload_interface: &WithPayloadInterface,
+        with_vector: &WithVector,
+        filter: Option<&Filter>,
+    ) -> CollectionResult<Vec<Record>> {
+        let segments = self.segments();
+        let sampled_points = segments
+            .read()
+            .iter()
+            .flat_map(|(_, segment)| segment.get().read().read_random_filtered(limit, filter))
+            .sorted_by(|(key_a, _), (key_b, _)| key_b.total_cmp(key_a))
+            .unique_by(|(_, point_id)| *point_id)
+            .take(limit)
+            .collect_vec();
+
+        let point_ids = sampled_points
+            .iter()
+            .map(|(_, point_id)| *point_id)
+            .collect_vec();
+        let with_payload = WithPayload::from(with_payload_interface);
+        let mut records: HashMap<_, _> =
+            SegmentsSearcher::retrieve(segments, &point_ids, &with_payload, with_vector)
+                .await?
+                .into_iter()
+                .map(|record| (record.id, record))
+                .collect();
+
+        let points = sampled_points
+            .into_iter()
+            .filter_map(|(key, point_id)| {
+                records.remove(&point_id).map(|mut record| {
+                    record.order_value = Some(key);
+                    record
+                })
+            })
+            .collect();
+
+        Ok(points)
+    }
 }
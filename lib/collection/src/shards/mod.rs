@@ -1,13 +1,18 @@
 pub mod channel_service;
 pub mod collection_shard_distribution;
 mod conversions;
+pub mod dead_letter;
 pub mod forward_proxy_shard;
 pub mod local_shard;
 pub mod local_shard_operations;
+pub mod operation_ids;
+pub mod peer_stats;
 pub mod proxy_shard;
 pub mod remote_shard;
 #[allow(dead_code)]
 pub mod replica_set;
+pub mod replica_sync;
+pub mod resharding_preview;
 pub mod shard;
 pub mod shard_config;
 pub mod shard_holder;
@@ -0,0 +1,72 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use segment::types::SeqNumberType;
+
+/// Client-supplied ids of the operations, recently written to the shard WAL.
+/// Used to apply retries of the same operation only once.
+#[derive(Debug, Default)]
+pub struct RecentOperationIds {
+    /// Sequential number of the operation in the WAL by its id
+    operations: HashMap<String, SeqNumberType>,
+    /// Ids in the order they were recorded, to forget the ones outside of the window
+    recorded: VecDeque<(Instant, String, SeqNumberType)>,
+}
+
+impl RecentOperationIds {
+    /// Sequential number of the operation with the `operation_id`, if it was recorded
+    /// within the `window`
+    pub fn get(&mut self, operation_id: &str, window: Duration) -> Option<SeqNumberType> {
+        self.forget_expired(window);
+        self.operations.get(operation_id).copied()
+    }
+
+    pub fn record(&mut self, operation_id: String, op_num: SeqNumberType) {
+        self.operations.insert(operation_id.clone(), op_num);
+        self.recorded
+            .push_back((Instant::now(), operation_id, op_num));
+    }
+
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    fn forget_expired(&mut self, window: Duration) {
+        while let Some((recorded_at, _, _)) = self.recorded.front() {
+            if recorded_at.elapsed() < window {
+                break;
+            }
+            let (_, operation_id, op_num) = self.recorded.pop_front().unwrap();
+            // The id might be recorded again by a retry after the window
+            if self.operations.get(&operation_id) == Some(&op_num) {
+                self.operations.remove(&operation_id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recent_operation_ids() {
+        let window = Duration::from_secs(60);
+        let mut ids = RecentOperationIds::default();
+        assert_eq!(ids.get("a", window), None);
+
+        ids.record("a".to_string(), 10);
+        ids.record("b".to_string(), 11);
+        assert_eq!(ids.get("a", window), Some(10));
+        assert_eq!(ids.get("b", window), Some(11));
+        assert_eq!(ids.len(), 2);
+
+        // Ids are forgotten, once they are outside of the window
+        assert_eq!(ids.get("a", Duration::ZERO), None);
+        assert!(ids.is_empty());
+    }
+}
@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
+use crate::shards::shard::PeerId;
+
+/// Weight of the latest request in the moving averages of latency and error rate
+const SMOOTHING_FACTOR: f64 = 0.2;
+
+/// Latency in seconds, added to the score of a peer, which fails every request
+const ERROR_PENALTY: f64 = 1.0;
+
+/// Peer, which was not requested for this long, is probed with the next read request,
+/// so its stats don't stay outdated after it recovers
+pub const PEER_PROBE_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy)]
+struct PeerRecord {
+    /// Exponential moving average of the read latency in seconds
+    latency: f64,
+    /// Exponential moving average of failures, from 0.0 (no failures) to 1.0 (all failed)
+    error_rate: f64,
+    last_request: Instant,
+}
+
+impl PeerRecord {
+    fn score(&self) -> f64 {
+        self.latency + ERROR_PENALTY * self.error_rate
+    }
+}
+
+/// Recent latency and error rate of read requests to each peer.
+/// Used to prefer the fastest replicas for reads.
+#[derive(Debug, Default)]
+pub struct PeerStats {
+    records: Mutex<HashMap<PeerId, PeerRecord>>,
+}
+
+impl PeerStats {
+    fn update(&self, peer_id: PeerId, latency: Duration, failed: bool) {
+        let latency = latency.as_secs_f64();
+        let error = if failed { 1.0 } else { 0.0 };
+        let mut records = self.records.lock();
+        match records.get_mut(&peer_id) {
+            Some(record) => {
+                record.latency += SMOOTHING_FACTOR * (latency - record.latency);
+                record.error_rate += SMOOTHING_FACTOR * (error - record.error_rate);
+            }
+            None => {
+                records.insert(
+                    peer_id,
+                    PeerRecord {
+                        latency,
+                        error_rate: error,
+                        last_request: Instant::now(),
+                    },
+                );
+            }
+        }
+    }
+
+    /// Peer responded to a read request after `latency`
+    pub fn report_success(&self, peer_id: PeerId, latency: Duration) {
+        self.update(peer_id, latency, false)
+    }
+
+    /// Read request to the peer failed after `latency`
+    pub fn report_failure(&self, peer_id: PeerId, latency: Duration) {
+        self.update(peer_id, latency, true)
+    }
+
+    /// Order peers from the most to the least preferable for a read request.
+    /// Peers without stats go first, so they get measured.
+    ///
+    /// If some peer outside of the first `selection` ones was not requested for
+    /// `PEER_PROBE_INTERVAL`, it replaces the last selected peer to refresh its stats.
+    pub fn order_peers<T>(
+        &self,
+        items: &mut [T],
+        selection: usize,
+        peer_id: impl Fn(&T) -> PeerId,
+    ) {
+        let records = self.records.lock();
+        let score = |item: &T| records.get(&peer_id(item)).map_or(0.0, PeerRecord::score);
+        // Sort is stable, so peers with equal scores keep the given order
+        items.sort_by(|a, b| score(a).total_cmp(&score(b)));
+
+        if selection == 0 || selection >= items.len() {
+            return;
+        }
+        let probe = items[selection..].iter().position(|item| {
+            records.get(&peer_id(item)).map_or(false, |record| {
+                record.last_request.elapsed() >= PEER_PROBE_INTERVAL
+            })
+        });
+        if let Some(probe) = probe {
+            items.swap(selection - 1, selection + probe);
+        }
+    }
+
+    /// Read request is sent to the peer
+    pub fn mark_requested(&self, peer_id: PeerId) {
+        if let Some(record) = self.records.lock().get_mut(&peer_id) {
+            record.last_request = Instant::now();
+        }
+    }
+
+    pub fn remove_peer(&self, peer_id: PeerId) {
+        self.records.lock().remove(&peer_id);
+    }
+}
+
+/// Reports the latency of a read request to `PeerStats`.
+/// If the request is dropped before it is finished, because another replica answered first,
+/// the time spent so far is reported as the latency, as the peer is at least that slow.
+pub struct ReadLatencyGuard<'a> {
+    stats: &'a PeerStats,
+    peer_id: PeerId,
+    start: Instant,
+    finished: bool,
+}
+
+impl<'a> ReadLatencyGuard<'a> {
+    pub fn new(stats: &'a PeerStats, peer_id: PeerId) -> Self {
+        stats.mark_requested(peer_id);
+        Self {
+            stats,
+            peer_id,
+            start: Instant::now(),
+            finished: false,
+        }
+    }
+
+    pub fn success(mut self) {
+        self.finished = true;
+        self.stats
+            .report_success(self.peer_id, self.start.elapsed());
+    }
+
+    pub fn failure(mut self) {
+        self.finished = true;
+        self.stats
+            .report_failure(self.peer_id, self.start.elapsed());
+    }
+}
+
+impl Drop for ReadLatencyGuard<'_> {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.stats
+                .report_success(self.peer_id, self.start.elapsed());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_order_peers() {
+        let stats = PeerStats::default();
+        stats.report_success(1, Duration::from_millis(50));
+        stats.report_success(2, Duration::from_millis(10));
+        stats.report_success(3, Duration::from_millis(20));
+
+        let mut peers = vec![1, 2, 3, 4];
+        stats.order_peers(&mut peers, 2, |peer| *peer);
+        // Peer 4 has no stats yet
+        assert_eq!(peers, vec![4, 2, 3, 1]);
+
+        // Failing peer is less preferable, than a slower one
+        stats.report_failure(2, Duration::from_millis(10));
+        let mut peers = vec![1, 2, 3];
+        stats.order_peers(&mut peers, 3, |peer| *peer);
+        assert_eq!(peers, vec![3, 1, 2]);
+    }
+
+    #[test]
+    fn test_probe_outdated_peer() {
+        let stats = PeerStats::default();
+        stats.report_success(1, Duration::from_millis(10));
+        stats.report_success(2, Duration::from_millis(20));
+        stats.report_success(3, Duration::from_millis(50));
+        stats.records.lock().get_mut(&3).unwrap().last_request -= PEER_PROBE_INTERVAL;
+
+        let mut peers = vec![1, 2, 3];
+        stats.order_peers(&mut peers, 2, |peer| *peer);
+        assert_eq!(peers, vec![1, 3, 2]);
+
+        // Once requested, peer is not probed again until the interval passes
+        stats.mark_requested(3);
+        let mut peers = vec![1, 2, 3];
+        stats.order_peers(&mut peers, 2, |peer| *peer);
+        assert_eq!(peers, vec![1, 2, 3]);
+    }
+}
@@ -2,12 +2,12 @@ use std::collections::HashSet;
 use std::path::Path;
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
 use segment::types::{
-    ExtendedPointId, Filter, PointIdType, ScoredPoint, WithPayload, WithPayloadInterface,
-    WithVector,
+    BatchSearchResult, ExtendedPointId, Filter, OrderBy, PointIdType, ScoredPoint, WithPayload,
+    WithPayloadInterface, WithVector,
 };
 use tokio::runtime::Handle;
 use tokio::sync::{oneshot, RwLock};
@@ -16,12 +16,15 @@ use tokio::time::timeout;
 use crate::operations::operation_effect::{
     EstimateOperationEffectArea, OperationEffectArea, PointsOperationEffect,
 };
+use crate::operations::snapshot_ops::SegmentVersions;
 use crate::operations::types::{
-    CollectionError, CollectionInfo, CollectionResult, CountRequest, CountResult, PointRequest,
-    Record, SearchRequestBatch, UpdateResult,
+    CollectionError, CollectionInfo, CollectionResult, CountRequest, CountResult, FacetRequest,
+    FacetResponse, PointRequest, Record, Sample, SearchRequestBatch, ShardDiscoverRequest,
+    UpdateResult,
 };
-use crate::operations::CollectionUpdateOperations;
+use crate::operations::OperationWithId;
 use crate::shards::local_shard::LocalShard;
+use crate::shards::replica_sync::PointDigest;
 use crate::shards::shard_trait::ShardOperation;
 use crate::shards::telemetry::LocalShardTelemetry;
 use crate::update_handler::UpdateSignal;
@@ -35,7 +38,7 @@ type ChangedPointsSet = Arc<RwLock<HashSet<PointIdType>>>;
 /// It can be used to provide all read and write operations while the wrapped shard is being transferred to another node.
 /// It keeps track of changed points during the shard transfer to assure consistency.
 pub struct ProxyShard {
-    wrapped_shard: LocalShard,
+    pub(crate) wrapped_shard: LocalShard,
     changed_points: ChangedPointsSet,
     pub changed_alot: AtomicBool,
 }
@@ -62,8 +65,12 @@ impl ProxyShard {
     }
 
     /// Forward `create_snapshot` to `wrapped_shard`
-    pub async fn create_snapshot(&self, target_path: &Path) -> CollectionResult<()> {
-        self.wrapped_shard.create_snapshot(target_path).await
+    pub async fn create_snapshot(
+        &self,
+        target_path: &Path,
+        base: &SegmentVersions,
+    ) -> CollectionResult<SegmentVersions> {
+        self.wrapped_shard.create_snapshot(target_path, base).await
     }
 
     pub async fn on_optimizer_config_update(&self) -> CollectionResult<()> {
@@ -124,11 +131,11 @@ impl ShardOperation for ProxyShard {
     /// Update `wrapped_shard` while keeping track of the changed points
     async fn update(
         &self,
-        operation: CollectionUpdateOperations,
+        operation: OperationWithId,
         wait: bool,
     ) -> CollectionResult<UpdateResult> {
         let local_shard = &self.wrapped_shard;
-        let estimate_effect = operation.estimate_effect_area();
+        let estimate_effect = operation.operation.estimate_effect_area();
         let points_operation_effect: PointsOperationEffect = match estimate_effect {
             OperationEffectArea::Empty => PointsOperationEffect::Empty,
             OperationEffectArea::Points(points) => PointsOperationEffect::Some(points),
@@ -173,10 +180,20 @@ impl ShardOperation for ProxyShard {
         with_payload_interface: &WithPayloadInterface,
         with_vector: &WithVector,
         filter: Option<&Filter>,
+        order_by: Option<&OrderBy>,
+        sample: Option<Sample>,
     ) -> CollectionResult<Vec<Record>> {
         let local_shard = &self.wrapped_shard;
         local_shard
-            .scroll_by(offset, limit, with_payload_interface, with_vector, filter)
+            .scroll_by(
+                offset,
+                limit,
+                with_payload_interface,
+                with_vector,
+                filter,
+                order_by,
+                sample,
+            )
             .await
     }
 
@@ -191,9 +208,22 @@ impl ShardOperation for ProxyShard {
         &self,
         request: Arc<SearchRequestBatch>,
         search_runtime_handle: &Handle,
-    ) -> CollectionResult<Vec<Vec<ScoredPoint>>> {
+        deadline: Option<Instant>,
+    ) -> CollectionResult<BatchSearchResult> {
+        let local_shard = &self.wrapped_shard;
+        local_shard
+            .search(request, search_runtime_handle, deadline)
+            .await
+    }
+
+    /// Forward read-only `discover` to `wrapped_shard`
+    async fn discover(
+        &self,
+        request: Arc<ShardDiscoverRequest>,
+        search_runtime_handle: &Handle,
+    ) -> CollectionResult<Vec<ScoredPoint>> {
         let local_shard = &self.wrapped_shard;
-        local_shard.search(request, search_runtime_handle).await
+        local_shard.discover(request, search_runtime_handle).await
     }
 
     /// Forward read-only `count` to `wrapped_shard`
@@ -202,6 +232,12 @@ impl ShardOperation for ProxyShard {
         local_shard.count(request).await
     }
 
+    /// Forward read-only `facet` to `wrapped_shard`
+    async fn facet(&self, request: Arc<FacetRequest>) -> CollectionResult<FacetResponse> {
+        let local_shard = &self.wrapped_shard;
+        local_shard.facet(request).await
+    }
+
     /// Forward read-only `retrieve` to `wrapped_shard`
     async fn retrieve(
         &self,
@@ -214,4 +250,15 @@ impl ShardOperation for ProxyShard {
             .retrieve(request, with_payload, with_vector)
             .await
     }
+
+    /// Forward read-only `point_digests` to `wrapped_shard`
+    async fn point_digests(
+        &self,
+        from: Option<ExtendedPointId>,
+        to: Option<ExtendedPointId>,
+        limit: Option<usize>,
+    ) -> CollectionResult<Vec<PointDigest>> {
+        let local_shard = &self.wrapped_shard;
+        local_shard.point_digests(from, to, limit).await
+    }
 }
@@ -1,13 +1,17 @@
 use std::future::Future;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Instant;
 
+use api::grpc::peer_channel::PeerChannel;
 use api::grpc::qdrant::collections_internal_client::CollectionsInternalClient;
 use api::grpc::qdrant::points_internal_client::PointsInternalClient;
 use api::grpc::qdrant::{
-    CollectionOperationResponse, CountPoints, CountPointsInternal, GetCollectionInfoRequest,
+    CollectionOperationResponse, ContextPairInternal, CountPoints, CountPointsInternal,
+    DiscoverPointsInternal, FacetPoints, FacetPointsInternal, GetCollectionInfoRequest,
     GetCollectionInfoRequestInternal, GetPoints, GetPointsInternal, InitiateShardTransferRequest,
-    ScrollPoints, ScrollPointsInternal, SearchBatchPointsInternal,
+    PointDigestsInternal, ReadChangesInternal, ScrollPoints, ScrollPointsInternal,
+    SearchBatchPointsInternal, SearchPoints,
 };
 use async_trait::async_trait;
 use parking_lot::Mutex;
@@ -15,26 +19,30 @@ use segment::common::operation_time_statistics::{
     OperationDurationsAggregator, ScopeDurationMeasurer,
 };
 use segment::types::{
-    ExtendedPointId, Filter, ScoredPoint, WithPayload, WithPayloadInterface, WithVector,
+    BatchSearchResult, ExtendedPointId, Filter, OrderBy, ScoredPoint, SeqNumberType, WithPayload,
+    WithPayloadInterface, WithVector,
 };
 use tokio::runtime::Handle;
-use tonic::transport::{Channel, Uri};
+use tonic::transport::Uri;
 use tonic::Status;
 
+use crate::operations::changes::PointsChanges;
 use crate::operations::payload_ops::PayloadOps;
-use crate::operations::point_ops::PointOperations;
+use crate::operations::point_ops::{PointOperations, WriteOrdering};
 use crate::operations::types::{
-    CollectionError, CollectionInfo, CollectionResult, CountRequest, CountResult, PointRequest,
-    Record, SearchRequest, SearchRequestBatch, UpdateResult,
+    CollectionError, CollectionInfo, CollectionResult, CountRequest, CountResult, FacetRequest,
+    FacetResponse, PointRequest, Record, Sample, SearchRequest, SearchRequestBatch,
+    ShardDiscoverRequest, UpdateResult,
 };
-use crate::operations::{CollectionUpdateOperations, FieldIndexOperations};
+use crate::operations::{CollectionUpdateOperations, FieldIndexOperations, OperationWithId};
 use crate::shards::channel_service::ChannelService;
 use crate::shards::conversions::{
     internal_clear_payload, internal_clear_payload_by_filter, internal_create_index,
     internal_delete_index, internal_delete_payload, internal_delete_points,
     internal_delete_points_by_filter, internal_set_payload, internal_sync_points,
-    internal_upsert_points,
+    internal_update_payload, internal_upsert_points,
 };
+use crate::shards::replica_sync::PointDigest;
 use crate::shards::shard::{PeerId, ShardId};
 use crate::shards::shard_config::ShardConfig;
 use crate::shards::shard_trait::ShardOperation;
@@ -44,6 +52,8 @@ use crate::shards::CollectionId;
 /// RemoteShard
 ///
 /// Remote Shard is a representation of a shard that is located on a remote peer.
+/// Clones share the telemetry of the original.
+#[derive(Clone)]
 pub struct RemoteShard {
     pub(crate) id: ShardId,
     pub(crate) collection_id: CollectionId,
@@ -114,13 +124,19 @@ impl RemoteShard {
 
     async fn with_points_client<T, O: Future<Output = Result<T, Status>>>(
         &self,
-        f: impl Fn(PointsInternalClient<Channel>) -> O,
+        f: impl Fn(PointsInternalClient<PeerChannel>) -> O,
     ) -> CollectionResult<T> {
         let current_address = self.current_address()?;
         self.channel_service
             .channel_pool
             .with_channel(&current_address, |channel| {
-                f(PointsInternalClient::new(channel))
+                let compression = channel.send_gzip();
+                let client = PointsInternalClient::new(channel).accept_gzip();
+                if compression {
+                    f(client.send_gzip())
+                } else {
+                    f(client)
+                }
             })
             .await
             .map_err(|err| err.into())
@@ -128,13 +144,19 @@ impl RemoteShard {
 
     async fn with_collections_client<T, O: Future<Output = Result<T, Status>>>(
         &self,
-        f: impl Fn(CollectionsInternalClient<Channel>) -> O,
+        f: impl Fn(CollectionsInternalClient<PeerChannel>) -> O,
     ) -> CollectionResult<T> {
         let current_address = self.current_address()?;
         self.channel_service
             .channel_pool
             .with_channel(&current_address, |channel| {
-                f(CollectionsInternalClient::new(channel))
+                let compression = channel.send_gzip();
+                let client = CollectionsInternalClient::new(channel).accept_gzip();
+                if compression {
+                    f(client.send_gzip())
+                } else {
+                    f(client)
+                }
             })
             .await
             .map_err(|err| err.into())
@@ -148,40 +170,34 @@ impl RemoteShard {
         }
     }
 
-    pub async fn initiate_transfer(&self) -> CollectionResult<CollectionOperationResponse> {
-        let res = self
-            .with_collections_client(|mut client| async move {
-                client
-                    .initiate(InitiateShardTransferRequest {
-                        collection_name: self.collection_id.clone(),
-                        shard_id: self.id,
-                    })
-                    .await
-            })
-            .await?
-            .into_inner();
-        Ok(res)
-    }
-}
-
-// New-type to own the type in the crate for conversions via From
-pub struct CollectionSearchRequest<'a>(pub(crate) (CollectionId, &'a SearchRequest));
-
-#[async_trait]
-#[allow(unused_variables)]
-impl ShardOperation for RemoteShard {
-    async fn update(
+    /// Send the update operation to the remote peer.
+    ///
+    /// With `ordering` defined, the remote peer is expected to be the leader of the replica set
+    /// and to apply the operation to all replicas of the shard.
+    /// Operation id is passed only with the upserts and deletions of points.
+    async fn execute_update_operation(
         &self,
-        operation: CollectionUpdateOperations,
+        operation: OperationWithId,
         wait: bool,
+        ordering: Option<WriteOrdering>,
     ) -> CollectionResult<UpdateResult> {
+        let OperationWithId {
+            operation,
+            operation_id,
+        } = operation;
         let mut timer = ScopeDurationMeasurer::new(&self.telemetry_update_durations);
         timer.set_success(false);
 
         let point_operation_response = match operation {
             CollectionUpdateOperations::PointOperation(point_ops) => match point_ops {
                 PointOperations::UpsertPoints(point_insert_operations) => {
-                    let request = &internal_upsert_points(point_insert_operations, self, wait)?;
+                    let request = &internal_upsert_points(
+                        point_insert_operations,
+                        self,
+                        wait,
+                        ordering,
+                        operation_id,
+                    )?;
                     self.with_points_client(|mut client| async move {
                         client.upsert(tonic::Request::new(request.clone())).await
                     })
@@ -189,7 +205,7 @@ impl ShardOperation for RemoteShard {
                     .into_inner()
                 }
                 PointOperations::DeletePoints { ids } => {
-                    let request = &internal_delete_points(ids, self, wait);
+                    let request = &internal_delete_points(ids, self, wait, ordering, operation_id);
                     self.with_points_client(|mut client| async move {
                         client.delete(tonic::Request::new(request.clone())).await
                     })
@@ -197,7 +213,13 @@ impl ShardOperation for RemoteShard {
                     .into_inner()
                 }
                 PointOperations::DeletePointsByFilter(filter) => {
-                    let request = &internal_delete_points_by_filter(filter, self, wait);
+                    let request = &internal_delete_points_by_filter(
+                        filter,
+                        self,
+                        wait,
+                        ordering,
+                        operation_id,
+                    );
                     self.with_points_client(|mut client| async move {
                         client.delete(tonic::Request::new(request.clone())).await
                     })
@@ -215,7 +237,7 @@ impl ShardOperation for RemoteShard {
             },
             CollectionUpdateOperations::PayloadOperation(payload_ops) => match payload_ops {
                 PayloadOps::SetPayload(set_payload) => {
-                    let request = &internal_set_payload(set_payload, self, wait);
+                    let request = &internal_set_payload(set_payload, self, wait, ordering);
                     self.with_points_client(|mut client| async move {
                         client
                             .set_payload(tonic::Request::new(request.clone()))
@@ -225,7 +247,7 @@ impl ShardOperation for RemoteShard {
                     .into_inner()
                 }
                 PayloadOps::DeletePayload(delete_payload) => {
-                    let request = &internal_delete_payload(delete_payload, self, wait);
+                    let request = &internal_delete_payload(delete_payload, self, wait, ordering);
                     self.with_points_client(|mut client| async move {
                         client
                             .delete_payload(tonic::Request::new(request.clone()))
@@ -234,8 +256,18 @@ impl ShardOperation for RemoteShard {
                     .await?
                     .into_inner()
                 }
+                PayloadOps::UpdatePayload(update_payload) => {
+                    let request = &internal_update_payload(update_payload, self, wait, ordering);
+                    self.with_points_client(|mut client| async move {
+                        client
+                            .update_payload(tonic::Request::new(request.clone()))
+                            .await
+                    })
+                    .await?
+                    .into_inner()
+                }
                 PayloadOps::ClearPayload { points } => {
-                    let request = &internal_clear_payload(points, self, wait);
+                    let request = &internal_clear_payload(points, self, wait, ordering);
                     self.with_points_client(|mut client| async move {
                         client
                             .clear_payload(tonic::Request::new(request.clone()))
@@ -245,7 +277,7 @@ impl ShardOperation for RemoteShard {
                     .into_inner()
                 }
                 PayloadOps::ClearPayloadByFilter(filter) => {
-                    let request = &internal_clear_payload_by_filter(filter, self, wait);
+                    let request = &internal_clear_payload_by_filter(filter, self, wait, ordering);
                     self.with_points_client(|mut client| async move {
                         client
                             .clear_payload(tonic::Request::new(request.clone()))
@@ -258,7 +290,7 @@ impl ShardOperation for RemoteShard {
             CollectionUpdateOperations::FieldIndexOperation(field_index_op) => match field_index_op
             {
                 FieldIndexOperations::CreateIndex(create_index) => {
-                    let request = &internal_create_index(create_index, self, wait);
+                    let request = &internal_create_index(create_index, self, wait, ordering);
                     self.with_points_client(|mut client| async move {
                         client
                             .create_field_index(tonic::Request::new(request.clone()))
@@ -268,7 +300,7 @@ impl ShardOperation for RemoteShard {
                     .into_inner()
                 }
                 FieldIndexOperations::DeleteIndex(delete_index) => {
-                    let request = &internal_delete_index(delete_index, self, wait);
+                    let request = &internal_delete_index(delete_index, self, wait, ordering);
                     self.with_points_client(|mut client| async move {
                         client
                             .delete_field_index(tonic::Request::new(request.clone()))
@@ -288,6 +320,70 @@ impl ShardOperation for RemoteShard {
         }
     }
 
+    /// Forward the update operation to the leader of the replica set on the remote peer
+    pub async fn forward_update(
+        &self,
+        operation: OperationWithId,
+        wait: bool,
+        ordering: WriteOrdering,
+    ) -> CollectionResult<UpdateResult> {
+        self.execute_update_operation(operation, wait, Some(ordering))
+            .await
+    }
+
+    pub async fn initiate_transfer(&self) -> CollectionResult<CollectionOperationResponse> {
+        let res = self
+            .with_collections_client(|mut client| async move {
+                client
+                    .initiate(InitiateShardTransferRequest {
+                        collection_name: self.collection_id.clone(),
+                        shard_id: self.id,
+                    })
+                    .await
+            })
+            .await?
+            .into_inner();
+        Ok(res)
+    }
+
+    /// Read changes of the points from the WAL of the shard replica on the remote peer
+    pub async fn read_changes(
+        &self,
+        from: SeqNumberType,
+        limit: usize,
+    ) -> CollectionResult<PointsChanges> {
+        let request = &ReadChangesInternal {
+            collection_name: self.collection_id.clone(),
+            shard_id: self.id,
+            from,
+            limit: limit as u64,
+        };
+        let response = self
+            .with_points_client(|mut client| async move {
+                client
+                    .read_changes(tonic::Request::new(request.clone()))
+                    .await
+            })
+            .await?
+            .into_inner();
+        Ok(response.try_into()?)
+    }
+}
+
+// New-type to own the type in the crate for conversions via From
+pub struct CollectionSearchRequest<'a>(pub(crate) (CollectionId, &'a SearchRequest));
+
+#[async_trait]
+#[allow(unused_variables)]
+impl ShardOperation for RemoteShard {
+    async fn update(
+        &self,
+        operation: OperationWithId,
+        wait: bool,
+    ) -> CollectionResult<UpdateResult> {
+        self.execute_update_operation(operation, wait, None).await
+    }
+
     async fn scroll_by(
         &self,
         offset: Option<ExtendedPointId>,
@@ -295,6 +391,8 @@ impl ShardOperation for RemoteShard {
         with_payload_interface: &WithPayloadInterface,
         with_vector: &WithVector,
         filter: Option<&Filter>,
+        order_by: Option<&OrderBy>,
+        sample: Option<Sample>,
     ) -> CollectionResult<Vec<Record>> {
         let scroll_points = ScrollPoints {
             collection_name: self.collection_id.clone(),
@@ -303,6 +401,8 @@ impl ShardOperation for RemoteShard {
             limit: Some(limit as u32),
             with_payload: Some(with_payload_interface.clone().into()),
             with_vectors: Some(with_vector.clone().into()),
+            order_by: order_by.map(|order_by| order_by.clone().into()),
+            sample: sample.map(|sample| api::grpc::qdrant::Sample::from(sample) as i32),
         };
         let request = &ScrollPointsInternal {
             scroll_points: Some(scroll_points),
@@ -347,14 +447,32 @@ impl ShardOperation for RemoteShard {
         &self,
         request: Arc<SearchRequestBatch>,
         search_runtime_handle: &Handle,
-    ) -> CollectionResult<Vec<Vec<ScoredPoint>>> {
+        deadline: Option<Instant>,
+    ) -> CollectionResult<BatchSearchResult> {
         let mut timer = ScopeDurationMeasurer::new(&self.telemetry_search_durations);
         timer.set_success(false);
 
+        // Remote peer gets the rest of the time budget, so it stops at the same deadline
+        let max_time_ms = deadline.map(|deadline| {
+            deadline
+                .saturating_duration_since(Instant::now())
+                .as_millis() as u64
+        });
+
         let search_points = request
             .searches
             .iter()
-            .map(|s| CollectionSearchRequest((self.collection_id.clone(), s)).into())
+            .map(|s| {
+                let mut search_points: SearchPoints =
+                    CollectionSearchRequest((self.collection_id.clone(), s)).into();
+                if max_time_ms.is_some() {
+                    search_points
+                        .params
+                        .get_or_insert_with(Default::default)
+                        .max_time_ms = max_time_ms;
+                }
+                search_points
+            })
             .collect();
 
         let request = &SearchBatchPointsInternal {
@@ -371,11 +489,67 @@ impl ShardOperation for RemoteShard {
             .await?
             .into_inner();
 
-        let result: Result<Vec<Vec<ScoredPoint>>, Status> = search_batch_response
+        let results: Result<Vec<Vec<ScoredPoint>>, Status> = search_batch_response
             .result
             .into_iter()
             .map(|scored| scored.result.into_iter().map(|s| s.try_into()).collect())
             .collect();
+        let result = results
+            .map(|results| BatchSearchResult {
+                results,
+                truncated: search_batch_response.truncated,
+                explanation: search_batch_response
+                    .explanation
+                    .map(Into::into)
+                    .unwrap_or_default(),
+            })
+            .map_err(|e| e.into());
+        if result.is_ok() {
+            timer.set_success(true);
+        }
+        result
+    }
+
+    async fn discover(
+        &self,
+        request: Arc<ShardDiscoverRequest>,
+        _search_runtime_handle: &Handle,
+    ) -> CollectionResult<Vec<ScoredPoint>> {
+        let mut timer = ScopeDurationMeasurer::new(&self.telemetry_search_durations);
+        timer.set_success(false);
+
+        let request = &DiscoverPointsInternal {
+            collection_name: self.collection_id.clone(),
+            shard_id: self.id,
+            vector_name: Some(request.vector_name.clone()),
+            target: request.query.target.clone(),
+            context: request
+                .query
+                .pairs
+                .iter()
+                .map(|pair| ContextPairInternal {
+                    positive: pair.positive.clone(),
+                    negative: pair.negative.clone(),
+                })
+                .collect(),
+            filter: request.filter.clone().map(|f| f.into()),
+            params: request.params.map(|sp| sp.into()),
+            limit: request.limit as u64,
+            with_payload: request.with_payload.clone().map(|wp| wp.into()),
+            with_vectors: Some(request.with_vector.clone().into()),
+        };
+        let discover_response = self
+            .with_points_client(|mut client| async move {
+                client.discover(tonic::Request::new(request.clone())).await
+            })
+            .await?
+            .into_inner();
+
+        let result: Result<Vec<ScoredPoint>, Status> = discover_response
+            .result
+            .into_iter()
+            .map(|scored| scored.try_into())
+            .collect();
         let result = result.map_err(|e| e.into());
         if result.is_ok() {
             timer.set_success(true);
@@ -410,6 +584,27 @@ impl ShardOperation for RemoteShard {
         )
     }
 
+    async fn facet(&self, request: Arc<FacetRequest>) -> CollectionResult<FacetResponse> {
+        let facet_points = FacetPoints {
+            collection_name: self.collection_id.clone(),
+            key: request.key.clone(),
+            filter: request.filter.clone().map(|f| f.into()),
+            limit: Some(request.limit as u64),
+        };
+
+        let request = &FacetPointsInternal {
+            facet_points: Some(facet_points),
+            shard_id: self.id,
+        };
+        let facet_response = self
+            .with_points_client(|mut client| async move {
+                client.facet(tonic::Request::new(request.clone())).await
+            })
+            .await?
+            .into_inner();
+        Ok(facet_response.try_into()?)
+    }
+
     async fn retrieve(
         &self,
         request: Arc<PointRequest>,
@@ -441,4 +636,33 @@ impl ShardOperation for RemoteShard {
             .collect();
         result.map_err(|e| e.into())
     }
+
+    async fn point_digests(
+        &self,
+        from: Option<ExtendedPointId>,
+        to: Option<ExtendedPointId>,
+        limit: Option<usize>,
+    ) -> CollectionResult<Vec<PointDigest>> {
+        let request = &PointDigestsInternal {
+            collection_name: self.collection_id.clone(),
+            shard_id: self.id,
+            from: from.map(Into::into),
+            to: to.map(Into::into),
+            limit: limit.map(|limit| limit as u64),
+        };
+        let response = self
+            .with_points_client(|mut client| async move {
+                client
+                    .point_digests(tonic::Request::new(request.clone()))
+                    .await
+            })
+            .await?
+            .into_inner();
+        let digests: Result<Vec<PointDigest>, Status> = response
+            .digests
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect();
+        digests.map_err(|e| e.into())
+    }
 }
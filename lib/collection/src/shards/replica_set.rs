@@ -4,6 +4,7 @@ use std::future::Future;
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use futures::future::{join, join_all};
 use futures::stream::FuturesUnordered;
@@ -11,30 +12,40 @@ use futures::StreamExt;
 use itertools::Itertools;
 use rand::seq::SliceRandom;
 use schemars::JsonSchema;
+use segment::common::encryption::StorageCipher;
 use segment::types::{
-    ExtendedPointId, Filter, PointIdType, ScoredPoint, WithPayload, WithPayloadInterface,
-    WithVector,
+    BatchSearchResult, ExtendedPointId, Filter, OrderBy, PointIdType, ScoredPoint, SeqNumberType,
+    WithPayload, WithPayloadInterface, WithVector,
 };
 use serde::{Deserialize, Serialize};
 use tokio::runtime::Handle;
-use tokio::sync::RwLock;
+use tokio::sync::{mpsc, Mutex, RwLock};
 
-use super::local_shard::LocalShard;
+use super::local_shard::{LocalShard, OnShardEvent};
 use super::remote_shard::RemoteShard;
 use super::{create_shard_dir, CollectionId};
+use crate::common::optimization_scheduler::OptimizationScheduler;
 use crate::config::CollectionConfig;
+use crate::operations::changes::PointsChanges;
+use crate::operations::point_ops::{
+    PointOperations, PointStruct, PointSyncOperation, WriteOrdering,
+};
+use crate::operations::snapshot_ops::SegmentVersions;
 use crate::operations::types::{
-    CollectionError, CollectionInfo, CollectionResult, CountRequest, CountResult, PointRequest,
-    Record, SearchRequestBatch, UpdateResult,
+    CollectionError, CollectionInfo, CollectionResult, CollectionVerificationReport, CountRequest,
+    CountResult, FacetRequest, FacetResponse, PointRequest, Record, ReplicaOperationId,
+    ReplicaSyncReport, Sample, SearchRequestBatch, ShardDiscoverRequest, UpdateResult,
 };
-use crate::operations::CollectionUpdateOperations;
+use crate::operations::{CollectionUpdateOperations, OperationWithId};
 use crate::save_on_disk::SaveOnDisk;
 use crate::shards::channel_service::ChannelService;
 use crate::shards::forward_proxy_shard::ForwardProxyShard;
+use crate::shards::peer_stats::ReadLatencyGuard;
+use crate::shards::replica_sync::{count_diverged_points, PointDigest, SYNC_BATCH_SIZE};
 use crate::shards::shard::Shard::{ForwardProxy, Local};
 use crate::shards::shard::{PeerId, Shard, ShardId};
 use crate::shards::shard_config::ShardConfig;
-use crate::shards::shard_trait::ShardOperation;
+use crate::shards::shard_trait::{ShardOperation, ShardOperationSS};
 use crate::shards::telemetry::ReplicaSetTelemetry;
 
 pub type OnPeerFailure = Arc<dyn Fn(PeerId, ShardId) + Send + Sync>;
@@ -44,6 +55,13 @@ const READ_REMOTE_REPLICAS: u32 = 2;
 
 const REPLICA_STATE_FILE: &str = "replica_state.json";
 
+/// Number of updates which may be queued for a single remote listener
+const LISTENER_QUEUE_SIZE: usize = 1024;
+
+/// How long an update waits for a place in a full listener queue.
+/// A listener which doesn't catch up in time is reported as failed and resynced with a shard transfer.
+const LISTENER_QUEUE_TIMEOUT: Duration = Duration::from_secs(1);
+
 /// State of the single shard within a replica set.
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Default, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum ReplicaState {
@@ -54,6 +72,22 @@ pub enum ReplicaState {
     Dead,
     // The shard is partially loaded and is currently receiving data from other shards
     Partial,
+    // Up to date, serves reads, but receives updates asynchronously:
+    // it is never a write target, so it doesn't slow down writes or affect write consistency
+    Listener,
+}
+
+impl ReplicaState {
+    /// Check whether the replica can be switched into `state` directly, by a bare state change.
+    /// Only active replicas can be turned into listeners. A listener is promoted back
+    /// with a shard transfer (`Partial`, then `Active`), which brings it up to date first.
+    /// Other states are managed by shard transfers and failure recovery.
+    pub fn is_switchable_to(self, state: ReplicaState) -> bool {
+        matches!(
+            (self, state),
+            (ReplicaState::Active, ReplicaState::Listener)
+        )
+    }
 }
 
 /// Represents a change in replica set, due to scaling of `replication_factor`
@@ -88,6 +122,11 @@ pub struct ShardReplicaSet {
     channel_service: ChannelService,
     collection_id: CollectionId,
     collection_config: Arc<RwLock<CollectionConfig>>,
+    pub(crate) optimization_scheduler: OptimizationScheduler,
+    pub(crate) storage_cipher: Option<StorageCipher>,
+    pub(crate) on_shard_event: OnShardEvent,
+    /// Ordered update queues of the remote listeners, `None` if the listener has fallen behind
+    listener_queues: Mutex<HashMap<PeerId, Option<mpsc::Sender<OperationWithId>>>>,
 }
 
 impl ShardReplicaSet {
@@ -153,6 +192,9 @@ impl ShardReplicaSet {
         collection_path: &Path,
         shared_config: Arc<RwLock<CollectionConfig>>,
         channel_service: ChannelService,
+        optimization_scheduler: OptimizationScheduler,
+        storage_cipher: Option<StorageCipher>,
+        on_shard_event: OnShardEvent,
     ) -> CollectionResult<Self> {
         let shard_path = create_shard_dir(collection_path, shard_id).await?;
         let local = if local {
@@ -161,6 +203,9 @@ impl ShardReplicaSet {
                 collection_id.clone(),
                 &shard_path,
                 shared_config.clone(),
+                optimization_scheduler.clone(),
+                storage_cipher.clone(),
+                on_shard_event.clone(),
             )
             .await?;
             Some(Local(shard))
@@ -204,6 +249,10 @@ impl ShardReplicaSet {
             channel_service,
             collection_id,
             collection_config: shared_config,
+            optimization_scheduler,
+            storage_cipher,
+            on_shard_event,
+            listener_queues: Default::default(),
         })
     }
 
@@ -294,6 +343,9 @@ impl ShardReplicaSet {
                         self.collection_id.clone(),
                         &self.shard_path,
                         self.collection_config.clone(),
+                        self.optimization_scheduler.clone(),
+                        self.storage_cipher.clone(),
+                        self.on_shard_event.clone(),
                     )
                     .await?,
                 ))
@@ -315,6 +367,7 @@ impl ShardReplicaSet {
     ///
     /// WARN: This method intended to be used only on the initial start of the node.
     /// It does not implement any logic to recover from a failure. Will panic if there is a failure.
+    #[allow(clippy::too_many_arguments)]
     pub async fn load(
         shard_id: ShardId,
         collection_id: CollectionId,
@@ -323,6 +376,9 @@ impl ShardReplicaSet {
         channel_service: ChannelService,
         on_peer_failure: OnPeerFailure,
         this_peer_id: PeerId,
+        optimization_scheduler: OptimizationScheduler,
+        storage_cipher: Option<StorageCipher>,
+        on_shard_event: OnShardEvent,
     ) -> Self {
         let replica_state: SaveOnDisk<ReplicaSetState> =
             SaveOnDisk::load_or_init(shard_path.join(REPLICA_STATE_FILE)).unwrap();
@@ -355,6 +411,9 @@ impl ShardReplicaSet {
                 collection_id.clone(),
                 shard_path,
                 shared_config.clone(),
+                optimization_scheduler.clone(),
+                storage_cipher.clone(),
+                on_shard_event.clone(),
             )
             .await;
             Some(Local(shard))
@@ -374,6 +433,10 @@ impl ShardReplicaSet {
             channel_service,
             collection_id,
             collection_config: shared_config,
+            optimization_scheduler,
+            storage_cipher,
+            on_shard_event,
+            listener_queues: Default::default(),
         }
     }
 
@@ -426,14 +489,16 @@ impl ShardReplicaSet {
                     self.collection_id.clone(),
                     &self.shard_path,
                     self.collection_config.clone(),
+                    self.optimization_scheduler.clone(),
+                    self.storage_cipher.clone(),
+                    self.on_shard_event.clone(),
                 )
                 .await?;
                 match state {
-                    ReplicaState::Active => {
+                    ReplicaState::Active | ReplicaState::Listener => {
                         // No way we can provide up-to-date replica right away at this point,
                         // so we report a failure to consensus
-                        self.set_local(local_shard, Some(ReplicaState::Active))
-                            .await?;
+                        self.set_local(local_shard, Some(state)).await?;
                         self.notify_peer_failure(peer_id);
                     }
                     ReplicaState::Dead => {
@@ -467,13 +532,19 @@ impl ShardReplicaSet {
         self.replica_state.read().peers.get(peer_id) == Some(&ReplicaState::Active)
     }
 
+    /// Check whether a peer is registered as `listener`.
+    pub fn peer_is_listener(&self, peer_id: &PeerId) -> bool {
+        self.replica_state.read().peers.get(peer_id) == Some(&ReplicaState::Listener)
+    }
+
     pub fn peer_state(&self, peer_id: &PeerId) -> Option<ReplicaState> {
         self.replica_state.read().peers.get(peer_id).copied()
     }
 
     /// Execute read op. on replica set:
-    /// 1 - Prefer local replica
+    /// 1 - Prefer local replica, active or listener
     /// 2 - Otherwise uses `read_fan_out_ratio` to compute list of active remote shards.
+    ///     Remote shards with the lowest recent latency and error rate are preferred.
     /// 3 - Fallbacks to all remaining shards if the optimisations fails.
     /// It does not report failing peer_ids to the consensus.
     pub async fn execute_read_operation<'a, F, Fut, Res>(
@@ -487,9 +558,10 @@ impl ShardReplicaSet {
         Fut: Future<Output = CollectionResult<Res>>,
     {
         let mut local_result = None;
-        // 1 - prefer the local shard if it is active
+        // 1 - prefer the local shard if it is active or a listener
         if let Some(local) = local {
-            if self.peer_is_active(&self.this_peer_id()) {
+            let this_peer_id = self.this_peer_id();
+            if self.peer_is_active(&this_peer_id) || self.peer_is_listener(&this_peer_id) {
                 let read_operation_res = read_operation(local.get()).await;
                 match read_operation_res {
                     Ok(_) => return read_operation_res,
@@ -527,6 +599,7 @@ impl ShardReplicaSet {
         }
 
         // Shuffle the list of active remote shards to avoid biasing the first ones
+        // among the replicas with equal stats
         active_remote_shards.shuffle(&mut rand::thread_rng());
 
         let fan_out_selection = cmp::min(
@@ -534,9 +607,30 @@ impl ShardReplicaSet {
             self.read_remote_replicas as usize,
         );
 
+        let peer_stats = &self.channel_service.peer_stats;
+        peer_stats.order_peers(&mut active_remote_shards, fan_out_selection, |remote| {
+            remote.peer_id
+        });
+
+        let measured_read = |remote: &'a RemoteShard| {
+            let latency_guard = ReadLatencyGuard::new(peer_stats, remote.peer_id);
+            let fut = read_operation(remote);
+            async move {
+                let result = fut.await;
+                match &result {
+                    Err(
+                        CollectionError::ServiceError { .. } | CollectionError::Cancelled { .. },
+                    ) => latency_guard.failure(),
+                    // Validation errors are also responses of the peer
+                    _ => latency_guard.success(),
+                }
+                result
+            }
+        };
+
         let mut futures = FuturesUnordered::new();
         for remote in &active_remote_shards[0..fan_out_selection] {
-            let fut = read_operation(*remote);
+            let fut = measured_read(*remote);
             futures.push(fut);
         }
 
@@ -564,7 +658,7 @@ impl ShardReplicaSet {
         // 3 - fallback to remaining remote shards as last chance
         let mut futures = FuturesUnordered::new();
         for remote in &active_remote_shards[fan_out_selection..] {
-            let fut = read_operation(*remote);
+            let fut = measured_read(*remote);
             futures.push(fut);
         }
 
@@ -589,6 +683,44 @@ impl ShardReplicaSet {
         captured_error.expect("at this point `captured_error` must be defined by construction")
     }
 
+    /// Read vectors of the local replica into the page cache, if there is one
+    pub async fn populate_local(&self) -> CollectionResult<()> {
+        let read_local = self.local.read().await;
+        if let Some(shard) = &*read_local {
+            shard.populate().await
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Verify data of the local replica, if there is one
+    pub async fn verify_local(&self) -> CollectionResult<CollectionVerificationReport> {
+        let read_local = self.local.read().await;
+        if let Some(shard) = &*read_local {
+            shard.verify(self.shard_id).await
+        } else {
+            Ok(CollectionVerificationReport::default())
+        }
+    }
+
+    /// Number of updates, waiting in the queue of the local replica. 0 if there is no local replica
+    pub async fn update_queue_length_local(&self) -> usize {
+        let read_local = self.local.read().await;
+        read_local
+            .as_ref()
+            .map_or(0, |shard| shard.update_queue_length())
+    }
+
+    /// Schedule a forced optimization cycle of the local replica, if there is one
+    pub async fn force_optimization_local(&self) -> CollectionResult<usize> {
+        let read_local = self.local.read().await;
+        if let Some(shard) = &*read_local {
+            shard.force_optimization().await
+        } else {
+            Ok(0)
+        }
+    }
+
     pub(crate) async fn on_optimizer_config_update(&self) -> CollectionResult<()> {
         let read_local = self.local.read().await;
         if let Some(shard) = &*read_local {
@@ -634,19 +766,26 @@ impl ShardReplicaSet {
         Ok(())
     }
 
-    pub async fn create_snapshot(&self, target_path: &Path) -> CollectionResult<()> {
+    /// Snapshot of the local replica, segments of the same version in `base` are not included.
+    /// Returns versions of the segments of the local replica, empty if there is none.
+    pub async fn create_snapshot(
+        &self,
+        target_path: &Path,
+        base: &SegmentVersions,
+    ) -> CollectionResult<SegmentVersions> {
         let local_read = self.local.read().await;
 
-        if let Some(local) = &*local_read {
-            local.create_snapshot(target_path).await?
-        }
+        let versions = match &*local_read {
+            Some(local) => local.create_snapshot(target_path, base).await?,
+            None => SegmentVersions::default(),
+        };
 
         self.replica_state
             .save_to(target_path.join(REPLICA_STATE_FILE))?;
 
         let shard_config = ShardConfig::new_replica_set();
         shard_config.save(target_path)?;
-        Ok(())
+        Ok(versions)
     }
 
     pub async fn proxify_local(&self, remote_shard: RemoteShard) -> CollectionResult<()> {
@@ -727,7 +866,7 @@ impl ShardReplicaSet {
     /// Update local shard if any without forwarding to remote shards
     pub async fn update_local(
         &self,
-        operation: CollectionUpdateOperations,
+        operation: OperationWithId,
         wait: bool,
     ) -> CollectionResult<Option<UpdateResult>> {
         if let Some(local_shard) = &*self.local.read().await {
@@ -738,6 +877,9 @@ impl ShardReplicaSet {
                 Some(ReplicaState::Partial) => {
                     Ok(Some(local_shard.get().update(operation, wait).await?))
                 }
+                Some(ReplicaState::Listener) => {
+                    Ok(Some(local_shard.get().update(operation, wait).await?))
+                }
                 Some(ReplicaState::Dead) | None => Ok(None),
             }
         } else {
@@ -775,9 +917,296 @@ impl ShardReplicaSet {
         }
     }
 
+    /// Digests of the points of the local replica regardless of its state, `None` if there is no local replica
+    pub async fn point_digests_local(
+        &self,
+        from: Option<PointIdType>,
+        to: Option<PointIdType>,
+        limit: Option<usize>,
+    ) -> CollectionResult<Option<Vec<PointDigest>>> {
+        let local = self.local.read().await;
+        match &*local {
+            None => Ok(None),
+            Some(shard) => Ok(Some(shard.get().point_digests(from, to, limit).await?)),
+        }
+    }
+
+    /// Whether this peer holds the leading replica of the shard, see [`Self::sync_replicas`]
+    pub fn is_leading_replica(&self) -> bool {
+        self.highest_alive_replica_peer_id() == Some(self.this_peer_id())
+    }
+
+    /// Compare the active replicas with the leading one and re-sync the points, which are missing
+    /// or different on some replica, e.g. after a write, which failed on a part of the replicas.
+    ///
+    /// The leading replica is the one, which applies writes with `medium` ordering,
+    /// so the replicas converge to the state, confirmed by the ordered writes.
+    /// Points, updated during the comparison, might be re-synced needlessly or stay diverged
+    /// until the next comparison.
+    pub async fn sync_replicas(&self) -> CollectionResult<ReplicaSyncReport> {
+        let mut report = ReplicaSyncReport::default();
+        let leader_peer = match self.highest_alive_replica_peer_id() {
+            Some(leader_peer) => leader_peer,
+            None => return Ok(report),
+        };
+        let active_replicas = self
+            .peers()
+            .values()
+            .filter(|state| **state == ReplicaState::Active)
+            .count();
+        if active_replicas < 2 {
+            return Ok(report);
+        }
+        report.shards_checked = 1;
+
+        let mut offset = None;
+        loop {
+            // Locks are released between the batches, so transfers are not blocked for long
+            let (points_checked, points_repaired, next_offset) =
+                self.sync_replicas_batch(leader_peer, offset).await?;
+            report.points_checked += points_checked;
+            report.points_repaired += points_repaired;
+            match next_offset {
+                None => break,
+                Some(_) => offset = next_offset,
+            }
+        }
+        Ok(report)
+    }
+
+    /// Compare and re-sync the points with ids starting from `offset`.
+    /// Returns the numbers of the checked and the repaired points and the offset of the next batch
+    async fn sync_replicas_batch(
+        &self,
+        leader_peer: PeerId,
+        offset: Option<PointIdType>,
+    ) -> CollectionResult<(usize, usize, Option<PointIdType>)> {
+        let local = self.local.read().await;
+        let remotes = self.remotes.read().await;
+
+        let mut replicas: Vec<(PeerId, &ShardOperationSS)> = Vec::new();
+        if let Some(local) = &*local {
+            if self.peer_is_active(&self.this_peer_id()) {
+                replicas.push((self.this_peer_id(), local.get()));
+            }
+        }
+        for remote in remotes.iter() {
+            if self.peer_is_active(&remote.peer_id) {
+                replicas.push((remote.peer_id, remote));
+            }
+        }
+        let (leaders, followers): (Vec<_>, Vec<_>) = replicas
+            .into_iter()
+            .partition(|(peer_id, _)| *peer_id == leader_peer);
+        let leader = match leaders.into_iter().next() {
+            Some((_, leader)) => leader,
+            None => return Ok((0, 0, None)),
+        };
+
+        let limit = SYNC_BATCH_SIZE + 1;
+        let mut source_digests = leader.point_digests(offset, None, Some(limit)).await?;
+        let next_offset = if source_digests.len() < limit {
+            // This was the last batch
+            None
+        } else {
+            // Remove extra point, it would be a first point of the next batch
+            source_digests.pop().map(|digest| digest.id)
+        };
+
+        let mut diverged_followers = Vec::new();
+        let mut points_repaired = 0;
+        for (peer_id, follower) in followers {
+            let target_digests = follower.point_digests(offset, next_offset, None).await?;
+            let diverged_points = count_diverged_points(&source_digests, &target_digests);
+            if diverged_points > 0 {
+                log::info!(
+                    "Re-syncing {} points of shard {} on peer {} from peer {}",
+                    diverged_points,
+                    self.shard_id,
+                    peer_id,
+                    leader_peer
+                );
+                points_repaired += diverged_points;
+                diverged_followers.push(follower);
+            }
+        }
+
+        if !diverged_followers.is_empty() {
+            let request = Arc::new(PointRequest {
+                ids: source_digests.iter().map(|digest| digest.id).collect(),
+                with_payload: Some(WithPayloadInterface::Bool(true)),
+                with_vector: true.into(),
+            });
+            let points: Result<Vec<PointStruct>, String> = leader
+                .retrieve(request, &true.into(), &true.into())
+                .await?
+                .into_iter()
+                .map(|point| point.try_into())
+                .collect();
+            // Sync removes the points of the range, which are missing in the leading replica
+            let operation = CollectionUpdateOperations::PointOperation(
+                PointOperations::SyncPoints(PointSyncOperation {
+                    from_id: offset,
+                    to_id: next_offset,
+                    points: points?,
+                }),
+            );
+            for follower in diverged_followers {
+                follower.update(operation.clone().into(), true).await?;
+            }
+        }
+
+        Ok((source_digests.len(), points_repaired, next_offset))
+    }
+
+    /// This peer is the leader of the replicas for the `medium` write ordering
+    pub fn is_alive_leader(&self) -> bool {
+        self.highest_alive_replica_peer_id() == Some(self.this_peer_id())
+    }
+
+    /// Highest peer id among the replicas, which are currently active
+    fn highest_alive_replica_peer_id(&self) -> Option<PeerId> {
+        let read_lock = self.replica_state.read();
+        read_lock
+            .peers
+            .iter()
+            .filter(|(_, state)| **state == ReplicaState::Active)
+            .map(|(peer_id, _)| *peer_id)
+            .max()
+    }
+
+    /// Highest peer id among all the replicas, regardless of their state.
+    /// Listeners are never write targets, so they are skipped.
+    /// Only changes when the replica set itself is changed through consensus.
+    fn highest_replica_peer_id(&self) -> Option<PeerId> {
+        let read_lock = self.replica_state.read();
+        read_lock
+            .peers
+            .iter()
+            .filter(|(_, state)| **state != ReplicaState::Listener)
+            .map(|(peer_id, _)| *peer_id)
+            .max()
+    }
+
+    /// Send the update to the remote listeners in background.
+    /// Listeners don't affect the result of the update, failed ones are reported to consensus.
+    ///
+    /// Each listener has its own queue, so it receives the updates one by one in the original order.
+    /// A full queue slows down the updates, a listener which doesn't catch up within
+    /// `LISTENER_QUEUE_TIMEOUT` is reported as failed, so that it is resynced with a shard transfer.
+    async fn update_remote_listeners(&self, operation: &OperationWithId, remotes: &[RemoteShard]) {
+        let mut listener_queues = self.listener_queues.lock().await;
+        listener_queues.retain(|peer_id, _| self.peer_is_listener(peer_id));
+
+        for remote in remotes {
+            if !self.peer_is_listener(&remote.peer_id) {
+                continue;
+            }
+            let queue = listener_queues
+                .entry(remote.peer_id)
+                .or_insert_with(|| Some(self.spawn_listener_worker(remote.clone())));
+            // Listener has fallen behind and awaits resync
+            let sent = match queue {
+                Some(sender) => {
+                    sender
+                        .send_timeout(operation.clone(), LISTENER_QUEUE_TIMEOUT)
+                        .await
+                }
+                None => continue,
+            };
+            match sent {
+                Ok(()) => {}
+                Err(mpsc::error::SendTimeoutError::Timeout(_)) => {
+                    log::warn!(
+                        "Listener of shard {} on peer {} has fallen too far behind",
+                        self.shard_id,
+                        remote.peer_id
+                    );
+                    *queue = None;
+                    self.notify_peer_failure(remote.peer_id);
+                }
+                // Worker has stopped on a failed update, which it has reported already
+                Err(mpsc::error::SendTimeoutError::Closed(_)) => *queue = None,
+            }
+        }
+    }
+
+    /// Spawn a worker, which applies the queued updates to the remote listener one at a time
+    fn spawn_listener_worker(&self, remote: RemoteShard) -> mpsc::Sender<OperationWithId> {
+        let (sender, mut receiver) = mpsc::channel::<OperationWithId>(LISTENER_QUEUE_SIZE);
+        let shard_id = self.shard_id;
+        let notify_peer_failure = self.notify_peer_failure_cb.clone();
+        tokio::spawn(async move {
+            while let Some(operation) = receiver.recv().await {
+                if let Err(err) = remote.update(operation, false).await {
+                    // Stop accepting updates, listener will be resynced as a whole
+                    receiver.close();
+                    log::warn!(
+                        "Failed to update listener of shard {} on peer {}, error: {:?}",
+                        shard_id,
+                        remote.peer_id,
+                        err
+                    );
+                    notify_peer_failure(remote.peer_id, shard_id);
+                    break;
+                }
+            }
+        });
+        sender
+    }
+
+    /// Update the replica set respecting the requested write ordering.
+    ///
+    /// `weak` ordering sends the operation to all active replicas directly.
+    /// `medium` and `strong` orderings route the operation through the leader replica,
+    /// which then applies it to all active replicas.
+    pub async fn update_with_consistency(
+        &self,
+        operation: OperationWithId,
+        wait: bool,
+        ordering: WriteOrdering,
+    ) -> CollectionResult<UpdateResult> {
+        let leader_peer = match ordering {
+            WriteOrdering::Weak => return self.update(operation, wait).await,
+            WriteOrdering::Medium => self.highest_alive_replica_peer_id(),
+            WriteOrdering::Strong => self.highest_replica_peer_id(),
+        };
+
+        let leader_peer = leader_peer.ok_or_else(|| {
+            CollectionError::service_error(format!(
+                "Cannot find leader for shard {} with {:?} write ordering",
+                self.shard_id, ordering
+            ))
+        })?;
+
+        if !self.peer_is_active(&leader_peer) {
+            return Err(CollectionError::service_error(format!(
+                "Leader {} of shard {} is not active, cannot apply update with {:?} write ordering",
+                leader_peer, self.shard_id, ordering
+            )));
+        }
+
+        if leader_peer == self.this_peer_id() {
+            return self.update(operation, wait).await;
+        }
+
+        let remotes = self.remotes.read().await;
+        let leader = remotes
+            .iter()
+            .find(|remote| remote.peer_id == leader_peer)
+            .ok_or_else(|| {
+                CollectionError::service_error(format!(
+                    "Leader {} of shard {} is not found among the remote replicas",
+                    leader_peer, self.shard_id
+                ))
+            })?;
+
+        leader.forward_update(operation, wait, ordering).await
+    }
+
     pub async fn update(
         &self,
-        operation: CollectionUpdateOperations,
+        operation: OperationWithId,
         wait: bool,
     ) -> CollectionResult<UpdateResult> {
         let all_res: Vec<Result<_, _>> = {
@@ -801,6 +1230,8 @@ impl ShardReplicaSet {
                 )));
             }
 
+            self.update_remote_listeners(&operation, &remotes).await;
+
             let mut remote_futures = Vec::new();
             for remote in active_remote_shards {
                 let op = operation.clone();
@@ -808,6 +1239,7 @@ impl ShardReplicaSet {
                     remote
                         .update(op, wait)
                         .await
+                        .map(|res| (remote.peer_id, res))
                         .map_err(|err| (remote.peer_id, err))
                 });
             }
@@ -819,6 +1251,7 @@ impl ShardReplicaSet {
                             .get()
                             .update(operation.clone(), wait)
                             .await
+                            .map(|res| (self.this_peer_id(), res))
                             .map_err(|err| (self.this_peer_id(), err))
                     };
                     let remote_updates = join_all(remote_futures);
@@ -829,6 +1262,22 @@ impl ShardReplicaSet {
                     remote_res.push(local_res);
                     remote_res
                 }
+                Some(local) if self.peer_is_listener(&self.this_peer_id()) => {
+                    // Local listener is not waited for and doesn't count for write consistency
+                    let local_update = async move {
+                        if let Err(err) = local.get().update(operation.clone(), false).await {
+                            log::warn!(
+                                "Failed to update local listener of shard {}, error: {:?}",
+                                self.shard_id,
+                                err
+                            );
+                            self.notify_peer_failure(self.this_peer_id());
+                        }
+                    };
+                    let remote_updates = join_all(remote_futures);
+                    let (remote_res, ()) = join(remote_updates, local_update).await;
+                    remote_res
+                }
                 _ => join_all(remote_futures).await,
             }
         };
@@ -870,10 +1319,20 @@ impl ShardReplicaSet {
             }
         }
         // there are enough successes, return the first one
-        let res = successes
+        // with the numbers of the operation in the WAL of all updated replicas
+        let replica_operation_ids = successes
+            .iter()
+            .map(|(peer_id, res)| ReplicaOperationId {
+                shard_id: self.shard_id,
+                peer_id: *peer_id,
+                operation_id: res.operation_id,
+            })
+            .collect();
+        let (_peer_id, mut res) = successes
             .into_iter()
             .next()
             .expect("successes is not empty");
+        res.replica_operation_ids = replica_operation_ids;
         Ok(res)
     }
 
@@ -885,12 +1344,24 @@ impl ShardReplicaSet {
         with_payload_interface: &WithPayloadInterface,
         with_vector: &WithVector,
         filter: Option<&Filter>,
+        order_by: Option<&OrderBy>,
+        sample: Option<Sample>,
     ) -> CollectionResult<Vec<Record>> {
         let local = self.local.read().await;
         let remotes = self.remotes.read().await;
 
         self.execute_read_operation(
-            |shard| shard.scroll_by(offset, limit, with_payload_interface, with_vector, filter),
+            |shard| {
+                shard.scroll_by(
+                    offset,
+                    limit,
+                    with_payload_interface,
+                    with_vector,
+                    filter,
+                    order_by,
+                    sample,
+                )
+            },
             &local,
             &remotes,
         )
@@ -909,18 +1380,112 @@ impl ShardReplicaSet {
         &self,
         request: Arc<SearchRequestBatch>,
         search_runtime_handle: &Handle,
-    ) -> CollectionResult<Vec<Vec<ScoredPoint>>> {
+        deadline: Option<Instant>,
+    ) -> CollectionResult<BatchSearchResult> {
+        let local = self.local.read().await;
+        let remotes = self.remotes.read().await;
+
+        self.execute_read_operation(
+            |shard| shard.search(request.clone(), search_runtime_handle, deadline),
+            &local,
+            &remotes,
+        )
+        .await
+    }
+
+    pub async fn discover(
+        &self,
+        request: Arc<ShardDiscoverRequest>,
+        search_runtime_handle: &Handle,
+    ) -> CollectionResult<Vec<ScoredPoint>> {
         let local = self.local.read().await;
         let remotes = self.remotes.read().await;
 
         self.execute_read_operation(
-            |shard| shard.search(request.clone(), search_runtime_handle),
+            |shard| shard.discover(request.clone(), search_runtime_handle),
             &local,
             &remotes,
         )
         .await
     }
 
+    /// Read operations from the WAL of the local replica, `None` if there is no local replica
+    pub async fn read_wal_local(
+        &self,
+        from: SeqNumberType,
+        limit: usize,
+    ) -> CollectionResult<Option<Vec<(SeqNumberType, CollectionUpdateOperations)>>> {
+        let local = self.local.read().await;
+        match &*local {
+            None => Ok(None),
+            Some(shard) => Ok(Some(shard.read_wal(from, limit)?)),
+        }
+    }
+
+    /// Build segments of the local replica from the points directly, see [`LocalShard::bulk_build`].
+    /// Built segments are not replicated, so only the shard with a single local replica could be
+    /// bulk built. Returns the sequential number, the points are written with.
+    pub async fn bulk_build_local(
+        &self,
+        points: Vec<PointStruct>,
+    ) -> CollectionResult<SeqNumberType> {
+        let local = self.local.read().await;
+        match &*local {
+            Some(Local(local_shard))
+                if self.peers().len() == 1 && self.peer_is_active(&self.this_peer_id()) =>
+            {
+                local_shard.bulk_build(points).await
+            }
+            _ => Err(CollectionError::bad_request(format!(
+                "Shard {} can't be bulk built, it should have a single active local replica",
+                self.shard_id
+            ))),
+        }
+    }
+
+    /// Wait for the operation to be applied to the local replica, `None` if there is no local replica
+    pub async fn wait_for_operation_local(
+        &self,
+        operation_id: SeqNumberType,
+        timeout: Duration,
+    ) -> CollectionResult<Option<bool>> {
+        let local = self.local.read().await;
+        match &*local {
+            None => Ok(None),
+            Some(shard) => Ok(Some(shard.wait_for_operation(operation_id, timeout).await?)),
+        }
+    }
+
+    /// Read changes of the points from the WAL of the local replica.
+    /// If there is no local replica, changes are read from the active remote replica
+    /// with the lowest peer id, so consecutive reads are served by the same WAL.
+    pub async fn read_points_changes(
+        &self,
+        from: SeqNumberType,
+        limit: usize,
+    ) -> CollectionResult<PointsChanges> {
+        if let Some(operations) = self.read_wal_local(from, limit).await? {
+            return Ok(PointsChanges::from_operations(
+                self.shard_id,
+                from,
+                operations,
+            ));
+        }
+        let remotes = self.remotes.read().await;
+        let remote = remotes
+            .iter()
+            .filter(|remote| self.peer_is_active(&remote.peer_id))
+            .min_by_key(|remote| remote.peer_id)
+            .ok_or_else(|| {
+                CollectionError::service_error(format!(
+                    "The replica set for shard {} on peer {} has no active replica",
+                    self.shard_id,
+                    self.this_peer_id()
+                ))
+            })?;
+        remote.read_changes(from, limit).await
+    }
+
     pub async fn count_local(
         &self,
         request: Arc<CountRequest>,
@@ -932,6 +1497,25 @@ impl ShardReplicaSet {
         }
     }
 
+    /// Retrieve points from the local replica regardless of its state, `None` if there is no local replica
+    pub async fn retrieve_local(
+        &self,
+        request: Arc<PointRequest>,
+        with_payload: &WithPayload,
+        with_vector: &WithVector,
+    ) -> CollectionResult<Option<Vec<Record>>> {
+        let local = self.local.read().await;
+        match &*local {
+            None => Ok(None),
+            Some(shard) => Ok(Some(
+                shard
+                    .get()
+                    .retrieve(request, with_payload, with_vector)
+                    .await?,
+            )),
+        }
+    }
+
     pub async fn count(&self, request: Arc<CountRequest>) -> CollectionResult<CountResult> {
         let local = self.local.read().await;
         let remotes = self.remotes.read().await;
@@ -940,6 +1524,14 @@ impl ShardReplicaSet {
             .await
     }
 
+    pub async fn facet(&self, request: Arc<FacetRequest>) -> CollectionResult<FacetResponse> {
+        let local = self.local.read().await;
+        let remotes = self.remotes.read().await;
+
+        self.execute_read_operation(|shard| shard.facet(request.clone()), &local, &remotes)
+            .await
+    }
+
     pub async fn retrieve(
         &self,
         request: Arc<PointRequest>,
@@ -957,3 +1549,329 @@ impl ShardReplicaSet {
         .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::num::{NonZeroU32, NonZeroU64};
+
+    use segment::types::Distance;
+    use tempfile::Builder;
+
+    use super::*;
+    use crate::common::optimization_scheduler::OptimizationScheduler;
+    use crate::config::{CollectionParams, VectorParams, VectorsConfig, WalConfig};
+    use crate::operations::point_ops::Batch;
+    use crate::optimizers_builder::OptimizersConfig;
+
+    const THIS_PEER_ID: PeerId = 1;
+    const REMOTE_PEER_ID: PeerId = 2;
+    const OTHER_REMOTE_PEER_ID: PeerId = 3;
+
+    fn test_config() -> CollectionConfig {
+        CollectionConfig {
+            params: CollectionParams {
+                vectors: VectorsConfig::Single(VectorParams {
+                    size: NonZeroU64::new(4).unwrap(),
+                    distance: Distance::Dot,
+                    hnsw_config: None,
+                    quantization_config: None,
+                    on_disk: None,
+                    datatype: None,
+                    multivector_config: None,
+                }),
+                shard_number: NonZeroU32::new(1).unwrap(),
+                replication_factor: NonZeroU32::new(3).unwrap(),
+                write_consistency_factor: NonZeroU32::new(1).unwrap(),
+                on_disk_payload: false,
+                default_vector: None,
+            },
+            optimizer_config: OptimizersConfig {
+                deleted_threshold: 0.9,
+                vacuum_min_vector_number: 1000,
+                default_segment_number: 2,
+                max_segment_size: None,
+                memmap_threshold: None,
+                indexing_threshold: 50_000,
+                flush_interval_sec: 30,
+                max_optimization_threads: 2,
+            },
+            wal_config: WalConfig {
+                wal_capacity_mb: 1,
+                wal_segments_ahead: 0,
+                encrypted: false,
+                operation_id_window_sec: 600,
+            },
+            hnsw_config: Default::default(),
+            payload_field_types: None,
+            auto_payload_index: None,
+            ttl: None,
+        }
+    }
+
+    /// Replica set with two remote replicas and a local one, if `local` is set.
+    /// Failed peers are collected
+    async fn test_replica_set(
+        collection_path: &Path,
+        local: bool,
+        failed_peers: Arc<parking_lot::Mutex<Vec<PeerId>>>,
+    ) -> ShardReplicaSet {
+        ShardReplicaSet::build(
+            0,
+            "test".to_string(),
+            THIS_PEER_ID,
+            local,
+            HashSet::from([REMOTE_PEER_ID, OTHER_REMOTE_PEER_ID]),
+            Arc::new(move |peer_id, _shard_id| failed_peers.lock().push(peer_id)),
+            collection_path,
+            Arc::new(RwLock::new(test_config())),
+            ChannelService::default(),
+            OptimizationScheduler::new(Handle::current(), Handle::current(), 0),
+            None,
+            Arc::new(|_shard_id, _event| {}),
+        )
+        .await
+        .unwrap()
+    }
+
+    fn insert_points() -> OperationWithId {
+        CollectionUpdateOperations::PointOperation(
+            Batch {
+                ids: vec![0.into(), 1.into(), 2.into()],
+                vectors: vec![
+                    vec![1.0, 0.0, 1.0, 1.0],
+                    vec![1.0, 0.0, 1.0, 0.0],
+                    vec![1.0, 1.0, 1.0, 1.0],
+                ]
+                .into(),
+                payloads: None,
+            }
+            .into(),
+        )
+        .into()
+    }
+
+    async fn count_local(replica_set: &ShardReplicaSet) -> usize {
+        let request = Arc::new(CountRequest {
+            filter: None,
+            exact: true,
+        });
+        replica_set
+            .count_local(request)
+            .await
+            .unwrap()
+            .unwrap()
+            .count
+    }
+
+    #[test]
+    fn test_replica_state_transitions() {
+        assert!(ReplicaState::Active.is_switchable_to(ReplicaState::Listener));
+
+        // Listeners are promoted back with a shard transfer only
+        assert!(!ReplicaState::Listener.is_switchable_to(ReplicaState::Active));
+        assert!(!ReplicaState::Listener.is_switchable_to(ReplicaState::Partial));
+
+        for state in [
+            ReplicaState::Active,
+            ReplicaState::Dead,
+            ReplicaState::Partial,
+            ReplicaState::Listener,
+        ] {
+            assert!(!ReplicaState::Dead.is_switchable_to(state));
+            assert!(!ReplicaState::Partial.is_switchable_to(state));
+            assert!(!state.is_switchable_to(ReplicaState::Dead));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_highest_replica_peer_id_skips_listeners() {
+        let collection_dir = Builder::new().prefix("test_collection").tempdir().unwrap();
+        let replica_set = test_replica_set(collection_dir.path(), true, Default::default()).await;
+
+        replica_set
+            .set_replica_state(&THIS_PEER_ID, ReplicaState::Active)
+            .unwrap();
+        replica_set
+            .set_replica_state(&REMOTE_PEER_ID, ReplicaState::Listener)
+            .unwrap();
+        replica_set
+            .set_replica_state(&OTHER_REMOTE_PEER_ID, ReplicaState::Listener)
+            .unwrap();
+
+        assert_eq!(replica_set.highest_replica_peer_id(), Some(THIS_PEER_ID));
+        assert_eq!(
+            replica_set.highest_alive_replica_peer_id(),
+            Some(THIS_PEER_ID)
+        );
+        assert!(replica_set.is_alive_leader());
+
+        // Dead replica is still a write target, unlike a listener
+        replica_set
+            .set_replica_state(&REMOTE_PEER_ID, ReplicaState::Dead)
+            .unwrap();
+        assert_eq!(replica_set.highest_replica_peer_id(), Some(REMOTE_PEER_ID));
+        assert_eq!(
+            replica_set.highest_alive_replica_peer_id(),
+            Some(THIS_PEER_ID)
+        );
+
+        replica_set
+            .set_replica_state(&OTHER_REMOTE_PEER_ID, ReplicaState::Active)
+            .unwrap();
+        assert_eq!(
+            replica_set.highest_replica_peer_id(),
+            Some(OTHER_REMOTE_PEER_ID)
+        );
+        assert!(!replica_set.is_alive_leader());
+    }
+
+    #[tokio::test]
+    async fn test_remote_listener_write_routing() {
+        let collection_dir = Builder::new().prefix("test_collection").tempdir().unwrap();
+        let failed_peers: Arc<parking_lot::Mutex<Vec<PeerId>>> = Default::default();
+        let replica_set = test_replica_set(collection_dir.path(), true, failed_peers.clone()).await;
+
+        replica_set
+            .set_replica_state(&THIS_PEER_ID, ReplicaState::Active)
+            .unwrap();
+        replica_set
+            .set_replica_state(&REMOTE_PEER_ID, ReplicaState::Listener)
+            .unwrap();
+        replica_set
+            .set_replica_state(&OTHER_REMOTE_PEER_ID, ReplicaState::Dead)
+            .unwrap();
+
+        // Unreachable listener doesn't affect the result of the update
+        replica_set.update(insert_points(), true).await.unwrap();
+        assert_eq!(count_local(&replica_set).await, 3);
+
+        // But it is queued for, and reported as failed in background
+        assert!(replica_set
+            .listener_queues
+            .lock()
+            .await
+            .contains_key(&REMOTE_PEER_ID));
+        let start = Instant::now();
+        while failed_peers.lock().is_empty() && start.elapsed() < Duration::from_secs(5) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(*failed_peers.lock(), vec![REMOTE_PEER_ID]);
+
+        // Stopped queue is not used anymore and the failure is not reported twice
+        replica_set.update(insert_points(), true).await.unwrap();
+        assert!(matches!(
+            replica_set
+                .listener_queues
+                .lock()
+                .await
+                .get(&REMOTE_PEER_ID),
+            Some(None)
+        ));
+        assert_eq!(*failed_peers.lock(), vec![REMOTE_PEER_ID]);
+
+        // Queue is dropped as soon as the peer is not a listener anymore
+        replica_set
+            .set_replica_state(&REMOTE_PEER_ID, ReplicaState::Dead)
+            .unwrap();
+        replica_set.update(insert_points(), true).await.unwrap();
+        assert!(replica_set.listener_queues.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_local_listener_write_routing() {
+        let collection_dir = Builder::new().prefix("test_collection").tempdir().unwrap();
+        let failed_peers: Arc<parking_lot::Mutex<Vec<PeerId>>> = Default::default();
+        let replica_set = test_replica_set(collection_dir.path(), true, failed_peers.clone()).await;
+
+        replica_set
+            .set_replica_state(&THIS_PEER_ID, ReplicaState::Listener)
+            .unwrap();
+        replica_set
+            .set_replica_state(&REMOTE_PEER_ID, ReplicaState::Dead)
+            .unwrap();
+        replica_set
+            .set_replica_state(&OTHER_REMOTE_PEER_ID, ReplicaState::Dead)
+            .unwrap();
+
+        // Listener alone is not a write target
+        let result = replica_set.update(insert_points(), true).await;
+        assert!(result.is_err());
+        assert_eq!(count_local(&replica_set).await, 0);
+
+        // Listener doesn't count for write consistency, so the failed active replica fails the update
+        replica_set
+            .set_replica_state(&REMOTE_PEER_ID, ReplicaState::Active)
+            .unwrap();
+        let result = replica_set.update(insert_points(), true).await;
+        assert!(result.is_err());
+        assert!(failed_peers.lock().is_empty());
+
+        // But the local listener still receives the update
+        let start = Instant::now();
+        while count_local(&replica_set).await < 3 && start.elapsed() < Duration::from_secs(5) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(count_local(&replica_set).await, 3);
+    }
+
+    #[tokio::test]
+    async fn test_read_points_changes_local() {
+        let collection_dir = Builder::new().prefix("test_collection").tempdir().unwrap();
+        let replica_set = test_replica_set(collection_dir.path(), true, Default::default()).await;
+
+        replica_set
+            .set_replica_state(&THIS_PEER_ID, ReplicaState::Active)
+            .unwrap();
+        replica_set.update(insert_points(), true).await.unwrap();
+
+        // Local replica is read, even if the remote ones are active
+        replica_set
+            .set_replica_state(&REMOTE_PEER_ID, ReplicaState::Active)
+            .unwrap();
+        let changes = replica_set.read_points_changes(0, 10).await.unwrap();
+        assert_eq!(changes.changes.len(), 1);
+        assert_eq!(changes.changes[0].shard_id, 0);
+        assert_eq!(changes.changes[0].event.name(), "upsert");
+        assert_eq!(changes.next_op_num, changes.changes[0].op_num + 1);
+
+        // Reading from the end gives no changes and the same position
+        let next_op_num = changes.next_op_num;
+        let changes = replica_set
+            .read_points_changes(next_op_num, 10)
+            .await
+            .unwrap();
+        assert!(changes.changes.is_empty());
+        assert_eq!(changes.next_op_num, next_op_num);
+    }
+
+    #[tokio::test]
+    async fn test_read_points_changes_remote() {
+        let collection_dir = Builder::new().prefix("test_collection").tempdir().unwrap();
+        let replica_set = test_replica_set(collection_dir.path(), false, Default::default()).await;
+
+        // Changes can't be read without an active replica
+        let err = replica_set.read_points_changes(0, 10).await.unwrap_err();
+        assert!(err.to_string().contains("has no active replica"));
+
+        // Active remote with the lowest peer id is read. Addresses of the peers are not known,
+        // so the request fails, naming the chosen peer
+        replica_set
+            .set_replica_state(&REMOTE_PEER_ID, ReplicaState::Active)
+            .unwrap();
+        replica_set
+            .set_replica_state(&OTHER_REMOTE_PEER_ID, ReplicaState::Active)
+            .unwrap();
+        let err = replica_set.read_points_changes(0, 10).await.unwrap_err();
+        assert!(err
+            .to_string()
+            .contains(&format!("no address found for peer {REMOTE_PEER_ID}")));
+
+        replica_set
+            .set_replica_state(&REMOTE_PEER_ID, ReplicaState::Dead)
+            .unwrap();
+        let err = replica_set.read_points_changes(0, 10).await.unwrap_err();
+        assert!(err
+            .to_string()
+            .contains(&format!("no address found for peer {OTHER_REMOTE_PEER_ID}")));
+    }
+}
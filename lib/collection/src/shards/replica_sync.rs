@@ -0,0 +1,140 @@
+use std::hash::Hasher;
+
+use itertools::{EitherOrBoth, Itertools};
+use seahash::SeaHasher;
+use segment::data_types::vectors::{VectorStruct, VectorType};
+use segment::types::PointIdType;
+
+use crate::operations::types::Record;
+
+/// Number of points, which are compared between the replicas at once
+pub const SYNC_BATCH_SIZE: usize = 1000;
+
+/// Digest of the vectors and the payload of a single point
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PointDigest {
+    pub id: PointIdType,
+    pub digest: u64,
+}
+
+impl PointDigest {
+    /// Digest does not depend on the peer, the point is stored on, so digests of
+    /// the same point could be compared between the replicas.
+    /// Record should contain the payload and all the vectors of the point.
+    pub fn from_record(record: &Record) -> Self {
+        let mut hasher = SeaHasher::new();
+        match &record.vector {
+            None => {}
+            Some(VectorStruct::Single(vector)) => hash_vector(&mut hasher, vector),
+            Some(VectorStruct::Multi(vectors)) => {
+                // Order of the named vectors in the map is random
+                for (name, vector) in vectors.iter().sorted_by_key(|(name, _)| *name) {
+                    hasher.write(name.as_bytes());
+                    hash_vector(&mut hasher, vector);
+                }
+            }
+        }
+        if let Some(payload) = &record.payload {
+            // Keys of the payload are ordered, so the serialized payload is stable
+            if let Ok(serialized) = serde_json::to_vec(payload) {
+                hasher.write(&serialized);
+            }
+        }
+        Self {
+            id: record.id,
+            digest: hasher.finish(),
+        }
+    }
+}
+
+fn hash_vector(hasher: &mut SeaHasher, vector: &VectorType) {
+    hasher.write_usize(vector.len());
+    for value in vector {
+        hasher.write_u32(value.to_bits());
+    }
+}
+
+/// Number of points, which are missing on one of the replicas or have different digests.
+/// Both lists should be ordered by id.
+pub fn count_diverged_points(source: &[PointDigest], target: &[PointDigest]) -> usize {
+    source
+        .iter()
+        .merge_join_by(target, |source, target| source.id.cmp(&target.id))
+        .filter(|pair| match pair {
+            EitherOrBoth::Both(source, target) => source.digest != target.digest,
+            EitherOrBoth::Left(_) | EitherOrBoth::Right(_) => true,
+        })
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::json;
+
+    use super::*;
+
+    fn record(id: u64, vector: VectorStruct, payload: serde_json::Value) -> Record {
+        Record {
+            id: id.into(),
+            payload: Some(payload.into()),
+            vector: Some(vector),
+            order_value: None,
+        }
+    }
+
+    #[test]
+    fn test_point_digest() {
+        let digest = |id, vector, payload| PointDigest::from_record(&record(id, vector, payload));
+
+        let base = digest(1, vec![1.0, 2.0].into(), json!({"a": 1, "b": "x"}));
+        assert_eq!(
+            base,
+            digest(1, vec![1.0, 2.0].into(), json!({"b": "x", "a": 1}))
+        );
+        assert_ne!(
+            base.digest,
+            digest(1, vec![1.0, 2.5].into(), json!({"a": 1, "b": "x"})).digest
+        );
+        assert_ne!(
+            base.digest,
+            digest(1, vec![1.0, 2.0].into(), json!({"a": 2, "b": "x"})).digest
+        );
+
+        let named = |names: &[&str]| {
+            let vectors: HashMap<_, _> = names
+                .iter()
+                .enumerate()
+                .map(|(i, name)| (name.to_string(), vec![i as f32; 4]))
+                .collect();
+            digest(2, VectorStruct::Multi(vectors), json!({}))
+        };
+        assert_eq!(
+            named(&["image", "text", "audio"]),
+            named(&["image", "text", "audio"])
+        );
+        assert_ne!(named(&["image", "text"]), named(&["text", "image"]));
+    }
+
+    #[test]
+    fn test_count_diverged_points() {
+        let digests = |points: &[(u64, u64)]| {
+            points
+                .iter()
+                .map(|(id, digest)| PointDigest {
+                    id: (*id).into(),
+                    digest: *digest,
+                })
+                .collect_vec()
+        };
+
+        let source = digests(&[(1, 10), (2, 20), (3, 30), (5, 50)]);
+        assert_eq!(count_diverged_points(&source, &source), 0);
+        // Point 2 differs, 3 is missing and 4 is extra
+        let target = digests(&[(1, 10), (2, 21), (4, 40), (5, 50)]);
+        assert_eq!(count_diverged_points(&source, &target), 3);
+        assert_eq!(count_diverged_points(&source, &[]), 4);
+        assert_eq!(count_diverged_points(&[], &target), 4);
+    }
+}
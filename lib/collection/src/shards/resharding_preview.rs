@@ -0,0 +1,271 @@
+use std::collections::BTreeMap;
+
+use segment::types::PointIdType;
+
+use crate::config::VectorsConfig;
+use crate::hash_ring::HashRing;
+use crate::operations::types::{ReshardingPreview, ShardPreview};
+use crate::shards::shard::ShardId;
+use crate::shards::HASH_RING_SHARD_SCALE;
+
+/// Size of all vectors of a single point, as they are stored in vector storage
+pub fn point_vectors_size_bytes(vectors: &VectorsConfig) -> usize {
+    vectors
+        .params_iter()
+        .map(|(_, params)| {
+            params.size.get() as usize * params.datatype.unwrap_or_default().element_size()
+        })
+        .sum()
+}
+
+/// Estimated values of a single shard. Fractional, because sampled points are scaled
+/// to the total number of points in the shard.
+#[derive(Default)]
+struct ShardEstimate {
+    points_count: f64,
+    size_bytes: f64,
+    incoming_points_count: f64,
+    outgoing_points_count: f64,
+}
+
+/// Accumulates sampled points of the collection and estimates their distribution
+/// over the shards of the new hash ring.
+pub struct ReshardingPreviewBuilder {
+    current_shard_number: u32,
+    shard_number: u32,
+    ring: HashRing<ShardId>,
+    /// Contains both new shards and current ones, which are going to be removed
+    shards: BTreeMap<ShardId, ShardEstimate>,
+    transfer_points_count: f64,
+    transfer_size_bytes: f64,
+}
+
+impl ReshardingPreviewBuilder {
+    pub fn new(current_shard_number: u32, shard_number: u32) -> Self {
+        let mut ring = HashRing::fair(HASH_RING_SHARD_SCALE);
+        for shard_id in 0..shard_number {
+            ring.add(shard_id);
+        }
+        let shards = (0..current_shard_number.max(shard_number))
+            .map(|shard_id| (shard_id, ShardEstimate::default()))
+            .collect();
+        Self {
+            current_shard_number,
+            shard_number,
+            ring,
+            shards,
+            transfer_points_count: 0.0,
+            transfer_size_bytes: 0.0,
+        }
+    }
+
+    /// Account a sample of points, which are currently stored in `current_shard_id`.
+    ///
+    /// Points are hashed into shards regardless of their ids order, so any subset of points
+    /// is distributed over the new shards the same way as the whole shard.
+    /// Each sampled point stands for `points_count / sample.len()` points of the shard.
+    pub fn add_sample(
+        &mut self,
+        current_shard_id: ShardId,
+        points_count: usize,
+        sample: &[(PointIdType, usize)],
+    ) {
+        if sample.is_empty() {
+            return;
+        }
+        let weight = points_count as f64 / sample.len() as f64;
+        for (point_id, size_bytes) in sample {
+            self.add_point(*point_id, current_shard_id, *size_bytes, weight);
+        }
+    }
+
+    fn add_point(
+        &mut self,
+        point_id: PointIdType,
+        current_shard_id: ShardId,
+        size_bytes: usize,
+        weight: f64,
+    ) {
+        let shard_id = *self
+            .ring
+            .get(&point_id)
+            .expect("Hash ring is guaranteed to be non-empty");
+        let size_bytes = size_bytes as f64 * weight;
+
+        let shard = self.shards.entry(shard_id).or_default();
+        shard.points_count += weight;
+        shard.size_bytes += size_bytes;
+
+        if shard_id != current_shard_id {
+            shard.incoming_points_count += weight;
+            self.shards
+                .entry(current_shard_id)
+                .or_default()
+                .outgoing_points_count += weight;
+            self.transfer_points_count += weight;
+            self.transfer_size_bytes += size_bytes;
+        }
+    }
+
+    pub fn build(self) -> ReshardingPreview {
+        let round = |value: f64| value.round() as usize;
+        ReshardingPreview {
+            current_shard_number: self.current_shard_number,
+            shard_number: self.shard_number,
+            shards: self
+                .shards
+                .into_iter()
+                .map(|(shard_id, estimate)| ShardPreview {
+                    shard_id,
+                    points_count: round(estimate.points_count),
+                    estimated_size_bytes: round(estimate.size_bytes),
+                    incoming_points_count: round(estimate.incoming_points_count),
+                    outgoing_points_count: round(estimate.outgoing_points_count),
+                })
+                .collect(),
+            transfer_points_count: round(self.transfer_points_count),
+            transfer_size_bytes: round(self.transfer_size_bytes),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::num::NonZeroU64;
+
+    use segment::types::{Distance, VectorStorageDatatype};
+
+    use super::*;
+    use crate::config::VectorParams;
+
+    fn current_shard(point_id: PointIdType, shard_number: u32) -> ShardId {
+        let mut ring = HashRing::fair(HASH_RING_SHARD_SCALE);
+        for shard_id in 0..shard_number {
+            ring.add(shard_id);
+        }
+        *ring.get(&point_id).unwrap()
+    }
+
+    /// Points 0..count, grouped by their current shard
+    fn current_shards(
+        count: u64,
+        shard_number: u32,
+    ) -> BTreeMap<ShardId, Vec<(PointIdType, usize)>> {
+        let mut shards: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        for id in 0..count {
+            let point_id = PointIdType::from(id);
+            shards
+                .entry(current_shard(point_id, shard_number))
+                .or_default()
+                .push((point_id, 10));
+        }
+        shards
+    }
+
+    #[test]
+    fn test_same_shard_number() {
+        let mut builder = ReshardingPreviewBuilder::new(3, 3);
+        for (shard_id, points) in current_shards(100, 3) {
+            builder.add_sample(shard_id, points.len(), &points);
+        }
+        let preview = builder.build();
+
+        assert_eq!(preview.transfer_points_count, 0);
+        assert_eq!(preview.transfer_size_bytes, 0);
+        assert_eq!(preview.shards.len(), 3);
+        assert_eq!(
+            preview
+                .shards
+                .iter()
+                .map(|shard| shard.points_count)
+                .sum::<usize>(),
+            100
+        );
+    }
+
+    #[test]
+    fn test_reduce_shard_number() {
+        let mut builder = ReshardingPreviewBuilder::new(3, 1);
+        for (shard_id, points) in current_shards(100, 3) {
+            builder.add_sample(shard_id, points.len(), &points);
+        }
+        let preview = builder.build();
+
+        // Removed shards are listed without points
+        assert_eq!(preview.shards.len(), 3);
+        assert_eq!(preview.shards[0].points_count, 100);
+        assert_eq!(preview.shards[0].estimated_size_bytes, 1000);
+        assert_eq!(preview.shards[1].points_count, 0);
+        assert_eq!(preview.shards[2].points_count, 0);
+
+        let moved =
+            preview.shards[1].outgoing_points_count + preview.shards[2].outgoing_points_count;
+        assert_eq!(preview.shards[0].incoming_points_count, moved);
+        assert_eq!(preview.transfer_points_count, moved);
+        assert_eq!(preview.transfer_size_bytes, moved * 10);
+    }
+
+    #[test]
+    fn test_sampled_estimation() {
+        let exact = {
+            let mut builder = ReshardingPreviewBuilder::new(2, 5);
+            for (shard_id, points) in current_shards(20_000, 2) {
+                builder.add_sample(shard_id, points.len(), &points);
+            }
+            builder.build()
+        };
+        let sampled = {
+            let mut builder = ReshardingPreviewBuilder::new(2, 5);
+            for (shard_id, points) in current_shards(20_000, 2) {
+                builder.add_sample(shard_id, points.len(), &points[..1000]);
+            }
+            builder.build()
+        };
+
+        // Sampled points are scaled to the whole shard
+        let total = |preview: &ReshardingPreview| {
+            preview
+                .shards
+                .iter()
+                .map(|shard| shard.points_count)
+                .sum::<usize>()
+        };
+        assert!(total(&sampled).abs_diff(20_000) <= 5);
+        for (exact, sampled) in exact.shards.iter().zip(&sampled.shards) {
+            assert!(
+                exact.points_count.abs_diff(sampled.points_count) < exact.points_count / 5 + 100,
+                "{exact:?} {sampled:?}"
+            );
+        }
+        let transfer_error = exact
+            .transfer_points_count
+            .abs_diff(sampled.transfer_points_count);
+        assert!(transfer_error < exact.transfer_points_count / 10);
+    }
+
+    #[test]
+    fn test_point_vectors_size() {
+        let params = |size| VectorParams {
+            size: NonZeroU64::new(size).unwrap(),
+            distance: Distance::Dot,
+            hnsw_config: None,
+            quantization_config: None,
+            on_disk: None,
+            datatype: None,
+            multivector_config: None,
+        };
+        assert_eq!(point_vectors_size_bytes(&params(4).into()), 16);
+        let multi = VectorsConfig::Multi(BTreeMap::from([
+            ("a".to_string(), params(4)),
+            ("b".to_string(), params(8)),
+        ]));
+        assert_eq!(point_vectors_size_bytes(&multi), 48);
+
+        let bytes = VectorParams {
+            datatype: Some(VectorStorageDatatype::Uint8),
+            ..params(4)
+        };
+        assert_eq!(point_vectors_size_bytes(&bytes.into()), 4);
+    }
+}
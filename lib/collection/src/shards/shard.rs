@@ -1,7 +1,12 @@
 use core::marker::{Send, Sync};
 use std::path::Path;
+use std::time::Duration;
 
-use crate::operations::types::CollectionResult;
+use segment::types::SeqNumberType;
+
+use crate::operations::snapshot_ops::SegmentVersions;
+use crate::operations::types::{CollectionResult, CollectionVerificationReport};
+use crate::operations::CollectionUpdateOperations;
 use crate::shards::forward_proxy_shard::ForwardProxyShard;
 use crate::shards::local_shard::LocalShard;
 use crate::shards::proxy_shard::ProxyShard;
@@ -58,11 +63,94 @@ impl Shard {
         telemetry
     }
 
-    pub async fn create_snapshot(&self, target_path: &Path) -> CollectionResult<()> {
+    pub async fn create_snapshot(
+        &self,
+        target_path: &Path,
+        base: &SegmentVersions,
+    ) -> CollectionResult<SegmentVersions> {
+        match self {
+            Shard::Local(local_shard) => local_shard.create_snapshot(target_path, base).await,
+            Shard::Proxy(proxy_shard) => proxy_shard.create_snapshot(target_path, base).await,
+            Shard::ForwardProxy(proxy_shard) => {
+                proxy_shard.create_snapshot(target_path, base).await
+            }
+        }
+    }
+
+    /// Read operations from the WAL of the local shard, wrapped shard is read for proxies
+    pub fn read_wal(
+        &self,
+        from: SeqNumberType,
+        limit: usize,
+    ) -> CollectionResult<Vec<(SeqNumberType, CollectionUpdateOperations)>> {
+        match self {
+            Shard::Local(local_shard) => local_shard.read_wal(from, limit),
+            Shard::Proxy(proxy_shard) => proxy_shard.wrapped_shard.read_wal(from, limit),
+            Shard::ForwardProxy(proxy_shard) => proxy_shard.wrapped_shard.read_wal(from, limit),
+        }
+    }
+
+    /// Wait for the operation to be applied to the local shard, wrapped shard is used for proxies
+    pub async fn wait_for_operation(
+        &self,
+        operation_id: SeqNumberType,
+        timeout: Duration,
+    ) -> CollectionResult<bool> {
+        match self {
+            Shard::Local(local_shard) => {
+                local_shard.wait_for_operation(operation_id, timeout).await
+            }
+            Shard::Proxy(proxy_shard) => {
+                proxy_shard
+                    .wrapped_shard
+                    .wait_for_operation(operation_id, timeout)
+                    .await
+            }
+            Shard::ForwardProxy(proxy_shard) => {
+                proxy_shard
+                    .wrapped_shard
+                    .wait_for_operation(operation_id, timeout)
+                    .await
+            }
+        }
+    }
+
+    /// Read vectors of the local shard into the page cache, wrapped shard is read for proxies
+    pub async fn populate(&self) -> CollectionResult<()> {
+        match self {
+            Shard::Local(local_shard) => local_shard.populate().await,
+            Shard::Proxy(proxy_shard) => proxy_shard.wrapped_shard.populate().await,
+            Shard::ForwardProxy(proxy_shard) => proxy_shard.wrapped_shard.populate().await,
+        }
+    }
+
+    /// Number of updates, waiting in the queue of the local shard, wrapped shard is used for proxies
+    pub fn update_queue_length(&self) -> usize {
+        match self {
+            Shard::Local(local_shard) => local_shard.update_queue_length(),
+            Shard::Proxy(proxy_shard) => proxy_shard.wrapped_shard.update_queue_length(),
+            Shard::ForwardProxy(proxy_shard) => proxy_shard.wrapped_shard.update_queue_length(),
+        }
+    }
+
+    /// Schedule a forced optimization cycle of the local shard.
+    /// Proxies are skipped, their shards are being transferred
+    pub async fn force_optimization(&self) -> CollectionResult<usize> {
+        match self {
+            Shard::Local(local_shard) => local_shard.force_optimization().await,
+            Shard::Proxy(_) | Shard::ForwardProxy(_) => Ok(0),
+        }
+    }
+
+    /// Verify data of the local shard, wrapped shard is verified for proxies
+    pub async fn verify(
+        &self,
+        shard_id: ShardId,
+    ) -> CollectionResult<CollectionVerificationReport> {
         match self {
-            Shard::Local(local_shard) => local_shard.create_snapshot(target_path).await,
-            Shard::Proxy(proxy_shard) => proxy_shard.create_snapshot(target_path).await,
-            Shard::ForwardProxy(proxy_shard) => proxy_shard.create_snapshot(target_path).await,
+            Shard::Local(local_shard) => local_shard.verify(shard_id).await,
+            Shard::Proxy(proxy_shard) => proxy_shard.wrapped_shard.verify(shard_id).await,
+            Shard::ForwardProxy(proxy_shard) => proxy_shard.wrapped_shard.verify(shard_id).await,
         }
     }
 
@@ -4,15 +4,17 @@ use std::sync::Arc;
 
 use futures::stream::FuturesUnordered;
 use futures::StreamExt;
+use segment::common::encryption::StorageCipher;
 use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 
+use crate::common::optimization_scheduler::OptimizationScheduler;
 use crate::config::CollectionConfig;
 use crate::hash_ring::HashRing;
 use crate::operations::types::{CollectionError, CollectionResult};
 use crate::operations::{OperationToShard, SplitByShard};
 use crate::save_on_disk::SaveOnDisk;
 use crate::shards::channel_service::ChannelService;
-use crate::shards::local_shard::LocalShard;
+use crate::shards::local_shard::{LocalShard, OnShardEvent};
 use crate::shards::replica_set::{OnPeerFailure, ReplicaState, ShardReplicaSet};
 use crate::shards::shard::{PeerId, ShardId};
 use crate::shards::shard_config::{ShardConfig, ShardType};
@@ -107,6 +109,16 @@ impl ShardHolder {
         shard_ops
     }
 
+    /// Ids of the shards, which are affected by the operation
+    pub fn affected_shards<O: SplitByShard>(&self, operation: O) -> Vec<ShardId> {
+        match operation.split_by_shard(&self.ring) {
+            OperationToShard::ByShard(by_shard) => {
+                by_shard.into_iter().map(|(shard_id, _)| shard_id).collect()
+            }
+            OperationToShard::ToAll(_) => self.shards.keys().copied().collect(),
+        }
+    }
+
     pub fn register_start_shard_transfer(&self, transfer: ShardTransfer) -> CollectionResult<bool> {
         Ok(self
             .shard_transfers
@@ -169,6 +181,7 @@ impl ShardHolder {
         self.shards.is_empty()
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn load_shards(
         &mut self,
         collection_path: &Path,
@@ -177,6 +190,9 @@ impl ShardHolder {
         channel_service: ChannelService,
         on_peer_failure: OnPeerFailure,
         this_peer_id: PeerId,
+        optimization_scheduler: OptimizationScheduler,
+        storage_cipher: Option<StorageCipher>,
+        on_shard_event: OnShardEvent,
     ) {
         let shard_number = shared_collection_config
             .read()
@@ -197,6 +213,9 @@ impl ShardHolder {
                     channel_service.clone(),
                     on_peer_failure.clone(),
                     this_peer_id,
+                    optimization_scheduler.clone(),
+                    storage_cipher.clone(),
+                    on_shard_event.clone(),
                 )
                 .await;
 
@@ -208,6 +227,9 @@ impl ShardHolder {
                             collection_id.clone(),
                             &path,
                             shared_collection_config.clone(),
+                            optimization_scheduler.clone(),
+                            storage_cipher.clone(),
+                            on_shard_event.clone(),
                         )
                         .await;
                         replica_set
@@ -227,6 +249,9 @@ impl ShardHolder {
                             collection_id.clone(),
                             &path,
                             shared_collection_config.clone(),
+                            optimization_scheduler.clone(),
+                            storage_cipher.clone(),
+                            on_shard_event.clone(),
                         )
                         .await;
 
@@ -1,22 +1,25 @@
 use std::sync::Arc;
+use std::time::Instant;
 
 use async_trait::async_trait;
 use segment::types::{
-    ExtendedPointId, Filter, ScoredPoint, WithPayload, WithPayloadInterface, WithVector,
+    BatchSearchResult, ExtendedPointId, Filter, OrderBy, ScoredPoint, WithPayload,
+    WithPayloadInterface, WithVector,
 };
 use tokio::runtime::Handle;
 
 use crate::operations::types::{
-    CollectionInfo, CollectionResult, CountRequest, CountResult, PointRequest, Record,
-    SearchRequestBatch, UpdateResult,
+    CollectionInfo, CollectionResult, CountRequest, CountResult, FacetRequest, FacetResponse,
+    PointRequest, Record, Sample, SearchRequestBatch, ShardDiscoverRequest, UpdateResult,
 };
-use crate::operations::CollectionUpdateOperations;
+use crate::operations::OperationWithId;
+use crate::shards::replica_sync::PointDigest;
 
 #[async_trait]
 pub trait ShardOperation {
     async fn update(
         &self,
-        operation: CollectionUpdateOperations,
+        operation: OperationWithId,
         wait: bool,
     ) -> CollectionResult<UpdateResult>;
 
@@ -28,24 +31,46 @@ pub trait ShardOperation {
         with_payload_interface: &WithPayloadInterface,
         with_vector: &WithVector,
         filter: Option<&Filter>,
+        order_by: Option<&OrderBy>,
+        sample: Option<Sample>,
     ) -> CollectionResult<Vec<Record>>;
 
     async fn info(&self) -> CollectionResult<CollectionInfo>;
 
+    /// Approximate searches are interrupted at `deadline`, result is marked as truncated then
     async fn search(
         &self,
         request: Arc<SearchRequestBatch>,
         search_runtime_handle: &Handle,
-    ) -> CollectionResult<Vec<Vec<ScoredPoint>>>;
+        deadline: Option<Instant>,
+    ) -> CollectionResult<BatchSearchResult>;
+
+    /// Discovery search, examples of the request are already resolved into vectors
+    async fn discover(
+        &self,
+        request: Arc<ShardDiscoverRequest>,
+        search_runtime_handle: &Handle,
+    ) -> CollectionResult<Vec<ScoredPoint>>;
 
     async fn count(&self, request: Arc<CountRequest>) -> CollectionResult<CountResult>;
 
+    async fn facet(&self, request: Arc<FacetRequest>) -> CollectionResult<FacetResponse>;
+
     async fn retrieve(
         &self,
         request: Arc<PointRequest>,
         with_payload: &WithPayload,
         with_vector: &WithVector,
     ) -> CollectionResult<Vec<Record>>;
+
+    /// Digests of the points with ids in range `[from, to)`, ordered by id.
+    /// Used to find points, which differ between the replicas of the shard.
+    async fn point_digests(
+        &self,
+        from: Option<ExtendedPointId>,
+        to: Option<ExtendedPointId>,
+        limit: Option<usize>,
+    ) -> CollectionResult<Vec<PointDigest>>;
 }
 
 pub type ShardOperationSS = dyn ShardOperation + Send + Sync;
@@ -28,6 +28,19 @@ pub struct LocalShardTelemetry {
     pub variant_name: Option<String>,
     pub segments: Vec<SegmentTelemetry>,
     pub optimizations: OptimizerTelemetry,
+    #[serde(default)]
+    pub update_queue: UpdateQueueTelemetry,
+}
+
+/// Backlog of the updates of the shard, which are accepted but not processed yet
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, Default)]
+pub struct UpdateQueueTelemetry {
+    /// Number of updates, waiting in the queue to be applied to the segments
+    pub queue_length: usize,
+    /// Number of operations, written to the WAL, but not applied to the segments yet
+    pub wal_lag: u64,
+    /// Number of optimizations, which are scheduled or running
+    pub optimizer_backlog: usize,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, Default)]
@@ -62,6 +75,7 @@ impl Anonymize for LocalShardTelemetry {
             variant_name: self.variant_name.clone(),
             segments: self.segments.anonymize(),
             optimizations: self.optimizations.anonymize(),
+            update_queue: self.update_queue.clone(),
         }
     }
 }
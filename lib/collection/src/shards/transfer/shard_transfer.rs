@@ -3,7 +3,7 @@ use std::collections::{HashMap, HashSet};
 use std::future::Future;
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -30,6 +30,21 @@ pub struct ShardTransfer {
     /// If this flag is true, the is a replication related transfer of shard from 1 peer to another
     /// Shard on original peer will not be deleted in this case
     pub sync: bool,
+    /// Method of the transfer, records streaming if not set
+    #[serde(default)]
+    pub method: Option<ShardTransferMethod>,
+    /// Max number of points, sent to the remote peer per second. Not limited if not set
+    #[serde(default)]
+    pub max_points_per_second: Option<usize>,
+}
+
+/// Methods of the shard transfer
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ShardTransferMethod {
+    /// Points are sent to the remote peer in batches, while new updates are forwarded by the proxy
+    #[default]
+    StreamRecords,
 }
 
 /// Unique identifier of a transfer
@@ -56,9 +71,21 @@ impl ShardTransfer {
     }
 }
 
+/// Size of the transferred batches and the min interval between their starts, if throttled.
+/// Throttled transfer sends smaller batches, so the rate is even within a second.
+fn transfer_batch_params(max_points_per_second: Option<usize>) -> (usize, Option<Duration>) {
+    let batch_size = max_points_per_second
+        .map(|rate| rate.clamp(1, TRANSFER_BATCH_SIZE))
+        .unwrap_or(TRANSFER_BATCH_SIZE);
+    let batch_interval = max_points_per_second
+        .map(|rate| Duration::from_secs_f64(batch_size as f64 / rate.max(1) as f64));
+    (batch_size, batch_interval)
+}
+
 async fn transfer_batches(
     shard_holder: Arc<LockedShardHolder>,
     shard_id: ShardId,
+    max_points_per_second: Option<usize>,
     stopped: Arc<AtomicBool>,
 ) -> CollectionResult<()> {
     // Create payload indexes on the remote shard.
@@ -77,6 +104,8 @@ async fn transfer_batches(
         }
     }
 
+    let (batch_size, batch_interval) = transfer_batch_params(max_points_per_second);
+
     // Transfer contents batch by batch
     let initial_offset = None;
     let mut offset = initial_offset;
@@ -86,13 +115,12 @@ async fn transfer_batches(
                 description: "Transfer cancelled".to_string(),
             });
         }
+        let batch_start = Instant::now();
         let shard_holder_guard = shard_holder.read().await;
         let transferring_shard_opt = shard_holder_guard.get_shard(&shard_id);
 
         if let Some(replica_set) = transferring_shard_opt {
-            offset = replica_set
-                .transfer_batch(offset, TRANSFER_BATCH_SIZE)
-                .await?;
+            offset = replica_set.transfer_batch(offset, batch_size).await?;
             if offset.is_none() {
                 // That was the last batch, all look good
                 break;
@@ -105,6 +133,14 @@ async fn transfer_batches(
                 shard_id
             )));
         }
+        drop(shard_holder_guard);
+
+        if let Some(batch_interval) = batch_interval {
+            let elapsed = batch_start.elapsed();
+            if elapsed < batch_interval {
+                sleep(batch_interval - elapsed).await;
+            }
+        }
     }
     Ok(())
 }
@@ -199,6 +235,7 @@ pub async fn transfer_shard(
     shard_id: ShardId,
     collection_id: CollectionId,
     peer_id: PeerId,
+    max_points_per_second: Option<usize>,
     channel_service: ChannelService,
     stopped: Arc<AtomicBool>,
 ) -> CollectionResult<()> {
@@ -224,7 +261,13 @@ pub async fn transfer_shard(
         }
     };
     // Transfer contents batch by batch
-    transfer_batches(shard_holder.clone(), shard_id, stopped.clone()).await
+    transfer_batches(
+        shard_holder.clone(),
+        shard_id,
+        max_points_per_second,
+        stopped.clone(),
+    )
+    .await
 }
 
 /// Confirms that the transfer makes sense with the current state cluster
@@ -233,6 +276,7 @@ pub async fn transfer_shard(
 /// 1. If `from` and `to` exists
 /// 2. If `from` have local shard and it is active
 /// 3. If there is no active transfer from the same shard and peer
+/// 4. If the rate limit is positive
 ///
 /// If validation fails, return `BadRequest` error.
 pub fn validate_transfer(
@@ -281,6 +325,12 @@ pub fn validate_transfer(
         )));
     }
 
+    if transfer.max_points_per_second == Some(0) {
+        return Err(CollectionError::bad_request(
+            "Transfer rate limit must be greater than 0".to_string(),
+        ));
+    }
+
     Ok(())
 }
 
@@ -422,6 +472,7 @@ where
                 transfer.shard_id,
                 collection_id.clone(),
                 transfer.to,
+                transfer.max_points_per_second,
                 channel_service.clone(),
                 stopped.clone(),
             )
@@ -462,3 +513,73 @@ where
         finished
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transfer_batch_params() {
+        assert_eq!(transfer_batch_params(None), (TRANSFER_BATCH_SIZE, None));
+
+        // Rate above the batch size splits a second into several full batches
+        let (batch_size, batch_interval) = transfer_batch_params(Some(1_000));
+        assert_eq!(batch_size, TRANSFER_BATCH_SIZE);
+        assert_eq!(batch_interval, Some(Duration::from_millis(100)));
+
+        let (batch_size, batch_interval) = transfer_batch_params(Some(250));
+        assert_eq!(batch_size, TRANSFER_BATCH_SIZE);
+        assert_eq!(batch_interval, Some(Duration::from_millis(400)));
+
+        // Rate below the batch size is sent as a single batch per second
+        assert_eq!(
+            transfer_batch_params(Some(10)),
+            (10, Some(Duration::from_secs(1)))
+        );
+        assert_eq!(
+            transfer_batch_params(Some(1)),
+            (1, Some(Duration::from_secs(1)))
+        );
+    }
+
+    #[test]
+    fn test_validate_transfer_rate_limit() {
+        let all_peers = HashSet::from([1, 2]);
+        let shard_state = HashMap::from([(1, ReplicaState::Active)]);
+        let current_transfers = HashSet::new();
+
+        let mut transfer = ShardTransfer {
+            shard_id: 0,
+            from: 1,
+            to: 2,
+            sync: false,
+            method: None,
+            max_points_per_second: Some(0),
+        };
+        let result = validate_transfer(
+            &transfer,
+            &all_peers,
+            Some(&shard_state),
+            &current_transfers,
+        );
+        assert!(matches!(result, Err(CollectionError::BadRequest { .. })));
+
+        transfer.max_points_per_second = Some(10);
+        validate_transfer(
+            &transfer,
+            &all_peers,
+            Some(&shard_state),
+            &current_transfers,
+        )
+        .unwrap();
+
+        transfer.max_points_per_second = None;
+        validate_transfer(
+            &transfer,
+            &all_peers,
+            Some(&shard_state),
+            &current_transfers,
+        )
+        .unwrap();
+    }
+}
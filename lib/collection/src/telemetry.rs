@@ -31,6 +31,9 @@ impl Anonymize for CollectionConfig {
             hnsw_config: self.hnsw_config,
             optimizer_config: self.optimizer_config.clone(),
             wal_config: self.wal_config.clone(),
+            payload_field_types: self.payload_field_types.clone(),
+            auto_payload_index: self.auto_payload_index,
+            ttl: self.ttl.clone(),
         }
     }
 }
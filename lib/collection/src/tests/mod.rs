@@ -7,14 +7,16 @@ use futures::future::join_all;
 use itertools::Itertools;
 use parking_lot::RwLock;
 use tempfile::Builder;
+use tokio::runtime::Handle;
 use tokio::time::{sleep, Instant};
 
-use crate::collection::Collection;
+use crate::collection::{default_changes_shard, Collection};
 use crate::collection_manager::fixtures::{
     get_indexing_optimizer, get_merge_optimizer, random_segment,
 };
 use crate::collection_manager::holders::segment_holder::{LockedSegment, SegmentHolder, SegmentId};
-use crate::update_handler::{Optimizer, UpdateHandler};
+use crate::common::optimization_scheduler::OptimizationScheduler;
+use crate::update_handler::{Optimizer, QueuedSegmentIds, UpdateHandler};
 
 #[tokio::test]
 async fn test_optimization_process() {
@@ -45,14 +47,43 @@ async fn test_optimization_process() {
     let optimizers = Arc::new(vec![merge_optimizer, indexing_optimizer]);
 
     let segments: Arc<RwLock<_>> = Arc::new(RwLock::new(holder));
-    let handles = UpdateHandler::launch_optimization(optimizers.clone(), segments.clone(), |_| {});
+    let scheduler = OptimizationScheduler::new(Handle::current(), Handle::current(), 0);
+    let queued_segment_ids: QueuedSegmentIds = Default::default();
+    let handles = UpdateHandler::launch_optimization(
+        optimizers.clone(),
+        segments.clone(),
+        &scheduler,
+        &queued_segment_ids,
+        |_| {},
+        |_| {},
+    );
 
     assert_eq!(handles.len(), 2);
 
+    // Segments of scheduled optimizations are not scheduled again
+    let handles_queued = UpdateHandler::launch_optimization(
+        optimizers.clone(),
+        segments.clone(),
+        &scheduler,
+        &queued_segment_ids,
+        |_| {},
+        |_| {},
+    );
+
+    assert_eq!(handles_queued.len(), 0);
+
     let join_res = join_all(handles.into_iter().map(|x| x.join_handle).collect_vec()).await;
 
-    let handles_2 =
-        UpdateHandler::launch_optimization(optimizers.clone(), segments.clone(), |_| {});
+    assert!(queued_segment_ids.lock().is_empty());
+
+    let handles_2 = UpdateHandler::launch_optimization(
+        optimizers.clone(),
+        segments.clone(),
+        &scheduler,
+        &queued_segment_ids,
+        |_| {},
+        |_| {},
+    );
 
     assert_eq!(handles_2.len(), 0);
 
@@ -90,7 +121,14 @@ async fn test_cancel_optimization() {
     let now = Instant::now();
 
     let segments: Arc<RwLock<_>> = Arc::new(RwLock::new(holder));
-    let handles = UpdateHandler::launch_optimization(optimizers.clone(), segments.clone(), |_| {});
+    let handles = UpdateHandler::launch_optimization(
+        optimizers.clone(),
+        segments.clone(),
+        &OptimizationScheduler::new(Handle::current(), Handle::current(), 0),
+        &Default::default(),
+        |_| {},
+        |_| {},
+    );
 
     sleep(Duration::from_millis(100)).await;
 
@@ -140,3 +178,21 @@ fn check_version_upgrade() {
         &"0.4.2".parse().unwrap()
     ));
 }
+
+#[test]
+fn test_default_changes_shard() {
+    // The only local shard is read, even if there are remote shards
+    assert_eq!(default_changes_shard(&[1], &[0, 1, 2]).unwrap(), 1);
+    // The only shard is read from a remote replica, if there is no local one
+    assert_eq!(default_changes_shard(&[], &[2]).unwrap(), 2);
+
+    // Shard can't be chosen among several local or several remote shards
+    let err = default_changes_shard(&[0, 1], &[0, 1, 2]).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Collection has 3 shards, 2 of them on this peer"));
+    let err = default_changes_shard(&[], &[0, 1]).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Collection has 2 shards, 0 of them on this peer"));
+}
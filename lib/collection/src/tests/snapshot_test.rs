@@ -4,12 +4,15 @@ use std::sync::Arc;
 
 use segment::types::Distance;
 use tempfile::Builder;
+use tokio::runtime::Handle;
 
 use crate::collection::{Collection, RequestShardTransfer};
+use crate::common::optimization_scheduler::OptimizationScheduler;
 use crate::config::{CollectionConfig, CollectionParams, VectorParams, VectorsConfig, WalConfig};
 use crate::optimizers_builder::OptimizersConfig;
 use crate::shards::channel_service::ChannelService;
 use crate::shards::collection_shard_distribution::CollectionShardDistribution;
+use crate::shards::local_shard::OnShardEvent;
 use crate::shards::replica_set::OnPeerFailure;
 
 const TEST_OPTIMIZERS_CONFIG: OptimizersConfig = OptimizersConfig {
@@ -31,22 +34,34 @@ pub fn dummy_request_shard_transfer() -> RequestShardTransfer {
     Arc::new(move |_transfer| {})
 }
 
+pub fn dummy_on_shard_event() -> OnShardEvent {
+    Arc::new(move |_shard_id, _event| {})
+}
+
 #[tokio::test]
 async fn test_snapshot_collection() {
     let wal_config = WalConfig {
         wal_capacity_mb: 1,
         wal_segments_ahead: 0,
+        encrypted: false,
+        operation_id_window_sec: 600,
     };
 
     let collection_params = CollectionParams {
         vectors: VectorsConfig::Single(VectorParams {
             size: NonZeroU64::new(4).unwrap(),
             distance: Distance::Dot,
+            hnsw_config: None,
+            quantization_config: None,
+            on_disk: None,
+            datatype: None,
+            multivector_config: None,
         }),
         shard_number: NonZeroU32::new(4).unwrap(),
         replication_factor: NonZeroU32::new(3).unwrap(),
         write_consistency_factor: NonZeroU32::new(2).unwrap(),
         on_disk_payload: false,
+        default_vector: None,
     };
 
     let config = CollectionConfig {
@@ -54,6 +69,9 @@ async fn test_snapshot_collection() {
         optimizer_config: TEST_OPTIMIZERS_CONFIG.clone(),
         wal_config,
         hnsw_config: Default::default(),
+        payload_field_types: None,
+        auto_payload_index: None,
+        ttl: None,
     };
 
     let snapshots_path = Builder::new().prefix("test_snapshots").tempdir().unwrap();
@@ -78,8 +96,11 @@ async fn test_snapshot_collection() {
         &config,
         CollectionShardDistribution { shards },
         ChannelService::default(),
+        OptimizationScheduler::new(Handle::current(), Handle::current(), 0),
+        None,
         dummy_on_replica_failure(),
         dummy_request_shard_transfer(),
+        dummy_on_shard_event(),
     )
     .await
     .unwrap();
@@ -87,7 +108,7 @@ async fn test_snapshot_collection() {
     let snapshots_tmp_dir = collection_dir.path().join("snapshots_tmp");
     std::fs::create_dir_all(&snapshots_tmp_dir).unwrap();
     let snapshot_description = collection
-        .create_snapshot(&snapshots_tmp_dir)
+        .create_snapshot(&snapshots_tmp_dir, 2)
         .await
         .unwrap();
 
@@ -103,8 +124,11 @@ async fn test_snapshot_collection() {
         recover_dir.path(),
         snapshots_path.path(),
         ChannelService::default(),
+        OptimizationScheduler::new(Handle::current(), Handle::current(), 0),
+        None,
         dummy_on_replica_failure(),
         dummy_request_shard_transfer(),
+        dummy_on_shard_event(),
     )
     .await;
 
@@ -1,5 +1,5 @@
 use std::cmp::min;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 use itertools::Itertools;
@@ -7,24 +7,81 @@ use log::{debug, error, info, trace, warn};
 use parking_lot::Mutex as ParkingMutex;
 use segment::entry::entry_point::OperationResult;
 use segment::types::SeqNumberType;
-use tokio::runtime::Handle;
 use tokio::sync::mpsc::{self, Receiver, Sender};
 use tokio::sync::{oneshot, Mutex as TokioMutex};
 use tokio::task::JoinHandle;
-use tokio::time::Duration;
+use tokio::time::{Duration, Instant};
 
 use crate::collection_manager::collection_updater::CollectionUpdater;
-use crate::collection_manager::holders::segment_holder::LockedSegmentHolder;
+use crate::collection_manager::holders::segment_holder::{LockedSegmentHolder, SegmentId};
 use crate::collection_manager::optimizers::segment_optimizer::SegmentOptimizer;
-use crate::common::stoppable_task::{spawn_stoppable, StoppableTaskHandle};
+use crate::common::optimization_scheduler::OptimizationScheduler;
+use crate::common::stoppable_task::StoppableTaskHandle;
 use crate::operations::types::{CollectionError, CollectionResult};
-use crate::operations::CollectionUpdateOperations;
+use crate::operations::{CollectionUpdateOperations, OperationWithId};
+use crate::shards::dead_letter::{DeadLetter, QuarantinedOperation};
+use crate::shards::local_shard::{OnShardEvent, ShardEvent};
+use crate::shards::shard::ShardId;
 use crate::wal::SerdeWal;
 
 pub const UPDATE_QUEUE_SIZE: usize = 100;
 
+/// Number of failed attempts to re-apply an operation, after which it is quarantined
+pub const MAX_RECOVERY_ATTEMPTS: usize = 5;
+
+/// Min interval between attempts to re-apply failed operations
+pub const RECOVERY_ATTEMPT_INTERVAL: Duration = Duration::from_secs(1);
+
 pub type Optimizer = dyn SegmentOptimizer + Sync + Send;
 
+/// Segments of optimizations, which are scheduled but not finished yet
+pub type QueuedSegmentIds = Arc<ParkingMutex<HashSet<SegmentId>>>;
+
+/// Marks segments as queued for optimization, until dropped
+struct QueuedSegments {
+    queued: QueuedSegmentIds,
+    ids: Vec<SegmentId>,
+}
+
+impl QueuedSegments {
+    fn new(queued: QueuedSegmentIds, ids: Vec<SegmentId>) -> Self {
+        queued.lock().extend(ids.iter().copied());
+        Self { queued, ids }
+    }
+}
+
+impl Drop for QueuedSegments {
+    fn drop(&mut self) {
+        let mut queued = self.queued.lock();
+        for id in &self.ids {
+            queued.remove(id);
+        }
+    }
+}
+
+/// Failed attempts to re-apply operations, used to detect operations which can't be applied
+#[derive(Default)]
+struct RecoveryAttempts {
+    attempts: HashMap<SeqNumberType, usize>,
+    last_attempt: Option<Instant>,
+}
+
+impl RecoveryAttempts {
+    /// Attempts are spaced in time, so transient failures are not counted many times in a row
+    fn is_due(&self) -> bool {
+        self.last_attempt.map_or(true, |last_attempt| {
+            last_attempt.elapsed() >= RECOVERY_ATTEMPT_INTERVAL
+        })
+    }
+
+    /// Registers the failed attempt, returns total number of failed attempts of the operation
+    fn failed(&mut self, op_num: SeqNumberType) -> usize {
+        let attempts = self.attempts.entry(op_num).or_insert(0);
+        *attempts += 1;
+        *attempts
+    }
+}
+
 /// Information, required to perform operation and notify regarding the result
 #[derive(Debug)]
 pub struct OperationData {
@@ -75,21 +132,32 @@ pub struct UpdateHandler {
     flush_worker: Option<JoinHandle<()>>,
     /// Sender to stop flush worker
     flush_stop: Option<oneshot::Sender<()>>,
-    runtime_handle: Handle,
+    /// Runs workers and optimizations on the runtimes, shared by all collections
+    optimization_scheduler: OptimizationScheduler,
     /// WAL, required for operations
-    wal: Arc<ParkingMutex<SerdeWal<CollectionUpdateOperations>>>,
+    wal: Arc<ParkingMutex<SerdeWal<OperationWithId>>>,
     optimization_handles: Arc<TokioMutex<Vec<StoppableTaskHandle<bool>>>>,
+    /// Segments, which are already scheduled for optimization, are not scheduled again
+    queued_segment_ids: QueuedSegmentIds,
     max_optimization_threads: usize,
+    /// Storage for operations, which can't be applied and are skipped
+    dead_letter: Arc<DeadLetter>,
+    shard_id: ShardId,
+    /// Reports optimizations and failed updates, which may change the status of the shard
+    on_shard_event: OnShardEvent,
 }
 
 impl UpdateHandler {
     pub fn new(
         optimizers: Arc<Vec<Arc<Optimizer>>>,
-        runtime_handle: Handle,
+        optimization_scheduler: OptimizationScheduler,
         segments: LockedSegmentHolder,
-        wal: Arc<ParkingMutex<SerdeWal<CollectionUpdateOperations>>>,
+        wal: Arc<ParkingMutex<SerdeWal<OperationWithId>>>,
         flush_interval_sec: u64,
         max_optimization_threads: usize,
+        dead_letter: DeadLetter,
+        shard_id: ShardId,
+        on_shard_event: OnShardEvent,
     ) -> UpdateHandler {
         UpdateHandler {
             optimizers,
@@ -98,32 +166,55 @@ impl UpdateHandler {
             optimizer_worker: None,
             flush_worker: None,
             flush_stop: None,
-            runtime_handle,
+            optimization_scheduler,
             wal,
             flush_interval_sec,
             optimization_handles: Arc::new(TokioMutex::new(vec![])),
+            queued_segment_ids: Default::default(),
             max_optimization_threads,
+            dead_letter: Arc::new(dead_letter),
+            shard_id,
+            on_shard_event,
         }
     }
 
+    /// Scheduler of the optimizations, shared by all collections
+    pub(crate) fn optimization_scheduler(&self) -> &OptimizationScheduler {
+        &self.optimization_scheduler
+    }
+
+    fn shard_event_notifier(&self) -> impl Fn(ShardEvent) + Clone + Send + 'static {
+        let shard_id = self.shard_id;
+        let on_shard_event = self.on_shard_event.clone();
+        move |event| on_shard_event(shard_id, event)
+    }
+
     pub fn run_workers(&mut self, update_receiver: Receiver<UpdateSignal>) {
         let (tx, rx) = mpsc::channel(UPDATE_QUEUE_SIZE);
-        self.optimizer_worker = Some(self.runtime_handle.spawn(Self::optimization_worker_fn(
+        // Workers apply changes to segments synchronously, so they are kept off the
+        // optimization runtime, where scheduled optimizations wait for their turn
+        let runtime_handle = self.optimization_scheduler.update_runtime_handle();
+        self.optimizer_worker = Some(runtime_handle.spawn(Self::optimization_worker_fn(
             self.optimizers.clone(),
             tx.clone(),
             rx,
             self.segments.clone(),
             self.wal.clone(),
             self.optimization_handles.clone(),
+            self.queued_segment_ids.clone(),
             self.max_optimization_threads,
+            self.optimization_scheduler.clone(),
+            self.dead_letter.clone(),
+            self.shard_event_notifier(),
         )));
-        self.update_worker = Some(self.runtime_handle.spawn(Self::update_worker_fn(
+        self.update_worker = Some(runtime_handle.spawn(Self::update_worker_fn(
             update_receiver,
             tx,
             self.segments.clone(),
+            self.shard_event_notifier(),
         )));
         let (flush_tx, flush_rx) = oneshot::channel();
-        self.flush_worker = Some(self.runtime_handle.spawn(Self::flush_worker(
+        self.flush_worker = Some(runtime_handle.spawn(Self::flush_worker(
             self.segments.clone(),
             self.wal.clone(),
             self.flush_interval_sec,
@@ -169,38 +260,76 @@ impl UpdateHandler {
 
     /// Checks if there are any failed operations.
     /// If so - attempts to re-apply all failed operations.
+    /// Operation, which failed `MAX_RECOVERY_ATTEMPTS` times, is saved into `dead_letter`
+    /// and skipped, so it doesn't block the updates of the shard forever.
     async fn try_recover(
         segments: LockedSegmentHolder,
-        wal: Arc<ParkingMutex<SerdeWal<CollectionUpdateOperations>>>,
+        wal: Arc<ParkingMutex<SerdeWal<OperationWithId>>>,
+        dead_letter: &DeadLetter,
+        recovery_attempts: &mut RecoveryAttempts,
+        notify: impl Fn(ShardEvent),
     ) -> CollectionResult<usize> {
         // Try to re-apply everything starting from the first failed operation
         let first_failed_operation_option = segments.read().failed_operation.iter().cloned().min();
         match first_failed_operation_option {
             None => {}
             Some(first_failed_op) => {
+                recovery_attempts.last_attempt = Some(Instant::now());
                 let wal_lock = wal.lock();
-                for (op_num, operation) in wal_lock.read(first_failed_op) {
-                    CollectionUpdater::update(&segments, op_num, operation)?;
+                for (op_num, record) in wal_lock.read(first_failed_op) {
+                    let operation = record.operation;
+                    let error =
+                        match CollectionUpdater::update(&segments, op_num, operation.clone()) {
+                            Ok(_) => continue,
+                            Err(error @ CollectionError::ServiceError { .. }) => error,
+                            // Operation is declined, same as when it was applied for the first time
+                            Err(_) => continue,
+                        };
+                    let attempts = recovery_attempts.failed(op_num);
+                    if attempts < MAX_RECOVERY_ATTEMPTS {
+                        return Err(error);
+                    }
+                    dead_letter.quarantine(&QuarantinedOperation {
+                        op_num,
+                        operation,
+                        error: error.to_string(),
+                    })?;
+                    segments.write().failed_operation.remove(&op_num);
+                    recovery_attempts.attempts.remove(&op_num);
+                    error!(
+                        "Operation {} failed to apply {} times and is quarantined: {}",
+                        op_num, attempts, error
+                    );
+                    notify(ShardEvent::OperationQuarantined { op_num });
                 }
             }
         };
+        *recovery_attempts = RecoveryAttempts::default();
         Ok(0)
     }
 
     /// Checks conditions for all optimizers until there is no suggested segment
-    /// Starts a task for each optimization
-    /// Returns handles for started tasks
-    pub(crate) fn launch_optimization<F>(
+    /// Schedules a task for each optimization, tasks are started when optimization threads are available
+    /// Segments in `queued_segment_ids` are skipped, segments of new tasks are added there until the task is finished
+    /// `notify` is called when a task is started and finished
+    /// Returns handles for scheduled tasks
+    pub(crate) fn launch_optimization<F, N>(
         optimizers: Arc<Vec<Arc<Optimizer>>>,
         segments: LockedSegmentHolder,
+        optimization_scheduler: &OptimizationScheduler,
+        queued_segment_ids: &QueuedSegmentIds,
         callback: F,
+        notify: N,
     ) -> Vec<StoppableTaskHandle<bool>>
     where
         F: FnOnce(bool),
         F: Send + 'static,
         F: Clone,
+        N: Fn(ShardEvent),
+        N: Send + 'static,
+        N: Clone,
     {
-        let mut scheduled_segment_ids: HashSet<_> = Default::default();
+        let mut scheduled_segment_ids: HashSet<_> = queued_segment_ids.lock().clone();
         let mut handles = vec![];
         for optimizer in optimizers.iter() {
             loop {
@@ -215,23 +344,40 @@ impl UpdateHandler {
                     for sid in &nsi {
                         scheduled_segment_ids.insert(*sid);
                     }
+                    let queued = QueuedSegments::new(queued_segment_ids.clone(), nsi.clone());
                     let callback_cloned = callback.clone();
+                    let notify_cloned = notify.clone();
 
-                    handles.push(spawn_stoppable(move |stopped| {
-                        match optim.as_ref().optimize(segs.clone(), nsi, stopped) {
+                    let priority = optim.priority();
+                    handles.push(optimization_scheduler.spawn(priority, move |stopped| {
+                        notify_cloned(ShardEvent::OptimizationStarted);
+                        let result = optim.as_ref().optimize(segs.clone(), nsi, stopped);
+                        // Optimized segments are replaced, so they could be released before
+                        // the callback triggers the next check
+                        drop(queued);
+                        match result {
                             Ok(result) => {
+                                notify_cloned(ShardEvent::OptimizationFinished {
+                                    completed: result,
+                                });
                                 callback_cloned(result); // Perform some actions when optimization if finished
                                 result
                             }
                             Err(error) => match error {
                                 CollectionError::Cancelled { description } => {
                                     log::debug!("Optimization cancelled - {}", description);
+                                    notify_cloned(ShardEvent::OptimizationFinished {
+                                        completed: false,
+                                    });
                                     false
                                 }
                                 _ => {
                                     // Save only the first error
                                     // If is more likely to be the real cause of all further problems
                                     segs.write().report_optimizer_error(error.clone());
+                                    notify_cloned(ShardEvent::OptimizationFinished {
+                                        completed: false,
+                                    });
 
                                     // Error of the optimization can not be handled by API user
                                     // It is only possible to fix after full restart,
@@ -249,15 +395,58 @@ impl UpdateHandler {
         handles
     }
 
+    /// Number of optimizations, which are scheduled or running
+    pub async fn optimizations_backlog(&self) -> usize {
+        self.optimization_handles
+            .lock()
+            .await
+            .iter()
+            .filter(|handle| !handle.is_finished())
+            .count()
+    }
+
+    /// Schedule optimizations of `optimizers` right away, along with the regular ones.
+    /// Tasks are stopped together with the workers of the handler.
+    /// Returns the number of scheduled optimizations
+    pub(crate) async fn launch_forced_optimization<F>(
+        &self,
+        optimizers: Arc<Vec<Arc<Optimizer>>>,
+        callback: F,
+    ) -> usize
+    where
+        F: FnOnce(bool),
+        F: Send + 'static,
+        F: Clone,
+    {
+        let mut new_handles = Self::launch_optimization(
+            optimizers,
+            self.segments.clone(),
+            &self.optimization_scheduler,
+            &self.queued_segment_ids,
+            callback,
+            self.shard_event_notifier(),
+        );
+        let scheduled = new_handles.len();
+        let mut handles = self.optimization_handles.lock().await;
+        handles.append(&mut new_handles);
+        handles.retain(|h| !h.is_finished());
+        scheduled
+    }
+
     pub(crate) async fn process_optimization(
         optimizers: Arc<Vec<Arc<Optimizer>>>,
         segments: LockedSegmentHolder,
         optimization_handles: Arc<TokioMutex<Vec<StoppableTaskHandle<bool>>>>,
+        queued_segment_ids: &QueuedSegmentIds,
         sender: Sender<OptimizerSignal>,
+        optimization_scheduler: &OptimizationScheduler,
+        notify: impl Fn(ShardEvent) + Clone + Send + 'static,
     ) {
         let mut new_handles = Self::launch_optimization(
             optimizers.clone(),
             segments.clone(),
+            optimization_scheduler,
+            queued_segment_ids,
             move |_optimization_result| {
                 // After optimization is finished, we still need to check if there are
                 // some further optimizations possible.
@@ -265,21 +454,28 @@ impl UpdateHandler {
                 // If channel is full - optimization will be triggered by some other signal
                 let _ = sender.try_send(OptimizerSignal::Nop);
             },
+            notify,
         );
         let mut handles = optimization_handles.lock().await;
         handles.append(&mut new_handles);
         handles.retain(|h| !h.is_finished())
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn optimization_worker_fn(
         optimizers: Arc<Vec<Arc<Optimizer>>>,
         sender: Sender<OptimizerSignal>,
         mut receiver: Receiver<OptimizerSignal>,
         segments: LockedSegmentHolder,
-        wal: Arc<ParkingMutex<SerdeWal<CollectionUpdateOperations>>>,
+        wal: Arc<ParkingMutex<SerdeWal<OperationWithId>>>,
         optimization_handles: Arc<TokioMutex<Vec<StoppableTaskHandle<bool>>>>,
+        queued_segment_ids: QueuedSegmentIds,
         max_handles: usize,
+        optimization_scheduler: OptimizationScheduler,
+        dead_letter: Arc<DeadLetter>,
+        notify: impl Fn(ShardEvent) + Clone + Send + 'static,
     ) {
+        let mut recovery_attempts = RecoveryAttempts::default();
         while let Some(signal) = receiver.recv().await {
             match signal {
                 OptimizerSignal::Nop | OptimizerSignal::Operation(_) => {
@@ -292,17 +488,33 @@ impl UpdateHandler {
                     }
                     // We skip the check for number of optimization handles here
                     // Because `Nop` usually means that we need to force the optimization
-                    if Self::try_recover(segments.clone(), wal.clone())
+                    let has_failed_operations = !segments.read().failed_operation.is_empty();
+                    if has_failed_operations {
+                        if !recovery_attempts.is_due() {
+                            continue;
+                        }
+                        if Self::try_recover(
+                            segments.clone(),
+                            wal.clone(),
+                            &dead_letter,
+                            &mut recovery_attempts,
+                            notify.clone(),
+                        )
                         .await
                         .is_err()
-                    {
-                        continue;
+                        {
+                            continue;
+                        }
+                        notify(ShardEvent::UpdateRecovered);
                     }
                     Self::process_optimization(
                         optimizers.clone(),
                         segments.clone(),
                         optimization_handles.clone(),
+                        &queued_segment_ids,
                         sender.clone(),
+                        &optimization_scheduler,
+                        notify.clone(),
                     )
                     .await;
                 }
@@ -315,6 +527,7 @@ impl UpdateHandler {
         mut receiver: Receiver<UpdateSignal>,
         optimize_sender: Sender<OptimizerSignal>,
         segments: LockedSegmentHolder,
+        notify: impl Fn(ShardEvent),
     ) {
         while let Some(signal) = receiver.recv().await {
             match signal {
@@ -329,7 +542,13 @@ impl UpdateHandler {
                             .await
                             .and(Ok(update_res))
                             .map_err(|send_err| send_err.into()),
-                        Err(err) => Err(err),
+                        Err(err) => {
+                            // Only service errors mark the operation as failed
+                            if matches!(err, CollectionError::ServiceError { .. }) {
+                                notify(ShardEvent::UpdateFailed);
+                            }
+                            Err(err)
+                        }
                     };
 
                     if let Some(feedback) = sender {
@@ -372,7 +591,7 @@ impl UpdateHandler {
 
     async fn flush_worker(
         segments: LockedSegmentHolder,
-        wal: Arc<ParkingMutex<SerdeWal<CollectionUpdateOperations>>>,
+        wal: Arc<ParkingMutex<SerdeWal<OperationWithId>>>,
         flush_interval_sec: u64,
         mut stop_receiver: oneshot::Receiver<()>,
     ) {
@@ -407,7 +626,7 @@ impl UpdateHandler {
     ///
     /// # Errors
     /// Returns an error on flush failure
-    fn flush_segments(segments: LockedSegmentHolder) -> OperationResult<SeqNumberType> {
+    pub(crate) fn flush_segments(segments: LockedSegmentHolder) -> OperationResult<SeqNumberType> {
         let read_segments = segments.read();
         let flushed_version = read_segments.flush_all(false)?;
         Ok(match read_segments.failed_operation.iter().cloned().min() {
@@ -5,6 +5,7 @@ use std::fmt::Debug;
 use std::marker::PhantomData;
 use std::result;
 
+use segment::common::encryption::StorageCipher;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -20,6 +21,8 @@ pub enum WalError {
     WriteWalError(String),
     #[error("Can't truncate WAL: {0}")]
     TruncateWalError(String),
+    #[error("Can't read WAL: {0}")]
+    ReadWalError(String),
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -54,21 +57,38 @@ type Result<T> = result::Result<T, WalError>;
 pub struct SerdeWal<R> {
     record: PhantomData<R>,
     wal: Wal,
+    cipher: Option<StorageCipher>,
 }
 
 impl<'s, R: DeserializeOwned + Serialize + Debug> SerdeWal<R> {
     pub fn new(dir: &str, wal_options: &WalOptions) -> Result<SerdeWal<R>> {
+        Self::new_with_cipher(dir, wal_options, None)
+    }
+
+    /// Open WAL, which records are encrypted with `cipher`, if it is provided
+    pub fn new_with_cipher(
+        dir: &str,
+        wal_options: &WalOptions,
+        cipher: Option<StorageCipher>,
+    ) -> Result<SerdeWal<R>> {
         let wal = Wal::with_options(dir, wal_options)
             .map_err(|err| WalError::InitWalError(format!("{:?}", err)))?;
         Ok(SerdeWal {
             record: PhantomData,
             wal,
+            cipher,
         })
     }
 
     pub fn write(&mut self, entity: &R) -> Result<u64> {
         // ToDo: Replace back to faster rmp, once this https://github.com/serde-rs/serde/issues/2055 solved
         let binary_entity = serde_cbor::to_vec(&entity).unwrap();
+        let binary_entity = match &self.cipher {
+            Some(cipher) => cipher
+                .encrypt(&binary_entity)
+                .map_err(|err| WalError::WriteWalError(err.to_string()))?,
+            None => binary_entity,
+        };
         self.wal
             .append(&binary_entity)
             .map_err(|err| WalError::WriteWalError(format!("{:?}", err)))
@@ -86,19 +106,54 @@ impl<'s, R: DeserializeOwned + Serialize + Debug> SerdeWal<R> {
         self.wal.num_entries()
     }
 
+    pub fn first_index(&self) -> u64 {
+        self.wal.first_index()
+    }
+
+    /// Read records without panicking on the ones, which can't be read or deserialized.
+    /// Used to inspect possibly corrupted WAL.
+    pub fn try_read(&'s self, start_from: u64) -> impl Iterator<Item = (u64, Result<R>)> + 's {
+        let first_index = self.wal.first_index();
+        let num_entries = self.wal.num_entries();
+
+        (start_from..(first_index + num_entries)).map(move |idx| {
+            let record = match self.wal.entry(idx) {
+                None => Err(WalError::ReadWalError(format!("entry {idx} is missing"))),
+                Some(record_bin) => self.decode(idx, &record_bin),
+            };
+            (idx, record)
+        })
+    }
+
     pub fn read(&'s self, start_from: u64) -> impl Iterator<Item = (u64, R)> + 's {
         let first_index = self.wal.first_index();
         let num_entries = self.wal.num_entries();
 
         (start_from..(first_index + num_entries)).map(move |idx| {
             let record_bin = self.wal.entry(idx).expect("Can't read entry from WAL");
-            let record: R = serde_cbor::from_slice(&record_bin)
-                .or_else(|_err| rmp_serde::from_slice(&record_bin))
+            let record: R = self
+                .decode(idx, &record_bin)
                 .expect("Can't deserialize entry, probably corrupted WAL on version mismatch");
             (idx, record)
         })
     }
 
+    fn decode(&self, idx: u64, record_bin: &[u8]) -> Result<R> {
+        let decrypted;
+        let record_bin = match &self.cipher {
+            Some(cipher) => {
+                decrypted = cipher.decrypt(record_bin).map_err(|err| {
+                    WalError::ReadWalError(format!("can't read entry {idx}: {err}"))
+                })?;
+                decrypted.as_slice()
+            }
+            None => record_bin,
+        };
+        serde_cbor::from_slice(record_bin)
+            .or_else(|_err| rmp_serde::from_slice(record_bin))
+            .map_err(|err| WalError::ReadWalError(format!("can't deserialize entry {idx}: {err}")))
+    }
+
     /// Inform WAL, that records older than `until_index` are no longer required.
     /// If it is possible, WAL will remove unused files.
     ///
@@ -111,6 +166,14 @@ impl<'s, R: DeserializeOwned + Serialize + Debug> SerdeWal<R> {
             .prefix_truncate(until_index)
             .map_err(|err| WalError::TruncateWalError(format!("{:?}", err)))
     }
+
+    /// Remove records, starting from `from_index` (inclusive), till the end of WAL.
+    /// Used to drop corrupted records, which can't be applied.
+    pub fn truncate(&mut self, from_index: u64) -> Result<()> {
+        self.wal
+            .truncate(from_index)
+            .map_err(|err| WalError::TruncateWalError(format!("{:?}", err)))
+    }
 }
 
 #[cfg(test)]
@@ -122,6 +185,7 @@ mod tests {
     use std::fs;
     use std::os::unix::fs::MetadataExt;
 
+    use segment::common::encryption::STORAGE_KEY_SIZE;
     use tempfile::Builder;
 
     #[test]
@@ -173,4 +237,77 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_truncate_wal() {
+        let dir = Builder::new().prefix("wal_test").tempdir().unwrap();
+        let wal_options = WalOptions {
+            segment_capacity: 1024 * 1024,
+            segment_queue_len: 0,
+        };
+
+        let mut serde_wal: SerdeWal<TestRecord> =
+            SerdeWal::new(dir.path().to_str().unwrap(), &wal_options).unwrap();
+        for data in 0..5 {
+            let record = TestRecord::Struct1(TestInternalStruct1 { data });
+            serde_wal.write(&record).expect("Can't write");
+        }
+
+        serde_wal.truncate(3).unwrap();
+        assert_eq!(serde_wal.len(), 3);
+        let indexes: Vec<_> = serde_wal
+            .try_read(serde_wal.first_index())
+            .map(|(idx, record)| {
+                assert!(record.is_ok());
+                idx
+            })
+            .collect();
+        assert_eq!(indexes, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_encrypted_wal() {
+        let dir = Builder::new().prefix("wal_test").tempdir().unwrap();
+        let wal_options = WalOptions {
+            segment_capacity: 1024 * 1024,
+            segment_queue_len: 0,
+        };
+        let cipher = StorageCipher::new(&[7; STORAGE_KEY_SIZE]).unwrap();
+
+        {
+            let mut serde_wal: SerdeWal<TestRecord> = SerdeWal::new_with_cipher(
+                dir.path().to_str().unwrap(),
+                &wal_options,
+                Some(cipher.clone()),
+            )
+            .unwrap();
+            for data in 0..3 {
+                let record = TestRecord::Struct1(TestInternalStruct1 { data });
+                serde_wal.write(&record).expect("Can't write");
+            }
+        }
+
+        let serde_wal: SerdeWal<TestRecord> =
+            SerdeWal::new_with_cipher(dir.path().to_str().unwrap(), &wal_options, Some(cipher))
+                .unwrap();
+        let data: Vec<_> = serde_wal
+            .read_all()
+            .map(|(_idx, record)| match record {
+                TestRecord::Struct1(x) => x.data,
+                TestRecord::Struct2(_) => panic!("Wrong structure"),
+            })
+            .collect();
+        assert_eq!(data, vec![0, 1, 2]);
+        drop(serde_wal);
+
+        // Records can't be read with another key
+        let wrong_cipher = StorageCipher::new(&[8; STORAGE_KEY_SIZE]).unwrap();
+        let serde_wal: SerdeWal<TestRecord> = SerdeWal::new_with_cipher(
+            dir.path().to_str().unwrap(),
+            &wal_options,
+            Some(wrong_cipher),
+        )
+        .unwrap();
+        assert!(serde_wal.try_read(0).all(|(_idx, record)| record.is_err()));
+    }
 }
@@ -1,12 +1,28 @@
-use collection::operations::point_ops::{Batch, PointInsertOperations, PointOperations};
-use collection::operations::types::ScrollRequest;
+use std::num::{NonZeroU32, NonZeroU64};
+use std::path::Path;
+use std::time::Duration;
+
+use collection::collection::Collection;
+use collection::config::{CollectionConfig, CollectionParams, VectorParams, WalConfig};
+use collection::operations::point_ops::{
+    Batch, PointInsertOperations, PointOperations, WriteOrdering,
+};
+use collection::operations::snapshot_ops::{SnapshotManifest, SNAPSHOT_MANIFEST_FILE};
+use collection::operations::types::{
+    CollectionInfo, CollectionStatus, CountRequest, ScrollRequest,
+};
 use collection::operations::CollectionUpdateOperations;
+use collection::optimizers_builder::OptimizersConfig;
 use itertools::Itertools;
-use segment::types::{PayloadSelectorExclude, WithPayloadInterface};
+use segment::index::hnsw_index::graph_layers::HNSW_GRAPH_FILE;
+use segment::types::{Distance, PayloadSelectorExclude, WithPayloadInterface};
 use serde_json::Value;
 use tempfile::Builder;
 
-use crate::common::{load_local_collection, simple_collection_fixture, N_SHARDS};
+use crate::common::{
+    load_local_collection, new_local_collection, simple_collection_fixture, N_SHARDS,
+    TEST_OPTIMIZERS_CONFIG,
+};
 
 mod common;
 
@@ -39,7 +55,7 @@ async fn test_collection_reloading_with_shards(shard_number: u32) {
             })),
         );
         collection
-            .update_from_client(insert_points, true)
+            .update_from_client(insert_points, true, WriteOrdering::default())
             .await
             .unwrap();
         collection.before_drop().await;
@@ -74,7 +90,7 @@ async fn test_collection_payload_reloading_with_shards(shard_number: u32) {
             })),
         );
         collection
-            .update_from_client(insert_points, true)
+            .update_from_client(insert_points, true, WriteOrdering::default())
             .await
             .unwrap();
         collection.before_drop().await;
@@ -95,6 +111,8 @@ async fn test_collection_payload_reloading_with_shards(shard_number: u32) {
                 filter: None,
                 with_payload: Some(WithPayloadInterface::Bool(true)),
                 with_vector: true.into(),
+                order_by: None,
+                sample: None,
             },
             None,
         )
@@ -142,7 +160,7 @@ async fn test_collection_payload_custom_payload_with_shards(shard_number: u32) {
             })),
         );
         collection
-            .update_from_client(insert_points, true)
+            .update_from_client(insert_points, true, WriteOrdering::default())
             .await
             .unwrap();
         collection.before_drop().await;
@@ -165,6 +183,8 @@ async fn test_collection_payload_custom_payload_with_shards(shard_number: u32) {
                 filter: None,
                 with_payload: Some(WithPayloadInterface::Fields(vec![String::from("k2")])),
                 with_vector: true.into(),
+                order_by: None,
+                sample: None,
             },
             None,
         )
@@ -196,6 +216,8 @@ async fn test_collection_payload_custom_payload_with_shards(shard_number: u32) {
                 filter: None,
                 with_payload: Some(PayloadSelectorExclude::new(vec!["k1".to_string()]).into()),
                 with_vector: false.into(),
+                order_by: None,
+                sample: None,
             },
             None,
         )
@@ -228,3 +250,221 @@ async fn test_collection_payload_custom_payload_with_shards(shard_number: u32) {
     }
     collection.before_drop().await;
 }
+
+/// Remove all files with `file_name` in `dir` recursively, return number of removed files
+fn remove_files(dir: &Path, file_name: &str) -> usize {
+    let mut removed = 0;
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            removed += remove_files(&path, file_name);
+        } else if path.file_name().map_or(false, |name| name == file_name) {
+            std::fs::remove_file(&path).unwrap();
+            removed += 1;
+        }
+    }
+    removed
+}
+
+/// Wait until the collection info satisfies `condition`
+async fn wait_for_info(
+    collection: &Collection,
+    condition: impl Fn(&CollectionInfo) -> bool,
+) -> CollectionInfo {
+    for _ in 0..300 {
+        let info = collection.info(None).await.unwrap();
+        if condition(&info) {
+            return info;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("collection info did not reach the expected state");
+}
+
+#[tokio::test]
+async fn test_collection_rebuilds_missing_vector_index() {
+    let points_count = 2000;
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let collection_path = collection_dir.path();
+
+    let collection_config = CollectionConfig {
+        params: CollectionParams {
+            vectors: VectorParams {
+                size: NonZeroU64::new(4).unwrap(),
+                distance: Distance::Dot,
+                hnsw_config: None,
+                quantization_config: None,
+                on_disk: None,
+                datatype: None,
+                multivector_config: None,
+            }
+            .into(),
+            shard_number: NonZeroU32::new(1).unwrap(),
+            replication_factor: NonZeroU32::new(1).unwrap(),
+            write_consistency_factor: NonZeroU32::new(1).unwrap(),
+            on_disk_payload: false,
+            default_vector: None,
+        },
+        optimizer_config: OptimizersConfig {
+            default_segment_number: 1,
+            // Size of all vectors is above 10 kB, so they are indexed
+            indexing_threshold: 10,
+            ..TEST_OPTIMIZERS_CONFIG
+        },
+        wal_config: WalConfig {
+            wal_capacity_mb: 1,
+            wal_segments_ahead: 0,
+            encrypted: false,
+            operation_id_window_sec: 600,
+        },
+        hnsw_config: Default::default(),
+        payload_field_types: None,
+        auto_payload_index: None,
+        ttl: None,
+    };
+
+    {
+        let mut collection = new_local_collection(
+            "test".to_string(),
+            collection_path,
+            &collection_path.join("snapshots"),
+            &collection_config,
+        )
+        .await
+        .unwrap();
+        let insert_points = CollectionUpdateOperations::PointOperation(
+            PointOperations::UpsertPoints(PointInsertOperations::PointsBatch(Batch {
+                ids: (0..points_count).map(|x| x.into()).collect_vec(),
+                vectors: (0..points_count)
+                    .map(|x| vec![x as f32, 1.0, 0.0, 1.0])
+                    .collect_vec()
+                    .into(),
+                payloads: None,
+            })),
+        );
+        collection
+            .update_from_client(insert_points, true, WriteOrdering::default())
+            .await
+            .unwrap();
+
+        wait_for_info(&collection, |info| {
+            info.indexed_vectors_count == points_count as usize
+        })
+        .await;
+        collection.before_drop().await;
+    }
+
+    // Simulate snapshot without index files
+    assert!(remove_files(collection_path, HNSW_GRAPH_FILE) > 0);
+
+    let mut collection = load_local_collection(
+        "test".to_string(),
+        collection_path,
+        &collection_path.join("snapshots"),
+    )
+    .await;
+
+    let info = collection.info(None).await.unwrap();
+    assert!(info.index_rebuild.is_some());
+    assert_eq!(info.status, CollectionStatus::Yellow);
+    assert_eq!(info.indexed_vectors_count, 0);
+    assert_eq!(info.vectors_count, points_count as usize);
+
+    let info = wait_for_info(&collection, |info| info.index_rebuild.is_none()).await;
+    assert_eq!(info.indexed_vectors_count, points_count as usize);
+    collection.before_drop().await;
+}
+
+#[tokio::test]
+async fn test_incremental_snapshot_restore() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let snapshots_tmp_dir = Builder::new().prefix("snapshots_tmp").tempdir().unwrap();
+    let recover_dir = Builder::new().prefix("collection_rec").tempdir().unwrap();
+    let snapshots_path = collection_dir.path().join("snapshots");
+
+    let mut collection = simple_collection_fixture(collection_dir.path(), 1).await;
+    let insert_points = |ids: Vec<u64>| {
+        let vectors = ids
+            .iter()
+            .map(|id| vec![*id as f32, 0.0, 1.0, 1.0])
+            .collect_vec();
+        CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(
+            PointInsertOperations::PointsBatch(Batch {
+                ids: ids.into_iter().map(|x| x.into()).collect_vec(),
+                vectors: vectors.into(),
+                payloads: None,
+            }),
+        ))
+    };
+
+    collection
+        .update_from_client(insert_points(vec![0, 1, 2]), true, WriteOrdering::default())
+        .await
+        .unwrap();
+    let full = collection
+        .create_snapshot(snapshots_tmp_dir.path(), 1)
+        .await
+        .unwrap();
+
+    collection
+        .update_from_client(insert_points(vec![3, 4]), true, WriteOrdering::default())
+        .await
+        .unwrap();
+    let incremental = collection
+        .create_incremental_snapshot(snapshots_tmp_dir.path(), &full.name, 1)
+        .await
+        .unwrap();
+    assert_ne!(incremental.name, full.name);
+    collection.before_drop().await;
+
+    let manifest = SnapshotManifest::read_from_archive(&snapshots_path.join(&incremental.name))
+        .unwrap()
+        .unwrap();
+    assert_eq!(manifest.base, Some(full.name.clone()));
+    assert_eq!(
+        manifest.shards[&0].len(),
+        TEST_OPTIMIZERS_CONFIG.default_segment_number
+    );
+
+    Collection::restore_snapshot(&snapshots_path.join(&incremental.name), recover_dir.path())
+        .unwrap();
+    assert!(!recover_dir.path().join(SNAPSHOT_MANIFEST_FILE).exists());
+
+    let mut recovered =
+        load_local_collection("test_rec".to_string(), recover_dir.path(), &snapshots_path).await;
+    let count = recovered
+        .count(
+            CountRequest {
+                filter: None,
+                exact: true,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(count.count, 5);
+    recovered.before_drop().await;
+}
+
+#[tokio::test]
+async fn test_truncated_snapshot_is_not_restored() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let snapshots_tmp_dir = Builder::new().prefix("snapshots_tmp").tempdir().unwrap();
+    let recover_dir = Builder::new().prefix("collection_rec").tempdir().unwrap();
+    let snapshots_path = collection_dir.path().join("snapshots");
+
+    let mut collection = simple_collection_fixture(collection_dir.path(), 1).await;
+    let snapshot = collection
+        .create_snapshot(snapshots_tmp_dir.path(), 1)
+        .await
+        .unwrap();
+    collection.before_drop().await;
+
+    // Cut the archive at the tar block boundary, so it still looks complete
+    let snapshot_path = snapshots_path.join(&snapshot.name);
+    let content = std::fs::read(&snapshot_path).unwrap();
+    let truncated_path = snapshots_path.join("truncated.snapshot");
+    std::fs::write(&truncated_path, &content[..content.len() / 2 / 512 * 512]).unwrap();
+
+    assert!(Collection::restore_snapshot(&truncated_path, recover_dir.path()).is_err());
+}
@@ -1,15 +1,27 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
 
+use collection::jobs::export::CreateExport;
+use collection::jobs::{JobKind, JobStatus};
+use collection::operations::auto_payload_index::AutoPayloadIndexConfig;
 use collection::operations::payload_ops::{PayloadOps, SetPayload};
-use collection::operations::point_ops::{Batch, PointOperations, PointStruct};
+use collection::operations::payload_types::PayloadFieldTypes;
+use collection::operations::point_ops::{
+    Batch, PointInsertOperations, PointOperations, PointStruct, WriteOrdering,
+};
+use collection::operations::ttl::TtlConfig;
 use collection::operations::types::{
-    CountRequest, PointRequest, RecommendRequest, ScrollRequest, SearchRequest, UpdateStatus,
+    CollectionError, ContextExamplePair, CountRequest, DiscoverExample, DiscoverRequest,
+    FacetRequest, FacetResponse, LookupLocation, PointRequest, RecommendRequest, Record, Sample,
+    SampleRequest, ScrollRequest, SearchRequest, UpdateStatus,
 };
-use collection::operations::CollectionUpdateOperations;
+use collection::operations::{CollectionUpdateOperations, CreateIndex, FieldIndexOperations};
 use itertools::Itertools;
+use segment::data_types::facets::FacetValue;
 use segment::data_types::vectors::VectorStruct;
 use segment::types::{
-    Condition, FieldCondition, Filter, HasIdCondition, Payload, PointIdType, WithPayloadInterface,
+    Condition, Direction, FieldCondition, Filter, HasIdCondition, OrderBy, Payload,
+    PayloadFieldSchema, PayloadSchemaType, PointIdType, WithPayloadInterface,
 };
 use tempfile::Builder;
 use tokio::runtime::Handle;
@@ -48,7 +60,9 @@ async fn test_collection_updater_with_shards(shard_number: u32) {
         .into(),
     );
 
-    let insert_result = collection.update_from_client(insert_points, true).await;
+    let insert_result = collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await;
 
     match insert_result {
         Ok(res) => {
@@ -83,6 +97,57 @@ async fn test_collection_updater_with_shards(shard_number: u32) {
     collection.before_drop().await;
 }
 
+#[tokio::test]
+async fn test_collection_updater_with_write_ordering() {
+    test_collection_updater_with_write_ordering_with_shards(1).await;
+    test_collection_updater_with_write_ordering_with_shards(N_SHARDS).await;
+}
+
+async fn test_collection_updater_with_write_ordering_with_shards(shard_number: u32) {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+
+    let mut collection = simple_collection_fixture(collection_dir.path(), shard_number).await;
+
+    // The only replica of each shard is the leader, so it applies updates of any ordering
+    for (id, ordering) in [
+        WriteOrdering::Weak,
+        WriteOrdering::Medium,
+        WriteOrdering::Strong,
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let insert_points = CollectionUpdateOperations::PointOperation(
+            Batch {
+                ids: vec![(id as u64).into()],
+                vectors: vec![vec![1.0, 0.0, 1.0, 1.0]].into(),
+                payloads: None,
+            }
+            .into(),
+        );
+
+        let insert_result = collection
+            .update_from_client(insert_points, true, ordering)
+            .await
+            .unwrap();
+        assert_eq!(insert_result.status, UpdateStatus::Completed);
+    }
+
+    let count_res = collection
+        .count(
+            CountRequest {
+                filter: None,
+                exact: true,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(count_res.count, 3);
+
+    collection.before_drop().await;
+}
+
 #[tokio::test]
 async fn test_collection_search_with_payload_and_vector() {
     test_collection_search_with_payload_and_vector_with_shards(1).await;
@@ -106,7 +171,9 @@ async fn test_collection_search_with_payload_and_vector_with_shards(shard_number
         .into(),
     );
 
-    let insert_result = collection.update_from_client(insert_points, true).await;
+    let insert_result = collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await;
 
     match insert_result {
         Ok(res) => {
@@ -151,6 +218,7 @@ async fn test_collection_search_with_payload_and_vector_with_shards(shard_number
             geo_bounding_box: None,
             geo_radius: None,
             values_count: None,
+            datetime_range: None,
         }))),
         exact: true,
     };
@@ -158,6 +226,19 @@ async fn test_collection_search_with_payload_and_vector_with_shards(shard_number
     let count_res = collection.count(count_request, None).await.unwrap();
     assert_eq!(count_res.count, 1);
 
+    // Without filter the estimation is exact
+    let approx_total_res = collection
+        .count(
+            CountRequest {
+                filter: None,
+                exact: false,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(approx_total_res.count, 2);
+
     collection.before_drop().await;
 }
 
@@ -193,7 +274,7 @@ async fn test_collection_loading_with_shards(shard_number: u32) {
         );
 
         collection
-            .update_from_client(insert_points, true)
+            .update_from_client(insert_points, true, WriteOrdering::default())
             .await
             .unwrap();
 
@@ -206,7 +287,7 @@ async fn test_collection_loading_with_shards(shard_number: u32) {
             }));
 
         collection
-            .update_from_client(assign_payload, true)
+            .update_from_client(assign_payload, true, WriteOrdering::default())
             .await
             .unwrap();
         collection.before_drop().await;
@@ -320,7 +401,7 @@ async fn test_recommendation_api_with_shards(shard_number: u32) {
     );
 
     collection
-        .update_from_client(insert_points, true)
+        .update_from_client(insert_points, true, WriteOrdering::default())
         .await
         .unwrap();
     let result = collection
@@ -336,9 +417,11 @@ async fn test_recommendation_api_with_shards(shard_number: u32) {
                 with_vector: None,
                 score_threshold: None,
                 using: None,
+                lookup_from: None,
             },
             &Handle::current(),
             None,
+            &HashMap::new(),
         )
         .await
         .unwrap();
@@ -346,6 +429,131 @@ async fn test_recommendation_api_with_shards(shard_number: u32) {
     let top1 = &result[0];
 
     assert!(top1.id == 5.into() || top1.id == 6.into());
+
+    // Examples from another collection are not excluded from the result
+    let lookup_records = HashMap::from([(
+        "items".to_string(),
+        vec![Record {
+            id: 5.into(),
+            payload: None,
+            vector: Some(vec![0.0, 0.0, 1.0, 0.0].into()),
+            order_value: None,
+        }],
+    )]);
+    let result = collection
+        .recommend_by(
+            RecommendRequest {
+                positive: vec![5.into()],
+                negative: vec![],
+                filter: None,
+                params: None,
+                limit: 2,
+                offset: 0,
+                with_payload: None,
+                with_vector: None,
+                score_threshold: None,
+                using: None,
+                lookup_from: Some(LookupLocation {
+                    collection: "items".to_string(),
+                    vector: None,
+                }),
+            },
+            &Handle::current(),
+            None,
+            &lookup_records,
+        )
+        .await
+        .unwrap();
+    let ids: HashSet<_> = result.iter().map(|hit| hit.id).collect();
+    assert_eq!(ids, HashSet::from([5.into(), 6.into()]));
+    collection.before_drop().await;
+}
+
+#[tokio::test]
+async fn test_discovery_api() {
+    test_discovery_api_with_shards(1).await;
+    test_discovery_api_with_shards(N_SHARDS).await;
+}
+
+async fn test_discovery_api_with_shards(shard_number: u32) {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let mut collection = simple_collection_fixture(collection_dir.path(), shard_number).await;
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: (0..9).map(|x: u64| x.into()).collect_vec(),
+            vectors: vec![
+                vec![0.0, 0.0, 1.0, 1.0],
+                vec![1.0, 0.0, 0.0, 0.0],
+                vec![1.0, 0.0, 0.0, 0.0],
+                vec![0.0, 1.0, 0.0, 0.0],
+                vec![0.0, 1.0, 0.0, 0.0],
+                vec![0.0, 0.0, 1.0, 0.0],
+                vec![0.0, 0.0, 1.0, 0.0],
+                vec![0.0, 0.0, 0.0, 1.0],
+                vec![0.0, 0.0, 0.0, 1.0],
+            ]
+            .into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    let result = collection
+        .discover_by(
+            DiscoverRequest {
+                target: DiscoverExample::Vector(vec![1.0, 0.0, 0.0, 0.0]),
+                context: vec![ContextExamplePair {
+                    positive: DiscoverExample::PointId(3.into()),
+                    negative: DiscoverExample::PointId(5.into()),
+                }],
+                filter: None,
+                params: None,
+                limit: 3,
+                offset: 0,
+                with_payload: None,
+                with_vector: None,
+                using: None,
+            },
+            &Handle::current(),
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.len(), 3);
+    // The only point in the preferred zone of the pair goes first, though it is far from the target
+    assert_eq!(result[0].id, 4.into());
+    assert!(result[1].id == 1.into() || result[1].id == 2.into());
+    // Context examples are excluded, vectors are only used for the ranking
+    assert!(result
+        .iter()
+        .all(|hit| hit.id != 3.into() && hit.id != 5.into() && hit.vector.is_none()));
+
+    let missing = collection
+        .discover_by(
+            DiscoverRequest {
+                target: DiscoverExample::PointId(100.into()),
+                context: vec![],
+                filter: None,
+                params: None,
+                limit: 3,
+                offset: 0,
+                with_payload: None,
+                with_vector: None,
+                using: None,
+            },
+            &Handle::current(),
+            None,
+        )
+        .await;
+    assert!(matches!(
+        missing,
+        Err(CollectionError::PointNotFound { .. })
+    ));
     collection.before_drop().await;
 }
 
@@ -383,7 +591,7 @@ async fn test_read_api_with_shards(shard_number: u32) {
     ));
 
     collection
-        .update_from_client(insert_points, true)
+        .update_from_client(insert_points, true, WriteOrdering::default())
         .await
         .unwrap();
 
@@ -395,6 +603,8 @@ async fn test_read_api_with_shards(shard_number: u32) {
                 filter: None,
                 with_payload: Some(WithPayloadInterface::Bool(true)),
                 with_vector: false.into(),
+                order_by: None,
+                sample: None,
             },
             None,
         )
@@ -407,83 +617,1088 @@ async fn test_read_api_with_shards(shard_number: u32) {
 }
 
 #[tokio::test]
-async fn test_collection_delete_points_by_filter() {
-    test_collection_delete_points_by_filter_with_shards(1).await;
-    test_collection_delete_points_by_filter_with_shards(N_SHARDS).await;
+async fn test_collection_approximate_count() {
+    test_collection_approximate_count_with_shards(1).await;
+    test_collection_approximate_count_with_shards(N_SHARDS).await;
 }
 
-async fn test_collection_delete_points_by_filter_with_shards(shard_number: u32) {
+async fn test_collection_approximate_count_with_shards(shard_number: u32) {
     let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
-
     let mut collection = simple_collection_fixture(collection_dir.path(), shard_number).await;
 
-    let insert_points = CollectionUpdateOperations::PointOperation(
+    let points_count = 1000;
+    let insert_points = CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(
         Batch {
-            ids: vec![0, 1, 2, 3, 4]
-                .into_iter()
-                .map(|x| x.into())
-                .collect_vec(),
-            vectors: vec![
-                vec![1.0, 0.0, 1.0, 1.0],
-                vec![1.0, 0.0, 1.0, 0.0],
-                vec![1.0, 1.0, 1.0, 1.0],
-                vec![1.0, 1.0, 0.0, 1.0],
-                vec![1.0, 0.0, 0.0, 0.0],
-            ]
-            .into(),
-            payloads: None,
+            ids: (0..points_count).map(|x| x.into()).collect_vec(),
+            vectors: vec![vec![1.0, 0.0, 1.0, 1.0]; points_count as usize].into(),
+            payloads: Some(
+                (0..points_count)
+                    .map(|x| {
+                        let color = if x % 10 == 0 { "red" } else { "blue" };
+                        Some(serde_json::json!({ "color": color }).into())
+                    })
+                    .collect(),
+            ),
         }
         .into(),
+    ));
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    let count_request = |exact: bool| CountRequest {
+        filter: Some(Filter::new_must(Condition::Field(
+            FieldCondition::new_match("color".to_string(), "red".to_string().into()),
+        ))),
+        exact,
+    };
+
+    let exact_count = collection.count(count_request(true), None).await.unwrap();
+    assert_eq!(exact_count.count, 100);
+
+    // Field is not indexed, so the estimation is only bounded by the number of points
+    let approx_count = collection.count(count_request(false), None).await.unwrap();
+    assert!(approx_count.count <= points_count as usize);
+    assert_ne!(approx_count.count, exact_count.count);
+
+    let create_index = CollectionUpdateOperations::FieldIndexOperation(
+        FieldIndexOperations::CreateIndex(CreateIndex {
+            field_name: "color".to_string(),
+            field_schema: Some(PayloadFieldSchema::FieldType(PayloadSchemaType::Keyword)),
+        }),
     );
+    collection
+        .update_from_client(create_index, true, WriteOrdering::default())
+        .await
+        .unwrap();
 
-    let insert_result = collection.update_from_client(insert_points, true).await;
+    // Keyword index knows the exact number of points with the value
+    let approx_count = collection.count(count_request(false), None).await.unwrap();
+    assert_eq!(approx_count.count, exact_count.count);
 
-    match insert_result {
-        Ok(res) => {
-            assert_eq!(res.status, UpdateStatus::Completed)
+    collection.before_drop().await;
+}
+
+#[tokio::test]
+async fn test_ordered_scroll_api() {
+    test_ordered_scroll_api_with_shards(1).await;
+    test_ordered_scroll_api_with_shards(N_SHARDS).await;
+}
+
+async fn test_ordered_scroll_api_with_shards(shard_number: u32) {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let mut collection = simple_collection_fixture(collection_dir.path(), shard_number).await;
+
+    let create_index = CollectionUpdateOperations::FieldIndexOperation(
+        FieldIndexOperations::CreateIndex(CreateIndex {
+            field_name: "price".to_string(),
+            field_schema: Some(PayloadFieldSchema::FieldType(PayloadSchemaType::Integer)),
+        }),
+    );
+    collection
+        .update_from_client(create_index, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    let prices = vec![5, 3, 3, 8, 1, 7, 3, 2, 6];
+    let insert_points = CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(
+        Batch {
+            ids: (0..prices.len() as u64).map(|x| x.into()).collect_vec(),
+            vectors: vec![vec![1.0, 0.0, 1.0, 1.0]; prices.len()].into(),
+            payloads: Some(
+                prices
+                    .iter()
+                    .map(|price| Some(serde_json::json!({ "price": price }).into()))
+                    .collect(),
+            ),
         }
-        Err(err) => panic!("operation failed: {:?}", err),
-    }
+        .into(),
+    ));
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
 
-    // delete points with id (0, 3)
-    let to_be_deleted: HashSet<PointIdType> = vec![0.into(), 3.into()].into_iter().collect();
-    let delete_filter = segment::types::Filter {
-        should: None,
-        must: Some(vec![Condition::HasId(HasIdCondition::from(to_be_deleted))]),
-        must_not: None,
+    let scroll_request =
+        |direction: Direction, start_from: Option<f64>, offset: Option<u64>| ScrollRequest {
+            offset: offset.map(|x| x.into()),
+            limit: Some(3),
+            filter: None,
+            with_payload: Some(WithPayloadInterface::Bool(false)),
+            with_vector: false.into(),
+            order_by: Some(OrderBy {
+                key: "price".to_string(),
+                direction: Some(direction),
+                start_from,
+            }),
+            sample: None,
+        };
+    let ids = |points: &[Record]| points.iter().map(|point| point.id).collect_vec();
+    let expected = |ids: &[u64]| ids.iter().map(|id| PointIdType::from(*id)).collect_vec();
+
+    let first_page = collection
+        .scroll_by(scroll_request(Direction::Asc, None, None), None)
+        .await
+        .unwrap();
+    assert_eq!(ids(&first_page.points), expected(&[4, 7, 1]));
+    assert_eq!(first_page.points[0].order_value, Some(1.0));
+    assert_eq!(first_page.next_page_offset, Some(2.into()));
+    assert_eq!(first_page.next_page_order_value, Some(3.0));
+
+    let second_page = collection
+        .scroll_by(scroll_request(Direction::Asc, Some(3.0), Some(2)), None)
+        .await
+        .unwrap();
+    assert_eq!(ids(&second_page.points), expected(&[2, 6, 0]));
+    assert_eq!(second_page.next_page_offset, Some(8.into()));
+    assert_eq!(second_page.next_page_order_value, Some(6.0));
+
+    let desc_page = collection
+        .scroll_by(scroll_request(Direction::Desc, None, None), None)
+        .await
+        .unwrap();
+    assert_eq!(ids(&desc_page.points), expected(&[3, 5, 8]));
+    assert_eq!(desc_page.next_page_offset, Some(0.into()));
+
+    let not_indexed = ScrollRequest {
+        order_by: Some(OrderBy {
+            key: "not_indexed".to_string(),
+            direction: None,
+            start_from: None,
+        }),
+        ..Default::default()
     };
+    assert!(collection.scroll_by(not_indexed, None).await.is_err());
 
-    let delete_points = CollectionUpdateOperations::PointOperation(
-        PointOperations::DeletePointsByFilter(delete_filter),
-    );
+    // Point with multiple values is returned once, at the position of its smallest value
+    let multi_valued = CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(
+        Batch {
+            ids: vec![9.into()],
+            vectors: vec![vec![1.0, 0.0, 1.0, 1.0]].into(),
+            payloads: Some(vec![Some(
+                serde_json::json!({ "price": [4, 0, 100] }).into(),
+            )]),
+        }
+        .into(),
+    ));
+    collection
+        .update_from_client(multi_valued, true, WriteOrdering::default())
+        .await
+        .unwrap();
 
-    let delete_result = collection.update_from_client(delete_points, true).await;
+    let mut all_points = vec![];
+    let mut cursor: Option<(f64, u64)> = None;
+    loop {
+        let page = collection
+            .scroll_by(
+                scroll_request(
+                    Direction::Asc,
+                    cursor.map(|(value, _)| value),
+                    cursor.map(|(_, id)| id),
+                ),
+                None,
+            )
+            .await
+            .unwrap();
+        all_points.extend(ids(&page.points));
+        match (page.next_page_order_value, page.next_page_offset) {
+            (Some(value), Some(PointIdType::NumId(id))) => cursor = Some((value, id)),
+            _ => break,
+        }
+    }
+    assert_eq!(all_points, expected(&[9, 4, 7, 1, 2, 6, 0, 8, 5, 3]));
 
-    match delete_result {
-        Ok(res) => {
-            assert_eq!(res.status, UpdateStatus::Completed)
+    collection.before_drop().await;
+}
+
+#[tokio::test]
+async fn test_sample_scroll_api() {
+    test_sample_scroll_api_with_shards(1).await;
+    test_sample_scroll_api_with_shards(N_SHARDS).await;
+}
+
+async fn test_sample_scroll_api_with_shards(shard_number: u32) {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let mut collection = simple_collection_fixture(collection_dir.path(), shard_number).await;
+
+    let colors = ["red", "blue"];
+    let insert_points = CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(
+        Batch {
+            ids: (0..20).map(|x: u64| x.into()).collect_vec(),
+            vectors: vec![vec![1.0, 0.0, 1.0, 1.0]; 20].into(),
+            payloads: Some(
+                (0..20)
+                    .map(|i| Some(serde_json::json!({ "color": colors[i % 2] }).into()))
+                    .collect(),
+            ),
         }
-        Err(err) => panic!("operation failed: {:?}", err),
+        .into(),
+    ));
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    let sample_request = |limit: usize| ScrollRequest {
+        limit: Some(limit),
+        filter: Some(Filter::new_must(Condition::Field(
+            FieldCondition::new_match("color".to_string(), "red".to_string().into()),
+        ))),
+        sample: Some(Sample::Random),
+        ..Default::default()
+    };
+    let is_red = |id: &PointIdType| matches!(id, PointIdType::NumId(id) if id % 2 == 0);
+
+    let sample = collection.scroll_by(sample_request(4), None).await.unwrap();
+    assert_eq!(sample.points.len(), 4);
+    assert!(sample.points.iter().all(|point| is_red(&point.id)));
+    assert!(sample.points.iter().map(|point| point.id).all_unique());
+    assert!(sample
+        .points
+        .iter()
+        .all(|point| point.order_value.is_none()));
+    assert!(sample.next_page_offset.is_none());
+
+    // Shards, requested by a remote peer, keep random keys to be merged by it
+    let mut shard_points = 0;
+    for shard_id in 0..shard_number {
+        let shard_sample = collection
+            .scroll_by(sample_request(100), Some(shard_id))
+            .await
+            .unwrap();
+        assert!(shard_sample
+            .points
+            .iter()
+            .all(|point| point.order_value.is_some()));
+        shard_points += shard_sample.points.len();
     }
+    assert_eq!(shard_points, 10);
 
-    let result = collection
-        .scroll_by(
-            ScrollRequest {
-                offset: None,
-                limit: Some(10),
-                filter: None,
-                with_payload: Some(WithPayloadInterface::Bool(false)),
-                with_vector: false.into(),
-            },
-            None,
-        )
+    // Sample is limited by the number of matching points
+    let sample = collection
+        .scroll_by(sample_request(100), None)
         .await
         .unwrap();
+    assert_eq!(sample.points.len(), 10);
+    assert!(sample.points.iter().all(|point| is_red(&point.id)));
 
-    // check if we only have 3 out of 5 points left and that the point id were really deleted
-    assert_eq!(result.points.len(), 3);
-    assert_eq!(result.points.get(0).unwrap().id, 1.into());
-    assert_eq!(result.points.get(1).unwrap().id, 2.into());
-    assert_eq!(result.points.get(2).unwrap().id, 4.into());
+    let with_offset = ScrollRequest {
+        offset: Some(1.into()),
+        ..sample_request(4)
+    };
+    assert!(collection.scroll_by(with_offset, None).await.is_err());
+
+    // Vectors and payloads are returned by default
+    let request: SampleRequest = serde_json::from_value(serde_json::json!({ "limit": 5 })).unwrap();
+    let sample = collection.sample(request, None).await.unwrap();
+    assert_eq!(sample.len(), 5);
+    assert!(sample.iter().map(|point| point.id).all_unique());
+    assert!(sample
+        .iter()
+        .all(|point| point.vector.is_some() && point.payload.is_some()));
+
+    collection.before_drop().await;
+}
+
+#[tokio::test]
+async fn test_facet_api() {
+    test_facet_api_with_shards(1).await;
+    test_facet_api_with_shards(N_SHARDS).await;
+}
+
+async fn test_facet_api_with_shards(shard_number: u32) {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let mut collection = simple_collection_fixture(collection_dir.path(), shard_number).await;
+
+    let create_index = CollectionUpdateOperations::FieldIndexOperation(
+        FieldIndexOperations::CreateIndex(CreateIndex {
+            field_name: "color".to_string(),
+            field_schema: Some(PayloadFieldSchema::FieldType(PayloadSchemaType::Keyword)),
+        }),
+    );
+    collection
+        .update_from_client(create_index, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    let payloads = vec![
+        serde_json::json!({ "color": "red", "size": 1 }),
+        serde_json::json!({ "color": ["red", "blue"], "size": 2 }),
+        serde_json::json!({ "color": "blue", "size": 1 }),
+        serde_json::json!({ "color": "red", "size": 1 }),
+        serde_json::json!({ "color": "green", "size": 3 }),
+        serde_json::json!({ "size": 1 }),
+    ];
+    let insert_points = CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(
+        Batch {
+            ids: (0..payloads.len() as u64).map(|x| x.into()).collect_vec(),
+            vectors: vec![vec![1.0, 0.0, 1.0, 1.0]; payloads.len()].into(),
+            payloads: Some(
+                payloads
+                    .into_iter()
+                    .map(|payload| Some(payload.into()))
+                    .collect(),
+            ),
+        }
+        .into(),
+    ));
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    let keyword = |value: &str| FacetValue::Keyword(value.to_string());
+    let hits = |response: FacetResponse| {
+        response
+            .hits
+            .into_iter()
+            .map(|hit| (hit.value, hit.count))
+            .collect_vec()
+    };
+
+    let all_colors = collection
+        .facet(
+            FacetRequest {
+                key: "color".to_string(),
+                filter: None,
+                limit: 2,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        hits(all_colors),
+        vec![(keyword("red"), 3), (keyword("blue"), 2)]
+    );
+
+    let small_colors = collection
+        .facet(
+            FacetRequest {
+                key: "color".to_string(),
+                filter: Some(Filter::new_must(Condition::Field(
+                    FieldCondition::new_match("size".to_string(), 1i64.into()),
+                ))),
+                limit: 10,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        hits(small_colors),
+        vec![(keyword("red"), 2), (keyword("blue"), 1)]
+    );
+
+    // Not indexed field is counted by payload
+    let sizes = collection
+        .facet(
+            FacetRequest {
+                key: "size".to_string(),
+                filter: None,
+                limit: 10,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        hits(sizes),
+        vec![
+            (FacetValue::Integer(1), 4),
+            (FacetValue::Integer(2), 1),
+            (FacetValue::Integer(3), 1)
+        ]
+    );
+
+    // Limit is capped, as the size of the sketch depends on it
+    let too_large = collection
+        .facet(
+            FacetRequest {
+                key: "size".to_string(),
+                filter: None,
+                limit: 1_000_000,
+            },
+            None,
+        )
+        .await;
+    assert!(matches!(too_large, Err(CollectionError::BadRequest { .. })));
+
+    collection.before_drop().await;
+}
+
+#[tokio::test]
+async fn test_collection_delete_points_by_filter() {
+    test_collection_delete_points_by_filter_with_shards(1).await;
+    test_collection_delete_points_by_filter_with_shards(N_SHARDS).await;
+}
+
+async fn test_collection_delete_points_by_filter_with_shards(shard_number: u32) {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+
+    let mut collection = simple_collection_fixture(collection_dir.path(), shard_number).await;
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: vec![0, 1, 2, 3, 4]
+                .into_iter()
+                .map(|x| x.into())
+                .collect_vec(),
+            vectors: vec![
+                vec![1.0, 0.0, 1.0, 1.0],
+                vec![1.0, 0.0, 1.0, 0.0],
+                vec![1.0, 1.0, 1.0, 1.0],
+                vec![1.0, 1.0, 0.0, 1.0],
+                vec![1.0, 0.0, 0.0, 0.0],
+            ]
+            .into(),
+            payloads: None,
+        }
+        .into(),
+    );
+
+    let insert_result = collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await;
+
+    match insert_result {
+        Ok(res) => {
+            assert_eq!(res.status, UpdateStatus::Completed)
+        }
+        Err(err) => panic!("operation failed: {:?}", err),
+    }
+
+    // delete points with id (0, 3)
+    let to_be_deleted: HashSet<PointIdType> = vec![0.into(), 3.into()].into_iter().collect();
+    let delete_filter = segment::types::Filter {
+        should: None,
+        min_should: None,
+        must: Some(vec![Condition::HasId(HasIdCondition::from(to_be_deleted))]),
+        must_not: None,
+    };
+
+    let delete_points = CollectionUpdateOperations::PointOperation(
+        PointOperations::DeletePointsByFilter(delete_filter),
+    );
+
+    let delete_result = collection
+        .update_from_client(delete_points, true, WriteOrdering::default())
+        .await;
+
+    match delete_result {
+        Ok(res) => {
+            assert_eq!(res.status, UpdateStatus::Completed)
+        }
+        Err(err) => panic!("operation failed: {:?}", err),
+    }
+
+    let result = collection
+        .scroll_by(
+            ScrollRequest {
+                offset: None,
+                limit: Some(10),
+                filter: None,
+                with_payload: Some(WithPayloadInterface::Bool(false)),
+                with_vector: false.into(),
+                order_by: None,
+                sample: None,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+
+    // check if we only have 3 out of 5 points left and that the point id were really deleted
+    assert_eq!(result.points.len(), 3);
+    assert_eq!(result.points.get(0).unwrap().id, 1.into());
+    assert_eq!(result.points.get(1).unwrap().id, 2.into());
+    assert_eq!(result.points.get(2).unwrap().id, 4.into());
+    collection.before_drop().await;
+}
+
+#[tokio::test]
+async fn test_collection_force_optimization() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+
+    let mut collection = simple_collection_fixture(collection_dir.path(), 1).await;
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: vec![0, 1, 2, 3].into_iter().map(|x| x.into()).collect_vec(),
+            vectors: vec![
+                vec![1.0, 0.0, 1.0, 1.0],
+                vec![1.0, 0.0, 1.0, 0.0],
+                vec![1.0, 1.0, 1.0, 1.0],
+                vec![1.0, 1.0, 0.0, 1.0],
+            ]
+            .into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    // Nothing is deleted, so there is nothing to vacuum
+    assert_eq!(collection.force_optimization(None).await.unwrap(), 0);
+    assert!(collection.force_optimization(Some(1)).await.is_err());
+
+    let delete_points = CollectionUpdateOperations::PointOperation(PointOperations::DeletePoints {
+        ids: vec![0.into()],
+    });
+    collection
+        .update_from_client(delete_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    // Deleted fraction is far below `deleted_threshold`, but the segment is vacuumed anyway
+    assert_eq!(collection.force_optimization(Some(0)).await.unwrap(), 1);
+
+    let count = collection
+        .count(
+            CountRequest {
+                filter: None,
+                exact: true,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(count.count, 3);
+    collection.before_drop().await;
+}
+
+#[tokio::test]
+async fn test_collection_update_queue_telemetry() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+
+    let mut collection = simple_collection_fixture(collection_dir.path(), 1).await;
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: vec![0, 1].into_iter().map(|x| x.into()).collect_vec(),
+            vectors: vec![vec![1.0, 0.0, 1.0, 1.0], vec![1.0, 0.0, 1.0, 0.0]].into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    // Update is awaited, so nothing is left in the queue
+    assert_eq!(collection.max_update_queue_length().await, 0);
+
+    let telemetry = collection.get_telemetry_data().await;
+    let update_queue = &telemetry.shards[0].local.as_ref().unwrap().update_queue;
+    assert_eq!(update_queue.queue_length, 0);
+    assert_eq!(update_queue.wal_lag, 0);
+    collection.before_drop().await;
+}
+
+#[tokio::test]
+async fn test_collection_export() {
+    test_collection_export_with_shards(1).await;
+    test_collection_export_with_shards(N_SHARDS).await;
+}
+
+async fn test_collection_export_with_shards(shard_number: u32) {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+
+    let mut collection = simple_collection_fixture(collection_dir.path(), shard_number).await;
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: vec![0, 1, 2, 3, 4]
+                .into_iter()
+                .map(|x| x.into())
+                .collect_vec(),
+            vectors: vec![
+                vec![1.0, 0.0, 1.0, 1.0],
+                vec![1.0, 0.0, 1.0, 0.0],
+                vec![1.0, 1.0, 1.0, 1.0],
+                vec![1.0, 1.0, 0.0, 1.0],
+                vec![1.0, 0.0, 0.0, 0.0],
+            ]
+            .into(),
+            payloads: None,
+        }
+        .into(),
+    );
+
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    let export = collection
+        .create_export(CreateExport {
+            chunk_size: Some(2),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    let mut export = collection.get_export(&export.job.id).await.unwrap();
+    for _ in 0..100 {
+        if export.job.status != JobStatus::Running {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        export = collection.get_export(&export.job.id).await.unwrap();
+    }
+
+    assert_eq!(export.job.status, JobStatus::Completed);
+    assert_eq!(export.chunks, 3);
+    assert_eq!(export.job.progress.processed, 5);
+    assert_eq!(export.job.progress.total, Some(5));
+
+    let first_chunk = collection
+        .get_export_chunk_path(&export.job.id, 0)
+        .await
+        .unwrap();
+    let points: Vec<Record> = std::fs::read_to_string(first_chunk)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(points.len(), 2);
+    assert_eq!(points[0].id, 0.into());
+    assert!(points[0].vector.is_some());
+
+    assert!(collection
+        .get_export_chunk_path(&export.job.id, 3)
+        .await
+        .is_err());
+
+    let jobs = collection.list_jobs().await;
+    assert_eq!(jobs.len(), 1);
+    assert_eq!(jobs[0].kind, JobKind::Export);
+    assert_eq!(jobs[0].collection_name, "test");
+
+    // Finished jobs are not affected by cancellation
+    let cancelled = collection.cancel_job(&export.job.id).await.unwrap();
+    assert_eq!(cancelled.status, JobStatus::Completed);
+    assert!(collection.cancel_job("missing").await.is_err());
+
+    collection.before_drop().await;
+
+    let mut loaded_collection = load_local_collection(
+        "test".to_string(),
+        collection_dir.path(),
+        &collection_dir.path().join("snapshots"),
+    )
+    .await;
+    let exports = loaded_collection.list_exports().await;
+    assert_eq!(exports.len(), 1);
+    assert_eq!(exports[0].job.status, JobStatus::Completed);
+    loaded_collection.before_drop().await;
+}
+
+#[tokio::test]
+async fn test_collection_payload_field_types() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+
+    let mut collection = simple_collection_fixture(collection_dir.path(), 1).await;
+    collection
+        .update_payload_field_types(PayloadFieldTypes::from([(
+            "count".to_string(),
+            PayloadSchemaType::Integer,
+        )]))
+        .await
+        .unwrap();
+
+    let upsert = |count: serde_json::Value| {
+        CollectionUpdateOperations::PointOperation(
+            Batch {
+                ids: vec![0.into()],
+                vectors: vec![vec![1.0, 0.0, 1.0, 1.0]].into(),
+                payloads: serde_json::from_value(serde_json::json!([{ "count": count }])).unwrap(),
+            }
+            .into(),
+        )
+    };
+
+    let result = collection
+        .update_from_client(upsert(1.into()), true, WriteOrdering::default())
+        .await;
+    assert!(result.is_ok());
+
+    let result = collection
+        .update_from_client(upsert("1".into()), true, WriteOrdering::default())
+        .await;
+    assert!(matches!(result, Err(CollectionError::BadInput { .. })));
+
+    let set_payload =
+        CollectionUpdateOperations::PayloadOperation(PayloadOps::SetPayload(SetPayload {
+            payload: serde_json::from_value(serde_json::json!({ "count": [1, 2.5] })).unwrap(),
+            points: vec![0.into()],
+        }));
+    let result = collection
+        .update_from_client(set_payload, true, WriteOrdering::default())
+        .await;
+    assert!(matches!(result, Err(CollectionError::BadInput { .. })));
+
+    // Empty map disables the check
+    collection
+        .update_payload_field_types(PayloadFieldTypes::new())
+        .await
+        .unwrap();
+    let result = collection
+        .update_from_client(upsert("1".into()), true, WriteOrdering::default())
+        .await;
+    assert!(result.is_ok());
+
+    collection.before_drop().await;
+}
+
+#[tokio::test]
+async fn test_collection_auto_payload_index() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+
+    let mut collection = simple_collection_fixture(collection_dir.path(), 1).await;
+    collection
+        .update_auto_payload_index(AutoPayloadIndexConfig {
+            max_indexed_fields: 2,
+        })
+        .await
+        .unwrap();
+
+    let upsert = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: vec![0.into()],
+            vectors: vec![vec![1.0, 0.0, 1.0, 1.0]].into(),
+            payloads: serde_json::from_value(serde_json::json!([
+                { "city": "Berlin", "count": 1, "flag": true }
+            ]))
+            .unwrap(),
+        }
+        .into(),
+    );
+    collection
+        .update_from_client(upsert, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    let payload_schema = collection.info(None).await.unwrap().payload_schema;
+    assert_eq!(payload_schema.len(), 2);
+    assert_eq!(payload_schema["city"].data_type, PayloadSchemaType::Keyword);
+    assert_eq!(
+        payload_schema["count"].data_type,
+        PayloadSchemaType::Integer
+    );
+
+    // The cap is reached, new fields are not indexed
+    let set_payload =
+        CollectionUpdateOperations::PayloadOperation(PayloadOps::SetPayload(SetPayload {
+            payload: serde_json::from_value(serde_json::json!({ "price": 1.5 })).unwrap(),
+            points: vec![0.into()],
+        }));
+    collection
+        .update_from_client(set_payload, true, WriteOrdering::default())
+        .await
+        .unwrap();
+    let payload_schema = collection.info(None).await.unwrap().payload_schema;
+    assert!(!payload_schema.contains_key("price"));
+
+    collection.before_drop().await;
+}
+
+#[tokio::test]
+async fn test_collection_wait_for_operation() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+
+    let mut collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    let insert_points = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: (0..10).map(|x: u64| x.into()).collect_vec(),
+            vectors: vec![vec![1.0, 0.0, 1.0, 1.0]; 10].into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    let insert_result = collection
+        .update_from_client(insert_points, false, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    // Each shard has a single local replica
+    let shard_ids: HashSet<_> = insert_result
+        .replica_operation_ids
+        .iter()
+        .map(|replica_operation_id| replica_operation_id.shard_id)
+        .collect();
+    assert_eq!(shard_ids.len(), insert_result.replica_operation_ids.len());
+
+    for replica_operation_id in &insert_result.replica_operation_ids {
+        let applied = collection
+            .wait_for_local_operation(
+                replica_operation_id.shard_id,
+                replica_operation_id.operation_id,
+                Duration::from_secs(10),
+            )
+            .await
+            .unwrap();
+        assert!(applied);
+
+        // The next operation is never sent
+        let applied = collection
+            .wait_for_local_operation(
+                replica_operation_id.shard_id,
+                replica_operation_id.operation_id + 1,
+                Duration::from_millis(50),
+            )
+            .await
+            .unwrap();
+        assert!(!applied);
+    }
+
+    let count_res = collection
+        .count(
+            CountRequest {
+                filter: None,
+                exact: true,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(count_res.count, 10);
+
+    collection.before_drop().await;
+}
+
+#[tokio::test]
+async fn test_collection_bulk_upsert() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+
+    let mut collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    let points = |ids: std::ops::Range<u64>| {
+        PointInsertOperations::PointsBatch(Batch {
+            ids: ids.clone().map(|x| x.into()).collect_vec(),
+            vectors: ids
+                .map(|x| vec![x as f32, 0.0, 1.0, 1.0])
+                .collect_vec()
+                .into(),
+            payloads: None,
+        })
+    };
+
+    let bulk_result = collection.bulk_upsert(points(0..100)).await.unwrap();
+    assert_eq!(bulk_result.status, UpdateStatus::Completed);
+
+    let count_res = collection
+        .count(
+            CountRequest {
+                filter: None,
+                exact: true,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(count_res.count, 100);
+
+    let search_res = collection
+        .search(
+            SearchRequest {
+                vector: vec![99.0, 0.0, 1.0, 1.0].into(),
+                with_payload: None,
+                with_vector: None,
+                filter: None,
+                params: None,
+                limit: 1,
+                offset: 0,
+                score_threshold: None,
+            },
+            &Handle::current(),
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(search_res.len(), 1);
+
+    // Shards are not empty anymore
+    assert!(collection.bulk_upsert(points(100..200)).await.is_err());
+
+    collection.before_drop().await;
+}
+
+#[tokio::test]
+async fn test_collection_ttl_expiration() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+
+    let mut collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+    collection
+        .update_ttl(TtlConfig {
+            field: "created_at".to_string(),
+            ttl_sec: 3600,
+            // Expiration is triggered explicitly by the test
+            check_interval_sec: 3600,
+        })
+        .await
+        .unwrap();
+
+    let recent = chrono::Utc::now().to_rfc3339();
+    let upsert = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: vec![0.into(), 1.into(), 2.into()],
+            vectors: vec![vec![1.0, 0.0, 1.0, 1.0]; 3].into(),
+            payloads: serde_json::from_value(serde_json::json!([
+                { "created_at": "2020-01-01T00:00:00Z" },
+                { "created_at": recent },
+                { "city": "Berlin" }
+            ]))
+            .unwrap(),
+        }
+        .into(),
+    );
+    collection
+        .update_from_client(upsert, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    collection.expire_points().await.unwrap();
+
+    let remaining = collection
+        .scroll_by(
+            ScrollRequest {
+                offset: None,
+                limit: Some(10),
+                filter: None,
+                with_payload: Some(WithPayloadInterface::Bool(false)),
+                with_vector: false.into(),
+                order_by: None,
+                sample: None,
+            },
+            None,
+        )
+        .await
+        .unwrap()
+        .points
+        .into_iter()
+        .map(|point| point.id)
+        .collect_vec();
+    assert_eq!(remaining, vec![1.into(), 2.into()]);
+
+    collection.before_drop().await;
+}
+
+#[tokio::test]
+async fn test_collection_update_with_operation_id() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+
+    let mut collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    let upsert = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: vec![0.into()],
+            vectors: vec![vec![1.0, 0.0, 1.0, 1.0]].into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    let delete = CollectionUpdateOperations::PointOperation(PointOperations::DeletePoints {
+        ids: vec![0.into()],
+    });
+    let operation_id = Some("upsert-0".to_string());
+
+    let first = collection
+        .update_from_client_with_id(
+            upsert.clone(),
+            operation_id.clone(),
+            true,
+            WriteOrdering::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(first.status, UpdateStatus::Completed);
+
+    collection
+        .update_from_client(delete, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    // Retry is acknowledged, but not applied again
+    let retry = collection
+        .update_from_client_with_id(upsert.clone(), operation_id, true, WriteOrdering::default())
+        .await
+        .unwrap();
+    assert_eq!(retry.status, UpdateStatus::Acknowledged);
+    assert_eq!(retry.operation_id, first.operation_id);
+
+    let request = || PointRequest {
+        ids: vec![0.into()],
+        with_payload: None,
+        with_vector: false.into(),
+    };
+    let points = collection.retrieve(request(), None).await.unwrap();
+    assert!(points.is_empty());
+
+    // Operations with another id are applied
+    collection
+        .update_from_client_with_id(
+            upsert,
+            Some("upsert-0-again".to_string()),
+            true,
+            WriteOrdering::default(),
+        )
+        .await
+        .unwrap();
+    let points = collection.retrieve(request(), None).await.unwrap();
+    assert_eq!(points.len(), 1);
+
+    collection.before_drop().await;
+}
+
+#[tokio::test]
+async fn test_read_points_changes() {
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let mut collection = simple_collection_fixture(collection_dir.path(), 1).await;
+
+    let upsert = CollectionUpdateOperations::PointOperation(
+        Batch {
+            ids: vec![0.into()],
+            vectors: vec![vec![1.0, 0.0, 1.0, 1.0]].into(),
+            payloads: None,
+        }
+        .into(),
+    );
+    collection
+        .update_from_client(upsert, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    // The only local shard is read, if the shard is not specified
+    let changes = collection.read_points_changes(None, 0, 10).await.unwrap();
+    assert_eq!(changes.changes.len(), 1);
+    assert_eq!(changes.changes[0].shard_id, 0);
+    assert_eq!(changes.changes[0].event.name(), "upsert");
+    assert_eq!(changes.next_op_num, changes.changes[0].op_num + 1);
+    collection.before_drop().await;
+
+    let collection_dir = Builder::new().prefix("collection").tempdir().unwrap();
+    let mut collection = simple_collection_fixture(collection_dir.path(), N_SHARDS).await;
+
+    // Shard is ambiguous, if there are several local shards
+    let err = collection
+        .read_points_changes(None, 0, 10)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, CollectionError::BadRequest { .. }));
+    assert!(err.to_string().contains(&format!(
+        "Collection has {N_SHARDS} shards, {N_SHARDS} of them on this peer"
+    )));
+    let changes = collection
+        .read_points_changes(Some(0), 0, 10)
+        .await
+        .unwrap();
+    assert!(changes.changes.is_empty());
     collection.before_drop().await;
 }
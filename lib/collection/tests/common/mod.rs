@@ -5,14 +5,17 @@ use std::path::Path;
 use std::sync::Arc;
 
 use collection::collection::{Collection, RequestShardTransfer};
+use collection::common::optimization_scheduler::OptimizationScheduler;
 use collection::config::{CollectionConfig, CollectionParams, VectorParams, WalConfig};
 use collection::operations::types::CollectionError;
 use collection::optimizers_builder::OptimizersConfig;
 use collection::shards::channel_service::ChannelService;
 use collection::shards::collection_shard_distribution::CollectionShardDistribution;
+use collection::shards::local_shard::OnShardEvent;
 use collection::shards::replica_set::{OnPeerFailure, ReplicaState};
 use collection::shards::CollectionId;
 use segment::types::Distance;
+use tokio::runtime::Handle;
 
 /// Test collections for this upper bound of shards.
 /// Testing with more shards is problematic due to `number of open files problem`
@@ -37,18 +40,26 @@ pub async fn simple_collection_fixture(collection_path: &Path, shard_number: u32
     let wal_config = WalConfig {
         wal_capacity_mb: 1,
         wal_segments_ahead: 0,
+        encrypted: false,
+        operation_id_window_sec: 600,
     };
 
     let collection_params = CollectionParams {
         vectors: VectorParams {
             size: NonZeroU64::new(4).unwrap(),
             distance: Distance::Dot,
+            hnsw_config: None,
+            quantization_config: None,
+            on_disk: None,
+            datatype: None,
+            multivector_config: None,
         }
         .into(),
         shard_number: NonZeroU32::new(shard_number).expect("Shard number can not be zero"),
         replication_factor: NonZeroU32::new(1).unwrap(),
         write_consistency_factor: NonZeroU32::new(1).unwrap(),
         on_disk_payload: false,
+        default_vector: None,
     };
 
     let collection_config = CollectionConfig {
@@ -56,6 +67,9 @@ pub async fn simple_collection_fixture(collection_path: &Path, shard_number: u32
         optimizer_config: TEST_OPTIMIZERS_CONFIG.clone(),
         wal_config,
         hnsw_config: Default::default(),
+        payload_field_types: None,
+        auto_payload_index: None,
+        ttl: None,
     };
 
     let snapshot_path = collection_path.join("snapshots");
@@ -79,6 +93,10 @@ pub fn dummy_request_shard_transfer() -> RequestShardTransfer {
     Arc::new(move |_transfer| {})
 }
 
+pub fn dummy_on_shard_event() -> OnShardEvent {
+    Arc::new(move |_shard_id, _event| {})
+}
+
 /// Default to a collection with all the shards local
 #[cfg(test)]
 pub async fn new_local_collection(
@@ -95,8 +113,11 @@ pub async fn new_local_collection(
         config,
         CollectionShardDistribution::all_local(Some(config.params.shard_number.into()), 0),
         ChannelService::default(),
+        OptimizationScheduler::new(Handle::current(), Handle::current(), 0),
+        None,
         dummy_on_replica_failure(),
         dummy_request_shard_transfer(),
+        dummy_on_shard_event(),
     )
     .await;
 
@@ -124,8 +145,11 @@ pub async fn load_local_collection(
         path,
         snapshots_path,
         ChannelService::default(),
+        OptimizationScheduler::new(Handle::current(), Handle::current(), 0),
+        None,
         dummy_on_replica_failure(),
         dummy_request_shard_transfer(),
+        dummy_on_shard_event(),
     )
     .await
 }
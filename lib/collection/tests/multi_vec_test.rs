@@ -1,18 +1,23 @@
 #![allow(deprecated)]
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 use std::num::{NonZeroU32, NonZeroU64};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use collection::collection::Collection;
 use collection::config::{
     CollectionConfig, CollectionParams, VectorParams, VectorsConfig, WalConfig,
 };
-use collection::operations::point_ops::{PointInsertOperations, PointOperations, PointStruct};
+use collection::operations::config_diff::HnswConfigDiff;
+use collection::operations::point_ops::{
+    PointInsertOperations, PointOperations, PointStruct, WriteOrdering,
+};
 use collection::operations::types::{
     CollectionError, PointRequest, RecommendRequest, SearchRequest,
 };
 use collection::operations::CollectionUpdateOperations;
+use collection::optimizers_builder::OptimizersConfig;
 use segment::data_types::named_vectors::NamedVectors;
 use segment::data_types::vectors::{NamedVector, VectorStruct};
 use segment::types::{Distance, WithPayloadInterface, WithVector};
@@ -33,19 +38,40 @@ async fn test_multi_vec() {
 }
 
 #[cfg(test)]
-pub async fn multi_vec_collection_fixture(collection_path: &Path, shard_number: u32) -> Collection {
+pub async fn multi_vec_collection_fixture(
+    collection_path: &Path,
+    shard_number: u32,
+    optimizer_config: OptimizersConfig,
+    default_vector: Option<&str>,
+) -> Collection {
     let wal_config = WalConfig {
         wal_capacity_mb: 1,
         wal_segments_ahead: 0,
+        encrypted: false,
+        operation_id_window_sec: 600,
     };
 
     let vector_params1 = VectorParams {
         size: NonZeroU64::new(4).unwrap(),
         distance: Distance::Dot,
+        hnsw_config: None,
+        quantization_config: None,
+        on_disk: None,
+        datatype: None,
+        multivector_config: None,
     };
     let vector_params2 = VectorParams {
         size: NonZeroU64::new(4).unwrap(),
         distance: Distance::Dot,
+        hnsw_config: Some(HnswConfigDiff {
+            m: Some(32),
+            ef_construct: None,
+            full_scan_threshold: None,
+        }),
+        quantization_config: None,
+        on_disk: None,
+        datatype: None,
+        multivector_config: None,
     };
 
     let mut vectors_config = BTreeMap::new();
@@ -59,13 +85,17 @@ pub async fn multi_vec_collection_fixture(collection_path: &Path, shard_number:
         replication_factor: NonZeroU32::new(1).unwrap(),
         write_consistency_factor: NonZeroU32::new(1).unwrap(),
         on_disk_payload: false,
+        default_vector: default_vector.map(str::to_string),
     };
 
     let collection_config = CollectionConfig {
         params: collection_params,
-        optimizer_config: TEST_OPTIMIZERS_CONFIG.clone(),
+        optimizer_config,
         wal_config,
         hnsw_config: Default::default(),
+        payload_field_types: None,
+        auto_payload_index: None,
+        ttl: None,
     };
 
     let snapshot_path = collection_path.join("snapshots");
@@ -87,7 +117,13 @@ async fn test_multi_vec_with_shards(shard_number: u32) {
         .tempdir()
         .unwrap();
 
-    let mut collection = multi_vec_collection_fixture(collection_dir.path(), shard_number).await;
+    let mut collection = multi_vec_collection_fixture(
+        collection_dir.path(),
+        shard_number,
+        TEST_OPTIMIZERS_CONFIG.clone(),
+        None,
+    )
+    .await;
 
     // Upload 1000 random vectors to the collection
     let mut points = Vec::new();
@@ -106,7 +142,7 @@ async fn test_multi_vec_with_shards(shard_number: u32) {
         PointInsertOperations::PointsList(points),
     ));
     collection
-        .update_from_client(insert_points, true)
+        .update_from_client(insert_points, true, WriteOrdering::default())
         .await
         .unwrap();
 
@@ -229,9 +265,11 @@ async fn test_multi_vec_with_shards(shard_number: u32) {
                 filter: None,
                 params: None,
                 using: None,
+                lookup_from: None,
             },
             &Handle::current(),
             None,
+            &HashMap::new(),
         )
         .await;
 
@@ -256,9 +294,11 @@ async fn test_multi_vec_with_shards(shard_number: u32) {
                 filter: None,
                 params: None,
                 using: Some(VEC_NAME1.to_string().into()),
+                lookup_from: None,
             },
             &Handle::current(),
             None,
+            &HashMap::new(),
         )
         .await
         .unwrap();
@@ -276,3 +316,193 @@ async fn test_multi_vec_with_shards(shard_number: u32) {
 
     collection.before_drop().await;
 }
+
+/// Find HNSW config files of `vector_name` index in all segments of the collection
+fn find_hnsw_configs(dir: &Path, vector_name: &str) -> Vec<PathBuf> {
+    let index_dir_name = format!("vector_index-{}", vector_name);
+    let mut configs = vec![];
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if !path.is_dir() {
+            continue;
+        }
+        if path
+            .file_name()
+            .map_or(false, |name| name == index_dir_name.as_str())
+        {
+            let config_path = path.join("hnsw_config.json");
+            if config_path.exists() {
+                configs.push(config_path);
+            }
+        } else {
+            configs.extend(find_hnsw_configs(&path, vector_name));
+        }
+    }
+    configs
+}
+
+fn read_hnsw_m(config_path: &Path) -> u64 {
+    let config: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(config_path).unwrap()).unwrap();
+    config["m"].as_u64().unwrap()
+}
+
+#[tokio::test]
+async fn test_multi_vec_hnsw_config_override() {
+    let collection_dir = Builder::new()
+        .prefix("test_multi_vec_hnsw_config_override")
+        .tempdir()
+        .unwrap();
+
+    let optimizer_config = OptimizersConfig {
+        default_segment_number: 1,
+        indexing_threshold: 10,
+        ..TEST_OPTIMIZERS_CONFIG
+    };
+    let mut collection =
+        multi_vec_collection_fixture(collection_dir.path(), 1, optimizer_config, None).await;
+
+    let points = (0..1000)
+        .map(|i| {
+            let mut vectors = NamedVectors::default();
+            vectors.insert(VEC_NAME1.to_string(), vec![i as f32, 0.0, 0.0, 0.0]);
+            vectors.insert(VEC_NAME2.to_string(), vec![0.0, i as f32, 0.0, 0.0]);
+            PointStruct {
+                id: i.into(),
+                vector: vectors.into(),
+                payload: None,
+            }
+        })
+        .collect();
+    let insert_points = CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(
+        PointInsertOperations::PointsList(points),
+    ));
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    // Wait until both vectors of all points are indexed
+    let mut indexed = false;
+    for _ in 0..300 {
+        let info = collection.info(None).await.unwrap();
+        if info.indexed_vectors_count == 2000 {
+            indexed = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert!(indexed, "collection was not indexed");
+
+    let default_configs = find_hnsw_configs(collection_dir.path(), VEC_NAME1);
+    assert!(!default_configs.is_empty());
+    for config_path in default_configs {
+        assert_eq!(read_hnsw_m(&config_path), 16);
+    }
+
+    let custom_configs = find_hnsw_configs(collection_dir.path(), VEC_NAME2);
+    assert!(!custom_configs.is_empty());
+    for config_path in custom_configs {
+        assert_eq!(read_hnsw_m(&config_path), 32);
+    }
+
+    collection.before_drop().await;
+}
+
+#[tokio::test]
+async fn test_multi_vec_default_vector() {
+    let collection_dir = Builder::new()
+        .prefix("test_multi_vec_default_vector")
+        .tempdir()
+        .unwrap();
+
+    let mut collection = multi_vec_collection_fixture(
+        collection_dir.path(),
+        1,
+        TEST_OPTIMIZERS_CONFIG.clone(),
+        Some(VEC_NAME2),
+    )
+    .await;
+
+    let points = (0..100)
+        .map(|i| {
+            let mut vectors = NamedVectors::default();
+            vectors.insert(VEC_NAME1.to_string(), vec![i as f32, 0.0, 0.0, 0.0]);
+            vectors.insert(VEC_NAME2.to_string(), vec![0.0, (100 - i) as f32, 0.0, 0.0]);
+            PointStruct {
+                id: i.into(),
+                vector: vectors.into(),
+                payload: None,
+            }
+        })
+        .collect();
+    let insert_points = CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(
+        PointInsertOperations::PointsList(points),
+    ));
+    collection
+        .update_from_client(insert_points, true, WriteOrdering::default())
+        .await
+        .unwrap();
+
+    // Vector name is omitted - the default vector is searched
+    let search_request = SearchRequest {
+        vector: vec![0.0, 1.0, 0.0, 0.0].into(),
+        filter: None,
+        limit: 1,
+        offset: 0,
+        with_payload: None,
+        with_vector: None,
+        params: None,
+        score_threshold: None,
+    };
+    let result = collection
+        .search(search_request.clone(), &Handle::current(), None)
+        .await
+        .unwrap();
+    assert_eq!(result[0].id, 0.into());
+
+    let recommend_result = collection
+        .recommend_by(
+            RecommendRequest {
+                positive: vec![1.into()],
+                negative: vec![],
+                filter: None,
+                params: None,
+                limit: 1,
+                offset: 0,
+                with_payload: None,
+                with_vector: None,
+                score_threshold: None,
+                using: None,
+                lookup_from: None,
+            },
+            &Handle::current(),
+            None,
+            &HashMap::new(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(recommend_result[0].id, 0.into());
+
+    // Unknown vector names are reported with the names of existing vectors
+    let unknown_vector_request = SearchRequest {
+        vector: NamedVector {
+            name: "vec3".to_string(),
+            vector: vec![0.0, 1.0, 0.0, 0.0],
+        }
+        .into(),
+        ..search_request
+    };
+    match collection
+        .search(unknown_vector_request, &Handle::current(), None)
+        .await
+    {
+        Err(CollectionError::BadInput { description }) => {
+            assert!(description.contains(VEC_NAME1), "{description}");
+            assert!(description.contains(VEC_NAME2), "{description}");
+        }
+        result => panic!("unexpected result: {result:?}"),
+    }
+
+    collection.before_drop().await;
+}
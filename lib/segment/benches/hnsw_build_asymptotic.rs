@@ -53,7 +53,7 @@ fn hnsw_build_asymptotic(c: &mut Criterion) {
             let query = random_vector(&mut rng, DIM);
             let raw_scorer = vector_holder.get_raw_scorer(query);
             let scorer = FilteredScorer::new(&raw_scorer, Some(&fake_filter_context));
-            graph_layers.search(TOP, EF, scorer);
+            graph_layers.search(TOP, EF, scorer, None, None);
         })
     });
 
@@ -62,7 +62,7 @@ fn hnsw_build_asymptotic(c: &mut Criterion) {
         let query = random_vector(&mut rng, DIM);
         let raw_scorer = vector_holder.get_raw_scorer(query);
         let scorer = FilteredScorer::new(&raw_scorer, Some(&fake_filter_context));
-        graph_layers.search(TOP, EF, scorer);
+        graph_layers.search(TOP, EF, scorer, None, None);
     }
 
     let (vector_holder, graph_layers) = build_index::<CosineMetric>(NUM_VECTORS * 10);
@@ -73,7 +73,7 @@ fn hnsw_build_asymptotic(c: &mut Criterion) {
             let query = random_vector(&mut rng, DIM);
             let raw_scorer = vector_holder.get_raw_scorer(query);
             let scorer = FilteredScorer::new(&raw_scorer, Some(&fake_filter_context));
-            graph_layers.search(TOP, EF, scorer);
+            graph_layers.search(TOP, EF, scorer, None, None);
         })
     });
 
@@ -96,7 +96,7 @@ fn hnsw_build_asymptotic(c: &mut Criterion) {
         let query = random_vector(&mut rng, DIM);
         let raw_scorer = vector_holder.get_raw_scorer(query);
         let scorer = FilteredScorer::new(&raw_scorer, Some(&fake_filter_context));
-        graph_layers.search(TOP, EF, scorer);
+        graph_layers.search(TOP, EF, scorer, None, None);
     }
 }
 
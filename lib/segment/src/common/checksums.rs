@@ -0,0 +1,148 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::file_operations::{atomic_save_json, read_json};
+use crate::entry::entry_point::{OperationError, OperationResult};
+
+/// Extension of the file with checksums, stored next to the checked file
+pub const CHECKSUMS_EXTENSION: &str = "checksums";
+/// Size of the file chunk, covered by a single checksum
+const CHECKSUM_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Checksums of the fixed size chunks of a file.
+/// Used to detect silent corruption of the data files, which are not modified after they are written
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct FileChecksums {
+    pub chunk_size: usize,
+    pub file_size: u64,
+    pub chunks: Vec<u64>,
+}
+
+impl FileChecksums {
+    pub fn compute(path: &Path) -> OperationResult<Self> {
+        let mut file = File::open(path)?;
+        let mut buffer = vec![0u8; CHECKSUM_CHUNK_SIZE];
+        let mut checksums = FileChecksums {
+            chunk_size: CHECKSUM_CHUNK_SIZE,
+            file_size: 0,
+            chunks: vec![],
+        };
+        loop {
+            let chunk_len = read_chunk(&mut file, &mut buffer)?;
+            if chunk_len == 0 {
+                break;
+            }
+            checksums.file_size += chunk_len as u64;
+            checksums.chunks.push(seahash::hash(&buffer[..chunk_len]));
+        }
+        Ok(checksums)
+    }
+}
+
+/// Fill the buffer from the file, unless the end of the file is reached
+fn read_chunk(file: &mut File, buffer: &mut [u8]) -> OperationResult<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        let read = file.read(&mut buffer[filled..])?;
+        if read == 0 {
+            break;
+        }
+        filled += read;
+    }
+    Ok(filled)
+}
+
+pub fn checksums_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".");
+    file_name.push(CHECKSUMS_EXTENSION);
+    path.with_file_name(file_name)
+}
+
+/// Compute and store checksums of the file. Should be called each time the file is changed
+pub fn save_checksums(path: &Path) -> OperationResult<()> {
+    let checksums = FileChecksums::compute(path)?;
+    atomic_save_json(&checksums_path(path), &checksums)?;
+    Ok(())
+}
+
+/// Check the file against the stored checksums.
+/// Files without stored checksums, e.g. written by older versions, are not checked
+pub fn verify_checksums(path: &Path) -> OperationResult<()> {
+    let stored_path = checksums_path(path);
+    if !stored_path.exists() {
+        return Ok(());
+    }
+    let stored: FileChecksums = read_json(&stored_path)?;
+    let actual = FileChecksums::compute(path)?;
+
+    if stored.file_size != actual.file_size {
+        return Err(OperationError::service_error(&format!(
+            "Data corruption detected in {}: expected size {}, got {}",
+            path.display(),
+            stored.file_size,
+            actual.file_size
+        )));
+    }
+    let mismatch = stored
+        .chunks
+        .iter()
+        .zip(&actual.chunks)
+        .position(|(stored, actual)| stored != actual);
+    if let Some(chunk) = mismatch {
+        return Err(OperationError::service_error(&format!(
+            "Data corruption detected in {}: checksum mismatch at offset {}",
+            path.display(),
+            chunk * stored.chunk_size
+        )));
+    }
+    Ok(())
+}
+
+/// Check all files with stored checksums in the directory and its subdirectories
+pub fn verify_dir_checksums(dir: &Path) -> OperationResult<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            verify_dir_checksums(&path)?;
+        } else if path.extension().and_then(|ext| ext.to_str()) == Some(CHECKSUMS_EXTENSION) {
+            verify_checksums(&path.with_extension(""))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use tempfile::Builder;
+
+    use super::*;
+
+    #[test]
+    fn test_checksums_detect_corruption() {
+        let dir = Builder::new().prefix("checksums").tempdir().unwrap();
+        let path = dir.path().join("vectors.bin");
+        let data = vec![7u8; CHECKSUM_CHUNK_SIZE * 2 + 10];
+        File::create(&path).unwrap().write_all(&data).unwrap();
+
+        // Not checked before checksums are stored
+        verify_checksums(&path).unwrap();
+
+        save_checksums(&path).unwrap();
+        let checksums: FileChecksums = read_json(&checksums_path(&path)).unwrap();
+        assert_eq!(checksums.chunks.len(), 3);
+        assert_eq!(checksums.file_size, data.len() as u64);
+        verify_dir_checksums(dir.path()).unwrap();
+
+        let mut corrupted = data;
+        corrupted[CHECKSUM_CHUNK_SIZE + 1] = 0;
+        File::create(&path).unwrap().write_all(&corrupted).unwrap();
+        assert!(verify_checksums(&path).is_err());
+        assert!(verify_dir_checksums(dir.path()).is_err());
+    }
+}
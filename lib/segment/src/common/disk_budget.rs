@@ -0,0 +1,116 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use parking_lot::{const_mutex, Condvar, Mutex};
+
+/// Identifier of the disk, which files are stored on
+type DiskId = u64;
+
+/// Searches over the data, stored on disk, page-fault on every cold read.
+/// Too many of them at once saturate the queue of the disk, and latency of all searches collapses,
+/// so the number of concurrent searches per disk is limited. 0 - not limited.
+static MAX_SEARCHES_PER_DISK: AtomicUsize = AtomicUsize::new(0);
+
+static DISK_PERMITS: Mutex<BTreeMap<DiskId, Arc<DiskPermits>>> = const_mutex(BTreeMap::new());
+
+/// Set the max number of concurrent searches over the data of a single disk.
+/// If `None` - searches are not limited.
+pub fn set_global_disk_budget(max_searches_per_disk: Option<usize>) {
+    MAX_SEARCHES_PER_DISK.store(max_searches_per_disk.unwrap_or(0), Ordering::Relaxed);
+}
+
+pub fn is_disk_budget_enabled() -> bool {
+    MAX_SEARCHES_PER_DISK.load(Ordering::Relaxed) > 0
+}
+
+#[derive(Default)]
+struct DiskPermits {
+    in_use: Mutex<usize>,
+    released: Condvar,
+}
+
+/// Permit to search over the data of a disk. Released on drop
+pub struct DiskPermit {
+    permits: Arc<DiskPermits>,
+}
+
+impl Drop for DiskPermit {
+    fn drop(&mut self) {
+        *self.permits.in_use.lock() -= 1;
+        self.permits.released.notify_one();
+    }
+}
+
+#[cfg(unix)]
+fn disk_id(path: &Path) -> DiskId {
+    use std::os::unix::fs::MetadataExt;
+    path.metadata().map(|metadata| metadata.dev()).unwrap_or(0)
+}
+
+#[cfg(not(unix))]
+fn disk_id(_path: &Path) -> DiskId {
+    0
+}
+
+/// Wait for a permit to search over the data, stored in `path`.
+/// Returns `None` without waiting, if the budget is not enabled.
+pub fn acquire_disk_permit(path: &Path) -> Option<DiskPermit> {
+    let max_searches = MAX_SEARCHES_PER_DISK.load(Ordering::Relaxed);
+    if max_searches == 0 {
+        return None;
+    }
+    let permits = DISK_PERMITS
+        .lock()
+        .entry(disk_id(path))
+        .or_default()
+        .clone();
+    {
+        let mut in_use = permits.in_use.lock();
+        while *in_use >= max_searches {
+            permits.released.wait(&mut in_use);
+        }
+        *in_use += 1;
+    }
+    Some(DiskPermit { permits })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+    use std::thread;
+    use std::time::Duration;
+
+    use tempfile::Builder;
+
+    use super::*;
+
+    #[test]
+    fn test_disk_permits() {
+        let dir = Builder::new().prefix("disk_budget").tempdir().unwrap();
+
+        set_global_disk_budget(Some(1));
+        let permit = acquire_disk_permit(dir.path());
+        assert!(permit.is_some());
+
+        let acquired = Arc::new(AtomicBool::new(false));
+        let waiter = {
+            let path = dir.path().to_owned();
+            let acquired = acquired.clone();
+            thread::spawn(move || {
+                let _permit = acquire_disk_permit(&path);
+                acquired.store(true, Ordering::SeqCst);
+            })
+        };
+        thread::sleep(Duration::from_millis(50));
+        assert!(!acquired.load(Ordering::SeqCst));
+
+        drop(permit);
+        waiter.join().unwrap();
+        assert!(acquired.load(Ordering::SeqCst));
+
+        set_global_disk_budget(None);
+        assert!(acquire_disk_permit(dir.path()).is_none());
+    }
+}
@@ -0,0 +1,88 @@
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+
+use crate::entry::entry_point::{OperationError, OperationResult};
+
+/// Size of the storage encryption key in bytes
+pub const STORAGE_KEY_SIZE: usize = 32;
+const NONCE_SIZE: usize = 12;
+
+/// AES-256-GCM cipher for records of encrypted storages.
+/// Each encrypted record is stored as a random nonce followed by the ciphertext.
+#[derive(Clone)]
+pub struct StorageCipher {
+    cipher: Arc<Aes256Gcm>,
+}
+
+impl Debug for StorageCipher {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StorageCipher").finish_non_exhaustive()
+    }
+}
+
+impl StorageCipher {
+    pub fn new(key: &[u8]) -> OperationResult<Self> {
+        let cipher = Aes256Gcm::new_from_slice(key).map_err(|_| {
+            OperationError::service_error(&format!(
+                "encryption key must be {STORAGE_KEY_SIZE} bytes long, got {}",
+                key.len()
+            ))
+        })?;
+        Ok(Self {
+            cipher: Arc::new(cipher),
+        })
+    }
+
+    pub fn encrypt(&self, data: &[u8]) -> OperationResult<Vec<u8>> {
+        let nonce_bytes: [u8; NONCE_SIZE] = rand::random();
+        let encrypted = self
+            .cipher
+            .encrypt(Nonce::from_slice(&nonce_bytes), data)
+            .map_err(|err| {
+                OperationError::service_error(&format!("can't encrypt record: {err}"))
+            })?;
+        let mut record = Vec::with_capacity(NONCE_SIZE + encrypted.len());
+        record.extend_from_slice(&nonce_bytes);
+        record.extend_from_slice(&encrypted);
+        Ok(record)
+    }
+
+    pub fn decrypt(&self, record: &[u8]) -> OperationResult<Vec<u8>> {
+        if record.len() < NONCE_SIZE {
+            return Err(OperationError::service_error(
+                "encrypted record is too short",
+            ));
+        }
+        let (nonce_bytes, encrypted) = record.split_at(NONCE_SIZE);
+        self.cipher
+            .decrypt(Nonce::from_slice(nonce_bytes), encrypted)
+            .map_err(|_| {
+                OperationError::service_error("can't decrypt record, probably wrong encryption key")
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_storage_cipher() {
+        let cipher = StorageCipher::new(&[7; STORAGE_KEY_SIZE]).unwrap();
+        let record = cipher.encrypt(b"payload").unwrap();
+        assert_ne!(&record[NONCE_SIZE..], b"payload");
+        assert_eq!(cipher.decrypt(&record).unwrap(), b"payload");
+
+        // Same data is encrypted with different nonces
+        assert_ne!(cipher.encrypt(b"payload").unwrap(), record);
+
+        let wrong_cipher = StorageCipher::new(&[8; STORAGE_KEY_SIZE]).unwrap();
+        assert!(wrong_cipher.decrypt(&record).is_err());
+        assert!(cipher.decrypt(&record[..NONCE_SIZE - 1]).is_err());
+
+        assert!(StorageCipher::new(&[0; 16]).is_err());
+    }
+}
@@ -0,0 +1,121 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+
+use parking_lot::{const_mutex, Condvar, Mutex};
+
+use crate::entry::entry_point::{OperationError, OperationResult};
+
+const BYTES_IN_MB: usize = 1024 * 1024;
+
+/// How often waiting builders check, if they are cancelled
+const CANCELLATION_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Memory, which all index builders of the node may take at once, in bytes. 0 - not limited.
+///
+/// Graph builders allocate links and visited lists for every point of the segment,
+/// so several large segments, optimized simultaneously, could run the node out of memory.
+static INDEXING_MEMORY_BUDGET: AtomicUsize = AtomicUsize::new(0);
+
+/// Memory, reserved by the running builders, in bytes
+static INDEXING_MEMORY_IN_USE: Mutex<usize> = const_mutex(0);
+
+static INDEXING_MEMORY_RELEASED: Condvar = Condvar::new();
+
+/// Set the max memory, taken by the index builders of the node at once.
+/// If `None` - builders are not limited.
+pub fn set_global_indexing_memory_budget(max_memory_mb: Option<usize>) {
+    INDEXING_MEMORY_BUDGET.store(
+        max_memory_mb.unwrap_or(0).saturating_mul(BYTES_IN_MB),
+        Ordering::Relaxed,
+    );
+    INDEXING_MEMORY_RELEASED.notify_all();
+}
+
+/// Memory budget of the index builders in bytes, if it is enabled
+pub fn indexing_memory_budget() -> Option<usize> {
+    match INDEXING_MEMORY_BUDGET.load(Ordering::Relaxed) {
+        0 => None,
+        budget => Some(budget),
+    }
+}
+
+/// Memory, reserved by a running builder. Released on drop
+pub struct IndexingMemoryPermit {
+    bytes: usize,
+}
+
+impl Drop for IndexingMemoryPermit {
+    fn drop(&mut self) {
+        *INDEXING_MEMORY_IN_USE.lock() -= self.bytes;
+        INDEXING_MEMORY_RELEASED.notify_all();
+    }
+}
+
+/// Wait until `bytes` of the budget are free and reserve them.
+/// A builder, which needs more than the whole budget, waits until it is the only running one.
+/// Returns `None` without waiting, if the budget is not enabled.
+pub fn acquire_indexing_memory(
+    bytes: usize,
+    stopped: &AtomicBool,
+) -> OperationResult<Option<IndexingMemoryPermit>> {
+    let mut in_use = INDEXING_MEMORY_IN_USE.lock();
+    loop {
+        let budget = match indexing_memory_budget() {
+            Some(budget) => budget,
+            None => return Ok(None),
+        };
+        if *in_use == 0 || in_use.saturating_add(bytes) <= budget {
+            break;
+        }
+        if stopped.load(Ordering::Relaxed) {
+            return Err(OperationError::Cancelled {
+                description: "Cancelled while waiting for indexing memory".to_string(),
+            });
+        }
+        INDEXING_MEMORY_RELEASED.wait_for(&mut in_use, CANCELLATION_CHECK_INTERVAL);
+    }
+    *in_use += bytes;
+    Ok(Some(IndexingMemoryPermit { bytes }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn test_indexing_memory_budget() {
+        set_global_indexing_memory_budget(Some(2));
+        let stopped = AtomicBool::new(false);
+        let first = acquire_indexing_memory(BYTES_IN_MB, &stopped).unwrap();
+        assert!(first.is_some());
+        // Builder larger than the budget is not blocked forever, but waits for the running ones
+        let acquired = Arc::new(AtomicBool::new(false));
+        let waiter = {
+            let acquired = acquired.clone();
+            thread::spawn(move || {
+                let _permit = acquire_indexing_memory(3 * BYTES_IN_MB, &AtomicBool::new(false));
+                acquired.store(true, Ordering::SeqCst);
+            })
+        };
+        thread::sleep(Duration::from_millis(50));
+        assert!(!acquired.load(Ordering::SeqCst));
+
+        drop(first);
+        waiter.join().unwrap();
+        assert!(acquired.load(Ordering::SeqCst));
+
+        // Cancelled builder stops waiting
+        let running = acquire_indexing_memory(BYTES_IN_MB, &stopped).unwrap();
+        stopped.store(true, Ordering::SeqCst);
+        assert!(acquire_indexing_memory(2 * BYTES_IN_MB, &stopped).is_err());
+        drop(running);
+
+        set_global_indexing_memory_budget(None);
+        assert!(acquire_indexing_memory(BYTES_IN_MB, &stopped)
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,160 @@
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Assumed size of the memory page, used to touch every page of the mapping
+const PAGE_SIZE: usize = 4096;
+
+/// Expected access pattern of the memory-mapped files, passed to the kernel with `madvise`
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MmapAdvice {
+    /// Default readahead of the kernel
+    #[default]
+    Normal,
+    /// No readahead. Suits random reads of the vectors by the HNSW search,
+    /// which don't need neighbouring pages
+    Random,
+    /// Kernel starts reading the whole file into the page cache in background
+    WillNeed,
+}
+
+impl MmapAdvice {
+    fn to_u8(self) -> u8 {
+        match self {
+            MmapAdvice::Normal => 0,
+            MmapAdvice::Random => 1,
+            MmapAdvice::WillNeed => 2,
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => MmapAdvice::Random,
+            2 => MmapAdvice::WillNeed,
+            _ => MmapAdvice::Normal,
+        }
+    }
+}
+
+static GLOBAL_MMAP_ADVICE: AtomicU8 = AtomicU8::new(0);
+static GLOBAL_MMAP_POPULATE: AtomicBool = AtomicBool::new(false);
+
+/// Set the advice for all mmap files, opened afterwards.
+/// If `populate` is true - pages of the opened files are read into the page cache right away,
+/// so the first searches after restart don't wait for the disk.
+pub fn set_global_mmap_advice(advice: MmapAdvice, populate: bool) {
+    GLOBAL_MMAP_ADVICE.store(advice.to_u8(), Ordering::Relaxed);
+    GLOBAL_MMAP_POPULATE.store(populate, Ordering::Relaxed);
+}
+
+pub fn global_mmap_advice() -> MmapAdvice {
+    MmapAdvice::from_u8(GLOBAL_MMAP_ADVICE.load(Ordering::Relaxed))
+}
+
+pub fn global_mmap_populate() -> bool {
+    GLOBAL_MMAP_POPULATE.load(Ordering::Relaxed)
+}
+
+/// Pass the advice for the mapped memory to the kernel. The advice is only a hint,
+/// so platforms without `madvise` ignore it.
+pub fn madvise(data: &[u8], advice: MmapAdvice) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        if data.is_empty() {
+            return Ok(());
+        }
+        let advice = match advice {
+            MmapAdvice::Normal => libc::MADV_NORMAL,
+            MmapAdvice::Random => libc::MADV_RANDOM,
+            MmapAdvice::WillNeed => libc::MADV_WILLNEED,
+        };
+        // Mapped memory is page-aligned, as `madvise` requires
+        let res = unsafe { libc::madvise(data.as_ptr() as *mut libc::c_void, data.len(), advice) };
+        if res != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (data, advice);
+    }
+    Ok(())
+}
+
+/// Read every page of the mapped memory, so it is loaded into the page cache.
+/// Blocks until all pages are read from the disk.
+pub fn populate(data: &[u8]) {
+    let mut checksum = 0u8;
+    for offset in (0..data.len()).step_by(PAGE_SIZE) {
+        // Volatile read can't be optimized away
+        checksum ^= unsafe { std::ptr::read_volatile(data.as_ptr().add(offset)) };
+    }
+    log::trace!(
+        "Populated {} bytes of mmap, checksum {checksum}",
+        data.len()
+    );
+}
+
+/// Fraction of the pages of the mapped memory, which are loaded into the page cache,
+/// so reading them doesn't wait for the disk. Platforms without `mincore` report nothing resident.
+pub fn resident_fraction(data: &[u8]) -> f64 {
+    if data.is_empty() {
+        return 1.0;
+    }
+    #[cfg(unix)]
+    {
+        let pages = data.len().div_ceil(PAGE_SIZE);
+        let mut residency = vec![0u8; pages];
+        // Mapped memory is page-aligned, as `mincore` requires
+        let res = unsafe {
+            libc::mincore(
+                data.as_ptr() as *mut libc::c_void,
+                data.len(),
+                residency.as_mut_ptr() as _,
+            )
+        };
+        if res != 0 {
+            return 0.0;
+        }
+        let resident = residency.iter().filter(|page| *page & 1 == 1).count();
+        resident as f64 / pages as f64
+    }
+    #[cfg(not(unix))]
+    {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_madvise_and_populate() {
+        let data = vec![1u8; 3 * PAGE_SIZE + 1];
+        populate(&data);
+        populate(&[]);
+        // Heap memory is not guaranteed to be page-aligned, so only empty data is advised
+        madvise(&[], MmapAdvice::Random).unwrap();
+
+        for advice in [MmapAdvice::Normal, MmapAdvice::Random, MmapAdvice::WillNeed] {
+            assert_eq!(MmapAdvice::from_u8(advice.to_u8()), advice);
+        }
+    }
+
+    #[test]
+    fn test_resident_fraction() {
+        assert_eq!(resident_fraction(&[]), 1.0);
+
+        let dir = tempfile::Builder::new().prefix("mmap").tempdir().unwrap();
+        let path = dir.path().join("data.bin");
+        std::fs::write(&path, vec![1u8; 4 * PAGE_SIZE]).unwrap();
+        let file = std::fs::File::open(&path).unwrap();
+        let mmap = unsafe { memmap::MmapOptions::new().map(&file).unwrap() };
+        populate(&mmap);
+        #[cfg(unix)]
+        assert_eq!(resident_fraction(&mmap), 1.0);
+    }
+}
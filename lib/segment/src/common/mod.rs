@@ -1,15 +1,21 @@
 pub mod anonymize;
 pub mod arc_atomic_ref_cell_iterator;
+pub mod checksums;
+pub mod disk_budget;
+pub mod encryption;
 pub mod error_logging;
 pub mod file_operations;
+pub mod indexing_budget;
+pub mod mmap_advice;
 pub mod operation_time_statistics;
 pub mod rocksdb_wrapper;
 pub mod utils;
 pub mod version;
 
 use crate::data_types::named_vectors::NamedVectors;
+use crate::data_types::vectors::VectorElementType;
 use crate::entry::entry_point::{OperationError, OperationResult};
-use crate::types::SegmentConfig;
+use crate::types::{SegmentConfig, VectorDataConfig};
 
 pub type Flusher = Box<dyn FnOnce() -> OperationResult<()> + Send>;
 
@@ -44,3 +50,32 @@ pub fn check_vectors_set(
 
     Ok(())
 }
+
+/// Check that all elements of the vector could be stored as bytes without loss
+pub fn check_byte_vector(vector: &[VectorElementType]) -> OperationResult<()> {
+    match vector
+        .iter()
+        .find(|element| element.fract() != 0.0 || !(0.0..=255.0).contains(*element))
+    {
+        Some(element) => Err(OperationError::WrongByteVectorElement { element: *element }),
+        None => Ok(()),
+    }
+}
+
+/// Check the length of the vector. Multivectors are flattened, so their length should be
+/// a positive multiple of the size
+pub fn check_vector_len(vector_len: usize, config: &VectorDataConfig) -> OperationResult<()> {
+    match config.multivector_config {
+        None if vector_len != config.size => Err(OperationError::WrongVector {
+            expected_dim: config.size,
+            received_dim: vector_len,
+        }),
+        Some(_) if vector_len == 0 || vector_len % config.size != 0 => {
+            Err(OperationError::WrongMultiVector {
+                dim: config.size,
+                received_len: vector_len,
+            })
+        }
+        _ => Ok(()),
+    }
+}
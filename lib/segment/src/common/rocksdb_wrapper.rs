@@ -3,8 +3,9 @@ use std::sync::Arc;
 
 use parking_lot::RwLock;
 //use atomic_refcell::{AtomicRef, AtomicRefCell};
-use rocksdb::{ColumnFamily, LogLevel, Options, WriteOptions, DB};
+use rocksdb::{ColumnFamily, LogLevel, Options, ReadOptions, WriteOptions, DB};
 
+use crate::common::encryption::StorageCipher;
 use crate::common::Flusher;
 //use crate::common::arc_rwlock_iterator::ArcRwLockIterator;
 use crate::entry::entry_point::{OperationError, OperationResult};
@@ -21,17 +22,21 @@ pub const DB_VERSIONS_CF: &str = "version";
 pub struct DatabaseColumnWrapper {
     pub database: Arc<RwLock<DB>>,
     pub column_name: String,
+    /// Values of the column are encrypted with this cipher, if it is set
+    cipher: Option<StorageCipher>,
 }
 
 pub struct DatabaseColumnIterator<'a> {
     pub handle: &'a ColumnFamily,
     pub iter: rocksdb::DBRawIterator<'a>,
     pub just_seeked: bool,
+    cipher: Option<&'a StorageCipher>,
 }
 
 pub struct LockedDatabaseColumnWrapper<'a> {
     guard: parking_lot::RwLockReadGuard<'a, DB>,
     column_name: &'a str,
+    cipher: Option<&'a StorageCipher>,
 }
 
 pub fn db_options() -> Options {
@@ -77,6 +82,36 @@ pub fn open_db_with_existing_cf(path: &Path) -> Result<Arc<RwLock<DB>>, rocksdb:
     Ok(Arc::new(RwLock::new(db)))
 }
 
+/// Read all column families of the database, verifying checksums of the data blocks
+pub fn verify_db(db: &DB, path: &Path) -> OperationResult<()> {
+    let column_families = DB::list_cf(&db_options(), path)
+        .map_err(|err| OperationError::service_error(&format!("RocksDB list_cf error: {}", err)))?;
+    for column_name in column_families {
+        let handle = match db.cf_handle(&column_name) {
+            Some(handle) => handle,
+            None => continue,
+        };
+        let mut read_options = ReadOptions::default();
+        read_options.set_verify_checksums(true);
+        // Do not evict hot data from the cache with a full scan
+        read_options.fill_cache(false);
+        let mut iter = db.raw_iterator_cf_opt(&handle, read_options);
+        iter.seek_to_first();
+        while iter.valid() {
+            iter.next();
+        }
+        iter.status().map_err(|err| {
+            OperationError::service_error(&format!(
+                "Data corruption detected in column family {} of {}: {}",
+                column_name,
+                path.display(),
+                err
+            ))
+        })?;
+    }
+    Ok(())
+}
+
 pub fn db_write_options() -> WriteOptions {
     let mut write_options = WriteOptions::default();
     write_options.set_sync(false);
@@ -108,9 +143,20 @@ pub fn recreate_cf(db: Arc<RwLock<DB>>, store_cf_name: &str) -> Result<(), rocks
 
 impl DatabaseColumnWrapper {
     pub fn new(database: Arc<RwLock<DB>>, column_name: &str) -> Self {
+        Self::new_with_cipher(database, column_name, None)
+    }
+
+    /// Wrap column, which values are encrypted with `cipher`, if it is provided.
+    /// Keys are stored as is.
+    pub fn new_with_cipher(
+        database: Arc<RwLock<DB>>,
+        column_name: &str,
+        cipher: Option<StorageCipher>,
+    ) -> Self {
         Self {
             database,
             column_name: column_name.to_string(),
+            cipher,
         }
     }
 
@@ -119,6 +165,14 @@ impl DatabaseColumnWrapper {
         K: AsRef<[u8]>,
         V: AsRef<[u8]>,
     {
+        let encrypted;
+        let value = match &self.cipher {
+            Some(cipher) => {
+                encrypted = cipher.encrypt(value.as_ref())?;
+                encrypted.as_slice()
+            }
+            None => value.as_ref(),
+        };
         let db = self.database.read();
         let cf_handle = self.get_column_family(&db)?;
         db.put_cf_opt(cf_handle, key, value, &Self::get_write_options())
@@ -134,12 +188,14 @@ impl DatabaseColumnWrapper {
     {
         let db = self.database.read();
         let cf_handle = self.get_column_family(&db)?;
-        let result = db
-            .get_pinned_cf(cf_handle, key)
-            .map_err(|err| {
-                OperationError::service_error(&format!("RocksDB get_pinned_cf error: {}", err))
-            })?
-            .map(|value| f(&value));
+        let value = db.get_pinned_cf(cf_handle, key).map_err(|err| {
+            OperationError::service_error(&format!("RocksDB get_pinned_cf error: {}", err))
+        })?;
+        let result = match (value, &self.cipher) {
+            (None, _) => None,
+            (Some(value), None) => Some(f(&value)),
+            (Some(value), Some(cipher)) => Some(f(&cipher.decrypt(&value)?)),
+        };
         Ok(result)
     }
 
@@ -159,6 +215,7 @@ impl DatabaseColumnWrapper {
         LockedDatabaseColumnWrapper {
             guard: self.database.read(),
             column_name: &self.column_name,
+            cipher: self.cipher.as_ref(),
         }
     }
 
@@ -234,7 +291,9 @@ impl DatabaseColumnWrapper {
 
 impl<'a> LockedDatabaseColumnWrapper<'a> {
     pub fn iter(&self) -> OperationResult<DatabaseColumnIterator> {
-        DatabaseColumnIterator::new(&self.guard, self.column_name)
+        let mut iter = DatabaseColumnIterator::new(&self.guard, self.column_name)?;
+        iter.cipher = self.cipher;
+        Ok(iter)
     }
 }
 
@@ -252,12 +311,20 @@ impl<'a> DatabaseColumnIterator<'a> {
             handle,
             iter,
             just_seeked: true,
+            cipher: None,
         })
     }
+
+    /// Move the iterator to the first key, which is not less than `key`
+    pub fn seek(&mut self, key: &[u8]) {
+        self.iter.seek(key);
+        self.just_seeked = true;
+    }
 }
 
 impl<'a> Iterator for DatabaseColumnIterator<'a> {
-    type Item = (Box<[u8]>, Box<[u8]>);
+    /// Values are decrypted, if the column is encrypted
+    type Item = OperationResult<(Box<[u8]>, Box<[u8]>)>;
 
     fn next(&mut self) -> Option<Self::Item> {
         if !self.iter.valid() {
@@ -274,10 +341,13 @@ impl<'a> Iterator for DatabaseColumnIterator<'a> {
 
         if self.iter.valid() {
             // .key() and .value() only ever return None if valid == false, which we've just checked
-            Some((
-                Box::from(self.iter.key().unwrap()),
-                Box::from(self.iter.value().unwrap()),
-            ))
+            let key = Box::from(self.iter.key().unwrap());
+            let value = self.iter.value().unwrap();
+            let value = match self.cipher {
+                Some(cipher) => cipher.decrypt(value).map(Box::from),
+                None => Ok(Box::from(value)),
+            };
+            Some(value.map(|value| (key, value)))
         } else {
             None
         }
@@ -46,6 +46,30 @@ pub fn remove_value_from_json_map(
     }
 }
 
+/// Set value by the dot-separated path, creating missing intermediate objects.
+/// Intermediate values, which are not objects, are replaced.
+pub fn set_value_to_json_map(path: &str, map: &mut serde_json::Map<String, Value>, value: Value) {
+    match path.split_once('.') {
+        Some((element, new_path)) if !new_path.is_empty() => {
+            let nested = map
+                .entry(element)
+                .or_insert_with(|| Value::Object(Default::default()));
+            if !nested.is_object() {
+                *nested = Value::Object(Default::default());
+            }
+            if let Value::Object(nested_map) = nested {
+                set_value_to_json_map(new_path, nested_map, value);
+            }
+        }
+        Some((element, _)) => {
+            map.insert(element.to_string(), value);
+        }
+        None => {
+            map.insert(path.to_string(), value);
+        }
+    }
+}
+
 pub fn transpose_map_into_named_vector(
     map: HashMap<String, Vec<Vec<VectorElementType>>>,
 ) -> Vec<NamedVectors<'static>> {
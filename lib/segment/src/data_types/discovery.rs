@@ -0,0 +1,100 @@
+use crate::data_types::vectors::VectorElementType;
+use crate::types::{Distance, ScoreType};
+
+/// Pair of the context examples. A vector is in the preferred zone of the pair, if it is closer
+/// to the positive example than to the negative one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContextPair {
+    pub positive: Vec<VectorElementType>,
+    pub negative: Vec<VectorElementType>,
+}
+
+/// Query of the discovery search: points are ranked by the number of context pairs, whose
+/// preferred zones they are in, and then by the similarity to the target.
+///
+/// All vectors are expected to be preprocessed with the distance of the vector storage.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscoveryQuery {
+    pub target: Vec<VectorElementType>,
+    pub pairs: Vec<ContextPair>,
+}
+
+impl DiscoveryQuery {
+    /// Rank of the vector: number of the pairs, whose preferred zone the vector is in,
+    /// minus the number of the other pairs
+    pub fn rank(&self, vector: &[VectorElementType], distance: Distance) -> i32 {
+        rank_by_pairs(self.pairs.iter().map(|pair| {
+            (
+                distance.similarity(vector, &pair.positive),
+                distance.similarity(vector, &pair.negative),
+            )
+        }))
+    }
+
+    /// Score of the vector, larger is better. Integer part is the rank, the similarity to the
+    /// target is squashed into `(0, 1)`, so it only orders the vectors of the same rank.
+    pub fn score(&self, vector: &[VectorElementType], distance: Distance) -> ScoreType {
+        let similarity = distance.similarity(vector, &self.target);
+        discovery_score(self.rank(vector, distance), similarity)
+    }
+}
+
+/// Rank of the point by its similarities to the positive and the negative example of each pair
+pub fn rank_by_pairs(similarities: impl IntoIterator<Item = (ScoreType, ScoreType)>) -> i32 {
+    similarities
+        .into_iter()
+        .map(|(positive, negative)| if positive > negative { 1 } else { -1 })
+        .sum()
+}
+
+/// Score of the point of the given `rank`, see [`DiscoveryQuery::score`]
+pub fn discovery_score(rank: i32, target_similarity: ScoreType) -> ScoreType {
+    rank as ScoreType + sigmoid(target_similarity)
+}
+
+fn sigmoid(x: ScoreType) -> ScoreType {
+    1.0 / (1.0 + (-x).exp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discovery_score() {
+        let query = DiscoveryQuery {
+            target: vec![1.0, 0.0],
+            pairs: vec![
+                ContextPair {
+                    positive: vec![0.0, 1.0],
+                    negative: vec![0.0, -1.0],
+                },
+                ContextPair {
+                    positive: vec![-1.0, 0.0],
+                    negative: vec![1.0, 0.0],
+                },
+            ],
+        };
+
+        // Preferred by both pairs, but far from the target
+        let preferred = [-1.0, 1.0];
+        // Closest to the target, but preferred by the first pair only
+        let nearest = [1.0, 0.1];
+        // Not preferred by any pair
+        let avoided = [0.0, -1.0];
+
+        assert_eq!(query.rank(&preferred, Distance::Dot), 2);
+        assert_eq!(query.rank(&nearest, Distance::Dot), 0);
+        assert_eq!(query.rank(&avoided, Distance::Dot), -2);
+
+        let score = |vector: &[VectorElementType]| query.score(vector, Distance::Dot);
+        assert!(score(&preferred) > score(&nearest));
+        assert!(score(&nearest) > score(&avoided));
+
+        // Similarity to the target orders the vectors of the same rank
+        assert!(score(&[1.0, 0.5]) > score(&[0.5, 0.5]));
+
+        // Euclid similarity is the negative distance
+        assert_eq!(query.rank(&preferred, Distance::Euclid), 2);
+    }
+}
@@ -0,0 +1,251 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::types::IntPayloadType;
+
+/// Value of the payload field, which could be counted in facets
+#[derive(
+    Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Eq, Hash, PartialOrd, Ord,
+)]
+#[serde(untagged)]
+pub enum FacetValue {
+    Keyword(String),
+    Integer(IntPayloadType),
+}
+
+impl FacetValue {
+    /// Convert payload value into facet value. Only strings and integers are counted.
+    pub fn from_payload(value: &Value) -> Option<Self> {
+        match value {
+            Value::String(keyword) => Some(FacetValue::Keyword(keyword.clone())),
+            Value::Number(number) => number.as_i64().map(FacetValue::Integer),
+            _ => None,
+        }
+    }
+}
+
+/// Value of the payload field and the approximate number of points with this value
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub struct FacetValueHit {
+    pub value: FacetValue,
+    pub count: usize,
+}
+
+/// Space-Saving sketch of the most frequent values.
+///
+/// Keeps at most `capacity` counters. If a new value does not fit, it replaces the value with
+/// the smallest counter and inherits its count. So counts are never underestimated, and values
+/// with frequency higher than `total / capacity` are guaranteed to be kept.
+///
+/// Counters are also kept ordered by count, so the smallest one is found in `O(log capacity)`.
+/// Among the smallest counters, the largest value is evicted first.
+#[derive(Debug, Clone)]
+pub struct FacetSketch {
+    capacity: usize,
+    counters: HashMap<FacetValue, usize>,
+    by_count: BTreeSet<(usize, Reverse<FacetValue>)>,
+}
+
+impl FacetSketch {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            counters: HashMap::new(),
+            by_count: BTreeSet::new(),
+        }
+    }
+
+    pub fn add(&mut self, value: FacetValue) {
+        self.add_count(value, 1);
+    }
+
+    fn add_count(&mut self, value: FacetValue, count: usize) {
+        if let Some(counter) = self.counters.get_mut(&value) {
+            let value = Reverse(value);
+            self.by_count.remove(&(*counter, value.clone()));
+            *counter += count;
+            self.by_count.insert((*counter, value));
+            return;
+        }
+        if self.counters.len() < self.capacity {
+            self.by_count.insert((count, Reverse(value.clone())));
+            self.counters.insert(value, count);
+            return;
+        }
+        let min = self
+            .by_count
+            .iter()
+            .next()
+            .cloned()
+            .expect("Sketch capacity is not zero");
+        self.by_count.remove(&min);
+        let (min_count, Reverse(min_value)) = min;
+        self.counters.remove(&min_value);
+        self.by_count
+            .insert((min_count + count, Reverse(value.clone())));
+        self.counters.insert(value, min_count + count);
+    }
+
+    /// Combine counters of the sketch, built over a disjoint set of points
+    pub fn merge(&mut self, other: FacetSketch) {
+        for (value, count) in other.counters {
+            self.add_count(value, count);
+        }
+    }
+
+    /// Most frequent values, sorted by count in descending order
+    pub fn top(self, limit: usize) -> Vec<FacetValueHit> {
+        let mut hits: Vec<_> = self
+            .counters
+            .into_iter()
+            .map(|(value, count)| FacetValueHit { value, count })
+            .collect();
+        sort_hits(&mut hits);
+        hits.truncate(limit);
+        hits
+    }
+}
+
+/// Sort by count in descending order. Values with the same count are sorted by value.
+pub fn sort_hits(hits: &mut [FacetValueHit]) {
+    hits.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+}
+
+/// Sum counts of the same values, returned from different shards
+pub fn merge_hits(
+    hits: impl IntoIterator<Item = FacetValueHit>,
+    limit: usize,
+) -> Vec<FacetValueHit> {
+    let mut counts: HashMap<FacetValue, usize> = HashMap::new();
+    for hit in hits {
+        *counts.entry(hit.value).or_default() += hit.count;
+    }
+    let mut merged: Vec<_> = counts
+        .into_iter()
+        .map(|(value, count)| FacetValueHit { value, count })
+        .collect();
+    sort_hits(&mut merged);
+    merged.truncate(limit);
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn keyword(value: &str) -> FacetValue {
+        FacetValue::Keyword(value.to_string())
+    }
+
+    #[test]
+    fn test_exact_within_capacity() {
+        let mut sketch = FacetSketch::new(10);
+        for value in ["a", "b", "a", "c", "a", "b"] {
+            sketch.add(keyword(value));
+        }
+        let top = sketch.top(2);
+        assert_eq!(
+            top,
+            vec![
+                FacetValueHit {
+                    value: keyword("a"),
+                    count: 3
+                },
+                FacetValueHit {
+                    value: keyword("b"),
+                    count: 2
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_frequent_values_survive_eviction() {
+        let mut sketch = FacetSketch::new(3);
+        for i in 0..100 {
+            sketch.add(FacetValue::Integer(1));
+            sketch.add(FacetValue::Integer(1000 + i));
+        }
+        let top = sketch.top(1);
+        assert_eq!(top[0].value, FacetValue::Integer(1));
+        assert!(top[0].count >= 100);
+    }
+
+    #[test]
+    fn test_evicts_smallest_counter() {
+        let mut sketch = FacetSketch::new(2);
+        sketch.add(keyword("a"));
+        sketch.add(keyword("a"));
+        sketch.add(keyword("b"));
+        // "b" has the smallest counter, "c" inherits it
+        sketch.add(keyword("c"));
+        assert_eq!(
+            sketch.clone().top(2),
+            vec![
+                FacetValueHit {
+                    value: keyword("a"),
+                    count: 2
+                },
+                FacetValueHit {
+                    value: keyword("c"),
+                    count: 2
+                },
+            ]
+        );
+        // Counters stay ordered after increments
+        sketch.add(keyword("c"));
+        sketch.add(keyword("c"));
+        sketch.add(keyword("d"));
+        let top = sketch.top(2);
+        assert_eq!(top[0].value, keyword("c"));
+        assert_eq!(top[0].count, 4);
+        assert_eq!(top[1].value, keyword("d"));
+        assert_eq!(top[1].count, 3);
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut sketch = FacetSketch::new(10);
+        sketch.add(keyword("a"));
+        let mut other = FacetSketch::new(10);
+        other.add(keyword("a"));
+        other.add(keyword("b"));
+        sketch.merge(other);
+        let top = sketch.top(10);
+        assert_eq!(top.len(), 2);
+        assert_eq!(top[0].count, 2);
+
+        let merged = merge_hits(
+            top.into_iter().chain(vec![FacetValueHit {
+                value: keyword("b"),
+                count: 5,
+            }]),
+            1,
+        );
+        assert_eq!(
+            merged,
+            vec![FacetValueHit {
+                value: keyword("b"),
+                count: 6
+            }]
+        );
+    }
+
+    #[test]
+    fn test_from_payload() {
+        assert_eq!(FacetValue::from_payload(&json!("a")), Some(keyword("a")));
+        assert_eq!(
+            FacetValue::from_payload(&json!(5)),
+            Some(FacetValue::Integer(5))
+        );
+        assert_eq!(FacetValue::from_payload(&json!(5.5)), None);
+        assert_eq!(FacetValue::from_payload(&json!(true)), None);
+    }
+}
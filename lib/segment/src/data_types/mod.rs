@@ -1,4 +1,8 @@
+pub mod discovery;
+pub mod facets;
+pub mod keyword_index;
 pub mod named_vectors;
+pub mod payload_update;
 pub mod text_index;
 pub mod tiny_map;
 pub mod vectors;
@@ -0,0 +1,147 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
+
+use crate::entry::entry_point::{OperationError, OperationResult};
+use crate::types::{Payload, PayloadKeyType};
+
+/// Change of a payload value, which depends on its current value.
+/// Applied to the stored payload, so concurrent changes of the same value are not lost.
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadUpdate {
+    /// Add `by` to the numeric value of the top-level `key`. Missing value is set to `by`
+    Increment { key: PayloadKeyType, by: Number },
+    /// Append `values` to the array of the top-level `key`. Missing value is set to `values`,
+    /// a single value is turned into an array
+    Append {
+        key: PayloadKeyType,
+        values: Vec<Value>,
+    },
+    /// Set the top-level `key` to `value`, if it has no value yet
+    SetIfAbsent { key: PayloadKeyType, value: Value },
+}
+
+impl PayloadUpdate {
+    pub fn key(&self) -> &PayloadKeyType {
+        match self {
+            PayloadUpdate::Increment { key, .. } => key,
+            PayloadUpdate::Append { key, .. } => key,
+            PayloadUpdate::SetIfAbsent { key, .. } => key,
+        }
+    }
+
+    /// New value of the key, if it is changed
+    fn apply(&self, current: Option<&Value>) -> OperationResult<Option<Value>> {
+        match self {
+            PayloadUpdate::Increment { key, by } => {
+                let sum = match current {
+                    None => Some(by.clone()),
+                    Some(Value::Number(current)) => add_numbers(current, by),
+                    Some(_) => None,
+                };
+                match sum {
+                    Some(sum) => Ok(Some(Value::Number(sum))),
+                    None => Err(OperationError::TypeError {
+                        field_name: key.clone(),
+                        expected_type: "number".to_string(),
+                    }),
+                }
+            }
+            PayloadUpdate::Append { values, .. } => {
+                let mut array = match current {
+                    None => vec![],
+                    Some(Value::Array(array)) => array.clone(),
+                    Some(value) => vec![value.clone()],
+                };
+                array.extend(values.iter().cloned());
+                Ok(Some(Value::Array(array)))
+            }
+            PayloadUpdate::SetIfAbsent { value, .. } => match current {
+                None => Ok(Some(value.clone())),
+                Some(_) => Ok(None),
+            },
+        }
+    }
+}
+
+/// Sum of integers stays an integer, unless it overflows
+fn add_numbers(a: &Number, b: &Number) -> Option<Number> {
+    if let (Some(a), Some(b)) = (a.as_i64(), b.as_i64()) {
+        if let Some(sum) = a.checked_add(b) {
+            return Some(sum.into());
+        }
+    }
+    Number::from_f64(a.as_f64()? + b.as_f64()?)
+}
+
+/// Values of the keys of `payload`, changed by `updates`, applied in order.
+/// The result is to be assigned over `payload`. Null values are considered missing.
+pub fn apply_payload_updates(
+    payload: &Payload,
+    updates: &[PayloadUpdate],
+) -> OperationResult<Payload> {
+    let mut changed = Map::new();
+    for update in updates {
+        let key = update.key();
+        let current = changed
+            .get(key)
+            .or_else(|| payload.0.get(key))
+            .filter(|value| !value.is_null());
+        if let Some(value) = update.apply(current)? {
+            changed.insert(key.clone(), value);
+        }
+    }
+    Ok(Payload(changed))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_apply_payload_updates() {
+        let payload: Payload = json!({
+            "views": 41,
+            "rating": 4.5,
+            "tags": ["a"],
+            "color": "red",
+            "created": null,
+        })
+        .into();
+        let updates: Vec<PayloadUpdate> = serde_json::from_value(json!([
+            {"increment": {"key": "views", "by": 1}},
+            {"increment": {"key": "views", "by": 1}},
+            {"increment": {"key": "rating", "by": 1}},
+            {"increment": {"key": "likes", "by": 1}},
+            {"append": {"key": "tags", "values": ["b", "c"]}},
+            {"append": {"key": "color", "values": ["blue"]}},
+            {"append": {"key": "sizes", "values": [1]}},
+            {"set_if_absent": {"key": "color", "value": "green"}},
+            {"set_if_absent": {"key": "created", "value": 1000}},
+        ]))
+        .unwrap();
+
+        let changed = apply_payload_updates(&payload, &updates).unwrap();
+        let expected: Payload = json!({
+            "views": 43,
+            "rating": 5.5,
+            "likes": 1,
+            "tags": ["a", "b", "c"],
+            "color": ["red", "blue"],
+            "sizes": [1],
+            "created": 1000,
+        })
+        .into();
+        assert_eq!(changed, expected);
+
+        let increment_string: Vec<PayloadUpdate> =
+            serde_json::from_value(json!([{"increment": {"key": "color", "by": 1}}])).unwrap();
+        assert!(matches!(
+            apply_payload_updates(&payload, &increment_string),
+            Err(OperationError::TypeError { .. })
+        ));
+    }
+}
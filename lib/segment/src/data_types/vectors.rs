@@ -3,6 +3,7 @@ use std::collections::HashMap;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use super::discovery::DiscoveryQuery;
 use super::named_vectors::NamedVectors;
 use crate::common::utils::transpose_map_into_named_vector;
 
@@ -22,6 +23,27 @@ pub fn only_default_vector(vec: &[VectorElementType]) -> NamedVectors {
     NamedVectors::from_ref(DEFAULT_VECTOR_NAME, vec)
 }
 
+/// Query of the vector index search
+#[derive(Debug, Clone, Copy)]
+pub enum QueryVector<'a> {
+    /// Points, nearest to the vector
+    Nearest(&'a [VectorElementType]),
+    /// Points, ranked by the context pairs and then by the similarity to the target
+    Discovery(&'a DiscoveryQuery),
+}
+
+impl<'a> From<&'a [VectorElementType]> for QueryVector<'a> {
+    fn from(vector: &'a [VectorElementType]) -> Self {
+        QueryVector::Nearest(vector)
+    }
+}
+
+impl<'a> From<&'a DiscoveryQuery> for QueryVector<'a> {
+    fn from(query: &'a DiscoveryQuery) -> Self {
+        QueryVector::Discovery(query)
+    }
+}
+
 /// Full vector data per point separator with single and multiple vector modes
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 #[serde(rename_all = "snake_case")]
@@ -2,20 +2,25 @@ use std::collections::HashMap;
 use std::io::Error as IoError;
 use std::path::{Path, PathBuf};
 use std::result;
+use std::time::Instant;
 
 use atomicwrites::Error as AtomicIoError;
 use rayon::ThreadPoolBuildError;
 use thiserror::Error;
 
+use crate::common::encryption::StorageCipher;
 use crate::common::file_operations::FileStorageError;
+use crate::data_types::discovery::DiscoveryQuery;
+use crate::data_types::facets::FacetSketch;
 use crate::data_types::named_vectors::NamedVectors;
+use crate::data_types::payload_update::PayloadUpdate;
 use crate::data_types::vectors::VectorElementType;
 use crate::index::field_index::CardinalityEstimation;
 use crate::telemetry::SegmentTelemetry;
 use crate::types::{
-    Filter, Payload, PayloadFieldSchema, PayloadKeyType, PayloadKeyTypeRef, PointIdType,
-    ScoredPoint, SearchParams, SegmentConfig, SegmentInfo, SegmentType, SeqNumberType, WithPayload,
-    WithVector,
+    BatchSearchResult, Filter, FloatPayloadType, OrderBy, Payload, PayloadFieldSchema,
+    PayloadKeyType, PayloadKeyTypeRef, PointIdType, ScoreType, ScoredPoint, SearchParams,
+    SegmentConfig, SegmentInfo, SegmentType, SeqNumberType, WithPayload, WithVector,
 };
 
 #[derive(Error, Debug, Clone)]
@@ -26,6 +31,10 @@ pub enum OperationError {
         expected_dim: usize,
         received_dim: usize,
     },
+    #[error("Vector inserting error: length {received_len} of multivector is not a multiple of dim {dim}")]
+    WrongMultiVector { dim: usize, received_len: usize },
+    #[error("Vector inserting error: element {element} of uint8 vector is not an integer in range [0, 255]")]
+    WrongByteVectorElement { element: VectorElementType },
     #[error("Not existing vector name error: {received_name}")]
     VectorNameNotExists { received_name: String },
     #[error("Missed vector name error: {received_name}")]
@@ -45,6 +54,8 @@ pub enum OperationError {
     ServiceError { description: String },
     #[error("Operation cancelled: {description}")]
     Cancelled { description: String },
+    #[error("Ordering requires an integer or float payload index on the field `{field_name}`")]
+    MissingOrderIndex { field_name: PayloadKeyType },
 }
 
 impl OperationError {
@@ -152,6 +163,9 @@ pub trait SegmentEntry {
     /// Get version of specified point
     fn point_version(&self, point_id: PointIdType) -> Option<SeqNumberType>;
 
+    /// Search for `top` nearest points.
+    /// Only points with the score better than `score_threshold` are returned,
+    /// threshold is compared with the score as it is returned to the user.
     #[allow(clippy::too_many_arguments)]
     fn search(
         &self,
@@ -162,8 +176,11 @@ pub trait SegmentEntry {
         filter: Option<&Filter>,
         top: usize,
         params: Option<&SearchParams>,
+        score_threshold: Option<ScoreType>,
     ) -> OperationResult<Vec<ScoredPoint>>;
 
+    /// Search for a batch of vectors with the same parameters.
+    /// Approximate search is interrupted at `deadline`, result is marked as truncated then.
     #[allow(clippy::too_many_arguments)]
     fn search_batch(
         &self,
@@ -174,7 +191,24 @@ pub trait SegmentEntry {
         filter: Option<&Filter>,
         top: usize,
         params: Option<&SearchParams>,
-    ) -> OperationResult<Vec<Vec<ScoredPoint>>>;
+        score_threshold: Option<ScoreType>,
+        deadline: Option<Instant>,
+    ) -> OperationResult<BatchSearchResult>;
+
+    /// Search for `top` points by the discovery `query`: points in the preferred zones of
+    /// the most context pairs go first, points of the same rank are ordered by the similarity
+    /// to the target. Vectors of the query are expected to be preprocessed like stored ones.
+    #[allow(clippy::too_many_arguments)]
+    fn discover(
+        &self,
+        vector_name: &str,
+        query: &DiscoveryQuery,
+        with_payload: &WithPayload,
+        with_vector: &WithVector,
+        filter: Option<&Filter>,
+        top: usize,
+        params: Option<&SearchParams>,
+    ) -> OperationResult<Vec<ScoredPoint>>;
 
     fn upsert_vector(
         &mut self,
@@ -203,6 +237,15 @@ pub trait SegmentEntry {
         full_payload: &Payload,
     ) -> OperationResult<bool>;
 
+    /// Change payload values of the point, depending on their current values.
+    /// Values are read and written under the lock of the segment, so no change is lost.
+    fn update_payload(
+        &mut self,
+        op_num: SeqNumberType,
+        point_id: PointIdType,
+        updates: &[PayloadUpdate],
+    ) -> OperationResult<bool>;
+
     fn delete_payload(
         &mut self,
         op_num: SeqNumberType,
@@ -236,6 +279,40 @@ pub trait SegmentEntry {
         filter: Option<&'a Filter>,
     ) -> Vec<PointIdType>;
 
+    /// Paginate over points which satisfies filtering condition in the order of payload values.
+    /// Pagination cursor is defined by `order_by.start_from` value and `offset` id.
+    ///
+    /// Returns at least `limit` points (if available) with their order values, plus all other
+    /// points which share the order value of the last one.
+    /// Uses numeric payload index of the field, if the segment has one. Otherwise reads payloads.
+    fn read_ordered_filtered<'a>(
+        &'a self,
+        offset: Option<PointIdType>,
+        limit: Option<usize>,
+        filter: Option<&'a Filter>,
+        order_by: &'a OrderBy,
+    ) -> OperationResult<Vec<(FloatPayloadType, PointIdType)>>;
+
+    /// Uniformly random sample of at most `limit` points, which satisfy filtering condition.
+    /// Points are returned with their random keys, sample of the union of several segments
+    /// consists of the points with the largest keys.
+    fn read_random_filtered<'a>(
+        &'a self,
+        limit: usize,
+        filter: Option<&'a Filter>,
+    ) -> Vec<(FloatPayloadType, PointIdType)>;
+
+    /// Approximate counts of the most frequent values of the `key` field among points,
+    /// which satisfy filtering condition.
+    /// Uses keyword or integer payload index of the field, if the segment has one.
+    /// Otherwise reads payloads.
+    fn facet<'a>(
+        &'a self,
+        key: PayloadKeyTypeRef,
+        filter: Option<&'a Filter>,
+        sketch_capacity: usize,
+    ) -> OperationResult<FacetSketch>;
+
     /// Read points in [from; to) range
     fn read_range(&self, from: Option<PointIdType>, to: Option<PointIdType>) -> Vec<PointIdType>;
 
@@ -255,6 +332,10 @@ pub trait SegmentEntry {
     /// Number of vectors, marked as deleted
     fn deleted_count(&self) -> usize;
 
+    /// Number of internal offsets, taken by the points of this segment, including deleted ones.
+    /// Deleted points keep their offsets, until the segment is rebuilt.
+    fn point_offsets_count(&self) -> usize;
+
     /// Get segment type
     fn segment_type(&self) -> SegmentType;
 
@@ -267,6 +348,12 @@ pub trait SegmentEntry {
     /// Get current stats of the segment
     fn is_appendable(&self) -> bool;
 
+    /// Check if some indexes of the segment were not found on load and have to be rebuilt
+    fn has_missing_indexes(&self) -> bool;
+
+    /// Cipher, which payloads and vectors of the segment are encrypted with
+    fn storage_cipher(&self) -> Option<StorageCipher>;
+
     /// Flushes current segment state into a persistent storage, if possible
     /// if sync == true, block current thread while flushing
     ///
@@ -319,4 +406,12 @@ pub trait SegmentEntry {
 
     // Get collected telemetry data of segment
     fn get_telemetry_data(&self) -> SegmentTelemetry;
+
+    /// Read vectors, stored on disk, into the page cache, so following searches are not slowed
+    /// down by disk reads
+    fn populate(&self);
+
+    /// Check data of the segment for silent corruption: files against the stored checksums
+    /// and all records of the database against the checksums of the data blocks
+    fn verify_data(&self) -> OperationResult<()>;
 }
@@ -1,6 +1,7 @@
 use serde_json::Value;
 
 use crate::common::Flusher;
+use crate::data_types::facets::FacetValue;
 use crate::entry::entry_point::OperationResult;
 use crate::index::field_index::full_text_index::text_index::FullTextIndex;
 use crate::index::field_index::geo_index::GeoMapIndex;
@@ -9,7 +10,8 @@ use crate::index::field_index::numeric_index::NumericIndex;
 use crate::index::field_index::{CardinalityEstimation, PayloadBlockCondition};
 use crate::telemetry::PayloadIndexTelemetry;
 use crate::types::{
-    FieldCondition, FloatPayloadType, IntPayloadType, PayloadKeyType, PointOffsetType,
+    DateTimePayloadType, Direction, FieldCondition, FloatPayloadType, IntPayloadType,
+    PayloadKeyType, PointOffsetType,
 };
 
 pub trait PayloadFieldIndex {
@@ -87,6 +89,7 @@ pub enum FieldIndex {
     FloatIndex(NumericIndex<FloatPayloadType>),
     GeoIndex(GeoMapIndex),
     FullTextIndex(FullTextIndex),
+    DatetimeIndex(NumericIndex<DateTimePayloadType>),
 }
 
 impl FieldIndex {
@@ -98,6 +101,7 @@ impl FieldIndex {
             FieldIndex::FloatIndex(payload_field_index) => payload_field_index,
             FieldIndex::GeoIndex(payload_field_index) => payload_field_index,
             FieldIndex::FullTextIndex(payload_field_index) => payload_field_index,
+            FieldIndex::DatetimeIndex(payload_field_index) => payload_field_index,
         }
     }
 
@@ -110,6 +114,7 @@ impl FieldIndex {
             FieldIndex::FloatIndex(ref mut payload_field_index) => payload_field_index,
             FieldIndex::GeoIndex(ref mut payload_field_index) => payload_field_index,
             FieldIndex::FullTextIndex(ref mut payload_field_index) => payload_field_index,
+            FieldIndex::DatetimeIndex(ref mut payload_field_index) => payload_field_index,
         }
     }
 
@@ -121,6 +126,7 @@ impl FieldIndex {
             FieldIndex::FloatIndex(ref mut payload_field_index) => payload_field_index.load(),
             FieldIndex::GeoIndex(ref mut payload_field_index) => payload_field_index.load(),
             FieldIndex::FullTextIndex(ref mut payload_field_index) => payload_field_index.load(),
+            FieldIndex::DatetimeIndex(ref mut payload_field_index) => payload_field_index.load(),
         }
     }
 
@@ -132,6 +138,7 @@ impl FieldIndex {
             FieldIndex::FloatIndex(index) => index.clear(),
             FieldIndex::GeoIndex(index) => index.clear(),
             FieldIndex::FullTextIndex(index) => index.clear(),
+            FieldIndex::DatetimeIndex(index) => index.clear(),
         }
     }
 
@@ -143,6 +150,7 @@ impl FieldIndex {
             FieldIndex::FloatIndex(index) => index.recreate(),
             FieldIndex::GeoIndex(index) => index.recreate(),
             FieldIndex::FullTextIndex(index) => index.recreate(),
+            FieldIndex::DatetimeIndex(index) => index.recreate(),
         }
     }
 
@@ -182,6 +190,78 @@ impl FieldIndex {
         self.get_payload_field_index().count_indexed_points()
     }
 
+    /// Iterate over `(value, point)` pairs ordered by value.
+    /// Return `None` if the index does not support ordering.
+    pub fn ordered_points(
+        &self,
+        start_from: Option<FloatPayloadType>,
+        direction: Direction,
+    ) -> Option<Box<dyn Iterator<Item = (FloatPayloadType, PointOffsetType)> + '_>> {
+        match self {
+            FieldIndex::IntIndex(index) => Some(index.ordered_points(start_from, direction)),
+            FieldIndex::FloatIndex(index) => Some(index.ordered_points(start_from, direction)),
+            FieldIndex::DatetimeIndex(index) => Some(index.ordered_points(start_from, direction)),
+            FieldIndex::IntMapIndex(_)
+            | FieldIndex::KeywordIndex(_)
+            | FieldIndex::GeoIndex(_)
+            | FieldIndex::FullTextIndex(_) => None,
+        }
+    }
+
+    /// Value, by which the point is ordered in the given direction.
+    /// Return `None` if the index does not support ordering or the point has no values.
+    pub fn ordering_value(
+        &self,
+        point_id: PointOffsetType,
+        direction: Direction,
+    ) -> Option<FloatPayloadType> {
+        match self {
+            FieldIndex::IntIndex(index) => index.ordering_value(point_id, direction),
+            FieldIndex::FloatIndex(index) => index.ordering_value(point_id, direction),
+            FieldIndex::DatetimeIndex(index) => index.ordering_value(point_id, direction),
+            FieldIndex::IntMapIndex(_)
+            | FieldIndex::KeywordIndex(_)
+            | FieldIndex::GeoIndex(_)
+            | FieldIndex::FullTextIndex(_) => None,
+        }
+    }
+
+    /// Whether the index stores values of each point, so they could be counted in facets
+    pub fn supports_facets(&self) -> bool {
+        matches!(
+            self,
+            FieldIndex::IntMapIndex(_) | FieldIndex::KeywordIndex(_)
+        )
+    }
+
+    /// Values of the point, stored in the index.
+    /// Return `None` if the index does not support facets.
+    pub fn facet_values(
+        &self,
+        point_id: PointOffsetType,
+    ) -> OperationResult<Option<Vec<FacetValue>>> {
+        let values = match self {
+            FieldIndex::IntMapIndex(index) => Some(
+                index
+                    .get_values(point_id)?
+                    .map(|values| values.iter().copied().map(FacetValue::Integer).collect())
+                    .unwrap_or_default(),
+            ),
+            FieldIndex::KeywordIndex(index) => Some(
+                index
+                    .get_values(point_id)?
+                    .map(|values| values.iter().cloned().map(FacetValue::Keyword).collect())
+                    .unwrap_or_default(),
+            ),
+            FieldIndex::IntIndex(_)
+            | FieldIndex::FloatIndex(_)
+            | FieldIndex::GeoIndex(_)
+            | FieldIndex::FullTextIndex(_)
+            | FieldIndex::DatetimeIndex(_) => None,
+        };
+        Ok(values)
+    }
+
     pub fn add_point(&mut self, id: PointOffsetType, payload: &Value) -> OperationResult<()> {
         match self {
             FieldIndex::IntIndex(ref mut payload_field_index) => {
@@ -202,6 +282,9 @@ impl FieldIndex {
             FieldIndex::FullTextIndex(ref mut payload_field_index) => {
                 payload_field_index.add_point(id, payload)
             }
+            FieldIndex::DatetimeIndex(ref mut payload_field_index) => {
+                payload_field_index.add_point(id, payload)
+            }
         }
     }
 
@@ -213,6 +296,7 @@ impl FieldIndex {
             FieldIndex::FloatIndex(index) => index.remove_point(point_id),
             FieldIndex::GeoIndex(index) => index.remove_point(point_id),
             FieldIndex::FullTextIndex(index) => index.remove_point(point_id),
+            FieldIndex::DatetimeIndex(index) => index.remove_point(point_id),
         }
     }
 
@@ -224,6 +308,7 @@ impl FieldIndex {
             FieldIndex::FloatIndex(index) => index.get_telemetry_data(),
             FieldIndex::GeoIndex(index) => index.get_telemetry_data(),
             FieldIndex::FullTextIndex(index) => index.get_telemetry_data(),
+            FieldIndex::DatetimeIndex(index) => index.get_telemetry_data(),
         }
     }
 }
@@ -181,6 +181,7 @@ impl InvertedIndex {
                         geo_bounding_box: None,
                         geo_radius: None,
                         values_count: None,
+                        datetime_range: None,
                     },
                     cardinality: posting.len(),
                 }),
@@ -154,7 +154,8 @@ impl PayloadFieldIndex for FullTextIndex {
             return Ok(false);
         };
 
-        for (key, value) in self.db_wrapper.lock_db().iter()? {
+        for record in self.db_wrapper.lock_db().iter()? {
+            let (key, value) = record?;
             let idx = Self::restore_key(&key);
             let document = Self::deserialize_document(&value)?;
             self.inverted_index.index_document(idx, document);
@@ -224,6 +225,7 @@ mod tests {
             geo_bounding_box: None,
             geo_radius: None,
             values_count: None,
+            datetime_range: None,
         }
     }
 
@@ -86,7 +86,8 @@ impl GeoMapIndex {
             return Ok(false);
         };
 
-        for (key, value) in self.db_wrapper.lock_db().iter()? {
+        for record in self.db_wrapper.lock_db().iter()? {
+            let (key, value) = record?;
             let key_str = std::str::from_utf8(&key).map_err(|_| {
                 OperationError::service_error("Index load error: UTF8 error while DB parsing")
             })?;
@@ -3,13 +3,15 @@ use std::sync::Arc;
 use parking_lot::RwLock;
 use rocksdb::DB;
 
+use crate::common::encryption::StorageCipher;
 use crate::index::field_index::full_text_index::text_index::FullTextIndex;
 use crate::index::field_index::geo_index::GeoMapIndex;
 use crate::index::field_index::map_index::MapIndex;
 use crate::index::field_index::numeric_index::NumericIndex;
 use crate::index::field_index::FieldIndex;
 use crate::types::{
-    FloatPayloadType, IntPayloadType, PayloadFieldSchema, PayloadSchemaParams, PayloadSchemaType,
+    DateTimePayloadType, FloatPayloadType, IntPayloadType, PayloadFieldSchema, PayloadSchemaParams,
+    PayloadSchemaType,
 };
 
 /// Selects index types based on field type
@@ -17,6 +19,7 @@ pub fn index_selector(
     field: &str,
     payload_schema: &PayloadFieldSchema,
     db: Arc<RwLock<DB>>,
+    cipher: Option<StorageCipher>,
 ) -> Vec<FieldIndex> {
     match payload_schema {
         PayloadFieldSchema::FieldType(payload_type) => match payload_type {
@@ -38,11 +41,25 @@ pub fn index_selector(
                 Default::default(),
                 field,
             ))],
+            PayloadSchemaType::Datetime => {
+                vec![FieldIndex::DatetimeIndex(
+                    NumericIndex::<DateTimePayloadType>::new(db, field),
+                )]
+            }
         },
         PayloadFieldSchema::FieldParams(payload_params) => match payload_params {
             PayloadSchemaParams::Text(text_index_params) => vec![FieldIndex::FullTextIndex(
                 FullTextIndex::new(db, text_index_params.clone(), field),
             )],
+            PayloadSchemaParams::Keyword(keyword_index_params) => {
+                if keyword_index_params.on_disk.unwrap_or(false) {
+                    vec![FieldIndex::KeywordIndex(MapIndex::new_on_disk(
+                        db, field, cipher,
+                    ))]
+                } else {
+                    vec![FieldIndex::KeywordIndex(MapIndex::new(db, field))]
+                }
+            }
         },
     }
 }
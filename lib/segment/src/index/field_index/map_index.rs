@@ -1,4 +1,5 @@
-use std::collections::{BTreeSet, HashMap};
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fmt::Display;
 use std::hash::Hash;
 use std::iter;
@@ -9,6 +10,7 @@ use parking_lot::RwLock;
 use rocksdb::DB;
 use serde_json::Value;
 
+use crate::common::encryption::StorageCipher;
 use crate::common::rocksdb_wrapper::DatabaseColumnWrapper;
 use crate::common::Flusher;
 use crate::entry::entry_point::{OperationError, OperationResult};
@@ -21,10 +23,23 @@ use crate::types::{
     ValueVariants,
 };
 
+/// Place, where points of the values and values of the points are kept
+enum MapIndexStorage<N: Hash + Eq + Clone + Display> {
+    InMemory {
+        map: HashMap<N, BTreeSet<PointOffsetType>>,
+        point_to_values: Vec<Vec<N>>,
+    },
+    /// Only amounts of points of the values are kept in RAM, the rest is read from the database
+    OnDisk {
+        points_count: HashMap<N, usize>,
+        /// Values of the points, stored as records of the point offsets
+        point_values_db_wrapper: DatabaseColumnWrapper,
+    },
+}
+
 /// HashMap-based type of index
 pub struct MapIndex<N: Hash + Eq + Clone + Display> {
-    map: HashMap<N, BTreeSet<PointOffsetType>>,
-    point_to_values: Vec<Vec<N>>,
+    storage: MapIndexStorage<N>,
     /// Amount of point which have at least one indexed payload value
     indexed_points: usize,
     values_count: usize,
@@ -33,11 +48,41 @@ pub struct MapIndex<N: Hash + Eq + Clone + Display> {
 
 impl<N: Hash + Eq + Clone + Display + FromStr> MapIndex<N> {
     pub fn new(db: Arc<RwLock<DB>>, field_name: &str) -> MapIndex<N> {
+        let storage = MapIndexStorage::InMemory {
+            map: Default::default(),
+            point_to_values: Vec::new(),
+        };
+        Self::with_storage(db, field_name, storage)
+    }
+
+    /// Index, which reads points of the values from the database instead of keeping them in RAM.
+    /// Values of the points are encrypted with `cipher`, if it is provided
+    pub fn new_on_disk(
+        db: Arc<RwLock<DB>>,
+        field_name: &str,
+        cipher: Option<StorageCipher>,
+    ) -> MapIndex<N> {
+        let point_values_cf_name = Self::point_values_cf_name(field_name);
+        let storage = MapIndexStorage::OnDisk {
+            points_count: Default::default(),
+            point_values_db_wrapper: DatabaseColumnWrapper::new_with_cipher(
+                db.clone(),
+                &point_values_cf_name,
+                cipher,
+            ),
+        };
+        Self::with_storage(db, field_name, storage)
+    }
+
+    fn with_storage(
+        db: Arc<RwLock<DB>>,
+        field_name: &str,
+        storage: MapIndexStorage<N>,
+    ) -> MapIndex<N> {
         let store_cf_name = Self::storage_cf_name(field_name);
         let db_wrapper = DatabaseColumnWrapper::new(db, &store_cf_name);
         MapIndex {
-            map: Default::default(),
-            point_to_values: Vec::new(),
+            storage,
             indexed_points: 0,
             values_count: 0,
             db_wrapper,
@@ -48,7 +93,22 @@ impl<N: Hash + Eq + Clone + Display + FromStr> MapIndex<N> {
         format!("{field}_map")
     }
 
+    fn point_values_cf_name(field: &str) -> String {
+        format!("{field}_map_points")
+    }
+
+    pub fn is_on_disk(&self) -> bool {
+        matches!(self.storage, MapIndexStorage::OnDisk { .. })
+    }
+
     pub fn recreate(&self) -> OperationResult<()> {
+        if let MapIndexStorage::OnDisk {
+            point_values_db_wrapper,
+            ..
+        } = &self.storage
+        {
+            point_values_db_wrapper.recreate_column_family()?;
+        }
         self.db_wrapper.recreate_column_family()
     }
 
@@ -56,34 +116,99 @@ impl<N: Hash + Eq + Clone + Display + FromStr> MapIndex<N> {
         if !self.db_wrapper.has_column_family()? {
             return Ok(false);
         }
+        if let MapIndexStorage::OnDisk {
+            point_values_db_wrapper,
+            ..
+        } = &self.storage
+        {
+            if !point_values_db_wrapper.has_column_family()? {
+                return Ok(false);
+            }
+        }
         self.indexed_points = 0;
-        for (record, _) in self.db_wrapper.lock_db().iter()? {
+        let mut point_to_values: Vec<Vec<N>> = Vec::new();
+        for record in self.db_wrapper.lock_db().iter()? {
+            let (record, _) = record?;
             let record = std::str::from_utf8(&record).map_err(|_| {
                 OperationError::service_error("Index load error: UTF8 error while DB parsing")
             })?;
             let (value, idx) = Self::decode_db_record(record)?;
-            if self.point_to_values.len() <= idx as usize {
-                self.point_to_values.resize(idx as usize + 1, Vec::new())
+            self.values_count += 1;
+            match &mut self.storage {
+                MapIndexStorage::InMemory { map, .. } => {
+                    if point_to_values.len() <= idx as usize {
+                        point_to_values.resize(idx as usize + 1, Vec::new())
+                    }
+                    if point_to_values[idx as usize].is_empty() {
+                        self.indexed_points += 1;
+                    }
+                    point_to_values[idx as usize].push(value.clone());
+                    map.entry(value).or_default().insert(idx);
+                }
+                MapIndexStorage::OnDisk { points_count, .. } => {
+                    *points_count.entry(value).or_default() += 1;
+                }
             }
-            if self.point_to_values[idx as usize].is_empty() {
-                self.indexed_points += 1;
+        }
+        match &mut self.storage {
+            MapIndexStorage::InMemory {
+                point_to_values: loaded_point_to_values,
+                ..
+            } => *loaded_point_to_values = point_to_values,
+            MapIndexStorage::OnDisk {
+                point_values_db_wrapper,
+                ..
+            } => {
+                // Only the points with values have a record
+                for record in point_values_db_wrapper.lock_db().iter()? {
+                    record?;
+                    self.indexed_points += 1;
+                }
             }
-            self.values_count += 1;
-            self.point_to_values[idx as usize].push(value.clone());
-            self.map.entry(value).or_default().insert(idx);
         }
         Ok(true)
     }
 
     pub fn flusher(&self) -> Flusher {
-        self.db_wrapper.flusher()
+        match &self.storage {
+            MapIndexStorage::InMemory { .. } => self.db_wrapper.flusher(),
+            MapIndexStorage::OnDisk {
+                point_values_db_wrapper,
+                ..
+            } => {
+                let flusher = self.db_wrapper.flusher();
+                let point_values_flusher = point_values_db_wrapper.flusher();
+                Box::new(move || {
+                    flusher()?;
+                    point_values_flusher()
+                })
+            }
+        }
+    }
+
+    fn points_count(&self, value: &N) -> usize {
+        match &self.storage {
+            MapIndexStorage::InMemory { map, .. } => map.get(value).map_or(0, BTreeSet::len),
+            MapIndexStorage::OnDisk { points_count, .. } => {
+                points_count.get(value).copied().unwrap_or(0)
+            }
+        }
+    }
+
+    /// Iterate over the values with amounts of their points
+    fn values_with_points_count(&self) -> Box<dyn Iterator<Item = (&N, usize)> + '_> {
+        match &self.storage {
+            MapIndexStorage::InMemory { map, .. } => {
+                Box::new(map.iter().map(|(value, points)| (value, points.len())))
+            }
+            MapIndexStorage::OnDisk { points_count, .. } => {
+                Box::new(points_count.iter().map(|(value, count)| (value, *count)))
+            }
+        }
     }
 
     pub fn match_cardinality(&self, value: &N) -> CardinalityEstimation {
-        let values_count = match self.map.get(value) {
-            None => 0,
-            Some(points) => points.len(),
-        };
+        let values_count = self.points_count(value);
 
         CardinalityEstimation {
             primary_clauses: vec![],
@@ -93,8 +218,23 @@ impl<N: Hash + Eq + Clone + Display + FromStr> MapIndex<N> {
         }
     }
 
-    pub fn get_values(&self, idx: PointOffsetType) -> Option<&Vec<N>> {
-        self.point_to_values.get(idx as usize)
+    /// Values of the point, `None` if the point has no values
+    pub fn get_values(&self, idx: PointOffsetType) -> OperationResult<Option<Cow<'_, [N]>>> {
+        let values = match &self.storage {
+            MapIndexStorage::InMemory {
+                point_to_values, ..
+            } => point_to_values
+                .get(idx as usize)
+                .map(|values| Cow::Borrowed(values.as_slice())),
+            MapIndexStorage::OnDisk {
+                point_values_db_wrapper,
+                ..
+            } => Some(Cow::Owned(Self::read_point_values(
+                point_values_db_wrapper,
+                idx,
+            )?)),
+        };
+        Ok(values.filter(|values| !values.is_empty()))
     }
 
     pub fn get_telemetry_data(&self) -> PayloadIndexTelemetry {
@@ -107,31 +247,117 @@ impl<N: Hash + Eq + Clone + Display + FromStr> MapIndex<N> {
     }
 
     fn add_many_to_map(&mut self, idx: PointOffsetType, values: Vec<N>) -> OperationResult<()> {
+        // Each value is stored once per point, so duplicated values are counted once as well
+        let mut unique_values = HashSet::new();
+        let values: Vec<N> = values
+            .into_iter()
+            .filter(|value| unique_values.insert(value.clone()))
+            .collect();
         if values.is_empty() {
             return Ok(());
         }
 
-        self.values_count += values.len();
-        if self.point_to_values.len() <= idx as usize {
-            self.point_to_values.resize(idx as usize + 1, Vec::new())
-        }
-        self.point_to_values[idx as usize] = values.into_iter().collect();
-        for value in &self.point_to_values[idx as usize] {
-            let entry = self.map.entry(value.clone()).or_default();
-            entry.insert(idx);
-
-            let db_record = Self::encode_db_record(value, idx);
-            self.db_wrapper.put(&db_record, [])?;
+        match &mut self.storage {
+            MapIndexStorage::InMemory {
+                map,
+                point_to_values,
+            } => {
+                self.values_count += values.len();
+                if point_to_values.len() <= idx as usize {
+                    point_to_values.resize(idx as usize + 1, Vec::new())
+                }
+                point_to_values[idx as usize] = values;
+                for value in &point_to_values[idx as usize] {
+                    let entry = map.entry(value.clone()).or_default();
+                    entry.insert(idx);
+
+                    let db_record = Self::encode_db_record(value, idx);
+                    self.db_wrapper.put(&db_record, [])?;
+                }
+            }
+            MapIndexStorage::OnDisk {
+                points_count,
+                point_values_db_wrapper,
+            } => {
+                self.values_count += values.len();
+                let point_values_record = Self::encode_point_values_record(&values)?;
+                point_values_db_wrapper.put(idx.to_be_bytes(), point_values_record)?;
+                for value in values {
+                    let db_record = Self::encode_db_record(&value, idx);
+                    self.db_wrapper.put(&db_record, [])?;
+                    *points_count.entry(value).or_default() += 1;
+                }
+            }
         }
         self.indexed_points += 1;
         Ok(())
     }
 
-    fn get_iterator(&self, value: &N) -> Box<dyn Iterator<Item = PointOffsetType> + '_> {
-        self.map
-            .get(value)
-            .map(|ids| Box::new(ids.iter().copied()) as Box<dyn Iterator<Item = PointOffsetType>>)
-            .unwrap_or_else(|| Box::new(iter::empty::<PointOffsetType>()))
+    fn get_iterator(
+        &self,
+        value: &N,
+    ) -> OperationResult<Box<dyn Iterator<Item = PointOffsetType> + '_>> {
+        match &self.storage {
+            MapIndexStorage::InMemory { map, .. } => Ok(map
+                .get(value)
+                .map(|ids| {
+                    Box::new(ids.iter().copied()) as Box<dyn Iterator<Item = PointOffsetType>>
+                })
+                .unwrap_or_else(|| Box::new(iter::empty::<PointOffsetType>()))),
+            MapIndexStorage::OnDisk { .. } => Ok(Box::new(self.read_points(value)?.into_iter())),
+        }
+    }
+
+    /// Points of the value for the `filter` of the payload field index.
+    /// Filters can't report errors, so if the points can't be read from the database,
+    /// the condition is not served by the index and is checked against the payload instead.
+    fn filter_points(&self, value: &N) -> Option<Box<dyn Iterator<Item = PointOffsetType> + '_>> {
+        match self.get_iterator(value) {
+            Ok(points) => Some(points),
+            Err(err) => {
+                log::error!("Failed to read points of value {value} from payload index: {err}");
+                None
+            }
+        }
+    }
+
+    /// Read points of the value from the database
+    fn read_points(&self, value: &N) -> OperationResult<Vec<PointOffsetType>> {
+        if self.points_count(value) == 0 {
+            return Ok(vec![]);
+        }
+        let prefix = format!("{value}/");
+        let locked_db = self.db_wrapper.lock_db();
+        let mut records = locked_db.iter()?;
+        records.seek(prefix.as_bytes());
+        let mut points = Vec::new();
+        for record in records {
+            let (record, _) = record?;
+            if !record.starts_with(prefix.as_bytes()) {
+                break;
+            }
+            let record = std::str::from_utf8(&record).map_err(|_| {
+                OperationError::service_error("Index read error: UTF8 error while DB parsing")
+            })?;
+            // Prefix of the other value might be the same, if the value contains a separator
+            let (record_value, idx) = Self::decode_db_record(record)?;
+            if &record_value == value {
+                points.push(idx);
+            }
+        }
+        // Keys are ordered as strings, but the points are expected in ascending order
+        points.sort_unstable();
+        Ok(points)
+    }
+
+    /// Read values of the point from the database
+    fn read_point_values(
+        point_values_db_wrapper: &DatabaseColumnWrapper,
+        idx: PointOffsetType,
+    ) -> OperationResult<Vec<N>> {
+        point_values_db_wrapper
+            .get_pinned(&idx.to_be_bytes(), Self::decode_point_values_record)?
+            .unwrap_or_else(|| Ok(Vec::new()))
     }
 
     fn encode_db_record(value: &N, idx: PointOffsetType) -> String {
@@ -155,12 +381,62 @@ impl<N: Hash + Eq + Clone + Display + FromStr> MapIndex<N> {
         Ok((value, idx))
     }
 
-    fn remove_point(&mut self, idx: PointOffsetType) -> OperationResult<()> {
-        if self.point_to_values.len() <= idx as usize {
-            return Ok(());
-        }
+    fn encode_point_values_record(values: &[N]) -> OperationResult<Vec<u8>> {
+        let values: Vec<String> = values.iter().map(ToString::to_string).collect();
+        serde_json::to_vec(&values).map_err(|err| {
+            OperationError::service_error(&format!("Index db encoding error: {err}"))
+        })
+    }
 
-        let removed_values = std::mem::take(&mut self.point_to_values[idx as usize]);
+    fn decode_point_values_record(record: &[u8]) -> OperationResult<Vec<N>> {
+        const DECODE_ERR: &str = "Index db parsing error: wrong data format";
+        let values: Vec<String> = serde_json::from_slice(record)
+            .map_err(|_| OperationError::service_error(DECODE_ERR))?;
+        values
+            .iter()
+            .map(|value| N::from_str(value).map_err(|_| OperationError::service_error(DECODE_ERR)))
+            .collect()
+    }
+
+    fn remove_point(&mut self, idx: PointOffsetType) -> OperationResult<()> {
+        let removed_values = match &mut self.storage {
+            MapIndexStorage::InMemory {
+                map,
+                point_to_values,
+            } => {
+                if point_to_values.len() <= idx as usize {
+                    return Ok(());
+                }
+                let removed_values = std::mem::take(&mut point_to_values[idx as usize]);
+                for value in &removed_values {
+                    if let Some(vals) = map.get_mut(value) {
+                        vals.remove(&idx);
+                        if vals.is_empty() {
+                            map.remove(value);
+                        }
+                    }
+                }
+                removed_values
+            }
+            MapIndexStorage::OnDisk {
+                points_count,
+                point_values_db_wrapper,
+            } => {
+                let removed_values = Self::read_point_values(point_values_db_wrapper, idx)?;
+                for value in &removed_values {
+                    if let Some(count) = points_count.get_mut(value) {
+                        *count -= 1;
+                        if *count == 0 {
+                            points_count.remove(value);
+                        }
+                    }
+                }
+                if !removed_values.is_empty() {
+                    point_values_db_wrapper.remove(idx.to_be_bytes())?;
+                }
+                removed_values
+            }
+        };
 
         if !removed_values.is_empty() {
             self.indexed_points -= 1;
@@ -168,9 +444,6 @@ impl<N: Hash + Eq + Clone + Display + FromStr> MapIndex<N> {
         self.values_count -= removed_values.len();
 
         for value in &removed_values {
-            if let Some(vals) = self.map.get_mut(value) {
-                vals.remove(&idx);
-            }
             let key = MapIndex::encode_db_record(value, idx);
             self.db_wrapper.remove(&key)?;
         }
@@ -189,7 +462,7 @@ impl PayloadFieldIndex for MapIndex<String> {
     }
 
     fn clear(self) -> OperationResult<()> {
-        self.db_wrapper.recreate_column_family()
+        self.recreate()
     }
 
     fn flusher(&self) -> Flusher {
@@ -203,7 +476,7 @@ impl PayloadFieldIndex for MapIndex<String> {
         match &condition.r#match {
             Some(Match::Value(MatchValue {
                 value: ValueVariants::Keyword(keyword),
-            })) => Some(self.get_iterator(keyword)),
+            })) => self.filter_points(keyword),
             _ => None,
         }
     }
@@ -229,12 +502,11 @@ impl PayloadFieldIndex for MapIndex<String> {
         key: PayloadKeyType,
     ) -> Box<dyn Iterator<Item = PayloadBlockCondition> + '_> {
         let iter = self
-            .map
-            .iter()
-            .filter(move |(_value, point_ids)| point_ids.len() > threshold)
-            .map(move |(value, point_ids)| PayloadBlockCondition {
+            .values_with_points_count()
+            .filter(move |(_value, points_count)| *points_count > threshold)
+            .map(move |(value, points_count)| PayloadBlockCondition {
                 condition: FieldCondition::new_match(key.clone(), value.to_owned().into()),
-                cardinality: point_ids.len(),
+                cardinality: points_count,
             });
         Box::new(iter)
     }
@@ -254,7 +526,7 @@ impl PayloadFieldIndex for MapIndex<IntPayloadType> {
     }
 
     fn clear(self) -> OperationResult<()> {
-        self.db_wrapper.recreate_column_family()
+        self.recreate()
     }
 
     fn flusher(&self) -> Flusher {
@@ -268,7 +540,7 @@ impl PayloadFieldIndex for MapIndex<IntPayloadType> {
         match &condition.r#match {
             Some(Match::Value(MatchValue {
                 value: ValueVariants::Integer(integer),
-            })) => Some(self.get_iterator(integer)),
+            })) => self.filter_points(integer),
             _ => None,
         }
     }
@@ -294,12 +566,11 @@ impl PayloadFieldIndex for MapIndex<IntPayloadType> {
         key: PayloadKeyType,
     ) -> Box<dyn Iterator<Item = PayloadBlockCondition> + '_> {
         let iter = self
-            .map
-            .iter()
-            .filter(move |(_value, point_ids)| point_ids.len() >= threshold)
-            .map(move |(value, point_ids)| PayloadBlockCondition {
+            .values_with_points_count()
+            .filter(move |(_value, points_count)| *points_count >= threshold)
+            .map(move |(value, points_count)| PayloadBlockCondition {
                 condition: FieldCondition::new_match(key.clone(), (*value).into()),
-                cardinality: point_ids.len(),
+                cardinality: points_count,
             });
         Box::new(iter)
     }
@@ -357,6 +628,7 @@ mod tests {
     use tempfile::Builder;
 
     use super::*;
+    use crate::common::encryption::STORAGE_KEY_SIZE;
     use crate::common::rocksdb_wrapper::open_db_with_existing_cf;
 
     const FIELD_NAME: &str = "test";
@@ -386,6 +658,7 @@ mod tests {
                 index
                     .get_values(idx as PointOffsetType)
                     .unwrap()
+                    .unwrap()
                     .iter()
                     .cloned(),
             );
@@ -439,4 +712,174 @@ mod tests {
         save_map_index(&data, tmp_dir.path());
         load_map_index(&data, tmp_dir.path());
     }
+
+    #[test]
+    fn test_on_disk_map_index() {
+        let data = vec![
+            vec![String::from("a"), String::from("a/b")],
+            vec![String::from("a")],
+            vec![String::from("a/b"), String::from("c")],
+            vec![],
+            vec![String::from("c"), String::from("c")],
+        ];
+
+        let tmp_dir = Builder::new().prefix("store_dir").tempdir().unwrap();
+        let db = open_db_with_existing_cf(tmp_dir.path()).unwrap();
+        let mut index = MapIndex::<String>::new_on_disk(db.clone(), FIELD_NAME, None);
+        index.recreate().unwrap();
+        for (idx, values) in data.iter().enumerate() {
+            index
+                .add_many_to_map(idx as PointOffsetType, values.clone())
+                .unwrap();
+        }
+        index.remove_point(1).unwrap();
+        index.flusher()().unwrap();
+
+        let mut index = MapIndex::<String>::new_on_disk(db, FIELD_NAME, None);
+        assert!(index.load().unwrap());
+        assert!(index.is_on_disk());
+        assert_eq!(index.indexed_points, 3);
+
+        let points = |value: &str| {
+            index
+                .get_iterator(&value.to_string())
+                .unwrap()
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(points("a"), vec![0]);
+        assert_eq!(points("a/b"), vec![0, 2]);
+        assert_eq!(points("c"), vec![2, 4]);
+        assert!(points("b").is_empty());
+
+        assert_eq!(index.match_cardinality(&"a/b".to_string()).exp, 2);
+        assert_eq!(
+            index.get_values(0).unwrap().unwrap().as_ref(),
+            &[String::from("a"), String::from("a/b")]
+        );
+        assert!(index.get_values(1).unwrap().is_none());
+        assert_eq!(
+            index.get_values(4).unwrap().unwrap().as_ref(),
+            &[String::from("c")]
+        );
+    }
+
+    /// Build both storages from the same data, they are expected to return the same results
+    fn check_storages_agree<N: Hash + Eq + Clone + Display + FromStr + Debug + Ord>(
+        data: &[Vec<N>],
+        removed: PointOffsetType,
+    ) {
+        let tmp_dir = Builder::new().prefix("store_dir").tempdir().unwrap();
+        let db = open_db_with_existing_cf(tmp_dir.path()).unwrap();
+
+        let build = |mut index: MapIndex<N>| {
+            index.recreate().unwrap();
+            for (idx, values) in data.iter().enumerate() {
+                index
+                    .add_many_to_map(idx as PointOffsetType, values.clone())
+                    .unwrap();
+            }
+            index.remove_point(removed).unwrap();
+            index.flusher()().unwrap();
+        };
+        build(MapIndex::<N>::new(db.clone(), "in_memory"));
+        build(MapIndex::<N>::new_on_disk(db.clone(), "on_disk", None));
+
+        let mut in_memory = MapIndex::<N>::new(db.clone(), "in_memory");
+        assert!(in_memory.load().unwrap());
+        let mut on_disk = MapIndex::<N>::new_on_disk(db, "on_disk", None);
+        assert!(on_disk.load().unwrap());
+
+        assert_eq!(in_memory.indexed_points, on_disk.indexed_points);
+        assert_eq!(in_memory.values_count, on_disk.values_count);
+        assert_eq!(
+            in_memory.get_telemetry_data().points_values_count,
+            on_disk.get_telemetry_data().points_values_count
+        );
+
+        let sorted_values = |index: &MapIndex<N>, idx: PointOffsetType| {
+            index.get_values(idx).unwrap().map(|values| {
+                let mut values = values.into_owned();
+                values.sort();
+                values
+            })
+        };
+        let all_values: BTreeSet<N> = data.iter().flatten().cloned().collect();
+        for idx in 0..data.len() as PointOffsetType + 1 {
+            assert_eq!(sorted_values(&in_memory, idx), sorted_values(&on_disk, idx));
+        }
+        for value in &all_values {
+            assert_eq!(
+                in_memory.get_iterator(value).unwrap().collect::<Vec<_>>(),
+                on_disk.get_iterator(value).unwrap().collect::<Vec<_>>()
+            );
+            assert_eq!(
+                in_memory.match_cardinality(value).exp,
+                on_disk.match_cardinality(value).exp
+            );
+        }
+
+        let blocks = |index: &MapIndex<N>| {
+            let mut blocks: Vec<_> = index
+                .values_with_points_count()
+                .map(|(value, count)| (value.clone(), count))
+                .collect();
+            blocks.sort();
+            blocks
+        };
+        assert_eq!(blocks(&in_memory), blocks(&on_disk));
+    }
+
+    #[test]
+    fn test_map_index_storages_agree() {
+        let data = vec![
+            vec![String::from("a"), String::from("a/b"), String::from("a")],
+            vec![String::from("a")],
+            vec![String::from("a/b"), String::from("c")],
+            vec![],
+            vec![String::from("c"), String::from("c")],
+        ];
+        check_storages_agree(&data, 1);
+
+        let data = vec![vec![1, 2, 2, 3], vec![3], vec![], vec![-1, 1], vec![2, 2]];
+        check_storages_agree(&data, 0);
+    }
+
+    #[test]
+    fn test_encrypted_on_disk_map_index() {
+        let data = vec![
+            vec![String::from("secret"), String::from("value")],
+            vec![String::from("secret")],
+        ];
+
+        let tmp_dir = Builder::new().prefix("store_dir").tempdir().unwrap();
+        let db = open_db_with_existing_cf(tmp_dir.path()).unwrap();
+        let cipher = StorageCipher::new(&[7; STORAGE_KEY_SIZE]).unwrap();
+        let mut index =
+            MapIndex::<String>::new_on_disk(db.clone(), FIELD_NAME, Some(cipher.clone()));
+        index.recreate().unwrap();
+        for (idx, values) in data.iter().enumerate() {
+            index
+                .add_many_to_map(idx as PointOffsetType, values.clone())
+                .unwrap();
+        }
+        index.flusher()().unwrap();
+
+        // Values of the points are not stored as plain text
+        let point_values_db_wrapper = DatabaseColumnWrapper::new(
+            db.clone(),
+            &MapIndex::<String>::point_values_cf_name(FIELD_NAME),
+        );
+        for record in point_values_db_wrapper.lock_db().iter().unwrap() {
+            let (_, value) = record.unwrap();
+            assert!(MapIndex::<String>::decode_point_values_record(&value).is_err());
+        }
+
+        let mut index = MapIndex::<String>::new_on_disk(db, FIELD_NAME, Some(cipher));
+        assert!(index.load().unwrap());
+        assert_eq!(index.indexed_points, 2);
+        assert_eq!(
+            index.get_values(0).unwrap().unwrap().as_ref(),
+            &[String::from("secret"), String::from("value")]
+        );
+    }
 }
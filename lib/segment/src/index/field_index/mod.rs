@@ -9,6 +9,7 @@ pub mod geo_index;
 mod histogram;
 pub mod index_selector;
 pub mod map_index;
+pub mod nested_path;
 pub mod numeric_index;
 mod stat_tools;
 
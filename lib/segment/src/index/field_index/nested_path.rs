@@ -0,0 +1,167 @@
+//! Traversal of nested payload paths.
+//!
+//! Path is a sequence of keys, separated by `.`. Key with `[]` suffix refers to all elements
+//! of the array, e.g. `metadata.tags[].id` refers to `id` field of each element in `metadata.tags`.
+
+use std::borrow::Cow;
+
+use serde_json::{Map, Value};
+
+/// Suffix of the key, which refers to all elements of the array
+pub const ARRAY_ELEMENTS_SUFFIX: &str = "[]";
+
+/// Returns all values, referred by the `path`.
+/// Paths without `[]` refer to at most one value.
+pub fn get_values<'a>(path: &str, map: &'a Map<String, Value>) -> Vec<&'a Value> {
+    let mut result = vec![];
+    collect_from_map(path, map, &mut result);
+    result
+}
+
+fn collect_from_map<'a>(path: &str, map: &'a Map<String, Value>, result: &mut Vec<&'a Value>) {
+    let (head, tail) = path.split_once('.').unwrap_or((path, ""));
+    let (key, all_elements) = match head.strip_suffix(ARRAY_ELEMENTS_SUFFIX) {
+        Some(key) => (key, true),
+        None => (head, false),
+    };
+    match map.get(key) {
+        None => {}
+        Some(Value::Array(elements)) if all_elements => {
+            for element in elements {
+                collect_from_value(tail, element, result);
+            }
+        }
+        // Only arrays could be traversed with `[]`
+        Some(_) if all_elements => {}
+        Some(value) => collect_from_value(tail, value, result),
+    }
+}
+
+fn collect_from_value<'a>(path: &str, value: &'a Value, result: &mut Vec<&'a Value>) {
+    if path.is_empty() {
+        result.push(value);
+        return;
+    }
+    if let Value::Object(map) = value {
+        collect_from_map(path, map, result);
+    }
+}
+
+/// Returns all objects, referred by the `path`. Arrays of objects are expanded into elements.
+/// Used to check conditions, scoped to a single element of the nested array.
+pub fn get_nested_objects<'a>(
+    path: &str,
+    map: &'a Map<String, Value>,
+) -> Vec<&'a Map<String, Value>> {
+    get_values(path, map)
+        .into_iter()
+        .flat_map(|value| match value {
+            Value::Object(object) => vec![object],
+            Value::Array(elements) => elements
+                .iter()
+                .filter_map(|element| match element {
+                    Value::Object(object) => Some(object),
+                    _ => None,
+                })
+                .collect(),
+            _ => vec![],
+        })
+        .collect()
+}
+
+/// Combine multiple values into a single value, suitable for indexing.
+/// Arrays are flattened, so that each element is indexed separately.
+pub fn merge_values<'a>(values: Vec<&'a Value>) -> Option<Cow<'a, Value>> {
+    match values.len() {
+        0 => None,
+        1 => Some(Cow::Borrowed(values[0])),
+        _ => Some(Cow::Owned(Value::Array(
+            values
+                .into_iter()
+                .flat_map(|value| match value {
+                    Value::Array(elements) => elements.clone(),
+                    value => vec![value.clone()],
+                })
+                .collect(),
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn payload() -> Map<String, Value> {
+        match json!({
+            "name": "doc",
+            "metadata": {
+                "author": {"name": "John"},
+                "tags": [
+                    {"id": 1, "labels": ["a", "b"]},
+                    {"id": 2, "labels": ["c"]},
+                    {"name": "no id"},
+                    "not an object"
+                ]
+            }
+        }) {
+            Value::Object(map) => map,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_get_values() {
+        let payload = payload();
+
+        assert_eq!(get_values("name", &payload), vec![&json!("doc")]);
+        assert_eq!(
+            get_values("metadata.author.name", &payload),
+            vec![&json!("John")]
+        );
+        assert!(get_values("metadata.author.age", &payload).is_empty());
+        assert!(get_values("name.first", &payload).is_empty());
+
+        assert_eq!(
+            get_values("metadata.tags[].id", &payload),
+            vec![&json!(1), &json!(2)]
+        );
+        assert_eq!(
+            get_values("metadata.tags[].labels", &payload),
+            vec![&json!(["a", "b"]), &json!(["c"])]
+        );
+        // Not an array
+        assert!(get_values("metadata.author[].name", &payload).is_empty());
+        // Without `[]` array is returned as a single value
+        assert_eq!(get_values("metadata.tags", &payload).len(), 1);
+        assert_eq!(get_values("metadata.tags[]", &payload).len(), 4);
+    }
+
+    #[test]
+    fn test_get_nested_objects() {
+        let payload = payload();
+
+        assert_eq!(get_nested_objects("metadata.tags", &payload).len(), 3);
+        assert_eq!(get_nested_objects("metadata.tags[]", &payload).len(), 3);
+        assert_eq!(get_nested_objects("metadata.author", &payload).len(), 1);
+        assert!(get_nested_objects("name", &payload).is_empty());
+    }
+
+    #[test]
+    fn test_merge_values() {
+        let payload = payload();
+
+        assert!(merge_values(get_values("missing", &payload)).is_none());
+        assert_eq!(
+            merge_values(get_values("name", &payload)).unwrap().as_ref(),
+            &json!("doc")
+        );
+        assert_eq!(
+            merge_values(get_values("metadata.tags[].labels", &payload))
+                .unwrap()
+                .as_ref(),
+            &json!(["a", "b", "c"])
+        );
+    }
+}
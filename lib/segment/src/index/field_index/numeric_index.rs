@@ -3,6 +3,7 @@ use std::collections::BTreeMap;
 use std::ops::Bound::{Excluded, Included, Unbounded};
 use std::sync::Arc;
 
+use ordered_float::OrderedFloat;
 use parking_lot::RwLock;
 use rocksdb::DB;
 use serde_json::Value;
@@ -21,7 +22,8 @@ use crate::index::key_encoding::{
 };
 use crate::telemetry::PayloadIndexTelemetry;
 use crate::types::{
-    FieldCondition, FloatPayloadType, IntPayloadType, PayloadKeyType, PointOffsetType, Range,
+    DateTimePayloadType, Direction, FieldCondition, FloatPayloadType, IntPayloadType,
+    PayloadKeyType, PointOffsetType, Range,
 };
 
 const HISTOGRAM_MAX_BUCKET_SIZE: usize = 10_000;
@@ -47,6 +49,12 @@ pub trait KeyDecoder {
     fn decode_key(key: &[u8]) -> (PointOffsetType, Self);
 }
 
+impl KeyEncoder for DateTimePayloadType {
+    fn encode_key(&self, id: PointOffsetType) -> Vec<u8> {
+        encode_i64_key_ascending(self.timestamp_micros(), id)
+    }
+}
+
 impl KeyDecoder for IntPayloadType {
     fn decode_key(key: &[u8]) -> (PointOffsetType, Self) {
         decode_i64_key_ascending(key)
@@ -59,6 +67,13 @@ impl KeyDecoder for FloatPayloadType {
     }
 }
 
+impl KeyDecoder for DateTimePayloadType {
+    fn decode_key(key: &[u8]) -> (PointOffsetType, Self) {
+        let (id, micros) = decode_i64_key_ascending(key);
+        (id, DateTimePayloadType(micros))
+    }
+}
+
 pub trait FromRangeValue {
     fn from_range(range_value: f64) -> Self;
 }
@@ -75,6 +90,12 @@ impl FromRangeValue for IntPayloadType {
     }
 }
 
+impl FromRangeValue for DateTimePayloadType {
+    fn from_range(range_value: f64) -> Self {
+        DateTimePayloadType(range_value as i64)
+    }
+}
+
 pub trait ToRangeValue {
     fn to_range(value: Self) -> f64;
 }
@@ -91,6 +112,51 @@ impl ToRangeValue for IntPayloadType {
     }
 }
 
+impl ToRangeValue for DateTimePayloadType {
+    fn to_range(value: Self) -> f64 {
+        value.timestamp_micros() as f64
+    }
+}
+
+/// Range conditions, which the index of the value type is able to serve
+pub trait RangeCondition {
+    /// Range of the `condition` in the index value space, `None` if the index can't serve it
+    fn condition_range(condition: &FieldCondition) -> Option<Range>;
+
+    /// Condition, which selects the points with the values in the given index value `range`
+    fn range_condition(key: PayloadKeyType, range: Range) -> FieldCondition;
+}
+
+impl RangeCondition for IntPayloadType {
+    fn condition_range(condition: &FieldCondition) -> Option<Range> {
+        condition.range.clone()
+    }
+
+    fn range_condition(key: PayloadKeyType, range: Range) -> FieldCondition {
+        FieldCondition::new_range(key, range)
+    }
+}
+
+impl RangeCondition for FloatPayloadType {
+    fn condition_range(condition: &FieldCondition) -> Option<Range> {
+        condition.range.clone()
+    }
+
+    fn range_condition(key: PayloadKeyType, range: Range) -> FieldCondition {
+        FieldCondition::new_range(key, range)
+    }
+}
+
+impl RangeCondition for DateTimePayloadType {
+    fn condition_range(condition: &FieldCondition) -> Option<Range> {
+        condition.datetime_range.map(Range::from)
+    }
+
+    fn range_condition(key: PayloadKeyType, range: Range) -> FieldCondition {
+        FieldCondition::new_datetime_range(key, range.into())
+    }
+}
+
 pub struct NumericIndex<T: KeyEncoder + KeyDecoder + FromRangeValue + Clone> {
     map: BTreeMap<Vec<u8>, u32>,
     db_wrapper: DatabaseColumnWrapper,
@@ -154,7 +220,8 @@ impl<T: KeyEncoder + KeyDecoder + FromRangeValue + ToRangeValue + Clone> Numeric
             return Ok(false);
         };
 
-        for (key, value) in self.db_wrapper.lock_db().iter()? {
+        for record in self.db_wrapper.lock_db().iter()? {
+            let (key, value) = record?;
             let value_idx = u32::from_be_bytes(value.as_ref().try_into().unwrap());
             let (idx, value) = T::decode_key(&key);
 
@@ -207,6 +274,61 @@ impl<T: KeyEncoder + KeyDecoder + FromRangeValue + ToRangeValue + Clone> Numeric
         self.point_to_values.get(idx as usize)
     }
 
+    /// Value, by which the point is ordered: the smallest one for ascending order
+    /// and the largest one for descending order.
+    pub fn ordering_value(
+        &self,
+        idx: PointOffsetType,
+        direction: Direction,
+    ) -> Option<FloatPayloadType> {
+        let values = self
+            .get_values(idx)?
+            .iter()
+            .map(|value| OrderedFloat(T::to_range(*value)));
+        let value = match direction {
+            Direction::Asc => values.min(),
+            Direction::Desc => values.max(),
+        };
+        value.map(|value| value.0)
+    }
+
+    /// Iterate over indexed `(value, point)` pairs in the given direction, starting from
+    /// `start_from` value (inclusive).
+    /// Points with multiple values are returned once per value.
+    pub fn ordered_points(
+        &self,
+        start_from: Option<FloatPayloadType>,
+        direction: Direction,
+    ) -> Box<dyn Iterator<Item = (FloatPayloadType, PointOffsetType)> + '_> {
+        let decode = |(key, _): (&Vec<u8>, &PointOffsetType)| {
+            let (idx, value) = T::decode_key(key);
+            (T::to_range(value), idx)
+        };
+        match (direction, start_from) {
+            (Direction::Asc, None) => Box::new(self.map.iter().map(decode)),
+            (Direction::Desc, None) => Box::new(self.map.iter().rev().map(decode)),
+            (Direction::Asc, Some(start_from)) => {
+                let start_key = T::from_range(start_from).encode_key(PointOffsetType::MIN);
+                Box::new(
+                    self.map
+                        .range((Included(start_key), Unbounded))
+                        .map(decode)
+                        .filter(move |(value, _)| *value >= start_from),
+                )
+            }
+            (Direction::Desc, Some(start_from)) => {
+                let end_key = T::from_range(start_from).encode_key(PointOffsetType::MAX);
+                Box::new(
+                    self.map
+                        .range((Unbounded, Included(end_key)))
+                        .rev()
+                        .map(decode)
+                        .filter(move |(value, _)| *value <= start_from),
+                )
+            }
+        }
+    }
+
     fn range_cardinality(&self, range: &Range) -> CardinalityEstimation {
         let lbound = if let Some(lte) = range.lte {
             Included(lte)
@@ -334,8 +456,8 @@ impl<T: KeyEncoder + KeyDecoder + FromRangeValue + ToRangeValue + Clone> Numeric
     }
 }
 
-impl<T: KeyEncoder + KeyDecoder + FromRangeValue + ToRangeValue + Clone> PayloadFieldIndex
-    for NumericIndex<T>
+impl<T: KeyEncoder + KeyDecoder + FromRangeValue + ToRangeValue + RangeCondition + Clone>
+    PayloadFieldIndex for NumericIndex<T>
 {
     fn indexed_points(&self) -> usize {
         self.points_count
@@ -357,7 +479,7 @@ impl<T: KeyEncoder + KeyDecoder + FromRangeValue + ToRangeValue + Clone> Payload
         &self,
         condition: &FieldCondition,
     ) -> Option<Box<dyn Iterator<Item = PointOffsetType> + '_>> {
-        let cond_range = condition.range.as_ref()?;
+        let cond_range = &T::condition_range(condition)?;
 
         let start_bound = match cond_range {
             Range { gt: Some(gt), .. } => {
@@ -403,8 +525,8 @@ impl<T: KeyEncoder + KeyDecoder + FromRangeValue + ToRangeValue + Clone> Payload
     }
 
     fn estimate_cardinality(&self, condition: &FieldCondition) -> Option<CardinalityEstimation> {
-        condition.range.as_ref().map(|range| {
-            let mut cardinality = self.range_cardinality(range);
+        T::condition_range(condition).map(|range| {
+            let mut cardinality = self.range_cardinality(&range);
             cardinality
                 .primary_clauses
                 .push(PrimaryCondition::Condition(condition.clone()));
@@ -450,7 +572,7 @@ impl<T: KeyEncoder + KeyDecoder + FromRangeValue + ToRangeValue + Clone> Payload
                 };
                 let cardinality = self.range_cardinality(&range);
                 let condition = PayloadBlockCondition {
-                    condition: FieldCondition::new_range(key.clone(), range),
+                    condition: T::range_condition(key.clone(), range),
                     cardinality: cardinality.exp,
                 };
 
@@ -458,7 +580,7 @@ impl<T: KeyEncoder + KeyDecoder + FromRangeValue + ToRangeValue + Clone> Payload
             } else if upper_bound == Unbounded {
                 // One block covers all points
                 payload_conditions.push(PayloadBlockCondition {
-                    condition: FieldCondition::new_range(
+                    condition: T::range_condition(
                         key.clone(),
                         Range {
                             gte: None,
@@ -529,6 +651,27 @@ impl ValueIndexer<FloatPayloadType> for NumericIndex<FloatPayloadType> {
     }
 }
 
+impl ValueIndexer<DateTimePayloadType> for NumericIndex<DateTimePayloadType> {
+    fn add_many(
+        &mut self,
+        id: PointOffsetType,
+        values: Vec<DateTimePayloadType>,
+    ) -> OperationResult<()> {
+        self.add_many_to_list(id, values)
+    }
+
+    fn get_value(&self, value: &Value) -> Option<DateTimePayloadType> {
+        if let Value::String(datetime) = value {
+            return datetime.parse().ok();
+        }
+        None
+    }
+
+    fn remove_point(&mut self, id: PointOffsetType) -> OperationResult<()> {
+        NumericIndex::remove_point(self, id)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use itertools::Itertools;
@@ -538,6 +681,7 @@ mod tests {
 
     use super::*;
     use crate::common::rocksdb_wrapper::open_db_with_existing_cf;
+    use crate::types::DatetimeRange;
 
     const COLUMN_NAME: &str = "test";
 
@@ -836,10 +980,82 @@ mod tests {
             geo_bounding_box: None,
             geo_radius: None,
             values_count: None,
+            datetime_range: None,
         };
 
         let offsets = index.filter(&condition).unwrap().collect_vec();
 
         assert_eq!(offsets, result);
     }
+
+    #[test]
+    fn test_ordered_points() {
+        let (_tmp_dir, mut index) = get_index();
+        index.add_many_to_list(0, [3.0]).unwrap();
+        index.add_many_to_list(1, [1.0, 5.0]).unwrap();
+        index.add_many_to_list(2, [2.0]).unwrap();
+        index.add_many_to_list(3, [2.0]).unwrap();
+
+        let asc = index.ordered_points(None, Direction::Asc).collect_vec();
+        assert_eq!(asc, vec![(1.0, 1), (2.0, 2), (2.0, 3), (3.0, 0), (5.0, 1)]);
+
+        let asc_from = index
+            .ordered_points(Some(2.5), Direction::Asc)
+            .collect_vec();
+        assert_eq!(asc_from, vec![(3.0, 0), (5.0, 1)]);
+
+        let desc_from = index
+            .ordered_points(Some(2.0), Direction::Desc)
+            .collect_vec();
+        assert_eq!(desc_from, vec![(2.0, 3), (2.0, 2), (1.0, 1)]);
+    }
+
+    #[test]
+    fn test_datetime_range_filter() {
+        let tmp_dir = Builder::new()
+            .prefix("test_datetime_index")
+            .tempdir()
+            .unwrap();
+        let db = open_db_with_existing_cf(tmp_dir.path()).unwrap();
+        let mut index: NumericIndex<DateTimePayloadType> = NumericIndex::new(db, COLUMN_NAME);
+        index.recreate().unwrap();
+
+        let payloads = [
+            "2023-02-08T10:49:00Z",
+            "2023-02-08T11:49:00Z",
+            "2023-02-08T14:49:00+02:00",
+            "not a datetime",
+        ];
+        for (id, payload) in payloads.into_iter().enumerate() {
+            index
+                .add_point(id as PointOffsetType, &Value::String(payload.to_string()))
+                .unwrap();
+        }
+
+        let range = DatetimeRange {
+            lt: None,
+            gt: Some("2023-02-08T10:49:00Z".parse().unwrap()),
+            gte: None,
+            lte: Some("2023-02-08T12:49:00Z".parse().unwrap()),
+        };
+        let condition = FieldCondition::new_datetime_range("".to_string(), range);
+
+        let offsets = index.filter(&condition).unwrap().collect_vec();
+        assert_eq!(offsets, vec![1, 2]);
+
+        let estimation = index.estimate_cardinality(&condition).unwrap();
+        assert!(estimation.min <= 2 && estimation.max >= 2);
+
+        // Numeric range conditions are not served by the datetime index
+        let numeric = FieldCondition::new_range(
+            "".to_string(),
+            Range {
+                lt: None,
+                gt: Some(0.0),
+                gte: None,
+                lte: None,
+            },
+        );
+        assert!(index.filter(&numeric).is_none());
+    }
 }
@@ -1,18 +1,21 @@
 use std::cmp::max;
 use std::path::{Path, PathBuf};
+use std::time::Instant;
 
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
+use crate::common::checksums::save_checksums;
 use crate::common::file_operations::{atomic_save_bin, read_bin};
 use crate::common::utils::rev_range;
 use crate::entry::entry_point::OperationResult;
+use crate::index::apply_score_threshold;
 use crate::index::hnsw_index::entry_points::EntryPoints;
 use crate::index::hnsw_index::point_scorer::FilteredScorer;
 use crate::index::hnsw_index::search_context::SearchContext;
 use crate::index::visited_pool::{VisitedList, VisitedPool};
 use crate::spaces::tools::FixedLengthPriorityQueue;
-use crate::types::PointOffsetType;
+use crate::types::{PointOffsetType, ScoreType};
 use crate::vector_storage::ScoredPointOffset;
 
 pub type LinkContainer = Vec<PointOffsetType>;
@@ -73,13 +76,14 @@ pub trait GraphLayersBase {
     fn get_m(&self, level: usize) -> usize;
 
     /// Greedy search for closest points within a single graph layer
+    /// Returns `true` if the search was interrupted by the deadline of the `searcher`
     fn _search_on_level(
         &self,
         searcher: &mut SearchContext,
         level: usize,
         visited_list: &mut VisitedList,
         points_scorer: &mut FilteredScorer,
-    ) {
+    ) -> bool {
         let limit = self.get_m(level);
         let mut points_ids: Vec<PointOffsetType> = Vec::with_capacity(2 * limit);
 
@@ -87,6 +91,9 @@ pub trait GraphLayersBase {
             if candidate.score < searcher.lower_bound() {
                 break;
             }
+            if searcher.is_expired() {
+                return true;
+            }
 
             points_ids.clear();
             self.links_map(candidate.idx, level, |link| {
@@ -101,8 +108,10 @@ pub trait GraphLayersBase {
                 .copied()
                 .for_each(|score_point| searcher.process_candidate(score_point));
         }
+        false
     }
 
+    /// Returns nearest points of the level and whether the search was interrupted by `deadline`
     fn search_on_level(
         &self,
         level_entry: ScoredPointOffset,
@@ -110,12 +119,14 @@ pub trait GraphLayersBase {
         ef: usize,
         points_scorer: &mut FilteredScorer,
         existing_links: &[PointOffsetType],
-    ) -> FixedLengthPriorityQueue<ScoredPointOffset> {
+        deadline: Option<Instant>,
+    ) -> (FixedLengthPriorityQueue<ScoredPointOffset>, bool) {
         let mut visited_list = self.get_visited_list_from_pool();
         visited_list.check_and_update_visited(level_entry.idx);
-        let mut search_context = SearchContext::new(level_entry, ef);
+        let mut search_context = SearchContext::new(level_entry, ef).with_deadline(deadline);
 
-        self._search_on_level(&mut search_context, level, &mut visited_list, points_scorer);
+        let truncated =
+            self._search_on_level(&mut search_context, level, &mut visited_list, points_scorer);
 
         for &existing_link in existing_links {
             if !visited_list.check(existing_link) {
@@ -127,7 +138,7 @@ pub trait GraphLayersBase {
         }
 
         self.return_visited_list_to_pool(visited_list);
-        search_context.nearest
+        (search_context.nearest, truncated)
     }
 
     /// Greedy searches for entry point of level `target_level`.
@@ -278,17 +289,25 @@ impl GraphLayers {
         self.visited_pool.return_back(visited_list);
     }
 
+    /// Search for `top` nearest points.
+    /// If `deadline` is reached, search stops and returns the best points found so far,
+    /// second value of the result is `true` in this case.
+    /// Points with the score below `score_threshold` are not returned.
+    /// Threshold does not limit the traversal: a candidate below it might still lead
+    /// to the points above it, so the result is the same as of the post-filtered search.
     pub fn search(
         &self,
         top: usize,
         ef: usize,
         mut points_scorer: FilteredScorer,
-    ) -> Vec<ScoredPointOffset> {
+        score_threshold: Option<ScoreType>,
+        deadline: Option<Instant>,
+    ) -> (Vec<ScoredPointOffset>, bool) {
         let entry_point = match self
             .entry_points
             .get_entry_point(|point_id| points_scorer.check_point(point_id))
         {
-            None => return vec![],
+            None => return (vec![], false),
             Some(ep) => ep,
         };
 
@@ -299,9 +318,18 @@ impl GraphLayers {
             &mut points_scorer,
         );
 
-        let nearest =
-            self.search_on_level(zero_level_entry, 0, max(top, ef), &mut points_scorer, &[]);
-        nearest.into_iter().take(top).collect_vec()
+        let (nearest, truncated) = self.search_on_level(
+            zero_level_entry,
+            0,
+            max(top, ef),
+            &mut points_scorer,
+            &[],
+            deadline,
+        );
+
+        let mut nearest = nearest.into_iter().take(top).collect_vec();
+        apply_score_threshold(&mut nearest, score_threshold);
+        (nearest, truncated)
     }
 
     pub fn get_path(path: &Path) -> PathBuf {
@@ -328,7 +356,8 @@ impl GraphLayers {
     }
 
     pub fn save(&self, path: &Path) -> OperationResult<()> {
-        Ok(atomic_save_bin(path, self)?)
+        atomic_save_bin(path, self)?;
+        save_checksums(path)
     }
 }
 
@@ -361,7 +390,9 @@ mod tests {
         let raw_scorer = vector_storage.get_raw_scorer(query.to_owned());
         let scorer = FilteredScorer::new(&raw_scorer, Some(&fake_filter_context));
         let ef = 16;
-        graph.search(top, ef, scorer)
+        let (result, truncated) = graph.search(top, ef, scorer, None, None);
+        assert!(!truncated);
+        result
     }
 
     const M: usize = 8;
@@ -391,7 +422,7 @@ mod tests {
         let raw_scorer = vector_holder.get_raw_scorer(added_vector);
         let mut scorer = FilteredScorer::new(&raw_scorer, Some(&fake_filter_context));
 
-        let nearest_on_level = graph_layers.search_on_level(
+        let (nearest_on_level, truncated) = graph_layers.search_on_level(
             ScoredPointOffset {
                 idx: 0,
                 score: scorer.score_point(0),
@@ -400,13 +431,31 @@ mod tests {
             32,
             &mut scorer,
             &[],
+            None,
         );
 
+        assert!(!truncated);
         assert_eq!(
             nearest_on_level.len(),
             graph_layers.links_layers[0][0].len() + 1
         );
 
+        // With exhausted time budget only the entry point is returned
+        let (truncated_on_level, truncated) = graph_layers.search_on_level(
+            ScoredPointOffset {
+                idx: 0,
+                score: scorer.score_point(0),
+            },
+            0,
+            32,
+            &mut scorer,
+            &[],
+            Some(Instant::now()),
+        );
+
+        assert!(truncated);
+        assert_eq!(truncated_on_level.len(), 1);
+
         for nearest in &nearest_on_level {
             // eprintln!("nearest = {:#?}", nearest);
             assert_eq!(
@@ -443,6 +492,44 @@ mod tests {
         assert_eq!(res1, res2)
     }
 
+    #[test]
+    fn test_search_with_score_threshold() {
+        let num_vectors = 1000;
+        let dim = 8;
+        let top = 50;
+        let ef = 64;
+
+        let mut rng = StdRng::seed_from_u64(42);
+
+        let (vector_holder, graph_layers) =
+            create_graph_layer_fixture::<CosineMetric, _>(num_vectors, M, dim, false, &mut rng);
+        let fake_filter_context = FakeFilterContext {};
+
+        for _ in 0..10 {
+            let query = random_vector(&mut rng, dim);
+            let raw_scorer = vector_holder.get_raw_scorer(query);
+
+            let scorer = FilteredScorer::new(&raw_scorer, Some(&fake_filter_context));
+            let (full_result, _) = graph_layers.search(top, ef, scorer, None, None);
+            assert_eq!(full_result.len(), top);
+
+            // High thresholds are likely above the score of the entry point
+            for threshold_rank in [0, 2, top / 2, top - 1] {
+                let score_threshold = full_result[threshold_rank].score;
+                let mut expected = full_result.clone();
+                apply_score_threshold(&mut expected, Some(score_threshold));
+
+                let scorer = FilteredScorer::new(&raw_scorer, Some(&fake_filter_context));
+                let (result, truncated) =
+                    graph_layers.search(top, ef, scorer, Some(score_threshold), None);
+
+                assert!(!truncated);
+                assert_eq!(result, expected);
+                assert!(result.iter().all(|point| point.score > score_threshold));
+            }
+        }
+    }
+
     #[test]
     fn test_add_points() {
         let num_vectors = 1000;
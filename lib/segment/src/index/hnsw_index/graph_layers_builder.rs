@@ -1,5 +1,6 @@
 use std::cmp::min;
 use std::collections::BinaryHeap;
+use std::mem::size_of;
 use std::sync::atomic::AtomicUsize;
 
 use parking_lot::{Mutex, RwLock};
@@ -133,6 +134,32 @@ impl GraphLayersBuilder {
         )
     }
 
+    /// Limit the number of visited lists, preserved between the insertions of points.
+    /// Lists are not shared by threads, so there is no need to keep more of them, than threads.
+    pub fn limit_visited_pool(&mut self, keep_limit: usize) {
+        self.visited_pool = VisitedPool::with_keep_limit(keep_limit);
+    }
+
+    /// Expected memory, taken by the links of the graph of `num_vectors` points, in bytes
+    pub fn estimate_links_memory(num_vectors: usize, m: usize, m0: usize, reserve: bool) -> usize {
+        let layer_size = size_of::<LockedLinkContainer>();
+        let link_size = size_of::<PointOffsetType>();
+        let level0_size = size_of::<LockedLayersContainer>()
+            + layer_size
+            + if reserve { m0 * link_size } else { 0 };
+        // Level of a point is higher than `l` with probability about `m^-l`,
+        // so each point has `1 / (m - 1)` upper layers on average
+        let upper_levels_size = (layer_size + m * link_size) / m.saturating_sub(1).max(1);
+        num_vectors.saturating_mul(level0_size + upper_levels_size)
+    }
+
+    /// Expected memory, taken by each thread, inserting points into the graph, in bytes.
+    /// Includes the visited list and the lists of candidates.
+    pub fn estimate_thread_memory(num_vectors: usize, ef_construct: usize) -> usize {
+        let candidates_size = 2 * ef_construct * size_of::<ScoredPointOffset>();
+        VisitedList::memory_size(num_vectors).saturating_add(candidates_size)
+    }
+
     fn num_points(&self) -> usize {
         self.links_layers.len()
     }
@@ -285,7 +312,7 @@ impl GraphLayersBuilder {
                 for curr_level in (0..=linking_level).rev() {
                     let level_m = self.get_m(curr_level);
 
-                    let nearest_points = {
+                    let (nearest_points, _) = {
                         let existing_links =
                             self.links_layers[point_id as usize][curr_level].read();
                         self.search_on_level(
@@ -294,6 +321,7 @@ impl GraphLayersBuilder {
                             self.ef_construct,
                             &mut points_scorer,
                             &existing_links,
+                            None,
                         )
                     };
 
@@ -544,7 +572,7 @@ mod tests {
         let raw_scorer = vector_holder.get_raw_scorer(query);
         let scorer = FilteredScorer::new(&raw_scorer, Some(&fake_filter_context));
         let ef = 16;
-        let graph_search = graph.search(top, ef, scorer);
+        let (graph_search, _) = graph.search(top, ef, scorer, None, None);
 
         assert_eq!(reference_top.into_vec(), graph_search);
     }
@@ -625,7 +653,7 @@ mod tests {
         let raw_scorer = vector_holder.get_raw_scorer(query);
         let scorer = FilteredScorer::new(&raw_scorer, Some(&fake_filter_context));
         let ef = 16;
-        let graph_search = graph.search(top, ef, scorer);
+        let (graph_search, _) = graph.search(top, ef, scorer, None, None);
 
         assert_eq!(reference_top.into_vec(), graph_search);
     }
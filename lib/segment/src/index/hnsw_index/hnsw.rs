@@ -1,8 +1,10 @@
+use std::cell::Cell;
 use std::cmp::max;
 use std::fs::create_dir_all;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 
 use atomic_refcell::AtomicRefCell;
 use log::debug;
@@ -11,23 +13,32 @@ use rand::thread_rng;
 use rayon::prelude::*;
 use rayon::ThreadPool;
 
+use crate::common::indexing_budget::{acquire_indexing_memory, indexing_memory_budget};
 use crate::common::operation_time_statistics::{
     OperationDurationsAggregator, ScopeDurationMeasurer,
 };
-use crate::data_types::vectors::VectorElementType;
+use crate::data_types::vectors::{QueryVector, VectorElementType};
 use crate::entry::entry_point::{OperationError, OperationResult};
 use crate::index::hnsw_index::build_condition_checker::BuildConditionChecker;
 use crate::index::hnsw_index::config::HnswGraphConfig;
 use crate::index::hnsw_index::graph_layers::GraphLayers;
 use crate::index::hnsw_index::graph_layers_builder::GraphLayersBuilder;
 use crate::index::hnsw_index::point_scorer::FilteredScorer;
+use crate::index::query_planner::{
+    plan_filtered_search, post_filter_oversampled_top, SearchStrategy,
+};
 use crate::index::sample_estimation::sample_check_cardinality;
 use crate::index::struct_payload_index::StructPayloadIndex;
 use crate::index::visited_pool::VisitedList;
-use crate::index::{PayloadIndex, VectorIndex};
+use crate::index::{apply_score_threshold, PayloadIndex, VectorIndex, VectorIndexSearchResult};
+use crate::spaces::tools::peek_top_largest_iterable;
 use crate::telemetry::VectorIndexSearchesTelemetry;
 use crate::types::Condition::Field;
-use crate::types::{FieldCondition, Filter, HnswConfig, SearchParams, VECTOR_ELEMENT_SIZE};
+use crate::types::{
+    FieldCondition, Filter, FilterCardinality, HnswConfig, ScoreType, SearchParams,
+    VECTOR_ELEMENT_SIZE,
+};
+use crate::vector_storage::query_scorer::{query_raw_scorer, score_query_all, score_query_points};
 use crate::vector_storage::{ScoredPointOffset, VectorStorageSS};
 
 const HNSW_USE_HEURISTIC: bool = true;
@@ -107,6 +118,11 @@ impl HNSWIndex {
         })
     }
 
+    /// Check if the graph of non-empty vector storage is not stored at `path`
+    pub fn is_graph_missing(path: &Path, vector_storage: &VectorStorageSS) -> bool {
+        vector_storage.total_vector_count() > 0 && !GraphLayers::get_path(path).exists()
+    }
+
     fn save_config(&self) -> OperationResult<()> {
         let config_path = HnswGraphConfig::get_config_path(&self.path);
         self.config.save(&config_path)
@@ -174,131 +190,322 @@ impl HNSWIndex {
         })
     }
 
+    /// Size of the beam to search `top` points with
+    fn search_ef(&self, top: usize, params: Option<&SearchParams>) -> usize {
+        let req_ef = params
+            .and_then(|params| params.hnsw_ef)
+            .unwrap_or(self.config.ef);
+
+        // ef should always be bigger that required top
+        max(req_ef, top)
+    }
+
+    /// Search with HNSW graph, stops at `deadline` if any.
+    /// Returns found points and whether the search was interrupted by the deadline.
     pub fn search_with_graph(
         &self,
         vector: &[VectorElementType],
         filter: Option<&Filter>,
         top: usize,
         params: Option<&SearchParams>,
-    ) -> Vec<ScoredPointOffset> {
-        let req_ef = params
-            .and_then(|params| params.hnsw_ef)
-            .unwrap_or(self.config.ef);
+        score_threshold: Option<ScoreType>,
+        deadline: Option<Instant>,
+    ) -> (Vec<ScoredPointOffset>, bool) {
+        self.search_with_graph_counted(
+            QueryVector::Nearest(vector),
+            filter,
+            top,
+            params,
+            score_threshold,
+            deadline,
+            &Cell::default(),
+        )
+    }
 
-        // ef should always be bigger that required top
-        let ef = max(req_ef, top);
+    /// Same as `search_with_graph`, adds the number of scored vectors to `scored`
+    #[allow(clippy::too_many_arguments)]
+    fn search_with_graph_counted(
+        &self,
+        query: QueryVector,
+        filter: Option<&Filter>,
+        top: usize,
+        params: Option<&SearchParams>,
+        score_threshold: Option<ScoreType>,
+        deadline: Option<Instant>,
+        scored: &Cell<usize>,
+    ) -> (Vec<ScoredPointOffset>, bool) {
+        let ef = self.search_ef(top, params);
 
         let vector_storage = self.vector_storage.borrow();
-        let raw_scorer = vector_storage.raw_scorer(vector.to_owned());
+        let raw_scorer = query_raw_scorer(&*vector_storage, query);
         let payload_index = self.payload_index.borrow();
 
         let filter_context = filter.map(|f| payload_index.filter_context(f));
 
-        let points_scorer = FilteredScorer::new(raw_scorer.as_ref(), filter_context.as_deref());
+        // Only the nearest search is approximated with the quantized vectors
+        let quantized_scorer = match query {
+            QueryVector::Nearest(vector) => vector_storage.quantized_raw_scorer(vector),
+            QueryVector::Discovery(_) => None,
+        };
+        if let Some(quantized_scorer) = quantized_scorer {
+            // Find `ef` candidates by approximate scores, then rescore them with original vectors
+            let points_scorer =
+                FilteredScorer::new(quantized_scorer.as_ref(), filter_context.as_deref())
+                    .with_scored_counter(scored);
+            let (candidates, truncated) = self.graph.search(ef, ef, points_scorer, None, deadline);
+            scored.set(scored.get() + candidates.len());
+            let rescored = candidates.into_iter().map(|candidate| ScoredPointOffset {
+                idx: candidate.idx,
+                score: raw_scorer.score_point(candidate.idx),
+            });
+            let mut result = peek_top_largest_iterable(rescored, top);
+            apply_score_threshold(&mut result, score_threshold);
+            return (result, truncated);
+        }
+
+        let points_scorer = FilteredScorer::new(raw_scorer.as_ref(), filter_context.as_deref())
+            .with_scored_counter(scored);
 
-        self.graph.search(top, ef, points_scorer)
+        self.graph
+            .search(top, ef, points_scorer, score_threshold, deadline)
     }
 
-    fn search_vectors_with_graph(
+    /// Search the graph without the filter for slightly more points, than required,
+    /// and filter them afterwards. Falls back to the filtered search, if not enough points match.
+    #[allow(clippy::too_many_arguments)]
+    fn search_queries_with_post_filter(
         &self,
-        vectors: &[&[VectorElementType]],
+        queries: &[QueryVector],
+        filter: &Filter,
+        top: usize,
+        params: Option<&SearchParams>,
+        score_threshold: Option<ScoreType>,
+        deadline: Option<Instant>,
+        scored: &Cell<usize>,
+    ) -> (Vec<Vec<ScoredPointOffset>>, bool) {
+        let payload_index = self.payload_index.borrow();
+        let filter_context = payload_index.filter_context(filter);
+        let oversampled_top = post_filter_oversampled_top(top);
+        let mut truncated = false;
+        let results = queries
+            .iter()
+            .map(|query| {
+                let (mut result, vector_truncated) = self.search_with_graph_counted(
+                    *query,
+                    None,
+                    oversampled_top,
+                    params,
+                    score_threshold,
+                    deadline,
+                    scored,
+                );
+                let found = result.len();
+                result.retain(|point| filter_context.check(point.idx));
+                if result.len() < top && found == oversampled_top {
+                    // Filter matches fewer points, than estimated
+                    let (result, vector_truncated) = self.search_with_graph_counted(
+                        *query,
+                        Some(filter),
+                        top,
+                        params,
+                        score_threshold,
+                        deadline,
+                        scored,
+                    );
+                    truncated |= vector_truncated;
+                    return result;
+                }
+                result.truncate(top);
+                truncated |= vector_truncated;
+                result
+            })
+            .collect();
+        (results, truncated)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn search_queries_with_graph(
+        &self,
+        queries: &[QueryVector],
         filter: Option<&Filter>,
         top: usize,
         params: Option<&SearchParams>,
-    ) -> Vec<Vec<ScoredPointOffset>> {
-        vectors
+        score_threshold: Option<ScoreType>,
+        deadline: Option<Instant>,
+        scored: &Cell<usize>,
+    ) -> (Vec<Vec<ScoredPointOffset>>, bool) {
+        let mut truncated = false;
+        let results = queries
             .iter()
-            .map(|vector| self.search_with_graph(vector, filter, top, params))
-            .collect()
+            .map(|query| {
+                let (result, vector_truncated) = self.search_with_graph_counted(
+                    *query,
+                    filter,
+                    top,
+                    params,
+                    score_threshold,
+                    deadline,
+                    scored,
+                );
+                truncated |= vector_truncated;
+                result
+            })
+            .collect();
+        (results, truncated)
     }
 }
 
 impl VectorIndex for HNSWIndex {
-    fn search(
+    fn search_queries(
         &self,
-        vectors: &[&[VectorElementType]],
+        queries: &[QueryVector],
         filter: Option<&Filter>,
         top: usize,
         params: Option<&SearchParams>,
-    ) -> Vec<Vec<ScoredPointOffset>> {
+        score_threshold: Option<ScoreType>,
+        deadline: Option<Instant>,
+    ) -> VectorIndexSearchResult {
         let exact = params.map(|params| params.exact).unwrap_or(false);
-        match filter {
+        let scored = Cell::default();
+        let query_filter = match filter {
             None => {
-                if exact {
+                let (strategy, (results, truncated)) = if exact {
                     let _timer =
                         ScopeDurationMeasurer::new(&self.searches_telemetry.exact_unfiltered);
                     let vector_storage = self.vector_storage.borrow();
-                    vectors
+                    let results = queries
                         .iter()
-                        .map(|vector| vector_storage.score_all(vector, top))
-                        .collect()
-                } else {
-                    let _timer = ScopeDurationMeasurer::new(&self.searches_telemetry.unfiltered);
-                    self.search_vectors_with_graph(vectors, None, top, params)
-                }
-            }
-            Some(query_filter) => {
-                // depending on the amount of filtered-out points the optimal strategy could be
-                // - to retrieve possible points and score them after
-                // - to use HNSW index with filtering condition
-
-                let payload_index = self.payload_index.borrow();
-                let vector_storage = self.vector_storage.borrow();
-
-                let plain_search = || -> Vec<Vec<ScoredPointOffset>> {
-                    let mut filtered_iter = payload_index.query_points(query_filter);
-                    return vectors
-                        .iter()
-                        .map(|vector| {
-                            vector_storage.score_points(vector, filtered_iter.as_mut(), top)
+                        .map(|query| {
+                            let mut result = score_query_all(&*vector_storage, *query, top);
+                            apply_score_threshold(&mut result, score_threshold);
+                            result
                         })
                         .collect();
+                    scored.set(vector_storage.vector_count() * queries.len());
+                    (SearchStrategy::Exact, (results, false))
+                } else {
+                    let _timer = ScopeDurationMeasurer::new(&self.searches_telemetry.unfiltered);
+                    let result = self.search_queries_with_graph(
+                        queries,
+                        None,
+                        top,
+                        params,
+                        score_threshold,
+                        deadline,
+                        &scored,
+                    );
+                    (SearchStrategy::Hnsw, result)
                 };
+                return VectorIndexSearchResult {
+                    results,
+                    truncated,
+                    strategy,
+                    scored_vectors: scored.get(),
+                    hnsw_ef: (strategy == SearchStrategy::Hnsw)
+                        .then(|| self.search_ef(top, params)),
+                    filter_cardinality: None,
+                };
+            }
+            Some(query_filter) => query_filter,
+        };
 
-                // if exact search is requested, we should not use HNSW index
-                if exact {
-                    return plain_search();
-                }
-
-                let query_cardinality = payload_index.estimate_cardinality(query_filter);
-
-                // debug!("query_cardinality: {:#?}", query_cardinality);
-
-                if query_cardinality.max < self.config.indexing_threshold {
-                    // if cardinality is small - use plain index
-                    let _timer =
-                        ScopeDurationMeasurer::new(&self.searches_telemetry.small_cardinality);
-                    return plain_search();
-                }
-
-                if query_cardinality.min > self.config.indexing_threshold {
-                    // if cardinality is high enough - use HNSW index
-                    let _timer =
-                        ScopeDurationMeasurer::new(&self.searches_telemetry.large_cardinality);
-                    return self.search_vectors_with_graph(vectors, filter, top, params);
-                }
+        // depending on the amount of filtered-out points the optimal strategy could be
+        // - to retrieve possible points and score them after
+        // - to use HNSW index with filtering condition
+        // - to use HNSW index without filtering condition and filter the found points after
 
-                let filter_context = payload_index.filter_context(query_filter);
+        let payload_index = self.payload_index.borrow();
+        let vector_storage = self.vector_storage.borrow();
 
+        // if exact search is requested, we should not use HNSW index
+        let (strategy, filter_cardinality) = if exact {
+            (SearchStrategy::Exact, None)
+        } else {
+            let query_cardinality = payload_index.estimate_cardinality(query_filter);
+            let strategy = plan_filtered_search(
+                &query_cardinality,
+                self.config.indexing_threshold,
+                vector_storage.vector_count(),
+            )
+            .unwrap_or_else(|| {
                 // Fast cardinality estimation is not enough, do sample estimation of cardinality
-
+                let filter_context = payload_index.filter_context(query_filter);
                 if sample_check_cardinality(
                     vector_storage.sample_ids(),
                     |idx| filter_context.check(idx),
                     self.config.indexing_threshold,
                     vector_storage.vector_count(),
                 ) {
-                    // if cardinality is high enough - use HNSW index
-                    let _timer =
-                        ScopeDurationMeasurer::new(&self.searches_telemetry.large_cardinality);
-                    self.search_vectors_with_graph(vectors, filter, top, params)
+                    SearchStrategy::FilteredHnsw
                 } else {
-                    // if cardinality is small - use plain index
-                    let _timer =
-                        ScopeDurationMeasurer::new(&self.searches_telemetry.small_cardinality);
-                    plain_search()
+                    SearchStrategy::Exact
                 }
+            });
+            (strategy, Some(FilterCardinality::from(&query_cardinality)))
+        };
+
+        let (results, truncated) = match strategy {
+            SearchStrategy::Exact => {
+                let _timer = ScopeDurationMeasurer::new(if exact {
+                    &self.searches_telemetry.exact_filtered
+                } else {
+                    &self.searches_telemetry.small_cardinality
+                });
+                let mut filtered_iter = payload_index
+                    .query_points(query_filter)
+                    .inspect(|_| scored.set(scored.get() + 1));
+                let results = queries
+                    .iter()
+                    .map(|query| {
+                        let mut result =
+                            score_query_points(&*vector_storage, *query, &mut filtered_iter, top);
+                        apply_score_threshold(&mut result, score_threshold);
+                        result
+                    })
+                    .collect();
+                (results, false)
+            }
+            SearchStrategy::Hnsw | SearchStrategy::FilteredHnsw => {
+                let _timer = ScopeDurationMeasurer::new(&self.searches_telemetry.large_cardinality);
+                self.search_queries_with_graph(
+                    queries,
+                    filter,
+                    top,
+                    params,
+                    score_threshold,
+                    deadline,
+                    &scored,
+                )
+            }
+            SearchStrategy::HnswPostFilter => {
+                let _timer = ScopeDurationMeasurer::new(&self.searches_telemetry.large_cardinality);
+                self.search_queries_with_post_filter(
+                    queries,
+                    query_filter,
+                    top,
+                    params,
+                    score_threshold,
+                    deadline,
+                    &scored,
+                )
             }
+        };
+        let hnsw_ef = match strategy {
+            SearchStrategy::Exact => None,
+            SearchStrategy::Hnsw | SearchStrategy::FilteredHnsw => {
+                Some(self.search_ef(top, params))
+            }
+            SearchStrategy::HnswPostFilter => {
+                Some(self.search_ef(post_filter_oversampled_top(top), params))
+            }
+        };
+        VectorIndexSearchResult {
+            results,
+            truncated,
+            strategy,
+            scored_vectors: scored.get(),
+            hnsw_ef,
+            filter_cardinality,
         }
     }
 
@@ -310,6 +517,47 @@ impl VectorIndex for HNSWIndex {
         let total_points = vector_storage.total_vector_count();
 
         debug!("building hnsw for {}", total_points);
+        let payload_index = self.payload_index.borrow();
+        let has_indexed_fields = !payload_index.indexed_fields().is_empty();
+
+        // Main graph is kept, while graphs for payload blocks are built one by one
+        let mut graph_memory = GraphLayersBuilder::estimate_links_memory(
+            total_points,
+            self.config.m,
+            self.config.m0,
+            true,
+        );
+        if has_indexed_fields {
+            graph_memory = graph_memory
+                .saturating_add(GraphLayersBuilder::estimate_links_memory(
+                    total_points,
+                    self.config.m,
+                    self.config.m0,
+                    false,
+                ))
+                .saturating_add(VisitedList::memory_size(total_points));
+        }
+        let thread_memory =
+            GraphLayersBuilder::estimate_thread_memory(total_points, self.config.ef_construct);
+
+        let mut num_threads = self.config.max_rayon_threads();
+        let budget = indexing_memory_budget();
+        if let Some(budget) = budget {
+            // Use fewer threads, so their visited lists fit into the budget together with the graph
+            let budget_threads = budget.saturating_sub(graph_memory) / thread_memory.max(1);
+            if budget_threads < num_threads {
+                num_threads = max(1, budget_threads);
+                debug!(
+                    "indexing memory budget limits building of hnsw to {} threads",
+                    num_threads
+                );
+            }
+        }
+        let _memory_permit = acquire_indexing_memory(
+            graph_memory.saturating_add(num_threads.saturating_mul(thread_memory)),
+            stopped,
+        )?;
+
         let mut graph_layers_builder = GraphLayersBuilder::new(
             total_points,
             self.config.m,
@@ -318,6 +566,9 @@ impl VectorIndex for HNSWIndex {
             max(1, total_points / self.config.indexing_threshold * 10),
             HNSW_USE_HEURISTIC,
         );
+        if budget.is_some() {
+            graph_layers_builder.limit_visited_pool(num_threads);
+        }
 
         let mut ids = vec![];
         for vector_id in vector_storage.iter_ids() {
@@ -327,7 +578,7 @@ impl VectorIndex for HNSWIndex {
         }
 
         let pool = rayon::ThreadPoolBuilder::new()
-            .num_threads(self.config.max_rayon_threads())
+            .num_threads(num_threads)
             .build()?;
 
         pool.install(|| {
@@ -347,14 +598,16 @@ impl VectorIndex for HNSWIndex {
         })?;
 
         self.graph = graph_layers_builder.into_graph_layers();
+        if budget.is_some() {
+            // Lists of the construction are not accounted after the build, search allocates its own
+            self.graph.visited_pool.clear();
+        }
 
         debug!("finish main graph");
 
         let total_vectors_count = vector_storage.total_vector_count();
         let mut block_filter_list = VisitedList::new(total_vectors_count);
 
-        let payload_index = self.payload_index.borrow();
-
         for (field, _) in payload_index.indexed_fields() {
             debug!("building additional index for field {}", &field);
 
@@ -385,6 +638,9 @@ impl VectorIndex for HNSWIndex {
                     HNSW_USE_HEURISTIC,
                     false,
                 );
+                if budget.is_some() {
+                    additional_graph.limit_visited_pool(num_threads);
+                }
                 self.build_filtered_graph(
                     &pool,
                     stopped,
@@ -1,3 +1,5 @@
+use std::cell::Cell;
+
 use crate::payload_storage::FilterContext;
 use crate::types::{PointOffsetType, ScoreType};
 use crate::vector_storage::{RawScorer, ScoredPointOffset};
@@ -6,6 +8,8 @@ pub struct FilteredScorer<'a> {
     pub raw_scorer: &'a dyn RawScorer,
     pub filter_context: Option<&'a dyn FilterContext>,
     points_buffer: Vec<ScoredPointOffset>,
+    /// Counts scored points, if set
+    scored_counter: Option<&'a Cell<usize>>,
 }
 
 impl<'a> FilteredScorer<'a> {
@@ -17,6 +21,19 @@ impl<'a> FilteredScorer<'a> {
             raw_scorer,
             filter_context,
             points_buffer: Vec::new(),
+            scored_counter: None,
+        }
+    }
+
+    /// Add the number of scored points to the `counter`
+    pub fn with_scored_counter(mut self, counter: &'a Cell<usize>) -> Self {
+        self.scored_counter = Some(counter);
+        self
+    }
+
+    fn count_scored(&self, count: usize) {
+        if let Some(counter) = self.scored_counter {
+            counter.set(counter.get() + count);
         }
     }
 
@@ -64,10 +81,12 @@ impl<'a> FilteredScorer<'a> {
         let count = self
             .raw_scorer
             .score_points(filtered_point_ids, &mut self.points_buffer);
+        self.count_scored(count);
         &self.points_buffer[0..count]
     }
 
     pub fn score_point(&self, point_id: PointOffsetType) -> ScoreType {
+        self.count_scored(1);
         self.raw_scorer.score_point(point_id)
     }
 
@@ -1,5 +1,6 @@
 use std::collections::BinaryHeap;
 use std::iter::FromIterator;
+use std::time::Instant;
 
 use num_traits::float::FloatCore;
 
@@ -13,6 +14,8 @@ pub struct SearchContext {
     pub nearest: FixedLengthPriorityQueue<ScoredPointOffset>,
     /// Current candidates to process
     pub candidates: BinaryHeap<ScoredPointOffset>,
+    /// Stop processing candidates after this moment, keeping whatever was found so far
+    pub deadline: Option<Instant>,
 }
 
 impl SearchContext {
@@ -22,9 +25,22 @@ impl SearchContext {
         SearchContext {
             nearest,
             candidates: BinaryHeap::from_iter([entry_point]),
+            deadline: None,
         }
     }
 
+    pub fn with_deadline(mut self, deadline: Option<Instant>) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Check if the time budget of the search is exhausted
+    pub fn is_expired(&self) -> bool {
+        self.deadline
+            .map(|deadline| Instant::now() >= deadline)
+            .unwrap_or(false)
+    }
+
     pub fn lower_bound(&self) -> ScoreType {
         match self.nearest.top() {
             None => ScoreType::min_value(),
@@ -1,30 +1,73 @@
 use std::collections::HashMap;
 use std::sync::atomic::AtomicBool;
+use std::time::Instant;
 
 use serde_json::Value;
 
 use crate::common::Flusher;
-use crate::data_types::vectors::VectorElementType;
+use crate::data_types::vectors::{QueryVector, VectorElementType};
 use crate::entry::entry_point::OperationResult;
 use crate::index::field_index::{CardinalityEstimation, PayloadBlockCondition};
+use crate::index::query_planner::SearchStrategy;
 use crate::payload_storage::FilterContext;
 use crate::telemetry::VectorIndexSearchesTelemetry;
 use crate::types::{
-    Filter, Payload, PayloadFieldSchema, PayloadKeyType, PayloadKeyTypeRef, PayloadSchemaType,
-    PointOffsetType, SearchParams,
+    Filter, FilterCardinality, Payload, PayloadFieldSchema, PayloadKeyType, PayloadKeyTypeRef,
+    PayloadSchemaType, PointOffsetType, ScoreType, SearchParams,
 };
 use crate::vector_storage::ScoredPointOffset;
 
+/// Found points of the vector index search
+#[derive(Debug, PartialEq)]
+pub struct VectorIndexSearchResult {
+    /// List of points per query vector
+    pub results: Vec<Vec<ScoredPointOffset>>,
+    /// Approximate search was interrupted by the deadline
+    pub truncated: bool,
+    /// Strategy, chosen by the query planner
+    pub strategy: SearchStrategy,
+    /// Number of stored vectors, the query vectors were compared with
+    pub scored_vectors: usize,
+    /// Size of the beam, if the HNSW graph was searched
+    pub hnsw_ef: Option<usize>,
+    /// Estimated number of points, matching the filter, if the search was planned by it
+    pub filter_cardinality: Option<FilterCardinality>,
+}
+
 /// Trait for vector searching
 pub trait VectorIndex {
     /// Return list of Ids with fitting
+    /// Approximate search stops at `deadline` and returns the best points found so far,
+    /// result is marked as truncated if it happened.
+    /// Only points with the score above `score_threshold` are returned,
+    /// threshold is in the internal score space - see [`crate::types::Distance::preprocess_score`].
     fn search(
         &self,
         vectors: &[&[VectorElementType]],
         filter: Option<&Filter>,
         top: usize,
         params: Option<&SearchParams>,
-    ) -> Vec<Vec<ScoredPointOffset>>;
+        score_threshold: Option<ScoreType>,
+        deadline: Option<Instant>,
+    ) -> VectorIndexSearchResult {
+        let queries: Vec<_> = vectors
+            .iter()
+            .map(|vector| QueryVector::from(*vector))
+            .collect();
+        self.search_queries(&queries, filter, top, params, score_threshold, deadline)
+    }
+
+    /// Same as `search`, but points could be ranked by other queries, than the nearest vector.
+    /// Queries are scored by the raw scorers of the vector storage, see [`QueryVector`].
+    fn search_queries(
+        &self,
+        queries: &[QueryVector],
+        filter: Option<&Filter>,
+        top: usize,
+        params: Option<&SearchParams>,
+        score_threshold: Option<ScoreType>,
+        deadline: Option<Instant>,
+    ) -> VectorIndexSearchResult;
 
     /// Force internal index rebuild.
     fn build_index(&mut self, stopped: &AtomicBool) -> OperationResult<()>;
@@ -32,6 +75,16 @@ pub trait VectorIndex {
     fn get_telemetry_data(&self) -> VectorIndexSearchesTelemetry;
 }
 
+/// Remove points, which do not pass the `score_threshold`, from the search result
+pub fn apply_score_threshold(
+    points: &mut Vec<ScoredPointOffset>,
+    score_threshold: Option<ScoreType>,
+) {
+    if let Some(score_threshold) = score_threshold {
+        points.retain(|point| point.score > score_threshold);
+    }
+}
+
 pub trait PayloadIndex {
     /// Get indexed fields
     fn indexed_fields(&self) -> HashMap<PayloadKeyType, PayloadFieldSchema>;
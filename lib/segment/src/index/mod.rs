@@ -6,6 +6,7 @@ mod payload_config;
 pub mod plain_payload_index;
 pub mod query_estimator;
 mod query_optimization;
+pub mod query_planner;
 mod sample_estimation;
 mod struct_filter_context;
 pub mod struct_payload_index;
@@ -3,6 +3,7 @@ use std::fs::create_dir_all;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
+use std::time::Instant;
 
 use atomic_refcell::AtomicRefCell;
 use parking_lot::Mutex;
@@ -13,20 +14,22 @@ use crate::common::operation_time_statistics::{
     OperationDurationStatistics, OperationDurationsAggregator, ScopeDurationMeasurer,
 };
 use crate::common::Flusher;
-use crate::data_types::vectors::VectorElementType;
+use crate::data_types::vectors::QueryVector;
 use crate::entry::entry_point::OperationResult;
 use crate::id_tracker::IdTrackerSS;
 use crate::index::field_index::{CardinalityEstimation, PayloadBlockCondition};
 use crate::index::payload_config::PayloadConfig;
+use crate::index::query_planner::SearchStrategy;
 use crate::index::struct_payload_index::StructPayloadIndex;
-use crate::index::{PayloadIndex, VectorIndex};
+use crate::index::{apply_score_threshold, PayloadIndex, VectorIndex, VectorIndexSearchResult};
 use crate::payload_storage::{ConditionCheckerSS, FilterContext};
 use crate::telemetry::VectorIndexSearchesTelemetry;
 use crate::types::{
     Filter, Payload, PayloadFieldSchema, PayloadKeyType, PayloadKeyTypeRef, PayloadSchemaType,
-    PointOffsetType, SearchParams,
+    PointOffsetType, ScoreType, SearchParams,
 };
-use crate::vector_storage::{ScoredPointOffset, VectorStorageSS};
+use crate::vector_storage::query_scorer::{score_query_all, score_query_points};
+use crate::vector_storage::VectorStorageSS;
 
 /// Implementation of `PayloadIndex` which does not really indexes anything.
 ///
@@ -207,37 +210,59 @@ impl PlainIndex {
 }
 
 impl VectorIndex for PlainIndex {
-    fn search(
+    fn search_queries(
         &self,
-        vectors: &[&[VectorElementType]],
+        queries: &[QueryVector],
         filter: Option<&Filter>,
         top: usize,
         _params: Option<&SearchParams>,
-    ) -> Vec<Vec<ScoredPointOffset>> {
-        match filter {
+        score_threshold: Option<ScoreType>,
+        _deadline: Option<Instant>,
+    ) -> VectorIndexSearchResult {
+        // Plain search is always exact, it is never interrupted
+        let (results, scored_vectors) = match filter {
             Some(filter) => {
                 let _timer = ScopeDurationMeasurer::new(&self.filtered_searches_telemetry);
                 let borrowed_payload_index = self.payload_index.borrow();
                 let filtered_ids_vec: Vec<_> =
                     borrowed_payload_index.query_points(filter).collect();
-                vectors
+                let vector_storage = self.vector_storage.borrow();
+                let results = queries
                     .iter()
-                    .map(|vector| {
-                        self.vector_storage.borrow().score_points(
-                            vector,
+                    .map(|query| {
+                        let mut result = score_query_points(
+                            &*vector_storage,
+                            *query,
                             &mut filtered_ids_vec.iter().copied(),
                             top,
-                        )
+                        );
+                        apply_score_threshold(&mut result, score_threshold);
+                        result
                     })
-                    .collect()
+                    .collect();
+                (results, filtered_ids_vec.len() * queries.len())
             }
             None => {
                 let _timer = ScopeDurationMeasurer::new(&self.unfiltered_searches_telemetry);
-                vectors
+                let vector_storage = self.vector_storage.borrow();
+                let results = queries
                     .iter()
-                    .map(|vector| self.vector_storage.borrow().score_all(vector, top))
-                    .collect()
+                    .map(|query| {
+                        let mut result = score_query_all(&*vector_storage, *query, top);
+                        apply_score_threshold(&mut result, score_threshold);
+                        result
+                    })
+                    .collect();
+                (results, vector_storage.vector_count() * queries.len())
             }
+        };
+        VectorIndexSearchResult {
+            results,
+            truncated: false,
+            strategy: SearchStrategy::Exact,
+            scored_vectors,
+            hnsw_ef: None,
+            filter_cardinality: None,
         }
     }
 
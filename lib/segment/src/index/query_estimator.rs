@@ -8,7 +8,7 @@ use std::cmp::{max, min};
 use itertools::Itertools;
 
 use crate::index::field_index::{CardinalityEstimation, PrimaryCondition};
-use crate::types::{Condition, Filter};
+use crate::types::{Condition, Filter, MinShould};
 
 pub fn combine_should_estimations(
     estimations: &[CardinalityEstimation],
@@ -38,6 +38,75 @@ pub fn combine_should_estimations(
     }
 }
 
+/// Estimation of the points, which match at least `min_count` of the conditions.
+/// Conditions are assumed to be independent for the expected value.
+pub fn combine_min_should_estimations(
+    estimations: &[CardinalityEstimation],
+    min_count: usize,
+    total: usize,
+) -> CardinalityEstimation {
+    if min_count == 0 {
+        return CardinalityEstimation::exact(total);
+    }
+    if min_count > estimations.len() {
+        return CardinalityEstimation::exact(0);
+    }
+    if min_count == 1 {
+        return combine_should_estimations(estimations, total);
+    }
+    if min_count == estimations.len() {
+        return combine_must_estimations(estimations, total);
+    }
+
+    // Each matching point is counted at least `min_count` times in the sum of all matches
+    let max_estimation = min(
+        estimations.iter().map(|x| x.max).sum::<usize>() / min_count,
+        total,
+    );
+    // Each point is counted at most `min_count - 1` times, if it is not matched,
+    // and at most once per condition otherwise
+    let min_matches = estimations.iter().map(|x| x.min).sum::<usize>() as i64;
+    let min_estimation = max(
+        0,
+        (min_matches - (min_count as i64 - 1) * total as i64)
+            / (estimations.len() - min_count + 1) as i64,
+    ) as usize;
+
+    // Probabilities of exactly `i` conditions to match, up to `min_count - 1`
+    let mut matched_prob = vec![0.0; min_count];
+    matched_prob[0] = 1.0;
+    for estimation in estimations {
+        let hit_prob = estimation.exp as f64 / total as f64;
+        for matched in (0..min_count).rev() {
+            let from_previous = if matched > 0 {
+                matched_prob[matched - 1] * hit_prob
+            } else {
+                0.0
+            };
+            matched_prob[matched] = matched_prob[matched] * (1.0 - hit_prob) + from_previous;
+        }
+    }
+    let hit_prob = 1.0 - matched_prob.iter().sum::<f64>();
+    let exp_estimation = (hit_prob * total as f64).round() as usize;
+
+    // Matching point hits at least one of the conditions, same as with `should`
+    let mut clauses: Vec<PrimaryCondition> = vec![];
+    for estimation in estimations {
+        if estimation.primary_clauses.is_empty() {
+            clauses = vec![];
+            break;
+        }
+        clauses.append(&mut estimation.primary_clauses.clone());
+    }
+
+    CardinalityEstimation {
+        primary_clauses: clauses,
+        min: min_estimation,
+        exp: min(max(exp_estimation, min_estimation), max_estimation),
+        max: max_estimation,
+    }
+}
+
 pub fn combine_must_estimations(
     estimations: &[CardinalityEstimation],
     total: usize,
@@ -109,6 +178,17 @@ where
             }
         }
     }
+    match &filter.min_should {
+        None => {}
+        Some(MinShould {
+            conditions,
+            min_count,
+        }) => {
+            filter_estimations.push(estimate_min_should(
+                estimator, conditions, *min_count, total,
+            ));
+        }
+    }
     match &filter.must_not {
         None => {}
         Some(conditions) => {
@@ -134,6 +214,20 @@ where
     combine_should_estimations(&should_estimations, total)
 }
 
+fn estimate_min_should<F>(
+    estimator: &F,
+    conditions: &[Condition],
+    min_count: usize,
+    total: usize,
+) -> CardinalityEstimation
+where
+    F: Fn(&Condition) -> CardinalityEstimation,
+{
+    let estimate = |x| estimate_condition(estimator, x, total);
+    let min_should_estimations = conditions.iter().map(estimate).collect_vec();
+    combine_min_should_estimations(&min_should_estimations, min_count, total)
+}
+
 fn estimate_must<F>(estimator: &F, conditions: &[Condition], total: usize) -> CardinalityEstimation
 where
     F: Fn(&Condition) -> CardinalityEstimation,
@@ -187,6 +281,7 @@ mod tests {
             geo_bounding_box: None,
             geo_radius: None,
             values_count: None,
+            datetime_range: None,
         })
     }
 
@@ -232,6 +327,7 @@ mod tests {
                 exp: TOTAL / 2,
                 max: TOTAL,
             },
+            Condition::Nested(_) => CardinalityEstimation::unknown(TOTAL),
         }
     }
 
@@ -247,6 +343,7 @@ mod tests {
     fn must_estimation_query_test() {
         let query = Filter {
             should: None,
+            min_should: None,
             must: Some(vec![
                 test_condition("color".to_owned()),
                 test_condition("size".to_owned()),
@@ -274,6 +371,7 @@ mod tests {
                 test_condition("color".to_owned()),
                 test_condition("size".to_owned()),
             ]),
+            min_should: None,
             must: None,
             must_not: None,
         };
@@ -293,6 +391,7 @@ mod tests {
                 test_condition("size".to_owned()),
                 test_condition("un-indexed".to_owned()),
             ]),
+            min_should: None,
             must: None,
             must_not: None,
         };
@@ -311,6 +410,7 @@ mod tests {
             should: Some(vec![
                 Condition::Filter(Filter {
                     should: None,
+                    min_should: None,
                     must: Some(vec![
                         test_condition("color".to_owned()),
                         test_condition("size".to_owned()),
@@ -319,6 +419,7 @@ mod tests {
                 }),
                 Condition::Filter(Filter {
                     should: None,
+                    min_should: None,
                     must: Some(vec![
                         test_condition("price".to_owned()),
                         test_condition("size".to_owned()),
@@ -326,6 +427,7 @@ mod tests {
                     must_not: None,
                 }),
             ]),
+            min_should: None,
             must: None,
             must_not: Some(vec![Condition::HasId(HasIdCondition {
                 has_id: HashSet::from_iter([1, 2, 3, 4, 5].into_iter().map(|x| x.into())),
@@ -343,8 +445,10 @@ mod tests {
     fn another_complex_estimation_query_test() {
         let query = Filter {
             should: None,
+            min_should: None,
             must: Some(vec![
                 Condition::Filter(Filter {
+                    min_should: None,
                     must: None,
                     should: Some(vec![
                         test_condition("color".to_owned()),
@@ -353,6 +457,7 @@ mod tests {
                     must_not: None,
                 }),
                 Condition::Filter(Filter {
+                    min_should: None,
                     must: None,
                     should: Some(vec![
                         test_condition("price".to_owned()),
@@ -391,4 +496,58 @@ mod tests {
         let res = combine_must_estimations(&estimations, 10_000);
         eprintln!("res = {:#?}", res);
     }
+
+    #[test]
+    fn min_should_estimation_query_test() {
+        let conditions = vec![
+            test_condition("color".to_owned()),
+            test_condition("size".to_owned()),
+            test_condition("price".to_owned()),
+        ];
+        let bounds =
+            |estimation: CardinalityEstimation| (estimation.min, estimation.exp, estimation.max);
+        let estimate = |min_count| {
+            estimate_filter(
+                &test_estimator,
+                &Filter::new_min_should(MinShould {
+                    conditions: conditions.clone(),
+                    min_count,
+                }),
+                TOTAL,
+            )
+        };
+
+        // Matching at least one or all of the conditions is the same as `should` and `must`
+        let should = Filter {
+            should: Some(conditions.clone()),
+            min_should: None,
+            must: None,
+            must_not: None,
+        };
+        assert_eq!(
+            bounds(estimate(1)),
+            bounds(estimate_filter(&test_estimator, &should, TOTAL))
+        );
+        let must = Filter {
+            should: None,
+            min_should: None,
+            must: Some(conditions.clone()),
+            must_not: None,
+        };
+        assert_eq!(
+            bounds(estimate(3)),
+            bounds(estimate_filter(&test_estimator, &must, TOTAL))
+        );
+
+        let estimation = estimate(2);
+        assert_eq!(estimation.primary_clauses.len(), 3);
+        assert!(estimation.max <= TOTAL);
+        assert!(estimation.exp <= estimation.max);
+        assert!(estimation.min <= estimation.exp);
+        assert!(estimation.exp <= estimate(1).exp);
+        assert!(estimation.exp >= estimate(3).exp);
+
+        assert_eq!(estimate(0).exp, TOTAL);
+        assert_eq!(estimate(4).exp, 0);
+    }
 }
@@ -5,10 +5,12 @@ use crate::index::field_index::FieldIndex;
 use crate::index::query_optimization::optimized_filter::ConditionCheckerFn;
 use crate::index::query_optimization::optimizer::IndexesMap;
 use crate::index::query_optimization::payload_provider::PayloadProvider;
-use crate::payload_storage::query_checker::{check_field_condition, check_is_empty_condition};
+use crate::payload_storage::query_checker::{
+    check_field_condition, check_is_empty_condition, check_nested_condition,
+};
 use crate::types::{
-    Condition, FieldCondition, FloatPayloadType, GeoBoundingBox, GeoRadius, Match, MatchText,
-    MatchValue, PointOffsetType, Range, ValueVariants,
+    Condition, DatetimeRange, FieldCondition, FloatPayloadType, GeoBoundingBox, GeoRadius, Match,
+    MatchText, MatchValue, PointOffsetType, Range, ValueVariants,
 };
 
 pub fn condition_converter<'a>(
@@ -41,6 +43,10 @@ pub fn condition_converter<'a>(
                 check_is_empty_condition(is_empty, &payload)
             })
         }),
+        Condition::Nested(nested) => Box::new(move |point_id| {
+            payload_provider
+                .with_payload(point_id, |payload| check_nested_condition(nested, &payload))
+        }),
         // ToDo: It might be possible to make this condition faster by using `VisitedPool` instead of HashSet
         Condition::HasId(has_id) => {
             let segment_ids: HashSet<_> = has_id
@@ -74,6 +80,13 @@ pub fn field_condition_index<'a>(
         return Some(checker);
     }
 
+    if let Some(checker) = field_condition
+        .datetime_range
+        .and_then(|cond| get_datetime_range_checkers(index, cond))
+    {
+        return Some(checker);
+    }
+
     if let Some(checker) = field_condition
         .geo_radius
         .clone()
@@ -148,24 +161,47 @@ pub fn get_range_checkers(index: &FieldIndex, range: Range) -> Option<ConditionC
     }
 }
 
+pub fn get_datetime_range_checkers(
+    index: &FieldIndex,
+    datetime_range: DatetimeRange,
+) -> Option<ConditionCheckerFn> {
+    match index {
+        FieldIndex::DatetimeIndex(num_index) => Some(Box::new(move |point_id: PointOffsetType| {
+            match num_index.get_values(point_id) {
+                None => false,
+                Some(values) => values
+                    .iter()
+                    .copied()
+                    .any(|datetime| datetime_range.check_range(datetime)),
+            }
+        })),
+        _ => None,
+    }
+}
+
 pub fn get_match_checkers(index: &FieldIndex, cond_match: Match) -> Option<ConditionCheckerFn> {
     match cond_match {
         Match::Value(MatchValue {
             value: value_variant,
         }) => match (value_variant, index) {
+            // On-disk index reads the values from the database, which might fail,
+            // and checkers can't report errors, so the points are checked against the payload
+            (ValueVariants::Keyword(_), FieldIndex::KeywordIndex(index)) if index.is_on_disk() => {
+                None
+            }
             (ValueVariants::Keyword(keyword), FieldIndex::KeywordIndex(index)) => {
                 Some(Box::new(move |point_id: PointOffsetType| {
                     match index.get_values(point_id) {
-                        None => false,
-                        Some(values) => values.iter().any(|k| k == &keyword),
+                        Ok(Some(values)) => values.iter().any(|k| k == &keyword),
+                        Ok(None) | Err(_) => false,
                     }
                 }))
             }
             (ValueVariants::Integer(value), FieldIndex::IntMapIndex(index)) => {
                 Some(Box::new(move |point_id: PointOffsetType| {
                     match index.get_values(point_id) {
-                        None => false,
-                        Some(values) => values.iter().any(|i| i == &value),
+                        Ok(Some(values)) => values.iter().any(|i| i == &value),
+                        Ok(None) | Err(_) => false,
                     }
                 }))
             }
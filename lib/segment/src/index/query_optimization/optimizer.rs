@@ -6,12 +6,15 @@ use itertools::Itertools;
 use crate::id_tracker::IdTrackerSS;
 use crate::index::field_index::{CardinalityEstimation, FieldIndex};
 use crate::index::query_estimator::{
-    combine_must_estimations, combine_should_estimations, invert_estimation,
+    combine_min_should_estimations, combine_must_estimations, combine_should_estimations,
+    invert_estimation,
 };
 use crate::index::query_optimization::condition_converter::condition_converter;
-use crate::index::query_optimization::optimized_filter::{OptimizedCondition, OptimizedFilter};
+use crate::index::query_optimization::optimized_filter::{
+    OptimizedCondition, OptimizedFilter, OptimizedMinShould,
+};
 use crate::index::query_optimization::payload_provider::PayloadProvider;
-use crate::types::{Condition, Filter, PayloadKeyType};
+use crate::types::{Condition, Filter, MinShould, PayloadKeyType};
 
 pub type IndexesMap = HashMap<PayloadKeyType, Vec<FieldIndex>>;
 
@@ -65,6 +68,18 @@ where
                 None
             }
         }),
+        min_should: filter.min_should.as_ref().map(|min_should| {
+            let (optimized_min_should, estimation) = optimize_min_should(
+                min_should,
+                id_tracker,
+                field_indexes,
+                payload_provider.clone(),
+                estimator,
+                total,
+            );
+            filter_estimations.push(estimation);
+            optimized_min_should
+        }),
         must: filter.must.as_ref().and_then(|conditions| {
             if !conditions.is_empty() {
                 let (optimized_conditions, estimation) = optimize_must(
@@ -170,6 +185,38 @@ where
     (conditions, combine_should_estimations(&estimations, total))
 }
 
+fn optimize_min_should<'a, F>(
+    min_should: &'a MinShould,
+    id_tracker: &IdTrackerSS,
+    field_indexes: &'a IndexesMap,
+    payload_provider: PayloadProvider,
+    estimator: &F,
+    total: usize,
+) -> (OptimizedMinShould<'a>, CardinalityEstimation)
+where
+    F: Fn(&Condition) -> CardinalityEstimation,
+{
+    let mut converted = convert_conditions(
+        &min_should.conditions,
+        id_tracker,
+        field_indexes,
+        payload_provider,
+        estimator,
+        total,
+    );
+    // More probable conditions first, so the required amount is reached sooner
+    converted.sort_by_key(|(_, estimation)| Reverse(estimation.exp));
+    let (conditions, estimations): (Vec<_>, Vec<_>) = converted.into_iter().unzip();
+
+    (
+        OptimizedMinShould {
+            conditions,
+            min_count: min_should.min_count,
+        },
+        combine_min_should_estimations(&estimations, min_should.min_count, total),
+    )
+}
+
 fn optimize_must<'a, F>(
     conditions: &'a [Condition],
     id_tracker: &IdTrackerSS,
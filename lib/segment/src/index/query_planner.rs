@@ -0,0 +1,84 @@
+use crate::index::field_index::CardinalityEstimation;
+
+/// Min share of the points, which should match the filter to search the graph without it and
+/// filter the found points afterwards. Checking the filter on each visited point doesn't pay off,
+/// if almost all of them match it.
+pub const POST_FILTER_MIN_MATCHING_SHARE: f64 = 0.9;
+
+/// How the search in the vector index is executed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SearchStrategy {
+    /// Score all the points, matching the filter, or all the points, if there is no filter
+    Exact,
+    /// Traverse the HNSW graph without a filter
+    Hnsw,
+    /// Traverse the HNSW graph, checking the filter on each visited point
+    FilteredHnsw,
+    /// Traverse the HNSW graph without the filter, filter the found points afterwards
+    HnswPostFilter,
+}
+
+/// Choose the strategy of the filtered search by the estimated number of matching points.
+/// `indexing_threshold` - min number of points, which the HNSW graph is worth to be used for.
+/// Returns `None`, if the estimation is not precise enough and the cardinality should be sampled.
+pub fn plan_filtered_search(
+    cardinality: &CardinalityEstimation,
+    indexing_threshold: usize,
+    points_count: usize,
+) -> Option<SearchStrategy> {
+    if cardinality.max < indexing_threshold {
+        return Some(SearchStrategy::Exact);
+    }
+    if points_count > 0
+        && cardinality.min as f64 >= points_count as f64 * POST_FILTER_MIN_MATCHING_SHARE
+    {
+        return Some(SearchStrategy::HnswPostFilter);
+    }
+    if cardinality.min > indexing_threshold {
+        return Some(SearchStrategy::FilteredHnsw);
+    }
+    None
+}
+
+/// Number of points to search without the filter, so that at least `top` of them are expected
+/// to match it after the post-filtering
+pub fn post_filter_oversampled_top(top: usize) -> usize {
+    (top as f64 / POST_FILTER_MIN_MATCHING_SHARE).ceil() as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cardinality(min: usize, max: usize) -> CardinalityEstimation {
+        CardinalityEstimation {
+            primary_clauses: vec![],
+            min,
+            exp: (min + max) / 2,
+            max,
+        }
+    }
+
+    #[test]
+    fn test_plan_filtered_search() {
+        let threshold = 1000;
+        let points = 100_000;
+        assert_eq!(
+            plan_filtered_search(&cardinality(0, 500), threshold, points),
+            Some(SearchStrategy::Exact)
+        );
+        assert_eq!(
+            plan_filtered_search(&cardinality(5_000, 50_000), threshold, points),
+            Some(SearchStrategy::FilteredHnsw)
+        );
+        assert_eq!(
+            plan_filtered_search(&cardinality(95_000, 100_000), threshold, points),
+            Some(SearchStrategy::HnswPostFilter)
+        );
+        assert_eq!(
+            plan_filtered_search(&cardinality(100, 50_000), threshold, points),
+            None
+        );
+        assert_eq!(post_filter_oversampled_top(9), 10);
+    }
+}
@@ -11,11 +11,13 @@ use rocksdb::DB;
 use schemars::_serde_json::Value;
 
 use crate::common::arc_atomic_ref_cell_iterator::ArcAtomicRefCellIterator;
+use crate::common::encryption::StorageCipher;
 use crate::common::rocksdb_wrapper::open_db_with_existing_cf;
 use crate::common::Flusher;
 use crate::entry::entry_point::{OperationError, OperationResult};
 use crate::id_tracker::IdTrackerSS;
 use crate::index::field_index::index_selector::index_selector;
+use crate::index::field_index::nested_path::merge_values;
 use crate::index::field_index::{
     CardinalityEstimation, FieldIndex, PayloadBlockCondition, PrimaryCondition,
 };
@@ -48,6 +50,8 @@ pub struct StructPayloadIndex {
     path: PathBuf,
     visited_pool: VisitedPool,
     db: Arc<RwLock<DB>>,
+    /// On-disk field indexes are encrypted with this cipher, if it is set
+    cipher: Option<StorageCipher>,
 }
 
 impl StructPayloadIndex {
@@ -116,7 +120,8 @@ impl StructPayloadIndex {
         field: PayloadKeyTypeRef,
         payload_schema: PayloadFieldSchema,
     ) -> OperationResult<Vec<FieldIndex>> {
-        let mut indexes = index_selector(field, &payload_schema, self.db.clone());
+        let mut indexes =
+            index_selector(field, &payload_schema, self.db.clone(), self.cipher.clone());
 
         let mut is_loaded = true;
         for ref mut index in indexes.iter_mut() {
@@ -137,6 +142,16 @@ impl StructPayloadIndex {
         payload: Arc<AtomicRefCell<PayloadStorageEnum>>,
         id_tracker: Arc<AtomicRefCell<IdTrackerSS>>,
         path: &Path,
+    ) -> OperationResult<Self> {
+        Self::open_with_cipher(payload, id_tracker, path, None)
+    }
+
+    /// Open index, which on-disk field indexes are encrypted with `cipher`, if it is provided
+    pub fn open_with_cipher(
+        payload: Arc<AtomicRefCell<PayloadStorageEnum>>,
+        id_tracker: Arc<AtomicRefCell<IdTrackerSS>>,
+        path: &Path,
+        cipher: Option<StorageCipher>,
     ) -> OperationResult<Self> {
         create_dir_all(path)?;
         let config_path = PayloadConfig::get_config_path(path);
@@ -158,6 +173,7 @@ impl StructPayloadIndex {
             path: path.to_owned(),
             visited_pool: Default::default(),
             db,
+            cipher,
         };
 
         if !index.config_path().exists() {
@@ -176,16 +192,17 @@ impl StructPayloadIndex {
         payload_schema: PayloadFieldSchema,
     ) -> OperationResult<Vec<FieldIndex>> {
         let payload_storage = self.payload.borrow();
-        let mut field_indexes = index_selector(field, &payload_schema, self.db.clone());
+        let mut field_indexes =
+            index_selector(field, &payload_schema, self.db.clone(), self.cipher.clone());
         for index in &field_indexes {
             index.recreate()?;
         }
 
         payload_storage.iter(|point_id, point_payload| {
-            let field_value_opt = point_payload.get_value(field);
+            let field_value_opt = merge_values(point_payload.get_values(field));
             if let Some(field_value) = field_value_opt {
                 for field_index in field_indexes.iter_mut() {
-                    field_index.add_point(point_id, field_value)?;
+                    field_index.add_point(point_id, &field_value)?;
                 }
             }
             Ok(true)
@@ -269,6 +286,8 @@ impl StructPayloadIndex {
             Condition::Field(field_condition) => self
                 .estimate_field_condition(field_condition)
                 .unwrap_or_else(|| CardinalityEstimation::unknown(self.total_points())),
+            // Conditions on the nested elements are not covered by indexes
+            Condition::Nested(_) => CardinalityEstimation::unknown(self.total_points()),
         }
     }
 
@@ -420,9 +439,9 @@ impl PayloadIndex for StructPayloadIndex {
 
     fn assign(&mut self, point_id: PointOffsetType, payload: &Payload) -> OperationResult<()> {
         for (field, field_index) in &mut self.field_indexes {
-            if let Some(field_value) = payload.get_value(field) {
+            if let Some(field_value) = merge_values(payload.get_values(field)) {
                 for index in field_index {
-                    index.add_point(point_id, field_value)?;
+                    index.add_point(point_id, &field_value)?;
                 }
             }
         }
@@ -487,8 +506,8 @@ impl PayloadIndex for StructPayloadIndex {
     ) -> OperationResult<Option<PayloadSchemaType>> {
         let mut schema = None;
         self.payload.borrow().iter(|_id, payload: &Payload| {
-            let field_value = payload.get_value(key);
-            schema = field_value.and_then(infer_value_type);
+            let field_value = merge_values(payload.get_values(key));
+            schema = field_value.and_then(|value| infer_value_type(&value));
             Ok(false)
         })?;
         Ok(schema)
@@ -50,6 +50,11 @@ impl VisitedList {
     pub fn next_iteration(&mut self) {
         self.current_iter += 1;
     }
+
+    /// Memory, taken by the list for `num_points` points, in bytes
+    pub fn memory_size(num_points: usize) -> usize {
+        num_points.saturating_mul(std::mem::size_of::<usize>())
+    }
 }
 
 /// Keeps a list of `VisitedList` which could be requested and released from multiple threads
@@ -58,12 +63,20 @@ impl VisitedList {
 #[derive(Debug)]
 pub struct VisitedPool {
     pool: RwLock<Vec<VisitedList>>,
+    /// Max number of lists to preserve
+    keep_limit: usize,
 }
 
 impl VisitedPool {
     pub fn new() -> Self {
+        Self::with_keep_limit(POOL_KEEP_LIMIT)
+    }
+
+    /// Pool, which preserves at most `keep_limit` lists. Other lists are freed, once returned
+    pub fn with_keep_limit(keep_limit: usize) -> Self {
         VisitedPool {
             pool: RwLock::new(vec![]),
+            keep_limit,
         }
     }
 
@@ -79,10 +92,15 @@ impl VisitedPool {
 
     pub fn return_back(&self, visited_list: VisitedList) {
         let mut pool = self.pool.write();
-        if pool.len() < POOL_KEEP_LIMIT {
+        if pool.len() < self.keep_limit {
             pool.push(visited_list);
         }
     }
+
+    /// Free memory of all preserved lists
+    pub fn clear(&self) {
+        self.pool.write().clear();
+    }
 }
 
 impl Default for VisitedPool {
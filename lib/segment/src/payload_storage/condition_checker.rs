@@ -3,7 +3,8 @@
 use serde_json::Value;
 
 use crate::types::{
-    GeoBoundingBox, GeoRadius, Match, MatchText, MatchValue, Range, ValueVariants, ValuesCount,
+    DateTimePayloadType, DatetimeRange, GeoBoundingBox, GeoRadius, Match, MatchText, MatchValue,
+    Range, ValueVariants, ValuesCount,
 };
 
 pub trait ValueChecker {
@@ -48,6 +49,18 @@ impl ValueChecker for Range {
     }
 }
 
+impl ValueChecker for DatetimeRange {
+    fn check_match(&self, payload: &Value) -> bool {
+        match payload {
+            Value::String(datetime) => datetime
+                .parse::<DateTimePayloadType>()
+                .map(|datetime| self.check_range(datetime))
+                .unwrap_or(false),
+            _ => false,
+        }
+    }
+}
+
 impl ValueChecker for GeoBoundingBox {
     fn check_match(&self, payload: &Value) -> bool {
         match payload {
@@ -4,6 +4,7 @@ use parking_lot::RwLock;
 use rocksdb::DB;
 use serde_json::Value;
 
+use crate::common::encryption::StorageCipher;
 use crate::common::rocksdb_wrapper::{DatabaseColumnWrapper, DB_PAYLOAD_CF};
 use crate::common::Flusher;
 use crate::entry::entry_point::{OperationError, OperationResult};
@@ -17,8 +18,9 @@ pub struct OnDiskPayloadStorage {
 }
 
 impl OnDiskPayloadStorage {
-    pub fn open(database: Arc<RwLock<DB>>) -> OperationResult<Self> {
-        let db_wrapper = DatabaseColumnWrapper::new(database, DB_PAYLOAD_CF);
+    /// Open storage, payloads are encrypted with `cipher` if it is provided
+    pub fn open(database: Arc<RwLock<DB>>, cipher: Option<StorageCipher>) -> OperationResult<Self> {
+        let db_wrapper = DatabaseColumnWrapper::new_with_cipher(database, DB_PAYLOAD_CF, cipher);
         Ok(OnDiskPayloadStorage { db_wrapper })
     }
 
@@ -50,7 +52,8 @@ impl OnDiskPayloadStorage {
     where
         F: FnMut(PointOffsetType, &Payload) -> OperationResult<bool>,
     {
-        for (key, val) in self.db_wrapper.lock_db().iter()? {
+        for record in self.db_wrapper.lock_db().iter()? {
+            let (key, val) = record?;
             let do_continue = callback(
                 serde_cbor::from_slice(&key)?,
                 &serde_cbor::from_slice(&val)?,
@@ -112,7 +112,7 @@ mod tests {
         let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
         let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
 
-        let mut storage: PayloadStorageEnum = SimplePayloadStorage::open(db).unwrap().into();
+        let mut storage: PayloadStorageEnum = SimplePayloadStorage::open(db, None).unwrap().into();
         let payload: Payload = serde_json::from_str(r#"{"name": "John Doe"}"#).unwrap();
         storage.assign(100, &payload).unwrap();
         storage.wipe().unwrap();
@@ -131,7 +131,7 @@ mod tests {
 
         {
             let mut storage: PayloadStorageEnum =
-                SimplePayloadStorage::open(db.clone()).unwrap().into();
+                SimplePayloadStorage::open(db.clone(), None).unwrap().into();
             let payload: Payload = serde_json::from_str(
                 r#"{
                 "name": "John Doe",
@@ -162,7 +162,8 @@ mod tests {
         }
 
         {
-            let mut storage: PayloadStorageEnum = OnDiskPayloadStorage::open(db).unwrap().into();
+            let mut storage: PayloadStorageEnum =
+                OnDiskPayloadStorage::open(db, None).unwrap().into();
 
             let res = storage.payload(100).unwrap();
 
@@ -3,14 +3,16 @@ use std::ops::Deref;
 use std::sync::Arc;
 
 use atomic_refcell::AtomicRefCell;
-use serde_json::Value;
+use serde_json::{Map, Value};
 
 use crate::id_tracker::IdTrackerSS;
+use crate::index::field_index::nested_path::{get_nested_objects, get_values};
 use crate::payload_storage::condition_checker::ValueChecker;
 use crate::payload_storage::payload_storage_enum::PayloadStorageEnum;
 use crate::payload_storage::ConditionChecker;
 use crate::types::{
-    Condition, FieldCondition, Filter, IsEmptyCondition, OwnedPayloadRef, Payload, PointOffsetType,
+    Condition, FieldCondition, Filter, IsEmptyCondition, MinShould, NestedCondition,
+    OwnedPayloadRef, Payload, PointOffsetType,
 };
 
 fn check_condition<F>(checker: &F, condition: &Condition) -> bool
@@ -28,6 +30,7 @@ where
     F: Fn(&Condition) -> bool,
 {
     check_should(checker, &filter.should)
+        && check_min_should(checker, &filter.min_should)
         && check_must(checker, &filter.must)
         && check_must_not(checker, &filter.must_not)
 }
@@ -43,6 +46,21 @@ where
     }
 }
 
+fn check_min_should<F>(checker: &F, min_should: &Option<MinShould>) -> bool
+where
+    F: Fn(&Condition) -> bool,
+{
+    let check = |x: &&Condition| check_condition(checker, x);
+    match min_should {
+        None => true,
+        // Stop as soon as enough conditions are matched
+        Some(MinShould {
+            conditions,
+            min_count,
+        }) => conditions.iter().filter(check).take(*min_count).count() == *min_count,
+    }
+}
+
 fn check_must<F>(checker: &F, must: &Option<Vec<Condition>>) -> bool
 where
     F: Fn(&Condition) -> bool,
@@ -79,6 +97,7 @@ where
             check_field_condition(field_condition, get_payload().deref())
         }
         Condition::IsEmpty(is_empty) => check_is_empty_condition(is_empty, get_payload().deref()),
+        Condition::Nested(nested) => check_nested_condition(nested, get_payload().deref()),
         Condition::HasId(has_id) => {
             let external_id = match id_tracker.external_id(point_id) {
                 None => return false,
@@ -93,18 +112,46 @@ where
 }
 
 pub fn check_is_empty_condition(is_empty: &IsEmptyCondition, payload: &Payload) -> bool {
-    match payload.get_value(&is_empty.is_empty.key) {
-        None => true,
-        Some(value) => match value {
+    check_is_empty_in_map(is_empty, &payload.0)
+}
+
+fn check_is_empty_in_map(is_empty: &IsEmptyCondition, map: &Map<String, Value>) -> bool {
+    get_values(&is_empty.is_empty.key, map)
+        .into_iter()
+        .all(|value| match value {
             Value::Null => true,
             Value::Array(array) => array.is_empty(),
             _ => false,
-        },
-    }
+        })
+}
+
+/// Check that at least one element of the nested array satisfies all conditions of the filter
+pub fn check_nested_condition(nested: &NestedCondition, payload: &Payload) -> bool {
+    check_nested_in_map(nested, &payload.0)
+}
+
+fn check_nested_in_map(nested: &NestedCondition, map: &Map<String, Value>) -> bool {
+    get_nested_objects(&nested.nested.key, map)
+        .into_iter()
+        .any(|element| {
+            let checker = |condition: &Condition| match condition {
+                Condition::Field(field_condition) => check_field_in_map(field_condition, element),
+                Condition::IsEmpty(is_empty) => check_is_empty_in_map(is_empty, element),
+                Condition::Nested(nested) => check_nested_in_map(nested, element),
+                // Rejected on validation: point ids are not defined for elements of the array
+                Condition::HasId(_) => false,
+                Condition::Filter(_) => unreachable!(),
+            };
+            check_filter(&checker, &nested.nested.filter)
+        })
 }
 
 pub fn check_field_condition(field_condition: &FieldCondition, payload: &Payload) -> bool {
-    payload.get_value(&field_condition.key).map_or(false, |p| {
+    check_field_in_map(field_condition, &payload.0)
+}
+
+fn check_field_in_map(field_condition: &FieldCondition, map: &Map<String, Value>) -> bool {
+    get_values(&field_condition.key, map).into_iter().any(|p| {
         let mut res = false;
         // ToDo: Convert onto iterator over checkers, so it would be impossible to forget a condition
         res = res
@@ -132,6 +179,11 @@ pub fn check_field_condition(field_condition: &FieldCondition, payload: &Payload
                 .values_count
                 .as_ref()
                 .map_or(false, |condition| condition.check(p));
+        res = res
+            || field_condition
+                .datetime_range
+                .as_ref()
+                .map_or(false, |condition| condition.check(p));
         res
     })
 }
@@ -220,7 +272,7 @@ mod tests {
     use crate::payload_storage::simple_payload_storage::SimplePayloadStorage;
     use crate::payload_storage::PayloadStorage;
     use crate::types::{
-        FieldCondition, GeoBoundingBox, GeoPoint, PayloadField, Range, ValuesCount,
+        FieldCondition, GeoBoundingBox, GeoPoint, Nested, PayloadField, Range, ValuesCount,
     };
 
     #[test]
@@ -243,7 +295,7 @@ mod tests {
         .into();
 
         let mut payload_storage: PayloadStorageEnum =
-            SimplePayloadStorage::open(db.clone()).unwrap().into();
+            SimplePayloadStorage::open(db.clone(), None).unwrap().into();
         let mut id_tracker = SimpleIdTracker::open(db).unwrap();
 
         id_tracker.set_link(0.into(), 0).unwrap();
@@ -350,6 +402,7 @@ mod tests {
 
         let query = Filter {
             should: None,
+            min_should: None,
             must: Some(vec![match_red.clone()]),
             must_not: None,
         };
@@ -357,6 +410,7 @@ mod tests {
 
         let query = Filter {
             should: None,
+            min_should: None,
             must: Some(vec![match_blue.clone()]),
             must_not: None,
         };
@@ -364,6 +418,7 @@ mod tests {
 
         let query = Filter {
             should: None,
+            min_should: None,
             must: None,
             must_not: Some(vec![match_blue.clone()]),
         };
@@ -371,6 +426,7 @@ mod tests {
 
         let query = Filter {
             should: None,
+            min_should: None,
             must: None,
             must_not: Some(vec![match_red.clone()]),
         };
@@ -378,6 +434,7 @@ mod tests {
 
         let query = Filter {
             should: Some(vec![match_red.clone(), match_blue.clone()]),
+            min_should: None,
             must: Some(vec![with_delivery.clone(), in_berlin.clone()]),
             must_not: None,
         };
@@ -385,6 +442,7 @@ mod tests {
 
         let query = Filter {
             should: Some(vec![match_red.clone(), match_blue.clone()]),
+            min_should: None,
             must: Some(vec![with_delivery, in_moscow.clone()]),
             must_not: None,
         };
@@ -394,15 +452,18 @@ mod tests {
             should: Some(vec![
                 Condition::Filter(Filter {
                     should: None,
+                    min_should: None,
                     must: Some(vec![match_red.clone(), in_moscow.clone()]),
                     must_not: None,
                 }),
                 Condition::Filter(Filter {
                     should: None,
+                    min_should: None,
                     must: Some(vec![match_blue.clone(), in_berlin.clone()]),
                     must_not: None,
                 }),
             ]),
+            min_should: None,
             must: None,
             must_not: None,
         };
@@ -412,15 +473,18 @@ mod tests {
             should: Some(vec![
                 Condition::Filter(Filter {
                     should: None,
+                    min_should: None,
                     must: Some(vec![match_blue, in_moscow]),
                     must_not: None,
                 }),
                 Condition::Filter(Filter {
                     should: None,
+                    min_should: None,
                     must: Some(vec![match_red, in_berlin]),
                     must_not: None,
                 }),
             ]),
+            min_should: None,
             must: None,
             must_not: None,
         };
@@ -428,6 +492,7 @@ mod tests {
 
         let query = Filter {
             should: None,
+            min_should: None,
             must: None,
             must_not: Some(vec![with_bad_rating]),
         };
@@ -437,6 +502,7 @@ mod tests {
 
         let query = Filter {
             should: None,
+            min_should: None,
             must: None,
             must_not: Some(vec![Condition::HasId(ids.into())]),
         };
@@ -446,6 +512,7 @@ mod tests {
 
         let query = Filter {
             should: None,
+            min_should: None,
             must: None,
             must_not: Some(vec![Condition::HasId(ids.into())]),
         };
@@ -455,9 +522,102 @@ mod tests {
 
         let query = Filter {
             should: None,
+            min_should: None,
             must: Some(vec![Condition::HasId(ids.into())]),
             must_not: None,
         };
         assert!(payload_checker.check(2, &query));
+
+        // Color and delivery match, while the location is not in Moscow
+        let min_should = |min_count| {
+            Filter::new_min_should(MinShould {
+                conditions: vec![
+                    Condition::Field(FieldCondition::new_match(
+                        "color".to_string(),
+                        "red".to_owned().into(),
+                    )),
+                    Condition::Field(FieldCondition::new_match(
+                        "has_delivery".to_string(),
+                        true.into(),
+                    )),
+                    in_moscow.clone(),
+                ],
+                min_count,
+            })
+        };
+        assert!(payload_checker.check(0, &min_should(0)));
+        assert!(payload_checker.check(0, &min_should(2)));
+        assert!(!payload_checker.check(0, &min_should(3)));
+    }
+
+    #[test]
+    fn test_nested_condition_checker() {
+        let payload: Payload = json!({
+            "metadata": {
+                "tags": [
+                    {"id": 1, "name": "red"},
+                    {"id": 2, "name": "blue"},
+                ]
+            }
+        })
+        .into();
+
+        let match_tag = |key: &str, id: i64, name: &str| {
+            Filter::new_must(Condition::Nested(NestedCondition {
+                nested: Nested {
+                    key: key.to_string(),
+                    filter: Filter {
+                        should: None,
+                        min_should: None,
+                        must: Some(vec![
+                            Condition::Field(FieldCondition::new_match(
+                                "id".to_string(),
+                                id.into(),
+                            )),
+                            Condition::Field(FieldCondition::new_match(
+                                "name".to_string(),
+                                name.to_string().into(),
+                            )),
+                        ]),
+                        must_not: None,
+                    },
+                },
+            }))
+        };
+        let check = |filter: &Filter| {
+            check_filter(
+                &|condition: &Condition| match condition {
+                    Condition::Nested(nested) => check_nested_condition(nested, &payload),
+                    Condition::Field(field) => check_field_condition(field, &payload),
+                    _ => unreachable!(),
+                },
+                filter,
+            )
+        };
+
+        // Both conditions match the same element
+        assert!(check(&match_tag("metadata.tags", 1, "red")));
+        assert!(check(&match_tag("metadata.tags[]", 2, "blue")));
+        // Conditions match different elements
+        assert!(!check(&match_tag("metadata.tags", 1, "blue")));
+        assert!(!check(&match_tag("metadata.missing", 1, "red")));
+
+        // Without nested condition, each condition may match any element
+        let flat_filter = Filter {
+            should: None,
+            min_should: None,
+            must: Some(vec![
+                Condition::Field(FieldCondition::new_match(
+                    "metadata.tags[].id".to_string(),
+                    1.into(),
+                )),
+                Condition::Field(FieldCondition::new_match(
+                    "metadata.tags[].name".to_string(),
+                    "blue".to_string().into(),
+                )),
+            ]),
+            must_not: None,
+        };
+        assert!(check(&flat_filter));
     }
 }
@@ -4,6 +4,7 @@ use std::sync::Arc;
 use parking_lot::RwLock;
 use rocksdb::DB;
 
+use crate::common::encryption::StorageCipher;
 use crate::common::rocksdb_wrapper::{DatabaseColumnWrapper, DB_PAYLOAD_CF};
 use crate::entry::entry_point::{OperationError, OperationResult};
 use crate::types::{Payload, PointOffsetType};
@@ -16,11 +17,13 @@ pub struct SimplePayloadStorage {
 }
 
 impl SimplePayloadStorage {
-    pub fn open(database: Arc<RwLock<DB>>) -> OperationResult<Self> {
+    /// Open storage, payloads are encrypted with `cipher` if it is provided
+    pub fn open(database: Arc<RwLock<DB>>, cipher: Option<StorageCipher>) -> OperationResult<Self> {
         let mut payload_map: HashMap<PointOffsetType, Payload> = Default::default();
 
-        let db_wrapper = DatabaseColumnWrapper::new(database, DB_PAYLOAD_CF);
-        for (key, val) in db_wrapper.lock_db().iter()? {
+        let db_wrapper = DatabaseColumnWrapper::new_with_cipher(database, DB_PAYLOAD_CF, cipher);
+        for record in db_wrapper.lock_db().iter()? {
+            let (key, val) = record?;
             let point_id: PointOffsetType = serde_cbor::from_slice(&key)
                 .map_err(|_| OperationError::service_error("cannot deserialize point id"))?;
             let payload: Payload = serde_cbor::from_slice(&val)
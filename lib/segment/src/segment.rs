@@ -1,21 +1,32 @@
 use std::cmp::max;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::{remove_dir_all, rename, File};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::thread::JoinHandle;
+use std::time::Instant;
 
 use atomic_refcell::AtomicRefCell;
 use fs_extra::dir::{copy_with_progress, CopyOptions, TransitProcess};
+use ordered_float::OrderedFloat;
 use parking_lot::{Mutex, RwLock};
+use rand::Rng;
 use rocksdb::DB;
+use serde_json::Value;
 use tar::Builder;
 
+use crate::common::checksums::verify_dir_checksums;
+use crate::common::disk_budget::{acquire_disk_permit, is_disk_budget_enabled, DiskPermit};
+use crate::common::encryption::StorageCipher;
 use crate::common::file_operations::{atomic_save_json, read_json};
+use crate::common::rocksdb_wrapper::verify_db;
 use crate::common::version::StorageVersion;
-use crate::common::{check_vector_name, check_vectors_set};
+use crate::common::{check_byte_vector, check_vector_len, check_vector_name, check_vectors_set};
+use crate::data_types::discovery::DiscoveryQuery;
+use crate::data_types::facets::{FacetSketch, FacetValue};
 use crate::data_types::named_vectors::NamedVectors;
-use crate::data_types::vectors::VectorElementType;
+use crate::data_types::payload_update::{apply_payload_updates, PayloadUpdate};
+use crate::data_types::vectors::{QueryVector, VectorElementType};
 use crate::entry::entry_point::OperationError::TypeInferenceError;
 use crate::entry::entry_point::{
     get_service_error, OperationError, OperationResult, SegmentEntry, SegmentFailedState,
@@ -24,17 +35,22 @@ use crate::id_tracker::IdTrackerSS;
 use crate::index::field_index::CardinalityEstimation;
 use crate::index::struct_payload_index::StructPayloadIndex;
 use crate::index::{PayloadIndex, VectorIndexSS};
-use crate::spaces::tools::peek_top_smallest_iterable;
+use crate::spaces::tools::{peek_top_largest_iterable, peek_top_smallest_iterable};
 use crate::telemetry::SegmentTelemetry;
 use crate::types::{
-    Filter, Payload, PayloadFieldSchema, PayloadIndexInfo, PayloadKeyType, PayloadKeyTypeRef,
-    PayloadSchemaType, PointIdType, PointOffsetType, ScoredPoint, SearchParams, SegmentConfig,
-    SegmentInfo, SegmentState, SegmentType, SeqNumberType, WithPayload, WithVector,
+    BatchSearchResult, Filter, FloatPayloadType, OrderBy, Payload, PayloadFieldSchema,
+    PayloadIndexInfo, PayloadKeyType, PayloadKeyTypeRef, PayloadSchemaType, PayloadStorageType,
+    PointIdType, PointOffsetType, ScoreType, ScoredPoint, SearchExplanation, SearchParams,
+    SegmentConfig, SegmentInfo, SegmentState, SegmentType, SeqNumberType, VectorStorageDatatype,
+    WithPayload, WithVector,
 };
 use crate::vector_storage::{ScoredPointOffset, VectorStorageSS};
 
 pub const SEGMENT_STATE_FILE: &str = "segment.json";
 
+/// Vectors are considered to be in RAM, if this fraction of them is in the page cache
+const DISK_RESIDENT_THRESHOLD: f64 = 0.9;
+
 pub struct SegmentVersion;
 
 impl StorageVersion for SegmentVersion {
@@ -65,6 +81,11 @@ pub struct Segment {
     /// Shows what kind of indexes and storages are used in this segment
     pub segment_type: SegmentType,
     pub segment_config: SegmentConfig,
+    /// Some of the indexes were not found on load, e.g. segment is restored from a snapshot of
+    /// an older version. Segment is searchable without them, but slower, until rebuilt by optimizer.
+    pub missing_indexes: bool,
+    /// Payloads and vectors of the segment are encrypted with this cipher, if it is set
+    pub storage_cipher: Option<StorageCipher>,
     /// Last unhandled error
     /// If not None, all update operations will be aborted until original operation is performed properly
     pub error_status: Option<SegmentFailedState>,
@@ -215,6 +236,11 @@ impl Segment {
         }
     }
 
+    /// Check data files of the segment against their stored checksums
+    pub fn verify_checksums(&self) -> OperationResult<()> {
+        verify_dir_checksums(&self.current_path)
+    }
+
     pub fn save_state(state: &SegmentState, current_path: &Path) -> OperationResult<()> {
         let state_path = current_path.join(SEGMENT_STATE_FILE);
         Ok(atomic_save_json(&state_path, state)?)
@@ -314,6 +340,41 @@ impl Segment {
         }
     }
 
+    /// Searches, which read vectors or payloads from the disk, wait for a permit of the disk budget.
+    /// Vectors, which are mostly in the page cache, are searched without a permit.
+    fn acquire_disk_permit(
+        &self,
+        vector_name: &str,
+        with_payload: &WithPayload,
+        filter: Option<&Filter>,
+    ) -> Option<DiskPermit> {
+        if !is_disk_budget_enabled() {
+            return None;
+        }
+        let vectors_on_disk = self.vector_data[vector_name]
+            .vector_storage
+            .borrow()
+            .resident_fraction()
+            < DISK_RESIDENT_THRESHOLD;
+        let payload_on_disk = self.segment_config.payload_storage_type
+            == PayloadStorageType::OnDisk
+            && (with_payload.enable || filter.is_some());
+        if !vectors_on_disk && !payload_on_disk {
+            return None;
+        }
+        acquire_disk_permit(&self.current_path)
+    }
+
+    /// Converts score threshold of the search request into the internal score space of the vector
+    fn internal_score_threshold(
+        &self,
+        vector_name: &str,
+        score_threshold: Option<ScoreType>,
+    ) -> Option<ScoreType> {
+        let distance = self.segment_config.vector_data[vector_name].distance;
+        score_threshold.map(|threshold| distance.preprocess_score(threshold))
+    }
+
     /// Converts raw ScoredPointOffset search result into ScoredPoint result
     fn process_search_result(
         &self,
@@ -432,6 +493,203 @@ impl Segment {
             .collect()
     }
 
+    pub fn filtered_read_by_order(
+        &self,
+        offset: Option<PointIdType>,
+        limit: Option<usize>,
+        condition: Option<&Filter>,
+        order_by: &OrderBy,
+    ) -> OperationResult<Vec<(FloatPayloadType, PointIdType)>> {
+        let payload_index = self.payload_index.borrow();
+        let id_tracker = self.id_tracker.borrow();
+
+        let direction = order_by.direction();
+        let order_index = payload_index
+            .field_indexes
+            .get(&order_by.key)
+            .and_then(|indexes| {
+                indexes.iter().find(|index| {
+                    index
+                        .ordered_points(order_by.start_from, direction)
+                        .is_some()
+                })
+            })
+            .ok_or_else(|| OperationError::MissingOrderIndex {
+                field_name: order_by.key.clone(),
+            })?;
+        let ordered_points = order_index
+            .ordered_points(order_by.start_from, direction)
+            .into_iter()
+            .flatten();
+        let filter_context = condition.map(|condition| payload_index.filter_context(condition));
+
+        let limit = limit.unwrap_or(usize::MAX);
+        let mut page: Vec<(FloatPayloadType, PointIdType)> = vec![];
+        for (value, internal_id) in ordered_points {
+            if page.len() >= limit {
+                // Points with the same value are ordered by external id, which does not match
+                // the order of internal ids. So we need to read all of them.
+                match page.last() {
+                    Some((last_value, _)) if *last_value == value => {}
+                    _ => break,
+                }
+            }
+            // Point with multiple values only takes the position of its first value in the
+            // ordering, so it is returned once and can't appear again on the following pages
+            if order_index.ordering_value(internal_id, direction) != Some(value) {
+                continue;
+            }
+            if let Some(filter_context) = &filter_context {
+                if !filter_context.check(internal_id) {
+                    continue;
+                }
+            }
+            let external_id = match id_tracker.external_id(internal_id) {
+                Some(external_id) => external_id,
+                None => continue,
+            };
+            if !order_by.is_after_cursor(value, external_id, offset) {
+                continue;
+            }
+            page.push((value, external_id));
+        }
+        Ok(page)
+    }
+
+    /// Reservoir sampling of the points, which satisfy the condition.
+    ///
+    /// Every point gets a uniformly random key and `limit` points with the largest keys are kept.
+    /// Keys are returned along with the points, so samples of several segments could be merged
+    /// by keys into a uniform sample of the union.
+    pub fn filtered_read_random(
+        &self,
+        limit: usize,
+        condition: Option<&Filter>,
+    ) -> Vec<(FloatPayloadType, PointIdType)> {
+        if condition.is_none() {
+            if let Some(points) = self.read_random_offsets(limit) {
+                return points;
+            }
+        }
+
+        let payload_index = self.payload_index.borrow();
+        let id_tracker = self.id_tracker.borrow();
+        let mut rng = rand::thread_rng();
+
+        let internal_ids = match condition {
+            None => id_tracker.iter_ids(),
+            Some(condition) => payload_index.query_points(condition),
+        };
+        let keyed_points = internal_ids.filter_map(|internal_id| {
+            id_tracker
+                .external_id(internal_id)
+                .map(|external_id| (OrderedFloat(rng.gen::<FloatPayloadType>()), external_id))
+        });
+
+        peek_top_largest_iterable(keyed_points, limit)
+            .into_iter()
+            .map(|(key, external_id)| (key.into_inner(), external_id))
+            .collect()
+    }
+
+    /// Random points of the segment, picked by random offsets in the id tracker without
+    /// iterating over all points.
+    ///
+    /// Keys are generated as the largest of `points_count` uniform random keys in the descending
+    /// order, so samples are merged with the ones of the other segments the same way as the
+    /// samples, read by iterating over the points.
+    ///
+    /// Returns `None`, if random offsets would mostly hit deleted or already sampled points.
+    fn read_random_offsets(&self, limit: usize) -> Option<Vec<(FloatPayloadType, PointIdType)>> {
+        let id_tracker = self.id_tracker.borrow();
+        let points_count = id_tracker.points_count();
+        let max_offset = id_tracker.max_id();
+        if limit.saturating_mul(2) > points_count || points_count * 2 < max_offset as usize + 1 {
+            return None;
+        }
+
+        let mut rng = rand::thread_rng();
+        let mut visited = HashSet::with_capacity(limit);
+        let mut points = Vec::with_capacity(limit);
+        let mut key: FloatPayloadType = 1.0;
+        while points.len() < limit {
+            let offset = rng.gen_range(0..=max_offset);
+            if !visited.insert(offset) {
+                continue;
+            }
+            let external_id = match id_tracker.external_id(offset) {
+                Some(external_id) => external_id,
+                // Deleted point
+                None => continue,
+            };
+            // Largest of the keys of the points, which are not sampled yet
+            let remaining = (points_count - points.len()) as FloatPayloadType;
+            key *= rng.gen::<FloatPayloadType>().powf(1.0 / remaining);
+            points.push((key, external_id));
+        }
+        Some(points)
+    }
+
+    fn facet_values_by_payload(
+        &self,
+        internal_id: PointOffsetType,
+        key: PayloadKeyTypeRef,
+    ) -> OperationResult<Vec<FacetValue>> {
+        let payload = self.payload_by_offset(internal_id)?;
+        let values = match payload.get_value(key) {
+            Some(Value::Array(values)) => {
+                values.iter().filter_map(FacetValue::from_payload).collect()
+            }
+            Some(value) => FacetValue::from_payload(value).into_iter().collect(),
+            None => vec![],
+        };
+        Ok(values)
+    }
+
+    /// Count values of the field among points, which satisfy the filter.
+    /// Each point is counted once per distinct value.
+    pub fn facet_sketch(
+        &self,
+        key: PayloadKeyTypeRef,
+        filter: Option<&Filter>,
+        sketch_capacity: usize,
+    ) -> OperationResult<FacetSketch> {
+        let payload_index = self.payload_index.borrow();
+        let id_tracker = self.id_tracker.borrow();
+
+        let facet_index = payload_index
+            .field_indexes
+            .get(key)
+            .and_then(|indexes| indexes.iter().find(|index| index.supports_facets()));
+
+        let points: Box<dyn Iterator<Item = PointOffsetType> + '_> = match filter {
+            Some(filter) => payload_index.query_points(filter),
+            None => id_tracker.iter_ids(),
+        };
+
+        let mut sketch = FacetSketch::new(sketch_capacity);
+        for internal_id in points {
+            if id_tracker.external_id(internal_id).is_none() {
+                continue;
+            }
+            let index_values = match facet_index {
+                Some(index) => index.facet_values(internal_id)?,
+                None => None,
+            };
+            let mut values = match index_values {
+                Some(values) => values,
+                // No suitable index in this segment (e.g. temporary segment of the optimizer)
+                None => self.facet_values_by_payload(internal_id, key)?,
+            };
+            values.sort_unstable();
+            values.dedup();
+            for value in values {
+                sketch.add(value);
+            }
+        }
+        Ok(sketch)
+    }
+
     pub fn check_consistency(&self) -> OperationResult<()> {
         let id_tracker = self.id_tracker.borrow();
         for (_vector_name, vector_storage) in self.vector_data.iter() {
@@ -470,24 +728,24 @@ impl SegmentEntry for Segment {
         filter: Option<&Filter>,
         top: usize,
         params: Option<&SearchParams>,
+        score_threshold: Option<ScoreType>,
     ) -> OperationResult<Vec<ScoredPoint>> {
         check_vector_name(vector_name, &self.segment_config)?;
         let vector_data = &self.vector_data[vector_name];
-        let expected_vector_dim = vector_data.vector_storage.borrow().vector_dim();
-        if vector.len() != expected_vector_dim {
-            return Err(OperationError::WrongVector {
-                expected_dim: expected_vector_dim,
-                received_dim: vector.len(),
-            });
-        }
-
-        let internal_result =
-            &vector_data
-                .vector_index
-                .borrow()
-                .search(&[vector], filter, top, params)[0];
+        check_vector_len(vector.len(), &self.segment_config.vector_data[vector_name])?;
+
+        let _disk_permit = self.acquire_disk_permit(vector_name, with_payload, filter);
+        let internal_threshold = self.internal_score_threshold(vector_name, score_threshold);
+        let search_result = vector_data.vector_index.borrow().search(
+            &[vector],
+            filter,
+            top,
+            params,
+            internal_threshold,
+            None,
+        );
 
-        self.process_search_result(internal_result, with_payload, with_vector)
+        self.process_search_result(&search_result.results[0], with_payload, with_vector)
     }
 
     fn search_batch(
@@ -499,32 +757,79 @@ impl SegmentEntry for Segment {
         filter: Option<&Filter>,
         top: usize,
         params: Option<&SearchParams>,
-    ) -> OperationResult<Vec<Vec<ScoredPoint>>> {
+        score_threshold: Option<ScoreType>,
+        deadline: Option<Instant>,
+    ) -> OperationResult<BatchSearchResult> {
         check_vector_name(vector_name, &self.segment_config)?;
         let vector_data = &self.vector_data[vector_name];
-        let expected_vector_dim = vector_data.vector_storage.borrow().vector_dim();
+        let vector_config = &self.segment_config.vector_data[vector_name];
         for vector in vectors {
-            if vector.len() != expected_vector_dim {
-                return Err(OperationError::WrongVector {
-                    expected_dim: expected_vector_dim,
-                    received_dim: vector.len(),
-                });
-            }
+            check_vector_len(vector.len(), vector_config)?;
         }
 
-        let internal_results = vector_data
-            .vector_index
-            .borrow()
-            .search(vectors, filter, top, params);
+        let _disk_permit = self.acquire_disk_permit(vector_name, with_payload, filter);
+        let internal_threshold = self.internal_score_threshold(vector_name, score_threshold);
+        let search_result = vector_data.vector_index.borrow().search(
+            vectors,
+            filter,
+            top,
+            params,
+            internal_threshold,
+            deadline,
+        );
 
-        let res = internal_results
+        let results = search_result
+            .results
             .iter()
             .map(|internal_result| {
                 self.process_search_result(internal_result, with_payload, with_vector)
             })
-            .collect();
+            .collect::<OperationResult<_>>()?;
 
-        res
+        let mut explanation = SearchExplanation {
+            scored_vectors: search_result.scored_vectors,
+            hnsw_ef: search_result.hnsw_ef,
+            filter_cardinality: search_result.filter_cardinality,
+            ..Default::default()
+        };
+        explanation.record(search_result.strategy);
+        Ok(BatchSearchResult {
+            results,
+            truncated: search_result.truncated,
+            explanation,
+        })
+    }
+
+    fn discover(
+        &self,
+        vector_name: &str,
+        query: &DiscoveryQuery,
+        with_payload: &WithPayload,
+        with_vector: &WithVector,
+        filter: Option<&Filter>,
+        top: usize,
+        params: Option<&SearchParams>,
+    ) -> OperationResult<Vec<ScoredPoint>> {
+        check_vector_name(vector_name, &self.segment_config)?;
+        let vector_data = &self.vector_data[vector_name];
+        let vector_config = &self.segment_config.vector_data[vector_name];
+        check_vector_len(query.target.len(), vector_config)?;
+        for pair in &query.pairs {
+            check_vector_len(pair.positive.len(), vector_config)?;
+            check_vector_len(pair.negative.len(), vector_config)?;
+        }
+
+        let _disk_permit = self.acquire_disk_permit(vector_name, with_payload, filter);
+        let search_result = vector_data.vector_index.borrow().search_queries(
+            &[QueryVector::Discovery(query)],
+            filter,
+            top,
+            params,
+            None,
+            None,
+        );
+
+        self.process_search_result(&search_result.results[0], with_payload, with_vector)
     }
 
     fn upsert_vector(
@@ -540,18 +845,23 @@ impl SegmentEntry for Segment {
             for (vector_name, vector) in vectors.iter() {
                 let vector_name: &str = vector_name;
                 let vector: &[VectorElementType] = vector;
-                let vector_data = &segment.vector_data[vector_name];
-                let vector_dim = vector_data.vector_storage.borrow().vector_dim();
-                if vector_dim != vector.len() {
-                    return Err(OperationError::WrongVector {
-                        expected_dim: vector_dim,
-                        received_dim: vector.len(),
-                    });
-                }
-
-                let processed_vector_opt = segment.segment_config.vector_data[vector_name]
-                    .distance
-                    .preprocess_vector(vector);
+                let vector_config = &segment.segment_config.vector_data[vector_name];
+                check_vector_len(vector.len(), vector_config)?;
+
+                let processed_vector_opt =
+                    match (vector_config.multivector_config, vector_config.datatype) {
+                        (Some(_), _) => vector_config
+                            .distance
+                            .preprocess_multivector(vector, vector_config.size),
+                        (None, VectorStorageDatatype::Float32) => {
+                            vector_config.distance.preprocess_vector(vector)
+                        }
+                        // Byte vectors are stored as is, so they are only validated
+                        (None, VectorStorageDatatype::Uint8) => {
+                            check_byte_vector(vector)?;
+                            None
+                        }
+                    };
                 match processed_vector_opt {
                     None => processed_vectors.insert_ref(vector_name, vector),
                     Some(preprocess_vector) => {
@@ -648,6 +958,21 @@ impl SegmentEntry for Segment {
         })
     }
 
+    fn update_payload(
+        &mut self,
+        op_num: SeqNumberType,
+        point_id: PointIdType,
+        updates: &[PayloadUpdate],
+    ) -> OperationResult<bool> {
+        self.handle_version_and_failure(op_num, Some(point_id), |segment| {
+            let internal_id = segment.lookup_internal_id(point_id)?;
+            let mut payload_index = segment.payload_index.borrow_mut();
+            let changed = apply_payload_updates(&payload_index.payload(internal_id)?, updates)?;
+            payload_index.assign(internal_id, &changed)?;
+            Ok(true)
+        })
+    }
+
     fn delete_payload(
         &mut self,
         op_num: SeqNumberType,
@@ -764,6 +1089,33 @@ impl SegmentEntry for Segment {
         }
     }
 
+    fn read_ordered_filtered<'a>(
+        &'a self,
+        offset: Option<PointIdType>,
+        limit: Option<usize>,
+        filter: Option<&'a Filter>,
+        order_by: &'a OrderBy,
+    ) -> OperationResult<Vec<(FloatPayloadType, PointIdType)>> {
+        self.filtered_read_by_order(offset, limit, filter, order_by)
+    }
+
+    fn read_random_filtered<'a>(
+        &'a self,
+        limit: usize,
+        filter: Option<&'a Filter>,
+    ) -> Vec<(FloatPayloadType, PointIdType)> {
+        self.filtered_read_random(limit, filter)
+    }
+
+    fn facet<'a>(
+        &'a self,
+        key: PayloadKeyTypeRef,
+        filter: Option<&'a Filter>,
+        sketch_capacity: usize,
+    ) -> OperationResult<FacetSketch> {
+        self.facet_sketch(key, filter, sketch_capacity)
+    }
+
     fn read_range(&self, from: Option<PointIdType>, to: Option<PointIdType>) -> Vec<PointIdType> {
         let id_tracker = self.id_tracker.borrow();
         let iterator = id_tracker.iter_from(from).map(|x| x.0);
@@ -808,6 +1160,15 @@ impl SegmentEntry for Segment {
         }
     }
 
+    fn point_offsets_count(&self) -> usize {
+        let vector_data = self.vector_data.values().next();
+        if let Some(vector_data) = vector_data {
+            vector_data.vector_storage.borrow().total_vector_count()
+        } else {
+            self.points_count()
+        }
+    }
+
     fn segment_type(&self) -> SegmentType {
         self.segment_type
     }
@@ -843,6 +1204,14 @@ impl SegmentEntry for Segment {
         self.appendable_flag
     }
 
+    fn has_missing_indexes(&self) -> bool {
+        self.missing_indexes
+    }
+
+    fn storage_cipher(&self) -> Option<StorageCipher> {
+        self.storage_cipher.clone()
+    }
+
     fn flush(&self, sync: bool) -> OperationResult<SeqNumberType> {
         let current_persisted_version: SeqNumberType = *self.persisted_version.lock();
         if !sync && self.is_background_flushing() {
@@ -1027,6 +1396,8 @@ impl SegmentEntry for Segment {
         }
         // flush segment to capture latest state
         self.flush(true)?;
+        // do not propagate corrupted data into snapshots
+        self.verify_checksums()?;
         // extract segment id from current path
         let segment_id = self
             .current_path
@@ -1107,6 +1478,18 @@ impl SegmentEntry for Segment {
             payload_field_indices: self.payload_index.borrow().get_telemetry_data(),
         }
     }
+    fn populate(&self) {
+        for vector_data in self.vector_data.values() {
+            vector_data.vector_storage.borrow().populate();
+        }
+    }
+
+    fn verify_data(&self) -> OperationResult<()> {
+        // flush segment, so checksums are up to date with the data
+        self.flush(true)?;
+        self.verify_checksums()?;
+        verify_db(&self.database.read(), &self.current_path)
+    }
 }
 
 impl Drop for Segment {
@@ -1124,9 +1507,13 @@ mod tests {
     use walkdir::WalkDir;
 
     use super::*;
+    use crate::common::checksums::save_checksums;
     use crate::data_types::vectors::{only_default_vector, DEFAULT_VECTOR_NAME};
     use crate::segment_constructor::build_segment;
-    use crate::types::{Distance, Indexes, SegmentConfig, StorageType, VectorDataConfig};
+    use crate::types::{
+        Distance, Indexes, MultiVectorConfig, SegmentConfig, StorageType, VectorDataConfig,
+        VectorStorageDatatype,
+    };
 
     // no longer valid since users are now allowed to store arbitrary json objects.
     // TODO(gvelo): add tests for invalid payload types on indexed fields.
@@ -1172,6 +1559,10 @@ mod tests {
                 VectorDataConfig {
                     size: dim,
                     distance: Distance::Dot,
+                    hnsw_config: None,
+                    quantization_config: None,
+                    datatype: VectorStorageDatatype::Float32,
+                    multivector_config: None,
                 },
             )]),
             index: Indexes::Plain {},
@@ -1200,6 +1591,7 @@ mod tests {
                 None,
                 10,
                 None,
+                None,
             )
             .unwrap();
         eprintln!("search_result = {:#?}", search_result);
@@ -1213,12 +1605,15 @@ mod tests {
                 None,
                 10,
                 None,
+                None,
+                None,
             )
             .unwrap();
         eprintln!("search_batch_result = {:#?}", search_batch_result);
 
         assert!(!search_result.is_empty());
-        assert_eq!(search_result, search_batch_result[0].clone())
+        assert!(!search_batch_result.truncated);
+        assert_eq!(search_result, search_batch_result.results[0].clone())
     }
 
     #[test]
@@ -1241,6 +1636,10 @@ mod tests {
                 VectorDataConfig {
                     size: dim,
                     distance: Distance::Dot,
+                    hnsw_config: None,
+                    quantization_config: None,
+                    datatype: VectorStorageDatatype::Float32,
+                    multivector_config: None,
                 },
             )]),
             index: Indexes::Plain {},
@@ -1292,6 +1691,7 @@ mod tests {
                 Some(&filter_valid),
                 1,
                 None,
+                None,
             )
             .unwrap();
         assert_eq!(results_with_valid_filter.len(), 1);
@@ -1305,11 +1705,127 @@ mod tests {
                 Some(&filter_invalid),
                 1,
                 None,
+                None,
             )
             .unwrap();
         assert!(results_with_invalid_filter.is_empty());
     }
 
+    #[test]
+    fn test_byte_vectors() {
+        let segment_base_dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+        let config = SegmentConfig {
+            vector_data: HashMap::from([(
+                DEFAULT_VECTOR_NAME.to_owned(),
+                VectorDataConfig {
+                    size: 3,
+                    distance: Distance::Cosine,
+                    hnsw_config: None,
+                    quantization_config: None,
+                    datatype: VectorStorageDatatype::Uint8,
+                    multivector_config: None,
+                },
+            )]),
+            index: Indexes::Plain {},
+            storage_type: StorageType::Mmap,
+            payload_storage_type: Default::default(),
+        };
+
+        let mut segment = build_segment(segment_base_dir.path(), &config).unwrap();
+        segment
+            .upsert_vector(0, 0.into(), &only_default_vector(&[1.0, 2.0, 3.0]))
+            .unwrap();
+        segment
+            .upsert_vector(1, 1.into(), &only_default_vector(&[200.0, 0.0, 0.0]))
+            .unwrap();
+        for invalid in [[0.5, 0.0, 0.0], [256.0, 0.0, 0.0], [-1.0, 0.0, 0.0]] {
+            assert!(matches!(
+                segment.upsert_vector(2, 2.into(), &only_default_vector(&invalid)),
+                Err(OperationError::WrongByteVectorElement { .. })
+            ));
+        }
+
+        // Byte vectors are not normalized for cosine
+        assert_eq!(
+            segment.vector(DEFAULT_VECTOR_NAME, 1.into()).unwrap(),
+            vec![200.0, 0.0, 0.0]
+        );
+
+        let results = segment
+            .search(
+                DEFAULT_VECTOR_NAME,
+                &[100.0, 0.0, 0.0],
+                &WithPayload::default(),
+                &false.into(),
+                None,
+                2,
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(results[0].id, 1.into());
+        assert!((results[0].score - 1.0).abs() < 1e-6);
+        assert_eq!(results[1].id, 0.into());
+    }
+
+    #[test]
+    fn test_multivectors() {
+        let segment_base_dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+        let config = SegmentConfig {
+            vector_data: HashMap::from([(
+                DEFAULT_VECTOR_NAME.to_owned(),
+                VectorDataConfig {
+                    size: 2,
+                    distance: Distance::Cosine,
+                    hnsw_config: None,
+                    quantization_config: None,
+                    datatype: VectorStorageDatatype::Float32,
+                    multivector_config: Some(MultiVectorConfig::default()),
+                },
+            )]),
+            index: Indexes::Plain {},
+            storage_type: StorageType::Mmap,
+            payload_storage_type: Default::default(),
+        };
+
+        let mut segment = build_segment(segment_base_dir.path(), &config).unwrap();
+        segment
+            .upsert_vector(0, 0.into(), &only_default_vector(&[2.0, 0.0]))
+            .unwrap();
+        segment
+            .upsert_vector(1, 1.into(), &only_default_vector(&[0.0, 3.0, 4.0, 0.0]))
+            .unwrap();
+        for invalid in [&[][..], &[1.0, 2.0, 3.0][..]] {
+            assert!(matches!(
+                segment.upsert_vector(2, 2.into(), &only_default_vector(invalid)),
+                Err(OperationError::WrongMultiVector { .. })
+            ));
+        }
+
+        // Each vector of the multivector is normalized for cosine
+        assert_eq!(
+            segment.vector(DEFAULT_VECTOR_NAME, 1.into()).unwrap(),
+            vec![0.0, 1.0, 1.0, 0.0]
+        );
+
+        let results = segment
+            .search(
+                DEFAULT_VECTOR_NAME,
+                &[1.0, 0.0, 0.0, 1.0],
+                &WithPayload::default(),
+                &false.into(),
+                None,
+                2,
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(results[0].id, 1.into());
+        assert!((results[0].score - 2.0).abs() < 1e-6);
+        assert_eq!(results[1].id, 0.into());
+        assert!((results[1].score - 1.0).abs() < 1e-6);
+    }
+
     #[test]
     fn test_snapshot() {
         let data = r#"
@@ -1329,6 +1845,10 @@ mod tests {
                 VectorDataConfig {
                     size: 2,
                     distance: Distance::Dot,
+                    hnsw_config: None,
+                    quantization_config: None,
+                    datatype: VectorStorageDatatype::Float32,
+                    multivector_config: None,
                 },
             )]),
             index: Indexes::Plain {},
@@ -1393,6 +1913,86 @@ mod tests {
         assert_eq!(decompressed_file_count, segment_file_count);
     }
 
+    #[test]
+    fn test_verify_data() {
+        let segment_base_dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+        let config = SegmentConfig {
+            vector_data: HashMap::from([(
+                DEFAULT_VECTOR_NAME.to_owned(),
+                VectorDataConfig {
+                    size: 2,
+                    distance: Distance::Dot,
+                    hnsw_config: None,
+                    quantization_config: None,
+                    datatype: VectorStorageDatatype::Float32,
+                    multivector_config: None,
+                },
+            )]),
+            index: Indexes::Plain {},
+            storage_type: StorageType::InMemory,
+            payload_storage_type: Default::default(),
+        };
+
+        let mut segment = build_segment(segment_base_dir.path(), &config).unwrap();
+        segment
+            .upsert_vector(0, 0.into(), &only_default_vector(&[1.0, 1.0]))
+            .unwrap();
+        let payload: Payload = serde_json::from_str(r#"{"name": "John Doe"}"#).unwrap();
+        segment.set_full_payload(1, 0.into(), &payload).unwrap();
+
+        segment.verify_data().unwrap();
+
+        // Checksums of the data files are verified as well
+        let file_path = segment.current_path.join("data.bin");
+        fs::write(&file_path, b"data").unwrap();
+        save_checksums(&file_path).unwrap();
+        fs::write(&file_path, b"date").unwrap();
+        assert!(segment.verify_data().is_err());
+    }
+
+    #[test]
+    fn test_read_random_offsets() {
+        let segment_base_dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+        let config = SegmentConfig {
+            vector_data: HashMap::from([(
+                DEFAULT_VECTOR_NAME.to_owned(),
+                VectorDataConfig {
+                    size: 2,
+                    distance: Distance::Dot,
+                    hnsw_config: None,
+                    quantization_config: None,
+                    datatype: VectorStorageDatatype::Float32,
+                    multivector_config: None,
+                },
+            )]),
+            index: Indexes::Plain {},
+            storage_type: StorageType::InMemory,
+            payload_storage_type: Default::default(),
+        };
+
+        let mut segment = build_segment(segment_base_dir.path(), &config).unwrap();
+        for id in 0..100u64 {
+            segment
+                .upsert_vector(id, id.into(), &only_default_vector(&[1.0, 1.0]))
+                .unwrap();
+        }
+        for id in 0..10u64 {
+            segment.delete_point(100 + id, id.into()).unwrap();
+        }
+
+        let sample = segment.filtered_read_random(10, None);
+        assert_eq!(sample.len(), 10);
+        // Keys are descending, points are unique and not deleted
+        assert!(sample.windows(2).all(|pair| pair[0].0 >= pair[1].0));
+        let ids: HashSet<_> = sample.iter().map(|(_, id)| *id).collect();
+        assert_eq!(ids.len(), 10);
+        assert!(ids.iter().all(|id| segment.has_point(*id)));
+
+        // Large samples are read by iterating over the points
+        let sample = segment.filtered_read_random(80, None);
+        assert_eq!(sample.len(), 80);
+    }
+
     #[test]
     fn test_copy_segment_directory() {
         let data = r#"
@@ -1412,6 +2012,10 @@ mod tests {
                 VectorDataConfig {
                     size: 2,
                     distance: Distance::Dot,
+                    hnsw_config: None,
+                    quantization_config: None,
+                    datatype: VectorStorageDatatype::Float32,
+                    multivector_config: None,
                 },
             )]),
             index: Indexes::Plain {},
@@ -1464,6 +2068,10 @@ mod tests {
                 VectorDataConfig {
                     size: 2,
                     distance: Distance::Dot,
+                    hnsw_config: None,
+                    quantization_config: None,
+                    datatype: VectorStorageDatatype::Float32,
+                    multivector_config: None,
                 },
             )]),
             index: Indexes::Plain {},
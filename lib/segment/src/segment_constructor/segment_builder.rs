@@ -4,11 +4,14 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 
+use crate::common::encryption::StorageCipher;
 use crate::common::error_logging::LogError;
 use crate::entry::entry_point::{OperationError, OperationResult, SegmentEntry};
 use crate::index::PayloadIndex;
 use crate::segment::Segment;
-use crate::segment_constructor::{build_segment, load_segment};
+use crate::segment_constructor::{
+    build_segment_with_cipher, get_vector_storage_path, load_segment_with_cipher,
+};
 use crate::types::{PayloadFieldSchema, PayloadKeyType, SegmentConfig};
 
 /// Structure for constructing segment out of several other segments
@@ -25,7 +28,17 @@ impl SegmentBuilder {
         temp_dir: &Path,
         segment_config: &SegmentConfig,
     ) -> OperationResult<Self> {
-        let segment = build_segment(temp_dir, segment_config)?;
+        Self::new_with_cipher(segment_path, temp_dir, segment_config, None)
+    }
+
+    /// Construct segment, which payloads and vectors are encrypted with `cipher`, if it is provided
+    pub fn new_with_cipher(
+        segment_path: &Path,
+        temp_dir: &Path,
+        segment_config: &SegmentConfig,
+        cipher: Option<StorageCipher>,
+    ) -> OperationResult<Self> {
+        let segment = build_segment_with_cipher(temp_dir, segment_config, cipher)?;
         let temp_path = segment.current_path.clone();
 
         let destination_path = segment_path.join(temp_path.file_name().unwrap());
@@ -55,6 +68,8 @@ impl SegmentBuilder {
                 "Segment building error: created segment not found",
             )),
             Some(self_segment) => {
+                // do not propagate corrupted data into optimized segments
+                other.verify_checksums()?;
                 self_segment.version = cmp::max(self_segment.version(), other.version());
 
                 let other_id_tracker = other.id_tracker.borrow();
@@ -173,7 +188,7 @@ impl SegmentBuilder {
     }
 
     pub fn build(mut self, stopped: &AtomicBool) -> Result<Segment, OperationError> {
-        {
+        let cipher = {
             let mut segment = self.segment.ok_or_else(|| {
                 OperationError::service_error("Segment building error: created segment not found")
             })?;
@@ -192,15 +207,44 @@ impl SegmentBuilder {
                 vector_data.vector_index.borrow_mut().build_index(stopped)?;
             }
 
+            for (vector_name, vector_data) in &segment.vector_data {
+                let quantization_config = match segment
+                    .segment_config
+                    .vector_data
+                    .get(vector_name)
+                    .and_then(|config| config.quantization_config)
+                {
+                    Some(quantization_config) => quantization_config,
+                    None => continue,
+                };
+                if segment.storage_cipher.is_some() {
+                    // Codes would be saved unencrypted
+                    log::warn!(
+                        "Quantization of vectors {vector_name} skipped for encrypted segment"
+                    );
+                    continue;
+                }
+                if stopped.load(Ordering::Relaxed) {
+                    return Err(OperationError::Cancelled {
+                        description: "Cancelled by external thread".to_string(),
+                    });
+                }
+                vector_data.vector_storage.borrow_mut().quantize(
+                    &get_vector_storage_path(&segment.current_path, vector_name),
+                    &quantization_config,
+                )?;
+            }
+
             segment.flush(true)?;
             // Now segment is going to be evicted from RAM
-        }
+            segment.storage_cipher.clone()
+        };
 
         // Move fully constructed segment into collection directory and load back to RAM
         fs::rename(&self.temp_path, &self.destination_path)
             .describe("Moving segment data after optimization")?;
 
-        load_segment(&self.destination_path)?.ok_or_else(|| {
+        load_segment_with_cipher(&self.destination_path, cipher)?.ok_or_else(|| {
             OperationError::service_error(&format!(
                 "Segment loading error: {}",
                 self.destination_path.display()
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 use std::fs::{create_dir_all, File};
 use std::io::Read;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use atomic_refcell::AtomicRefCell;
@@ -11,6 +11,7 @@ use semver::Version;
 use serde::Deserialize;
 use uuid::Uuid;
 
+use crate::common::encryption::StorageCipher;
 use crate::common::rocksdb_wrapper::{open_db, DB_VECTOR_CF};
 use crate::common::version::StorageVersion;
 use crate::data_types::vectors::DEFAULT_VECTOR_NAME;
@@ -25,9 +26,11 @@ use crate::payload_storage::simple_payload_storage::SimplePayloadStorage;
 use crate::segment::{Segment, SegmentVersion, VectorData, SEGMENT_STATE_FILE};
 use crate::types::{
     Distance, Indexes, PayloadStorageType, SegmentConfig, SegmentState, SegmentType, SeqNumberType,
-    StorageType, VectorDataConfig,
+    StorageType, VectorDataConfig, VectorStorageDatatype,
 };
 use crate::vector_storage::memmap_vector_storage::open_memmap_vector_storage;
+use crate::vector_storage::simple_byte_vector_storage::open_simple_byte_vector_storage;
+use crate::vector_storage::simple_multi_vector_storage::open_simple_multi_vector_storage;
 use crate::vector_storage::simple_vector_storage::open_simple_vector_storage;
 use crate::vector_storage::VectorStorageSS;
 
@@ -35,18 +38,32 @@ fn sp<T>(t: T) -> Arc<AtomicRefCell<T>> {
     Arc::new(AtomicRefCell::new(t))
 }
 
+fn get_vector_name_with_prefix(prefix: &str, vector_name: &str) -> String {
+    if !vector_name.is_empty() {
+        format!("{}-{}", prefix, vector_name)
+    } else {
+        prefix.to_owned()
+    }
+}
+
+/// Directory of the vector storage files, also used for the quantized vectors
+pub fn get_vector_storage_path(segment_path: &Path, vector_name: &str) -> PathBuf {
+    segment_path.join(get_vector_name_with_prefix("vector_storage", vector_name))
+}
+
 fn create_segment(
     version: SeqNumberType,
     segment_path: &Path,
     config: &SegmentConfig,
+    cipher: Option<StorageCipher>,
 ) -> OperationResult<Segment> {
-    let get_vector_name_with_prefix = |prefix: &str, vector_name: &str| {
-        if !vector_name.is_empty() {
-            format!("{}-{}", prefix, vector_name)
-        } else {
-            prefix.to_owned()
-        }
-    };
+    if cipher.is_some() && config.storage_type == StorageType::Mmap {
+        // Memmap storage is read in place, so it can't be transparently decrypted
+        return Err(OperationError::service_error(
+            "Mmap vector storage can't be used for encrypted segments",
+        ));
+    }
+
     let vector_db_names: Vec<String> = config
         .vector_data
         .iter()
@@ -56,53 +73,111 @@ fn create_segment(
         .map_err(|err| OperationError::service_error(&format!("RocksDB open error: {}", err)))?;
 
     let payload_storage = match config.payload_storage_type {
-        PayloadStorageType::InMemory => sp(SimplePayloadStorage::open(database.clone())?.into()),
-        PayloadStorageType::OnDisk => sp(OnDiskPayloadStorage::open(database.clone())?.into()),
+        PayloadStorageType::InMemory => {
+            sp(SimplePayloadStorage::open(database.clone(), cipher.clone())?.into())
+        }
+        PayloadStorageType::OnDisk => {
+            sp(OnDiskPayloadStorage::open(database.clone(), cipher.clone())?.into())
+        }
     };
 
     let id_tracker = sp(SimpleIdTracker::open(database.clone())?);
 
     let payload_index_path = segment_path.join("payload_index");
-    let payload_index: Arc<AtomicRefCell<StructPayloadIndex>> = sp(StructPayloadIndex::open(
-        payload_storage,
-        id_tracker.clone(),
-        &payload_index_path,
-    )?);
+    let payload_index: Arc<AtomicRefCell<StructPayloadIndex>> =
+        sp(StructPayloadIndex::open_with_cipher(
+            payload_storage,
+            id_tracker.clone(),
+            &payload_index_path,
+            cipher.clone(),
+        )?);
+
+    // Payload indexes are rebuilt on load, only vector indexes are rebuilt in background
+    let mut missing_indexes = false;
 
     let mut vector_data = HashMap::new();
     for (vector_name, vector_config) in &config.vector_data {
-        let vector_storage_path =
-            segment_path.join(&get_vector_name_with_prefix("vector_storage", vector_name));
+        let vector_storage_path = get_vector_storage_path(segment_path, vector_name);
         let vector_index_path =
             segment_path.join(&get_vector_name_with_prefix("vector_index", vector_name));
 
-        let vector_storage: Arc<AtomicRefCell<VectorStorageSS>> = match config.storage_type {
-            StorageType::InMemory => {
+        let vector_storage: Arc<AtomicRefCell<VectorStorageSS>> = match (
+            vector_config.multivector_config,
+            vector_config.datatype,
+            config.storage_type,
+        ) {
+            // Multivectors have different lengths, so they are kept in RAM
+            (Some(_), _, _) => {
+                let db_column_name = get_vector_name_with_prefix(DB_VECTOR_CF, vector_name);
+                open_simple_multi_vector_storage(
+                    database.clone(),
+                    &db_column_name,
+                    vector_config.size,
+                    vector_config.distance,
+                    cipher.clone(),
+                )?
+            }
+            // Byte vectors are compact enough to be kept in RAM regardless of the storage type
+            (None, VectorStorageDatatype::Uint8, _) => {
+                let db_column_name = get_vector_name_with_prefix(DB_VECTOR_CF, vector_name);
+                open_simple_byte_vector_storage(
+                    database.clone(),
+                    &db_column_name,
+                    vector_config.size,
+                    vector_config.distance,
+                    cipher.clone(),
+                )?
+            }
+            (None, VectorStorageDatatype::Float32, StorageType::InMemory) => {
                 let db_column_name = get_vector_name_with_prefix(DB_VECTOR_CF, vector_name);
                 open_simple_vector_storage(
                     database.clone(),
                     &db_column_name,
                     vector_config.size,
                     vector_config.distance,
+                    cipher.clone(),
+                )?
+            }
+            (None, VectorStorageDatatype::Float32, StorageType::Mmap) => {
+                open_memmap_vector_storage(
+                    &vector_storage_path,
+                    vector_config.size,
+                    vector_config.distance,
                 )?
             }
-            StorageType::Mmap => open_memmap_vector_storage(
-                &vector_storage_path,
-                vector_config.size,
-                vector_config.distance,
-            )?,
         };
 
+        if vector_config.quantization_config.is_some() {
+            vector_storage
+                .borrow_mut()
+                .load_quantization(&vector_storage_path)?;
+        }
+
         let vector_index: Arc<AtomicRefCell<VectorIndexSS>> = match config.index {
             Indexes::Plain { .. } => sp(PlainIndex::new(
                 vector_storage.clone(),
                 payload_index.clone(),
             )),
+            Indexes::Hnsw(_)
+                if HNSWIndex::is_graph_missing(&vector_index_path, &vector_storage.borrow()) =>
+            {
+                // Segment might be restored from a snapshot without index files.
+                // Search it without index, the segment will be rebuilt by optimizer.
+                log::warn!(
+                    "Vector index is not found in {}, it will be rebuilt in background",
+                    vector_index_path.display()
+                );
+                missing_indexes = true;
+                sp(PlainIndex::new(
+                    vector_storage.clone(),
+                    payload_index.clone(),
+                ))
+            }
             Indexes::Hnsw(hnsw_config) => sp(HNSWIndex::open(
                 &vector_index_path,
                 vector_storage.clone(),
                 payload_index.clone(),
-                hnsw_config,
+                vector_config.hnsw_config.unwrap_or(hnsw_config),
             )?),
         };
 
@@ -133,6 +208,8 @@ fn create_segment(
         appendable_flag,
         payload_index,
         segment_config: config.clone(),
+        missing_indexes,
+        storage_cipher: cipher,
         error_status: None,
         database,
         flush_thread: Mutex::new(None),
@@ -140,38 +217,82 @@ fn create_segment(
 }
 
 pub fn load_segment(path: &Path) -> OperationResult<Option<Segment>> {
+    load_segment_with_cipher(path, None)
+}
+
+/// Compatibility of the segment data format with the current version of the application
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SegmentCompatibility {
+    /// Segment is stored in the current format
+    Current,
+    /// Segment is stored in the older format, it will be migrated on load
+    NeedsMigration(Version),
+    /// Segment can't be loaded by the current version
+    Incompatible(Version),
+}
+
+/// Check if segment in `path` could be loaded by the current version, without loading it.
+/// Returns `None` if the segment has no version file, such segments are skipped on load.
+pub fn check_segment_version(path: &Path) -> OperationResult<Option<SegmentCompatibility>> {
     if !SegmentVersion::check_exists(path) {
-        // Assume segment was not properly saved.
-        // Server might have crashed before saving the segment fully.
-        log::warn!(
-            "Segment version file not found, skipping: {}",
-            path.display()
-        );
         return Ok(None);
     }
 
     let stored_version: Version = SegmentVersion::load(path)?.parse()?;
     let app_version: Version = SegmentVersion::current().parse()?;
 
-    if stored_version != app_version {
-        info!("Migrating segment {} -> {}", stored_version, app_version,);
+    let compatibility = if stored_version == app_version {
+        SegmentCompatibility::Current
+    } else if stored_version.minor == 3 {
+        SegmentCompatibility::NeedsMigration(stored_version)
+    } else {
+        SegmentCompatibility::Incompatible(stored_version)
+    };
+    Ok(Some(compatibility))
+}
+
+/// Load segment, which payloads and vectors are encrypted with `cipher`, if it is provided
+pub fn load_segment_with_cipher(
+    path: &Path,
+    cipher: Option<StorageCipher>,
+) -> OperationResult<Option<Segment>> {
+    let compatibility = match check_segment_version(path)? {
+        Some(compatibility) => compatibility,
+        None => {
+            // Assume segment was not properly saved.
+            // Server might have crashed before saving the segment fully.
+            log::warn!(
+                "Segment version file not found, skipping: {}",
+                path.display()
+            );
+            return Ok(None);
+        }
+    };
 
-        if stored_version.minor == 3 {
+    match compatibility {
+        SegmentCompatibility::Current => {}
+        SegmentCompatibility::NeedsMigration(stored_version) => {
+            info!(
+                "Migrating segment {} -> {}",
+                stored_version,
+                SegmentVersion::current()
+            );
             let segment_state = load_segment_state_v3(path)?;
             Segment::save_state(&segment_state, path)?;
-        } else {
+            SegmentVersion::save(path)?
+        }
+        SegmentCompatibility::Incompatible(stored_version) => {
             return Err(OperationError::service_error(&format!(
                 "Segment version({}) is not compatible with current version({})",
-                stored_version, app_version
+                stored_version,
+                SegmentVersion::current()
             )));
         }
-
-        SegmentVersion::save(path)?
     }
 
     let segment_state = Segment::load_state(path)?;
 
-    let segment = create_segment(segment_state.version, path, &segment_state.config)?;
+    let segment = create_segment(segment_state.version, path, &segment_state.config, cipher)?;
 
     #[cfg(debug_assertions)]
     {
@@ -192,11 +313,21 @@ pub fn load_segment(path: &Path) -> OperationResult<Option<Segment>> {
 ///
 ///
 pub fn build_segment(path: &Path, config: &SegmentConfig) -> OperationResult<Segment> {
+    build_segment_with_cipher(path, config, None)
+}
+
+/// Build segment, which payloads and vectors are encrypted with `cipher`, if it is provided.
+/// Encrypted segments can't use mmap vector storage.
+pub fn build_segment_with_cipher(
+    path: &Path,
+    config: &SegmentConfig,
+    cipher: Option<StorageCipher>,
+) -> OperationResult<Segment> {
     let segment_path = path.join(Uuid::new_v4().to_string());
 
     create_dir_all(&segment_path)?;
 
-    let segment = create_segment(0, &segment_path, config)?;
+    let segment = create_segment(0, &segment_path, config, cipher)?;
     segment.save_current_state()?;
 
     // Version is the last file to save, as it will be used to check if segment was built correctly.
@@ -242,6 +373,10 @@ fn load_segment_state_v3(segment_path: &Path) -> OperationResult<SegmentState> {
             let vector_data = VectorDataConfig {
                 size: state.config.vector_size,
                 distance: state.config.distance,
+                hnsw_config: None,
+                quantization_config: None,
+                datatype: VectorStorageDatatype::Float32,
+                multivector_config: None,
             };
             SegmentState {
                 version: state.version,
@@ -5,7 +5,7 @@ use crate::data_types::vectors::DEFAULT_VECTOR_NAME;
 use crate::entry::entry_point::OperationResult;
 use crate::segment::Segment;
 use crate::segment_constructor::build_segment;
-use crate::types::{Distance, Indexes, SegmentConfig, VectorDataConfig};
+use crate::types::{Distance, Indexes, SegmentConfig, VectorDataConfig, VectorStorageDatatype};
 
 /// Build new segment with plain index in given directory
 ///
@@ -26,6 +26,10 @@ pub fn build_simple_segment(
                 VectorDataConfig {
                     size: dim,
                     distance,
+                    hnsw_config: None,
+                    quantization_config: None,
+                    datatype: VectorStorageDatatype::Float32,
+                    multivector_config: None,
                 },
             )]),
             index: Indexes::Plain {},
@@ -47,6 +51,10 @@ pub fn build_multivec_segment(
         VectorDataConfig {
             size: dim1,
             distance,
+            hnsw_config: None,
+            quantization_config: None,
+            datatype: VectorStorageDatatype::Float32,
+            multivector_config: None,
         },
     );
     vectors_config.insert(
@@ -54,6 +62,10 @@ pub fn build_multivec_segment(
         VectorDataConfig {
             size: dim2,
             distance,
+            hnsw_config: None,
+            quantization_config: None,
+            datatype: VectorStorageDatatype::Float32,
+            multivector_config: None,
         },
     );
 
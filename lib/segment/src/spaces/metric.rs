@@ -15,3 +15,9 @@ pub trait Metric {
     /// correct metric score for displaying
     fn postprocess(score: ScoreType) -> ScoreType;
 }
+
+/// Same metric over vectors of bytes. Byte vectors are not preprocessed,
+/// so the score is comparable with the score of preprocessed float vectors
+pub trait ByteMetric: Metric {
+    fn similarity_bytes(v1: &[u8], v2: &[u8]) -> ScoreType;
+}
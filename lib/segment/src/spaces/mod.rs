@@ -1,5 +1,7 @@
 pub mod metric;
+pub mod multi_vector;
 pub mod simple;
+pub mod simple_byte;
 pub mod tools;
 
 #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
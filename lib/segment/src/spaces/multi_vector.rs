@@ -0,0 +1,62 @@
+use super::metric::Metric;
+use crate::data_types::vectors::VectorElementType;
+use crate::types::ScoreType;
+
+// Multivector is a list of vectors of the same dim, flattened into a single vector
+
+/// Preprocess each vector of the multivector.
+/// Return None if metric does not required preprocessing
+pub fn preprocess_multivector<TMetric: Metric>(
+    multivector: &[VectorElementType],
+    dim: usize,
+) -> Option<Vec<VectorElementType>> {
+    let mut preprocessed = Vec::with_capacity(multivector.len());
+    for vector in multivector.chunks(dim) {
+        preprocessed.extend(TMetric::preprocess(vector)?);
+    }
+    Some(preprocessed)
+}
+
+/// Late interaction score: sum of the best similarities of each query vector
+/// to any of the vectors of the multivector
+pub fn max_sim<TMetric: Metric>(
+    query: &[VectorElementType],
+    multivector: &[VectorElementType],
+    dim: usize,
+) -> ScoreType {
+    query
+        .chunks(dim)
+        .map(|query_vector| {
+            multivector
+                .chunks(dim)
+                .map(|vector| TMetric::similarity(query_vector, vector))
+                .fold(ScoreType::NEG_INFINITY, ScoreType::max)
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spaces::simple::{CosineMetric, DotProductMetric};
+
+    #[test]
+    fn test_max_sim() {
+        let multivector = [1.0, 0.0, 0.0, 2.0, 3.0, 3.0];
+
+        // Each query vector takes the best of the stored vectors
+        assert_eq!(
+            max_sim::<DotProductMetric>(&[1.0, 0.0, 0.0, 1.0], &multivector, 2),
+            3.0 + 3.0
+        );
+        // Single vector is a multivector of one vector
+        assert_eq!(
+            max_sim::<DotProductMetric>(&[0.0, 1.0], &multivector, 2),
+            3.0
+        );
+
+        let preprocessed = preprocess_multivector::<CosineMetric>(&multivector, 2).unwrap();
+        assert_eq!(&preprocessed[..4], &[1.0, 0.0, 0.0, 1.0]);
+        assert!(preprocess_multivector::<DotProductMetric>(&multivector, 2).is_none());
+    }
+}
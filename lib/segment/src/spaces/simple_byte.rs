@@ -0,0 +1,84 @@
+use super::metric::ByteMetric;
+use super::simple::{CosineMetric, DotProductMetric, EuclidMetric};
+use crate::types::ScoreType;
+
+// Products of bytes are accumulated in integers, loops are vectorized by the compiler
+
+impl ByteMetric for EuclidMetric {
+    fn similarity_bytes(v1: &[u8], v2: &[u8]) -> ScoreType {
+        euclid_similarity_bytes(v1, v2)
+    }
+}
+
+impl ByteMetric for DotProductMetric {
+    fn similarity_bytes(v1: &[u8], v2: &[u8]) -> ScoreType {
+        dot_similarity_bytes(v1, v2)
+    }
+}
+
+impl ByteMetric for CosineMetric {
+    fn similarity_bytes(v1: &[u8], v2: &[u8]) -> ScoreType {
+        cosine_similarity_bytes(v1, v2)
+    }
+}
+
+pub fn euclid_similarity_bytes(v1: &[u8], v2: &[u8]) -> ScoreType {
+    let s: u64 = v1
+        .iter()
+        .zip(v2)
+        .map(|(a, b)| {
+            let diff = a.abs_diff(*b) as u32;
+            (diff * diff) as u64
+        })
+        .sum();
+    -(s as ScoreType)
+}
+
+pub fn dot_similarity_bytes(v1: &[u8], v2: &[u8]) -> ScoreType {
+    let s: u64 = v1
+        .iter()
+        .zip(v2)
+        .map(|(a, b)| (*a as u32 * *b as u32) as u64)
+        .sum();
+    s as ScoreType
+}
+
+/// Cosine of the vectors, which is 0 if any of them is zero
+pub fn cosine_similarity_bytes(v1: &[u8], v2: &[u8]) -> ScoreType {
+    let norm1 = dot_similarity_bytes(v1, v1);
+    let norm2 = dot_similarity_bytes(v2, v2);
+    if norm1 == 0.0 || norm2 == 0.0 {
+        return 0.0;
+    }
+    dot_similarity_bytes(v1, v2) / (norm1 * norm2).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spaces::metric::Metric;
+
+    #[test]
+    fn test_byte_similarity() {
+        let v1 = [1u8, 2, 255, 0];
+        let v2 = [3u8, 0, 255, 7];
+        let f1: Vec<_> = v1.iter().map(|x| *x as f32).collect();
+        let f2: Vec<_> = v2.iter().map(|x| *x as f32).collect();
+
+        assert_eq!(
+            DotProductMetric::similarity_bytes(&v1, &v2),
+            DotProductMetric::similarity(&f1, &f2)
+        );
+        assert_eq!(
+            EuclidMetric::similarity_bytes(&v1, &v2),
+            EuclidMetric::similarity(&f1, &f2)
+        );
+
+        let cosine = CosineMetric::similarity(
+            &CosineMetric::preprocess(&f1).unwrap(),
+            &CosineMetric::preprocess(&f2).unwrap(),
+        );
+        assert!((CosineMetric::similarity_bytes(&v1, &v2) - cosine).abs() < 1e-6);
+        assert_eq!(CosineMetric::similarity_bytes(&v1, &[0; 4]), 0.0);
+    }
+}
@@ -105,6 +105,10 @@ impl Anonymize for VectorDataConfig {
         VectorDataConfig {
             size: self.size.anonymize(),
             distance: self.distance,
+            hnsw_config: self.hnsw_config,
+            quantization_config: self.quantization_config,
+            datatype: self.datatype,
+            multivector_config: self.multivector_config,
         }
     }
 }
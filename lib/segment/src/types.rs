@@ -5,20 +5,29 @@ use std::mem::size_of;
 use std::ops::Deref;
 use std::rc::Rc;
 use std::str::FromStr;
+use std::time::Duration;
 
+use chrono::TimeZone;
 use geo::prelude::HaversineDistance;
 use geo::Point;
 use itertools::Itertools;
 use ordered_float::OrderedFloat;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{InstanceType, Schema, SchemaObject};
 use schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
+use serde::de::{self, Unexpected, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::{Map, Value};
 use uuid::Uuid;
 
 use crate::common::utils;
+use crate::data_types::keyword_index::KeywordIndexParams;
 use crate::data_types::text_index::TextIndexParams;
 use crate::data_types::vectors::{VectorElementType, VectorStruct};
+use crate::index::field_index::{nested_path, CardinalityEstimation};
+use crate::index::query_planner::SearchStrategy;
 use crate::spaces::metric::Metric;
+use crate::spaces::multi_vector::preprocess_multivector;
 use crate::spaces::simple::{CosineMetric, DotProductMetric, EuclidMetric};
 
 /// Type of point index inside a segment
@@ -37,16 +46,41 @@ pub type IntPayloadType = i64;
 
 pub const VECTOR_ELEMENT_SIZE: usize = size_of::<VectorElementType>();
 
-/// Type, used for specifying point ID in user interface
-#[derive(
-    Debug, Deserialize, Serialize, Copy, Clone, PartialEq, Eq, Hash, Ord, PartialOrd, JsonSchema,
-)]
+/// Type, used for specifying point ID in user interface.
+/// Besides unsigned integers and UUIDs, any other non-empty string (e.g. document URI) can be used
+/// as an ID, see [`ExtendedPointId::parse_str`]
+#[derive(Debug, Serialize, Copy, Clone, PartialEq, Eq, Hash, Ord, PartialOrd, JsonSchema)]
 #[serde(untagged)]
 pub enum ExtendedPointId {
     NumId(u64),
     Uuid(Uuid),
 }
 
+/// Namespace of the name-based UUIDs, generated for the string point ids
+const POINT_ID_ALIAS_NAMESPACE: Uuid = Uuid::from_u128(0x5f0c_1c2e_9b1a_4d7e_8f3a_2b6c_0d9e_7a41);
+
+impl ExtendedPointId {
+    /// Point ID, given as a string: in the URL path, in the JSON body or in the gRPC request.
+    /// All of them are parsed here, so the same string always resolves into the same point.
+    ///
+    /// Unsigned integers and UUIDs are parsed as is. Any other string, e.g. document URI,
+    /// is mapped into the name-based UUID, so it resolves into the same point in every segment
+    /// and shard without any shared state. The original string is not stored:
+    /// points are returned with the UUID as their ID.
+    /// Returns `None` for an empty string.
+    pub fn parse_str(value: &str) -> Option<Self> {
+        if value.is_empty() {
+            return None;
+        }
+        if let Ok(num) = value.parse() {
+            return Some(Self::NumId(num));
+        }
+        let uuid = Uuid::from_str(value)
+            .unwrap_or_else(|_| Uuid::new_v5(&POINT_ID_ALIAS_NAMESPACE, value.as_bytes()));
+        Some(Self::Uuid(uuid))
+    }
+}
+
 impl std::fmt::Display for ExtendedPointId {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -66,15 +100,48 @@ impl FromStr for ExtendedPointId {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let try_num: Result<u64, _> = s.parse();
-        if let Ok(num) = try_num {
-            return Ok(Self::NumId(num));
-        }
-        let try_uuid = Uuid::from_str(s);
-        if let Ok(uuid) = try_uuid {
-            return Ok(Self::Uuid(uuid));
+        Self::parse_str(s).ok_or(())
+    }
+}
+
+impl<'de> Deserialize<'de> for ExtendedPointId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct PointIdVisitor;
+
+        impl<'de> Visitor<'de> for PointIdVisitor {
+            type Value = ExtendedPointId;
+
+            fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+                formatter.write_str("an unsigned integer or a non-empty string")
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+                Ok(ExtendedPointId::NumId(value))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+                u64::try_from(value)
+                    .map(ExtendedPointId::NumId)
+                    .map_err(|_| E::invalid_value(Unexpected::Signed(value), &self))
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                ExtendedPointId::parse_str(value)
+                    .ok_or_else(|| E::invalid_value(Unexpected::Str(value), &self))
+            }
+
+            // UUIDs are serialized as bytes by non human-readable formats
+            fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<Self::Value, E> {
+                Uuid::from_slice(value)
+                    .map(ExtendedPointId::Uuid)
+                    .map_err(|_| E::invalid_value(Unexpected::Bytes(value), &self))
+            }
         }
-        Err(())
+
+        deserializer.deserialize_any(PointIdVisitor)
     }
 }
 
@@ -107,6 +174,28 @@ impl Distance {
         }
     }
 
+    /// Preprocess each vector of the multivector, which consists of the vectors of size `dim`
+    pub fn preprocess_multivector(
+        &self,
+        multivector: &[VectorElementType],
+        dim: usize,
+    ) -> Option<Vec<VectorElementType>> {
+        match self {
+            Distance::Cosine => preprocess_multivector::<CosineMetric>(multivector, dim),
+            Distance::Euclid => preprocess_multivector::<EuclidMetric>(multivector, dim),
+            Distance::Dot => preprocess_multivector::<DotProductMetric>(multivector, dim),
+        }
+    }
+
+    /// Internal score of the preprocessed vectors, greater the value - closer the vectors
+    pub fn similarity(&self, v1: &[VectorElementType], v2: &[VectorElementType]) -> ScoreType {
+        match self {
+            Distance::Cosine => CosineMetric::similarity(v1, v2),
+            Distance::Euclid => EuclidMetric::similarity(v1, v2),
+            Distance::Dot => DotProductMetric::similarity(v1, v2),
+        }
+    }
+
     pub fn postprocess_score(&self, score: ScoreType) -> ScoreType {
         match self {
             Distance::Cosine => CosineMetric::postprocess(score),
@@ -115,6 +204,17 @@ impl Distance {
         }
     }
 
+    /// Convert the score, as returned to the user, back into the internal score space.
+    /// Inverse of [`Self::postprocess_score`], used to compare thresholds with internal scores
+    pub fn preprocess_score(&self, score: ScoreType) -> ScoreType {
+        match self {
+            Distance::Cosine | Distance::Dot => score,
+            // Internal score is a negative squared distance.
+            // Negative threshold is converted into positive, which no point can pass
+            Distance::Euclid => -score * score.abs(),
+        }
+    }
+
     pub fn distance_order(&self) -> Order {
         match self {
             Distance::Cosine | Distance::Dot => Order::LargeBetter,
@@ -171,6 +271,107 @@ impl PartialEq for ScoredPoint {
     }
 }
 
+/// Results of a batch of searches, one list of points per search
+#[derive(Clone, Debug, Default)]
+pub struct BatchSearchResult {
+    pub results: Vec<Vec<ScoredPoint>>,
+    /// Some search was interrupted by its deadline and returned the best points found so far
+    pub truncated: bool,
+    /// How the searches were executed
+    pub explanation: SearchExplanation,
+}
+
+/// How the search was executed: number of segment searches per strategy, chosen by the planner,
+/// and the amount of work done by them
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct SearchExplanation {
+    /// Searches, which scored all the points, matching the filter
+    pub exact: usize,
+    /// Searches in the HNSW graph without a filter
+    pub hnsw: usize,
+    /// Searches in the HNSW graph, which checked the filter on each visited point
+    pub filtered_hnsw: usize,
+    /// Searches in the HNSW graph without the filter, which filtered the found points afterwards
+    pub hnsw_post_filter: usize,
+    /// Number of searched segments
+    pub segments: usize,
+    /// Number of stored vectors, the query vectors were compared with
+    pub scored_vectors: usize,
+    /// The largest size of the beam, used by the HNSW searches
+    pub hnsw_ef: Option<usize>,
+    /// Estimated number of points, matching the filter, summed over the planned searches
+    pub filter_cardinality: Option<FilterCardinality>,
+    /// Time spent in each shard
+    pub shards: Vec<ShardSearchExplanation>,
+}
+
+/// Estimated number of points, matching the filter
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub struct FilterCardinality {
+    /// Number of matching points in the best case
+    pub min: usize,
+    /// Expected number of matching points
+    pub exp: usize,
+    /// Number of matching points in the worst case
+    pub max: usize,
+}
+
+impl From<&CardinalityEstimation> for FilterCardinality {
+    fn from(estimation: &CardinalityEstimation) -> Self {
+        FilterCardinality {
+            min: estimation.min,
+            exp: estimation.exp,
+            max: estimation.max,
+        }
+    }
+}
+
+/// Search of a single shard
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct ShardSearchExplanation {
+    pub shard_id: u32,
+    /// Time spent to search the shard, including the network round trip for remote shards
+    pub time: f64,
+    /// Number of searched segments of the shard
+    pub segments: usize,
+    /// Number of stored vectors of the shard, the query vectors were compared with
+    pub scored_vectors: usize,
+}
+
+impl SearchExplanation {
+    pub fn record(&mut self, strategy: SearchStrategy) {
+        let counter = match strategy {
+            SearchStrategy::Exact => &mut self.exact,
+            SearchStrategy::Hnsw => &mut self.hnsw,
+            SearchStrategy::FilteredHnsw => &mut self.filtered_hnsw,
+            SearchStrategy::HnswPostFilter => &mut self.hnsw_post_filter,
+        };
+        *counter += 1;
+    }
+
+    pub fn merge(&mut self, other: &SearchExplanation) {
+        self.exact += other.exact;
+        self.hnsw += other.hnsw;
+        self.filtered_hnsw += other.filtered_hnsw;
+        self.hnsw_post_filter += other.hnsw_post_filter;
+        self.segments += other.segments;
+        self.scored_vectors += other.scored_vectors;
+        self.hnsw_ef = self.hnsw_ef.max(other.hnsw_ef);
+        self.filter_cardinality = match (self.filter_cardinality, other.filter_cardinality) {
+            (Some(cardinality), Some(other)) => Some(FilterCardinality {
+                min: cardinality.min + other.min,
+                exp: cardinality.exp + other.exp,
+                max: cardinality.max + other.max,
+            }),
+            (cardinality, other) => cardinality.or(other),
+        };
+        self.shards.extend(other.shards.iter().cloned());
+    }
+}
+
 /// Type of segment
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -209,6 +410,11 @@ impl PayloadIndexInfo {
                     params: Some(schema_params),
                     points: points_count,
                 },
+                PayloadSchemaParams::Keyword(_) => PayloadIndexInfo {
+                    data_type: PayloadSchemaType::Keyword,
+                    params: Some(schema_params),
+                    points: points_count,
+                },
             },
         }
     }
@@ -239,6 +445,23 @@ pub struct SearchParams {
     /// Search without approximation. If set to true, search may run long but with exact results.
     #[serde(default)]
     pub exact: bool,
+
+    /// Time budget of the HNSW search in milliseconds.
+    /// If the budget is exhausted, search returns the best points found so far instead of failing.
+    /// Response of such search is marked as `truncated`.
+    #[serde(default)]
+    pub max_time_ms: Option<u64>,
+
+    /// Report, how the search was executed: which strategy the query planner chose for each
+    /// segment, depending on the number of points, matching the filter.
+    #[serde(default)]
+    pub explain: bool,
+}
+
+impl SearchParams {
+    pub fn max_time(&self) -> Option<Duration> {
+        self.max_time_ms.map(Duration::from_millis)
+    }
 }
 
 /// Vector index configuration of the segment
@@ -277,6 +500,20 @@ fn default_max_indexing_threads() -> usize {
     0
 }
 
+/// Config of the product quantization of vectors.
+/// Vectors are split into `num_subvectors` parts, each part is encoded with a single byte -
+/// id of the closest centroid in the codebook of this part. Codebooks are trained on the vectors
+/// of the segment during optimization.
+/// Search uses the quantized vectors kept in RAM, the best candidates are rescored with the
+/// original vectors.
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Copy, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub struct ProductQuantizationConfig {
+    /// Number of parts, vectors are split into. Larger the value - more accurate the search,
+    /// more memory is required: 1 byte per part for each vector.
+    pub num_subvectors: usize,
+}
+
 impl Default for HnswConfig {
     fn default() -> Self {
         HnswConfig {
@@ -288,6 +525,16 @@ impl Default for HnswConfig {
     }
 }
 
+impl HnswConfig {
+    /// Whether the graph, built with this config, differs from the one built with `other`.
+    /// The number of indexing threads does not affect the graph.
+    pub fn mismatch_requires_rebuild(&self, other: &HnswConfig) -> bool {
+        self.m != other.m
+            || self.ef_construct != other.ef_construct
+            || self.full_scan_threshold != other.full_scan_threshold
+    }
+}
+
 impl Indexes {
     pub fn default_hnsw() -> Self {
         Indexes::Hnsw(Default::default())
@@ -372,6 +619,57 @@ pub struct VectorDataConfig {
     pub size: usize,
     /// Type of distance function used for measuring distance between vectors
     pub distance: Distance,
+    /// Config of HNSW index of this vector. If none - config of the segment index is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hnsw_config: Option<HnswConfig>,
+    /// If set - vectors of indexed segments are also stored product-quantized
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quantization_config: Option<ProductQuantizationConfig>,
+    /// Type of the stored vector elements
+    #[serde(default)]
+    pub datatype: VectorStorageDatatype,
+    /// If set - each point stores a list of vectors of the `size`, compared by the comparator
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multivector_config: Option<MultiVectorConfig>,
+}
+
+/// Way to compare multivectors with the query, which is a multivector too
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MultiVectorComparator {
+    /// Sum of the best similarities of each query vector to any of the point vectors
+    #[default]
+    MaxSim,
+}
+
+/// Config of vectors, each point of which is a list of vectors of the same size,
+/// e.g. token embeddings for late interaction models like ColBERT
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct MultiVectorConfig {
+    pub comparator: MultiVectorComparator,
+}
+
+/// Type of the elements of stored vectors
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum VectorStorageDatatype {
+    /// 32-bit floating point numbers
+    #[default]
+    Float32,
+    /// Unsigned bytes. Vector elements should be integers in range `[0, 255]`.
+    /// Vectors take 4 times less space, but they are not preprocessed, e.g. normalized for cosine
+    Uint8,
+}
+
+impl VectorStorageDatatype {
+    /// Size of a single stored vector element in bytes
+    pub fn element_size(&self) -> usize {
+        match self {
+            VectorStorageDatatype::Float32 => VECTOR_ELEMENT_SIZE,
+            VectorStorageDatatype::Uint8 => size_of::<u8>(),
+        }
+    }
 }
 
 /// Default value based on <https://github.com/google-research/google-research/blob/master/scann/docs/algorithms.md>
@@ -443,6 +741,72 @@ impl TryFrom<GeoPointShadow> for GeoPoint {
     }
 }
 
+/// Datetime payload: RFC 3339 string, e.g. `2023-02-08T10:49:00Z`.
+/// Stored as the number of microseconds since the Unix epoch.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(try_from = "String", into = "String")]
+pub struct DateTimePayloadType(pub i64);
+
+impl DateTimePayloadType {
+    pub fn timestamp_micros(self) -> i64 {
+        self.0
+    }
+}
+
+impl FromStr for DateTimePayloadType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let datetime = chrono::DateTime::parse_from_rfc3339(s)
+            .map_err(|err| format!("Wrong format of datetime `{s}`, expected RFC 3339: {err}"))?;
+        let micros = datetime.timestamp() * 1_000_000 + datetime.timestamp_subsec_micros() as i64;
+        Ok(DateTimePayloadType(micros))
+    }
+}
+
+impl TryFrom<String> for DateTimePayloadType {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl std::fmt::Display for DateTimePayloadType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let secs = self.0.div_euclid(1_000_000);
+        let nanos = self.0.rem_euclid(1_000_000) as u32 * 1_000;
+        match chrono::Utc.timestamp_opt(secs, nanos).single() {
+            Some(datetime) => write!(
+                f,
+                "{}",
+                datetime.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true)
+            ),
+            None => write!(f, "{}", self.0),
+        }
+    }
+}
+
+impl From<DateTimePayloadType> for String {
+    fn from(value: DateTimePayloadType) -> Self {
+        value.to_string()
+    }
+}
+
+impl JsonSchema for DateTimePayloadType {
+    fn schema_name() -> String {
+        "DateTimePayloadType".to_string()
+    }
+
+    fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
+        Schema::Object(SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            format: Some("date-time".to_string()),
+            ..Default::default()
+        })
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Eq)]
 pub struct Payload(pub Map<String, Value>);
 
@@ -460,6 +824,12 @@ impl Payload {
         utils::get_value_from_json_map(path, &self.0)
     }
 
+    /// Get all values by the path, which may refer to elements of nested arrays,
+    /// e.g. `metadata.tags[].id`
+    pub fn get_values(&self, path: &str) -> Vec<&Value> {
+        nested_path::get_values(path, &self.0)
+    }
+
     pub fn remove(&mut self, path: &str) -> Option<Value> {
         utils::remove_value_from_json_map(path, &mut self.0)
     }
@@ -596,6 +966,7 @@ pub enum PayloadSchemaType {
     Float,
     Geo,
     Text,
+    Datetime,
 }
 
 /// Payload type with parameters
@@ -604,6 +975,7 @@ pub enum PayloadSchemaType {
 #[serde(untagged)]
 pub enum PayloadSchemaParams {
     Text(TextIndexParams),
+    Keyword(KeywordIndexParams),
 }
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Hash, Eq)]
@@ -628,6 +1000,9 @@ impl TryFrom<PayloadIndexInfo> for PayloadFieldSchema {
             (PayloadSchemaType::Text, Some(PayloadSchemaParams::Text(params))) => Ok(
                 PayloadFieldSchema::FieldParams(PayloadSchemaParams::Text(params)),
             ),
+            (PayloadSchemaType::Keyword, Some(PayloadSchemaParams::Keyword(params))) => Ok(
+                PayloadFieldSchema::FieldParams(PayloadSchemaParams::Keyword(params)),
+            ),
             (data_type, Some(_)) => Err(format!(
                 "Payload field with type {:?} has unexpected params",
                 data_type
@@ -780,6 +1155,122 @@ impl Range {
     }
 }
 
+/// Range filter request on datetime payload, bounds are RFC 3339 strings
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub struct DatetimeRange {
+    /// point.key < range.lt
+    pub lt: Option<DateTimePayloadType>,
+    /// point.key > range.gt
+    pub gt: Option<DateTimePayloadType>,
+    /// point.key >= range.gte
+    pub gte: Option<DateTimePayloadType>,
+    /// point.key <= range.lte
+    pub lte: Option<DateTimePayloadType>,
+}
+
+impl DatetimeRange {
+    pub fn check_range(&self, datetime: DateTimePayloadType) -> bool {
+        self.lt.map_or(true, |x| datetime < x)
+            && self.gt.map_or(true, |x| datetime > x)
+            && self.lte.map_or(true, |x| datetime <= x)
+            && self.gte.map_or(true, |x| datetime >= x)
+    }
+}
+
+/// Datetime range in microseconds since the Unix epoch, as used by the numeric index
+impl From<DatetimeRange> for Range {
+    fn from(range: DatetimeRange) -> Self {
+        let micros =
+            |datetime: DateTimePayloadType| datetime.timestamp_micros() as FloatPayloadType;
+        Range {
+            lt: range.lt.map(micros),
+            gt: range.gt.map(micros),
+            gte: range.gte.map(micros),
+            lte: range.lte.map(micros),
+        }
+    }
+}
+
+impl From<Range> for DatetimeRange {
+    fn from(range: Range) -> Self {
+        let datetime = |micros: FloatPayloadType| DateTimePayloadType(micros as i64);
+        DatetimeRange {
+            lt: range.lt.map(datetime),
+            gt: range.gt.map(datetime),
+            gte: range.gte.map(datetime),
+            lte: range.lte.map(datetime),
+        }
+    }
+}
+
+/// Direction of ordering by payload value
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    /// Smallest values first
+    #[default]
+    Asc,
+    /// Largest values first
+    Desc,
+}
+
+/// Order points by the value of a numeric payload field.
+/// Requires a numeric (integer or float) payload index on the field, ordering without the index
+/// is not supported.
+/// Point with multiple values of the field is returned once, at the position of its smallest value
+/// for `asc` direction and its largest value for `desc`. Points without the field are skipped.
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct OrderBy {
+    /// Payload key to order by
+    pub key: PayloadKeyType,
+    /// Direction of ordering: `asc` or `desc`. Default: `asc`
+    pub direction: Option<Direction>,
+    /// Value of the payload field to start reading from (inclusive).
+    /// Used together with `offset` for pagination: points with the same value
+    /// are additionally ordered by id.
+    pub start_from: Option<FloatPayloadType>,
+}
+
+impl OrderBy {
+    pub fn direction(&self) -> Direction {
+        self.direction.unwrap_or_default()
+    }
+
+    /// Compare `(value, id)` pairs according to the ordering.
+    /// Points with equal values are always ordered by id ascending.
+    pub fn compare(
+        &self,
+        a: (FloatPayloadType, PointIdType),
+        b: (FloatPayloadType, PointIdType),
+    ) -> std::cmp::Ordering {
+        let by_value = OrderedFloat(a.0).cmp(&OrderedFloat(b.0));
+        let by_value = match self.direction() {
+            Direction::Asc => by_value,
+            Direction::Desc => by_value.reverse(),
+        };
+        by_value.then_with(|| a.1.cmp(&b.1))
+    }
+
+    /// Check if the `(value, id)` pair is located at or after the pagination cursor,
+    /// defined by `start_from` and `offset`.
+    pub fn is_after_cursor(
+        &self,
+        value: FloatPayloadType,
+        id: PointIdType,
+        offset: Option<PointIdType>,
+    ) -> bool {
+        match self.start_from {
+            None => true,
+            Some(start_from) => match offset {
+                None => self.compare((value, id), (start_from, id)).is_ge(),
+                Some(offset) => self.compare((value, id), (start_from, offset)).is_ge(),
+            },
+        }
+    }
+}
+
 /// Values count filter request
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Copy, Clone, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -865,6 +1356,8 @@ pub struct FieldCondition {
     pub geo_radius: Option<GeoRadius>,
     /// Check number of values of the field
     pub values_count: Option<ValuesCount>,
+    /// Check if points datetime lies in a given range
+    pub datetime_range: Option<DatetimeRange>,
 }
 
 impl FieldCondition {
@@ -876,6 +1369,7 @@ impl FieldCondition {
             geo_bounding_box: None,
             geo_radius: None,
             values_count: None,
+            datetime_range: None,
         }
     }
 
@@ -887,6 +1381,7 @@ impl FieldCondition {
             geo_bounding_box: None,
             geo_radius: None,
             values_count: None,
+            datetime_range: None,
         }
     }
 
@@ -898,6 +1393,7 @@ impl FieldCondition {
             geo_bounding_box: Some(geo_bounding_box),
             geo_radius: None,
             values_count: None,
+            datetime_range: None,
         }
     }
 
@@ -909,6 +1405,7 @@ impl FieldCondition {
             geo_bounding_box: None,
             geo_radius: Some(geo_radius),
             values_count: None,
+            datetime_range: None,
         }
     }
 
@@ -920,6 +1417,19 @@ impl FieldCondition {
             geo_bounding_box: None,
             geo_radius: None,
             values_count: Some(values_count),
+            datetime_range: None,
+        }
+    }
+
+    pub fn new_datetime_range(key: PayloadKeyType, datetime_range: DatetimeRange) -> Self {
+        Self {
+            key,
+            r#match: None,
+            range: None,
+            geo_bounding_box: None,
+            geo_radius: None,
+            values_count: None,
+            datetime_range: Some(datetime_range),
         }
     }
 }
@@ -949,6 +1459,53 @@ impl From<HashSet<PointIdType>> for HasIdCondition {
     }
 }
 
+/// Filter, applied to each element of the nested array separately
+#[derive(Debug, Serialize, JsonSchema, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct Nested {
+    /// Path to the array of objects, e.g. `metadata.tags`
+    pub key: PayloadKeyType,
+    /// All conditions of the filter are checked against the same element of the array.
+    /// `has_id` conditions are not allowed inside the nested filter.
+    pub filter: Filter,
+}
+
+impl Nested {
+    /// Elements of the nested array have no ids, so `has_id` conditions are rejected
+    pub fn new(key: PayloadKeyType, filter: Filter) -> Result<Self, String> {
+        if filter.has_id_condition() {
+            return Err(format!(
+                "`has_id` condition is not allowed inside of the nested filter on `{key}`"
+            ));
+        }
+        Ok(Self { key, filter })
+    }
+}
+
+impl<'de> Deserialize<'de> for Nested {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "snake_case")]
+        struct NestedShadow {
+            key: PayloadKeyType,
+            filter: Filter,
+        }
+
+        let NestedShadow { key, filter } = NestedShadow::deserialize(deserializer)?;
+        Nested::new(key, filter).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Select points, which have at least one element of the nested array, matching the filter
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct NestedCondition {
+    pub nested: Nested,
+}
+
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
 #[serde(untagged)]
 #[allow(clippy::large_enum_variant)]
@@ -959,6 +1516,8 @@ pub enum Condition {
     IsEmpty(IsEmptyCondition),
     /// Check if points id is in a given set
     HasId(HasIdCondition),
+    /// Check conditions against each element of the nested array
+    Nested(NestedCondition),
     /// Nested filter
     Filter(Filter),
 }
@@ -1051,7 +1610,8 @@ impl From<&WithPayloadInterface> for WithPayload {
 #[serde(deny_unknown_fields)]
 #[serde(rename_all = "snake_case")]
 pub struct PayloadSelectorInclude {
-    /// Only include this payload keys
+    /// Only include this payload keys.
+    /// Nested fields are selected by the dot-separated path, e.g. `meta.title`
     pub include: Vec<PayloadKeyType>,
 }
 
@@ -1065,7 +1625,8 @@ impl PayloadSelectorInclude {
 #[serde(deny_unknown_fields)]
 #[serde(rename_all = "snake_case")]
 pub struct PayloadSelectorExclude {
-    /// Exclude this fields from returning payload
+    /// Exclude this fields from returning payload.
+    /// Nested fields are selected by the dot-separated path, e.g. `meta.raw_html`
     pub exclude: Vec<PayloadKeyType>,
 }
 
@@ -1113,10 +1674,31 @@ impl PayloadSelector {
         }
     }
 
+    /// Select fields of the payload. Paths are resolved through nested objects, but not arrays.
+    /// Top-level keys, which contain dots themselves, take precedence over nested fields.
     pub fn process(&self, x: Payload) -> Payload {
-        let map: serde_json::Map<String, Value> =
-            x.into_iter().filter(|(key, _)| self.check(key)).collect();
-        map.into()
+        match self {
+            PayloadSelector::Include(selector) => {
+                let mut map = Map::new();
+                for path in &selector.include {
+                    if let Some(value) = x.0.get(path) {
+                        map.insert(path.clone(), value.clone());
+                    } else if let Some(value) = x.get_value(path) {
+                        utils::set_value_to_json_map(path, &mut map, value.clone());
+                    }
+                }
+                map.into()
+            }
+            PayloadSelector::Exclude(selector) => {
+                let mut map = x.0;
+                for path in &selector.exclude {
+                    if map.remove(path).is_none() {
+                        utils::remove_value_from_json_map(path, &mut map);
+                    }
+                }
+                map.into()
+            }
+        }
     }
 }
 
@@ -1130,12 +1712,23 @@ pub struct WithPayload {
     pub payload_selector: Option<PayloadSelector>,
 }
 
+/// At least `min_count` of the conditions should match
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "snake_case")]
+pub struct MinShould {
+    pub conditions: Vec<Condition>,
+    pub min_count: usize,
+}
+
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
 #[serde(deny_unknown_fields)]
 #[serde(rename_all = "snake_case")]
 pub struct Filter {
     /// At least one of those conditions should match
     pub should: Option<Vec<Condition>>,
+    /// At least minimum amount of given conditions should match
+    pub min_should: Option<MinShould>,
     /// All conditions must match
     pub must: Option<Vec<Condition>>,
     /// All conditions must NOT match
@@ -1143,9 +1736,29 @@ pub struct Filter {
 }
 
 impl Filter {
+    /// Whether any of the conditions, including ones of the sub-filters, is `has_id`
+    pub fn has_id_condition(&self) -> bool {
+        [&self.should, &self.must, &self.must_not]
+            .into_iter()
+            .flatten()
+            .flatten()
+            .chain(
+                self.min_should
+                    .iter()
+                    .flat_map(|min_should| &min_should.conditions),
+            )
+            .any(|condition| match condition {
+                Condition::HasId(_) => true,
+                Condition::Filter(filter) => filter.has_id_condition(),
+                Condition::Nested(nested) => nested.nested.filter.has_id_condition(),
+                Condition::Field(_) | Condition::IsEmpty(_) => false,
+            })
+    }
+
     pub fn new_should(condition: Condition) -> Self {
         Filter {
             should: Some(vec![condition]),
+            min_should: None,
             must: None,
             must_not: None,
         }
@@ -1154,6 +1767,7 @@ impl Filter {
     pub fn new_must(condition: Condition) -> Self {
         Filter {
             should: None,
+            min_should: None,
             must: Some(vec![condition]),
             must_not: None,
         }
@@ -1162,10 +1776,20 @@ impl Filter {
     pub fn new_must_not(condition: Condition) -> Self {
         Filter {
             should: None,
+            min_should: None,
             must: None,
             must_not: Some(vec![condition]),
         }
     }
+
+    pub fn new_min_should(min_should: MinShould) -> Self {
+        Filter {
+            should: None,
+            min_should: Some(min_should),
+            must: None,
+            must_not: None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -1197,6 +1821,42 @@ mod tests {
         eprintln!("de_record = {:#?}", de_record);
     }
 
+    #[test]
+    fn test_string_point_id() {
+        let uri = "https://example.com/documents/2023/very-long-document-name.html#section-1";
+        let id: PointIdType = serde_json::from_value(json!(uri)).unwrap();
+        assert!(matches!(id, ExtendedPointId::Uuid(_)));
+        assert_eq!(Ok(id), uri.parse::<PointIdType>());
+        assert_ne!(Some(id), ExtendedPointId::parse_str("another-document"));
+
+        // Original string is not kept, the point is returned with the name-based UUID
+        let serialized = serde_json::to_value(id).unwrap();
+        assert_ne!(serialized, json!(uri));
+        assert_eq!(
+            serde_json::from_value::<PointIdType>(serialized).unwrap(),
+            id
+        );
+
+        // Strings of digits are the same numeric IDs in the URL path and in the body
+        let id: PointIdType = serde_json::from_value(json!("123")).unwrap();
+        assert_eq!(id, ExtendedPointId::NumId(123));
+        assert_eq!(Ok(id), "123".parse::<PointIdType>());
+
+        let uuid = Uuid::new_v4();
+        let id: PointIdType = serde_json::from_value(json!(uuid.to_string())).unwrap();
+        assert_eq!(id, ExtendedPointId::Uuid(uuid));
+
+        let id: PointIdType = serde_json::from_value(json!(42)).unwrap();
+        assert_eq!(id, ExtendedPointId::NumId(42));
+
+        assert!(serde_json::from_value::<PointIdType>(json!("")).is_err());
+        assert!("".parse::<PointIdType>().is_err());
+        assert!(serde_json::from_value::<PointIdType>(json!(-1)).is_err());
+
+        check_rms_serialization(ExtendedPointId::Uuid(uuid));
+        check_rms_serialization(ExtendedPointId::NumId(42));
+    }
+
     #[test]
     fn test_serialize_query() {
         let filter = Filter {
@@ -1206,6 +1866,7 @@ mod tests {
             ))]),
             must_not: None,
             should: None,
+            min_should: None,
         };
         let json = serde_json::to_string_pretty(&filter).unwrap();
         println!("{}", json)
@@ -1348,6 +2009,58 @@ mod tests {
         assert!(filter.is_err());
     }
 
+    #[test]
+    fn test_parse_datetime_range() {
+        let query = r#"
+        {
+            "key": "created_at",
+            "datetime_range": {
+                "gte": "2023-02-08T10:49:00Z",
+                "lt": "2023-02-08T12:49:00.5+02:00"
+            }
+        }
+        "#;
+        let condition: FieldCondition = serde_json::from_str(query).unwrap();
+        let range = condition.datetime_range.unwrap();
+        assert_eq!(range.gte, Some(DateTimePayloadType(1_675_853_340_000_000)));
+        assert_eq!(range.lt, Some(DateTimePayloadType(1_675_853_340_500_000)));
+        assert!(range.check_range("2023-02-08T10:49:00.25Z".parse().unwrap()));
+        assert!(!range.check_range("2023-02-08T10:49:00.5Z".parse().unwrap()));
+        assert_eq!(
+            range.gte.unwrap().to_string(),
+            "2023-02-08T10:49:00Z".to_string()
+        );
+
+        let query = r#"{ "key": "created_at", "datetime_range": { "gte": "yesterday" } }"#;
+        assert!(serde_json::from_str::<FieldCondition>(query).is_err());
+    }
+
+    #[test]
+    fn test_nested_validation() {
+        let query = |inner: &str| {
+            format!(
+                r#"{{ "must": [ {{ "nested": {{ "key": "tags", "filter": {{ "must": [ {inner} ] }} }} }} ] }}"#
+            )
+        };
+
+        let filter: Result<Filter, _> =
+            serde_json::from_str(&query(r#"{ "key": "id", "match": { "value": 1 } }"#));
+        assert!(filter.is_ok());
+
+        let filter: Result<Filter, _> = serde_json::from_str(&query(r#"{ "has_id": [1] }"#));
+        assert!(filter.is_err());
+
+        // Also rejected inside of sub-filters
+        let filter: Result<Filter, _> =
+            serde_json::from_str(&query(r#"{ "must_not": [ { "has_id": [1] } ] }"#));
+        assert!(filter.is_err());
+
+        let nested: Result<Nested, _> = serde_json::from_str(
+            r#"{ "key": "tags", "filter": { "must": [ { "has_id": [1] } ] } }"#,
+        );
+        assert!(nested.unwrap_err().to_string().contains("has_id"));
+    }
+
     #[test]
     fn test_remove_key() {
         let mut payload: Payload = serde_json::from_str(
@@ -1394,10 +2107,50 @@ mod tests {
         let ft_json = serde_json::to_string(&ft).unwrap();
         eprintln!("ft_json = {:?}", ft_json);
 
+        let query = r#"{"type": "keyword", "on_disk": true}"#;
+        let field_schema: PayloadFieldSchema = serde_json::from_str(query).unwrap();
+        assert_eq!(
+            field_schema,
+            PayloadFieldSchema::FieldParams(PayloadSchemaParams::Keyword(KeywordIndexParams {
+                r#type: Default::default(),
+                on_disk: Some(true),
+            }))
+        );
+
         let query = r#""keyword""#;
         let field_type: PayloadSchemaType = serde_json::from_str(query).unwrap();
         eprintln!("field_type = {:?}", field_type);
     }
+
+    #[test]
+    fn test_nested_payload_selector() {
+        let payload: Payload = json!({
+            "id": 1,
+            "meta": {"title": "Title", "raw_html": "<html></html>", "lang": "en"},
+            "a.b": "dotted key",
+        })
+        .into();
+
+        let include = PayloadSelector::new_include(vec![
+            "id".to_string(),
+            "meta.title".to_string(),
+            "a.b".to_string(),
+            "meta.missing".to_string(),
+        ]);
+        assert_eq!(
+            include.process(payload.clone()),
+            Payload::from(json!({"id": 1, "meta": {"title": "Title"}, "a.b": "dotted key"}))
+        );
+
+        let exclude = PayloadSelector::Exclude(PayloadSelectorExclude::new(vec![
+            "meta.raw_html".to_string(),
+            "a.b".to_string(),
+        ]));
+        assert_eq!(
+            exclude.process(payload),
+            Payload::from(json!({"id": 1, "meta": {"title": "Title", "lang": "en"}}))
+        );
+    }
 }
 
 pub type TheMap<K, V> = BTreeMap<K, V>;
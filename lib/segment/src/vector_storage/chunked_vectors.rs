@@ -4,7 +4,7 @@ use std::mem;
 use crate::data_types::vectors::VectorElementType;
 use crate::types::PointOffsetType;
 
-type Chunk = Vec<VectorElementType>;
+type Chunk<T> = Vec<T>;
 
 // chunk size in bytes
 const CHUNK_SIZE: usize = 32 * 1024 * 1024;
@@ -12,17 +12,18 @@ const CHUNK_SIZE: usize = 32 * 1024 * 1024;
 // if dimension is too high, use this capacity
 const MIN_CHUNK_CAPACITY: usize = 16;
 
-pub struct ChunkedVectors {
+/// Vectors of elements of type `T`, stored in chunks of contiguous memory
+pub struct ChunkedVectors<T = VectorElementType> {
     dim: usize,
     len: usize,            // amount of stored vectors
     chunk_capacity: usize, // max amount of vectors in each chunk
-    chunks: Vec<Chunk>,
+    chunks: Vec<Chunk<T>>,
 }
 
-impl ChunkedVectors {
-    pub fn new(dim: usize) -> ChunkedVectors {
+impl<T: Copy + Default> ChunkedVectors<T> {
+    pub fn new(dim: usize) -> ChunkedVectors<T> {
         assert_ne!(dim, 0, "The vector's dimension cannot be 0");
-        let vector_size = dim * mem::size_of::<VectorElementType>();
+        let vector_size = dim * mem::size_of::<T>();
         let chunk_capacity = max(MIN_CHUNK_CAPACITY, CHUNK_SIZE / vector_size);
         ChunkedVectors {
             dim,
@@ -40,20 +41,20 @@ impl ChunkedVectors {
         self.len == 0
     }
 
-    pub fn get(&self, key: PointOffsetType) -> &[VectorElementType] {
+    pub fn get(&self, key: PointOffsetType) -> &[T] {
         let key = key as usize;
         let chunk_data = &self.chunks[key / self.chunk_capacity];
         let idx = (key % self.chunk_capacity) * self.dim;
         &chunk_data[idx..idx + self.dim]
     }
 
-    pub fn push(&mut self, vector: &[VectorElementType]) -> PointOffsetType {
+    pub fn push(&mut self, vector: &[T]) -> PointOffsetType {
         let new_id = self.len as PointOffsetType;
         self.insert(new_id, vector);
         new_id
     }
 
-    pub fn insert(&mut self, key: PointOffsetType, vector: &[VectorElementType]) {
+    pub fn insert(&mut self, key: PointOffsetType, vector: &[T]) {
         let key = key as usize;
         self.len = max(self.len, key + 1);
         while self.chunks.len() * self.chunk_capacity < self.len {
@@ -63,7 +64,7 @@ impl ChunkedVectors {
         let chunk_data = &mut self.chunks[key / self.chunk_capacity];
         let idx = (key % self.chunk_capacity) * self.dim;
         if chunk_data.len() < idx + self.dim {
-            chunk_data.resize(idx + self.dim, 0.);
+            chunk_data.resize(idx + self.dim, T::default());
         }
         let data = &mut chunk_data[idx..idx + self.dim];
         data.copy_from_slice(vector);
@@ -0,0 +1,128 @@
+use std::sync::Arc;
+
+use cudarc::driver::{CudaDevice, LaunchAsync, LaunchConfig};
+use cudarc::nvrtc::compile_ptx;
+use log::{info, warn};
+use once_cell::sync::OnceCell;
+
+use crate::data_types::vectors::VectorElementType;
+use crate::entry::entry_point::{OperationError, OperationResult};
+use crate::types::{Distance, ScoreType};
+use crate::vector_storage::scoring_backend::ScoringBackend;
+
+const MODULE_NAME: &str = "scoring";
+const KERNEL_NAME: &str = "score_bulk";
+
+/// One thread computes the similarity of the query with a single vector.
+/// Matches the CPU metrics: dot product for preprocessed cosine and dot, negative squared distance for euclid.
+const KERNEL: &str = r#"
+extern "C" __global__ void score_bulk(
+    const float *query,
+    const float *vectors,
+    float *scores,
+    const int dim,
+    const int count,
+    const int euclid
+) {
+    int i = blockIdx.x * blockDim.x + threadIdx.x;
+    if (i >= count) {
+        return;
+    }
+    const float *vector = vectors + (size_t)i * dim;
+    float score = 0.0f;
+    for (int j = 0; j < dim; j++) {
+        if (euclid) {
+            float diff = query[j] - vector[j];
+            score -= diff * diff;
+        } else {
+            score += query[j] * vector[j];
+        }
+    }
+    scores[i] = score;
+}
+"#;
+
+fn gpu_error(err: impl std::fmt::Debug) -> OperationError {
+    OperationError::service_error(&format!("GPU scoring error: {err:?}"))
+}
+
+/// Scoring backend, which computes distances on a CUDA device
+pub struct GpuScoringBackend {
+    device: Arc<CudaDevice>,
+}
+
+impl GpuScoringBackend {
+    pub fn new(ordinal: usize) -> OperationResult<Self> {
+        let device = CudaDevice::new(ordinal).map_err(gpu_error)?;
+        let ptx = compile_ptx(KERNEL).map_err(gpu_error)?;
+        device
+            .load_ptx(ptx, MODULE_NAME, &[KERNEL_NAME])
+            .map_err(gpu_error)?;
+        Ok(Self { device })
+    }
+
+    /// Backend on the first CUDA device, initialized once.
+    /// `None` if there is no device or it can't be initialized
+    pub fn global() -> Option<&'static Self> {
+        static BACKEND: OnceCell<Option<GpuScoringBackend>> = OnceCell::new();
+        BACKEND
+            .get_or_init(|| match Self::new(0) {
+                Ok(backend) => {
+                    info!("Using GPU for bulk vector scoring");
+                    Some(backend)
+                }
+                Err(err) => {
+                    warn!("GPU is not available, vectors are scored on CPU: {err}");
+                    None
+                }
+            })
+            .as_ref()
+    }
+}
+
+impl ScoringBackend for GpuScoringBackend {
+    fn name(&self) -> &'static str {
+        "gpu"
+    }
+
+    fn score_bulk(
+        &self,
+        distance: Distance,
+        query: &[VectorElementType],
+        vectors: &[VectorElementType],
+    ) -> OperationResult<Vec<ScoreType>> {
+        let dim = query.len();
+        let count = vectors.len() / dim;
+        if count == 0 {
+            return Ok(vec![]);
+        }
+        let euclid = i32::from(distance == Distance::Euclid);
+
+        let query_buffer = self.device.htod_sync_copy(query).map_err(gpu_error)?;
+        let vectors_buffer = self.device.htod_sync_copy(vectors).map_err(gpu_error)?;
+        let mut scores_buffer = self
+            .device
+            .alloc_zeros::<ScoreType>(count)
+            .map_err(gpu_error)?;
+
+        let kernel = self
+            .device
+            .get_func(MODULE_NAME, KERNEL_NAME)
+            .ok_or_else(|| gpu_error("scoring kernel is not loaded"))?;
+        let params = (
+            &query_buffer,
+            &vectors_buffer,
+            &mut scores_buffer,
+            dim as i32,
+            count as i32,
+            euclid,
+        );
+        // Safety: kernel signature matches the parameters, buffers hold `dim` and `count * dim` elements
+        unsafe { kernel.launch(LaunchConfig::for_num_elems(count as u32), params) }
+            .map_err(gpu_error)?;
+
+        self.device
+            .dtoh_sync_copy(&scores_buffer)
+            .map_err(gpu_error)
+    }
+}
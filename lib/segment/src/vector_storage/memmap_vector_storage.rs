@@ -8,14 +8,17 @@ use std::sync::Arc;
 
 use atomic_refcell::AtomicRefCell;
 
+use crate::common::checksums::save_checksums;
 use crate::common::Flusher;
 use crate::data_types::vectors::VectorElementType;
 use crate::entry::entry_point::OperationResult;
 use crate::spaces::metric::Metric;
 use crate::spaces::simple::{CosineMetric, DotProductMetric, EuclidMetric};
 use crate::spaces::tools::peek_top_largest_iterable;
-use crate::types::{Distance, PointOffsetType, ScoreType};
+use crate::types::{Distance, PointOffsetType, ProductQuantizationConfig, ScoreType};
 use crate::vector_storage::mmap_vectors::MmapVectors;
+use crate::vector_storage::product_quantization::{ProductQuantizedVectors, QuantizedRawScorer};
+use crate::vector_storage::scoring_backend::score_accelerated;
 use crate::vector_storage::{RawScorer, ScoredPointOffset, VectorStorage, VectorStorageSS};
 
 fn vf_to_u8<T>(v: &[T]) -> &[u8] {
@@ -83,6 +86,8 @@ pub struct MemmapVectorStorage<TMetric: Metric> {
     vectors_path: PathBuf,
     deleted_path: PathBuf,
     mmap_store: Option<MmapVectors>,
+    /// Codes of the vectors, if the storage is quantized
+    quantized_vectors: Option<ProductQuantizedVectors>,
     metric: PhantomData<TMetric>,
 }
 
@@ -105,6 +110,7 @@ pub fn open_memmap_vector_storage(
             vectors_path,
             deleted_path,
             mmap_store: Some(mmap_store),
+            quantized_vectors: None,
             metric: PhantomData,
         }))),
         Distance::Euclid => Ok(Arc::new(AtomicRefCell::new(MemmapVectorStorage::<
@@ -113,6 +119,7 @@ pub fn open_memmap_vector_storage(
             vectors_path,
             deleted_path,
             mmap_store: Some(mmap_store),
+            quantized_vectors: None,
             metric: PhantomData,
         }))),
         Distance::Dot => Ok(Arc::new(AtomicRefCell::new(MemmapVectorStorage::<
@@ -121,6 +128,7 @@ pub fn open_memmap_vector_storage(
             vectors_path,
             deleted_path,
             mmap_store: Some(mmap_store),
+            quantized_vectors: None,
             metric: PhantomData,
         }))),
     }
@@ -176,6 +184,7 @@ where
         let mut end_index = start_index;
 
         self.mmap_store = None;
+        self.quantized_vectors = None;
 
         {
             let mut file = OpenOptions::new()
@@ -194,6 +203,7 @@ where
 
             file.flush()?;
         }
+        save_checksums(&self.vectors_path)?;
         {
             let mut file = OpenOptions::new()
                 .read(false)
@@ -207,6 +217,7 @@ where
             file.write_all(flag_bytes)?;
             file.flush()?;
         }
+        save_checksums(&self.deleted_path)?;
 
         self.mmap_store = Some(MmapVectors::open(
             &self.vectors_path,
@@ -243,6 +254,18 @@ where
         }
     }
 
+    fn populate(&self) {
+        if let Some(mmap_store) = &self.mmap_store {
+            mmap_store.populate();
+        }
+    }
+
+    fn resident_fraction(&self) -> f64 {
+        self.mmap_store
+            .as_ref()
+            .map_or(1.0, |mmap_store| mmap_store.resident_fraction())
+    }
+
     fn raw_scorer(&self, vector: Vec<VectorElementType>) -> Box<dyn RawScorer + '_> {
         Box::new(MemmapRawScorer::<TMetric> {
             query: TMetric::preprocess(&vector).unwrap_or(vector),
@@ -269,22 +292,25 @@ where
         let preprocessed_vector = preprocessed_vector_opt
             .as_ref()
             .map_or(vector, |x| x as &[_]);
-        let scores = points
-            .filter(|point| {
-                !self
-                    .mmap_store
-                    .as_ref()
-                    .unwrap()
-                    .deleted(*point)
-                    .unwrap_or(true)
-            })
-            .map(|point| {
-                let other_vector = self.mmap_store.as_ref().unwrap().raw_vector(point).unwrap();
-                ScoredPointOffset {
-                    idx: point,
-                    score: TMetric::similarity(preprocessed_vector, other_vector),
-                }
-            });
+        let mmap_store = self.mmap_store.as_ref().unwrap();
+        let mut points = points.filter(|point| !mmap_store.deleted(*point).unwrap_or(true));
+        let expected_count = points.size_hint().1.unwrap_or(0);
+        if let Some(scores) = score_accelerated(
+            TMetric::distance(),
+            preprocessed_vector,
+            &mut points,
+            expected_count,
+            |point| mmap_store.raw_vector(point).unwrap(),
+        ) {
+            return peek_top_largest_iterable(scores, top);
+        }
+        let scores = points.map(|point| {
+            let other_vector = mmap_store.raw_vector(point).unwrap();
+            ScoredPointOffset {
+                idx: point,
+                score: TMetric::similarity(preprocessed_vector, other_vector),
+            }
+        });
         peek_top_largest_iterable(scores, top)
     }
 
@@ -293,8 +319,18 @@ where
         let preprocessed_vector = preprocessed_vector_opt
             .as_ref()
             .map_or(vector, |x| x as &[_]);
+        let mmap_store = self.mmap_store.as_ref().unwrap();
+        if let Some(scores) = score_accelerated(
+            TMetric::distance(),
+            preprocessed_vector,
+            &mut self.iter_ids(),
+            self.vector_count(),
+            |point| mmap_store.raw_vector(point).unwrap(),
+        ) {
+            return peek_top_largest_iterable(scores, top);
+        }
         let scores = self.iter_ids().map(|point| {
-            let other_vector = self.mmap_store.as_ref().unwrap().raw_vector(point).unwrap();
+            let other_vector = mmap_store.raw_vector(point).unwrap();
             ScoredPointOffset {
                 idx: point,
                 score: TMetric::similarity(preprocessed_vector, other_vector),
@@ -313,6 +349,34 @@ where
         let vector = self.get_vector(point).unwrap();
         self.score_points(&vector, points, top)
     }
+
+    fn quantize(&mut self, path: &Path, config: &ProductQuantizationConfig) -> OperationResult<()> {
+        let quantized_vectors = ProductQuantizedVectors::build(&*self, config);
+        quantized_vectors.save(path)?;
+        self.quantized_vectors = Some(quantized_vectors);
+        Ok(())
+    }
+
+    fn load_quantization(&mut self, path: &Path) -> OperationResult<()> {
+        // Codes, saved before the vectors were appended, are not usable
+        let total_vector_count = self.total_vector_count();
+        self.quantized_vectors = ProductQuantizedVectors::load(path)?
+            .filter(|quantized| quantized.len() == total_vector_count);
+        Ok(())
+    }
+
+    fn quantized_raw_scorer(
+        &self,
+        vector: &[VectorElementType],
+    ) -> Option<Box<dyn RawScorer + '_>> {
+        let quantized_vectors = self.quantized_vectors.as_ref()?;
+        let query = TMetric::preprocess(vector).unwrap_or_else(|| vector.to_owned());
+        Some(Box::new(QuantizedRawScorer::<TMetric>::new(
+            quantized_vectors,
+            &query,
+            self,
+        )))
+    }
 }
 
 #[cfg(test)]
@@ -322,6 +386,7 @@ mod tests {
     use tempfile::Builder;
 
     use super::*;
+    use crate::common::checksums::verify_dir_checksums;
     use crate::common::rocksdb_wrapper::{open_db, DB_VECTOR_CF};
     use crate::vector_storage::simple_vector_storage::open_simple_vector_storage;
 
@@ -342,7 +407,7 @@ mod tests {
         {
             let dir2 = Builder::new().prefix("db_dir").tempdir().unwrap();
             let db = open_db(dir2.path(), &[DB_VECTOR_CF]).unwrap();
-            let storage2 = open_simple_vector_storage(db, DB_VECTOR_CF, 4, dist).unwrap();
+            let storage2 = open_simple_vector_storage(db, DB_VECTOR_CF, 4, dist, None).unwrap();
             {
                 let mut borrowed_storage2 = storage2.borrow_mut();
                 borrowed_storage2.put_vector(vec1).unwrap();
@@ -365,7 +430,7 @@ mod tests {
         {
             let dir2 = Builder::new().prefix("db_dir").tempdir().unwrap();
             let db = open_db(dir2.path(), &[DB_VECTOR_CF]).unwrap();
-            let storage2 = open_simple_vector_storage(db, DB_VECTOR_CF, 4, dist).unwrap();
+            let storage2 = open_simple_vector_storage(db, DB_VECTOR_CF, 4, dist, None).unwrap();
             {
                 let mut borrowed_storage2 = storage2.borrow_mut();
                 borrowed_storage2.put_vector(vec4).unwrap();
@@ -408,7 +473,7 @@ mod tests {
         {
             let dir2 = Builder::new().prefix("db_dir").tempdir().unwrap();
             let db = open_db(dir2.path(), &[DB_VECTOR_CF]).unwrap();
-            let storage2 = open_simple_vector_storage(db, DB_VECTOR_CF, 4, dist).unwrap();
+            let storage2 = open_simple_vector_storage(db, DB_VECTOR_CF, 4, dist, None).unwrap();
             {
                 let mut borrowed_storage2 = storage2.borrow_mut();
                 borrowed_storage2.put_vector(vec1).unwrap();
@@ -437,6 +502,41 @@ mod tests {
         assert_eq!(res[2].score, -1.0);
     }
 
+    #[test]
+    fn test_deleted_flags_checksums() {
+        let dist = Distance::Dot;
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        let storage = open_memmap_vector_storage(dir.path(), 4, dist).unwrap();
+        let mut borrowed_storage = storage.borrow_mut();
+
+        {
+            let dir2 = Builder::new().prefix("db_dir").tempdir().unwrap();
+            let db = open_db(dir2.path(), &[DB_VECTOR_CF]).unwrap();
+            let storage2 = open_simple_vector_storage(db, DB_VECTOR_CF, 4, dist, None).unwrap();
+            {
+                let mut borrowed_storage2 = storage2.borrow_mut();
+                borrowed_storage2
+                    .put_vector(vec![1.0, 0.0, 1.0, 1.0])
+                    .unwrap();
+                borrowed_storage2
+                    .put_vector(vec![1.0, 0.0, 1.0, 0.0])
+                    .unwrap();
+            }
+            borrowed_storage.update_from(&*storage2.borrow()).unwrap();
+        }
+
+        borrowed_storage.delete(1).unwrap();
+        borrowed_storage.flusher()().unwrap();
+        verify_dir_checksums(dir.path()).unwrap();
+
+        // Flip the deletion flag behind the storage
+        let deleted_path = dir.path().join("deleted.dat");
+        let mut deleted = std::fs::read(&deleted_path).unwrap();
+        *deleted.last_mut().unwrap() ^= 1;
+        std::fs::write(&deleted_path, deleted).unwrap();
+        assert!(verify_dir_checksums(dir.path()).is_err());
+    }
+
     #[test]
     fn test_casts() {
         let data: Vec<VectorElementType> = vec![0.42, 0.069, 333.1, 100500.];
@@ -1,13 +1,18 @@
 use std::fs::{File, OpenOptions};
 use std::io::Write;
 use std::mem::{size_of, transmute};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use memmap::{Mmap, MmapMut, MmapOptions};
-use parking_lot::{RwLock, RwLockReadGuard};
+use parking_lot::{Mutex, RwLock, RwLockReadGuard};
 
+use crate::common::checksums::save_checksums;
 use crate::common::error_logging::LogError;
+use crate::common::mmap_advice::{
+    global_mmap_advice, global_mmap_populate, madvise, populate, resident_fraction,
+};
 use crate::common::Flusher;
 use crate::data_types::vectors::VectorElementType;
 use crate::entry::entry_point::OperationResult;
@@ -17,13 +22,20 @@ const HEADER_SIZE: usize = 4;
 const DELETED_HEADER: &[u8; 4] = b"drop";
 const VECTORS_HEADER: &[u8; 4] = b"data";
 
+/// Residency of the vectors in the page cache is measured at most once per interval,
+/// as it takes a syscall and a byte per page of the file
+const RESIDENCY_REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
 /// Mem-mapped file with vectors and soft-delete flags
 pub struct MmapVectors {
     pub dim: usize,
     pub num_vectors: usize,
     mmap: Mmap,
     deleted_mmap: Arc<RwLock<MmapMut>>,
+    deleted_path: PathBuf,
     pub deleted_count: usize,
+    /// Last measured fraction of the vectors in the page cache
+    residency: Mutex<Option<(Instant, f64)>>,
 }
 
 fn open_read(path: &Path) -> OperationResult<Mmap> {
@@ -63,6 +75,10 @@ impl MmapVectors {
             .describe("Create mmap deleted flags file")?;
 
         let mmap = open_read(vectors_path).describe("Open mmap for reading")?;
+        madvise(&mmap, global_mmap_advice()).describe("Advise mmap access pattern")?;
+        if global_mmap_populate() {
+            populate(&mmap);
+        }
         let num_vectors = (mmap.len() - HEADER_SIZE) / dim / size_of::<VectorElementType>();
 
         let deleted_mmap = open_write(deleted_path).describe("Open mmap for writing")?;
@@ -76,7 +92,9 @@ impl MmapVectors {
             num_vectors,
             mmap,
             deleted_mmap: Arc::new(RwLock::new(deleted_mmap)),
+            deleted_path: deleted_path.to_owned(),
             deleted_count,
+            residency: Mutex::new(None),
         })
     }
 
@@ -139,10 +157,34 @@ impl MmapVectors {
         Ok(())
     }
 
+    /// Read all vectors into the page cache
+    pub fn populate(&self) {
+        populate(&self.mmap);
+    }
+
+    /// Fraction of the vectors, loaded into the page cache
+    pub fn resident_fraction(&self) -> f64 {
+        let mut residency = self.residency.lock();
+        match *residency {
+            Some((measured_at, fraction)) if measured_at.elapsed() < RESIDENCY_REFRESH_INTERVAL => {
+                fraction
+            }
+            _ => {
+                let fraction = resident_fraction(&self.mmap);
+                *residency = Some((Instant::now(), fraction));
+                fraction
+            }
+        }
+    }
+
     pub fn flusher(&self) -> Flusher {
         let deleted_mmap = self.deleted_mmap.clone();
+        let deleted_path = self.deleted_path.clone();
         Box::new(move || {
-            deleted_mmap.read().flush()?;
+            // Flags are not changed while the read lock is held, so checksums match the flushed file
+            let deleted_mmap = deleted_mmap.read();
+            deleted_mmap.flush()?;
+            save_checksums(&deleted_path)?;
             Ok(())
         })
     }
@@ -1,6 +1,13 @@
 pub mod chunked_vectors;
+#[cfg(feature = "gpu")]
+pub mod gpu_scoring_backend;
 pub mod memmap_vector_storage;
 mod mmap_vectors;
+pub mod product_quantization;
+pub mod query_scorer;
+pub mod scoring_backend;
+pub mod simple_byte_vector_storage;
+pub mod simple_multi_vector_storage;
 pub mod simple_vector_storage;
 mod vector_storage_base;
 
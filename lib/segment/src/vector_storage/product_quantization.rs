@@ -0,0 +1,384 @@
+use std::fs::create_dir_all;
+use std::marker::PhantomData;
+use std::path::Path;
+
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::common::file_operations::{atomic_save_bin, read_bin};
+use crate::data_types::vectors::VectorElementType;
+use crate::entry::entry_point::OperationResult;
+use crate::spaces::metric::Metric;
+use crate::spaces::simple::euclid_similarity;
+use crate::types::{PointOffsetType, ProductQuantizationConfig, ScoreType};
+use crate::vector_storage::{RawScorer, ScoredPointOffset, VectorStorage};
+
+pub const PRODUCT_QUANTIZATION_FILE: &str = "product_quantization.bin";
+
+/// Each part of the vector is encoded with a single byte
+const MAX_CENTROIDS: usize = 256;
+/// Max number of vectors, used to train the codebooks. Larger samples barely improve the centroids
+const MAX_TRAINING_VECTORS: usize = 65_536;
+const KMEANS_ITERATIONS: usize = 16;
+
+/// Vectors, encoded with product quantization.
+/// Each vector is split into parts, each part is replaced by the id of the closest centroid
+/// in the codebook of this part.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ProductQuantizedVectors {
+    dim: usize,
+    /// Start and end of each part in the original vector
+    subvectors: Vec<(usize, usize)>,
+    /// Centroids of each part, stored contiguously
+    codebooks: Vec<Vec<VectorElementType>>,
+    /// Codes of all vectors, `subvectors.len()` bytes each.
+    /// Deleted vectors are also encoded, so offsets of the codes match ids of the points
+    codes: Vec<u8>,
+}
+
+impl ProductQuantizedVectors {
+    /// Train codebooks on the not deleted vectors of the `storage` and encode all its vectors
+    pub fn build(storage: &dyn VectorStorage, config: &ProductQuantizationConfig) -> Self {
+        let dim = storage.vector_dim();
+        let subvectors = split_dimensions(dim, config.num_subvectors);
+
+        let mut training_ids: Vec<_> = storage.iter_ids().collect();
+        if training_ids.len() > MAX_TRAINING_VECTORS {
+            training_ids.shuffle(&mut rand::thread_rng());
+            training_ids.truncate(MAX_TRAINING_VECTORS);
+        }
+        let training_vectors: Vec<_> = training_ids
+            .into_iter()
+            .filter_map(|id| storage.get_vector(id))
+            .collect();
+
+        let codebooks = subvectors
+            .iter()
+            .map(|&(start, end)| {
+                let parts: Vec<_> = training_vectors
+                    .iter()
+                    .map(|vector| &vector[start..end])
+                    .collect();
+                train_codebook(&parts, end - start)
+            })
+            .collect();
+
+        let mut quantized = ProductQuantizedVectors {
+            dim,
+            subvectors,
+            codebooks,
+            codes: Vec::new(),
+        };
+        let total_vectors = storage.total_vector_count();
+        quantized.codes = Vec::with_capacity(total_vectors * quantized.subvectors.len());
+        for id in 0..total_vectors as PointOffsetType {
+            match storage.get_vector(id) {
+                Some(vector) => {
+                    let codes = quantized.encode(&vector);
+                    quantized.codes.extend(codes);
+                }
+                None => quantized
+                    .codes
+                    .extend(std::iter::repeat(0).take(quantized.subvectors.len())),
+            }
+        }
+        quantized
+    }
+
+    /// Load quantized vectors from `dir`, if they were saved there
+    pub fn load(dir: &Path) -> OperationResult<Option<Self>> {
+        let path = dir.join(PRODUCT_QUANTIZATION_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(read_bin(&path)?))
+    }
+
+    pub fn save(&self, dir: &Path) -> OperationResult<()> {
+        create_dir_all(dir)?;
+        atomic_save_bin(&dir.join(PRODUCT_QUANTIZATION_FILE), self)?;
+        Ok(())
+    }
+
+    /// Number of encoded vectors, including deleted
+    pub fn len(&self) -> usize {
+        self.codes.len() / self.subvectors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.codes.is_empty()
+    }
+
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    fn encode(&self, vector: &[VectorElementType]) -> Vec<u8> {
+        self.subvectors
+            .iter()
+            .zip(&self.codebooks)
+            .map(|(&(start, end), codebook)| {
+                closest_centroid(&vector[start..end], codebook, end - start) as u8
+            })
+            .collect()
+    }
+
+    fn point_codes(&self, point: PointOffsetType) -> &[u8] {
+        let size = self.subvectors.len();
+        let offset = point as usize * size;
+        &self.codes[offset..offset + size]
+    }
+
+    fn centroid(&self, subvector: usize, code: u8) -> &[VectorElementType] {
+        let (start, end) = self.subvectors[subvector];
+        let size = end - start;
+        let offset = code as usize * size;
+        &self.codebooks[subvector][offset..offset + size]
+    }
+
+    /// Similarities between parts of the query and all centroids of the corresponding codebooks.
+    /// Metrics are sums over the dimensions, so the similarity of the whole vector is a sum
+    /// of the similarities of its parts.
+    fn query_table<TMetric: Metric>(&self, query: &[VectorElementType]) -> Vec<Vec<ScoreType>> {
+        self.subvectors
+            .iter()
+            .zip(&self.codebooks)
+            .map(|(&(start, end), codebook)| {
+                codebook
+                    .chunks_exact(end - start)
+                    .map(|centroid| TMetric::similarity(&query[start..end], centroid))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Split `dim` dimensions into `num_subvectors` nearly equal parts
+fn split_dimensions(dim: usize, num_subvectors: usize) -> Vec<(usize, usize)> {
+    let num_subvectors = num_subvectors.clamp(1, dim.max(1));
+    let base = dim / num_subvectors;
+    let extra = dim % num_subvectors;
+    let mut start = 0;
+    (0..num_subvectors)
+        .map(|i| {
+            let size = base + usize::from(i < extra);
+            let range = (start, start + size);
+            start += size;
+            range
+        })
+        .collect()
+}
+
+fn closest_centroid(
+    vector: &[VectorElementType],
+    codebook: &[VectorElementType],
+    size: usize,
+) -> usize {
+    codebook
+        .chunks_exact(size)
+        .enumerate()
+        .map(|(id, centroid)| (id, euclid_similarity(vector, centroid)))
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(id, _)| id)
+        .unwrap_or(0)
+}
+
+/// K-means clustering of the vector parts, returns centroids stored contiguously
+fn train_codebook(parts: &[&[VectorElementType]], size: usize) -> Vec<VectorElementType> {
+    let centroids_count = parts.len().min(MAX_CENTROIDS);
+    if centroids_count == 0 {
+        return vec![0.0; size];
+    }
+
+    // Initialize centroids with random distinct parts
+    let mut centroids: Vec<VectorElementType> = parts
+        .choose_multiple(&mut rand::thread_rng(), centroids_count)
+        .flat_map(|part| part.iter().copied())
+        .collect();
+
+    let mut assignments = vec![0; parts.len()];
+    for _ in 0..KMEANS_ITERATIONS {
+        let mut changed = false;
+        for (part, assignment) in parts.iter().zip(assignments.iter_mut()) {
+            let closest = closest_centroid(part, &centroids, size);
+            changed |= closest != *assignment;
+            *assignment = closest;
+        }
+
+        let mut sums = vec![0.0; centroids.len()];
+        let mut counts = vec![0usize; centroids_count];
+        for (part, &assignment) in parts.iter().zip(&assignments) {
+            counts[assignment] += 1;
+            let sum = &mut sums[assignment * size..(assignment + 1) * size];
+            for (sum, value) in sum.iter_mut().zip(part.iter()) {
+                *sum += value;
+            }
+        }
+        for (centroid_id, &count) in counts.iter().enumerate() {
+            // Empty clusters keep their centroids
+            if count == 0 {
+                continue;
+            }
+            let range = centroid_id * size..(centroid_id + 1) * size;
+            for (centroid, sum) in centroids[range.clone()].iter_mut().zip(&sums[range]) {
+                *centroid = sum / count as VectorElementType;
+            }
+        }
+
+        if !changed {
+            break;
+        }
+    }
+    centroids
+}
+
+/// Scorer over the quantized vectors, approximates the scores of the original vectors
+pub struct QuantizedRawScorer<'a, TMetric: Metric> {
+    quantized: &'a ProductQuantizedVectors,
+    query_table: Vec<Vec<ScoreType>>,
+    /// Storage of the original vectors, used to check if points are deleted
+    storage: &'a dyn VectorStorage,
+    metric: PhantomData<TMetric>,
+}
+
+impl<'a, TMetric: Metric> QuantizedRawScorer<'a, TMetric> {
+    /// `query` is expected to be already preprocessed by the metric
+    pub fn new(
+        quantized: &'a ProductQuantizedVectors,
+        query: &[VectorElementType],
+        storage: &'a dyn VectorStorage,
+    ) -> Self {
+        Self {
+            quantized,
+            query_table: quantized.query_table::<TMetric>(query),
+            storage,
+            metric: PhantomData,
+        }
+    }
+}
+
+impl<TMetric: Metric> RawScorer for QuantizedRawScorer<'_, TMetric> {
+    fn score_points(&self, points: &[PointOffsetType], scores: &mut [ScoredPointOffset]) -> usize {
+        let mut size: usize = 0;
+        for point_id in points.iter().copied() {
+            if !self.check_point(point_id) {
+                continue;
+            }
+            scores[size] = ScoredPointOffset {
+                idx: point_id,
+                score: self.score_point(point_id),
+            };
+            size += 1;
+            if size == scores.len() {
+                return size;
+            }
+        }
+        size
+    }
+
+    fn check_point(&self, point: PointOffsetType) -> bool {
+        (point as usize) < self.quantized.len() && !self.storage.is_deleted(point)
+    }
+
+    fn score_point(&self, point: PointOffsetType) -> ScoreType {
+        self.quantized
+            .point_codes(point)
+            .iter()
+            .zip(&self.query_table)
+            .map(|(&code, similarities)| similarities[code as usize])
+            .sum()
+    }
+
+    fn score_internal(&self, point_a: PointOffsetType, point_b: PointOffsetType) -> ScoreType {
+        let codes_a = self.quantized.point_codes(point_a);
+        let codes_b = self.quantized.point_codes(point_b);
+        codes_a
+            .iter()
+            .zip(codes_b)
+            .enumerate()
+            .map(|(subvector, (&code_a, &code_b))| {
+                TMetric::similarity(
+                    self.quantized.centroid(subvector, code_a),
+                    self.quantized.centroid(subvector, code_b),
+                )
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+    use tempfile::Builder;
+
+    use super::*;
+    use crate::common::rocksdb_wrapper::{open_db, DB_VECTOR_CF};
+    use crate::types::Distance;
+    use crate::vector_storage::simple_vector_storage::open_simple_vector_storage;
+
+    #[test]
+    fn test_split_dimensions() {
+        assert_eq!(split_dimensions(8, 4), vec![(0, 2), (2, 4), (4, 6), (6, 8)]);
+        assert_eq!(split_dimensions(7, 3), vec![(0, 3), (3, 5), (5, 7)]);
+        assert_eq!(split_dimensions(2, 5), vec![(0, 1), (1, 2)]);
+    }
+
+    #[test]
+    fn test_codebook_of_few_vectors_is_exact() {
+        let mut rng = rand::thread_rng();
+        let vectors: Vec<Vec<VectorElementType>> = (0..10)
+            .map(|_| (0..4).map(|_| rng.gen()).collect())
+            .collect();
+        let parts: Vec<_> = vectors.iter().map(|vector| vector.as_slice()).collect();
+
+        // Every vector becomes its own centroid, if there are fewer vectors than centroids
+        let codebook = train_codebook(&parts, 4);
+        for vector in &vectors {
+            let centroid_id = closest_centroid(vector, &codebook, 4);
+            assert_eq!(&codebook[centroid_id * 4..(centroid_id + 1) * 4], vector);
+        }
+    }
+
+    #[test]
+    fn test_quantized_scores_of_few_vectors_are_exact() {
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+        let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 8, Distance::Dot, None).unwrap();
+        let mut borrowed_storage = storage.borrow_mut();
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..20 {
+            let vector: Vec<VectorElementType> = (0..8).map(|_| rng.gen()).collect();
+            borrowed_storage.put_vector(vector).unwrap();
+        }
+        borrowed_storage.delete(3).unwrap();
+
+        let config = ProductQuantizationConfig { num_subvectors: 4 };
+        let quantization_path = dir.path().join("vector_storage");
+        borrowed_storage
+            .quantize(&quantization_path, &config)
+            .unwrap();
+        borrowed_storage
+            .load_quantization(&quantization_path)
+            .unwrap();
+
+        let query: Vec<VectorElementType> = (0..8).map(|_| rng.gen()).collect();
+        let raw_scorer = borrowed_storage.raw_scorer(query.clone());
+        let quantized_scorer = borrowed_storage.quantized_raw_scorer(&query).unwrap();
+
+        assert!(!quantized_scorer.check_point(3));
+        assert!(!quantized_scorer.check_point(20));
+        for point in borrowed_storage.iter_ids() {
+            let expected = raw_scorer.score_point(point);
+            assert!((quantized_scorer.score_point(point) - expected).abs() < 1e-5);
+        }
+
+        // Codes are stale after the vectors are changed
+        borrowed_storage.put_vector(vec![1.0; 8]).unwrap();
+        assert!(borrowed_storage.quantized_raw_scorer(&query).is_none());
+        borrowed_storage
+            .load_quantization(&quantization_path)
+            .unwrap();
+        assert!(borrowed_storage.quantized_raw_scorer(&query).is_none());
+    }
+}
@@ -0,0 +1,175 @@
+use crate::data_types::discovery::{discovery_score, rank_by_pairs, DiscoveryQuery};
+use crate::data_types::vectors::QueryVector;
+use crate::spaces::tools::peek_top_largest_iterable;
+use crate::types::{PointOffsetType, ScoreType};
+use crate::vector_storage::{RawScorer, ScoredPointOffset, VectorStorageSS};
+
+/// Scores stored vectors by the [`DiscoveryQuery`].
+/// Each example is compared with the stored vectors by a regular raw scorer of the storage,
+/// so the similarity is the same as in the nearest search.
+pub struct DiscoveryRawScorer<'a> {
+    target: Box<dyn RawScorer + 'a>,
+    pairs: Vec<(Box<dyn RawScorer + 'a>, Box<dyn RawScorer + 'a>)>,
+}
+
+impl<'a> DiscoveryRawScorer<'a> {
+    pub fn new(vector_storage: &'a VectorStorageSS, query: &DiscoveryQuery) -> Self {
+        DiscoveryRawScorer {
+            target: vector_storage.raw_scorer(query.target.clone()),
+            pairs: query
+                .pairs
+                .iter()
+                .map(|pair| {
+                    (
+                        vector_storage.raw_scorer(pair.positive.clone()),
+                        vector_storage.raw_scorer(pair.negative.clone()),
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+impl RawScorer for DiscoveryRawScorer<'_> {
+    fn score_points(&self, points: &[PointOffsetType], scores: &mut [ScoredPointOffset]) -> usize {
+        let mut size: usize = 0;
+        for point_id in points.iter().copied() {
+            if !self.check_point(point_id) {
+                continue;
+            }
+            scores[size] = ScoredPointOffset {
+                idx: point_id,
+                score: self.score_point(point_id),
+            };
+
+            size += 1;
+            if size == scores.len() {
+                return size;
+            }
+        }
+        size
+    }
+
+    fn check_point(&self, point: PointOffsetType) -> bool {
+        self.target.check_point(point)
+    }
+
+    fn score_point(&self, point: PointOffsetType) -> ScoreType {
+        let rank = rank_by_pairs(self.pairs.iter().map(|(positive, negative)| {
+            (positive.score_point(point), negative.score_point(point))
+        }));
+        discovery_score(rank, self.target.score_point(point))
+    }
+
+    /// Similarity of the stored vectors does not depend on the query
+    fn score_internal(&self, point_a: PointOffsetType, point_b: PointOffsetType) -> ScoreType {
+        self.target.score_internal(point_a, point_b)
+    }
+}
+
+/// Generate a `RawScorer`, which scores stored vectors by the `query`
+pub fn query_raw_scorer<'a>(
+    vector_storage: &'a VectorStorageSS,
+    query: QueryVector,
+) -> Box<dyn RawScorer + 'a> {
+    match query {
+        QueryVector::Nearest(vector) => vector_storage.raw_scorer(vector.to_vec()),
+        QueryVector::Discovery(query) => Box::new(DiscoveryRawScorer::new(vector_storage, query)),
+    }
+}
+
+/// Exact search of the `top` points by the `query` among the given `points`
+pub fn score_query_points(
+    vector_storage: &VectorStorageSS,
+    query: QueryVector,
+    points: &mut dyn Iterator<Item = PointOffsetType>,
+    top: usize,
+) -> Vec<ScoredPointOffset> {
+    match query {
+        QueryVector::Nearest(vector) => vector_storage.score_points(vector, points, top),
+        QueryVector::Discovery(_) => {
+            let scorer = query_raw_scorer(vector_storage, query);
+            let scores = points
+                .filter(|point_id| scorer.check_point(*point_id))
+                .map(|point_id| ScoredPointOffset {
+                    idx: point_id,
+                    score: scorer.score_point(point_id),
+                });
+            peek_top_largest_iterable(scores, top)
+        }
+    }
+}
+
+/// Exact search of the `top` points by the `query` among all stored vectors
+pub fn score_query_all(
+    vector_storage: &VectorStorageSS,
+    query: QueryVector,
+    top: usize,
+) -> Vec<ScoredPointOffset> {
+    match query {
+        QueryVector::Nearest(vector) => vector_storage.score_all(vector, top),
+        QueryVector::Discovery(_) => {
+            score_query_points(vector_storage, query, &mut vector_storage.iter_ids(), top)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::Builder;
+
+    use super::*;
+    use crate::common::rocksdb_wrapper::{open_db, DB_VECTOR_CF};
+    use crate::data_types::discovery::ContextPair;
+    use crate::types::Distance;
+    use crate::vector_storage::simple_vector_storage::open_simple_vector_storage;
+
+    #[test]
+    fn test_score_discovery_query() {
+        let dir = Builder::new().prefix("db_dir").tempdir().unwrap();
+        let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+        let storage = open_simple_vector_storage(db, DB_VECTOR_CF, 2, Distance::Dot, None).unwrap();
+        let vectors = [
+            vec![1.0, 0.1],
+            vec![-1.0, 1.0],
+            vec![0.0, -1.0],
+            vec![1.0, 0.5],
+        ];
+        {
+            let mut borrowed_storage = storage.borrow_mut();
+            for vector in vectors.iter() {
+                borrowed_storage.put_vector(vector.clone()).unwrap();
+            }
+            borrowed_storage.delete(3).unwrap();
+        }
+
+        let query = DiscoveryQuery {
+            target: vec![1.0, 0.0],
+            pairs: vec![
+                ContextPair {
+                    positive: vec![0.0, 1.0],
+                    negative: vec![0.0, -1.0],
+                },
+                ContextPair {
+                    positive: vec![-1.0, 0.0],
+                    negative: vec![1.0, 0.0],
+                },
+            ],
+        };
+
+        let borrowed_storage = storage.borrow();
+        let scorer = query_raw_scorer(&*borrowed_storage, QueryVector::Discovery(&query));
+        for (idx, vector) in vectors.iter().enumerate().take(3) {
+            assert_eq!(
+                scorer.score_point(idx as PointOffsetType),
+                query.score(vector, Distance::Dot)
+            );
+        }
+        assert!(!scorer.check_point(3));
+
+        // Deleted point is not found
+        let found = score_query_all(&*borrowed_storage, QueryVector::Discovery(&query), 3);
+        let ids: Vec<_> = found.iter().map(|point| point.idx).collect();
+        assert_eq!(ids, vec![1, 0, 2]);
+    }
+}
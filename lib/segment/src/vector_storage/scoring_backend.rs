@@ -0,0 +1,141 @@
+use log::warn;
+
+use crate::data_types::vectors::VectorElementType;
+use crate::entry::entry_point::OperationResult;
+use crate::spaces::metric::Metric;
+use crate::spaces::simple::{CosineMetric, DotProductMetric, EuclidMetric};
+use crate::types::{Distance, PointOffsetType, ScoreType};
+use crate::vector_storage::ScoredPointOffset;
+
+/// Minimal number of vectors in a single request, for which the accelerated backend is used.
+/// Smaller batches are scored faster on CPU because of the data transfer overhead
+pub const MIN_ACCELERATED_BATCH: usize = 4096;
+
+/// Backend for bulk distance computation between a single query and many stored vectors
+pub trait ScoringBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Compute similarity between `query` and each vector in `vectors`.
+    /// `vectors` are stored contiguously, `query.len()` elements each.
+    /// Both query and vectors are expected to be already preprocessed by the metric.
+    fn score_bulk(
+        &self,
+        distance: Distance,
+        query: &[VectorElementType],
+        vectors: &[VectorElementType],
+    ) -> OperationResult<Vec<ScoreType>>;
+}
+
+/// Default backend, uses the same SIMD-optimized metrics as the regular search
+pub struct CpuScoringBackend;
+
+impl ScoringBackend for CpuScoringBackend {
+    fn name(&self) -> &'static str {
+        "cpu"
+    }
+
+    fn score_bulk(
+        &self,
+        distance: Distance,
+        query: &[VectorElementType],
+        vectors: &[VectorElementType],
+    ) -> OperationResult<Vec<ScoreType>> {
+        Ok(cpu_scores(distance, query, vectors))
+    }
+}
+
+fn cpu_scores(
+    distance: Distance,
+    query: &[VectorElementType],
+    vectors: &[VectorElementType],
+) -> Vec<ScoreType> {
+    let similarity = match distance {
+        Distance::Cosine => CosineMetric::similarity,
+        Distance::Euclid => EuclidMetric::similarity,
+        Distance::Dot => DotProductMetric::similarity,
+    };
+    vectors
+        .chunks_exact(query.len())
+        .map(|vector| similarity(query, vector))
+        .collect()
+}
+
+/// Backend, which runs on an accelerator device.
+/// `None` if no accelerated backend is compiled in or no device is available
+pub fn accelerated_backend() -> Option<&'static dyn ScoringBackend> {
+    #[cfg(feature = "gpu")]
+    {
+        super::gpu_scoring_backend::GpuScoringBackend::global()
+            .map(|backend| backend as &dyn ScoringBackend)
+    }
+    #[cfg(not(feature = "gpu"))]
+    {
+        None
+    }
+}
+
+/// Score points with the accelerated backend, if it is available and the batch is large enough.
+/// `expected_count` is the estimated number of points, used to decide whether to offload the batch.
+/// Returns `None` without consuming `points`, if the batch should be scored in a regular way.
+pub fn score_accelerated<'a>(
+    distance: Distance,
+    query: &[VectorElementType],
+    points: &mut dyn Iterator<Item = PointOffsetType>,
+    expected_count: usize,
+    get_vector: impl Fn(PointOffsetType) -> &'a [VectorElementType],
+) -> Option<Vec<ScoredPointOffset>> {
+    if expected_count < MIN_ACCELERATED_BATCH {
+        return None;
+    }
+    let backend = accelerated_backend()?;
+
+    let ids: Vec<_> = points.collect();
+    let mut vectors = Vec::with_capacity(ids.len() * query.len());
+    for &id in &ids {
+        vectors.extend_from_slice(get_vector(id));
+    }
+
+    let scores = backend
+        .score_bulk(distance, query, &vectors)
+        .unwrap_or_else(|err| {
+            warn!(
+                "Scoring with {} backend failed, using cpu: {err}",
+                backend.name()
+            );
+            cpu_scores(distance, query, &vectors)
+        });
+
+    Some(
+        ids.into_iter()
+            .zip(scores)
+            .map(|(idx, score)| ScoredPointOffset { idx, score })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cpu_backend_matches_metric() {
+        let query = vec![1.0, 2.0, 3.0];
+        let vectors = vec![1.0, 0.0, 0.0, 0.0, 1.0, 1.0];
+
+        let scores = CpuScoringBackend
+            .score_bulk(Distance::Dot, &query, &vectors)
+            .unwrap();
+        assert_eq!(scores, vec![1.0, 5.0]);
+
+        let scores = CpuScoringBackend
+            .score_bulk(Distance::Euclid, &query, &vectors)
+            .unwrap();
+        assert_eq!(
+            scores,
+            vec![
+                EuclidMetric::similarity(&query, &vectors[..3]),
+                EuclidMetric::similarity(&query, &vectors[3..]),
+            ]
+        );
+    }
+}
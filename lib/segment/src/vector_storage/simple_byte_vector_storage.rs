@@ -0,0 +1,389 @@
+use std::marker::PhantomData;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+
+use atomic_refcell::AtomicRefCell;
+use bitvec::prelude::BitVec;
+use log::debug;
+use parking_lot::RwLock;
+use rocksdb::DB;
+use serde::{Deserialize, Serialize};
+
+use super::chunked_vectors::ChunkedVectors;
+use super::vector_storage_base::VectorStorage;
+use crate::common::encryption::StorageCipher;
+use crate::common::rocksdb_wrapper::DatabaseColumnWrapper;
+use crate::common::Flusher;
+use crate::data_types::vectors::VectorElementType;
+use crate::entry::entry_point::{OperationError, OperationResult};
+use crate::spaces::metric::ByteMetric;
+use crate::spaces::simple::{CosineMetric, DotProductMetric, EuclidMetric};
+use crate::spaces::tools::peek_top_largest_iterable;
+use crate::types::{Distance, PointOffsetType, ProductQuantizationConfig, ScoreType};
+use crate::vector_storage::{RawScorer, ScoredPointOffset, VectorStorageSS};
+
+/// In-memory storage of vectors of bytes with on-update persistence using `store`.
+/// Vectors are accepted and returned as floats, stored elements are expected to be validated
+/// by the segment.
+pub struct SimpleByteVectorStorage<TMetric: ByteMetric> {
+    dim: usize,
+    metric: PhantomData<TMetric>,
+    vectors: ChunkedVectors<u8>,
+    deleted: BitVec,
+    deleted_count: usize,
+    db_wrapper: DatabaseColumnWrapper,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+struct StoredRecord {
+    pub deleted: bool,
+    pub vector: Vec<u8>,
+}
+
+/// Convert the vector into bytes. Elements are rounded and clamped into `[0, 255]`,
+/// so query vectors with arbitrary elements could be used
+pub fn vector_to_bytes(vector: &[VectorElementType]) -> Vec<u8> {
+    vector
+        .iter()
+        .map(|element| element.round().clamp(0.0, u8::MAX as VectorElementType) as u8)
+        .collect()
+}
+
+fn bytes_to_vector(bytes: &[u8]) -> Vec<VectorElementType> {
+    bytes
+        .iter()
+        .map(|element| *element as VectorElementType)
+        .collect()
+}
+
+pub struct ByteRawScorer<'a, TMetric: ByteMetric> {
+    pub query: Vec<u8>,
+    pub vectors: &'a ChunkedVectors<u8>,
+    pub deleted: &'a BitVec,
+    pub metric: PhantomData<TMetric>,
+}
+
+impl<TMetric> RawScorer for ByteRawScorer<'_, TMetric>
+where
+    TMetric: ByteMetric,
+{
+    fn score_points(&self, points: &[PointOffsetType], scores: &mut [ScoredPointOffset]) -> usize {
+        let mut size: usize = 0;
+        for point_id in points.iter().copied() {
+            if self.deleted[point_id as usize] {
+                continue;
+            }
+            scores[size] = ScoredPointOffset {
+                idx: point_id,
+                score: self.score_point(point_id),
+            };
+
+            size += 1;
+            if size == scores.len() {
+                return size;
+            }
+        }
+        size
+    }
+
+    fn check_point(&self, point: PointOffsetType) -> bool {
+        (point as usize) < self.vectors.len() && !self.deleted[point as usize]
+    }
+
+    fn score_point(&self, point: PointOffsetType) -> ScoreType {
+        TMetric::similarity_bytes(&self.query, self.vectors.get(point))
+    }
+
+    fn score_internal(&self, point_a: PointOffsetType, point_b: PointOffsetType) -> ScoreType {
+        TMetric::similarity_bytes(self.vectors.get(point_a), self.vectors.get(point_b))
+    }
+}
+
+/// Open storage of byte vectors, persisted vectors are encrypted with `cipher` if it is provided
+pub fn open_simple_byte_vector_storage(
+    database: Arc<RwLock<DB>>,
+    database_column_name: &str,
+    dim: usize,
+    distance: Distance,
+    cipher: Option<StorageCipher>,
+) -> OperationResult<Arc<AtomicRefCell<VectorStorageSS>>> {
+    let wrapper = DatabaseColumnWrapper::new_with_cipher(database, database_column_name, cipher);
+    let storage: Arc<AtomicRefCell<VectorStorageSS>> = match distance {
+        Distance::Cosine => Arc::new(AtomicRefCell::new(load::<CosineMetric>(wrapper, dim)?)),
+        Distance::Euclid => Arc::new(AtomicRefCell::new(load::<EuclidMetric>(wrapper, dim)?)),
+        Distance::Dot => Arc::new(AtomicRefCell::new(load::<DotProductMetric>(wrapper, dim)?)),
+    };
+    Ok(storage)
+}
+
+fn load<TMetric: ByteMetric>(
+    db_wrapper: DatabaseColumnWrapper,
+    dim: usize,
+) -> OperationResult<SimpleByteVectorStorage<TMetric>> {
+    let mut vectors = ChunkedVectors::new(dim);
+    let mut deleted = BitVec::new();
+    let mut deleted_count = 0;
+
+    for record in db_wrapper.lock_db().iter()? {
+        let (key, value) = record?;
+        let point_id: PointOffsetType = bincode::deserialize(&key)
+            .map_err(|_| OperationError::service_error("cannot deserialize point id from db"))?;
+        let stored_record: StoredRecord = bincode::deserialize(&value)
+            .map_err(|_| OperationError::service_error("cannot deserialize record from db"))?;
+        if stored_record.deleted {
+            deleted_count += 1;
+        }
+
+        if deleted.len() <= (point_id as usize) {
+            deleted.resize(point_id as usize + 1, false);
+        }
+
+        deleted.set(point_id as usize, stored_record.deleted);
+        vectors.insert(point_id, &stored_record.vector);
+    }
+
+    debug!("Segment byte vectors: {}", vectors.len());
+    debug!(
+        "Estimated segment size {} MB",
+        vectors.len() * dim / 1024 / 1024
+    );
+
+    Ok(SimpleByteVectorStorage {
+        dim,
+        metric: PhantomData,
+        vectors,
+        deleted,
+        deleted_count,
+        db_wrapper,
+    })
+}
+
+impl<TMetric> SimpleByteVectorStorage<TMetric>
+where
+    TMetric: ByteMetric,
+{
+    fn update_stored(&self, point_id: PointOffsetType) -> OperationResult<()> {
+        let record = StoredRecord {
+            deleted: self.deleted[point_id as usize],
+            vector: self.vectors.get(point_id).to_vec(),
+        };
+
+        self.db_wrapper.put(
+            &bincode::serialize(&point_id).unwrap(),
+            &bincode::serialize(&record).unwrap(),
+        )?;
+
+        Ok(())
+    }
+}
+
+impl<TMetric> VectorStorage for SimpleByteVectorStorage<TMetric>
+where
+    TMetric: ByteMetric,
+{
+    fn vector_dim(&self) -> usize {
+        self.dim
+    }
+
+    fn vector_count(&self) -> usize {
+        self.vectors.len() - self.deleted_count
+    }
+
+    fn deleted_count(&self) -> usize {
+        self.deleted_count
+    }
+
+    fn total_vector_count(&self) -> usize {
+        self.vectors.len()
+    }
+
+    fn get_vector(&self, key: PointOffsetType) -> Option<Vec<VectorElementType>> {
+        if self.deleted.get(key as usize).map(|x| *x).unwrap_or(true) {
+            return None;
+        }
+        Some(bytes_to_vector(self.vectors.get(key)))
+    }
+
+    fn put_vector(&mut self, vector: Vec<VectorElementType>) -> OperationResult<PointOffsetType> {
+        assert_eq!(self.dim, vector.len());
+        let new_id = self.vectors.push(&vector_to_bytes(&vector));
+        self.deleted.push(false);
+        self.update_stored(new_id)?;
+        Ok(new_id)
+    }
+
+    fn insert_vector(
+        &mut self,
+        key: PointOffsetType,
+        vector: Vec<VectorElementType>,
+    ) -> OperationResult<()> {
+        self.vectors.insert(key, &vector_to_bytes(&vector));
+        if self.deleted.len() <= (key as usize) {
+            self.deleted.resize(key as usize + 1, true);
+        }
+        self.deleted.set(key as usize, false);
+        self.update_stored(key)?;
+        Ok(())
+    }
+
+    fn next_id(&self) -> PointOffsetType {
+        self.vectors.len() as PointOffsetType
+    }
+
+    fn update_from(&mut self, other: &VectorStorageSS) -> OperationResult<Range<PointOffsetType>> {
+        let start_index = self.vectors.len() as PointOffsetType;
+        for point_id in other.iter_ids() {
+            let other_vector = other.get_vector(point_id).unwrap();
+            self.deleted.push(false);
+            let new_id = self.vectors.push(&vector_to_bytes(&other_vector));
+            self.update_stored(new_id)?;
+        }
+        let end_index = self.vectors.len() as PointOffsetType;
+        Ok(start_index..end_index)
+    }
+
+    fn delete(&mut self, key: PointOffsetType) -> OperationResult<()> {
+        if (key as usize) >= self.deleted.len() {
+            return Ok(());
+        }
+        if !self.deleted[key as usize] {
+            self.deleted_count += 1;
+        }
+        self.deleted.set(key as usize, true);
+        self.update_stored(key)?;
+        Ok(())
+    }
+
+    fn is_deleted(&self, key: PointOffsetType) -> bool {
+        self.deleted[key as usize]
+    }
+
+    fn iter_ids(&self) -> Box<dyn Iterator<Item = PointOffsetType> + '_> {
+        let iter = (0..self.vectors.len() as PointOffsetType)
+            .filter(move |id| !self.deleted[*id as usize]);
+        Box::new(iter)
+    }
+
+    fn flusher(&self) -> Flusher {
+        self.db_wrapper.flusher()
+    }
+
+    fn raw_scorer(&self, vector: Vec<VectorElementType>) -> Box<dyn RawScorer + '_> {
+        Box::new(ByteRawScorer::<TMetric> {
+            query: vector_to_bytes(&vector),
+            vectors: &self.vectors,
+            deleted: &self.deleted,
+            metric: PhantomData,
+        })
+    }
+
+    fn raw_scorer_internal(&self, point_id: PointOffsetType) -> Box<dyn RawScorer + '_> {
+        Box::new(ByteRawScorer::<TMetric> {
+            query: self.vectors.get(point_id).to_vec(),
+            vectors: &self.vectors,
+            deleted: &self.deleted,
+            metric: PhantomData,
+        })
+    }
+
+    fn score_points(
+        &self,
+        vector: &[VectorElementType],
+        points: &mut dyn Iterator<Item = PointOffsetType>,
+        top: usize,
+    ) -> Vec<ScoredPointOffset> {
+        let query = vector_to_bytes(vector);
+        let scores = points
+            .filter(|point_id| !self.deleted[*point_id as usize])
+            .map(|point_id| ScoredPointOffset {
+                idx: point_id,
+                score: TMetric::similarity_bytes(&query, self.vectors.get(point_id)),
+            });
+        peek_top_largest_iterable(scores, top)
+    }
+
+    fn score_all(&self, vector: &[VectorElementType], top: usize) -> Vec<ScoredPointOffset> {
+        self.score_points(vector, &mut self.iter_ids(), top)
+    }
+
+    fn score_internal(
+        &self,
+        point: PointOffsetType,
+        points: &mut dyn Iterator<Item = PointOffsetType>,
+        top: usize,
+    ) -> Vec<ScoredPointOffset> {
+        let vector = self.get_vector(point).unwrap();
+        self.score_points(&vector, points, top)
+    }
+
+    /// Byte vectors are already compact, so they are not quantized
+    fn quantize(
+        &mut self,
+        _path: &Path,
+        _config: &ProductQuantizationConfig,
+    ) -> OperationResult<()> {
+        Ok(())
+    }
+
+    fn load_quantization(&mut self, _path: &Path) -> OperationResult<()> {
+        Ok(())
+    }
+
+    fn quantized_raw_scorer(
+        &self,
+        _vector: &[VectorElementType],
+    ) -> Option<Box<dyn RawScorer + '_>> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::Builder;
+
+    use super::*;
+    use crate::common::rocksdb_wrapper::{open_db, DB_VECTOR_CF};
+
+    #[test]
+    fn test_byte_vector_storage() {
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
+        let storage =
+            open_simple_byte_vector_storage(db.clone(), DB_VECTOR_CF, 4, Distance::Dot, None)
+                .unwrap();
+        {
+            let mut borrowed_storage = storage.borrow_mut();
+            borrowed_storage
+                .put_vector(vec![1.0, 0.0, 1.0, 1.0])
+                .unwrap();
+            borrowed_storage
+                .put_vector(vec![255.0, 0.0, 255.0, 0.0])
+                .unwrap();
+            borrowed_storage
+                .put_vector(vec![2.0, 2.0, 2.0, 2.0])
+                .unwrap();
+            borrowed_storage.delete(2).unwrap();
+
+            // Query elements are rounded and clamped into bytes
+            let closest = borrowed_storage.score_points(&[300.0, 0.4, 0.6, 1.0], &mut (0..3), 3);
+            assert_eq!(closest.len(), 2);
+            assert_eq!(closest[0].idx, 1);
+            assert_eq!(closest[0].score, (255 * 255 + 255) as ScoreType);
+            assert_eq!(closest[1].score, 257.0);
+        }
+
+        // Vectors are restored from the database
+        drop(storage);
+        let storage =
+            open_simple_byte_vector_storage(db, DB_VECTOR_CF, 4, Distance::Dot, None).unwrap();
+        let borrowed_storage = storage.borrow();
+        assert_eq!(borrowed_storage.vector_count(), 2);
+        assert_eq!(borrowed_storage.deleted_count(), 1);
+        assert_eq!(
+            borrowed_storage.get_vector(1),
+            Some(vec![255.0, 0.0, 255.0, 0.0])
+        );
+        assert_eq!(borrowed_storage.get_vector(2), None);
+    }
+}
@@ -1,6 +1,7 @@
 use std::marker::PhantomData;
 use std::mem::size_of;
 use std::ops::Range;
+use std::path::Path;
 use std::sync::Arc;
 
 use atomic_refcell::AtomicRefCell;
@@ -12,6 +13,7 @@ use serde::{Deserialize, Serialize};
 
 use super::chunked_vectors::ChunkedVectors;
 use super::vector_storage_base::VectorStorage;
+use crate::common::encryption::StorageCipher;
 use crate::common::rocksdb_wrapper::DatabaseColumnWrapper;
 use crate::common::Flusher;
 use crate::data_types::vectors::VectorElementType;
@@ -19,7 +21,9 @@ use crate::entry::entry_point::{OperationError, OperationResult};
 use crate::spaces::metric::Metric;
 use crate::spaces::simple::{CosineMetric, DotProductMetric, EuclidMetric};
 use crate::spaces::tools::peek_top_largest_iterable;
-use crate::types::{Distance, PointOffsetType, ScoreType};
+use crate::types::{Distance, PointOffsetType, ProductQuantizationConfig, ScoreType};
+use crate::vector_storage::product_quantization::{ProductQuantizedVectors, QuantizedRawScorer};
+use crate::vector_storage::scoring_backend::score_accelerated;
 use crate::vector_storage::{RawScorer, ScoredPointOffset, VectorStorageSS};
 
 /// In-memory vector storage with on-update persistence using `store`
@@ -30,6 +34,9 @@ pub struct SimpleVectorStorage<TMetric: Metric> {
     deleted: BitVec,
     deleted_count: usize,
     db_wrapper: DatabaseColumnWrapper,
+    /// Codes of the vectors, if the storage is quantized.
+    /// Reset on any change of the vectors, as the codes become stale
+    quantized_vectors: Option<ProductQuantizedVectors>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -85,18 +92,21 @@ where
     }
 }
 
+/// Open vector storage, persisted vectors are encrypted with `cipher` if it is provided
 pub fn open_simple_vector_storage(
     database: Arc<RwLock<DB>>,
     database_column_name: &str,
     dim: usize,
     distance: Distance,
+    cipher: Option<StorageCipher>,
 ) -> OperationResult<Arc<AtomicRefCell<VectorStorageSS>>> {
     let mut vectors = ChunkedVectors::new(dim);
     let mut deleted = BitVec::new();
     let mut deleted_count = 0;
 
-    let db_wrapper = DatabaseColumnWrapper::new(database, database_column_name);
-    for (key, value) in db_wrapper.lock_db().iter()? {
+    let db_wrapper = DatabaseColumnWrapper::new_with_cipher(database, database_column_name, cipher);
+    for record in db_wrapper.lock_db().iter()? {
+        let (key, value) = record?;
         let point_id: PointOffsetType = bincode::deserialize(&key)
             .map_err(|_| OperationError::service_error("cannot deserialize point id from db"))?;
         let stored_record: StoredRecord = bincode::deserialize(&value)
@@ -129,6 +139,7 @@ pub fn open_simple_vector_storage(
             deleted,
             deleted_count,
             db_wrapper,
+            quantized_vectors: None,
         }))),
         Distance::Euclid => Ok(Arc::new(AtomicRefCell::new(SimpleVectorStorage::<
             EuclidMetric,
@@ -139,6 +150,7 @@ pub fn open_simple_vector_storage(
             deleted,
             deleted_count,
             db_wrapper,
+            quantized_vectors: None,
         }))),
         Distance::Dot => Ok(Arc::new(AtomicRefCell::new(SimpleVectorStorage::<
             DotProductMetric,
@@ -149,6 +161,7 @@ pub fn open_simple_vector_storage(
             deleted,
             deleted_count,
             db_wrapper,
+            quantized_vectors: None,
         }))),
     }
 }
@@ -205,6 +218,7 @@ where
         assert_eq!(self.dim, vector.len());
         let new_id = self.vectors.push(&vector);
         self.deleted.push(false);
+        self.quantized_vectors = None;
         self.update_stored(new_id)?;
         Ok(new_id)
     }
@@ -219,6 +233,7 @@ where
             self.deleted.resize(key as usize + 1, true);
         }
         self.deleted.set(key as usize, false);
+        self.quantized_vectors = None;
         self.update_stored(key)?;
         Ok(())
     }
@@ -229,6 +244,7 @@ where
 
     fn update_from(&mut self, other: &VectorStorageSS) -> OperationResult<Range<PointOffsetType>> {
         let start_index = self.vectors.len() as PointOffsetType;
+        self.quantized_vectors = None;
         for point_id in other.iter_ids() {
             let other_vector = other.get_vector(point_id).unwrap();
             // Do not perform preprocessing - vectors should be already processed
@@ -291,21 +307,40 @@ where
         top: usize,
     ) -> Vec<ScoredPointOffset> {
         let preprocessed_vector = TMetric::preprocess(vector).unwrap_or_else(|| vector.to_owned());
-        let scores = points
-            .filter(|point_id| !self.deleted[*point_id as usize])
-            .map(|point_id| {
-                let other_vector = self.vectors.get(point_id);
-                ScoredPointOffset {
-                    idx: point_id,
-                    score: TMetric::similarity(&preprocessed_vector, other_vector),
-                }
-            });
+        let mut points = points.filter(|point_id| !self.deleted[*point_id as usize]);
+        let expected_count = points.size_hint().1.unwrap_or(0);
+        if let Some(scores) = score_accelerated(
+            TMetric::distance(),
+            &preprocessed_vector,
+            &mut points,
+            expected_count,
+            |point_id| self.vectors.get(point_id),
+        ) {
+            return peek_top_largest_iterable(scores, top);
+        }
+        let scores = points.map(|point_id| {
+            let other_vector = self.vectors.get(point_id);
+            ScoredPointOffset {
+                idx: point_id,
+                score: TMetric::similarity(&preprocessed_vector, other_vector),
+            }
+        });
         peek_top_largest_iterable(scores, top)
     }
 
     fn score_all(&self, vector: &[VectorElementType], top: usize) -> Vec<ScoredPointOffset> {
         let preprocessed_vector = TMetric::preprocess(vector).unwrap_or_else(|| vector.to_owned());
 
+        if let Some(scores) = score_accelerated(
+            TMetric::distance(),
+            &preprocessed_vector,
+            &mut self.iter_ids(),
+            self.vectors.len() - self.deleted_count,
+            |point_id| self.vectors.get(point_id),
+        ) {
+            return peek_top_largest_iterable(scores, top);
+        }
+
         let scores = (0..self.vectors.len())
             .filter(|point_id| !self.deleted[*point_id])
             .map(|point_id| {
@@ -328,6 +363,33 @@ where
         let vector = self.get_vector(point).unwrap();
         self.score_points(&vector, points, top)
     }
+
+    fn quantize(&mut self, path: &Path, config: &ProductQuantizationConfig) -> OperationResult<()> {
+        let quantized_vectors = ProductQuantizedVectors::build(&*self, config);
+        quantized_vectors.save(path)?;
+        self.quantized_vectors = Some(quantized_vectors);
+        Ok(())
+    }
+
+    fn load_quantization(&mut self, path: &Path) -> OperationResult<()> {
+        // Codes, saved before the vectors were changed, are not usable
+        self.quantized_vectors = ProductQuantizedVectors::load(path)?
+            .filter(|quantized| quantized.len() == self.vectors.len());
+        Ok(())
+    }
+
+    fn quantized_raw_scorer(
+        &self,
+        vector: &[VectorElementType],
+    ) -> Option<Box<dyn RawScorer + '_>> {
+        let quantized_vectors = self.quantized_vectors.as_ref()?;
+        let query = TMetric::preprocess(vector).unwrap_or_else(|| vector.to_owned());
+        Some(Box::new(QuantizedRawScorer::<TMetric>::new(
+            quantized_vectors,
+            &query,
+            self,
+        )))
+    }
 }
 
 #[cfg(test)]
@@ -343,7 +405,7 @@ mod tests {
         let db = open_db(dir.path(), &[DB_VECTOR_CF]).unwrap();
         let distance = Distance::Dot;
         let dim = 4;
-        let storage = open_simple_vector_storage(db, DB_VECTOR_CF, dim, distance).unwrap();
+        let storage = open_simple_vector_storage(db, DB_VECTOR_CF, dim, distance, None).unwrap();
         let mut borrowed_storage = storage.borrow_mut();
 
         let vec0 = vec![1.0, 0.0, 1.0, 1.0];
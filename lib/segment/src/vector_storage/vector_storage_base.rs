@@ -1,5 +1,6 @@
 use std::cmp::Ordering;
 use std::ops::Range;
+use std::path::Path;
 
 use ordered_float::OrderedFloat;
 use rand::Rng;
@@ -7,7 +8,7 @@ use rand::Rng;
 use crate::common::Flusher;
 use crate::data_types::vectors::VectorElementType;
 use crate::entry::entry_point::OperationResult;
-use crate::types::{PointOffsetType, ScoreType};
+use crate::types::{PointOffsetType, ProductQuantizationConfig, ScoreType};
 
 #[derive(Copy, Clone, PartialEq, Debug, Default)]
 pub struct ScoredPointOffset {
@@ -69,6 +70,12 @@ pub trait VectorStorage {
     fn is_deleted(&self, key: PointOffsetType) -> bool;
     fn iter_ids(&self) -> Box<dyn Iterator<Item = PointOffsetType> + '_>;
     fn flusher(&self) -> Flusher;
+    /// Read the vectors into the page cache, if they are stored on disk
+    fn populate(&self) {}
+    /// Fraction of the vectors, which are read without waiting for the disk
+    fn resident_fraction(&self) -> f64 {
+        1.0
+    }
 
     /// Generate a `RawScorer` object which contains all required context for searching similar vector
     fn raw_scorer(&self, vector: Vec<VectorElementType>) -> Box<dyn RawScorer + '_>;
@@ -89,6 +96,15 @@ pub trait VectorStorage {
         top: usize,
     ) -> Vec<ScoredPointOffset>;
 
+    /// Encode stored vectors with product quantization and save the codes into `path`
+    fn quantize(&mut self, path: &Path, config: &ProductQuantizationConfig) -> OperationResult<()>;
+    /// Load vectors, encoded by `quantize`, from `path` if they are present
+    fn load_quantization(&mut self, path: &Path) -> OperationResult<()>;
+    /// Generate a `RawScorer` over the quantized vectors, if the storage is quantized.
+    /// Scores are approximate and should be refined with the original vectors
+    fn quantized_raw_scorer(&self, vector: &[VectorElementType])
+        -> Option<Box<dyn RawScorer + '_>>;
+
     /// Iterator over `n` random ids which are not deleted
     fn sample_ids(&self) -> Box<dyn Iterator<Item = PointOffsetType> + '_> {
         let total = self.total_vector_count() as PointOffsetType;
@@ -0,0 +1,157 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::atomic::AtomicBool;
+
+    use itertools::Itertools;
+    use rand::thread_rng;
+    use segment::data_types::discovery::{ContextPair, DiscoveryQuery};
+    use segment::data_types::vectors::{only_default_vector, QueryVector, DEFAULT_VECTOR_NAME};
+    use segment::entry::entry_point::SegmentEntry;
+    use segment::fixtures::payload_fixtures::random_vector;
+    use segment::index::hnsw_index::hnsw::HNSWIndex;
+    use segment::index::VectorIndex;
+    use segment::segment_constructor::build_segment;
+    use segment::types::{
+        Distance, HnswConfig, Indexes, PointOffsetType, SearchParams, SegmentConfig, SeqNumberType,
+        StorageType, VectorDataConfig, VectorStorageDatatype, WithPayload, WithVector,
+    };
+    use tempfile::Builder;
+
+    #[test]
+    fn test_discovery_search() {
+        let stopped = AtomicBool::new(false);
+
+        let dim = 8;
+        let num_vectors: u64 = 1_000;
+        let distance = Distance::Dot;
+        let top = 5;
+
+        let mut rnd = thread_rng();
+
+        let dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+        let hnsw_dir = Builder::new().prefix("hnsw_dir").tempdir().unwrap();
+
+        let config = SegmentConfig {
+            vector_data: HashMap::from([(
+                DEFAULT_VECTOR_NAME.to_owned(),
+                VectorDataConfig {
+                    size: dim,
+                    distance,
+                    hnsw_config: None,
+                    quantization_config: None,
+                    datatype: VectorStorageDatatype::Float32,
+                    multivector_config: None,
+                },
+            )]),
+            index: Indexes::Plain {},
+            storage_type: StorageType::InMemory,
+            payload_storage_type: Default::default(),
+        };
+
+        let mut segment = build_segment(dir.path(), &config).unwrap();
+        let mut vectors = vec![];
+        for n in 0..num_vectors {
+            let vector = random_vector(&mut rnd, dim);
+            segment
+                .upsert_vector(n as SeqNumberType, n.into(), &only_default_vector(&vector))
+                .unwrap();
+            vectors.push(vector);
+        }
+
+        let mut hnsw_index = HNSWIndex::open(
+            hnsw_dir.path(),
+            segment.vector_data[DEFAULT_VECTOR_NAME]
+                .vector_storage
+                .clone(),
+            segment.payload_index.clone(),
+            HnswConfig {
+                m: 16,
+                ef_construct: 64,
+                full_scan_threshold: 1, // KB
+                max_indexing_threads: 2,
+            },
+        )
+        .unwrap();
+        hnsw_index.build_index(&stopped).unwrap();
+
+        let attempts = 10;
+        for _ in 0..attempts {
+            let query = DiscoveryQuery {
+                target: random_vector(&mut rnd, dim),
+                pairs: (0..3)
+                    .map(|_| ContextPair {
+                        positive: random_vector(&mut rnd, dim),
+                        negative: random_vector(&mut rnd, dim),
+                    })
+                    .collect(),
+            };
+
+            let expected: Vec<PointOffsetType> = vectors
+                .iter()
+                .enumerate()
+                .sorted_by(|(_, a), (_, b)| {
+                    query
+                        .score(b, distance)
+                        .total_cmp(&query.score(a, distance))
+                })
+                .take(top)
+                .map(|(idx, _)| idx as PointOffsetType)
+                .collect();
+
+            // Plain index scores every point by the query
+            let found = segment
+                .discover(
+                    DEFAULT_VECTOR_NAME,
+                    &query,
+                    &WithPayload::default(),
+                    &WithVector::Bool(false),
+                    None,
+                    top,
+                    None,
+                )
+                .unwrap();
+            let found_ids: Vec<_> = found.iter().map(|point| point.id).collect();
+            let expected_ids: Vec<_> = expected.iter().map(|idx| (*idx as u64).into()).collect();
+            assert_eq!(found_ids, expected_ids);
+            for (point, idx) in found.iter().zip(&expected) {
+                assert_eq!(point.score, query.score(&vectors[*idx as usize], distance));
+            }
+
+            let queries = [QueryVector::Discovery(&query)];
+            let exact_result = hnsw_index.search_queries(
+                &queries,
+                None,
+                top,
+                Some(&SearchParams {
+                    hnsw_ef: None,
+                    exact: true,
+                    max_time_ms: None,
+                    explain: false,
+                }),
+                None,
+                None,
+            );
+            let exact_ids: Vec<_> = exact_result.results[0].iter().map(|p| p.idx).collect();
+            assert_eq!(exact_ids, expected);
+
+            // Beam, which is as large as the graph, visits every point, so the graph search
+            // is guided by the discovery score to the same points
+            let graph_result = hnsw_index.search_queries(
+                &queries,
+                None,
+                top,
+                Some(&SearchParams {
+                    hnsw_ef: Some(num_vectors as usize),
+                    exact: false,
+                    max_time_ms: None,
+                    explain: false,
+                }),
+                None,
+                None,
+            );
+            let graph_ids: Vec<_> = graph_result.results[0].iter().map(|p| p.idx).collect();
+            assert_eq!(graph_ids, expected);
+        }
+    }
+}
@@ -0,0 +1,96 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::fs;
+    use std::path::Path;
+
+    use segment::common::encryption::{StorageCipher, STORAGE_KEY_SIZE};
+    use segment::data_types::vectors::{only_default_vector, DEFAULT_VECTOR_NAME};
+    use segment::entry::entry_point::SegmentEntry;
+    use segment::segment_constructor::{build_segment_with_cipher, load_segment_with_cipher};
+    use segment::types::{
+        Distance, Indexes, PayloadStorageType, SegmentConfig, StorageType, VectorDataConfig,
+        VectorStorageDatatype,
+    };
+    use serde_json::json;
+    use tempfile::Builder;
+
+    const SECRET: &str = "very-secret-payload-value";
+
+    fn config(storage_type: StorageType) -> SegmentConfig {
+        SegmentConfig {
+            vector_data: HashMap::from([(
+                DEFAULT_VECTOR_NAME.to_owned(),
+                VectorDataConfig {
+                    size: 4,
+                    distance: Distance::Dot,
+                    hnsw_config: None,
+                    quantization_config: None,
+                    datatype: VectorStorageDatatype::Float32,
+                    multivector_config: None,
+                },
+            )]),
+            index: Indexes::Plain {},
+            storage_type,
+            payload_storage_type: PayloadStorageType::InMemory,
+        }
+    }
+
+    fn contains_bytes(dir: &Path, needle: &[u8]) -> bool {
+        fs::read_dir(dir).unwrap().any(|entry| {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                contains_bytes(&path, needle)
+            } else {
+                let content = fs::read(&path).unwrap();
+                content.windows(needle.len()).any(|window| window == needle)
+            }
+        })
+    }
+
+    #[test]
+    fn test_encrypted_segment() {
+        let dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+        let cipher = StorageCipher::new(&[7; STORAGE_KEY_SIZE]).unwrap();
+        let payload = json!({ "secret": SECRET }).into();
+
+        let segment_path = {
+            let mut segment = build_segment_with_cipher(
+                dir.path(),
+                &config(StorageType::InMemory),
+                Some(cipher.clone()),
+            )
+            .unwrap();
+            segment
+                .upsert_vector(1, 1.into(), &only_default_vector(&[1.0, 0.0, 1.0, 1.0]))
+                .unwrap();
+            segment.set_full_payload(2, 1.into(), &payload).unwrap();
+            segment.flush(true).unwrap();
+            segment.current_path.clone()
+        };
+
+        assert!(!contains_bytes(&segment_path, SECRET.as_bytes()));
+
+        let segment = load_segment_with_cipher(&segment_path, Some(cipher))
+            .unwrap()
+            .unwrap();
+        assert_eq!(segment.payload(1.into()).unwrap(), payload);
+        assert_eq!(
+            segment.vector(DEFAULT_VECTOR_NAME, 1.into()).unwrap(),
+            vec![1.0, 0.0, 1.0, 1.0]
+        );
+        drop(segment);
+
+        let wrong_cipher = StorageCipher::new(&[8; STORAGE_KEY_SIZE]).unwrap();
+        assert!(load_segment_with_cipher(&segment_path, Some(wrong_cipher)).is_err());
+    }
+
+    #[test]
+    fn test_encrypted_mmap_segment_is_rejected() {
+        let dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+        let cipher = StorageCipher::new(&[7; STORAGE_KEY_SIZE]).unwrap();
+
+        let res = build_segment_with_cipher(dir.path(), &config(StorageType::Mmap), Some(cipher));
+        assert!(res.is_err());
+    }
+}
@@ -14,7 +14,7 @@ mod tests {
     use segment::types::{
         Condition, Distance, FieldCondition, Filter, HnswConfig, Indexes, Payload,
         PayloadSchemaType, PointOffsetType, Range, SearchParams, SegmentConfig, SeqNumberType,
-        StorageType, VectorDataConfig,
+        StorageType, VectorDataConfig, VectorStorageDatatype,
     };
     use serde_json::json;
     use tempfile::Builder;
@@ -44,6 +44,10 @@ mod tests {
                 VectorDataConfig {
                     size: dim,
                     distance,
+                    hnsw_config: None,
+                    quantization_config: None,
+                    datatype: VectorStorageDatatype::Float32,
+                    multivector_config: None,
                 },
             )]),
             index: Indexes::Plain {},
@@ -143,12 +147,16 @@ mod tests {
                 Some(&SearchParams {
                     hnsw_ef: Some(ef),
                     exact: true,
+                    max_time_ms: None,
+                    explain: false,
                 }),
+                None,
+                None,
             );
             let plain_result = segment.vector_data[DEFAULT_VECTOR_NAME]
                 .vector_index
                 .borrow()
-                .search(&[&query], None, top, None);
+                .search(&[&query], None, top, None, None, None);
 
             assert!(
                 index_result == plain_result,
@@ -177,12 +185,16 @@ mod tests {
                 Some(&SearchParams {
                     hnsw_ef: Some(ef),
                     exact: true,
+                    max_time_ms: None,
+                    explain: false,
                 }),
+                None,
+                None,
             );
             let plain_result = segment.vector_data[DEFAULT_VECTOR_NAME]
                 .vector_index
                 .borrow()
-                .search(&[&query], filter_query, top, None);
+                .search(&[&query], filter_query, top, None, None, None);
 
             assert!(
                 index_result == plain_result,
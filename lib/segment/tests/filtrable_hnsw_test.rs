@@ -2,6 +2,7 @@
 mod tests {
     use std::collections::HashMap;
     use std::sync::atomic::AtomicBool;
+    use std::time::{Duration, Instant};
 
     use itertools::Itertools;
     use rand::{thread_rng, Rng};
@@ -9,12 +10,13 @@ mod tests {
     use segment::entry::entry_point::SegmentEntry;
     use segment::fixtures::payload_fixtures::{random_int_payload, random_vector};
     use segment::index::hnsw_index::hnsw::HNSWIndex;
+    use segment::index::query_planner::SearchStrategy;
     use segment::index::{PayloadIndex, VectorIndex};
     use segment::segment_constructor::build_segment;
     use segment::types::{
         Condition, Distance, FieldCondition, Filter, HnswConfig, Indexes, Payload,
         PayloadSchemaType, PointOffsetType, Range, SearchParams, SegmentConfig, SeqNumberType,
-        StorageType, VectorDataConfig,
+        StorageType, VectorDataConfig, VectorStorageDatatype,
     };
     use serde_json::json;
     use tempfile::Builder;
@@ -44,6 +46,10 @@ mod tests {
                 VectorDataConfig {
                     size: dim,
                     distance,
+                    hnsw_config: None,
+                    quantization_config: None,
+                    datatype: VectorStorageDatatype::Float32,
+                    multivector_config: None,
                 },
             )]),
             index: Indexes::Plain {},
@@ -154,20 +160,25 @@ mod tests {
             let filter_query = Some(&filter);
             // let filter_query = None;
 
-            let index_result = hnsw_index.search_with_graph(
+            let (index_result, _) = hnsw_index.search_with_graph(
                 &query,
                 filter_query,
                 top,
                 Some(&SearchParams {
                     hnsw_ef: Some(ef),
                     exact: false,
+                    max_time_ms: None,
+                    explain: false,
                 }),
+                None,
+                None,
             );
 
             let plain_result = segment.vector_data[DEFAULT_VECTOR_NAME]
                 .vector_index
                 .borrow()
-                .search(&[&query], filter_query, top, None);
+                .search(&[&query], filter_query, top, None, None, None)
+                .results;
 
             if plain_result.get(0).unwrap() == &index_result {
                 hits += 1;
@@ -175,5 +186,39 @@ mod tests {
         }
         assert!(attempts - hits < 5, "hits: {} of {}", hits, attempts); // Not more than 5% failures
         eprintln!("hits = {:#?} out of {}", hits, attempts);
+
+        // Search with expired deadline returns what it has found so far
+        let query = random_vector(&mut rnd, dim);
+        let expired_result =
+            hnsw_index.search(&[&query], None, top, None, None, Some(Instant::now()));
+        assert!(expired_result.truncated);
+        assert!(!expired_result.results[0].is_empty());
+
+        let result = hnsw_index.search(
+            &[&query],
+            None,
+            top,
+            None,
+            None,
+            Some(Instant::now() + Duration::from_secs(60)),
+        );
+        assert!(!result.truncated);
+
+        // Filter, which matches almost all the points, is applied after the search in the graph
+        let filter = Filter::new_must(Condition::Field(FieldCondition::new_range(
+            int_key.to_owned(),
+            Range {
+                lt: None,
+                gt: None,
+                gte: Some(-1_000_000.0),
+                lte: None,
+            },
+        )));
+        let result = hnsw_index.search(&[&query], Some(&filter), top, None, None, None);
+        assert_eq!(result.strategy, SearchStrategy::HnswPostFilter);
+        assert_eq!(result.results[0].len(), top);
+        assert!(result.hnsw_ef.is_some());
+        assert!(result.filter_cardinality.is_some());
+        assert!(result.scored_vectors >= top);
     }
 }
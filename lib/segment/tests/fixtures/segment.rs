@@ -7,7 +7,7 @@ use segment::entry::entry_point::SegmentEntry;
 use segment::segment::Segment;
 use segment::segment_constructor::build_segment;
 use segment::segment_constructor::simple_segment_constructor::build_simple_segment;
-use segment::types::{Distance, Indexes, SegmentConfig, VectorDataConfig};
+use segment::types::{Distance, Indexes, SegmentConfig, VectorDataConfig, VectorStorageDatatype};
 use serde_json::json;
 
 pub fn empty_segment(path: &Path) -> Segment {
@@ -117,6 +117,10 @@ pub fn build_segment_3(path: &Path) -> Segment {
                     VectorDataConfig {
                         size: 4,
                         distance: Distance::Dot,
+                        hnsw_config: None,
+                        quantization_config: None,
+                        datatype: VectorStorageDatatype::Float32,
+                        multivector_config: None,
                     },
                 ),
                 (
@@ -124,6 +128,10 @@ pub fn build_segment_3(path: &Path) -> Segment {
                     VectorDataConfig {
                         size: 1,
                         distance: Distance::Dot,
+                        hnsw_config: None,
+                        quantization_config: None,
+                        datatype: VectorStorageDatatype::Float32,
+                        multivector_config: None,
                     },
                 ),
                 (
@@ -131,6 +139,10 @@ pub fn build_segment_3(path: &Path) -> Segment {
                     VectorDataConfig {
                         size: 4,
                         distance: Distance::Euclid,
+                        hnsw_config: None,
+                        quantization_config: None,
+                        datatype: VectorStorageDatatype::Float32,
+                        multivector_config: None,
                     },
                 ),
             ]),
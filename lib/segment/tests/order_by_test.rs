@@ -0,0 +1,148 @@
+mod fixtures;
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use segment::data_types::vectors::only_default_vector;
+    use segment::entry::entry_point::{OperationError, SegmentEntry};
+    use segment::types::{
+        Direction, FloatPayloadType, OrderBy, PayloadFieldSchema, PayloadSchemaType, PointIdType,
+    };
+    use serde_json::json;
+    use tempfile::Builder;
+
+    use crate::fixtures::segment::empty_segment;
+
+    fn order_by(direction: Direction, start_from: Option<FloatPayloadType>) -> OrderBy {
+        OrderBy {
+            key: "price".to_string(),
+            direction: Some(direction),
+            start_from,
+        }
+    }
+
+    /// Read all points page by page, using the first point of the next page as a cursor
+    fn read_all_pages(
+        segment: &dyn SegmentEntry,
+        direction: Direction,
+        page_size: usize,
+    ) -> Vec<(FloatPayloadType, PointIdType)> {
+        let mut result = vec![];
+        let mut cursor: Option<(FloatPayloadType, PointIdType)> = None;
+        loop {
+            let order_by = order_by(direction, cursor.map(|(value, _)| value));
+            let mut page = segment
+                .read_ordered_filtered(
+                    cursor.map(|(_, id)| id),
+                    Some(page_size + 1),
+                    None,
+                    &order_by,
+                )
+                .unwrap();
+            page.sort_by(|a, b| order_by.compare(*a, *b));
+            cursor = page.get(page_size).copied();
+            result.extend(page.into_iter().take(page_size));
+            if cursor.is_none() {
+                return result;
+            }
+        }
+    }
+
+    #[test]
+    fn test_order_by_multi_valued_field() {
+        let dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+        let mut segment = empty_segment(dir.path());
+        segment
+            .create_field_index(
+                0,
+                "price",
+                Some(&PayloadFieldSchema::FieldType(PayloadSchemaType::Float)),
+            )
+            .unwrap();
+
+        let prices = vec![
+            json!([5.0, 1.0]),
+            json!(2.0),
+            json!([3.0, 9.0]),
+            json!(4.0),
+            json!([0.0, 6.0, 7.0]),
+        ];
+        for (id, price) in prices.into_iter().enumerate() {
+            let id = id as u64;
+            segment
+                .upsert_vector(1, id.into(), &only_default_vector(&[1.0, 0.0, 1.0, 1.0]))
+                .unwrap();
+            segment
+                .set_full_payload(1, id.into(), &json!({ "price": price }).into())
+                .unwrap();
+        }
+
+        for page_size in 1..=3 {
+            let ascending = read_all_pages(&segment, Direction::Asc, page_size);
+            // Points are ordered by their smallest value
+            assert_eq!(
+                ascending,
+                vec![
+                    (0.0, 4.into()),
+                    (1.0, 0.into()),
+                    (2.0, 1.into()),
+                    (3.0, 2.into()),
+                    (4.0, 3.into()),
+                ],
+                "page size: {page_size}"
+            );
+
+            let descending = read_all_pages(&segment, Direction::Desc, page_size);
+            // Points are ordered by their largest value
+            assert_eq!(
+                descending,
+                vec![
+                    (9.0, 2.into()),
+                    (7.0, 4.into()),
+                    (5.0, 0.into()),
+                    (4.0, 3.into()),
+                    (2.0, 1.into()),
+                ],
+                "page size: {page_size}"
+            );
+            let unique: HashSet<_> = descending.iter().map(|(_, id)| *id).collect();
+            assert_eq!(unique.len(), descending.len());
+        }
+    }
+
+    #[test]
+    fn test_order_by_requires_index() {
+        let dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+        let mut segment = empty_segment(dir.path());
+        segment
+            .upsert_vector(1, 0.into(), &only_default_vector(&[1.0, 0.0, 1.0, 1.0]))
+            .unwrap();
+        segment
+            .set_full_payload(1, 0.into(), &json!({ "price": 1.0 }).into())
+            .unwrap();
+
+        // No index on the field at all
+        let result =
+            segment.read_ordered_filtered(None, Some(10), None, &order_by(Direction::Asc, None));
+        assert!(matches!(
+            result,
+            Err(OperationError::MissingOrderIndex { .. })
+        ));
+
+        // Keyword index does not support ordering
+        segment
+            .create_field_index(
+                2,
+                "price",
+                Some(&PayloadFieldSchema::FieldType(PayloadSchemaType::Keyword)),
+            )
+            .unwrap();
+        let result =
+            segment.read_ordered_filtered(None, Some(10), None, &order_by(Direction::Asc, None));
+        assert!(matches!(
+            result,
+            Err(OperationError::MissingOrderIndex { .. })
+        ));
+    }
+}
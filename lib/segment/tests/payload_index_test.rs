@@ -18,7 +18,7 @@ mod tests {
     use segment::types::{
         Condition, Distance, FieldCondition, Filter, GeoPoint, GeoRadius, Indexes,
         IsEmptyCondition, Payload, PayloadField, PayloadSchemaType, Range, SegmentConfig,
-        StorageType, VectorDataConfig, WithPayload,
+        StorageType, VectorDataConfig, VectorStorageDatatype, WithPayload,
     };
     use tempfile::Builder;
 
@@ -32,6 +32,10 @@ mod tests {
                 VectorDataConfig {
                     size: dim,
                     distance: Distance::Dot,
+                    hnsw_config: None,
+                    quantization_config: None,
+                    datatype: VectorStorageDatatype::Float32,
+                    multivector_config: None,
                 },
             )]),
             index: Indexes::Plain {},
@@ -240,6 +244,7 @@ mod tests {
                     Some(&query_filter),
                     5,
                     None,
+                    None,
                 )
                 .unwrap();
             let struct_result = struct_segment
@@ -251,6 +256,7 @@ mod tests {
                     Some(&query_filter),
                     5,
                     None,
+                    None,
                 )
                 .unwrap();
 
@@ -313,6 +319,7 @@ mod tests {
 
             let query_filter = Filter {
                 should: None,
+                min_should: None,
                 must: Some(vec![condition]),
                 must_not: None,
             };
@@ -326,6 +333,7 @@ mod tests {
                     Some(&query_filter),
                     5,
                     None,
+                    None,
                 )
                 .unwrap();
 
@@ -355,6 +363,7 @@ mod tests {
                     Some(&query_filter),
                     5,
                     None,
+                    None,
                 )
                 .unwrap();
 
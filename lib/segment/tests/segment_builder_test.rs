@@ -10,9 +10,11 @@ mod tests {
     use itertools::Itertools;
     use segment::data_types::vectors::{only_default_vector, DEFAULT_VECTOR_NAME};
     use segment::entry::entry_point::{OperationError, SegmentEntry};
+    use segment::index::hnsw_index::graph_layers::HNSW_GRAPH_FILE;
     use segment::segment::Segment;
+    use segment::segment_constructor::load_segment;
     use segment::segment_constructor::segment_builder::SegmentBuilder;
-    use segment::types::{Indexes, SegmentConfig, VectorDataConfig};
+    use segment::types::{Indexes, SegmentConfig, VectorDataConfig, VectorStorageDatatype};
     use tempfile::Builder;
 
     use crate::fixtures::segment::{build_segment_1, build_segment_2, empty_segment};
@@ -84,6 +86,10 @@ mod tests {
                 VectorDataConfig {
                     size: segment.segment_config.vector_data[DEFAULT_VECTOR_NAME].size,
                     distance: segment.segment_config.vector_data[DEFAULT_VECTOR_NAME].distance,
+                    hnsw_config: None,
+                    quantization_config: None,
+                    datatype: VectorStorageDatatype::Float32,
+                    multivector_config: None,
                 },
             )]),
             index: Indexes::Hnsw(Default::default()),
@@ -138,4 +144,47 @@ mod tests {
 
         assert!(time_fast < time_long);
     }
+
+    #[test]
+    fn test_load_segment_with_missing_vector_index() {
+        let dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+        let temp_dir = Builder::new().prefix("segment_temp_dir").tempdir().unwrap();
+
+        let stopped = AtomicBool::new(false);
+
+        let segment1 = build_segment_1(dir.path());
+
+        let config = SegmentConfig {
+            index: Indexes::Hnsw(Default::default()),
+            ..segment1.segment_config.clone()
+        };
+        let mut builder = SegmentBuilder::new(dir.path(), temp_dir.path(), &config).unwrap();
+        builder.update_from(&segment1, &stopped).unwrap();
+        let indexed_segment: Segment = builder.build(&stopped).unwrap();
+        assert!(!indexed_segment.has_missing_indexes());
+
+        let segment_path = indexed_segment.current_path.clone();
+        drop(indexed_segment);
+
+        // Simulate snapshot without index files
+        std::fs::remove_file(segment_path.join("vector_index").join(HNSW_GRAPH_FILE)).unwrap();
+
+        let loaded_segment = load_segment(&segment_path).unwrap().unwrap();
+        assert!(loaded_segment.has_missing_indexes());
+
+        // Segment is still searchable without index
+        let res = loaded_segment
+            .search(
+                DEFAULT_VECTOR_NAME,
+                &[1.0, 1.0, 1.0, 1.0],
+                &false.into(),
+                &false.into(),
+                None,
+                3,
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(res.len(), 3);
+    }
 }
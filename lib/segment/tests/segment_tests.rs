@@ -5,9 +5,11 @@ mod tests {
     use std::collections::HashSet;
     use std::iter::FromIterator;
 
+    use segment::common::version::VERSION_FILE;
     use segment::data_types::named_vectors::NamedVectors;
     use segment::data_types::vectors::DEFAULT_VECTOR_NAME;
     use segment::entry::entry_point::{OperationError, SegmentEntry};
+    use segment::segment_constructor::{check_segment_version, SegmentCompatibility};
     use segment::types::{Condition, Filter, WithPayload};
     use tempfile::Builder;
 
@@ -32,6 +34,7 @@ mod tests {
                 None,
                 1,
                 None,
+                None,
             )
             .unwrap();
 
@@ -42,6 +45,7 @@ mod tests {
 
         let frt = Filter {
             should: None,
+            min_should: None,
             must: None,
             must_not: Some(vec![Condition::HasId(ids.into())]),
         };
@@ -55,6 +59,7 @@ mod tests {
                 Some(&frt),
                 1,
                 None,
+                None,
             )
             .unwrap();
 
@@ -89,6 +94,7 @@ mod tests {
                 None,
                 1,
                 None,
+                None,
             )
             .unwrap();
 
@@ -99,6 +105,7 @@ mod tests {
 
         let frt = Filter {
             should: None,
+            min_should: None,
             must: None,
             must_not: Some(vec![Condition::HasId(ids.into())]),
         };
@@ -112,6 +119,7 @@ mod tests {
                 Some(&frt),
                 1,
                 None,
+                None,
             )
             .unwrap();
 
@@ -170,4 +178,32 @@ mod tests {
             panic!("wrong upsert result")
         }
     }
+
+    #[test]
+    fn test_check_segment_version() {
+        let dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+        let segment = build_segment_1(dir.path());
+        let segment_path = segment.current_path.clone();
+        let version_path = segment_path.join(VERSION_FILE);
+
+        assert_eq!(
+            check_segment_version(&segment_path).unwrap(),
+            Some(SegmentCompatibility::Current)
+        );
+
+        std::fs::write(&version_path, "0.3.0").unwrap();
+        assert!(matches!(
+            check_segment_version(&segment_path).unwrap(),
+            Some(SegmentCompatibility::NeedsMigration(_))
+        ));
+
+        std::fs::write(&version_path, "0.1.0").unwrap();
+        assert!(matches!(
+            check_segment_version(&segment_path).unwrap(),
+            Some(SegmentCompatibility::Incompatible(_))
+        ));
+
+        std::fs::remove_file(&version_path).unwrap();
+        assert_eq!(check_segment_version(&segment_path).unwrap(), None);
+    }
 }
@@ -24,6 +24,58 @@ impl AliasMapping {
     pub fn save(&self, path: &Path) -> Result<(), StorageError> {
         Ok(atomic_save_json(path, self)?)
     }
+
+    pub fn get(&self, alias: &str) -> Option<&CollectionId> {
+        self.0.get(alias)
+    }
+
+    pub fn insert(&mut self, alias: Alias, collection_name: CollectionId) {
+        self.0.insert(alias, collection_name);
+    }
+
+    pub fn remove(&mut self, alias: &str) -> Option<CollectionId> {
+        self.0.remove(alias)
+    }
+
+    pub fn rename(
+        &mut self,
+        old_alias_name: &str,
+        new_alias_name: Alias,
+    ) -> Result<(), StorageError> {
+        match self.0.remove(old_alias_name) {
+            None => Err(StorageError::NotFound {
+                description: format!("Alias {} does not exists!", old_alias_name),
+            }),
+            Some(collection_name) => {
+                self.0.insert(new_alias_name, collection_name);
+                Ok(())
+            }
+        }
+    }
+
+    /// Exchange target collections of the aliases, both aliases should exist
+    pub fn swap(
+        &mut self,
+        first_alias_name: &str,
+        second_alias_name: &str,
+    ) -> Result<(), StorageError> {
+        let not_found = |alias_name: &str| StorageError::NotFound {
+            description: format!("Alias {} does not exists!", alias_name),
+        };
+        let first_collection = self
+            .get(first_alias_name)
+            .cloned()
+            .ok_or_else(|| not_found(first_alias_name))?;
+        let second_collection = self
+            .get(second_alias_name)
+            .cloned()
+            .ok_or_else(|| not_found(second_alias_name))?;
+        self.0
+            .insert(first_alias_name.to_string(), second_collection);
+        self.0
+            .insert(second_alias_name.to_string(), first_collection);
+        Ok(())
+    }
 }
 
 /// Persists mapping between alias and collection name. The data is assumed to be relatively small.
@@ -83,18 +135,22 @@ impl AliasPersistence {
         old_alias_name: &str,
         new_alias_name: String,
     ) -> Result<(), StorageError> {
-        match self.get(old_alias_name) {
-            None => Err(StorageError::NotFound {
-                description: format!("Alias {} does not exists!", old_alias_name),
-            }),
-            Some(collection_name) => {
-                self.alias_mapping.0.remove(old_alias_name);
-                self.alias_mapping.0.insert(new_alias_name, collection_name);
-                // 'remove' & 'insert' saved atomically
-                self.alias_mapping.save(&self.data_path)?;
-                Ok(())
-            }
-        }
+        self.alias_mapping.rename(old_alias_name, new_alias_name)?;
+        // 'remove' & 'insert' saved atomically
+        self.alias_mapping.save(&self.data_path)?;
+        Ok(())
+    }
+
+    /// All aliases with their target collections, ordered by alias name
+    pub fn all_aliases(&self) -> Vec<(String, CollectionId)> {
+        let mut result: Vec<_> = self
+            .alias_mapping
+            .0
+            .iter()
+            .map(|(alias, collection_name)| (alias.clone(), collection_name.clone()))
+            .collect();
+        result.sort();
+        result
     }
 
     pub fn collection_aliases(&self, collection_name: &str) -> Vec<String> {
@@ -112,8 +168,29 @@ impl AliasPersistence {
     }
 
     pub fn apply_state(&mut self, alias_mapping: AliasMapping) -> Result<(), StorageError> {
+        alias_mapping.save(&self.data_path)?;
         self.alias_mapping = alias_mapping;
-        self.alias_mapping.save(&self.data_path)?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_swap_aliases() {
+        let mut aliases = AliasMapping::default();
+        aliases.insert("blue".to_string(), "collection_v1".to_string());
+        aliases.insert("green".to_string(), "collection_v2".to_string());
+
+        aliases.swap("blue", "green").unwrap();
+        assert_eq!(aliases.get("blue").unwrap(), "collection_v2");
+        assert_eq!(aliases.get("green").unwrap(), "collection_v1");
+
+        // Aliases are not changed if one of them is missing
+        assert!(aliases.swap("blue", "missing").is_err());
+        assert_eq!(aliases.get("blue").unwrap(), "collection_v2");
+        assert!(aliases.get("missing").is_none());
+    }
+}
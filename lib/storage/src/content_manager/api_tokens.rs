@@ -0,0 +1,332 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use schemars::JsonSchema;
+use segment::common::file_operations::{atomic_save_json, read_json};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::content_manager::errors::StorageError;
+
+pub const API_TOKENS_CONFIG_FILE: &str = "data.json";
+
+const API_TOKEN_LENGTH: usize = 32;
+/// Number of hex characters of the token hash, which identify the token in the audit log
+const API_TOKEN_FINGERPRINT_LENGTH: usize = 16;
+
+/// What the holder of a token is allowed to do with the collection.
+/// Each scope includes the previous ones.
+#[derive(
+    Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiTokenScope {
+    /// Search, recommend, retrieve, scroll and count points
+    Read,
+    /// Upsert and delete points and their payloads
+    Write,
+    /// Change the configuration, indexes and snapshots of the collection
+    Manage,
+}
+
+/// Access to a single collection or alias, granted by a token
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub struct ApiToken {
+    /// Name of the collection or alias, the token was issued for
+    pub collection_name: String,
+    pub scope: ApiTokenScope,
+}
+
+/// Access of the request, resolved from its credentials
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Access {
+    /// Master API key, or authentication is disabled
+    Full,
+    /// Collection token
+    Collection(ApiToken),
+    /// JWT, which claims restrict the collections and the scope
+    Claims {
+        /// If `None` - all collections are accessible
+        collection_names: Option<Vec<String>>,
+        scope: ApiTokenScope,
+    },
+}
+
+impl Access {
+    /// Check, if the request is allowed to perform an operation of `scope` on the collection.
+    /// Operations outside of any collection (`None`) require access to all collections.
+    pub fn check(
+        &self,
+        collection_name: Option<&str>,
+        scope: ApiTokenScope,
+    ) -> Result<(), StorageError> {
+        let (collection_names, granted_scope) = match self {
+            Access::Full => return Ok(()),
+            Access::Collection(token) => (
+                Some(std::slice::from_ref(&token.collection_name)),
+                token.scope,
+            ),
+            Access::Claims {
+                collection_names,
+                scope,
+            } => (collection_names.as_deref(), *scope),
+        };
+        match (collection_name, collection_names) {
+            (_, None) => {}
+            (None, Some(_)) => {
+                return Err(StorageError::Forbidden {
+                    description: "Operation requires the master API key".to_string(),
+                })
+            }
+            (Some(collection_name), Some(collection_names)) => {
+                if !collection_names.iter().any(|name| name == collection_name) {
+                    return Err(StorageError::Forbidden {
+                        description: format!("Token does not grant access to {collection_name}"),
+                    });
+                }
+            }
+        }
+        if granted_scope >= scope {
+            Ok(())
+        } else {
+            Err(StorageError::Forbidden {
+                description: format!(
+                    "Token scope `{:?}` is not sufficient, `{:?}` is required",
+                    granted_scope, scope
+                ),
+            })
+        }
+    }
+}
+
+/// Client, which sent the request, resolved from its credentials.
+/// Credentials themselves are never included, so the identity could be logged.
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ClientIdentity {
+    /// Master API key
+    MasterKey,
+    /// Collection API token
+    ApiToken {
+        collection_name: String,
+        /// Prefix of the hex-encoded SHA256 of the token
+        fingerprint: String,
+    },
+    /// JWT, identified by its `sub` claim, if it has one
+    Jwt { subject: Option<String> },
+}
+
+impl ClientIdentity {
+    pub fn api_token(token: &str, api_token: &ApiToken) -> Self {
+        let mut fingerprint = hex::encode(Sha256::digest(token.as_bytes()));
+        fingerprint.truncate(API_TOKEN_FINGERPRINT_LENGTH);
+        ClientIdentity::ApiToken {
+            collection_name: api_token.collection_name.clone(),
+            fingerprint,
+        }
+    }
+}
+
+/// Generate a new random token
+pub fn generate_api_token() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(API_TOKEN_LENGTH)
+        .map(char::from)
+        .collect()
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+pub struct ApiTokensMapping(HashMap<String, ApiToken>);
+
+impl ApiTokensMapping {
+    pub fn load(path: &Path) -> Result<Self, StorageError> {
+        Ok(read_json(path)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), StorageError> {
+        Ok(atomic_save_json(path, self)?)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &ApiToken)> {
+        self.0.iter()
+    }
+}
+
+/// Persists tokens of collections. The data is assumed to be relatively small.
+/// - Reads are served from memory.
+/// - Writes are durably saved.
+#[derive(Debug)]
+pub struct ApiTokensPersistence {
+    data_path: PathBuf,
+    tokens: ApiTokensMapping,
+}
+
+impl ApiTokensPersistence {
+    pub fn get_config_path(path: &Path) -> PathBuf {
+        path.join(API_TOKENS_CONFIG_FILE)
+    }
+
+    fn init_file(dir_path: &Path) -> Result<PathBuf, StorageError> {
+        let data_path = Self::get_config_path(dir_path);
+        if !data_path.exists() {
+            let mut file = fs::File::create(&data_path)?;
+            let empty_json = "{}";
+            file.write_all(empty_json.as_bytes())?;
+        }
+        Ok(data_path)
+    }
+
+    pub fn open(dir_path: PathBuf) -> Result<Self, StorageError> {
+        if !dir_path.exists() {
+            fs::create_dir_all(&dir_path)?;
+        }
+        let data_path = Self::init_file(&dir_path)?;
+        let tokens = ApiTokensMapping::load(&data_path)?;
+        Ok(ApiTokensPersistence { data_path, tokens })
+    }
+
+    pub fn get(&self, token: &str) -> Option<&ApiToken> {
+        self.tokens.0.get(token)
+    }
+
+    pub fn insert(&mut self, token: String, api_token: ApiToken) -> Result<(), StorageError> {
+        self.tokens.0.insert(token, api_token);
+        self.tokens.save(&self.data_path)
+    }
+
+    /// Returns `false`, if there is no such token
+    pub fn remove(&mut self, token: &str) -> Result<bool, StorageError> {
+        if self.tokens.0.remove(token).is_none() {
+            return Ok(false);
+        }
+        self.tokens.save(&self.data_path)?;
+        Ok(true)
+    }
+
+    /// Remove all tokens of the collection or alias, e.g. if it is deleted
+    pub fn remove_collection(&mut self, collection_name: &str) -> Result<(), StorageError> {
+        let count = self.tokens.0.len();
+        self.tokens
+            .0
+            .retain(|_, api_token| api_token.collection_name != collection_name);
+        if self.tokens.0.len() != count {
+            self.tokens.save(&self.data_path)?;
+        }
+        Ok(())
+    }
+
+    pub fn state(&self) -> &ApiTokensMapping {
+        &self.tokens
+    }
+
+    pub fn apply_state(&mut self, tokens: ApiTokensMapping) -> Result<(), StorageError> {
+        tokens.save(&self.data_path)?;
+        self.tokens = tokens;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::Builder;
+
+    use super::*;
+
+    fn token(collection_name: &str, scope: ApiTokenScope) -> ApiToken {
+        ApiToken {
+            collection_name: collection_name.to_string(),
+            scope,
+        }
+    }
+
+    #[test]
+    fn test_access_check() {
+        assert!(Access::Full.check(None, ApiTokenScope::Manage).is_ok());
+
+        let access = Access::Collection(token("test", ApiTokenScope::Write));
+        assert!(access.check(Some("test"), ApiTokenScope::Read).is_ok());
+        assert!(access.check(Some("test"), ApiTokenScope::Write).is_ok());
+        assert!(access.check(Some("test"), ApiTokenScope::Manage).is_err());
+        assert!(access.check(Some("other"), ApiTokenScope::Read).is_err());
+        assert!(access.check(None, ApiTokenScope::Read).is_err());
+
+        let access = Access::Claims {
+            collection_names: Some(vec!["test".to_string(), "other".to_string()]),
+            scope: ApiTokenScope::Read,
+        };
+        assert!(access.check(Some("other"), ApiTokenScope::Read).is_ok());
+        assert!(access.check(Some("other"), ApiTokenScope::Write).is_err());
+        assert!(access.check(Some("third"), ApiTokenScope::Read).is_err());
+        assert!(access.check(None, ApiTokenScope::Read).is_err());
+
+        let access = Access::Claims {
+            collection_names: None,
+            scope: ApiTokenScope::Manage,
+        };
+        assert!(access.check(Some("any"), ApiTokenScope::Manage).is_ok());
+        assert!(access.check(None, ApiTokenScope::Manage).is_ok());
+    }
+
+    #[test]
+    fn test_api_token_identity() {
+        let api_token = token("test", ApiTokenScope::Read);
+        let secret = generate_api_token();
+        let identity = ClientIdentity::api_token(&secret, &api_token);
+        let fingerprint = match &identity {
+            ClientIdentity::ApiToken {
+                collection_name,
+                fingerprint,
+            } => {
+                assert_eq!(collection_name, "test");
+                fingerprint.clone()
+            }
+            _ => panic!("unexpected identity {identity:?}"),
+        };
+        assert_eq!(fingerprint.len(), API_TOKEN_FINGERPRINT_LENGTH);
+        assert!(!serde_json::to_string(&identity).unwrap().contains(&secret));
+        // Same token is always identified the same way, other tokens differ
+        assert_eq!(ClientIdentity::api_token(&secret, &api_token), identity);
+        assert_ne!(
+            ClientIdentity::api_token(&generate_api_token(), &api_token),
+            identity
+        );
+    }
+
+    #[test]
+    fn test_persistence() {
+        let dir = Builder::new().prefix("api_tokens").tempdir().unwrap();
+
+        let read_token = generate_api_token();
+        let manage_token = generate_api_token();
+        assert_eq!(read_token.len(), API_TOKEN_LENGTH);
+        assert_ne!(read_token, manage_token);
+
+        let mut persistence = ApiTokensPersistence::open(dir.path().to_path_buf()).unwrap();
+        persistence
+            .insert(read_token.clone(), token("test", ApiTokenScope::Read))
+            .unwrap();
+        persistence
+            .insert(manage_token.clone(), token("other", ApiTokenScope::Manage))
+            .unwrap();
+        drop(persistence);
+
+        let mut persistence = ApiTokensPersistence::open(dir.path().to_path_buf()).unwrap();
+        assert_eq!(
+            persistence.get(&read_token),
+            Some(&token("test", ApiTokenScope::Read))
+        );
+
+        persistence.remove_collection("test").unwrap();
+        assert!(persistence.get(&read_token).is_none());
+
+        assert!(persistence.remove(&manage_token).unwrap());
+        assert!(!persistence.remove(&manage_token).unwrap());
+        assert!(persistence.get(&manage_token).is_none());
+    }
+}
@@ -1,15 +1,27 @@
-use collection::config::VectorsConfig;
+use collection::config::{
+    default_replication_factor, default_write_consistency_factor, CollectionConfig, VectorParams,
+    VectorsConfig,
+};
+use collection::operations::auto_payload_index::AutoPayloadIndexConfig;
 use collection::operations::config_diff::{
-    CollectionParamsDiff, HnswConfigDiff, OptimizersConfigDiff, WalConfigDiff,
+    CollectionParamsDiff, DiffConfig, HnswConfigDiff, OptimizersConfigDiff, VectorsConfigDiff,
+    WalConfigDiff,
 };
+use collection::operations::payload_types::PayloadFieldTypes;
+use collection::operations::ttl::TtlConfig;
+use collection::optimizers_builder::OptimizersConfig;
 use collection::shards::replica_set::ReplicaState;
 use collection::shards::shard::{PeerId, ShardId};
 use collection::shards::transfer::shard_transfer::{ShardTransfer, ShardTransferKey};
 use collection::shards::{replica_set, CollectionId};
 use schemars::JsonSchema;
+use segment::types::{Filter, HnswConfig, ProductQuantizationConfig, VectorStorageDatatype};
 use serde::{Deserialize, Serialize};
 
+use crate::content_manager::api_tokens::{ApiToken, ApiTokenScope};
+use crate::content_manager::errors::StorageError;
 use crate::content_manager::shard_distribution::ShardDistributionProposal;
+use crate::types::{StorageConfig, StrictModeConfig};
 
 // *Operation wrapper structure is only required for better OpenAPI generation
 
@@ -57,6 +69,23 @@ pub struct RenameAliasOperation {
     pub rename_alias: RenameAlias,
 }
 
+/// Exchange target collections of two aliases.
+/// Both aliases point to a collection at any moment, e.g. to switch traffic to a re-indexed
+/// collection and keep the old one available under the other alias.
+#[derive(Debug, Deserialize, Serialize, JsonSchema, PartialEq, Eq, Hash, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct SwapAliases {
+    pub first_alias_name: String,
+    pub second_alias_name: String,
+}
+
+/// Exchange target collections of two aliases
+#[derive(Debug, Deserialize, Serialize, JsonSchema, PartialEq, Eq, Hash, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct SwapAliasesOperation {
+    pub swap_aliases: SwapAliases,
+}
+
 /// Group of all the possible operations related to collection aliases
 #[derive(Debug, Deserialize, Serialize, JsonSchema, PartialEq, Eq, Hash, Clone)]
 #[serde(rename_all = "snake_case")]
@@ -65,6 +94,7 @@ pub enum AliasOperations {
     CreateAlias(CreateAliasOperation),
     DeleteAlias(DeleteAliasOperation),
     RenameAlias(RenameAliasOperation),
+    SwapAliases(SwapAliasesOperation),
 }
 
 impl From<CreateAlias> for AliasOperations {
@@ -85,6 +115,19 @@ impl From<RenameAlias> for AliasOperations {
     }
 }
 
+impl From<SwapAliases> for AliasOperations {
+    fn from(swap_aliases: SwapAliases) -> Self {
+        AliasOperations::SwapAliases(SwapAliasesOperation { swap_aliases })
+    }
+}
+
+/// Source of the initial data for the new collection
+#[derive(Debug, Deserialize, Serialize, JsonSchema, PartialEq, Eq, Hash, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct InitFrom {
+    pub collection: CollectionId,
+}
+
 /// Operation for creating new collection and (optionally) specify index params
 #[derive(Debug, Deserialize, Serialize, JsonSchema, PartialEq, Eq, Hash, Clone)]
 #[serde(rename_all = "snake_case")]
@@ -120,6 +163,115 @@ pub struct CreateCollection {
     pub wal_config: Option<WalConfigDiff>,
     /// Custom params for Optimizers.  If none - values from service configuration file are used.
     pub optimizers_config: Option<OptimizersConfigDiff>,
+    /// Specify other collection to copy data from.
+    /// Points are copied in background, after the collection is created, and the progress is
+    /// reported in the collection info. Points, written by clients in the meantime, are not
+    /// overwritten. The collection must have the same number of shards as the source one,
+    /// which is also the default.
+    #[serde(default)]
+    pub init_from: Option<InitFrom>,
+    /// Expected types of payload fields.
+    /// Upserts and payload updates with values of other types are rejected, so payload indexes
+    /// do not receive values of mixed types. Fields, which are not listed, could have any type.
+    #[serde(default)]
+    pub payload_field_types: Option<PayloadFieldTypes>,
+    /// If set - payload indexes of inferred types are created for the fields, first seen in
+    /// upserted points or set payload, until the collection has `max_indexed_fields` indexes.
+    #[serde(default)]
+    pub auto_payload_index: Option<AutoPayloadIndexConfig>,
+    /// If set - points are deleted in background, once `ttl_sec` seconds are passed since the
+    /// datetime in the payload `field`. Points without the field never expire.
+    #[serde(default)]
+    pub ttl: Option<TtlConfig>,
+    /// Name of the vector, used by searches and recommendations which do not specify one.
+    /// Must be one of the named vectors of the collection.
+    #[serde(default)]
+    pub default_vector: Option<String>,
+}
+
+impl CreateCollection {
+    /// Check that the collection parameters, combined with the defaults of the storage, make sense
+    /// and fit into the limits of the strict mode.
+    ///
+    /// Should be called before the operation is proposed, so invalid collections are rejected
+    /// with an explanation instead of being created with a useless configuration.
+    pub fn validate(&self, storage_config: &StorageConfig) -> Result<(), StorageError> {
+        if storage_config.strict_mode.enabled {
+            self.check_strict_mode(&storage_config.strict_mode)?;
+        }
+
+        let optimizers_config = match &self.optimizers_config {
+            None => storage_config.optimizers.clone(),
+            Some(diff) => diff.clone().update(&storage_config.optimizers)?,
+        };
+        validate_optimizers_config(&optimizers_config)?;
+
+        let hnsw_config = match self.hnsw_config {
+            None => storage_config.hnsw_index,
+            Some(diff) => diff.update(&storage_config.hnsw_index)?,
+        };
+        validate_hnsw_config(&hnsw_config, "hnsw_config")?;
+        for (name, params) in self.vectors.params_iter() {
+            if let Some(diff) = params.hnsw_config {
+                validate_hnsw_config(
+                    &diff.update(&hnsw_config)?,
+                    &format!("hnsw_config of vector `{name}`"),
+                )?;
+            }
+            if let Some(quantization_config) = &params.quantization_config {
+                validate_quantization_config(quantization_config, params.size.get(), name)?;
+            }
+            validate_vector_storage(params, name)?;
+        }
+
+        if let Some(default_vector) = &self.default_vector {
+            if !self
+                .vectors
+                .params_iter()
+                .any(|(name, _)| name == default_vector)
+            {
+                return Err(StorageError::BadInput {
+                    description: format!(
+                        "`default_vector` {default_vector} is not one of the vectors of the collection"
+                    ),
+                });
+            }
+        }
+
+        validate_consistency_factor(
+            self.replication_factor
+                .unwrap_or_else(|| default_replication_factor().get()),
+            self.write_consistency_factor
+                .unwrap_or_else(|| default_write_consistency_factor().get()),
+        )
+    }
+
+    fn check_strict_mode(&self, strict_mode: &StrictModeConfig) -> Result<(), StorageError> {
+        if let (Some(shard_number), Some(max_shard_number)) =
+            (self.shard_number, strict_mode.max_shard_number)
+        {
+            if shard_number > max_shard_number {
+                return Err(StorageError::BadInput {
+                    description: format!(
+                        "`shard_number` {shard_number} exceeds the limit of {max_shard_number} shards set by strict mode, use at most {max_shard_number} shards"
+                    ),
+                });
+            }
+        }
+        if let Some(max_vector_size) = strict_mode.max_vector_size {
+            for (name, params) in self.vectors.params_iter() {
+                if params.size.get() > max_vector_size {
+                    return Err(StorageError::BadInput {
+                        description: format!(
+                            "Size {} of vector `{name}` exceeds the limit of {max_vector_size} set by strict mode, reduce the dimensionality of the vectors",
+                            params.size
+                        ),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 /// Operation for creating new collection and (optionally) specify index params
@@ -162,6 +314,150 @@ pub struct UpdateCollection {
     pub optimizers_config: Option<OptimizersConfigDiff>, // ToDo: Allow updates for other configuration params as well
     /// Collection base params.  If none - values from service configuration file are used.
     pub params: Option<CollectionParamsDiff>,
+    /// New expected types of payload fields, replacing the existing ones.
+    /// Empty map disables the check. Existing points are not validated.
+    #[serde(default)]
+    pub payload_field_types: Option<PayloadFieldTypes>,
+    /// New config of the automatic payload indexing.
+    /// `max_indexed_fields: 0` disables it. Already created indexes are kept.
+    #[serde(default)]
+    pub auto_payload_index: Option<AutoPayloadIndexConfig>,
+    /// New config of the points expiration. `ttl_sec: 0` disables it.
+    #[serde(default)]
+    pub ttl: Option<TtlConfig>,
+    /// New params for HNSW index of the collection.
+    /// Indexed segments, built with different params, are rebuilt in background.
+    #[serde(default)]
+    pub hnsw_config: Option<HnswConfigDiff>,
+    /// New HNSW, quantization and storage params of the vectors by their names.
+    /// Indexed segments, built with different params, are rebuilt in background.
+    #[serde(default)]
+    pub vectors: Option<VectorsConfigDiff>,
+}
+
+impl UpdateCollection {
+    /// Check that the parameters of the collection still make sense after the update.
+    pub fn validate(&self, config: &CollectionConfig) -> Result<(), StorageError> {
+        if let Some(diff) = &self.optimizers_config {
+            validate_optimizers_config(&diff.clone().update(&config.optimizer_config)?)?;
+        }
+        if let Some(diff) = &self.params {
+            let params = diff.clone().update(&config.params)?;
+            validate_consistency_factor(
+                params.replication_factor.get(),
+                params.write_consistency_factor.get(),
+            )?;
+        }
+
+        let hnsw_config = match self.hnsw_config {
+            None => config.hnsw_config,
+            Some(diff) => diff.update(&config.hnsw_config)?,
+        };
+        validate_hnsw_config(&hnsw_config, "hnsw_config")?;
+        if let Some(diff) = &self.vectors {
+            let mut params = config.params.clone();
+            params.update_vectors_from_diff(diff)?;
+            for (name, params) in params.vectors.params_iter() {
+                if let Some(diff) = params.hnsw_config {
+                    validate_hnsw_config(
+                        &diff.update(&hnsw_config)?,
+                        &format!("hnsw_config of vector `{name}`"),
+                    )?;
+                }
+                if let Some(quantization_config) = &params.quantization_config {
+                    validate_quantization_config(quantization_config, params.size.get(), name)?;
+                }
+                validate_vector_storage(params, name)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn validate_optimizers_config(config: &OptimizersConfig) -> Result<(), StorageError> {
+    if let Some(memmap_threshold) = config.memmap_threshold {
+        if memmap_threshold < config.indexing_threshold {
+            return Err(StorageError::BadInput {
+                description: format!(
+                    "`memmap_threshold` {memmap_threshold} is lower than `indexing_threshold` {}, so segments would be moved to disk before they are indexed. Set `memmap_threshold` to at least {} or lower `indexing_threshold`",
+                    config.indexing_threshold, config.indexing_threshold
+                ),
+            });
+        }
+    }
+    if let Some(max_segment_size) = config.max_segment_size {
+        if max_segment_size < config.indexing_threshold {
+            return Err(StorageError::BadInput {
+                description: format!(
+                    "`max_segment_size` {max_segment_size} is lower than `indexing_threshold` {}, so segments would never be indexed. Set `max_segment_size` to at least {} or lower `indexing_threshold`",
+                    config.indexing_threshold, config.indexing_threshold
+                ),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn validate_hnsw_config(config: &HnswConfig, context: &str) -> Result<(), StorageError> {
+    if config.ef_construct < config.m {
+        return Err(StorageError::BadInput {
+            description: format!(
+                "{context}: `ef_construct` {} is lower than `m` {}, so nodes can't get all their edges while the index is built. Set `ef_construct` to at least {}",
+                config.ef_construct, config.m, config.m
+            ),
+        });
+    }
+    Ok(())
+}
+
+fn validate_quantization_config(
+    config: &ProductQuantizationConfig,
+    vector_size: u64,
+    vector_name: &str,
+) -> Result<(), StorageError> {
+    if config.num_subvectors == 0 || config.num_subvectors as u64 > vector_size {
+        return Err(StorageError::BadInput {
+            description: format!(
+                "quantization_config of vector `{vector_name}`: `num_subvectors` {} must be between 1 and the vector size {vector_size}",
+                config.num_subvectors
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Byte vectors and multivectors only have in-memory storages
+fn validate_vector_storage(params: &VectorParams, vector_name: &str) -> Result<(), StorageError> {
+    if params.datatype != Some(VectorStorageDatatype::Uint8) {
+        return Ok(());
+    }
+    if params.multivector_config.is_some() {
+        return Err(StorageError::BadInput {
+            description: format!("Multivector `{vector_name}` can't be stored as uint8"),
+        });
+    }
+    if params.on_disk == Some(true) {
+        return Err(StorageError::BadInput {
+            description: format!(
+                "Vector `{vector_name}` of uint8 type can't be stored on disk, uint8 vectors are always kept in RAM. Remove `on_disk` or use `float32` datatype"
+            ),
+        });
+    }
+    Ok(())
+}
+
+fn validate_consistency_factor(
+    replication_factor: u32,
+    write_consistency_factor: u32,
+) -> Result<(), StorageError> {
+    if write_consistency_factor > replication_factor {
+        return Err(StorageError::BadInput {
+            description: format!(
+                "`write_consistency_factor` {write_consistency_factor} is higher than `replication_factor` {replication_factor}, so there are never enough replicas to confirm a write. Set `write_consistency_factor` to at most {replication_factor}"
+            ),
+        });
+    }
+    Ok(())
 }
 
 /// Operation for updating parameters of the existing collection
@@ -180,6 +476,11 @@ impl UpdateCollectionOperation {
             update_collection: UpdateCollection {
                 optimizers_config: None,
                 params: None,
+                payload_field_types: None,
+                auto_payload_index: None,
+                ttl: None,
+                hnsw_config: None,
+                vectors: None,
             },
             shard_replica_changes: None,
         }
@@ -240,6 +541,69 @@ pub struct SetShardReplicaState {
     pub state: ReplicaState,
 }
 
+/// Set filter, which is automatically combined with filters of all search, recommend,
+/// scroll and count requests to the collection or alias.
+/// Allows to define logical views of the collection without duplicating data.
+#[derive(Debug, Deserialize, Serialize, JsonSchema, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct SetDefaultFilter {
+    /// If `null` - default filter is removed
+    pub filter: Option<Filter>,
+}
+
+/// Operation for setting default filter of the collection or alias
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct SetDefaultFilterOperation {
+    /// Name of the collection or alias
+    pub name: String,
+    pub set_default_filter: SetDefaultFilter,
+}
+
+impl std::hash::Hash for SetDefaultFilterOperation {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        // Filter contains floats, so it is hashed by its serialized representation
+        serde_json::to_vec(&self.set_default_filter.filter)
+            .unwrap_or_default()
+            .hash(state);
+    }
+}
+
+impl Eq for SetDefaultFilterOperation {}
+
+/// Issue a token, which grants access of the `scope` to the collection or alias.
+/// The token is returned only once and can't be retrieved later.
+#[derive(Debug, Deserialize, Serialize, JsonSchema, PartialEq, Eq, Hash, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct CreateApiToken {
+    pub scope: ApiTokenScope,
+}
+
+/// Operation for issuing a token. The token is generated by the peer, which accepted the request,
+/// so all peers store the same one
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Hash, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct CreateApiTokenOperation {
+    pub token: String,
+    pub api_token: ApiToken,
+}
+
+/// Revoke a token of the collection or alias
+#[derive(Debug, Deserialize, Serialize, JsonSchema, PartialEq, Eq, Hash, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct DeleteApiToken {
+    pub token: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Hash, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct DeleteApiTokenOperation {
+    /// Name of the collection or alias
+    pub collection_name: String,
+    pub delete_api_token: DeleteApiToken,
+}
+
 /// Enumeration of all possible collection update operations
 #[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Hash, Clone)]
 #[serde(rename_all = "snake_case")]
@@ -250,5 +614,200 @@ pub enum CollectionMetaOperations {
     ChangeAliases(ChangeAliasesOperation),
     TransferShard(CollectionId, ShardTransferOperations),
     SetShardReplicaState(SetShardReplicaState),
+    SetDefaultFilter(SetDefaultFilterOperation),
+    CreateApiToken(CreateApiTokenOperation),
+    DeleteApiToken(DeleteApiTokenOperation),
     Nop { token: usize }, // Empty operation
 }
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU64;
+
+    use segment::types::Distance;
+
+    use super::*;
+    use crate::types::PerformanceConfig;
+
+    fn storage_config(strict_mode: StrictModeConfig) -> StorageConfig {
+        StorageConfig {
+            storage_path: "./storage".to_string(),
+            snapshots_path: "./snapshots".to_string(),
+            on_disk_payload: false,
+            optimizers: OptimizersConfig {
+                deleted_threshold: 0.2,
+                vacuum_min_vector_number: 1000,
+                default_segment_number: 0,
+                max_segment_size: None,
+                memmap_threshold: None,
+                indexing_threshold: 20_000,
+                flush_interval_sec: 5,
+                max_optimization_threads: 1,
+            },
+            wal: Default::default(),
+            performance: PerformanceConfig {
+                max_search_threads: 1,
+                max_optimization_threads: 1,
+                search_memory_limit_mb: None,
+                mmap_advice: Default::default(),
+                mmap_populate: false,
+                slow_query_threshold_ms: None,
+                max_disk_searches: None,
+                update_queue_retry_threshold: None,
+                max_parallel_snapshot_shards: 0,
+                indexing_memory_limit_mb: None,
+            },
+            hnsw_index: Default::default(),
+            encryption: Default::default(),
+            strict_mode,
+            quotas: Default::default(),
+        }
+    }
+
+    fn create_collection(size: u64) -> CreateCollection {
+        CreateCollection {
+            vectors: VectorParams {
+                size: NonZeroU64::new(size).unwrap(),
+                distance: Distance::Dot,
+                hnsw_config: None,
+                quantization_config: None,
+                on_disk: None,
+                datatype: None,
+                multivector_config: None,
+            }
+            .into(),
+            shard_number: None,
+            replication_factor: None,
+            write_consistency_factor: None,
+            on_disk_payload: None,
+            hnsw_config: None,
+            wal_config: None,
+            optimizers_config: None,
+            init_from: None,
+            payload_field_types: None,
+            auto_payload_index: None,
+            ttl: None,
+            default_vector: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_create_collection() {
+        let config = storage_config(StrictModeConfig::default());
+        assert!(create_collection(4).validate(&config).is_ok());
+
+        let memmap_before_indexing = CreateCollection {
+            optimizers_config: Some(OptimizersConfigDiff {
+                deleted_threshold: None,
+                vacuum_min_vector_number: None,
+                default_segment_number: None,
+                max_segment_size: None,
+                memmap_threshold: Some(10_000),
+                indexing_threshold: None,
+                flush_interval_sec: None,
+                max_optimization_threads: None,
+            }),
+            ..create_collection(4)
+        };
+        assert!(memmap_before_indexing.validate(&config).is_err());
+
+        let small_ef_construct = CreateCollection {
+            hnsw_config: Some(HnswConfigDiff {
+                m: Some(32),
+                ef_construct: Some(16),
+                full_scan_threshold: None,
+            }),
+            ..create_collection(4)
+        };
+        assert!(small_ef_construct.validate(&config).is_err());
+
+        // Vector params are checked on top of the collection params
+        let small_vector_ef_construct = CreateCollection {
+            vectors: VectorParams {
+                size: NonZeroU64::new(4).unwrap(),
+                distance: Distance::Dot,
+                hnsw_config: Some(HnswConfigDiff {
+                    m: None,
+                    ef_construct: Some(8),
+                    full_scan_threshold: None,
+                }),
+                quantization_config: None,
+                on_disk: None,
+                datatype: None,
+                multivector_config: None,
+            }
+            .into(),
+            ..create_collection(4)
+        };
+        assert!(small_vector_ef_construct.validate(&config).is_err());
+
+        let byte_multivector = CreateCollection {
+            vectors: VectorParams {
+                size: NonZeroU64::new(4).unwrap(),
+                distance: Distance::Dot,
+                hnsw_config: None,
+                quantization_config: None,
+                on_disk: None,
+                datatype: Some(VectorStorageDatatype::Uint8),
+                multivector_config: Some(Default::default()),
+            }
+            .into(),
+            ..create_collection(4)
+        };
+        assert!(byte_multivector.validate(&config).is_err());
+
+        let byte_vector_on_disk = CreateCollection {
+            vectors: VectorParams {
+                size: NonZeroU64::new(4).unwrap(),
+                distance: Distance::Dot,
+                hnsw_config: None,
+                quantization_config: None,
+                on_disk: Some(true),
+                datatype: Some(VectorStorageDatatype::Uint8),
+                multivector_config: None,
+            }
+            .into(),
+            ..create_collection(4)
+        };
+        assert!(byte_vector_on_disk.validate(&config).is_err());
+
+        let unreachable_consistency = CreateCollection {
+            replication_factor: Some(2),
+            write_consistency_factor: Some(3),
+            ..create_collection(4)
+        };
+        assert!(unreachable_consistency.validate(&config).is_err());
+
+        let unknown_default_vector = CreateCollection {
+            default_vector: Some("image".to_string()),
+            ..create_collection(4)
+        };
+        assert!(unknown_default_vector.validate(&config).is_err());
+    }
+
+    #[test]
+    fn test_validate_strict_mode() {
+        let strict_mode = StrictModeConfig {
+            enabled: true,
+            max_shard_number: Some(4),
+            max_vector_size: Some(1024),
+        };
+        let config = storage_config(strict_mode.clone());
+        assert!(create_collection(1024).validate(&config).is_ok());
+        assert!(create_collection(1025).validate(&config).is_err());
+
+        let too_many_shards = CreateCollection {
+            shard_number: Some(8),
+            ..create_collection(4)
+        };
+        assert!(too_many_shards.validate(&config).is_err());
+
+        // Limits are not checked, if strict mode is disabled
+        let config = storage_config(StrictModeConfig {
+            enabled: false,
+            ..strict_mode
+        });
+        assert!(create_collection(1025).validate(&config).is_ok());
+        assert!(too_many_shards.validate(&config).is_ok());
+    }
+}
@@ -0,0 +1,294 @@
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use atomicwrites::{AllowOverwrite, AtomicFile};
+use chrono::{DateTime, Utc};
+use collection::shards::shard::PeerId;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::content_manager::api_tokens::ClientIdentity;
+use crate::content_manager::collection_meta_ops::CollectionMetaOperations;
+use crate::content_manager::consensus_ops::EntryContext;
+use crate::StorageError;
+
+const AUDIT_LOG_FILE: &str = "consensus_audit.jsonl";
+
+/// Max number of records, kept in the audit log. Older records are dropped first.
+pub const AUDIT_LOG_CAPACITY: usize = 1000;
+
+const REDACTED: &str = "<redacted>";
+
+/// Collection meta operation, applied by the consensus on this peer
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
+pub struct AuditRecord {
+    /// Time, when the operation was applied on this peer
+    pub timestamp: DateTime<Utc>,
+    /// Raft term of the entry
+    pub term: u64,
+    /// Raft index of the entry
+    pub index: u64,
+    /// Peer, which accepted the request. Unknown for entries, proposed by older versions
+    pub peer_id: Option<PeerId>,
+    /// Client, which requested the operation, resolved from its API key or JWT.
+    /// Unknown for internal operations, if authentication is disabled and for older entries
+    pub proposer: Option<ClientIdentity>,
+    /// Collection or alias, the operation is addressed to
+    pub collection_name: Option<String>,
+    /// Kind of the operation, e.g. `delete_collection`
+    pub kind: String,
+    /// Body of the operation with the API tokens redacted
+    pub operation: serde_json::Value,
+    /// Error message, if the operation has failed
+    pub error: Option<String>,
+}
+
+impl AuditRecord {
+    pub fn new(
+        term: u64,
+        index: u64,
+        context: Option<EntryContext>,
+        mut operation: CollectionMetaOperations,
+        result: &Result<bool, StorageError>,
+    ) -> Self {
+        let collection_name = match &mut operation {
+            CollectionMetaOperations::CreateCollection(op) => Some(op.collection_name.clone()),
+            CollectionMetaOperations::UpdateCollection(op) => Some(op.collection_name.clone()),
+            CollectionMetaOperations::DeleteCollection(op) => Some(op.0.clone()),
+            CollectionMetaOperations::ChangeAliases(_) => None,
+            CollectionMetaOperations::TransferShard(collection_name, _) => {
+                Some(collection_name.clone())
+            }
+            CollectionMetaOperations::SetShardReplicaState(op) => Some(op.collection_name.clone()),
+            CollectionMetaOperations::SetDefaultFilter(op) => Some(op.name.clone()),
+            CollectionMetaOperations::CreateApiToken(op) => {
+                op.token = REDACTED.to_string();
+                Some(op.api_token.collection_name.clone())
+            }
+            CollectionMetaOperations::DeleteApiToken(op) => {
+                op.delete_api_token.token = REDACTED.to_string();
+                Some(op.collection_name.clone())
+            }
+            CollectionMetaOperations::Nop { .. } => None,
+        };
+        let operation = serde_json::to_value(&operation).unwrap_or_default();
+        // Operations are externally tagged, so the only key of the object is the kind
+        let kind = match &operation {
+            serde_json::Value::Object(object) => object.keys().next().cloned(),
+            serde_json::Value::String(kind) => Some(kind.clone()),
+            _ => None,
+        }
+        .unwrap_or_default();
+        let (peer_id, proposer) = match context {
+            Some(context) => (Some(context.proposer), context.identity),
+            None => (None, None),
+        };
+        Self {
+            timestamp: Utc::now(),
+            term,
+            index,
+            peer_id,
+            proposer,
+            collection_name,
+            kind,
+            operation,
+            error: result.as_ref().err().map(|err| err.to_string()),
+        }
+    }
+}
+
+/// Recent collection meta operations, applied on this peer.
+///
+/// Records are appended to a JSON lines file in the storage directory, so they survive restarts.
+/// The file is rewritten with the kept records, once it grows twice as large as the log.
+/// Operations, received as a part of a consensus snapshot, are not recorded.
+#[derive(Debug)]
+pub struct ConsensusAuditLog {
+    path: PathBuf,
+    records: VecDeque<AuditRecord>,
+    /// Number of records in the file, including the ones dropped from memory
+    file_records: usize,
+}
+
+impl ConsensusAuditLog {
+    pub fn load(storage_path: &str) -> Result<Self, StorageError> {
+        let path = Path::new(storage_path).join(AUDIT_LOG_FILE);
+        let mut records = VecDeque::new();
+        let mut file_records = 0;
+        if path.exists() {
+            for line in BufReader::new(File::open(&path)?).split(b'\n') {
+                file_records += 1;
+                // Last line might be incomplete, if the peer was stopped during the write
+                match serde_json::from_slice(&line?) {
+                    Ok(record) => records.push_back(record),
+                    Err(err) => log::warn!("Skipping invalid consensus audit record: {err}"),
+                }
+                if records.len() > AUDIT_LOG_CAPACITY {
+                    records.pop_front();
+                }
+            }
+        }
+        Ok(Self {
+            path,
+            records,
+            file_records,
+        })
+    }
+
+    pub fn record(&mut self, record: AuditRecord) -> Result<(), StorageError> {
+        // Entries, applied before the restart, might be applied again, if the progress was not saved
+        if self
+            .records
+            .back()
+            .map_or(false, |last| last.index >= record.index)
+        {
+            return Ok(());
+        }
+        if self.records.len() >= AUDIT_LOG_CAPACITY {
+            self.records.pop_front();
+        }
+        let line = encode_line(&record)?;
+        self.records.push_back(record);
+
+        if self.file_records >= 2 * AUDIT_LOG_CAPACITY {
+            return self.rewrite();
+        }
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .write_all(&line)?;
+        self.file_records += 1;
+        Ok(())
+    }
+
+    /// Latest records, addressed to the collection if it is specified, from the oldest to the newest
+    pub fn get(&self, collection_name: Option<&str>, limit: usize) -> Vec<AuditRecord> {
+        let mut records: Vec<_> = self
+            .records
+            .iter()
+            .rev()
+            .filter(|record| {
+                collection_name.map_or(true, |name| record.collection_name.as_deref() == Some(name))
+            })
+            .take(limit)
+            .cloned()
+            .collect();
+        records.reverse();
+        records
+    }
+
+    fn rewrite(&mut self) -> Result<(), StorageError> {
+        let mut data = vec![];
+        for record in &self.records {
+            data.extend(encode_line(record)?);
+        }
+        AtomicFile::new(&self.path, AllowOverwrite).write(|file| file.write_all(&data))?;
+        self.file_records = self.records.len();
+        Ok(())
+    }
+}
+
+fn encode_line(record: &AuditRecord) -> Result<Vec<u8>, StorageError> {
+    let mut line = serde_json::to_vec(record).map_err(|err| {
+        StorageError::service_error(&format!("Can't serialize audit record: {err}"))
+    })?;
+    line.push(b'\n');
+    Ok(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::Builder;
+
+    use super::*;
+    use crate::content_manager::api_tokens::{ApiToken, ApiTokenScope};
+    use crate::content_manager::collection_meta_ops::{
+        CreateApiTokenOperation, DeleteCollectionOperation,
+    };
+
+    fn delete_collection(name: &str) -> CollectionMetaOperations {
+        CollectionMetaOperations::DeleteCollection(DeleteCollectionOperation(name.to_string()))
+    }
+
+    fn context(identity: Option<ClientIdentity>) -> Option<EntryContext> {
+        Some(EntryContext {
+            proposer: 1,
+            identity,
+        })
+    }
+
+    #[test]
+    fn test_audit_record() {
+        let identity = ClientIdentity::Jwt {
+            subject: Some("admin".to_string()),
+        };
+        let record = AuditRecord::new(
+            2,
+            10,
+            context(Some(identity.clone())),
+            delete_collection("test"),
+            &Ok(true),
+        );
+        assert_eq!(record.kind, "delete_collection");
+        assert_eq!(record.collection_name.as_deref(), Some("test"));
+        assert_eq!(record.peer_id, Some(1));
+        assert_eq!(record.proposer, Some(identity));
+        assert!(record.error.is_none());
+
+        let create_token = CollectionMetaOperations::CreateApiToken(CreateApiTokenOperation {
+            token: "secret".to_string(),
+            api_token: ApiToken {
+                collection_name: "test".to_string(),
+                scope: ApiTokenScope::Read,
+            },
+        });
+        let result = Err(StorageError::BadInput {
+            description: "failed".to_string(),
+        });
+        let record = AuditRecord::new(2, 11, None, create_token, &result);
+        assert_eq!(record.kind, "create_api_token");
+        assert_eq!(record.peer_id, None);
+        assert_eq!(record.proposer, None);
+        assert_eq!(record.operation["create_api_token"]["token"], REDACTED);
+        assert!(!record.operation.to_string().contains("secret"));
+        assert!(record.error.is_some());
+    }
+
+    #[test]
+    fn test_audit_log_is_persisted() {
+        let dir = Builder::new().prefix("audit_log").tempdir().unwrap();
+        let storage_path = dir.path().to_str().unwrap();
+
+        let mut log = ConsensusAuditLog::load(storage_path).unwrap();
+        for index in 1..=AUDIT_LOG_CAPACITY as u64 * 3 {
+            let name = if index % 2 == 0 { "even" } else { "odd" };
+            let record = AuditRecord::new(
+                1,
+                index,
+                context(Some(ClientIdentity::MasterKey)),
+                delete_collection(name),
+                &Ok(true),
+            );
+            log.record(record).unwrap();
+        }
+        // Entry, applied again after restart, is not recorded twice
+        let reapplied = AuditRecord::new(1, 5, context(None), delete_collection("odd"), &Ok(true));
+        log.record(reapplied).unwrap();
+
+        let records = log.get(None, usize::MAX);
+        assert_eq!(records.len(), AUDIT_LOG_CAPACITY);
+        assert_eq!(records.last().unwrap().index, AUDIT_LOG_CAPACITY as u64 * 3);
+        assert!(log.file_records <= 2 * AUDIT_LOG_CAPACITY);
+
+        let even = log.get(Some("even"), 2);
+        assert_eq!(even.len(), 2);
+        assert!(even[0].index < even[1].index);
+        assert!(even.iter().all(|record| record.index % 2 == 0));
+
+        let loaded = ConsensusAuditLog::load(storage_path).unwrap();
+        assert_eq!(loaded.get(None, usize::MAX), records);
+    }
+}
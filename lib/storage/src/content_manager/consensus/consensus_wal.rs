@@ -1,5 +1,5 @@
-use std::fs::create_dir_all;
-use std::path::Path;
+use std::fs::{create_dir_all, read_dir};
+use std::path::{Path, PathBuf};
 
 use itertools::Itertools;
 use prost::Message;
@@ -12,18 +12,58 @@ use crate::{ConsensusOperations, StorageError};
 
 const COLLECTIONS_META_WAL_DIR: &str = "collections_meta_wal";
 
-pub struct ConsensusOpWal(Wal);
+pub struct ConsensusOpWal {
+    wal: Wal,
+    path: PathBuf,
+}
 
 impl ConsensusOpWal {
     pub fn new(storage_path: &str) -> Self {
         let collections_meta_wal_path = Path::new(storage_path).join(COLLECTIONS_META_WAL_DIR);
         create_dir_all(&collections_meta_wal_path)
             .expect("Can't create Collections meta Wal directory");
-        ConsensusOpWal(Wal::open(collections_meta_wal_path).unwrap())
+        ConsensusOpWal {
+            wal: Wal::open(&collections_meta_wal_path).unwrap(),
+            path: collections_meta_wal_path,
+        }
+    }
+
+    /// Number of entries in the WAL
+    pub fn len(&self) -> u64 {
+        self.wal.num_entries()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Total size of the WAL files
+    pub fn size_bytes(&self) -> Result<u64, StorageError> {
+        let mut size = 0;
+        for entry in read_dir(&self.path)? {
+            let metadata = entry?.metadata()?;
+            if metadata.is_file() {
+                size += metadata.len();
+            }
+        }
+        Ok(size)
+    }
+
+    /// Remove entries older than the raft index `until_index`, as they are no longer required.
+    /// Entries are removed by whole WAL segments, so some of the older entries might remain.
+    pub fn compact(&mut self, until_index: u64) -> Result<(), StorageError> {
+        let offset = match self.index_offset()? {
+            Some(offset) => offset,
+            None => return Ok(()),
+        };
+        if until_index <= offset {
+            return Ok(());
+        }
+        Ok(self.wal.prefix_truncate(until_index - offset)?)
     }
 
     pub fn clear(&mut self) -> Result<(), StorageError> {
-        Ok(self.0.clear()?)
+        Ok(self.wal.clear()?)
     }
 
     pub fn entry(&self, id: u64) -> raft::Result<RaftEntry> {
@@ -39,9 +79,9 @@ impl ConsensusOpWal {
             return Err(raft::Error::Store(raft::StorageError::Compacted));
         }
         // Due to snapshots there might be different offsets between wal index and raft entry index
-        let offset = first_entry.index - self.0.first_index();
+        let offset = first_entry.index - self.wal.first_index();
         <RaftEntry as prost::Message>::decode(
-            self.0
+            self.wal
                 .entry(id - offset)
                 .ok_or(raft::Error::Store(raft::StorageError::Unavailable))?
                 .as_ref(),
@@ -61,18 +101,18 @@ impl ConsensusOpWal {
     }
 
     pub fn first_entry(&self) -> Result<Option<RaftEntry>, StorageError> {
-        let first_index = self.0.first_index();
+        let first_index = self.wal.first_index();
         let entry = self
-            .0
+            .wal
             .entry(first_index)
             .map(|entry| <RaftEntry as prost::Message>::decode(entry.as_ref()));
         Ok(entry.transpose()?)
     }
 
     pub fn last_entry(&self) -> Result<Option<RaftEntry>, StorageError> {
-        let last_index = self.0.last_index();
+        let last_index = self.wal.last_index();
         let entry = self
-            .0
+            .wal
             .entry(last_index)
             .map(|entry| <RaftEntry as prost::Message>::decode(entry.as_ref()));
         Ok(entry.transpose()?)
@@ -81,7 +121,7 @@ impl ConsensusOpWal {
     /// Difference between raft index and WAL record number.
     /// Difference might be different because of consensus snapshot.
     fn index_offset(&self) -> Result<Option<u64>, StorageError> {
-        let last_known_index = self.0.first_index();
+        let last_known_index = self.wal.first_index();
         let first_entry = self.first_entry()?;
         let offset = first_entry.map(|entry| entry.index - last_known_index);
         Ok(offset)
@@ -92,7 +132,7 @@ impl ConsensusOpWal {
             let operation_opt = ConsensusOperations::try_from(&entry).ok();
 
             let index = entry.index;
-            let current_index = self.0.last_index();
+            let current_index = self.wal.last_index();
             let index_offset = self.index_offset()?;
 
             if let Some(offset) = index_offset {
@@ -124,7 +164,7 @@ impl ConsensusOpWal {
                         index - offset,
                         index
                     );
-                    self.0.truncate(index - offset)?;
+                    self.wal.truncate(index - offset)?;
                 } // else:
                   // Offset = 1
                   // raft index = 11
@@ -149,7 +189,7 @@ impl ConsensusOpWal {
             let mut buf = vec![];
             entry.encode(&mut buf)?;
             #[allow(unused_variables)]
-            let wal_index = self.0.append(&buf)?;
+            let wal_index = self.wal.append(&buf)?;
             #[cfg(debug_assertions)]
             if let Some(offset) = index_offset {
                 debug_assert!(wal_index == index - offset);
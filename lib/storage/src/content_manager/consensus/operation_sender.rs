@@ -2,18 +2,32 @@ use std::sync::mpsc::Sender;
 
 use parking_lot::Mutex;
 
+use crate::content_manager::api_tokens::ClientIdentity;
+use crate::content_manager::consensus_ops::ConsensusProposal;
 use crate::{ConsensusOperations, StorageError};
 
 /// Structure used to notify consensus about operation
-pub struct OperationSender(Mutex<Sender<ConsensusOperations>>);
+pub struct OperationSender(Mutex<Sender<ConsensusProposal>>);
 
 impl OperationSender {
-    pub fn new(sender: Sender<ConsensusOperations>) -> Self {
+    pub fn new(sender: Sender<ConsensusProposal>) -> Self {
         OperationSender(Mutex::new(sender))
     }
 
     pub fn send(&self, operation: ConsensusOperations) -> Result<(), StorageError> {
-        self.0.lock().send(operation)?;
+        self.send_with_identity(operation, None)
+    }
+
+    /// Propose the operation, requested by the client
+    pub fn send_with_identity(
+        &self,
+        operation: ConsensusOperations,
+        identity: Option<ClientIdentity>,
+    ) -> Result<(), StorageError> {
+        self.0.lock().send(ConsensusProposal {
+            operation,
+            identity,
+        })?;
         Ok(())
     }
 }
@@ -1,4 +1,5 @@
 use std::cmp;
+use std::collections::HashSet;
 use std::fs::{create_dir_all, File};
 use std::io::BufWriter;
 use std::path::{Path, PathBuf};
@@ -29,6 +30,9 @@ pub struct Persistent {
     pub apply_progress_queue: EntryApplyProgressQueue,
     #[serde(with = "serialize_peer_addresses")]
     pub peer_address_by_id: Arc<RwLock<PeerAddressById>>,
+    /// Learners, which are never promoted to voters
+    #[serde(default)]
+    pub permanent_learners: HashSet<PeerId>,
     pub this_peer_id: u64,
     #[serde(skip)]
     pub path: PathBuf,
@@ -50,8 +54,10 @@ impl Persistent {
         &mut self,
         meta: &SnapshotMetadata,
         address_by_id: PeerAddressById,
+        permanent_learners: HashSet<PeerId>,
     ) -> Result<(), StorageError> {
         *self.peer_address_by_id.write() = address_by_id;
+        self.permanent_learners = permanent_learners;
         self.state.conf_state = meta.get_conf_state().clone();
         self.state.hard_state.term = cmp::max(self.state.hard_state.term, meta.term);
         self.state.hard_state.commit = meta.index;
@@ -60,6 +66,14 @@ impl Persistent {
         self.save()
     }
 
+    /// Remember, that entries up to `index` are compacted from the WAL.
+    /// Their effect is persisted in the collections, which state is sent as a raft snapshot
+    /// to the peers, lagging behind the compacted entries.
+    pub fn set_compacted(&mut self, index: u64, term: u64) -> Result<(), StorageError> {
+        self.latest_snapshot_meta = SnapshotMetadataSer { term, index };
+        self.save()
+    }
+
     /// Returns state and if it was initialized for the first time
     pub fn load_or_init(
         storage_path: impl AsRef<Path>,
@@ -131,6 +145,26 @@ impl Persistent {
         self.save()
     }
 
+    pub fn set_permanent_learner(
+        &mut self,
+        peer_id: PeerId,
+        permanent: bool,
+    ) -> Result<(), StorageError> {
+        let changed = if permanent {
+            self.permanent_learners.insert(peer_id)
+        } else {
+            self.permanent_learners.remove(&peer_id)
+        };
+        if changed {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    pub fn is_permanent_learner(&self, peer_id: PeerId) -> bool {
+        self.permanent_learners.contains(&peer_id)
+    }
+
     pub fn last_applied_entry(&self) -> Option<u64> {
         self.apply_progress_queue.get_last_applied()
     }
@@ -167,6 +201,7 @@ impl Persistent {
             },
             apply_progress_queue: Default::default(),
             peer_address_by_id: Default::default(),
+            permanent_learners: Default::default(),
             this_peer_id,
             path,
             latest_snapshot_meta: Default::default(),
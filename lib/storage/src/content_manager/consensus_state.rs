@@ -1,8 +1,9 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use std::fmt::Display;
 use std::future::Future;
 use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -21,17 +22,21 @@ use tokio::time::error::Elapsed;
 use tonic::transport::Uri;
 
 use super::alias_mapping::AliasMapping;
-use super::consensus_ops::ConsensusOperations;
+use super::api_tokens::{ApiTokensMapping, ClientIdentity};
+use super::consensus_ops::{AddPeerContext, ConsensusOperations, EntryContext};
+use super::default_filters::DefaultFiltersMapping;
 use super::errors::StorageError;
+use super::events::{StateChange, StateChanges};
 use super::CollectionContainer;
+use crate::content_manager::consensus::audit_log::{AuditRecord, ConsensusAuditLog};
 use crate::content_manager::consensus::consensus_wal::ConsensusOpWal;
 use crate::content_manager::consensus::entry_queue::EntryId;
 use crate::content_manager::consensus::is_ready::IsReady;
 use crate::content_manager::consensus::operation_sender::OperationSender;
 use crate::content_manager::consensus::persistent::Persistent;
 use crate::types::{
-    ClusterInfo, ClusterStatus, ConsensusThreadStatus, MessageSendErrors, PeerAddressById,
-    PeerInfo, RaftInfo,
+    ClusterInfo, ClusterStatus, ConsensusThreadStatus, ConsensusWalInfo, MessageSendErrors,
+    PeerAddressById, PeerInfo, RaftInfo,
 };
 use crate::CollectionMetaOperations;
 
@@ -48,12 +53,18 @@ pub struct SnapshotData {
     pub collections_data: CollectionsSnapshot,
     #[serde(with = "crate::serialize_peer_addresses")]
     pub address_by_id: PeerAddressById,
+    #[serde(default)]
+    pub permanent_learners: HashSet<PeerId>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct CollectionsSnapshot {
     pub collections: HashMap<CollectionId, collection_state::State>,
     pub aliases: AliasMapping,
+    #[serde(default)]
+    pub default_filters: DefaultFiltersMapping,
+    #[serde(default)]
+    pub api_tokens: ApiTokensMapping,
 }
 
 impl TryFrom<&[u8]> for SnapshotData {
@@ -76,6 +87,11 @@ pub struct ConsensusState<C: CollectionContainer> {
     first_voter: RwLock<Option<PeerId>>,
     consensus_thread_status: RwLock<ConsensusThreadStatus>,
     message_send_failures: RwLock<HashMap<String, MessageSendErrors>>,
+    /// Reports changes of peers and the leader to subscribers
+    state_changes: StateChanges,
+    /// Peer is shutting down, the leadership should be handed over to another voter
+    leadership_transfer_requested: AtomicBool,
+    audit_log: Mutex<ConsensusAuditLog>,
 }
 
 impl<C: CollectionContainer> ConsensusState<C> {
@@ -84,8 +100,10 @@ impl<C: CollectionContainer> ConsensusState<C> {
         toc: Arc<C>,
         propose_sender: OperationSender,
         storage_path: &str,
-    ) -> Self {
-        Self {
+        state_changes: StateChanges,
+    ) -> Result<Self, StorageError> {
+        let audit_log = ConsensusAuditLog::load(storage_path)?;
+        Ok(Self {
             persistent: RwLock::new(persistent_state),
             is_leader_established: Arc::new(IsReady::default()),
             wal: Mutex::new(ConsensusOpWal::new(storage_path)),
@@ -98,7 +116,10 @@ impl<C: CollectionContainer> ConsensusState<C> {
                 last_update: Utc::now(),
             }),
             message_send_failures: Default::default(),
-        }
+            state_changes,
+            leadership_transfer_requested: AtomicBool::new(false),
+            audit_log: Mutex::new(audit_log),
+        })
     }
 
     pub fn record_message_send_failure<E: Error>(&self, peer_address: &Uri, error: E) {
@@ -137,13 +158,39 @@ impl<C: CollectionContainer> ConsensusState<C> {
     }
 
     pub fn set_raft_soft_state(&self, state: &SoftState) {
-        *self.soft_state.write() = Some(SoftState { ..*state });
+        let previous_leader = self
+            .soft_state
+            .write()
+            .replace(SoftState { ..*state })
+            .map(|previous| previous.leader_id);
+        if previous_leader != Some(state.leader_id) {
+            self.state_changes.notify(StateChange::Cluster);
+        }
     }
 
     pub fn this_peer_id(&self) -> PeerId {
         self.persistent.read().this_peer_id
     }
 
+    pub fn is_leader(&self) -> bool {
+        let this_peer_id = self.this_peer_id();
+        self.soft_state
+            .read()
+            .as_ref()
+            .map_or(false, |state| state.leader_id == this_peer_id)
+    }
+
+    /// Ask the consensus thread to hand over the leadership, if this peer is the leader.
+    /// The request is kept till the shutdown, so the leadership is not taken back
+    pub fn request_leadership_transfer(&self) {
+        self.leadership_transfer_requested
+            .store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_leadership_transfer_requested(&self) -> bool {
+        self.leadership_transfer_requested.load(Ordering::Relaxed)
+    }
+
     pub fn first_voter(&self) -> PeerId {
         match self.first_voter.read().as_ref() {
             Some(id) => *id,
@@ -166,6 +213,7 @@ impl<C: CollectionContainer> ConsensusState<C> {
                     peer_id,
                     PeerInfo {
                         uri: uri.to_string(),
+                        permanent_learner: persistent.is_permanent_learner(peer_id),
                     },
                 )
             })
@@ -192,6 +240,58 @@ impl<C: CollectionContainer> ConsensusState<C> {
         })
     }
 
+    pub fn wal_info(&self) -> Result<ConsensusWalInfo, StorageError> {
+        let compacted_index = self.persistent.read().latest_snapshot_meta().index;
+        let wal = self.wal.lock();
+        Ok(ConsensusWalInfo {
+            entries: wal.len(),
+            size_bytes: wal.size_bytes()?,
+            compacted_index,
+        })
+    }
+
+    /// Compact the WAL up to the last applied entry, if more than `max_entries` entries were
+    /// applied since the previous compaction, or if the WAL files take more than `max_size_bytes`.
+    ///
+    /// Applied operations are persisted by the collections, so the compacted entries are only
+    /// required by lagging peers. They receive the state of collections as a raft snapshot instead.
+    ///
+    /// Should be called from the consensus thread, which is the only writer of the WAL.
+    /// Returns `true`, if the WAL was compacted.
+    pub fn compact_wal(
+        &self,
+        max_entries: Option<u64>,
+        max_size_bytes: Option<u64>,
+    ) -> Result<bool, StorageError> {
+        let (last_applied, compacted_index) = {
+            let persistent = self.persistent.read();
+            match persistent.last_applied_entry() {
+                Some(index) => (index, persistent.latest_snapshot_meta().index),
+                None => return Ok(false),
+            }
+        };
+        if last_applied <= compacted_index {
+            return Ok(false);
+        }
+        let too_many_entries = max_entries.map_or(false, |max_entries| {
+            last_applied - compacted_index > max_entries
+        });
+        let too_large = match max_size_bytes {
+            None => false,
+            Some(max_size_bytes) => self.wal.lock().size_bytes()? > max_size_bytes,
+        };
+        if !too_many_entries && !too_large {
+            return Ok(false);
+        }
+        let term = self.wal.lock().entry(last_applied)?.term;
+        // Persist the compaction point first, so entries are never missing from the WAL
+        // while the raft state refers to them
+        self.persistent.write().set_compacted(last_applied, term)?;
+        self.wal.lock().compact(last_applied)?;
+        log::debug!("Consensus WAL is compacted up to entry {last_applied}");
+        Ok(true)
+    }
+
     /// Handle peer removal operation.
     ///
     /// 1. Try to remove peer
@@ -252,19 +352,30 @@ impl<C: CollectionContainer> ConsensusState<C> {
                 }
                 ConfChangeType::RemoveNode => {
                     log::debug!("Removing node {}", single_change.node_id);
+                    self.persistent
+                        .write()
+                        .set_permanent_learner(single_change.node_id, false)?;
                     stop_consensus |= self.on_peer_remove(single_change.node_id)?;
                 }
                 ConfChangeType::AddLearnerNode => {
                     log::debug!("Adding learner node {}", single_change.node_id);
-                    if let Ok(peer_uri) = String::from_utf8_lossy(entry.get_context())
-                        .deref()
-                        .try_into()
-                    {
+                    let context = AddPeerContext::decode(entry.get_context());
+                    if let Ok(peer_uri) = context.uri.deref().try_into() {
                         let peer_uri: Uri = peer_uri;
+                        self.persistent
+                            .write()
+                            .set_permanent_learner(single_change.node_id, context.learner)?;
                         self.add_peer(single_change.node_id, peer_uri.clone())?;
-                        let operation = ConsensusOperations::AddPeer {
-                            peer_id: single_change.node_id,
-                            uri: peer_uri.to_string(),
+                        let operation = if context.learner {
+                            ConsensusOperations::AddLearner {
+                                peer_id: single_change.node_id,
+                                uri: peer_uri.to_string(),
+                            }
+                        } else {
+                            ConsensusOperations::AddPeer {
+                                peer_id: single_change.node_id,
+                                uri: peer_uri.to_string(),
+                            }
                         };
                         let on_apply = self.on_consensus_op_apply.lock().remove(&operation);
                         if let Some(on_apply) = on_apply {
@@ -388,7 +499,17 @@ impl<C: CollectionContainer> ConsensusState<C> {
         let operation: ConsensusOperations = entry.try_into()?;
         let on_apply = self.on_consensus_op_apply.lock().remove(&operation);
         let result = if let ConsensusOperations::CollectionMeta(operation) = operation {
-            self.toc.perform_collection_meta_op(*operation)
+            let audit_operation = (!matches!(*operation, CollectionMetaOperations::Nop { .. }))
+                .then(|| operation.clone());
+            let result = self.toc.perform_collection_meta_op(*operation);
+            if let Some(operation) = audit_operation {
+                let context = EntryContext::decode(entry.get_context());
+                let record = AuditRecord::new(entry.term, entry.index, context, operation, &result);
+                if let Err(err) = self.audit_log.lock().record(record) {
+                    log::error!("Failed to record consensus operation to the audit log: {err}");
+                }
+            }
+            result
         } else {
             // RemovePeer or AddPeer should be converted into native ConfChangeV2 message before sending to the Raft.
             // So we do not expect to receive these operations as a normal entry.
@@ -418,9 +539,12 @@ impl<C: CollectionContainer> ConsensusState<C> {
         let data: SnapshotData = snapshot.get_data().try_into()?;
         self.toc.apply_collections_snapshot(data.collections_data)?;
         self.wal.lock().clear()?;
-        self.persistent
-            .write()
-            .update_from_snapshot(meta, data.address_by_id)?;
+        self.persistent.write().update_from_snapshot(
+            meta,
+            data.address_by_id,
+            data.permanent_learners,
+        )?;
+        self.state_changes.notify(StateChange::Cluster);
         Ok(())
     }
 
@@ -456,7 +580,9 @@ impl<C: CollectionContainer> ConsensusState<C> {
     }
 
     pub fn add_peer(&self, peer_id: PeerId, uri: Uri) -> Result<(), StorageError> {
-        self.persistent.write().insert_peer(peer_id, uri)
+        self.persistent.write().insert_peer(peer_id, uri)?;
+        self.state_changes.notify(StateChange::Cluster);
+        Ok(())
     }
 
     pub fn remove_peer(&self, peer_id: PeerId) -> Result<(), StorageError> {
@@ -465,7 +591,9 @@ impl<C: CollectionContainer> ConsensusState<C> {
         // plus we need to make additional removing in the `channel_pool`.
         // So we handle `remove_peer` inside the `toc` and persist changes in the `persistent` after that.
         self.toc.remove_peer(peer_id)?;
-        self.persistent.read().save()
+        self.persistent.read().save()?;
+        self.state_changes.notify(StateChange::Cluster);
+        Ok(())
     }
 
     pub async fn propose_consensus_op(
@@ -523,9 +651,12 @@ impl<C: CollectionContainer> ConsensusState<C> {
         }
     }
 
+    /// Propose the operation and wait until it is applied on this peer.
+    /// `identity` of the client, which requested the operation, is recorded in the audit log.
     pub async fn propose_consensus_op_with_await(
         &self,
         operation: ConsensusOperations,
+        identity: Option<ClientIdentity>,
         wait_timeout: Option<Duration>,
         with_confirmation: bool,
     ) -> Result<bool, StorageError> {
@@ -544,7 +675,8 @@ impl<C: CollectionContainer> ConsensusState<C> {
         let (sender, receiver) = oneshot::channel();
         {
             let mut on_apply_lock = self.on_consensus_op_apply.lock();
-            self.propose_sender.send(operation.clone())?;
+            self.propose_sender
+                .send_with_identity(operation.clone(), identity)?;
             on_apply_lock.insert(operation, sender);
         }
         let res = Self::await_receiver(receiver, wait_timeout).await?;
@@ -574,6 +706,30 @@ impl<C: CollectionContainer> ConsensusState<C> {
         self.persistent.read().peer_address_by_id()
     }
 
+    /// Learners, which should not be promoted to voters
+    pub fn permanent_learners(&self) -> HashSet<PeerId> {
+        self.persistent.read().permanent_learners.clone()
+    }
+
+    pub fn is_permanent_learner(&self, peer_id: PeerId) -> bool {
+        self.persistent.read().is_permanent_learner(peer_id)
+    }
+
+    /// Latest collection meta operations, applied on this peer, from the oldest to the newest
+    pub fn audit_log(&self, collection_name: Option<&str>, limit: usize) -> Vec<AuditRecord> {
+        self.audit_log.lock().get(collection_name, limit)
+    }
+
+    /// Peers, to which the last message of the consensus was delivered successfully
+    pub fn reachable_peers(&self) -> Vec<PeerId> {
+        let message_send_failures = self.message_send_failures.read();
+        self.peer_address_by_id()
+            .into_iter()
+            .filter(|(_, uri)| !message_send_failures.contains_key(&uri.to_string()))
+            .map(|(peer_id, _)| peer_id)
+            .collect()
+    }
+
     pub fn peer_count(&self) -> usize {
         self.persistent.read().peer_address_by_id.read().len()
     }
@@ -647,6 +803,7 @@ impl<C: CollectionContainer> Storage for ConsensusState<C> {
             let snapshot = SnapshotData {
                 collections_data,
                 address_by_id: persistent.peer_address_by_id(),
+                permanent_learners: persistent.permanent_learners.clone(),
             };
             Ok(raft::eraftpb::Snapshot {
                 data: serde_cbor::to_vec(&snapshot).map_err(raft_error_other)?,
@@ -729,6 +886,7 @@ mod tests {
     use proptest::prelude::*;
     use raft::eraftpb::Entry;
     use raft::storage::{MemStorage, Storage};
+    use raft::GetEntriesContext;
     use tempfile::Builder;
 
     use super::ConsensusState;
@@ -736,6 +894,7 @@ mod tests {
     use crate::content_manager::consensus::entry_queue::EntryApplyProgressQueue;
     use crate::content_manager::consensus::operation_sender::OperationSender;
     use crate::content_manager::consensus::persistent::Persistent;
+    use crate::content_manager::consensus_ops::AddPeerContext;
     use crate::content_manager::CollectionContainer;
 
     #[test]
@@ -749,6 +908,34 @@ mod tests {
         assert_eq!(state.state().hard_state.commit, 1);
     }
 
+    #[test]
+    fn permanent_learners_are_loaded() {
+        let dir = Builder::new().prefix("raft_state_test").tempdir().unwrap();
+        let mut state = Persistent::load_or_init(dir.path(), false).unwrap();
+        state.set_permanent_learner(42, true).unwrap();
+        assert!(state.is_permanent_learner(42));
+
+        let mut state_loaded = Persistent::load_or_init(dir.path(), false).unwrap();
+        assert!(state_loaded.is_permanent_learner(42));
+        state_loaded.set_permanent_learner(42, false).unwrap();
+        assert!(!state_loaded.is_permanent_learner(42));
+    }
+
+    #[test]
+    fn add_peer_context_is_compatible() {
+        let uri = "http://qdrant-1:6335/".to_string();
+        let voter = AddPeerContext {
+            uri: uri.clone(),
+            learner: false,
+        };
+        // Context of the peers, which become voters, is readable by older versions
+        assert_eq!(voter.encode(), uri.as_bytes());
+        assert_eq!(AddPeerContext::decode(&voter.encode()), voter);
+
+        let learner = AddPeerContext { uri, learner: true };
+        assert_eq!(AddPeerContext::decode(&learner.encode()), learner);
+    }
+
     #[test]
     fn save_failure() {
         let mut state = Persistent {
@@ -870,13 +1057,57 @@ mod tests {
             Arc::new(NoCollections),
             OperationSender::new(sender),
             path.to_str().unwrap(),
-        );
+            Default::default(),
+        )
+        .unwrap();
         let mem_storage = MemStorage::new();
         mem_storage.wl().append(entries.as_ref()).unwrap();
         consensus_state.append_entries(entries).unwrap();
         (consensus_state, mem_storage)
     }
 
+    #[test]
+    fn wal_compaction() {
+        let dir = Builder::new().prefix("raft_state_test").tempdir().unwrap();
+        let entries = (1..=10)
+            .map(|index| Entry {
+                index,
+                term: 1,
+                ..Default::default()
+            })
+            .collect();
+        let (consensus_state, _) = setup_storages(entries, dir.path());
+        {
+            let mut persistent = consensus_state.persistent.write();
+            persistent.set_unapplied_entries(1, 10).unwrap();
+            for _ in 0..8 {
+                persistent.entry_applied().unwrap();
+            }
+        }
+
+        // Not enough applied entries
+        assert!(!consensus_state.compact_wal(Some(10), None).unwrap());
+        assert_eq!(consensus_state.wal_info().unwrap().compacted_index, 0);
+
+        assert!(consensus_state.compact_wal(Some(5), None).unwrap());
+        let wal_info = consensus_state.wal_info().unwrap();
+        assert_eq!(wal_info.compacted_index, 8);
+        assert!(wal_info.size_bytes > 0);
+        assert_eq!(consensus_state.term(8).unwrap(), 1);
+        // Entries, which are not applied yet, are kept
+        assert_eq!(consensus_state.last_index().unwrap(), 10);
+        assert_eq!(
+            consensus_state
+                .entries(9, 11, None, GetEntriesContext::empty(false))
+                .unwrap()
+                .len(),
+            2
+        );
+
+        // Nothing was applied since the previous compaction
+        assert!(!consensus_state.compact_wal(Some(0), None).unwrap());
+    }
+
     prop_compose! {
         fn gen_entries(min_entries: u64, max_entries: u64)(n in min_entries..max_entries, inc_term_every in 1u64..max_entries) -> Vec<Entry> {
             (1..(n+1)).into_iter().map(|index| Entry {index, term: 1 + index/inc_term_every, ..Default::default()}).collect::<Vec<Entry>>()
@@ -6,8 +6,8 @@ use tonic::Status;
 use crate::content_manager::collection_meta_ops::{
     AliasOperations, ChangeAliasesOperation, CollectionMetaOperations, CreateAlias,
     CreateAliasOperation, CreateCollection, CreateCollectionOperation, DeleteAlias,
-    DeleteAliasOperation, DeleteCollectionOperation, RenameAlias, RenameAliasOperation,
-    UpdateCollection, UpdateCollectionOperation,
+    DeleteAliasOperation, DeleteCollectionOperation, InitFrom, RenameAlias, RenameAliasOperation,
+    SwapAliases, SwapAliasesOperation, UpdateCollection, UpdateCollectionOperation,
 };
 use crate::content_manager::errors::StorageError;
 
@@ -18,6 +18,10 @@ pub fn error_to_status(error: StorageError) -> tonic::Status {
         StorageError::ServiceError { .. } => tonic::Code::Internal,
         StorageError::BadRequest { .. } => tonic::Code::InvalidArgument,
         StorageError::Locked { .. } => tonic::Code::FailedPrecondition,
+        StorageError::Overloaded { .. } => tonic::Code::Unavailable,
+        StorageError::Forbidden { .. } => tonic::Code::PermissionDenied,
+        StorageError::Unauthorized { .. } => tonic::Code::Unauthenticated,
+        StorageError::PayloadTooLarge { .. } => tonic::Code::ResourceExhausted,
     };
     tonic::Status::new(error_code, format!("{}", error))
 }
@@ -56,6 +60,16 @@ impl TryFrom<api::grpc::qdrant::CreateCollection> for CollectionMetaOperations {
                 on_disk_payload: value.on_disk_payload,
                 replication_factor: value.replication_factor,
                 write_consistency_factor: value.write_consistency_factor,
+                init_from: value
+                    .init_from_collection
+                    .map(|collection| InitFrom { collection }),
+                payload_field_types: value
+                    .payload_field_types
+                    .map(TryInto::try_into)
+                    .transpose()?,
+                auto_payload_index: value.auto_payload_index.map(Into::into),
+                ttl: value.ttl.map(Into::into),
+                default_vector: value.default_vector,
             },
         )))
     }
@@ -70,6 +84,14 @@ impl TryFrom<api::grpc::qdrant::UpdateCollection> for CollectionMetaOperations {
             UpdateCollection {
                 optimizers_config: value.optimizers_config.map(Into::into),
                 params: value.params.map(TryInto::try_into).transpose()?,
+                payload_field_types: value
+                    .payload_field_types
+                    .map(TryInto::try_into)
+                    .transpose()?,
+                auto_payload_index: value.auto_payload_index.map(Into::into),
+                hnsw_config: value.hnsw_config.map(Into::into),
+                vectors: value.vectors_config.map(TryInto::try_into).transpose()?,
+                ttl: value.ttl.map(Into::into),
             },
         )))
     }
@@ -117,6 +139,17 @@ impl From<api::grpc::qdrant::RenameAlias> for AliasOperations {
     }
 }
 
+impl From<api::grpc::qdrant::SwapAliases> for AliasOperations {
+    fn from(value: api::grpc::qdrant::SwapAliases) -> Self {
+        Self::SwapAliases(SwapAliasesOperation {
+            swap_aliases: SwapAliases {
+                first_alias_name: value.first_alias_name,
+                second_alias_name: value.second_alias_name,
+            },
+        })
+    }
+}
+
 impl TryFrom<api::grpc::qdrant::AliasOperations> for AliasOperations {
     type Error = Status;
 
@@ -131,6 +164,7 @@ impl TryFrom<api::grpc::qdrant::AliasOperations> for AliasOperations {
             Some(api::grpc::qdrant::alias_operations::Action::RenameAlias(rename)) => {
                 Ok(rename.into())
             }
+            Some(api::grpc::qdrant::alias_operations::Action::SwapAliases(swap)) => Ok(swap.into()),
             _ => Err(Status::invalid_argument("Malformed AliasOperation type")),
         }
     }
@@ -0,0 +1,203 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use collection::operations::point_ops::{PointInsertOperations, PointOperations, PointStruct};
+use collection::operations::types::{
+    CollectionError, CollectionInitStatus, CollectionResult, PointRequest,
+};
+use collection::operations::CollectionUpdateOperations;
+use collection::shards::shard::ShardId;
+use collection::shards::shard_holder::LockedShardHolder;
+use collection::shards::CollectionId;
+use segment::types::{ExtendedPointId, WithPayload, WithPayloadInterface, WithVector};
+use tokio::sync::RwLock;
+
+use crate::content_manager::collections_ops::Collections;
+use crate::content_manager::events::{StateChange, StateChanges};
+
+/// Number of points, which are read from the source collection at once
+const INIT_FROM_BATCH_SIZE: usize = 100;
+
+/// Copy all points of the `from_collection` into the local replicas of the `to_collection`,
+/// reporting the progress in the info of the target collection.
+pub async fn init_collection(
+    collections: Arc<RwLock<Collections>>,
+    state_changes: &StateChanges,
+    from_collection: &CollectionId,
+    to_collection: &CollectionId,
+) -> CollectionResult<()> {
+    let mut status = CollectionInitStatus {
+        source: from_collection.clone(),
+        copied_points: 0,
+        error: None,
+    };
+    report_status(&collections, to_collection, Some(status.clone())).await;
+    state_changes.notify(StateChange::Collection(to_collection.clone()));
+
+    let result = populate_collection(
+        &collections,
+        from_collection,
+        to_collection,
+        &mut status.copied_points,
+    )
+    .await;
+
+    match &result {
+        Ok(()) => report_status(&collections, to_collection, None).await,
+        Err(err) => {
+            status.error = Some(err.to_string());
+            report_status(&collections, to_collection, Some(status)).await;
+        }
+    }
+    state_changes.notify(StateChange::Collection(to_collection.clone()));
+    result
+}
+
+async fn report_status(
+    collections: &RwLock<Collections>,
+    collection_name: &CollectionId,
+    status: Option<CollectionInitStatus>,
+) {
+    if let Some(collection) = collections.read().await.get(collection_name) {
+        collection.set_init_status(status);
+    }
+}
+
+/// Copy points shard by shard. Both collections have the same number of shards,
+/// so each point belongs to the shard with the same id in both of them.
+///
+/// Every peer only populates replicas of the target collection hosted on it, and reads the
+/// source shard from the local replica, if there is one. This way the data is only transferred
+/// over the network for source shards, which are not co-located with the target ones.
+async fn populate_collection(
+    collections: &RwLock<Collections>,
+    from_collection: &CollectionId,
+    to_collection: &CollectionId,
+    copied_points: &mut usize,
+) -> CollectionResult<()> {
+    // Collections lock is not held during the copying, so it doesn't block other collections
+    let (source_shards, target_shards) = {
+        let collections_read = collections.read().await;
+        let source =
+            collections_read
+                .get(from_collection)
+                .ok_or_else(|| CollectionError::NotFound {
+                    what: format!("Collection {from_collection}"),
+                })?;
+        let target =
+            collections_read
+                .get(to_collection)
+                .ok_or_else(|| CollectionError::NotFound {
+                    what: format!("Collection {to_collection}"),
+                })?;
+        (source.shards_holder(), target.shards_holder())
+    };
+
+    let local_shards = {
+        let target_shards_read = target_shards.read().await;
+        let mut local_shards = vec![];
+        for (shard_id, replica_set) in target_shards_read.get_shards() {
+            if replica_set.has_local_shard().await {
+                local_shards.push(*shard_id);
+            }
+        }
+        local_shards
+    };
+
+    for shard_id in local_shards {
+        copy_shard(&source_shards, &target_shards, shard_id, copied_points).await?;
+        report_status(
+            collections,
+            to_collection,
+            Some(CollectionInitStatus {
+                source: from_collection.clone(),
+                copied_points: *copied_points,
+                error: None,
+            }),
+        )
+        .await;
+    }
+    Ok(())
+}
+
+async fn copy_shard(
+    source_shards: &LockedShardHolder,
+    target_shards: &LockedShardHolder,
+    shard_id: ShardId,
+    copied_points: &mut usize,
+) -> CollectionResult<()> {
+    let missing_shard = || CollectionError::service_error(format!("Shard {shard_id} not found"));
+    let mut offset: Option<ExtendedPointId> = None;
+    loop {
+        let mut records = {
+            let source_shards_read = source_shards.read().await;
+            let source_shard = source_shards_read
+                .get_shard(&shard_id)
+                .ok_or_else(missing_shard)?;
+            source_shard
+                .scroll_by(
+                    offset,
+                    // One more point to get the offset of the next batch
+                    INIT_FROM_BATCH_SIZE + 1,
+                    &WithPayloadInterface::Bool(true),
+                    &WithVector::Bool(true),
+                    None,
+                    None,
+                    None,
+                )
+                .await?
+        };
+        offset = if records.len() > INIT_FROM_BATCH_SIZE {
+            records.pop().map(|record| record.id)
+        } else {
+            None
+        };
+
+        let target_shards_read = target_shards.read().await;
+        let target_shard = target_shards_read
+            .get_shard(&shard_id)
+            .ok_or_else(missing_shard)?;
+
+        // Points, written into the new collection by clients, are newer than the copied ones
+        let existing: HashSet<_> = target_shard
+            .retrieve_local(
+                Arc::new(PointRequest {
+                    ids: records.iter().map(|record| record.id).collect(),
+                    with_payload: None,
+                    with_vector: WithVector::Bool(false),
+                }),
+                &WithPayload {
+                    enable: false,
+                    payload_selector: None,
+                },
+                &WithVector::Bool(false),
+            )
+            .await?
+            .unwrap_or_default()
+            .into_iter()
+            .map(|record| record.id)
+            .collect();
+
+        let points: Vec<PointStruct> = records
+            .into_iter()
+            .filter(|record| !existing.contains(&record.id))
+            .map(PointStruct::try_from)
+            .collect::<Result<_, _>>()?;
+
+        if !points.is_empty() {
+            let points_count = points.len();
+            let operation = CollectionUpdateOperations::PointOperation(
+                PointOperations::UpsertPoints(PointInsertOperations::PointsList(points)),
+            );
+            // We only need to wait for the last batch
+            target_shard
+                .update_local(operation.into(), offset.is_none())
+                .await?;
+            *copied_points += points_count;
+        }
+
+        if offset.is_none() {
+            return Ok(());
+        }
+    }
+}
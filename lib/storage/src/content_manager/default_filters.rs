@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use segment::common::file_operations::{atomic_save_json, read_json};
+use segment::types::{Condition, Filter};
+use serde::{Deserialize, Serialize};
+
+use crate::content_manager::errors::StorageError;
+
+pub const DEFAULT_FILTERS_CONFIG_FILE: &str = "data.json";
+
+/// Name of the collection or alias, the filter is applied to
+type FilterTarget = String;
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+pub struct DefaultFiltersMapping(HashMap<FilterTarget, Filter>);
+
+impl DefaultFiltersMapping {
+    pub fn load(path: &Path) -> Result<Self, StorageError> {
+        Ok(read_json(path)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), StorageError> {
+        Ok(atomic_save_json(path, self)?)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&FilterTarget, &Filter)> {
+        self.0.iter()
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Filter> {
+        self.0.remove(name)
+    }
+
+    /// Move default filter to the new name, if there is any
+    pub fn rename(&mut self, old_name: &str, new_name: FilterTarget) {
+        if let Some(filter) = self.0.remove(old_name) {
+            self.0.insert(new_name, filter);
+        }
+    }
+}
+
+/// Persists default filters of collections and aliases. The data is assumed to be relatively small.
+/// - Reads are served from memory.
+/// - Writes are durably saved.
+#[derive(Debug)]
+pub struct DefaultFiltersPersistence {
+    data_path: PathBuf,
+    filters: DefaultFiltersMapping,
+}
+
+impl DefaultFiltersPersistence {
+    pub fn get_config_path(path: &Path) -> PathBuf {
+        path.join(DEFAULT_FILTERS_CONFIG_FILE)
+    }
+
+    fn init_file(dir_path: &Path) -> Result<PathBuf, StorageError> {
+        let data_path = Self::get_config_path(dir_path);
+        if !data_path.exists() {
+            let mut file = fs::File::create(&data_path)?;
+            let empty_json = "{}";
+            file.write_all(empty_json.as_bytes())?;
+        }
+        Ok(data_path)
+    }
+
+    pub fn open(dir_path: PathBuf) -> Result<Self, StorageError> {
+        if !dir_path.exists() {
+            fs::create_dir_all(&dir_path)?;
+        }
+        let data_path = Self::init_file(&dir_path)?;
+        let filters = DefaultFiltersMapping::load(&data_path)?;
+        Ok(DefaultFiltersPersistence { data_path, filters })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Filter> {
+        self.filters.0.get(name)
+    }
+
+    /// Set or remove (if `filter` is `None`) default filter of the collection or alias
+    pub fn set(&mut self, name: String, filter: Option<Filter>) -> Result<(), StorageError> {
+        match filter {
+            None => self.filters.0.remove(&name),
+            Some(filter) => self.filters.0.insert(name, filter),
+        };
+        self.filters.save(&self.data_path)?;
+        Ok(())
+    }
+
+    /// Move default filter to the new name, e.g. if an alias is renamed
+    pub fn rename(&mut self, old_name: &str, new_name: String) -> Result<(), StorageError> {
+        if let Some(filter) = self.filters.0.remove(old_name) {
+            self.filters.0.insert(new_name, filter);
+            self.filters.save(&self.data_path)?;
+        }
+        Ok(())
+    }
+
+    pub fn state(&self) -> &DefaultFiltersMapping {
+        &self.filters
+    }
+
+    pub fn apply_state(&mut self, filters: DefaultFiltersMapping) -> Result<(), StorageError> {
+        filters.save(&self.data_path)?;
+        self.filters = filters;
+        Ok(())
+    }
+}
+
+/// Combine filter of the request with the default filters, so that the point should match all of them
+pub fn merge_filters(
+    request_filter: Option<&Filter>,
+    default_filters: Vec<Filter>,
+) -> Option<Filter> {
+    let mut filters: Vec<_> = request_filter
+        .cloned()
+        .into_iter()
+        .chain(default_filters)
+        .collect();
+    match filters.len() {
+        0 => None,
+        1 => filters.pop(),
+        _ => Some(Filter {
+            should: None,
+            min_should: None,
+            must: Some(filters.into_iter().map(Condition::Filter).collect()),
+            must_not: None,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use segment::types::{FieldCondition, Match};
+    use tempfile::Builder;
+
+    use super::*;
+
+    fn visible_filter() -> Filter {
+        Filter::new_must(Condition::Field(FieldCondition::new_match(
+            "visible".to_string(),
+            Match::from(true),
+        )))
+    }
+
+    #[test]
+    fn test_merge_filters() {
+        assert_eq!(merge_filters(None, vec![]), None);
+        assert_eq!(
+            merge_filters(Some(&visible_filter()), vec![]),
+            Some(visible_filter())
+        );
+        assert_eq!(
+            merge_filters(None, vec![visible_filter()]),
+            Some(visible_filter())
+        );
+
+        let merged = merge_filters(Some(&visible_filter()), vec![visible_filter()]).unwrap();
+        assert_eq!(merged.must.map(|must| must.len()), Some(2));
+        assert!(merged.should.is_none());
+        assert!(merged.must_not.is_none());
+    }
+
+    #[test]
+    fn test_persistence() {
+        let dir = Builder::new().prefix("default_filters").tempdir().unwrap();
+
+        let mut persistence = DefaultFiltersPersistence::open(dir.path().to_path_buf()).unwrap();
+        persistence
+            .set("alias".to_string(), Some(visible_filter()))
+            .unwrap();
+        persistence
+            .rename("alias", "new_alias".to_string())
+            .unwrap();
+        drop(persistence);
+
+        let mut persistence = DefaultFiltersPersistence::open(dir.path().to_path_buf()).unwrap();
+        assert!(persistence.get("alias").is_none());
+        assert_eq!(persistence.get("new_alias"), Some(&visible_filter()));
+
+        persistence.set("new_alias".to_string(), None).unwrap();
+        assert!(persistence.get("new_alias").is_none());
+    }
+}
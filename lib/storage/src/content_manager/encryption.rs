@@ -0,0 +1,133 @@
+use std::process::Command;
+
+use api::grpc::peer_channel::{MessageCipher, P2P_KEY_SIZE};
+use segment::common::encryption::{StorageCipher, STORAGE_KEY_SIZE};
+
+use crate::content_manager::errors::StorageError;
+use crate::types::EncryptionConfig;
+
+/// Build cipher for encrypted collection storages from the configured key.
+/// Returns `None` if no key is configured.
+pub fn load_storage_cipher(
+    config: &EncryptionConfig,
+) -> Result<Option<StorageCipher>, StorageError> {
+    let key = match load_key(config, STORAGE_KEY_SIZE)? {
+        Some(key) => key,
+        None => return Ok(None),
+    };
+    let cipher = StorageCipher::new(&key)
+        .map_err(|err| StorageError::service_error(&format!("Can't init storage cipher: {err}")))?;
+    Ok(Some(cipher))
+}
+
+/// Build cipher for messages between peers from the configured key.
+/// Returns `None` if no key is configured.
+pub fn load_p2p_cipher(config: &EncryptionConfig) -> Result<Option<MessageCipher>, StorageError> {
+    let key = match load_key(config, P2P_KEY_SIZE)? {
+        Some(key) => key,
+        None => return Ok(None),
+    };
+    let cipher = MessageCipher::new(&key).map_err(|err| {
+        StorageError::service_error(&format!("Can't init peer-to-peer cipher: {err}"))
+    })?;
+    Ok(Some(cipher))
+}
+
+fn load_key(config: &EncryptionConfig, key_size: usize) -> Result<Option<Vec<u8>>, StorageError> {
+    let hex_key = match (&config.key, &config.key_command) {
+        (Some(_), Some(_)) => {
+            return Err(StorageError::BadInput {
+                description: "Only one of `key` and `key_command` can be set for encryption"
+                    .to_string(),
+            })
+        }
+        (Some(key), None) => key.trim().to_string(),
+        (None, Some(command)) => run_key_command(command)?,
+        (None, None) => return Ok(None),
+    };
+
+    let key = hex::decode(hex_key).map_err(|err| StorageError::BadInput {
+        description: format!("Encryption key must be hex-encoded: {err}"),
+    })?;
+    if key.len() != key_size {
+        return Err(StorageError::BadInput {
+            description: format!(
+                "Encryption key must be {key_size} bytes long, got {}",
+                key.len()
+            ),
+        });
+    }
+    Ok(Some(key))
+}
+
+/// Fetch the key from an external source, e.g. a KMS client
+fn run_key_command(command: &str) -> Result<String, StorageError> {
+    let output = Command::new("sh").arg("-c").arg(command).output()?;
+    if !output.status.success() {
+        return Err(StorageError::service_error(&format!(
+            "Encryption key command failed with {}",
+            output.status
+        )));
+    }
+    let key = String::from_utf8(output.stdout).map_err(|_| StorageError::BadInput {
+        description: "Encryption key command printed non UTF-8 output".to_string(),
+    })?;
+    Ok(key.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_storage_cipher() {
+        let hex_key = "ab".repeat(STORAGE_KEY_SIZE);
+
+        assert!(load_storage_cipher(&EncryptionConfig::default())
+            .unwrap()
+            .is_none());
+
+        let config = EncryptionConfig {
+            key: Some(hex_key.clone()),
+            key_command: None,
+        };
+        assert!(load_storage_cipher(&config).unwrap().is_some());
+
+        let config = EncryptionConfig {
+            key: None,
+            key_command: Some(format!("echo {hex_key}")),
+        };
+        assert!(load_storage_cipher(&config).unwrap().is_some());
+
+        let config = EncryptionConfig {
+            key: Some("abcd".to_string()),
+            key_command: None,
+        };
+        assert!(load_storage_cipher(&config).is_err());
+
+        let config = EncryptionConfig {
+            key: None,
+            key_command: Some("exit 1".to_string()),
+        };
+        assert!(load_storage_cipher(&config).is_err());
+    }
+
+    #[test]
+    fn test_load_p2p_cipher() {
+        assert!(load_p2p_cipher(&EncryptionConfig::default())
+            .unwrap()
+            .is_none());
+
+        let config = EncryptionConfig {
+            key: Some("cd".repeat(P2P_KEY_SIZE)),
+            key_command: None,
+        };
+        assert!(load_p2p_cipher(&config).unwrap().is_some());
+
+        let config = EncryptionConfig {
+            key: Some("cd".repeat(P2P_KEY_SIZE / 2)),
+            key_command: None,
+        };
+        assert!(load_p2p_cipher(&config).is_err());
+    }
+}
@@ -17,6 +17,18 @@ pub enum StorageError {
     BadRequest { description: String },
     #[error("Storage locked: {description}")]
     Locked { description: String },
+    /// Request is rejected to protect the service from overload, it could be retried later
+    #[error("Service overloaded: {description}")]
+    Overloaded { description: String },
+    /// Request is valid, but not allowed by the configuration of the service (e.g. quotas)
+    #[error("Forbidden: {description}")]
+    Forbidden { description: String },
+    /// API key of the request is missing or invalid
+    #[error("Unauthorized: {description}")]
+    Unauthorized { description: String },
+    /// Request exceeds the size limits of the service
+    #[error("Payload too large: {description}")]
+    PayloadTooLarge { description: String },
 }
 
 impl StorageError {
@@ -32,6 +44,39 @@ impl StorageError {
         }
     }
 
+    /// Prepend `context` to the description, keeping the kind of the error
+    pub fn with_context(self, context: &str) -> StorageError {
+        match self {
+            StorageError::BadInput { description } => StorageError::BadInput {
+                description: format!("{context}: {description}"),
+            },
+            StorageError::NotFound { description } => StorageError::NotFound {
+                description: format!("{context}: {description}"),
+            },
+            StorageError::ServiceError { description } => StorageError::ServiceError {
+                description: format!("{context}: {description}"),
+            },
+            StorageError::BadRequest { description } => StorageError::BadRequest {
+                description: format!("{context}: {description}"),
+            },
+            StorageError::Locked { description } => StorageError::Locked {
+                description: format!("{context}: {description}"),
+            },
+            StorageError::Overloaded { description } => StorageError::Overloaded {
+                description: format!("{context}: {description}"),
+            },
+            StorageError::Forbidden { description } => StorageError::Forbidden {
+                description: format!("{context}: {description}"),
+            },
+            StorageError::Unauthorized { description } => StorageError::Unauthorized {
+                description: format!("{context}: {description}"),
+            },
+            StorageError::PayloadTooLarge { description } => StorageError::PayloadTooLarge {
+                description: format!("{context}: {description}"),
+            },
+        }
+    }
+
     /// Used to override the `description` field of the resulting `StorageError`
     pub fn from_inconsistent_shard_failure(
         err: CollectionError,
@@ -0,0 +1,56 @@
+use collection::shards::shard::ShardId;
+use collection::shards::CollectionId;
+use segment::types::SeqNumberType;
+use tokio::sync::broadcast;
+
+/// Max number of changes buffered for a slow subscriber. Lagging subscribers skip the oldest ones.
+const STATE_CHANGES_BUFFER_SIZE: usize = 1024;
+
+/// Transition of the storage state, reported at the moment it happens
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateChange {
+    /// Collection is created or removed, or the status of the collection might have changed
+    Collection(CollectionId),
+    /// Optimization of a local shard of the collection is completed
+    OptimizationCompleted {
+        collection_name: CollectionId,
+        shard_id: ShardId,
+    },
+    /// Update operation of a local shard can't be applied, so it is saved aside and skipped
+    OperationQuarantined {
+        collection_name: CollectionId,
+        shard_id: ShardId,
+        op_num: SeqNumberType,
+    },
+    /// Peers or the leader of the cluster might have changed
+    Cluster,
+}
+
+/// Broadcasts state transitions of collections and the cluster to subscribers,
+/// so they don't need to poll the state.
+#[derive(Clone)]
+pub struct StateChanges {
+    sender: broadcast::Sender<StateChange>,
+}
+
+impl StateChanges {
+    pub fn new() -> Self {
+        let (sender, _receiver) = broadcast::channel(STATE_CHANGES_BUFFER_SIZE);
+        Self { sender }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<StateChange> {
+        self.sender.subscribe()
+    }
+
+    pub fn notify(&self, change: StateChange) {
+        // Error means that nobody is subscribed, so there is nobody to notify
+        let _ = self.sender.send(change);
+    }
+}
+
+impl Default for StateChanges {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,217 @@
+use std::cmp::Ordering;
+
+use collection::operations::types::SearchRequest;
+use schemars::JsonSchema;
+use segment::types::{Order, ScoreType, ScoredPoint};
+use serde::{Deserialize, Serialize};
+
+/// How scores of different collections are made comparable, before the results are merged
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreNormalization {
+    /// Scores are merged as is. Only allowed, if all collections order scores the same way
+    #[default]
+    None,
+    /// Scores of each collection are scaled into `[0, 1]`, the best one is 1
+    MinMax,
+    /// Score is `1 / (rank + 1)`, where rank is the position of the point in its collection
+    Rank,
+}
+
+/// Search of the same query in multiple collections with merged results
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct FederatedSearchRequest {
+    /// Collections or aliases to search in. `*` in the name matches any sequence of characters,
+    /// e.g. `logs-*` matches all collections and aliases, starting with `logs-`
+    pub collections: Vec<String>,
+    /// Search in each collection. Limit and offset are applied to the merged results
+    pub search: SearchRequest,
+    #[serde(default)]
+    pub normalization: ScoreNormalization,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct FederatedScoredPoint {
+    /// Collection, the point is found in
+    pub collection: String,
+    /// Point with the normalized score, used to merge the results
+    #[serde(flatten)]
+    pub point: ScoredPoint,
+    /// Score of the point in its collection
+    pub raw_score: ScoreType,
+}
+
+/// Results of the search in a single collection, ordered from the best
+pub struct CollectionSearchResult {
+    pub collection: String,
+    pub order: Order,
+    pub points: Vec<ScoredPoint>,
+}
+
+/// Check if the name matches the pattern, where `*` matches any sequence of characters
+pub fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    // Split always yields at least one part
+    let first = parts.next().unwrap_or_default();
+    let mut rest = match name.strip_prefix(first) {
+        Some(rest) => rest,
+        None => return false,
+    };
+    let mut parts: Vec<_> = parts.collect();
+    let last = match parts.pop() {
+        Some(last) => last,
+        // No wildcards in the pattern
+        None => return rest.is_empty(),
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(position) => rest = &rest[position + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+fn normalize(
+    result: CollectionSearchResult,
+    normalization: ScoreNormalization,
+) -> Vec<FederatedScoredPoint> {
+    let best = result.points.first().map(|point| point.score);
+    let worst = result.points.last().map(|point| point.score);
+    let collection = result.collection;
+    result
+        .points
+        .into_iter()
+        .enumerate()
+        .map(|(rank, mut point)| {
+            let raw_score = point.score;
+            point.score = match normalization {
+                ScoreNormalization::None => raw_score,
+                ScoreNormalization::MinMax => match (best, worst) {
+                    (Some(best), Some(worst)) if best != worst => {
+                        (raw_score - worst) / (best - worst)
+                    }
+                    _ => 1.0,
+                },
+                ScoreNormalization::Rank => 1.0 / (rank + 1) as ScoreType,
+            };
+            FederatedScoredPoint {
+                collection: collection.clone(),
+                point,
+                raw_score,
+            }
+        })
+        .collect()
+}
+
+/// Merge the results of the collections by their normalized scores and take the requested page.
+/// Points with equal scores are ordered by the position of their collection in `results`.
+pub fn merge_results(
+    results: Vec<CollectionSearchResult>,
+    normalization: ScoreNormalization,
+    offset: usize,
+    limit: usize,
+) -> Vec<FederatedScoredPoint> {
+    // Normalized scores are always larger-better
+    let small_better = normalization == ScoreNormalization::None
+        && results
+            .first()
+            .map_or(false, |result| matches!(result.order, Order::SmallBetter));
+    let mut merged: Vec<_> = results
+        .into_iter()
+        .flat_map(|result| normalize(result, normalization))
+        .collect();
+    merged.sort_by(|a, b| {
+        let ordering = a
+            .point
+            .score
+            .partial_cmp(&b.point.score)
+            .unwrap_or(Ordering::Equal);
+        if small_better {
+            ordering
+        } else {
+            ordering.reverse()
+        }
+    });
+    merged.into_iter().skip(offset).take(limit).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(collection: &str, order: Order, scores: &[ScoreType]) -> CollectionSearchResult {
+        CollectionSearchResult {
+            collection: collection.to_string(),
+            order,
+            points: scores
+                .iter()
+                .enumerate()
+                .map(|(id, score)| ScoredPoint {
+                    id: (id as u64).into(),
+                    version: 0,
+                    score: *score,
+                    payload: None,
+                    vector: None,
+                })
+                .collect(),
+        }
+    }
+
+    fn merged(results: &[FederatedScoredPoint]) -> Vec<(&str, ScoreType)> {
+        results
+            .iter()
+            .map(|point| (point.collection.as_str(), point.point.score))
+            .collect()
+    }
+
+    #[test]
+    fn test_matches_pattern() {
+        assert!(matches_pattern("logs", "logs"));
+        assert!(!matches_pattern("logs", "logs-1"));
+        assert!(matches_pattern("logs-*", "logs-1"));
+        assert!(matches_pattern("logs-*", "logs-"));
+        assert!(!matches_pattern("logs-*", "metrics-1"));
+        assert!(matches_pattern("*-2024", "logs-2024"));
+        assert!(matches_pattern("*", "anything"));
+        assert!(matches_pattern("a*b*c", "a-b-b-c"));
+        assert!(!matches_pattern("a*b*c", "a-c-b"));
+        assert!(!matches_pattern("ab*ba", "aba"));
+    }
+
+    #[test]
+    fn test_merge_results() {
+        let results = || {
+            vec![
+                result("a", Order::LargeBetter, &[0.75, 0.5, 0.25]),
+                result("b", Order::LargeBetter, &[0.7, 0.6]),
+            ]
+        };
+
+        let raw = merge_results(results(), ScoreNormalization::None, 0, 3);
+        assert_eq!(merged(&raw), vec![("a", 0.75), ("b", 0.7), ("b", 0.6)]);
+
+        let min_max = merge_results(results(), ScoreNormalization::MinMax, 1, 10);
+        assert_eq!(
+            merged(&min_max),
+            vec![("b", 1.0), ("a", 0.5), ("a", 0.0), ("b", 0.0)]
+        );
+        assert_eq!(min_max[0].raw_score, 0.7);
+
+        let rank = merge_results(results(), ScoreNormalization::Rank, 0, 4);
+        assert_eq!(
+            merged(&rank),
+            vec![("a", 1.0), ("b", 1.0), ("a", 0.5), ("b", 0.5)]
+        );
+
+        // Distances are merged from the smallest
+        let distances = vec![
+            result("a", Order::SmallBetter, &[1.0, 3.0]),
+            result("b", Order::SmallBetter, &[2.0]),
+        ];
+        let raw = merge_results(distances, ScoreNormalization::None, 0, 3);
+        assert_eq!(merged(&raw), vec![("a", 1.0), ("b", 2.0), ("a", 3.0)]);
+    }
+}
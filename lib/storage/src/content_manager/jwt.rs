@@ -0,0 +1,121 @@
+use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+
+use crate::content_manager::api_tokens::{Access, ApiTokenScope, ClientIdentity};
+use crate::content_manager::errors::StorageError;
+
+/// Claims of the JWT, issued by an external auth service and signed with the shared secret
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct JwtClaims {
+    /// Expiration time, seconds since the UNIX epoch. If not set - token does not expire
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exp: Option<u64>,
+    /// Collections or aliases, the token grants access to. If not set - all of them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collections: Option<Vec<String>>,
+    /// What the holder of the token is allowed to do. If not set - `manage`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<ApiTokenScope>,
+    /// Subject of the token, e.g. the user it was issued to. Recorded in the audit log
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+}
+
+impl From<JwtClaims> for Access {
+    fn from(claims: JwtClaims) -> Self {
+        Access::Claims {
+            collection_names: claims.collections,
+            scope: claims.scope.unwrap_or(ApiTokenScope::Manage),
+        }
+    }
+}
+
+impl From<&JwtClaims> for ClientIdentity {
+    fn from(claims: &JwtClaims) -> Self {
+        ClientIdentity::Jwt {
+            subject: claims.sub.clone(),
+        }
+    }
+}
+
+/// Verify signature and expiration of the HS256 token and return its claims
+pub fn decode_jwt(secret: &str, token: &str) -> Result<JwtClaims, StorageError> {
+    let mut validation = Validation::new(Algorithm::HS256);
+    // Expiration is validated only if it is present
+    validation.required_spec_claims.clear();
+    validation.leeway = 0;
+    decode::<JwtClaims>(
+        token,
+        &DecodingKey::from_secret(secret.as_bytes()),
+        &validation,
+    )
+    .map(|token_data| token_data.claims)
+    .map_err(|err| StorageError::Unauthorized {
+        description: format!("Invalid JWT: {err}"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use jsonwebtoken::{encode, EncodingKey, Header};
+
+    use super::*;
+
+    const SECRET: &str = "secret";
+
+    fn sign(claims: &JwtClaims, secret: &str) -> String {
+        encode(
+            &Header::default(),
+            claims,
+            &EncodingKey::from_secret(secret.as_bytes()),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_decode_jwt() {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let claims = JwtClaims {
+            exp: Some(now + 60),
+            collections: Some(vec!["test".to_string()]),
+            scope: Some(ApiTokenScope::Read),
+            sub: Some("user".to_string()),
+        };
+        assert_eq!(decode_jwt(SECRET, &sign(&claims, SECRET)).unwrap(), claims);
+        assert_eq!(
+            ClientIdentity::from(&claims),
+            ClientIdentity::Jwt {
+                subject: Some("user".to_string())
+            }
+        );
+
+        let access = Access::from(claims.clone());
+        assert!(access.check(Some("test"), ApiTokenScope::Read).is_ok());
+        assert!(access.check(Some("test"), ApiTokenScope::Write).is_err());
+
+        // Signed with another secret
+        assert!(decode_jwt(SECRET, &sign(&claims, "other")).is_err());
+
+        let expired = JwtClaims {
+            exp: Some(now - 1),
+            ..claims
+        };
+        assert!(decode_jwt(SECRET, &sign(&expired, SECRET)).is_err());
+
+        let unrestricted = JwtClaims {
+            exp: None,
+            collections: None,
+            scope: None,
+            sub: None,
+        };
+        let decoded = decode_jwt(SECRET, &sign(&unrestricted, SECRET)).unwrap();
+        assert!(Access::from(decoded)
+            .check(None, ApiTokenScope::Manage)
+            .is_ok());
+    }
+}
@@ -0,0 +1,326 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+use collection::shards::shard::ShardId;
+use collection::shards::CollectionId;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::content_manager::errors::StorageError;
+
+pub const DEFAULT_KEYED_LOCK_ERROR_MESSAGE: &str = "Write operations are forbidden by lock";
+
+/// Part of the storage, write operations to which are forbidden by a named lock
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Eq, Hash)]
+pub struct LockScope {
+    /// Name of the collection to lock
+    pub collection: CollectionId,
+    /// If specified - only writes into this shard are forbidden.
+    /// Otherwise the whole collection is locked.
+    pub shard_id: Option<ShardId>,
+}
+
+impl LockScope {
+    fn covers(&self, collection: &str, shard_id: Option<ShardId>) -> bool {
+        if self.collection != collection {
+            return false;
+        }
+        match (self.shard_id, shard_id) {
+            (None, _) => true,
+            (Some(locked_shard), Some(shard_id)) => locked_shard == shard_id,
+            (Some(_), None) => false,
+        }
+    }
+}
+
+/// Request to acquire (or prolong) a named write lock
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+pub struct KeyedLockRequest {
+    pub scope: LockScope,
+    /// Identifier of whoever holds the lock, e.g. name of a maintenance job
+    pub owner: Option<String>,
+    /// Human-readable reason of the lock, returned to clients in the error message
+    pub reason: Option<String>,
+    /// Lock is released automatically after this number of seconds.
+    /// If not specified - lock is held until explicitly released.
+    pub ttl_sec: Option<u64>,
+}
+
+/// Named write lock, currently held on a part of the storage
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq)]
+pub struct KeyedLockInfo {
+    pub name: String,
+    pub scope: LockScope,
+    pub owner: Option<String>,
+    pub reason: Option<String>,
+    pub acquired_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl KeyedLockInfo {
+    fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at
+            .map_or(false, |expires_at| expires_at <= now)
+    }
+
+    fn error_message(&self) -> String {
+        let reason = self
+            .reason
+            .clone()
+            .unwrap_or_else(|| DEFAULT_KEYED_LOCK_ERROR_MESSAGE.to_string());
+        match &self.owner {
+            Some(owner) => format!("{reason} (lock `{}` held by {owner})", self.name),
+            None => format!("{reason} (lock `{}`)", self.name),
+        }
+    }
+}
+
+/// Set of named write locks.
+///
+/// Unlike the global write lock, keyed locks only affect a single collection or a single shard
+/// of a collection. Locks are local to the peer: they are neither persisted nor replicated
+/// through consensus, so they only block writes which are sent to the peer holding the lock.
+/// In a cluster, the lock must be acquired on every peer that receives client writes.
+#[derive(Debug, Default)]
+pub struct KeyedLocks {
+    locks: HashMap<String, KeyedLockInfo>,
+}
+
+impl KeyedLocks {
+    /// Acquire lock with the given name, or update it if it is already held.
+    /// Returns the state of the acquired lock.
+    pub fn acquire(
+        &mut self,
+        name: String,
+        request: KeyedLockRequest,
+    ) -> Result<KeyedLockInfo, StorageError> {
+        let now = Utc::now();
+        self.remove_expired(now);
+
+        let KeyedLockRequest {
+            scope,
+            owner,
+            reason,
+            ttl_sec,
+        } = request;
+
+        if let Some(existing) = self.locks.get(&name) {
+            if existing.owner.is_some() && existing.owner != owner {
+                return Err(StorageError::Locked {
+                    description: format!(
+                        "Lock `{name}` is already held by {}",
+                        existing.owner.as_deref().unwrap_or_default()
+                    ),
+                });
+            }
+        }
+
+        let expires_at = match ttl_sec {
+            None => None,
+            Some(0) => {
+                return Err(StorageError::BadInput {
+                    description: "`ttl_sec` cannot be 0".to_string(),
+                })
+            }
+            Some(ttl_sec) => {
+                let expires_at = Duration::from_std(std::time::Duration::from_secs(ttl_sec))
+                    .ok()
+                    .and_then(|ttl| now.checked_add_signed(ttl))
+                    .ok_or_else(|| StorageError::BadInput {
+                        description: format!("`ttl_sec` is too large: {ttl_sec}"),
+                    })?;
+                Some(expires_at)
+            }
+        };
+
+        let lock = KeyedLockInfo {
+            name: name.clone(),
+            scope,
+            owner,
+            reason,
+            acquired_at: now,
+            expires_at,
+        };
+        self.locks.insert(name, lock.clone());
+        Ok(lock)
+    }
+
+    /// Release lock with the given name. Returns the released lock, if it was held.
+    ///
+    /// Lock with an owner can only be released by the same owner, as well as prolonged.
+    pub fn release(
+        &mut self,
+        name: &str,
+        owner: Option<&str>,
+    ) -> Result<Option<KeyedLockInfo>, StorageError> {
+        self.remove_expired(Utc::now());
+        if let Some(existing) = self.locks.get(name) {
+            if existing.owner.is_some() && existing.owner.as_deref() != owner {
+                return Err(StorageError::Locked {
+                    description: format!(
+                        "Lock `{name}` is held by {}",
+                        existing.owner.as_deref().unwrap_or_default()
+                    ),
+                });
+            }
+        }
+        Ok(self.locks.remove(name))
+    }
+
+    /// Remove all locks related to the collection, e.g. if the collection is deleted
+    pub fn release_collection(&mut self, collection: &str) {
+        self.locks
+            .retain(|_, lock| lock.scope.collection != collection);
+    }
+
+    /// List all currently active locks
+    pub fn list(&mut self) -> Vec<KeyedLockInfo> {
+        self.remove_expired(Utc::now());
+        let mut locks: Vec<_> = self.locks.values().cloned().collect();
+        locks.sort_by(|a, b| a.name.cmp(&b.name));
+        locks
+    }
+
+    /// Returns true if there is any active lock, related to the collection or its shards
+    pub fn has_locks(&self, collection: &str) -> bool {
+        let now = Utc::now();
+        self.locks
+            .values()
+            .any(|lock| lock.scope.collection == collection && !lock.is_expired(now))
+    }
+
+    /// Returns an error if writes into the collection (or into the specific shard of it) are locked
+    ///
+    /// If `shard_id` is `None` - only collection-wide locks are considered.
+    pub fn check(&self, collection: &str, shard_id: Option<ShardId>) -> Result<(), StorageError> {
+        let now = Utc::now();
+        let active_lock = self
+            .locks
+            .values()
+            .filter(|lock| !lock.is_expired(now))
+            .find(|lock| lock.scope.covers(collection, shard_id));
+
+        match active_lock {
+            None => Ok(()),
+            Some(lock) => Err(StorageError::Locked {
+                description: lock.error_message(),
+            }),
+        }
+    }
+
+    fn remove_expired(&mut self, now: DateTime<Utc>) {
+        self.locks.retain(|_, lock| !lock.is_expired(now));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lock_request(collection: &str, shard_id: Option<ShardId>) -> KeyedLockRequest {
+        KeyedLockRequest {
+            scope: LockScope {
+                collection: collection.to_string(),
+                shard_id,
+            },
+            owner: Some("maintenance".to_string()),
+            reason: Some("reindexing".to_string()),
+            ttl_sec: None,
+        }
+    }
+
+    #[test]
+    fn test_collection_lock() {
+        let mut locks = KeyedLocks::default();
+        locks
+            .acquire("tenant".to_string(), lock_request("tenant_a", None))
+            .unwrap();
+
+        assert!(locks.check("tenant_a", None).is_err());
+        assert!(locks.check("tenant_a", Some(1)).is_err());
+        assert!(locks.check("tenant_b", None).is_ok());
+
+        assert!(locks
+            .release("tenant", Some("maintenance"))
+            .unwrap()
+            .is_some());
+        assert!(locks.check("tenant_a", None).is_ok());
+        assert!(locks
+            .release("tenant", Some("maintenance"))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_shard_lock() {
+        let mut locks = KeyedLocks::default();
+        locks
+            .acquire("shard".to_string(), lock_request("tenant_a", Some(2)))
+            .unwrap();
+
+        assert!(locks.check("tenant_a", Some(2)).is_err());
+        assert!(locks.check("tenant_a", Some(1)).is_ok());
+        assert!(locks.check("tenant_a", None).is_ok());
+    }
+
+    #[test]
+    fn test_lock_owner() {
+        let mut locks = KeyedLocks::default();
+        locks
+            .acquire("tenant".to_string(), lock_request("tenant_a", None))
+            .unwrap();
+
+        let mut other_owner = lock_request("tenant_a", None);
+        other_owner.owner = Some("someone else".to_string());
+        assert!(locks.acquire("tenant".to_string(), other_owner).is_err());
+
+        // Same owner can prolong the lock
+        let mut prolong = lock_request("tenant_a", None);
+        prolong.ttl_sec = Some(60);
+        let acquired = locks.acquire("tenant".to_string(), prolong).unwrap();
+        assert_eq!(acquired.owner.as_deref(), Some("maintenance"));
+        assert!(acquired.expires_at.is_some());
+        assert_eq!(locks.list(), vec![acquired]);
+
+        // Only the owner can release the lock
+        assert!(locks.release("tenant", None).is_err());
+        assert!(locks.release("tenant", Some("someone else")).is_err());
+        assert!(locks.check("tenant_a", None).is_err());
+        assert!(locks
+            .release("tenant", Some("maintenance"))
+            .unwrap()
+            .is_some());
+
+        // Lock without an owner can be released by anyone
+        let mut no_owner = lock_request("tenant_a", None);
+        no_owner.owner = None;
+        locks.acquire("tenant".to_string(), no_owner).unwrap();
+        assert!(locks
+            .release("tenant", Some("someone else"))
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn test_has_locks() {
+        let mut locks = KeyedLocks::default();
+        assert!(!locks.has_locks("tenant_a"));
+        locks
+            .acquire("shard".to_string(), lock_request("tenant_a", Some(2)))
+            .unwrap();
+        assert!(locks.has_locks("tenant_a"));
+        assert!(!locks.has_locks("tenant_b"));
+    }
+
+    #[test]
+    fn test_expired_lock() {
+        let mut locks = KeyedLocks::default();
+        locks
+            .acquire("tenant".to_string(), lock_request("tenant_a", None))
+            .unwrap();
+        locks.locks.get_mut("tenant").unwrap().expires_at = Some(Utc::now() - Duration::seconds(1));
+
+        assert!(locks.check("tenant_a", None).is_ok());
+        assert!(locks.list().is_empty());
+    }
+}
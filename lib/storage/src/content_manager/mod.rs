@@ -5,13 +5,26 @@ use self::consensus_state::CollectionsSnapshot;
 use self::errors::StorageError;
 
 pub mod alias_mapping;
+pub mod api_tokens;
 pub mod collection_meta_ops;
 mod collections_ops;
 pub mod consensus;
 pub mod consensus_state;
 pub mod conversions;
+mod data_transfer;
+pub mod default_filters;
+pub mod encryption;
 pub mod errors;
+pub mod events;
+pub mod federated_search;
+pub mod jwt;
+pub mod keyed_locks;
+pub mod quotas;
+pub mod replication_reconciler;
+pub mod response_memory;
+pub mod search_runtime;
 pub mod shard_distribution;
+pub mod slow_queries;
 pub mod snapshots;
 pub mod toc;
 
@@ -23,6 +36,7 @@ pub mod consensus_ops {
     use raft::eraftpb::Entry as RaftEntry;
     use serde::{Deserialize, Serialize};
 
+    use crate::content_manager::api_tokens::ClientIdentity;
     use crate::content_manager::collection_meta_ops::{
         CollectionMetaOperations, SetShardReplicaState, ShardTransferOperations,
     };
@@ -31,10 +45,73 @@ pub mod consensus_ops {
     #[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Hash, Clone)]
     pub enum ConsensusOperations {
         CollectionMeta(Box<CollectionMetaOperations>),
-        AddPeer { peer_id: PeerId, uri: String },
+        AddPeer {
+            peer_id: PeerId,
+            uri: String,
+        },
+        /// Add peer, which stays a learner: it follows the consensus, but never votes
+        AddLearner {
+            peer_id: PeerId,
+            uri: String,
+        },
         RemovePeer(PeerId),
     }
 
+    /// Context of the conf change, which adds a peer.
+    /// Peers, which are promoted to voters, are added with a plain URI for compatibility.
+    #[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
+    pub struct AddPeerContext {
+        pub uri: String,
+        /// Peer is never promoted to a voter
+        pub learner: bool,
+    }
+
+    impl AddPeerContext {
+        pub fn encode(&self) -> Vec<u8> {
+            if self.learner {
+                serde_json::to_vec(self).expect("Can't serialize peer context")
+            } else {
+                self.uri.clone().into_bytes()
+            }
+        }
+
+        pub fn decode(context: &[u8]) -> Self {
+            serde_json::from_slice(context).unwrap_or_else(|_| AddPeerContext {
+                uri: String::from_utf8_lossy(context).into_owned(),
+                learner: false,
+            })
+        }
+    }
+
+    /// Operation, proposed by this peer, with the client, which requested it
+    #[derive(Debug)]
+    pub struct ConsensusProposal {
+        pub operation: ConsensusOperations,
+        /// Not known for internal operations and if authentication is disabled
+        pub identity: Option<ClientIdentity>,
+    }
+
+    /// Context of the normal entry, proposed by a client
+    #[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
+    pub struct EntryContext {
+        /// Peer, which accepted the request
+        pub proposer: PeerId,
+        /// Client, which requested the operation
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub identity: Option<ClientIdentity>,
+    }
+
+    impl EntryContext {
+        pub fn encode(&self) -> Vec<u8> {
+            serde_json::to_vec(self).expect("Can't serialize entry context")
+        }
+
+        /// Entries, proposed by older versions, have no context
+        pub fn decode(context: &[u8]) -> Option<Self> {
+            serde_json::from_slice(context).ok()
+        }
+    }
+
     impl TryFrom<&RaftEntry> for ConsensusOperations {
         type Error = serde_cbor::Error;
 
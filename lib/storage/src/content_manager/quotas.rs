@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use collection::operations::types::CollectionUsage;
+use collection::shards::CollectionId;
+use parking_lot::Mutex;
+
+use crate::content_manager::errors::StorageError;
+use crate::types::UsageQuota;
+
+/// Usage of a collection is measured at most once per interval,
+/// as measuring of the disk usage walks through all files of the collection
+const USAGE_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Recently measured usage of the collections
+#[derive(Debug, Default)]
+pub struct UsageCache {
+    usages: Mutex<HashMap<CollectionId, (Instant, CollectionUsage)>>,
+}
+
+impl UsageCache {
+    /// Usage of the collection, if it was measured recently
+    pub fn get(&self, collection_name: &str) -> Option<CollectionUsage> {
+        self.usages
+            .lock()
+            .get(collection_name)
+            .filter(|(measured_at, _)| measured_at.elapsed() < USAGE_REFRESH_INTERVAL)
+            .map(|(_, usage)| *usage)
+    }
+
+    pub fn insert(&self, collection_name: CollectionId, usage: CollectionUsage) {
+        self.usages
+            .lock()
+            .insert(collection_name, (Instant::now(), usage));
+    }
+
+    pub fn remove(&self, collection_name: &str) {
+        self.usages.lock().remove(collection_name);
+    }
+}
+
+/// Returns `Forbidden` error, if the usage of `subject` exceeds any of the quota limits
+pub fn check_usage(
+    quota: &UsageQuota,
+    usage: &CollectionUsage,
+    subject: &str,
+) -> Result<(), StorageError> {
+    let forbidden = |resource: &str, used: u64, limit: u64| StorageError::Forbidden {
+        description: format!(
+            "{subject} exceeds the quota: {used} {resource} used, limit is {limit}. \
+             Delete some points to continue writing"
+        ),
+    };
+
+    if let Some(max_points) = quota.max_points {
+        if usage.points_count >= max_points {
+            return Err(forbidden(
+                "points",
+                usage.points_count as u64,
+                max_points as u64,
+            ));
+        }
+    }
+    if let Some(max_disk_bytes) = quota.max_disk_bytes {
+        if usage.disk_bytes >= max_disk_bytes {
+            return Err(forbidden("bytes of disk", usage.disk_bytes, max_disk_bytes));
+        }
+    }
+    if let Some(max_ram_bytes) = quota.max_ram_bytes {
+        if usage.ram_bytes >= max_ram_bytes {
+            return Err(forbidden("bytes of RAM", usage.ram_bytes, max_ram_bytes));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_usage() {
+        let usage = CollectionUsage {
+            points_count: 100,
+            disk_bytes: 1000,
+            ram_bytes: 500,
+        };
+
+        assert!(check_usage(&UsageQuota::default(), &usage, "Collection test").is_ok());
+
+        let quota = UsageQuota {
+            max_points: Some(101),
+            max_disk_bytes: Some(2000),
+            max_ram_bytes: Some(501),
+        };
+        assert!(check_usage(&quota, &usage, "Collection test").is_ok());
+
+        let quota = UsageQuota {
+            max_points: Some(100),
+            ..quota
+        };
+        let err = check_usage(&quota, &usage, "Collection test").unwrap_err();
+        assert!(matches!(err, StorageError::Forbidden { .. }));
+
+        let quota = UsageQuota {
+            max_disk_bytes: Some(1000),
+            ..Default::default()
+        };
+        assert!(check_usage(&quota, &usage, "Collection test").is_err());
+    }
+
+    #[test]
+    fn test_usage_cache() {
+        let cache = UsageCache::default();
+        assert!(cache.get("test").is_none());
+
+        let usage = CollectionUsage {
+            points_count: 1,
+            ..Default::default()
+        };
+        cache.insert("test".to_string(), usage);
+        assert_eq!(cache.get("test"), Some(usage));
+
+        cache.remove("test");
+        assert!(cache.get("test").is_none());
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::{HashMap, HashSet};
+
+use collection::collection_state::ShardInfo;
+use collection::shards::replica_set::ReplicaState;
+use collection::shards::shard::{PeerId, ShardId};
+use collection::shards::transfer::shard_transfer::ShardTransfer;
+
+/// Transfers, which bring the number of active replicas of the collection shards
+/// up to the `replication_factor`.
+///
+/// `live_peers` - peers, which are reachable and may be sources or targets of the transfers.
+///
+/// At most one transfer is suggested for each shard per call. Shards with ongoing transfers
+/// are skipped, as they are already being replicated, recovered or moved.
+/// Peers with a dead replica of the shard are preferred as targets, then the least loaded peers.
+pub fn suggest_replications(
+    replication_factor: usize,
+    shards: &HashMap<ShardId, ShardInfo>,
+    current_transfers: &HashSet<ShardTransfer>,
+    live_peers: &[PeerId],
+) -> Vec<ShardTransfer> {
+    let desired_replicas = replication_factor.min(live_peers.len());
+
+    // Number of replicas of this collection on each peer, to spread new replicas evenly
+    let mut peer_load: HashMap<PeerId, usize> =
+        live_peers.iter().map(|peer_id| (*peer_id, 0)).collect();
+    for shard_info in shards.values() {
+        for peer_id in shard_info.replicas.keys() {
+            if let Some(load) = peer_load.get_mut(peer_id) {
+                *load += 1;
+            }
+        }
+    }
+
+    let mut shard_ids: Vec<_> = shards.keys().copied().collect();
+    shard_ids.sort_unstable();
+
+    let mut transfers = Vec::new();
+    for shard_id in shard_ids {
+        if current_transfers
+            .iter()
+            .any(|transfer| transfer.shard_id == shard_id)
+        {
+            continue;
+        }
+        let replicas = &shards[&shard_id].replicas;
+        let active_replicas = replicas
+            .values()
+            .filter(|state| **state == ReplicaState::Active)
+            .count();
+        if active_replicas >= desired_replicas {
+            continue;
+        }
+
+        let source = live_peers
+            .iter()
+            .filter(|peer_id| replicas.get(peer_id) == Some(&ReplicaState::Active))
+            .min();
+        let source = match source {
+            Some(source) => *source,
+            // No live replicas to copy the shard from
+            None => continue,
+        };
+
+        let target = live_peers
+            .iter()
+            .filter(|peer_id| match replicas.get(peer_id) {
+                None | Some(ReplicaState::Dead) | Some(ReplicaState::Partial) => true,
+                Some(ReplicaState::Active) | Some(ReplicaState::Listener) => false,
+            })
+            .min_by_key(|peer_id| {
+                let has_replica = replicas.contains_key(peer_id);
+                (!has_replica, peer_load[peer_id], **peer_id)
+            });
+        let target = match target {
+            Some(target) => *target,
+            None => continue,
+        };
+
+        if !replicas.contains_key(&target) {
+            *peer_load.get_mut(&target).unwrap() += 1;
+        }
+        transfers.push(ShardTransfer {
+            shard_id,
+            from: source,
+            to: target,
+            sync: true,
+            method: None,
+            max_points_per_second: None,
+        });
+    }
+    transfers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shards(replicas: &[(ShardId, &[(PeerId, ReplicaState)])]) -> HashMap<ShardId, ShardInfo> {
+        replicas
+            .iter()
+            .map(|(shard_id, replicas)| {
+                let replicas = replicas.iter().copied().collect();
+                (*shard_id, ShardInfo { replicas })
+            })
+            .collect()
+    }
+
+    fn transfer(shard_id: ShardId, from: PeerId, to: PeerId) -> ShardTransfer {
+        ShardTransfer {
+            shard_id,
+            from,
+            to,
+            sync: true,
+            method: None,
+            max_points_per_second: None,
+        }
+    }
+
+    #[test]
+    fn test_replicate_to_new_peers() {
+        let shards = shards(&[
+            (0, &[(1, ReplicaState::Active)]),
+            (1, &[(1, ReplicaState::Active), (2, ReplicaState::Active)]),
+        ]);
+        let transfers = suggest_replications(2, &shards, &HashSet::new(), &[1, 2, 3]);
+        // Peer 3 holds no replicas yet, so it is less loaded than peer 2
+        assert_eq!(transfers, vec![transfer(0, 1, 3)]);
+
+        // Replication factor can't be reached without more peers
+        let transfers = suggest_replications(3, &shards, &HashSet::new(), &[1, 2]);
+        assert_eq!(transfers, vec![transfer(0, 1, 2)]);
+    }
+
+    #[test]
+    fn test_recover_dead_replicas() {
+        let shards = shards(&[(0, &[(1, ReplicaState::Active), (2, ReplicaState::Dead)])]);
+        let transfers = suggest_replications(2, &shards, &HashSet::new(), &[1, 2, 3]);
+        assert_eq!(transfers, vec![transfer(0, 1, 2)]);
+
+        // Unreachable peers are neither sources nor targets
+        let transfers = suggest_replications(2, &shards, &HashSet::new(), &[1, 3]);
+        assert_eq!(transfers, vec![transfer(0, 1, 3)]);
+        let transfers = suggest_replications(2, &shards, &HashSet::new(), &[2, 3]);
+        assert!(transfers.is_empty());
+    }
+
+    #[test]
+    fn test_skip_shards_in_transfer() {
+        let shards = shards(&[(0, &[(1, ReplicaState::Active), (2, ReplicaState::Partial)])]);
+        let current_transfers = HashSet::from([transfer(0, 1, 2)]);
+        let transfers = suggest_replications(2, &shards, &current_transfers, &[1, 2, 3]);
+        assert!(transfers.is_empty());
+    }
+}
@@ -0,0 +1,114 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crate::content_manager::errors::StorageError;
+
+/// Soft limit on the memory, held by responses of in-flight read requests.
+/// Memory is estimated from the request before it is executed, so pathological requests
+/// (e.g. large scrolls `with_vector`) are rejected before they allocate anything.
+#[derive(Debug)]
+pub struct ResponseMemoryLimiter {
+    /// Limit in bytes. If `None` - memory is not tracked
+    limit: Option<usize>,
+    used: Arc<AtomicUsize>,
+}
+
+/// Memory, reserved for a single response. Released on drop
+#[derive(Debug)]
+pub struct ResponseMemoryPermit {
+    used: Option<Arc<AtomicUsize>>,
+    size: usize,
+}
+
+impl Drop for ResponseMemoryPermit {
+    fn drop(&mut self) {
+        if let Some(used) = &self.used {
+            used.fetch_sub(self.size, Ordering::AcqRel);
+        }
+    }
+}
+
+impl ResponseMemoryLimiter {
+    pub fn new(limit_mb: Option<usize>) -> Self {
+        Self {
+            limit: limit_mb.map(|limit_mb| limit_mb * 1024 * 1024),
+            used: Default::default(),
+        }
+    }
+
+    pub fn is_limited(&self) -> bool {
+        self.limit.is_some()
+    }
+
+    /// Estimated memory, currently held by in-flight responses
+    pub fn used(&self) -> usize {
+        self.used.load(Ordering::Acquire)
+    }
+
+    /// Reserve memory for the response of the request.
+    /// A single request is always admitted if nothing else is in flight, even if it exceeds the limit,
+    /// so that large requests are slowed down instead of being rejected forever.
+    pub fn try_acquire(&self, size: usize) -> Result<ResponseMemoryPermit, StorageError> {
+        let limit = match self.limit {
+            None => return Ok(ResponseMemoryPermit::unlimited()),
+            Some(limit) => limit,
+        };
+
+        self.used
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |used| {
+                (used == 0 || used + size <= limit).then_some(used + size)
+            })
+            .map_err(|used| StorageError::Overloaded {
+                description: format!(
+                    "Responses of in-flight requests take ~{used} bytes, \
+                     request for ~{size} bytes exceeds the limit of {limit} bytes. Retry later"
+                ),
+            })?;
+
+        Ok(ResponseMemoryPermit {
+            used: Some(self.used.clone()),
+            size,
+        })
+    }
+}
+
+impl ResponseMemoryPermit {
+    /// Permit, which does not hold any memory
+    pub fn unlimited() -> Self {
+        Self {
+            used: None,
+            size: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_response_memory_limit() {
+        let limiter = ResponseMemoryLimiter::new(Some(1));
+        let limit = 1024 * 1024;
+
+        let first = limiter.try_acquire(limit / 2).unwrap();
+        let second = limiter.try_acquire(limit / 2).unwrap();
+        assert_eq!(limiter.used(), limit);
+
+        let err = limiter.try_acquire(1).unwrap_err();
+        assert!(matches!(err, StorageError::Overloaded { .. }));
+
+        drop(first);
+        drop(second);
+        assert_eq!(limiter.used(), 0);
+
+        // Single request is admitted, even if larger than the limit
+        let large = limiter.try_acquire(limit * 2).unwrap();
+        assert!(limiter.try_acquire(1).is_err());
+        drop(large);
+
+        let unlimited = ResponseMemoryLimiter::new(None);
+        unlimited.try_acquire(limit * 10).unwrap();
+        assert_eq!(unlimited.used(), 0);
+    }
+}
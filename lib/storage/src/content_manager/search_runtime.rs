@@ -0,0 +1,137 @@
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use parking_lot::RwLock;
+use tokio::runtime::{Handle, Runtime};
+
+/// How often the replaced runtime is checked for the searches, which are still running on it
+const RETIRED_RUNTIME_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+/// Time, given to the tasks of the replaced runtime to finish, once no searches are using it
+const RETIRED_RUNTIME_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Resolve number of search threads. If 0 - use all CPUs except one.
+pub fn search_threads(max_search_threads: usize) -> usize {
+    if max_search_threads == 0 {
+        std::cmp::max(1, num_cpus::get().saturating_sub(1))
+    } else {
+        max_search_threads
+    }
+}
+
+/// Runtime for searches, which could be replaced by a runtime with another number of threads
+/// without restarting the service.
+///
+/// The initial runtime also runs the API and background tasks of the service, so it is never
+/// stopped. Replacing runtimes are stopped, once the searches, which are still running on them,
+/// are finished.
+pub struct SearchRuntime {
+    initial: Runtime,
+    initial_threads: usize,
+    resized: RwLock<Option<(Arc<Runtime>, usize)>>,
+}
+
+/// Keeps the runtime alive, while the search is running on it
+pub enum SearchRuntimeGuard<'a> {
+    Initial(&'a Runtime),
+    Resized(Arc<Runtime>),
+}
+
+impl SearchRuntimeGuard<'_> {
+    pub fn handle(&self) -> &Handle {
+        match self {
+            SearchRuntimeGuard::Initial(runtime) => runtime.handle(),
+            SearchRuntimeGuard::Resized(runtime) => runtime.handle(),
+        }
+    }
+}
+
+impl SearchRuntime {
+    pub fn new(initial: Runtime, threads: usize) -> Self {
+        Self {
+            initial,
+            initial_threads: threads,
+            resized: Default::default(),
+        }
+    }
+
+    /// Runtime for the next search
+    pub fn get(&self) -> SearchRuntimeGuard {
+        match &*self.resized.read() {
+            None => SearchRuntimeGuard::Initial(&self.initial),
+            Some((runtime, _)) => SearchRuntimeGuard::Resized(runtime.clone()),
+        }
+    }
+
+    /// Number of threads of the runtime for the next search
+    pub fn threads(&self) -> usize {
+        match &*self.resized.read() {
+            None => self.initial_threads,
+            Some((_, threads)) => *threads,
+        }
+    }
+
+    /// Run the next searches on the `runtime` with the given number of threads.
+    /// Searches, which are already running, are finished on the previous runtime.
+    pub fn replace(&self, runtime: Runtime, threads: usize) {
+        let previous = self.resized.write().replace((Arc::new(runtime), threads));
+        if let Some((previous, _)) = previous {
+            retire(previous);
+        }
+    }
+}
+
+/// Stop the runtime in background, once it is no longer used by searches.
+/// Runtime can't be dropped in async context, so it is dropped by a dedicated thread.
+fn retire(runtime: Arc<Runtime>) {
+    thread::Builder::new()
+        .name("search-retire".to_string())
+        .spawn(move || {
+            let mut runtime = runtime;
+            loop {
+                match Arc::try_unwrap(runtime) {
+                    Ok(runtime) => {
+                        runtime.shutdown_timeout(RETIRED_RUNTIME_SHUTDOWN_TIMEOUT);
+                        return;
+                    }
+                    Err(used_runtime) => {
+                        runtime = used_runtime;
+                        thread::sleep(RETIRED_RUNTIME_CHECK_INTERVAL);
+                    }
+                }
+            }
+        })
+        .expect("Can't spawn thread to stop the replaced search runtime");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn runtime(threads: usize) -> Runtime {
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(threads)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_replace_search_runtime() {
+        let search_runtime = SearchRuntime::new(runtime(1), 1);
+        assert!(matches!(
+            search_runtime.get(),
+            SearchRuntimeGuard::Initial(_)
+        ));
+        assert_eq!(search_runtime.threads(), 1);
+
+        search_runtime.replace(runtime(2), 2);
+        assert_eq!(search_runtime.threads(), 2);
+        let guard = search_runtime.get();
+        assert_eq!(guard.handle().block_on(async { 42 }), 42);
+
+        // Runtime, used by the guard, is kept alive after replacing it
+        search_runtime.replace(runtime(3), 3);
+        assert_eq!(search_runtime.threads(), 3);
+        assert_eq!(guard.handle().block_on(async { 42 }), 42);
+    }
+}
@@ -1,5 +1,5 @@
 use std::cmp::{self, Reverse};
-use std::collections::BinaryHeap;
+use std::collections::{BinaryHeap, HashMap};
 use std::num::NonZeroU32;
 
 use collection::shards::collection_shard_distribution::CollectionShardDistribution;
@@ -14,9 +14,9 @@ struct PeerShardCount {
 }
 
 impl PeerShardCount {
-    fn new(peer_id: PeerId) -> Self {
+    fn new(peer_id: PeerId, shard_count: usize) -> Self {
         Self {
-            shard_count: 0,
+            shard_count,
             peer_id,
         }
     }
@@ -34,17 +34,21 @@ pub struct ShardDistributionProposal {
 
 impl ShardDistributionProposal {
     /// Builds a proposal for the distribution of shards.
-    /// It will propose to allocate shards so that all peers have the same number of shards of this collection  at the end.
+    /// It will propose to allocate shards so that all peers have the same number of shards at the end.
+    /// `peer_loads` - number of shard replicas of other collections, already placed on each peer,
+    /// so new collections land on the least loaded peers first. Missing peers are considered empty.
     pub fn new(
         shard_number: NonZeroU32,
         replication_factor: NonZeroU32,
         known_peers: &[PeerId],
+        peer_loads: &HashMap<PeerId, usize>,
     ) -> Self {
         // min number of shard_count on top to make this a min-heap
         let mut min_heap: BinaryHeap<Reverse<PeerShardCount>> =
             BinaryHeap::with_capacity(known_peers.len());
         for peer in known_peers {
-            min_heap.push(Reverse(PeerShardCount::new(*peer)));
+            let shard_count = peer_loads.get(peer).copied().unwrap_or(0);
+            min_heap.push(Reverse(PeerShardCount::new(*peer, shard_count)));
         }
 
         let mut distribution = Vec::with_capacity(shard_number.get() as usize);
@@ -52,13 +56,16 @@ impl ShardDistributionProposal {
         let n_replicas = cmp::min(replication_factor.get() as usize, known_peers.len());
 
         for shard_id in 0..shard_number.get() {
-            let mut replicas = Vec::new();
+            // Peers are taken out of the heap until all replicas are placed,
+            // so a much less loaded peer does not get several replicas of the same shard
+            let mut selected_peers = Vec::with_capacity(n_replicas);
             for _replica in 0..n_replicas {
-                let mut least_loaded_peer = min_heap.peek_mut().unwrap();
-                let selected_peer = least_loaded_peer.0.peer_id;
-                least_loaded_peer.0.inc_shard_count();
-                replicas.push(selected_peer);
+                let Reverse(mut least_loaded_peer) = min_heap.pop().unwrap();
+                least_loaded_peer.inc_shard_count();
+                selected_peers.push(least_loaded_peer);
             }
+            let replicas = selected_peers.iter().map(|peer| peer.peer_id).collect();
+            min_heap.extend(selected_peers.into_iter().map(Reverse));
             distribution.push((shard_id, replicas))
         }
 
@@ -111,6 +118,7 @@ mod tests {
             NonZeroU32::new(6).unwrap(),
             NonZeroU32::new(1).unwrap(),
             &known_peers,
+            &HashMap::new(),
         );
 
         // Check it distribution is as even as possible
@@ -131,4 +139,34 @@ mod tests {
         assert_eq!(shard_counts.iter().min(), Some(&1));
         assert_eq!(shard_counts.iter().max(), Some(&2));
     }
+
+    #[test]
+    fn test_distribution_with_peer_loads() {
+        let known_peers = vec![1, 2, 3, 4];
+        let peer_loads = HashMap::from([(1, 4), (2, 4), (3, 1)]);
+        let distribution = ShardDistributionProposal::new(
+            NonZeroU32::new(4).unwrap(),
+            NonZeroU32::new(2).unwrap(),
+            &known_peers,
+            &peer_loads,
+        );
+
+        let mut shard_counts: HashMap<PeerId, usize> = HashMap::new();
+        for (_shard_id, peers) in &distribution.distribution {
+            // Replicas of the same shard are always on different peers
+            assert_eq!(peers.len(), 2);
+            assert_ne!(peers[0], peers[1]);
+            for peer_id in peers {
+                *shard_counts.entry(*peer_id).or_default() += 1;
+            }
+        }
+
+        // Empty and less loaded peers are filled up first
+        assert_eq!(shard_counts.get(&4), Some(&4));
+        assert_eq!(shard_counts.get(&3), Some(&3));
+        assert_eq!(
+            shard_counts.get(&1).unwrap_or(&0) + shard_counts.get(&2).unwrap_or(&0),
+            1
+        );
+    }
 }
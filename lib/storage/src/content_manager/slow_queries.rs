@@ -0,0 +1,140 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use collection::shards::shard::ShardId;
+use collection::shards::CollectionId;
+use parking_lot::Mutex;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// Max number of slow queries, kept for each collection. Older queries are dropped first.
+pub const SLOW_QUERY_LOG_CAPACITY: usize = 100;
+
+/// Search or recommendation, which took longer than the configured threshold
+#[derive(Debug, Serialize, JsonSchema, Clone)]
+pub struct SlowQuery {
+    pub timestamp: DateTime<Utc>,
+    /// Kind of the request: `search`, `search_batch`, `recommend` or `recommend_batch`
+    pub method: String,
+    /// Body of the request, with the default filter of the collection applied
+    pub request: serde_json::Value,
+    pub timings: SlowQueryTimings,
+    /// Shards, which were queried on this peer
+    pub shards: Vec<ShardId>,
+    /// Error message, if the request has failed
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, JsonSchema, Clone, Copy, PartialEq)]
+pub struct SlowQueryTimings {
+    pub total_ms: f64,
+    /// Applying of the default filter and reservation of the response memory
+    pub preparation_ms: f64,
+    /// Querying of the shards and merging of the results
+    pub execution_ms: f64,
+}
+
+impl SlowQueryTimings {
+    pub fn new(preparation: Duration, total: Duration) -> Self {
+        Self {
+            total_ms: total.as_secs_f64() * 1000.0,
+            preparation_ms: preparation.as_secs_f64() * 1000.0,
+            execution_ms: total.saturating_sub(preparation).as_secs_f64() * 1000.0,
+        }
+    }
+}
+
+/// Recent slow queries of each collection, kept in memory only
+#[derive(Debug, Default)]
+pub struct SlowQueryLog {
+    /// If not set - queries are not recorded
+    threshold: Option<Duration>,
+    queries: Mutex<HashMap<CollectionId, VecDeque<SlowQuery>>>,
+}
+
+impl SlowQueryLog {
+    pub fn new(threshold_ms: Option<u64>) -> Self {
+        Self {
+            threshold: threshold_ms.map(Duration::from_millis),
+            queries: Default::default(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.threshold.is_some()
+    }
+
+    pub fn is_slow(&self, duration: Duration) -> bool {
+        self.threshold
+            .map_or(false, |threshold| duration >= threshold)
+    }
+
+    pub fn record(&self, collection_name: CollectionId, query: SlowQuery) {
+        let mut queries = self.queries.lock();
+        let collection_queries = queries.entry(collection_name).or_default();
+        if collection_queries.len() >= SLOW_QUERY_LOG_CAPACITY {
+            collection_queries.pop_front();
+        }
+        collection_queries.push_back(query);
+    }
+
+    /// Recorded slow queries of the collection, from the oldest to the newest
+    pub fn get(&self, collection_name: &str) -> Vec<SlowQuery> {
+        self.queries
+            .lock()
+            .get(collection_name)
+            .map(|queries| queries.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn remove(&self, collection_name: &str) {
+        self.queries.lock().remove(collection_name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slow_query(method: &str) -> SlowQuery {
+        SlowQuery {
+            timestamp: Utc::now(),
+            method: method.to_string(),
+            request: serde_json::json!({ "limit": 10 }),
+            timings: SlowQueryTimings::new(Duration::from_millis(1), Duration::from_millis(3)),
+            shards: vec![0],
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_slow_query_log() {
+        let disabled = SlowQueryLog::new(None);
+        assert!(!disabled.is_enabled());
+        assert!(!disabled.is_slow(Duration::from_secs(100)));
+
+        let log = SlowQueryLog::new(Some(100));
+        assert!(log.is_slow(Duration::from_millis(100)));
+        assert!(!log.is_slow(Duration::from_millis(99)));
+
+        for i in 0..SLOW_QUERY_LOG_CAPACITY + 5 {
+            log.record("test".to_string(), slow_query(&i.to_string()));
+        }
+        let queries = log.get("test");
+        assert_eq!(queries.len(), SLOW_QUERY_LOG_CAPACITY);
+        assert_eq!(queries.first().unwrap().method, "5");
+        assert!(log.get("other").is_empty());
+
+        log.remove("test");
+        assert!(log.get("test").is_empty());
+    }
+
+    #[test]
+    fn test_slow_query_timings() {
+        let timings = SlowQueryTimings::new(Duration::from_millis(1), Duration::from_millis(3));
+        assert_eq!(timings.total_ms, 3.0);
+        assert_eq!(timings.preparation_ms, 1.0);
+        assert_eq!(timings.execution_ms, 2.0);
+    }
+}
@@ -1,16 +1,27 @@
 use std::collections::HashMap;
+use std::ffi::OsStr;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
+use chrono::{DateTime, Utc};
 use collection::operations::snapshot_ops::{
-    get_snapshot_description, list_snapshots_in_directory, SnapshotDescription,
+    file_checksum, get_snapshot_description, list_snapshots_in_directory, SnapshotDescription,
+    SnapshotManifest, SNAPSHOT_MANIFEST_FILE,
 };
 use serde::{Deserialize, Serialize};
 use tar::Builder as TarBuilder;
 use tokio::io::AsyncWriteExt;
+use tokio::sync::OwnedMutexGuard;
 
-use crate::content_manager::toc::FULL_SNAPSHOT_FILE_NAME;
+use crate::content_manager::consensus_state::SnapshotData;
+use crate::content_manager::toc::{FULL_SNAPSHOT_FILE_NAME, SNAPSHOTS_TMP_DIR};
 use crate::{StorageError, TableOfContent};
 
+/// Name of the file with snapshot-to-collection mapping inside the full snapshot archive
+pub const FULL_SNAPSHOT_CONFIG_FILE: &str = "config.json";
+/// Name of the file with consensus metadata inside the full snapshot archive
+pub const FULL_SNAPSHOT_CONSENSUS_FILE: &str = "consensus.json";
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SnapshotConfig {
     /// Map collection name to snapshot file name
@@ -25,7 +36,9 @@ pub async fn get_full_snapshot_path(
     snapshot_name: &str,
 ) -> Result<PathBuf, StorageError> {
     let snapshot_path = Path::new(toc.snapshots_path()).join(snapshot_name);
-    if !snapshot_path.exists() {
+    // Only files in the root of the snapshots directory are full snapshots
+    let is_plain_name = Path::new(snapshot_name).file_name() == Some(OsStr::new(snapshot_name));
+    if !is_plain_name || !snapshot_path.is_file() {
         return Err(StorageError::NotFound {
             description: format!("Snapshot {} not found", snapshot_name),
         });
@@ -40,20 +53,79 @@ pub async fn do_list_full_snapshots(
     Ok(list_snapshots_in_directory(snapshots_path).await?)
 }
 
+pub async fn do_delete_full_snapshot(
+    toc: &TableOfContent,
+    snapshot_name: &str,
+) -> Result<bool, StorageError> {
+    let snapshot_path = get_full_snapshot_path(toc, snapshot_name).await?;
+    tokio::fs::remove_file(snapshot_path).await?;
+    Ok(true)
+}
+
+/// Create snapshot of all collections, aliases and consensus metadata of the running node.
+/// API tokens of the collections are not included.
+///
+/// Each collection is locked for writes while its snapshot is being created.
+/// Archive is assembled in the temporary directory inside the storage and only then moved
+/// into the snapshots directory, so incomplete snapshots are never listed.
 pub async fn do_create_full_snapshot(
     toc: &TableOfContent,
+) -> Result<SnapshotDescription, StorageError> {
+    let full_snapshot_guard = toc.lock_full_snapshot()?;
+    create_full_snapshot(toc, full_snapshot_guard, Utc::now()).await
+}
+
+/// Start creation of the full snapshot in background.
+///
+/// Fails immediately if another full snapshot is in progress. Otherwise returns description of
+/// the snapshot, which is being created. Its size is unknown until the creation is finished,
+/// so it is reported as 0. The snapshot appears in the list of snapshots once it is complete.
+pub fn do_create_full_snapshot_in_background(
+    toc: Arc<TableOfContent>,
+) -> Result<SnapshotDescription, StorageError> {
+    let full_snapshot_guard = toc.lock_full_snapshot()?;
+    let creation_time = Utc::now();
+    let snapshot_name = full_snapshot_name(creation_time);
+    tokio::spawn(async move {
+        if let Err(err) = create_full_snapshot(&toc, full_snapshot_guard, creation_time).await {
+            log::error!("Failed to create full snapshot: {err}");
+        }
+    });
+    Ok(SnapshotDescription {
+        name: snapshot_name,
+        creation_time: Some(creation_time.naive_utc()),
+        size: 0,
+    })
+}
+
+fn full_snapshot_name(creation_time: DateTime<Utc>) -> String {
+    let current_time = creation_time.format("%Y-%m-%d-%H-%M-%S");
+    format!("{FULL_SNAPSHOT_FILE_NAME}-{current_time}.snapshot")
+}
+
+async fn create_full_snapshot(
+    toc: &TableOfContent,
+    _full_snapshot_guard: OwnedMutexGuard<()>,
+    creation_time: DateTime<Utc>,
 ) -> Result<SnapshotDescription, StorageError> {
     let snapshot_dir = Path::new(toc.snapshots_path());
+    let tmp_dir = Path::new(toc.storage_path()).join(SNAPSHOTS_TMP_DIR);
+    tokio::fs::create_dir_all(&tmp_dir).await?;
 
     let all_collections = toc.all_collections().await;
     let mut created_snapshots: Vec<(&str, SnapshotDescription)> = vec![];
     for collection_name in &all_collections {
-        let snapshot_details = toc.create_snapshot(collection_name).await?;
-        created_snapshots.push((collection_name, snapshot_details));
+        match toc.create_locked_snapshot(collection_name).await {
+            Ok(snapshot_details) => created_snapshots.push((collection_name, snapshot_details)),
+            // Collection was deleted while snapshots of other collections were created
+            Err(StorageError::NotFound { .. }) => {
+                log::warn!("Collection {collection_name} was removed during full snapshot");
+            }
+            Err(err) => return Err(err),
+        }
     }
-    let current_time = chrono::Utc::now().format("%Y-%m-%d-%H-%M-%S").to_string();
-
-    let snapshot_name = format!("{}-{}.snapshot", FULL_SNAPSHOT_FILE_NAME, &current_time);
+    let current_time = creation_time.format("%Y-%m-%d-%H-%M-%S").to_string();
+    let snapshot_name = full_snapshot_name(creation_time);
 
     let collection_name_to_snapshot_path: HashMap<_, _> = created_snapshots
         .iter()
@@ -63,45 +135,89 @@ pub async fn do_create_full_snapshot(
         .collect();
 
     let mut alias_mapping: HashMap<String, String> = Default::default();
-    for collection_name in &all_collections {
+    for (collection_name, _) in &created_snapshots {
         for alias in toc.collection_aliases(collection_name).await? {
             alias_mapping.insert(alias.to_string(), collection_name.to_string());
         }
     }
 
-    let config_path = snapshot_dir.join(format!("config-{current_time}.json"));
-
+    let config_path = tmp_dir.join(format!("config-{current_time}.json"));
     {
         let snapshot_config = SnapshotConfig {
             collections_mapping: collection_name_to_snapshot_path,
             collections_aliases: alias_mapping,
         };
-        let mut config_file = tokio::fs::File::create(&config_path).await?;
-        config_file
-            .write_all(
-                serde_json::to_string_pretty(&snapshot_config)
-                    .unwrap()
-                    .as_bytes(),
-            )
-            .await?;
+        write_json(&config_path, &snapshot_config).await?;
+    }
+
+    let consensus_path = tmp_dir.join(format!("consensus-{current_time}.json"));
+    {
+        let mut collections_data = toc.collections_snapshot().await;
+        // API tokens are credentials, so they are not stored in plain text in snapshots.
+        // Tokens of the recovered collections should be issued again.
+        collections_data.api_tokens = Default::default();
+        let consensus_data = SnapshotData {
+            collections_data,
+            address_by_id: toc.peer_address_by_id(),
+            permanent_learners: Default::default(),
+        };
+        write_json(&consensus_path, &consensus_data).await?;
+    }
+
+    // Files of the archive by their names in it
+    let mut archive_files: Vec<(PathBuf, String)> = created_snapshots
+        .into_iter()
+        .map(|(collection_name, snapshot_details)| {
+            let snapshot_path = snapshot_dir
+                .join(collection_name)
+                .join(&snapshot_details.name);
+            (snapshot_path, snapshot_details.name)
+        })
+        .collect();
+    archive_files.push((config_path, FULL_SNAPSHOT_CONFIG_FILE.to_string()));
+    archive_files.push((consensus_path, FULL_SNAPSHOT_CONSENSUS_FILE.to_string()));
+
+    let manifest_path = tmp_dir.join(format!("manifest-{current_time}.json"));
+    {
+        let mut manifest = SnapshotManifest::default();
+        for (path, name) in &archive_files {
+            manifest
+                .checksums
+                .insert(name.clone(), file_checksum(path)?);
+        }
+        manifest.save(&manifest_path)?;
     }
 
     let full_snapshot_path = snapshot_dir.join(&snapshot_name);
+    let full_snapshot_path_tmp = full_snapshot_path.with_extension("tmp");
+    let full_snapshot_path_arc = tmp_dir.join(&snapshot_name).with_extension("arc");
     // have to use std here, cause TarBuilder is not async
-    let file = std::fs::File::create(&full_snapshot_path)?;
+    let file = std::fs::File::create(&full_snapshot_path_arc)?;
     let mut builder = TarBuilder::new(file);
-    for (collection_name, snapshot_details) in created_snapshots {
-        let snapshot_path = snapshot_dir
-            .join(collection_name)
-            .join(&snapshot_details.name);
-        builder.append_path_with_name(&snapshot_path, &snapshot_details.name)?;
-        tokio::fs::remove_file(snapshot_path).await?;
+    // Manifest goes first, so a truncated archive is detected on recovery
+    builder.append_path_with_name(&manifest_path, SNAPSHOT_MANIFEST_FILE)?;
+    for (path, name) in &archive_files {
+        builder.append_path_with_name(path, name)?;
     }
-    builder.append_path_with_name(&config_path, "config.json")?;
 
     builder.finish()?;
 
-    tokio::fs::remove_file(&config_path).await?;
+    tokio::fs::remove_file(&manifest_path).await?;
+    for (path, _) in archive_files {
+        tokio::fs::remove_file(path).await?;
+    }
+
+    // Snapshots directory might be on another mounting point, so copy first and then rename
+    tokio::fs::copy(&full_snapshot_path_arc, &full_snapshot_path_tmp).await?;
+    tokio::fs::rename(&full_snapshot_path_tmp, &full_snapshot_path).await?;
+    tokio::fs::remove_file(&full_snapshot_path_arc).await?;
 
     Ok(get_snapshot_description(&full_snapshot_path).await?)
 }
+
+async fn write_json<T: Serialize>(path: &Path, data: &T) -> Result<(), StorageError> {
+    let mut file = tokio::fs::File::create(path).await?;
+    file.write_all(serde_json::to_string_pretty(data).unwrap().as_bytes())
+        .await?;
+    Ok(())
+}
@@ -4,54 +4,99 @@ use std::num::NonZeroU32;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+use chrono::Utc;
 use collection::collection::{Collection, RequestShardTransfer};
 use collection::collection_state;
+use collection::common::optimization_scheduler::{
+    create_optimization_runtime, create_update_runtime, OptimizationScheduler,
+};
 use collection::config::{
     default_replication_factor, default_write_consistency_factor, CollectionConfig,
-    CollectionParams,
+    CollectionParams, WalConfig,
 };
-use collection::operations::config_diff::DiffConfig;
+use collection::jobs::JobInfo;
+use collection::operations::changes::PointsChanges;
+use collection::operations::config_diff::{DiffConfig, WalConfigDiff};
+use collection::operations::point_ops::{PointInsertOperations, WriteOrdering};
 use collection::operations::snapshot_ops::SnapshotDescription;
 use collection::operations::types::{
-    CollectionResult, CountRequest, CountResult, PointRequest, RecommendRequest,
-    RecommendRequestBatch, Record, ScrollRequest, ScrollResult, SearchRequest, SearchRequestBatch,
-    UpdateResult,
+    CollectionError, CollectionResult, CollectionUsage, CollectionVerificationReport, CountRequest,
+    CountResult, DiscoverRequest, FacetRequest, FacetResponse, PointRequest, RecommendRequest,
+    RecommendRequestBatch, Record, ReplicaSyncReport, SampleRequest, ScrollRequest, ScrollResult,
+    SearchRequest, SearchRequestBatch, ShardDiscoverRequest, UpdateResult,
 };
-use collection::operations::CollectionUpdateOperations;
+use collection::operations::{CollectionUpdateOperations, OperationWithId};
 use collection::shards::channel_service::ChannelService;
 use collection::shards::collection_shard_distribution::CollectionShardDistribution;
+use collection::shards::local_shard::{OnShardEvent, ShardEvent};
 use collection::shards::replica_set::ReplicaState;
+use collection::shards::replica_sync::PointDigest;
 use collection::shards::shard::{PeerId, ShardId};
 use collection::shards::transfer::shard_transfer::validate_transfer;
 use collection::shards::{replica_set, CollectionId};
 use collection::telemetry::CollectionTelemetry;
-use segment::types::ScoredPoint;
+use futures::future::try_join_all;
+use segment::common::disk_budget::set_global_disk_budget;
+use segment::common::encryption::StorageCipher;
+use segment::common::indexing_budget::set_global_indexing_memory_budget;
+use segment::common::mmap_advice::set_global_mmap_advice;
+use segment::types::{
+    BatchSearchResult, Filter, Order, PointIdType, ScoredPoint, SearchExplanation, SeqNumberType,
+    WithPayloadInterface, WithVector,
+};
+use serde::Serialize;
 use tokio::runtime::Runtime;
-use tokio::sync::{RwLock, RwLockReadGuard};
+use tokio::sync::{Mutex, OwnedMutexGuard, RwLock, RwLockReadGuard};
 
 use super::collection_meta_ops::{
     CreateCollectionOperation, SetShardReplicaState, ShardTransferOperations,
     UpdateCollectionOperation,
 };
 use super::{consensus_state, CollectionContainer};
-use crate::content_manager::alias_mapping::AliasPersistence;
+use crate::content_manager::alias_mapping::{AliasMapping, AliasPersistence};
+use crate::content_manager::api_tokens::{ApiToken, ApiTokensPersistence};
 use crate::content_manager::collection_meta_ops::{
     AliasOperations, ChangeAliasesOperation, CollectionMetaOperations, CreateAlias,
-    CreateAliasOperation, CreateCollection, DeleteAlias, DeleteAliasOperation, RenameAlias,
-    RenameAliasOperation, UpdateCollection,
+    CreateAliasOperation, CreateApiTokenOperation, CreateCollection, DeleteAlias,
+    DeleteAliasOperation, DeleteApiTokenOperation, RenameAlias, RenameAliasOperation,
+    SetDefaultFilterOperation, SwapAliases, SwapAliasesOperation, UpdateCollection,
 };
 use crate::content_manager::collections_ops::{Checker, Collections};
 use crate::content_manager::consensus::operation_sender::OperationSender;
+use crate::content_manager::data_transfer;
+use crate::content_manager::default_filters::{
+    merge_filters, DefaultFiltersMapping, DefaultFiltersPersistence,
+};
+use crate::content_manager::encryption::load_storage_cipher;
 use crate::content_manager::errors::StorageError;
+use crate::content_manager::events::{StateChange, StateChanges};
+use crate::content_manager::federated_search::{
+    matches_pattern, merge_results, CollectionSearchResult, FederatedScoredPoint,
+    FederatedSearchRequest, ScoreNormalization,
+};
+use crate::content_manager::keyed_locks::{KeyedLockInfo, KeyedLockRequest, KeyedLocks, LockScope};
+use crate::content_manager::quotas::{check_usage, UsageCache};
+use crate::content_manager::replication_reconciler::suggest_replications;
+use crate::content_manager::response_memory::{ResponseMemoryLimiter, ResponseMemoryPermit};
+use crate::content_manager::search_runtime::{search_threads, SearchRuntime};
 use crate::content_manager::shard_distribution::ShardDistributionProposal;
+use crate::content_manager::slow_queries::{SlowQuery, SlowQueryLog, SlowQueryTimings};
 use crate::types::{PeerAddressById, StorageConfig};
 use crate::ConsensusOperations;
 
 pub const ALIASES_PATH: &str = "aliases";
 pub const COLLECTIONS_DIR: &str = "collections";
+pub const DEFAULT_FILTERS_PATH: &str = "default_filters";
+pub const API_TOKENS_PATH: &str = "api_tokens";
 pub const SNAPSHOTS_TMP_DIR: &str = "snapshots_tmp";
 pub const FULL_SNAPSHOT_FILE_NAME: &str = "full-snapshot";
+pub const FULL_SNAPSHOT_LOCK_MESSAGE: &str = "Full storage snapshot is in progress";
+/// Safety net for the write lock of the collection, taken for the full snapshot.
+/// The lock is released once the snapshot of the collection is created, but if the task is
+/// stuck, writes into the collection become possible again after this timeout.
+const FULL_SNAPSHOT_LOCK_TTL_SEC: u64 = 60 * 60;
 pub const DEFAULT_WRITE_LOCK_ERROR_MESSAGE: &str = "Write operations are forbidden";
 
 /// The main object of the service. It holds all objects, required for proper functioning.
@@ -60,15 +105,37 @@ pub const DEFAULT_WRITE_LOCK_ERROR_MESSAGE: &str = "Write operations are forbidd
 pub struct TableOfContent {
     collections: Arc<RwLock<Collections>>,
     storage_config: StorageConfig,
-    search_runtime: Runtime,
+    search_runtime: SearchRuntime,
     collection_management_runtime: Runtime,
+    /// Runs optimizations of all collections, keeps them away from search threads
+    optimization_runtime: Runtime,
+    /// Runs update and flush workers of all collections, keeps them away from optimizations
+    update_runtime: Runtime,
+    optimization_scheduler: OptimizationScheduler,
+    /// Cipher for WALs of collections with enabled encryption, if the key is configured
+    storage_cipher: Option<StorageCipher>,
     alias_persistence: RwLock<AliasPersistence>,
+    default_filters: RwLock<DefaultFiltersPersistence>,
+    /// Tokens, which grant access to a single collection or alias
+    api_tokens: parking_lot::RwLock<ApiTokensPersistence>,
     pub this_peer_id: PeerId,
     channel_service: ChannelService,
     /// Backlink to the consensus, if none - single node mode
     consensus_proposal_sender: Option<OperationSender>,
     is_write_locked: AtomicBool,
     lock_error_message: parking_lot::Mutex<Option<String>>,
+    /// Named write locks, scoped to a single collection or shard
+    keyed_locks: parking_lot::RwLock<KeyedLocks>,
+    /// Prevents concurrent creation of full storage snapshots
+    full_snapshot_lock: Arc<Mutex<()>>,
+    /// Reports transitions of collections to subscribers
+    state_changes: StateChanges,
+    /// Sheds read requests, which responses would take too much memory
+    response_memory: ResponseMemoryLimiter,
+    /// Recently measured usage of collections, checked against the quotas
+    usage_cache: UsageCache,
+    /// Recent searches and recommendations of each collection, which exceeded the threshold
+    slow_queries: SlowQueryLog,
 }
 
 impl TableOfContent {
@@ -84,6 +151,24 @@ impl TableOfContent {
         create_dir_all(&snapshots_path).expect("Can't create Snapshots directory");
         let collections_path = Path::new(&storage_config.storage_path).join(COLLECTIONS_DIR);
         let collection_management_runtime = Runtime::new().unwrap();
+        let optimization_runtime =
+            create_optimization_runtime().expect("Can't create optimization runtime");
+        let update_runtime = create_update_runtime().expect("Can't create update runtime");
+        let optimization_scheduler = OptimizationScheduler::new(
+            optimization_runtime.handle().clone(),
+            update_runtime.handle().clone(),
+            storage_config.performance.max_optimization_threads,
+        );
+        let storage_cipher =
+            load_storage_cipher(&storage_config.encryption).expect("Can't load encryption key");
+        let state_changes = StateChanges::new();
+        // Mmap files of the collections are opened with the advice from the config
+        set_global_mmap_advice(
+            storage_config.performance.mmap_advice,
+            storage_config.performance.mmap_populate,
+        );
+        set_global_disk_budget(storage_config.performance.max_disk_searches);
+        set_global_indexing_memory_budget(storage_config.performance.indexing_memory_limit_mb);
         create_dir_all(&collections_path).expect("Can't create Collections directory");
         let collection_paths =
             read_dir(&collections_path).expect("Can't read Collections directory");
@@ -122,6 +207,8 @@ impl TableOfContent {
                 &collection_path,
                 &collection_snapshots_path,
                 channel_service.clone(),
+                optimization_scheduler.clone(),
+                storage_cipher.clone(),
                 Self::on_peer_failure_callback(
                     consensus_proposal_sender.clone(),
                     collection_name.clone(),
@@ -130,6 +217,7 @@ impl TableOfContent {
                     consensus_proposal_sender.clone(),
                     collection_name.clone(),
                 ),
+                Self::on_shard_event_callback(state_changes.clone(), collection_name.clone()),
             ));
 
             collections.insert(collection_name, collection);
@@ -137,17 +225,41 @@ impl TableOfContent {
         let alias_path = Path::new(&storage_config.storage_path).join(ALIASES_PATH);
         let alias_persistence =
             AliasPersistence::open(alias_path).expect("Can't open database by the provided config");
+        let default_filters_path =
+            Path::new(&storage_config.storage_path).join(DEFAULT_FILTERS_PATH);
+        let default_filters = DefaultFiltersPersistence::open(default_filters_path)
+            .expect("Can't open default filters by the provided config");
+        let api_tokens_path = Path::new(&storage_config.storage_path).join(API_TOKENS_PATH);
+        let api_tokens = ApiTokensPersistence::open(api_tokens_path)
+            .expect("Can't open API tokens by the provided config");
         TableOfContent {
             collections: Arc::new(RwLock::new(collections)),
             storage_config: storage_config.clone(),
-            search_runtime,
+            search_runtime: SearchRuntime::new(
+                search_runtime,
+                search_threads(storage_config.performance.max_search_threads),
+            ),
             alias_persistence: RwLock::new(alias_persistence),
+            default_filters: RwLock::new(default_filters),
+            api_tokens: parking_lot::RwLock::new(api_tokens),
             collection_management_runtime,
+            optimization_runtime,
+            update_runtime,
+            optimization_scheduler,
+            storage_cipher,
             this_peer_id,
             channel_service,
             consensus_proposal_sender,
             is_write_locked: AtomicBool::new(false),
             lock_error_message: parking_lot::Mutex::new(None),
+            keyed_locks: Default::default(),
+            full_snapshot_lock: Default::default(),
+            state_changes,
+            response_memory: ResponseMemoryLimiter::new(
+                storage_config.performance.search_memory_limit_mb,
+            ),
+            usage_cache: Default::default(),
+            slow_queries: SlowQueryLog::new(storage_config.performance.slow_query_threshold_ms),
         }
     }
 
@@ -157,6 +269,21 @@ impl TableOfContent {
             .join(collection_name)
     }
 
+    pub fn storage_config(&self) -> &StorageConfig {
+        &self.storage_config
+    }
+
+    /// Finish pending updates and flush all collections, which are unloaded afterwards.
+    /// Used on shutdown, when the table of content could still be referenced by other threads.
+    /// Should not be called from async context.
+    pub fn stop_collections(&self) {
+        self.collection_management_runtime.block_on(async {
+            for (_, mut collection) in self.collections.write().await.drain() {
+                collection.before_drop().await;
+            }
+        });
+    }
+
     pub fn storage_path(&self) -> &str {
         &self.storage_config.storage_path
     }
@@ -242,6 +369,11 @@ impl TableOfContent {
             optimizers_config: optimizers_config_diff,
             replication_factor,
             write_consistency_factor,
+            init_from,
+            payload_field_types,
+            auto_payload_index,
+            ttl,
+            default_vector,
         } = operation;
 
         self.collections
@@ -250,6 +382,28 @@ impl TableOfContent {
             .validate_collection_not_exists(collection_name)
             .await?;
 
+        let init_from_collection = match init_from {
+            None => None,
+            Some(init_from) => {
+                let from_collection = self.resolve_name(&init_from.collection).await?;
+                let source_shard_number = self
+                    .get_collection(&from_collection)
+                    .await?
+                    .shards_holder()
+                    .read()
+                    .await
+                    .len();
+                if source_shard_number != collection_shard_distribution.shard_count() {
+                    return Err(StorageError::BadInput {
+                        description: format!(
+                            "Collection initialized from {from_collection} must have the same number of shards: {source_shard_number}"
+                        ),
+                    });
+                }
+                Some(from_collection)
+            }
+        };
+
         let collection_path = self.create_collection_path(collection_name).await?;
         let snapshots_path = self.create_snapshots_path(collection_name).await?;
 
@@ -283,11 +437,9 @@ impl TableOfContent {
                     description: "`write_consistency_factor` cannot be 0".to_string(),
                 },
             )?,
+            default_vector,
         };
-        let wal_config = match wal_config_diff {
-            None => self.storage_config.wal.clone(),
-            Some(diff) => diff.update(&self.storage_config.wal)?,
-        };
+        let wal_config = self.new_collection_wal_config(wal_config_diff)?;
 
         let optimizers_config = match optimizers_config_diff {
             None => self.storage_config.optimizers.clone(),
@@ -304,6 +456,9 @@ impl TableOfContent {
             params: collection_params,
             optimizer_config: optimizers_config,
             hnsw_config,
+            payload_field_types: payload_field_types.filter(|field_types| !field_types.is_empty()),
+            auto_payload_index: auto_payload_index.filter(|config| config.max_indexed_fields > 0),
+            ttl: ttl.filter(|config| config.ttl_sec > 0),
         };
         let collection = Collection::new(
             collection_name.to_string(),
@@ -313,6 +468,8 @@ impl TableOfContent {
             &collection_config,
             collection_shard_distribution,
             self.channel_service.clone(),
+            self.optimization_scheduler.clone(),
+            self.storage_cipher.clone(),
             Self::on_peer_failure_callback(
                 self.consensus_proposal_sender.clone(),
                 collection_name.to_string(),
@@ -321,6 +478,7 @@ impl TableOfContent {
                 self.consensus_proposal_sender.clone(),
                 collection_name.to_string(),
             ),
+            Self::on_shard_event_callback(self.state_changes.clone(), collection_name.to_string()),
         )
         .await?;
 
@@ -333,15 +491,74 @@ impl TableOfContent {
                 .await?;
             write_collections.insert(collection_name.to_string(), collection);
         }
+        self.state_changes
+            .notify(StateChange::Collection(collection_name.to_string()));
 
         // Notify the collection is created and ready to use
         for shard_id in local_shards {
             self.on_peer_created(collection_name.to_string(), self.this_peer_id, shard_id)
                 .await?;
         }
+
+        if let Some(from_collection) = init_from_collection {
+            self.run_data_initialization(from_collection, collection_name.to_string());
+        }
         Ok(true)
     }
 
+    /// Copy points of the existing collection into the newly created one in background.
+    /// Progress and errors are reported in the collection info.
+    fn run_data_initialization(&self, from_collection: CollectionId, to_collection: CollectionId) {
+        let collections = self.collections.clone();
+        let state_changes = self.state_changes.clone();
+        self.collection_management_runtime.spawn(async move {
+            log::info!("Initializing collection {to_collection} with data from {from_collection}");
+            match data_transfer::init_collection(
+                collections,
+                &state_changes,
+                &from_collection,
+                &to_collection,
+            )
+            .await
+            {
+                Ok(()) => log::info!(
+                    "Collection {to_collection} is initialized with data from {from_collection}"
+                ),
+                Err(err) => log::error!(
+                    "Failed to initialize collection {to_collection} from {from_collection}: {err}"
+                ),
+            }
+        });
+    }
+
+    /// Copy points of the `from_collection` into the local shards of the `to_collection` and wait
+    /// for the copying to finish. Points, already present in the `to_collection`, are not overwritten.
+    pub async fn init_collection_from(
+        &self,
+        from_collection: &str,
+        to_collection: &str,
+    ) -> Result<(), StorageError> {
+        let from_collection = self.resolve_name(from_collection).await?;
+        let to_collection = self.resolve_name(to_collection).await?;
+        data_transfer::init_collection(
+            self.collections.clone(),
+            &self.state_changes,
+            &from_collection,
+            &to_collection,
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Collections, initialized from another one, are populated shard by shard,
+    /// so by default they get the same number of shards as the source collection
+    async fn init_from_shard_number(&self, create_collection: &CreateCollection) -> Option<u32> {
+        let init_from = create_collection.init_from.as_ref()?;
+        let collection = self.get_collection(&init_from.collection).await.ok()?;
+        let shard_number = collection.shards_holder().read().await.len();
+        Some(shard_number as u32)
+    }
+
     async fn on_peer_created(
         &self,
         collection_name: String,
@@ -398,6 +615,32 @@ impl TableOfContent {
         })
     }
 
+    /// Status of the collection might change on shard transitions, so subscribers are notified
+    fn on_shard_event_callback(
+        state_changes: StateChanges,
+        collection_name: String,
+    ) -> OnShardEvent {
+        Arc::new(move |shard_id, event| {
+            match event {
+                ShardEvent::OptimizationFinished { completed: true } => {
+                    state_changes.notify(StateChange::OptimizationCompleted {
+                        collection_name: collection_name.clone(),
+                        shard_id,
+                    })
+                }
+                ShardEvent::OperationQuarantined { op_num } => {
+                    state_changes.notify(StateChange::OperationQuarantined {
+                        collection_name: collection_name.clone(),
+                        shard_id,
+                        op_num,
+                    })
+                }
+                _ => {}
+            }
+            state_changes.notify(StateChange::Collection(collection_name.clone()));
+        })
+    }
+
     fn request_shard_transfer_callback(
         proposal_sender: Option<OperationSender>,
         collection_name: String,
@@ -430,6 +673,11 @@ impl TableOfContent {
         let UpdateCollection {
             optimizers_config,
             params,
+            payload_field_types,
+            auto_payload_index,
+            ttl,
+            hnsw_config,
+            vectors,
         } = operation.update_collection;
         let collection = self.get_collection(&operation.collection_name).await?;
         if let Some(diff) = optimizers_config {
@@ -438,6 +686,21 @@ impl TableOfContent {
         if let Some(diff) = params {
             collection.update_params_from_diff(diff).await?;
         }
+        if let Some(field_types) = payload_field_types {
+            collection.update_payload_field_types(field_types).await?;
+        }
+        if let Some(config) = auto_payload_index {
+            collection.update_auto_payload_index(config).await?;
+        }
+        if let Some(config) = ttl {
+            collection.update_ttl(config).await?;
+        }
+        if let Some(diff) = hnsw_config {
+            collection.update_hnsw_config_from_diff(diff).await?;
+        }
+        if let Some(diff) = vectors {
+            collection.update_vectors_from_diff(&diff).await?;
+        }
         if let Some(changes) = replica_changes {
             collection.handle_replica_changes(changes).await?;
         }
@@ -446,15 +709,28 @@ impl TableOfContent {
 
     async fn delete_collection(&self, collection_name: &str) -> Result<bool, StorageError> {
         if let Some(mut removed) = self.collections.write().await.remove(collection_name) {
+            self.state_changes
+                .notify(StateChange::Collection(collection_name.to_string()));
             removed.before_drop().await;
             let path = self.get_collection_path(collection_name);
             drop(removed);
-            remove_dir_all(path).map_err(|err| StorageError::ServiceError {
+            self.keyed_locks.write().release_collection(collection_name);
+            self.usage_cache.remove(collection_name);
+            self.slow_queries.remove(collection_name);
+            let removed_dir = remove_dir_all(path).map_err(|err| StorageError::ServiceError {
                 description: format!(
                     "Can't delete collection {}, error: {}",
                     collection_name, err
                 ),
-            })?;
+            });
+            // The collection is already gone, its default filter should not outlive it
+            // even if the files could not be removed
+            self.default_filters
+                .write()
+                .await
+                .set(collection_name.to_string(), None)?;
+            self.api_tokens.write().remove_collection(collection_name)?;
+            removed_dir?;
             Ok(true)
         } else {
             Ok(false)
@@ -462,6 +738,10 @@ impl TableOfContent {
     }
 
     /// performs several alias changes in an atomic fashion
+    ///
+    /// All actions are validated against the state produced by the preceding actions of the batch.
+    /// Changes are persisted only if every action succeeds, otherwise the error points to the
+    /// first failed action and no changes are applied.
     async fn update_aliases(
         &self,
         operation: ChangeAliasesOperation,
@@ -470,43 +750,106 @@ impl TableOfContent {
         // Prevent search on partially switched collections
         let collection_lock = self.collections.write().await;
         let mut alias_lock = self.alias_persistence.write().await;
-        for action in operation.actions {
-            match action {
-                AliasOperations::CreateAlias(CreateAliasOperation {
-                    create_alias:
-                        CreateAlias {
-                            collection_name,
-                            alias_name,
-                        },
-                }) => {
-                    collection_lock
-                        .validate_collection_exists(&collection_name)
-                        .await?;
-                    collection_lock
-                        .validate_collection_not_exists(&alias_name)
-                        .await?;
-
-                    alias_lock.insert(alias_name, collection_name)?;
+        let mut filters_lock = self.default_filters.write().await;
+
+        let mut aliases = alias_lock.state().clone();
+        let mut filters = filters_lock.state().clone();
+
+        // Tokens of the removed names should not grant access to a new alias with the same name
+        let removed_aliases: Vec<_> = operation
+            .actions
+            .iter()
+            .filter_map(|action| match action {
+                AliasOperations::CreateAlias(_) => None,
+                AliasOperations::DeleteAlias(operation) => {
+                    Some(operation.delete_alias.alias_name.clone())
                 }
-                AliasOperations::DeleteAlias(DeleteAliasOperation {
-                    delete_alias: DeleteAlias { alias_name },
-                }) => {
-                    alias_lock.remove(&alias_name)?;
+                AliasOperations::RenameAlias(operation) => {
+                    Some(operation.rename_alias.old_alias_name.clone())
                 }
-                AliasOperations::RenameAlias(RenameAliasOperation {
-                    rename_alias:
-                        RenameAlias {
-                            old_alias_name,
-                            new_alias_name,
-                        },
-                }) => {
-                    alias_lock.rename_alias(&old_alias_name, new_alias_name)?;
-                }
-            };
+                AliasOperations::SwapAliases(_) => None,
+            })
+            .collect();
+
+        for (action_idx, action) in operation.actions.into_iter().enumerate() {
+            Self::apply_alias_action(&collection_lock, &mut aliases, &mut filters, action)
+                .await
+                .map_err(|err| err.with_context(&format!("Alias action #{action_idx} failed")))?;
+        }
+
+        // Filters are saved first, so that an alias never points to a collection without
+        // the filter it was created with. Filters are reverted if aliases can't be saved.
+        let old_filters = filters_lock.state().clone();
+        filters_lock.apply_state(filters)?;
+        if let Err(err) = alias_lock.apply_state(aliases) {
+            filters_lock.apply_state(old_filters)?;
+            return Err(err);
+        }
+        let mut api_tokens = self.api_tokens.write();
+        for alias_name in removed_aliases {
+            api_tokens.remove_collection(&alias_name)?;
         }
         Ok(true)
     }
 
+    /// Apply single alias action to the in-memory copy of aliases and default filters
+    async fn apply_alias_action(
+        collections: &Collections,
+        aliases: &mut AliasMapping,
+        filters: &mut DefaultFiltersMapping,
+        action: AliasOperations,
+    ) -> Result<(), StorageError> {
+        match action {
+            AliasOperations::CreateAlias(CreateAliasOperation {
+                create_alias:
+                    CreateAlias {
+                        collection_name,
+                        alias_name,
+                    },
+            }) => {
+                collections
+                    .validate_collection_exists(&collection_name)
+                    .await?;
+                collections
+                    .validate_collection_not_exists(&alias_name)
+                    .await?;
+
+                aliases.insert(alias_name, collection_name);
+            }
+            AliasOperations::DeleteAlias(DeleteAliasOperation {
+                delete_alias: DeleteAlias { alias_name },
+            }) => {
+                aliases.remove(&alias_name);
+                filters.remove(&alias_name);
+            }
+            AliasOperations::RenameAlias(RenameAliasOperation {
+                rename_alias:
+                    RenameAlias {
+                        old_alias_name,
+                        new_alias_name,
+                    },
+            }) => {
+                collections
+                    .validate_collection_not_exists(&new_alias_name)
+                    .await?;
+
+                aliases.rename(&old_alias_name, new_alias_name.clone())?;
+                filters.rename(&old_alias_name, new_alias_name);
+            }
+            // Default filters stay with the alias names, as they define what is exposed under them
+            AliasOperations::SwapAliases(SwapAliasesOperation {
+                swap_aliases:
+                    SwapAliases {
+                        first_alias_name,
+                        second_alias_name,
+                    },
+            }) => {
+                aliases.swap(&first_alias_name, &second_alias_name)?;
+            }
+        };
+        Ok(())
+    }
+
     pub fn perform_collection_meta_op_sync(
         &self,
         operation: CollectionMetaOperations,
@@ -524,7 +867,13 @@ impl TableOfContent {
                 log::debug!("Creating collection {}", operation.collection_name);
                 let distribution = match operation.take_distribution() {
                     None => CollectionShardDistribution::all_local(
-                        operation.create_collection.shard_number,
+                        match operation.create_collection.shard_number {
+                            Some(shard_number) => Some(shard_number),
+                            None => {
+                                self.init_from_shard_number(&operation.create_collection)
+                                    .await
+                            }
+                        },
                         self.this_peer_id,
                     ),
                     Some(distribution) => distribution.into(),
@@ -559,6 +908,21 @@ impl TableOfContent {
                 log::debug!("Set shard replica state {:?}", operation);
                 self.set_shard_replica_state(operation).await.map(|()| true)
             }
+            CollectionMetaOperations::SetDefaultFilter(operation) => {
+                log::debug!("Setting default filter of {}", operation.name);
+                self.set_default_filter(operation).await
+            }
+            CollectionMetaOperations::CreateApiToken(operation) => {
+                log::debug!(
+                    "Creating API token of {}",
+                    operation.api_token.collection_name
+                );
+                self.create_api_token(operation).await
+            }
+            CollectionMetaOperations::DeleteApiToken(operation) => {
+                log::debug!("Deleting API token of {}", operation.collection_name);
+                self.delete_api_token(operation)
+            }
             CollectionMetaOperations::Nop { .. } => Ok(true),
         }
     }
@@ -591,6 +955,65 @@ impl TableOfContent {
         Ok(())
     }
 
+    /// Check that the new collection parameters make sense and fit into the strict mode limits.
+    /// Used before proposing the operation, so nonsensical collections are not created.
+    pub fn validate_create_collection(
+        &self,
+        create_collection: &CreateCollection,
+    ) -> Result<(), StorageError> {
+        // Fail before proposing, otherwise the collection can't be created on apply
+        self.new_collection_wal_config(create_collection.wal_config.clone())?;
+        create_collection.validate(&self.storage_config)
+    }
+
+    /// Check that the parameters of the collection still make sense after the update.
+    /// Used before proposing the operation, as [`Self::validate_create_collection`].
+    pub async fn validate_update_collection(
+        &self,
+        collection_name: &str,
+        update_collection: &UpdateCollection,
+    ) -> Result<(), StorageError> {
+        let collection = self.get_collection(collection_name).await?;
+        let config = collection.state().await.config;
+        update_collection.validate(&config)
+    }
+
+    /// Check that the transfer can be started with the current state of the collection.
+    /// Used on apply of the consensus operation and before proposing it, so invalid
+    /// transfers requested by users don't get into the consensus log.
+    pub async fn validate_shard_transfer(
+        &self,
+        collection: &Collection,
+        transfer: &ShardTransfer,
+    ) -> Result<(), StorageError> {
+        let collection_state::State {
+            config: _,
+            shards,
+            transfers,
+        } = collection.state().await;
+        let all_peers: HashSet<_> = self
+            .channel_service
+            .id_to_address
+            .read()
+            .keys()
+            .cloned()
+            .collect();
+        let shard_state = shards.get(&transfer.shard_id).map(|info| &info.replicas);
+
+        // Valid transfer:
+        // All peers: 123, 321, 111, 222, 333
+        // Peers: shard_id=1 - [{123: Active}]
+        // Transfer: {123 -> 321}, shard_id=1
+
+        // Invalid transfer:
+        // All peers: 123, 321, 111, 222, 333
+        // Peers: shard_id=1 - [{123: Active}]
+        // Transfer: {321 -> 123}, shard_id=1
+
+        validate_transfer(transfer, &all_peers, shard_state, &transfers)?;
+        Ok(())
+    }
+
     pub async fn handle_transfer(
         &self,
         collection_id: CollectionId,
@@ -608,31 +1031,7 @@ impl TableOfContent {
 
         match transfer_operation {
             ShardTransferOperations::Start(transfer) => {
-                let collection_state::State {
-                    config: _,
-                    shards,
-                    transfers,
-                } = collection.state().await;
-                let all_peers: HashSet<_> = self
-                    .channel_service
-                    .id_to_address
-                    .read()
-                    .keys()
-                    .cloned()
-                    .collect();
-                let shard_state = shards.get(&transfer.shard_id).map(|info| &info.replicas);
-
-                // Valid transfer:
-                // All peers: 123, 321, 111, 222, 333
-                // Peers: shard_id=1 - [{123: Active}]
-                // Transfer: {123 -> 321}, shard_id=1
-
-                // Invalid transfer:
-                // All peers: 123, 321, 111, 222, 333
-                // Peers: shard_id=1 - [{123: Active}]
-                // Transfer: {321 -> 123}, shard_id=1
-
-                validate_transfer(&transfer, &all_peers, shard_state, &transfers)?;
+                self.validate_shard_transfer(&collection, &transfer).await?;
 
                 let collection_id_clone = collection_id.clone();
                 let transfer_clone = transfer.clone();
@@ -705,6 +1104,41 @@ impl TableOfContent {
         Ok(())
     }
 
+    /// Retrieve vectors of the examples of the requests with `lookup_from`, by the name of the
+    /// lookup collection. Examples are retrieved before the recommended collection is locked,
+    /// so that two collections are never locked at once.
+    async fn lookup_recommend_examples(
+        &self,
+        requests: &[RecommendRequest],
+    ) -> Result<HashMap<String, Vec<Record>>, StorageError> {
+        let mut ids_by_collection: HashMap<&str, HashSet<PointIdType>> = HashMap::new();
+        for request in requests {
+            if let Some(lookup_location) = &request.lookup_from {
+                ids_by_collection
+                    .entry(lookup_location.collection.as_str())
+                    .or_default()
+                    .extend(request.positive.iter().chain(&request.negative));
+            }
+        }
+
+        let mut lookup_records = HashMap::with_capacity(ids_by_collection.len());
+        for (collection_name, ids) in ids_by_collection {
+            let collection = self.get_collection(collection_name).await?;
+            let records = collection
+                .retrieve(
+                    PointRequest {
+                        ids: ids.into_iter().collect(),
+                        with_payload: Some(WithPayloadInterface::Bool(false)),
+                        with_vector: true.into(),
+                    },
+                    None,
+                )
+                .await?;
+            lookup_records.insert(collection_name.to_string(), records);
+        }
+        Ok(lookup_records)
+    }
+
     /// Recommend points using positive and negative example from the request
     ///
     /// # Arguments
@@ -718,12 +1152,122 @@ impl TableOfContent {
     pub async fn recommend(
         &self,
         collection_name: &str,
-        request: RecommendRequest,
+        mut request: RecommendRequest,
+        shard_selection: Option<ShardId>,
+    ) -> Result<Vec<ScoredPoint>, StorageError> {
+        let started = Instant::now();
+        if shard_selection.is_none() {
+            request.filter = self
+                .with_default_filters(collection_name, request.filter.as_ref())
+                .await;
+        }
+        let lookup_records = self
+            .lookup_recommend_examples(std::slice::from_ref(&request))
+            .await?;
+        let collection = self.get_collection(collection_name).await?;
+        let _permit = self
+            .reserve_response_memory(
+                &collection,
+                request.limit + request.offset,
+                request.with_vector.as_ref(),
+                request.with_payload.as_ref(),
+            )
+            .await?;
+        let preparation_time = started.elapsed();
+        let logged_request = self.slow_queries.is_enabled().then(|| request.clone());
+        let result = collection
+            .recommend_by(
+                request,
+                self.search_runtime.get().handle(),
+                shard_selection,
+                &lookup_records,
+            )
+            .await;
+        if let Some(logged_request) = logged_request {
+            self.record_slow_query(
+                &collection,
+                "recommend",
+                &logged_request,
+                shard_selection,
+                preparation_time,
+                started.elapsed(),
+                result.as_ref().err(),
+            )
+            .await;
+        }
+        result.map_err(|err| err.into())
+    }
+
+    /// Discover points using the target and the context pairs from the request
+    ///
+    /// # Arguments
+    ///
+    /// * `collection_name` - in what collection do we discover
+    /// * `request` - [`DiscoverRequest`]
+    ///
+    /// # Result
+    ///
+    /// Points with discovery score
+    pub async fn discover(
+        &self,
+        collection_name: &str,
+        mut request: DiscoverRequest,
         shard_selection: Option<ShardId>,
+    ) -> Result<Vec<ScoredPoint>, StorageError> {
+        let started = Instant::now();
+        if shard_selection.is_none() {
+            request.filter = self
+                .with_default_filters(collection_name, request.filter.as_ref())
+                .await;
+        }
+        let collection = self.get_collection(collection_name).await?;
+        let _permit = self
+            .reserve_response_memory(
+                &collection,
+                request.limit + request.offset,
+                request.with_vector.as_ref(),
+                request.with_payload.as_ref(),
+            )
+            .await?;
+        let preparation_time = started.elapsed();
+        let logged_request = self.slow_queries.is_enabled().then(|| request.clone());
+        let result = collection
+            .discover_by(request, self.search_runtime.get().handle(), shard_selection)
+            .await;
+        if let Some(logged_request) = logged_request {
+            self.record_slow_query(
+                &collection,
+                "discover",
+                &logged_request,
+                shard_selection,
+                preparation_time,
+                started.elapsed(),
+                result.as_ref().err(),
+            )
+            .await;
+        }
+        result.map_err(|err| err.into())
+    }
+
+    /// Discovery search in the shard with the examples already resolved into vectors.
+    /// Serves the requests of other peers, which run [`Self::discover`].
+    pub async fn discover_in_shard(
+        &self,
+        collection_name: &str,
+        request: ShardDiscoverRequest,
+        shard_id: ShardId,
     ) -> Result<Vec<ScoredPoint>, StorageError> {
         let collection = self.get_collection(collection_name).await?;
+        let _permit = self
+            .reserve_response_memory(
+                &collection,
+                request.limit,
+                Some(&request.with_vector),
+                request.with_payload.as_ref(),
+            )
+            .await?;
         collection
-            .recommend_by(request, self.search_runtime.handle(), shard_selection)
+            .discover_shards(request, self.search_runtime.get().handle(), Some(shard_id))
             .await
             .map_err(|err| err.into())
     }
@@ -741,14 +1285,54 @@ impl TableOfContent {
     pub async fn recommend_batch(
         &self,
         collection_name: &str,
-        request: RecommendRequestBatch,
+        mut request: RecommendRequestBatch,
         shard_selection: Option<ShardId>,
     ) -> Result<Vec<Vec<ScoredPoint>>, StorageError> {
+        let started = Instant::now();
+        if shard_selection.is_none() {
+            for search in &mut request.searches {
+                search.filter = self
+                    .with_default_filters(collection_name, search.filter.as_ref())
+                    .await;
+            }
+        }
+        let lookup_records = self.lookup_recommend_examples(&request.searches).await?;
         let collection = self.get_collection(collection_name).await?;
-        collection
-            .recommend_batch_by(request, self.search_runtime.handle(), shard_selection)
-            .await
-            .map_err(|err| err.into())
+        let mut permits = Vec::with_capacity(request.searches.len());
+        for search in &request.searches {
+            permits.push(
+                self.reserve_response_memory(
+                    &collection,
+                    search.limit + search.offset,
+                    search.with_vector.as_ref(),
+                    search.with_payload.as_ref(),
+                )
+                .await?,
+            );
+        }
+        let preparation_time = started.elapsed();
+        let logged_request = self.slow_queries.is_enabled().then(|| request.clone());
+        let result = collection
+            .recommend_batch_by(
+                request,
+                self.search_runtime.get().handle(),
+                shard_selection,
+                &lookup_records,
+            )
+            .await;
+        if let Some(logged_request) = logged_request {
+            self.record_slow_query(
+                &collection,
+                "recommend_batch",
+                &logged_request,
+                shard_selection,
+                preparation_time,
+                started.elapsed(),
+                result.as_ref().err(),
+            )
+            .await;
+        }
+        result.map_err(|err| err.into())
     }
 
     /// Search for the closest points using vector similarity with given restrictions defined
@@ -761,18 +1345,145 @@ impl TableOfContent {
     /// * `shard_selection` - which local shard to use
     /// # Result
     ///
-    /// Points with search score
+    /// Points with search score and whether the time budget of the search was exhausted
     pub async fn search(
         &self,
         collection_name: &str,
-        request: SearchRequest,
+        mut request: SearchRequest,
         shard_selection: Option<ShardId>,
-    ) -> Result<Vec<ScoredPoint>, StorageError> {
+    ) -> Result<(Vec<ScoredPoint>, bool, SearchExplanation), StorageError> {
+        let started = Instant::now();
+        if shard_selection.is_none() {
+            request.filter = self
+                .with_default_filters(collection_name, request.filter.as_ref())
+                .await;
+        }
         let collection = self.get_collection(collection_name).await?;
-        collection
-            .search(request, self.search_runtime.handle(), shard_selection)
-            .await
-            .map_err(|err| err.into())
+        let _permit = self
+            .reserve_response_memory(
+                &collection,
+                request.limit + request.offset,
+                request.with_vector.as_ref(),
+                request.with_payload.as_ref(),
+            )
+            .await?;
+        let preparation_time = started.elapsed();
+        let logged_request = self.slow_queries.is_enabled().then(|| request.clone());
+        let result = collection
+            .search_with_truncation(request, self.search_runtime.get().handle(), shard_selection)
+            .await;
+        if let Some(logged_request) = logged_request {
+            self.record_slow_query(
+                &collection,
+                "search",
+                &logged_request,
+                shard_selection,
+                preparation_time,
+                started.elapsed(),
+                result.as_ref().err(),
+            )
+            .await;
+        }
+        result.map_err(|err| err.into())
+    }
+
+    /// Collections and aliases, matching the names or patterns of the federated search.
+    /// Each collection is searched once, by the name it is matched with first.
+    async fn resolve_federated_names(&self, names: &[String]) -> Result<Vec<String>, StorageError> {
+        let mut resolved = HashSet::new();
+        let mut matched = vec![];
+        for name in names {
+            let candidates = if name.contains('*') {
+                let mut candidates = self.all_collections().await;
+                candidates.sort();
+                candidates.extend(
+                    self.list_aliases()
+                        .await
+                        .into_iter()
+                        .map(|(alias, _)| alias),
+                );
+                candidates
+                    .into_iter()
+                    .filter(|candidate| matches_pattern(name, candidate))
+                    .collect()
+            } else {
+                vec![name.clone()]
+            };
+            for candidate in candidates {
+                if resolved.insert(self.resolve_name(&candidate).await?) {
+                    matched.push(candidate);
+                }
+            }
+        }
+        if matched.is_empty() {
+            return Err(StorageError::BadRequest {
+                description: format!("No collections match {names:?}"),
+            });
+        }
+        Ok(matched)
+    }
+
+    /// Search for the same request in multiple collections and merge the results
+    ///
+    /// # Result
+    ///
+    /// Points with their collections, ordered by the normalized score, and whether the time
+    /// budget of any search was exhausted
+    pub async fn federated_search(
+        &self,
+        request: FederatedSearchRequest,
+    ) -> Result<(Vec<FederatedScoredPoint>, bool, SearchExplanation), StorageError> {
+        let FederatedSearchRequest {
+            collections,
+            search,
+            normalization,
+        } = request;
+        let collections = self.resolve_federated_names(&collections).await?;
+
+        let mut orders = Vec::with_capacity(collections.len());
+        for collection_name in &collections {
+            let collection = self.get_collection(collection_name).await?;
+            orders.push(collection.score_order(search.vector.get_name()).await?);
+        }
+        let mixed_orders = orders.windows(2).any(|pair| {
+            matches!(
+                pair,
+                [Order::LargeBetter, Order::SmallBetter] | [Order::SmallBetter, Order::LargeBetter]
+            )
+        });
+        if normalization == ScoreNormalization::None && mixed_orders {
+            return Err(StorageError::BadRequest {
+                description: "Scores of the collections are ordered differently, \
+                              normalization is required to merge them"
+                    .to_string(),
+            });
+        }
+
+        // Each collection could contribute the whole page
+        let mut collection_search = search.clone();
+        collection_search.offset = 0;
+        collection_search.limit = search.limit + search.offset;
+        let searches = collections
+            .iter()
+            .map(|collection_name| self.search(collection_name, collection_search.clone(), None));
+        let searched = try_join_all(searches).await?;
+
+        let mut truncated = false;
+        let mut explanation = SearchExplanation::default();
+        let mut results = Vec::with_capacity(collections.len());
+        for ((collection, order), (points, collection_truncated, collection_explanation)) in
+            collections.into_iter().zip(orders).zip(searched)
+        {
+            truncated |= collection_truncated;
+            explanation.merge(&collection_explanation);
+            results.push(CollectionSearchResult {
+                collection,
+                order,
+                points,
+            });
+        }
+        let merged = merge_results(results, normalization, search.offset, search.limit);
+        Ok((merged, truncated, explanation))
     }
 
     /// Search in a batching fashion for the closest points using vector similarity with given restrictions defined
@@ -785,18 +1496,52 @@ impl TableOfContent {
     /// * `shard_selection` - which local shard to use
     /// # Result
     ///
-    /// Points with search score
+    /// Points with search score for each request of the batch
     pub async fn search_batch(
         &self,
         collection_name: &str,
-        request: SearchRequestBatch,
+        mut request: SearchRequestBatch,
         shard_selection: Option<ShardId>,
-    ) -> Result<Vec<Vec<ScoredPoint>>, StorageError> {
+    ) -> Result<BatchSearchResult, StorageError> {
+        let started = Instant::now();
+        if shard_selection.is_none() {
+            for search in &mut request.searches {
+                search.filter = self
+                    .with_default_filters(collection_name, search.filter.as_ref())
+                    .await;
+            }
+        }
         let collection = self.get_collection(collection_name).await?;
-        collection
-            .search_batch(request, self.search_runtime.handle(), shard_selection)
-            .await
-            .map_err(|err| err.into())
+        let mut permits = Vec::with_capacity(request.searches.len());
+        for search in &request.searches {
+            permits.push(
+                self.reserve_response_memory(
+                    &collection,
+                    search.limit + search.offset,
+                    search.with_vector.as_ref(),
+                    search.with_payload.as_ref(),
+                )
+                .await?,
+            );
+        }
+        let preparation_time = started.elapsed();
+        let logged_request = self.slow_queries.is_enabled().then(|| request.clone());
+        let result = collection
+            .search_batch(request, self.search_runtime.get().handle(), shard_selection)
+            .await;
+        if let Some(logged_request) = logged_request {
+            self.record_slow_query(
+                &collection,
+                "search_batch",
+                &logged_request,
+                shard_selection,
+                preparation_time,
+                started.elapsed(),
+                result.as_ref().err(),
+            )
+            .await;
+        }
+        result.map_err(|err| err.into())
     }
 
     /// Count points in the collection.
@@ -814,9 +1559,14 @@ impl TableOfContent {
     pub async fn count(
         &self,
         collection_name: &str,
-        request: CountRequest,
+        mut request: CountRequest,
         shard_selection: Option<ShardId>,
     ) -> Result<CountResult, StorageError> {
+        if shard_selection.is_none() {
+            request.filter = self
+                .with_default_filters(collection_name, request.filter.as_ref())
+                .await;
+        }
         let collection = self.get_collection(collection_name).await?;
         collection
             .count(request, shard_selection)
@@ -824,37 +1574,247 @@ impl TableOfContent {
             .map_err(|err| err.into())
     }
 
-    /// Return specific points by IDs
+    /// Count the most frequent values of the payload field among points in the collection.
     ///
     /// # Arguments
     ///
-    /// * `collection_name` - select from this collection
-    /// * `request` - [`PointRequest`]
+    /// * `collection_name` - in what collection do we count
+    /// * `request` - [`FacetRequest`]
     /// * `shard_selection` - which local shard to use
     ///
     /// # Result
     ///
-    /// List of points with specified information included
-    pub async fn retrieve(
+    /// Values with approximate number of points, sorted by count in descending order.
+    ///
+    pub async fn facet(
         &self,
         collection_name: &str,
-        request: PointRequest,
+        mut request: FacetRequest,
         shard_selection: Option<ShardId>,
-    ) -> Result<Vec<Record>, StorageError> {
+    ) -> Result<FacetResponse, StorageError> {
+        if shard_selection.is_none() {
+            request.filter = self
+                .with_default_filters(collection_name, request.filter.as_ref())
+                .await;
+        }
         let collection = self.get_collection(collection_name).await?;
         collection
-            .retrieve(request, shard_selection)
+            .facet(request, shard_selection)
             .await
             .map_err(|err| err.into())
     }
 
-    /// List of all collections
-    pub async fn all_collections(&self) -> Vec<String> {
-        self.collections.read().await.keys().cloned().collect()
+    /// Read vectors of the local shards of the collection, stored on disk, into the page cache.
+    /// Blocks until all vectors are read.
+    pub async fn warm_up_collection(&self, collection_name: &str) -> Result<bool, StorageError> {
+        let collection = self.get_collection(collection_name).await?;
+        collection.warm_up().await?;
+        Ok(true)
     }
 
-    /// List of all collections
-    pub fn all_collections_sync(&self) -> Vec<String> {
+    /// Vacuum and merge segments of the local shards of the collection right away,
+    /// regardless of the thresholds in the optimizers config.
+    /// Responds when optimizations are scheduled, with the number of them
+    pub async fn optimize_collection(
+        &self,
+        collection_name: &str,
+        shard_id: Option<ShardId>,
+    ) -> Result<usize, StorageError> {
+        let collection = self.get_collection(collection_name).await?;
+        Ok(collection.force_optimization(shard_id).await?)
+    }
+
+    /// Verify data of the local shards of the collection against the stored checksums
+    pub async fn verify_collection(
+        &self,
+        collection_name: &str,
+    ) -> Result<CollectionVerificationReport, StorageError> {
+        let collection = self.get_collection(collection_name).await?;
+        Ok(collection.verify().await?)
+    }
+
+    /// Compare the replicas of all the shards of the collection with the leading replicas
+    /// and re-sync the points, which diverged
+    pub async fn sync_collection_replicas(
+        &self,
+        collection_name: &str,
+    ) -> Result<ReplicaSyncReport, StorageError> {
+        let collection = self.get_collection(collection_name).await?;
+        Ok(collection.sync_replicas(false).await?)
+    }
+
+    /// Compare the replicas of the shards, which leading replica is on this peer, and re-sync
+    /// the points, which diverged. Failed collections are logged and skipped.
+    pub async fn sync_leading_replicas(&self) -> ReplicaSyncReport {
+        let mut report = ReplicaSyncReport::default();
+        for (collection_name, collection) in self.collections.read().await.iter() {
+            match collection.sync_replicas(true).await {
+                Ok(collection_report) => report += collection_report,
+                Err(err) => log::warn!(
+                    "Failed to sync replicas of collection {}: {}",
+                    collection_name,
+                    err
+                ),
+            }
+        }
+        report
+    }
+
+    /// Digests of the points of the local replica of the shard.
+    /// Used by other peers to compare their replicas with this one.
+    pub async fn local_point_digests(
+        &self,
+        collection_name: &str,
+        shard_id: ShardId,
+        from: Option<PointIdType>,
+        to: Option<PointIdType>,
+        limit: Option<usize>,
+    ) -> Result<Vec<PointDigest>, StorageError> {
+        let collection = self.get_collection(collection_name).await?;
+        Ok(collection
+            .local_point_digests(shard_id, from, to, limit)
+            .await?)
+    }
+
+    /// Wait for the operation to be applied to the local replica of the shard.
+    /// Sequential numbers of the operation in the WAL of each replica are reported in
+    /// the result of the update, so readers of a replica could see their own writes.
+    pub async fn wait_for_operation(
+        &self,
+        collection_name: &str,
+        shard_id: ShardId,
+        operation_id: SeqNumberType,
+        timeout: Duration,
+    ) -> Result<bool, StorageError> {
+        let collection = self.get_collection(collection_name).await?;
+        Ok(collection
+            .wait_for_local_operation(shard_id, operation_id, timeout)
+            .await?)
+    }
+
+    /// Read changes of the points from the WAL of the shard of the collection.
+    /// Shard without a local replica is read from a replica on another peer.
+    ///
+    /// # Arguments
+    ///
+    /// * `collection_name` - read changes of this collection
+    /// * `shard_id` - shard to read, may be omitted if there is only one local shard
+    /// * `from` - sequential number of the first operation to read
+    /// * `limit` - max number of operations to read
+    ///
+    /// # Result
+    ///
+    /// Changes in the order of operations and the number of the operation to continue from.
+    ///
+    pub async fn read_points_changes(
+        &self,
+        collection_name: &str,
+        shard_id: Option<ShardId>,
+        from: SeqNumberType,
+        limit: usize,
+    ) -> Result<PointsChanges, StorageError> {
+        let collection = self.get_collection(collection_name).await?;
+        collection
+            .read_points_changes(shard_id, from, limit)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    /// Read changes of the points from the WAL of the local replica of the shard.
+    /// Used to serve reads of other peers, which have no local replica.
+    pub async fn read_local_points_changes(
+        &self,
+        collection_name: &str,
+        shard_id: ShardId,
+        from: SeqNumberType,
+        limit: usize,
+    ) -> Result<PointsChanges, StorageError> {
+        let collection = self.get_collection(collection_name).await?;
+        collection
+            .read_local_points_changes(shard_id, from, limit)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    /// Return specific points by IDs
+    ///
+    /// # Arguments
+    ///
+    /// * `collection_name` - select from this collection
+    /// * `request` - [`PointRequest`]
+    /// * `shard_selection` - which local shard to use
+    ///
+    /// # Result
+    ///
+    /// List of points with specified information included
+    pub async fn retrieve(
+        &self,
+        collection_name: &str,
+        request: PointRequest,
+        shard_selection: Option<ShardId>,
+    ) -> Result<Vec<Record>, StorageError> {
+        let collection = self.get_collection(collection_name).await?;
+        let _permit = self
+            .reserve_response_memory(
+                &collection,
+                request.ids.len(),
+                Some(&request.with_vector),
+                request.with_payload.as_ref(),
+            )
+            .await?;
+        collection
+            .retrieve(request, shard_selection)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    /// List of all collections
+    pub async fn all_collections(&self) -> Vec<String> {
+        self.collections.read().await.keys().cloned().collect()
+    }
+
+    /// All aliases with their target collections, ordered by alias name
+    pub async fn list_aliases(&self) -> Vec<(String, String)> {
+        self.alias_persistence.read().await.all_aliases()
+    }
+
+    /// Aliases of the collection with its resolved name, ordered by alias name.
+    /// Collection could be addressed by an alias as well.
+    pub async fn list_collection_aliases(
+        &self,
+        collection_name: &str,
+    ) -> Result<Vec<(String, String)>, StorageError> {
+        let resolved_name = self.get_collection(collection_name).await?.name();
+        let mut aliases = self
+            .alias_persistence
+            .read()
+            .await
+            .collection_aliases(&resolved_name);
+        aliases.sort();
+        Ok(aliases
+            .into_iter()
+            .map(|alias| (alias, resolved_name.clone()))
+            .collect())
+    }
+
+    /// Local replicas, which can't serve requests yet or anymore: `Partial` or `Dead` ones
+    pub async fn unavailable_local_shards(&self) -> Vec<(String, ShardId, ReplicaState)> {
+        let mut unavailable = vec![];
+        for (collection_name, collection) in self.collections.read().await.iter() {
+            for (shard_id, shard_info) in collection.state().await.shards {
+                match shard_info.replicas.get(&self.this_peer_id) {
+                    Some(state @ (ReplicaState::Partial | ReplicaState::Dead)) => {
+                        unavailable.push((collection_name.clone(), shard_id, *state))
+                    }
+                    _ => {}
+                }
+            }
+        }
+        unavailable
+    }
+
+    /// List of all collections
+    pub fn all_collections_sync(&self) -> Vec<String> {
         self.collection_management_runtime
             .block_on(self.collections.read())
             .keys()
@@ -875,6 +1835,82 @@ impl TableOfContent {
         Ok(result)
     }
 
+    /// Default filter of the collection or alias, if set
+    pub async fn default_filter(&self, name: &str) -> Result<Option<Filter>, StorageError> {
+        self.resolve_name(name).await?;
+        Ok(self.default_filters.read().await.get(name).cloned())
+    }
+
+    /// Combine the filter of the request with default filters of the collection and,
+    /// if `collection_name` is an alias, with the default filter of the alias.
+    async fn with_default_filters(
+        &self,
+        collection_name: &str,
+        filter: Option<&Filter>,
+    ) -> Option<Filter> {
+        let alias_target = self.alias_persistence.read().await.get(collection_name);
+        let default_filters = self.default_filters.read().await;
+        let filters = [Some(collection_name), alias_target.as_deref()]
+            .into_iter()
+            .flatten()
+            .filter_map(|name| default_filters.get(name).cloned())
+            .collect();
+        merge_filters(filter, filters)
+    }
+
+    async fn set_default_filter(
+        &self,
+        operation: SetDefaultFilterOperation,
+    ) -> Result<bool, StorageError> {
+        let SetDefaultFilterOperation {
+            name,
+            set_default_filter,
+        } = operation;
+        self.resolve_name(&name).await?;
+        self.default_filters
+            .write()
+            .await
+            .set(name, set_default_filter.filter)?;
+        Ok(true)
+    }
+
+    async fn create_api_token(
+        &self,
+        operation: CreateApiTokenOperation,
+    ) -> Result<bool, StorageError> {
+        self.resolve_name(&operation.api_token.collection_name)
+            .await?;
+        self.api_tokens
+            .write()
+            .insert(operation.token, operation.api_token)?;
+        Ok(true)
+    }
+
+    fn delete_api_token(&self, operation: DeleteApiTokenOperation) -> Result<bool, StorageError> {
+        let DeleteApiTokenOperation {
+            collection_name,
+            delete_api_token,
+        } = operation;
+        let mut api_tokens = self.api_tokens.write();
+        // Token of another collection is not revealed
+        let is_owned = api_tokens
+            .get(&delete_api_token.token)
+            .map_or(false, |api_token| {
+                api_token.collection_name == collection_name
+            });
+        if !is_owned {
+            return Err(StorageError::NotFound {
+                description: format!("Token of {collection_name} not found"),
+            });
+        }
+        api_tokens.remove(&delete_api_token.token)
+    }
+
+    /// Access, granted by the token of a collection, if there is such token
+    pub fn api_token(&self, token: &str) -> Option<ApiToken> {
+        self.api_tokens.read().get(token).cloned()
+    }
+
     /// Paginate over all stored points with given filtering conditions
     ///
     /// # Arguments
@@ -889,40 +1925,191 @@ impl TableOfContent {
     pub async fn scroll(
         &self,
         collection_name: &str,
-        request: ScrollRequest,
+        mut request: ScrollRequest,
         shard_selection: Option<ShardId>,
     ) -> Result<ScrollResult, StorageError> {
+        if shard_selection.is_none() {
+            request.filter = self
+                .with_default_filters(collection_name, request.filter.as_ref())
+                .await;
+        }
         let collection = self.get_collection(collection_name).await?;
+        let default_request = ScrollRequest::default();
+        let _permit = self
+            .reserve_response_memory(
+                &collection,
+                request.limit.or(default_request.limit).unwrap_or_default(),
+                Some(&request.with_vector),
+                request
+                    .with_payload
+                    .as_ref()
+                    .or(default_request.with_payload.as_ref()),
+            )
+            .await?;
         collection
             .scroll_by(request, shard_selection)
             .await
             .map_err(|err| err.into())
     }
 
+    /// Uniformly random points of the collection, which satisfy the filter
+    ///
+    /// # Arguments
+    ///
+    /// * `collection_name` - which collection to use
+    /// * `request` - [`SampleRequest`]
+    /// * `shard_selection` - which local shard to use
+    ///
+    /// # Result
+    ///
+    /// List of points with specified information included
+    pub async fn sample(
+        &self,
+        collection_name: &str,
+        mut request: SampleRequest,
+        shard_selection: Option<ShardId>,
+    ) -> Result<Vec<Record>, StorageError> {
+        if shard_selection.is_none() {
+            request.filter = self
+                .with_default_filters(collection_name, request.filter.as_ref())
+                .await;
+        }
+        let collection = self.get_collection(collection_name).await?;
+        let _permit = self
+            .reserve_response_memory(
+                &collection,
+                request.limit,
+                Some(&request.with_vector),
+                request.with_payload.as_ref(),
+            )
+            .await?;
+        collection
+            .sample(request, shard_selection)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    /// Number of threads, used by the searches of this peer
+    pub fn search_threads(&self) -> usize {
+        self.search_runtime.threads()
+    }
+
+    /// Run the next searches on the `runtime` with the given number of threads.
+    /// Searches, which are already running, are finished on the previous runtime.
+    pub fn replace_search_runtime(&self, runtime: Runtime, threads: usize) {
+        log::info!("Resizing search runtime to {threads} threads");
+        self.search_runtime.replace(runtime, threads);
+    }
+
+    /// Max number of simultaneously running optimizations of all collections of this peer
+    pub fn max_optimization_threads(&self) -> usize {
+        self.optimization_scheduler.max_optimization_threads()
+    }
+
+    /// Change the max number of simultaneously running optimizations. If 0 - auto selection.
+    pub fn set_max_optimization_threads(&self, max_optimization_threads: usize) {
+        self.optimization_scheduler
+            .set_max_optimization_threads(max_optimization_threads);
+        log::info!(
+            "Max optimization threads are set to {}",
+            self.optimization_scheduler.max_optimization_threads()
+        );
+    }
+
+    /// Seconds, the client is advised to wait before the next write to the collection, if the
+    /// update queue of a local shard is longer than `update_queue_retry_threshold`.
+    /// One second for each threshold of queued updates.
+    pub async fn update_retry_after(&self, collection_name: &str) -> Option<u64> {
+        let threshold = self
+            .storage_config
+            .performance
+            .update_queue_retry_threshold?
+            .max(1);
+        let collection = self.get_collection(collection_name).await.ok()?;
+        let queue_length = collection.max_update_queue_length().await;
+        (queue_length > threshold).then(|| (queue_length / threshold) as u64)
+    }
+
     pub async fn update(
         &self,
         collection_name: &str,
         operation: CollectionUpdateOperations,
         shard_selection: Option<ShardId>,
         wait: bool,
+        ordering: WriteOrdering,
     ) -> Result<UpdateResult, StorageError> {
+        self.update_with_id(
+            collection_name,
+            operation,
+            None,
+            shard_selection,
+            wait,
+            ordering,
+        )
+        .await
+    }
+
+    /// Update with the client-supplied `operation_id`, so retries of the operation are applied once
+    pub async fn update_with_id(
+        &self,
+        collection_name: &str,
+        operation: CollectionUpdateOperations,
+        operation_id: Option<String>,
+        shard_selection: Option<ShardId>,
+        wait: bool,
+        ordering: WriteOrdering,
+    ) -> Result<UpdateResult, StorageError> {
+        if shard_selection.is_none() && operation.is_write_operation() {
+            // Reads all collections, so it is checked before the collection is locked
+            self.check_total_quota().await?;
+        }
         let collection = self.get_collection(collection_name).await?;
         let result = match shard_selection {
             Some(shard_selection) => {
                 collection
-                    .update_from_peer(operation, shard_selection, wait)
+                    .update_from_peer(
+                        OperationWithId::new(operation, operation_id),
+                        shard_selection,
+                        wait,
+                        ordering,
+                    )
                     .await
             }
             None => {
                 if operation.is_write_operation() {
                     self.check_write_lock()?;
+                    self.check_keyed_locks(&collection, &operation).await?;
+                    self.check_collection_quota(&collection).await?;
                 }
-                collection.update_from_client(operation, wait).await
+                collection
+                    .update_from_client_with_id(operation, operation_id, wait, ordering)
+                    .await
             }
         };
         result.map_err(|err| err.into())
     }
 
+    /// Load points into the empty shards of the collection by building indexed segments from them
+    /// directly, bypassing the appendable segments and the optimizer
+    pub async fn bulk_upsert(
+        &self,
+        collection_name: &str,
+        points: PointInsertOperations,
+    ) -> Result<UpdateResult, StorageError> {
+        self.check_total_quota().await?;
+        let collection = self.get_collection(collection_name).await?;
+        self.check_write_lock()?;
+        if self.keyed_locks.read().has_locks(&collection.name()) {
+            return Err(StorageError::Locked {
+                description: format!(
+                    "Collection {collection_name} has locked keys, bulk upsert is not allowed"
+                ),
+            });
+        }
+        self.check_collection_quota(&collection).await?;
+        Ok(collection.bulk_upsert(points).await?)
+    }
+
     fn this_peer_id(&self) -> PeerId {
         self.this_peer_id
     }
@@ -944,6 +2131,8 @@ impl TableOfContent {
         consensus_state::CollectionsSnapshot {
             collections,
             aliases: self.alias_persistence.read().await.state().clone(),
+            default_filters: self.default_filters.read().await.state().clone(),
+            api_tokens: self.api_tokens.read().state().clone(),
         }
     }
 
@@ -997,6 +2186,8 @@ impl TableOfContent {
                             &state.config,
                             shard_distribution,
                             self.channel_service.clone(),
+                            self.optimization_scheduler.clone(),
+                            self.storage_cipher.clone(),
                             Self::on_peer_failure_callback(
                                 self.consensus_proposal_sender.clone(),
                                 id.to_string(),
@@ -1005,10 +2196,16 @@ impl TableOfContent {
                                 self.consensus_proposal_sender.clone(),
                                 id.to_string(),
                             ),
+                            Self::on_shard_event_callback(
+                                self.state_changes.clone(),
+                                id.to_string(),
+                            ),
                         )
                         .await?;
                         collections.validate_collection_not_exists(id).await?;
                         collections.insert(id.to_string(), collection);
+                        self.state_changes
+                            .notify(StateChange::Collection(id.to_string()));
                     }
                 }
             }
@@ -1029,6 +2226,11 @@ impl TableOfContent {
                 .write()
                 .await
                 .apply_state(data.aliases)?;
+            self.default_filters
+                .write()
+                .await
+                .apply_state(data.default_filters)?;
+            self.api_tokens.write().apply_state(data.api_tokens)?;
             Ok(())
         })
     }
@@ -1042,7 +2244,70 @@ impl TableOfContent {
         // snapshot directory is mounted as network share and multiple writes to it could be slow
         let tmp_dir = Path::new(&self.storage_config.storage_path).join(SNAPSHOTS_TMP_DIR);
         tokio::fs::create_dir_all(&tmp_dir).await?;
-        Ok(collection.create_snapshot(&tmp_dir).await?)
+        Ok(collection
+            .create_snapshot(&tmp_dir, self.max_parallel_snapshot_shards())
+            .await?)
+    }
+
+    /// Create snapshot of the segments of the collection, changed since the `base` snapshot
+    pub async fn create_incremental_snapshot(
+        &self,
+        collection_name: &str,
+        base: &str,
+    ) -> Result<SnapshotDescription, StorageError> {
+        let collection = self.get_collection(collection_name).await?;
+        let tmp_dir = Path::new(&self.storage_config.storage_path).join(SNAPSHOTS_TMP_DIR);
+        tokio::fs::create_dir_all(&tmp_dir).await?;
+        Ok(collection
+            .create_incremental_snapshot(&tmp_dir, base, self.max_parallel_snapshot_shards())
+            .await?)
+    }
+
+    /// Number of shards of a collection, snapshotted simultaneously
+    fn max_parallel_snapshot_shards(&self) -> usize {
+        match self.storage_config.performance.max_parallel_snapshot_shards {
+            0 => num_cpus::get(),
+            max_parallel_shards => max_parallel_shards,
+        }
+    }
+
+    /// Create snapshot of the collection, while writes into it are forbidden by a keyed lock.
+    /// Used to get a consistent state of each collection in the full storage snapshot.
+    pub async fn create_locked_snapshot(
+        &self,
+        collection_name: &str,
+    ) -> Result<SnapshotDescription, StorageError> {
+        let collection_name = self.resolve_name(collection_name).await?;
+        let lock_name = format!("{FULL_SNAPSHOT_FILE_NAME}-{collection_name}");
+        self.keyed_locks.write().acquire(
+            lock_name.clone(),
+            KeyedLockRequest {
+                scope: LockScope {
+                    collection: collection_name.clone(),
+                    shard_id: None,
+                },
+                owner: Some(FULL_SNAPSHOT_FILE_NAME.to_string()),
+                reason: Some(FULL_SNAPSHOT_LOCK_MESSAGE.to_string()),
+                ttl_sec: Some(FULL_SNAPSHOT_LOCK_TTL_SEC),
+            },
+        )?;
+        // Lock is released even if the future is cancelled
+        let _lock_guard = KeyedLockGuard {
+            locks: &self.keyed_locks,
+            name: lock_name,
+            owner: FULL_SNAPSHOT_FILE_NAME,
+        };
+        self.create_snapshot(&collection_name).await
+    }
+
+    /// Returns an error if another full storage snapshot is currently being created
+    pub fn lock_full_snapshot(&self) -> Result<OwnedMutexGuard<()>, StorageError> {
+        self.full_snapshot_lock
+            .clone()
+            .try_lock_owned()
+            .map_err(|_| StorageError::BadRequest {
+                description: "Another full snapshot is already in progress".to_string(),
+            })
     }
 
     pub async fn suggest_shard_distribution(
@@ -1050,11 +2315,12 @@ impl TableOfContent {
         op: &CreateCollectionOperation,
         suggested_shard_number: NonZeroU32,
     ) -> ShardDistributionProposal {
-        let shard_number = op
-            .create_collection
-            .shard_number
-            .and_then(NonZeroU32::new)
-            .unwrap_or(suggested_shard_number);
+        let shard_number = match op.create_collection.shard_number {
+            Some(shard_number) => Some(shard_number),
+            None => self.init_from_shard_number(&op.create_collection).await,
+        }
+        .and_then(NonZeroU32::new)
+        .unwrap_or(suggested_shard_number);
         let mut known_peers_set: HashSet<_> = self
             .channel_service
             .id_to_address
@@ -1070,19 +2336,40 @@ impl TableOfContent {
             .and_then(NonZeroU32::new)
             .unwrap_or_else(default_replication_factor);
 
-        let shard_distribution =
-            ShardDistributionProposal::new(shard_number, replication_factor, &known_peers);
+        let peer_loads = self.peer_shard_counts().await;
+
+        let shard_distribution = ShardDistributionProposal::new(
+            shard_number,
+            replication_factor,
+            &known_peers,
+            &peer_loads,
+        );
 
         log::debug!(
-            "Suggesting distribution for {} shards for collection '{}' among {} peers {:?}",
+            "Suggesting distribution for {} shards for collection '{}' among {} peers {:?}, current peer loads {:?}",
             shard_number,
             op.collection_name,
             known_peers.len(),
-            shard_distribution.distribution
+            shard_distribution.distribution,
+            peer_loads
         );
         shard_distribution
     }
 
+    /// Number of shard replicas of all collections, placed on each peer.
+    /// Collection states are replicated by consensus, so it also covers shards of remote peers.
+    pub async fn peer_shard_counts(&self) -> HashMap<PeerId, usize> {
+        let mut peer_shard_counts: HashMap<PeerId, usize> = HashMap::new();
+        for collection in self.collections.read().await.values() {
+            for shard_info in collection.state().await.shards.values() {
+                for peer_id in shard_info.replicas.keys() {
+                    *peer_shard_counts.entry(*peer_id).or_default() += 1;
+                }
+            }
+        }
+        peer_shard_counts
+    }
+
     pub async fn suggest_shard_replica_changes(
         &self,
         collection: &CollectionId,
@@ -1103,6 +2390,53 @@ impl TableOfContent {
         Ok(changes)
     }
 
+    /// Propose transfers, which bring under-replicated shards of all collections
+    /// up to their `replication_factor`.
+    ///
+    /// `live_peers` - peers, which are reachable and may be sources or targets of the transfers.
+    ///
+    /// Returns the number of proposed transfers.
+    pub async fn reconcile_replication(
+        &self,
+        live_peers: &[PeerId],
+    ) -> Result<usize, StorageError> {
+        let proposal_sender = match &self.consensus_proposal_sender {
+            Some(proposal_sender) => proposal_sender,
+            None => {
+                return Err(StorageError::service_error(
+                    "Can't reconcile replication: this is a single node deployment",
+                ))
+            }
+        };
+        let mut proposed = 0;
+        for (collection_name, collection) in self.collections.read().await.iter() {
+            let collection_state::State {
+                config,
+                shards,
+                transfers,
+            } = collection.state().await;
+            let replication_factor = config.params.replication_factor.get() as usize;
+            let replications =
+                suggest_replications(replication_factor, &shards, &transfers, live_peers);
+            for transfer in replications {
+                log::info!(
+                    "Replicating shard {} of collection {} from peer {} to peer {} to restore replication factor {}",
+                    transfer.shard_id,
+                    collection_name,
+                    transfer.from,
+                    transfer.to,
+                    replication_factor
+                );
+                proposal_sender.send(ConsensusOperations::start_transfer(
+                    collection_name.clone(),
+                    transfer,
+                ))?;
+                proposed += 1;
+            }
+        }
+        Ok(proposed)
+    }
+
     pub async fn get_telemetry_data(&self) -> Vec<CollectionTelemetry> {
         let mut result = Vec::new();
         let all_collections = self.all_collections().await;
@@ -1114,6 +2448,26 @@ impl TableOfContent {
         result
     }
 
+    /// Check that we are not removing some unique shards with the peer.
+    /// Used on apply of the peer removal and before proposing it.
+    pub async fn validate_peer_removal(&self, peer_id: PeerId) -> Result<(), StorageError> {
+        for collection_name in self.all_collections().await {
+            let collection = self.get_collection(&collection_name).await?;
+            let collection_state = collection.state().await;
+            for (shard_id, shard) in collection_state.shards.iter() {
+                if shard.replicas.len() == 1 && shard.replicas.contains_key(&peer_id) {
+                    return Err(StorageError::bad_request(&format!(
+                        "Cannot remove peer {} because it is the only replica of shard {} of collection {}",
+                        peer_id,
+                        shard_id,
+                        collection_name
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
     pub async fn peer_has_shards(&self, peer_id: PeerId) -> bool {
         for collection in self.collections.read().await.values() {
             let state = collection.state().await;
@@ -1129,6 +2483,61 @@ impl TableOfContent {
         false
     }
 
+    /// Reserve memory for the response of the read request.
+    /// Fails with a retryable error, if too much memory is already taken by in-flight responses
+    async fn reserve_response_memory(
+        &self,
+        collection: &Collection,
+        points: usize,
+        with_vector: Option<&WithVector>,
+        with_payload: Option<&WithPayloadInterface>,
+    ) -> Result<ResponseMemoryPermit, StorageError> {
+        if !self.response_memory.is_limited() {
+            return Ok(ResponseMemoryPermit::unlimited());
+        }
+        let without_vector = WithVector::Bool(false);
+        let size = collection
+            .estimate_response_size(
+                points,
+                with_vector.unwrap_or(&without_vector),
+                with_payload.map_or(false, |with_payload| with_payload.is_required()),
+            )
+            .await;
+        self.response_memory.try_acquire(size)
+    }
+
+    /// Background jobs of all collections
+    pub async fn list_jobs(&self) -> Vec<JobInfo> {
+        let mut jobs = vec![];
+        for collection in self.collections.read().await.values() {
+            jobs.extend(collection.list_jobs().await);
+        }
+        jobs
+    }
+
+    pub async fn get_job(&self, job_id: &str) -> Result<JobInfo, StorageError> {
+        for collection in self.collections.read().await.values() {
+            if collection.has_job(job_id).await {
+                return Ok(collection.get_job(job_id).await?);
+            }
+        }
+        Err(StorageError::NotFound {
+            description: format!("Job {job_id} not found"),
+        })
+    }
+
+    /// Stop the running background job, regardless of the collection it belongs to
+    pub async fn cancel_job(&self, job_id: &str) -> Result<JobInfo, StorageError> {
+        for collection in self.collections.read().await.values() {
+            if collection.has_job(job_id).await {
+                return Ok(collection.cancel_job(job_id).await?);
+            }
+        }
+        Err(StorageError::NotFound {
+            description: format!("Job {job_id} not found"),
+        })
+    }
+
     pub fn set_locks(&self, is_write_locked: bool, error_message: Option<String>) {
         self.is_write_locked
             .store(is_write_locked, Ordering::Relaxed);
@@ -1143,6 +2552,116 @@ impl TableOfContent {
         self.lock_error_message.lock().clone()
     }
 
+    /// Transitions of collections, e.g. status changes and completed optimizations
+    pub fn state_changes(&self) -> &StateChanges {
+        &self.state_changes
+    }
+
+    /// Build WAL config of a new collection.
+    /// Returns an error if the collection should be encrypted, but the key is not configured.
+    pub fn new_collection_wal_config(
+        &self,
+        wal_config_diff: Option<WalConfigDiff>,
+    ) -> Result<WalConfig, StorageError> {
+        let wal_config = match wal_config_diff {
+            None => self.storage_config.wal.clone(),
+            Some(diff) => diff.update(&self.storage_config.wal)?,
+        };
+        if wal_config.encrypted && self.storage_cipher.is_none() {
+            return Err(StorageError::BadInput {
+                description: "Can't create encrypted collection: encryption key is not configured"
+                    .to_string(),
+            });
+        }
+        Ok(wal_config)
+    }
+
+    /// Usage of the collection on this peer, measured at most once per refresh interval
+    async fn collection_usage(
+        &self,
+        collection: &Collection,
+    ) -> Result<CollectionUsage, StorageError> {
+        let collection_name = collection.name();
+        if let Some(usage) = self.usage_cache.get(&collection_name) {
+            return Ok(usage);
+        }
+        let usage = collection.local_usage().await?;
+        self.usage_cache.insert(collection_name, usage);
+        Ok(usage)
+    }
+
+    /// Recent searches and recommendations of the collection, which exceeded the configured threshold
+    pub async fn slow_queries(
+        &self,
+        collection_name: &str,
+    ) -> Result<Vec<SlowQuery>, StorageError> {
+        let collection = self.get_collection(collection_name).await?;
+        Ok(self.slow_queries.get(&collection.name()))
+    }
+
+    /// Records the request into the slow query log, if it took longer than the threshold
+    #[allow(clippy::too_many_arguments)]
+    async fn record_slow_query(
+        &self,
+        collection: &Collection,
+        method: &str,
+        request: &impl Serialize,
+        shard_selection: Option<ShardId>,
+        preparation_time: Duration,
+        total_time: Duration,
+        error: Option<&CollectionError>,
+    ) {
+        if !self.slow_queries.is_slow(total_time) {
+            return;
+        }
+        let shards = match shard_selection {
+            Some(shard_id) => vec![shard_id],
+            None => {
+                let shard_holder = collection.shards_holder();
+                let shard_holder = shard_holder.read().await;
+                shard_holder
+                    .get_shards()
+                    .map(|(shard_id, _)| *shard_id)
+                    .collect()
+            }
+        };
+        let request = serde_json::to_value(request).unwrap_or_default();
+        self.slow_queries.record(
+            collection.name(),
+            SlowQuery {
+                timestamp: Utc::now(),
+                method: method.to_string(),
+                request,
+                timings: SlowQueryTimings::new(preparation_time, total_time),
+                shards,
+                error: error.map(|err| err.to_string()),
+            },
+        );
+    }
+
+    /// Returns `Forbidden` error if the collection exceeds the configured quota on this peer
+    async fn check_collection_quota(&self, collection: &Collection) -> Result<(), StorageError> {
+        let quota = &self.storage_config.quotas.collection;
+        if !quota.is_limited() {
+            return Ok(());
+        }
+        let usage = self.collection_usage(collection).await?;
+        check_usage(quota, &usage, &format!("Collection {}", collection.name()))
+    }
+
+    /// Returns `Forbidden` error if all collections together exceed the configured quota on this peer
+    async fn check_total_quota(&self) -> Result<(), StorageError> {
+        let quota = &self.storage_config.quotas.total;
+        if !quota.is_limited() {
+            return Ok(());
+        }
+        let mut total_usage = CollectionUsage::default();
+        for collection in self.collections.read().await.values() {
+            total_usage += self.collection_usage(collection).await?;
+        }
+        check_usage(quota, &total_usage, "Storage")
+    }
+
     /// Returns an error if the write lock is set
     pub fn check_write_lock(&self) -> Result<(), StorageError> {
         if self.is_write_locked.load(Ordering::Relaxed) {
@@ -1157,6 +2676,56 @@ impl TableOfContent {
         Ok(())
     }
 
+    /// Acquire or prolong a named write lock on a collection or a shard.
+    /// Returns the state of the acquired lock.
+    ///
+    /// The lock only affects writes, received by this peer. See [`KeyedLocks`].
+    pub async fn acquire_keyed_lock(
+        &self,
+        name: String,
+        mut request: KeyedLockRequest,
+    ) -> Result<KeyedLockInfo, StorageError> {
+        // Locks are always bound to the original collection name, not to the alias
+        request.scope.collection = self.resolve_name(&request.scope.collection).await?;
+        self.keyed_locks.write().acquire(name, request)
+    }
+
+    /// Release a named write lock on behalf of the owner. Returns the released lock, if it was held.
+    pub fn release_keyed_lock(
+        &self,
+        name: &str,
+        owner: Option<&str>,
+    ) -> Result<Option<KeyedLockInfo>, StorageError> {
+        self.keyed_locks.write().release(name, owner)
+    }
+
+    pub fn list_keyed_locks(&self) -> Vec<KeyedLockInfo> {
+        self.keyed_locks.write().list()
+    }
+
+    /// Returns an error if the operation writes into a collection or shard, locked by a named lock
+    async fn check_keyed_locks(
+        &self,
+        collection: &Collection,
+        operation: &CollectionUpdateOperations,
+    ) -> Result<(), StorageError> {
+        let collection_name = collection.name();
+        {
+            let keyed_locks = self.keyed_locks.read();
+            // Fast path: resolving affected shards is not free, skip it if nothing is locked
+            if !keyed_locks.has_locks(&collection_name) {
+                return Ok(());
+            }
+            keyed_locks.check(&collection_name, None)?;
+        }
+        for shard_id in collection.affected_shards(operation).await {
+            self.keyed_locks
+                .read()
+                .check(&collection_name, Some(shard_id))?;
+        }
+        Ok(())
+    }
+
     pub async fn remove_shards_at_peer(&self, peer_id: PeerId) -> Result<(), StorageError> {
         let collections = self.collections.read().await;
         for collection in collections.values() {
@@ -1192,25 +2761,7 @@ impl CollectionContainer for TableOfContent {
 
     fn remove_peer(&self, peer_id: PeerId) -> Result<(), StorageError> {
         self.collection_management_runtime.block_on(async {
-            // Validation:
-            // 1. Check that we are not removing some unique shards
-
-            for collection_name in self.all_collections().await {
-                let collection = self.get_collection(&collection_name).await?;
-                let collection_state = collection.state().await;
-                for (shard_id, shard) in collection_state.shards.iter() {
-                    if shard.replicas.len() == 1 && shard.replicas.contains_key(&peer_id) {
-                        return Err(StorageError::bad_request(&format!(
-                            "Cannot remove peer {} because it is the only replica of shard {} of collection {}",
-                            peer_id,
-                            shard_id,
-                            collection_name
-                        )));
-                    }
-                }
-            }
-
-            // Validation passed
+            self.validate_peer_removal(peer_id).await?;
 
             self.remove_shards_at_peer(peer_id).await?;
 
@@ -1237,13 +2788,24 @@ impl CollectionContainer for TableOfContent {
     }
 }
 
+/// Releases the keyed lock, when dropped
+struct KeyedLockGuard<'a> {
+    locks: &'a parking_lot::RwLock<KeyedLocks>,
+    name: String,
+    owner: &'static str,
+}
+
+impl Drop for KeyedLockGuard<'_> {
+    fn drop(&mut self) {
+        if let Err(err) = self.locks.write().release(&self.name, Some(self.owner)) {
+            log::warn!("Can't release lock `{}`: {err}", self.name);
+        }
+    }
+}
+
 // `TableOfContent` should not be dropped from async context.
 impl Drop for TableOfContent {
     fn drop(&mut self) {
-        self.collection_management_runtime.block_on(async {
-            for (_, mut collection) in self.collections.write().await.drain() {
-                collection.before_drop().await;
-            }
-        });
+        self.stop_collections();
     }
 }
@@ -3,6 +3,10 @@ use std::ops::Deref;
 use std::sync::Arc;
 use std::time::Duration;
 
+use crate::content_manager::api_tokens::{Access, ClientIdentity};
+use crate::content_manager::jwt::decode_jwt;
+use crate::content_manager::shard_distribution::ShardDistributionProposal;
+use crate::types::{ClusterInfo, ConsensusThreadStatus, ConsensusWalInfo};
 use crate::{
     ClusterStatus, CollectionMetaOperations, ConsensusOperations, ConsensusStateRef, StorageError,
     TableOfContent,
@@ -11,6 +15,11 @@ use crate::{
 pub struct Dispatcher {
     toc: Arc<TableOfContent>,
     consensus_state: Option<ConsensusStateRef>,
+    /// Master API key
+    api_key: Option<String>,
+    /// Secret, which HS256 JWTs are signed with.
+    /// If neither it, nor the master key is set - requests are not authenticated
+    jwt_secret: Option<String>,
 }
 
 impl Dispatcher {
@@ -18,9 +27,57 @@ impl Dispatcher {
         Self {
             toc,
             consensus_state: None,
+            api_key: None,
+            jwt_secret: None,
         }
     }
 
+    pub fn with_api_key(self, api_key: Option<String>) -> Self {
+        Self { api_key, ..self }
+    }
+
+    pub fn with_jwt_secret(self, jwt_secret: Option<String>) -> Self {
+        Self { jwt_secret, ..self }
+    }
+
+    /// Resolve access of the request by its credentials: the master key grants full access,
+    /// a collection token grants access of its scope to its collection only,
+    /// and a JWT grants access, restricted by its claims.
+    /// Also returns the identity of the client, if authentication is enabled.
+    pub fn resolve_access(
+        &self,
+        api_key: Option<&str>,
+        bearer_token: Option<&str>,
+    ) -> Result<(Access, Option<ClientIdentity>), StorageError> {
+        if self.api_key.is_none() && self.jwt_secret.is_none() {
+            return Ok((Access::Full, None));
+        }
+        if let (Some(jwt_secret), Some(bearer_token)) = (&self.jwt_secret, bearer_token) {
+            return decode_jwt(jwt_secret, bearer_token).map(|claims| {
+                let identity = ClientIdentity::from(&claims);
+                (Access::from(claims), Some(identity))
+            });
+        }
+        let api_key = api_key.ok_or_else(|| StorageError::Unauthorized {
+            description: "API key or bearer token is required".to_string(),
+        })?;
+        let is_master_key = self.api_key.as_ref().map_or(false, |master_key| {
+            constant_time_eq(api_key.as_bytes(), master_key.as_bytes())
+        });
+        if is_master_key {
+            return Ok((Access::Full, Some(ClientIdentity::MasterKey)));
+        }
+        self.toc
+            .api_token(api_key)
+            .map(|api_token| {
+                let identity = ClientIdentity::api_token(api_key, &api_token);
+                (Access::Collection(api_token), Some(identity))
+            })
+            .ok_or_else(|| StorageError::Unauthorized {
+                description: "Invalid API key".to_string(),
+            })
+    }
+
     pub fn with_consensus(self, state_ref: ConsensusStateRef) -> Self {
         Self {
             consensus_state: Some(state_ref),
@@ -37,20 +94,38 @@ impl Dispatcher {
     }
 
     /// If `wait_timeout` is not supplied - then default duration will be used.
+    /// `identity` of the client, which requested the operation, is recorded in the audit log.
     /// This function needs to be called from a runtime with timers enabled.
     pub async fn submit_collection_meta_op(
         &self,
         operation: CollectionMetaOperations,
+        identity: Option<ClientIdentity>,
         wait_timeout: Option<Duration>,
     ) -> Result<bool, StorageError> {
+        self.submit_collection_meta_op_with_placement(operation, identity, wait_timeout)
+            .await
+            .map(|(result, _shard_placement)| result)
+    }
+
+    /// Same as `submit_collection_meta_op`, but also returns the distribution of shards,
+    /// chosen for a newly created collection.
+    /// The distribution is only chosen in distributed mode, if it is not set explicitly.
+    pub async fn submit_collection_meta_op_with_placement(
+        &self,
+        operation: CollectionMetaOperations,
+        identity: Option<ClientIdentity>,
+        wait_timeout: Option<Duration>,
+    ) -> Result<(bool, Option<ShardDistributionProposal>), StorageError> {
         // if distributed deployment is enabled
         if let Some(state) = self.consensus_state.as_ref() {
             // List of operations to await for collection to be operational
             let mut expect_operations: Vec<ConsensusOperations> = vec![];
+            let mut shard_placement = None;
 
             let op = match operation {
                 CollectionMetaOperations::CreateCollection(mut op) => {
                     self.toc.check_write_lock()?;
+                    self.toc.validate_create_collection(&op.create_collection)?;
                     if !op.is_distribution_set() {
                         // Suggest even distribution of shards across nodes
                         let number_of_peers = state.0.peer_count();
@@ -74,11 +149,15 @@ impl Dispatcher {
                             }
                         }
 
+                        shard_placement = Some(shard_distribution.clone());
                         op.set_distribution(shard_distribution);
                     }
                     CollectionMetaOperations::CreateCollection(op)
                 }
                 CollectionMetaOperations::UpdateCollection(mut op) => {
+                    self.toc
+                        .validate_update_collection(&op.collection_name, &op.update_collection)
+                        .await?;
                     if let Some(repl_factor) = op
                         .update_collection
                         .params
@@ -109,6 +188,7 @@ impl Dispatcher {
             let res = state
                 .propose_consensus_op_with_await(
                     ConsensusOperations::CollectionMeta(Box::new(op)),
+                    identity,
                     wait_timeout,
                     true,
                 )
@@ -125,12 +205,24 @@ impl Dispatcher {
                 }
             }
 
-            Ok(res)
+            Ok((res, shard_placement))
         } else {
-            if let CollectionMetaOperations::CreateCollection(_) = &operation {
-                self.toc.check_write_lock()?;
+            match &operation {
+                CollectionMetaOperations::CreateCollection(op) => {
+                    self.toc.check_write_lock()?;
+                    self.toc.validate_create_collection(&op.create_collection)?;
+                }
+                CollectionMetaOperations::UpdateCollection(op) => {
+                    self.toc
+                        .validate_update_collection(&op.collection_name, &op.update_collection)
+                        .await?;
+                }
+                _ => {}
             }
-            self.toc.perform_collection_meta_op(operation).await
+            self.toc
+                .perform_collection_meta_op(operation)
+                .await
+                .map(|res| (res, None))
         }
     }
 
@@ -140,6 +232,44 @@ impl Dispatcher {
             None => ClusterStatus::Disabled,
         }
     }
+
+    /// Reasons, why the peer can't recover without a restart. Empty, if the peer is alive
+    pub fn liveness_issues(&self) -> Vec<String> {
+        match self.cluster_status() {
+            ClusterStatus::Enabled(ClusterInfo {
+                consensus_thread_status: ConsensusThreadStatus::Stopped,
+                ..
+            }) => vec!["Consensus thread is stopped".to_string()],
+            ClusterStatus::Enabled(ClusterInfo {
+                consensus_thread_status: ConsensusThreadStatus::StoppedWithErr { err },
+                ..
+            }) => vec![format!("Consensus thread is stopped with error: {err}")],
+            _ => vec![],
+        }
+    }
+
+    /// Reasons, why the peer should not receive traffic: consensus is not working,
+    /// the leader is not known or local shards are not recovered. Empty, if the peer is ready.
+    /// Collections are loaded before the API is served, so they are not checked here
+    pub async fn readiness_issues(&self) -> Vec<String> {
+        let mut issues = self.liveness_issues();
+        if let Some(state) = &self.consensus_state {
+            if !state.is_leader_established.check_ready() {
+                issues.push("Consensus leader is not established".to_string());
+            }
+        }
+        for (collection_name, shard_id, state) in self.toc.unavailable_local_shards().await {
+            issues.push(format!(
+                "Shard {shard_id} of collection {collection_name} is {state:?}"
+            ));
+        }
+        issues
+    }
+
+    /// If `None` - distributed mode is disabled
+    pub fn consensus_wal_info(&self) -> Option<Result<ConsensusWalInfo, StorageError>> {
+        self.consensus_state.as_ref().map(|state| state.wal_info())
+    }
 }
 
 impl Deref for Dispatcher {
@@ -149,3 +279,9 @@ impl Deref for Dispatcher {
         self.toc.deref()
     }
 }
+
+/// Comparison time does not depend on the position of the first mismatch,
+/// so the key can't be guessed byte by byte
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
@@ -6,6 +6,7 @@ use collection::optimizers_builder::OptimizersConfig;
 use collection::shards::shard::PeerId;
 use schemars::JsonSchema;
 use segment::common::anonymize::Anonymize;
+use segment::common::mmap_advice::MmapAdvice;
 use segment::types::HnswConfig;
 use serde::{Deserialize, Serialize};
 use tonic::transport::Uri;
@@ -15,6 +16,43 @@ pub type PeerAddressById = HashMap<PeerId, Uri>;
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 pub struct PerformanceConfig {
     pub max_search_threads: usize,
+    /// Max number of optimizations, running simultaneously across all collections. If 0 - auto selection.
+    #[serde(default)]
+    pub max_optimization_threads: usize,
+    /// Soft limit of the memory, taken by responses of in-flight read requests, in megabytes.
+    /// Requests above the limit are rejected with a retryable error. If not set - not limited.
+    #[serde(default)]
+    pub search_memory_limit_mb: Option<usize>,
+    /// Expected access pattern of the memory-mapped vectors, passed to the kernel.
+    /// `random` disables readahead, which is useless for the HNSW search on fast disks.
+    #[serde(default)]
+    pub mmap_advice: MmapAdvice,
+    /// If true - memory-mapped vectors are read into the page cache when collections are loaded,
+    /// so the first searches after restart are not slowed down by disk reads
+    #[serde(default)]
+    pub mmap_populate: bool,
+    /// Searches and recommendations, which take longer than this threshold in milliseconds,
+    /// are recorded in the slow query log of the collection. If not set - not recorded.
+    #[serde(default)]
+    pub slow_query_threshold_ms: Option<u64>,
+    /// Max number of searches, reading vectors or payloads of on-disk segments from the same disk
+    /// simultaneously. Other searches wait for their turn instead of saturating the disk.
+    /// Segments with vectors in the page cache are not limited. If not set - not limited.
+    #[serde(default)]
+    pub max_disk_searches: Option<usize>,
+    /// If the update queue of a local shard is longer than this threshold, responses of writes
+    /// carry a `Retry-After` hint, so clients could slow down. If not set - no hint is given.
+    #[serde(default)]
+    pub update_queue_retry_threshold: Option<usize>,
+    /// Max number of shards of a collection, which are snapshotted simultaneously.
+    /// If 0 - auto selection.
+    #[serde(default)]
+    pub max_parallel_snapshot_shards: usize,
+    /// Max memory, taken by the vector index builders of all collections at once, in megabytes.
+    /// Builders use fewer threads to fit into the limit, or wait for the running ones to finish.
+    /// If not set - not limited.
+    #[serde(default)]
+    pub indexing_memory_limit_mb: Option<usize>,
 }
 
 /// Global configuration of the storage, loaded on the service launch, default stored in ./config
@@ -29,6 +67,12 @@ pub struct StorageConfig {
     pub wal: WalConfig,
     pub performance: PerformanceConfig,
     pub hnsw_index: HnswConfig,
+    #[serde(default)]
+    pub encryption: EncryptionConfig,
+    #[serde(default)]
+    pub strict_mode: StrictModeConfig,
+    #[serde(default)]
+    pub quotas: QuotaConfig,
 }
 
 fn default_snapshots_path() -> String {
@@ -39,10 +83,79 @@ fn default_on_disk_payload() -> bool {
     false
 }
 
+/// Source of the key, used to encrypt data of collections at rest
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Default)]
+pub struct EncryptionConfig {
+    /// Hex-encoded 256-bit key
+    #[serde(default, skip_serializing)]
+    pub key: Option<String>,
+    /// Shell command, which prints hex-encoded 256-bit key to stdout.
+    /// Allows to fetch the key from an external key management service instead of the config.
+    #[serde(default)]
+    pub key_command: Option<String>,
+}
+
+impl std::fmt::Debug for EncryptionConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EncryptionConfig")
+            .field("key", &self.key.as_ref().map(|_| "<redacted>"))
+            .field("key_command", &self.key_command)
+            .finish()
+    }
+}
+
+/// Limits on the parameters of new collections, checked before the collection is created
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default)]
+pub struct StrictModeConfig {
+    /// If false - limits are not checked
+    #[serde(default)]
+    pub enabled: bool,
+    /// Maximal number of shards of a collection. If none - not limited
+    #[serde(default)]
+    pub max_shard_number: Option<u32>,
+    /// Maximal size of a vector. If none - not limited
+    #[serde(default)]
+    pub max_vector_size: Option<u64>,
+}
+
+/// Limits on the resources, used by collections on this peer.
+/// Checked before write operations, which add data, so deletions are allowed over the limits.
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default)]
+pub struct QuotaConfig {
+    /// Limits of each collection
+    #[serde(default)]
+    pub collection: UsageQuota,
+    /// Limits of all collections together
+    #[serde(default)]
+    pub total: UsageQuota,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Default)]
+pub struct UsageQuota {
+    /// Maximal number of points. If none - not limited
+    #[serde(default)]
+    pub max_points: Option<usize>,
+    /// Maximal size of the files on disk. If none - not limited
+    #[serde(default)]
+    pub max_disk_bytes: Option<u64>,
+    /// Maximal estimated size of the vectors in RAM. If none - not limited
+    #[serde(default)]
+    pub max_ram_bytes: Option<u64>,
+}
+
+impl UsageQuota {
+    pub fn is_limited(&self) -> bool {
+        self.max_points.is_some() || self.max_disk_bytes.is_some() || self.max_ram_bytes.is_some()
+    }
+}
+
 /// Information of a peer in the cluster
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
 pub struct PeerInfo {
     pub uri: String,
+    /// Peer follows the consensus, but never votes
+    #[serde(default)]
+    pub permanent_learner: bool,
     // ToDo: How long ago was the last communication? In milliseconds
     // pub last_responded_millis: usize
 }
@@ -67,6 +180,17 @@ pub struct RaftInfo {
     pub is_voter: bool,
 }
 
+/// State of the WAL of consensus operations
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+pub struct ConsensusWalInfo {
+    /// Number of entries in the WAL
+    pub entries: u64,
+    /// Total size of the WAL files
+    pub size_bytes: u64,
+    /// Index of the latest entry, which the WAL was compacted up to
+    pub compacted_index: u64,
+}
+
 /// Role of the peer in the consensus
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, JsonSchema, Deserialize)]
 pub enum StateRole {
@@ -137,6 +261,7 @@ impl Anonymize for PeerInfo {
     fn anonymize(&self) -> Self {
         PeerInfo {
             uri: self.uri.anonymize(),
+            permanent_learner: self.permanent_learner,
         }
     }
 }
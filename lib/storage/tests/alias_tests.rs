@@ -45,8 +45,20 @@ mod tests {
             wal: Default::default(),
             performance: PerformanceConfig {
                 max_search_threads: 1,
+                max_optimization_threads: 1,
+                search_memory_limit_mb: None,
+                mmap_advice: Default::default(),
+                mmap_populate: false,
+                slow_query_threshold_ms: None,
+                max_disk_searches: None,
+                update_queue_retry_threshold: None,
+                max_parallel_snapshot_shards: 0,
+                indexing_memory_limit_mb: None,
             },
             hnsw_index: Default::default(),
+            encryption: Default::default(),
+            strict_mode: Default::default(),
+            quotas: Default::default(),
         };
 
         let runtime = Runtime::new().unwrap();
@@ -73,6 +85,11 @@ mod tests {
                             vectors: VectorParams {
                                 size: NonZeroU64::new(10).unwrap(),
                                 distance: Distance::Cosine,
+                                hnsw_config: None,
+                                quantization_config: None,
+                                on_disk: None,
+                                datatype: None,
+                                multivector_config: None,
                             }
                             .into(),
                             hnsw_config: None,
@@ -82,9 +99,15 @@ mod tests {
                             on_disk_payload: None,
                             replication_factor: None,
                             write_consistency_factor: None,
+                            init_from: None,
+                            payload_field_types: None,
+                            auto_payload_index: None,
+                            ttl: None,
+                            default_vector: None,
                         },
                     )),
                     None,
+                    None,
                 ),
             )
             .unwrap();
@@ -99,6 +122,7 @@ mod tests {
                         .into()],
                 }),
                 None,
+                None,
             ))
             .unwrap();
 
@@ -123,11 +147,51 @@ mod tests {
                         ],
                 }),
                 None,
+                None,
             ))
             .unwrap();
 
         let _ = handle
             .block_on(dispatcher.get_collection("test_alias3"))
             .unwrap();
+
+        // Whole batch is rejected if any action fails
+        let err = handle
+            .block_on(dispatcher.submit_collection_meta_op(
+                CollectionMetaOperations::ChangeAliases(ChangeAliasesOperation {
+                    actions: vec![
+                        CreateAlias {
+                            collection_name: "test".to_string(),
+                            alias_name: "test_alias4".to_string(),
+                        }
+                        .into(),
+                        RenameAlias {
+                            old_alias_name: "missing_alias".to_string(),
+                            new_alias_name: "test_alias5".to_string(),
+                        }
+                        .into(),
+                    ],
+                }),
+                None,
+                None,
+            ))
+            .unwrap_err();
+        assert!(err.to_string().contains("Alias action #1 failed"));
+
+        assert!(handle
+            .block_on(dispatcher.get_collection("test_alias4"))
+            .is_err());
+        let _ = handle
+            .block_on(dispatcher.get_collection("test_alias3"))
+            .unwrap();
+
+        let expected_aliases = vec![("test_alias3".to_string(), "test".to_string())];
+        assert_eq!(handle.block_on(dispatcher.list_aliases()), expected_aliases);
+        assert_eq!(
+            handle
+                .block_on(dispatcher.list_collection_aliases("test_alias3"))
+                .unwrap(),
+            expected_aliases
+        );
     }
 }
@@ -0,0 +1,272 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::num::NonZeroU64;
+    use std::sync::Arc;
+
+    use collection::config::VectorParams;
+    use collection::operations::point_ops::{
+        PointInsertOperations, PointOperations, PointStruct, WriteOrdering,
+    };
+    use collection::operations::types::{CountRequest, ScrollRequest, SearchRequest};
+    use collection::operations::CollectionUpdateOperations;
+    use collection::optimizers_builder::OptimizersConfig;
+    use segment::types::{
+        Condition, Distance, FieldCondition, Filter, Match, PointIdType, WithVector,
+    };
+    use serde_json::json;
+    use storage::content_manager::collection_meta_ops::{
+        ChangeAliasesOperation, CollectionMetaOperations, CreateAlias, CreateCollection,
+        CreateCollectionOperation, DeleteCollectionOperation, SetDefaultFilter,
+        SetDefaultFilterOperation,
+    };
+    use storage::content_manager::toc::TableOfContent;
+    use storage::types::{PerformanceConfig, StorageConfig};
+    use tempfile::Builder;
+    use tokio::runtime::{Handle, Runtime};
+
+    const POINTS_COUNT: u64 = 10;
+
+    fn storage_config(storage_path: &std::path::Path) -> StorageConfig {
+        StorageConfig {
+            storage_path: storage_path.to_str().unwrap().to_string(),
+            snapshots_path: storage_path.join("snapshots").to_str().unwrap().to_string(),
+            on_disk_payload: false,
+            optimizers: OptimizersConfig {
+                deleted_threshold: 0.5,
+                vacuum_min_vector_number: 100,
+                default_segment_number: 2,
+                max_segment_size: None,
+                memmap_threshold: None,
+                indexing_threshold: 10_000,
+                flush_interval_sec: 30,
+                max_optimization_threads: 1,
+            },
+            wal: Default::default(),
+            performance: PerformanceConfig {
+                max_search_threads: 1,
+                max_optimization_threads: 1,
+                search_memory_limit_mb: None,
+                mmap_advice: Default::default(),
+                mmap_populate: false,
+                slow_query_threshold_ms: None,
+                max_disk_searches: None,
+                update_queue_retry_threshold: None,
+                max_parallel_snapshot_shards: 0,
+                indexing_memory_limit_mb: None,
+            },
+            hnsw_index: Default::default(),
+            encryption: Default::default(),
+            strict_mode: Default::default(),
+            quotas: Default::default(),
+        }
+    }
+
+    fn perform(toc: &TableOfContent, handle: &Handle, op: CollectionMetaOperations) {
+        handle.block_on(toc.perform_collection_meta_op(op)).unwrap();
+    }
+
+    fn create_collection(toc: &TableOfContent, handle: &Handle) {
+        perform(
+            toc,
+            handle,
+            CollectionMetaOperations::CreateCollection(CreateCollectionOperation::new(
+                "test".to_string(),
+                CreateCollection {
+                    vectors: VectorParams {
+                        size: NonZeroU64::new(4).unwrap(),
+                        distance: Distance::Dot,
+                        hnsw_config: None,
+                        quantization_config: None,
+                        on_disk: None,
+                        datatype: None,
+                        multivector_config: None,
+                    }
+                    .into(),
+                    hnsw_config: None,
+                    wal_config: None,
+                    optimizers_config: None,
+                    shard_number: Some(1),
+                    on_disk_payload: None,
+                    replication_factor: None,
+                    write_consistency_factor: None,
+                    init_from: None,
+                    payload_field_types: None,
+                    auto_payload_index: None,
+                    ttl: None,
+                    default_vector: None,
+                },
+            )),
+        );
+    }
+
+    fn set_default_filter(toc: &TableOfContent, handle: &Handle, name: &str, key: &str) {
+        perform(
+            toc,
+            handle,
+            CollectionMetaOperations::SetDefaultFilter(SetDefaultFilterOperation {
+                name: name.to_string(),
+                set_default_filter: SetDefaultFilter {
+                    filter: Some(Filter::new_must(Condition::Field(
+                        FieldCondition::new_match(key.to_string(), Match::from(true)),
+                    ))),
+                },
+            }),
+        );
+    }
+
+    fn search_ids(
+        toc: &TableOfContent,
+        handle: &Handle,
+        name: &str,
+        shard_selection: Option<u32>,
+    ) -> HashSet<PointIdType> {
+        let request = SearchRequest {
+            vector: vec![1.0, 1.0, 1.0, 1.0].into(),
+            filter: None,
+            params: None,
+            limit: POINTS_COUNT as usize,
+            offset: 0,
+            with_payload: None,
+            with_vector: None,
+            score_threshold: None,
+        };
+        handle
+            .block_on(toc.search(name, request, shard_selection))
+            .unwrap()
+            .0
+            .into_iter()
+            .map(|point| point.id)
+            .collect()
+    }
+
+    fn scroll_ids(
+        toc: &TableOfContent,
+        handle: &Handle,
+        name: &str,
+        shard_selection: Option<u32>,
+    ) -> HashSet<PointIdType> {
+        let request = ScrollRequest {
+            offset: None,
+            limit: Some(POINTS_COUNT as usize),
+            filter: None,
+            with_payload: None,
+            with_vector: WithVector::Bool(false),
+            order_by: None,
+            sample: None,
+        };
+        handle
+            .block_on(toc.scroll(name, request, shard_selection))
+            .unwrap()
+            .points
+            .into_iter()
+            .map(|point| point.id)
+            .collect()
+    }
+
+    fn count(
+        toc: &TableOfContent,
+        handle: &Handle,
+        name: &str,
+        shard_selection: Option<u32>,
+    ) -> usize {
+        let request = CountRequest {
+            filter: None,
+            exact: true,
+        };
+        handle
+            .block_on(toc.count(name, request, shard_selection))
+            .unwrap()
+            .count
+    }
+
+    /// Check that search, scroll and count all see the same set of points
+    fn assert_visible(
+        toc: &TableOfContent,
+        handle: &Handle,
+        name: &str,
+        shard_selection: Option<u32>,
+        expected: &[u64],
+    ) {
+        let expected: HashSet<PointIdType> = expected.iter().map(|id| (*id).into()).collect();
+        assert_eq!(search_ids(toc, handle, name, shard_selection), expected);
+        assert_eq!(scroll_ids(toc, handle, name, shard_selection), expected);
+        assert_eq!(count(toc, handle, name, shard_selection), expected.len());
+    }
+
+    #[test]
+    fn test_default_filters() {
+        let storage_dir = Builder::new().prefix("storage").tempdir().unwrap();
+        let runtime = Runtime::new().unwrap();
+        let handle = runtime.handle().clone();
+        let toc = Arc::new(TableOfContent::new(
+            &storage_config(storage_dir.path()),
+            runtime,
+            Default::default(),
+            0,
+            None,
+        ));
+
+        create_collection(&toc, &handle);
+        let points = (0..POINTS_COUNT)
+            .map(|id| PointStruct {
+                id: id.into(),
+                vector: vec![id as f32, 1.0, 0.0, 0.0].into(),
+                payload: Some(json!({ "even": id % 2 == 0, "small": id < 5 }).into()),
+            })
+            .collect();
+        handle
+            .block_on(toc.update(
+                "test",
+                CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(
+                    PointInsertOperations::PointsList(points),
+                )),
+                None,
+                true,
+                WriteOrdering::default(),
+            ))
+            .unwrap();
+        perform(
+            &toc,
+            &handle,
+            CollectionMetaOperations::ChangeAliases(ChangeAliasesOperation {
+                actions: vec![CreateAlias {
+                    collection_name: "test".to_string(),
+                    alias_name: "test_alias".to_string(),
+                }
+                .into()],
+            }),
+        );
+
+        let all: Vec<u64> = (0..POINTS_COUNT).collect();
+        assert_visible(&toc, &handle, "test", None, &all);
+
+        set_default_filter(&toc, &handle, "test", "even");
+        assert_visible(&toc, &handle, "test", None, &[0, 2, 4, 6, 8]);
+        // Alias inherits the default filter of the collection
+        assert_visible(&toc, &handle, "test_alias", None, &[0, 2, 4, 6, 8]);
+
+        // Default filter of the alias is combined with the one of the collection
+        set_default_filter(&toc, &handle, "test_alias", "small");
+        assert_visible(&toc, &handle, "test_alias", None, &[0, 2, 4]);
+        assert_visible(&toc, &handle, "test", None, &[0, 2, 4, 6, 8]);
+
+        // Requests to a specific shard come from other peers, which already applied the filters
+        assert_visible(&toc, &handle, "test", Some(0), &all);
+        assert_visible(&toc, &handle, "test_alias", Some(0), &all);
+
+        perform(
+            &toc,
+            &handle,
+            CollectionMetaOperations::DeleteCollection(DeleteCollectionOperation(
+                "test".to_string(),
+            )),
+        );
+        // Default filter is not inherited by a new collection with the same name
+        create_collection(&toc, &handle);
+        assert!(handle
+            .block_on(toc.default_filter("test"))
+            .unwrap()
+            .is_none());
+    }
+}
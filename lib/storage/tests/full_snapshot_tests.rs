@@ -0,0 +1,208 @@
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU64;
+    use std::path::Path;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use collection::config::VectorParams;
+    use collection::optimizers_builder::OptimizersConfig;
+    use segment::types::{Condition, Distance, FieldCondition, Filter, Match};
+    use storage::content_manager::api_tokens::{ApiToken, ApiTokenScope};
+    use storage::content_manager::collection_meta_ops::{
+        ChangeAliasesOperation, CollectionMetaOperations, CreateAlias, CreateApiTokenOperation,
+        CreateCollection, CreateCollectionOperation, SetDefaultFilter, SetDefaultFilterOperation,
+    };
+    use storage::content_manager::snapshots::{
+        do_create_full_snapshot, do_create_full_snapshot_in_background, do_delete_full_snapshot,
+        do_list_full_snapshots, FULL_SNAPSHOT_CONFIG_FILE, FULL_SNAPSHOT_CONSENSUS_FILE,
+    };
+    use storage::content_manager::toc::TableOfContent;
+    use storage::types::{PerformanceConfig, StorageConfig};
+    use tempfile::Builder;
+    use tokio::runtime::Runtime;
+
+    fn storage_config(storage_path: &Path) -> StorageConfig {
+        StorageConfig {
+            storage_path: storage_path.to_str().unwrap().to_string(),
+            snapshots_path: storage_path.join("snapshots").to_str().unwrap().to_string(),
+            on_disk_payload: false,
+            optimizers: OptimizersConfig {
+                deleted_threshold: 0.5,
+                vacuum_min_vector_number: 100,
+                default_segment_number: 2,
+                max_segment_size: None,
+                memmap_threshold: None,
+                indexing_threshold: 10_000,
+                flush_interval_sec: 30,
+                max_optimization_threads: 1,
+            },
+            wal: Default::default(),
+            performance: PerformanceConfig {
+                max_search_threads: 1,
+                max_optimization_threads: 1,
+                search_memory_limit_mb: None,
+                mmap_advice: Default::default(),
+                mmap_populate: false,
+                slow_query_threshold_ms: None,
+                max_disk_searches: None,
+                update_queue_retry_threshold: None,
+                max_parallel_snapshot_shards: 0,
+                indexing_memory_limit_mb: None,
+            },
+            hnsw_index: Default::default(),
+            encryption: Default::default(),
+            strict_mode: Default::default(),
+            quotas: Default::default(),
+        }
+    }
+
+    fn perform(
+        toc: &TableOfContent,
+        handle: &tokio::runtime::Handle,
+        op: CollectionMetaOperations,
+    ) {
+        handle.block_on(toc.perform_collection_meta_op(op)).unwrap();
+    }
+
+    #[test]
+    fn test_full_snapshot() {
+        let storage_dir = Builder::new().prefix("storage").tempdir().unwrap();
+        let runtime = Runtime::new().unwrap();
+        let handle = runtime.handle().clone();
+        let toc = Arc::new(TableOfContent::new(
+            &storage_config(storage_dir.path()),
+            runtime,
+            Default::default(),
+            0,
+            None,
+        ));
+
+        perform(
+            &toc,
+            &handle,
+            CollectionMetaOperations::CreateCollection(CreateCollectionOperation::new(
+                "test".to_string(),
+                CreateCollection {
+                    vectors: VectorParams {
+                        size: NonZeroU64::new(4).unwrap(),
+                        distance: Distance::Dot,
+                        hnsw_config: None,
+                        quantization_config: None,
+                        on_disk: None,
+                        datatype: None,
+                        multivector_config: None,
+                    }
+                    .into(),
+                    hnsw_config: None,
+                    wal_config: None,
+                    optimizers_config: None,
+                    shard_number: Some(1),
+                    on_disk_payload: None,
+                    replication_factor: None,
+                    write_consistency_factor: None,
+                    init_from: None,
+                    payload_field_types: None,
+                    auto_payload_index: None,
+                    ttl: None,
+                    default_vector: None,
+                },
+            )),
+        );
+        perform(
+            &toc,
+            &handle,
+            CollectionMetaOperations::ChangeAliases(ChangeAliasesOperation {
+                actions: vec![CreateAlias {
+                    collection_name: "test".to_string(),
+                    alias_name: "test_alias".to_string(),
+                }
+                .into()],
+            }),
+        );
+        perform(
+            &toc,
+            &handle,
+            CollectionMetaOperations::SetDefaultFilter(SetDefaultFilterOperation {
+                name: "test_alias".to_string(),
+                set_default_filter: SetDefaultFilter {
+                    filter: Some(Filter::new_must(Condition::Field(
+                        FieldCondition::new_match("visible".to_string(), Match::from(true)),
+                    ))),
+                },
+            }),
+        );
+
+        perform(
+            &toc,
+            &handle,
+            CollectionMetaOperations::CreateApiToken(CreateApiTokenOperation {
+                token: "collection_api_token".to_string(),
+                api_token: ApiToken {
+                    collection_name: "test".to_string(),
+                    scope: ApiTokenScope::Read,
+                },
+            }),
+        );
+
+        // Only one full snapshot could be created at a time
+        let full_snapshot_guard = toc.lock_full_snapshot().unwrap();
+        assert!(handle.block_on(do_create_full_snapshot(&toc)).is_err());
+        assert!(handle
+            .block_on(async { do_create_full_snapshot_in_background(toc.clone()) })
+            .is_err());
+        drop(full_snapshot_guard);
+
+        let description = handle.block_on(do_create_full_snapshot(&toc)).unwrap();
+        // Write lock of the collection is released after its snapshot is created
+        assert!(toc.list_keyed_locks().is_empty());
+
+        let snapshot_path = Path::new(toc.snapshots_path()).join(&description.name);
+        let unpack_dir = Builder::new().prefix("unpacked").tempdir().unwrap();
+        tar::Archive::new(std::fs::File::open(&snapshot_path).unwrap())
+            .unpack(unpack_dir.path())
+            .unwrap();
+        assert!(unpack_dir.path().join(FULL_SNAPSHOT_CONFIG_FILE).exists());
+        let consensus_file =
+            std::fs::File::open(unpack_dir.path().join(FULL_SNAPSHOT_CONSENSUS_FILE)).unwrap();
+        let consensus: serde_json::Value = serde_json::from_reader(consensus_file).unwrap();
+        let collections_data = &consensus["collections_data"];
+        assert!(collections_data["collections"]["test"].is_object());
+        assert_eq!(collections_data["aliases"]["test_alias"], "test");
+        assert!(collections_data["default_filters"]["test_alias"].is_object());
+        // API tokens are not stored in the snapshot
+        assert_eq!(collections_data["api_tokens"], serde_json::json!({}));
+        assert!(!consensus.to_string().contains("collection_api_token"));
+
+        // Names of snapshots have a precision of one second
+        std::thread::sleep(Duration::from_millis(1100));
+
+        let background_description = handle
+            .block_on(async { do_create_full_snapshot_in_background(toc.clone()) })
+            .unwrap();
+        assert_eq!(background_description.size, 0);
+        assert_ne!(background_description.name, description.name);
+
+        let mut created = false;
+        for _ in 0..100 {
+            let snapshots = handle.block_on(do_list_full_snapshots(&toc)).unwrap();
+            if snapshots
+                .iter()
+                .any(|snapshot| snapshot.name == background_description.name)
+            {
+                created = true;
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        assert!(created);
+
+        handle
+            .block_on(do_delete_full_snapshot(&toc, &description.name))
+            .unwrap();
+        assert!(!snapshot_path.exists());
+        assert!(handle
+            .block_on(do_delete_full_snapshot(&toc, "../storage"))
+            .is_err());
+    }
+}
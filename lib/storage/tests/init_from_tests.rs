@@ -0,0 +1,248 @@
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU64;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use collection::config::VectorParams;
+    use collection::operations::point_ops::{
+        PointInsertOperations, PointOperations, PointStruct, WriteOrdering,
+    };
+    use collection::operations::types::{CountRequest, PointRequest};
+    use collection::operations::CollectionUpdateOperations;
+    use collection::optimizers_builder::OptimizersConfig;
+    use segment::types::{Distance, WithPayloadInterface, WithVector};
+    use serde_json::json;
+    use storage::content_manager::collection_meta_ops::{
+        CollectionMetaOperations, CreateCollection, CreateCollectionOperation, InitFrom,
+    };
+    use storage::content_manager::toc::TableOfContent;
+    use storage::types::{PerformanceConfig, StorageConfig};
+    use tempfile::Builder;
+    use tokio::runtime::Runtime;
+
+    const POINTS_COUNT: u64 = 250;
+
+    fn storage_config(storage_path: &std::path::Path) -> StorageConfig {
+        StorageConfig {
+            storage_path: storage_path.to_str().unwrap().to_string(),
+            snapshots_path: storage_path.join("snapshots").to_str().unwrap().to_string(),
+            on_disk_payload: false,
+            optimizers: OptimizersConfig {
+                deleted_threshold: 0.5,
+                vacuum_min_vector_number: 100,
+                default_segment_number: 2,
+                max_segment_size: None,
+                memmap_threshold: None,
+                indexing_threshold: 10_000,
+                flush_interval_sec: 30,
+                max_optimization_threads: 1,
+            },
+            wal: Default::default(),
+            performance: PerformanceConfig {
+                max_search_threads: 1,
+                max_optimization_threads: 1,
+                search_memory_limit_mb: None,
+                mmap_advice: Default::default(),
+                mmap_populate: false,
+                slow_query_threshold_ms: None,
+                max_disk_searches: None,
+                update_queue_retry_threshold: None,
+                max_parallel_snapshot_shards: 0,
+                indexing_memory_limit_mb: None,
+            },
+            hnsw_index: Default::default(),
+            encryption: Default::default(),
+            strict_mode: Default::default(),
+            quotas: Default::default(),
+        }
+    }
+
+    fn create_collection(
+        toc: &TableOfContent,
+        runtime: &tokio::runtime::Handle,
+        name: &str,
+        shard_number: Option<u32>,
+        init_from: Option<&str>,
+    ) -> Result<bool, storage::content_manager::errors::StorageError> {
+        runtime.block_on(
+            toc.perform_collection_meta_op(CollectionMetaOperations::CreateCollection(
+                CreateCollectionOperation::new(
+                    name.to_string(),
+                    CreateCollection {
+                        vectors: VectorParams {
+                            size: NonZeroU64::new(4).unwrap(),
+                            distance: Distance::Dot,
+                            hnsw_config: None,
+                            quantization_config: None,
+                            on_disk: None,
+                            datatype: None,
+                            multivector_config: None,
+                        }
+                        .into(),
+                        hnsw_config: None,
+                        wal_config: None,
+                        optimizers_config: None,
+                        shard_number,
+                        on_disk_payload: None,
+                        replication_factor: None,
+                        write_consistency_factor: None,
+                        init_from: init_from.map(|collection| InitFrom {
+                            collection: collection.to_string(),
+                        }),
+                        payload_field_types: None,
+                        auto_payload_index: None,
+                        ttl: None,
+                        default_vector: None,
+                    },
+                ),
+            )),
+        )
+    }
+
+    fn upsert(
+        toc: &TableOfContent,
+        runtime: &tokio::runtime::Handle,
+        collection: &str,
+        points: Vec<PointStruct>,
+    ) {
+        runtime
+            .block_on(toc.update(
+                collection,
+                CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(
+                    PointInsertOperations::PointsList(points),
+                )),
+                None,
+                true,
+                WriteOrdering::default(),
+            ))
+            .unwrap();
+    }
+
+    fn point(id: u64, source: &str) -> PointStruct {
+        PointStruct {
+            id: id.into(),
+            vector: vec![id as f32, 1.0, 0.0, 0.0].into(),
+            payload: Some(json!({ "source": source }).into()),
+        }
+    }
+
+    fn count(toc: &TableOfContent, runtime: &tokio::runtime::Handle, collection: &str) -> usize {
+        runtime
+            .block_on(toc.count(
+                collection,
+                CountRequest {
+                    filter: None,
+                    exact: true,
+                },
+                None,
+            ))
+            .unwrap()
+            .count
+    }
+
+    #[test]
+    fn test_init_from() {
+        let storage_dir = Builder::new().prefix("storage").tempdir().unwrap();
+        let runtime = Runtime::new().unwrap();
+        let handle = runtime.handle().clone();
+        let toc = Arc::new(TableOfContent::new(
+            &storage_config(storage_dir.path()),
+            runtime,
+            Default::default(),
+            0,
+            None,
+        ));
+
+        create_collection(&toc, &handle, "source", Some(3), None).unwrap();
+        upsert(
+            &toc,
+            &handle,
+            "source",
+            (0..POINTS_COUNT).map(|id| point(id, "source")).collect(),
+        );
+
+        // Shard number must match the source one
+        assert!(create_collection(&toc, &handle, "mismatch", Some(2), Some("source")).is_err());
+
+        // Shard number is inherited from the source by default
+        create_collection(&toc, &handle, "copy", None, Some("source")).unwrap();
+
+        let mut initialized = false;
+        for _ in 0..100 {
+            let collection = handle.block_on(toc.get_collection("copy")).unwrap();
+            let info = handle.block_on(collection.info(None)).unwrap();
+            drop(collection);
+            assert_eq!(info.config.params.shard_number.get(), 3);
+            match info.initialization {
+                None => {
+                    initialized = true;
+                    break;
+                }
+                Some(status) => {
+                    assert_eq!(status.source, "source");
+                    assert!(status.error.is_none());
+                }
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        assert!(initialized);
+        assert_eq!(count(&toc, &handle, "copy"), POINTS_COUNT as usize);
+    }
+
+    #[test]
+    fn test_init_from_keeps_newer_points() {
+        let storage_dir = Builder::new().prefix("storage").tempdir().unwrap();
+        let runtime = Runtime::new().unwrap();
+        let handle = runtime.handle().clone();
+        let toc = Arc::new(TableOfContent::new(
+            &storage_config(storage_dir.path()),
+            runtime,
+            Default::default(),
+            0,
+            None,
+        ));
+
+        create_collection(&toc, &handle, "source", Some(2), None).unwrap();
+        upsert(
+            &toc,
+            &handle,
+            "source",
+            (0..POINTS_COUNT).map(|id| point(id, "source")).collect(),
+        );
+        create_collection(&toc, &handle, "target", Some(2), None).unwrap();
+
+        // Written by a client before the copying reached the point
+        upsert(&toc, &handle, "target", vec![point(7, "client")]);
+
+        handle
+            .block_on(toc.init_collection_from("source", "target"))
+            .unwrap();
+
+        assert_eq!(count(&toc, &handle, "target"), POINTS_COUNT as usize);
+        let records = handle
+            .block_on(toc.retrieve(
+                "target",
+                PointRequest {
+                    ids: vec![7.into(), 8.into()],
+                    with_payload: Some(WithPayloadInterface::Bool(true)),
+                    with_vector: WithVector::Bool(false),
+                },
+                None,
+            ))
+            .unwrap();
+        let source_of = |id: u64| {
+            let record = records
+                .iter()
+                .find(|record| record.id == id.into())
+                .unwrap();
+            record.payload.as_ref().unwrap().0["source"].clone()
+        };
+        assert_eq!(source_of(7), json!("client"));
+        assert_eq!(source_of(8), json!("source"));
+
+        let collection = handle.block_on(toc.get_collection("target")).unwrap();
+        let info = handle.block_on(collection.info(None)).unwrap();
+        assert!(info.initialization.is_none());
+    }
+}
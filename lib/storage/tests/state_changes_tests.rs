@@ -0,0 +1,176 @@
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU64;
+    use std::time::Duration;
+
+    use collection::config::VectorParams;
+    use collection::operations::point_ops::{
+        PointInsertOperations, PointOperations, PointStruct, WriteOrdering,
+    };
+    use collection::operations::CollectionUpdateOperations;
+    use collection::optimizers_builder::OptimizersConfig;
+    use segment::types::Distance;
+    use storage::content_manager::collection_meta_ops::{
+        CollectionMetaOperations, CreateCollection, CreateCollectionOperation,
+        DeleteCollectionOperation,
+    };
+    use storage::content_manager::events::StateChange;
+    use storage::content_manager::toc::TableOfContent;
+    use storage::types::{PerformanceConfig, StorageConfig};
+    use tempfile::Builder;
+    use tokio::runtime::Runtime;
+    use tokio::sync::broadcast::Receiver;
+
+    const POINTS_COUNT: u64 = 500;
+
+    fn storage_config(storage_path: &std::path::Path) -> StorageConfig {
+        StorageConfig {
+            storage_path: storage_path.to_str().unwrap().to_string(),
+            snapshots_path: storage_path.join("snapshots").to_str().unwrap().to_string(),
+            on_disk_payload: false,
+            optimizers: OptimizersConfig {
+                deleted_threshold: 0.5,
+                vacuum_min_vector_number: 100,
+                default_segment_number: 1,
+                max_segment_size: None,
+                memmap_threshold: None,
+                indexing_threshold: 100,
+                flush_interval_sec: 30,
+                max_optimization_threads: 1,
+            },
+            wal: Default::default(),
+            performance: PerformanceConfig {
+                max_search_threads: 1,
+                max_optimization_threads: 1,
+                search_memory_limit_mb: None,
+                mmap_advice: Default::default(),
+                mmap_populate: false,
+                slow_query_threshold_ms: None,
+                max_disk_searches: None,
+                update_queue_retry_threshold: None,
+                max_parallel_snapshot_shards: 0,
+                indexing_memory_limit_mb: None,
+            },
+            hnsw_index: Default::default(),
+            encryption: Default::default(),
+            strict_mode: Default::default(),
+            quotas: Default::default(),
+        }
+    }
+
+    /// Wait for the first change, which matches the `condition`
+    async fn wait_for_change(
+        changes: &mut Receiver<StateChange>,
+        condition: impl Fn(&StateChange) -> bool,
+    ) -> StateChange {
+        tokio::time::timeout(Duration::from_secs(30), async {
+            loop {
+                let change = changes.recv().await.unwrap();
+                if condition(&change) {
+                    return change;
+                }
+            }
+        })
+        .await
+        .expect("State change is not reported")
+    }
+
+    #[test]
+    fn test_collection_state_changes() {
+        let storage_dir = Builder::new().prefix("storage").tempdir().unwrap();
+        let runtime = Runtime::new().unwrap();
+        let handle = runtime.handle().clone();
+        let toc = TableOfContent::new(
+            &storage_config(storage_dir.path()),
+            runtime,
+            Default::default(),
+            0,
+            None,
+        );
+        let mut changes = toc.state_changes().subscribe();
+
+        handle
+            .block_on(
+                toc.perform_collection_meta_op(CollectionMetaOperations::CreateCollection(
+                    CreateCollectionOperation::new(
+                        "test".to_string(),
+                        CreateCollection {
+                            vectors: VectorParams {
+                                size: NonZeroU64::new(4).unwrap(),
+                                distance: Distance::Dot,
+                                hnsw_config: None,
+                                quantization_config: None,
+                                on_disk: None,
+                                datatype: None,
+                                multivector_config: None,
+                            }
+                            .into(),
+                            hnsw_config: None,
+                            wal_config: None,
+                            optimizers_config: None,
+                            shard_number: Some(1),
+                            on_disk_payload: None,
+                            replication_factor: None,
+                            write_consistency_factor: None,
+                            init_from: None,
+                            payload_field_types: None,
+                            auto_payload_index: None,
+                            ttl: None,
+                            default_vector: None,
+                        },
+                    ),
+                )),
+            )
+            .unwrap();
+
+        let created = handle.block_on(wait_for_change(&mut changes, |_| true));
+        assert_eq!(created, StateChange::Collection("test".to_string()));
+
+        let points = (0..POINTS_COUNT)
+            .map(|id| PointStruct {
+                id: id.into(),
+                vector: vec![id as f32, 1.0, 0.0, 0.0].into(),
+                payload: None,
+            })
+            .collect();
+        handle
+            .block_on(toc.update(
+                "test",
+                CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(
+                    PointInsertOperations::PointsList(points),
+                )),
+                None,
+                true,
+                WriteOrdering::default(),
+            ))
+            .unwrap();
+
+        // Segment exceeds the indexing threshold, so it is optimized right after the update
+        let optimized = handle.block_on(wait_for_change(&mut changes, |change| {
+            matches!(change, StateChange::OptimizationCompleted { .. })
+        }));
+        assert_eq!(
+            optimized,
+            StateChange::OptimizationCompleted {
+                collection_name: "test".to_string(),
+                shard_id: 0,
+            }
+        );
+        // Status is re-checked after the optimization
+        let status_check = handle.block_on(wait_for_change(&mut changes, |_| true));
+        assert_eq!(status_check, StateChange::Collection("test".to_string()));
+
+        handle
+            .block_on(
+                toc.perform_collection_meta_op(CollectionMetaOperations::DeleteCollection(
+                    DeleteCollectionOperation("test".to_string()),
+                )),
+            )
+            .unwrap();
+
+        let deleted = handle.block_on(wait_for_change(&mut changes, |change| {
+            !matches!(change, StateChange::OptimizationCompleted { .. })
+        }));
+        assert_eq!(deleted, StateChange::Collection("test".to_string()));
+    }
+}
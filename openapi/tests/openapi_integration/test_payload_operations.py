@@ -96,3 +96,58 @@ def test_payload_operations():
     assert response.ok
     assert len(response.json()['result']['payload']) == 0
 
+
+
+def test_update_payload():
+    response = request_with_validation(
+        api='/collections/{collection_name}/points/payload',
+        method="POST",
+        path_params={'collection_name': collection_name},
+        query_params={'wait': 'true'},
+        body={
+            "payload": {"views": 1, "tags": ["a"]},
+            "points": [6]
+        }
+    )
+    assert response.ok
+
+    response = request_with_validation(
+        api='/collections/{collection_name}/points/payload/update',
+        method="POST",
+        path_params={'collection_name': collection_name},
+        query_params={'wait': 'true'},
+        body={
+            "updates": [
+                {"increment": {"key": "views", "by": 2}},
+                {"append": {"key": "tags", "values": ["b"]}},
+                {"set_if_absent": {"key": "views", "value": 100}},
+                {"set_if_absent": {"key": "author", "value": "anonymous"}},
+            ],
+            "points": [6]
+        }
+    )
+    assert response.ok
+
+    response = request_with_validation(
+        api='/collections/{collection_name}/points/{id}',
+        method="GET",
+        path_params={'collection_name': collection_name, 'id': 6},
+    )
+    assert response.ok
+    payload = response.json()['result']['payload']
+    assert payload['views'] == 3
+    assert payload['tags'] == ["a", "b"]
+    assert payload['author'] == "anonymous"
+
+    # increment of a non-numeric value is rejected
+    response = request_with_validation(
+        api='/collections/{collection_name}/points/payload/update',
+        method="POST",
+        path_params={'collection_name': collection_name},
+        query_params={'wait': 'true'},
+        body={
+            "updates": [{"increment": {"key": "author", "by": 1}}],
+            "points": [6]
+        }
+    )
+    assert response.status_code == 400
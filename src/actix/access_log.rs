@@ -0,0 +1,78 @@
+use std::future::{ready, Ready};
+use std::time::Instant;
+
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::CONTENT_LENGTH;
+use actix_web::Error;
+use futures_util::future::LocalBoxFuture;
+
+use crate::common::access_log::{duration_ms, is_access_log_enabled, log_access, AccessLogRecord};
+
+pub struct AccessLogService<S> {
+    service: S,
+}
+
+/// Writes a JSON line into the access log for each handled request
+pub struct AccessLogTransform;
+
+impl<S, B> Service<ServiceRequest> for AccessLogService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_web::dev::forward_ready!(service);
+
+    fn call(&self, request: ServiceRequest) -> Self::Future {
+        if !is_access_log_enabled() {
+            return Box::pin(self.service.call(request));
+        }
+
+        let match_pattern = request
+            .match_pattern()
+            .unwrap_or_else(|| "unknown".to_owned());
+        let method = format!("{} {}", request.method(), match_pattern);
+        let request_size = request
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok());
+        let future = self.service.call(request);
+        Box::pin(async move {
+            let instant = Instant::now();
+            let response = future.await?;
+            // Path is matched by the router, so the collection is known only after handling
+            let collection = response.request().match_info().get("name");
+            log_access(&AccessLogRecord {
+                protocol: "http",
+                method: &method,
+                collection,
+                status: response.response().status().as_u16(),
+                duration_ms: duration_ms(instant.elapsed()),
+                request_size,
+            });
+            Ok(response)
+        })
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for AccessLogTransform
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = AccessLogService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(AccessLogService { service }))
+    }
+}
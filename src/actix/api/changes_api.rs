@@ -0,0 +1,92 @@
+use actix_web::rt::time::Instant;
+use actix_web::web::Query;
+use actix_web::{get, web, Either, HttpResponse, Responder};
+use collection::operations::changes::PointsChange;
+use collection::shards::shard::ShardId;
+use futures::StreamExt;
+use schemars::JsonSchema;
+use segment::types::SeqNumberType;
+use serde::{Deserialize, Serialize};
+use storage::content_manager::toc::TableOfContent;
+use tokio::time::interval_at;
+
+use crate::actix::helpers::process_response;
+use crate::common::events::{EVENTS_KEEP_ALIVE_INTERVAL, EVENTS_KEEP_ALIVE_MESSAGE};
+use crate::common::points::do_stream_points_changes;
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct PointsChangesParam {
+    /// Shard to read changes of, may be omitted if there is only one local shard
+    pub shard_id: Option<ShardId>,
+    /// Sequential number of the first operation to stream
+    #[serde(default)]
+    pub from: SeqNumberType,
+    /// Max number of operations to read from the WAL at once
+    pub limit: Option<usize>,
+}
+
+/// Format change as a message of the server-sent events stream.
+/// Operation number is used as the id of the message, so clients know where to resume from.
+fn to_sse_message(change: &PointsChange) -> String {
+    let data = serde_json::to_string(change).unwrap_or_default();
+    format!(
+        "id: {}\nevent: {}\ndata: {data}\n\n",
+        change.op_num,
+        change.event.name()
+    )
+}
+
+/// Stream of the changes of the points, read from the WAL of the shard, as server-sent events
+#[get("/collections/{name}/points/changes")]
+pub async fn stream_points_changes(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<String>,
+    params: Query<PointsChangesParam>,
+) -> impl Responder {
+    let collection_name = path.into_inner();
+    let PointsChangesParam {
+        shard_id,
+        from,
+        limit,
+    } = params.into_inner();
+
+    let timing = Instant::now();
+    let changes =
+        match do_stream_points_changes(toc.into_inner(), collection_name, shard_id, from, limit)
+            .await
+        {
+            Ok(changes) => changes.boxed(),
+            Err(err) => return Either::Left(process_response::<()>(Err(err), timing)),
+        };
+
+    // Idle connections are kept alive with comments, otherwise proxies may close them
+    let keep_alive = interval_at(
+        Instant::now() + EVENTS_KEEP_ALIVE_INTERVAL,
+        EVENTS_KEEP_ALIVE_INTERVAL,
+    );
+    let stream = futures::stream::unfold(Some((changes, keep_alive)), |state| async move {
+        let (mut changes, mut keep_alive) = state?;
+        let message = tokio::select! {
+            change = changes.next() => match change {
+                Some(Ok(change)) => web::Bytes::from(to_sse_message(&change)),
+                Some(Err(err)) => {
+                    // Status of the response is already sent, so the error is the last event
+                    let data = serde_json::json!({ "error": err.to_string() });
+                    let message = format!("event: error\ndata: {data}\n\n");
+                    return Some((Ok::<_, actix_web::Error>(web::Bytes::from(message)), None));
+                }
+                None => return None,
+            },
+            _ = keep_alive.tick() => {
+                web::Bytes::from_static(EVENTS_KEEP_ALIVE_MESSAGE.as_bytes())
+            }
+        };
+        Some((Ok(message), Some((changes, keep_alive))))
+    });
+    Either::Right(
+        HttpResponse::Ok()
+            .content_type("text/event-stream")
+            .insert_header(("Cache-Control", "no-cache"))
+            .streaming(stream),
+    )
+}
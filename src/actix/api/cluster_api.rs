@@ -1,6 +1,7 @@
 use actix_web::rt::time::Instant;
 use actix_web::{delete, get, web, Responder};
 use serde::Deserialize;
+use storage::content_manager::consensus::audit_log::AUDIT_LOG_CAPACITY;
 use storage::content_manager::consensus_ops::ConsensusOperations;
 use storage::content_manager::errors::StorageError;
 use storage::dispatcher::Dispatcher;
@@ -15,6 +16,12 @@ struct QueryParams {
     timeout: Option<u64>,
 }
 
+#[derive(Debug, Deserialize)]
+struct OperationsParams {
+    collection: Option<String>,
+    limit: Option<usize>,
+}
+
 #[get("/cluster")]
 async fn cluster_status(dispatcher: web::Data<Dispatcher>) -> impl Responder {
     let timing = Instant::now();
@@ -22,6 +29,24 @@ async fn cluster_status(dispatcher: web::Data<Dispatcher>) -> impl Responder {
     process_response(Ok(response), timing)
 }
 
+#[get("/cluster/operations")]
+async fn cluster_operations(
+    dispatcher: web::Data<Dispatcher>,
+    web::Query(params): web::Query<OperationsParams>,
+) -> impl Responder {
+    let timing = Instant::now();
+    let response = match dispatcher.consensus_state() {
+        Some(consensus_state) => Ok(consensus_state.audit_log(
+            params.collection.as_deref(),
+            params.limit.unwrap_or(AUDIT_LOG_CAPACITY),
+        )),
+        None => Err(StorageError::BadRequest {
+            description: "Distributed deployment is disabled.".to_string(),
+        }),
+    };
+    process_response(response, timing)
+}
+
 #[delete("/cluster/peer/{peer_id}")]
 async fn remove_peer(
     dispatcher: web::Data<Dispatcher>,
@@ -32,6 +57,27 @@ async fn remove_peer(
     let dispatcher = dispatcher.into_inner();
     let peer_id = peer_id.into_inner();
 
+    let consensus_state = match dispatcher.consensus_state() {
+        Some(consensus_state) => consensus_state,
+        None => {
+            return process_response(
+                Err(StorageError::BadRequest {
+                    description: "Distributed deployment is disabled.".to_string(),
+                }),
+                timing,
+            )
+        }
+    };
+
+    if !consensus_state.peer_address_by_id().contains_key(&peer_id) {
+        return process_response(
+            Err(StorageError::NotFound {
+                description: format!("Peer {peer_id} does not exist"),
+            }),
+            timing,
+        );
+    }
+
     let has_shards = dispatcher.peer_has_shards(peer_id).await;
     if !params.force && has_shards {
         return process_response(
@@ -42,24 +88,25 @@ async fn remove_peer(
         );
     }
 
-    let response = match dispatcher.consensus_state() {
-        Some(consensus_state) => {
-            consensus_state
-                .propose_consensus_op_with_await(
-                    ConsensusOperations::RemovePeer(peer_id),
-                    params.timeout.map(std::time::Duration::from_secs),
-                    false,
-                )
-                .await
-        }
-        None => Err(StorageError::BadRequest {
-            description: "Distributed deployment is disabled.".to_string(),
-        }),
-    };
+    // Same validation is performed on apply, but invalid removals should not get into consensus
+    if let Err(err) = dispatcher.validate_peer_removal(peer_id).await {
+        return process_response(Err(err), timing);
+    }
+
+    let response = consensus_state
+        .propose_consensus_op_with_await(
+            ConsensusOperations::RemovePeer(peer_id),
+            None,
+            params.timeout.map(std::time::Duration::from_secs),
+            false,
+        )
+        .await;
     process_response(response, timing)
 }
 
 // Configure services
 pub fn config_cluster_api(cfg: &mut web::ServiceConfig) {
-    cfg.service(cluster_status).service(remove_peer);
+    cfg.service(cluster_status)
+        .service(cluster_operations)
+        .service(remove_peer);
 }
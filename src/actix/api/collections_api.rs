@@ -1,17 +1,24 @@
 use std::time::Duration;
 
 use actix_web::rt::time::Instant;
-use actix_web::{delete, get, patch, post, put, web, Responder};
+use actix_web::{delete, get, patch, post, put, web, HttpRequest, Responder};
 use collection::operations::cluster_ops::ClusterOperations;
+use collection::operations::types::ReshardingPreviewRequest;
+use collection::shards::shard::ShardId;
+use segment::types::SeqNumberType;
 use serde::Deserialize;
+use storage::content_manager::api_tokens::{generate_api_token, ApiToken};
 use storage::content_manager::collection_meta_ops::{
-    ChangeAliasesOperation, CollectionMetaOperations, CreateCollection, CreateCollectionOperation,
-    DeleteCollectionOperation, UpdateCollection, UpdateCollectionOperation,
+    ChangeAliasesOperation, CollectionMetaOperations, CreateApiToken, CreateApiTokenOperation,
+    CreateCollection, CreateCollectionOperation, DeleteApiToken, DeleteApiTokenOperation,
+    DeleteCollectionOperation, SetDefaultFilter, SetDefaultFilterOperation, UpdateCollection,
+    UpdateCollectionOperation,
 };
 use storage::content_manager::toc::TableOfContent;
 use storage::dispatcher::Dispatcher;
 
-use crate::actix::helpers::process_response;
+use crate::actix::auth::client_identity;
+use crate::actix::helpers::{process_create_collection_response, process_response};
 use crate::common::collections::*;
 
 #[derive(Debug, Deserialize)]
@@ -25,6 +32,20 @@ impl WaitTimeout {
     }
 }
 
+#[derive(Debug, Deserialize)]
+struct WaitForOperationParam {
+    operation_id: SeqNumberType,
+    timeout: Option<u64>,
+}
+
+/// Default time to wait for the operation to be applied, if no timeout is requested
+const DEFAULT_WAIT_FOR_OPERATION_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Deserialize)]
+struct OptimizeParam {
+    shard_id: Option<ShardId>,
+}
+
 #[get("/collections")]
 async fn get_collections(toc: web::Data<TableOfContent>) -> impl Responder {
     let timing = Instant::now();
@@ -42,6 +63,7 @@ async fn get_collection(toc: web::Data<TableOfContent>, path: web::Path<String>)
 
 #[put("/collections/{name}")]
 async fn create_collection(
+    http_request: HttpRequest,
     dispatcher: web::Data<Dispatcher>,
     path: web::Path<String>,
     operation: web::Json<CreateCollection>,
@@ -50,19 +72,21 @@ async fn create_collection(
     let timing = Instant::now();
     let name = path.into_inner();
     let response = dispatcher
-        .submit_collection_meta_op(
+        .submit_collection_meta_op_with_placement(
             CollectionMetaOperations::CreateCollection(CreateCollectionOperation::new(
                 name,
                 operation.into_inner(),
             )),
+            client_identity(&http_request),
             query.timeout(),
         )
         .await;
-    process_response(response, timing)
+    process_create_collection_response(response, timing)
 }
 
 #[patch("/collections/{name}")]
 async fn update_collection(
+    http_request: HttpRequest,
     dispatcher: web::Data<Dispatcher>,
     path: web::Path<String>,
     operation: web::Json<UpdateCollection>,
@@ -76,6 +100,7 @@ async fn update_collection(
                 name,
                 operation.into_inner(),
             )),
+            client_identity(&http_request),
             query.timeout(),
         )
         .await;
@@ -84,6 +109,7 @@ async fn update_collection(
 
 #[delete("/collections/{name}")]
 async fn delete_collection(
+    http_request: HttpRequest,
     dispatcher: web::Data<Dispatcher>,
     path: web::Path<String>,
     web::Query(query): web::Query<WaitTimeout>,
@@ -93,6 +119,7 @@ async fn delete_collection(
     let response = dispatcher
         .submit_collection_meta_op(
             CollectionMetaOperations::DeleteCollection(DeleteCollectionOperation(name)),
+            client_identity(&http_request),
             query.timeout(),
         )
         .await;
@@ -101,6 +128,7 @@ async fn delete_collection(
 
 #[post("/collections/aliases")]
 async fn update_aliases(
+    http_request: HttpRequest,
     dispatcher: web::Data<Dispatcher>,
     operation: web::Json<ChangeAliasesOperation>,
     web::Query(query): web::Query<WaitTimeout>,
@@ -109,12 +137,161 @@ async fn update_aliases(
     let response = dispatcher
         .submit_collection_meta_op(
             CollectionMetaOperations::ChangeAliases(operation.0),
+            client_identity(&http_request),
             query.timeout(),
         )
         .await;
     process_response(response, timing)
 }
 
+#[get("/aliases")]
+async fn get_aliases(toc: web::Data<TableOfContent>) -> impl Responder {
+    let timing = Instant::now();
+    let response = Ok(do_list_aliases(toc.get_ref()).await);
+    process_response(response, timing)
+}
+
+#[get("/collections/{name}/aliases")]
+async fn get_collection_aliases(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let timing = Instant::now();
+    let name = path.into_inner();
+    let response = do_list_collection_aliases(toc.get_ref(), &name).await;
+    process_response(response, timing)
+}
+
+#[get("/collections/{name}/default_filter")]
+async fn get_default_filter(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let name = path.into_inner();
+    let timing = Instant::now();
+    let response = toc.default_filter(&name).await;
+    process_response(response, timing)
+}
+
+#[put("/collections/{name}/default_filter")]
+async fn set_default_filter(
+    http_request: HttpRequest,
+    dispatcher: web::Data<Dispatcher>,
+    path: web::Path<String>,
+    operation: web::Json<SetDefaultFilter>,
+    web::Query(query): web::Query<WaitTimeout>,
+) -> impl Responder {
+    let timing = Instant::now();
+    let name = path.into_inner();
+    let response = dispatcher
+        .submit_collection_meta_op(
+            CollectionMetaOperations::SetDefaultFilter(SetDefaultFilterOperation {
+                name,
+                set_default_filter: operation.into_inner(),
+            }),
+            client_identity(&http_request),
+            query.timeout(),
+        )
+        .await;
+    process_response(response, timing)
+}
+
+#[post("/collections/{name}/tokens")]
+async fn create_api_token(
+    http_request: HttpRequest,
+    dispatcher: web::Data<Dispatcher>,
+    path: web::Path<String>,
+    operation: web::Json<CreateApiToken>,
+    web::Query(query): web::Query<WaitTimeout>,
+) -> impl Responder {
+    let timing = Instant::now();
+    let collection_name = path.into_inner();
+    let token = generate_api_token();
+    let response = dispatcher
+        .submit_collection_meta_op(
+            CollectionMetaOperations::CreateApiToken(CreateApiTokenOperation {
+                token: token.clone(),
+                api_token: ApiToken {
+                    collection_name,
+                    scope: operation.scope,
+                },
+            }),
+            client_identity(&http_request),
+            query.timeout(),
+        )
+        .await
+        .map(|_| token);
+    process_response(response, timing)
+}
+
+#[post("/collections/{name}/tokens/delete")]
+async fn delete_api_token(
+    http_request: HttpRequest,
+    dispatcher: web::Data<Dispatcher>,
+    path: web::Path<String>,
+    operation: web::Json<DeleteApiToken>,
+    web::Query(query): web::Query<WaitTimeout>,
+) -> impl Responder {
+    let timing = Instant::now();
+    let collection_name = path.into_inner();
+    let response = dispatcher
+        .submit_collection_meta_op(
+            CollectionMetaOperations::DeleteApiToken(DeleteApiTokenOperation {
+                collection_name,
+                delete_api_token: operation.into_inner(),
+            }),
+            client_identity(&http_request),
+            query.timeout(),
+        )
+        .await;
+    process_response(response, timing)
+}
+
+#[post("/collections/{name}/warmup")]
+async fn warm_up_collection(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let name = path.into_inner();
+    let timing = Instant::now();
+    let response = toc.warm_up_collection(&name).await;
+    process_response(response, timing)
+}
+
+#[post("/collections/{name}/optimize")]
+async fn optimize_collection(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<String>,
+    query: web::Query<OptimizeParam>,
+) -> impl Responder {
+    let name = path.into_inner();
+    let timing = Instant::now();
+    let response = toc.optimize_collection(&name, query.shard_id).await;
+    process_response(response, timing)
+}
+
+#[post("/collections/{name}/verify")]
+async fn verify_collection(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let name = path.into_inner();
+    let timing = Instant::now();
+    let response = toc.verify_collection(&name).await;
+    process_response(response, timing)
+}
+
+#[get("/collections/{name}/slow_queries")]
+async fn get_slow_queries(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let name = path.into_inner();
+    let timing = Instant::now();
+    let response = toc.slow_queries(&name).await;
+    process_response(response, timing)
+}
+
 #[get("/collections/{name}/cluster")]
 async fn get_cluster_info(
     toc: web::Data<TableOfContent>,
@@ -128,6 +305,7 @@ async fn get_cluster_info(
 
 #[post("/collections/{name}/cluster")]
 async fn update_collection_cluster(
+    http_request: HttpRequest,
     toc: web::Data<TableOfContent>,
     dispatcher: web::Data<Dispatcher>,
     path: web::Path<String>,
@@ -142,12 +320,54 @@ async fn update_collection_cluster(
         name,
         operation.0,
         &dispatcher.into_inner(),
+        client_identity(&http_request),
         wait_timeout,
     )
     .await;
     process_response(response, timing)
 }
 
+#[post("/collections/{name}/cluster/sync")]
+async fn sync_collection_replicas(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let name = path.into_inner();
+    let timing = Instant::now();
+    let response = toc.sync_collection_replicas(&name).await;
+    process_response(response, timing)
+}
+
+#[get("/collections/{name}/shards/{shard_id}/wait_for")]
+async fn wait_for_operation(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<(String, ShardId)>,
+    web::Query(query): web::Query<WaitForOperationParam>,
+) -> impl Responder {
+    let (name, shard_id) = path.into_inner();
+    let timing = Instant::now();
+    let timeout = query
+        .timeout
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_WAIT_FOR_OPERATION_TIMEOUT);
+    let response = toc
+        .wait_for_operation(&name, shard_id, query.operation_id, timeout)
+        .await;
+    process_response(response, timing)
+}
+
+#[post("/collections/{name}/cluster/resharding/preview")]
+async fn resharding_preview(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<String>,
+    request: web::Json<ReshardingPreviewRequest>,
+) -> impl Responder {
+    let timing = Instant::now();
+    let name = path.into_inner();
+    let response = do_resharding_preview(toc.get_ref(), &name, request.into_inner()).await;
+    process_response(response, timing)
+}
+
 // Configure services
 pub fn config_collections_api(cfg: &mut web::ServiceConfig) {
     cfg.service(get_collections)
@@ -156,8 +376,21 @@ pub fn config_collections_api(cfg: &mut web::ServiceConfig) {
         .service(update_collection)
         .service(delete_collection)
         .service(update_aliases)
+        .service(get_aliases)
+        .service(get_collection_aliases)
+        .service(get_default_filter)
+        .service(set_default_filter)
+        .service(create_api_token)
+        .service(delete_api_token)
+        .service(warm_up_collection)
+        .service(optimize_collection)
+        .service(verify_collection)
+        .service(get_slow_queries)
         .service(get_cluster_info)
-        .service(update_collection_cluster);
+        .service(update_collection_cluster)
+        .service(sync_collection_replicas)
+        .service(wait_for_operation)
+        .service(resharding_preview);
 }
 
 #[cfg(test)]
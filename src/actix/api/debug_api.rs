@@ -0,0 +1,31 @@
+use actix_web::rt::time::Instant;
+use actix_web::{get, post, web, Responder};
+use storage::content_manager::errors::StorageError;
+
+use crate::actix::helpers::process_response;
+use crate::common::memory_stats::{dump_heap_profile, MemoryStats};
+
+/// Allocator stats of the process, to diagnose the growth of its resident memory
+#[get("/debug/memory")]
+async fn memory_stats() -> impl Responder {
+    let timing = Instant::now();
+    process_response(MemoryStats::collect(), timing)
+}
+
+/// Dump the heap profile into the temp directory of the host, returns path of the profile
+#[post("/debug/memory/profile")]
+async fn heap_profile() -> impl Responder {
+    let timing = Instant::now();
+    let result = match web::block(|| dump_heap_profile(&std::env::temp_dir())).await {
+        Ok(result) => result,
+        Err(err) => Err(StorageError::ServiceError {
+            description: format!("Failed to dump heap profile: {err}"),
+        }),
+    };
+    process_response(result, timing)
+}
+
+// Configure services
+pub fn config_debug_api(cfg: &mut web::ServiceConfig) {
+    cfg.service(memory_stats).service(heap_profile);
+}
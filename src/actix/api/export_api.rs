@@ -0,0 +1,112 @@
+use actix_files::NamedFile;
+use actix_web::rt::time::Instant;
+use actix_web::{get, post, web, Either, HttpResponse, Responder, Result};
+use collection::jobs::export::{CreateExport, StreamExport};
+use futures::StreamExt;
+use storage::content_manager::toc::TableOfContent;
+
+use crate::actix::helpers::{
+    collection_into_actix_error, process_response, storage_into_actix_error,
+};
+use crate::common::collections::*;
+use crate::common::points::do_export_points;
+
+pub async fn do_get_export_chunk(
+    toc: &TableOfContent,
+    collection_name: &str,
+    export_id: &str,
+    chunk: usize,
+) -> Result<NamedFile> {
+    let collection = toc
+        .get_collection(collection_name)
+        .await
+        .map_err(storage_into_actix_error)?;
+
+    let file_name = collection
+        .get_export_chunk_path(export_id, chunk)
+        .await
+        .map_err(collection_into_actix_error)?;
+
+    Ok(NamedFile::open(file_name)?)
+}
+
+#[get("/collections/{name}/exports")]
+async fn list_exports(toc: web::Data<TableOfContent>, path: web::Path<String>) -> impl Responder {
+    let collection_name = path.into_inner();
+
+    let timing = Instant::now();
+    let response = do_list_exports(toc.get_ref(), &collection_name).await;
+    process_response(response, timing)
+}
+
+#[post("/collections/{name}/exports")]
+async fn create_export(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<String>,
+    request: web::Json<CreateExport>,
+) -> impl Responder {
+    let collection_name = path.into_inner();
+
+    let timing = Instant::now();
+    let response = do_create_export(toc.get_ref(), &collection_name, request.into_inner()).await;
+    process_response(response, timing)
+}
+
+#[get("/collections/{name}/exports/{export_id}")]
+async fn get_export(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<(String, String)>,
+) -> impl Responder {
+    let (collection_name, export_id) = path.into_inner();
+
+    let timing = Instant::now();
+    let response = do_get_export(toc.get_ref(), &collection_name, &export_id).await;
+    process_response(response, timing)
+}
+
+#[get("/collections/{name}/exports/{export_id}/chunks/{chunk}")]
+async fn get_export_chunk(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<(String, String, usize)>,
+) -> impl Responder {
+    let (collection_name, export_id, chunk) = path.into_inner();
+    do_get_export_chunk(toc.get_ref(), &collection_name, &export_id, chunk).await
+}
+
+/// Stream all points of the collection, or points selected by the filter, as JSONL
+#[post("/collections/{name}/points/export")]
+async fn export_points(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<String>,
+    request: web::Json<StreamExport>,
+) -> impl Responder {
+    let collection_name = path.into_inner();
+
+    let timing = Instant::now();
+    let points =
+        match do_export_points(toc.into_inner(), collection_name, request.into_inner()).await {
+            Ok(points) => points,
+            Err(err) => return Either::Left(process_response::<()>(Err(err), timing)),
+        };
+
+    // Status of the response is already sent, so an error can only interrupt the stream
+    let stream = points.map(|batch| {
+        batch
+            .map(web::Bytes::from)
+            .map_err(storage_into_actix_error)
+    });
+    Either::Right(
+        HttpResponse::Ok()
+            .content_type("application/x-ndjson")
+            .streaming(stream),
+    )
+}
+
+// Configure services
+pub fn config_export_api(cfg: &mut web::ServiceConfig) {
+    cfg.service(list_exports)
+        .service(create_export)
+        .service(get_export)
+        .service(get_export_chunk)
+        .service(export_points);
+}
@@ -0,0 +1,38 @@
+use actix_web::rt::time::Instant;
+use actix_web::{post, web, Responder};
+use collection::operations::types::FacetRequest;
+use storage::content_manager::toc::TableOfContent;
+
+use crate::actix::helpers::process_response;
+use crate::common::points::do_facet_points;
+
+#[post("/collections/{name}/points/facet")]
+pub async fn facet_points(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<String>,
+    request: web::Json<FacetRequest>,
+) -> impl Responder {
+    let collection_name = path.into_inner();
+    let timing = Instant::now();
+
+    let response =
+        do_facet_points(toc.get_ref(), &collection_name, request.into_inner(), None).await;
+
+    process_response(response, timing)
+}
+
+/// Same as `facet_points`, under the path of the collection
+#[post("/collections/{name}/facet")]
+pub async fn facet_collection(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<String>,
+    request: web::Json<FacetRequest>,
+) -> impl Responder {
+    let collection_name = path.into_inner();
+    let timing = Instant::now();
+
+    let response =
+        do_facet_points(toc.get_ref(), &collection_name, request.into_inner(), None).await;
+
+    process_response(response, timing)
+}
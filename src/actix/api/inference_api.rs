@@ -0,0 +1,37 @@
+use actix_web::rt::time::Instant;
+use actix_web::{post, put, web, Responder};
+
+use crate::actix::helpers::process_response;
+use crate::common::inference::{InferenceService, TextPointsStatusRequest, UpsertTextPoints};
+
+#[put("/collections/{name}/points/text")]
+async fn upsert_text_points(
+    inference: web::Data<InferenceService>,
+    path: web::Path<String>,
+    request: web::Json<UpsertTextPoints>,
+) -> impl Responder {
+    let collection_name = path.into_inner();
+    let timing = Instant::now();
+    let response = inference
+        .into_inner()
+        .upsert(collection_name, request.into_inner())
+        .await;
+    process_response(response, timing)
+}
+
+#[post("/collections/{name}/points/text/status")]
+async fn text_points_status(
+    inference: web::Data<InferenceService>,
+    path: web::Path<String>,
+    request: web::Json<TextPointsStatusRequest>,
+) -> impl Responder {
+    let collection_name = path.into_inner();
+    let timing = Instant::now();
+    let response = inference.status(&collection_name, &request.ids);
+    process_response(Ok(response), timing)
+}
+
+// Configure services
+pub fn config_inference_api(cfg: &mut web::ServiceConfig) {
+    cfg.service(upsert_text_points).service(text_points_status);
+}
@@ -0,0 +1,35 @@
+use actix_web::rt::time::Instant;
+use actix_web::{get, post, web, Responder};
+use storage::content_manager::toc::TableOfContent;
+
+use crate::actix::helpers::process_response;
+
+#[get("/jobs")]
+async fn list_jobs(toc: web::Data<TableOfContent>) -> impl Responder {
+    let timing = Instant::now();
+    let response = Ok(toc.list_jobs().await);
+    process_response(response, timing)
+}
+
+#[get("/jobs/{job_id}")]
+async fn get_job(toc: web::Data<TableOfContent>, path: web::Path<String>) -> impl Responder {
+    let job_id = path.into_inner();
+
+    let timing = Instant::now();
+    let response = toc.get_job(&job_id).await;
+    process_response(response, timing)
+}
+
+#[post("/jobs/{job_id}/cancel")]
+async fn cancel_job(toc: web::Data<TableOfContent>, path: web::Path<String>) -> impl Responder {
+    let job_id = path.into_inner();
+
+    let timing = Instant::now();
+    let response = toc.cancel_job(&job_id).await;
+    process_response(response, timing)
+}
+
+// Configure services
+pub fn config_jobs_api(cfg: &mut web::ServiceConfig) {
+    cfg.service(list_jobs).service(get_job).service(cancel_job);
+}
@@ -1,6 +1,13 @@
+pub mod changes_api;
 pub mod cluster_api;
 pub mod collections_api;
 pub mod count_api;
+#[cfg(all(feature = "service_debug", not(target_env = "msvc")))]
+pub mod debug_api;
+pub mod export_api;
+pub mod facet_api;
+pub mod inference_api;
+pub mod jobs_api;
 pub mod recommend_api;
 pub mod retrieve_api;
 pub mod search_api;
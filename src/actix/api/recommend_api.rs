@@ -1,11 +1,33 @@
 use actix_web::rt::time::Instant;
-use actix_web::{post, web, Responder};
-use collection::operations::types::{RecommendRequest, RecommendRequestBatch};
+use actix_web::{post, web, HttpRequest, Responder};
+use collection::operations::types::{DiscoverRequest, RecommendRequest, RecommendRequestBatch};
 use segment::types::ScoredPoint;
+use storage::content_manager::api_tokens::ApiTokenScope;
 use storage::content_manager::errors::StorageError;
 use storage::content_manager::toc::TableOfContent;
 
+use crate::actix::auth::check_access;
 use crate::actix::helpers::process_response;
+use crate::common::request_limits::RequestLimits;
+
+/// Examples of the requests with `lookup_from` are read from another collection,
+/// so it requires read access as well
+fn check_lookup_access(
+    http_request: &HttpRequest,
+    requests: &[RecommendRequest],
+) -> Result<(), StorageError> {
+    for lookup_from in requests
+        .iter()
+        .filter_map(|request| request.lookup_from.as_ref())
+    {
+        check_access(
+            http_request,
+            Some(&lookup_from.collection),
+            ApiTokenScope::Read,
+        )?;
+    }
+    Ok(())
+}
 
 async fn do_recommend_points(
     toc: &TableOfContent,
@@ -17,14 +39,19 @@ async fn do_recommend_points(
 
 #[post("/collections/{name}/points/recommend")]
 pub async fn recommend_points(
+    http_request: HttpRequest,
     toc: web::Data<TableOfContent>,
     path: web::Path<String>,
     request: web::Json<RecommendRequest>,
 ) -> impl Responder {
     let name = path.into_inner();
     let timing = Instant::now();
+    let request = request.into_inner();
 
-    let response = do_recommend_points(toc.get_ref(), &name, request.into_inner()).await;
+    let response = match check_lookup_access(&http_request, std::slice::from_ref(&request)) {
+        Ok(()) => do_recommend_points(toc.get_ref(), &name, request).await,
+        Err(err) => Err(err),
+    };
 
     process_response(response, timing)
 }
@@ -39,14 +66,37 @@ async fn do_recommend_batch_points(
 
 #[post("/collections/{name}/points/recommend/batch")]
 pub async fn recommend_batch_points(
+    http_request: HttpRequest,
     toc: web::Data<TableOfContent>,
+    limits: web::Data<RequestLimits>,
     path: web::Path<String>,
     request: web::Json<RecommendRequestBatch>,
 ) -> impl Responder {
     let name = path.into_inner();
     let timing = Instant::now();
+    let request = request.into_inner();
 
-    let response = do_recommend_batch_points(toc.get_ref(), &name, request.into_inner()).await;
+    let checked = limits
+        .check_batch_size(request.searches.len())
+        .and_then(|()| check_lookup_access(&http_request, &request.searches));
+    let response = match checked {
+        Ok(()) => do_recommend_batch_points(toc.get_ref(), &name, request).await,
+        Err(err) => Err(err),
+    };
+
+    process_response(response, timing)
+}
+
+#[post("/collections/{name}/points/discover")]
+pub async fn discover_points(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<String>,
+    request: web::Json<DiscoverRequest>,
+) -> impl Responder {
+    let name = path.into_inner();
+    let timing = Instant::now();
+
+    let response = toc.discover(&name, request.into_inner(), None).await;
 
     process_response(response, timing)
 }
@@ -54,5 +104,53 @@ pub async fn recommend_batch_points(
 // Configure services
 pub fn config_recommend_api(cfg: &mut web::ServiceConfig) {
     cfg.service(recommend_points)
-        .service(recommend_batch_points);
+        .service(recommend_batch_points)
+        .service(discover_points);
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+    use collection::operations::types::LookupLocation;
+    use storage::content_manager::api_tokens::{Access, ApiToken};
+
+    use super::*;
+
+    fn recommend_request(lookup_collection: Option<&str>) -> RecommendRequest {
+        serde_json::from_value(serde_json::json!({
+            "positive": [1],
+            "negative": [],
+            "limit": 10,
+        }))
+        .map(|request: RecommendRequest| RecommendRequest {
+            lookup_from: lookup_collection.map(|collection| LookupLocation {
+                collection: collection.to_string(),
+                vector: None,
+            }),
+            ..request
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_lookup_access() {
+        let http_request = TestRequest::default().to_http_request();
+        assert!(check_lookup_access(&http_request, &[recommend_request(Some("b"))]).is_ok());
+
+        http_request
+            .extensions_mut()
+            .insert(Access::Collection(ApiToken {
+                collection_name: "a".to_string(),
+                scope: ApiTokenScope::Read,
+            }));
+        let allowed = [recommend_request(None), recommend_request(Some("a"))];
+        assert!(check_lookup_access(&http_request, &allowed).is_ok());
+
+        // Token of the collection does not grant access to the lookup collection
+        let denied = [recommend_request(Some("a")), recommend_request(Some("b"))];
+        assert!(matches!(
+            check_lookup_access(&http_request, &denied),
+            Err(StorageError::Forbidden { .. })
+        ));
+    }
 }
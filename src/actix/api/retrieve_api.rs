@@ -1,6 +1,8 @@
 use actix_web::rt::time::Instant;
 use actix_web::{get, post, web, Responder};
-use collection::operations::types::{PointRequest, Record, ScrollRequest, ScrollResult};
+use collection::operations::types::{
+    PointRequest, Record, SampleRequest, ScrollRequest, ScrollResult,
+};
 use segment::types::{PointIdType, WithPayloadInterface};
 use storage::content_manager::errors::StorageError;
 use storage::content_manager::toc::TableOfContent;
@@ -91,3 +93,18 @@ pub async fn scroll_points(
     let response = scroll_get_points(toc.get_ref(), &collection_name, request.into_inner()).await;
     process_response(response, timing)
 }
+
+#[post("/collections/{name}/points/sample")]
+pub async fn sample_points(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<String>,
+    request: web::Json<SampleRequest>,
+) -> impl Responder {
+    let collection_name = path.into_inner();
+    let timing = Instant::now();
+
+    let response = toc
+        .sample(&collection_name, request.into_inner(), None)
+        .await;
+    process_response(response, timing)
+}
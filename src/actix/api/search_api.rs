@@ -1,10 +1,12 @@
 use actix_web::rt::time::Instant;
 use actix_web::{post, web, Responder};
 use collection::operations::types::{SearchRequest, SearchRequestBatch};
+use storage::content_manager::federated_search::FederatedSearchRequest;
 use storage::content_manager::toc::TableOfContent;
 
-use crate::actix::helpers::process_response;
-use crate::common::points::{do_search_batch_points, do_search_points};
+use crate::actix::helpers::process_search_response;
+use crate::common::points::{do_federated_search, do_search_batch_points, do_search_points};
+use crate::common::request_limits::RequestLimits;
 
 #[post("/collections/{name}/points/search")]
 pub async fn search_points(
@@ -14,29 +16,56 @@ pub async fn search_points(
 ) -> impl Responder {
     let collection_name = path.into_inner();
     let timing = Instant::now();
+    let request = request.into_inner();
+    let with_time_budget = request.max_time().is_some();
+    let explain = request.explain();
 
-    let response =
-        do_search_points(toc.get_ref(), &collection_name, request.into_inner(), None).await;
+    let response = do_search_points(toc.get_ref(), &collection_name, request, None).await;
 
-    process_response(response, timing)
+    process_search_response(response, timing, with_time_budget, explain)
 }
 
 #[post("/collections/{name}/points/search/batch")]
 pub async fn batch_search_points(
     toc: web::Data<TableOfContent>,
+    limits: web::Data<RequestLimits>,
     path: web::Path<String>,
     request: web::Json<SearchRequestBatch>,
 ) -> impl Responder {
     let collection_name = path.into_inner();
     let timing = Instant::now();
+    let request = request.into_inner();
+    let with_time_budget = request.max_time().is_some();
+    let explain = request.explain();
 
-    let response =
-        do_search_batch_points(toc.get_ref(), &collection_name, request.into_inner(), None).await;
+    let response = match limits.check_batch_size(request.searches.len()) {
+        Ok(()) => do_search_batch_points(toc.get_ref(), &collection_name, request, None)
+            .await
+            .map(|result| (result.results, result.truncated, result.explanation)),
+        Err(err) => Err(err),
+    };
 
-    process_response(response, timing)
+    process_search_response(response, timing, with_time_budget, explain)
+}
+
+#[post("/federated/search")]
+pub async fn federated_search(
+    toc: web::Data<TableOfContent>,
+    request: web::Json<FederatedSearchRequest>,
+) -> impl Responder {
+    let timing = Instant::now();
+    let request = request.into_inner();
+    let with_time_budget = request.search.max_time().is_some();
+    let explain = request.search.explain();
+
+    let response = do_federated_search(toc.get_ref(), request).await;
+
+    process_search_response(response, timing, with_time_budget, explain)
 }
 
 // Configure services
 pub fn config_search_api(cfg: &mut web::ServiceConfig) {
-    cfg.service(search_points).service(batch_search_points);
+    cfg.service(search_points)
+        .service(batch_search_points)
+        .service(federated_search);
 }
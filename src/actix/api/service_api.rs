@@ -1,14 +1,21 @@
 use actix_web::rt::time::Instant;
 use actix_web::web::Query;
-use actix_web::{get, post, web, Responder};
+use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
 use schemars::JsonSchema;
 use segment::common::anonymize::Anonymize;
 use serde::{Deserialize, Serialize};
+use storage::content_manager::errors::StorageError;
+use storage::content_manager::keyed_locks::KeyedLockRequest;
 use storage::content_manager::toc::TableOfContent;
+use storage::dispatcher::Dispatcher;
+use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::Mutex;
+use tokio::time::interval_at;
 
 use crate::actix::helpers::process_response;
-use crate::common::helpers::LocksOption;
+use crate::common::diagnostics::DiagnosticsReport;
+use crate::common::events::{EventsService, EVENTS_KEEP_ALIVE_INTERVAL, EVENTS_KEEP_ALIVE_MESSAGE};
+use crate::common::helpers::{resize_threads, LocksOption, ThreadsInfo, ThreadsOption};
 use crate::common::telemetry::TelemetryCollector;
 
 #[derive(Deserialize, Serialize, JsonSchema)]
@@ -60,7 +67,153 @@ async fn get_locks(toc: web::Data<TableOfContent>) -> impl Responder {
     process_response(Ok(result), timing)
 }
 
+#[get("/locks/keyed")]
+async fn get_keyed_locks(toc: web::Data<TableOfContent>) -> impl Responder {
+    let timing = Instant::now();
+    let result = toc.get_ref().list_keyed_locks();
+    process_response(Ok(result), timing)
+}
+
+#[put("/locks/keyed/{lock_name}")]
+async fn put_keyed_lock(
+    toc: web::Data<TableOfContent>,
+    lock_name: web::Path<String>,
+    request: web::Json<KeyedLockRequest>,
+) -> impl Responder {
+    let timing = Instant::now();
+    let result = toc
+        .get_ref()
+        .acquire_keyed_lock(lock_name.into_inner(), request.into_inner())
+        .await;
+    process_response(result, timing)
+}
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+pub struct ReleaseKeyedLockParam {
+    /// Owner, the lock was acquired by
+    pub owner: Option<String>,
+}
+
+#[delete("/locks/keyed/{lock_name}")]
+async fn delete_keyed_lock(
+    toc: web::Data<TableOfContent>,
+    lock_name: web::Path<String>,
+    params: Query<ReleaseKeyedLockParam>,
+) -> impl Responder {
+    let timing = Instant::now();
+    let result = toc
+        .get_ref()
+        .release_keyed_lock(&lock_name, params.owner.as_deref())
+        .map(|released| released.is_some());
+    process_response(result, timing)
+}
+
+#[get("/threads")]
+async fn get_threads(toc: web::Data<TableOfContent>) -> impl Responder {
+    let timing = Instant::now();
+    let result = ThreadsInfo::collect(toc.get_ref());
+    process_response(Ok(result), timing)
+}
+
+#[put("/threads")]
+async fn put_threads(
+    toc: web::Data<TableOfContent>,
+    threads_option: web::Json<ThreadsOption>,
+) -> impl Responder {
+    let timing = Instant::now();
+    let result = resize_threads(toc.get_ref(), &threads_option);
+    process_response(result, timing)
+}
+
+#[get("/diagnostics")]
+async fn diagnostics(toc: web::Data<TableOfContent>) -> impl Responder {
+    let timing = Instant::now();
+    let storage_config = toc.get_ref().storage_config().clone();
+    let result = web::block(move || DiagnosticsReport::collect(&storage_config))
+        .await
+        .map_err(|err| StorageError::ServiceError {
+            description: format!("Failed to collect diagnostics: {err}"),
+        });
+    process_response(result, timing)
+}
+
+/// Stream of collection status, optimization and cluster topology changes as server-sent events
+#[get("/events")]
+async fn events(events_service: web::Data<EventsService>) -> impl Responder {
+    let receiver = events_service.subscribe();
+    // Idle connections are kept alive with comments, otherwise proxies may close them
+    let keep_alive = interval_at(
+        Instant::now() + EVENTS_KEEP_ALIVE_INTERVAL,
+        EVENTS_KEEP_ALIVE_INTERVAL,
+    );
+    let stream = futures::stream::unfold(
+        (receiver, keep_alive),
+        |(mut receiver, mut keep_alive)| async move {
+            loop {
+                let message = tokio::select! {
+                    event = receiver.recv() => match event {
+                        Ok(event) => web::Bytes::from(event.to_sse_message()),
+                        Err(RecvError::Lagged(skipped)) => {
+                            log::debug!("Events subscriber lagged, skipped {skipped} events");
+                            continue;
+                        }
+                        Err(RecvError::Closed) => return None,
+                    },
+                    _ = keep_alive.tick() => {
+                        web::Bytes::from_static(EVENTS_KEEP_ALIVE_MESSAGE.as_bytes())
+                    }
+                };
+                return Some((Ok::<_, actix_web::Error>(message), (receiver, keep_alive)));
+            }
+        },
+    );
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .streaming(stream)
+}
+
+/// Plain text response for Kubernetes probes: `200`, if there are no issues, `503` otherwise
+fn probe_response(probe: &str, issues: Vec<String>) -> HttpResponse {
+    if issues.is_empty() {
+        HttpResponse::Ok()
+            .content_type("text/plain")
+            .body(format!("{probe} check passed"))
+    } else {
+        HttpResponse::ServiceUnavailable()
+            .content_type("text/plain")
+            .body(format!("{probe} check failed:\n{}", issues.join("\n")))
+    }
+}
+
+#[get("/healthz")]
+async fn healthz(dispatcher: web::Data<Dispatcher>) -> impl Responder {
+    probe_response("healthz", dispatcher.liveness_issues())
+}
+
+#[get("/livez")]
+async fn livez(dispatcher: web::Data<Dispatcher>) -> impl Responder {
+    probe_response("livez", dispatcher.liveness_issues())
+}
+
+#[get("/readyz")]
+async fn readyz(dispatcher: web::Data<Dispatcher>) -> impl Responder {
+    probe_response("readyz", dispatcher.readiness_issues().await)
+}
+
 // Configure services
 pub fn config_service_api(cfg: &mut web::ServiceConfig) {
-    cfg.service(telemetry).service(put_locks).service(get_locks);
+    cfg.service(telemetry)
+        .service(healthz)
+        .service(livez)
+        .service(readyz)
+        .service(diagnostics)
+        .service(events)
+        .service(put_locks)
+        .service(get_locks)
+        .service(get_keyed_locks)
+        .service(put_keyed_lock)
+        .service(delete_keyed_lock)
+        .service(get_threads)
+        .service(put_threads);
 }
@@ -1,8 +1,12 @@
 use actix_files::NamedFile;
 use actix_web::rt::time::Instant;
-use actix_web::{get, post, web, Responder, Result};
+use actix_web::web::Query;
+use actix_web::{delete, get, post, web, Responder, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use storage::content_manager::snapshots::{
-    do_create_full_snapshot, do_list_full_snapshots, get_full_snapshot_path,
+    do_create_full_snapshot, do_create_full_snapshot_in_background, do_delete_full_snapshot,
+    do_list_full_snapshots, get_full_snapshot_path,
 };
 use storage::content_manager::toc::TableOfContent;
 
@@ -46,15 +50,23 @@ async fn list_snapshots(toc: web::Data<TableOfContent>, path: web::Path<String>)
     process_response(response, timing)
 }
 
+#[derive(Deserialize, Serialize, JsonSchema)]
+pub struct CreateCollectionSnapshotParam {
+    /// Create an incremental snapshot, which only includes the segments, changed since this snapshot
+    pub base: Option<String>,
+}
+
 #[post("/collections/{name}/snapshots")]
 async fn create_snapshot(
     toc: web::Data<TableOfContent>,
     path: web::Path<String>,
+    params: Query<CreateCollectionSnapshotParam>,
 ) -> impl Responder {
     let collection_name = path.into_inner();
 
     let timing = Instant::now();
-    let response = do_create_snapshot(toc.get_ref(), &collection_name).await;
+    let response =
+        do_create_snapshot(toc.get_ref(), &collection_name, params.base.as_deref()).await;
     process_response(response, timing)
 }
 
@@ -74,10 +86,23 @@ async fn list_full_snapshots(toc: web::Data<TableOfContent>) -> impl Responder {
     process_response(response, timing)
 }
 
+#[derive(Deserialize, Serialize, JsonSchema)]
+pub struct CreateSnapshotParam {
+    pub wait: Option<bool>,
+}
+
 #[post("/snapshots")]
-async fn create_full_snapshot(toc: web::Data<TableOfContent>) -> impl Responder {
+async fn create_full_snapshot(
+    toc: web::Data<TableOfContent>,
+    params: Query<CreateSnapshotParam>,
+) -> impl Responder {
     let timing = Instant::now();
-    let response = do_create_full_snapshot(toc.get_ref()).await;
+    let wait = params.wait.unwrap_or(true);
+    let response = if wait {
+        do_create_full_snapshot(toc.get_ref()).await
+    } else {
+        do_create_full_snapshot_in_background(toc.into_inner())
+    };
     process_response(response, timing)
 }
 
@@ -90,6 +115,17 @@ async fn get_full_snapshot(
     do_get_full_snapshot(toc.get_ref(), &snapshot_name).await
 }
 
+#[delete("/snapshots/{snapshot_name}")]
+async fn delete_full_snapshot(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let snapshot_name = path.into_inner();
+    let timing = Instant::now();
+    let response = do_delete_full_snapshot(toc.get_ref(), &snapshot_name).await;
+    process_response(response, timing)
+}
+
 // Configure services
 pub fn config_snapshots_api(cfg: &mut web::ServiceConfig) {
     cfg.service(list_snapshots)
@@ -97,5 +133,6 @@ pub fn config_snapshots_api(cfg: &mut web::ServiceConfig) {
         .service(get_snapshot)
         .service(list_full_snapshots)
         .service(create_full_snapshot)
-        .service(get_full_snapshot);
+        .service(get_full_snapshot)
+        .service(delete_full_snapshot);
 }
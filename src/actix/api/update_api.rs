@@ -1,36 +1,81 @@
 use actix_web::rt::time::Instant;
 use actix_web::web::Query;
 use actix_web::{delete, post, put, web, Responder};
-use collection::operations::payload_ops::{DeletePayload, SetPayload};
-use collection::operations::point_ops::{PointInsertOperations, PointsSelector};
+use collection::operations::payload_ops::{DeletePayload, SetPayload, UpdatePayload};
+use collection::operations::point_ops::{PointInsertOperations, PointsSelector, WriteOrdering};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use storage::content_manager::toc::TableOfContent;
 
-use crate::actix::helpers::process_response;
+use crate::actix::helpers::{process_response, process_update_response};
 use crate::common::points::{
     do_clear_payload, do_create_index, do_delete_index, do_delete_payload, do_delete_points,
-    do_set_payload, do_upsert_points, CreateFieldIndex,
+    do_set_payload, do_update_payload, do_upsert_points, CreateFieldIndex,
 };
+use crate::common::request_limits::RequestLimits;
 
 #[derive(Deserialize, Serialize, JsonSchema)]
 pub struct UpdateParam {
     pub wait: Option<bool>,
+    pub ordering: Option<WriteOrdering>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+pub struct PointsUpdateParam {
+    pub wait: Option<bool>,
+    pub ordering: Option<WriteOrdering>,
+    /// Retries of the operation with the same id are applied only once
+    pub operation_id: Option<String>,
 }
 
 #[put("/collections/{name}/points")]
 pub async fn upsert_points(
     toc: web::Data<TableOfContent>,
+    limits: web::Data<RequestLimits>,
     path: web::Path<String>,
     operation: web::Json<PointInsertOperations>,
-    params: Query<UpdateParam>,
+    params: Query<PointsUpdateParam>,
 ) -> impl Responder {
     let collection_name = path.into_inner();
     let operation = operation.into_inner();
     let wait = params.wait.unwrap_or(false);
+    let ordering = params.ordering.unwrap_or_default();
+    let timing = Instant::now();
+
+    let response = match limits.check_upsert_operation(&operation) {
+        Ok(()) => {
+            do_upsert_points(
+                toc.get_ref(),
+                &collection_name,
+                operation,
+                params.operation_id.clone(),
+                None,
+                wait,
+                ordering,
+            )
+            .await
+        }
+        Err(err) => Err(err),
+    };
+    let retry_after = toc.update_retry_after(&collection_name).await;
+    process_update_response(response, timing, retry_after)
+}
+
+#[put("/collections/{name}/points/bulk")]
+pub async fn bulk_upsert_points(
+    toc: web::Data<TableOfContent>,
+    limits: web::Data<RequestLimits>,
+    path: web::Path<String>,
+    operation: web::Json<PointInsertOperations>,
+) -> impl Responder {
+    let collection_name = path.into_inner();
+    let operation = operation.into_inner();
     let timing = Instant::now();
 
-    let response = do_upsert_points(toc.get_ref(), &collection_name, operation, None, wait).await;
+    let response = match limits.check_upsert_operation(&operation) {
+        Ok(()) => toc.bulk_upsert(&collection_name, operation).await,
+        Err(err) => Err(err),
+    };
     process_response(response, timing)
 }
 
@@ -39,15 +84,26 @@ pub async fn delete_points(
     toc: web::Data<TableOfContent>,
     path: web::Path<String>,
     operation: web::Json<PointsSelector>,
-    params: Query<UpdateParam>,
+    params: Query<PointsUpdateParam>,
 ) -> impl Responder {
     let collection_name = path.into_inner();
     let operation = operation.into_inner();
     let wait = params.wait.unwrap_or(false);
+    let ordering = params.ordering.unwrap_or_default();
     let timing = Instant::now();
 
-    let response = do_delete_points(toc.get_ref(), &collection_name, operation, None, wait).await;
-    process_response(response, timing)
+    let response = do_delete_points(
+        toc.get_ref(),
+        &collection_name,
+        operation,
+        params.operation_id.clone(),
+        None,
+        wait,
+        ordering,
+    )
+    .await;
+    let retry_after = toc.update_retry_after(&collection_name).await;
+    process_update_response(response, timing, retry_after)
 }
 
 #[post("/collections/{name}/points/payload")]
@@ -60,10 +116,46 @@ pub async fn set_payload(
     let collection_name = path.into_inner();
     let operation = operation.into_inner();
     let wait = params.wait.unwrap_or(false);
+    let ordering = params.ordering.unwrap_or_default();
     let timing = Instant::now();
 
-    let response = do_set_payload(toc.get_ref(), &collection_name, operation, None, wait).await;
-    process_response(response, timing)
+    let response = do_set_payload(
+        toc.get_ref(),
+        &collection_name,
+        operation,
+        None,
+        wait,
+        ordering,
+    )
+    .await;
+    let retry_after = toc.update_retry_after(&collection_name).await;
+    process_update_response(response, timing, retry_after)
+}
+
+#[post("/collections/{name}/points/payload/update")]
+pub async fn update_payload(
+    toc: web::Data<TableOfContent>,
+    path: web::Path<String>,
+    operation: web::Json<UpdatePayload>,
+    params: Query<UpdateParam>,
+) -> impl Responder {
+    let collection_name = path.into_inner();
+    let operation = operation.into_inner();
+    let wait = params.wait.unwrap_or(false);
+    let ordering = params.ordering.unwrap_or_default();
+    let timing = Instant::now();
+
+    let response = do_update_payload(
+        toc.get_ref(),
+        &collection_name,
+        operation,
+        None,
+        wait,
+        ordering,
+    )
+    .await;
+    let retry_after = toc.update_retry_after(&collection_name).await;
+    process_update_response(response, timing, retry_after)
 }
 
 #[post("/collections/{name}/points/payload/delete")]
@@ -76,10 +168,20 @@ pub async fn delete_payload(
     let collection_name = path.into_inner();
     let operation = operation.into_inner();
     let wait = params.wait.unwrap_or(false);
+    let ordering = params.ordering.unwrap_or_default();
     let timing = Instant::now();
 
-    let response = do_delete_payload(toc.get_ref(), &collection_name, operation, None, wait).await;
-    process_response(response, timing)
+    let response = do_delete_payload(
+        toc.get_ref(),
+        &collection_name,
+        operation,
+        None,
+        wait,
+        ordering,
+    )
+    .await;
+    let retry_after = toc.update_retry_after(&collection_name).await;
+    process_update_response(response, timing, retry_after)
 }
 
 #[post("/collections/{name}/points/payload/clear")]
@@ -92,10 +194,20 @@ pub async fn clear_payload(
     let collection_name = path.into_inner();
     let operation = operation.into_inner();
     let wait = params.wait.unwrap_or(false);
+    let ordering = params.ordering.unwrap_or_default();
     let timing = Instant::now();
 
-    let response = do_clear_payload(toc.get_ref(), &collection_name, operation, None, wait).await;
-    process_response(response, timing)
+    let response = do_clear_payload(
+        toc.get_ref(),
+        &collection_name,
+        operation,
+        None,
+        wait,
+        ordering,
+    )
+    .await;
+    let retry_after = toc.update_retry_after(&collection_name).await;
+    process_update_response(response, timing, retry_after)
 }
 
 #[put("/collections/{name}/index")]
@@ -108,9 +220,18 @@ pub async fn create_field_index(
     let collection_name = path.into_inner();
     let operation = operation.into_inner();
     let wait = params.wait.unwrap_or(false);
+    let ordering = params.ordering.unwrap_or_default();
     let timing = Instant::now();
 
-    let response = do_create_index(toc.get_ref(), &collection_name, operation, None, wait).await;
+    let response = do_create_index(
+        toc.get_ref(),
+        &collection_name,
+        operation,
+        None,
+        wait,
+        ordering,
+    )
+    .await;
     process_response(response, timing)
 }
 
@@ -122,17 +243,28 @@ pub async fn delete_field_index(
 ) -> impl Responder {
     let (collection_name, field_name) = path.into_inner();
     let wait = params.wait.unwrap_or(false);
+    let ordering = params.ordering.unwrap_or_default();
     let timing = Instant::now();
 
-    let response = do_delete_index(toc.get_ref(), &collection_name, field_name, None, wait).await;
+    let response = do_delete_index(
+        toc.get_ref(),
+        &collection_name,
+        field_name,
+        None,
+        wait,
+        ordering,
+    )
+    .await;
     process_response(response, timing)
 }
 
 // Configure services
 pub fn config_update_api(cfg: &mut web::ServiceConfig) {
     cfg.service(upsert_points)
+        .service(bulk_upsert_points)
         .service(delete_points)
         .service(set_payload)
+        .service(update_payload)
         .service(delete_payload)
         .service(clear_payload)
         .service(create_field_index)
@@ -0,0 +1,214 @@
+use std::future::{ready, Ready};
+
+use actix_web::body::EitherBody;
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::{Method, StatusCode};
+use actix_web::{web, Error, HttpRequest, HttpResponse};
+use api::grpc::models::{ApiResponse, ApiStatus};
+use futures_util::future::LocalBoxFuture;
+use storage::content_manager::api_tokens::{Access, ApiTokenScope, ClientIdentity};
+use storage::content_manager::errors::StorageError;
+use storage::dispatcher::Dispatcher;
+
+use crate::common::auth::{bearer_token, API_KEY_HEADER, AUTHORIZATION_HEADER};
+
+pub struct AuthService<S> {
+    service: S,
+}
+
+/// Rejects requests, which API key does not grant access to the requested collection
+pub struct AuthTransform;
+
+/// Collection, the request is addressed to, and the token scope, required to handle it.
+/// Requests outside of a single collection require the master key.
+/// So does creation of a collection, as it is not covered by the tokens of existing ones
+/// and might copy data from other collections with `init_from`.
+fn required_access<'a>(method: &Method, path: &'a str) -> (Option<&'a str>, ApiTokenScope) {
+    let mut segments = path.trim_matches('/').split('/');
+    let collection_name = match (segments.next(), segments.next()) {
+        (Some("collections"), Some(name)) if name != "aliases" => name,
+        _ => return (None, ApiTokenScope::Manage),
+    };
+    let rest: Vec<_> = segments.collect();
+    if *method == Method::PUT && rest.is_empty() {
+        return (None, ApiTokenScope::Manage);
+    }
+    let scope = match (method, rest.as_slice()) {
+        (&Method::GET, ["snapshots", ..] | ["slow_queries"]) => ApiTokenScope::Manage,
+        (&Method::GET, _) => ApiTokenScope::Read,
+        (
+            &Method::POST,
+            ["points"]
+            | ["points", "search" | "recommend" | "discover" | "sample" | "scroll" | "count" | "facet"
+            | "export", ..]
+            | ["points", "text", "status"]
+            | ["facet"]
+            | ["exports"],
+        ) => ApiTokenScope::Read,
+        (_, ["points", ..]) => ApiTokenScope::Write,
+        _ => ApiTokenScope::Manage,
+    };
+    (Some(collection_name), scope)
+}
+
+/// Check, if access of the request, resolved by [`AuthTransform`], allows the operation on
+/// a collection, referenced in the body of the request.
+/// Requests, which did not pass the middleware, are not restricted.
+pub fn check_access(
+    request: &HttpRequest,
+    collection_name: Option<&str>,
+    scope: ApiTokenScope,
+) -> Result<(), StorageError> {
+    match request.extensions().get::<Access>() {
+        None => Ok(()),
+        Some(access) => access.check(collection_name, scope),
+    }
+}
+
+/// Client, which sent the request, resolved by [`AuthTransform`].
+/// Unknown, if authentication is disabled.
+pub fn client_identity(request: &HttpRequest) -> Option<ClientIdentity> {
+    request.extensions().get::<ClientIdentity>().cloned()
+}
+
+fn access_error_response(err: StorageError) -> HttpResponse {
+    let status = match err {
+        StorageError::Unauthorized { .. } => StatusCode::UNAUTHORIZED,
+        _ => StatusCode::FORBIDDEN,
+    };
+    HttpResponse::build(status).json(ApiResponse::<()> {
+        result: None,
+        status: ApiStatus::Error(err.to_string()),
+        time: 0.0,
+        truncated: None,
+        explanation: None,
+        shard_placement: None,
+    })
+}
+
+impl<S, B> Service<ServiceRequest> for AuthService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_web::dev::forward_ready!(service);
+
+    fn call(&self, request: ServiceRequest) -> Self::Future {
+        // Health checks and probes of the service are always available
+        if matches!(request.path(), "/" | "/healthz" | "/livez" | "/readyz") {
+            let future = self.service.call(request);
+            return Box::pin(async move { Ok(future.await?.map_into_left_body()) });
+        }
+
+        let checked = match request.app_data::<web::Data<Dispatcher>>() {
+            None => Ok(()),
+            Some(dispatcher) => {
+                let api_key = request
+                    .headers()
+                    .get(API_KEY_HEADER)
+                    .and_then(|value| value.to_str().ok());
+                let bearer_token = request
+                    .headers()
+                    .get(AUTHORIZATION_HEADER)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(bearer_token);
+                let (collection_name, scope) = required_access(request.method(), request.path());
+                dispatcher
+                    .resolve_access(api_key, bearer_token)
+                    .and_then(|(access, identity)| {
+                        access.check(collection_name, scope)?;
+                        // Handlers check access to the collections, referenced in the body
+                        request.extensions_mut().insert(access);
+                        // ... and record the client, which requested collection meta operations
+                        if let Some(identity) = identity {
+                            request.extensions_mut().insert(identity);
+                        }
+                        Ok(())
+                    })
+            }
+        };
+
+        match checked {
+            Ok(()) => {
+                let future = self.service.call(request);
+                Box::pin(async move { Ok(future.await?.map_into_left_body()) })
+            }
+            Err(err) => {
+                let response = request
+                    .into_response(access_error_response(err))
+                    .map_into_right_body();
+                Box::pin(async move { Ok(response) })
+            }
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for AuthTransform
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = AuthService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(AuthService { service }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_required_access() {
+        use ApiTokenScope::{Manage, Read, Write};
+
+        let cases = [
+            ("GET /collections", None, Manage),
+            ("POST /collections/aliases", None, Manage),
+            ("GET /telemetry", None, Manage),
+            ("GET /aliases", None, Manage),
+            ("GET /collections/test/aliases", Some("test"), Read),
+            ("GET /collections/test", Some("test"), Read),
+            (
+                "POST /collections/test/points/search/batch",
+                Some("test"),
+                Read,
+            ),
+            ("POST /collections/test/points", Some("test"), Read),
+            ("POST /collections/test/points/export", Some("test"), Read),
+            ("POST /collections/test/points/discover", Some("test"), Read),
+            ("POST /collections/test/points/sample", Some("test"), Read),
+            ("PUT /collections/test/points", Some("test"), Write),
+            ("POST /collections/test/points/payload", Some("test"), Write),
+            ("PUT /collections/test/index", Some("test"), Manage),
+            ("DELETE /collections/test", Some("test"), Manage),
+            ("PATCH /collections/test", Some("test"), Manage),
+            ("PUT /collections/test", None, Manage),
+            (
+                "GET /collections/test/snapshots/a.snapshot",
+                Some("test"),
+                Manage,
+            ),
+        ];
+        for (request, collection_name, scope) in cases {
+            let (method, path) = request.split_once(' ').unwrap();
+            let method = Method::from_bytes(method.as_bytes()).unwrap();
+            assert_eq!(
+                required_access(&method, path),
+                (collection_name, scope),
+                "{request}"
+            );
+        }
+    }
+}
@@ -1,11 +1,14 @@
 use std::fmt::Debug;
 
+use actix_web::http::header::{HeaderValue, RETRY_AFTER};
 use actix_web::rt::time::Instant;
 use actix_web::{error, Error, HttpResponse, Responder};
-use api::grpc::models::{ApiResponse, ApiStatus};
+use api::grpc::models::{ApiResponse, ApiStatus, ShardPlacement};
 use collection::operations::types::CollectionError;
+use segment::types::SearchExplanation;
 use serde::Serialize;
 use storage::content_manager::errors::StorageError;
+use storage::content_manager::shard_distribution::ShardDistributionProposal;
 
 pub fn collection_into_actix_error(err: CollectionError) -> Error {
     let storage_error: StorageError = err.into();
@@ -19,10 +22,90 @@ pub fn storage_into_actix_error(err: StorageError) -> Error {
         StorageError::ServiceError { .. } => error::ErrorInternalServerError(format!("{}", err)),
         StorageError::BadRequest { .. } => error::ErrorBadRequest(format!("{}", err)),
         StorageError::Locked { .. } => error::ErrorForbidden(format!("{}", err)),
+        StorageError::Overloaded { .. } => error::ErrorServiceUnavailable(format!("{}", err)),
+        StorageError::Forbidden { .. } => error::ErrorForbidden(format!("{}", err)),
+        StorageError::Unauthorized { .. } => error::ErrorUnauthorized(format!("{}", err)),
+        StorageError::PayloadTooLarge { .. } => error::ErrorPayloadTooLarge(format!("{}", err)),
     }
 }
 
 pub fn process_response<D>(response: Result<D, StorageError>, timing: Instant) -> impl Responder
+where
+    D: Serialize + Debug,
+{
+    process_response_with_truncation(response, timing, None, None, None)
+}
+
+/// Same as `process_response`, but for writes.
+/// `retry_after` is the number of seconds, the client is advised to wait before the next write
+pub fn process_update_response<D>(
+    response: Result<D, StorageError>,
+    timing: Instant,
+    retry_after: Option<u64>,
+) -> HttpResponse
+where
+    D: Serialize + Debug,
+{
+    let mut response = process_response_with_truncation(response, timing, None, None, None);
+    if let Some(retry_after) = retry_after {
+        response
+            .headers_mut()
+            .insert(RETRY_AFTER, HeaderValue::from(retry_after));
+    }
+    response
+}
+
+/// Same as `process_response`, but for the search result, its truncation flag and explanation.
+/// The flag is reported only for searches `with_time_budget`, the explanation - only if `explain`.
+pub fn process_search_response<D>(
+    response: Result<(D, bool, SearchExplanation), StorageError>,
+    timing: Instant,
+    with_time_budget: bool,
+    explain: bool,
+) -> impl Responder
+where
+    D: Serialize + Debug,
+{
+    let (response, truncated, explanation) = match response {
+        Ok((result, truncated, explanation)) => (
+            Ok(result),
+            with_time_budget.then_some(truncated),
+            explain.then_some(explanation),
+        ),
+        Err(err) => (Err(err), None, None),
+    };
+    process_response_with_truncation(response, timing, truncated, explanation, None)
+}
+
+/// Same as `process_response`, but for the collection creation and the placement of its shards.
+/// The placement is only reported, if it was chosen for the collection in distributed mode.
+pub fn process_create_collection_response(
+    response: Result<(bool, Option<ShardDistributionProposal>), StorageError>,
+    timing: Instant,
+) -> impl Responder {
+    let (response, shard_placement) = match response {
+        Ok((result, shard_placement)) => (
+            Ok(result),
+            shard_placement.map(|proposal| {
+                proposal
+                    .distribution
+                    .into_iter()
+                    .map(|(shard_id, peers)| ShardPlacement { shard_id, peers })
+                    .collect()
+            }),
+        ),
+        Err(err) => (Err(err), None),
+    };
+    process_response_with_truncation(response, timing, None, None, shard_placement)
+}
+
+fn process_response_with_truncation<D>(
+    response: Result<D, StorageError>,
+    timing: Instant,
+    truncated: Option<bool>,
+    explanation: Option<SearchExplanation>,
+    shard_placement: Option<Vec<ShardPlacement>>,
+) -> HttpResponse
 where
     D: Serialize + Debug,
 {
@@ -31,6 +114,9 @@ where
             result: Some(res),
             status: ApiStatus::Ok,
             time: timing.elapsed().as_secs_f64(),
+            truncated,
+            explanation,
+            shard_placement,
         }),
         Err(err) => {
             let error_description = format!("{}", err);
@@ -44,12 +130,19 @@ where
                 }
                 StorageError::BadRequest { .. } => HttpResponse::BadRequest(),
                 StorageError::Locked { .. } => HttpResponse::Forbidden(),
+                StorageError::Overloaded { .. } => HttpResponse::ServiceUnavailable(),
+                StorageError::Forbidden { .. } => HttpResponse::Forbidden(),
+                StorageError::Unauthorized { .. } => HttpResponse::Unauthorized(),
+                StorageError::PayloadTooLarge { .. } => HttpResponse::PayloadTooLarge(),
             };
 
             resp.json(ApiResponse::<()> {
                 result: None,
                 status: ApiStatus::Error(error_description),
                 time: timing.elapsed().as_secs_f64(),
+                truncated: None,
+                explanation: None,
+                shard_placement: None,
             })
         }
     }
@@ -1,6 +1,8 @@
+pub mod access_log;
 #[allow(dead_code)] // May contain functions used in different binaries. Not actually dead
 pub mod actix_telemetry;
 pub mod api;
+pub mod auth;
 #[allow(dead_code)] // May contain functions used in different binaries. Not actually dead
 pub mod helpers;
 
@@ -13,15 +15,26 @@ use actix_web::web::Data;
 use actix_web::{error, get, web, App, HttpRequest, HttpResponse, HttpServer, Responder};
 use storage::dispatcher::Dispatcher;
 
+use crate::actix::api::changes_api::stream_points_changes;
 use crate::actix::api::cluster_api::config_cluster_api;
 use crate::actix::api::collections_api::config_collections_api;
 use crate::actix::api::count_api::count_points;
+#[cfg(all(feature = "service_debug", not(target_env = "msvc")))]
+use crate::actix::api::debug_api::config_debug_api;
+use crate::actix::api::export_api::config_export_api;
+use crate::actix::api::facet_api::{facet_collection, facet_points};
+use crate::actix::api::inference_api::config_inference_api;
+use crate::actix::api::jobs_api::config_jobs_api;
 use crate::actix::api::recommend_api::config_recommend_api;
-use crate::actix::api::retrieve_api::{get_point, get_points, scroll_points};
+use crate::actix::api::retrieve_api::{get_point, get_points, sample_points, scroll_points};
 use crate::actix::api::search_api::config_search_api;
 use crate::actix::api::service_api::config_service_api;
 use crate::actix::api::snapshot_api::config_snapshots_api;
 use crate::actix::api::update_api::config_update_api;
+use crate::common::events::EventsService;
+use crate::common::inference::InferenceService;
+use crate::common::request_limits::RequestLimits;
+use crate::common::shutdown::ShutdownSignal;
 use crate::common::telemetry::TelemetryCollector;
 use crate::settings::{max_web_workers, Settings};
 
@@ -31,6 +44,9 @@ fn json_error_handler(err: error::JsonPayloadError, _req: &HttpRequest) -> error
     let detail = err.to_string();
     let mut resp_b = match &err {
         JsonPayloadError::ContentType => HttpResponse::UnsupportedMediaType(),
+        JsonPayloadError::Overflow { .. } | JsonPayloadError::OverflowKnownLength { .. } => {
+            HttpResponse::PayloadTooLarge()
+        }
         JsonPayloadError::Deserialize(json_err) if json_err.is_data() => {
             HttpResponse::UnprocessableEntity()
         }
@@ -40,6 +56,9 @@ fn json_error_handler(err: error::JsonPayloadError, _req: &HttpRequest) -> error
         result: None,
         status: ApiStatus::Error(detail),
         time: 0.0,
+        truncated: None,
+        explanation: None,
+        shard_placement: None,
     });
     error::InternalError::from_response(err, response).into()
 }
@@ -49,14 +68,32 @@ pub async fn index() -> impl Responder {
     HttpResponse::Ok().json(VersionInfo::default())
 }
 
+/// CORS policy, which allows any method and header from the listed origins.
+/// If no origins are listed or `*` is listed, requests from any origin are allowed
+fn cors_middleware(allowed_origins: &[String]) -> Cors {
+    let cors = Cors::default().allow_any_method().allow_any_header();
+    if allowed_origins.is_empty() || allowed_origins.iter().any(|origin| origin == "*") {
+        return cors.allow_any_origin();
+    }
+    allowed_origins
+        .iter()
+        .fold(cors, |cors, origin| cors.allowed_origin(origin))
+}
+
 #[allow(dead_code)]
 pub fn init(
     dispatcher: Arc<Dispatcher>,
     telemetry_collector: Arc<tokio::sync::Mutex<TelemetryCollector>>,
+    events_service: EventsService,
     settings: Settings,
+    shutdown: ShutdownSignal,
 ) -> std::io::Result<()> {
     actix_web::rt::System::new().block_on(async {
         let toc_data = web::Data::from(dispatcher.toc().clone());
+        let inference_data = web::Data::new(InferenceService::new(
+            settings.inference.clone(),
+            dispatcher.toc().clone(),
+        ));
         let dispatcher_data = web::Data::from(dispatcher);
         let actix_telemetry_collector = telemetry_collector
             .lock()
@@ -64,52 +101,92 @@ pub fn init(
             .actix_telemetry_collector
             .clone();
         let telemetry_collector_data = web::Data::from(telemetry_collector);
-        HttpServer::new(move || {
-            let cors = Cors::default()
-                .allow_any_origin()
-                .allow_any_method()
-                .allow_any_header();
+        let events_service_data = web::Data::new(events_service);
+        let request_limits_data = web::Data::new(RequestLimits::new(&settings.service));
+        let workers = max_web_workers(&settings);
+        let bind_hosts = settings.service.bind_hosts();
+        let http_port = settings.service.http_port;
+        let shutdown_timeout_sec = settings.service.shutdown_timeout_sec;
+        let mut server = HttpServer::new(move || {
+            let cors = cors_middleware(&settings.service.cors_allowed_origins);
 
-            App::new()
+            let app = App::new()
+                // Registered first to run after CORS, which answers preflight requests without keys
+                .wrap(auth::AuthTransform)
                 .wrap(Condition::new(settings.service.enable_cors, cors))
-                .wrap(Logger::default().exclude("/")) // Avoid logging healthcheck requests
+                // Avoid logging healthcheck and probe requests
+                .wrap(
+                    Logger::default()
+                        .exclude("/")
+                        .exclude("/healthz")
+                        .exclude("/livez")
+                        .exclude("/readyz"),
+                )
+                .wrap(access_log::AccessLogTransform)
                 .wrap(actix_telemetry::ActixTelemetryTransform::new(
                     actix_telemetry_collector.clone(),
                 ))
                 .app_data(dispatcher_data.clone())
                 .app_data(toc_data.clone())
                 .app_data(telemetry_collector_data.clone())
+                .app_data(inference_data.clone())
+                .app_data(events_service_data.clone())
+                .app_data(request_limits_data.clone())
                 .app_data(Data::new(
                     web::JsonConfig::default()
-                        .limit(settings.service.max_request_size_mb * 1024 * 1024)
+                        .limit(request_limits_data.max_request_size)
                         .error_handler(json_error_handler),
                 ))
                 .service(index)
                 .configure(config_collections_api)
                 .configure(config_snapshots_api)
+                .configure(config_export_api)
+                .configure(config_jobs_api)
                 .configure(config_update_api)
                 .configure(config_cluster_api)
                 .configure(config_service_api)
                 .configure(config_search_api)
                 .configure(config_recommend_api)
+                .configure(config_inference_api)
+                // Must be registered before `get_point`, otherwise `changes` is taken for a point id
+                .service(stream_points_changes)
                 .service(get_point)
                 .service(get_points)
                 .service(scroll_points)
+                .service(sample_points)
                 .service(count_points)
+                .service(facet_points)
+                .service(facet_collection);
+            #[cfg(all(feature = "service_debug", not(target_env = "msvc")))]
+            let app = app.configure(config_debug_api);
+            app
         })
-        .workers(max_web_workers(&settings))
-        .bind(format!(
-            "{}:{}",
-            settings.service.host, settings.service.http_port
-        ))?
-        .run()
-        .await
+        .workers(workers)
+        // Stopped by the shared shutdown signal, to drain all the servers together
+        .disable_signals()
+        .shutdown_timeout(shutdown_timeout_sec);
+        for host in bind_hosts {
+            server = server.bind((host, http_port))?;
+        }
+        let server = server.run();
+        let server_handle = server.handle();
+        actix_web::rt::spawn(async move {
+            shutdown.wait().await;
+            log::debug!("Stopping HTTP");
+            // Stop accepting connections and wait for in-flight requests
+            server_handle.stop(true).await;
+        });
+        server.await
     })
 }
 
 #[cfg(test)]
 mod tests {
     use ::api::grpc::api_crate_version;
+    use actix_web::http::header;
+    use actix_web::{test, web, App, HttpResponse};
+
+    use super::cors_middleware;
 
     #[test]
     fn test_version() {
@@ -119,4 +196,62 @@ mod tests {
             "Qdrant and lib/api crate versions are not same"
         );
     }
+
+    /// Origin, which the CORS policy allows to read the response to a request from `origin`
+    async fn allowed_origin(allowed_origins: &[&str], origin: &str) -> Option<String> {
+        let allowed_origins: Vec<_> = allowed_origins.iter().map(|o| o.to_string()).collect();
+        let app = test::init_service(
+            App::new()
+                .wrap(cors_middleware(&allowed_origins))
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+        let request = test::TestRequest::get()
+            .uri("/")
+            .insert_header((header::ORIGIN, origin))
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        response
+            .headers()
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .map(|value| value.to_str().unwrap().to_string())
+    }
+
+    #[actix_web::test]
+    async fn test_cors_any_origin() {
+        let origin = "https://example.com";
+        // Empty list allows any origin
+        assert_eq!(allowed_origin(&[], origin).await.as_deref(), Some(origin));
+        // ... so does the wildcard, even if other origins are listed
+        assert_eq!(
+            allowed_origin(&["*"], origin).await.as_deref(),
+            Some(origin)
+        );
+        assert_eq!(
+            allowed_origin(&["https://qdrant.tech", "*"], origin)
+                .await
+                .as_deref(),
+            Some(origin)
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_cors_explicit_origins() {
+        let allowed_origins = ["https://qdrant.tech", "http://localhost:3000"];
+        for origin in allowed_origins {
+            assert_eq!(
+                allowed_origin(&allowed_origins, origin).await.as_deref(),
+                Some(origin)
+            );
+        }
+        assert_eq!(
+            allowed_origin(&allowed_origins, "https://example.com").await,
+            None
+        );
+        // Port is a part of the origin
+        assert_eq!(
+            allowed_origin(&allowed_origins, "http://localhost:8080").await,
+            None
+        );
+    }
 }
@@ -0,0 +1,65 @@
+use std::time::Duration;
+
+use serde::Serialize;
+
+/// Target of the access log records.
+/// Could be enabled or disabled separately with `log_level`, e.g. `INFO,access_log=off`
+pub const ACCESS_LOG_TARGET: &str = "access_log";
+
+/// Single handled request, logged as a JSON line
+#[derive(Debug, Serialize)]
+pub struct AccessLogRecord<'a> {
+    /// `http` or `grpc`
+    pub protocol: &'a str,
+    /// HTTP method and path pattern, or full name of the gRPC method
+    pub method: &'a str,
+    pub collection: Option<&'a str>,
+    /// HTTP status code, or gRPC status code
+    pub status: u16,
+    pub duration_ms: f64,
+    /// Size of the request body in bytes, if known before reading it
+    pub request_size: Option<u64>,
+}
+
+pub fn is_access_log_enabled() -> bool {
+    log::log_enabled!(target: ACCESS_LOG_TARGET, log::Level::Info)
+}
+
+pub fn log_access(record: &AccessLogRecord) {
+    match serde_json::to_string(record) {
+        Ok(line) => log::info!(target: ACCESS_LOG_TARGET, "{line}"),
+        Err(err) => log::warn!("Failed to serialize access log record: {err}"),
+    }
+}
+
+pub fn duration_ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_access_log_record() {
+        let record = AccessLogRecord {
+            protocol: "http",
+            method: "POST /collections/{name}/points/search",
+            collection: Some("test"),
+            status: 200,
+            duration_ms: duration_ms(Duration::from_micros(1500)),
+            request_size: None,
+        };
+        assert_eq!(
+            serde_json::to_value(&record).unwrap(),
+            serde_json::json!({
+                "protocol": "http",
+                "method": "POST /collections/{name}/points/search",
+                "collection": "test",
+                "status": 200,
+                "duration_ms": 1.5,
+                "request_size": null,
+            })
+        );
+    }
+}
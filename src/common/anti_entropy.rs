@@ -0,0 +1,39 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use storage::content_manager::toc::TableOfContent;
+
+use crate::settings::AntiEntropyConfig;
+
+/// Periodically compares replicas of the shards and re-syncs points, which diverged from
+/// the leading replica, e.g. after a write failed on a part of the replicas.
+/// Every peer compares only the shards with the leading replica on it,
+/// so each shard is compared once per round across the cluster.
+pub struct AntiEntropy {
+    config: AntiEntropyConfig,
+    toc: Arc<TableOfContent>,
+}
+
+impl AntiEntropy {
+    pub fn new(config: AntiEntropyConfig, toc: Arc<TableOfContent>) -> Self {
+        Self { config, toc }
+    }
+
+    /// Compare replicas until the process is stopped. Failed collections are logged and skipped.
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.interval_sec));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            let report = self.toc.sync_leading_replicas().await;
+            if report.points_repaired > 0 {
+                log::info!(
+                    "Re-synced {} of {} points in {} shards with diverged replicas",
+                    report.points_repaired,
+                    report.points_checked,
+                    report.shards_checked
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,14 @@
+/// Header of REST requests and metadata key of gRPC requests, which carries the API key:
+/// either the master key or a token of a collection
+pub const API_KEY_HEADER: &str = "api-key";
+
+/// Header of REST requests and metadata key of gRPC requests, which carries the JWT
+pub const AUTHORIZATION_HEADER: &str = "authorization";
+
+/// Token of the `Bearer <token>` authorization value
+pub fn bearer_token(authorization: &str) -> Option<&str> {
+    authorization
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+}
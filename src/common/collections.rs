@@ -1,19 +1,25 @@
 use std::time::Duration;
 
-use api::grpc::models::{CollectionDescription, CollectionsResponse};
+use api::grpc::models::{
+    AliasDescription, CollectionDescription, CollectionsAliasesResponse, CollectionsResponse,
+};
+use collection::jobs::export::{CreateExport, ExportDescription};
 use collection::operations::cluster_ops::{
     AbortTransferOperation, ClusterOperations, DropReplicaOperation, MoveShardOperation,
-    ReplicateShardOperation,
+    ReplicaStateChange, ReplicateShardOperation, SetReplicaStateOperation,
 };
 use collection::operations::snapshot_ops::SnapshotDescription;
-use collection::operations::types::{CollectionClusterInfo, CollectionInfo};
+use collection::operations::types::{
+    CollectionClusterInfo, CollectionInfo, ReshardingPreview, ReshardingPreviewRequest,
+};
 use collection::shards::replica_set;
 use collection::shards::shard::ShardId;
 use collection::shards::transfer::shard_transfer::{ShardTransfer, ShardTransferKey};
 use itertools::Itertools;
+use storage::content_manager::api_tokens::ClientIdentity;
 use storage::content_manager::collection_meta_ops::ShardTransferOperations::{Abort, Start};
 use storage::content_manager::collection_meta_ops::{
-    CollectionMetaOperations, UpdateCollectionOperation,
+    CollectionMetaOperations, SetShardReplicaState, UpdateCollectionOperation,
 };
 use storage::content_manager::errors::StorageError;
 use storage::content_manager::toc::TableOfContent;
@@ -39,6 +45,37 @@ pub async fn do_list_collections(toc: &TableOfContent) -> CollectionsResponse {
     CollectionsResponse { collections }
 }
 
+pub async fn do_list_aliases(toc: &TableOfContent) -> CollectionsAliasesResponse {
+    let aliases = toc
+        .list_aliases()
+        .await
+        .into_iter()
+        .map(|(alias_name, collection_name)| AliasDescription {
+            alias_name,
+            collection_name,
+        })
+        .collect_vec();
+
+    CollectionsAliasesResponse { aliases }
+}
+
+pub async fn do_list_collection_aliases(
+    toc: &TableOfContent,
+    collection_name: &str,
+) -> Result<CollectionsAliasesResponse, StorageError> {
+    let aliases = toc
+        .list_collection_aliases(collection_name)
+        .await?
+        .into_iter()
+        .map(|(alias_name, collection_name)| AliasDescription {
+            alias_name,
+            collection_name,
+        })
+        .collect_vec();
+
+    Ok(CollectionsAliasesResponse { aliases })
+}
+
 pub async fn do_list_snapshots(
     toc: &TableOfContent,
     collection_name: &str,
@@ -50,11 +87,51 @@ pub async fn do_list_snapshots(
         .await?)
 }
 
+/// Create a full snapshot of the collection, or an incremental one, if the `base` is given
 pub async fn do_create_snapshot(
     toc: &TableOfContent,
     collection_name: &str,
+    base: Option<&str>,
 ) -> Result<SnapshotDescription, StorageError> {
-    toc.create_snapshot(collection_name).await
+    match base {
+        None => toc.create_snapshot(collection_name).await,
+        Some(base) => toc.create_incremental_snapshot(collection_name, base).await,
+    }
+}
+
+pub async fn do_list_exports(
+    toc: &TableOfContent,
+    collection_name: &str,
+) -> Result<Vec<ExportDescription>, StorageError> {
+    Ok(toc
+        .get_collection(collection_name)
+        .await?
+        .list_exports()
+        .await)
+}
+
+pub async fn do_create_export(
+    toc: &TableOfContent,
+    collection_name: &str,
+    request: CreateExport,
+) -> Result<ExportDescription, StorageError> {
+    Ok(toc
+        .get_collection(collection_name)
+        .await?
+        .create_export(request)
+        .await?)
+}
+
+pub async fn do_get_export(
+    toc: &TableOfContent,
+    collection_name: &str,
+    export_id: &str,
+) -> Result<ExportDescription, StorageError> {
+    Ok(toc
+        .get_collection(collection_name)
+        .await?
+        .get_export(export_id)
+        .await?)
 }
 
 pub async fn do_get_collection_cluster(
@@ -65,11 +142,21 @@ pub async fn do_get_collection_cluster(
     Ok(collection.cluster_info(toc.this_peer_id).await?)
 }
 
+pub async fn do_resharding_preview(
+    toc: &TableOfContent,
+    name: &str,
+    request: ReshardingPreviewRequest,
+) -> Result<ReshardingPreview, StorageError> {
+    let collection = toc.get_collection(name).await?;
+    Ok(collection.resharding_preview(request).await?)
+}
+
 pub async fn do_update_collection_cluster(
     toc: &TableOfContent,
     collection_name: String,
     operation: ClusterOperations,
     dispatcher: &Dispatcher,
+    identity: Option<ClientIdentity>,
     wait_timeout: Option<Duration>,
 ) -> Result<bool, StorageError> {
     if dispatcher.consensus_state().is_none() {
@@ -114,18 +201,23 @@ pub async fn do_update_collection_cluster(
             // validate source peer exists
             validate_peer_exists(move_shard.from_peer_id)?;
 
+            let transfer = ShardTransfer {
+                shard_id: move_shard.shard_id,
+                to: move_shard.to_peer_id,
+                from: move_shard.from_peer_id,
+                sync: false,
+                method: move_shard.method,
+                max_points_per_second: move_shard.max_points_per_second,
+            };
+
+            // validate transfer against the current state, so invalid transfers are not proposed
+            toc.validate_shard_transfer(&collection, &transfer).await?;
+
             // submit operation to consensus
             dispatcher
                 .submit_collection_meta_op(
-                    CollectionMetaOperations::TransferShard(
-                        collection_name,
-                        Start(ShardTransfer {
-                            shard_id: move_shard.shard_id,
-                            to: move_shard.to_peer_id,
-                            from: move_shard.from_peer_id,
-                            sync: false,
-                        }),
-                    ),
+                    CollectionMetaOperations::TransferShard(collection_name, Start(transfer)),
+                    identity,
                     wait_timeout,
                 )
                 .await
@@ -147,18 +239,23 @@ pub async fn do_update_collection_cluster(
             // validate source peer exists
             validate_peer_exists(replicate_shard.from_peer_id)?;
 
+            let transfer = ShardTransfer {
+                shard_id: replicate_shard.shard_id,
+                to: replicate_shard.to_peer_id,
+                from: replicate_shard.from_peer_id,
+                sync: true,
+                method: replicate_shard.method,
+                max_points_per_second: replicate_shard.max_points_per_second,
+            };
+
+            // validate transfer against the current state, so invalid transfers are not proposed
+            toc.validate_shard_transfer(&collection, &transfer).await?;
+
             // submit operation to consensus
             dispatcher
                 .submit_collection_meta_op(
-                    CollectionMetaOperations::TransferShard(
-                        collection_name,
-                        Start(ShardTransfer {
-                            shard_id: replicate_shard.shard_id,
-                            to: replicate_shard.to_peer_id,
-                            from: replicate_shard.from_peer_id,
-                            sync: true,
-                        }),
-                    ),
+                    CollectionMetaOperations::TransferShard(collection_name, Start(transfer)),
+                    identity,
                     wait_timeout,
                 )
                 .await
@@ -188,6 +285,7 @@ pub async fn do_update_collection_cluster(
                             reason: "user request".to_string(),
                         },
                     ),
+                    identity,
                     wait_timeout,
                 )
                 .await
@@ -214,6 +312,49 @@ pub async fn do_update_collection_cluster(
             dispatcher
                 .submit_collection_meta_op(
                     CollectionMetaOperations::UpdateCollection(update_operation),
+                    identity,
+                    wait_timeout,
+                )
+                .await
+        }
+        ClusterOperations::SetReplicaState(SetReplicaStateOperation { set_replica_state }) => {
+            let ReplicaStateChange {
+                shard_id,
+                peer_id,
+                state,
+            } = set_replica_state;
+
+            let current_state = collection
+                .state()
+                .await
+                .shards
+                .get(&shard_id)
+                .and_then(|shard_info| shard_info.replicas.get(&peer_id).copied())
+                .ok_or_else(|| StorageError::BadRequest {
+                    description: format!(
+                        "Replica of shard {shard_id} of {collection_name} on peer {peer_id} does not exist"
+                    ),
+                })?;
+
+            if !current_state.is_switchable_to(state) {
+                return Err(StorageError::BadRequest {
+                    description: format!(
+                        "Only active replicas can be turned into listeners, \
+                         listeners are promoted back with a shard transfer. \
+                         Replica of shard {shard_id} on peer {peer_id} is {current_state:?}"
+                    ),
+                });
+            }
+
+            dispatcher
+                .submit_collection_meta_op(
+                    CollectionMetaOperations::SetShardReplicaState(SetShardReplicaState {
+                        collection_name,
+                        shard_id,
+                        peer_id,
+                        state,
+                    }),
+                    identity,
                     wait_timeout,
                 )
                 .await
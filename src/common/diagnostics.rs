@@ -0,0 +1,479 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use storage::types::StorageConfig;
+
+/// Name of the temporary file, used to check capabilities of the filesystem
+const PROBE_FILE_NAME: &str = ".qdrant-diagnostics-probe";
+/// Size of the probe file. Should be a multiple of the block size to allow direct IO
+const PROBE_FILE_SIZE: usize = 4096;
+/// RocksDB and segments keep a lot of files open, lower limits cause failures under load
+const MIN_RECOMMENDED_OPEN_FILES: u64 = 10_000;
+/// Each mmap-ed file takes at least one memory map, default Linux value is 65530
+const MIN_RECOMMENDED_MAX_MAP_COUNT: u64 = 262_144;
+
+/// Capabilities of the filesystem, used for the storage or snapshots
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+pub struct PathDiagnostics {
+    pub path: String,
+    /// It is possible to create files in this directory
+    pub writable: bool,
+    /// Files in this directory could be memory-mapped. `None` if not checked
+    pub mmap: Option<bool>,
+    /// Files in this directory could be opened with `O_DIRECT`. `None` if not checked
+    pub direct_io: Option<bool>,
+    /// Total size of the filesystem in bytes
+    pub total_space: Option<u64>,
+    /// Space available for writing in bytes
+    pub available_space: Option<u64>,
+    /// Size of the data in this directory in bytes
+    pub used_space: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+pub struct LimitsDiagnostics {
+    /// Soft limit of open file descriptors (`ulimit -n`)
+    pub open_files: Option<u64>,
+    /// Hard limit of open file descriptors (`ulimit -Hn`)
+    pub open_files_hard: Option<u64>,
+    /// Maximum number of memory map areas (`vm.max_map_count`)
+    pub max_map_count: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+pub struct MemoryDiagnostics {
+    /// Total RAM in bytes
+    pub total: Option<u64>,
+    /// RAM available for new allocations and page cache, in bytes
+    pub available: Option<u64>,
+    /// RAM currently used by page cache, in bytes
+    pub cached: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+pub struct CpuDiagnostics {
+    pub cores: Option<usize>,
+    /// SIMD instruction sets, used for distance calculation
+    pub features: Vec<String>,
+}
+
+/// Report of the environment self-test.
+/// Contains detected capabilities of the system and warnings about potential misconfiguration.
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+pub struct DiagnosticsReport {
+    pub storage: PathDiagnostics,
+    pub snapshots: PathDiagnostics,
+    pub limits: LimitsDiagnostics,
+    pub memory: MemoryDiagnostics,
+    pub cpu: CpuDiagnostics,
+    /// Human-readable descriptions of detected problems
+    pub warnings: Vec<String>,
+}
+
+impl DiagnosticsReport {
+    /// Run all checks. Might take a while on large storages, as it calculates size of the data.
+    pub fn collect(storage_config: &StorageConfig) -> Self {
+        let storage = check_path(Path::new(&storage_config.storage_path));
+        let snapshots = check_path(Path::new(&storage_config.snapshots_path));
+        let limits = check_limits();
+        let memory = check_memory();
+        let cpu = check_cpu();
+
+        let mut report = DiagnosticsReport {
+            storage,
+            snapshots,
+            limits,
+            memory,
+            cpu,
+            warnings: vec![],
+        };
+        report.warnings = report.find_problems(storage_config.performance.max_search_threads);
+        report
+    }
+
+    fn find_problems(&self, max_search_threads: usize) -> Vec<String> {
+        let mut warnings = vec![];
+
+        for (name, path) in [("storage", &self.storage), ("snapshots", &self.snapshots)] {
+            if !path.writable {
+                warnings.push(format!("Can't write into {name} directory `{}`", path.path));
+            }
+            if path.mmap == Some(false) {
+                warnings.push(format!(
+                    "Filesystem of {name} directory `{}` does not support mmap, \
+                     collections with `memmap_threshold` will fail to optimize",
+                    path.path
+                ));
+            }
+        }
+
+        if let Some(open_files) = self.limits.open_files {
+            if open_files < MIN_RECOMMENDED_OPEN_FILES {
+                warnings.push(format!(
+                    "Limit of open files is {open_files}, \
+                     at least {MIN_RECOMMENDED_OPEN_FILES} is recommended (`ulimit -n`)"
+                ));
+            }
+        }
+        if let Some(max_map_count) = self.limits.max_map_count {
+            if max_map_count < MIN_RECOMMENDED_MAX_MAP_COUNT {
+                warnings.push(format!(
+                    "`vm.max_map_count` is {max_map_count}, \
+                     at least {MIN_RECOMMENDED_MAX_MAP_COUNT} is recommended for mmap storage"
+                ));
+            }
+        }
+
+        if let (Some(available), Some(used)) = (self.memory.available, self.storage.used_space) {
+            if used > available {
+                warnings.push(format!(
+                    "Storage size ({used} bytes) exceeds available memory ({available} bytes), \
+                     not all data fits into page cache. Consider `on_disk_payload` \
+                     and `memmap_threshold` to reduce memory usage"
+                ));
+            }
+        }
+
+        if self.is_same_device() {
+            if let (Some(available), Some(used)) =
+                (self.snapshots.available_space, self.storage.used_space)
+            {
+                if available < used {
+                    warnings.push(format!(
+                        "Snapshots are stored on the same disk as the data and free space \
+                         ({available} bytes) is less than storage size ({used} bytes). \
+                         Creating a snapshot may run out of disk space"
+                    ));
+                }
+            }
+        }
+
+        if let Some(cores) = self.cpu.cores {
+            if max_search_threads > cores {
+                warnings.push(format!(
+                    "`max_search_threads` ({max_search_threads}) is greater than \
+                     the number of CPU cores ({cores})"
+                ));
+            }
+        }
+        if self.cpu.features.is_empty() {
+            warnings.push(
+                "No SIMD instructions detected, distance calculation will be slow".to_string(),
+            );
+        }
+
+        if cfg!(debug_assertions) {
+            warnings.push("Qdrant is built in debug mode, performance will be poor".to_string());
+        }
+
+        warnings
+    }
+
+    #[cfg(unix)]
+    fn is_same_device(&self) -> bool {
+        use std::os::unix::fs::MetadataExt;
+        let device = |path: &str| fs::metadata(path).ok().map(|metadata| metadata.dev());
+        match (device(&self.storage.path), device(&self.snapshots.path)) {
+            (Some(storage), Some(snapshots)) => storage == snapshots,
+            _ => false,
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn is_same_device(&self) -> bool {
+        false
+    }
+}
+
+fn check_path(path: &Path) -> PathDiagnostics {
+    let probe_path = path.join(PROBE_FILE_NAME);
+    let writable = fs::create_dir_all(path).is_ok()
+        && File::create(&probe_path)
+            .and_then(|mut file| file.write_all(&[0u8; PROBE_FILE_SIZE]))
+            .is_ok();
+
+    let (mmap, direct_io) = if writable {
+        (Some(check_mmap(&probe_path)), check_direct_io(&probe_path))
+    } else {
+        (None, None)
+    };
+    let _ = fs::remove_file(&probe_path);
+
+    let (total_space, available_space) = match disk_space(path) {
+        Some((total, available)) => (Some(total), Some(available)),
+        None => (None, None),
+    };
+
+    PathDiagnostics {
+        path: path.to_string_lossy().to_string(),
+        writable,
+        mmap,
+        direct_io,
+        total_space,
+        available_space,
+        used_space: dir_size(path),
+    }
+}
+
+fn check_mmap(path: &Path) -> bool {
+    let file = match OpenOptions::new().read(true).write(true).open(path) {
+        Ok(file) => file,
+        Err(_) => return false,
+    };
+    match unsafe { memmap::MmapMut::map_mut(&file) } {
+        Ok(mut mmap) => {
+            mmap[0] = 1;
+            mmap.flush().is_ok()
+        }
+        Err(_) => false,
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn check_direct_io(path: &Path) -> Option<bool> {
+    use std::os::unix::fs::OpenOptionsExt;
+    Some(
+        OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_DIRECT)
+            .open(path)
+            .is_ok(),
+    )
+}
+
+#[cfg(not(target_os = "linux"))]
+fn check_direct_io(_path: &Path) -> Option<bool> {
+    None
+}
+
+/// Returns total and available space of the filesystem, containing `path`
+#[cfg(unix)]
+#[allow(clippy::unnecessary_cast)] // Types of `statvfs` fields differ between platforms
+fn disk_space(path: &Path) -> Option<(u64, u64)> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    let block_size = stat.f_frsize as u64;
+    Some((
+        stat.f_blocks as u64 * block_size,
+        stat.f_bavail as u64 * block_size,
+    ))
+}
+
+#[cfg(not(unix))]
+fn disk_space(_path: &Path) -> Option<(u64, u64)> {
+    None
+}
+
+fn dir_size(path: &Path) -> Option<u64> {
+    let mut size = 0;
+    for entry in fs::read_dir(path).ok()? {
+        let entry = entry.ok()?;
+        let metadata = entry.metadata().ok()?;
+        if metadata.is_dir() {
+            size += dir_size(&entry.path()).unwrap_or(0);
+        } else {
+            size += metadata.len();
+        }
+    }
+    Some(size)
+}
+
+#[cfg(unix)]
+#[allow(clippy::unnecessary_cast)] // `rlim_t` differs between platforms
+fn check_limits() -> LimitsDiagnostics {
+    let mut limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    let (open_files, open_files_hard) =
+        if unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut limit) } == 0 {
+            (Some(limit.rlim_cur as u64), Some(limit.rlim_max as u64))
+        } else {
+            (None, None)
+        };
+    let max_map_count = fs::read_to_string("/proc/sys/vm/max_map_count")
+        .ok()
+        .and_then(|value| value.trim().parse().ok());
+
+    LimitsDiagnostics {
+        open_files,
+        open_files_hard,
+        max_map_count,
+    }
+}
+
+#[cfg(not(unix))]
+fn check_limits() -> LimitsDiagnostics {
+    LimitsDiagnostics {
+        open_files: None,
+        open_files_hard: None,
+        max_map_count: None,
+    }
+}
+
+fn check_memory() -> MemoryDiagnostics {
+    match sys_info::mem_info() {
+        // `sys_info` reports memory in KiB
+        Ok(info) => MemoryDiagnostics {
+            total: Some(info.total * 1024),
+            available: Some(info.avail * 1024),
+            cached: Some(info.cached * 1024),
+        },
+        Err(_) => MemoryDiagnostics {
+            total: None,
+            available: None,
+            cached: None,
+        },
+    }
+}
+
+fn check_cpu() -> CpuDiagnostics {
+    let mut features = vec![];
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        if std::arch::is_x86_feature_detected!("sse") {
+            features.push("sse".to_string());
+        }
+        if std::arch::is_x86_feature_detected!("avx") {
+            features.push("avx".to_string());
+        }
+        if std::arch::is_x86_feature_detected!("avx2") {
+            features.push("avx2".to_string());
+        }
+        if std::arch::is_x86_feature_detected!("fma") {
+            features.push("fma".to_string());
+        }
+        if std::arch::is_x86_feature_detected!("avx512f") {
+            features.push("avx512f".to_string());
+        }
+    }
+    #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+    {
+        if std::arch::is_aarch64_feature_detected!("neon") {
+            features.push("neon".to_string());
+        }
+    }
+    CpuDiagnostics {
+        cores: sys_info::cpu_num().ok().map(|x| x as usize),
+        features,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::Builder;
+
+    use super::*;
+
+    const GB: u64 = 1024 * 1024 * 1024;
+
+    fn path_diagnostics(path: &str, used_space: u64) -> PathDiagnostics {
+        PathDiagnostics {
+            path: path.to_string(),
+            writable: true,
+            mmap: Some(true),
+            direct_io: Some(true),
+            total_space: Some(100 * GB),
+            available_space: Some(50 * GB),
+            used_space: Some(used_space),
+        }
+    }
+
+    fn healthy_report(storage_path: &str, snapshots_path: &str) -> DiagnosticsReport {
+        DiagnosticsReport {
+            storage: path_diagnostics(storage_path, GB),
+            snapshots: path_diagnostics(snapshots_path, 0),
+            limits: LimitsDiagnostics {
+                open_files: Some(MIN_RECOMMENDED_OPEN_FILES),
+                open_files_hard: Some(MIN_RECOMMENDED_OPEN_FILES),
+                max_map_count: Some(MIN_RECOMMENDED_MAX_MAP_COUNT),
+            },
+            memory: MemoryDiagnostics {
+                total: Some(16 * GB),
+                available: Some(8 * GB),
+                cached: Some(GB),
+            },
+            cpu: CpuDiagnostics {
+                cores: Some(4),
+                features: vec!["avx2".to_string()],
+            },
+            warnings: vec![],
+        }
+    }
+
+    /// Problems, which are not caused by the build of the service
+    fn find_problems(report: &DiagnosticsReport, max_search_threads: usize) -> Vec<String> {
+        report
+            .find_problems(max_search_threads)
+            .into_iter()
+            .filter(|warning| !warning.contains("debug mode"))
+            .collect()
+    }
+
+    #[test]
+    fn test_healthy_environment() {
+        let report = healthy_report("/not/existing/storage", "/not/existing/snapshots");
+        assert!(find_problems(&report, 4).is_empty());
+    }
+
+    #[test]
+    fn test_find_problems() {
+        let mut report = healthy_report("/not/existing/storage", "/not/existing/snapshots");
+        report.storage.writable = false;
+        report.snapshots.mmap = Some(false);
+        report.limits.open_files = Some(1024);
+        report.limits.max_map_count = Some(65530);
+        report.memory.available = Some(GB / 2);
+        report.cpu.features.clear();
+
+        let warnings = find_problems(&report, 8);
+        assert_eq!(warnings.len(), 7, "{warnings:#?}");
+        let has_warning = |text: &str| warnings.iter().any(|warning| warning.contains(text));
+        assert!(has_warning("Can't write into storage directory"));
+        assert!(has_warning(
+            "snapshots directory `/not/existing/snapshots` does not support mmap"
+        ));
+        assert!(has_warning("Limit of open files is 1024"));
+        assert!(has_warning("`vm.max_map_count` is 65530"));
+        assert!(has_warning("exceeds available memory"));
+        assert!(has_warning("`max_search_threads` (8)"));
+        assert!(has_warning("No SIMD instructions detected"));
+
+        // Unknown values are not reported as problems
+        let mut report = healthy_report("/not/existing/storage", "/not/existing/snapshots");
+        report.limits.open_files = None;
+        report.limits.max_map_count = None;
+        report.memory.available = None;
+        report.cpu.cores = None;
+        assert!(find_problems(&report, 8).is_empty());
+    }
+
+    #[test]
+    fn test_snapshots_on_the_same_disk() {
+        let dir = Builder::new().prefix("diagnostics").tempdir().unwrap();
+        let storage_path = dir.path().join("storage");
+        let snapshots_path = dir.path().join("snapshots");
+        fs::create_dir_all(&storage_path).unwrap();
+        fs::create_dir_all(&snapshots_path).unwrap();
+
+        let mut report = healthy_report(
+            storage_path.to_str().unwrap(),
+            snapshots_path.to_str().unwrap(),
+        );
+        assert!(find_problems(&report, 4).is_empty());
+
+        report.snapshots.available_space = Some(GB / 2);
+        let warnings = find_problems(&report, 4);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("Creating a snapshot may run out of disk space"));
+    }
+}
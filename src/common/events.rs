@@ -0,0 +1,341 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use collection::operations::types::CollectionStatus;
+use collection::shards::shard::{PeerId, ShardId};
+use segment::types::SeqNumberType;
+use serde::Serialize;
+use storage::content_manager::events::StateChange;
+use storage::dispatcher::Dispatcher;
+use storage::types::ClusterStatus;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+
+/// Max number of events buffered for a slow subscriber. Lagging subscribers skip the oldest events.
+const EVENTS_BUFFER_SIZE: usize = 1024;
+
+/// How often a comment is sent to idle subscribers, so proxies don't close the connection
+pub const EVENTS_KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Comment line of the server-sent events stream, ignored by clients
+pub const EVENTS_KEEP_ALIVE_MESSAGE: &str = ": keep-alive\n\n";
+
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct ClusterTopology {
+    /// Peers of the cluster, sorted by id
+    pub peers: Vec<PeerId>,
+    /// Leader of the current term
+    pub leader: Option<PeerId>,
+}
+
+/// Change of the service state, streamed to subscribers
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum ServiceEvent {
+    /// Status of the collection changed.
+    /// `previous` is `None` for a new collection, `status` is `None` for a removed one.
+    CollectionStatus {
+        collection_name: String,
+        previous: Option<CollectionStatus>,
+        status: Option<CollectionStatus>,
+    },
+    /// Optimization of a local shard of the collection is completed
+    OptimizationCompleted {
+        collection_name: String,
+        shard_id: ShardId,
+    },
+    /// Update operation of a local shard repeatedly failed to apply,
+    /// so it is saved into the dead letter storage of the shard and skipped
+    OperationQuarantined {
+        collection_name: String,
+        shard_id: ShardId,
+        op_num: SeqNumberType,
+    },
+    /// Set of peers or the leader of the cluster changed
+    ClusterTopology(ClusterTopology),
+}
+
+impl ServiceEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ServiceEvent::CollectionStatus { .. } => "collection_status",
+            ServiceEvent::OptimizationCompleted { .. } => "optimization_completed",
+            ServiceEvent::OperationQuarantined { .. } => "operation_quarantined",
+            ServiceEvent::ClusterTopology(_) => "cluster_topology",
+        }
+    }
+
+    /// Format event as a message of the server-sent events stream
+    pub fn to_sse_message(&self) -> String {
+        let data = serde_json::to_string(self).unwrap_or_default();
+        format!("event: {}\ndata: {data}\n\n", self.name())
+    }
+}
+
+/// Last reported state of the service, events are only sent if it changes
+#[derive(Debug, Default)]
+struct ServiceState {
+    collections: HashMap<String, CollectionStatus>,
+    topology: Option<ClusterTopology>,
+}
+
+impl ServiceState {
+    async fn collect(dispatcher: &Dispatcher) -> Self {
+        let mut collections = HashMap::new();
+        for collection_name in dispatcher.toc().all_collections().await {
+            if let Some(status) = collection_status(dispatcher, &collection_name).await {
+                collections.insert(collection_name, status);
+            }
+        }
+        Self {
+            collections,
+            topology: cluster_topology(dispatcher),
+        }
+    }
+
+    /// Re-check the part of the state, affected by the `change`, and return events for
+    /// everything that actually changed since the last report
+    async fn apply(&mut self, dispatcher: &Dispatcher, change: StateChange) -> Vec<ServiceEvent> {
+        match change {
+            StateChange::Collection(collection_name) => {
+                let status = collection_status(dispatcher, &collection_name).await;
+                let previous = match status {
+                    Some(status) => self.collections.insert(collection_name.clone(), status),
+                    None => self.collections.remove(&collection_name),
+                };
+                if previous == status {
+                    return vec![];
+                }
+                vec![ServiceEvent::CollectionStatus {
+                    collection_name,
+                    previous,
+                    status,
+                }]
+            }
+            StateChange::OptimizationCompleted {
+                collection_name,
+                shard_id,
+            } => vec![ServiceEvent::OptimizationCompleted {
+                collection_name,
+                shard_id,
+            }],
+            StateChange::OperationQuarantined {
+                collection_name,
+                shard_id,
+                op_num,
+            } => vec![ServiceEvent::OperationQuarantined {
+                collection_name,
+                shard_id,
+                op_num,
+            }],
+            StateChange::Cluster => match cluster_topology(dispatcher) {
+                Some(topology) if self.topology.as_ref() != Some(&topology) => {
+                    self.topology = Some(topology.clone());
+                    vec![ServiceEvent::ClusterTopology(topology)]
+                }
+                _ => vec![],
+            },
+        }
+    }
+
+    /// Events, which lead from `self` to the `new` state
+    fn changes(&self, new: &ServiceState) -> Vec<ServiceEvent> {
+        let mut events = vec![];
+
+        for (collection_name, status) in &new.collections {
+            let previous = self.collections.get(collection_name).copied();
+            if previous != Some(*status) {
+                events.push(ServiceEvent::CollectionStatus {
+                    collection_name: collection_name.clone(),
+                    previous,
+                    status: Some(*status),
+                });
+            }
+        }
+
+        for (collection_name, status) in &self.collections {
+            if !new.collections.contains_key(collection_name) {
+                events.push(ServiceEvent::CollectionStatus {
+                    collection_name: collection_name.clone(),
+                    previous: Some(*status),
+                    status: None,
+                });
+            }
+        }
+
+        if let Some(topology) = &new.topology {
+            if self.topology.as_ref() != Some(topology) {
+                events.push(ServiceEvent::ClusterTopology(topology.clone()));
+            }
+        }
+
+        events
+    }
+}
+
+/// Status of the collection, `None` if it doesn't exist
+async fn collection_status(
+    dispatcher: &Dispatcher,
+    collection_name: &str,
+) -> Option<CollectionStatus> {
+    let collection = dispatcher
+        .toc()
+        .get_collection(collection_name)
+        .await
+        .ok()?;
+    match collection.info(None).await {
+        Ok(info) => Some(info.status),
+        Err(err) => {
+            log::debug!("Can't get info of collection {collection_name}: {err}");
+            Some(CollectionStatus::Red)
+        }
+    }
+}
+
+fn cluster_topology(dispatcher: &Dispatcher) -> Option<ClusterTopology> {
+    match dispatcher.cluster_status() {
+        ClusterStatus::Disabled => None,
+        ClusterStatus::Enabled(cluster_info) => {
+            let mut peers: Vec<_> = cluster_info.peers.keys().copied().collect();
+            peers.sort_unstable();
+            Some(ClusterTopology {
+                peers,
+                leader: cluster_info.raft_info.leader,
+            })
+        }
+    }
+}
+
+/// Streams changes of collection statuses, completed optimizations, quarantined operations
+/// and cluster topology, so clients don't need to poll info endpoints.
+///
+/// The state is only re-checked when the storage or the consensus reports a transition.
+#[derive(Clone)]
+pub struct EventsService {
+    sender: broadcast::Sender<ServiceEvent>,
+}
+
+impl EventsService {
+    pub fn new() -> Self {
+        let (sender, _receiver) = broadcast::channel(EVENTS_BUFFER_SIZE);
+        Self { sender }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ServiceEvent> {
+        self.sender.subscribe()
+    }
+
+    fn send(&self, events: Vec<ServiceEvent>) {
+        for event in events {
+            // Error means that there are no subscribers at the moment
+            let _ = self.sender.send(event);
+        }
+    }
+
+    /// Turn state transitions into events for subscribers, until the storage is dropped
+    pub async fn run(self, dispatcher: Arc<Dispatcher>) {
+        let mut changes = dispatcher.toc().state_changes().subscribe();
+        let mut state = ServiceState::collect(&dispatcher).await;
+        loop {
+            match changes.recv().await {
+                Ok(change) => {
+                    let events = state.apply(&dispatcher, change).await;
+                    self.send(events);
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    // Transitions are missed, so the whole state has to be re-checked
+                    log::debug!("Events service lagged, skipped {skipped} state changes");
+                    let new_state = ServiceState::collect(&dispatcher).await;
+                    self.send(state.changes(&new_state));
+                    state = new_state;
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    }
+}
+
+impl Default for EventsService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(collections: &[(&str, CollectionStatus)]) -> ServiceState {
+        ServiceState {
+            collections: collections
+                .iter()
+                .map(|(name, status)| (name.to_string(), *status))
+                .collect(),
+            topology: None,
+        }
+    }
+
+    #[test]
+    fn test_state_changes() {
+        let old = state(&[
+            ("a", CollectionStatus::Green),
+            ("b", CollectionStatus::Green),
+        ]);
+        let new = state(&[
+            ("a", CollectionStatus::Yellow),
+            ("c", CollectionStatus::Green),
+        ]);
+
+        let mut events = old.changes(&new);
+        events.sort_by_key(|event| format!("{event:?}"));
+
+        assert_eq!(
+            events,
+            vec![
+                ServiceEvent::CollectionStatus {
+                    collection_name: "a".to_string(),
+                    previous: Some(CollectionStatus::Green),
+                    status: Some(CollectionStatus::Yellow),
+                },
+                ServiceEvent::CollectionStatus {
+                    collection_name: "b".to_string(),
+                    previous: Some(CollectionStatus::Green),
+                    status: None,
+                },
+                ServiceEvent::CollectionStatus {
+                    collection_name: "c".to_string(),
+                    previous: None,
+                    status: Some(CollectionStatus::Green),
+                },
+            ]
+        );
+
+        assert!(new.changes(&new).is_empty());
+    }
+
+    #[test]
+    fn test_topology_changes() {
+        let old = ServiceState {
+            collections: Default::default(),
+            topology: Some(ClusterTopology {
+                peers: vec![1, 2],
+                leader: Some(1),
+            }),
+        };
+        let new = ServiceState {
+            collections: Default::default(),
+            topology: Some(ClusterTopology {
+                peers: vec![1, 2],
+                leader: Some(2),
+            }),
+        };
+
+        let events = old.changes(&new);
+        assert_eq!(events.len(), 1);
+        assert_eq!(
+            events[0].to_sse_message(),
+            "event: cluster_topology\ndata: {\"type\":\"cluster_topology\",\"peers\":[1,2],\"leader\":2}\n\n"
+        );
+    }
+}
@@ -2,6 +2,9 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use storage::content_manager::errors::StorageError;
+use storage::content_manager::search_runtime::search_threads;
+use storage::content_manager::toc::TableOfContent;
 use tokio::runtime;
 use tokio::runtime::Runtime;
 
@@ -11,13 +14,31 @@ pub struct LocksOption {
     pub write: bool,
 }
 
-pub fn create_search_runtime(max_search_threads: usize) -> std::io::Result<Runtime> {
-    let mut search_threads = max_search_threads;
+/// Threads of the search runtime and optimizations to set, if 0 - auto selection
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct ThreadsOption {
+    pub max_search_threads: Option<usize>,
+    pub max_optimization_threads: Option<usize>,
+}
 
-    if search_threads == 0 {
-        let num_cpu = num_cpus::get();
-        search_threads = std::cmp::max(1, num_cpu - 1);
+/// Number of threads, currently used by searches and optimizations
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct ThreadsInfo {
+    pub search_threads: usize,
+    pub max_optimization_threads: usize,
+}
+
+impl ThreadsInfo {
+    pub fn collect(toc: &TableOfContent) -> Self {
+        Self {
+            search_threads: toc.search_threads(),
+            max_optimization_threads: toc.max_optimization_threads(),
+        }
     }
+}
+
+pub fn create_search_runtime(max_search_threads: usize) -> std::io::Result<Runtime> {
+    let search_threads = search_threads(max_search_threads);
 
     runtime::Builder::new_multi_thread()
         .worker_threads(search_threads)
@@ -30,6 +51,28 @@ pub fn create_search_runtime(max_search_threads: usize) -> std::io::Result<Runti
         .build()
 }
 
+/// Resize the search runtime and the optimization budget without restarting the service.
+/// The search runtime is rebuilt, if the number of its threads is changed.
+pub fn resize_threads(
+    toc: &TableOfContent,
+    option: &ThreadsOption,
+) -> Result<ThreadsInfo, StorageError> {
+    if let Some(max_search_threads) = option.max_search_threads {
+        let threads = search_threads(max_search_threads);
+        if threads != toc.search_threads() {
+            let runtime =
+                create_search_runtime(threads).map_err(|err| StorageError::ServiceError {
+                    description: format!("Can't create search runtime: {err}"),
+                })?;
+            toc.replace_search_runtime(runtime, threads);
+        }
+    }
+    if let Some(max_optimization_threads) = option.max_optimization_threads {
+        toc.set_max_optimization_threads(max_optimization_threads);
+    }
+    Ok(ThreadsInfo::collect(toc))
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -0,0 +1,550 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use collection::operations::point_ops::{PointInsertOperations, PointStruct, WriteOrdering};
+use hyper::client::HttpConnector;
+use hyper::{Body, Client, Request};
+use parking_lot::Mutex;
+use schemars::JsonSchema;
+use segment::data_types::vectors::{VectorStruct, VectorType};
+use segment::types::{Payload, PointIdType};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use storage::content_manager::errors::StorageError;
+use storage::content_manager::toc::TableOfContent;
+
+use crate::common::points::do_upsert_points;
+use crate::settings::InferenceConfig;
+
+/// Max number of point statuses to keep. The oldest statuses are evicted first.
+const MAX_TRACKED_POINTS: usize = 1_000_000;
+
+/// Statuses, which were not updated for this long, are evicted
+const STATUS_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Point, vector of which should be computed from the text
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+pub struct TextPointStruct {
+    /// Point id
+    pub id: PointIdType,
+    /// Text to convert into vector. If not specified - value of the `field` is used
+    pub text: Option<String>,
+    /// Name of the payload field, which contains text to convert into vector
+    pub field: Option<String>,
+    /// Payload values (optional)
+    pub payload: Option<Payload>,
+}
+
+impl TextPointStruct {
+    fn input_text(&self) -> Result<String, StorageError> {
+        if let Some(text) = &self.text {
+            return Ok(text.clone());
+        }
+        let field = self.field.as_ref().ok_or_else(|| StorageError::BadInput {
+            description: format!("Point {}: either `text` or `field` should be set", self.id),
+        })?;
+        match self
+            .payload
+            .as_ref()
+            .and_then(|payload| payload.0.get(field))
+        {
+            Some(Value::String(text)) => Ok(text.clone()),
+            _ => Err(StorageError::BadInput {
+                description: format!("Point {}: payload field `{field}` is not a string", self.id),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+pub struct UpsertTextPoints {
+    pub points: Vec<TextPointStruct>,
+    /// Name of the vector to fill. If not specified - the default vector is used
+    pub vector_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+pub struct TextPointsStatusRequest {
+    /// Look for status of points with given IDs
+    pub ids: Vec<PointIdType>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InferenceStatus {
+    /// Vector is not computed yet
+    Pending,
+    /// Point is stored with the computed vector
+    Completed,
+    /// Vector could not be computed or point could not be stored
+    Failed,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone)]
+pub struct TextPointStatus {
+    pub id: PointIdType,
+    /// `None` if the point was never upserted as text, or if its status was already evicted
+    pub status: Option<InferenceStatus>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct EmbeddingRequest<'a> {
+    inputs: &'a [String],
+}
+
+/// Computes vectors of points, upserted as text, with the external embedding service.
+///
+/// Points are upserted into the collection in background, once their vectors are ready.
+/// Status of each point is tracked in memory and is not persisted.
+pub struct InferenceService {
+    config: Option<InferenceConfig>,
+    toc: Arc<TableOfContent>,
+    client: Client<HttpConnector>,
+    statuses: Mutex<StatusTracker>,
+}
+
+type StatusKey = (String, PointIdType);
+
+struct TrackedStatus {
+    status: InferenceStatus,
+    error: Option<String>,
+    updated_at: Instant,
+    /// Position of the latest update in the eviction queue
+    seq: u64,
+}
+
+/// Bounded map of point statuses, which evicts the least recently updated entries.
+///
+/// Every update is appended to the queue; queue records, superseded by a later update
+/// of the same key, are skipped on eviction and compacted once they outnumber live entries.
+/// So each operation is amortized O(1).
+struct StatusTracker {
+    capacity: usize,
+    ttl: Duration,
+    statuses: HashMap<StatusKey, TrackedStatus>,
+    queue: VecDeque<(StatusKey, u64)>,
+    next_seq: u64,
+}
+
+impl StatusTracker {
+    fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity,
+            ttl,
+            statuses: HashMap::new(),
+            queue: VecDeque::new(),
+            next_seq: 0,
+        }
+    }
+
+    fn get(&self, key: &StatusKey) -> Option<&TrackedStatus> {
+        self.statuses
+            .get(key)
+            .filter(|tracked| tracked.updated_at.elapsed() < self.ttl)
+    }
+
+    fn set(&mut self, key: StatusKey, status: InferenceStatus, error: Option<String>) {
+        self.set_at(key, status, error, Instant::now())
+    }
+
+    fn set_at(
+        &mut self,
+        key: StatusKey,
+        status: InferenceStatus,
+        error: Option<String>,
+        now: Instant,
+    ) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.queue.push_back((key.clone(), seq));
+        self.statuses.insert(
+            key,
+            TrackedStatus {
+                status,
+                error,
+                updated_at: now,
+                seq,
+            },
+        );
+        self.evict(now);
+    }
+
+    fn evict(&mut self, now: Instant) {
+        while let Some((key, seq)) = self.queue.front() {
+            let is_latest = self
+                .statuses
+                .get(key)
+                .map_or(false, |tracked| tracked.seq == *seq);
+            if !is_latest {
+                // Superseded by a later update or already evicted
+                self.queue.pop_front();
+                continue;
+            }
+            let expired = now.duration_since(self.statuses[key].updated_at) >= self.ttl;
+            if !expired && self.statuses.len() <= self.capacity {
+                break;
+            }
+            let (key, _) = self.queue.pop_front().unwrap();
+            self.statuses.remove(&key);
+        }
+        // Drop superseded records, if they make up the most of the queue
+        if self.queue.len() > 2 * self.statuses.len() + 1 {
+            let statuses = &self.statuses;
+            self.queue.retain(|(key, seq)| {
+                statuses
+                    .get(key)
+                    .map_or(false, |tracked| tracked.seq == *seq)
+            });
+        }
+    }
+}
+
+impl InferenceService {
+    pub fn new(config: Option<InferenceConfig>, toc: Arc<TableOfContent>) -> Self {
+        Self {
+            config,
+            toc,
+            client: Client::new(),
+            statuses: Mutex::new(StatusTracker::new(MAX_TRACKED_POINTS, STATUS_TTL)),
+        }
+    }
+
+    fn config(&self) -> Result<&InferenceConfig, StorageError> {
+        self.config.as_ref().ok_or_else(|| {
+            StorageError::bad_request("Inference is not configured, see `inference` in config")
+        })
+    }
+
+    /// Validate the request and schedule computation of vectors.
+    /// Returns `Pending` status for all accepted points.
+    pub async fn upsert(
+        self: Arc<Self>,
+        collection_name: String,
+        request: UpsertTextPoints,
+    ) -> Result<Vec<TextPointStatus>, StorageError> {
+        let batch_size = self.config()?.batch_size.max(1);
+        // Fail early, if the collection does not exist
+        self.toc.get_collection(&collection_name).await?;
+
+        let UpsertTextPoints {
+            points,
+            vector_name,
+        } = request;
+        let texts = points
+            .iter()
+            .map(TextPointStruct::input_text)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let response = points
+            .iter()
+            .map(|point| TextPointStatus {
+                id: point.id,
+                status: Some(InferenceStatus::Pending),
+                error: None,
+            })
+            .collect();
+        for point in &points {
+            self.set_status(&collection_name, point.id, InferenceStatus::Pending, None);
+        }
+
+        tokio::spawn(async move {
+            for (points, texts) in points.chunks(batch_size).zip(texts.chunks(batch_size)) {
+                let result = self
+                    .process_batch(&collection_name, points, texts, vector_name.as_deref())
+                    .await;
+                let (status, error) = match result {
+                    Ok(()) => (InferenceStatus::Completed, None),
+                    Err(err) => {
+                        log::warn!("Failed to upsert points as text: {}", err);
+                        (InferenceStatus::Failed, Some(err.to_string()))
+                    }
+                };
+                for point in points {
+                    self.set_status(&collection_name, point.id, status, error.clone());
+                }
+            }
+        });
+
+        Ok(response)
+    }
+
+    pub fn status(&self, collection_name: &str, ids: &[PointIdType]) -> Vec<TextPointStatus> {
+        let statuses = self.statuses.lock();
+        ids.iter()
+            .map(
+                |id| match statuses.get(&(collection_name.to_string(), *id)) {
+                    Some(tracked) => TextPointStatus {
+                        id: *id,
+                        status: Some(tracked.status),
+                        error: tracked.error.clone(),
+                    },
+                    None => TextPointStatus {
+                        id: *id,
+                        status: None,
+                        error: None,
+                    },
+                },
+            )
+            .collect()
+    }
+
+    async fn process_batch(
+        &self,
+        collection_name: &str,
+        points: &[TextPointStruct],
+        texts: &[String],
+        vector_name: Option<&str>,
+    ) -> Result<(), StorageError> {
+        let vectors = self.embed(texts).await?;
+        let points = points
+            .iter()
+            .zip(vectors)
+            .map(|(point, vector)| PointStruct {
+                id: point.id,
+                vector: match vector_name {
+                    None => VectorStruct::Single(vector),
+                    Some(name) => VectorStruct::Multi(HashMap::from([(name.to_string(), vector)])),
+                },
+                payload: point.payload.clone(),
+            })
+            .collect();
+        let operation = PointInsertOperations::PointsList(points);
+        do_upsert_points(
+            &self.toc,
+            collection_name,
+            operation,
+            None,
+            None,
+            true,
+            WriteOrdering::default(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<VectorType>, StorageError> {
+        request_embeddings(&self.client, self.config()?, texts).await
+    }
+
+    fn set_status(
+        &self,
+        collection_name: &str,
+        id: PointIdType,
+        status: InferenceStatus,
+        error: Option<String>,
+    ) {
+        self.statuses
+            .lock()
+            .set((collection_name.to_string(), id), status, error);
+    }
+}
+
+/// Send texts to the embedding service and check, that it returned a vector for each of them
+async fn request_embeddings(
+    client: &Client<HttpConnector>,
+    config: &InferenceConfig,
+    texts: &[String],
+) -> Result<Vec<VectorType>, StorageError> {
+    let service_error = |err: String| StorageError::ServiceError {
+        description: format!("Embedding service error: {err}"),
+    };
+
+    let body = serde_json::to_vec(&EmbeddingRequest { inputs: texts })
+        .map_err(|err| service_error(err.to_string()))?;
+    let request = Request::post(&config.url)
+        .header(hyper::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body))
+        .map_err(|err| service_error(err.to_string()))?;
+
+    let timeout = Duration::from_secs(config.timeout_sec);
+    let response = tokio::time::timeout(timeout, client.request(request))
+        .await
+        .map_err(|_| service_error("timeout".to_string()))?
+        .map_err(|err| service_error(err.to_string()))?;
+    let status = response.status();
+    let bytes = hyper::body::to_bytes(response.into_body())
+        .await
+        .map_err(|err| service_error(err.to_string()))?;
+    if !status.is_success() {
+        return Err(service_error(format!(
+            "{status}: {}",
+            String::from_utf8_lossy(&bytes)
+        )));
+    }
+
+    let vectors: Vec<VectorType> =
+        serde_json::from_slice(&bytes).map_err(|err| service_error(err.to_string()))?;
+    if vectors.len() != texts.len() {
+        return Err(service_error(format!(
+            "expected {} vectors, got {}",
+            texts.len(),
+            vectors.len()
+        )));
+    }
+    Ok(vectors)
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    fn key(id: u64) -> StatusKey {
+        ("test".to_string(), id.into())
+    }
+
+    /// Serve a single HTTP request with the given response and return the request body
+    async fn serve_once(
+        status: &'static str,
+        body: &'static str,
+    ) -> (String, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/embed", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            let request_body = loop {
+                let read = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..read]);
+                let text = String::from_utf8_lossy(&request).to_string();
+                if let Some((headers, body)) = text.split_once("\r\n\r\n") {
+                    let content_length = headers
+                        .lines()
+                        .find_map(|line| {
+                            let (name, value) = line.split_once(':')?;
+                            name.eq_ignore_ascii_case("content-length")
+                                .then(|| value.trim().parse::<usize>().unwrap())
+                        })
+                        .unwrap_or(0);
+                    if body.len() >= content_length {
+                        break body.to_string();
+                    }
+                }
+                assert!(read > 0, "connection closed before the request was read");
+            };
+            let response = format!(
+                "HTTP/1.1 {status}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+            request_body
+        });
+        (url, handle)
+    }
+
+    fn config(url: String) -> InferenceConfig {
+        InferenceConfig {
+            url,
+            timeout_sec: 5,
+            batch_size: 2,
+        }
+    }
+
+    #[test]
+    fn test_input_text() {
+        let point = |text: Option<&str>, field: Option<&str>| TextPointStruct {
+            id: 1.into(),
+            text: text.map(str::to_string),
+            field: field.map(str::to_string),
+            payload: Some(serde_json::json!({"title": "from payload", "count": 1}).into()),
+        };
+        assert_eq!(
+            point(Some("explicit"), Some("title")).input_text().unwrap(),
+            "explicit"
+        );
+        assert_eq!(
+            point(None, Some("title")).input_text().unwrap(),
+            "from payload"
+        );
+        assert!(point(None, Some("count")).input_text().is_err());
+        assert!(point(None, Some("missing")).input_text().is_err());
+        assert!(point(None, None).input_text().is_err());
+    }
+
+    #[test]
+    fn test_status_tracker_capacity() {
+        let mut tracker = StatusTracker::new(3, STATUS_TTL);
+        for id in 0..3 {
+            tracker.set(key(id), InferenceStatus::Pending, None);
+        }
+        // Re-inserted key becomes the most recent one
+        tracker.set(key(0), InferenceStatus::Completed, None);
+        tracker.set(key(3), InferenceStatus::Pending, None);
+
+        assert!(tracker.get(&key(1)).is_none());
+        assert_eq!(
+            tracker.get(&key(0)).unwrap().status,
+            InferenceStatus::Completed
+        );
+        assert!(tracker.get(&key(2)).is_some());
+        assert!(tracker.get(&key(3)).is_some());
+        assert_eq!(tracker.statuses.len(), 3);
+        // Superseded queue records are dropped on eviction, the queue stays bounded
+        for _ in 0..100 {
+            tracker.set(key(3), InferenceStatus::Pending, None);
+        }
+        assert!(tracker.queue.len() <= 2 * tracker.statuses.len() + 1);
+    }
+
+    #[test]
+    fn test_status_tracker_ttl() {
+        let ttl = Duration::from_secs(10);
+        let mut tracker = StatusTracker::new(100, ttl);
+        let start = Instant::now();
+        tracker.set_at(
+            key(0),
+            InferenceStatus::Failed,
+            Some("error".to_string()),
+            start,
+        );
+        tracker.set_at(
+            key(1),
+            InferenceStatus::Pending,
+            None,
+            start + Duration::from_secs(5),
+        );
+        assert_eq!(
+            tracker.get(&key(0)).unwrap().error.as_deref(),
+            Some("error")
+        );
+
+        tracker.set_at(key(2), InferenceStatus::Pending, None, start + ttl);
+        assert!(!tracker.statuses.contains_key(&key(0)));
+        assert!(tracker.statuses.contains_key(&key(1)));
+        assert!(tracker.statuses.contains_key(&key(2)));
+    }
+
+    #[tokio::test]
+    async fn test_request_embeddings() {
+        let client = Client::new();
+        let texts = vec!["first".to_string(), "second".to_string()];
+
+        let (url, server) = serve_once("200 OK", "[[1.0, 0.0], [0.0, 1.0]]").await;
+        let vectors = request_embeddings(&client, &config(url), &texts)
+            .await
+            .unwrap();
+        assert_eq!(vectors, vec![vec![1.0, 0.0], vec![0.0, 1.0]]);
+        let request: serde_json::Value = serde_json::from_str(&server.await.unwrap()).unwrap();
+        assert_eq!(request, serde_json::json!({"inputs": ["first", "second"]}));
+
+        // Number of vectors should match the number of texts
+        let (url, server) = serve_once("200 OK", "[[1.0, 0.0]]").await;
+        assert!(request_embeddings(&client, &config(url), &texts)
+            .await
+            .is_err());
+        server.await.unwrap();
+
+        let (url, server) =
+            serve_once("500 Internal Server Error", "\"model is not loaded\"").await;
+        let error = request_embeddings(&client, &config(url), &texts)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("model is not loaded"));
+        server.await.unwrap();
+    }
+}
@@ -0,0 +1,139 @@
+use std::ffi::CString;
+use std::os::raw::c_char;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use storage::content_manager::errors::StorageError;
+use tikv_jemalloc_ctl::{arenas, epoch, raw, stats};
+
+/// Memory of a single jemalloc arena, sizes are in bytes
+#[derive(Serialize, Clone, Debug)]
+pub struct ArenaMemoryStats {
+    pub arena: u32,
+    /// Number of threads, currently assigned to the arena
+    pub threads: u32,
+    /// Size of the small and large allocations
+    pub allocated: usize,
+    /// Size of the pages with active allocations
+    pub active: usize,
+    /// Size of the unused pages, which are not returned to the OS yet
+    pub dirty: usize,
+    pub resident: usize,
+    pub mapped: usize,
+}
+
+/// Memory of the process, as seen by jemalloc, sizes are in bytes.
+/// Statistics are refreshed on each collection.
+#[derive(Serialize, Clone, Debug)]
+pub struct MemoryStats {
+    /// Size of the memory, allocated by the application
+    pub allocated: usize,
+    /// Size of the pages with active allocations, larger than `allocated` due to fragmentation
+    pub active: usize,
+    /// Size of the physically resident pages, including allocator metadata and dirty pages
+    pub resident: usize,
+    pub mapped: usize,
+    /// Size of the virtual memory, retained by jemalloc instead of returning it to the OS
+    pub retained: usize,
+    /// Size of the allocator metadata
+    pub metadata: usize,
+    /// Heap profiling is enabled, so profiles could be dumped.
+    /// Enabled on start with `_RJEM_MALLOC_CONF=prof:true`
+    pub profiling: bool,
+    pub arenas: Vec<ArenaMemoryStats>,
+}
+
+fn jemalloc_error(err: tikv_jemalloc_ctl::Error) -> StorageError {
+    StorageError::ServiceError {
+        description: format!("Failed to read jemalloc stats: {err}"),
+    }
+}
+
+/// Read the value of the `mallctl` name, which is built at runtime
+fn read_ctl<T: Copy>(name: &str) -> Result<T, StorageError> {
+    let name = format!("{name}\0");
+    // Safety: types of the values are defined by the jemalloc docs for each name
+    unsafe { raw::read(name.as_bytes()) }.map_err(jemalloc_error)
+}
+
+fn is_profiling_enabled() -> bool {
+    // Safety: `opt.prof` is a bool, reading fails if jemalloc is built without profiling
+    unsafe { raw::read::<bool>(b"opt.prof\0") }.unwrap_or(false)
+}
+
+impl ArenaMemoryStats {
+    /// `None` if the arena is not initialized
+    fn collect(arena: u32, page_size: usize) -> Result<Option<Self>, StorageError> {
+        if !read_ctl::<bool>(&format!("arena.{arena}.initialized"))? {
+            return Ok(None);
+        }
+        let prefix = format!("stats.arenas.{arena}");
+        Ok(Some(Self {
+            arena,
+            threads: read_ctl(&format!("{prefix}.nthreads"))?,
+            allocated: read_ctl::<usize>(&format!("{prefix}.small.allocated"))?
+                + read_ctl::<usize>(&format!("{prefix}.large.allocated"))?,
+            active: read_ctl::<usize>(&format!("{prefix}.pactive"))? * page_size,
+            dirty: read_ctl::<usize>(&format!("{prefix}.pdirty"))? * page_size,
+            resident: read_ctl(&format!("{prefix}.resident"))?,
+            mapped: read_ctl(&format!("{prefix}.mapped"))?,
+        }))
+    }
+}
+
+impl MemoryStats {
+    pub fn collect() -> Result<Self, StorageError> {
+        // Stats are cached by jemalloc until the epoch is advanced
+        epoch::advance().map_err(jemalloc_error)?;
+
+        let page_size = read_ctl::<usize>("arenas.page")?;
+        let mut arenas_stats = Vec::new();
+        for arena in 0..arenas::narenas::read().map_err(jemalloc_error)? {
+            arenas_stats.extend(ArenaMemoryStats::collect(arena, page_size)?);
+        }
+
+        Ok(Self {
+            allocated: stats::allocated::read().map_err(jemalloc_error)?,
+            active: stats::active::read().map_err(jemalloc_error)?,
+            resident: stats::resident::read().map_err(jemalloc_error)?,
+            mapped: stats::mapped::read().map_err(jemalloc_error)?,
+            retained: stats::retained::read().map_err(jemalloc_error)?,
+            metadata: stats::metadata::read().map_err(jemalloc_error)?,
+            profiling: is_profiling_enabled(),
+            arenas: arenas_stats,
+        })
+    }
+}
+
+/// Dump the heap profile into the directory, returns path of the profile.
+/// Profile could be analyzed with `jeprof`.
+pub fn dump_heap_profile(dir: &Path) -> Result<String, StorageError> {
+    if !is_profiling_enabled() {
+        return Err(StorageError::BadRequest {
+            description: "Heap profiling is not enabled, start the service with \
+                          `_RJEM_MALLOC_CONF=prof:true` to enable it"
+                .to_string(),
+        });
+    }
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis())
+        .unwrap_or_default();
+    let path = dir
+        .join(format!("qdrant-heap-{timestamp}.prof"))
+        .to_string_lossy()
+        .into_owned();
+    let c_path = CString::new(path.clone()).map_err(|err| StorageError::ServiceError {
+        description: format!("Invalid heap profile path {path}: {err}"),
+    })?;
+
+    // Safety: `prof.dump` takes a pointer to the null-terminated path, which is alive for the call
+    unsafe { raw::write(b"prof.dump\0", c_path.as_ptr() as *const c_char) }.map_err(|err| {
+        StorageError::ServiceError {
+            description: format!("Failed to dump heap profile into {path}: {err}"),
+        }
+    })?;
+    Ok(path)
+}
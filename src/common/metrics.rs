@@ -0,0 +1,145 @@
+use segment::common::operation_time_statistics::OperationDurationStatistics;
+
+use crate::common::telemetry::TelemetryData;
+use crate::common::telemetry_ops::collections_telemetry::CollectionTelemetryEnum;
+
+/// Single value of the metric, identified by the name and labels.
+/// Names of all metrics start with `qdrant_`, so they don't clash with metrics of other services.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricSample {
+    pub name: &'static str,
+    pub labels: Vec<(&'static str, String)>,
+    pub value: f64,
+}
+
+/// Flat list of metrics, derived from the telemetry data
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetricsData {
+    pub samples: Vec<MetricSample>,
+}
+
+impl MetricsData {
+    fn add(&mut self, name: &'static str, labels: Vec<(&'static str, String)>, value: f64) {
+        self.samples.push(MetricSample {
+            name,
+            labels,
+            value,
+        });
+    }
+
+    fn add_responses(
+        &mut self,
+        prefix: Prefix,
+        labels: Vec<(&'static str, String)>,
+        statistics: &OperationDurationStatistics,
+    ) {
+        self.add(prefix.total, labels.clone(), statistics.count as f64);
+        self.add(
+            prefix.fail_total,
+            labels.clone(),
+            statistics.fail_count as f64,
+        );
+        if let Some(avg_duration_micros) = statistics.avg_duration_micros {
+            self.add(
+                prefix.avg_duration_seconds,
+                labels,
+                f64::from(avg_duration_micros) / 1_000_000.0,
+            );
+        }
+    }
+}
+
+/// Names of the metrics of responses of a single API
+struct Prefix {
+    total: &'static str,
+    fail_total: &'static str,
+    avg_duration_seconds: &'static str,
+}
+
+const REST_RESPONSES: Prefix = Prefix {
+    total: "qdrant_rest_responses_total",
+    fail_total: "qdrant_rest_responses_fail_total",
+    avg_duration_seconds: "qdrant_rest_responses_avg_duration_seconds",
+};
+
+const GRPC_RESPONSES: Prefix = Prefix {
+    total: "qdrant_grpc_responses_total",
+    fail_total: "qdrant_grpc_responses_fail_total",
+    avg_duration_seconds: "qdrant_grpc_responses_avg_duration_seconds",
+};
+
+impl From<&TelemetryData> for MetricsData {
+    fn from(telemetry: &TelemetryData) -> Self {
+        let mut metrics = MetricsData::default();
+
+        metrics.add(
+            "qdrant_app_info",
+            vec![("version", env!("CARGO_PKG_VERSION").to_string())],
+            1.0,
+        );
+
+        metrics.add(
+            "qdrant_collections_total",
+            vec![],
+            telemetry.collections.number_of_collections as f64,
+        );
+        for collection in telemetry.collections.collections.iter().flatten() {
+            // Vectors of each collection are only available in full telemetry
+            if let CollectionTelemetryEnum::Full(collection) = collection {
+                let vectors: usize = collection
+                    .shards
+                    .iter()
+                    .filter_map(|shard| shard.local.as_ref())
+                    .flat_map(|local| local.segments.iter())
+                    .map(|segment| segment.info.num_vectors)
+                    .sum();
+                metrics.add(
+                    "qdrant_collection_vectors_total",
+                    vec![("collection", collection.id.clone())],
+                    vectors as f64,
+                );
+            }
+        }
+
+        metrics.add(
+            "qdrant_cluster_enabled",
+            vec![],
+            if telemetry.cluster.enabled { 1.0 } else { 0.0 },
+        );
+        if let Some(status) = &telemetry.cluster.status {
+            metrics.add(
+                "qdrant_cluster_peers_total",
+                vec![],
+                status.number_of_peers as f64,
+            );
+            metrics.add("qdrant_cluster_term", vec![], status.term as f64);
+            metrics.add("qdrant_cluster_commit", vec![], status.commit as f64);
+            metrics.add(
+                "qdrant_cluster_pending_operations_total",
+                vec![],
+                status.pending_operations as f64,
+            );
+            metrics.add(
+                "qdrant_cluster_voter",
+                vec![],
+                if status.is_voter { 1.0 } else { 0.0 },
+            );
+        }
+
+        for (endpoint, responses) in &telemetry.requests.rest.responses {
+            for (status, statistics) in responses {
+                let labels = vec![
+                    ("endpoint", endpoint.clone()),
+                    ("status", status.to_string()),
+                ];
+                metrics.add_responses(REST_RESPONSES, labels, statistics);
+            }
+        }
+        for (endpoint, statistics) in &telemetry.requests.grpc.responses {
+            let labels = vec![("endpoint", endpoint.clone())];
+            metrics.add_responses(GRPC_RESPONSES, labels, statistics);
+        }
+
+        metrics
+    }
+}
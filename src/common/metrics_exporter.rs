@@ -0,0 +1,186 @@
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use hyper::client::HttpConnector;
+use hyper::{Body, Client, Request};
+use prost::Message;
+use tokio::sync::Mutex;
+
+use crate::common::metrics::MetricsData;
+use crate::common::telemetry::TelemetryCollector;
+use crate::settings::MetricsExportConfig;
+
+/// Telemetry level, which includes per-collection data
+const METRICS_TELEMETRY_LEVEL: usize = 2;
+
+// Messages of the Prometheus remote write protocol
+// See https://prometheus.io/docs/concepts/remote_write_spec/
+
+#[derive(Clone, PartialEq, Message)]
+struct WriteRequest {
+    #[prost(message, repeated, tag = "1")]
+    timeseries: Vec<TimeSeries>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct TimeSeries {
+    #[prost(message, repeated, tag = "1")]
+    labels: Vec<Label>,
+    #[prost(message, repeated, tag = "2")]
+    samples: Vec<Sample>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Label {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(string, tag = "2")]
+    value: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Sample {
+    #[prost(double, tag = "1")]
+    value: f64,
+    #[prost(int64, tag = "2")]
+    timestamp: i64,
+}
+
+/// Convert metrics into remote write request.
+/// Each time series contains the metric name as the `__name__` label, labels are sorted by name.
+fn write_request(
+    metrics: &MetricsData,
+    extra_labels: &[(String, String)],
+    timestamp_ms: i64,
+) -> WriteRequest {
+    let timeseries = metrics
+        .samples
+        .iter()
+        .map(|sample| {
+            let mut labels: Vec<_> = std::iter::once(("__name__", sample.name.to_string()))
+                .chain(
+                    sample
+                        .labels
+                        .iter()
+                        .map(|(name, value)| (*name, value.clone())),
+                )
+                .chain(
+                    extra_labels
+                        .iter()
+                        .map(|(name, value)| (name.as_str(), value.clone())),
+                )
+                .map(|(name, value)| Label {
+                    name: name.to_string(),
+                    value,
+                })
+                .collect();
+            labels.sort_by(|a, b| a.name.cmp(&b.name));
+            labels.dedup_by(|a, b| a.name == b.name);
+            TimeSeries {
+                labels,
+                samples: vec![Sample {
+                    value: sample.value,
+                    timestamp: timestamp_ms,
+                }],
+            }
+        })
+        .collect();
+    WriteRequest { timeseries }
+}
+
+/// Periodically pushes metrics into the Prometheus remote write endpoint
+pub struct MetricsExporter {
+    config: MetricsExportConfig,
+    telemetry_collector: Arc<Mutex<TelemetryCollector>>,
+    client: Client<HttpConnector>,
+}
+
+impl MetricsExporter {
+    pub fn new(
+        config: MetricsExportConfig,
+        telemetry_collector: Arc<Mutex<TelemetryCollector>>,
+    ) -> Self {
+        Self {
+            config,
+            telemetry_collector,
+            client: Client::new(),
+        }
+    }
+
+    /// Push metrics until the process is stopped. Failed pushes are logged and skipped.
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.interval_sec));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            if let Err(err) = self.push().await {
+                log::warn!("Failed to push metrics to {}: {}", self.config.url, err);
+            }
+        }
+    }
+
+    async fn push(&self) -> anyhow::Result<()> {
+        let telemetry = self
+            .telemetry_collector
+            .lock()
+            .await
+            .prepare_data(METRICS_TELEMETRY_LEVEL)
+            .await;
+        let metrics = MetricsData::from(&telemetry);
+
+        let timestamp_ms = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as i64;
+        let extra_labels: Vec<_> = self
+            .config
+            .labels
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        let body = write_request(&metrics, &extra_labels, timestamp_ms).encode_to_vec();
+        let body = snap::raw::Encoder::new().compress_vec(&body)?;
+
+        let request = Request::post(&self.config.url)
+            .header(hyper::header::CONTENT_TYPE, "application/x-protobuf")
+            .header(hyper::header::CONTENT_ENCODING, "snappy")
+            .header("X-Prometheus-Remote-Write-Version", "0.1.0")
+            .body(Body::from(body))?;
+
+        let timeout = Duration::from_secs(self.config.timeout_sec);
+        let response = tokio::time::timeout(timeout, self.client.request(request)).await??;
+        let status = response.status();
+        if !status.is_success() {
+            let bytes = hyper::body::to_bytes(response.into_body()).await?;
+            anyhow::bail!("{status}: {}", String::from_utf8_lossy(&bytes));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::metrics::MetricSample;
+
+    #[test]
+    fn test_write_request_labels() {
+        let metrics = MetricsData {
+            samples: vec![MetricSample {
+                name: "qdrant_grpc_responses_total",
+                labels: vec![("endpoint", "/qdrant.Points/Upsert".to_string())],
+                value: 3.0,
+            }],
+        };
+        let extra_labels = vec![("instance".to_string(), "node-0".to_string())];
+        let request = write_request(&metrics, &extra_labels, 1000);
+
+        assert_eq!(request.timeseries.len(), 1);
+        let series = &request.timeseries[0];
+        let names: Vec<_> = series.labels.iter().map(|l| l.name.as_str()).collect();
+        assert_eq!(names, vec!["__name__", "endpoint", "instance"]);
+        assert_eq!(series.labels[0].value, "qdrant_grpc_responses_total");
+        assert_eq!(series.samples[0].value, 3.0);
+        assert_eq!(series.samples[0].timestamp, 1000);
+
+        let encoded = request.encode_to_vec();
+        assert_eq!(WriteRequest::decode(encoded.as_slice()).unwrap(), request);
+    }
+}
@@ -1,9 +1,35 @@
 #[allow(dead_code)] // May contain functions used in different binaries. Not actually dead
+pub mod access_log;
+#[allow(dead_code)] // May contain functions used in different binaries. Not actually dead
+pub mod anti_entropy;
+pub mod auth;
+#[allow(dead_code)] // May contain functions used in different binaries. Not actually dead
 pub mod collections;
 #[allow(dead_code)] // May contain functions used in different binaries. Not actually dead
+pub mod diagnostics;
+#[allow(dead_code)] // May contain functions used in different binaries. Not actually dead
+pub mod events;
+#[allow(dead_code)] // May contain functions used in different binaries. Not actually dead
 pub mod helpers;
 #[allow(dead_code)] // May contain functions used in different binaries. Not actually dead
+pub mod inference;
+#[cfg(all(feature = "service_debug", not(target_env = "msvc")))]
+pub mod memory_stats;
+#[allow(dead_code)] // May contain functions used in different binaries. Not actually dead
+pub mod metrics;
+#[allow(dead_code)] // May contain functions used in different binaries. Not actually dead
+pub mod metrics_exporter;
+#[allow(dead_code)] // May contain functions used in different binaries. Not actually dead
 pub mod points;
 #[allow(dead_code)] // May contain functions used in different binaries. Not actually dead
+pub mod replication_reconciler;
+#[allow(dead_code)] // May contain functions used in different binaries. Not actually dead
+pub mod request_limits;
+#[allow(dead_code)] // May contain functions used in different binaries. Not actually dead
+pub mod scrubber;
+pub mod shutdown;
+#[allow(dead_code)] // May contain functions used in different binaries. Not actually dead
 pub mod telemetry;
 pub mod telemetry_ops;
+#[allow(dead_code)] // May contain functions used in different binaries. Not actually dead
+pub mod telemetry_reporter;
@@ -1,17 +1,33 @@
-use collection::operations::payload_ops::{DeletePayload, PayloadOps, SetPayload};
-use collection::operations::point_ops::{PointInsertOperations, PointOperations, PointsSelector};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+
+use collection::jobs::export::{ExportFormat, StreamExport, DEFAULT_STREAM_EXPORT_BATCH_SIZE};
+use collection::operations::changes::{PointsChange, DEFAULT_CHANGES_LIMIT};
+use collection::operations::payload_ops::{DeletePayload, PayloadOps, SetPayload, UpdatePayload};
+use collection::operations::point_ops::{
+    PointInsertOperations, PointOperations, PointsSelector, WriteOrdering,
+};
 use collection::operations::types::{
-    CountRequest, CountResult, PointRequest, Record, ScrollRequest, ScrollResult, SearchRequest,
-    SearchRequestBatch, UpdateResult,
+    CountRequest, CountResult, FacetRequest, FacetResponse, PointRequest, Record, ScrollRequest,
+    ScrollResult, SearchRequest, SearchRequestBatch, UpdateResult,
 };
 use collection::operations::{CollectionUpdateOperations, CreateIndex, FieldIndexOperations};
 use collection::shards::shard::ShardId;
+use futures::Stream;
 use schemars::JsonSchema;
-use segment::types::{PayloadFieldSchema, ScoredPoint};
+use segment::types::{
+    BatchSearchResult, PayloadFieldSchema, ScoredPoint, SearchExplanation, SeqNumberType,
+    WithPayloadInterface, WithVector,
+};
 use serde::{Deserialize, Serialize};
 use storage::content_manager::errors::StorageError;
+use storage::content_manager::federated_search::{FederatedScoredPoint, FederatedSearchRequest};
 use storage::content_manager::toc::TableOfContent;
 
+/// How often the WAL is re-read, when all changes of the points are already streamed
+pub const CHANGES_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
 #[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct CreateFieldIndex {
     pub field_name: String,
@@ -23,21 +39,32 @@ pub async fn do_upsert_points(
     toc: &TableOfContent,
     collection_name: &str,
     operation: PointInsertOperations,
+    operation_id: Option<String>,
     shard_selection: Option<ShardId>,
     wait: bool,
+    ordering: WriteOrdering,
 ) -> Result<UpdateResult, StorageError> {
     let collection_operation =
         CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(operation));
-    toc.update(collection_name, collection_operation, shard_selection, wait)
-        .await
+    toc.update_with_id(
+        collection_name,
+        collection_operation,
+        operation_id,
+        shard_selection,
+        wait,
+        ordering,
+    )
+    .await
 }
 
 pub async fn do_delete_points(
     toc: &TableOfContent,
     collection_name: &str,
     points: PointsSelector,
+    operation_id: Option<String>,
     shard_selection: Option<ShardId>,
     wait: bool,
+    ordering: WriteOrdering,
 ) -> Result<UpdateResult, StorageError> {
     let point_operation = match points {
         PointsSelector::PointIdsSelector(points) => {
@@ -48,8 +75,15 @@ pub async fn do_delete_points(
         }
     };
     let collection_operation = CollectionUpdateOperations::PointOperation(point_operation);
-    toc.update(collection_name, collection_operation, shard_selection, wait)
-        .await
+    toc.update_with_id(
+        collection_name,
+        collection_operation,
+        operation_id,
+        shard_selection,
+        wait,
+        ordering,
+    )
+    .await
 }
 
 pub async fn do_set_payload(
@@ -58,11 +92,38 @@ pub async fn do_set_payload(
     operation: SetPayload,
     shard_selection: Option<ShardId>,
     wait: bool,
+    ordering: WriteOrdering,
 ) -> Result<UpdateResult, StorageError> {
     let collection_operation =
         CollectionUpdateOperations::PayloadOperation(PayloadOps::SetPayload(operation));
-    toc.update(collection_name, collection_operation, shard_selection, wait)
-        .await
+    toc.update(
+        collection_name,
+        collection_operation,
+        shard_selection,
+        wait,
+        ordering,
+    )
+    .await
+}
+
+pub async fn do_update_payload(
+    toc: &TableOfContent,
+    collection_name: &str,
+    operation: UpdatePayload,
+    shard_selection: Option<ShardId>,
+    wait: bool,
+    ordering: WriteOrdering,
+) -> Result<UpdateResult, StorageError> {
+    let collection_operation =
+        CollectionUpdateOperations::PayloadOperation(PayloadOps::UpdatePayload(operation));
+    toc.update(
+        collection_name,
+        collection_operation,
+        shard_selection,
+        wait,
+        ordering,
+    )
+    .await
 }
 
 pub async fn do_delete_payload(
@@ -71,11 +132,18 @@ pub async fn do_delete_payload(
     operation: DeletePayload,
     shard_selection: Option<ShardId>,
     wait: bool,
+    ordering: WriteOrdering,
 ) -> Result<UpdateResult, StorageError> {
     let collection_operation =
         CollectionUpdateOperations::PayloadOperation(PayloadOps::DeletePayload(operation));
-    toc.update(collection_name, collection_operation, shard_selection, wait)
-        .await
+    toc.update(
+        collection_name,
+        collection_operation,
+        shard_selection,
+        wait,
+        ordering,
+    )
+    .await
 }
 
 pub async fn do_clear_payload(
@@ -84,6 +152,7 @@ pub async fn do_clear_payload(
     points: PointsSelector,
     shard_selection: Option<ShardId>,
     wait: bool,
+    ordering: WriteOrdering,
 ) -> Result<UpdateResult, StorageError> {
     let points_operation = match points {
         PointsSelector::PointIdsSelector(points) => PayloadOps::ClearPayload {
@@ -95,8 +164,14 @@ pub async fn do_clear_payload(
     };
 
     let collection_operation = CollectionUpdateOperations::PayloadOperation(points_operation);
-    toc.update(collection_name, collection_operation, shard_selection, wait)
-        .await
+    toc.update(
+        collection_name,
+        collection_operation,
+        shard_selection,
+        wait,
+        ordering,
+    )
+    .await
 }
 
 pub async fn do_create_index(
@@ -105,6 +180,7 @@ pub async fn do_create_index(
     operation: CreateFieldIndex,
     shard_selection: Option<ShardId>,
     wait: bool,
+    ordering: WriteOrdering,
 ) -> Result<UpdateResult, StorageError> {
     let collection_operation = CollectionUpdateOperations::FieldIndexOperation(
         FieldIndexOperations::CreateIndex(CreateIndex {
@@ -112,8 +188,14 @@ pub async fn do_create_index(
             field_schema: operation.field_schema,
         }),
     );
-    toc.update(collection_name, collection_operation, shard_selection, wait)
-        .await
+    toc.update(
+        collection_name,
+        collection_operation,
+        shard_selection,
+        wait,
+        ordering,
+    )
+    .await
 }
 
 pub async fn do_delete_index(
@@ -122,12 +204,19 @@ pub async fn do_delete_index(
     index_name: String,
     shard_selection: Option<ShardId>,
     wait: bool,
+    ordering: WriteOrdering,
 ) -> Result<UpdateResult, StorageError> {
     let collection_operation = CollectionUpdateOperations::FieldIndexOperation(
         FieldIndexOperations::DeleteIndex(index_name),
     );
-    toc.update(collection_name, collection_operation, shard_selection, wait)
-        .await
+    toc.update(
+        collection_name,
+        collection_operation,
+        shard_selection,
+        wait,
+        ordering,
+    )
+    .await
 }
 
 pub async fn do_search_points(
@@ -135,7 +224,7 @@ pub async fn do_search_points(
     collection_name: &str,
     request: SearchRequest,
     shard_selection: Option<ShardId>,
-) -> Result<Vec<ScoredPoint>, StorageError> {
+) -> Result<(Vec<ScoredPoint>, bool, SearchExplanation), StorageError> {
     toc.search(collection_name, request, shard_selection).await
 }
 
@@ -144,11 +233,18 @@ pub async fn do_search_batch_points(
     collection_name: &str,
     request: SearchRequestBatch,
     shard_selection: Option<ShardId>,
-) -> Result<Vec<Vec<ScoredPoint>>, StorageError> {
+) -> Result<BatchSearchResult, StorageError> {
     toc.search_batch(collection_name, request, shard_selection)
         .await
 }
 
+pub async fn do_federated_search(
+    toc: &TableOfContent,
+    request: FederatedSearchRequest,
+) -> Result<(Vec<FederatedScoredPoint>, bool, SearchExplanation), StorageError> {
+    toc.federated_search(request).await
+}
+
 pub async fn do_count_points(
     toc: &TableOfContent,
     collection_name: &str,
@@ -158,6 +254,108 @@ pub async fn do_count_points(
     toc.count(collection_name, request, shard_selection).await
 }
 
+pub async fn do_facet_points(
+    toc: &TableOfContent,
+    collection_name: &str,
+    request: FacetRequest,
+    shard_selection: Option<ShardId>,
+) -> Result<FacetResponse, StorageError> {
+    toc.facet(collection_name, request, shard_selection).await
+}
+
+/// Stream of the changes of the points in the shard of the collection, starting from the
+/// operation `from`. Stream doesn't end, the WAL is re-read when all changes are sent.
+/// Stream stops after the first error.
+///
+/// Collection and shard are checked before the stream is returned, so errors of the request
+/// are not delayed until the first read.
+pub async fn do_stream_points_changes(
+    toc: Arc<TableOfContent>,
+    collection_name: String,
+    shard_id: Option<ShardId>,
+    from: SeqNumberType,
+    limit: Option<usize>,
+) -> Result<impl Stream<Item = Result<PointsChange, StorageError>>, StorageError> {
+    let limit = limit.unwrap_or(DEFAULT_CHANGES_LIMIT);
+    toc.read_points_changes(&collection_name, shard_id, from, 0)
+        .await?;
+
+    let stream = futures::stream::unfold(Some((from, VecDeque::new())), move |state| {
+        let toc = toc.clone();
+        let collection_name = collection_name.clone();
+        async move {
+            let (mut from, mut pending) = state?;
+            loop {
+                if let Some(change) = pending.pop_front() {
+                    return Some((Ok(change), Some((from, pending))));
+                }
+                let changes = match toc
+                    .read_points_changes(&collection_name, shard_id, from, limit)
+                    .await
+                {
+                    Ok(changes) => changes,
+                    Err(err) => return Some((Err(err), None)),
+                };
+                if changes.next_op_num == from {
+                    // All changes are already sent, wait for new operations
+                    tokio::time::sleep(CHANGES_POLL_INTERVAL).await;
+                }
+                from = changes.next_op_num;
+                pending.extend(changes.changes);
+            }
+        }
+    });
+    Ok(stream)
+}
+
+/// Stream of the points of the collection, encoded as JSONL, one batch per item.
+/// Points are read page by page in the order of ids, so the export doesn't hold the collection
+/// between the batches. Stream stops after the first error.
+pub async fn do_export_points(
+    toc: Arc<TableOfContent>,
+    collection_name: String,
+    request: StreamExport,
+) -> Result<impl Stream<Item = Result<Vec<u8>, StorageError>>, StorageError> {
+    let StreamExport {
+        filter,
+        with_vector,
+        batch_size,
+    } = request;
+    let batch_size = batch_size.unwrap_or(DEFAULT_STREAM_EXPORT_BATCH_SIZE);
+    if batch_size == 0 {
+        return Err(StorageError::BadRequest {
+            description: "Batch size cannot be 0".to_string(),
+        });
+    }
+    toc.get_collection(&collection_name).await?;
+
+    // `None` - all points are exported, `Some(None)` - export starts from the first point
+    let stream = futures::stream::unfold(Some(None), move |offset| {
+        let toc = toc.clone();
+        let collection_name = collection_name.clone();
+        let filter = filter.clone();
+        async move {
+            let request = ScrollRequest {
+                offset: offset?,
+                limit: Some(batch_size),
+                filter,
+                with_payload: Some(WithPayloadInterface::Bool(true)),
+                with_vector: WithVector::Bool(with_vector.unwrap_or(true)),
+                ..Default::default()
+            };
+            let page = match toc.scroll(&collection_name, request, None).await {
+                Ok(page) => page,
+                Err(err) => return Some((Err(err), None)),
+            };
+            match ExportFormat::Jsonl.encode(&page.points) {
+                Ok(bytes) => Some((Ok(bytes), page.next_page_offset.map(Some))),
+                Err(err) => Some((Err(err.into()), None)),
+            }
+        }
+    });
+    Ok(stream)
+}
+
 pub async fn do_get_points(
     toc: &TableOfContent,
     collection_name: &str,
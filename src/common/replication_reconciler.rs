@@ -0,0 +1,50 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use storage::content_manager::consensus_state::ConsensusStateRef;
+use storage::content_manager::toc::TableOfContent;
+
+use crate::settings::ReplicationReconcilerConfig;
+
+/// Periodically re-replicates under-replicated shards, e.g. after peers join or recover.
+/// Every peer runs the reconciler, but only the consensus leader proposes transfers,
+/// so the same shard is not replicated by several peers at once.
+pub struct ReplicationReconciler {
+    config: ReplicationReconcilerConfig,
+    toc: Arc<TableOfContent>,
+    consensus_state: ConsensusStateRef,
+}
+
+impl ReplicationReconciler {
+    pub fn new(
+        config: ReplicationReconcilerConfig,
+        toc: Arc<TableOfContent>,
+        consensus_state: ConsensusStateRef,
+    ) -> Self {
+        Self {
+            config,
+            toc,
+            consensus_state,
+        }
+    }
+
+    /// Reconcile replication until the process is stopped. Failed rounds are logged and skipped.
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.interval_sec));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            if !self.consensus_state.is_leader() {
+                continue;
+            }
+            let live_peers = self.consensus_state.reachable_peers();
+            match self.toc.reconcile_replication(&live_peers).await {
+                Ok(0) => {}
+                Ok(proposed) => {
+                    log::debug!("Proposed {proposed} transfers to restore replication factor")
+                }
+                Err(err) => log::warn!("Failed to reconcile replication of shards: {err}"),
+            }
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use collection::operations::point_ops::PointInsertOperations;
+use storage::content_manager::errors::StorageError;
+
+use crate::settings::ServiceConfig;
+
+/// Limits of a single API request, which protect the service from running out of memory
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RequestLimits {
+    /// Max size of the REST request body or the gRPC message in bytes
+    pub max_request_size: usize,
+    /// Max number of points in a single upsert
+    pub max_upsert_points: Option<usize>,
+    /// Max number of requests in a single batch
+    pub max_batch_size: Option<usize>,
+}
+
+impl RequestLimits {
+    pub fn new(service_config: &ServiceConfig) -> Self {
+        Self {
+            max_request_size: service_config.max_request_size_mb * 1024 * 1024,
+            max_upsert_points: service_config.max_upsert_points,
+            max_batch_size: service_config.max_batch_size,
+        }
+    }
+
+    pub fn check_upsert_points(&self, points_count: usize) -> Result<(), StorageError> {
+        match self.max_upsert_points {
+            Some(max_upsert_points) if points_count > max_upsert_points => {
+                Err(StorageError::PayloadTooLarge {
+                    description: format!(
+                        "Upsert of {points_count} points exceeds the limit of {max_upsert_points} \
+                         points per request, split it into smaller ones"
+                    ),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn check_upsert_operation(
+        &self,
+        operation: &PointInsertOperations,
+    ) -> Result<(), StorageError> {
+        let points_count = match operation {
+            PointInsertOperations::PointsBatch(batch) => batch.ids.len(),
+            PointInsertOperations::PointsList(points) => points.len(),
+        };
+        self.check_upsert_points(points_count)
+    }
+
+    pub fn check_batch_size(&self, batch_size: usize) -> Result<(), StorageError> {
+        match self.max_batch_size {
+            Some(max_batch_size) if batch_size > max_batch_size => {
+                Err(StorageError::PayloadTooLarge {
+                    description: format!(
+                        "Batch of {batch_size} requests exceeds the limit of {max_batch_size} \
+                         requests per batch, split it into smaller ones"
+                    ),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_limits() {
+        let limits = RequestLimits {
+            max_request_size: 1024,
+            max_upsert_points: Some(2),
+            max_batch_size: Some(3),
+        };
+        assert!(limits.check_upsert_points(2).is_ok());
+        assert!(matches!(
+            limits.check_upsert_points(3),
+            Err(StorageError::PayloadTooLarge { .. })
+        ));
+        assert!(limits.check_batch_size(3).is_ok());
+        assert!(limits.check_batch_size(4).is_err());
+
+        // Not limited by default
+        let limits = RequestLimits::default();
+        assert!(limits.check_upsert_points(usize::MAX).is_ok());
+        assert!(limits.check_batch_size(usize::MAX).is_ok());
+    }
+}
@@ -0,0 +1,49 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use storage::content_manager::toc::TableOfContent;
+
+use crate::settings::ScrubConfig;
+
+/// Periodically verifies data of all local shards against the stored checksums,
+/// so silent corruption of the disk is detected before the data is read by searches
+pub struct Scrubber {
+    config: ScrubConfig,
+    toc: Arc<TableOfContent>,
+}
+
+impl Scrubber {
+    pub fn new(config: ScrubConfig, toc: Arc<TableOfContent>) -> Self {
+        Self { config, toc }
+    }
+
+    /// Scrub collections until the process is stopped. Corrupted segments are logged as errors.
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.interval_sec));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        // The first tick completes immediately, do not read all data right after the start
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            for collection_name in self.toc.all_collections().await {
+                match self.toc.verify_collection(&collection_name).await {
+                    Ok(report) => {
+                        for corrupted in &report.corrupted_segments {
+                            log::error!(
+                                "Segment {} of shard {} of collection {collection_name} is corrupted: {}",
+                                corrupted.segment,
+                                corrupted.shard_id,
+                                corrupted.error
+                            );
+                        }
+                        log::debug!(
+                            "Scrubbed {} segments of collection {collection_name}",
+                            report.segments_checked
+                        );
+                    }
+                    Err(err) => log::warn!("Failed to scrub collection {collection_name}: {err}"),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use std::io::Error;
+use std::sync::Arc;
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use tokio::sync::watch;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Signal to gracefully stop the service: stop accepting new requests and finish in-flight ones.
+/// Could be awaited from any runtime.
+#[derive(Clone)]
+pub struct ShutdownSignal {
+    sender: Arc<watch::Sender<bool>>,
+}
+
+impl Default for ShutdownSignal {
+    fn default() -> Self {
+        let (sender, _receiver) = watch::channel(false);
+        Self {
+            sender: Arc::new(sender),
+        }
+    }
+}
+
+impl ShutdownSignal {
+    pub fn trigger(&self) {
+        self.sender.send_replace(true);
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        *self.sender.borrow()
+    }
+
+    /// Resolves, once the signal is triggered
+    pub async fn wait(&self) {
+        let mut receiver = self.sender.subscribe();
+        while !*receiver.borrow_and_update() {
+            // Sender can't be dropped, while `self` holds it
+            if receiver.changed().await.is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Trigger the signal on SIGTERM or SIGINT
+    pub async fn listen_os_signals(self) {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+
+            let mut sigterm = signal(SignalKind::terminate()).expect("Can't listen for SIGTERM");
+            tokio::select! {
+                _ = sigterm.recv() => log::info!("Received SIGTERM"),
+                _ = tokio::signal::ctrl_c() => log::info!("Received SIGINT"),
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            tokio::signal::ctrl_c()
+                .await
+                .expect("Can't listen for Ctrl-C");
+            log::info!("Received Ctrl-C");
+        }
+        self.trigger();
+    }
+
+    /// Block the current thread, till the signal is triggered or `is_stopped` returns `true`,
+    /// e.g. if a server failed to start
+    pub fn wait_blocking(&self, is_stopped: impl Fn() -> bool) {
+        while !self.is_triggered() && !is_stopped() {
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+/// Join the `threads`, which are finished before the `deadline`.
+/// Returns the first error of the joined threads, threads still running are left detached.
+pub fn join_until(
+    threads: Vec<JoinHandle<Result<(), Error>>>,
+    deadline: Instant,
+) -> Result<(), Error> {
+    let mut result = Ok(());
+    for handle in threads {
+        while !handle.is_finished() && Instant::now() < deadline {
+            thread::sleep(POLL_INTERVAL);
+        }
+        if !handle.is_finished() {
+            let name = handle.thread().name().unwrap_or_default().to_string();
+            log::warn!("Thread {name} is not stopped in time, in-flight requests are dropped");
+            continue;
+        }
+        let thread_result = handle.join().expect("Couldn't join on the thread");
+        if result.is_ok() {
+            result = thread_result;
+        }
+    }
+    result
+}
+
+/// Block the current thread, till the `condition` is met or the `deadline` is reached.
+/// Returns `false` on timeout
+pub fn wait_until(condition: impl Fn() -> bool, deadline: Instant) -> bool {
+    while !condition() {
+        if Instant::now() >= deadline {
+            return false;
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shutdown_signal() {
+        let signal = ShutdownSignal::default();
+        assert!(!signal.is_triggered());
+
+        let waiter = {
+            let signal = signal.clone();
+            thread::spawn(move || {
+                tokio::runtime::Builder::new_current_thread()
+                    .build()
+                    .unwrap()
+                    .block_on(signal.wait())
+            })
+        };
+        signal.trigger();
+        waiter.join().unwrap();
+        assert!(signal.is_triggered());
+
+        let deadline = Instant::now() + Duration::from_millis(200);
+        let finished = thread::spawn(|| Ok(()));
+        let running = thread::spawn(|| {
+            thread::sleep(Duration::from_secs(1));
+            Ok(())
+        });
+        assert!(join_until(vec![finished, running], deadline).is_ok());
+        assert!(!wait_until(|| false, deadline));
+    }
+}
@@ -26,11 +26,11 @@ pub struct TelemetryCollector {
 // Whole telemetry data
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
 pub struct TelemetryData {
-    id: String,
-    app: AppBuildTelemetry,
-    collections: CollectionsTelemetry,
-    cluster: ClusterTelemetry,
-    requests: RequestsTelemetry,
+    pub id: String,
+    pub app: AppBuildTelemetry,
+    pub collections: CollectionsTelemetry,
+    pub cluster: ClusterTelemetry,
+    pub requests: RequestsTelemetry,
 }
 
 impl Anonymize for TelemetryData {
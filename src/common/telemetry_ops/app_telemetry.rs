@@ -24,7 +24,7 @@ pub struct RunningEnvironmentTelemetry {
 
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
 pub struct AppBuildTelemetry {
-    version: String,
+    pub version: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
     pub features: Option<AppFeaturesTelemetry>,
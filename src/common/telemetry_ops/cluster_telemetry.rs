@@ -2,7 +2,7 @@ use schemars::JsonSchema;
 use segment::common::anonymize::Anonymize;
 use serde::{Deserialize, Serialize};
 use storage::dispatcher::Dispatcher;
-use storage::types::{ClusterStatus, StateRole};
+use storage::types::{ClusterStatus, ConsensusWalInfo, StateRole};
 
 use crate::settings::Settings;
 
@@ -16,6 +16,8 @@ pub struct ConsensusConfigTelemetry {
     max_message_queue_size: usize,
     tick_period_ms: u64,
     bootstrap_timeout_sec: u64,
+    compact_wal_entries: Option<u64>,
+    compact_wal_size_mb: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
@@ -36,6 +38,8 @@ impl From<&Settings> for ClusterConfigTelemetry {
                 max_message_queue_size: settings.cluster.consensus.max_message_queue_size,
                 tick_period_ms: settings.cluster.consensus.tick_period_ms,
                 bootstrap_timeout_sec: settings.cluster.consensus.bootstrap_timeout_sec,
+                compact_wal_entries: settings.cluster.consensus.compact_wal_entries,
+                compact_wal_size_mb: settings.cluster.consensus.compact_wal_size_mb,
             },
         }
     }
@@ -49,6 +53,8 @@ pub struct ClusterStatusTelemetry {
     pub pending_operations: usize,
     pub role: Option<StateRole>,
     pub is_voter: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub consensus_wal: Option<ConsensusWalInfo>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
@@ -72,6 +78,9 @@ impl ClusterTelemetry {
                     pending_operations: cluster_info.raft_info.pending_operations,
                     role: cluster_info.raft_info.role,
                     is_voter: cluster_info.raft_info.is_voter,
+                    consensus_wal: dispatcher
+                        .consensus_wal_info()
+                        .and_then(|wal_info| wal_info.ok()),
                 }),
             }
         } else {
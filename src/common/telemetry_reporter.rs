@@ -0,0 +1,100 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use hyper::client::HttpConnector;
+use hyper::{Body, Client, Request};
+use serde::Serialize;
+use tokio::sync::Mutex;
+
+use crate::common::telemetry::{TelemetryCollector, TelemetryData};
+use crate::common::telemetry_ops::collections_telemetry::CollectionTelemetryEnum;
+use crate::settings::TelemetryReportingConfig;
+
+/// Telemetry level, which includes aggregated per-collection data, but no configs
+const REPORT_TELEMETRY_LEVEL: usize = 1;
+
+/// Anonymized aggregate usage statistics of a single instance.
+/// Contains no names, ids or payloads of collections and points.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct TelemetryReport {
+    /// Random id, generated on each start of the process
+    pub id: String,
+    pub version: String,
+    pub collections: usize,
+    pub vectors: usize,
+}
+
+impl From<&TelemetryData> for TelemetryReport {
+    fn from(telemetry: &TelemetryData) -> Self {
+        let vectors = telemetry
+            .collections
+            .collections
+            .iter()
+            .flatten()
+            .map(|collection| match collection {
+                CollectionTelemetryEnum::Aggregated(aggregated) => aggregated.vectors,
+                CollectionTelemetryEnum::Full(_) => 0,
+            })
+            .sum();
+        TelemetryReport {
+            id: telemetry.id.clone(),
+            version: telemetry.app.version.clone(),
+            collections: telemetry.collections.number_of_collections,
+            vectors,
+        }
+    }
+}
+
+/// Periodically sends anonymized usage statistics to the configured endpoint
+pub struct TelemetryReporter {
+    config: TelemetryReportingConfig,
+    telemetry_collector: Arc<Mutex<TelemetryCollector>>,
+    client: Client<HttpConnector>,
+}
+
+impl TelemetryReporter {
+    pub fn new(
+        config: TelemetryReportingConfig,
+        telemetry_collector: Arc<Mutex<TelemetryCollector>>,
+    ) -> Self {
+        Self {
+            config,
+            telemetry_collector,
+            client: Client::new(),
+        }
+    }
+
+    /// Send reports until the process is stopped. Failed reports are logged and skipped.
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.interval_sec));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            if let Err(err) = self.report().await {
+                log::debug!("Failed to report telemetry to {}: {}", self.config.url, err);
+            }
+        }
+    }
+
+    async fn report(&self) -> anyhow::Result<()> {
+        let telemetry = self
+            .telemetry_collector
+            .lock()
+            .await
+            .prepare_data(REPORT_TELEMETRY_LEVEL)
+            .await;
+        let report = TelemetryReport::from(&telemetry);
+
+        let request = Request::post(&self.config.url)
+            .header(hyper::header::CONTENT_TYPE, "application/json")
+            .body(Body::from(serde_json::to_vec(&report)?))?;
+
+        let timeout = Duration::from_secs(self.config.timeout_sec);
+        let response = tokio::time::timeout(timeout, self.client.request(request)).await??;
+        let status = response.status();
+        if !status.is_success() {
+            anyhow::bail!("{status}");
+        }
+        Ok(())
+    }
+}
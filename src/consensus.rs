@@ -16,7 +16,9 @@ use collection::shards::shard::PeerId;
 use raft::eraftpb::Message as RaftMessage;
 use raft::prelude::*;
 use raft::{SoftState, StateRole};
-use storage::content_manager::consensus_ops::ConsensusOperations;
+use storage::content_manager::consensus_ops::{
+    AddPeerContext, ConsensusOperations, ConsensusProposal, EntryContext,
+};
 use storage::content_manager::consensus_state::ConsensusStateRef;
 use storage::content_manager::errors::StorageError;
 use storage::content_manager::toc::TableOfContent;
@@ -31,7 +33,7 @@ use crate::tonic::init_internal;
 type Node = RawNode<ConsensusStateRef>;
 
 pub enum Message {
-    FromClient(ConsensusOperations),
+    FromClient(ConsensusProposal),
     FromPeer(Box<RaftMessage>),
 }
 
@@ -51,19 +53,29 @@ impl Consensus {
         logger: &slog::Logger,
         state_ref: ConsensusStateRef,
         bootstrap_peer: Option<Uri>,
+        learner: bool,
         uri: Option<String>,
-        p2p_host: String,
+        p2p_hosts: Vec<String>,
         p2p_port: u16,
         config: ConsensusConfig,
         channel_service: ChannelService,
-        propose_receiver: mpsc::Receiver<ConsensusOperations>,
+        propose_receiver: mpsc::Receiver<ConsensusProposal>,
         telemetry_collector: Arc<parking_lot::Mutex<TonicTelemetryCollector>>,
         toc: Arc<TableOfContent>,
     ) -> anyhow::Result<JoinHandle<std::io::Result<()>>> {
+        // Internal server accepts messages encrypted with the same key, as the outgoing ones
+        let p2p_cipher = channel_service.channel_pool.cipher();
+        // ... and verifies the same secret, as the one attached to the outgoing requests
+        let p2p_secret = channel_service
+            .channel_pool
+            .credentials()
+            .and_then(|credentials| credentials.secret())
+            .cloned();
         let (mut consensus, message_sender) = Self::new(
             logger,
             state_ref.clone(),
             bootstrap_peer,
+            learner,
             uri,
             p2p_port,
             config,
@@ -105,9 +117,11 @@ impl Consensus {
                     toc,
                     state_ref,
                     telemetry_collector,
-                    p2p_host,
+                    p2p_hosts,
                     p2p_port,
                     message_sender,
+                    p2p_cipher,
+                    p2p_secret,
                 )
             })
             .unwrap();
@@ -115,12 +129,14 @@ impl Consensus {
         Ok(handle)
     }
 
-    /// If `bootstrap_peer` peer is supplied, then either `uri` or `p2p_port` should be also supplied
+    /// If `bootstrap_peer` peer is supplied, then either `uri` or `p2p_port` should be also supplied.
+    /// If `learner` - the bootstrapping peer joins as a learner, which is never promoted to a voter.
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         logger: &slog::Logger,
         state_ref: ConsensusStateRef,
         bootstrap_peer: Option<Uri>,
+        learner: bool,
         uri: Option<String>,
         p2p_port: u16,
         config: ConsensusConfig,
@@ -160,20 +176,30 @@ impl Consensus {
             Self::init(
                 &state_ref,
                 bootstrap_peer.clone(),
+                learner,
                 uri,
                 p2p_port,
                 &config,
+                &channel_service.channel_pool,
                 &runtime,
                 leader_established_in_ms,
             )
             .context("Failed to initialize Consensus for new Raft state")?;
         } else {
             runtime
-                .block_on(Self::recover(&state_ref, uri.clone(), p2p_port, &config))
+                .block_on(Self::recover(
+                    &state_ref,
+                    uri.clone(),
+                    p2p_port,
+                    &config,
+                    &channel_service.channel_pool,
+                ))
                 .context("Failed to recover Consensus from existing Raft state")?;
 
-            if bootstrap_peer.is_some() || uri.is_some() {
-                log::debug!("Local raft state found - bootstrap and uri cli arguments were ignored")
+            if bootstrap_peer.is_some() || uri.is_some() || learner {
+                log::debug!(
+                    "Local raft state found - bootstrap, uri and learner cli arguments were ignored"
+                )
             }
             log::debug!("Local raft state found - skipping initialization");
         };
@@ -198,9 +224,11 @@ impl Consensus {
     fn init(
         state_ref: &ConsensusStateRef,
         bootstrap_peer: Option<Uri>,
+        learner: bool,
         uri: Option<String>,
         p2p_port: u16,
         config: &ConsensusConfig,
+        channel_pool: &TransportChannelPool,
         runtime: &Runtime,
         leader_established_in_ms: u64,
     ) -> anyhow::Result<()> {
@@ -209,12 +237,17 @@ impl Consensus {
             runtime.block_on(Self::bootstrap(
                 state_ref,
                 bootstrap_peer,
+                learner,
                 uri,
                 p2p_port,
                 config,
+                channel_pool,
             ))?;
             Ok(())
         } else {
+            if learner {
+                anyhow::bail!("The first peer of the network can't be a learner");
+            }
             log::debug!(
                 "Bootstrapping is disabled. Assuming this peer is the first in the network"
             );
@@ -230,21 +263,25 @@ impl Consensus {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn add_peer_to_known_for(
         this_peer_id: PeerId,
         cluster_uri: Uri,
         current_uri: Option<String>,
         p2p_port: u16,
+        learner: bool,
         config: &ConsensusConfig,
+        channel_pool: &TransportChannelPool,
     ) -> anyhow::Result<AllPeers> {
         // Use dedicated transport channel for bootstrapping because of specific timeout
-        let channel = TransportChannelPool::make_channel(
-            Duration::from_secs(config.bootstrap_timeout_sec),
-            Duration::from_secs(config.bootstrap_timeout_sec),
-            cluster_uri,
-        )
-        .await
-        .context("Failed to create timeout channel")?;
+        let channel = channel_pool
+            .make_peer_channel(
+                Duration::from_secs(config.bootstrap_timeout_sec),
+                Duration::from_secs(config.bootstrap_timeout_sec),
+                cluster_uri,
+            )
+            .await
+            .context("Failed to create timeout channel")?;
         let mut client = RaftClient::new(channel);
         let all_peers = client
             .add_peer_to_known(tonic::Request::new(
@@ -252,6 +289,7 @@ impl Consensus {
                     uri: current_uri,
                     port: Some(p2p_port as u32),
                     id: this_peer_id,
+                    learner,
                 },
             ))
             .await
@@ -267,6 +305,7 @@ impl Consensus {
         uri: Option<String>,
         p2p_port: u16,
         config: &ConsensusConfig,
+        channel_pool: &TransportChannelPool,
     ) -> anyhow::Result<()> {
         let this_peer_id = state_ref.this_peer_id();
         let mut peer_to_uri = state_ref
@@ -276,6 +315,7 @@ impl Consensus {
             .read()
             .clone();
         let this_peer_url = peer_to_uri.remove(&this_peer_id);
+        let learner = state_ref.is_permanent_learner(this_peer_id);
         // Recover url if a different one is provided
         let do_recover = match (&this_peer_url, &uri) {
             (Some(this_peer_url), Some(uri)) => this_peer_url != &Uri::from_str(uri)?,
@@ -289,7 +329,9 @@ impl Consensus {
                     peer_uri.clone(),
                     uri.clone(),
                     p2p_port,
+                    learner,
                     config,
+                    channel_pool,
                 )
                 .await;
                 if res.is_err() {
@@ -311,9 +353,11 @@ impl Consensus {
     async fn bootstrap(
         state_ref: &ConsensusStateRef,
         bootstrap_peer: Uri,
+        learner: bool,
         uri: Option<String>,
         p2p_port: u16,
         config: &ConsensusConfig,
+        channel_pool: &TransportChannelPool,
     ) -> anyhow::Result<()> {
         let this_peer_id = state_ref.this_peer_id();
         let all_peers = Self::add_peer_to_known_for(
@@ -321,7 +365,9 @@ impl Consensus {
             bootstrap_peer,
             uri.clone(),
             p2p_port,
+            learner,
             config,
+            channel_pool,
         )
         .await?;
 
@@ -368,6 +414,17 @@ impl Consensus {
                 if stop_consensus {
                     return Ok(());
                 }
+                if let Err(err) = store.compact_wal(
+                    self.config.compact_wal_entries,
+                    self.config
+                        .compact_wal_size_mb
+                        .map(|size_mb| size_mb * 1024 * 1024),
+                ) {
+                    log::error!("Failed to compact consensus WAL: {err}");
+                }
+                if store.is_leadership_transfer_requested() {
+                    self.try_transfer_leadership();
+                }
             } else {
                 timeout -= d;
             }
@@ -396,7 +453,10 @@ impl Consensus {
                     log::warn!("Failed to step message: {:?}", error);
                 }
             }
-            Ok(Message::FromClient(operation)) => {
+            Ok(Message::FromClient(ConsensusProposal {
+                operation,
+                identity,
+            })) => {
                 let result = match operation {
                     ConsensusOperations::RemovePeer(peer_id) => {
                         let mut change = ConfChangeV2::default();
@@ -408,13 +468,15 @@ impl Consensus {
                         self.node.propose_conf_change(vec![], change)
                     }
                     ConsensusOperations::AddPeer { peer_id, uri } => {
-                        let mut change = ConfChangeV2::default();
-                        change.set_changes(vec![raft_proto::new_conf_change_single(
-                            peer_id,
-                            ConfChangeType::AddLearnerNode,
-                        )]);
-                        log::debug!("Proposing network configuration change: {:?}", change);
-                        self.node.propose_conf_change(uri.into_bytes(), change)
+                        let context = AddPeerContext {
+                            uri,
+                            learner: false,
+                        };
+                        self.propose_add_learner(peer_id, context)
+                    }
+                    ConsensusOperations::AddLearner { peer_id, uri } => {
+                        let context = AddPeerContext { uri, learner: true };
+                        self.propose_add_learner(peer_id, context)
                     }
                     _ => {
                         let message = match serde_cbor::to_vec(&operation) {
@@ -425,7 +487,11 @@ impl Consensus {
                             }
                         };
                         log::trace!("Proposing entry from client with length: {}", message.len());
-                        self.node.propose(vec![], message)
+                        let context = EntryContext {
+                            proposer: self.node.raft.id,
+                            identity,
+                        };
+                        self.node.propose(context.encode(), message)
                     }
                 };
 
@@ -447,6 +513,22 @@ impl Consensus {
         Ok(())
     }
 
+    /// Every peer joins as a learner. Unless it is a permanent one, it is promoted to a voter,
+    /// once it catches up with the log.
+    fn propose_add_learner(
+        &mut self,
+        peer_id: PeerId,
+        context: AddPeerContext,
+    ) -> raft::Result<()> {
+        let mut change = ConfChangeV2::default();
+        change.set_changes(vec![raft_proto::new_conf_change_single(
+            peer_id,
+            ConfChangeType::AddLearnerNode,
+        )]);
+        log::debug!("Proposing network configuration change: {:?}", change);
+        self.node.propose_conf_change(context.encode(), change)
+    }
+
     /// Returns `true` if learner promotion was proposed, `false` otherwise.
     fn try_promote_learner(&mut self) -> anyhow::Result<bool> {
         let learner = if let Some(learner) = self.find_learner_to_promote() {
@@ -476,14 +558,40 @@ impl Consensus {
         Ok(true)
     }
 
+    /// Hand over the leadership to the most up-to-date voter, if this peer is the leader
+    fn try_transfer_leadership(&mut self) {
+        if self.node.raft.lead_transferee.is_some() {
+            // Transfer is already in progress
+            return;
+        }
+        let status = self.node.status();
+        if status.ss.raft_state != StateRole::Leader {
+            return;
+        }
+        let voters: HashSet<_> = self.node.store().conf_state().voters.into_iter().collect();
+        let transferee = status.progress.and_then(|progress| {
+            progress
+                .iter()
+                .filter(|(id, _)| **id != status.id && voters.contains(id))
+                .max_by_key(|(_, progress)| progress.matched)
+                .map(|(id, _)| *id)
+        });
+        if let Some(transferee) = transferee {
+            log::info!("Transferring leadership to peer {transferee}");
+            self.node.transfer_leader(transferee);
+        }
+    }
+
     fn find_learner_to_promote(&self) -> Option<u64> {
         let commit = self.node.store().hard_state().commit;
+        let permanent_learners = self.store().permanent_learners();
         let learners: HashSet<_> = self
             .node
             .store()
             .conf_state()
             .learners
             .into_iter()
+            .filter(|learner| !permanent_learners.contains(learner))
             .collect();
         let status = self.node.status();
         status
@@ -658,6 +766,7 @@ impl Consensus {
                         message.to,
                         bootstrap_uri.clone(),
                         consensus_config_arc.clone(),
+                        &pool,
                     )
                     .await
                     {
@@ -702,16 +811,17 @@ async fn who_is(
     peer_id: collection::shards::shard::PeerId,
     bootstrap_uri: Option<Uri>,
     config: Arc<ConsensusConfig>,
+    channel_pool: &TransportChannelPool,
 ) -> anyhow::Result<Uri> {
     log::debug!("Resolving who is {peer_id}");
     let bootstrap_uri =
         bootstrap_uri.ok_or_else(|| anyhow::anyhow!("No bootstrap uri supplied"))?;
     let bootstrap_timeout = Duration::from_secs(config.bootstrap_timeout_sec);
     // Use dedicated transport channel for who_is because of specific timeout
-    let channel =
-        TransportChannelPool::make_channel(bootstrap_timeout, bootstrap_timeout, bootstrap_uri)
-            .await
-            .context("Failed to create timeout channel")?;
+    let channel = channel_pool
+        .make_peer_channel(bootstrap_timeout, bootstrap_timeout, bootstrap_uri)
+        .await
+        .context("Failed to create timeout channel")?;
     let mut client = RaftClient::new(channel);
     Ok(client
         .who_is(tonic::Request::new(GrpcPeerId { id: peer_id }))
@@ -735,7 +845,11 @@ async fn send_message(
 
     if let Err(err) = transport_channel_pool
         .with_channel(&address, |channel| async move {
-            let mut client = RaftClient::new(channel);
+            let compression = channel.send_gzip();
+            let mut client = RaftClient::new(channel).accept_gzip();
+            if compression {
+                client = client.send_gzip();
+            }
             client.send(tonic::Request::new(message.clone())).await
         })
         .await
@@ -797,7 +911,9 @@ mod tests {
             toc_arc.clone(),
             operation_sender,
             storage_path,
+            toc_arc.state_changes().clone(),
         )
+        .unwrap()
         .into();
         let dispatcher = Dispatcher::new(toc_arc.clone()).with_consensus(consensus_state.clone());
         let slog_logger = slog::Logger::root(slog_stdlog::StdLog.fuse(), slog::o!());
@@ -805,6 +921,7 @@ mod tests {
             &slog_logger,
             consensus_state.clone(),
             None,
+            false,
             Some("http://127.0.0.1:6335".parse().unwrap()),
             6335,
             ConsensusConfig::default(),
@@ -845,6 +962,11 @@ mod tests {
                             vectors: VectorParams {
                                 size: NonZeroU64::new(10).unwrap(),
                                 distance: Distance::Cosine,
+                                hnsw_config: None,
+                                quantization_config: None,
+                                on_disk: None,
+                                datatype: None,
+                                multivector_config: None,
                             }
                             .into(),
                             hnsw_config: None,
@@ -854,9 +976,15 @@ mod tests {
                             on_disk_payload: None,
                             replication_factor: None,
                             write_consistency_factor: None,
+                            init_from: None,
+                            payload_field_types: None,
+                            auto_payload_index: None,
+                            ttl: None,
+                            default_vector: None,
                         },
                     )),
                     None,
+                    None,
                 ),
             )
             .unwrap();
@@ -10,16 +10,20 @@ mod settings;
 mod snapshots;
 mod startup;
 mod tonic;
+mod upgrade_check;
+mod wal_tools;
 
 use std::io::Error;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::thread;
 use std::thread::JoinHandle;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use ::tonic::transport::Uri;
+use api::grpc::peer_channel::PeerCredentials;
 use api::grpc::transport_channel_pool::TransportChannelPool;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use collection::shards::channel_service::ChannelService;
 use consensus::Consensus;
 use slog::Drain;
@@ -27,18 +31,30 @@ use startup::setup_panic_hook;
 use storage::content_manager::consensus::operation_sender::OperationSender;
 use storage::content_manager::consensus::persistent::Persistent;
 use storage::content_manager::consensus_state::{ConsensusState, ConsensusStateRef};
+use storage::content_manager::encryption::load_p2p_cipher;
 use storage::content_manager::toc::TableOfContent;
 use storage::dispatcher::Dispatcher;
 #[cfg(not(target_env = "msvc"))]
 use tikv_jemallocator::Jemalloc;
 
+use crate::common::anti_entropy::AntiEntropy;
+use crate::common::diagnostics::DiagnosticsReport;
+use crate::common::events::EventsService;
 use crate::common::helpers::create_search_runtime;
+use crate::common::metrics_exporter::MetricsExporter;
+use crate::common::replication_reconciler::ReplicationReconciler;
+use crate::common::request_limits::RequestLimits;
+use crate::common::scrubber::Scrubber;
+use crate::common::shutdown::{join_until, wait_until, ShutdownSignal};
 use crate::common::telemetry::TelemetryCollector;
+use crate::common::telemetry_reporter::TelemetryReporter;
 use crate::greeting::welcome;
 use crate::migrations::single_to_cluster::handle_existing_collections;
 use crate::settings::Settings;
 use crate::snapshots::{recover_full_snapshot, recover_snapshots};
 use crate::startup::setup_logger;
+use crate::upgrade_check::check_upgrade;
+use crate::wal_tools::{inspect_wal, truncate_wal};
 
 #[cfg(not(target_env = "msvc"))]
 #[global_allocator]
@@ -55,6 +71,12 @@ struct Args {
     /// If not specified - this peer will be considered as a first in a new deployment.
     #[arg(long, value_parser, value_name = "URI")]
     bootstrap: Option<Uri>,
+    /// Join the cluster as a learner, which follows the cluster metadata, but never votes.
+    /// Learners do not affect the quorum size and the election time, e.g. for read-only query nodes.
+    ///
+    /// Only applies on bootstrap, together with `--bootstrap`.
+    #[arg(long, action, default_value_t = false)]
+    learner: bool,
     /// Uri of this peer.
     /// Other peers should be able to reach it by this uri.
     ///
@@ -62,6 +84,8 @@ struct Args {
     ///
     /// In case this is not the first peer and it bootstraps the value is optional.
     /// If not supplied then qdrant will take internal grpc port from config and derive the IP address of this peer on bootstrap peer (receiving side)
+    ///
+    /// Takes precedence over `cluster.p2p.advertised_uri` of the config.
     #[arg(long, value_parser, value_name = "URI")]
     uri: Option<Uri>,
 
@@ -80,6 +104,44 @@ struct Args {
     /// Format: <snapshot_file_path>
     #[arg(long, value_name = "PATH")]
     storage_snapshot: Option<String>,
+
+    /// Run self-test of the environment and configuration, print the report and exit.
+    /// Checks filesystem capabilities, resource limits, memory and CPU features.
+    #[arg(long, action, default_value_t = false)]
+    diagnostics: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// Offline maintenance commands. Qdrant should not be running on the same storage.
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Print records of the shard WAL and check that all of them could be read.
+    /// Fails if any of the records is corrupted.
+    WalInspect {
+        /// Path to the shard directory, e.g. `./storage/collections/<collection_name>/0`
+        #[arg(long, value_name = "PATH")]
+        shard_path: PathBuf,
+        /// Do not print records, only report unreadable ones
+        #[arg(long, action, default_value_t = false)]
+        verify: bool,
+    },
+    /// Remove records of the shard WAL, starting from the given index till the end.
+    /// Allows to recover a shard with corrupted tail of the WAL without deleting the whole shard.
+    WalTruncate {
+        /// Path to the shard directory, e.g. `./storage/collections/<collection_name>/0`
+        #[arg(long, value_name = "PATH")]
+        shard_path: PathBuf,
+        /// Index of the first record to remove.
+        /// If not specified - the first unreadable record is used.
+        #[arg(long, value_name = "INDEX")]
+        from: Option<u64>,
+    },
+    /// Check if the storage could be loaded by this version, without modifying any data.
+    /// Reports collection and segment versions, which require migration or can't be upgraded.
+    /// Fails if anything would block the upgrade.
+    UpgradeCheck,
 }
 
 fn main() -> anyhow::Result<()> {
@@ -89,6 +151,28 @@ fn main() -> anyhow::Result<()> {
     setup_panic_hook();
     let args = Args::parse();
 
+    if args.diagnostics {
+        let report = DiagnosticsReport::collect(&settings.storage);
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        for warning in &report.warnings {
+            log::warn!("{warning}");
+        }
+        return Ok(());
+    }
+
+    match args.command {
+        None => {}
+        Some(Command::WalInspect { shard_path, verify }) => {
+            return inspect_wal(&shard_path, verify, &settings.storage.encryption);
+        }
+        Some(Command::WalTruncate { shard_path, from }) => {
+            return truncate_wal(&shard_path, from, &settings.storage.encryption);
+        }
+        Some(Command::UpgradeCheck) => {
+            return check_upgrade(&settings.storage);
+        }
+    }
+
     let restored_collections = if let Some(full_snapshot) = args.storage_snapshot {
         recover_full_snapshot(
             &full_snapshot,
@@ -114,6 +198,10 @@ fn main() -> anyhow::Result<()> {
         .expect("Can't create runtime.");
     let runtime_handle = runtime.handle().clone();
 
+    // Stops the servers gracefully on SIGTERM or SIGINT
+    let shutdown = ShutdownSignal::default();
+    runtime_handle.spawn(shutdown.clone().listen_os_signals());
+
     // Create a signal sender and receiver. It is used to communicate with the consensus thread.
     let (propose_sender, propose_receiver) = std::sync::mpsc::channel();
 
@@ -138,11 +226,22 @@ fn main() -> anyhow::Result<()> {
         // So we initialize it with real values here
         let p2p_grpc_timeout = Duration::from_millis(settings.cluster.grpc_timeout_ms);
         let connection_timeout = Duration::from_millis(settings.cluster.connection_timeout_ms);
-        channel_service.channel_pool = Arc::new(TransportChannelPool::new(
-            p2p_grpc_timeout,
-            connection_timeout,
-            settings.cluster.p2p.connection_pool_size,
-        ));
+        channel_service.channel_pool = Arc::new(
+            TransportChannelPool::new(
+                p2p_grpc_timeout,
+                connection_timeout,
+                settings.cluster.p2p.connection_pool_size,
+            )
+            .with_compression(settings.cluster.p2p.enable_compression)
+            .with_encryption(
+                load_p2p_cipher(&settings.cluster.p2p.encryption)
+                    .expect("Can't load peer-to-peer encryption key"),
+            )
+            .with_credentials(PeerCredentials::new(
+                persistent_consensus_state.this_peer_id(),
+                settings.cluster.p2p.secret.as_deref(),
+            )),
+        );
         channel_service.id_to_address = persistent_consensus_state.peer_address_by_id.clone();
     }
 
@@ -166,20 +265,25 @@ fn main() -> anyhow::Result<()> {
     let toc_arc = Arc::new(toc);
     let storage_path = toc_arc.storage_path();
 
-    // Holder for all actively running threads of the service: web, gPRC, consensus, etc.
+    // Holder for the threads of the servers, which are drained on shutdown: web and gRPC
     let mut handles: Vec<JoinHandle<Result<(), Error>>> = vec![];
+    // Internal gRPC, which serves consensus messages till the process exits, so it is not joined
+    let mut consensus_handles: Vec<JoinHandle<Result<(), Error>>> = vec![];
 
     // Router for external queries.
     // It decides if query should go directly to the ToC or through the consensus.
-    let mut dispatcher = Dispatcher::new(toc_arc.clone());
+    let mut dispatcher = Dispatcher::new(toc_arc.clone())
+        .with_api_key(settings.service.api_key.clone())
+        .with_jwt_secret(settings.service.jwt_secret.clone());
 
-    let (telemetry_collector, dispatcher_arc) = if settings.cluster.enabled {
+    let (telemetry_collector, dispatcher_arc, consensus_state) = if settings.cluster.enabled {
         let consensus_state: ConsensusStateRef = ConsensusState::new(
             persistent_consensus_state,
             toc_arc.clone(),
             propose_operation_sender.unwrap(),
             storage_path,
-        )
+            toc_arc.state_changes().clone(),
+        )?
         .into();
         let is_new_deployment = consensus_state.is_new_deployment();
 
@@ -203,8 +307,11 @@ fn main() -> anyhow::Result<()> {
             &slog_logger,
             consensus_state.clone(),
             args.bootstrap,
-            args.uri.map(|uri| uri.to_string()),
-            settings.service.host.clone(),
+            args.learner,
+            args.uri
+                .map(|uri| uri.to_string())
+                .or_else(|| settings.cluster.p2p.advertised_uri.clone()),
+            settings.p2p_bind_hosts(),
             p2p_port,
             settings.cluster.consensus.clone(),
             channel_service,
@@ -214,7 +321,7 @@ fn main() -> anyhow::Result<()> {
         )
         .expect("Can't initialize consensus");
 
-        handles.push(handle);
+        consensus_handles.push(handle);
 
         let toc_arc_clone = toc_arc.clone();
         let consensus_state_clone = consensus_state.clone();
@@ -233,6 +340,20 @@ fn main() -> anyhow::Result<()> {
             }
         });
 
+        if let Some(reconciler_config) = settings.cluster.replication_reconciler.clone() {
+            let reconciler = ReplicationReconciler::new(
+                reconciler_config,
+                toc_arc.clone(),
+                consensus_state.clone(),
+            );
+            runtime_handle.spawn(reconciler.run());
+        }
+
+        if let Some(anti_entropy_config) = settings.cluster.anti_entropy.clone() {
+            let anti_entropy = AntiEntropy::new(anti_entropy_config, toc_arc.clone());
+            runtime_handle.spawn(anti_entropy.run());
+        }
+
         let collections_to_recover_in_consensus = if is_new_deployment {
             let existing_collections = runtime_handle.block_on(toc_arc.all_collections());
             existing_collections
@@ -250,40 +371,80 @@ fn main() -> anyhow::Result<()> {
             ));
         }
 
-        (telemetry_collector, dispatcher_arc)
+        (telemetry_collector, dispatcher_arc, Some(consensus_state))
     } else {
         log::info!("Distributed mode disabled");
         let dispatcher_arc = Arc::new(dispatcher);
 
         // Monitoring and telemetry.
         let telemetry_collector = TelemetryCollector::new(settings.clone(), dispatcher_arc.clone());
-        (telemetry_collector, dispatcher_arc)
+        (telemetry_collector, dispatcher_arc, None)
     };
 
     let tonic_telemetry_collector = telemetry_collector.tonic_telemetry_collector.clone();
+    let telemetry_collector = Arc::new(tokio::sync::Mutex::new(telemetry_collector));
+
+    if let Some(metrics_export_config) = settings.metrics_export.clone() {
+        log::info!("Pushing metrics to {}", metrics_export_config.url);
+        let exporter = MetricsExporter::new(metrics_export_config, telemetry_collector.clone());
+        runtime_handle.spawn(exporter.run());
+    }
+
+    match (&settings.telemetry_reporting, settings.telemetry_disabled) {
+        (Some(_), true) => log::info!("Telemetry reporting is disabled"),
+        (Some(telemetry_reporting), false) => {
+            log::info!(
+                "Reporting anonymized usage statistics to {}",
+                telemetry_reporting.url
+            );
+            let reporter =
+                TelemetryReporter::new(telemetry_reporting.clone(), telemetry_collector.clone());
+            runtime_handle.spawn(reporter.run());
+        }
+        (None, _) => {}
+    }
+
+    if let Some(scrub_config) = settings.scrub.clone() {
+        let scrubber = Scrubber::new(scrub_config, toc_arc.clone());
+        runtime_handle.spawn(scrubber.run());
+    }
+
+    let events_service = EventsService::new();
+    runtime_handle.spawn(events_service.clone().run(dispatcher_arc.clone()));
 
     #[cfg(feature = "web")]
     {
         let dispatcher_arc = dispatcher_arc.clone();
-        let telemetry_collector = Arc::new(tokio::sync::Mutex::new(telemetry_collector));
         let settings = settings.clone();
+        let shutdown = shutdown.clone();
         let handle = thread::Builder::new()
             .name("web".to_string())
-            .spawn(move || actix::init(dispatcher_arc.clone(), telemetry_collector, settings))
+            .spawn(move || {
+                actix::init(
+                    dispatcher_arc.clone(),
+                    telemetry_collector,
+                    events_service,
+                    settings,
+                    shutdown,
+                )
+            })
             .unwrap();
         handles.push(handle);
     }
 
     if let Some(grpc_port) = settings.service.grpc_port {
         let settings = settings.clone();
+        let shutdown = shutdown.clone();
         let handle = thread::Builder::new()
             .name("grpc".to_string())
             .spawn(move || {
                 tonic::init(
                     dispatcher_arc,
                     tonic_telemetry_collector,
-                    settings.service.host,
+                    settings.service.bind_hosts(),
                     grpc_port,
+                    RequestLimits::new(&settings.service),
+                    shutdown,
                 )
             })
             .unwrap();
@@ -327,10 +488,35 @@ fn main() -> anyhow::Result<()> {
             .unwrap();
     }
 
-    for handle in handles.into_iter() {
-        handle.join().expect("Couldn't join on the thread")?;
+    // Serve till the termination signal, or till any of the servers is stopped, e.g. on error
+    shutdown.wait_blocking(|| {
+        handles
+            .iter()
+            .chain(&consensus_handles)
+            .any(|handle| handle.is_finished())
+    });
+    shutdown.trigger();
+    log::info!("Shutting down, waiting for in-flight requests");
+    let deadline = Instant::now() + Duration::from_secs(settings.service.shutdown_timeout_sec);
+
+    if let Some(consensus_state) = consensus_state {
+        consensus_state.request_leadership_transfer();
+        let is_handed_over = wait_until(
+            || consensus_state.peer_count() <= 1 || !consensus_state.is_leader(),
+            deadline,
+        );
+        if !is_handed_over {
+            log::warn!("Leadership is not transferred in time, peers will elect a new leader");
+        }
     }
+
+    // Servers stop accepting new requests on the signal and finish in-flight ones
+    let result = join_until(handles, deadline);
+
+    log::info!("Flushing collections");
+    toc_arc.stop_collections();
     drop(toc_arc);
     drop(settings);
+    result?;
     Ok(())
 }
@@ -47,6 +47,11 @@ pub async fn handle_existing_collections(
                 hnsw_config: Some(collection_state.config.hnsw_config.into()),
                 wal_config: Some(collection_state.config.wal_config.into()),
                 optimizers_config: Some(collection_state.config.optimizer_config.into()),
+                init_from: None,
+                payload_field_types: collection_state.config.payload_field_types,
+                auto_payload_index: collection_state.config.auto_payload_index,
+                ttl: collection_state.config.ttl,
+                default_vector: collection_state.config.params.default_vector,
             },
         );
 
@@ -68,6 +73,7 @@ pub async fn handle_existing_collections(
             .submit_collection_meta_op(
                 CollectionMetaOperations::CreateCollection(collection_create_operation),
                 None,
+                None,
             )
             .await;
         for (shard_id, shard_info) in collection_state.shards {
@@ -81,6 +87,7 @@ pub async fn handle_existing_collections(
                             state: ReplicaState::Active,
                         }),
                         None,
+                        None,
                     )
                     .await;
             }
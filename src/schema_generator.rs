@@ -1,22 +1,33 @@
-use api::grpc::models::CollectionsResponse;
+use api::grpc::models::{CollectionsAliasesResponse, CollectionsResponse, ShardPlacement};
+use collection::jobs::export::{CreateExport, ExportDescription, StreamExport};
+use collection::jobs::JobInfo;
+use collection::operations::changes::PointsChange;
 use collection::operations::cluster_ops::ClusterOperations;
-use collection::operations::payload_ops::{DeletePayload, SetPayload};
-use collection::operations::point_ops::{PointInsertOperations, PointsSelector};
+use collection::operations::payload_ops::{DeletePayload, SetPayload, UpdatePayload};
+use collection::operations::point_ops::{PointInsertOperations, PointsSelector, WriteOrdering};
 use collection::operations::snapshot_ops::SnapshotDescription;
 use collection::operations::types::{
-    CollectionClusterInfo, CollectionInfo, CountRequest, CountResult, PointRequest,
-    RecommendRequest, RecommendRequestBatch, Record, ScrollRequest, ScrollResult, SearchRequest,
-    SearchRequestBatch, UpdateResult,
+    CollectionClusterInfo, CollectionInfo, CollectionVerificationReport, CountRequest, CountResult,
+    DiscoverRequest, FacetRequest, FacetResponse, PointRequest, RecommendRequest,
+    RecommendRequestBatch, Record, ReplicaSyncReport, ReshardingPreview, ReshardingPreviewRequest,
+    SampleRequest, ScrollRequest, ScrollResult, SearchRequest, SearchRequestBatch, UpdateResult,
 };
 use schemars::{schema_for, JsonSchema};
-use segment::types::ScoredPoint;
+use segment::types::{ScoredPoint, SearchExplanation};
 use serde::{Deserialize, Serialize};
 use storage::content_manager::collection_meta_ops::{
-    ChangeAliasesOperation, CreateCollection, UpdateCollection,
+    ChangeAliasesOperation, CreateApiToken, CreateCollection, DeleteApiToken, SetDefaultFilter,
+    UpdateCollection,
 };
+use storage::content_manager::consensus::audit_log::AuditRecord;
+use storage::content_manager::federated_search::{FederatedScoredPoint, FederatedSearchRequest};
+use storage::content_manager::keyed_locks::{KeyedLockInfo, KeyedLockRequest};
+use storage::content_manager::slow_queries::SlowQuery;
 use storage::types::ClusterStatus;
 
-use crate::common::helpers::LocksOption;
+use crate::common::diagnostics::DiagnosticsReport;
+use crate::common::helpers::{LocksOption, ThreadsInfo, ThreadsOption};
+use crate::common::inference::{TextPointStatus, TextPointsStatusRequest, UpsertTextPoints};
 use crate::common::points::CreateFieldIndex;
 use crate::common::telemetry::TelemetryData;
 
@@ -56,6 +67,39 @@ struct AllDefinitions {
     at: SearchRequestBatch,
     au: RecommendRequestBatch,
     av: LocksOption,
+    aw: KeyedLockRequest,
+    ax: KeyedLockInfo,
+    ay: DiagnosticsReport,
+    az: UpsertTextPoints,
+    b1: TextPointsStatusRequest,
+    b2: TextPointStatus,
+    b3: SetDefaultFilter,
+    b4: ReshardingPreviewRequest,
+    b5: ReshardingPreview,
+    b6: FacetRequest,
+    b7: FacetResponse,
+    b8: WriteOrdering,
+    b9: CreateExport,
+    ba: ExportDescription,
+    bb: JobInfo,
+    bc: PointsChange,
+    bd: SlowQuery,
+    be: CollectionVerificationReport,
+    bf: CreateApiToken,
+    bg: DeleteApiToken,
+    bh: StreamExport,
+    bi: SearchExplanation,
+    bj: ReplicaSyncReport,
+    bk: ShardPlacement,
+    bl: CollectionsAliasesResponse,
+    bm: ThreadsOption,
+    bn: ThreadsInfo,
+    bo: FederatedSearchRequest,
+    bp: FederatedScoredPoint,
+    bq: DiscoverRequest,
+    br: SampleRequest,
+    bs: UpdatePayload,
+    bt: AuditRecord,
 }
 
 fn save_schema<T: JsonSchema>() {
@@ -1,18 +1,49 @@
+use std::collections::HashMap;
 use std::env;
+use std::net::IpAddr;
 
 use config::{Config, ConfigError, Environment, File};
 use serde::Deserialize;
-use storage::types::StorageConfig;
+use storage::types::{EncryptionConfig, StorageConfig};
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct ServiceConfig {
     pub host: String,
+    /// Additional addresses to bind the HTTP and gRPC servers on, e.g. the address of another
+    /// network interface or an IPv6 address, if `host` is an IPv4 one
+    #[serde(default)]
+    pub additional_hosts: Vec<String>,
     pub http_port: u16,
     pub grpc_port: Option<u16>, // None means that gRPC is disabled
+    /// Max size of the REST request body and of the gRPC message
     pub max_request_size_mb: usize,
+    /// Max number of points in a single upsert request. If not set - not limited
+    #[serde(default)]
+    pub max_upsert_points: Option<usize>,
+    /// Max number of searches, recommendations or update operations in a single batch request.
+    /// If not set - not limited
+    #[serde(default)]
+    pub max_batch_size: Option<usize>,
     pub max_workers: Option<usize>,
     #[serde(default = "default_cors")]
     pub enable_cors: bool,
+    /// Origins, allowed to make cross-origin requests, e.g. `https://dashboard.example.com`.
+    /// If empty - any origin is allowed
+    #[serde(default)]
+    pub cors_allowed_origins: Vec<String>,
+    /// Master API key, required in the `api-key` header of all REST and gRPC requests.
+    /// Collection tokens could be used instead to access a single collection.
+    /// If not set - requests are not authenticated
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// Secret, which HS256 JWTs in the `Authorization: Bearer` header are verified with.
+    /// Claims of the token restrict the accessible collections, the scope and the expiration
+    #[serde(default)]
+    pub jwt_secret: Option<String>,
+    /// Max time to finish in-flight requests and transfer the raft leadership on SIGTERM,
+    /// before the collections are flushed and the process exits
+    #[serde(default = "default_shutdown_timeout_sec")]
+    pub shutdown_timeout_sec: u64,
 }
 
 #[derive(Debug, Deserialize, Clone, Default)]
@@ -26,21 +57,56 @@ pub struct ClusterConfig {
     pub p2p: P2pConfig,
     #[serde(default)]
     pub consensus: ConsensusConfig,
+    /// If not specified - under-replicated shards are not re-replicated automatically
+    #[serde(default)]
+    pub replication_reconciler: Option<ReplicationReconcilerConfig>,
+    /// If not specified - replicas of the shards are compared only on request
+    #[serde(default)]
+    pub anti_entropy: Option<AntiEntropyConfig>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct P2pConfig {
     #[serde(default)]
     pub port: Option<u16>,
+    /// Address to bind the internal gRPC server on. If not set - addresses of the service are used
+    #[serde(default)]
+    pub host: Option<String>,
+    /// Additional addresses to bind the internal gRPC server on, if `host` is set
+    #[serde(default)]
+    pub additional_hosts: Vec<String>,
+    /// URI, which other peers reach this peer by, if it differs from the bind address,
+    /// e.g. behind NAT or a Kubernetes service. The `--uri` command line argument takes precedence.
+    /// If not set - peers derive it from the address of incoming connections.
+    #[serde(default)]
+    pub advertised_uri: Option<String>,
     #[serde(default = "default_connection_pool_size")]
     pub connection_pool_size: usize,
+    /// Compress raft messages and shard transfer traffic with `gzip`.
+    /// Requests are only compressed for peers, which advertised support of compression.
+    #[serde(default)]
+    pub enable_compression: bool,
+    /// Encrypt messages between peers with a key, shared by all peers of the cluster
+    #[serde(default)]
+    pub encryption: EncryptionConfig,
+    /// Secret, shared by all peers of the cluster. If set - internal requests without a valid
+    /// signature, made with it, are rejected, and points of the shards are only changed
+    /// by the members of the cluster.
+    #[serde(default)]
+    pub secret: Option<String>,
 }
 
 impl Default for P2pConfig {
     fn default() -> Self {
         P2pConfig {
             port: None,
+            host: None,
+            additional_hosts: Vec::new(),
+            advertised_uri: None,
             connection_pool_size: default_connection_pool_size(),
+            enable_compression: false,
+            encryption: Default::default(),
+            secret: None,
         }
     }
 }
@@ -53,6 +119,15 @@ pub struct ConsensusConfig {
     pub tick_period_ms: u64,
     #[serde(default = "default_bootstrap_timeout_sec")]
     pub bootstrap_timeout_sec: u64,
+    /// Compact the consensus WAL, once this number of entries were applied since the previous
+    /// compaction. Lagging peers receive a snapshot instead of the compacted entries.
+    /// If `null` - the WAL is not compacted by the number of entries
+    #[serde(default = "default_compact_wal_entries")]
+    pub compact_wal_entries: Option<u64>,
+    /// Compact the consensus WAL, once its files take more than this size.
+    /// If not set - the WAL is not compacted by size
+    #[serde(default)]
+    pub compact_wal_size_mb: Option<u64>,
 }
 
 impl Default for ConsensusConfig {
@@ -61,10 +136,76 @@ impl Default for ConsensusConfig {
             max_message_queue_size: default_max_message_queue_size(),
             tick_period_ms: default_tick_period_ms(),
             bootstrap_timeout_sec: default_bootstrap_timeout_sec(),
+            compact_wal_entries: default_compact_wal_entries(),
+            compact_wal_size_mb: None,
         }
     }
 }
 
+/// Configuration of the periodic check, which replicates shards with fewer active replicas
+/// than `replication_factor` of their collection to other live peers.
+#[derive(Debug, Deserialize, Clone)]
+pub struct ReplicationReconcilerConfig {
+    #[serde(default = "default_replication_reconciler_interval_sec")]
+    pub interval_sec: u64,
+}
+
+/// Configuration of the periodic comparison of the shard replicas, which re-syncs points,
+/// diverged from the leading replica of the shard.
+#[derive(Debug, Deserialize, Clone)]
+pub struct AntiEntropyConfig {
+    #[serde(default = "default_anti_entropy_interval_sec")]
+    pub interval_sec: u64,
+}
+
+/// Configuration of the external embedding service, used to fill vectors of points,
+/// upserted as raw text.
+#[derive(Debug, Deserialize, Clone)]
+pub struct InferenceConfig {
+    /// URL of the HTTP endpoint, which converts texts into vectors. Only `http://` is supported.
+    /// It receives `{"inputs": ["text", ...]}` and should respond with `[[0.1, ...], ...]`
+    pub url: String,
+    #[serde(default = "default_inference_timeout_sec")]
+    pub timeout_sec: u64,
+    /// Max number of texts, sent to the embedding service in a single request
+    #[serde(default = "default_inference_batch_size")]
+    pub batch_size: usize,
+}
+
+/// Configuration of the periodic push of metrics into the Prometheus remote write endpoint.
+/// Useful, if individual nodes can't be scraped, e.g. if they are behind NAT.
+#[derive(Debug, Deserialize, Clone)]
+pub struct MetricsExportConfig {
+    /// URL of the remote write endpoint, e.g. `http://prometheus:9090/api/v1/write`
+    pub url: String,
+    #[serde(default = "default_metrics_export_interval_sec")]
+    pub interval_sec: u64,
+    #[serde(default = "default_metrics_export_timeout_sec")]
+    pub timeout_sec: u64,
+    /// Labels, added to all exported metrics, e.g. `instance` to distinguish nodes
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+}
+
+/// Configuration of the periodic reporting of anonymized usage statistics:
+/// version, number of collections and vectors. Names and data are never reported.
+#[derive(Debug, Deserialize, Clone)]
+pub struct TelemetryReportingConfig {
+    /// URL of the HTTP endpoint, which receives reports as JSON. Only `http://` is supported
+    pub url: String,
+    #[serde(default = "default_telemetry_reporting_interval_sec")]
+    pub interval_sec: u64,
+    #[serde(default = "default_telemetry_reporting_timeout_sec")]
+    pub timeout_sec: u64,
+}
+
+/// Configuration of the periodic verification of the local data against the stored checksums
+#[derive(Debug, Deserialize, Clone)]
+pub struct ScrubConfig {
+    #[serde(default = "default_scrub_interval_sec")]
+    pub interval_sec: u64,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct Settings {
     #[serde(default = "default_debug")]
@@ -75,12 +216,31 @@ pub struct Settings {
     pub service: ServiceConfig,
     #[serde(default)]
     pub cluster: ClusterConfig,
+    /// If not specified - upsert of points as text is disabled
+    #[serde(default)]
+    pub inference: Option<InferenceConfig>,
+    /// If not specified - metrics are not pushed
+    #[serde(default)]
+    pub metrics_export: Option<MetricsExportConfig>,
+    /// Opt-out of usage statistics reporting, takes precedence over `telemetry_reporting`
+    #[serde(default)]
+    pub telemetry_disabled: bool,
+    /// If not specified - usage statistics are not reported
+    #[serde(default)]
+    pub telemetry_reporting: Option<TelemetryReportingConfig>,
+    /// If not specified - data is only verified with the `verify` API
+    #[serde(default)]
+    pub scrub: Option<ScrubConfig>,
 }
 
 fn default_cors() -> bool {
     true
 }
 
+fn default_shutdown_timeout_sec() -> u64 {
+    30
+}
+
 fn default_debug() -> bool {
     false
 }
@@ -106,6 +266,10 @@ fn default_bootstrap_timeout_sec() -> u64 {
     15
 }
 
+fn default_compact_wal_entries() -> Option<u64> {
+    Some(1000)
+}
+
 fn default_max_message_queue_size() -> usize {
     100
 }
@@ -114,6 +278,52 @@ fn default_connection_pool_size() -> usize {
     2
 }
 
+fn default_inference_timeout_sec() -> u64 {
+    30
+}
+
+fn default_inference_batch_size() -> usize {
+    32
+}
+
+fn default_replication_reconciler_interval_sec() -> u64 {
+    30
+}
+
+fn default_anti_entropy_interval_sec() -> u64 {
+    600
+}
+
+fn default_metrics_export_interval_sec() -> u64 {
+    15
+}
+
+fn default_metrics_export_timeout_sec() -> u64 {
+    10
+}
+
+fn default_telemetry_reporting_interval_sec() -> u64 {
+    60 * 60
+}
+
+fn default_telemetry_reporting_timeout_sec() -> u64 {
+    10
+}
+
+fn default_scrub_interval_sec() -> u64 {
+    24 * 60 * 60
+}
+
+impl ServiceConfig {
+    /// All addresses, which the HTTP and gRPC servers listen on
+    pub fn bind_hosts(&self) -> Vec<String> {
+        std::iter::once(&self.host)
+            .chain(&self.additional_hosts)
+            .cloned()
+            .collect()
+    }
+}
+
 impl Settings {
     #[allow(dead_code)]
     pub fn new() -> Result<Self, ConfigError> {
@@ -135,7 +345,118 @@ impl Settings {
             .build()?;
 
         // You can deserialize (and thus freeze) the entire configuration as
-        s.try_deserialize()
+        let settings: Settings = s.try_deserialize()?;
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    /// All addresses, which the internal gRPC server listens on
+    pub fn p2p_bind_hosts(&self) -> Vec<String> {
+        match &self.cluster.p2p.host {
+            None => self.service.bind_hosts(),
+            Some(host) => std::iter::once(host)
+                .chain(&self.cluster.p2p.additional_hosts)
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Check values, which can't be expressed with types alone
+    fn validate(&self) -> Result<(), ConfigError> {
+        for host in self.service.bind_hosts() {
+            validate_ip_addr("service.host", &host)?;
+        }
+        for host in self.p2p_bind_hosts() {
+            validate_ip_addr("cluster.p2p.host", &host)?;
+        }
+        if let Some(advertised_uri) = &self.cluster.p2p.advertised_uri {
+            validate_uri("cluster.p2p.advertised_uri", advertised_uri)?;
+        }
+        if let Some(inference) = &self.inference {
+            inference.validate()?;
+        }
+        if let Some(metrics_export) = &self.metrics_export {
+            metrics_export.validate()?;
+        }
+        if let Some(telemetry_reporting) = &self.telemetry_reporting {
+            telemetry_reporting.validate()?;
+        }
+        if let Some(scrub) = &self.scrub {
+            scrub.validate()?;
+        }
+        Ok(())
+    }
+}
+
+/// Servers are bound on IP addresses only, host names are not resolved
+fn validate_ip_addr(name: &str, host: &str) -> Result<(), ConfigError> {
+    host.parse::<IpAddr>().map_err(|err| {
+        ConfigError::Message(format!("{name} `{host}` is not an IP address: {err}"))
+    })?;
+    Ok(())
+}
+
+fn validate_uri(name: &str, uri: &str) -> Result<(), ConfigError> {
+    let parsed: hyper::Uri = uri
+        .parse()
+        .map_err(|err| ConfigError::Message(format!("{name} `{uri}` is invalid: {err}")))?;
+    if parsed.scheme().is_none() || parsed.host().is_none() {
+        return Err(ConfigError::Message(format!(
+            "{name} `{uri}` should contain a scheme and a host, e.g. `http://qdrant-0:6335`"
+        )));
+    }
+    Ok(())
+}
+
+/// Only plain HTTP endpoints are supported: the client has no TLS connector.
+/// Put the external service behind a local proxy to reach it over HTTPS.
+fn validate_http_url(name: &str, url: &str) -> Result<(), ConfigError> {
+    let uri: hyper::Uri = url
+        .parse()
+        .map_err(|err| ConfigError::Message(format!("{name} `{url}` is invalid: {err}")))?;
+    if uri.scheme_str() != Some("http") {
+        return Err(ConfigError::Message(format!(
+            "{name} `{url}` is not supported, only `http://` URLs are allowed"
+        )));
+    }
+    Ok(())
+}
+
+fn validate_non_zero(name: &str, value: u64) -> Result<(), ConfigError> {
+    if value == 0 {
+        return Err(ConfigError::Message(format!(
+            "{name} should be greater than 0"
+        )));
+    }
+    Ok(())
+}
+
+impl InferenceConfig {
+    fn validate(&self) -> Result<(), ConfigError> {
+        validate_http_url("inference.url", &self.url)?;
+        validate_non_zero("inference.timeout_sec", self.timeout_sec)
+    }
+}
+
+impl MetricsExportConfig {
+    fn validate(&self) -> Result<(), ConfigError> {
+        validate_http_url("metrics_export.url", &self.url)?;
+        validate_non_zero("metrics_export.interval_sec", self.interval_sec)?;
+        validate_non_zero("metrics_export.timeout_sec", self.timeout_sec)
+    }
+}
+
+impl TelemetryReportingConfig {
+    fn validate(&self) -> Result<(), ConfigError> {
+        validate_http_url("telemetry_reporting.url", &self.url)?;
+        validate_non_zero("telemetry_reporting.interval_sec", self.interval_sec)?;
+        validate_non_zero("telemetry_reporting.timeout_sec", self.timeout_sec)
+    }
+}
+
+impl ScrubConfig {
+    fn validate(&self) -> Result<(), ConfigError> {
+        validate_non_zero("scrub.interval_sec", self.interval_sec)
     }
 }
 
@@ -164,4 +485,43 @@ mod tests {
         env::set_var(key, "TEST");
         Settings::new().unwrap();
     }
+
+    #[test]
+    fn test_validate_inference_url() {
+        let config = |url: &str| InferenceConfig {
+            url: url.to_string(),
+            timeout_sec: default_inference_timeout_sec(),
+            batch_size: default_inference_batch_size(),
+        };
+        assert!(config("http://localhost:8080/embed").validate().is_ok());
+        assert!(config("https://localhost:8080/embed").validate().is_err());
+        assert!(config("localhost:8080/embed").validate().is_err());
+        assert!(config("not a url").validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_metrics_export() {
+        let config = |interval_sec, timeout_sec| MetricsExportConfig {
+            url: "http://prometheus:9090/api/v1/write".to_string(),
+            interval_sec,
+            timeout_sec,
+            labels: Default::default(),
+        };
+        assert!(config(15, 10).validate().is_ok());
+        // Zero interval would make the exporter spin, zero timeout would fail every push
+        assert!(config(0, 10).validate().is_err());
+        assert!(config(15, 0).validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_bind_addresses() {
+        assert!(validate_ip_addr("service.host", "0.0.0.0").is_ok());
+        assert!(validate_ip_addr("service.host", "::").is_ok());
+        assert!(validate_ip_addr("service.host", "fd00::1").is_ok());
+        assert!(validate_ip_addr("service.host", "localhost").is_err());
+
+        assert!(validate_uri("cluster.p2p.advertised_uri", "http://qdrant-0:6335").is_ok());
+        assert!(validate_uri("cluster.p2p.advertised_uri", "http://[fd00::1]:6335").is_ok());
+        assert!(validate_uri("cluster.p2p.advertised_uri", "qdrant-0:6335").is_err());
+    }
 }
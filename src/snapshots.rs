@@ -2,10 +2,18 @@ use std::fs::{remove_dir_all, rename};
 use std::path::Path;
 
 use collection::collection::Collection;
+use collection::operations::snapshot_ops::SnapshotManifest;
 use log::info;
 use storage::content_manager::alias_mapping::AliasPersistence;
-use storage::content_manager::snapshots::SnapshotConfig;
-use storage::content_manager::toc::{ALIASES_PATH, COLLECTIONS_DIR};
+use storage::content_manager::api_tokens::ApiTokensPersistence;
+use storage::content_manager::consensus_state::SnapshotData;
+use storage::content_manager::default_filters::DefaultFiltersPersistence;
+use storage::content_manager::snapshots::{
+    SnapshotConfig, FULL_SNAPSHOT_CONFIG_FILE, FULL_SNAPSHOT_CONSENSUS_FILE,
+};
+use storage::content_manager::toc::{
+    ALIASES_PATH, API_TOKENS_PATH, COLLECTIONS_DIR, DEFAULT_FILTERS_PATH,
+};
 
 /// Recover snapshots from the given arguments
 ///
@@ -76,11 +84,25 @@ pub fn recover_full_snapshot(snapshot_path: &str, storage_dir: &str, force: bool
     let mut ar = tar::Archive::new(archive_file);
     ar.unpack(&temporary_dir).unwrap();
 
+    // Full snapshots of older versions have no manifest
+    if let Some(manifest) = SnapshotManifest::load(&temporary_dir).unwrap() {
+        if let Err(err) = manifest.verify_checksums(&temporary_dir) {
+            panic!("Failed to verify snapshot {}: {}", snapshot_path, err);
+        }
+    }
+
     // Read configuration file with snapshot-to-collection mapping
-    let config_path = temporary_dir.join("config.json");
+    let config_path = temporary_dir.join(FULL_SNAPSHOT_CONFIG_FILE);
     let config_file = std::fs::File::open(&config_path).unwrap();
     let config_json: SnapshotConfig = serde_json::from_reader(config_file).unwrap();
 
+    // Consensus metadata is absent in full snapshots of older versions
+    let consensus_path = temporary_dir.join(FULL_SNAPSHOT_CONSENSUS_FILE);
+    let consensus_data: Option<SnapshotData> = consensus_path.exists().then(|| {
+        let consensus_file = std::fs::File::open(&consensus_path).unwrap();
+        serde_json::from_reader(consensus_file).unwrap()
+    });
+
     // Create mapping from the configuration file
     let mapping: Vec<String> = config_json
         .collections_mapping
@@ -100,17 +122,156 @@ pub fn recover_full_snapshot(snapshot_path: &str, storage_dir: &str, force: bool
     let alias_path = Path::new(storage_dir).join(ALIASES_PATH);
     let mut alias_persistence =
         AliasPersistence::open(alias_path).expect("Can't open database by the provided config");
-    for (alias, collection_name) in config_json.collections_aliases {
-        if alias_persistence.get(&alias).is_some() && !force {
+    for (alias, collection_name) in &config_json.collections_aliases {
+        if alias_persistence.get(alias).is_some() && !force {
             panic!(
                 "Alias {} already exists. Use --force-snapshot to overwrite it.",
                 alias
             );
         }
-        alias_persistence.insert(alias, collection_name).unwrap();
+        alias_persistence
+            .insert(alias.clone(), collection_name.clone())
+            .unwrap();
+    }
+
+    if let Some(consensus_data) = consensus_data {
+        recover_consensus_metadata(&consensus_data, &config_json, storage_dir, force);
     }
 
     // Remove temporary directory
     remove_dir_all(&temporary_dir).unwrap();
     recovered_collection
 }
+
+/// Restore parts of the consensus metadata, which are not included into collection snapshots
+fn recover_consensus_metadata(
+    consensus_data: &SnapshotData,
+    config: &SnapshotConfig,
+    storage_dir: &str,
+    force: bool,
+) {
+    let filters_path = Path::new(storage_dir).join(DEFAULT_FILTERS_PATH);
+    let mut filters_persistence = DefaultFiltersPersistence::open(filters_path)
+        .expect("Can't open default filters by the provided config");
+    for (target, filter) in consensus_data.collections_data.default_filters.iter() {
+        let is_recovered = config.collections_mapping.contains_key(target)
+            || config.collections_aliases.contains_key(target);
+        if !is_recovered {
+            continue;
+        }
+        if filters_persistence.get(target).is_some() && !force {
+            panic!(
+                "Default filter of {} already exists. Use --force-snapshot to overwrite it.",
+                target
+            );
+        }
+        filters_persistence
+            .set(target.clone(), Some(filter.clone()))
+            .unwrap();
+    }
+
+    // Only full snapshots of older versions contain API tokens.
+    // Tokens are random, so tokens of the recovered collections can't clash with existing ones
+    let tokens_path = Path::new(storage_dir).join(API_TOKENS_PATH);
+    let mut tokens_persistence = ApiTokensPersistence::open(tokens_path)
+        .expect("Can't open API tokens by the provided config");
+    for (token, api_token) in consensus_data.collections_data.api_tokens.iter() {
+        let is_recovered = config
+            .collections_mapping
+            .contains_key(&api_token.collection_name)
+            || config
+                .collections_aliases
+                .contains_key(&api_token.collection_name);
+        if is_recovered {
+            tokens_persistence
+                .insert(token.clone(), api_token.clone())
+                .unwrap();
+        }
+    }
+
+    let peers_count = consensus_data.address_by_id.len();
+    if peers_count > 1 {
+        log::warn!(
+            "Snapshot was created on a peer of a cluster with {} peers. \
+             It only contains shards, which were hosted by that peer",
+            peers_count
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::json;
+    use tempfile::Builder;
+
+    use super::*;
+
+    #[test]
+    fn test_recover_default_filters() {
+        let storage_dir = Builder::new().prefix("storage").tempdir().unwrap();
+        let filter = json!({ "must": [{ "key": "visible", "match": { "value": true } }] });
+        let consensus_data: SnapshotData = serde_json::from_value(json!({
+            "collections_data": {
+                "collections": {},
+                "aliases": { "test_alias": "test" },
+                "default_filters": {
+                    "test": filter,
+                    "test_alias": filter,
+                    "not_recovered": filter,
+                },
+            },
+            "address_by_id": {},
+        }))
+        .unwrap();
+        let config = SnapshotConfig {
+            collections_mapping: HashMap::from([("test".to_string(), "test.snapshot".to_string())]),
+            collections_aliases: HashMap::from([("test_alias".to_string(), "test".to_string())]),
+        };
+
+        let storage_path = storage_dir.path().to_str().unwrap();
+        recover_consensus_metadata(&consensus_data, &config, storage_path, false);
+
+        let filters =
+            DefaultFiltersPersistence::open(storage_dir.path().join(DEFAULT_FILTERS_PATH)).unwrap();
+        assert!(filters.get("test").is_some());
+        assert!(filters.get("test_alias").is_some());
+        assert!(filters.get("not_recovered").is_none());
+
+        // Existing filters are only overwritten with `force`
+        recover_consensus_metadata(&consensus_data, &config, storage_path, true);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            recover_consensus_metadata(&consensus_data, &config, storage_path, false)
+        }));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_recover_api_tokens() {
+        let storage_dir = Builder::new().prefix("storage").tempdir().unwrap();
+        let consensus_data: SnapshotData = serde_json::from_value(json!({
+            "collections_data": {
+                "collections": {},
+                "aliases": {},
+                "api_tokens": {
+                    "recovered_token": { "collection_name": "test", "scope": "read" },
+                    "other_token": { "collection_name": "not_recovered", "scope": "manage" },
+                },
+            },
+            "address_by_id": {},
+        }))
+        .unwrap();
+        let config = SnapshotConfig {
+            collections_mapping: HashMap::from([("test".to_string(), "test.snapshot".to_string())]),
+            collections_aliases: HashMap::new(),
+        };
+
+        let storage_path = storage_dir.path().to_str().unwrap();
+        recover_consensus_metadata(&consensus_data, &config, storage_path, false);
+
+        let tokens = ApiTokensPersistence::open(storage_dir.path().join(API_TOKENS_PATH)).unwrap();
+        assert!(tokens.get("recovered_token").is_some());
+        assert!(tokens.get("other_token").is_none());
+    }
+}
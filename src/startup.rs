@@ -4,6 +4,8 @@ use std::panic;
 
 use log::LevelFilter;
 
+use crate::common::access_log::ACCESS_LOG_TARGET;
+
 pub fn setup_logger(log_level: &str) {
     let is_info = log_level.to_ascii_uppercase() == "INFO";
     let mut log_builder = env_logger::Builder::new();
@@ -22,7 +24,9 @@ pub fn setup_logger(log_level: &str) {
         // Additionally filter verbose modules if no extended logging configuration is provided
         log_builder
             .filter_module("wal", LevelFilter::Warn)
-            .filter_module("raft::raft", LevelFilter::Warn);
+            .filter_module("raft::raft", LevelFilter::Warn)
+            // Access log is enabled explicitly, e.g. with `INFO,access_log=info`
+            .filter_module(ACCESS_LOG_TARGET, LevelFilter::Off);
     };
 
     log_builder.init();
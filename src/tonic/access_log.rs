@@ -0,0 +1,74 @@
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use futures_util::future::BoxFuture;
+use hyper::body::HttpBody;
+use tonic::codegen::http;
+use tower::Service;
+use tower_layer::Layer;
+
+use crate::common::access_log::{duration_ms, is_access_log_enabled, log_access, AccessLogRecord};
+
+#[derive(Clone)]
+pub struct AccessLogService<T> {
+    service: T,
+}
+
+/// Writes a JSON line into the access log for each handled call.
+///
+/// Collection is a field of the protobuf message, so it is not known at this level and is omitted.
+/// Status is taken from the headers of trailers-only responses, which are sent on errors,
+/// otherwise the call is logged as `OK`.
+#[derive(Clone)]
+pub struct AccessLogLayer;
+
+impl<S, B> Service<http::Request<tonic::transport::Body>> for AccessLogService<S>
+where
+    S: Service<http::Request<tonic::transport::Body>, Response = http::Response<B>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<S::Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<tonic::transport::Body>) -> Self::Future {
+        if !is_access_log_enabled() {
+            return Box::pin(self.service.call(request));
+        }
+
+        let method = request.uri().path().to_string();
+        let request_size = request.body().size_hint().exact();
+        let future = self.service.call(request);
+        Box::pin(async move {
+            let instant = Instant::now();
+            let response = future.await?;
+            let status = response
+                .headers()
+                .get("grpc-status")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(tonic::Code::Ok as u16);
+            log_access(&AccessLogRecord {
+                protocol: "grpc",
+                method: &method,
+                collection: None,
+                status,
+                duration_ms: duration_ms(instant.elapsed()),
+                request_size,
+            });
+            Ok(response)
+        })
+    }
+}
+
+impl<S> Layer<S> for AccessLogLayer {
+    type Service = AccessLogService<S>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        AccessLogService { service }
+    }
+}
@@ -4,15 +4,18 @@ use std::time::{Duration, Instant};
 use api::grpc::qdrant::collections_server::Collections;
 use api::grpc::qdrant::{
     ChangeAliases, CollectionOperationResponse, CreateCollection, DeleteCollection,
-    GetCollectionInfoRequest, GetCollectionInfoResponse, ListCollectionsRequest,
-    ListCollectionsResponse, UpdateCollection,
+    GetCollectionInfoRequest, GetCollectionInfoResponse, ListAliasesRequest, ListAliasesResponse,
+    ListCollectionAliasesRequest, ListCollectionsRequest, ListCollectionsResponse, ShardPlacement,
+    UpdateCollection,
 };
+use storage::content_manager::api_tokens::ApiTokenScope;
 use storage::content_manager::conversions::error_to_status;
 use storage::dispatcher::Dispatcher;
 use tonic::{Request, Response, Status};
 
 use crate::common::collections::*;
 use crate::tonic::api::collections_common::get;
+use crate::tonic::auth::{check_access, client_identity};
 
 pub struct CollectionsService {
     dispatcher: Arc<Dispatcher>,
@@ -34,16 +37,24 @@ impl CollectionsService {
                 Error = Status,
             >,
     {
+        let identity = client_identity(&request);
         let operation = request.into_inner();
         let wait_timeout = operation.wait_timeout();
         let timing = Instant::now();
-        let result = self
+        let (result, shard_placement) = self
             .dispatcher
-            .submit_collection_meta_op(operation.try_into()?, wait_timeout)
+            .submit_collection_meta_op_with_placement(operation.try_into()?, identity, wait_timeout)
             .await
             .map_err(error_to_status)?;
 
-        let response = CollectionOperationResponse::from((timing, result));
+        let mut response = CollectionOperationResponse::from((timing, result));
+        if let Some(shard_placement) = shard_placement {
+            response.shard_placement = shard_placement
+                .distribution
+                .into_iter()
+                .map(|(shard_id, peers)| ShardPlacement { shard_id, peers })
+                .collect();
+        }
         Ok(Response::new(response))
     }
 }
@@ -54,13 +65,19 @@ impl Collections for CollectionsService {
         &self,
         request: Request<GetCollectionInfoRequest>,
     ) -> Result<Response<GetCollectionInfoResponse>, Status> {
+        check_access(
+            &request,
+            Some(&request.get_ref().collection_name),
+            ApiTokenScope::Read,
+        )?;
         get(self.dispatcher.as_ref(), request.into_inner(), None).await
     }
 
     async fn list(
         &self,
-        _request: Request<ListCollectionsRequest>,
+        request: Request<ListCollectionsRequest>,
     ) -> Result<Response<ListCollectionsResponse>, Status> {
+        check_access(&request, None, ApiTokenScope::Manage)?;
         let timing = Instant::now();
         let result = do_list_collections(&self.dispatcher).await;
 
@@ -72,6 +89,7 @@ impl Collections for CollectionsService {
         &self,
         request: Request<CreateCollection>,
     ) -> Result<Response<CollectionOperationResponse>, Status> {
+        check_access(&request, None, ApiTokenScope::Manage)?;
         self.perform_operation(request).await
     }
 
@@ -79,6 +97,11 @@ impl Collections for CollectionsService {
         &self,
         request: Request<UpdateCollection>,
     ) -> Result<Response<CollectionOperationResponse>, Status> {
+        check_access(
+            &request,
+            Some(&request.get_ref().collection_name),
+            ApiTokenScope::Manage,
+        )?;
         self.perform_operation(request).await
     }
 
@@ -86,6 +109,11 @@ impl Collections for CollectionsService {
         &self,
         request: Request<DeleteCollection>,
     ) -> Result<Response<CollectionOperationResponse>, Status> {
+        check_access(
+            &request,
+            Some(&request.get_ref().collection_name),
+            ApiTokenScope::Manage,
+        )?;
         self.perform_operation(request).await
     }
 
@@ -93,8 +121,40 @@ impl Collections for CollectionsService {
         &self,
         request: Request<ChangeAliases>,
     ) -> Result<Response<CollectionOperationResponse>, Status> {
+        check_access(&request, None, ApiTokenScope::Manage)?;
         self.perform_operation(request).await
     }
+
+    async fn list_collection_aliases(
+        &self,
+        request: Request<ListCollectionAliasesRequest>,
+    ) -> Result<Response<ListAliasesResponse>, Status> {
+        check_access(
+            &request,
+            Some(&request.get_ref().collection_name),
+            ApiTokenScope::Read,
+        )?;
+        let timing = Instant::now();
+        let ListCollectionAliasesRequest { collection_name } = request.into_inner();
+        let result = do_list_collection_aliases(&self.dispatcher, &collection_name)
+            .await
+            .map_err(error_to_status)?;
+
+        let response = ListAliasesResponse::from((timing, result));
+        Ok(Response::new(response))
+    }
+
+    async fn list_aliases(
+        &self,
+        request: Request<ListAliasesRequest>,
+    ) -> Result<Response<ListAliasesResponse>, Status> {
+        check_access(&request, None, ApiTokenScope::Manage)?;
+        let timing = Instant::now();
+        let result = do_list_aliases(&self.dispatcher).await;
+
+        let response = ListAliasesResponse::from((timing, result));
+        Ok(Response::new(response))
+    }
 }
 
 trait WithTimeout {
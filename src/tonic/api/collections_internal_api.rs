@@ -62,6 +62,7 @@ impl CollectionsInternal for CollectionsInternalService {
         let response = CollectionOperationResponse {
             result: true,
             time: timing.elapsed().as_secs_f64(),
+            shard_placement: vec![],
         };
         Ok(Response::new(response))
     }
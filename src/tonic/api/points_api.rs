@@ -1,29 +1,107 @@
+use std::pin::Pin;
 use std::sync::Arc;
 
 use api::grpc::qdrant::points_server::Points;
 use api::grpc::qdrant::{
     ClearPayloadPoints, CountPoints, CountResponse, CreateFieldIndexCollection,
-    DeleteFieldIndexCollection, DeletePayloadPoints, DeletePoints, GetPoints, GetResponse,
-    PointsOperationResponse, RecommendBatchPoints, RecommendBatchResponse, RecommendPoints,
-    RecommendResponse, ScrollPoints, ScrollResponse, SearchBatchPoints, SearchBatchResponse,
-    SearchPoints, SearchResponse, SetPayloadPoints, UpsertPoints,
+    DeleteFieldIndexCollection, DeletePayloadPoints, DeletePoints, FacetPoints, FacetResponse,
+    GetPoints, GetResponse, PointsChange, PointsOperationResponse, RecommendBatchPoints,
+    RecommendBatchResponse, RecommendPoints, RecommendResponse, ScrollPoints, ScrollResponse,
+    SearchBatchPoints, SearchBatchResponse, SearchPoints, SearchResponse, SetPayloadPoints,
+    StreamPointsChanges, UpdateBatchPoints, UpdateBatchResponse, UpdatePayloadPoints, UpsertPoints,
 };
+use futures::{Stream, StreamExt};
+use storage::content_manager::api_tokens::{Access, ApiTokenScope};
+use storage::content_manager::conversions::error_to_status;
 use storage::content_manager::toc::TableOfContent;
-use tonic::{Request, Response, Status};
+use tonic::{Request, Response, Status, Streaming};
 
+use crate::common::request_limits::RequestLimits;
 use crate::tonic::api::points_common::{
-    clear_payload, count, create_field_index, delete, delete_field_index, delete_payload, get,
-    recommend, recommend_batch, scroll, search, search_batch, set_payload, upsert,
+    clear_payload, count, create_field_index, delete, delete_field_index, delete_payload, facet,
+    get, recommend, recommend_batch, scroll, search, search_batch, set_payload, stream_changes,
+    update_batch, update_payload, upsert,
 };
+use crate::tonic::auth::check_access;
+
+/// Acknowledgements of the chunks, upserted with the stream
+pub type UpsertStream =
+    Pin<Box<dyn Stream<Item = Result<PointsOperationResponse, Status>> + Send + 'static>>;
+
+/// Changes of the points, read from the WAL of the shard
+pub type PointsChangesStream =
+    Pin<Box<dyn Stream<Item = Result<PointsChange, Status>> + Send + 'static>>;
+
+/// Examples of the requests with `lookup_from` are read from another collection,
+/// so it requires read access as well
+fn check_lookup_access<T>(
+    request: &Request<T>,
+    recommend_points: &[RecommendPoints],
+) -> Result<(), Status> {
+    for lookup_from in recommend_points
+        .iter()
+        .filter_map(|recommend| recommend.lookup_from.as_ref())
+    {
+        check_access(
+            request,
+            Some(&lookup_from.collection_name),
+            ApiTokenScope::Read,
+        )?;
+    }
+    Ok(())
+}
 
 pub struct PointsService {
     toc: Arc<TableOfContent>,
+    limits: RequestLimits,
 }
 
 impl PointsService {
-    pub fn new(toc: Arc<TableOfContent>) -> Self {
-        Self { toc }
+    pub fn new(toc: Arc<TableOfContent>, limits: RequestLimits) -> Self {
+        Self { toc, limits }
+    }
+}
+
+/// Metadata of the write response with the number of seconds, the client is advised to wait
+/// before the next write
+const RETRY_AFTER_METADATA: &str = "retry-after";
+
+/// Add `retry-after` metadata to the response of the write, if update queues are overloaded
+async fn with_retry_after<T>(
+    toc: &TableOfContent,
+    collection_name: &str,
+    response: Result<Response<T>, Status>,
+) -> Result<Response<T>, Status> {
+    let mut response = response?;
+    if let Some(retry_after) = toc.update_retry_after(collection_name).await {
+        response
+            .metadata_mut()
+            .insert(RETRY_AFTER_METADATA, retry_after.into());
     }
+    Ok(response)
+}
+
+/// Upsert chunks one by one: next chunk is not read until the previous one is acknowledged.
+/// The stream ends with the first error, the following chunks are not applied.
+fn upsert_stream_responses<S>(toc: Arc<TableOfContent>, requests: S) -> UpsertStream
+where
+    S: Stream<Item = Result<UpsertPoints, Status>> + Unpin + Send + 'static,
+{
+    let responses = futures::stream::unfold(Some(requests), move |requests| {
+        let toc = toc.clone();
+        async move {
+            let mut requests = requests?;
+            let response = match requests.next().await? {
+                Ok(upsert_points) => upsert(toc.as_ref(), upsert_points, None)
+                    .await
+                    .map(Response::into_inner),
+                Err(err) => Err(err),
+            };
+            let requests = response.is_ok().then_some(requests);
+            Some((response, requests))
+        }
+    });
+    Box::pin(responses)
 }
 
 #[tonic::async_trait]
@@ -32,17 +110,83 @@ impl Points for PointsService {
         &self,
         request: Request<UpsertPoints>,
     ) -> Result<Response<PointsOperationResponse>, Status> {
-        upsert(self.toc.as_ref(), request.into_inner(), None).await
+        check_access(
+            &request,
+            Some(&request.get_ref().collection_name),
+            ApiTokenScope::Write,
+        )?;
+        self.limits
+            .check_upsert_points(request.get_ref().points.len())
+            .map_err(error_to_status)?;
+        let collection_name = request.get_ref().collection_name.clone();
+        let response = upsert(self.toc.as_ref(), request.into_inner(), None).await;
+        with_retry_after(self.toc.as_ref(), &collection_name, response).await
+    }
+
+    type UpsertStreamStream = UpsertStream;
+
+    async fn upsert_stream(
+        &self,
+        request: Request<Streaming<UpsertPoints>>,
+    ) -> Result<Response<Self::UpsertStreamStream>, Status> {
+        // Each chunk may address a different collection, so access is checked per chunk
+        let access = request.extensions().get::<Access>().cloned();
+        let limits = self.limits;
+        let requests = request.into_inner().map(move |upsert_points| {
+            let upsert_points = upsert_points?;
+            if let Some(access) = &access {
+                access
+                    .check(Some(&upsert_points.collection_name), ApiTokenScope::Write)
+                    .map_err(error_to_status)?;
+            }
+            limits
+                .check_upsert_points(upsert_points.points.len())
+                .map_err(error_to_status)?;
+            Ok(upsert_points)
+        });
+        Ok(Response::new(upsert_stream_responses(
+            self.toc.clone(),
+            requests,
+        )))
     }
 
     async fn delete(
         &self,
         request: Request<DeletePoints>,
     ) -> Result<Response<PointsOperationResponse>, Status> {
-        delete(self.toc.as_ref(), request.into_inner(), None).await
+        check_access(
+            &request,
+            Some(&request.get_ref().collection_name),
+            ApiTokenScope::Write,
+        )?;
+        let collection_name = request.get_ref().collection_name.clone();
+        let response = delete(self.toc.as_ref(), request.into_inner(), None).await;
+        with_retry_after(self.toc.as_ref(), &collection_name, response).await
+    }
+
+    async fn update_batch(
+        &self,
+        request: Request<UpdateBatchPoints>,
+    ) -> Result<Response<UpdateBatchResponse>, Status> {
+        check_access(
+            &request,
+            Some(&request.get_ref().collection_name),
+            ApiTokenScope::Write,
+        )?;
+        self.limits
+            .check_batch_size(request.get_ref().operations.len())
+            .map_err(error_to_status)?;
+        let collection_name = request.get_ref().collection_name.clone();
+        let response = update_batch(self.toc.as_ref(), request.into_inner(), None).await;
+        with_retry_after(self.toc.as_ref(), &collection_name, response).await
     }
 
     async fn get(&self, request: Request<GetPoints>) -> Result<Response<GetResponse>, Status> {
+        check_access(
+            &request,
+            Some(&request.get_ref().collection_name),
+            ApiTokenScope::Read,
+        )?;
         get(self.toc.as_ref(), request.into_inner(), None).await
     }
 
@@ -50,27 +194,67 @@ impl Points for PointsService {
         &self,
         request: Request<SetPayloadPoints>,
     ) -> Result<Response<PointsOperationResponse>, Status> {
-        set_payload(self.toc.as_ref(), request.into_inner(), None).await
+        check_access(
+            &request,
+            Some(&request.get_ref().collection_name),
+            ApiTokenScope::Write,
+        )?;
+        let collection_name = request.get_ref().collection_name.clone();
+        let response = set_payload(self.toc.as_ref(), request.into_inner(), None).await;
+        with_retry_after(self.toc.as_ref(), &collection_name, response).await
+    }
+
+    async fn update_payload(
+        &self,
+        request: Request<UpdatePayloadPoints>,
+    ) -> Result<Response<PointsOperationResponse>, Status> {
+        check_access(
+            &request,
+            Some(&request.get_ref().collection_name),
+            ApiTokenScope::Write,
+        )?;
+        let collection_name = request.get_ref().collection_name.clone();
+        let response = update_payload(self.toc.as_ref(), request.into_inner(), None).await;
+        with_retry_after(self.toc.as_ref(), &collection_name, response).await
     }
 
     async fn delete_payload(
         &self,
         request: Request<DeletePayloadPoints>,
     ) -> Result<Response<PointsOperationResponse>, Status> {
-        delete_payload(self.toc.as_ref(), request.into_inner(), None).await
+        check_access(
+            &request,
+            Some(&request.get_ref().collection_name),
+            ApiTokenScope::Write,
+        )?;
+        let collection_name = request.get_ref().collection_name.clone();
+        let response = delete_payload(self.toc.as_ref(), request.into_inner(), None).await;
+        with_retry_after(self.toc.as_ref(), &collection_name, response).await
     }
 
     async fn clear_payload(
         &self,
         request: Request<ClearPayloadPoints>,
     ) -> Result<Response<PointsOperationResponse>, Status> {
-        clear_payload(self.toc.as_ref(), request.into_inner(), None).await
+        check_access(
+            &request,
+            Some(&request.get_ref().collection_name),
+            ApiTokenScope::Write,
+        )?;
+        let collection_name = request.get_ref().collection_name.clone();
+        let response = clear_payload(self.toc.as_ref(), request.into_inner(), None).await;
+        with_retry_after(self.toc.as_ref(), &collection_name, response).await
     }
 
     async fn create_field_index(
         &self,
         request: Request<CreateFieldIndexCollection>,
     ) -> Result<Response<PointsOperationResponse>, Status> {
+        check_access(
+            &request,
+            Some(&request.get_ref().collection_name),
+            ApiTokenScope::Manage,
+        )?;
         create_field_index(self.toc.as_ref(), request.into_inner(), None).await
     }
 
@@ -78,6 +262,11 @@ impl Points for PointsService {
         &self,
         request: Request<DeleteFieldIndexCollection>,
     ) -> Result<Response<PointsOperationResponse>, Status> {
+        check_access(
+            &request,
+            Some(&request.get_ref().collection_name),
+            ApiTokenScope::Manage,
+        )?;
         delete_field_index(self.toc.as_ref(), request.into_inner(), None).await
     }
 
@@ -85,6 +274,11 @@ impl Points for PointsService {
         &self,
         request: Request<SearchPoints>,
     ) -> Result<Response<SearchResponse>, Status> {
+        check_access(
+            &request,
+            Some(&request.get_ref().collection_name),
+            ApiTokenScope::Read,
+        )?;
         search(self.toc.as_ref(), request.into_inner(), None).await
     }
 
@@ -92,10 +286,18 @@ impl Points for PointsService {
         &self,
         request: Request<SearchBatchPoints>,
     ) -> Result<Response<SearchBatchResponse>, Status> {
+        check_access(
+            &request,
+            Some(&request.get_ref().collection_name),
+            ApiTokenScope::Read,
+        )?;
         let SearchBatchPoints {
             collection_name,
             search_points,
         } = request.into_inner();
+        self.limits
+            .check_batch_size(search_points.len())
+            .map_err(error_to_status)?;
         search_batch(self.toc.as_ref(), collection_name, search_points, None).await
     }
 
@@ -103,6 +305,11 @@ impl Points for PointsService {
         &self,
         request: Request<ScrollPoints>,
     ) -> Result<Response<ScrollResponse>, Status> {
+        check_access(
+            &request,
+            Some(&request.get_ref().collection_name),
+            ApiTokenScope::Read,
+        )?;
         scroll(self.toc.as_ref(), request.into_inner(), None).await
     }
 
@@ -110,6 +317,12 @@ impl Points for PointsService {
         &self,
         request: Request<RecommendPoints>,
     ) -> Result<Response<RecommendResponse>, Status> {
+        check_access(
+            &request,
+            Some(&request.get_ref().collection_name),
+            ApiTokenScope::Read,
+        )?;
+        check_lookup_access(&request, std::slice::from_ref(request.get_ref()))?;
         recommend(self.toc.as_ref(), request.into_inner(), None).await
     }
 
@@ -117,10 +330,19 @@ impl Points for PointsService {
         &self,
         request: Request<RecommendBatchPoints>,
     ) -> Result<Response<RecommendBatchResponse>, Status> {
+        check_access(
+            &request,
+            Some(&request.get_ref().collection_name),
+            ApiTokenScope::Read,
+        )?;
+        check_lookup_access(&request, &request.get_ref().recommend_points)?;
         let RecommendBatchPoints {
             collection_name,
             recommend_points,
         } = request.into_inner();
+        self.limits
+            .check_batch_size(recommend_points.len())
+            .map_err(error_to_status)?;
         recommend_batch(self.toc.as_ref(), collection_name, recommend_points, None).await
     }
 
@@ -128,15 +350,227 @@ impl Points for PointsService {
         &self,
         request: Request<CountPoints>,
     ) -> Result<Response<CountResponse>, Status> {
+        check_access(
+            &request,
+            Some(&request.get_ref().collection_name),
+            ApiTokenScope::Read,
+        )?;
         count(self.toc.as_ref(), request.into_inner(), None).await
     }
+
+    async fn facet(
+        &self,
+        request: Request<FacetPoints>,
+    ) -> Result<Response<FacetResponse>, Status> {
+        check_access(
+            &request,
+            Some(&request.get_ref().collection_name),
+            ApiTokenScope::Read,
+        )?;
+        facet(self.toc.as_ref(), request.into_inner(), None).await
+    }
+
+    type StreamChangesStream = PointsChangesStream;
+
+    async fn stream_changes(
+        &self,
+        request: Request<StreamPointsChanges>,
+    ) -> Result<Response<Self::StreamChangesStream>, Status> {
+        check_access(
+            &request,
+            Some(&request.get_ref().collection_name),
+            ApiTokenScope::Read,
+        )?;
+        stream_changes(self.toc.clone(), request.into_inner()).await
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::num::NonZeroU64;
+
+    use api::grpc::qdrant::{LookupLocation, PointId, PointStruct, Vectors};
+    use collection::config::VectorParams;
+    use collection::operations::types::CountRequest;
+    use collection::optimizers_builder::OptimizersConfig;
+    use segment::types::Distance;
+    use storage::content_manager::api_tokens::ApiToken;
+    use storage::content_manager::collection_meta_ops::{
+        CollectionMetaOperations, CreateCollection, CreateCollectionOperation,
+    };
+    use storage::types::{PerformanceConfig, StorageConfig};
+    use tempfile::Builder;
+    use tokio::runtime::Runtime;
+
+    use super::*;
+
     #[test]
     fn test_grpc() {
         // For running build from IDE
         eprintln!("hello");
     }
+
+    #[test]
+    fn test_lookup_access() {
+        let recommend = |lookup_collection: Option<&str>| RecommendPoints {
+            collection_name: "a".to_string(),
+            lookup_from: lookup_collection.map(|collection_name| LookupLocation {
+                collection_name: collection_name.to_string(),
+                vector_name: None,
+            }),
+            ..Default::default()
+        };
+
+        let mut request = Request::new(());
+        assert!(check_lookup_access(&request, &[recommend(Some("b"))]).is_ok());
+
+        request
+            .extensions_mut()
+            .insert(Access::Collection(ApiToken {
+                collection_name: "a".to_string(),
+                scope: ApiTokenScope::Read,
+            }));
+        assert!(check_lookup_access(&request, &[recommend(None), recommend(Some("a"))]).is_ok());
+
+        // Token of the collection does not grant access to the lookup collection
+        let status = check_lookup_access(&request, &[recommend(Some("a")), recommend(Some("b"))])
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+    }
+
+    fn upsert_points(collection_name: &str, ids: std::ops::Range<u64>) -> UpsertPoints {
+        UpsertPoints {
+            collection_name: collection_name.to_string(),
+            wait: Some(true),
+            points: ids
+                .map(|id| PointStruct {
+                    id: Some(PointId::from(segment::types::PointIdType::from(id))),
+                    payload: Default::default(),
+                    vectors: Some(Vectors::from(
+                        segment::data_types::vectors::VectorStruct::from(vec![
+                            id as f32, 1.0, 0.0, 0.0,
+                        ]),
+                    )),
+                })
+                .collect(),
+            ordering: None,
+            operation_id: None,
+        }
+    }
+
+    #[test]
+    fn test_upsert_stream() {
+        let storage_dir = Builder::new().prefix("storage").tempdir().unwrap();
+        let config = StorageConfig {
+            storage_path: storage_dir.path().to_str().unwrap().to_string(),
+            snapshots_path: storage_dir
+                .path()
+                .join("snapshots")
+                .to_str()
+                .unwrap()
+                .to_string(),
+            on_disk_payload: false,
+            optimizers: OptimizersConfig {
+                deleted_threshold: 0.5,
+                vacuum_min_vector_number: 100,
+                default_segment_number: 2,
+                max_segment_size: None,
+                memmap_threshold: None,
+                indexing_threshold: 10_000,
+                flush_interval_sec: 30,
+                max_optimization_threads: 1,
+            },
+            wal: Default::default(),
+            performance: PerformanceConfig {
+                max_search_threads: 1,
+                max_optimization_threads: 1,
+                search_memory_limit_mb: None,
+                mmap_advice: Default::default(),
+                mmap_populate: false,
+                slow_query_threshold_ms: None,
+                max_disk_searches: None,
+                update_queue_retry_threshold: None,
+                max_parallel_snapshot_shards: 0,
+                indexing_memory_limit_mb: None,
+            },
+            hnsw_index: Default::default(),
+            encryption: Default::default(),
+            strict_mode: Default::default(),
+            quotas: Default::default(),
+        };
+        let runtime = Runtime::new().unwrap();
+        let handle = runtime.handle().clone();
+        let toc = Arc::new(TableOfContent::new(
+            &config,
+            runtime,
+            Default::default(),
+            0,
+            None,
+        ));
+        handle
+            .block_on(
+                toc.perform_collection_meta_op(CollectionMetaOperations::CreateCollection(
+                    CreateCollectionOperation::new(
+                        "test".to_string(),
+                        CreateCollection {
+                            vectors: VectorParams {
+                                size: NonZeroU64::new(4).unwrap(),
+                                distance: Distance::Dot,
+                                hnsw_config: None,
+                                quantization_config: None,
+                                on_disk: None,
+                                datatype: None,
+                                multivector_config: None,
+                            }
+                            .into(),
+                            hnsw_config: None,
+                            wal_config: None,
+                            optimizers_config: None,
+                            shard_number: Some(1),
+                            on_disk_payload: None,
+                            replication_factor: None,
+                            write_consistency_factor: None,
+                            init_from: None,
+                            payload_field_types: None,
+                            auto_payload_index: None,
+                            ttl: None,
+                            default_vector: None,
+                        },
+                    ),
+                )),
+            )
+            .unwrap();
+
+        let requests = futures::stream::iter(vec![
+            Ok(upsert_points("test", 0..3)),
+            Ok(upsert_points("test", 3..6)),
+            Ok(upsert_points("missing", 6..9)),
+            Ok(upsert_points("test", 9..12)),
+        ]);
+        let responses: Vec<_> =
+            handle.block_on(upsert_stream_responses(toc.clone(), requests).collect());
+
+        // Each chunk is acknowledged, the stream ends with the first error
+        assert_eq!(responses.len(), 3);
+        assert!(responses[0].is_ok());
+        assert!(responses[1].is_ok());
+        assert_eq!(
+            responses[2].as_ref().unwrap_err().code(),
+            tonic::Code::NotFound
+        );
+
+        // Chunks after the error are not applied
+        let count = handle
+            .block_on(toc.count(
+                "test",
+                CountRequest {
+                    filter: None,
+                    exact: true,
+                },
+                None,
+            ))
+            .unwrap()
+            .count;
+        assert_eq!(count, 6);
+    }
 }
@@ -1,25 +1,32 @@
+use std::sync::Arc;
 use std::time::Instant;
 
 use api::grpc::conversions::proto_to_payloads;
 use api::grpc::qdrant::payload_index_params::IndexParams;
+use api::grpc::qdrant::points_update_operation::Operation;
 use api::grpc::qdrant::{
-    BatchResult, ClearPayloadPoints, CountPoints, CountResponse, CreateFieldIndexCollection,
-    DeleteFieldIndexCollection, DeletePayloadPoints, DeletePoints, FieldType, GetPoints,
-    GetResponse, PayloadIndexParams, PointsOperationResponse, RecommendBatchResponse,
-    RecommendPoints, RecommendResponse, ScrollPoints, ScrollResponse, SearchBatchResponse,
-    SearchPoints, SearchResponse, SetPayloadPoints, SyncPoints, UpsertPoints,
+    points_update_operation, BatchResult, ClearPayloadPoints, CountPoints, CountResponse,
+    CreateFieldIndexCollection, DeleteFieldIndexCollection, DeletePayloadPoints, DeletePoints,
+    DiscoverPointsInternal, FacetPoints, FacetResponse, FieldType, GetPoints, GetResponse,
+    PayloadIndexParams, PointDigestsInternal, PointDigestsResponse, PointsChange,
+    PointsOperationResponse, PointsUpdateOperation, ReadChangesInternal, ReadChangesResponse,
+    RecommendBatchResponse, RecommendPoints, RecommendResponse, ScrollPoints, ScrollResponse,
+    SearchBatchResponse, SearchPoints, SearchResponse, SetPayloadPoints, StreamPointsChanges,
+    SyncPoints, UpdateBatchPoints, UpdateBatchResponse, UpdatePayloadPoints, UpsertPoints,
 };
-use collection::operations::payload_ops::DeletePayload;
+use collection::operations::payload_ops::{DeletePayload, PayloadOps, SetPayload, UpdatePayload};
 use collection::operations::point_ops::{
-    PointInsertOperations, PointOperations, PointSyncOperation,
+    PointInsertOperations, PointOperations, PointSyncOperation, PointsSelector, WriteOrdering,
 };
 use collection::operations::types::{
     default_exact_count, PointRequest, RecommendRequestBatch, ScrollRequest, SearchRequest,
-    SearchRequestBatch,
+    SearchRequestBatch, ShardDiscoverRequest,
 };
 use collection::operations::CollectionUpdateOperations;
 use collection::shards::shard::ShardId;
-use segment::data_types::vectors::NamedVector;
+use futures::StreamExt;
+use segment::data_types::discovery::{ContextPair, DiscoveryQuery};
+use segment::data_types::vectors::{NamedVector, DEFAULT_VECTOR_NAME};
 use segment::types::{PayloadFieldSchema, PayloadSchemaParams, PayloadSchemaType};
 use storage::content_manager::conversions::error_to_status;
 use storage::content_manager::toc::TableOfContent;
@@ -27,9 +34,11 @@ use tonic::{Response, Status};
 
 use crate::common::points::{
     do_clear_payload, do_count_points, do_create_index, do_delete_index, do_delete_payload,
-    do_delete_points, do_get_points, do_scroll_points, do_search_batch_points, do_search_points,
-    do_set_payload, do_upsert_points, CreateFieldIndex,
+    do_delete_points, do_facet_points, do_get_points, do_scroll_points, do_search_batch_points,
+    do_search_points, do_set_payload, do_stream_points_changes, do_update_payload,
+    do_upsert_points, CreateFieldIndex,
 };
+use crate::tonic::api::points_api::PointsChangesStream;
 
 pub fn points_operation_response(
     timing: Instant,
@@ -41,6 +50,14 @@ pub fn points_operation_response(
     }
 }
 
+/// Missing ordering means `weak` ordering, which is also used for internal replication requests
+fn write_ordering_from_proto(ordering: Option<i32>) -> Result<WriteOrdering, Status> {
+    ordering
+        .map(WriteOrdering::try_from)
+        .transpose()
+        .map(Option::unwrap_or_default)
+}
+
 pub async fn upsert(
     toc: &TableOfContent,
     upsert_points: UpsertPoints,
@@ -50,6 +67,8 @@ pub async fn upsert(
         collection_name,
         wait,
         points,
+        ordering,
+        operation_id,
     } = upsert_points;
     let points = points
         .into_iter()
@@ -61,8 +80,10 @@ pub async fn upsert(
         toc,
         &collection_name,
         operation,
+        operation_id,
         shard_selection,
         wait.unwrap_or(false),
+        write_ordering_from_proto(ordering)?,
     )
     .await
     .map_err(error_to_status)?;
@@ -71,6 +92,111 @@ pub async fn upsert(
     Ok(Response::new(response))
 }
 
+/// Convert the operation of the batch, so invalid batches are rejected before anything is applied
+fn batch_operation_to_internal(
+    operation: PointsUpdateOperation,
+) -> Result<CollectionUpdateOperations, Status> {
+    let operation = operation
+        .operation
+        .ok_or_else(|| Status::invalid_argument("Operation is missing"))?;
+    let collection_operation = match operation {
+        Operation::Upsert(points_update_operation::PointStructList { points }) => {
+            let points = points
+                .into_iter()
+                .map(|point| point.try_into())
+                .collect::<Result<_, _>>()?;
+            CollectionUpdateOperations::PointOperation(PointOperations::UpsertPoints(
+                PointInsertOperations::PointsList(points),
+            ))
+        }
+        Operation::Delete(points) => {
+            let points: PointsSelector = points.try_into()?;
+            let point_operation = match points {
+                PointsSelector::PointIdsSelector(points) => {
+                    PointOperations::DeletePoints { ids: points.points }
+                }
+                PointsSelector::FilterSelector(filter_selector) => {
+                    PointOperations::DeletePointsByFilter(filter_selector.filter)
+                }
+            };
+            CollectionUpdateOperations::PointOperation(point_operation)
+        }
+        Operation::SetPayload(points_update_operation::SetPayload { payload, points }) => {
+            CollectionUpdateOperations::PayloadOperation(PayloadOps::SetPayload(SetPayload {
+                payload: proto_to_payloads(payload)?,
+                points: points
+                    .into_iter()
+                    .map(|p| p.try_into())
+                    .collect::<Result<_, _>>()?,
+            }))
+        }
+        Operation::DeletePayload(points_update_operation::DeletePayload { keys, points }) => {
+            CollectionUpdateOperations::PayloadOperation(PayloadOps::DeletePayload(DeletePayload {
+                keys,
+                points: points
+                    .into_iter()
+                    .map(|p| p.try_into())
+                    .collect::<Result<_, _>>()?,
+            }))
+        }
+        Operation::ClearPayload(points) => {
+            let points: PointsSelector = points.try_into()?;
+            let payload_operation = match points {
+                PointsSelector::PointIdsSelector(points) => PayloadOps::ClearPayload {
+                    points: points.points,
+                },
+                PointsSelector::FilterSelector(filter_selector) => {
+                    PayloadOps::ClearPayloadByFilter(filter_selector.filter)
+                }
+            };
+            CollectionUpdateOperations::PayloadOperation(payload_operation)
+        }
+    };
+    Ok(collection_operation)
+}
+
+pub async fn update_batch(
+    toc: &TableOfContent,
+    update_batch_points: UpdateBatchPoints,
+    shard_selection: Option<ShardId>,
+) -> Result<Response<UpdateBatchResponse>, Status> {
+    let UpdateBatchPoints {
+        collection_name,
+        wait,
+        operations,
+        ordering,
+    } = update_batch_points;
+
+    let operations = operations
+        .into_iter()
+        .map(batch_operation_to_internal)
+        .collect::<Result<Vec<_>, _>>()?;
+    let ordering = write_ordering_from_proto(ordering)?;
+
+    let timing = Instant::now();
+    let mut results = Vec::with_capacity(operations.len());
+    // Operations are applied one by one, so each of them is ordered after the previous one
+    for operation in operations {
+        let result = toc
+            .update(
+                &collection_name,
+                operation,
+                shard_selection,
+                wait.unwrap_or(false),
+                ordering,
+            )
+            .await
+            .map_err(error_to_status)?;
+        results.push(result.into());
+    }
+
+    let response = UpdateBatchResponse {
+        result: results,
+        time: timing.elapsed().as_secs_f64(),
+    };
+    Ok(Response::new(response))
+}
+
 pub async fn sync(
     toc: &TableOfContent,
     sync_points: SyncPoints,
@@ -104,6 +230,7 @@ pub async fn sync(
             collection_operation,
             Some(shard_selection),
             wait.unwrap_or(false),
+            WriteOrdering::default(),
         )
         .await
         .map_err(error_to_status)?;
@@ -121,6 +248,8 @@ pub async fn delete(
         collection_name,
         wait,
         points,
+        ordering,
+        operation_id,
     } = delete_points;
 
     let points_selector = match points {
@@ -133,8 +262,10 @@ pub async fn delete(
         toc,
         &collection_name,
         points_selector,
+        operation_id,
         shard_selection,
         wait.unwrap_or(false),
+        write_ordering_from_proto(ordering)?,
     )
     .await
     .map_err(error_to_status)?;
@@ -153,6 +284,7 @@ pub async fn set_payload(
         wait,
         payload,
         points,
+        ordering,
     } = set_payload_points;
 
     let operation = collection::operations::payload_ops::SetPayload {
@@ -170,6 +302,47 @@ pub async fn set_payload(
         operation,
         shard_selection,
         wait.unwrap_or(false),
+        write_ordering_from_proto(ordering)?,
+    )
+    .await
+    .map_err(error_to_status)?;
+
+    let response = points_operation_response(timing, result);
+    Ok(Response::new(response))
+}
+
+pub async fn update_payload(
+    toc: &TableOfContent,
+    update_payload_points: UpdatePayloadPoints,
+    shard_selection: Option<ShardId>,
+) -> Result<Response<PointsOperationResponse>, Status> {
+    let UpdatePayloadPoints {
+        collection_name,
+        wait,
+        updates,
+        points,
+        ordering,
+    } = update_payload_points;
+
+    let operation = UpdatePayload {
+        updates: updates
+            .into_iter()
+            .map(|update| update.try_into())
+            .collect::<Result<_, _>>()?,
+        points: points
+            .into_iter()
+            .map(|p| p.try_into())
+            .collect::<Result<_, _>>()?,
+    };
+
+    let timing = Instant::now();
+    let result = do_update_payload(
+        toc,
+        &collection_name,
+        operation,
+        shard_selection,
+        wait.unwrap_or(false),
+        write_ordering_from_proto(ordering)?,
     )
     .await
     .map_err(error_to_status)?;
@@ -188,6 +361,7 @@ pub async fn delete_payload(
         wait,
         keys,
         points,
+        ordering,
     } = delete_payload_points;
 
     let operation = DeletePayload {
@@ -205,6 +379,7 @@ pub async fn delete_payload(
         operation,
         shard_selection,
         wait.unwrap_or(false),
+        write_ordering_from_proto(ordering)?,
     )
     .await
     .map_err(error_to_status)?;
@@ -222,6 +397,7 @@ pub async fn clear_payload(
         collection_name,
         wait,
         points,
+        ordering,
     } = clear_payload_points;
 
     let points_selector = match points {
@@ -236,6 +412,7 @@ pub async fn clear_payload(
         points_selector,
         shard_selection,
         wait.unwrap_or(false),
+        write_ordering_from_proto(ordering)?,
     )
     .await
     .map_err(error_to_status)?;
@@ -255,6 +432,7 @@ pub async fn create_field_index(
         field_name,
         field_type,
         field_index_params,
+        ordering,
     } = create_field_index_collection;
 
     let filed_type_parsed = field_type
@@ -277,12 +455,28 @@ pub async fn create_field_index(
                 ))
             }
         },
+        (
+            Some(v),
+            Some(PayloadIndexParams {
+                index_params: Some(IndexParams::KeywordIndexParams(keyword_index_params)),
+            }),
+        ) => match v {
+            FieldType::Keyword => Some(PayloadFieldSchema::FieldParams(
+                PayloadSchemaParams::Keyword(keyword_index_params.into()),
+            )),
+            _ => {
+                return Err(Status::invalid_argument(
+                    "field_type and field_index_params do not match",
+                ))
+            }
+        },
         (Some(v), None | Some(PayloadIndexParams { index_params: None })) => match v {
             FieldType::Keyword => Some(PayloadSchemaType::Keyword.into()),
             FieldType::Integer => Some(PayloadSchemaType::Integer.into()),
             FieldType::Float => Some(PayloadSchemaType::Float.into()),
             FieldType::Geo => Some(PayloadSchemaType::Geo.into()),
             FieldType::Text => Some(PayloadSchemaType::Text.into()),
+            FieldType::Datetime => Some(PayloadSchemaType::Datetime.into()),
         },
         (None, Some(_)) => return Err(Status::invalid_argument("field type is missing")),
         (None, None) => None,
@@ -300,6 +494,7 @@ pub async fn create_field_index(
         operation,
         shard_selection,
         wait.unwrap_or(false),
+        write_ordering_from_proto(ordering)?,
     )
     .await
     .map_err(error_to_status)?;
@@ -317,6 +512,7 @@ pub async fn delete_field_index(
         collection_name,
         wait,
         field_name,
+        ordering,
     } = delete_field_index_collection;
 
     let timing = Instant::now();
@@ -326,6 +522,7 @@ pub async fn delete_field_index(
         field_name,
         shard_selection,
         wait.unwrap_or(false),
+        write_ordering_from_proto(ordering)?,
     )
     .await
     .map_err(error_to_status)?;
@@ -371,9 +568,11 @@ pub async fn search(
     };
 
     let timing = Instant::now();
-    let scored_points = do_search_points(toc, &collection_name, search_request, shard_selection)
-        .await
-        .map_err(error_to_status)?;
+    let explain = search_request.explain();
+    let (scored_points, truncated, explanation) =
+        do_search_points(toc, &collection_name, search_request, shard_selection)
+            .await
+            .map_err(error_to_status)?;
 
     let response = SearchResponse {
         result: scored_points
@@ -381,6 +580,8 @@ pub async fn search(
             .map(|point| point.into())
             .collect(),
         time: timing.elapsed().as_secs_f64(),
+        truncated,
+        explanation: explain.then(|| explanation.into()),
     };
 
     Ok(Response::new(response))
@@ -401,6 +602,7 @@ pub async fn search_batch(
     };
 
     let timing = Instant::now();
+    let explain = search_requests.explain();
     let scored_points =
         do_search_batch_points(toc, &collection_name, search_requests, shard_selection)
             .await
@@ -408,12 +610,15 @@ pub async fn search_batch(
 
     let response = SearchBatchResponse {
         result: scored_points
+            .results
             .into_iter()
             .map(|points| BatchResult {
                 result: points.into_iter().map(|p| p.into()).collect(),
             })
             .collect(),
         time: timing.elapsed().as_secs_f64(),
+        truncated: scored_points.truncated,
+        explanation: explain.then(|| scored_points.explanation.into()),
     };
 
     Ok(Response::new(response))
@@ -436,6 +641,7 @@ pub async fn recommend(
         score_threshold,
         using,
         with_vectors,
+        lookup_from,
     } = recommend_points;
 
     let request = collection::operations::types::RecommendRequest {
@@ -459,6 +665,7 @@ pub async fn recommend(
         ),
         score_threshold,
         using: using.map(|u| u.into()),
+        lookup_from: lookup_from.map(|location| location.into()),
     };
 
     let timing = Instant::now();
@@ -523,6 +730,8 @@ pub async fn scroll(
         limit,
         with_payload,
         with_vectors,
+        order_by,
+        sample,
     } = scroll_points;
 
     let scroll_request = ScrollRequest {
@@ -533,6 +742,8 @@ pub async fn scroll(
         with_vector: with_vectors
             .map(|selector| selector.into())
             .unwrap_or_default(),
+        order_by: order_by.map(|o| o.try_into()).transpose()?,
+        sample: sample.map(|s| s.try_into()).transpose()?,
     };
 
     let timing = Instant::now();
@@ -548,6 +759,7 @@ pub async fn scroll(
             .map(|point| point.into())
             .collect(),
         time: timing.elapsed().as_secs_f64(),
+        next_page_order_value: scrolled_points.next_page_order_value,
     };
 
     Ok(Response::new(response))
@@ -582,6 +794,175 @@ pub async fn count(
     Ok(Response::new(response))
 }
 
+pub async fn facet(
+    toc: &TableOfContent,
+    facet_points: FacetPoints,
+    shard_selection: Option<ShardId>,
+) -> Result<Response<FacetResponse>, Status> {
+    let collection_name = facet_points.collection_name.clone();
+    let facet_request = facet_points.try_into()?;
+
+    let timing = Instant::now();
+    let facet_response = do_facet_points(toc, &collection_name, facet_request, shard_selection)
+        .await
+        .map_err(error_to_status)?;
+
+    let response = FacetResponse {
+        hits: facet_response
+            .hits
+            .into_iter()
+            .map(|hit| hit.into())
+            .collect(),
+        time: timing.elapsed().as_secs_f64(),
+    };
+
+    Ok(Response::new(response))
+}
+
+pub async fn stream_changes(
+    toc: Arc<TableOfContent>,
+    stream_points_changes: StreamPointsChanges,
+) -> Result<Response<PointsChangesStream>, Status> {
+    let StreamPointsChanges {
+        collection_name,
+        shard_id,
+        from,
+        limit,
+    } = stream_points_changes;
+
+    let changes = do_stream_points_changes(
+        toc,
+        collection_name,
+        shard_id,
+        from,
+        limit.map(|limit| limit as usize),
+    )
+    .await
+    .map_err(error_to_status)?;
+
+    let changes = changes.map(|change| PointsChange::try_from(change.map_err(error_to_status)?));
+    Ok(Response::new(Box::pin(changes)))
+}
+
+pub async fn read_changes(
+    toc: &TableOfContent,
+    read_changes: ReadChangesInternal,
+) -> Result<Response<ReadChangesResponse>, Status> {
+    let ReadChangesInternal {
+        collection_name,
+        shard_id,
+        from,
+        limit,
+    } = read_changes;
+
+    let timing = Instant::now();
+    let changes = toc
+        .read_local_points_changes(&collection_name, shard_id, from, limit as usize)
+        .await
+        .map_err(error_to_status)?;
+
+    let response = ReadChangesResponse {
+        changes: changes
+            .changes
+            .into_iter()
+            .map(PointsChange::try_from)
+            .collect::<Result<_, _>>()?,
+        next_op_num: changes.next_op_num,
+        time: timing.elapsed().as_secs_f64(),
+    };
+
+    Ok(Response::new(response))
+}
+
+pub async fn discover_internal(
+    toc: &TableOfContent,
+    discover_points: DiscoverPointsInternal,
+) -> Result<Response<SearchResponse>, Status> {
+    let DiscoverPointsInternal {
+        collection_name,
+        shard_id,
+        vector_name,
+        target,
+        context,
+        filter,
+        params,
+        limit,
+        with_payload,
+        with_vectors,
+    } = discover_points;
+
+    let request = ShardDiscoverRequest {
+        vector_name: vector_name.unwrap_or_else(|| DEFAULT_VECTOR_NAME.to_owned()),
+        query: DiscoveryQuery {
+            target,
+            pairs: context
+                .into_iter()
+                .map(|pair| ContextPair {
+                    positive: pair.positive,
+                    negative: pair.negative,
+                })
+                .collect(),
+        },
+        filter: filter.map(|f| f.try_into()).transpose()?,
+        params: params.map(|p| p.into()),
+        limit: limit as usize,
+        with_payload: with_payload.map(|wp| wp.try_into()).transpose()?,
+        with_vector: with_vectors
+            .map(|selector| selector.into())
+            .unwrap_or_default(),
+    };
+
+    let timing = Instant::now();
+    let scored_points = toc
+        .discover_in_shard(&collection_name, request, shard_id)
+        .await
+        .map_err(error_to_status)?;
+
+    let response = SearchResponse {
+        result: scored_points
+            .into_iter()
+            .map(|point| point.into())
+            .collect(),
+        time: timing.elapsed().as_secs_f64(),
+        truncated: false,
+        explanation: None,
+    };
+
+    Ok(Response::new(response))
+}
+
+pub async fn point_digests(
+    toc: &TableOfContent,
+    point_digests: PointDigestsInternal,
+) -> Result<Response<PointDigestsResponse>, Status> {
+    let PointDigestsInternal {
+        collection_name,
+        shard_id,
+        from,
+        to,
+        limit,
+    } = point_digests;
+
+    let timing = Instant::now();
+    let digests = toc
+        .local_point_digests(
+            &collection_name,
+            shard_id,
+            from.map(TryInto::try_into).transpose()?,
+            to.map(TryInto::try_into).transpose()?,
+            limit.map(|limit| limit as usize),
+        )
+        .await
+        .map_err(error_to_status)?;
+
+    let response = PointDigestsResponse {
+        digests: digests.into_iter().map(Into::into).collect(),
+        time: timing.elapsed().as_secs_f64(),
+    };
+
+    Ok(Response::new(response))
+}
+
 pub async fn get(
     toc: &TableOfContent,
     get_points: GetPoints,
@@ -4,17 +4,20 @@ use api::grpc::qdrant::points_internal_server::PointsInternal;
 use api::grpc::qdrant::{
     ClearPayloadPointsInternal, CountPointsInternal, CountResponse,
     CreateFieldIndexCollectionInternal, DeleteFieldIndexCollectionInternal,
-    DeletePayloadPointsInternal, DeletePointsInternal, GetPointsInternal, GetResponse,
-    PointsOperationResponse, RecommendPointsInternal, RecommendResponse, ScrollPointsInternal,
-    ScrollResponse, SearchBatchPointsInternal, SearchBatchResponse, SearchPointsInternal,
-    SearchResponse, SetPayloadPointsInternal, SyncPointsInternal, UpsertPointsInternal,
+    DeletePayloadPointsInternal, DeletePointsInternal, DiscoverPointsInternal, FacetPointsInternal,
+    FacetResponse, GetPointsInternal, GetResponse, PointDigestsInternal, PointDigestsResponse,
+    PointsOperationResponse, ReadChangesInternal, ReadChangesResponse, RecommendPointsInternal,
+    RecommendResponse, ScrollPointsInternal, ScrollResponse, SearchBatchPointsInternal,
+    SearchBatchResponse, SearchPointsInternal, SearchResponse, SetPayloadPointsInternal,
+    SyncPointsInternal, UpdatePayloadPointsInternal, UpsertPointsInternal,
 };
 use storage::content_manager::toc::TableOfContent;
 use tonic::{Request, Response, Status};
 
 use crate::tonic::api::points_common::{
-    clear_payload, count, create_field_index, delete, delete_field_index, delete_payload, get,
-    recommend, scroll, search, search_batch, set_payload, sync, upsert,
+    clear_payload, count, create_field_index, delete, delete_field_index, delete_payload,
+    discover_internal, facet, get, point_digests, read_changes, recommend, scroll, search,
+    search_batch, set_payload, sync, update_payload, upsert,
 };
 
 /// This API is intended for P2P communication within a distributed deployment.
@@ -75,6 +78,21 @@ impl PointsInternal for PointsInternalService {
         set_payload(self.toc.as_ref(), set_payload_points, Some(shard_id)).await
     }
 
+    async fn update_payload(
+        &self,
+        request: Request<UpdatePayloadPointsInternal>,
+    ) -> Result<Response<PointsOperationResponse>, Status> {
+        let UpdatePayloadPointsInternal {
+            update_payload_points,
+            shard_id,
+        } = request.into_inner();
+
+        let update_payload_points = update_payload_points
+            .ok_or_else(|| Status::invalid_argument("UpdatePayloadPoints is missing"))?;
+
+        update_payload(self.toc.as_ref(), update_payload_points, Some(shard_id)).await
+    }
+
     async fn delete_payload(
         &self,
         request: Request<DeletePayloadPointsInternal>,
@@ -224,6 +242,27 @@ impl PointsInternal for PointsInternalService {
         get(self.toc.as_ref(), get_points, Some(shard_id)).await
     }
 
+    async fn read_changes(
+        &self,
+        request: Request<ReadChangesInternal>,
+    ) -> Result<Response<ReadChangesResponse>, Status> {
+        read_changes(self.toc.as_ref(), request.into_inner()).await
+    }
+
+    async fn discover(
+        &self,
+        request: Request<DiscoverPointsInternal>,
+    ) -> Result<Response<SearchResponse>, Status> {
+        discover_internal(self.toc.as_ref(), request.into_inner()).await
+    }
+
+    async fn point_digests(
+        &self,
+        request: Request<PointDigestsInternal>,
+    ) -> Result<Response<PointDigestsResponse>, Status> {
+        point_digests(self.toc.as_ref(), request.into_inner()).await
+    }
+
     async fn count(
         &self,
         request: Request<CountPointsInternal>,
@@ -238,6 +277,20 @@ impl PointsInternal for PointsInternalService {
         count(self.toc.as_ref(), count_points, Some(shard_id)).await
     }
 
+    async fn facet(
+        &self,
+        request: Request<FacetPointsInternal>,
+    ) -> Result<Response<FacetResponse>, Status> {
+        let FacetPointsInternal {
+            facet_points,
+            shard_id,
+        } = request.into_inner();
+
+        let facet_points =
+            facet_points.ok_or_else(|| Status::invalid_argument("FacetPoints is missing"))?;
+        facet(self.toc.as_ref(), facet_points, Some(shard_id)).await
+    }
+
     async fn sync(
         &self,
         request: Request<SyncPointsInternal>,
@@ -1,3 +1,4 @@
+use std::net::SocketAddr;
 use std::sync::mpsc::SyncSender;
 use std::sync::Mutex;
 
@@ -73,23 +74,30 @@ impl Raft for RaftService {
             let port = peer
                 .port
                 .ok_or_else(|| Status::invalid_argument("URI or port should be supplied"))?;
-            format!("http://{ip}:{port}")
+            let port = u16::try_from(port)
+                .map_err(|_| Status::invalid_argument(format!("Invalid port {port}")))?;
+            // Formatted as a socket address, so IPv6 addresses are enclosed in brackets
+            format!("http://{}", SocketAddr::new(ip, port))
         };
         let uri: Uri = uri_string
             .parse()
             .map_err(|err| Status::internal(format!("Failed to parse uri: {err}")))?;
         let peer = request.into_inner();
 
+        let operation = if peer.learner {
+            ConsensusOperations::AddLearner {
+                peer_id: peer.id,
+                uri: uri.to_string(),
+            }
+        } else {
+            ConsensusOperations::AddPeer {
+                peer_id: peer.id,
+                uri: uri.to_string(),
+            }
+        };
         // the consensus operation can take up to DEFAULT_META_OP_WAIT
         self.consensus_state
-            .propose_consensus_op_with_await(
-                ConsensusOperations::AddPeer {
-                    peer_id: peer.id,
-                    uri: uri.to_string(),
-                },
-                None,
-                true,
-            )
+            .propose_consensus_op_with_await(operation, None, None, true)
             .await
             .map_err(|err| Status::internal(format!("Failed to add peer: {err}")))?;
         let addresses = self.consensus_state.peer_address_by_id();
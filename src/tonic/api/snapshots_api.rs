@@ -6,12 +6,14 @@ use api::grpc::qdrant::{
     CreateFullSnapshotRequest, CreateSnapshotRequest, CreateSnapshotResponse,
     ListFullSnapshotsRequest, ListSnapshotsRequest, ListSnapshotsResponse,
 };
+use storage::content_manager::api_tokens::ApiTokenScope;
 use storage::content_manager::conversions::error_to_status;
 use storage::content_manager::snapshots::{do_create_full_snapshot, do_list_full_snapshots};
 use storage::content_manager::toc::TableOfContent;
 use tonic::{async_trait, Request, Response, Status};
 
 use crate::common::collections::{do_create_snapshot, do_list_snapshots};
+use crate::tonic::auth::check_access;
 
 pub struct SnapshotsService {
     toc: Arc<TableOfContent>,
@@ -29,9 +31,17 @@ impl Snapshots for SnapshotsService {
         &self,
         request: Request<CreateSnapshotRequest>,
     ) -> Result<Response<CreateSnapshotResponse>, Status> {
-        let collection_name = request.into_inner().collection_name;
+        check_access(
+            &request,
+            Some(&request.get_ref().collection_name),
+            ApiTokenScope::Manage,
+        )?;
+        let CreateSnapshotRequest {
+            collection_name,
+            base,
+        } = request.into_inner();
         let timing = Instant::now();
-        let response = do_create_snapshot(&self.toc, &collection_name)
+        let response = do_create_snapshot(&self.toc, &collection_name, base.as_deref())
             .await
             .map_err(error_to_status)?;
         Ok(Response::new(CreateSnapshotResponse {
@@ -44,6 +54,11 @@ impl Snapshots for SnapshotsService {
         &self,
         request: Request<ListSnapshotsRequest>,
     ) -> Result<Response<ListSnapshotsResponse>, Status> {
+        check_access(
+            &request,
+            Some(&request.get_ref().collection_name),
+            ApiTokenScope::Manage,
+        )?;
         let collection_name = request.into_inner().collection_name;
 
         let timing = Instant::now();
@@ -58,8 +73,9 @@ impl Snapshots for SnapshotsService {
 
     async fn create_full(
         &self,
-        _request: Request<CreateFullSnapshotRequest>,
+        request: Request<CreateFullSnapshotRequest>,
     ) -> Result<Response<CreateSnapshotResponse>, Status> {
+        check_access(&request, None, ApiTokenScope::Manage)?;
         let timing = Instant::now();
         let response = do_create_full_snapshot(&self.toc)
             .await
@@ -72,8 +88,9 @@ impl Snapshots for SnapshotsService {
 
     async fn list_full(
         &self,
-        _request: Request<ListFullSnapshotsRequest>,
+        request: Request<ListFullSnapshotsRequest>,
     ) -> Result<Response<ListSnapshotsResponse>, Status> {
+        check_access(&request, None, ApiTokenScope::Manage)?;
         let timing = Instant::now();
         let snapshots = do_list_full_snapshots(&self.toc)
             .await
@@ -0,0 +1,435 @@
+use std::collections::{HashSet, VecDeque};
+use std::sync::Arc;
+
+use api::grpc::peer_channel::{
+    unix_timestamp, PeerRequestDigest, PeerSecret, PEER_ID_HEADER, PEER_NONCE_HEADER,
+    PEER_SIGNATURE_HEADER, PEER_SIGNATURE_TTL_SEC, PEER_TIMESTAMP_HEADER,
+};
+use collection::shards::shard::PeerId;
+use parking_lot::Mutex;
+use storage::content_manager::api_tokens::{Access, ApiTokenScope, ClientIdentity};
+use storage::content_manager::consensus_state::ConsensusStateRef;
+use storage::content_manager::conversions::error_to_status;
+use storage::dispatcher::Dispatcher;
+use tonic::service::Interceptor;
+use tonic::{Request, Status};
+
+use crate::common::auth::{bearer_token, API_KEY_HEADER, AUTHORIZATION_HEADER};
+
+/// Resolves access of the request from its API key or bearer token.
+/// Access to the collection is checked by the handlers, as only they know the requested collection.
+#[derive(Clone)]
+pub struct AuthInterceptor {
+    dispatcher: Arc<Dispatcher>,
+}
+
+impl AuthInterceptor {
+    pub fn new(dispatcher: Arc<Dispatcher>) -> Self {
+        Self { dispatcher }
+    }
+}
+
+impl Interceptor for AuthInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        let api_key = request
+            .metadata()
+            .get(API_KEY_HEADER)
+            .and_then(|value| value.to_str().ok());
+        let bearer_token = request
+            .metadata()
+            .get(AUTHORIZATION_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(bearer_token);
+        let (access, identity) = self
+            .dispatcher
+            .resolve_access(api_key, bearer_token)
+            .map_err(error_to_status)?;
+        request.extensions_mut().insert(access);
+        if let Some(identity) = identity {
+            request.extensions_mut().insert(identity);
+        }
+        Ok(request)
+    }
+}
+
+/// Check, if access of the request, resolved by [`AuthInterceptor`], allows the operation.
+/// Requests, which did not pass the interceptor, are not restricted.
+pub fn check_access<T>(
+    request: &Request<T>,
+    collection_name: Option<&str>,
+    scope: ApiTokenScope,
+) -> Result<(), Status> {
+    match request.extensions().get::<Access>() {
+        None => Ok(()),
+        Some(access) => access
+            .check(collection_name, scope)
+            .map_err(error_to_status),
+    }
+}
+
+/// Client, which sent the request, resolved by [`AuthInterceptor`].
+/// Unknown, if authentication is disabled.
+pub fn client_identity<T>(request: &Request<T>) -> Option<ClientIdentity> {
+    request.extensions().get::<ClientIdentity>().cloned()
+}
+
+/// Authenticates requests of the internal gRPC API by the signature of the peer id, made with
+/// the secret, shared by the peers. If no secret is configured - requests are not restricted.
+/// Signature also covers the method and the body of the request, digested by the
+/// `PeerTransportLayer`, which should be applied to the server.
+#[derive(Clone)]
+pub struct PeerAuthInterceptor {
+    secret: Option<PeerSecret>,
+    /// If set - only the current members of the cluster are accepted
+    consensus_state: Option<ConsensusStateRef>,
+    /// Shared by the clones of the interceptor, so a request can't be replayed to another service
+    seen_nonces: Arc<Mutex<SeenNonces>>,
+}
+
+impl PeerAuthInterceptor {
+    pub fn new(secret: Option<PeerSecret>) -> Self {
+        Self {
+            secret,
+            consensus_state: None,
+            seen_nonces: Default::default(),
+        }
+    }
+
+    /// Also reject requests of the peers, which are not members of the cluster,
+    /// e.g. removed ones. Not applicable to consensus messages of the joining peers.
+    pub fn with_membership(self, consensus_state: ConsensusStateRef) -> Self {
+        Self {
+            consensus_state: Some(consensus_state),
+            ..self
+        }
+    }
+
+    /// Returns id of the peer, which signed the request
+    fn authenticate(
+        &self,
+        secret: &PeerSecret,
+        request: &Request<()>,
+        now: u64,
+    ) -> Result<PeerId, Status> {
+        let header = |name: &str| {
+            request
+                .metadata()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .ok_or_else(|| {
+                    Status::unauthenticated(format!("Peer-to-peer request has no `{name}` header"))
+                })
+        };
+        let peer_id = header(PEER_ID_HEADER)?;
+        let timestamp = header(PEER_TIMESTAMP_HEADER)?;
+        let nonce = header(PEER_NONCE_HEADER)?;
+        let digest = request
+            .extensions()
+            .get::<PeerRequestDigest>()
+            .ok_or_else(|| Status::internal("Peer-to-peer request was not digested"))?;
+        if !secret.verify(
+            peer_id,
+            timestamp,
+            nonce,
+            digest,
+            header(PEER_SIGNATURE_HEADER)?,
+        ) {
+            return Err(Status::unauthenticated(
+                "Invalid peer-to-peer signature, all peers must use the same secret",
+            ));
+        }
+
+        let timestamp: u64 = timestamp
+            .parse()
+            .map_err(|_| Status::unauthenticated("Invalid peer-to-peer request timestamp"))?;
+        if timestamp.abs_diff(now) > PEER_SIGNATURE_TTL_SEC {
+            return Err(Status::unauthenticated(format!(
+                "Peer-to-peer request is outdated, clocks of the peers must not differ \
+                 by more than {PEER_SIGNATURE_TTL_SEC} seconds"
+            )));
+        }
+        if !self.seen_nonces.lock().insert(nonce, timestamp, now) {
+            return Err(Status::unauthenticated(
+                "Peer-to-peer request was already received",
+            ));
+        }
+        peer_id
+            .parse()
+            .map_err(|_| Status::unauthenticated("Invalid peer id"))
+    }
+}
+
+impl Interceptor for PeerAuthInterceptor {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        let secret = match &self.secret {
+            Some(secret) => secret,
+            None => return Ok(request),
+        };
+        // Peer id is signed, so it could be trusted
+        let peer_id = self.authenticate(secret, &request, unix_timestamp())?;
+
+        if let Some(consensus_state) = &self.consensus_state {
+            if !consensus_state.peer_address_by_id().contains_key(&peer_id) {
+                return Err(Status::permission_denied(format!(
+                    "Peer {peer_id} is not a member of the cluster"
+                )));
+            }
+        }
+        Ok(request)
+    }
+}
+
+/// Nonces of the recently authenticated requests
+#[derive(Default)]
+struct SeenNonces {
+    nonces: HashSet<String>,
+    /// Nonces with timestamps of their requests, in the order of authentication
+    queue: VecDeque<(u64, String)>,
+}
+
+impl SeenNonces {
+    /// Returns false, if the nonce was already seen
+    fn insert(&mut self, nonce: &str, timestamp: u64, now: u64) -> bool {
+        // Requests, older than the TTL, are rejected anyway, so their nonces are not needed
+        while let Some((oldest, _)) = self.queue.front() {
+            if oldest + PEER_SIGNATURE_TTL_SEC >= now {
+                break;
+            }
+            if let Some((_, expired)) = self.queue.pop_front() {
+                self.nonces.remove(&expired);
+            }
+        }
+        if !self.nonces.insert(nonce.to_string()) {
+            return false;
+        }
+        self.queue.push_back((timestamp, nonce.to_string()));
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use collection::shards::channel_service::ChannelService;
+    use storage::content_manager::consensus::operation_sender::OperationSender;
+    use storage::content_manager::consensus::persistent::Persistent;
+    use storage::content_manager::consensus_state::ConsensusState;
+    use storage::content_manager::toc::TableOfContent;
+    use tempfile::Builder;
+    use tonic::Code;
+
+    use super::*;
+
+    const MEMBER_PEER_ID: PeerId = 1;
+    const JOINING_PEER_ID: PeerId = 2;
+
+    fn signed_request(secret: &PeerSecret, peer_id: PeerId, timestamp: u64) -> Request<()> {
+        static NONCE: AtomicUsize = AtomicUsize::new(0);
+        let nonce = NONCE.fetch_add(1, Ordering::Relaxed);
+        signed_request_with_nonce(secret, peer_id, timestamp, &format!("nonce-{nonce}"))
+    }
+
+    fn request_digest() -> PeerRequestDigest {
+        PeerRequestDigest::new("/qdrant.PointsInternal/Upsert", b"points")
+    }
+
+    fn signed_request_with_nonce(
+        secret: &PeerSecret,
+        peer_id: PeerId,
+        timestamp: u64,
+        nonce: &str,
+    ) -> Request<()> {
+        let peer_id = peer_id.to_string();
+        let timestamp = timestamp.to_string();
+        let digest = request_digest();
+        let signature = secret.sign(&peer_id, &timestamp, nonce, &digest);
+        let mut request = Request::new(());
+        let metadata = request.metadata_mut();
+        metadata.insert(PEER_ID_HEADER, peer_id.parse().unwrap());
+        metadata.insert(PEER_TIMESTAMP_HEADER, timestamp.parse().unwrap());
+        metadata.insert(PEER_NONCE_HEADER, nonce.parse().unwrap());
+        metadata.insert(PEER_SIGNATURE_HEADER, signature.parse().unwrap());
+        request.extensions_mut().insert(digest);
+        request
+    }
+
+    fn check(interceptor: &mut PeerAuthInterceptor, request: Request<()>) -> Result<(), Code> {
+        interceptor
+            .call(request)
+            .map(|_| ())
+            .map_err(|status| status.code())
+    }
+
+    #[test]
+    fn test_peer_auth() {
+        let secret = PeerSecret::new("cluster-secret");
+        let now = unix_timestamp();
+
+        // No secret configured
+        let mut unrestricted = PeerAuthInterceptor::new(None);
+        assert!(unrestricted.call(Request::new(())).is_ok());
+
+        let mut interceptor = PeerAuthInterceptor::new(Some(secret.clone()));
+
+        // Missing signature
+        let mut unsigned = Request::new(());
+        unsigned
+            .metadata_mut()
+            .insert(PEER_ID_HEADER, "1".parse().unwrap());
+        assert_eq!(
+            check(&mut interceptor, unsigned),
+            Err(Code::Unauthenticated)
+        );
+
+        // Wrong secret
+        let wrong_secret = PeerSecret::new("other-secret");
+        assert_eq!(
+            check(
+                &mut interceptor,
+                signed_request(&wrong_secret, MEMBER_PEER_ID, now)
+            ),
+            Err(Code::Unauthenticated)
+        );
+
+        // Peer id, changed after signing
+        let mut forged = signed_request(&secret, MEMBER_PEER_ID, now);
+        forged
+            .metadata_mut()
+            .insert(PEER_ID_HEADER, "3".parse().unwrap());
+        assert_eq!(check(&mut interceptor, forged), Err(Code::Unauthenticated));
+
+        // Request, redirected to another method
+        let mut redirected = signed_request(&secret, MEMBER_PEER_ID, now);
+        redirected.extensions_mut().insert(PeerRequestDigest::new(
+            "/qdrant.PointsInternal/Delete",
+            b"points",
+        ));
+        assert_eq!(
+            check(&mut interceptor, redirected),
+            Err(Code::Unauthenticated)
+        );
+
+        // Body, changed after signing
+        let mut altered = signed_request(&secret, MEMBER_PEER_ID, now);
+        altered.extensions_mut().insert(PeerRequestDigest::new(
+            "/qdrant.PointsInternal/Upsert",
+            b"other",
+        ));
+        assert_eq!(check(&mut interceptor, altered), Err(Code::Unauthenticated));
+
+        // Outdated request
+        let outdated = signed_request(&secret, MEMBER_PEER_ID, now - 2 * PEER_SIGNATURE_TTL_SEC);
+        assert_eq!(
+            check(&mut interceptor, outdated),
+            Err(Code::Unauthenticated)
+        );
+
+        // Valid request is accepted only once, by any of the services
+        let nonce = "0123456789abcdef";
+        let mut other_service = interceptor.clone();
+        assert_eq!(
+            check(
+                &mut interceptor,
+                signed_request_with_nonce(&secret, MEMBER_PEER_ID, now, nonce)
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            check(
+                &mut interceptor,
+                signed_request_with_nonce(&secret, MEMBER_PEER_ID, now, nonce)
+            ),
+            Err(Code::Unauthenticated)
+        );
+        assert_eq!(
+            check(
+                &mut other_service,
+                signed_request_with_nonce(&secret, MEMBER_PEER_ID, now, nonce)
+            ),
+            Err(Code::Unauthenticated)
+        );
+    }
+
+    #[test]
+    fn test_seen_nonces() {
+        let mut seen_nonces = SeenNonces::default();
+        assert!(seen_nonces.insert("a", 100, 100));
+        assert!(!seen_nonces.insert("a", 100, 100));
+        assert!(seen_nonces.insert("b", 110, 110));
+        // Nonces are kept while their requests could still be accepted
+        assert!(!seen_nonces.insert("a", 100, 100 + PEER_SIGNATURE_TTL_SEC));
+        assert!(seen_nonces.insert("c", 200, 200));
+        assert_eq!(seen_nonces.queue.len(), 1);
+        assert_eq!(seen_nonces.nonces.len(), 1);
+    }
+
+    #[test]
+    fn test_peer_auth_membership() {
+        let storage_dir = Builder::new().prefix("storage").tempdir().unwrap();
+        let mut settings = crate::Settings::new().expect("Can't read config.");
+        settings.storage.storage_path = storage_dir.path().to_str().unwrap().to_string();
+        let runtime = crate::create_search_runtime(settings.storage.performance.max_search_threads)
+            .expect("Can't create runtime.");
+        let (propose_sender, _propose_receiver) = std::sync::mpsc::channel();
+        let mut persistent_state =
+            Persistent::load_or_init(&settings.storage.storage_path, true).unwrap();
+        persistent_state
+            .insert_peer(MEMBER_PEER_ID, "http://127.0.0.1:6335".parse().unwrap())
+            .unwrap();
+        let operation_sender = OperationSender::new(propose_sender);
+        let toc = Arc::new(TableOfContent::new(
+            &settings.storage,
+            runtime,
+            ChannelService::default(),
+            persistent_state.this_peer_id(),
+            Some(operation_sender.clone()),
+        ));
+        let consensus_state: ConsensusStateRef = ConsensusState::new(
+            persistent_state,
+            toc.clone(),
+            operation_sender,
+            toc.storage_path(),
+            toc.state_changes().clone(),
+        )
+        .unwrap()
+        .into();
+
+        let secret = PeerSecret::new("cluster-secret");
+        let now = unix_timestamp();
+        let mut raft_interceptor = PeerAuthInterceptor::new(Some(secret.clone()));
+        let mut member_interceptor = raft_interceptor.clone().with_membership(consensus_state);
+
+        assert_eq!(
+            check(
+                &mut member_interceptor,
+                signed_request(&secret, MEMBER_PEER_ID, now)
+            ),
+            Ok(())
+        );
+        // Peer, which is not a member of the cluster
+        assert_eq!(
+            check(
+                &mut member_interceptor,
+                signed_request(&secret, JOINING_PEER_ID, now)
+            ),
+            Err(Code::PermissionDenied)
+        );
+        // ... is still unauthenticated with a wrong secret
+        assert_eq!(
+            check(
+                &mut member_interceptor,
+                signed_request(&PeerSecret::new("other-secret"), JOINING_PEER_ID, now)
+            ),
+            Err(Code::Unauthenticated)
+        );
+        // Joining peer can send consensus messages
+        assert_eq!(
+            check(
+                &mut raft_interceptor,
+                signed_request(&secret, JOINING_PEER_ID, now)
+            ),
+            Ok(())
+        );
+    }
+}
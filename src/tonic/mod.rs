@@ -1,4 +1,7 @@
+mod access_log;
 mod api;
+mod auth;
+mod request_size_limit;
 mod tonic_telemetry;
 
 use std::net::{IpAddr, SocketAddr};
@@ -6,6 +9,7 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 use ::api::grpc::models::VersionInfo;
+use ::api::grpc::peer_channel::{MessageCipher, PeerSecret};
 use ::api::grpc::qdrant::collections_internal_server::CollectionsInternalServer;
 use ::api::grpc::qdrant::collections_server::CollectionsServer;
 use ::api::grpc::qdrant::points_internal_server::PointsInternalServer;
@@ -13,19 +17,26 @@ use ::api::grpc::qdrant::points_server::PointsServer;
 use ::api::grpc::qdrant::qdrant_server::{Qdrant, QdrantServer};
 use ::api::grpc::qdrant::snapshots_server::SnapshotsServer;
 use ::api::grpc::qdrant::{HealthCheckReply, HealthCheckRequest};
+use futures::stream::{select_all, SelectAll};
 use storage::content_manager::consensus_state::ConsensusStateRef;
 use storage::content_manager::toc::TableOfContent;
 use storage::dispatcher::Dispatcher;
-use tokio::{runtime, signal};
+use tokio::runtime;
+use tonic::service::interceptor::InterceptedService;
+use tonic::transport::server::TcpIncoming;
 use tonic::transport::Server;
 use tonic::{Request, Response, Status};
+use tonic_health::ServingStatus;
 
+use crate::common::request_limits::RequestLimits;
+use crate::common::shutdown::ShutdownSignal;
 use crate::common::telemetry_ops::requests_telemetry::TonicTelemetryCollector;
 use crate::tonic::api::collections_api::CollectionsService;
 use crate::tonic::api::collections_internal_api::CollectionsInternalService;
 use crate::tonic::api::points_api::PointsService;
 use crate::tonic::api::points_internal_api::PointsInternalService;
 use crate::tonic::api::snapshots_api::SnapshotsService;
+use crate::tonic::auth::{AuthInterceptor, PeerAuthInterceptor};
 
 #[derive(Default)]
 pub struct QdrantService {}
@@ -40,11 +51,23 @@ impl Qdrant for QdrantService {
     }
 }
 
+/// Listen on the same port of all the `hosts`.
+/// Must be called inside of the runtime, which serves the connections.
+fn bind_incoming(hosts: &[String], port: u16) -> SelectAll<TcpIncoming> {
+    select_all(hosts.iter().map(|host| {
+        let socket = SocketAddr::from((host.parse::<IpAddr>().unwrap(), port));
+        TcpIncoming::new(socket, false, None)
+            .unwrap_or_else(|err| panic!("Can't bind gRPC server on {socket}: {err}"))
+    }))
+}
+
 pub fn init(
     dispatcher: Arc<Dispatcher>,
     telemetry_collector: Arc<parking_lot::Mutex<TonicTelemetryCollector>>,
-    host: String,
+    hosts: Vec<String>,
     grpc_port: u16,
+    limits: RequestLimits,
+    shutdown: ShutdownSignal,
 ) -> std::io::Result<()> {
     let tonic_runtime = runtime::Builder::new_multi_thread()
         .enable_io()
@@ -57,12 +80,35 @@ pub fn init(
         .build()?;
     tonic_runtime
         .block_on(async {
-            let socket = SocketAddr::from((host.parse::<IpAddr>().unwrap(), grpc_port));
+            let incoming = bind_incoming(&hosts, grpc_port);
 
             let service = QdrantService::default();
             let collections_service = CollectionsService::new(dispatcher.clone());
-            let points_service = PointsService::new(dispatcher.toc().clone());
+            let points_service = PointsService::new(dispatcher.toc().clone(), limits);
             let snapshot_service = SnapshotsService::new(dispatcher.toc().clone());
+            let auth_interceptor = AuthInterceptor::new(dispatcher.clone());
+
+            // Standard health checks for load balancers, the empty service name stands for
+            // the whole server
+            let (mut health_reporter, health_service) = tonic_health::server::health_reporter();
+            health_reporter
+                .set_serving::<QdrantServer<QdrantService>>()
+                .await;
+            health_reporter
+                .set_serving::<CollectionsServer<CollectionsService>>()
+                .await;
+            health_reporter
+                .set_serving::<PointsServer<PointsService>>()
+                .await;
+            health_reporter
+                .set_serving::<SnapshotsServer<SnapshotsService>>()
+                .await;
+
+            // Lets tools like grpcurl discover the API without the proto files
+            let reflection_service = tonic_reflection::server::Builder::configure()
+                .register_encoded_file_descriptor_set(::api::grpc::QDRANT_DESCRIPTOR_SET)
+                .build()
+                .expect("Can't build gRPC reflection service");
 
             log::info!("Qdrant gRPC listening on {}", grpc_port);
 
@@ -70,13 +116,32 @@ pub fn init(
                 .layer(tonic_telemetry::TonicTelemetryLayer::new(
                     telemetry_collector,
                 ))
+                .layer(access_log::AccessLogLayer)
+                .layer(request_size_limit::RequestSizeLimitLayer::new(
+                    limits.max_request_size,
+                ))
                 .add_service(QdrantServer::new(service))
-                .add_service(CollectionsServer::new(collections_service))
-                .add_service(PointsServer::new(points_service))
-                .add_service(SnapshotsServer::new(snapshot_service))
-                .serve_with_shutdown(socket, async {
-                    signal::ctrl_c().await.unwrap();
+                .add_service(health_service)
+                .add_service(reflection_service)
+                .add_service(CollectionsServer::with_interceptor(
+                    collections_service,
+                    auth_interceptor.clone(),
+                ))
+                .add_service(PointsServer::with_interceptor(
+                    points_service,
+                    auth_interceptor.clone(),
+                ))
+                .add_service(SnapshotsServer::with_interceptor(
+                    snapshot_service,
+                    auth_interceptor,
+                ))
+                .serve_with_incoming_shutdown(incoming, async move {
+                    shutdown.wait().await;
                     log::debug!("Stopping gRPC");
+                    // Watching load balancers stop sending new requests
+                    health_reporter
+                        .set_service_status("", ServingStatus::NotServing)
+                        .await;
                 })
                 .await
         })
@@ -84,14 +149,18 @@ pub fn init(
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn init_internal(
     toc: Arc<TableOfContent>,
     consensus_state: ConsensusStateRef,
     telemetry_collector: Arc<parking_lot::Mutex<TonicTelemetryCollector>>,
-    host: String,
+    hosts: Vec<String>,
     internal_grpc_port: u16,
     to_consensus: std::sync::mpsc::SyncSender<crate::consensus::Message>,
+    p2p_cipher: Option<MessageCipher>,
+    p2p_secret: Option<PeerSecret>,
 ) -> std::io::Result<()> {
+    use ::api::grpc::peer_channel::PeerTransportLayer;
     use ::api::grpc::qdrant::raft_server::RaftServer;
 
     use crate::tonic::api::raft_api::RaftService;
@@ -107,11 +176,18 @@ pub fn init_internal(
         .build()?;
     tonic_runtime
         .block_on(async {
-            let socket = SocketAddr::from((host.parse::<IpAddr>().unwrap(), internal_grpc_port));
+            let incoming = bind_incoming(&hosts, internal_grpc_port);
 
             let service = QdrantService::default();
             let collections_internal_service = CollectionsInternalService::new(toc.clone());
             let points_internal_service = PointsInternalService::new(toc.clone());
+            // Signatures of the requests cover their bodies, digested by the transport layer
+            let transport_layer = PeerTransportLayer::new(p2p_cipher, p2p_secret.is_some());
+            // Joining peers send consensus messages before they become members
+            let peer_auth_interceptor = PeerAuthInterceptor::new(p2p_secret);
+            let member_auth_interceptor = peer_auth_interceptor
+                .clone()
+                .with_membership(consensus_state.clone());
             let raft_service = RaftService::new(to_consensus, consensus_state);
 
             log::debug!("Qdrant internal gRPC listening on {}", internal_grpc_port);
@@ -120,14 +196,27 @@ pub fn init_internal(
                 .layer(tonic_telemetry::TonicTelemetryLayer::new(
                     telemetry_collector,
                 ))
+                .layer(transport_layer)
                 .add_service(QdrantServer::new(service))
-                .add_service(CollectionsInternalServer::new(collections_internal_service))
-                .add_service(PointsInternalServer::new(points_internal_service))
-                .add_service(RaftServer::new(raft_service))
-                .serve_with_shutdown(socket, async {
-                    signal::ctrl_c().await.unwrap();
-                    log::debug!("Stopping internal gRPC");
-                })
+                .add_service(InterceptedService::new(
+                    CollectionsInternalServer::new(collections_internal_service)
+                        .accept_gzip()
+                        .send_gzip(),
+                    member_auth_interceptor.clone(),
+                ))
+                .add_service(InterceptedService::new(
+                    PointsInternalServer::new(points_internal_service)
+                        .accept_gzip()
+                        .send_gzip(),
+                    member_auth_interceptor,
+                ))
+                .add_service(InterceptedService::new(
+                    RaftServer::new(raft_service).accept_gzip().send_gzip(),
+                    peer_auth_interceptor,
+                ))
+                // Not stopped on shutdown: peers should be reachable till the process exits,
+                // to hand over the leadership and to serve in-flight transfers
+                .serve_with_incoming(incoming)
                 .await
         })
         .unwrap();
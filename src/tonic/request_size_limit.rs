@@ -0,0 +1,153 @@
+use std::task::{Context, Poll};
+
+use futures::StreamExt;
+use tonic::codegen::http;
+use tonic::transport::Body;
+use tonic::Status;
+use tower::Service;
+use tower_layer::Layer;
+
+/// Each gRPC message in the body is prefixed with the compression flag and 4 bytes of its length
+const MESSAGE_PREFIX_SIZE: usize = 5;
+
+#[derive(Clone)]
+pub struct RequestSizeLimitService<T> {
+    service: T,
+    max_message_size: usize,
+}
+
+/// Rejects gRPC messages, larger than the limit, with `RESOURCE_EXHAUSTED`.
+///
+/// The length of the message is read from its prefix, so the message is rejected before it is
+/// read into memory. Each message of a stream is limited separately.
+#[derive(Clone)]
+pub struct RequestSizeLimitLayer {
+    max_message_size: usize,
+}
+
+impl RequestSizeLimitLayer {
+    pub fn new(max_message_size: usize) -> Self {
+        Self { max_message_size }
+    }
+}
+
+/// Tracks the boundaries of the messages in the chunks of the body
+struct MessageSizeChecker {
+    max_message_size: usize,
+    /// Bytes of the prefix of the next message, read so far
+    prefix: Vec<u8>,
+    /// Bytes of the current message, which are not read yet
+    remaining: usize,
+}
+
+impl MessageSizeChecker {
+    fn new(max_message_size: usize) -> Self {
+        Self {
+            max_message_size,
+            prefix: Vec::with_capacity(MESSAGE_PREFIX_SIZE),
+            remaining: 0,
+        }
+    }
+
+    fn check(&mut self, mut chunk: &[u8]) -> Result<(), Status> {
+        while !chunk.is_empty() {
+            if self.remaining > 0 {
+                let skipped = self.remaining.min(chunk.len());
+                self.remaining -= skipped;
+                chunk = &chunk[skipped..];
+                continue;
+            }
+
+            let taken = (MESSAGE_PREFIX_SIZE - self.prefix.len()).min(chunk.len());
+            self.prefix.extend_from_slice(&chunk[..taken]);
+            chunk = &chunk[taken..];
+            if self.prefix.len() < MESSAGE_PREFIX_SIZE {
+                break;
+            }
+
+            let mut length = [0; 4];
+            length.copy_from_slice(&self.prefix[1..]);
+            self.prefix.clear();
+            let message_size = u32::from_be_bytes(length) as usize;
+            if message_size > self.max_message_size {
+                return Err(Status::resource_exhausted(format!(
+                    "Message of {message_size} bytes exceeds the limit of {} bytes",
+                    self.max_message_size
+                )));
+            }
+            self.remaining = message_size;
+        }
+        Ok(())
+    }
+}
+
+impl<S> Service<http::Request<Body>> for RequestSizeLimitService<S>
+where
+    S: Service<http::Request<Body>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<Body>) -> Self::Future {
+        let (parts, body) = request.into_parts();
+        let mut checker = MessageSizeChecker::new(self.max_message_size);
+        // Error of the body is converted into the status of the call by the decoder
+        let body = Body::wrap_stream(body.map(move |chunk| {
+            let chunk = chunk?;
+            checker.check(&chunk)?;
+            Ok::<_, Box<dyn std::error::Error + Send + Sync>>(chunk)
+        }));
+        self.service.call(http::Request::from_parts(parts, body))
+    }
+}
+
+impl<S> Layer<S> for RequestSizeLimitLayer {
+    type Service = RequestSizeLimitService<S>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        RequestSizeLimitService {
+            service,
+            max_message_size: self.max_message_size,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(size: u32) -> Vec<u8> {
+        let mut message = vec![0];
+        message.extend_from_slice(&size.to_be_bytes());
+        message.resize(MESSAGE_PREFIX_SIZE + size as usize, 1);
+        message
+    }
+
+    #[test]
+    fn test_message_size_checker() {
+        let mut body = message(10);
+        body.extend(message(3));
+
+        // Messages and prefixes are split between the chunks in any way
+        for chunk_size in 1..body.len() {
+            let mut checker = MessageSizeChecker::new(10);
+            for chunk in body.chunks(chunk_size) {
+                checker.check(chunk).unwrap();
+            }
+        }
+
+        body.extend(message(11));
+        let mut checker = MessageSizeChecker::new(10);
+        let status = body
+            .chunks(4)
+            .map(|chunk| checker.check(chunk))
+            .find_map(Result::err)
+            .unwrap();
+        assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+    }
+}
@@ -0,0 +1,228 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::anyhow;
+use collection::collection::{Collection, CollectionVersion};
+use collection::config::CollectionConfig;
+use collection::shards::local_shard::LocalShard;
+use segment::common::version::StorageVersion;
+use segment::segment::SegmentVersion;
+use segment::segment_constructor::{check_segment_version, SegmentCompatibility};
+use semver::Version;
+use serde::Serialize;
+use storage::content_manager::encryption::load_storage_cipher;
+use storage::content_manager::toc::COLLECTIONS_DIR;
+use storage::types::StorageConfig;
+
+/// State of the single collection storage, as seen by the current version
+#[derive(Serialize, Debug, Default)]
+pub struct CollectionUpgradeCheck {
+    pub name: String,
+    /// Version of the collection storage. `None` if it can't be read
+    pub version: Option<String>,
+    /// Number of segments in all local shards of the collection
+    pub segments: usize,
+    /// Number of segments, which are stored in the older format and will be migrated on load
+    pub segments_to_migrate: usize,
+    /// Number of segments, which can't be loaded by the current version
+    pub incompatible_segments: usize,
+}
+
+/// Report of the dry-run upgrade check.
+/// Storage is only read, nothing is migrated or modified.
+#[derive(Serialize, Debug, Default)]
+pub struct UpgradeCheckReport {
+    /// Version of the running binary
+    pub app_version: String,
+    /// Version of the segment format, supported by the running binary
+    pub segment_version: String,
+    pub collections: Vec<CollectionUpgradeCheck>,
+    /// Problems, which prevent the storage from being loaded by this version
+    pub blocking: Vec<String>,
+    /// Problems, which do not prevent loading, but cause migration or degraded functionality
+    pub warnings: Vec<String>,
+}
+
+impl UpgradeCheckReport {
+    /// Inspect all collections in the storage against the running binary
+    pub fn collect(storage_config: &StorageConfig) -> anyhow::Result<Self> {
+        let mut report = UpgradeCheckReport {
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            segment_version: SegmentVersion::current(),
+            ..Default::default()
+        };
+
+        let has_storage_key = match load_storage_cipher(&storage_config.encryption) {
+            Ok(cipher) => cipher.is_some(),
+            Err(err) => {
+                report
+                    .blocking
+                    .push(format!("Can't load storage encryption key: {err}"));
+                false
+            }
+        };
+
+        let collections_path = Path::new(&storage_config.storage_path).join(COLLECTIONS_DIR);
+        if !collections_path.exists() {
+            return Ok(report);
+        }
+
+        let mut collection_paths: Vec<_> = fs::read_dir(&collections_path)?
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .collect();
+        collection_paths.sort();
+
+        for collection_path in collection_paths {
+            let collection = report.check_collection(&collection_path, has_storage_key)?;
+            report.collections.push(collection);
+        }
+
+        Ok(report)
+    }
+
+    fn check_collection(
+        &mut self,
+        collection_path: &Path,
+        has_storage_key: bool,
+    ) -> anyhow::Result<CollectionUpgradeCheck> {
+        let name = collection_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| anyhow!("Invalid collection path {}", collection_path.display()))?
+            .to_string();
+        let mut check = CollectionUpgradeCheck {
+            name: name.clone(),
+            ..Default::default()
+        };
+
+        match CollectionVersion::load(collection_path) {
+            Ok(stored) => {
+                check.version = Some(stored.clone());
+                self.check_collection_version(&name, &stored);
+            }
+            Err(err) => self
+                .blocking
+                .push(format!("Collection `{name}`: can't read version: {err}")),
+        }
+
+        match CollectionConfig::load(collection_path) {
+            Ok(config) => {
+                if config.wal_config.encrypted && !has_storage_key {
+                    self.blocking.push(format!(
+                        "Collection `{name}` is encrypted, but no encryption key is configured"
+                    ));
+                }
+            }
+            Err(err) => self
+                .blocking
+                .push(format!("Collection `{name}`: can't read config: {err}")),
+        }
+
+        for entry in fs::read_dir(collection_path)? {
+            let segments_path = LocalShard::segments_path(&entry?.path());
+            if !segments_path.is_dir() {
+                continue;
+            }
+            for segment_entry in fs::read_dir(&segments_path)? {
+                let segment_path = segment_entry?.path();
+                if segment_path.is_dir() {
+                    self.check_segment(&name, &segment_path, &mut check);
+                }
+            }
+        }
+
+        if check.segments_to_migrate > 0 {
+            self.warnings.push(format!(
+                "Collection `{name}`: {} segment(s) use a deprecated format \
+                 and will be migrated on the first start",
+                check.segments_to_migrate
+            ));
+        }
+
+        Ok(check)
+    }
+
+    fn check_collection_version(&mut self, name: &str, stored: &str) {
+        let (stored_version, app_version) = match (
+            stored.parse::<Version>(),
+            CollectionVersion::current().parse::<Version>(),
+        ) {
+            (Ok(stored_version), Ok(app_version)) => (stored_version, app_version),
+            (Err(err), _) | (_, Err(err)) => {
+                self.blocking.push(format!(
+                    "Collection `{name}`: can't parse version `{stored}`: {err}"
+                ));
+                return;
+            }
+        };
+
+        if stored_version > app_version {
+            self.blocking.push(format!(
+                "Collection `{name}` was created by a newer version ({stored_version}) \
+                 than the current one ({app_version})"
+            ));
+        } else if stored_version != app_version {
+            if Collection::can_upgrade_storage(&stored_version, &app_version) {
+                self.warnings.push(format!(
+                    "Collection `{name}` will be migrated {stored_version} -> {app_version}"
+                ));
+            } else {
+                self.blocking.push(format!(
+                    "Collection `{name}` can't be upgraded {stored_version} -> {app_version}, \
+                     upgrade through the intermediate versions first"
+                ));
+            }
+        }
+    }
+
+    fn check_segment(
+        &mut self,
+        name: &str,
+        segment_path: &Path,
+        check: &mut CollectionUpgradeCheck,
+    ) {
+        check.segments += 1;
+        match check_segment_version(segment_path) {
+            Ok(Some(SegmentCompatibility::Current)) => {}
+            Ok(Some(SegmentCompatibility::NeedsMigration(_))) => check.segments_to_migrate += 1,
+            Ok(Some(SegmentCompatibility::Incompatible(stored_version))) => {
+                check.incompatible_segments += 1;
+                self.blocking.push(format!(
+                    "Collection `{name}`: segment {} has incompatible version {stored_version}",
+                    segment_path.display()
+                ));
+            }
+            Ok(None) => self.warnings.push(format!(
+                "Collection `{name}`: segment {} has no version file and will be skipped",
+                segment_path.display()
+            )),
+            Err(err) => {
+                check.incompatible_segments += 1;
+                self.blocking.push(format!(
+                    "Collection `{name}`: can't read version of segment {}: {err}",
+                    segment_path.display()
+                ));
+            }
+        }
+    }
+}
+
+/// Print the upgrade check report of the storage.
+/// Fails if anything would prevent the storage from being loaded by this version.
+pub fn check_upgrade(storage_config: &StorageConfig) -> anyhow::Result<()> {
+    let report = UpgradeCheckReport::collect(storage_config)?;
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    for warning in &report.warnings {
+        log::warn!("{warning}");
+    }
+    if !report.blocking.is_empty() {
+        return Err(anyhow!(
+            "Found {} problem(s) blocking the upgrade",
+            report.blocking.len()
+        ));
+    }
+    Ok(())
+}
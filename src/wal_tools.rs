@@ -0,0 +1,126 @@
+use std::path::Path;
+
+use anyhow::{anyhow, Context};
+use collection::config::CollectionConfig;
+use collection::operations::OperationWithId;
+use collection::shards::local_shard::LocalShard;
+use collection::wal::SerdeWal;
+use storage::content_manager::encryption::load_storage_cipher;
+use storage::types::EncryptionConfig;
+
+/// Open WAL of the shard, located in `shard_path`.
+/// WAL options are taken from the collection config, if it exists.
+/// Encrypted WAL is decrypted with the key from `encryption` settings.
+fn open_shard_wal(
+    shard_path: &Path,
+    encryption: &EncryptionConfig,
+) -> anyhow::Result<SerdeWal<OperationWithId>> {
+    let wal_path = LocalShard::wal_path(shard_path);
+    if !wal_path.exists() {
+        return Err(anyhow!("WAL not found in {}", wal_path.display()));
+    }
+    let wal_config = shard_path
+        .parent()
+        .and_then(|collection_path| CollectionConfig::load(collection_path).ok())
+        .map(|config| config.wal_config)
+        .unwrap_or_default();
+    let cipher = if wal_config.encrypted {
+        let cipher = load_storage_cipher(encryption).context("Can't load encryption key")?;
+        Some(
+            cipher
+                .ok_or_else(|| anyhow!("WAL is encrypted, but no encryption key is configured"))?,
+        )
+    } else {
+        None
+    };
+    let wal_path = wal_path
+        .to_str()
+        .ok_or_else(|| anyhow!("Invalid WAL path {}", wal_path.display()))?;
+    SerdeWal::new_with_cipher(wal_path, &(&wal_config).into(), cipher).context("Can't open WAL")
+}
+
+/// Print all records of the shard WAL, or only unreadable ones if `verify_only` is set.
+/// Fails if any of the records can't be read.
+pub fn inspect_wal(
+    shard_path: &Path,
+    verify_only: bool,
+    encryption: &EncryptionConfig,
+) -> anyhow::Result<()> {
+    let wal = open_shard_wal(shard_path, encryption)?;
+
+    let mut first_unreadable = None;
+    let mut unreadable_count = 0;
+    for (idx, record) in wal.try_read(wal.first_index()) {
+        match record {
+            Ok(operation) => {
+                if !verify_only {
+                    println!("{idx}: {}", serde_json::to_string(&operation)?);
+                }
+            }
+            Err(err) => {
+                println!("{idx}: ERROR {err}");
+                first_unreadable.get_or_insert(idx);
+                unreadable_count += 1;
+            }
+        }
+    }
+
+    println!(
+        "Records: {}, first index: {}, unreadable: {unreadable_count}",
+        wal.len(),
+        wal.first_index(),
+    );
+    match first_unreadable {
+        None => Ok(()),
+        Some(idx) => Err(anyhow!(
+            "WAL contains unreadable records, starting from {idx}. Use `wal-truncate` to remove them"
+        )),
+    }
+}
+
+/// Remove records of the shard WAL, starting from `from_index`.
+/// If `from_index` is not specified - the first unreadable record is used.
+pub fn truncate_wal(
+    shard_path: &Path,
+    from_index: Option<u64>,
+    encryption: &EncryptionConfig,
+) -> anyhow::Result<()> {
+    let mut wal = open_shard_wal(shard_path, encryption)?;
+
+    let from_index = match from_index {
+        Some(idx) => idx,
+        None => {
+            let first_unreadable = wal
+                .try_read(wal.first_index())
+                .find(|(_idx, record)| record.is_err())
+                .map(|(idx, _record)| idx);
+            match first_unreadable {
+                Some(idx) => idx,
+                None => {
+                    println!("All records are readable, nothing to truncate");
+                    return Ok(());
+                }
+            }
+        }
+    };
+
+    let first_index = wal.first_index();
+    if from_index < first_index {
+        return Err(anyhow!(
+            "Can't truncate WAL from {from_index}: records before the first index {first_index} \
+             are already removed, use an index not less than {first_index}"
+        ));
+    }
+    if from_index >= first_index + wal.len() {
+        println!("WAL has no records, starting from {from_index}, nothing to truncate");
+        return Ok(());
+    }
+
+    let records_before = wal.len();
+    wal.truncate(from_index).context("Can't truncate WAL")?;
+    println!(
+        "Removed {} records, starting from {from_index}",
+        records_before - wal.len()
+    );
+    Ok(())
+}
@@ -9,6 +9,12 @@ cd "$(dirname "$0")/../"
 QDRANT_HOST='localhost:6334'
 
 docker_grpcurl="docker run --rm --network=host -v ${PWD}/lib/api/src/grpc/proto:/proto fullstorydev/grpcurl -plaintext -import-path /proto -proto qdrant.proto"
+# Without the proto files, the API is discovered by the server reflection
+docker_grpcurl_reflection="docker run --rm --network=host fullstorydev/grpcurl -plaintext"
+
+$docker_grpcurl_reflection $QDRANT_HOST list | grep -q 'qdrant.Points'
+$docker_grpcurl_reflection -d '{}' $QDRANT_HOST grpc.health.v1.Health/Check | grep -q '"SERVING"'
+$docker_grpcurl_reflection -d '{"service": "qdrant.Collections"}' $QDRANT_HOST grpc.health.v1.Health/Check | grep -q '"SERVING"'
 
 $docker_grpcurl -d '{
    "collection_name": "test_collection"
@@ -50,6 +56,16 @@ $docker_grpcurl -d '{
   ]
 }' $QDRANT_HOST qdrant.Points/Upsert
 
+$docker_grpcurl -d '{
+  "collection_name": "test_collection",
+  "wait": true,
+  "operations": [
+    {"upsert": {"points": [{"id": { "num": 7 }, "vectors": {"vector": {"data": [0.45, 0.09, 0.01, 0.2]}}}]}},
+    {"set_payload": {"payload": {"city": { "string_value": "Berlin" }}, "points": [{ "num": 7 }]}},
+    {"delete": {"points": {"ids": [{ "num": 7 }]}}}
+  ]
+}' $QDRANT_HOST qdrant.Points/UpdateBatch
+
 $docker_grpcurl -d '{ "collection_name": "test_collection" }' $QDRANT_HOST qdrant.Collections/Get
 
 $docker_grpcurl -d '{
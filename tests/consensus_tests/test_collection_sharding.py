@@ -54,6 +54,8 @@ def test_collection_sharding(tmp_path: pathlib.Path):
     # Check collection's cluster info
     collection_cluster_info = get_collection_cluster_info(peer_api_uris[0], "test_collection")
     assert collection_cluster_info["shard_count"] == N_SHARDS
+    for remote_shard in collection_cluster_info["remote_shards"]:
+        assert remote_shard["peer_uri"] is not None
 
     # Create points in first peer's collection
     r = requests.put(
@@ -0,0 +1,67 @@
+import pathlib
+from collections import Counter
+
+from .utils import *
+
+N_PEERS = 3
+N_SHARDS = 6
+N_POINTS = 100
+N_SAMPLES = 200
+SAMPLE_SIZE = 10
+
+
+def test_points_sample(tmp_path: pathlib.Path):
+    peer_api_uris, _peer_dirs, _bootstrap_uri = start_cluster(tmp_path, N_PEERS)
+
+    r = requests.put(
+        f"{peer_api_uris[0]}/collections/test_collection", json={
+            "vectors": {
+                "size": 4,
+                "distance": "Dot"
+            },
+            "shard_number": N_SHARDS,
+            "replication_factor": 1,
+        })
+    assert_http_ok(r)
+
+    wait_collection_exists_and_active_on_all_peers(collection_name="test_collection", peer_api_uris=peer_api_uris)
+
+    # Every peer holds some of the shards, so most of the points are remote for any peer
+    collection_cluster_info = get_collection_cluster_info(peer_api_uris[0], "test_collection")
+    assert len(collection_cluster_info["remote_shards"]) > 0
+
+    r = requests.put(
+        f"{peer_api_uris[0]}/collections/test_collection/points?wait=true", json={
+            "points": [
+                {"id": i, "vector": [0.1, 0.2, 0.3, 0.4]} for i in range(N_POINTS)
+            ]
+        })
+    assert_http_ok(r)
+
+    # Samples, merged from local and remote shards, are uniform
+    counts = Counter()
+    for _ in range(N_SAMPLES):
+        r = requests.post(
+            f"{peer_api_uris[0]}/collections/test_collection/points/sample", json={
+                "limit": SAMPLE_SIZE,
+                "with_payload": False,
+            })
+        assert_http_ok(r)
+        ids = [point["id"] for point in r.json()["result"]]
+        assert len(ids) == SAMPLE_SIZE
+        assert len(set(ids)) == SAMPLE_SIZE
+        counts.update(ids)
+
+    # Each point is expected to be sampled 20 times
+    expected = N_SAMPLES * SAMPLE_SIZE / N_POINTS
+    assert len(counts) == N_POINTS
+    assert max(counts.values()) < 3 * expected
+
+    # Sampling keys are not exposed by the peer, which merges the shards
+    r = requests.post(
+        f"{peer_api_uris[1]}/collections/test_collection/points/scroll", json={
+            "limit": SAMPLE_SIZE,
+            "sample": "random",
+        })
+    assert_http_ok(r)
+    assert all(point.get("order_value") is None for point in r.json()["result"]["points"])